@@ -0,0 +1,177 @@
+//! 清算模拟（dry-run）
+//!
+//! 输入一笔或一批成交，打印系统将生成的清算记录：成交额、双方手续费明细、结算分录与资产小计。
+//! 走与记账管道相同的 [`clear_spot_trade`]，不写任何账户，用于排查客户争议。
+//!
+//! 成交格式（CSV，一行一笔，`#` 开头为注释）：
+//! `trade_id,symbol,price,quantity,buyer,seller,maker`，其中 maker 为 `BUY` 或 `SELL`
+//!
+//! ```text
+//! clearing-sim --trade "7,BTCUSDT,40000,0.5,1,2,SELL" --maker-fee 0.001 --taker-fee 0.002
+//! clearing-sim --file trades.csv --buyer-vip 2
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use base_types::account::clearing::{ClearingContext, FeeProfile, TradeInput, clear_spot_trade};
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{AccountId, OrderSide, Price, Quantity, Timestamp, TradingPair};
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "clearing-sim")]
+#[command(about = "成交清算模拟：打印清算记录、手续费明细与结算分录（不记账）", long_about = None)]
+struct Cli {
+    /// 单笔成交（CSV 一行）
+    #[arg(short, long, conflicts_with = "file")]
+    trade: Option<String>,
+
+    /// 成交文件（CSV，每行一笔）
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+
+    /// 挂单费率
+    #[arg(long, default_value_t = 0.001, allow_negative_numbers = true)]
+    maker_fee: f64,
+
+    /// 吃单费率
+    #[arg(long, default_value_t = 0.001)]
+    taker_fee: f64,
+
+    /// 手续费收入账户
+    #[arg(long, default_value_t = 0)]
+    fee_account: u64,
+
+    /// 买方 30 天交易量
+    #[arg(long)]
+    buyer_volume: Option<f64>,
+
+    /// 买方 VIP 等级
+    #[arg(long)]
+    buyer_vip: Option<u32>,
+
+    /// 买方是否做市商
+    #[arg(long)]
+    buyer_market_maker: bool,
+
+    /// 卖方 30 天交易量
+    #[arg(long)]
+    seller_volume: Option<f64>,
+
+    /// 卖方 VIP 等级
+    #[arg(long)]
+    seller_vip: Option<u32>,
+
+    /// 卖方是否做市商
+    #[arg(long)]
+    seller_market_maker: bool,
+}
+
+/// 解析一行成交，空行与注释返回 None
+fn parse_trade_line(line: &str) -> Option<Result<TradeInput, String>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("trade_id") {
+        return None;
+    }
+    Some(parse_fields(line))
+}
+
+fn parse_fields(line: &str) -> Result<TradeInput, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [trade_id, symbol, price, quantity, buyer, seller, maker] = fields.as_slice() else {
+        return Err(format!("expected 7 fields, got {}: {}", fields.len(), line));
+    };
+    let number = |name: &str, value: &str| {
+        value.parse::<f64>().map_err(|_| format!("invalid {}: {}", name, value))
+    };
+    let account = |name: &str, value: &str| {
+        value.parse::<u64>().map(AccountId).map_err(|_| format!("invalid {}: {}", name, value))
+    };
+    let maker_side = match maker.to_uppercase().as_str() {
+        "BUY" => OrderSide::Buy,
+        "SELL" => OrderSide::Sell,
+        _ => return Err(format!("invalid maker side: {}", maker)),
+    };
+    Ok(TradeInput {
+        trade_id: trade_id.parse().map_err(|_| format!("invalid trade_id: {}", trade_id))?,
+        trading_pair: TradingPair::from_symbol_str(symbol)
+            .ok_or_else(|| format!("unknown symbol: {}", symbol))?,
+        price: Price::from_f64(number("price", price)?),
+        quantity: Quantity::from_f64(number("quantity", quantity)?),
+        buyer: account("buyer", buyer)?,
+        seller: account("seller", seller)?,
+        maker_side,
+        timestamp: Timestamp(0),
+    })
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let input = match (&cli.trade, &cli.file) {
+        (Some(trade), _) => trade.clone(),
+        (None, Some(path)) => match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("failed to read {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+        },
+        (None, None) => {
+            eprintln!("either --trade or --file is required");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fee_config = ProductFeeConfig::spot(cli.maker_fee, cli.taker_fee);
+    let context = ClearingContext {
+        fee_config: &fee_config,
+        fee_account: AccountId(cli.fee_account),
+        buyer_profile: FeeProfile {
+            volume_30d: cli.buyer_volume,
+            vip_level: cli.buyer_vip,
+            market_maker: cli.buyer_market_maker,
+        },
+        seller_profile: FeeProfile {
+            volume_30d: cli.seller_volume,
+            vip_level: cli.seller_vip,
+            market_maker: cli.seller_market_maker,
+        },
+    };
+
+    let mut failed = 0;
+    for (number, line) in input.lines().enumerate() {
+        let result = match parse_trade_line(line) {
+            None => continue,
+            Some(Err(e)) => Err(e),
+            Some(Ok(trade)) => clear_spot_trade(&trade, &context).map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(record) => println!("{}", record),
+            Err(e) => {
+                failed += 1;
+                eprintln!("line {}: {}", number + 1, e);
+            }
+        }
+    }
+    if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trade_line() {
+        let trade = parse_trade_line("7, BTCUSDT, 40000, 0.5, 1, 2, sell").unwrap().unwrap();
+        assert_eq!(trade.trade_id, 7);
+        assert_eq!(trade.trading_pair, TradingPair::BtcUsdt);
+        assert_eq!(trade.maker_side, OrderSide::Sell);
+        assert_eq!(trade.quantity, Quantity::from_f64(0.5));
+
+        assert!(parse_trade_line("# comment").is_none());
+        assert!(parse_trade_line("trade_id,symbol,price,quantity,buyer,seller,maker").is_none());
+        assert!(parse_trade_line("7,DOGEUSDT,1,1,1,2,BUY").unwrap().is_err());
+        assert!(parse_trade_line("7,BTCUSDT,1,1").unwrap().is_err());
+    }
+}
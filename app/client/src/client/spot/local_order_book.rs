@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use base_types::{Price, Quantity};
+
+/// 参与校验和计算的档位数
+pub const CHECKSUM_LEVELS: usize = 25;
+
+/// 快照到达前最多缓存的增量条数
+const MAX_BUFFERED_DIFFS: usize = 1000;
+
+/// REST 深度快照
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DepthSnapshot {
+    pub last_update_id: u64,
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+}
+
+/// WebSocket 深度增量（数量为 0 表示删除该价位）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DepthDiff {
+    /// 本条增量的首个更新ID
+    pub first_update_id: u64,
+    /// 本条增量的最后更新ID
+    pub last_update_id: u64,
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+    /// 应用后前 [`CHECKSUM_LEVELS`] 档的校验和（服务端未提供为 None）
+    pub checksum: Option<u32>,
+}
+
+/// 增量应用结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOutcome {
+    /// 已应用
+    Applied,
+    /// 尚无快照，已缓存
+    Buffered,
+    /// 早于当前簿的增量，忽略
+    Ignored,
+}
+
+/// 本地订单簿失步，需重新拉取快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookError {
+    /// 更新ID不连续
+    Gap { expected: u64, first_update_id: u64 },
+    /// 校验和不一致
+    ChecksumMismatch { expected: u32, actual: u32 },
+}
+
+/// 本地订单簿
+///
+/// 按「先订阅增量并缓存、再拉快照、丢弃快照之前的增量、逐条衔接应用」维护：
+/// - 首条应用的增量须满足 `first_update_id <= 快照ID + 1 <= last_update_id`
+/// - 之后每条须满足 `first_update_id == 上一条 last_update_id + 1`
+/// - 增量携带校验和时，应用后与本地前 [`CHECKSUM_LEVELS`] 档的校验和比对
+///
+/// 任一校验失败时订单簿清空并回到未同步状态，调用方重新拉取快照，
+/// 期间到达的增量继续缓存
+#[derive(Debug, Default)]
+pub struct LocalOrderBook {
+    bids: BTreeMap<Price, Quantity>,
+    asks: BTreeMap<Price, Quantity>,
+    /// 已应用的最后更新ID（None=未同步）
+    last_update_id: Option<u64>,
+    buffered: VecDeque<DepthDiff>,
+}
+
+impl LocalOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否已与服务端同步
+    pub fn is_synced(&self) -> bool {
+        self.last_update_id.is_some()
+    }
+
+    pub fn last_update_id(&self) -> Option<u64> {
+        self.last_update_id
+    }
+
+    /// 应用快照并衔接已缓存的增量
+    pub fn apply_snapshot(&mut self, snapshot: DepthSnapshot) -> Result<(), BookError> {
+        self.bids = snapshot.bids.into_iter().filter(|(_, q)| !q.is_zero()).collect();
+        self.asks = snapshot.asks.into_iter().filter(|(_, q)| !q.is_zero()).collect();
+        self.last_update_id = Some(snapshot.last_update_id);
+
+        // 衔接失败时，失败的与其后的增量留待下一份快照
+        let buffered: Vec<DepthDiff> = self.buffered.drain(..).collect();
+        let mut result = Ok(());
+        for diff in buffered {
+            if result.is_ok() {
+                match self.apply_diff(diff.clone()) {
+                    Ok(_) => continue,
+                    Err(error) => result = Err(error),
+                }
+            }
+            self.buffered.push_back(diff);
+        }
+        result
+    }
+
+    /// 应用一条增量
+    pub fn apply_diff(&mut self, diff: DepthDiff) -> Result<DiffOutcome, BookError> {
+        let Some(last) = self.last_update_id else {
+            if self.buffered.len() == MAX_BUFFERED_DIFFS {
+                self.buffered.pop_front();
+            }
+            self.buffered.push_back(diff);
+            return Ok(DiffOutcome::Buffered);
+        };
+        if diff.last_update_id <= last {
+            return Ok(DiffOutcome::Ignored);
+        }
+        if diff.first_update_id > last + 1 {
+            self.reset();
+            return Err(BookError::Gap {
+                expected: last + 1,
+                first_update_id: diff.first_update_id,
+            });
+        }
+
+        for (book, levels) in [(&mut self.bids, diff.bids), (&mut self.asks, diff.asks)] {
+            for (price, quantity) in levels {
+                if quantity.is_zero() {
+                    book.remove(&price);
+                } else {
+                    book.insert(price, quantity);
+                }
+            }
+        }
+        self.last_update_id = Some(diff.last_update_id);
+
+        if let Some(expected) = diff.checksum {
+            let actual = self.checksum();
+            if actual != expected {
+                self.reset();
+                return Err(BookError::ChecksumMismatch { expected, actual });
+            }
+        }
+        Ok(DiffOutcome::Applied)
+    }
+
+    /// 清空并回到未同步状态
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.last_update_id = None;
+    }
+
+    pub fn best_bid(&self) -> Option<(Price, Quantity)> {
+        self.bids.iter().next_back().map(|(&p, &q)| (p, q))
+    }
+
+    pub fn best_ask(&self) -> Option<(Price, Quantity)> {
+        self.asks.iter().next().map(|(&p, &q)| (p, q))
+    }
+
+    /// 买盘（价格从高到低）
+    pub fn bids(&self) -> impl Iterator<Item = (Price, Quantity)> + '_ {
+        self.bids.iter().rev().map(|(&p, &q)| (p, q))
+    }
+
+    /// 卖盘（价格从低到高）
+    pub fn asks(&self) -> impl Iterator<Item = (Price, Quantity)> + '_ {
+        self.asks.iter().map(|(&p, &q)| (p, q))
+    }
+
+    /// 前 [`CHECKSUM_LEVELS`] 档的校验和
+    pub fn checksum(&self) -> u32 {
+        depth_checksum(self.bids(), self.asks())
+    }
+}
+
+/// 深度校验和
+///
+/// 买卖盘前 [`CHECKSUM_LEVELS`] 档按 `买价:买量:卖价:卖量` 交替拼接（一侧档位不足时
+/// 只拼另一侧），价格与数量取定点数原始整数，结果为拼接串的 CRC-32（IEEE）
+pub fn depth_checksum(
+    bids: impl IntoIterator<Item = (Price, Quantity)>,
+    asks: impl IntoIterator<Item = (Price, Quantity)>,
+) -> u32 {
+    let mut bids = bids.into_iter().take(CHECKSUM_LEVELS);
+    let mut asks = asks.into_iter().take(CHECKSUM_LEVELS);
+    let mut fields: Vec<String> = Vec::with_capacity(CHECKSUM_LEVELS * 4);
+    loop {
+        let (bid, ask) = (bids.next(), asks.next());
+        if bid.is_none() && ask.is_none() {
+            break;
+        }
+        for (price, quantity) in bid.into_iter().chain(ask) {
+            fields.push(price.raw().to_string());
+            fields.push(quantity.raw().to_string());
+        }
+    }
+    crc32(fields.join(":").as_bytes())
+}
+
+/// CRC-32（IEEE 802.3）
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: f64, quantity: f64) -> (Price, Quantity) {
+        (Price::from_f64(price), Quantity::from_f64(quantity))
+    }
+
+    fn diff(first: u64, last: u64, bids: Vec<(Price, Quantity)>) -> DepthDiff {
+        DepthDiff { first_update_id: first, last_update_id: last, bids, ..Default::default() }
+    }
+
+    #[test]
+    fn test_snapshot_then_buffered_diffs() {
+        let mut book = LocalOrderBook::new();
+        // 快照前的增量先缓存
+        assert_eq!(
+            book.apply_diff(diff(98, 100, vec![level(99.0, 9.0)])),
+            Ok(DiffOutcome::Buffered)
+        );
+        assert_eq!(
+            book.apply_diff(diff(101, 103, vec![level(100.0, 2.0)])),
+            Ok(DiffOutcome::Buffered)
+        );
+
+        book.apply_snapshot(DepthSnapshot {
+            last_update_id: 101,
+            bids: vec![level(100.0, 1.0), level(99.0, 3.0)],
+            asks: vec![level(101.0, 1.0)],
+        })
+        .unwrap();
+        // 第一条早于快照被丢弃，第二条衔接应用
+        assert_eq!(book.last_update_id(), Some(103));
+        assert_eq!(book.best_bid(), Some(level(100.0, 2.0)));
+        assert_eq!(book.bids().nth(1), Some(level(99.0, 3.0)));
+
+        let mut next = diff(104, 104, vec![level(100.0, 0.0)]);
+        next.asks = vec![level(101.5, 4.0)];
+        next.checksum =
+            Some(depth_checksum([level(99.0, 3.0)], [level(101.0, 1.0), level(101.5, 4.0)]));
+        assert_eq!(book.apply_diff(next), Ok(DiffOutcome::Applied));
+        assert_eq!(book.best_bid(), Some(level(99.0, 3.0)));
+        assert_eq!(book.asks().count(), 2);
+        assert_eq!(book.apply_diff(diff(90, 104, vec![])), Ok(DiffOutcome::Ignored));
+    }
+
+    #[test]
+    fn test_gap_and_checksum_mismatch_reset_book() {
+        let mut book = LocalOrderBook::new();
+        let snapshot =
+            DepthSnapshot { last_update_id: 10, bids: vec![level(100.0, 1.0)], asks: vec![] };
+        book.apply_snapshot(snapshot.clone()).unwrap();
+        assert_eq!(
+            book.apply_diff(diff(12, 12, vec![])),
+            Err(BookError::Gap { expected: 11, first_update_id: 12 })
+        );
+        assert!(!book.is_synced());
+        assert_eq!(book.best_bid(), None);
+
+        book.apply_snapshot(snapshot).unwrap();
+        let mut bad = diff(11, 11, vec![level(100.0, 2.0)]);
+        bad.checksum = Some(0);
+        assert!(matches!(
+            book.apply_diff(bad),
+            Err(BookError::ChecksumMismatch { expected: 0, .. })
+        ));
+        assert!(!book.is_synced());
+    }
+}
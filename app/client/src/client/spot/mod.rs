@@ -1,3 +1,5 @@
+pub mod local_order_book;
+pub mod order_tracker;
 pub mod restful_client;
 
 pub mod spot_http_client;
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use base_types::exchange::spot::spot_types::{OrderStatus, SpotExecutionReport};
+use base_types::{OrderId, OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+/// REST 下单应答（服务端受理订单时返回）
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderAck {
+    pub order_id: OrderId,
+    pub client_order_id: Option<String>,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    pub status: OrderStatus,
+    pub cumulative_filled_qty: Quantity,
+    pub average_price: Price,
+    pub cumulative_quote_qty: Quantity,
+    pub remaining_qty: Quantity,
+    pub timestamp: Timestamp,
+}
+
+/// 客户端维护的订单规范状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub order_id: OrderId,
+    pub client_order_id: Option<String>,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    pub status: OrderStatus,
+    pub cumulative_filled_qty: Quantity,
+    pub average_price: Price,
+    pub cumulative_quote_qty: Quantity,
+    pub remaining_qty: Quantity,
+    pub updated_at: Timestamp,
+    /// 检测到漏收执行回报，需通过 REST 查询订单后 [`OrderTracker::resync`]
+    pub needs_resync: bool,
+}
+
+/// 一次更新的对账结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconcile {
+    /// 首次见到该订单
+    Created,
+    /// 状态前进
+    Updated,
+    /// 过期或重复的消息（已被更新的状态覆盖），忽略
+    Stale,
+    /// 累计成交量与本次成交量对不上，中间漏收了执行回报；已按新状态更新并标记待重同步
+    Gap,
+}
+
+/// 订单状态跟踪器
+///
+/// 合并 REST 下单应答与 WebSocket 执行回报，得到每个订单的规范状态。
+/// 两路消息可能乱序到达（执行回报先于应答），按累计成交量与状态迁移规则
+/// 只接受使订单前进的消息，过期消息被忽略；终态订单不再变化
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<OrderId, TrackedOrder>,
+    client_ids: HashMap<String, OrderId>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理 REST 下单应答
+    pub fn on_ack(&mut self, ack: &OrderAck) -> Reconcile {
+        if let Some(client_order_id) = &ack.client_order_id {
+            self.client_ids.insert(client_order_id.clone(), ack.order_id);
+        }
+        let incoming = TrackedOrder {
+            order_id: ack.order_id,
+            client_order_id: ack.client_order_id.clone(),
+            trading_pair: ack.trading_pair,
+            side: ack.side,
+            status: ack.status,
+            cumulative_filled_qty: ack.cumulative_filled_qty,
+            average_price: ack.average_price,
+            cumulative_quote_qty: ack.cumulative_quote_qty,
+            remaining_qty: ack.remaining_qty,
+            updated_at: ack.timestamp,
+            needs_resync: false,
+        };
+        let result = self.merge(incoming, None);
+        // 执行回报不带客户订单号，由应答补齐
+        if let Some(order) = self.orders.get_mut(&ack.order_id) {
+            if order.client_order_id.is_none() {
+                order.client_order_id = ack.client_order_id.clone();
+            }
+        }
+        result
+    }
+
+    /// 处理 WebSocket 执行回报
+    pub fn on_execution_report(&mut self, report: &SpotExecutionReport) -> Reconcile {
+        let incoming = TrackedOrder {
+            order_id: report.order_id,
+            client_order_id: None,
+            trading_pair: report.trading_pair,
+            side: report.side,
+            status: report.status,
+            cumulative_filled_qty: report.cumulative_filled_qty,
+            average_price: report.average_price,
+            cumulative_quote_qty: report.cumulative_quote_qty,
+            remaining_qty: report.remaining_qty,
+            updated_at: report.timestamp,
+            needs_resync: false,
+        };
+        self.merge(incoming, Some(report.last_fill_qty))
+    }
+
+    /// 用 REST 查询到的订单覆盖本地状态并清除重同步标记
+    pub fn resync(&mut self, ack: &OrderAck) {
+        self.orders.remove(&ack.order_id);
+        self.on_ack(ack);
+    }
+
+    /// `last_fill_qty` 仅执行回报携带，用于检测漏收
+    fn merge(&mut self, incoming: TrackedOrder, last_fill_qty: Option<Quantity>) -> Reconcile {
+        let Some(current) = self.orders.get_mut(&incoming.order_id) else {
+            self.orders.insert(incoming.order_id, incoming);
+            return Reconcile::Created;
+        };
+
+        let advances = incoming.cumulative_filled_qty > current.cumulative_filled_qty
+            || (incoming.cumulative_filled_qty == current.cumulative_filled_qty
+                && incoming.status != current.status
+                && current.status.can_transition_to(incoming.status));
+        if current.status.is_terminal() || !advances {
+            return Reconcile::Stale;
+        }
+
+        let gap = last_fill_qty.is_some_and(|last| {
+            !last.is_zero()
+                && incoming.cumulative_filled_qty - current.cumulative_filled_qty != last
+        });
+        let client_order_id = current.client_order_id.take().or(incoming.client_order_id.clone());
+        let needs_resync = current.needs_resync || gap;
+        *current = TrackedOrder { client_order_id, needs_resync, ..incoming };
+        if gap { Reconcile::Gap } else { Reconcile::Updated }
+    }
+
+    pub fn get(&self, order_id: OrderId) -> Option<&TrackedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    /// 按客户订单号查找（需已收到下单应答）
+    pub fn by_client_order_id(&self, client_order_id: &str) -> Option<&TrackedOrder> {
+        self.client_ids.get(client_order_id).and_then(|id| self.orders.get(id))
+    }
+
+    /// 未到终态的订单
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().filter(|o| !o.status.is_terminal())
+    }
+
+    /// 需要重同步的订单
+    pub fn needs_resync(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().filter(|o| o.needs_resync)
+    }
+
+    /// 清理终态订单，返回清理数量
+    pub fn prune_terminal(&mut self) -> usize {
+        let before = self.orders.len();
+        self.orders.retain(|_, o| !o.status.is_terminal());
+        let orders = &self.orders;
+        self.client_ids.retain(|_, id| orders.contains_key(id));
+        before - self.orders.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::base_types::TraderId;
+
+    use super::*;
+
+    fn qty(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    fn ack(status: OrderStatus, filled: f64) -> OrderAck {
+        OrderAck {
+            order_id: 7,
+            client_order_id: Some("my-order".to_string()),
+            trading_pair: TradingPair::BtcUsdt,
+            side: OrderSide::Buy,
+            status,
+            cumulative_filled_qty: qty(filled),
+            average_price: Price::from_f64(50_000.0),
+            cumulative_quote_qty: qty(filled * 50_000.0),
+            remaining_qty: qty(1.0 - filled),
+            timestamp: Timestamp::default(),
+        }
+    }
+
+    fn report(status: OrderStatus, last_fill: f64, filled: f64) -> SpotExecutionReport {
+        SpotExecutionReport {
+            order_id: 7,
+            trader_id: TraderId::default(),
+            trading_pair: TradingPair::BtcUsdt,
+            side: OrderSide::Buy,
+            status,
+            last_fill_price: Price::from_f64(50_000.0),
+            last_fill_qty: qty(last_fill),
+            cumulative_filled_qty: qty(filled),
+            average_price: Price::from_f64(50_000.0),
+            cumulative_quote_qty: qty(filled * 50_000.0),
+            remaining_qty: qty(1.0 - filled),
+            timestamp: Timestamp::default(),
+            effective_user: None,
+        }
+    }
+
+    #[test]
+    fn test_report_before_ack() {
+        let mut tracker = OrderTracker::new();
+        // 成交回报先于下单应答到达
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::PartiallyFilled, 0.4, 0.4)),
+            Reconcile::Created
+        );
+        // 应答携带的是受理时的状态，已被回报覆盖
+        assert_eq!(tracker.on_ack(&ack(OrderStatus::New, 0.0)), Reconcile::Stale);
+        let order = tracker.by_client_order_id("my-order").unwrap();
+        assert_eq!(
+            (order.status, order.cumulative_filled_qty),
+            (OrderStatus::PartiallyFilled, qty(0.4))
+        );
+
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::Filled, 0.6, 1.0)),
+            Reconcile::Updated
+        );
+        // 重复与终态后的消息被忽略
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::PartiallyFilled, 0.4, 0.4)),
+            Reconcile::Stale
+        );
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::Cancelled, 0.0, 1.0)),
+            Reconcile::Stale
+        );
+        assert_eq!(tracker.open_orders().count(), 0);
+        assert_eq!(tracker.prune_terminal(), 1);
+        assert!(tracker.by_client_order_id("my-order").is_none());
+    }
+
+    #[test]
+    fn test_missed_fill_flags_resync() {
+        let mut tracker = OrderTracker::new();
+        tracker.on_ack(&ack(OrderStatus::New, 0.0));
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::PartiallyFilled, 0.2, 0.2)),
+            Reconcile::Updated
+        );
+        // 0.3 的成交回报丢失
+        assert_eq!(
+            tracker.on_execution_report(&report(OrderStatus::PartiallyFilled, 0.1, 0.6)),
+            Reconcile::Gap
+        );
+        assert_eq!(tracker.needs_resync().count(), 1);
+        assert_eq!(tracker.get(7).unwrap().cumulative_filled_qty, qty(0.6));
+
+        tracker.resync(&ack(OrderStatus::PartiallyFilled, 0.6));
+        assert_eq!(tracker.needs_resync().count(), 0);
+        assert_eq!(tracker.get(7).unwrap().client_order_id.as_deref(), Some("my-order"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::{Arc, RwLock};
+
+use base_types::account::activity::{
+    ActivityCategory, ActivityEntry, ActivityFilter, ActivityLedger,
+};
+use base_types::account::settlement::Settlement;
+use base_types::{AccountId, AssetId};
+
+use super::exchange_info::{json_response, query_param};
+
+/// 账户流水接口路径
+pub const ACCOUNT_ACTIVITY_PATH: &str = "/api/account/activity";
+
+/// `GET /api/account/activity` 处理器
+///
+/// 按鉴权得到的账户（JWT 或 API Key 签名）返回统一的资金流水（成交、手续费、资金费用、
+/// 划转、调整、利息），由结算管道写入的流水账本应答，按 `fromId` 翻页
+pub struct AccountActivityHandler {
+    ledger: Arc<RwLock<ActivityLedger>>,
+}
+
+impl Default for AccountActivityHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(ActivityLedger::new())))
+    }
+}
+
+impl AccountActivityHandler {
+    pub fn new(ledger: Arc<RwLock<ActivityLedger>>) -> Self {
+        Self { ledger }
+    }
+
+    /// 结算管道写入流水的入口
+    pub fn ledger(&self) -> Arc<RwLock<ActivityLedger>> {
+        self.ledger.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(ACCOUNT_ACTIVITY_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    ///
+    /// 参数：`category`（逗号分隔）、`asset`、`startTime`/`endTime`（毫秒）、`fromId`、
+    /// `limit`（默认 100，最大 1000）
+    fn render(&self, path: &str, account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(account_id) = account.parse::<u64>() else {
+            return Self::bad_request(format!("Invalid account: {}", account));
+        };
+        let filter = match Self::filter(path) {
+            Ok(filter) => filter,
+            Err(msg) => return Self::bad_request(msg),
+        };
+
+        let Ok(ledger) = self.ledger.read() else {
+            return (500, serde_json::json!({ "msg": "Activity ledger unavailable" }).to_string());
+        };
+        let page = match ledger.query(AccountId(account_id), &filter) {
+            Ok(page) => page,
+            Err(e) => return Self::bad_request(e.to_string()),
+        };
+        let entries: Vec<serde_json::Value> = page.entries.iter().map(Self::entry_json).collect();
+        let body = serde_json::json!({
+            "accountId": account_id,
+            "entries": entries,
+            "nextFromId": page.next_from_id,
+        });
+        (200, body.to_string())
+    }
+
+    fn filter(path: &str) -> Result<ActivityFilter, String> {
+        let categories = match query_param(path, "category") {
+            None => Vec::new(),
+            Some(list) => ActivityCategory::parse_list(list).map_err(|e| e.to_string())?,
+        };
+        let asset_id = match query_param(path, "asset") {
+            None => None,
+            Some(asset) => Some(
+                AssetId::from_str(&asset.to_uppercase())
+                    .ok_or_else(|| format!("Invalid asset: {}", asset))?,
+            ),
+        };
+        Ok(ActivityFilter {
+            categories,
+            asset_id,
+            start_time: Self::number(path, "startTime")?,
+            end_time: Self::number(path, "endTime")?,
+            from_id: Self::number(path, "fromId")?,
+            limit: Self::number(path, "limit")?,
+        })
+    }
+
+    fn number<T: std::str::FromStr>(path: &str, name: &str) -> Result<Option<T>, String> {
+        match query_param(path, name).map(str::parse::<T>) {
+            None => Ok(None),
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(_)) => Err(format!("Invalid parameter: {}", name)),
+        }
+    }
+
+    fn entry_json(entry: &ActivityEntry) -> serde_json::Value {
+        serde_json::json!({
+            "id": entry.id,
+            "settlementId": entry.settlement_id,
+            "category": entry.category.as_str(),
+            "asset": entry.asset_id.as_str(),
+            "amount": entry.amount.to_string(),
+            "time": entry.time,
+        })
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+/// 结算写入流水账本
+pub fn record_settlement(ledger: &RwLock<ActivityLedger>, settlement: &Settlement) {
+    if let Ok(mut ledger) = ledger.write() {
+        ledger.record(settlement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::account::balance_change::BalanceChangeType;
+    use base_types::{Quantity, Timestamp};
+
+    use super::*;
+
+    #[test]
+    fn test_account_activity() {
+        let handler = AccountActivityHandler::default();
+        let ledger = handler.ledger();
+        let settlement = Settlement::new(1, Timestamp(5_000_000))
+            .with_entry(
+                AccountId(7),
+                AssetId::Usdt,
+                Quantity::from_f64(-100.0),
+                BalanceChangeType::Trade,
+            )
+            .with_entry(
+                AccountId(7),
+                AssetId::Btc,
+                Quantity::from_f64(0.01),
+                BalanceChangeType::Trade,
+            )
+            .with_entry(
+                AccountId(7),
+                AssetId::Usdt,
+                Quantity::from_f64(-0.1),
+                BalanceChangeType::Fee,
+            );
+        record_settlement(&ledger, &settlement);
+        assert!(AccountActivityHandler::matches("GET", "/api/account/activity?limit=1"));
+        assert!(!AccountActivityHandler::matches("POST", "/api/account/activity"));
+
+        let (status, body) = handler.render("/api/account/activity?limit=2", Some("7"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["entries"].as_array().unwrap().len(), 2);
+        assert_eq!(json["entries"][0]["category"], "TRADE");
+        assert_eq!(json["nextFromId"], 3);
+
+        let (_, body) = handler.render("/api/account/activity?category=fee&asset=usdt", Some("7"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["entries"][0]["category"], "FEE");
+        assert_eq!(json["entries"][0]["time"], 5);
+        assert!(json["nextFromId"].is_null());
+
+        assert_eq!(handler.render(ACCOUNT_ACTIVITY_PATH, None).0, 401);
+        assert_eq!(handler.render("/api/account/activity?category=bogus", Some("7")).0, 400);
+        assert_eq!(handler.render("/api/account/activity?startTime=9&endTime=1", Some("7")).0, 400);
+    }
+}
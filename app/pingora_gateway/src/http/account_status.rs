@@ -0,0 +1,262 @@
+//! 账户状态闸门
+//!
+//! 网关维护账户状态登记表（见 `base_types::account::account`），转发前按请求账户检查：
+//! - 暂停（SUSPENDED）与冻结（FROZEN）的账户，新委托与大宗交易报告直接返回 403
+//! - 状态变更同时以 [`Command::SetAccountStatus`] 提交到合约撮合分片，
+//!   由引擎在事前检查、撤单与保证金转出时执行并写入命令日志
+//!
+//! 管理接口（`X-Admin-Token` 鉴权，未配置令牌时关闭）：
+//! - `POST /api/admin/account/status`：`{accountId, action, reason, operator}`，
+//!   `action` 为 `SUSPEND`、`FREEZE` 或 `REINSTATE`，原因必填并写入审计日志
+
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::account::account::{
+    AccountStatus, AccountStatusCommand, AccountStatusError, AccountStatusRegistry,
+};
+use base_types::{AccountId, SystemClock, TimestampProvider};
+use prep::domain::entity::AccountStatus as PrepAccountStatus;
+use prep::domain::service::Command;
+use serde::Deserialize;
+use tracing::warn;
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::block_trade::BLOCK_TRADE_PATH;
+use super::codec::{header_value, request_body};
+use super::degradation::NEW_ORDER_PATH;
+use super::exchange_info::json_response;
+
+/// 账户状态管理接口路径
+pub const ADMIN_ACCOUNT_STATUS_PATH: &str = "/api/admin/account/status";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusRequest {
+    account_id: u64,
+    action: String,
+    reason: String,
+    operator: String,
+}
+
+/// 账户状态闸门
+pub struct AccountStatusGate {
+    registry: Arc<RwLock<AccountStatusRegistry>>,
+    /// 合约撮合分片的命令发送端
+    engine: Option<Sender<Command>>,
+    admin_token: Option<String>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for AccountStatusGate {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(AccountStatusRegistry::new())), Arc::new(SystemClock))
+    }
+}
+
+impl AccountStatusGate {
+    pub fn new(
+        registry: Arc<RwLock<AccountStatusRegistry>>,
+        clock: Arc<dyn TimestampProvider>,
+    ) -> Self {
+        Self { registry, engine: None, admin_token: None, clock }
+    }
+
+    /// 配置管理令牌时开放管理接口
+    pub fn from_env() -> Self {
+        let gate = Self::default();
+        match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => gate.with_admin_token(admin_token),
+            None => gate,
+        }
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// 接入合约撮合分片，状态变更同步提交到引擎
+    pub fn with_engine(mut self, engine: Sender<Command>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// 账户状态登记表（供账户服务加载已有账户的状态）
+    pub fn registry(&self) -> &Arc<RwLock<AccountStatusRegistry>> {
+        &self.registry
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "POST" && path.split('?').next() == Some(ADMIN_ACCOUNT_STATUS_PATH)
+    }
+
+    /// 转发前检查：受限账户的新委托与大宗交易报告返回 403
+    ///
+    /// `account` 为请求所代表的账户（鉴权或代账户操作后的结果）
+    pub fn check(&self, method: &str, path: &str, account: Option<&str>) -> Result<(), Vec<u8>> {
+        let route = path.split('?').next();
+        if method != "POST" || !(route == Some(NEW_ORDER_PATH) || route == Some(BLOCK_TRADE_PATH)) {
+            return Ok(());
+        }
+        let Some(account_id) = account.and_then(|account| account.parse::<u64>().ok()) else {
+            return Ok(());
+        };
+        let status = self
+            .registry
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .status(AccountId(account_id));
+        if status.can_trade() {
+            return Ok(());
+        }
+        let body = serde_json::json!({
+            "msg": format!("Account {} is {}", account_id, status.as_str()),
+            "status": status.as_str(),
+        });
+        Err(json_response(403, &body.to_string()))
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, request: &[u8]) -> Vec<u8> {
+        let (status, body) = self.render(request);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, request: &[u8]) -> (u16, String) {
+        match self.admin(request) {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn admin(&self, request: &[u8]) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        let req: StatusRequest =
+            serde_json::from_slice(request_body(request)).map_err(|e| (400, e.to_string()))?;
+        let command = match req.action.as_str() {
+            "SUSPEND" => AccountStatusCommand::Suspend,
+            "FREEZE" => AccountStatusCommand::Freeze,
+            "REINSTATE" => AccountStatusCommand::Reinstate,
+            action => return Err((400, format!("Unknown action: {}", action))),
+        };
+        if req.operator.is_empty() {
+            return Err((400, "operator is required".to_string()));
+        }
+
+        let account = AccountId(req.account_id);
+        let change = self
+            .registry
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .apply(account, command, req.reason.as_str(), req.operator.as_str(), self.clock.now())
+            .map_err(|e| match e {
+                AccountStatusError::MissingReason => (400, e.to_string()),
+                _ => (409, e.to_string()),
+            })?;
+
+        // 合约引擎没有注销状态，注销的账户不会走到这里（状态机拒绝变更）
+        let target = match change.to {
+            AccountStatus::Suspended => PrepAccountStatus::Suspended,
+            AccountStatus::Frozen => PrepAccountStatus::Frozen,
+            _ => PrepAccountStatus::Active,
+        };
+        if let Some(engine) = &self.engine {
+            let command = Command::SetAccountStatus {
+                trader: req.account_id,
+                status: target,
+                reason: change.reason.clone(),
+                operator: change.operator.clone(),
+            };
+            if engine.send(command).is_err() {
+                warn!("Matching engine stopped, account {} status not forwarded", req.account_id);
+            }
+        }
+        Ok(serde_json::json!({
+            "accountId": req.account_id,
+            "from": change.from.as_str(),
+            "status": change.to.as_str(),
+        }))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn admin_request(body: &str) -> Vec<u8> {
+        format!(
+            "POST {} HTTP/1.1\r\nX-Admin-Token: secret\r\n\r\n{}",
+            ADMIN_ACCOUNT_STATUS_PATH, body
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_suspended_account_blocked_and_forwarded_to_engine() {
+        let (engine, inbox) = mpsc::channel();
+        let gate = AccountStatusGate::default().with_admin_token("secret").with_engine(engine);
+        assert!(gate.check("POST", NEW_ORDER_PATH, Some("7")).is_ok());
+
+        let suspend = admin_request(
+            r#"{"accountId":7,"action":"SUSPEND","reason":"KYC review","operator":"ops"}"#,
+        );
+        let (status, body) = gate.render(&suspend);
+        assert_eq!(status, 200, "{}", body);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap()["status"],
+            "SUSPENDED"
+        );
+        match inbox.try_recv().unwrap() {
+            Command::SetAccountStatus { trader, status, reason, .. } => {
+                assert_eq!(
+                    (trader, status, reason.as_str()),
+                    (7, PrepAccountStatus::Suspended, "KYC review")
+                );
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        // 新委托与大宗交易被拒，其他账户与查询不受影响
+        assert!(gate.check("POST", NEW_ORDER_PATH, Some("7")).is_err());
+        assert!(gate.check("POST", BLOCK_TRADE_PATH, Some("7")).is_err());
+        assert!(gate.check("GET", NEW_ORDER_PATH, Some("7")).is_ok());
+        assert!(gate.check("POST", NEW_ORDER_PATH, Some("8")).is_ok());
+
+        // 重复暂停、缺少原因
+        assert_eq!(gate.render(&suspend).0, 409);
+        let missing =
+            admin_request(r#"{"accountId":7,"action":"FREEZE","reason":" ","operator":"ops"}"#);
+        assert_eq!(gate.render(&missing).0, 400);
+        assert!(inbox.try_recv().is_err());
+
+        let reinstate = admin_request(
+            r#"{"accountId":7,"action":"REINSTATE","reason":"cleared","operator":"ops"}"#,
+        );
+        assert_eq!(gate.render(&reinstate).0, 200);
+        assert!(gate.check("POST", NEW_ORDER_PATH, Some("7")).is_ok());
+        assert!(matches!(
+            inbox.try_recv().unwrap(),
+            Command::SetAccountStatus { status: PrepAccountStatus::Active, .. }
+        ));
+        let registry = gate.registry().read().unwrap();
+        assert_eq!(registry.audit_log().len(), 2);
+    }
+}
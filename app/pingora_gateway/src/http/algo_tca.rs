@@ -0,0 +1,167 @@
+use std::sync::{Arc, RwLock};
+
+use base_types::AccountId;
+use base_types::exchange::spot::algo_tca::{TcaReport, TcaStore};
+
+use super::exchange_info::{json_response, query_param};
+
+/// TCA 报告接口路径
+pub const ALGO_TCA_PATH: &str = "/api/algo/tca";
+
+/// `GET /api/algo/tca` 处理器
+///
+/// 返回算法母单的交易成本分析：指定 `parentOrderId` 时返回该母单报告，
+/// 否则返回账户的全部母单报告。报告由算法执行写入的 TCA 记录生成；账户取自鉴权
+/// （JWT 或 API Key 签名），只能查询本账户的母单
+pub struct AlgoTcaHandler {
+    store: Arc<RwLock<TcaStore>>,
+}
+
+impl Default for AlgoTcaHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(TcaStore::new())))
+    }
+}
+
+impl AlgoTcaHandler {
+    pub fn new(store: Arc<RwLock<TcaStore>>) -> Self {
+        Self { store }
+    }
+
+    /// 算法执行写入 TCA 记录的入口
+    pub fn store(&self) -> Arc<RwLock<TcaStore>> {
+        self.store.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(ALGO_TCA_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, path: &str, account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return Self::error(401, "Authentication required".to_string());
+        };
+        let Ok(account_id) = account.parse::<u64>() else {
+            return Self::error(400, format!("Invalid account: {}", account));
+        };
+        let Ok(store) = self.store.read() else {
+            return Self::error(500, "TCA store unavailable".to_string());
+        };
+
+        match query_param(path, "parentOrderId") {
+            None => {
+                let reports: Vec<serde_json::Value> = store
+                    .reports_for(AccountId(account_id))
+                    .iter()
+                    .map(Self::report_json)
+                    .collect();
+                (
+                    200,
+                    serde_json::json!({ "accountId": account_id, "reports": reports }).to_string(),
+                )
+            }
+            Some(id) => {
+                let Ok(parent_order_id) = id.parse::<u64>() else {
+                    return Self::error(400, format!("Invalid parentOrderId: {}", id));
+                };
+                // 其他账户的母单与不存在的母单同样应答 404
+                match store.report(parent_order_id) {
+                    Some(report) if report.account_id == AccountId(account_id) => {
+                        (200, Self::report_json(&report).to_string())
+                    }
+                    _ => Self::error(404, format!("Unknown parent order: {}", parent_order_id)),
+                }
+            }
+        }
+    }
+
+    fn report_json(report: &TcaReport) -> serde_json::Value {
+        serde_json::json!({
+            "parentOrderId": report.parent_order_id,
+            "symbol": report.trading_pair.to_symbol_string(),
+            "side": format!("{:?}", report.side).to_uppercase(),
+            "strategy": format!("{:?}", report.strategy).to_uppercase(),
+            "quantity": report.quantity.to_string(),
+            "filledQuantity": report.filled_quantity.to_string(),
+            "averagePrice": report.average_price.map(|price| price.to_string()),
+            "arrivalPrice": report.arrival_price.to_string(),
+            "arrivalSlippageBps": report.arrival_slippage_bps,
+            "implementationShortfall": report.implementation_shortfall,
+            "implementationShortfallBps": report.implementation_shortfall_bps,
+            "participationRate": report.participation_rate,
+            "marketVolume": report.market_volume.to_string(),
+            "fees": report.fees.to_string(),
+            "startTime": report.start_time.0 / 1_000_000,
+            "endTime": report.end_time.map(|time| time.0 / 1_000_000),
+        })
+    }
+
+    fn error(status: u16, msg: String) -> (u16, String) {
+        (status, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::exchange::spot::algo_tca::{ChildFill, ParentOrder};
+    use base_types::exchange::spot::spot_types::AlgorithmStrategy;
+    use base_types::{OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+    use super::*;
+
+    #[test]
+    fn test_algo_tca_report() {
+        let handler = AlgoTcaHandler::default();
+        let store = handler.store();
+        {
+            let mut store = store.write().unwrap();
+            store
+                .register(ParentOrder {
+                    parent_order_id: 42,
+                    account_id: AccountId(7),
+                    trading_pair: TradingPair::BtcUsdt,
+                    side: OrderSide::Buy,
+                    strategy: AlgorithmStrategy::VWAP,
+                    quantity: Quantity::from_f64(2.0),
+                    arrival_price: Price::from_f64(100.0),
+                    start_time: Timestamp(3_000_000),
+                })
+                .unwrap();
+            let fill = ChildFill {
+                price: Price::from_f64(101.0),
+                quantity: Quantity::from_f64(2.0),
+                fee: Quantity::default(),
+                time: Timestamp(4_000_000),
+            };
+            store.record_fill(42, fill).unwrap();
+            store.record_market_trade(TradingPair::BtcUsdt, fill.price, Quantity::from_f64(8.0));
+            store.complete(42, Timestamp(5_000_000)).unwrap();
+        }
+        assert!(AlgoTcaHandler::matches("GET", "/api/algo/tca?parentOrderId=42"));
+        assert!(!AlgoTcaHandler::matches("POST", ALGO_TCA_PATH));
+
+        let (status, body) = handler.render("/api/algo/tca?parentOrderId=42", Some("7"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbol"], "BTCUSDT");
+        assert_eq!(json["strategy"], "VWAP");
+        assert_eq!(json["participationRate"], 0.25);
+        assert_eq!(json["endTime"], 5);
+
+        let (_, body) = handler.render(ALGO_TCA_PATH, Some("7"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["reports"].as_array().unwrap().len(), 1);
+
+        assert_eq!(handler.render("/api/algo/tca?parentOrderId=42", Some("8")).0, 404);
+        assert_eq!(handler.render("/api/algo/tca?parentOrderId=x", Some("7")).0, 400);
+        assert_eq!(handler.render(ALGO_TCA_PATH, None).0, 401);
+    }
+}
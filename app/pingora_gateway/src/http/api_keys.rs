@@ -0,0 +1,690 @@
+//! API Key 管理接口
+//!
+//! 自助接口（按 JWT 会话鉴权得到的账户操作，未鉴权返回 401；不接受 API Key 鉴权
+//! 或代账户操作的请求，避免泄露的 Key 自我续期、被授权人为他人账户签发 Key）：
+//! - `GET /api/apiKeys`：列出本账户的 Key（不含密钥）
+//! - `POST /api/apiKeys`：`{label, scopes, expiresAt}` 签发，密钥只在响应中出现一次
+//! - `POST /api/apiKeys/rotate`：`{apiKey}` 轮换，旧 Key 在宽限期内继续可用
+//! - `POST /api/apiKeys/revoke`：`{apiKey}` 吊销，立即生效
+//!
+//! 管理接口（`X-Admin-Token` 鉴权，未配置令牌时关闭）：
+//! - `GET /api/admin/apiKeys?accountId=`：列出任意账户的 Key
+//! - `POST /api/admin/apiKeys`：`{accountId, label, scopes, expiresAt}` 为账户签发
+//! - `POST /api/admin/apiKeys/revoke`：`{apiKey}` 吊销任意 Key
+//!
+//! 变更事件发布到 `ApiKeyEventLog`，其他网关消费后经 [`ApiKeyHandler::apply_event`] 同步。
+//! 发布在请求线程内进行：暂时性错误短暂退避重试，持续失败时熔断，不拖慢管理请求
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::account::api_key::{
+    ApiKey, ApiKeyError, ApiKeyEvent, ApiKeyRepo, ApiKeyScope, ApiKeyScopes, ApiKeyStore,
+    DEFAULT_ROTATION_GRACE, MemApiKeyRepo, NewApiKey,
+};
+use base_types::spot_topic::SpotTopic;
+use base_types::{AccountId, SystemClock, Timestamp, TimestampProvider};
+use resilience::{CircuitBreaker, CircuitBreakerConfig, Resilience, RetryPolicy, is_transient_io};
+use serde::Deserialize;
+use tracing::warn;
+
+use super::codec::{header_value, request_body};
+use super::delegation::ON_BEHALF_HEADER;
+use super::exchange_info::{json_response, query_param};
+
+/// 自助接口路径
+pub const API_KEYS_PATH: &str = "/api/apiKeys";
+pub const ROTATE_API_KEY_PATH: &str = "/api/apiKeys/rotate";
+pub const REVOKE_API_KEY_PATH: &str = "/api/apiKeys/revoke";
+/// 管理接口路径
+pub const ADMIN_API_KEYS_PATH: &str = "/api/admin/apiKeys";
+pub const ADMIN_REVOKE_API_KEY_PATH: &str = "/api/admin/apiKeys/revoke";
+
+/// 管理令牌请求头
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Key 仓储文件路径（JSON lines）
+pub const ENV_API_KEY_FILE: &str = "GATEWAY_API_KEY_FILE";
+/// 管理令牌
+pub const ENV_ADMIN_TOKEN: &str = "GATEWAY_ADMIN_TOKEN";
+
+/// 变更事件发送端（Kafka / Redpanda 生产者等）
+pub trait ApiKeyEventSink: Send + Sync {
+    fn send(&self, topic: &str, key: &[u8], payload: &[u8]) -> io::Result<()>;
+}
+
+/// 文件仓储：每次保存追加一行 JSON，加载时同一 Key 以最后一行为准
+#[derive(Debug)]
+pub struct FileApiKeyRepo {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl FileApiKeyRepo {
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+}
+
+impl ApiKeyRepo for FileApiKeyRepo {
+    fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError> {
+        let mut line = serde_json::to_vec(key).map_err(|e| ApiKeyError::Storage(e.to_string()))?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.write_all(&line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| ApiKeyError::Storage(e.to_string()))
+    }
+
+    fn load(&self, api_key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        Ok(self.load_all()?.into_iter().find(|key| key.api_key == api_key))
+    }
+
+    fn load_all(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        let storage = |e: io::Error| ApiKeyError::Storage(e.to_string());
+        let file = File::open(&self.path).map_err(storage)?;
+        let mut keys: Vec<ApiKey> = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(storage)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let key: ApiKey =
+                serde_json::from_str(&line).map_err(|e| ApiKeyError::Storage(e.to_string()))?;
+            match keys.iter_mut().find(|existing| existing.api_key == key.api_key) {
+                Some(existing) => *existing = key,
+                None => keys.push(key),
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateRequest {
+    /// 仅管理接口
+    account_id: Option<u64>,
+    #[serde(default)]
+    label: String,
+    scopes: Vec<String>,
+    /// 到期时间（毫秒）
+    expires_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyRequest {
+    api_key: String,
+}
+
+/// API Key 管理接口处理器
+pub struct ApiKeyHandler {
+    store: Arc<RwLock<ApiKeyStore>>,
+    admin_token: Option<String>,
+    sink: Option<Arc<dyn ApiKeyEventSink>>,
+    /// 事件发布的重试与熔断
+    publish_policy: Resilience,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for ApiKeyHandler {
+    /// 内存仓储、未配置管理令牌与事件发送端
+    fn default() -> Self {
+        let store = ApiKeyStore::new(Arc::new(MemApiKeyRepo::new()))
+            .expect("memory repo never fails to load");
+        Self::new(Arc::new(RwLock::new(store)), Arc::new(SystemClock))
+    }
+}
+
+impl ApiKeyHandler {
+    pub fn new(store: Arc<RwLock<ApiKeyStore>>, clock: Arc<dyn TimestampProvider>) -> Self {
+        Self {
+            store,
+            admin_token: None,
+            sink: None,
+            publish_policy: default_publish_policy(),
+            clock,
+        }
+    }
+
+    /// 从环境变量构建，两者都未设置时返回 `Ok(None)`（使用内存存储）
+    pub fn from_env() -> Result<Option<Self>, ApiKeyError> {
+        let path = std::env::var(ENV_API_KEY_FILE).ok();
+        let admin_token = std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty());
+        if path.is_none() && admin_token.is_none() {
+            return Ok(None);
+        }
+        let repo: Arc<dyn ApiKeyRepo> = match path {
+            Some(path) => Arc::new(
+                FileApiKeyRepo::open(&path)
+                    .map_err(|e| ApiKeyError::Storage(format!("{}: {}", path, e)))?,
+            ),
+            None => Arc::new(MemApiKeyRepo::new()),
+        };
+        let store = ApiKeyStore::new(repo)?;
+        let handler = Self::new(Arc::new(RwLock::new(store)), Arc::new(SystemClock));
+        Ok(Some(match admin_token {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        }))
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// 变更事件发送端
+    pub fn with_event_sink(mut self, sink: Arc<dyn ApiKeyEventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// 事件发布的重试与熔断策略
+    pub fn with_publish_policy(mut self, publish_policy: Resilience) -> Self {
+        self.publish_policy = publish_policy;
+        self
+    }
+
+    /// 与签名请求鉴权共享的 Key 存储
+    pub fn store(&self) -> &Arc<RwLock<ApiKeyStore>> {
+        &self.store
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        match method {
+            "GET" => path == API_KEYS_PATH || path == ADMIN_API_KEYS_PATH,
+            "POST" => matches!(
+                path,
+                API_KEYS_PATH
+                    | ROTATE_API_KEY_PATH
+                    | REVOKE_API_KEY_PATH
+                    | ADMIN_API_KEYS_PATH
+                    | ADMIN_REVOKE_API_KEY_PATH
+            ),
+            _ => false,
+        }
+    }
+
+    /// 生成完整的 HTTP 响应，`account` 为鉴权得到的账户（不可取自请求头）
+    pub fn respond(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+        account: Option<&str>,
+    ) -> Vec<u8> {
+        let (status, body) = self.render(method, path, request, account);
+        self.publish_events();
+        json_response(status, &body)
+    }
+
+    /// 应用其他网关发布的变更事件（JSON）
+    pub fn apply_event(&self, payload: &[u8]) -> Result<(), ApiKeyError> {
+        let event: ApiKeyEvent =
+            serde_json::from_slice(payload).map_err(|e| ApiKeyError::Storage(e.to_string()))?;
+        self.store.write().unwrap_or_else(PoisonError::into_inner).apply_event(&event)
+    }
+
+    /// 回写最近使用时间
+    pub fn flush_last_used(&self) -> Result<usize, ApiKeyError> {
+        self.store.write().unwrap_or_else(PoisonError::into_inner).flush_last_used()
+    }
+
+    /// 在独立线程中按间隔回写最近使用时间
+    pub fn spawn_flush(
+        store: Arc<RwLock<ApiKeyStore>>,
+        interval: Duration,
+    ) -> io::Result<JoinHandle<()>> {
+        std::thread::Builder::new().name("api-key-flush".to_string()).spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                if let Err(e) =
+                    store.write().unwrap_or_else(PoisonError::into_inner).flush_last_used()
+                {
+                    warn!("Failed to flush API key usage: {}", e);
+                }
+            }
+        })
+    }
+
+    fn render(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+        account: Option<&str>,
+    ) -> (u16, String) {
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let route = path.split('?').next().unwrap_or(path);
+        let result = if route.starts_with(ADMIN_API_KEYS_PATH) {
+            self.admin(method, route, path, head, request_body(request))
+        } else if header_value(head, "X-Api-Key").is_some() {
+            Err((403, "API keys cannot manage API keys".to_string()))
+        } else if header_value(head, ON_BEHALF_HEADER).is_some() {
+            Err((403, "API keys can only be managed by the account owner".to_string()))
+        } else {
+            match account.and_then(|id| id.parse::<u64>().ok()) {
+                Some(account_id) => {
+                    self.self_service(method, route, AccountId(account_id), request_body(request))
+                }
+                None => Err((401, "Authentication required".to_string())),
+            }
+        };
+        match result {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn self_service(
+        &self,
+        method: &str,
+        route: &str,
+        account_id: AccountId,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let now = self.clock.now();
+        match (method, route) {
+            ("GET", _) => Ok(self.list(account_id, now)),
+            ("POST", API_KEYS_PATH) => self.create(account_id, parse(body)?, now),
+            ("POST", ROTATE_API_KEY_PATH) => {
+                let req: KeyRequest = parse(body)?;
+                let (api_key, secret) = generate_key();
+                let new = self
+                    .store
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .rotate(
+                        &req.api_key,
+                        Some(account_id),
+                        api_key,
+                        secret,
+                        DEFAULT_ROTATION_GRACE,
+                        now,
+                    )
+                    .map_err(error_status)?;
+                let mut json = key_json(&new, now);
+                json["secret"] = new.secret.clone().into();
+                json["previousKeyExpiresAt"] = self
+                    .store
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get(&req.api_key)
+                    .and_then(|old| old.expires_at)
+                    .map(millis)
+                    .into();
+                Ok(json)
+            }
+            _ => {
+                let req: KeyRequest = parse(body)?;
+                self.revoke(&req.api_key, Some(account_id), now)
+            }
+        }
+    }
+
+    fn admin(
+        &self,
+        method: &str,
+        route: &str,
+        path: &str,
+        head: &str,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        let now = self.clock.now();
+        match (method, route) {
+            ("GET", _) => {
+                let account_id = query_param(path, "accountId")
+                    .and_then(|id| id.parse::<u64>().ok())
+                    .ok_or((400, "Missing accountId".to_string()))?;
+                Ok(self.list(AccountId(account_id), now))
+            }
+            ("POST", ADMIN_API_KEYS_PATH) => {
+                let req: CreateRequest = parse(body)?;
+                let account_id = req.account_id.ok_or((400, "Missing accountId".to_string()))?;
+                self.create(AccountId(account_id), req, now)
+            }
+            _ => {
+                let req: KeyRequest = parse(body)?;
+                self.revoke(&req.api_key, None, now)
+            }
+        }
+    }
+
+    fn list(&self, account_id: AccountId, now: Timestamp) -> serde_json::Value {
+        let store = self.store.read().unwrap_or_else(PoisonError::into_inner);
+        serde_json::Value::Array(
+            store.list(account_id).into_iter().map(|key| key_json(key, now)).collect(),
+        )
+    }
+
+    fn create(
+        &self,
+        account_id: AccountId,
+        req: CreateRequest,
+        now: Timestamp,
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let scopes = req
+            .scopes
+            .iter()
+            .map(|scope| {
+                ApiKeyScope::parse(scope).ok_or((400, format!("Unknown scope: {}", scope)))
+            })
+            .collect::<Result<ApiKeyScopes, _>>()?;
+        let (api_key, secret) = generate_key();
+        let new = NewApiKey {
+            api_key,
+            secret,
+            account_id,
+            label: req.label,
+            scopes,
+            expires_at: req.expires_at.map(|ms| Timestamp(ms.saturating_mul(1_000_000))),
+        };
+        let key = self
+            .store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .create(new, now)
+            .map_err(error_status)?;
+        let mut json = key_json(&key, now);
+        json["secret"] = key.secret.clone().into();
+        Ok(json)
+    }
+
+    fn revoke(
+        &self,
+        api_key: &str,
+        owner: Option<AccountId>,
+        now: Timestamp,
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let key = self
+            .store
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .revoke(api_key, owner, now)
+            .map_err(error_status)?;
+        Ok(key_json(&key, now))
+    }
+
+    /// 发布待发布的变更事件；重试后仍失败只记录日志，副本可从仓储重新加载
+    fn publish_events(&self) {
+        let events = self.store.write().unwrap_or_else(PoisonError::into_inner).drain_events();
+        let Some(sink) = self.sink.as_ref() else {
+            return;
+        };
+        let topic = SpotTopic::ApiKeyEventLog.name();
+        for event in events {
+            let payload = match serde_json::to_vec(&event) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Failed to encode API key event {}: {}", event.seq, e);
+                    continue;
+                }
+            };
+            let result = self
+                .publish_policy
+                .call(is_transient_io, |_| sink.send(topic, event.api_key.as_bytes(), &payload));
+            if let Err(e) = result {
+                warn!("Failed to publish API key event {}: {}", event.seq, e);
+            }
+        }
+    }
+}
+
+/// 默认发布策略：最多 3 次、总计不超过 200ms，连续 5 次失败熔断 30s
+fn default_publish_policy() -> Resilience {
+    let retry =
+        RetryPolicy::default().with_delays(Duration::from_millis(10), Duration::from_millis(50));
+    Resilience::new(retry)
+        .with_budget(Duration::from_millis(200))
+        .with_circuit_breaker(Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default())))
+}
+
+/// Key 信息（不含密钥）
+fn key_json(key: &ApiKey, now: Timestamp) -> serde_json::Value {
+    serde_json::json!({
+        "apiKey": key.api_key,
+        "accountId": key.account_id.0,
+        "label": key.label,
+        "scopes": key.scopes.iter().map(ApiKeyScope::as_str).collect::<Vec<_>>(),
+        "status": key.status(now).as_str(),
+        "createdAt": millis(key.created_at),
+        "expiresAt": key.expires_at.map(millis),
+        "lastUsedAt": key.last_used_at.map(millis),
+        "rotatedTo": key.rotated_to,
+    })
+}
+
+fn millis(timestamp: Timestamp) -> u64 {
+    timestamp.0 / 1_000_000
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, (u16, String)> {
+    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))
+}
+
+fn error_status(error: ApiKeyError) -> (u16, String) {
+    let status = match error {
+        ApiKeyError::UnknownKey(_) => 404,
+        ApiKeyError::NotOwner { .. } => 403,
+        ApiKeyError::DuplicateKey(_) => 409,
+        ApiKeyError::Storage(_) => 503,
+        _ => 400,
+    };
+    (status, error.to_string())
+}
+
+/// 随机 Key（32 位十六进制）与密钥（64 位十六进制）
+fn generate_key() -> (String, String) {
+    let api_key = uuid::Uuid::new_v4().simple().to_string();
+    let secret = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    (api_key, secret)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+    use base_types::account::api_key::ApiKeyEventKind;
+    use resilience::Jitter;
+
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    #[derive(Default)]
+    struct Captured {
+        messages: Mutex<Vec<(String, Vec<u8>)>>,
+        /// 接下来失败的次数
+        failures: Mutex<usize>,
+        attempts: Mutex<usize>,
+    }
+
+    impl ApiKeyEventSink for Captured {
+        fn send(&self, topic: &str, _key: &[u8], payload: &[u8]) -> io::Result<()> {
+            *self.attempts.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(io::ErrorKind::ConnectionRefused.into());
+            }
+            self.messages.lock().unwrap().push((topic.to_string(), payload.to_vec()));
+            Ok(())
+        }
+    }
+
+    fn handler(repo: Arc<dyn ApiKeyRepo>) -> ApiKeyHandler {
+        let store = ApiKeyStore::new(repo).unwrap();
+        ApiKeyHandler::new(Arc::new(RwLock::new(store)), Arc::new(ManualClock::from_millis(NOW_MS)))
+            .with_admin_token("root")
+    }
+
+    fn post(
+        handler: &ApiKeyHandler,
+        path: &str,
+        headers: &str,
+        body: &str,
+    ) -> (u16, serde_json::Value) {
+        let request = format!("POST {} HTTP/1.1\r\n{}\r\n{}", path, headers, body);
+        let (status, body) = handler.render("POST", path, request.as_bytes(), Some("10"));
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    #[test]
+    fn test_self_service_lifecycle() {
+        let sink = Arc::new(Captured::default());
+        let handler = handler(Arc::new(MemApiKeyRepo::new())).with_event_sink(sink.clone());
+        let (status, created) =
+            post(&handler, API_KEYS_PATH, "", r#"{"label":"bot","scopes":["READ","TRADE"]}"#);
+        assert_eq!(status, 200);
+        assert_eq!(created["scopes"], serde_json::json!(["READ", "TRADE"]));
+        assert_eq!(created["secret"].as_str().unwrap().len(), 64);
+        let api_key = created["apiKey"].as_str().unwrap().to_string();
+
+        // 列表不含密钥
+        let (_, listed) =
+            handler.render("GET", API_KEYS_PATH, b"GET / HTTP/1.1\r\n\r\n", Some("10"));
+        let listed: serde_json::Value = serde_json::from_str(&listed).unwrap();
+        assert_eq!(listed[0]["status"], "ACTIVE");
+        assert!(listed[0].get("secret").is_none());
+
+        let body = format!(r#"{{"apiKey":"{}"}}"#, api_key);
+        let (status, rotated) = post(&handler, ROTATE_API_KEY_PATH, "", &body);
+        assert_eq!(status, 200);
+        assert_eq!(rotated["previousKeyExpiresAt"], NOW_MS + 24 * 3600 * 1000);
+        assert_eq!(post(&handler, REVOKE_API_KEY_PATH, "", &body).1["status"], "REVOKED");
+
+        // 其他账户不能操作，API Key 鉴权的请求不能管理 Key
+        let request = format!("POST {} HTTP/1.1\r\n\r\n{}", REVOKE_API_KEY_PATH, body);
+        assert_eq!(
+            handler.render("POST", REVOKE_API_KEY_PATH, request.as_bytes(), Some("11")).0,
+            403
+        );
+        assert_eq!(post(&handler, API_KEYS_PATH, "X-Api-Key: k\r\n", "{}").0, 403);
+        assert_eq!(post(&handler, API_KEYS_PATH, "X-On-Behalf-Of: 11\r\n", "{}").0, 403);
+        // 未鉴权的请求不能按请求头中的用户ID操作
+        let request = format!("POST {} HTTP/1.1\r\nX-User-Id: 10\r\n\r\n{}", API_KEYS_PATH, "{}");
+        assert_eq!(handler.render("POST", API_KEYS_PATH, request.as_bytes(), None).0, 401);
+        assert_eq!(
+            post(&handler, API_KEYS_PATH, "", r#"{"scopes":["ADMIN"]}"#).1["msg"],
+            "Unknown scope: ADMIN"
+        );
+
+        handler.publish_events();
+        let messages = sink.messages.lock().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].0, "ApiKeyEventLog");
+        let revoked: ApiKeyEvent = serde_json::from_slice(&messages[2].1).unwrap();
+        assert_eq!(
+            (revoked.api_key.as_str(), revoked.kind),
+            (api_key.as_str(), ApiKeyEventKind::Revoked)
+        );
+    }
+
+    #[test]
+    fn test_publish_retries_then_breaks() {
+        let clock = Arc::new(resilience::ManualClock::new());
+        let breaker = CircuitBreaker::with_clock(
+            CircuitBreakerConfig::default().with_failure_threshold(3),
+            clock.clone(),
+        );
+        let policy = Resilience::new(RetryPolicy::default().with_jitter(Jitter::None))
+            .with_circuit_breaker(Arc::new(breaker))
+            .with_clock(clock.clone());
+        let sink = Arc::new(Captured { failures: Mutex::new(2), ..Default::default() });
+        let handler = handler(Arc::new(MemApiKeyRepo::new()))
+            .with_event_sink(sink.clone())
+            .with_publish_policy(policy);
+
+        // 两次暂时性失败后第三次成功
+        assert_eq!(post(&handler, API_KEYS_PATH, "", r#"{"scopes":["READ"]}"#).0, 200);
+        handler.publish_events();
+        assert_eq!(sink.messages.lock().unwrap().len(), 1);
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(50), Duration::from_millis(100)]);
+
+        // 重试用尽后熔断，之后的事件不再尝试发送
+        *sink.failures.lock().unwrap() = usize::MAX;
+        post(&handler, API_KEYS_PATH, "", r#"{"scopes":["READ"]}"#);
+        handler.publish_events();
+        assert_eq!(*sink.attempts.lock().unwrap(), 6);
+        post(&handler, API_KEYS_PATH, "", r#"{"scopes":["READ"]}"#);
+        handler.publish_events();
+        assert_eq!(*sink.attempts.lock().unwrap(), 6);
+        assert_eq!(sink.messages.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_admin_endpoints_and_event_replication() {
+        let repo: Arc<dyn ApiKeyRepo> = Arc::new(MemApiKeyRepo::new());
+        let primary = handler(repo.clone());
+        let replica = handler(repo);
+
+        let body = r#"{"accountId":42,"scopes":["READ"],"expiresAt":1700000060000}"#;
+        assert_eq!(post(&primary, ADMIN_API_KEYS_PATH, "", body).0, 401);
+        let (status, created) =
+            post(&primary, ADMIN_API_KEYS_PATH, "X-Admin-Token: root\r\n", body);
+        assert_eq!(status, 200);
+        let api_key = created["apiKey"].as_str().unwrap();
+
+        let path = format!("{}?accountId=42", ADMIN_API_KEYS_PATH);
+        let request = format!("GET {} HTTP/1.1\r\nX-Admin-Token: root\r\n\r\n", path);
+        let (_, listed) = primary.render("GET", &path, request.as_bytes(), None);
+        assert!(listed.contains(api_key));
+
+        // 副本消费事件后可见新 Key，吊销事件立即生效
+        for event in primary.store().write().unwrap().drain_events() {
+            replica.apply_event(&serde_json::to_vec(&event).unwrap()).unwrap();
+        }
+        let now = Timestamp(NOW_MS * 1_000_000);
+        assert!(replica.store().read().unwrap().check(api_key, ApiKeyScope::Read, now).is_ok());
+        let body = format!(r#"{{"apiKey":"{}"}}"#, api_key);
+        assert_eq!(
+            post(&primary, ADMIN_REVOKE_API_KEY_PATH, "X-Admin-Token: root\r\n", &body).0,
+            200
+        );
+        for event in primary.store().write().unwrap().drain_events() {
+            replica.apply_event(&serde_json::to_vec(&event).unwrap()).unwrap();
+        }
+        assert_eq!(
+            replica.store().read().unwrap().check(api_key, ApiKeyScope::Read, now),
+            Err(ApiKeyError::Revoked(api_key.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_file_repo_persists_latest_record() {
+        let path = std::env::temp_dir().join(format!("api-keys-{}.jsonl", uuid::Uuid::new_v4()));
+        let repo = Arc::new(FileApiKeyRepo::open(&path).unwrap());
+        let handler = handler(repo.clone());
+        let (_, created) = post(&handler, API_KEYS_PATH, "", r#"{"scopes":["TRADE"]}"#);
+        let api_key = created["apiKey"].as_str().unwrap();
+        post(&handler, REVOKE_API_KEY_PATH, "", &format!(r#"{{"apiKey":"{}"}}"#, api_key));
+
+        let reopened = ApiKeyStore::new(Arc::new(FileApiKeyRepo::open(&path).unwrap())).unwrap();
+        let key = reopened.get(api_key).unwrap();
+        assert!(key.revoked_at.is_some());
+        assert_eq!(key.secret, created["secret"].as_str().unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,532 @@
+//! 按账户、API Key 与接口统计的调用量
+//!
+//! 网关在鉴权之后为每个带账户的请求记账：请求数、消耗的权重、请求与响应字节数，
+//! 按分钟分桶保留一天，查询时按 1m / 1h / 1d 滚动窗口汇总：
+//! - 本分钟已用权重写入响应头 `X-Used-Weight-1m`（本地应答与转发的响应都带）
+//! - `GET /api/account/apiUsage?window=`：本账户按 Key 与接口的明细（默认 1h）
+//! - `GET /api/admin/apiUsage?window=`：管理控制台，各账户汇总按权重降序；
+//!   带 `accountId=` 时为该账户明细（`X-Admin-Token` 鉴权，未配置令牌时关闭）
+//!
+//! 账户只取自鉴权（JWT 或 API Key 签名），未鉴权的请求不记账
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::{AccountId, SystemClock, TimestampProvider};
+use serde::Serialize;
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::codec::header_value;
+use super::exchange_info::{EXCHANGE_INFO_PATH, json_response, query_param};
+use super::prep_history::{FUNDING_RATE_PATH, MARK_PRICE_KLINES_PATH};
+use super::trades::{HISTORICAL_TRADES_PATH, TRADES_PATH};
+
+/// 本账户调用量接口路径
+pub const API_USAGE_PATH: &str = "/api/account/apiUsage";
+/// 管理控制台调用量接口路径
+pub const ADMIN_API_USAGE_PATH: &str = "/api/admin/apiUsage";
+/// 本分钟已用权重响应头
+pub const USED_WEIGHT_HEADER: &str = "X-Used-Weight-1m";
+
+const MINUTE_MS: u64 = 60_000;
+/// 分桶保留时长（分钟）
+const RETENTION_MINUTES: u64 = 24 * 60;
+
+/// 接口权重（`route` 不含查询串），未列出的接口为 1
+pub fn endpoint_weight(route: &str) -> u64 {
+    match route {
+        EXCHANGE_INFO_PATH => 20,
+        HISTORICAL_TRADES_PATH => 25,
+        TRADES_PATH | MARK_PRICE_KLINES_PATH | FUNDING_RATE_PATH => 5,
+        API_USAGE_PATH => 2,
+        _ => 1,
+    }
+}
+
+/// 滚动窗口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageWindow {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl UsageWindow {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            UsageWindow::Minute => "1m",
+            UsageWindow::Hour => "1h",
+            UsageWindow::Day => "1d",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "1m" => Some(UsageWindow::Minute),
+            "1h" => Some(UsageWindow::Hour),
+            "1d" => Some(UsageWindow::Day),
+            _ => None,
+        }
+    }
+
+    /// 窗口覆盖的分钟数（含当前分钟）
+    pub const fn minutes(self) -> u64 {
+        match self {
+            UsageWindow::Minute => 1,
+            UsageWindow::Hour => 60,
+            UsageWindow::Day => RETENTION_MINUTES,
+        }
+    }
+}
+
+/// 调用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub requests: u64,
+    pub weight: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+impl UsageStats {
+    fn add(&mut self, other: &UsageStats) {
+        self.requests += other.requests;
+        self.weight += other.weight;
+        self.bytes_in += other.bytes_in;
+        self.bytes_out += other.bytes_out;
+    }
+}
+
+/// 记账维度
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    account_id: AccountId,
+    /// 签名请求使用的 Key（会话或内部请求为 None）
+    api_key: Option<String>,
+    /// `方法 路径`
+    endpoint: String,
+}
+
+/// 一个账户在窗口内按 Key 与接口的调用量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointUsage {
+    pub api_key: Option<String>,
+    pub endpoint: String,
+    #[serde(flatten)]
+    pub stats: UsageStats,
+}
+
+/// 分钟分桶的调用量表
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    /// 每个维度的分桶（分钟序号递增）
+    buckets: HashMap<UsageKey, VecDeque<(u64, UsageStats)>>,
+}
+
+impl UsageMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记账，`now_ms` 决定落入的分钟桶
+    pub fn record(
+        &mut self,
+        account_id: AccountId,
+        api_key: Option<&str>,
+        endpoint: &str,
+        stats: UsageStats,
+        now_ms: u64,
+    ) {
+        let minute = now_ms / MINUTE_MS;
+        let key = UsageKey {
+            account_id,
+            api_key: api_key.map(str::to_string),
+            endpoint: endpoint.to_string(),
+        };
+        let buckets = self.buckets.entry(key).or_default();
+        match buckets.back_mut() {
+            Some((last, total)) if *last == minute => total.add(&stats),
+            _ => buckets.push_back((minute, stats)),
+        }
+        while buckets.front().is_some_and(|(first, _)| first + RETENTION_MINUTES <= minute) {
+            buckets.pop_front();
+        }
+    }
+
+    /// 丢弃超过保留时长的分桶，返回剩余维度数
+    pub fn prune(&mut self, now_ms: u64) -> usize {
+        let minute = now_ms / MINUTE_MS;
+        self.buckets.retain(|_, buckets| {
+            buckets.retain(|(bucket, _)| bucket + RETENTION_MINUTES > minute);
+            !buckets.is_empty()
+        });
+        self.buckets.len()
+    }
+
+    /// 账户在窗口内已用的权重
+    pub fn used_weight(&self, account_id: AccountId, window: UsageWindow, now_ms: u64) -> u64 {
+        self.account_usage(account_id, window, now_ms).iter().map(|usage| usage.stats.weight).sum()
+    }
+
+    /// 账户在窗口内按 Key 与接口的明细（按权重降序）
+    pub fn account_usage(
+        &self,
+        account_id: AccountId,
+        window: UsageWindow,
+        now_ms: u64,
+    ) -> Vec<EndpointUsage> {
+        let mut usage: Vec<EndpointUsage> = self
+            .buckets
+            .iter()
+            .filter(|(key, _)| key.account_id == account_id)
+            .filter_map(|(key, buckets)| {
+                let stats = window_stats(buckets, window, now_ms)?;
+                Some(EndpointUsage {
+                    api_key: key.api_key.clone(),
+                    endpoint: key.endpoint.clone(),
+                    stats,
+                })
+            })
+            .collect();
+        usage.sort_by(|a, b| {
+            b.stats
+                .weight
+                .cmp(&a.stats.weight)
+                .then_with(|| a.endpoint.cmp(&b.endpoint))
+                .then_with(|| a.api_key.cmp(&b.api_key))
+        });
+        usage
+    }
+
+    /// 各账户在窗口内的汇总（按权重降序）
+    pub fn accounts(&self, window: UsageWindow, now_ms: u64) -> Vec<(AccountId, UsageStats)> {
+        let mut totals: HashMap<u64, UsageStats> = HashMap::new();
+        for (key, buckets) in &self.buckets {
+            if let Some(stats) = window_stats(buckets, window, now_ms) {
+                totals.entry(key.account_id.0).or_default().add(&stats);
+            }
+        }
+        let mut accounts: Vec<(AccountId, UsageStats)> =
+            totals.into_iter().map(|(account_id, stats)| (AccountId(account_id), stats)).collect();
+        accounts.sort_by(|a, b| b.1.weight.cmp(&a.1.weight).then_with(|| a.0.0.cmp(&b.0.0)));
+        accounts
+    }
+}
+
+fn window_stats(
+    buckets: &VecDeque<(u64, UsageStats)>,
+    window: UsageWindow,
+    now_ms: u64,
+) -> Option<UsageStats> {
+    let minute = now_ms / MINUTE_MS;
+    let mut total: Option<UsageStats> = None;
+    for (_, stats) in buckets.iter().filter(|(bucket, _)| bucket + window.minutes() > minute) {
+        total.get_or_insert_with(UsageStats::default).add(stats);
+    }
+    total
+}
+
+/// 在状态行之后插入响应头（无法定位状态行，或头名、头值含非法字符时原样返回）
+pub fn insert_header(response: &[u8], name: &str, value: &str) -> Vec<u8> {
+    let valid = |field: &str| !field.bytes().any(|b| b == b'\r' || b == b'\n' || b == 0);
+    if name.is_empty() || name.contains(':') || !valid(name) || !valid(value) {
+        return response.to_vec();
+    }
+    let Some(line_end) = response.windows(2).position(|window| window == b"\r\n") else {
+        return response.to_vec();
+    };
+    let mut out = Vec::with_capacity(response.len() + name.len() + value.len() + 4);
+    out.extend_from_slice(&response[..line_end + 2]);
+    out.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+    out.extend_from_slice(&response[line_end + 2..]);
+    out
+}
+
+/// 一次请求的记账凭据，响应写出后据此补记响应字节数
+#[derive(Debug, Clone)]
+pub struct UsageTicket {
+    account_id: AccountId,
+    api_key: Option<String>,
+    endpoint: String,
+    /// 记账后本分钟已用权重
+    pub used_weight_1m: u64,
+}
+
+/// 调用量记账与查询接口处理器
+pub struct ApiUsageHandler {
+    meter: Arc<Mutex<UsageMeter>>,
+    admin_token: Option<String>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for ApiUsageHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(Mutex::new(UsageMeter::new())), Arc::new(SystemClock))
+    }
+}
+
+impl ApiUsageHandler {
+    pub fn new(meter: Arc<Mutex<UsageMeter>>, clock: Arc<dyn TimestampProvider>) -> Self {
+        Self { meter, admin_token: None, clock }
+    }
+
+    /// 配置管理令牌时开放管理控制台接口
+    pub fn from_env() -> Self {
+        let handler = Self::default();
+        match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        }
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    pub fn meter(&self) -> &Arc<Mutex<UsageMeter>> {
+        &self.meter
+    }
+
+    /// 在独立线程中按间隔丢弃过期分桶
+    pub fn spawn_prune(&self, interval: Duration) -> io::Result<JoinHandle<()>> {
+        let (meter, clock) = (self.meter.clone(), self.clock.clone());
+        std::thread::Builder::new().name("api-usage-prune".to_string()).spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                meter.lock().unwrap_or_else(PoisonError::into_inner).prune(clock.now_millis());
+            }
+        })
+    }
+
+    /// 为请求记账（请求数、权重、请求字节），未鉴权的请求返回 None
+    ///
+    /// `account` 为鉴权得到的账户，`api_key` 只在签名请求通过校验后传入
+    pub fn record(
+        &self,
+        account: Option<&str>,
+        api_key: Option<&str>,
+        method: &str,
+        path: &str,
+        request_bytes: usize,
+    ) -> Option<UsageTicket> {
+        let account_id = AccountId(account?.parse().ok()?);
+        let route = path.split('?').next().unwrap_or(path);
+        let endpoint = format!("{} {}", method, route);
+        let stats = UsageStats {
+            requests: 1,
+            weight: endpoint_weight(route),
+            bytes_in: request_bytes as u64,
+            bytes_out: 0,
+        };
+        let now_ms = self.clock.now_millis();
+        let mut meter = self.meter.lock().unwrap_or_else(PoisonError::into_inner);
+        meter.record(account_id, api_key, &endpoint, stats, now_ms);
+        Some(UsageTicket {
+            account_id,
+            api_key: api_key.map(str::to_string),
+            endpoint,
+            used_weight_1m: meter.used_weight(account_id, UsageWindow::Minute, now_ms),
+        })
+    }
+
+    /// 补记响应字节数
+    pub fn record_response(&self, ticket: &UsageTicket, response_bytes: usize) {
+        let stats = UsageStats { bytes_out: response_bytes as u64, ..UsageStats::default() };
+        self.meter.lock().unwrap_or_else(PoisonError::into_inner).record(
+            ticket.account_id,
+            ticket.api_key.as_deref(),
+            &ticket.endpoint,
+            stats,
+            self.clock.now_millis(),
+        );
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        method == "GET" && (path == API_USAGE_PATH || path == ADMIN_API_USAGE_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, request: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, request, account);
+        json_response(status, &body)
+    }
+
+    fn render(&self, path: &str, request: &[u8], account: Option<&str>) -> (u16, String) {
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let route = path.split('?').next().unwrap_or(path);
+        let result = match query_param(path, "window").map(|w| (UsageWindow::parse(w), w)) {
+            Some((None, window)) => Err((400, format!("Invalid window: {}", window))),
+            Some((Some(window), _)) => Ok(window),
+            None => Ok(UsageWindow::Hour),
+        }
+        .and_then(|window| {
+            if route == ADMIN_API_USAGE_PATH {
+                self.admin(path, head, window)
+            } else {
+                match account.and_then(|id| id.parse::<u64>().ok()) {
+                    Some(account_id) => Ok(self.account_json(AccountId(account_id), window)),
+                    None => Err((401, "Authentication required".to_string())),
+                }
+            }
+        });
+        match result {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn admin(
+        &self,
+        path: &str,
+        head: &str,
+        window: UsageWindow,
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        if let Some(account_id) = query_param(path, "accountId") {
+            let account_id =
+                account_id.parse().map_err(|_| (400, "Invalid accountId".to_string()))?;
+            return Ok(self.account_json(AccountId(account_id), window));
+        }
+        let accounts = self
+            .meter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .accounts(window, self.clock.now_millis());
+        Ok(serde_json::json!({
+            "window": window.as_str(),
+            "accounts": accounts
+                .iter()
+                .map(|(account_id, stats)| {
+                    let mut json = serde_json::json!(stats);
+                    json["accountId"] = account_id.0.into();
+                    json
+                })
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    fn account_json(&self, account_id: AccountId, window: UsageWindow) -> serde_json::Value {
+        let now_ms = self.clock.now_millis();
+        let meter = self.meter.lock().unwrap_or_else(PoisonError::into_inner);
+        let endpoints = meter.account_usage(account_id, window, now_ms);
+        let mut total = UsageStats::default();
+        for usage in &endpoints {
+            total.add(&usage.stats);
+        }
+        serde_json::json!({
+            "accountId": account_id.0,
+            "window": window.as_str(),
+            "usedWeight1m": meter.used_weight(account_id, UsageWindow::Minute, now_ms),
+            "total": total,
+            "endpoints": endpoints,
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    fn stats(weight: u64) -> UsageStats {
+        UsageStats { requests: 1, weight, bytes_in: 100, bytes_out: 0 }
+    }
+
+    #[test]
+    fn test_rolling_windows() {
+        let mut meter = UsageMeter::new();
+        let trades = "GET /api/spot/trades";
+        meter.record(AccountId(7), Some("key-1"), trades, stats(5), NOW_MS - 2 * 3_600_000);
+        meter.record(AccountId(7), Some("key-1"), trades, stats(5), NOW_MS - 10 * MINUTE_MS);
+        meter.record(AccountId(7), Some("key-1"), trades, stats(5), NOW_MS);
+        meter.record(AccountId(7), None, "GET /api/apiKeys", stats(1), NOW_MS);
+        meter.record(AccountId(9), None, trades, stats(5), NOW_MS);
+
+        assert_eq!(meter.used_weight(AccountId(7), UsageWindow::Minute, NOW_MS), 6);
+        assert_eq!(meter.used_weight(AccountId(7), UsageWindow::Hour, NOW_MS), 11);
+        assert_eq!(meter.used_weight(AccountId(7), UsageWindow::Day, NOW_MS), 16);
+
+        let usage = meter.account_usage(AccountId(7), UsageWindow::Hour, NOW_MS);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].api_key.as_deref(), Some("key-1"));
+        assert_eq!(
+            usage[0].stats,
+            UsageStats { requests: 2, weight: 10, bytes_in: 200, bytes_out: 0 }
+        );
+
+        let accounts = meter.accounts(UsageWindow::Day, NOW_MS);
+        assert_eq!(accounts[0].0, AccountId(7));
+        assert_eq!(accounts[1], (AccountId(9), stats(5)));
+
+        // 一天后分桶全部过期
+        assert_eq!(meter.prune(NOW_MS + RETENTION_MINUTES * MINUTE_MS), 0);
+    }
+
+    #[test]
+    fn test_usage_endpoints() {
+        let clock = Arc::new(ManualClock::from_millis(NOW_MS));
+        let handler = ApiUsageHandler::new(Arc::new(Mutex::new(UsageMeter::new())), clock)
+            .with_admin_token("secret");
+        assert!(handler.record(None, None, "GET", "/api/spot/trades", 64).is_none());
+        let ticket = handler
+            .record(Some("7"), Some("key-1"), "GET", "/api/spot/trades?symbol=BTCUSDT", 64)
+            .unwrap();
+        assert_eq!(ticket.used_weight_1m, 5);
+        handler.record_response(&ticket, 1_000);
+        let ticket = handler.record(Some("7"), None, "GET", API_USAGE_PATH, 32).unwrap();
+        assert_eq!(ticket.used_weight_1m, 7);
+
+        let request = b"GET /api/account/apiUsage HTTP/1.1\r\n\r\n";
+        let (status, body) = handler.render("/api/account/apiUsage?window=1m", request, Some("7"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["usedWeight1m"], 7);
+        assert_eq!(json["total"]["bytesOut"], 1_000);
+        assert_eq!(json["endpoints"][0]["endpoint"], "GET /api/spot/trades");
+        assert_eq!(json["endpoints"][0]["apiKey"], "key-1");
+        assert_eq!(handler.render(API_USAGE_PATH, request, None).0, 401);
+        assert_eq!(handler.render("/api/account/apiUsage?window=5m", request, Some("7")).0, 400);
+
+        let admin = b"GET /api/admin/apiUsage HTTP/1.1\r\nX-Admin-Token: secret\r\n\r\n";
+        let (status, body) = handler.render(ADMIN_API_USAGE_PATH, admin, None);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["accounts"][0]["accountId"], 7);
+        assert_eq!(json["accounts"][0]["weight"], 7);
+        let path = "/api/admin/apiUsage?accountId=7&window=1d";
+        assert_eq!(handler.render(path, admin, None).0, 200);
+        assert_eq!(handler.render(ADMIN_API_USAGE_PATH, request, None).0, 401);
+
+        let response = insert_header(&json_response(200, "{}"), USED_WEIGHT_HEADER, "7");
+        assert!(
+            String::from_utf8(response)
+                .unwrap()
+                .starts_with("HTTP/1.1 200 OK\r\nX-Used-Weight-1m: 7\r\n")
+        );
+        let original = json_response(200, "{}");
+        assert_eq!(insert_header(&original, USED_WEIGHT_HEADER, "7\r\nX-Evil: 1"), original);
+    }
+}
@@ -0,0 +1,373 @@
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use base_types::account::activity::ActivityLedger;
+use base_types::account::clearing::{ClearingContext, FeeProfile, clear_spot_trade};
+use base_types::account::settlement_export::{SettlementExporter, SettlementSink};
+use base_types::exchange::spot::block_trade::{BlockTradeDesk, BlockTradeError, BlockTradeReport};
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{
+    AccountId, AtomicSequence, Price, Quantity, SequenceGenerator, SystemClock, TimestampProvider,
+    TradingPair,
+};
+use serde::Deserialize;
+
+use super::exchange_info::{json_response, query_param};
+use crate::websocket::block_trade::BlockTradeStream;
+
+/// 大宗交易报告接口路径
+pub const BLOCK_TRADE_PATH: &str = "/api/spot/blockTrade";
+/// 大宗交易确认接口路径
+pub const BLOCK_TRADE_AFFIRM_PATH: &str = "/api/spot/blockTrade/affirm";
+
+/// 大宗交易成交ID起点：撮合引擎的成交ID从 1 递增，远小于此值，两者不会重叠
+pub const BLOCK_TRADE_ID_BASE: u64 = 1 << 52;
+
+/// 报告请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportRequest {
+    symbol: String,
+    buyer: u64,
+    seller: u64,
+    price: Price,
+    quantity: Quantity,
+}
+
+/// 确认请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AffirmRequest {
+    report_id: u64,
+}
+
+/// 结算导出器（发送端由部署方选择）
+pub type BlockTradeSettlementExporter = SettlementExporter<Box<dyn SettlementSink + Send>>;
+
+/// 大宗交易与询价成交共用的结算导出器
+pub type SharedSettlementExporter = Arc<Mutex<BlockTradeSettlementExporter>>;
+
+/// 大宗交易处理器
+///
+/// - `POST /api/spot/blockTrade`：交易一方报告场外协商的成交，返回 `reportId`
+/// - `POST /api/spot/blockTrade/affirm`：另一方确认，校验通过后经清算入账并推送到 `<symbol>@blockTrade`
+/// - `GET /api/spot/blockTrade?reportId=`：交易双方查询待确认报告
+///
+/// 账户取自鉴权（JWT 或 API Key 签名），只能以自己的身份报告或确认。
+/// 配置流水账本后，入账的成交在确认时即写入双方流水；配置结算导出后同时导出结算分录
+pub struct BlockTradeHandler {
+    desk: Arc<RwLock<BlockTradeDesk>>,
+    stream: BlockTradeStream,
+    ledger: Option<Arc<RwLock<ActivityLedger>>>,
+    settlement_export: Option<SharedSettlementExporter>,
+    fee_config: ProductFeeConfig,
+    /// 手续费收入账户
+    fee_account: AccountId,
+    clock: Arc<dyn TimestampProvider>,
+    trade_ids: Arc<dyn SequenceGenerator>,
+}
+
+impl Default for BlockTradeHandler {
+    /// 不收手续费，部署时以 [`BlockTradeHandler::new`] 指定费率与手续费账户
+    fn default() -> Self {
+        Self::new(
+            Arc::new(RwLock::new(BlockTradeDesk::new())),
+            ProductFeeConfig::spot(0.0, 0.0),
+            AccountId(0),
+        )
+    }
+}
+
+impl BlockTradeHandler {
+    pub fn new(
+        desk: Arc<RwLock<BlockTradeDesk>>,
+        fee_config: ProductFeeConfig,
+        fee_account: AccountId,
+    ) -> Self {
+        let clock: Arc<dyn TimestampProvider> = Arc::new(SystemClock);
+        Self {
+            desk,
+            stream: BlockTradeStream::default(),
+            ledger: None,
+            settlement_export: None,
+            fee_config,
+            fee_account,
+            trade_ids: Arc::new(Self::trade_id_sequence(clock.as_ref())),
+            clock,
+        }
+    }
+
+    /// 本进程的成交ID：[`BLOCK_TRADE_ID_BASE`] 之上按启动时间（秒 × 1000）起步，
+    /// 重启后不复用上次运行已分配的ID（每秒不超过 1000 笔时），且不超出 JSON 安全整数范围
+    pub fn trade_id_sequence(clock: &dyn TimestampProvider) -> AtomicSequence {
+        AtomicSequence::starting_at(BLOCK_TRADE_ID_BASE + clock.now_millis() / 1_000 * 1_000)
+    }
+
+    /// 入账成交写入流水账本（与账户流水接口共用）
+    pub fn with_ledger(mut self, ledger: Arc<RwLock<ActivityLedger>>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// 入账成交导出结算分录（导出失败不影响确认，计入导出器的发送失败数）
+    pub fn with_settlement_export(mut self, exporter: SharedSettlementExporter) -> Self {
+        self.settlement_export = Some(exporter);
+        self
+    }
+
+    /// 替换时间与成交ID来源
+    pub fn with_sources(
+        mut self,
+        clock: Arc<dyn TimestampProvider>,
+        trade_ids: Arc<dyn SequenceGenerator>,
+    ) -> Self {
+        self.clock = clock;
+        self.trade_ids = trade_ids;
+        self
+    }
+
+    /// 成交ID来源（询价成交共用，保证两者不重叠）
+    pub fn trade_ids(&self) -> Arc<dyn SequenceGenerator> {
+        self.trade_ids.clone()
+    }
+
+    /// 大宗交易台（配置规则、写入参考价；未配置流水账本时由记账管道取走入账成交）
+    pub fn desk(&self) -> Arc<RwLock<BlockTradeDesk>> {
+        self.desk.clone()
+    }
+
+    /// blockTrade 流
+    pub fn stream(&self) -> &BlockTradeStream {
+        &self.stream
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        match method {
+            "POST" => route == Some(BLOCK_TRADE_PATH) || route == Some(BLOCK_TRADE_AFFIRM_PATH),
+            "GET" => route == Some(BLOCK_TRADE_PATH),
+            _ => false,
+        }
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户）
+    pub fn respond(&self, method: &str, path: &str, body: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(method, path, body, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        account: Option<&str>,
+    ) -> (u16, String) {
+        let Some(user_id) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(account) = user_id.parse::<u64>().map(AccountId) else {
+            return Self::error(400, format!("Invalid account: {}", user_id));
+        };
+        let Ok(mut desk) = self.desk.write() else {
+            return Self::error(500, "Block trade desk unavailable".to_string());
+        };
+
+        if method == "GET" {
+            let Some(report_id) = query_param(path, "reportId").and_then(|id| id.parse().ok())
+            else {
+                return Self::error(400, "Missing parameter: reportId".to_string());
+            };
+            return match desk.pending_report(report_id, account) {
+                Some(report) => (200, Self::report_json(report).to_string()),
+                None => Self::error(404, format!("Unknown block trade report: {}", report_id)),
+            };
+        }
+
+        let now = self.clock.now();
+        if path.split('?').next() == Some(BLOCK_TRADE_AFFIRM_PATH) {
+            let request: AffirmRequest = match serde_json::from_slice(body) {
+                Ok(request) => request,
+                Err(e) => return Self::error(400, format!("Invalid request: {}", e)),
+            };
+            let context = ClearingContext {
+                fee_config: &self.fee_config,
+                fee_account: self.fee_account,
+                buyer_profile: FeeProfile::default(),
+                seller_profile: FeeProfile::default(),
+            };
+            let booked = match desk.affirm(
+                request.report_id,
+                account,
+                now,
+                self.trade_ids.as_ref(),
+                |trade| clear_spot_trade(trade, &context),
+            ) {
+                Ok(booked) => booked,
+                Err(e) => return Self::rejected(e),
+            };
+            self.stream.publish(&booked);
+            if let Some(exporter) = &self.settlement_export {
+                let mut exporter = exporter.lock().unwrap_or_else(PoisonError::into_inner);
+                // 清算已校验不变量，导出不会因校验失败
+                let _ = exporter.export(&booked.record.settlement);
+            }
+            let mut json = Self::report_json(&booked.report);
+            json["tradeId"] = booked.trade_id.into();
+            json["status"] = "BOOKED".into();
+            if let Some(ledger) = &self.ledger {
+                let mut ledger = ledger.write().unwrap_or_else(PoisonError::into_inner);
+                for booked in desk.drain_booked() {
+                    ledger.record(&booked.record.settlement);
+                }
+            }
+            return (200, json.to_string());
+        }
+
+        let request: ReportRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Self::error(400, format!("Invalid request: {}", e)),
+        };
+        let Some(pair) = TradingPair::from_symbol_str(&request.symbol) else {
+            return Self::error(400, format!("Invalid symbol: {}", request.symbol));
+        };
+        match desk.report(
+            account,
+            pair,
+            AccountId(request.buyer),
+            AccountId(request.seller),
+            request.price,
+            request.quantity,
+            now,
+        ) {
+            Ok(report_id) => (
+                200,
+                serde_json::json!({ "reportId": report_id, "status": "PENDING_AFFIRMATION" })
+                    .to_string(),
+            ),
+            Err(e) => Self::rejected(e),
+        }
+    }
+
+    fn report_json(report: &BlockTradeReport) -> serde_json::Value {
+        serde_json::json!({
+            "reportId": report.report_id,
+            "symbol": report.trading_pair.to_symbol_string(),
+            "buyer": report.buyer.0,
+            "seller": report.seller.0,
+            "price": report.price.to_string(),
+            "quantity": report.quantity.to_string(),
+            "reportedBy": report.reporter.0,
+            "reportTime": report.reported_at.0 / 1_000_000,
+        })
+    }
+
+    fn rejected(e: BlockTradeError) -> (u16, String) {
+        let status = match e {
+            BlockTradeError::NotCounterparty(_) => 403,
+            BlockTradeError::UnknownReport(_) => 404,
+            BlockTradeError::Clearing(_) => 500,
+            _ => 400,
+        };
+        Self::error(status, e.to_string())
+    }
+
+    fn error(status: u16, msg: String) -> (u16, String) {
+        (status, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+    use base_types::account::settlement_export::SettlementExportConfig;
+    use base_types::exchange::spot::block_trade::BlockTradeRules;
+
+    use super::*;
+
+    /// 记录导出消息的键
+    struct Captured(Arc<Mutex<Vec<String>>>);
+
+    impl SettlementSink for Captured {
+        fn send(&mut self, _topic: &str, key: &[u8], _payload: &[u8]) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(String::from_utf8_lossy(key).into_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_report_affirm_and_publish() {
+        let ledger = Arc::new(RwLock::new(ActivityLedger::new()));
+        let exported = Arc::new(Mutex::new(Vec::new()));
+        let sink: Box<dyn SettlementSink + Send> = Box::new(Captured(exported.clone()));
+        let handler = BlockTradeHandler::default()
+            .with_sources(
+                Arc::new(ManualClock::from_millis(5_000)),
+                Arc::new(AtomicSequence::starting_at(40)),
+            )
+            .with_ledger(ledger.clone())
+            .with_settlement_export(Arc::new(Mutex::new(SettlementExporter::new(
+                SettlementExportConfig::default(),
+                sink,
+            ))));
+        {
+            let desk = handler.desk();
+            let mut desk = desk.write().unwrap();
+            desk.set_rules(
+                TradingPair::BtcUsdt,
+                BlockTradeRules { min_quantity: Quantity::from_f64(10.0), price_band_bps: 50 },
+            );
+            desk.set_reference_price(TradingPair::BtcUsdt, Price::from_f64(100.0));
+        }
+        let mut receiver = handler.stream().subscribe();
+        assert!(BlockTradeHandler::matches("POST", BLOCK_TRADE_AFFIRM_PATH));
+        assert!(BlockTradeHandler::matches("GET", "/api/spot/blockTrade?reportId=1"));
+        assert!(!BlockTradeHandler::matches("GET", BLOCK_TRADE_AFFIRM_PATH));
+
+        let report =
+            br#"{"symbol":"BTCUSDT","buyer":1,"seller":2,"price":"100.2","quantity":"25"}"#;
+        assert_eq!(handler.render("POST", BLOCK_TRADE_PATH, report, None).0, 401);
+        assert_eq!(handler.render("POST", BLOCK_TRADE_PATH, report, Some("3")).0, 403);
+        let wide = br#"{"symbol":"BTCUSDT","buyer":1,"seller":2,"price":"101","quantity":"25"}"#;
+        assert_eq!(handler.render("POST", BLOCK_TRADE_PATH, wide, Some("2")).0, 400);
+
+        let (status, body) = handler.render("POST", BLOCK_TRADE_PATH, report, Some("2"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["reportId"], 1);
+
+        let (status, body) =
+            handler.render("GET", "/api/spot/blockTrade?reportId=1", b"", Some("1"));
+        assert_eq!(status, 200);
+        assert!(body.contains("\"reportedBy\":2"));
+        assert_eq!(handler.render("GET", "/api/spot/blockTrade?reportId=1", b"", Some("3")).0, 404);
+
+        let affirm = br#"{"reportId":1}"#;
+        assert_eq!(handler.render("POST", BLOCK_TRADE_AFFIRM_PATH, affirm, Some("2")).0, 403);
+        let (status, body) = handler.render("POST", BLOCK_TRADE_AFFIRM_PATH, affirm, Some("1"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!((json["tradeId"].as_u64(), json["status"].as_str()), (Some(40), Some("BOOKED")));
+
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "btcusdt@blockTrade");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["q"], "25");
+        assert!(json["data"].get("buyer").is_none());
+        // 入账成交已写入双方流水
+        assert!(handler.desk().write().unwrap().drain_booked().is_empty());
+        let ledger = ledger.read().unwrap();
+        assert!(ledger.len(AccountId(1)) > 0 && ledger.len(AccountId(2)) > 0);
+        // 结算按成交ID导出
+        assert_eq!(*exported.lock().unwrap(), ["40"]);
+    }
+
+    #[test]
+    fn test_trade_ids_do_not_overlap_engine_ids() {
+        let ids =
+            BlockTradeHandler::trade_id_sequence(&ManualClock::from_millis(1_700_000_000_123));
+        assert_eq!(ids.next_id(), BLOCK_TRADE_ID_BASE + 1_700_000_000_000);
+        assert!(BLOCK_TRADE_ID_BASE + 1_700_000_000_000 < 1 << 53);
+    }
+}
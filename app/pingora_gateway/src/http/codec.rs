@@ -0,0 +1,387 @@
+//! 网关 DTO 编解码
+//!
+//! 同一处理器按请求的 `Accept` 头选择响应格式：
+//! - JSON（默认，便于人工调试）
+//! - MessagePack（`application/msgpack`，节省带宽）：与 JSON 同一数据模型
+//! - SBE（`application/sbe`，低延迟）：8 字节消息头（块长度、模板ID、模式ID、版本，小端）
+//!   后接定长块，只有提供 SBE 模板的 DTO 支持，其他 DTO 以 406 拒绝
+//!
+//! 三种格式的语义一致：同一 DTO 编码后再解码得到相同的值
+
+use std::fmt;
+
+use base_types::Decimal;
+use base_types::mark_data::spot::ticker::{AvgPrice, BookTicker};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// SBE 模式ID
+pub const SBE_SCHEMA_ID: u16 = 1;
+/// SBE 模式版本
+pub const SBE_SCHEMA_VERSION: u16 = 0;
+/// SBE 消息头长度
+const SBE_HEADER_LEN: usize = 8;
+/// 可空小数字段的空值
+const SBE_NULL_DECIMAL: i64 = i64::MIN;
+
+/// 响应格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireFormat {
+    Json,
+    MessagePack,
+    Sbe,
+}
+
+impl WireFormat {
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            WireFormat::Json => "application/json",
+            WireFormat::MessagePack => "application/msgpack",
+            WireFormat::Sbe => "application/sbe",
+        }
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(WireFormat::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(WireFormat::MessagePack)
+            }
+            "application/sbe" | "application/x-sbe" => Some(WireFormat::Sbe),
+            _ => None,
+        }
+    }
+
+    /// 按 `Accept` 头协商格式：取 q 值最高的受支持类型，q 相同按出现顺序；
+    /// 未携带时为 JSON，没有受支持的类型时为 None（应答 406）
+    pub fn negotiate(accept: Option<&str>) -> Option<Self> {
+        let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+            return Some(WireFormat::Json);
+        };
+        let mut best: Option<(WireFormat, f32)> = None;
+        for range in accept.split(',') {
+            let mut params = range.split(';').map(str::trim);
+            let Some(format) = params.next().and_then(Self::from_media_type) else {
+                continue;
+            };
+            let q = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(1.0, |q| q.parse::<f32>().unwrap_or(0.0));
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((format, q));
+            }
+        }
+        best.map(|(format, _)| format)
+    }
+}
+
+/// 编解码错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// DTO 不支持该格式
+    Unsupported(WireFormat),
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Unsupported(format) => {
+                write!(f, "{} is not supported for this resource", format.content_type())
+            }
+            CodecError::Encode(e) => write!(f, "Encode failed: {}", e),
+            CodecError::Decode(e) => write!(f, "Decode failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// SBE 模板
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SbeTemplate {
+    pub template_id: u16,
+    pub block_length: u16,
+}
+
+/// 网关 DTO
+///
+/// JSON 与 MessagePack 经 serde 编解码；提供 [`SbeTemplate`] 的 DTO 同时支持 SBE
+pub trait GatewayDto: Serialize + DeserializeOwned {
+    /// SBE 模板（不支持 SBE 为 None）
+    fn sbe_template() -> Option<SbeTemplate> {
+        None
+    }
+
+    /// 写入 SBE 定长块（长度须等于模板的块长度）
+    fn encode_sbe_block(&self, _out: &mut Vec<u8>) {}
+
+    /// 读取 SBE 定长块
+    fn decode_sbe_block(_block: &[u8]) -> Option<Self> {
+        None
+    }
+}
+
+/// 按格式编码 DTO
+pub fn encode<T: GatewayDto>(format: WireFormat, dto: &T) -> Result<Vec<u8>, CodecError> {
+    match format {
+        WireFormat::Json => serde_json::to_vec(dto).map_err(|e| CodecError::Encode(e.to_string())),
+        WireFormat::MessagePack => {
+            rmp_serde::to_vec_named(dto).map_err(|e| CodecError::Encode(e.to_string()))
+        }
+        WireFormat::Sbe => {
+            let template = T::sbe_template().ok_or(CodecError::Unsupported(format))?;
+            let mut out = Vec::with_capacity(SBE_HEADER_LEN + template.block_length as usize);
+            for field in
+                [template.block_length, template.template_id, SBE_SCHEMA_ID, SBE_SCHEMA_VERSION]
+            {
+                out.extend_from_slice(&field.to_le_bytes());
+            }
+            dto.encode_sbe_block(&mut out);
+            if out.len() != SBE_HEADER_LEN + template.block_length as usize {
+                return Err(CodecError::Encode("SBE block length mismatch".to_string()));
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// 按格式解码 DTO
+pub fn decode<T: GatewayDto>(format: WireFormat, bytes: &[u8]) -> Result<T, CodecError> {
+    match format {
+        WireFormat::Json => {
+            serde_json::from_slice(bytes).map_err(|e| CodecError::Decode(e.to_string()))
+        }
+        WireFormat::MessagePack => {
+            rmp_serde::from_slice(bytes).map_err(|e| CodecError::Decode(e.to_string()))
+        }
+        WireFormat::Sbe => {
+            let template = T::sbe_template().ok_or(CodecError::Unsupported(format))?;
+            let header =
+                |i: usize| bytes.get(i * 2..i * 2 + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+            let (Some(block_length), Some(template_id), Some(schema_id)) =
+                (header(0), header(1), header(2))
+            else {
+                return Err(CodecError::Decode("truncated SBE header".to_string()));
+            };
+            if template_id != template.template_id || schema_id != SBE_SCHEMA_ID {
+                return Err(CodecError::Decode("unexpected SBE template".to_string()));
+            }
+            // 块长度取自消息头，兼容向后追加字段的新版本
+            bytes
+                .get(SBE_HEADER_LEN..SBE_HEADER_LEN + block_length as usize)
+                .filter(|block| block.len() >= template.block_length as usize)
+                .and_then(T::decode_sbe_block)
+                .ok_or_else(|| CodecError::Decode("truncated SBE block".to_string()))
+        }
+    }
+}
+
+/// 生成完整的 HTTP 响应
+pub(crate) fn encoded_response(status: u16, format: WireFormat, body: &[u8]) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        406 => "Not Acceptable",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        format.content_type(),
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// 请求头的值（名称不区分大小写）
+pub(crate) fn header_value<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().skip(1).take_while(|line| !line.is_empty()).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// 请求体（请求头之后的部分）
+pub(crate) fn request_body(request: &[u8]) -> &[u8] {
+    request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map_or(&[][..], |header_end| &request[header_end + 4..])
+}
+
+/// 替换请求体并同步 `Content-Length`
+pub(crate) fn replace_body(request: &[u8], body: &[u8]) -> Vec<u8> {
+    let head_len = request.windows(4).position(|window| window == b"\r\n\r\n");
+    let head = head_len.map_or(request, |header_end| &request[..header_end]);
+    let mut out = Vec::with_capacity(head.len() + body.len() + 32);
+    for line in head.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let name = line.split(|&b| b == b':').next().unwrap_or(&[]);
+        if name.trim_ascii().eq_ignore_ascii_case(b"content-length") {
+            continue;
+        }
+        out.extend_from_slice(line);
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+fn put_decimal(out: &mut Vec<u8>, value: Option<Decimal>) {
+    out.extend_from_slice(&value.map_or(SBE_NULL_DECIMAL, |v| v.raw()).to_le_bytes());
+}
+
+fn get_u32(block: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(block[offset..offset + 4].try_into().unwrap())
+}
+
+fn get_u64(block: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(block[offset..offset + 8].try_into().unwrap())
+}
+
+fn get_decimal(block: &[u8], offset: usize) -> Option<Decimal> {
+    let raw = i64::from_le_bytes(block[offset..offset + 8].try_into().unwrap());
+    (raw != SBE_NULL_DECIMAL).then(|| Decimal::from_raw(raw))
+}
+
+/// `symbolId u32 | updateId u64 | bidPrice i64 | bidQty i64 | askPrice i64 | askQty i64`
+impl GatewayDto for BookTicker {
+    fn sbe_template() -> Option<SbeTemplate> {
+        Some(SbeTemplate { template_id: 1, block_length: 44 })
+    }
+
+    fn encode_sbe_block(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.symbol_id.to_le_bytes());
+        out.extend_from_slice(&self.update_id.to_le_bytes());
+        put_decimal(out, self.bid_price);
+        put_decimal(out, Some(self.bid_qty));
+        put_decimal(out, self.ask_price);
+        put_decimal(out, Some(self.ask_qty));
+    }
+
+    fn decode_sbe_block(block: &[u8]) -> Option<Self> {
+        Some(Self {
+            symbol_id: get_u32(block, 0),
+            update_id: get_u64(block, 4),
+            bid_price: get_decimal(block, 12),
+            bid_qty: get_decimal(block, 20)?,
+            ask_price: get_decimal(block, 28),
+            ask_qty: get_decimal(block, 36)?,
+        })
+    }
+}
+
+/// `mins u32 | price i64 | closeTime u64`
+impl GatewayDto for AvgPrice {
+    fn sbe_template() -> Option<SbeTemplate> {
+        Some(SbeTemplate { template_id: 2, block_length: 20 })
+    }
+
+    fn encode_sbe_block(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.mins.to_le_bytes());
+        put_decimal(out, self.price);
+        out.extend_from_slice(&self.close_time.to_le_bytes());
+    }
+
+    fn decode_sbe_block(block: &[u8]) -> Option<Self> {
+        Some(Self {
+            mins: get_u32(block, 0),
+            price: get_decimal(block, 4),
+            close_time: get_u64(block, 12),
+        })
+    }
+}
+
+/// 列表 DTO（如全部交易对的最优挂单）只支持 JSON 与 MessagePack
+impl<T: GatewayDto> GatewayDto for Vec<T> {}
+
+/// 动态结构（附加字段的响应）只支持 JSON 与 MessagePack
+impl GatewayDto for serde_json::Value {}
+
+#[cfg(test)]
+mod tests {
+    use base_types::{Price, Quantity};
+
+    use super::*;
+
+    const FORMATS: [WireFormat; 3] = [WireFormat::Json, WireFormat::MessagePack, WireFormat::Sbe];
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(WireFormat::negotiate(None), Some(WireFormat::Json));
+        assert_eq!(WireFormat::negotiate(Some("*/*")), Some(WireFormat::Json));
+        assert_eq!(WireFormat::negotiate(Some("application/sbe")), Some(WireFormat::Sbe));
+        assert_eq!(
+            WireFormat::negotiate(Some("application/json;q=0.5, application/x-msgpack")),
+            Some(WireFormat::MessagePack)
+        );
+        assert_eq!(
+            WireFormat::negotiate(Some("application/sbe;q=0, application/json")),
+            Some(WireFormat::Json)
+        );
+        assert_eq!(WireFormat::negotiate(Some("text/html")), None);
+
+        let request = "GET /api/spot/bookTicker HTTP/1.1\r\naccept: application/sbe\r\n\r\n";
+        assert_eq!(header_value(request, "Accept"), Some("application/sbe"));
+        assert_eq!(header_value(request, "Host"), None);
+    }
+
+    #[test]
+    fn test_every_codec_round_trips_to_same_value() {
+        let tickers = [
+            BookTicker {
+                symbol_id: 1,
+                update_id: 42,
+                bid_price: Some(Price::from_f64(99.5)),
+                bid_qty: Quantity::from_f64(1.25),
+                ask_price: None,
+                ask_qty: Quantity::default(),
+            },
+            BookTicker {
+                symbol_id: 2,
+                update_id: 0,
+                bid_price: None,
+                bid_qty: Quantity::default(),
+                ask_price: Some(Price::from_f64(3000.0)),
+                ask_qty: Quantity::from_f64(0.5),
+            },
+        ];
+        for ticker in tickers {
+            for format in FORMATS {
+                let bytes = encode(format, &ticker).unwrap();
+                assert_eq!(decode::<BookTicker>(format, &bytes).unwrap(), ticker, "{:?}", format);
+            }
+        }
+        let avg = AvgPrice { mins: 5, price: Some(Price::from_f64(100.0)), close_time: 2_000 };
+        for format in FORMATS {
+            assert_eq!(decode::<AvgPrice>(format, &encode(format, &avg).unwrap()).unwrap(), avg);
+        }
+
+        // SBE 定长：消息头 8 字节 + 块 44 字节
+        assert_eq!(encode(WireFormat::Sbe, &tickers[0]).unwrap().len(), 52);
+        // 列表没有 SBE 模板
+        let list = tickers.to_vec();
+        assert_eq!(encode(WireFormat::Sbe, &list), Err(CodecError::Unsupported(WireFormat::Sbe)));
+        assert_eq!(
+            decode::<Vec<BookTicker>>(
+                WireFormat::MessagePack,
+                &encode(WireFormat::MessagePack, &list).unwrap()
+            )
+            .unwrap(),
+            list
+        );
+        // 模板不符的 SBE 消息拒绝解码
+        let sbe = encode(WireFormat::Sbe, &avg).unwrap();
+        assert!(decode::<BookTicker>(WireFormat::Sbe, &sbe).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! 大响应压缩与 ETag 协商缓存
+//!
+//! 交易对元数据、K线、资金费率与成交列表的响应体较大且变化不频繁，网关在本地应答后统一处理：
+//! - ETag：对未压缩的响应体取 SHA-256 前 16 字节，作为弱校验值（不同编码共享同一 ETag）
+//! - `If-None-Match` 命中时应答 304，不带响应体
+//! - 响应体达到阈值时按 `Accept-Encoding` 压缩，服务端偏好 zstd > gzip > deflate，
+//!   q 值高者优先；压缩后不比原文小时原样返回
+//!
+//! 只处理 200 的 GET 响应，错误响应与其他接口不变
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::{DeflateEncoder, GzEncoder};
+use sha2::{Digest, Sha256};
+
+use super::codec::header_value;
+use super::exchange_info::EXCHANGE_INFO_PATH;
+use super::prep_history::{FUNDING_RATE_PATH, MARK_PRICE_KLINES_PATH};
+use super::trades::{HISTORICAL_TRADES_PATH, TRADES_PATH};
+
+/// 压缩阈值（字节）
+pub const DEFAULT_MIN_SIZE: usize = 1024;
+/// 压缩阈值环境变量
+const ENV_MIN_SIZE: &str = "GATEWAY_COMPRESSION_MIN_SIZE";
+/// zstd 压缩级别
+const ZSTD_LEVEL: i32 = 3;
+
+/// 启用压缩与 ETag 的接口
+const BULKY_ROUTES: [&str; 5] = [
+    EXCHANGE_INFO_PATH,
+    MARK_PRICE_KLINES_PATH,
+    FUNDING_RATE_PATH,
+    TRADES_PATH,
+    HISTORICAL_TRADES_PATH,
+];
+
+/// 内容编码（按服务端偏好排序）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Zstd,
+    Gzip,
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// 服务端偏好顺序
+    pub const PREFERENCE: [ContentEncoding; 3] =
+        [ContentEncoding::Zstd, ContentEncoding::Gzip, ContentEncoding::Deflate];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ContentEncoding::Zstd => "zstd",
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Deflate => "deflate",
+        }
+    }
+
+    /// 按 `Accept-Encoding` 协商：取 q 值最高的编码，q 相同按服务端偏好；
+    /// `*` 匹配未单独列出的编码，q=0 表示拒绝。未携带或都不支持时为 None（不压缩）
+    pub fn negotiate(accept_encoding: Option<&str>) -> Option<Self> {
+        let accept_encoding = accept_encoding?;
+        let mut wildcard = None;
+        let mut listed: Vec<(&str, f32)> = Vec::new();
+        for coding in accept_encoding.split(',') {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+            let q = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(1.0, |q| q.parse::<f32>().unwrap_or(0.0));
+            if name == "*" {
+                wildcard = Some(q);
+            } else {
+                listed.push((name, q));
+            }
+        }
+        let mut best: Option<(ContentEncoding, f32)> = None;
+        for encoding in Self::PREFERENCE {
+            let q = listed
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(encoding.as_str()))
+                .map(|(_, q)| *q)
+                .or(wildcard)
+                .unwrap_or(0.0);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((encoding, q));
+            }
+        }
+        best.map(|(encoding, _)| encoding)
+    }
+
+    /// 压缩响应体
+    pub fn encode(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            ContentEncoding::Zstd => zstd::bulk::compress(body, ZSTD_LEVEL),
+            ContentEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            ContentEncoding::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// 响应体的弱 ETag
+pub fn entity_tag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("W/\"{}\"", hex)
+}
+
+/// `If-None-Match` 是否命中（弱比较，支持 `*` 与逗号分隔的列表）
+pub fn if_none_match(header: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    header.split(',').any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+/// 大响应压缩与 ETag 处理
+#[derive(Debug, Clone)]
+pub struct ResponseCompression {
+    /// 响应体不小于该长度才压缩
+    min_size: usize,
+}
+
+impl Default for ResponseCompression {
+    fn default() -> Self {
+        Self { min_size: DEFAULT_MIN_SIZE }
+    }
+}
+
+impl ResponseCompression {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 读取 `GATEWAY_COMPRESSION_MIN_SIZE`（未设置用默认阈值）
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(ENV_MIN_SIZE) {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .map(|min_size| Self::new().with_min_size(min_size))
+                .map_err(|_| format!("{} must be a byte count: {}", ENV_MIN_SIZE, value)),
+            Err(_) => Ok(Self::new()),
+        }
+    }
+
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    /// 请求是否启用压缩与 ETag（`path` 含查询串）
+    pub fn applies(method: &str, path: &str) -> bool {
+        let route = path.split('?').next().unwrap_or_default();
+        method == "GET" && BULKY_ROUTES.contains(&route)
+    }
+
+    /// 处理完整的 HTTP 响应：附加 ETag，命中 `If-None-Match` 时改为 304，否则按协商压缩
+    ///
+    /// `request` 为原始请求（读取 `If-None-Match` 与 `Accept-Encoding`）；非 200 或无法解析的响应原样返回
+    pub fn apply(&self, request: &[u8], response: Vec<u8>) -> Vec<u8> {
+        let Some(header_end) = response.windows(4).position(|window| window == b"\r\n\r\n") else {
+            return response;
+        };
+        let Ok(head) = std::str::from_utf8(&response[..header_end]) else {
+            return response;
+        };
+        let mut lines = head.split("\r\n");
+        let status_line = lines.next().unwrap_or_default();
+        if status_line.split(' ').nth(1) != Some("200") {
+            return response;
+        }
+        let body = &response[header_end + 4..];
+        let request = std::str::from_utf8(request).unwrap_or_default();
+        let etag = entity_tag(body);
+
+        if header_value(request, "If-None-Match").is_some_and(|header| if_none_match(header, &etag))
+        {
+            return format!(
+                "HTTP/1.1 304 Not Modified\r\nETag: {}\r\nVary: Accept-Encoding\r\nConnection: close\r\n\r\n",
+                etag
+            )
+            .into_bytes();
+        }
+
+        let encoded = ContentEncoding::negotiate(header_value(request, "Accept-Encoding"))
+            .filter(|_| body.len() >= self.min_size)
+            .and_then(|encoding| Some((encoding, encoding.encode(body).ok()?)))
+            .filter(|(_, encoded)| encoded.len() < body.len());
+
+        let mut out = format!("{}\r\n", status_line);
+        for line in lines {
+            let name = line.split(':').next().unwrap_or_default().trim();
+            if !name.eq_ignore_ascii_case("Content-Length") {
+                out.push_str(line);
+                out.push_str("\r\n");
+            }
+        }
+        out.push_str(&format!("ETag: {}\r\nVary: Accept-Encoding\r\n", etag));
+        let body = match &encoded {
+            Some((encoding, encoded)) => {
+                out.push_str(&format!("Content-Encoding: {}\r\n", encoding.as_str()));
+                encoded.as_slice()
+            }
+            None => body,
+        };
+        out.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+        let mut out = out.into_bytes();
+        out.extend_from_slice(body);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::{DeflateDecoder, GzDecoder};
+
+    use super::*;
+    use crate::http::exchange_info::json_response;
+
+    fn split(response: &[u8]) -> (String, Vec<u8>) {
+        let header_end = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8(response[..header_end].to_vec()).unwrap();
+        (head, response[header_end + 4..].to_vec())
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ContentEncoding::negotiate(None), None);
+        assert_eq!(ContentEncoding::negotiate(Some("identity")), None);
+        assert_eq!(
+            ContentEncoding::negotiate(Some("gzip, deflate, br, zstd")),
+            Some(ContentEncoding::Zstd)
+        );
+        assert_eq!(
+            ContentEncoding::negotiate(Some("deflate;q=1.0, gzip;q=0.5")),
+            Some(ContentEncoding::Deflate)
+        );
+        assert_eq!(ContentEncoding::negotiate(Some("*, zstd;q=0")), Some(ContentEncoding::Gzip));
+        assert_eq!(ContentEncoding::negotiate(Some("gzip;q=0")), None);
+
+        assert!(ResponseCompression::applies("GET", "/api/exchangeInfo?symbol=BTCUSDT"));
+        assert!(ResponseCompression::applies("GET", "/api/prep/markPriceKlines?symbol=BTCUSDT"));
+        assert!(!ResponseCompression::applies("POST", "/api/exchangeInfo"));
+        assert!(!ResponseCompression::applies("GET", "/api/time"));
+    }
+
+    #[test]
+    fn test_compress_and_revalidate() {
+        let body =
+            format!("[{}]", vec!["{\"symbol\":\"BTCUSDT\",\"status\":\"TRADING\"}"; 100].join(","));
+        let response = json_response(200, &body);
+        let compression = ResponseCompression::new();
+
+        for (accept, encoding) in [("gzip", "gzip"), ("deflate", "deflate"), ("zstd, gzip", "zstd")]
+        {
+            let request =
+                format!("GET /api/exchangeInfo HTTP/1.1\r\nAccept-Encoding: {}\r\n\r\n", accept);
+            let (head, encoded) = split(&compression.apply(request.as_bytes(), response.clone()));
+            assert!(head.contains(&format!("Content-Encoding: {}", encoding)));
+            assert!(head.contains(&format!("Content-Length: {}", encoded.len())));
+            assert!(head.contains("Vary: Accept-Encoding"));
+            assert!(encoded.len() < body.len());
+            let mut decoded = String::new();
+            match encoding {
+                "gzip" => GzDecoder::new(&encoded[..]).read_to_string(&mut decoded).unwrap(),
+                "deflate" => {
+                    DeflateDecoder::new(&encoded[..]).read_to_string(&mut decoded).unwrap()
+                }
+                _ => {
+                    decoded = String::from_utf8(zstd::decode_all(&encoded[..]).unwrap()).unwrap();
+                    decoded.len()
+                }
+            };
+            assert_eq!(decoded, body);
+        }
+
+        // 未协商编码或低于阈值时不压缩，但仍带 ETag
+        let plain = b"GET /api/exchangeInfo HTTP/1.1\r\n\r\n";
+        let (head, unchanged) = split(&compression.apply(plain, response.clone()));
+        assert!(!head.contains("Content-Encoding"));
+        assert_eq!(unchanged, body.as_bytes());
+        let etag = entity_tag(body.as_bytes());
+        assert!(head.contains(&format!("ETag: {}", etag)));
+        let small = ResponseCompression::new().with_min_size(body.len() + 1);
+        let gzip = b"GET /api/exchangeInfo HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n";
+        assert!(!split(&small.apply(gzip, response.clone())).0.contains("Content-Encoding"));
+
+        // If-None-Match 命中应答 304，不命中返回完整响应
+        let revalidate = format!(
+            "GET /api/exchangeInfo HTTP/1.1\r\nIf-None-Match: \"stale\", {}\r\n\r\n",
+            etag.trim_start_matches("W/")
+        );
+        let (head, empty) = split(&compression.apply(revalidate.as_bytes(), response.clone()));
+        assert!(head.starts_with("HTTP/1.1 304 Not Modified"));
+        assert!(empty.is_empty());
+        let stale = b"GET /api/exchangeInfo HTTP/1.1\r\nIf-None-Match: W/\"stale\"\r\n\r\n";
+        assert!(split(&compression.apply(stale, response)).0.starts_with("HTTP/1.1 200 OK"));
+
+        // 错误响应原样返回
+        let error = json_response(400, "{\"msg\":\"bad\"}");
+        assert_eq!(compression.apply(gzip, error.clone()), error);
+    }
+}
@@ -0,0 +1,160 @@
+//! 现货持仓成本与盈亏接口
+//!
+//! `GET /api/asset/costBasis?asset=` 返回鉴权账户（JWT 或 API Key 签名）各资产的持有数量、
+//! 持仓成本、平均取得价格与累计已实现盈亏，数据取自余额推送写入的快照（与小额资产兑换
+//! 共用，见 [`DustHandler::balances`](super::dust::DustHandler::balances)）；未启用成本追踪的
+//! 余额不返回。成本与盈亏以 USDT 计，未实现盈亏按合成行情的指数价计算，无指数价时为 null
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::account::balance::BalanceCostBasis;
+use base_types::mark_data::spot::synthetic::SyntheticTickers;
+use base_types::{AccountId, AssetId, Quantity};
+
+use super::dust::BalanceBook;
+use super::exchange_info::{json_response, query_param};
+
+/// 持仓成本接口路径
+pub const COST_BASIS_PATH: &str = "/api/asset/costBasis";
+
+/// 成本与盈亏的计价资产
+const VALUATION_ASSET: AssetId = AssetId::Usdt;
+
+/// `GET /api/asset/costBasis` 处理器
+pub struct CostBasisHandler {
+    prices: Arc<RwLock<SyntheticTickers>>,
+    balances: Arc<RwLock<BalanceBook>>,
+}
+
+impl CostBasisHandler {
+    /// `balances` 与 [`DustHandler`](super::dust::DustHandler) 共用，`prices` 通常为
+    /// [`TickerHandler::synthetic_tickers`](super::market_ticker::TickerHandler::synthetic_tickers)
+    pub fn new(prices: Arc<RwLock<SyntheticTickers>>, balances: Arc<RwLock<BalanceBook>>) -> Self {
+        Self { prices, balances }
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(COST_BASIS_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，参数：`asset`（只返回该资产，可选）
+    fn render(&self, path: &str, account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(account_id) = account.parse::<u64>().map(AccountId) else {
+            return Self::bad_request(format!("Invalid account: {}", account));
+        };
+        let asset = match query_param(path, "asset") {
+            None => None,
+            Some(asset) => match AssetId::from_str(&asset.to_uppercase()) {
+                Some(asset) => Some(asset),
+                None => return Self::bad_request(format!("Invalid asset: {}", asset)),
+            },
+        };
+
+        let balances = self.balances.read().unwrap_or_else(PoisonError::into_inner);
+        let prices = self.prices.read().unwrap_or_else(PoisonError::into_inner);
+        let mut views: Vec<(BalanceCostBasis, Option<Quantity>)> = balances
+            .values()
+            .filter(|balance| balance.account_id == account_id)
+            .filter(|balance| asset.is_none_or(|asset| balance.asset_id == asset))
+            .filter_map(|balance| {
+                let view = balance.cost_basis_view()?;
+                let unrealized = prices
+                    .index_price(balance.asset_id, VALUATION_ASSET)
+                    .and_then(|mark| balance.unrealized_pnl(mark));
+                Some((view, unrealized))
+            })
+            .collect();
+        views.sort_by_key(|(view, _)| view.asset_id.as_u32());
+        let assets: Vec<serde_json::Value> =
+            views.iter().map(|(view, unrealized)| Self::view_json(view, *unrealized)).collect();
+        (
+            200,
+            serde_json::json!({
+                "accountId": account_id.0,
+                "valuationAsset": VALUATION_ASSET.as_str(),
+                "assets": assets,
+            })
+            .to_string(),
+        )
+    }
+
+    fn view_json(view: &BalanceCostBasis, unrealized: Option<Quantity>) -> serde_json::Value {
+        serde_json::json!({
+            "asset": view.asset_id.as_str(),
+            "quantity": view.quantity.to_string(),
+            "costBasis": view.cost_basis.to_string(),
+            "avgCostPrice": view.avg_cost_price.map(|price| price.to_string()),
+            "realizedPnl": view.realized_pnl.to_string(),
+            "unrealizedPnl": unrealized.map(|pnl| pnl.to_string()),
+        })
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::account::balance::Balance;
+    use base_types::mark_data::spot::level_types::SymbolId;
+    use base_types::mark_data::spot::ticker::BookTicker;
+    use base_types::{Price, Timestamp, TradingPair};
+
+    use super::*;
+    use crate::http::dust::record_balance;
+
+    #[test]
+    fn test_cost_basis_and_pnl() {
+        let mut tickers = SyntheticTickers::new(AssetId::Usdt);
+        tickers.on_book_ticker(&BookTicker {
+            symbol_id: TradingPair::BtcUsdt as SymbolId,
+            update_id: 1,
+            bid_price: Some(Price::from_f64(45_990.0)),
+            bid_qty: Quantity::from_f64(1.0),
+            ask_price: Some(Price::from_f64(46_010.0)),
+            ask_qty: Quantity::from_f64(1.0),
+        });
+        let balances = Arc::new(RwLock::new(BalanceBook::new()));
+        let handler = CostBasisHandler::new(Arc::new(RwLock::new(tickers)), balances.clone());
+
+        // 均价 45000 买入 2 BTC，47000 卖出 0.5
+        let mut btc = Balance::new(AccountId(7), AssetId::Btc, Timestamp(0))
+            .with_cost_tracking(Quantity::default());
+        btc.record_acquisition(Quantity::from_f64(2.0), Price::from_f64(45_000.0));
+        btc.add_balance(Quantity::from_f64(2.0), Timestamp(1));
+        btc.record_disposal(Quantity::from_f64(0.5), Price::from_f64(47_000.0));
+        btc.add_balance(Quantity::from_f64(-0.5), Timestamp(2));
+        record_balance(&balances, &btc);
+        record_balance(&balances, &Balance::new(AccountId(7), AssetId::Eth, Timestamp(0)));
+
+        assert!(CostBasisHandler::matches("GET", "/api/asset/costBasis?asset=BTC"));
+        assert!(!CostBasisHandler::matches("POST", COST_BASIS_PATH));
+        let (status, body) = handler.render(COST_BASIS_PATH, Some("7"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        // 未启用追踪的 ETH 不返回
+        assert_eq!(json["assets"].as_array().unwrap().len(), 1);
+        let view = &json["assets"][0];
+        assert_eq!(view["asset"], "BTC");
+        assert_eq!(view["avgCostPrice"], Price::from_f64(45_000.0).to_string());
+        assert_eq!(view["realizedPnl"], Quantity::from_f64(1_000.0).to_string());
+        assert_eq!(view["unrealizedPnl"], Quantity::from_f64(1_500.0).to_string());
+
+        let (_, body) = handler.render("/api/asset/costBasis?asset=eth", Some("7"));
+        assert!(body.contains("\"assets\":[]"));
+        assert_eq!(handler.render(COST_BASIS_PATH, Some("8")).0, 200);
+        assert_eq!(handler.render(COST_BASIS_PATH, None).0, 401);
+        assert_eq!(handler.render("/api/asset/costBasis?asset=XYZ", Some("7")).0, 400);
+    }
+}
@@ -0,0 +1,308 @@
+//! 交易对降级信号
+//!
+//! 分片或依赖不健康时，健康检查（或运维演练）把受影响的交易对标记为降级，网关据此：
+//! - 在 exchangeInfo 中为这些产品附带 `degradation`（功能受限代码、原因、来源）
+//! - 经 `!systemStatus` 推送流广播（见 [`crate::websocket::system_status`]）
+//! - 在转发前拒绝受限的新委托（503），响应携带降级代码与原因，而不是后端的通用错误
+//!
+//! 公开接口：
+//! - `GET /api/systemStatus`：整体状态与全部降级的交易对
+//!
+//! 管理接口（`X-Admin-Token` 鉴权，未配置令牌时关闭），用于接入健康检查与模拟局部故障：
+//! - `POST /api/admin/degradation`：`{symbols, source, codes, reason}` 标记降级
+//! - `POST /api/admin/degradation/clear`：`{source, symbols}` 撤销来源的标记（不带 `symbols` 时撤销全部）
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::instrument::degradation::{
+    DegradationCode, DegradationRegistry, DegradationRejection,
+};
+use base_types::{SystemClock, TimestampProvider};
+use serde::Deserialize;
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::codec::{header_value, request_body};
+use super::exchange_info::{json_response, query_param};
+use crate::websocket::system_status::SystemStatusStream;
+
+/// 系统状态接口路径
+pub const SYSTEM_STATUS_PATH: &str = "/api/systemStatus";
+/// 管理接口路径
+pub const ADMIN_DEGRADATION_PATH: &str = "/api/admin/degradation";
+pub const ADMIN_CLEAR_DEGRADATION_PATH: &str = "/api/admin/degradation/clear";
+/// 下单接口路径（撤单、查询不受降级限制）
+pub const NEW_ORDER_PATH: &str = "/api/spot/v2/order";
+
+/// 共享的降级登记表
+pub type SharedDegradations = Arc<RwLock<DegradationRegistry>>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkRequest {
+    symbols: Vec<String>,
+    source: String,
+    codes: Vec<String>,
+    #[serde(default)]
+    reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClearRequest {
+    source: String,
+    symbols: Option<Vec<String>>,
+}
+
+/// 降级信号处理器
+pub struct DegradationHandler {
+    registry: SharedDegradations,
+    stream: Arc<SystemStatusStream>,
+    admin_token: Option<String>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for DegradationHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(DegradationRegistry::new())), Arc::new(SystemClock))
+    }
+}
+
+impl DegradationHandler {
+    pub fn new(registry: SharedDegradations, clock: Arc<dyn TimestampProvider>) -> Self {
+        Self { registry, stream: Arc::new(SystemStatusStream::default()), admin_token: None, clock }
+    }
+
+    /// 配置管理令牌时开放管理接口
+    pub fn from_env() -> Self {
+        let handler = Self::default();
+        match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        }
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// 与 exchangeInfo、`!systemStatus` 推送及健康检查共享的登记表
+    pub fn registry(&self) -> &SharedDegradations {
+        &self.registry
+    }
+
+    /// `!systemStatus` 推送流（健康检查直接修改登记表后调用 `publish` 广播）
+    pub fn stream(&self) -> &Arc<SystemStatusStream> {
+        &self.stream
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        match method {
+            "GET" => path == SYSTEM_STATUS_PATH,
+            "POST" => path == ADMIN_DEGRADATION_PATH || path == ADMIN_CLEAR_DEGRADATION_PATH,
+            _ => false,
+        }
+    }
+
+    /// 转发前检查新委托：受限时返回携带降级原因的 503 响应
+    ///
+    /// 交易对与委托类型取自查询串或 JSON 请求体的 `symbol`、`type`
+    pub fn check_order(&self, method: &str, path: &str, request: &[u8]) -> Result<(), Vec<u8>> {
+        if method != "POST" || path.split('?').next() != Some(NEW_ORDER_PATH) {
+            return Ok(());
+        }
+        let registry = self.registry.read().unwrap_or_else(PoisonError::into_inner);
+        if registry.is_empty() {
+            return Ok(());
+        }
+        let body: serde_json::Value =
+            serde_json::from_slice(request_body(request)).unwrap_or_default();
+        let field = |name: &str| {
+            query_param(path, name)
+                .map(str::to_string)
+                .or_else(|| body[name].as_str().map(str::to_string))
+        };
+        let Some(symbol) = field("symbol") else {
+            return Ok(());
+        };
+        let is_market = field("type").is_some_and(|kind| kind.eq_ignore_ascii_case("MARKET"));
+        registry
+            .check_order(&symbol.to_uppercase(), is_market)
+            .map_err(|rejection| json_response(503, &rejection_json(&rejection).to_string()))
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str, request: &[u8]) -> Vec<u8> {
+        let (status, body) = self.render(path, request);
+        json_response(status, &body)
+    }
+
+    fn render(&self, path: &str, request: &[u8]) -> (u16, String) {
+        let route = path.split('?').next().unwrap_or(path);
+        let result = if route == SYSTEM_STATUS_PATH {
+            Ok(system_status_json(&self.registry.read().unwrap_or_else(PoisonError::into_inner)))
+        } else {
+            self.admin(route, request)
+        };
+        match result {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn admin(&self, route: &str, request: &[u8]) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        let body = request_body(request);
+        if route == ADMIN_DEGRADATION_PATH {
+            let req: MarkRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let codes = req
+                .codes
+                .iter()
+                .map(|code| {
+                    DegradationCode::parse(code)
+                        .ok_or_else(|| (400, format!("Unknown degradation code: {}", code)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if req.source.is_empty() || req.symbols.is_empty() || codes.is_empty() {
+                return Err((400, "symbols, source and codes are required".to_string()));
+            }
+            let now_ms = self.clock.now_millis();
+            let mut registry = self.registry.write().unwrap_or_else(PoisonError::into_inner);
+            for symbol in &req.symbols {
+                registry.mark(
+                    &symbol.to_uppercase(),
+                    &req.source,
+                    codes.iter().copied(),
+                    &req.reason,
+                    now_ms,
+                );
+            }
+            self.stream.publish(&registry);
+            Ok(system_status_json(&registry))
+        } else {
+            let req: ClearRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let mut registry = self.registry.write().unwrap_or_else(PoisonError::into_inner);
+            match req.symbols {
+                Some(symbols) => {
+                    for symbol in &symbols {
+                        registry.clear(&symbol.to_uppercase(), &req.source);
+                    }
+                }
+                None => {
+                    registry.clear_source(&req.source);
+                }
+            }
+            self.stream.publish(&registry);
+            Ok(system_status_json(&registry))
+        }
+    }
+}
+
+/// 整体状态：`{"status": "NORMAL" | "DEGRADED", "symbols": [...]}`
+pub fn system_status_json(registry: &DegradationRegistry) -> serde_json::Value {
+    let symbols: Vec<serde_json::Value> = registry
+        .degraded()
+        .into_iter()
+        .map(|(symbol, degradation)| {
+            let mut json = serde_json::json!(degradation);
+            json["symbol"] = symbol.into();
+            json
+        })
+        .collect();
+    serde_json::json!({
+        "status": if symbols.is_empty() { "NORMAL" } else { "DEGRADED" },
+        "symbols": symbols,
+    })
+}
+
+fn rejection_json(rejection: &DegradationRejection) -> serde_json::Value {
+    serde_json::json!({
+        "code": "SYMBOL_DEGRADED",
+        "degradation": rejection.code,
+        "symbol": rejection.symbol,
+        "msg": rejection.to_string(),
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin_request(path: &str, body: &str) -> Vec<u8> {
+        format!("POST {} HTTP/1.1\r\nX-Admin-Token: secret\r\n\r\n{}", path, body).into_bytes()
+    }
+
+    fn order_request(body: &str) -> Vec<u8> {
+        format!("POST /api/spot/v2/order HTTP/1.1\r\n\r\n{}", body).into_bytes()
+    }
+
+    #[test]
+    fn test_simulated_outage_rejects_orders() {
+        let handler = DegradationHandler::default().with_admin_token("secret");
+        let mut status_stream = handler.stream().subscribe();
+        let limit = order_request(r#"{"symbol":"BTCUSDT","type":"LIMIT","price":"100"}"#);
+        let market = order_request(r#"{"symbol":"BTCUSDT","type":"MARKET"}"#);
+        assert!(handler.check_order("POST", NEW_ORDER_PATH, &market).is_ok());
+
+        let mark = r#"{"symbols":["btcusdt"],"source":"shard-2","codes":["LIMIT_ONLY"],"reason":"shard 2 failover"}"#;
+        let (status, body) =
+            handler.render(ADMIN_DEGRADATION_PATH, &admin_request(ADMIN_DEGRADATION_PATH, mark));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["status"], "DEGRADED");
+        assert_eq!(json["symbols"][0]["symbol"], "BTCUSDT");
+        assert_eq!(json["symbols"][0]["codes"][0], "LIMIT_ONLY");
+        assert!(status_stream.try_recv().unwrap().payload.contains("shard 2 failover"));
+
+        assert!(handler.check_order("POST", NEW_ORDER_PATH, &limit).is_ok());
+        let rejected =
+            String::from_utf8(handler.check_order("POST", NEW_ORDER_PATH, &market).unwrap_err())
+                .unwrap();
+        assert!(rejected.starts_with("HTTP/1.1 503 Service Unavailable"));
+        let json: serde_json::Value =
+            serde_json::from_str(rejected.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(json["code"], "SYMBOL_DEGRADED");
+        assert_eq!(json["degradation"], "LIMIT_ONLY");
+        assert_eq!(json["msg"], "BTCUSDT is degraded (LIMIT_ONLY): shard 2 failover");
+        // 查询串中的交易对同样检查
+        assert!(
+            handler
+                .check_order("POST", "/api/spot/v2/order?symbol=BTCUSDT&type=MARKET", b"")
+                .is_err()
+        );
+
+        let clear = admin_request(ADMIN_CLEAR_DEGRADATION_PATH, r#"{"source":"shard-2"}"#);
+        assert_eq!(handler.render(ADMIN_CLEAR_DEGRADATION_PATH, &clear).0, 200);
+        assert!(handler.check_order("POST", NEW_ORDER_PATH, &market).is_ok());
+        let (_, body) =
+            handler.render(SYSTEM_STATUS_PATH, b"GET /api/systemStatus HTTP/1.1\r\n\r\n");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["status"], "NORMAL");
+
+        let bad_code = r#"{"symbols":["BTCUSDT"],"source":"x","codes":["BOGUS"]}"#;
+        assert_eq!(
+            handler
+                .render(ADMIN_DEGRADATION_PATH, &admin_request(ADMIN_DEGRADATION_PATH, bad_code))
+                .0,
+            400
+        );
+        let anonymous = format!("POST {} HTTP/1.1\r\n\r\n{}", ADMIN_DEGRADATION_PATH, mark);
+        assert_eq!(handler.render(ADMIN_DEGRADATION_PATH, anonymous.as_bytes()).0, 401);
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::account::delegation::{AccessRegistry, DelegationError, DelegationScope};
+use base_types::{AccountId, SystemClock, TimestampProvider, UserId};
+
+use super::codec::header_value;
+use super::exchange_info::json_response;
+
+/// 代账户操作的请求头，值为目标账户ID
+pub const ON_BEHALF_HEADER: &str = "X-On-Behalf-Of";
+/// 转发给后端的实际操作用户请求头，由网关写入
+pub const EFFECTIVE_USER_HEADER: &str = "X-Effective-User";
+
+/// 联名账户与代操作鉴权
+///
+/// - 请求带 `X-On-Behalf-Of: <accountId>` 时，先要求请求已鉴权（JWT 或 API Key 签名，
+///   请求头中的用户ID不算，否则 401），再校验鉴权用户是该账户的所有人、联名持有人或持有有效授权：
+///   `GET` 需要读权限，其余方法需要交易权限；通过后按目标账户路由，并以 `X-Effective-User`
+///   告知后端实际操作用户，后端据此写入订单的 `effective_user`
+/// - 客户端自带的 `X-Effective-User` 一律剔除，后端看到的该请求头只可能来自网关
+pub struct DelegationGate {
+    registry: Arc<RwLock<AccessRegistry>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for DelegationGate {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(AccessRegistry::new())), Arc::new(SystemClock))
+    }
+}
+
+impl DelegationGate {
+    pub fn new(registry: Arc<RwLock<AccessRegistry>>, clock: Arc<dyn TimestampProvider>) -> Self {
+        Self { registry, clock }
+    }
+
+    /// 账户与授权登记表（账户服务同步所有人、联名持有人与授权）
+    pub fn registry(&self) -> &Arc<RwLock<AccessRegistry>> {
+        &self.registry
+    }
+
+    /// 鉴权并改写请求
+    ///
+    /// `authenticated` 为鉴权得到的用户。返回 (代操作时为目标账户、否则原样返回的鉴权用户,
+    /// 转发的请求)；拒绝时返回错误响应
+    pub fn admit(
+        &self,
+        method: &str,
+        request: Vec<u8>,
+        authenticated: Option<String>,
+    ) -> Result<(Option<String>, Vec<u8>), Vec<u8>> {
+        let head = std::str::from_utf8(header_block(&request)).unwrap_or("");
+        let on_behalf = header_value(head, ON_BEHALF_HEADER).map(str::to_string);
+        let spoofed = header_value(head, EFFECTIVE_USER_HEADER).is_some();
+
+        let Some(on_behalf) = on_behalf else {
+            let request = if spoofed { rewrite_headers(&request, None) } else { request };
+            return Ok((authenticated, request));
+        };
+        let Some(user) = authenticated.as_deref().and_then(|id| id.parse::<u64>().ok()) else {
+            return Err(json_response(401, r#"{"msg":"Authentication required"}"#));
+        };
+        let Ok(account) = on_behalf.parse::<u64>() else {
+            return Err(json_response(400, r#"{"msg":"Invalid X-On-Behalf-Of"}"#));
+        };
+        let required = if method == "GET" { DelegationScope::Read } else { DelegationScope::Trade };
+
+        let authorized = self.registry.read().unwrap_or_else(PoisonError::into_inner).authorize(
+            UserId(user),
+            AccountId(account),
+            required,
+            self.clock.now(),
+        );
+        match authorized {
+            Ok(context) => {
+                let request = rewrite_headers(&request, Some(context.effective_user.0));
+                Ok((Some(context.account_id.0.to_string()), request))
+            }
+            Err(e) => Err(error_response(e)),
+        }
+    }
+}
+
+fn error_response(error: DelegationError) -> Vec<u8> {
+    let status = match error {
+        DelegationError::UnknownAccount(_) => 404,
+        _ => 403,
+    };
+    let body = serde_json::json!({ "msg": error.to_string() });
+    json_response(status, &body.to_string())
+}
+
+/// 请求头部分（不含结尾空行）
+fn header_block(request: &[u8]) -> &[u8] {
+    request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map_or(request, |header_end| &request[..header_end])
+}
+
+/// 剔除请求中的 `X-Effective-User`，`effective_user` 非空时写入网关鉴权后的值
+fn rewrite_headers(request: &[u8], effective_user: Option<u64>) -> Vec<u8> {
+    let head = header_block(request);
+    let body = &request[head.len()..];
+    let mut out = Vec::with_capacity(request.len() + 32);
+    for (i, line) in head.split(|&b| b == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let name = line.split(|&b| b == b':').next().unwrap_or(&[]);
+        if i > 0 && name.trim_ascii().eq_ignore_ascii_case(EFFECTIVE_USER_HEADER.as_bytes()) {
+            continue;
+        }
+        if i > 0 {
+            out.extend_from_slice(b"\r\n");
+        }
+        out.extend_from_slice(line);
+    }
+    if let Some(user) = effective_user {
+        out.extend_from_slice(format!("\r\n{}: {}", EFFECTIVE_USER_HEADER, user).as_bytes());
+    }
+    out.extend_from_slice(body);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::Timestamp;
+    use base_types::account::account::{Account, AccountType};
+    use base_types::account::delegation::AccessKind;
+
+    use super::*;
+
+    struct FixedClock;
+
+    impl TimestampProvider for FixedClock {
+        fn now(&self) -> Timestamp {
+            Timestamp(1_000)
+        }
+    }
+
+    fn gate() -> DelegationGate {
+        let mut registry = AccessRegistry::new();
+        registry.register_account(&Account::new(
+            AccountId(10),
+            UserId(1),
+            AccountType::Spot,
+            Timestamp(0),
+        ));
+        registry.add_joint_holder(AccountId(10), UserId(2)).unwrap();
+        registry
+            .grant(AccountId(10), UserId(1), UserId(3), DelegationScope::Read, None, Timestamp(0))
+            .unwrap();
+        DelegationGate::new(Arc::new(RwLock::new(registry)), Arc::new(FixedClock))
+    }
+
+    fn request(extra: &str) -> Vec<u8> {
+        format!("POST /api/spot/v2/order HTTP/1.1\r\nHost: x\r\n{}\r\n{{}}", extra).into_bytes()
+    }
+
+    #[test]
+    fn test_admit_on_behalf() {
+        let gate = gate();
+        let (user, forwarded) = gate
+            .admit(
+                "POST",
+                request("X-On-Behalf-Of: 10\r\nX-Effective-User: 99\r\n"),
+                Some("2".to_string()),
+            )
+            .unwrap();
+        assert_eq!(user.as_deref(), Some("10"));
+        let forwarded = String::from_utf8(forwarded).unwrap();
+        assert!(forwarded.contains("X-Effective-User: 2\r\n\r\n{}"));
+        assert!(!forwarded.contains("99"));
+
+        // 未鉴权的请求不能代账户操作
+        let denied = gate.admit("GET", request("X-On-Behalf-Of: 10\r\n"), None);
+        assert!(String::from_utf8(denied.unwrap_err()).unwrap().starts_with("HTTP/1.1 401"));
+
+        // 只读授权不能下单，但可以查询
+        let denied = gate.admit("POST", request("X-On-Behalf-Of: 10\r\n"), Some("3".to_string()));
+        assert!(String::from_utf8(denied.unwrap_err()).unwrap().starts_with("HTTP/1.1 403"));
+        assert!(
+            gate.admit("GET", request("X-On-Behalf-Of: 10\r\n"), Some("3".to_string())).is_ok()
+        );
+        assert!(
+            gate.admit("GET", request("X-On-Behalf-Of: 10\r\n"), Some("4".to_string())).is_err()
+        );
+        let context = gate
+            .registry()
+            .read()
+            .unwrap()
+            .authorize(UserId(3), AccountId(10), DelegationScope::Read, Timestamp(1_000))
+            .unwrap();
+        assert!(matches!(context.access, AccessKind::Delegated(_)));
+    }
+
+    #[test]
+    fn test_admit_strips_spoofed_effective_user() {
+        let (user, forwarded) = gate()
+            .admit("POST", request("x-effective-user: 99\r\n"), Some("5".to_string()))
+            .unwrap();
+        assert_eq!(user.as_deref(), Some("5"));
+        assert_eq!(
+            String::from_utf8(forwarded).unwrap(),
+            "POST /api/spot/v2/order HTTP/1.1\r\nHost: x\r\n\r\n{}"
+        );
+    }
+}
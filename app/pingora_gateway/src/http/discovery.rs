@@ -0,0 +1,202 @@
+//! 集群成员发现驱动的用户路由
+//!
+//! 网关以 `gateway` 角色加入集群成员服务，订阅引擎分片的成员变化，把各分片的 HTTP
+//! 端点映射为用户路由分区（分片 ID 即分区号，从 1 开始），经
+//! [`UserRouter::update_config`] 热更新。没有发现任何分片时回退到静态配置。
+//!
+//! 通过环境变量启用：
+//! - `GATEWAY_CLUSTER_BOOTSTRAP`: 逗号分隔的引导节点 multiaddr
+//! - `GATEWAY_CLUSTER_LISTEN`: 集群监听地址（默认 `/ip4/0.0.0.0/tcp/0`）
+//! - `GATEWAY_ADVERTISE_HTTP`: 向集群公布的 HTTP 地址（默认 `127.0.0.1:8080`）
+//!
+//! 前两者都未设置时不启用发现，沿用静态路由配置。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use cluster_membership::{
+    HTTP_ENDPOINT, MemberRole, MembershipConfig, MembershipError, MembershipService,
+    MembershipView, Multiaddr, Registration,
+};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use super::router::{UserRouteConfig, UserRouter};
+
+const ENV_BOOTSTRAP: &str = "GATEWAY_CLUSTER_BOOTSTRAP";
+const ENV_LISTEN: &str = "GATEWAY_CLUSTER_LISTEN";
+const ENV_ADVERTISE_HTTP: &str = "GATEWAY_ADVERTISE_HTTP";
+
+/// 网关集群发现配置
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    pub membership: MembershipConfig,
+    /// 向集群公布的网关 HTTP 地址
+    pub advertise_http: String,
+}
+
+impl DiscoveryConfig {
+    /// 从环境变量读取，未配置集群时返回 `None`
+    pub fn from_env() -> Result<Option<Self>, MembershipError> {
+        let bootstrap = std::env::var(ENV_BOOTSTRAP).ok();
+        let listen = std::env::var(ENV_LISTEN).ok();
+        if bootstrap.is_none() && listen.is_none() {
+            return Ok(None);
+        }
+
+        let mut membership =
+            MembershipConfig { watch_roles: vec![MemberRole::EngineShard], ..Default::default() };
+        if let Some(listen) = listen {
+            membership.listen_addr = parse_addr(&listen)?;
+        }
+        for address in bootstrap.iter().flat_map(|list| list.split(',')) {
+            let address = address.trim();
+            if !address.is_empty() {
+                membership.bootstrap.push(parse_addr(address)?);
+            }
+        }
+        let advertise_http =
+            std::env::var(ENV_ADVERTISE_HTTP).unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+        Ok(Some(Self { membership, advertise_http }))
+    }
+}
+
+fn parse_addr(address: &str) -> Result<Multiaddr, MembershipError> {
+    address
+        .parse()
+        .map_err(|e| MembershipError::Config(format!("invalid multiaddr {}: {}", address, e)))
+}
+
+/// 由成员视图生成路由配置：分区 = 已发现的分片，分区数 = 最大分片 ID
+///
+/// 没有发现分片（或分片 ID 无效）时返回静态配置。
+pub fn discovered_route_config(base: &UserRouteConfig, view: &MembershipView) -> UserRouteConfig {
+    let partition_ips: HashMap<usize, Vec<String>> = view
+        .shard_endpoints(HTTP_ENDPOINT)
+        .into_iter()
+        .filter(|(shard_id, _)| *shard_id >= 1)
+        .map(|(shard_id, backends)| (shard_id as usize, backends))
+        .collect();
+    let Some(&num_partitions) = partition_ips.keys().max() else {
+        return base.clone();
+    };
+    UserRouteConfig { partition_ips, num_partitions, default_backend: base.default_backend.clone() }
+}
+
+/// 持续把成员变化同步到用户路由，直到成员服务退出
+pub async fn sync_routes(
+    router: Arc<UserRouter>,
+    base: UserRouteConfig,
+    mut changes: watch::Receiver<MembershipView>,
+) {
+    loop {
+        let config = discovered_route_config(&base, &changes.borrow_and_update());
+        info!("🔄 Updating user routes from cluster membership:");
+        let mut partitions: Vec<_> = config.partition_ips.iter().collect();
+        partitions.sort();
+        for (partition, ips) in partitions {
+            info!("   - {} → {:?}", partition, ips);
+        }
+        router.update_config(config).await;
+
+        if changes.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// 在独立线程（自带 tokio 运行时）中加入集群并同步路由
+pub fn spawn_discovery(
+    config: DiscoveryConfig,
+    router: Arc<UserRouter>,
+    base: UserRouteConfig,
+) -> std::io::Result<JoinHandle<()>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    std::thread::Builder::new().name("cluster-discovery".to_string()).spawn(move || {
+        runtime.block_on(async move {
+            let registration = Registration::new(MemberRole::Gateway)
+                .with_endpoint(HTTP_ENDPOINT, config.advertise_http);
+            let handle = match MembershipService::start(registration, config.membership).await {
+                Ok(handle) => handle,
+                Err(e) => {
+                    warn!("Cluster discovery failed to start: {}", e);
+                    return;
+                }
+            };
+            for address in handle.listen_addrs() {
+                info!("🌐 Cluster membership listening on {}", address);
+            }
+            sync_routes(router, base, handle.subscribe()).await;
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cluster_membership::Member;
+    use pingora_core::upstreams::peer::Peer;
+
+    use super::*;
+
+    fn base_config() -> UserRouteConfig {
+        let mut partition_ips = HashMap::new();
+        partition_ips.insert(1, vec!["127.0.0.1:3001".to_string()]);
+        UserRouteConfig {
+            partition_ips,
+            num_partitions: 4,
+            default_backend: "127.0.0.1:3999".to_string(),
+        }
+    }
+
+    fn shard(node_id: &str, shards: &[u32], http: &str) -> Member {
+        let registration = Registration::new(MemberRole::EngineShard)
+            .with_shards(shards.iter().copied())
+            .with_endpoint(HTTP_ENDPOINT, http);
+        let mut member = Member::new(node_id, registration, u64::MAX / 2);
+        member.heartbeat_ms = 1;
+        member
+    }
+
+    #[test]
+    fn test_discovered_route_config() {
+        let base = base_config();
+        // 未发现分片时沿用静态配置
+        let config = discovered_route_config(&base, &MembershipView::new());
+        assert_eq!(config.partition_ips, base.partition_ips);
+        assert_eq!(config.num_partitions, 4);
+
+        let mut view = MembershipView::new();
+        view.apply(shard("a", &[1, 2], "10.0.0.1:3001"), 1);
+        view.apply(shard("b", &[2], "10.0.0.2:3001"), 1);
+        view.apply(shard("c", &[0], "10.0.0.3:3001"), 1);
+        let config = discovered_route_config(&base, &view);
+        assert_eq!(config.num_partitions, 2);
+        assert_eq!(config.partition_ips[&1], vec!["10.0.0.1:3001"]);
+        assert_eq!(config.partition_ips[&2], vec!["10.0.0.1:3001", "10.0.0.2:3001"]);
+        assert!(!config.partition_ips.contains_key(&0));
+        assert_eq!(config.default_backend, "127.0.0.1:3999");
+    }
+
+    #[tokio::test]
+    async fn test_sync_routes_updates_router() {
+        let router = Arc::new(UserRouter::new(base_config()));
+        let (view_tx, view_rx) = watch::channel(MembershipView::new());
+        let task = tokio::spawn(sync_routes(router.clone(), base_config(), view_rx));
+
+        let mut view = MembershipView::new();
+        view.apply(shard("a", &[1], "10.0.0.9:3001"), 1);
+        view_tx.send_replace(view);
+        // 单分区时所有用户都命中新发现的分片
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !router.select_backend("alice").await.address().to_string().contains("10.0.0.9") {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("router never picked up discovered shard");
+
+        drop(view_tx);
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use base_types::account::balance::Balance;
+use base_types::account::dust::{DustConfig, DustPreview, DustSweeper};
+use base_types::exchange::spot::convert::{ConvertEngine, ConvertQuote, IndexPriceSource};
+use base_types::mark_data::spot::synthetic::SyntheticTickers;
+use base_types::{AccountId, AssetId, Quantity};
+
+use super::exchange_info::{json_response, query_param};
+
+/// 小额资产兑换预览接口路径
+pub const DUST_PREVIEW_PATH: &str = "/api/asset/dust/preview";
+
+/// 默认流动性账户
+pub const DEFAULT_LIQUIDITY_ACCOUNT: AccountId = AccountId(900_000);
+/// 默认小额阈值（以 USDT 计）
+pub const DEFAULT_DUST_THRESHOLD_USDT: f64 = 10.0;
+
+/// 账户余额快照，由余额变更推送写入
+pub type BalanceBook = HashMap<(AccountId, AssetId), Balance>;
+
+/// `GET /api/asset/dust/preview` 处理器
+///
+/// 按合成行情的指数价预览鉴权账户（JWT 或 API Key 签名）可兑换为 `target`（默认 USDT）的
+/// 小额资产，阈值以 USDT 配置，其他目标资产按指数价换算。实际兑换由撮合引擎按同一规则执行
+pub struct DustHandler {
+    sweeper: DustSweeper,
+    prices: Arc<RwLock<SyntheticTickers>>,
+    balances: Arc<RwLock<BalanceBook>>,
+}
+
+impl Default for DustHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(SyntheticTickers::new(AssetId::Usdt))))
+    }
+}
+
+impl DustHandler {
+    /// `prices` 通常为 [`TickerHandler::synthetic_tickers`](super::market_ticker::TickerHandler::synthetic_tickers)，
+    /// 由行情组播（[`MarketFeed`](super::market_feed::MarketFeed)）写入的最优挂单持续更新
+    pub fn new(prices: Arc<RwLock<SyntheticTickers>>) -> Self {
+        let config = DustConfig {
+            target: AssetId::Usdt,
+            threshold: Quantity::from_f64(DEFAULT_DUST_THRESHOLD_USDT),
+        };
+        Self {
+            sweeper: DustSweeper::new(ConvertEngine::new(DEFAULT_LIQUIDITY_ACCOUNT), config),
+            prices,
+            balances: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_sweeper(mut self, sweeper: DustSweeper) -> Self {
+        self.sweeper = sweeper;
+        self
+    }
+
+    /// 余额推送写入快照的入口
+    pub fn balances(&self) -> Arc<RwLock<BalanceBook>> {
+        self.balances.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(DUST_PREVIEW_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，参数：`target`（目标资产，默认 USDT）
+    fn render(&self, path: &str, account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(account_id) = account.parse::<u64>() else {
+            return Self::bad_request(format!("Invalid account: {}", account));
+        };
+        let target = match query_param(path, "target") {
+            None => self.sweeper.config().target,
+            Some(asset) => match AssetId::from_str(&asset.to_uppercase()) {
+                Some(asset) => asset,
+                None => return Self::bad_request(format!("Invalid asset: {}", asset)),
+            },
+        };
+
+        let (Ok(prices), Ok(balances)) = (self.prices.read(), self.balances.read()) else {
+            return (500, serde_json::json!({ "msg": "Dust preview unavailable" }).to_string());
+        };
+        let Some(sweeper) = self.sweeper_for(target, &*prices) else {
+            return Self::bad_request(format!("No index price for {}", target.as_str()));
+        };
+        let account_id = AccountId(account_id);
+        let preview = sweeper.preview(account_id, balances.values(), &*prices);
+        (200, Self::preview_json(&preview, sweeper.config().threshold).to_string())
+    }
+
+    /// 阈值按 USDT 配置，换算为目标资产
+    fn sweeper_for(&self, target: AssetId, prices: &dyn IndexPriceSource) -> Option<DustSweeper> {
+        let config = self.sweeper.config();
+        if target == config.target {
+            return Some(self.sweeper);
+        }
+        let rate = prices.index_price(config.target, target)?;
+        let threshold = config.threshold.checked_mul(rate)?;
+        Some(self.sweeper.with_target(target).with_threshold(threshold))
+    }
+
+    fn preview_json(preview: &DustPreview, threshold: Quantity) -> serde_json::Value {
+        let quotes: Vec<serde_json::Value> = preview.quotes.iter().map(Self::quote_json).collect();
+        let skipped: Vec<serde_json::Value> = preview
+            .skipped
+            .iter()
+            .map(|skip| {
+                serde_json::json!({
+                    "asset": skip.asset.as_str(),
+                    "amount": skip.amount.to_string(),
+                    "reason": skip.reason.as_str(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "accountId": preview.account_id.0,
+            "target": preview.target.as_str(),
+            "threshold": threshold.to_string(),
+            "quotes": quotes,
+            "skipped": skipped,
+            "totalFee": preview.total_fee().to_string(),
+            "totalNet": preview.total_net().to_string(),
+        })
+    }
+
+    fn quote_json(quote: &ConvertQuote) -> serde_json::Value {
+        serde_json::json!({
+            "asset": quote.from.as_str(),
+            "amount": quote.amount.to_string(),
+            "indexPrice": quote.index_price.to_string(),
+            "gross": quote.gross.to_string(),
+            "fee": quote.fee.to_string(),
+            "net": quote.net().to_string(),
+        })
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+/// 余额推送写入快照
+pub fn record_balance(balances: &RwLock<BalanceBook>, balance: &Balance) {
+    if let Ok(mut balances) = balances.write() {
+        balances.insert((balance.account_id, balance.asset_id), balance.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::mark_data::spot::level_types::{BboChangeEvent, MarketDataDelta, SymbolId};
+    use base_types::mark_data::spot::ticker::BookTicker;
+    use base_types::{Price, Timestamp, TradingPair};
+
+    use super::*;
+    use crate::http::market_ticker::TickerHandler;
+
+    fn book(pair: TradingPair, bid: f64, ask: f64) -> BookTicker {
+        BookTicker {
+            symbol_id: pair as SymbolId,
+            update_id: 1,
+            bid_price: Some(Price::from_f64(bid)),
+            bid_qty: Quantity::from_f64(1.0),
+            ask_price: Some(Price::from_f64(ask)),
+            ask_qty: Quantity::from_f64(1.0),
+        }
+    }
+
+    fn balance(asset: AssetId, available: f64) -> Balance {
+        let mut balance = Balance::new(AccountId(7), asset, Timestamp(0));
+        balance.available = Quantity::from_f64(available);
+        balance
+    }
+
+    #[test]
+    fn test_dust_preview() {
+        let mut tickers = SyntheticTickers::new(AssetId::Usdt);
+        tickers.on_book_ticker(&book(TradingPair::BtcUsdt, 39_990.0, 40_010.0));
+        tickers.on_book_ticker(&book(TradingPair::EthUsdt, 1_999.0, 2_001.0));
+        let handler = DustHandler::new(Arc::new(RwLock::new(tickers)));
+        let balances = handler.balances();
+        record_balance(&balances, &balance(AssetId::Btc, 0.0001));
+        record_balance(&balances, &balance(AssetId::Eth, 1.0));
+        assert!(DustHandler::matches("GET", "/api/asset/dust/preview?target=BTC"));
+        assert!(!DustHandler::matches("POST", DUST_PREVIEW_PATH));
+
+        let (status, body) = handler.render(DUST_PREVIEW_PATH, Some("7"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["quotes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["quotes"][0]["asset"], "BTC");
+        assert_eq!(json["skipped"][0]["reason"], "ABOVE_THRESHOLD");
+        assert_eq!(json["totalNet"], Quantity::from_f64(3.992).to_string());
+
+        // 目标资产为 ETH 时阈值按指数价换算为 0.005 ETH
+        let (_, body) = handler.render("/api/asset/dust/preview?target=eth", Some("7"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["threshold"], Quantity::from_f64(0.005).to_string());
+        assert_eq!(json["quotes"][0]["gross"], Quantity::from_f64(0.002).to_string());
+
+        assert_eq!(handler.render(DUST_PREVIEW_PATH, None).0, 401);
+        assert_eq!(handler.render("/api/asset/dust/preview?target=XYZ", Some("7")).0, 400);
+    }
+
+    #[test]
+    fn test_prices_follow_market_data() {
+        let tickers =
+            TickerHandler::default().with_synthetic_pairs(&[(AssetId::Eth, AssetId::Btc)]).unwrap();
+        let handler = DustHandler::new(tickers.synthetic_tickers());
+        record_balance(&handler.balances(), &balance(AssetId::Btc, 0.0001));
+        let (_, body) = handler.render(DUST_PREVIEW_PATH, Some("7"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(json["quotes"].as_array().unwrap().is_empty());
+
+        tickers.on_market_data(&MarketDataDelta::BboChange(BboChangeEvent {
+            symbol_id: TradingPair::BtcUsdt as SymbolId,
+            timestamp: 1,
+            sequence: 1,
+            best_bid: Some(Price::from_f64(39_990.0)),
+            best_bid_quantity: Quantity::from_f64(1.0),
+            best_ask: Some(Price::from_f64(40_010.0)),
+            best_ask_quantity: Quantity::from_f64(1.0),
+        }));
+        let (_, body) = handler.render(DUST_PREVIEW_PATH, Some("7"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["quotes"][0]["asset"], "BTC");
+    }
+}
@@ -0,0 +1,172 @@
+//! 行情权限管理接口
+//!
+//! 管理 WebSocket 订阅所需的 API Key 行情权限（见 [`crate::websocket::subscription`]），
+//! 与推送服务共享 [`EntitlementStore`]：收回后已有连接立即停止推送对应的流。
+//!
+//! 管理接口（`X-Admin-Token` 鉴权，未配置令牌时关闭）：
+//! - `GET /api/admin/entitlements?apiKey=`：查询 Key 的全部权限
+//! - `POST /api/admin/entitlements`：`{apiKey, entitlement, action}`，
+//!   `entitlement` 为 `FULL_DEPTH`、`L3` 或 `HISTORICAL_REPLAY`，`action` 为 `GRANT` 或 `REVOKE`
+
+use serde::Deserialize;
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::codec::{header_value, request_body};
+use super::exchange_info::{json_response, query_param};
+use crate::websocket::subscription::{Entitlement, EntitlementStore};
+
+/// 行情权限管理接口路径
+pub const ADMIN_ENTITLEMENTS_PATH: &str = "/api/admin/entitlements";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EntitlementRequest {
+    api_key: String,
+    entitlement: String,
+    action: String,
+}
+
+/// 行情权限管理处理器
+#[derive(Debug, Default)]
+pub struct EntitlementHandler {
+    store: EntitlementStore,
+    admin_token: Option<String>,
+}
+
+impl EntitlementHandler {
+    pub fn new(store: EntitlementStore) -> Self {
+        Self { store, admin_token: None }
+    }
+
+    /// 配置管理令牌时开放管理接口
+    pub fn from_env() -> Self {
+        let handler = Self::default();
+        match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        }
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// 与 WebSocket 推送服务共享的权限表
+    pub fn store(&self) -> &EntitlementStore {
+        &self.store
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        (method == "GET" || method == "POST")
+            && path.split('?').next() == Some(ADMIN_ENTITLEMENTS_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, method: &str, path: &str, request: &[u8]) -> Vec<u8> {
+        let (status, body) = self.render(method, path, request);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, method: &str, path: &str, request: &[u8]) -> (u16, String) {
+        match self.admin(method, path, request) {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn admin(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+
+        if method == "GET" {
+            let api_key = query_param(path, "apiKey")
+                .ok_or_else(|| (400, "apiKey is required".to_string()))?;
+            return Ok(self.entitlements_json(api_key, None));
+        }
+        let req: EntitlementRequest =
+            serde_json::from_slice(request_body(request)).map_err(|e| (400, e.to_string()))?;
+        if req.api_key.is_empty() {
+            return Err((400, "apiKey is required".to_string()));
+        }
+        let entitlement = Entitlement::parse(&req.entitlement)
+            .ok_or_else(|| (400, format!("Unknown entitlement: {}", req.entitlement)))?;
+        let changed = match req.action.as_str() {
+            "GRANT" => self.store.grant(&req.api_key, entitlement),
+            "REVOKE" => self.store.revoke(&req.api_key, entitlement),
+            action => return Err((400, format!("Unknown action: {}", action))),
+        };
+        Ok(self.entitlements_json(&req.api_key, Some(changed)))
+    }
+
+    fn entitlements_json(&self, api_key: &str, changed: Option<bool>) -> serde_json::Value {
+        let entitlements: Vec<&str> =
+            self.store.entitlements_of(api_key).into_iter().map(Entitlement::as_str).collect();
+        let mut body = serde_json::json!({ "apiKey": api_key, "entitlements": entitlements });
+        if let Some(changed) = changed {
+            body["changed"] = changed.into();
+        }
+        body
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin_request(method: &str, path: &str, token: &str, body: &str) -> Vec<u8> {
+        format!("{} {} HTTP/1.1\r\nX-Admin-Token: {}\r\n\r\n{}", method, path, token, body)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_grant_list_and_revoke() {
+        let store = EntitlementStore::new();
+        let handler = EntitlementHandler::new(store.clone()).with_admin_token("secret");
+        let grant = r#"{"apiKey":"key-1","entitlement":"L3","action":"GRANT"}"#;
+        let request = admin_request("POST", ADMIN_ENTITLEMENTS_PATH, "secret", grant);
+        let (status, body) = handler.render("POST", ADMIN_ENTITLEMENTS_PATH, &request);
+        assert_eq!(status, 200, "{}", body);
+        assert_eq!(body, r#"{"apiKey":"key-1","changed":true,"entitlements":["L3"]}"#);
+        assert!(store.has("key-1", Entitlement::Level3));
+
+        let path = "/api/admin/entitlements?apiKey=key-1";
+        let request = admin_request("GET", path, "secret", "");
+        assert_eq!(
+            handler.render("GET", path, &request).1,
+            r#"{"apiKey":"key-1","entitlements":["L3"]}"#
+        );
+
+        let revoke = grant.replace("GRANT", "REVOKE");
+        let request = admin_request("POST", ADMIN_ENTITLEMENTS_PATH, "secret", &revoke);
+        assert_eq!(handler.render("POST", ADMIN_ENTITLEMENTS_PATH, &request).0, 200);
+        assert!(!store.has("key-1", Entitlement::Level3));
+
+        let unknown = grant.replace("L3", "L4");
+        let request = admin_request("POST", ADMIN_ENTITLEMENTS_PATH, "secret", &unknown);
+        assert_eq!(handler.render("POST", ADMIN_ENTITLEMENTS_PATH, &request).0, 400);
+        let request = admin_request("POST", ADMIN_ENTITLEMENTS_PATH, "wrong", grant);
+        assert_eq!(handler.render("POST", ADMIN_ENTITLEMENTS_PATH, &request).0, 401);
+        let disabled = EntitlementHandler::new(store);
+        assert_eq!(disabled.render("POST", ADMIN_ENTITLEMENTS_PATH, &request).0, 403);
+    }
+}
@@ -0,0 +1,388 @@
+use std::io;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::instrument::degradation::DegradationRegistry;
+use base_types::instrument::exchange_info::{ExchangeInfo, RateLimitDescriptor};
+use base_types::instrument::normalize::InstrumentScale;
+use base_types::instrument::registry::{InstrumentRegistry, InstrumentSpec};
+use base_types::{AssetId, Decimal, InstrumentType, SystemClock, Timestamp, TimestampProvider};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use super::codec::{WireFormat, encoded_response};
+
+/// exchangeInfo 接口路径
+pub const EXCHANGE_INFO_PATH: &str = "/api/exchangeInfo";
+/// 上线产品与限频规则配置文件
+const ENV_EXCHANGE_INFO_FILE: &str = "GATEWAY_EXCHANGE_INFO_FILE";
+
+/// `GET /api/exchangeInfo` 处理器
+///
+/// 由网关直接应答，不转发后端：产品元数据来自产品注册表，限频规则来自网关配置，
+/// 降级中的产品附带降级状态
+pub struct ExchangeInfoHandler {
+    registry: Arc<RwLock<InstrumentRegistry>>,
+    rate_limits: Vec<RateLimitDescriptor>,
+    degradations: Arc<RwLock<DegradationRegistry>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for ExchangeInfoHandler {
+    /// 空注册表、无限频规则；上线产品与限频规则由 [`ExchangeInfoConfig`] 装载
+    fn default() -> Self {
+        Self::new(InstrumentRegistry::new(), Vec::new())
+    }
+}
+
+/// 单个上线产品的配置
+///
+/// 数量类字段以数量精度的整数单位表示，与 [`InstrumentSpec`] 一致
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListingConfig {
+    symbol: String,
+    symbol_id: u32,
+    #[serde(default = "default_instrument_type")]
+    instrument_type: String,
+    base_asset: String,
+    quote_asset: String,
+    price_precision: u32,
+    quantity_precision: u32,
+    #[serde(default)]
+    tick_size: Option<i64>,
+    #[serde(default)]
+    lot_size: Option<i64>,
+    min_quantity: i64,
+    max_quantity: i64,
+    #[serde(default)]
+    min_notional: f64,
+    #[serde(default)]
+    fee_schedule: Option<String>,
+    /// 交割时间（毫秒，交割合约与期权必填，到期后由定时任务停止交易）
+    #[serde(default)]
+    delivery_date: Option<u64>,
+}
+
+fn default_instrument_type() -> String {
+    "Spot".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigFile {
+    symbols: Vec<ListingConfig>,
+    #[serde(default)]
+    rate_limits: Vec<RateLimitDescriptor>,
+}
+
+/// exchangeInfo 的上线产品与限频规则
+///
+/// 从 `GATEWAY_EXCHANGE_INFO_FILE` 指向的 JSON 文件读取，格式见
+/// `app/pingora_gateway/exchange_info.example.json`
+#[derive(Debug)]
+pub struct ExchangeInfoConfig {
+    pub registry: InstrumentRegistry,
+    pub rate_limits: Vec<RateLimitDescriptor>,
+}
+
+impl ExchangeInfoConfig {
+    /// 未设置 `GATEWAY_EXCHANGE_INFO_FILE` 时返回 `None`
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match std::env::var(ENV_EXCHANGE_INFO_FILE).ok().filter(|path| !path.is_empty()) {
+            Some(path) => Self::load(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&json).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// 解析配置；资产或产品类型未知、交易对重复时报错
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file: ConfigFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut registry = InstrumentRegistry::new();
+        for listing in file.symbols {
+            let asset = |name: &str| {
+                AssetId::from_str(name)
+                    .ok_or_else(|| format!("{}: unknown asset {}", listing.symbol, name))
+            };
+            let instrument_type = match listing.instrument_type.to_uppercase().as_str() {
+                "SPOT" => InstrumentType::Spot,
+                "PERPETUAL" => InstrumentType::Perpetual,
+                "FUTURES" => InstrumentType::Futures,
+                "OPTIONS" => InstrumentType::Options,
+                other => {
+                    return Err(format!("{}: unknown instrument type {}", listing.symbol, other));
+                }
+            };
+            let scale = InstrumentScale::new(listing.price_precision, listing.quantity_precision);
+            let lot_size = listing.lot_size.unwrap_or(listing.min_quantity);
+            let mut spec = InstrumentSpec::new(
+                listing.symbol_id,
+                &listing.symbol.to_uppercase(),
+                instrument_type,
+                asset(&listing.base_asset)?,
+                asset(&listing.quote_asset)?,
+                scale,
+            )
+            .with_lot_size(lot_size, listing.min_quantity, listing.max_quantity)
+            .with_min_notional(Decimal::from_f64(listing.min_notional));
+            if let Some(tick_size) = listing.tick_size {
+                spec = spec.with_tick_size(tick_size);
+            }
+            if let Some(fee_schedule) = &listing.fee_schedule {
+                spec = spec.with_fee_schedule(fee_schedule);
+            }
+            match (instrument_type.has_expiry(), listing.delivery_date) {
+                (true, Some(ms)) => {
+                    spec = spec.with_expiry(Timestamp(ms.saturating_mul(1_000_000)))
+                }
+                (true, None) => {
+                    return Err(format!("{}: deliveryDate required", listing.symbol));
+                }
+                (false, Some(_)) => {
+                    return Err(format!(
+                        "{}: deliveryDate on a non-expiring listing",
+                        listing.symbol
+                    ));
+                }
+                (false, None) => {}
+            }
+            registry.register(spec).map_err(|e| e.to_string())?;
+        }
+        Ok(Self { registry, rate_limits: file.rate_limits })
+    }
+}
+
+impl ExchangeInfoHandler {
+    pub fn new(registry: InstrumentRegistry, rate_limits: Vec<RateLimitDescriptor>) -> Self {
+        Self {
+            registry: Arc::new(RwLock::new(registry)),
+            rate_limits,
+            degradations: Arc::new(RwLock::new(DegradationRegistry::new())),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 装载上线产品与限频规则
+    ///
+    /// 在共享的注册表上原地替换，委托入口规范化随之生效
+    pub fn with_config(mut self, config: ExchangeInfoConfig) -> Self {
+        *self.registry.write().unwrap_or_else(PoisonError::into_inner) = config.registry;
+        self.rate_limits = config.rate_limits;
+        self
+    }
+
+    /// 产品注册表（与委托入口规范化共享）
+    pub fn registry(&self) -> &Arc<RwLock<InstrumentRegistry>> {
+        &self.registry
+    }
+
+    /// 与降级信号共享的降级登记表
+    pub fn with_degradations(mut self, degradations: Arc<RwLock<DegradationRegistry>>) -> Self {
+        self.degradations = degradations;
+        self
+    }
+
+    /// 替换时间来源（测试注入 `ManualClock`）
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 停止已到期产品的交易，返回本次停止的交易对
+    pub fn halt_expired(&self) -> Vec<String> {
+        Self::halt_expired_in(&self.registry, self.clock.as_ref())
+    }
+
+    fn halt_expired_in(
+        registry: &RwLock<InstrumentRegistry>,
+        clock: &dyn TimestampProvider,
+    ) -> Vec<String> {
+        let halted =
+            registry.write().unwrap_or_else(PoisonError::into_inner).halt_expired(clock.now());
+        for symbol in &halted {
+            warn!("Instrument {} reached delivery, trading halted", symbol);
+        }
+        halted
+    }
+
+    /// 启动到期停牌定时任务：每隔 `interval` 停止已到期产品的交易
+    ///
+    /// 引擎侧的交割由命令日志中的 `SetExpiry` / `DeliverFutures` 驱动，这里只负责
+    /// 在入口停止接收到期产品的新委托
+    pub fn spawn_expiry_halt(&self, interval: Duration) -> io::Result<JoinHandle<()>> {
+        let registry = self.registry.clone();
+        let clock = self.clock.clone();
+        let expiring = registry
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|spec| spec.expiry.is_some())
+            .count();
+        info!("⌛ Expiry halt checks {} futures every {:?}", expiring, interval);
+        std::thread::Builder::new().name("expiry-halt".to_string()).spawn(move || {
+            loop {
+                Self::halt_expired_in(&registry, clock.as_ref());
+                std::thread::sleep(interval);
+            }
+        })
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(EXCHANGE_INFO_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应
+    ///
+    /// 支持 `?symbol=BTCUSDT` 只查询单个产品，未知产品返回 400
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        let (status, body) = self.render(path, self.clock.now_millis());
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, path: &str, server_time: u64) -> (u16, String) {
+        let symbol = query_param(path, "symbol");
+        let registry = self.registry.read().unwrap_or_else(PoisonError::into_inner);
+        match ExchangeInfo::build(&registry, &self.rate_limits, server_time, symbol) {
+            Some(mut info) => {
+                let degradations = self.degradations.read().unwrap_or_else(PoisonError::into_inner);
+                info.apply_degradations(&degradations);
+                match serde_json::to_string(&info) {
+                    Ok(body) => (200, body),
+                    Err(e) => (500, serde_json::json!({ "msg": e.to_string() }).to_string()),
+                }
+            }
+            None => (
+                400,
+                serde_json::json!({ "msg": format!("Invalid symbol: {}", symbol.unwrap_or("")) })
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// 查询串参数（空值视为未指定）
+pub(crate) fn query_param<'a>(path: &'a str, key: &str) -> Option<&'a str> {
+    let (_, query) = path.split_once('?')?;
+    query.split('&').find_map(|param| match param.split_once('=') {
+        Some((k, value)) if k == key && !value.is_empty() => Some(value),
+        _ => None,
+    })
+}
+
+/// 生成完整的 JSON HTTP 响应
+pub(crate) fn json_response(status: u16, body: &str) -> Vec<u8> {
+    encoded_response(status, WireFormat::Json, body.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handler() -> ExchangeInfoHandler {
+        let json = include_str!("../../exchange_info.example.json");
+        ExchangeInfoHandler::default().with_config(ExchangeInfoConfig::parse(json).unwrap())
+    }
+
+    #[test]
+    fn test_matches_exchange_info() {
+        assert!(ExchangeInfoHandler::matches("GET", "/api/exchangeInfo"));
+        assert!(ExchangeInfoHandler::matches("GET", "/api/exchangeInfo?symbol=BTCUSDT"));
+        assert!(!ExchangeInfoHandler::matches("POST", "/api/exchangeInfo"));
+        assert!(!ExchangeInfoHandler::matches("GET", "/api/spot/health"));
+    }
+
+    #[test]
+    fn test_render_exchange_info() {
+        let handler = handler();
+        let (status, body) = handler.render("/api/exchangeInfo", 1);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbols"].as_array().unwrap().len(), 3);
+        assert_eq!(json["rateLimits"][0]["rateLimitType"], "REQUEST_WEIGHT");
+
+        let (status, body) = handler.render("/api/exchangeInfo?symbol=ETHUSDT", 1);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbols"][0]["symbol"], "ETHUSDT");
+        assert_eq!(json["symbols"][0]["tickSize"], "0.01");
+        assert_eq!(json["symbols"][0]["minNotional"], "5.00000000");
+
+        let (status, _) = handler.render("/api/exchangeInfo?symbol=DOGEUSDT", 1);
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_server_time_from_injected_clock() {
+        let clock = base_types::ManualClock::from_millis(1_700_000_000_000);
+        let handler = handler().with_clock(Arc::new(clock.clone()));
+        clock.advance_millis(250);
+
+        let response = String::from_utf8(handler.respond("/api/exchangeInfo")).unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["serverTime"], 1_700_000_000_250u64);
+    }
+
+    #[test]
+    fn test_listings_come_from_config() {
+        let (_, body) = ExchangeInfoHandler::default().render("/api/exchangeInfo", 1);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(json["symbols"].as_array().unwrap().is_empty());
+        assert!(json["rateLimits"].as_array().unwrap().is_empty());
+
+        // 注册表原地替换，共享方随之可见
+        let empty = ExchangeInfoHandler::default();
+        let shared = empty.registry().clone();
+        let loaded = empty.with_config(
+            ExchangeInfoConfig::parse(include_str!("../../exchange_info.example.json")).unwrap(),
+        );
+        assert_eq!(shared.read().unwrap().len(), 3);
+        assert_eq!(shared.read().unwrap().get("ETHUSDT").unwrap().min_quantity, 100);
+        assert_eq!(loaded.rate_limits.len(), 3);
+
+        let listing = r#"{"symbol":"BTCUSDT","symbolId":1,"baseAsset":"BTC","quoteAsset":"USDT",
+            "pricePrecision":2,"quantityPrecision":6,"minQuantity":1,"maxQuantity":10}"#;
+        assert!(ExchangeInfoConfig::parse(&format!(r#"{{"symbols":[{}]}}"#, listing)).is_ok());
+        let duplicate = format!(r#"{{"symbols":[{},{}]}}"#, listing, listing);
+        assert!(ExchangeInfoConfig::parse(&duplicate).is_err());
+        let unknown = listing.replace("\"BTC\"", "\"DOGE\"");
+        assert!(ExchangeInfoConfig::parse(&format!(r#"{{"symbols":[{}]}}"#, unknown)).is_err());
+        assert!(ExchangeInfoConfig::parse("{}").is_err());
+    }
+
+    #[test]
+    fn test_expired_futures_halted_by_timer_check() {
+        let futures = r#"{"symbol":"BTCUSDT0329","symbolId":9,"instrumentType":"Futures",
+            "baseAsset":"BTC","quoteAsset":"USDT","pricePrecision":2,"quantityPrecision":6,
+            "minQuantity":1,"maxQuantity":10,"deliveryDate":1700000060000}"#;
+        let config = ExchangeInfoConfig::parse(&format!(r#"{{"symbols":[{}]}}"#, futures)).unwrap();
+        let clock = base_types::ManualClock::from_millis(1_700_000_000_000);
+        let handler =
+            ExchangeInfoHandler::default().with_config(config).with_clock(Arc::new(clock.clone()));
+        let (_, body) = handler.render("/api/exchangeInfo", 0);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbols"][0]["deliveryDate"], 1_700_000_060_000u64);
+
+        assert!(handler.halt_expired().is_empty());
+        clock.advance_millis(60_000);
+        assert_eq!(handler.halt_expired(), ["BTCUSDT0329"]);
+        assert!(handler.halt_expired().is_empty());
+        let (_, body) = handler.render("/api/exchangeInfo", 0);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbols"][0]["status"], "HALT");
+
+        // 交割合约必须配置交割时间，无到期日的产品不接受交割时间
+        let parse =
+            |listing: String| ExchangeInfoConfig::parse(&format!(r#"{{"symbols":[{}]}}"#, listing));
+        assert!(parse(futures.replace(r#","deliveryDate":1700000060000"#, "")).is_err());
+        assert!(parse(futures.replace("Futures", "Perpetual")).is_err());
+    }
+}
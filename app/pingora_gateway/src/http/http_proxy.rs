@@ -1,17 +1,59 @@
-use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use base_types::SystemClock;
+use base_types::account::settlement_export::{SettlementExportConfig, SettlementExporter};
+use db_repo::adapter::kafka_settlement_sink::{KafkaSettlementSink, KafkaSettlementSinkConfig};
 use pingora::apps::ServerApp;
 use pingora::connectors::TransportConnector;
 use pingora::protocols::Stream;
 use pingora::server::ShutdownWatch;
 use pingora::upstreams::peer::{HttpPeer, Peer};
 use pingora_proxy::http_proxy_service;
+use prep::domain::service::{
+    Command, EngineStatsRegistry, ReadModelProjection, StatisticsProjection,
+};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::select;
 use tracing::{debug, info, warn};
 
+use super::account_activity::AccountActivityHandler;
+use super::account_status::AccountStatusGate;
+use super::algo_tca::AlgoTcaHandler;
+use super::api_keys::ApiKeyHandler;
+use super::api_usage::{ApiUsageHandler, USED_WEIGHT_HEADER, UsageTicket, insert_header};
+use super::block_trade::{BlockTradeHandler, BlockTradeSettlementExporter};
+use super::codec::{header_value, request_body};
+use super::compression::ResponseCompression;
+use super::cost_basis::CostBasisHandler;
+use super::degradation::DegradationHandler;
+use super::delegation::DelegationGate;
+use super::discovery::{DiscoveryConfig, spawn_discovery};
+use super::dust::DustHandler;
+use super::entitlements::EntitlementHandler;
+use super::exchange_info::{ExchangeInfoConfig, ExchangeInfoHandler, json_response};
+use super::klines::{KlinesHandler, candle_file_from_env};
+use super::leaderboard::LeaderboardHandler;
+use super::market_feed::{MarketFeed, MarketFeedConfig, spawn_market_feed};
+use super::market_ticker::TickerHandler;
+use super::order_ingress::OrderNormalizer;
+use super::payload_keys::PayloadKeyHandler;
+use super::prep_account::PrepAccountHandler;
+use super::prep_admin::PrepAdminHandler;
+use super::prep_history::PrepHistoryHandler;
+use super::prep_quote::PrepQuoteHandler;
+use super::prep_recurring::PrepRecurringHandler;
+use super::prep_schedule::PrepScheduleHandler;
+use super::rfq::{DEFAULT_SETTLE_INTERVAL, RfqHandler};
 use super::router::{UserIdExtractor, UserRouteConfig, UserRouter};
+use super::server_time::{ServerTimeHandler, TimeSyncConfig, TimeSyncMonitor};
+use super::session_auth::SessionAuth;
+use super::signed_request::SignedRequestAuth;
+use super::trades::TradesHandler;
+use crate::websocket::heatmap::HeatmapStream;
+use crate::websocket::server::WebSocketGateway;
 
 enum DuplexEvent {
     DownstreamRead(usize),
@@ -24,6 +66,67 @@ pub struct HttpProxyApp {
     proxy_to: HttpPeer,
     /// 用户路由器（用于 /api/spot/v2/ 和 /api/spot/user/data）
     user_router: Arc<UserRouter>,
+    /// 网关直接应答的 exchangeInfo 接口
+    exchange_info: ExchangeInfoHandler,
+    /// 新委托与大宗交易报告的价格 / 数量规范化（与 `exchange_info` 共享产品注册表）
+    orders: OrderNormalizer,
+    /// 网关直接应答的服务器时间接口
+    server_time: ServerTimeHandler,
+    /// 网关直接应答的公开成交接口
+    trades: TradesHandler,
+    /// 网关直接应答的K线与 24h 成交量接口（由行情组播的成交通道写入，启动时可加载回填K线）
+    klines: KlinesHandler,
+    /// 网关直接应答的均价与最优挂单接口（由行情组播接入写入）
+    tickers: Arc<TickerHandler>,
+    /// 订单簿热力图推送（由行情组播的逐笔委托通道写入）
+    heatmap: Arc<HeatmapStream>,
+    /// 网关直接应答的资金费率与标记价格K线接口
+    prep_history: PrepHistoryHandler,
+    /// 网关直接应答的成交量与盈亏排行榜接口
+    leaderboard: LeaderboardHandler,
+    /// 网关直接应答的合约账户查询接口（读侧投影）
+    prep_account: PrepAccountHandler,
+    /// 合约引擎管理接口（命令提交到撮合分片）
+    prep_admin: PrepAdminHandler,
+    /// 合约定投接口（命令提交到撮合分片，查询走读侧投影）
+    prep_recurring: PrepRecurringHandler,
+    /// 合约定时委托接口（命令提交到撮合分片）
+    prep_schedule: PrepScheduleHandler,
+    /// 合约做市报价与做市商保护接口（命令提交到撮合分片，断线撤单挂到 WebSocket 会话）
+    prep_quotes: PrepQuoteHandler,
+    /// 网关直接应答的账户流水接口
+    activity: AccountActivityHandler,
+    /// 网关直接应答的算法 TCA 报告接口
+    algo_tca: AlgoTcaHandler,
+    /// 网关直接应答的大宗交易报告与确认接口
+    block_trades: BlockTradeHandler,
+    /// 网关直接应答的询价（RFQ）接口，报价窗口由定时器结束
+    rfq: RfqHandler,
+    /// 联名账户与代操作鉴权
+    delegation: DelegationGate,
+    /// 浏览器会话（JWT）鉴权
+    sessions: Arc<SessionAuth>,
+    /// 网关直接应答的小额资产兑换预览接口（与 `tickers` 共享指数价）
+    dust: DustHandler,
+    cost_basis: CostBasisHandler,
+    /// API Key 签名请求鉴权与防重放
+    signed: SignedRequestAuth,
+    /// 网关直接应答的 API Key 管理接口（与 `signed` 共享 Key 存储）
+    api_keys: ApiKeyHandler,
+    /// 网关直接应答的报告签名与加密密钥管理接口
+    payload_keys: PayloadKeyHandler,
+    /// 大响应的压缩与 ETag 协商缓存
+    compression: ResponseCompression,
+    /// 按账户、Key 与接口的调用量统计
+    api_usage: ApiUsageHandler,
+    /// 交易对降级信号（与 `exchange_info` 共享登记表）
+    degradation: DegradationHandler,
+    /// 账户状态闸门（暂停、冻结的账户不能下单）
+    account_status: AccountStatusGate,
+    /// WebSocket 推送（握手鉴权与 HTTP 接口共用会话服务）
+    websocket: WebSocketGateway,
+    /// 行情权限管理接口（与 `websocket` 共享权限表）
+    entitlements: EntitlementHandler,
 }
 
 // todo 打印转发数据
@@ -33,14 +136,279 @@ impl HttpProxyApp {
         let user_route_config = UserRouteConfig::default();
         let user_router = Arc::new(UserRouter::new(user_route_config));
 
-        HttpProxyApp { client_connector: TransportConnector::new(None), proxy_to, user_router }
+        let api_keys = ApiKeyHandler::default();
+        let tickers = TickerHandler::default();
+        let dust = DustHandler::new(tickers.synthetic_tickers());
+        let cost_basis = CostBasisHandler::new(tickers.synthetic_tickers(), dust.balances());
+        let degradation = DegradationHandler::default();
+        let exchange_info =
+            ExchangeInfoHandler::default().with_degradations(degradation.registry().clone());
+        let prep_account = PrepAccountHandler::default();
+        let prep_recurring = PrepRecurringHandler::new(prep_account.projection());
+        let activity = AccountActivityHandler::default();
+        let sessions = Arc::new(SessionAuth::default());
+        let websocket = WebSocketGateway::default()
+            .with_key_store(api_keys.store().clone())
+            .with_sessions(sessions.clone())
+            .with_degradations(degradation.registry().clone());
+        let entitlements = EntitlementHandler::new(websocket.entitlements().clone());
+        let block_trades = BlockTradeHandler::default().with_ledger(activity.ledger());
+        HttpProxyApp {
+            client_connector: TransportConnector::new(None),
+            proxy_to,
+            user_router,
+            orders: OrderNormalizer::new(exchange_info.registry().clone()),
+            exchange_info,
+            server_time: ServerTimeHandler::default(),
+            trades: TradesHandler::default(),
+            klines: KlinesHandler::default(),
+            dust,
+            cost_basis,
+            tickers: Arc::new(tickers),
+            heatmap: Arc::new(HeatmapStream::default()),
+            prep_history: PrepHistoryHandler::default(),
+            leaderboard: LeaderboardHandler::default(),
+            prep_account,
+            prep_admin: PrepAdminHandler::default(),
+            prep_recurring,
+            prep_schedule: PrepScheduleHandler::default(),
+            prep_quotes: PrepQuoteHandler::default(),
+            rfq: RfqHandler::default()
+                .with_ledger(activity.ledger())
+                .with_sources(Arc::new(SystemClock), block_trades.trade_ids()),
+            block_trades,
+            activity,
+            algo_tca: AlgoTcaHandler::default(),
+            delegation: DelegationGate::default(),
+            sessions,
+            signed: SignedRequestAuth::default().with_key_store(api_keys.store().clone()),
+            api_keys,
+            payload_keys: PayloadKeyHandler::default(),
+            compression: ResponseCompression::default(),
+            api_usage: ApiUsageHandler::default(),
+            degradation,
+            account_status: AccountStatusGate::default(),
+            websocket,
+            entitlements,
+        }
     }
 
     /// 创建带自定义路由配置的代理服务器应用实例
     pub fn with_router(proxy_to: HttpPeer, user_route_config: UserRouteConfig) -> Self {
-        let user_router = Arc::new(UserRouter::new(user_route_config));
+        Self::with_user_router(proxy_to, Arc::new(UserRouter::new(user_route_config)))
+    }
+
+    /// 使用共享的用户路由器创建实例（路由器可由集群发现在外部热更新）
+    pub fn with_user_router(proxy_to: HttpPeer, user_router: Arc<UserRouter>) -> Self {
+        let api_keys = ApiKeyHandler::default();
+        let tickers = TickerHandler::default();
+        let dust = DustHandler::new(tickers.synthetic_tickers());
+        let cost_basis = CostBasisHandler::new(tickers.synthetic_tickers(), dust.balances());
+        let degradation = DegradationHandler::default();
+        let exchange_info =
+            ExchangeInfoHandler::default().with_degradations(degradation.registry().clone());
+        let prep_account = PrepAccountHandler::default();
+        let prep_recurring = PrepRecurringHandler::new(prep_account.projection());
+        let activity = AccountActivityHandler::default();
+        let sessions = Arc::new(SessionAuth::default());
+        let websocket = WebSocketGateway::default()
+            .with_key_store(api_keys.store().clone())
+            .with_sessions(sessions.clone())
+            .with_degradations(degradation.registry().clone());
+        let entitlements = EntitlementHandler::new(websocket.entitlements().clone());
+        let block_trades = BlockTradeHandler::default().with_ledger(activity.ledger());
+        HttpProxyApp {
+            client_connector: TransportConnector::new(None),
+            proxy_to,
+            user_router,
+            orders: OrderNormalizer::new(exchange_info.registry().clone()),
+            exchange_info,
+            server_time: ServerTimeHandler::default(),
+            trades: TradesHandler::default(),
+            klines: KlinesHandler::default(),
+            dust,
+            cost_basis,
+            tickers: Arc::new(tickers),
+            heatmap: Arc::new(HeatmapStream::default()),
+            prep_history: PrepHistoryHandler::default(),
+            leaderboard: LeaderboardHandler::default(),
+            prep_account,
+            prep_admin: PrepAdminHandler::default(),
+            prep_recurring,
+            prep_schedule: PrepScheduleHandler::default(),
+            prep_quotes: PrepQuoteHandler::default(),
+            rfq: RfqHandler::default()
+                .with_ledger(activity.ledger())
+                .with_sources(Arc::new(SystemClock), block_trades.trade_ids()),
+            block_trades,
+            activity,
+            algo_tca: AlgoTcaHandler::default(),
+            delegation: DelegationGate::default(),
+            sessions,
+            signed: SignedRequestAuth::default().with_key_store(api_keys.store().clone()),
+            api_keys,
+            payload_keys: PayloadKeyHandler::default(),
+            compression: ResponseCompression::default(),
+            api_usage: ApiUsageHandler::default(),
+            degradation,
+            account_status: AccountStatusGate::default(),
+            websocket,
+            entitlements,
+        }
+    }
+
+    /// 在 `/api/time` 中附带时钟同步指标
+    pub fn with_time_sync(mut self, sync: Arc<TimeSyncMonitor>) -> Self {
+        self.server_time = ServerTimeHandler::default().with_sync_monitor(sync);
+        self
+    }
+
+    /// 使用持久化的 API Key 存储，签名请求鉴权随之切换
+    pub fn with_api_keys(mut self, api_keys: ApiKeyHandler) -> Self {
+        self.signed = std::mem::take(&mut self.signed).with_key_store(api_keys.store().clone());
+        self.websocket =
+            std::mem::take(&mut self.websocket).with_key_store(api_keys.store().clone());
+        self.api_keys = api_keys;
+        self
+    }
+
+    /// 使用外部配置的会话鉴权（签名密钥、签发方与登录校验）
+    pub fn with_sessions(mut self, sessions: SessionAuth) -> Self {
+        self.sessions = Arc::new(sessions);
+        self.websocket = std::mem::take(&mut self.websocket).with_sessions(self.sessions.clone());
+        self
+    }
+
+    /// 使用外部配置的报告签名密钥
+    pub fn with_payload_keys(mut self, payload_keys: PayloadKeyHandler) -> Self {
+        self.payload_keys = payload_keys;
+        self
+    }
+
+    /// 使用外部配置的压缩阈值
+    pub fn with_compression(mut self, compression: ResponseCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// 使用外部配置的调用量统计（开放管理控制台接口）
+    pub fn with_api_usage(mut self, api_usage: ApiUsageHandler) -> Self {
+        self.api_usage = api_usage;
+        self
+    }
+
+    /// 装载上线产品与限频规则（exchangeInfo 与委托入口规范化共享注册表）
+    pub fn with_exchange_info(mut self, config: ExchangeInfoConfig) -> Self {
+        self.exchange_info = std::mem::take(&mut self.exchange_info).with_config(config);
+        self
+    }
+
+    /// 使用外部配置的降级信号，exchangeInfo 随之切换登记表
+    pub fn with_degradation(mut self, degradation: DegradationHandler) -> Self {
+        self.exchange_info = std::mem::take(&mut self.exchange_info)
+            .with_degradations(degradation.registry().clone());
+        self.websocket =
+            std::mem::take(&mut self.websocket).with_degradations(degradation.registry().clone());
+        self.degradation = degradation;
+        self
+    }
+
+    /// 使用外部配置的 WebSocket 推送（令牌密钥、Origin 白名单与续传存储），
+    /// API Key 与会话鉴权沿用 HTTP 接口的配置
+    pub fn with_websocket(mut self, websocket: WebSocketGateway) -> Self {
+        self.websocket = websocket
+            .with_key_store(self.api_keys.store().clone())
+            .with_entitlements(self.entitlements.store().clone())
+            .with_sessions(self.sessions.clone())
+            .with_degradations(self.degradation.registry().clone());
+        if let Some(hook) = self.prep_quotes.disconnect_hook() {
+            self.websocket = std::mem::take(&mut self.websocket).with_disconnect_hook(hook);
+        }
+        self
+    }
+
+    /// 使用外部配置的行情权限管理接口，WebSocket 推送随之切换权限表
+    pub fn with_entitlements(mut self, entitlements: EntitlementHandler) -> Self {
+        self.websocket =
+            std::mem::take(&mut self.websocket).with_entitlements(entitlements.store().clone());
+        self.entitlements = entitlements;
+        self
+    }
+
+    /// 使用外部配置的合约引擎管理接口
+    pub fn with_prep_admin(mut self, prep_admin: PrepAdminHandler) -> Self {
+        self.prep_admin = prep_admin;
+        self
+    }
+
+    /// 使用外部配置的账户状态闸门
+    pub fn with_account_status(mut self, account_status: AccountStatusGate) -> Self {
+        self.account_status = account_status;
+        self
+    }
+
+    /// 大宗成交与询价成交入账后导出结算分录
+    pub fn with_settlement_export(mut self, exporter: BlockTradeSettlementExporter) -> Self {
+        let exporter = Arc::new(Mutex::new(exporter));
+        self.block_trades =
+            std::mem::take(&mut self.block_trades).with_settlement_export(exporter.clone());
+        self.rfq = self.rfq.clone().with_settlement_export(exporter);
+        self
+    }
+
+    /// 接入合约撮合分片的命令发送端，管理接口、定投、定时委托与做市报价接口的命令与账户状态变更随之提交，
+    /// WebSocket 会话断线撤单随之生效
+    pub fn with_prep_engine(mut self, engine: Sender<Command>) -> Self {
+        self.account_status = std::mem::take(&mut self.account_status).with_engine(engine.clone());
+        self.prep_admin = std::mem::take(&mut self.prep_admin).with_engine(engine.clone());
+        self.prep_recurring = std::mem::take(&mut self.prep_recurring).with_engine(engine.clone());
+        self.prep_schedule = std::mem::take(&mut self.prep_schedule).with_engine(engine.clone());
+        self.prep_quotes = std::mem::take(&mut self.prep_quotes).with_engine(engine);
+        if let Some(hook) = self.prep_quotes.disconnect_hook() {
+            self.websocket = std::mem::take(&mut self.websocket).with_disconnect_hook(hook);
+        }
+        self
+    }
 
-        HttpProxyApp { client_connector: TransportConnector::new(None), proxy_to, user_router }
+    /// 行情接口（供行情组播接入写入）
+    pub fn tickers(&self) -> Arc<TickerHandler> {
+        self.tickers.clone()
+    }
+
+    /// 订单簿热力图推送（供行情组播接入写入）
+    pub fn heatmap(&self) -> Arc<HeatmapStream> {
+        self.heatmap.clone()
+    }
+
+    /// 把行情、热力图、大宗成交、询价与系统状态推送并入 WebSocket 推送总线，
+    /// 并启动热力图采样与询价结算定时器
+    pub fn spawn_websocket_feeds(&self) -> std::io::Result<()> {
+        let tickers = &self.tickers;
+        self.websocket.spawn_forward("bookTicker", tickers.book_ticker_stream().subscribe())?;
+        self.websocket
+            .spawn_forward("syntheticTicker", tickers.synthetic_ticker_stream().subscribe())?;
+        self.websocket.spawn_forward("heatmap", self.heatmap.subscribe())?;
+        self.heatmap.spawn_poll()?;
+        self.websocket.spawn_forward("blockTrade", self.block_trades.stream().subscribe())?;
+        self.websocket.spawn_forward("rfq", self.rfq.stream().subscribe())?;
+        self.rfq.spawn_settle(DEFAULT_SETTLE_INTERVAL)?;
+        self.websocket.spawn_forward("systemStatus", self.degradation.stream().subscribe())?;
+        Ok(())
+    }
+
+    /// 排行榜统计投影（交给 `prep::adaptor::ProjectionFeed`，由分片输出的事件写入）
+    pub fn leaderboard(&self) -> Arc<RwLock<StatisticsProjection>> {
+        self.leaderboard.stats()
+    }
+
+    /// 合约引擎运行统计注册表（撮合分片按交易对注册计数器）
+    pub fn engine_stats(&self) -> Arc<EngineStatsRegistry> {
+        self.prep_admin.engine_stats()
+    }
+
+    /// 合约账户查询的读侧投影（交给 `prep::adaptor::ProjectionFeed`，由分片输出的事件写入）
+    pub fn prep_projection(&self) -> Arc<RwLock<ReadModelProjection>> {
+        self.prep_account.projection()
     }
 
     /// 解析 HTTP 请求并提取路径和用户ID
@@ -83,8 +451,8 @@ impl HttpProxyApp {
                             user_id = UserIdExtractor::extract_from_headers(&header_str);
                         }
 
-                        // 如果是 POST 请求，尝试从请求体提取用户ID
-                        if user_id.is_none() && parts[0] == "POST" {
+                        // POST 请求读取完整请求体，未解析出用户ID时从请求体提取
+                        if parts[0] == "POST" {
                             // 检查 Content-Length
                             if let Some(content_length) = Self::extract_content_length(&header_str)
                             {
@@ -101,7 +469,7 @@ impl HttpProxyApp {
                                 }
 
                                 // 提取请求体
-                                if buffer.len() >= header_size {
+                                if user_id.is_none() && buffer.len() >= header_size {
                                     let body = &buffer[header_size..];
                                     user_id = UserIdExtractor::extract_from_json(body);
                                 }
@@ -142,9 +510,22 @@ impl HttpProxyApp {
         path.starts_with("/api/spot/v2/") || path.starts_with("/api/spot/user/data")
     }
 
-    async fn duplex(&self, mut server_session: Stream, mut client_session: Stream) {
+    /// 双工转发；带记账凭据时在后端响应的状态行后插入已用权重头，连接关闭时补记响应字节数
+    async fn duplex(
+        &self,
+        mut server_session: Stream,
+        mut client_session: Stream,
+        usage: Option<UsageTicket>,
+    ) {
         let mut upstream_buf = [0; 1024];
         let mut downstream_buf = [0; 1024];
+        let mut weight_header_pending = usage.is_some();
+        let mut bytes_out = 0;
+        let record_response = |bytes_out: usize| {
+            if let Some(ticket) = &usage {
+                self.api_usage.record_response(ticket, bytes_out);
+            }
+        };
         loop {
             let downstream_read = server_session.read(&mut upstream_buf);
             let upstream_read = client_session.read(&mut downstream_buf);
@@ -156,10 +537,12 @@ impl HttpProxyApp {
             match event {
                 DuplexEvent::DownstreamRead(0) => {
                     debug!("Downstream session closing");
+                    record_response(bytes_out);
                     return;
                 }
                 DuplexEvent::UpstreamRead(0) => {
                     debug!("Upstream session closing");
+                    record_response(bytes_out);
                     return;
                 }
                 DuplexEvent::DownstreamRead(n) => {
@@ -167,8 +550,28 @@ impl HttpProxyApp {
                     client_session.flush().await.unwrap();
                 }
                 DuplexEvent::UpstreamRead(n) => {
-                    server_session.write_all(&downstream_buf[0..n]).await.unwrap();
-                    server_session.flush().await.unwrap();
+                    bytes_out += n;
+                    let written = match usage.as_ref().filter(|_| weight_header_pending) {
+                        Some(ticket) => {
+                            weight_header_pending = false;
+                            let chunk = insert_header(
+                                &downstream_buf[0..n],
+                                USED_WEIGHT_HEADER,
+                                &ticket.used_weight_1m.to_string(),
+                            );
+                            server_session.write_all(&chunk).await
+                        }
+                        None => server_session.write_all(&downstream_buf[0..n]).await,
+                    };
+                    let flushed = match written {
+                        Ok(()) => server_session.flush().await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = flushed {
+                        debug!("Downstream write failed: {}", e);
+                        record_response(bytes_out);
+                        return;
+                    }
                 }
             }
         }
@@ -191,6 +594,215 @@ impl ServerApp for HttpProxyApp {
             }
         };
 
+        // 公开元数据与行情接口由网关直接应答
+        let method = if request_data.starts_with(b"GET ") {
+            "GET"
+        } else if request_data.starts_with(b"POST ") {
+            "POST"
+        } else {
+            ""
+        };
+
+        // WebSocket：握手鉴权由推送服务完成（API Key 挑战签名与 REST 签名格式不同），
+        // 连接保持到断开
+        if WebSocketGateway::matches(method, &path) {
+            info!("🔌 WebSocket upgrade {}", path);
+            self.websocket.serve(io, &request_data).await;
+            return None;
+        }
+
+        // 鉴权得到的账户：只来自有效的 JWT 或 API Key 签名，请求头中的用户ID不算
+        let mut authenticated = None;
+
+        // 浏览器会话：携带 JWT 的请求以令牌中的账户为准，令牌无效时拒绝
+        let user_id_opt = if SessionAuth::matches(method, &path) {
+            user_id_opt
+        } else {
+            match self.sessions.authenticate(&request_data, self.sessions.now_ms()) {
+                Ok(Some(claims)) => {
+                    authenticated = Some(claims.sub.to_string());
+                    authenticated.clone()
+                }
+                Ok(None) => user_id_opt,
+                Err(e) => {
+                    warn!("🚫 Bearer token rejected for {}: {}", path, e);
+                    let body = serde_json::json!({ "msg": e.to_string() }).to_string();
+                    if let Err(e) = io.write_all(&json_response(401, &body)).await {
+                        warn!("Failed to write denial response: {}", e);
+                    }
+                    return None;
+                }
+            }
+        };
+
+        // API Key 签名请求：校验签名、时间窗口与 nonce，通过后以 Key 所属账户为准
+        let mut signed = false;
+        let user_id_opt = match self.signed.authenticate(method, &path, &request_data) {
+            Ok(Some(account_id)) => {
+                signed = true;
+                authenticated = Some(account_id.0.to_string());
+                authenticated.clone()
+            }
+            Ok(None) => user_id_opt,
+            Err(e) => {
+                warn!("🚫 Signed request rejected for {}: {}", path, e);
+                if let Err(e) = io.write_all(&e.reject_response()).await {
+                    warn!("Failed to write denial response: {}", e);
+                }
+                return None;
+            }
+        };
+
+        // 代账户操作：只接受已鉴权的用户，授权后按目标账户处理，并写入实际操作用户
+        let (authenticated, request_data) =
+            match self.delegation.admit(method, request_data, authenticated) {
+                Ok(admitted) => admitted,
+                Err(response) => {
+                    warn!("🚫 Delegated access denied: {}", path);
+                    if let Err(e) = io.write_all(&response).await {
+                        warn!("Failed to write denial response: {}", e);
+                    }
+                    return None;
+                }
+            };
+        let user_id_opt = authenticated.clone().or(user_id_opt);
+
+        // 委托入口：价格与数量按产品精度规范化，不合法直接拒绝
+        let request_data = match self.orders.normalize(method, &path, request_data) {
+            Ok(request_data) => request_data,
+            Err(response) => {
+                warn!("⛔ Order rejected by normalization: {}", path);
+                if let Err(e) = io.write_all(&response).await {
+                    warn!("Failed to write normalization response: {}", e);
+                }
+                return None;
+            }
+        };
+
+        // 调用量记账：只记鉴权账户，签名通过的请求按 Key 细分，已用权重随响应返回
+        let api_key = std::str::from_utf8(&request_data)
+            .ok()
+            .filter(|_| signed)
+            .and_then(|request| header_value(request, "X-Api-Key"))
+            .map(str::to_string);
+        let usage = self.api_usage.record(
+            authenticated.as_deref(),
+            api_key.as_deref(),
+            method,
+            &path,
+            request_data.len(),
+        );
+
+        // 降级中的交易对：受限的新委托不转发，直接返回降级原因
+        if let Err(response) = self.degradation.check_order(method, &path, &request_data) {
+            warn!("⛔ Order rejected by symbol degradation: {}", path);
+            if let Err(e) = io.write_all(&response).await {
+                warn!("Failed to write degradation response: {}", e);
+            }
+            return None;
+        }
+
+        // 暂停、冻结的账户：新委托不转发
+        if let Err(response) = self.account_status.check(method, &path, user_id_opt.as_deref()) {
+            warn!("⛔ Order rejected by account status: {}", path);
+            if let Err(e) = io.write_all(&response).await {
+                warn!("Failed to write account status response: {}", e);
+            }
+            return None;
+        }
+
+        let local_response = if SessionAuth::matches(method, &path) {
+            Some(self.sessions.respond(&path, &request_data))
+        } else if ServerTimeHandler::matches(method, &path) {
+            Some(self.server_time.respond())
+        } else if ApiKeyHandler::matches(method, &path) {
+            Some(self.api_keys.respond(method, &path, &request_data, authenticated.as_deref()))
+        } else if PayloadKeyHandler::matches(method, &path) {
+            Some(self.payload_keys.respond(method, &path, &request_data, authenticated.as_deref()))
+        } else if ApiUsageHandler::matches(method, &path) {
+            Some(self.api_usage.respond(&path, &request_data, authenticated.as_deref()))
+        } else if DegradationHandler::matches(method, &path) {
+            Some(self.degradation.respond(&path, &request_data))
+        } else if AccountStatusGate::matches(method, &path) {
+            Some(self.account_status.respond(&request_data))
+        } else if EntitlementHandler::matches(method, &path) {
+            Some(self.entitlements.respond(method, &path, &request_data))
+        } else if PrepAdminHandler::matches(method, &path) {
+            Some(self.prep_admin.respond(&path, &request_data))
+        } else if ExchangeInfoHandler::matches(method, &path) {
+            Some(self.exchange_info.respond(&path))
+        } else if TradesHandler::matches(method, &path) {
+            Some(self.trades.respond(&path))
+        } else if KlinesHandler::matches(method, &path) {
+            Some(self.klines.respond(&path))
+        } else if TickerHandler::matches(method, &path) {
+            let accept = std::str::from_utf8(&request_data)
+                .ok()
+                .and_then(|request| header_value(request, "Accept"));
+            Some(self.tickers.respond_as(&path, accept))
+        } else if DustHandler::matches(method, &path) {
+            Some(self.dust.respond(&path, authenticated.as_deref()))
+        } else if CostBasisHandler::matches(method, &path) {
+            Some(self.cost_basis.respond(&path, authenticated.as_deref()))
+        } else if PrepHistoryHandler::matches(method, &path) {
+            Some(self.prep_history.respond(&path))
+        } else if LeaderboardHandler::matches(method, &path) {
+            Some(self.leaderboard.respond(&path))
+        } else if PrepAccountHandler::matches(method, &path) {
+            Some(self.prep_account.respond(&path, authenticated.as_deref()))
+        } else if PrepRecurringHandler::matches(method, &path) {
+            let body = request_body(&request_data);
+            Some(self.prep_recurring.respond(method, &path, body, authenticated.as_deref()))
+        } else if PrepScheduleHandler::matches(method, &path) {
+            let body = request_body(&request_data);
+            Some(self.prep_schedule.respond(&path, body, authenticated.as_deref()))
+        } else if PrepQuoteHandler::matches(method, &path) {
+            let body = request_body(&request_data);
+            Some(self.prep_quotes.respond(&path, body, authenticated.as_deref()))
+        } else if AccountActivityHandler::matches(method, &path) {
+            Some(self.activity.respond(&path, authenticated.as_deref()))
+        } else if AlgoTcaHandler::matches(method, &path) {
+            Some(self.algo_tca.respond(&path, authenticated.as_deref()))
+        } else if BlockTradeHandler::matches(method, &path) {
+            let body = request_body(&request_data);
+            Some(self.block_trades.respond(method, &path, body, authenticated.as_deref()))
+        } else if RfqHandler::matches(method, &path) {
+            let body = request_body(&request_data);
+            Some(self.rfq.respond(&path, body, authenticated.as_deref()))
+        } else {
+            None
+        };
+        // 大响应：附加 ETag，按 If-None-Match 应答 304，按 Accept-Encoding 压缩
+        let local_response = local_response.map(|response| {
+            if ResponseCompression::applies(method, &path) {
+                self.compression.apply(&request_data, response)
+            } else {
+                response
+            }
+        });
+        let local_response = match (local_response, &usage) {
+            (Some(response), Some(ticket)) => Some(insert_header(
+                &response,
+                USED_WEIGHT_HEADER,
+                &ticket.used_weight_1m.to_string(),
+            )),
+            (local_response, _) => local_response,
+        };
+        if let Some(response) = local_response {
+            if let Some(ticket) = &usage {
+                self.api_usage.record_response(ticket, response.len());
+            }
+            info!("📋 Serving {} locally", path);
+            if let Err(e) = io.write_all(&response).await {
+                warn!("Failed to write local response: {}", e);
+                return None;
+            }
+            if let Err(e) = io.flush().await {
+                warn!("Failed to flush local response: {}", e);
+            }
+            return None;
+        }
+
         // 根据路径决定是否使用用户路由
         let target_peer = if Self::needs_user_routing(&path) {
             if let Some(user_id) = user_id_opt.as_ref() {
@@ -225,7 +837,7 @@ impl ServerApp for HttpProxyApp {
                 }
 
                 // 进入双工转发模式
-                self.duplex(io, client_session).await;
+                self.duplex(io, client_session, usage).await;
                 None
             }
             Err(e) => {
@@ -258,17 +870,157 @@ impl HttpProxyServer {
         let mut server = Server::new(opt).unwrap();
         server.bootstrap();
 
-        // 配置用户路由
+        // 时钟同步：启动时偏差超过上限则拒绝启动，之后持续监控
+        let time_sync = TimeSyncConfig::from_env()
+            .unwrap_or_else(|e| panic!("refusing to start: {}", e))
+            .map(|config| Arc::new(TimeSyncMonitor::sntp(config)));
+        if let Some(sync) = &time_sync {
+            let status =
+                sync.check_startup().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+            info!("⏱️  Clock offset {:?}ms vs {}", status.offset_ms, status.source);
+            sync.clone().spawn().expect("failed to spawn time sync thread");
+        }
+
+        // API Key：配置文件路径时持久化，配置管理令牌时开放管理接口
+        let api_keys =
+            ApiKeyHandler::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        if let Some(api_keys) = &api_keys {
+            ApiKeyHandler::spawn_flush(api_keys.store().clone(), Duration::from_secs(60))
+                .expect("failed to spawn API key flush thread");
+        }
+
+        // 报告签名密钥：配置种子时跨重启保持签名公钥不变
+        let payload_keys =
+            PayloadKeyHandler::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+
+        // 浏览器会话：未配置签名密钥时使用随机密钥，登录关闭，令牌在重启后失效
+        let sessions =
+            SessionAuth::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        if sessions.is_none() {
+            warn!("GATEWAY_JWT_SECRET not set: login disabled, sessions use a random signing key");
+        }
+
+        // 调用量统计：配置管理令牌时开放管理控制台接口
+        let api_usage = ApiUsageHandler::from_env();
+
+        // 交易对降级：配置管理令牌时开放标记接口（接入健康检查、模拟局部故障）
+        let degradation = DegradationHandler::from_env();
+
+        // 合约引擎管理：配置管理令牌时开放，命令经撮合分片的发送端提交
+        let prep_admin = PrepAdminHandler::from_env();
+
+        // 账户状态：配置管理令牌时开放暂停、冻结与恢复接口
+        let account_status = AccountStatusGate::from_env();
+
+        // 行情权限：配置管理令牌时开放授予与收回接口
+        let entitlements = EntitlementHandler::from_env();
+        api_usage
+            .spawn_prune(Duration::from_secs(60))
+            .expect("failed to spawn API usage prune thread");
+
+        // 上线产品与限频规则：未配置时 exchangeInfo 为空，新委托因产品未知被拒绝
+        let exchange_info =
+            ExchangeInfoConfig::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        if exchange_info.is_none() {
+            warn!("GATEWAY_EXCHANGE_INFO_FILE not set: no instruments listed");
+        }
+
+        // 大响应压缩阈值
+        let compression =
+            ResponseCompression::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+
+        // 配置用户路由：静态配置，启用集群发现后由引擎分片成员动态更新
         let user_route_config = UserRouteConfig::default();
+        let user_router = Arc::new(UserRouter::new(user_route_config.clone()));
+        let discovery = match DiscoveryConfig::from_env() {
+            Ok(discovery) => discovery,
+            Err(e) => {
+                warn!("Cluster discovery disabled: {}", e);
+                None
+            }
+        };
+        let discovery_enabled = discovery.is_some();
+        if let Some(discovery) = discovery {
+            spawn_discovery(discovery, user_router.clone(), user_route_config.clone())
+                .expect("failed to spawn cluster discovery thread");
+        }
 
         // 配置代理服务：监听 8080 端口
+        let mut app = HttpProxyApp::with_user_router(
+            HttpPeer::new("127.0.0.1:3001", false, "localhost".to_string()),
+            user_router,
+        );
+        if let Some(sync) = time_sync {
+            app = app.with_time_sync(sync);
+        }
+        if let Some(api_keys) = api_keys {
+            app = app.with_api_keys(api_keys);
+        }
+        if let Some(sessions) = sessions {
+            app = app.with_sessions(sessions);
+        }
+        app = app
+            .with_payload_keys(payload_keys)
+            .with_compression(compression)
+            .with_api_usage(api_usage)
+            .with_degradation(degradation)
+            .with_prep_admin(prep_admin)
+            .with_account_status(account_status)
+            .with_entitlements(entitlements);
+        if let Some(exchange_info) = exchange_info {
+            info!("📋 Listed {} instruments", exchange_info.registry.len());
+            app = app.with_exchange_info(exchange_info);
+        }
+        // 结算导出：配置 Kafka brokers 时入账的大宗成交结算写入账本 topic
+        let settlement_sink = KafkaSettlementSinkConfig::from_env()
+            .unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        if let Some(config) = settlement_sink {
+            let sink = KafkaSettlementSink::new(&config)
+                .unwrap_or_else(|e| panic!("refusing to start: {}", e));
+            let export = SettlementExportConfig::default();
+            info!("🧾 Settlement export to {} via {}", export.topic, config.brokers);
+            app = app.with_settlement_export(SettlementExporter::new(export, Box::new(sink)));
+        }
+        // 回填K线：交易对须已上线，未上线或文件无效时拒绝启动
+        if let Some(path) = candle_file_from_env() {
+            let registry =
+                app.exchange_info.registry().read().unwrap_or_else(PoisonError::into_inner);
+            let count = app
+                .klines
+                .history()
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .load(&path, &registry)
+                .unwrap_or_else(|e| panic!("refusing to start: {}", e));
+            info!("🕯️  Loaded {} backfilled candles from {}", count, path);
+        }
+        // 交割合约到期后停止接收新委托
+        app.exchange_info
+            .spawn_expiry_halt(Duration::from_secs(1))
+            .expect("failed to spawn expiry halt thread");
+
+        // 行情组播：配置组播组时接入引擎行情，驱动 bookTicker、avgPrice、最近成交接口与热力图推送
+        let market_feed =
+            MarketFeedConfig::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        if let Some(config) = market_feed {
+            let feed = MarketFeed::new(app.tickers())
+                .with_trades(app.trades.recent_trades())
+                .with_candles(app.klines.history())
+                .with_heatmap(app.heatmap());
+            spawn_market_feed(config, feed)
+                .unwrap_or_else(|e| panic!("failed to join market feed {}: {}", config.group, e));
+            info!("📡 Market data feed joined {}", config.group);
+        }
+
+        // WebSocket 推送：令牌密钥、Origin 白名单与续传存储由环境变量配置
+        let websocket =
+            WebSocketGateway::from_env().unwrap_or_else(|e| panic!("refusing to start: {}", e));
+        app = app.with_websocket(websocket);
+        app.spawn_websocket_feeds().expect("failed to spawn WebSocket feed threads");
         let proxy_service = Service::with_listeners(
             "HTTP Proxy Service".to_string(),
             Listeners::tcp("0.0.0.0:8080"),
-            HttpProxyApp::with_router(
-                HttpPeer::new("127.0.0.1:3001", false, "localhost".to_string()),
-                user_route_config.clone(),
-            ),
+            app,
         );
 
         info!("🚀 Pingora HTTP proxy started at http://localhost:8080");
@@ -277,13 +1029,94 @@ impl HttpProxyServer {
         info!("   - /api/spot/v2/*");
         info!("   - /api/spot/user/data");
         info!("");
-        info!("👥 User routing configuration:");
+        if discovery_enabled {
+            info!("👥 User routing: discovered from cluster engine shards (static fallback)");
+        } else {
+            info!("👥 User routing configuration:");
+        }
         for (partition, ips) in &user_route_config.partition_ips {
             info!("   - {} → {:?}", partition, ips);
         }
         info!("");
+        info!(
+            "🗜️  Compression (zstd/gzip/deflate) and ETag on exchangeInfo, trades, funding rate and mark price klines"
+        );
         info!("💹 Available routes:");
         info!("  - GET  /api/spot/health");
+        info!("  - GET  /ws?token=&sessionId=&lastSequence= [WebSocket, served by gateway]");
+        info!(
+            "  - GET  /ws?...&cancelOnDisconnect=true [WebSocket, pulls prep quotes on disconnect]"
+        );
+        info!("  - GET  /api/time [served by gateway]");
+        info!("  - GET  /api/apiKeys [served by gateway]");
+        info!("  - POST /api/apiKeys (JSON) [served by gateway]");
+        info!("  - POST /api/apiKeys/rotate (JSON) [served by gateway]");
+        info!("  - POST /api/apiKeys/revoke (JSON) [served by gateway]");
+        info!("  - GET  /api/admin/apiKeys?accountId= [X-Admin-Token]");
+        info!("  - POST /api/admin/apiKeys (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/apiKeys/revoke (JSON) [X-Admin-Token]");
+        info!("  - GET  /api/payloadKeys [served by gateway]");
+        info!("  - POST /api/payloadKeys/encryption (JSON) [served by gateway]");
+        info!("  - POST /api/payloadKeys/encryption/remove [served by gateway]");
+        info!("  - POST /api/admin/payloadKeys/rotate [X-Admin-Token]");
+        info!("  - GET  /api/account/apiUsage?window= [served by gateway]");
+        info!("  - GET  /api/admin/apiUsage?window=&accountId= [X-Admin-Token]");
+        info!("  - GET  /api/systemStatus [served by gateway]");
+        info!("  - POST /api/admin/degradation (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/degradation/clear (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/account/status (JSON) [X-Admin-Token]");
+        info!("  - GET  /api/admin/entitlements?apiKey= [X-Admin-Token]");
+        info!("  - POST /api/admin/entitlements (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/prep/tradeBust (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/prep/riskProfile (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/prep/killSwitch/reset (JSON) [X-Admin-Token]");
+        info!("  - POST /api/admin/prep/featureFlag (JSON) [X-Admin-Token]");
+        info!("  - GET  /api/admin/engine/stats [X-Admin-Token]");
+        info!("  - GET  /api/exchangeInfo [served by gateway]");
+        info!("  - GET  /api/asset/dust/preview?target= [served by gateway]");
+        info!("  - GET  /api/asset/costBasis?asset= [served by gateway, authenticated]");
+        info!("  - GET  /api/spot/trades?symbol=&limit= [served by gateway]");
+        info!("  - GET  /api/spot/historicalTrades?symbol=&fromId=&limit= [served by gateway]");
+        info!(
+            "  - GET  /api/spot/klines?symbol=&interval=&startTime=&endTime=&limit= [served by gateway]"
+        );
+        info!("  - GET  /api/spot/ticker/24hr?symbol= [served by gateway]");
+        info!("  - GET  /api/spot/avgPrice?symbol= [served by gateway]");
+        info!("  - GET  /api/spot/bookTicker?symbol= [served by gateway]");
+        info!(
+            "  - GET  /api/prep/fundingRate?symbol=&startTime=&endTime=&limit= [served by gateway]"
+        );
+        info!(
+            "  - GET  /api/prep/markPriceKlines?symbol=&interval=&startTime=&endTime=&limit= [served by gateway]"
+        );
+        info!(
+            "  - GET  /api/prep/leaderboard?period=&metric=&time=&page=&pageSize= [served by gateway]"
+        );
+        info!("  - GET  /api/account [served by gateway, authenticated]");
+        info!("  - GET  /api/prep/positionRisk [served by gateway, authenticated]");
+        info!("  - GET  /api/prep/order/queuePosition?orderId= [served by gateway, authenticated]");
+        info!(
+            "  - GET  /api/prep/account/settingHistory?limit= [served by gateway, authenticated]"
+        );
+        info!("  - GET  /api/prep/recurring [served by gateway, authenticated]");
+        info!(
+            "  - GET  /api/prep/recurring/history?planId=&limit= [served by gateway, authenticated]"
+        );
+        info!("  - POST /api/prep/recurring (JSON) [authenticated]");
+        info!("  - POST /api/prep/recurring/pause|resume|cancel (JSON) [authenticated]");
+        info!("  - POST /api/prep/order/schedule (JSON) [authenticated]");
+        info!("  - POST /api/prep/order/schedule/cancel (JSON) [authenticated]");
+        info!("  - POST /api/prep/massQuote (JSON) [authenticated]");
+        info!("  - POST /api/prep/mmp (JSON) [authenticated]");
+        info!("  - POST /api/prep/mmp/reset [authenticated]");
+        info!("  - GET  /api/account/activity?category=&asset=&fromId=&limit= [served by gateway]");
+        info!("  - GET  /api/algo/tca?parentOrderId= [served by gateway]");
+        info!("  - GET  /api/spot/blockTrade?reportId= [served by gateway]");
+        info!("  - POST /api/spot/blockTrade (JSON) [served by gateway]");
+        info!("  - POST /api/spot/blockTrade/affirm (JSON) [served by gateway]");
+        info!("  - POST /api/spot/rfq (JSON) [served by gateway]");
+        info!("  - POST /api/spot/rfq/quote (JSON) [served by gateway]");
+        info!("  - POST /api/spot/rfq/cancel (JSON) [served by gateway]");
         info!("  - POST /api/spot/order/ (JSON)");
         info!("  - POST /api/spot/v2/ (JSON) [user routing]");
         info!("  - POST /api/spot/market/data (JSON)");
@@ -0,0 +1,329 @@
+//! 现货K线与 24h 成交量接口
+//!
+//! - `GET /api/spot/klines?symbol=&interval=&startTime=&endTime=&limit=`
+//! - `GET /api/spot/ticker/24hr?symbol=`
+//!
+//! K线与滚动成交量由行情组播的成交通道实时聚合（见
+//! [`MarketFeed::with_candles`](super::market_feed::MarketFeed::with_candles)）；
+//! 启动时可加载 `trade_backfill` 导出的K线文件（`GATEWAY_CANDLE_FILE`），文件中的交易对
+//! 须已在产品注册表上线，1 分钟K线同时种子化 24h 成交量
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::TradingPair;
+use base_types::instrument::registry::InstrumentRegistry;
+use base_types::mark_data::spot::candle::{Candle, CandleInterval, CandleStore};
+use base_types::mark_data::spot::level_types::{SymbolId, TradeEvent};
+use base_types::mark_data::spot::rolling_volume::RollingVolume;
+use base_types::{SystemClock, TimestampProvider};
+
+use super::exchange_info::{json_response, query_param};
+
+/// K线接口路径
+pub const KLINES_PATH: &str = "/api/spot/klines";
+/// 24h 行情接口路径
+pub const TICKER_24HR_PATH: &str = "/api/spot/ticker/24hr";
+
+const ENV_CANDLE_FILE: &str = "GATEWAY_CANDLE_FILE";
+
+const NANOS_PER_MILLI: u64 = 1_000_000;
+const MINUTE_NS: u64 = 60_000_000_000;
+const DAY_NS: u64 = 86_400_000_000_000;
+
+/// 每个 (交易对, 周期) 保留的K线数
+const CANDLE_RETENTION: usize = 10_000;
+const DEFAULT_LIMIT: usize = 500;
+const MAX_LIMIT: usize = 1000;
+
+/// K线与 24h 滚动成交量
+#[derive(Debug)]
+pub struct CandleHistory {
+    candles: CandleStore,
+    volumes: HashMap<SymbolId, RollingVolume>,
+}
+
+impl Default for CandleHistory {
+    fn default() -> Self {
+        Self {
+            candles: CandleStore::new(&CandleInterval::ALL).with_retention(CANDLE_RETENTION),
+            volumes: HashMap::new(),
+        }
+    }
+}
+
+impl CandleHistory {
+    pub fn candles(&self) -> &CandleStore {
+        &self.candles
+    }
+
+    /// 处理一笔成交
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        self.candles.on_trade(trade);
+        self.volume(trade.symbol_id).on_trade(trade);
+    }
+
+    /// 加载 `trade_backfill` 导出的K线，返回K线数
+    ///
+    /// 文件含未在 `registry` 上线的交易对时整个文件不加载
+    pub fn load_csv<R: BufRead>(
+        &mut self,
+        reader: R,
+        registry: &InstrumentRegistry,
+    ) -> Result<usize, String> {
+        let mut loaded = CandleStore::new(&CandleInterval::ALL);
+        let count = loaded.load_csv(reader).map_err(|e| e.to_string())?;
+        if let Some(unknown) =
+            loaded.symbol_ids().into_iter().find(|&symbol_id| registry.by_id(symbol_id).is_none())
+        {
+            return Err(format!("symbol id {} is not listed", unknown));
+        }
+        for symbol_id in loaded.symbol_ids() {
+            for interval in CandleInterval::ALL {
+                for candle in loaded.candles(symbol_id, interval, 0, u64::MAX) {
+                    if interval == CandleInterval::Minute1 {
+                        self.volume(symbol_id).seed(candle);
+                    }
+                    self.candles.insert(*candle);
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// 加载K线文件
+    pub fn load(&mut self, path: &str, registry: &InstrumentRegistry) -> Result<usize, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        self.load_csv(BufReader::new(file), registry).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn volume(&mut self, symbol_id: SymbolId) -> &mut RollingVolume {
+        self.volumes.entry(symbol_id).or_insert_with(|| RollingVolume::new(DAY_NS, MINUTE_NS))
+    }
+}
+
+/// 行情管道写入成交
+pub fn record_candle_trade(history: &RwLock<CandleHistory>, trade: &TradeEvent) {
+    history.write().unwrap_or_else(PoisonError::into_inner).on_trade(trade);
+}
+
+/// 启动时加载的K线文件，未设置 `GATEWAY_CANDLE_FILE` 时返回 `None`
+pub fn candle_file_from_env() -> Option<String> {
+    std::env::var(ENV_CANDLE_FILE).ok().filter(|path| !path.is_empty())
+}
+
+/// `GET /api/spot/klines` 与 `GET /api/spot/ticker/24hr` 处理器
+pub struct KlinesHandler {
+    history: Arc<RwLock<CandleHistory>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for KlinesHandler {
+    fn default() -> Self {
+        Self {
+            history: Arc::new(RwLock::new(CandleHistory::default())),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl KlinesHandler {
+    /// 24h 窗口使用的时钟
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 行情管道写入成交与启动加载的入口
+    pub fn history(&self) -> Arc<RwLock<CandleHistory>> {
+        self.history.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "GET" && (route == Some(KLINES_PATH) || route == Some(TICKER_24HR_PATH))
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        let (status, body) = self.render(path);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    ///
+    /// K线参数：`symbol`、`interval`（必填）、`startTime`/`endTime`（毫秒）、
+    /// `limit`（默认 500，最大 1000）；未指定 `startTime` 时返回最近的 `limit` 根
+    fn render(&self, path: &str) -> (u16, String) {
+        let Some(symbol) = query_param(path, "symbol") else {
+            return Self::bad_request("Missing parameter: symbol".to_string());
+        };
+        let Some(pair) = TradingPair::from_symbol_str(symbol) else {
+            return Self::bad_request(format!("Invalid symbol: {}", symbol));
+        };
+        let symbol_id = pair as SymbolId;
+        if path.starts_with(TICKER_24HR_PATH) {
+            return (200, self.ticker_24hr(pair, symbol_id).to_string());
+        }
+
+        let Some(interval) = query_param(path, "interval") else {
+            return Self::bad_request("Missing parameter: interval".to_string());
+        };
+        let Some(interval) = CandleInterval::parse(interval) else {
+            return Self::bad_request(format!("Invalid interval: {}", interval));
+        };
+        let mut times = [None, None];
+        for (slot, name) in times.iter_mut().zip(["startTime", "endTime"]) {
+            *slot = match query_param(path, name).map(str::parse::<u64>) {
+                None => None,
+                Some(Ok(millis)) => Some(millis.saturating_mul(NANOS_PER_MILLI)),
+                Some(Err(_)) => return Self::bad_request(format!("Invalid parameter: {}", name)),
+            };
+        }
+        let [start, end] = times;
+        // endTime 为毫秒，包含该毫秒内开盘的K线
+        let end = end.map_or(u64::MAX, |end| end.saturating_add(NANOS_PER_MILLI - 1));
+        let limit = match query_param(path, "limit").map(str::parse::<usize>) {
+            None => DEFAULT_LIMIT,
+            Some(Ok(limit)) => limit.clamp(1, MAX_LIMIT),
+            Some(Err(_)) => return Self::bad_request("Invalid parameter: limit".to_string()),
+        };
+
+        let history = self.history.read().unwrap_or_else(PoisonError::into_inner);
+        let candles: Vec<&Candle> =
+            history.candles.candles(symbol_id, interval, start.unwrap_or(0), end).collect();
+        let page = match start {
+            Some(_) => &candles[..candles.len().min(limit)],
+            None => &candles[candles.len().saturating_sub(limit)..],
+        };
+        let rows: Vec<serde_json::Value> =
+            page.iter().map(|candle| Self::kline_json(candle)).collect();
+        (200, serde_json::Value::Array(rows).to_string())
+    }
+
+    /// 最近 24h 的成交量、成交额与笔数，最新价取最近一根 1 分钟K线的收盘价
+    fn ticker_24hr(&self, pair: TradingPair, symbol_id: SymbolId) -> serde_json::Value {
+        let now = self.clock.now().0;
+        let mut history = self.history.write().unwrap_or_else(PoisonError::into_inner);
+        let last_price = history
+            .candles
+            .latest(symbol_id, CandleInterval::Minute1)
+            .map(|candle| candle.close.to_string());
+        let totals = history.volume(symbol_id).totals(now);
+        serde_json::json!({
+            "symbol": pair.to_symbol_string(),
+            "lastPrice": last_price,
+            "volume": totals.volume.to_string(),
+            "quoteVolume": totals.quote_volume.to_string(),
+            "count": totals.trade_count,
+            "openTime": now.saturating_sub(DAY_NS) / NANOS_PER_MILLI,
+            "closeTime": now / NANOS_PER_MILLI,
+        })
+    }
+
+    /// 币安格式：[开盘时间, 开, 高, 低, 收, 成交量, 收盘时间, 成交额, 笔数]
+    fn kline_json(candle: &Candle) -> serde_json::Value {
+        serde_json::json!([
+            candle.open_time / NANOS_PER_MILLI,
+            candle.open.to_string(),
+            candle.high.to_string(),
+            candle.low.to_string(),
+            candle.close.to_string(),
+            candle.volume.to_string(),
+            candle.close_time() / NANOS_PER_MILLI - 1,
+            candle.quote_volume.to_string(),
+            candle.trade_count,
+        ])
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::instrument::normalize::InstrumentScale;
+    use base_types::instrument::registry::InstrumentSpec;
+    use base_types::{AssetId, InstrumentType, ManualClock, OrderSide, Price, Quantity, Timestamp};
+
+    use super::*;
+
+    fn trade(timestamp: u64, price: f64) -> TradeEvent {
+        TradeEvent {
+            symbol_id: TradingPair::BtcUsdt as SymbolId,
+            timestamp,
+            sequence: 0,
+            trade_id: 0,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(price),
+            quantity: Quantity::from_f64(1.0),
+            aggressor_side: OrderSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_backfill_load_and_live_trades() {
+        let mut backfill = CandleStore::new(&CandleInterval::ALL);
+        backfill.on_trade(&trade(10 * MINUTE_NS, 100.0));
+        backfill.on_trade(&trade(11 * MINUTE_NS, 102.0));
+        let mut file = Vec::new();
+        backfill.write_csv(&mut file).unwrap();
+
+        let clock = ManualClock::from_millis(0);
+        let handler = KlinesHandler::default().with_clock(Arc::new(clock.clone()));
+        let history = handler.history();
+        let mut registry = InstrumentRegistry::new();
+        // 未上线的交易对：整个文件拒绝
+        assert!(history.write().unwrap().load_csv(file.as_slice(), &registry).is_err());
+        assert!(history.read().unwrap().candles().is_empty());
+        registry
+            .register(InstrumentSpec::new(
+                TradingPair::BtcUsdt as u32,
+                "BTCUSDT",
+                InstrumentType::Spot,
+                AssetId::Btc,
+                AssetId::Usdt,
+                InstrumentScale::new(2, 6),
+            ))
+            .unwrap();
+        assert_eq!(history.write().unwrap().load_csv(file.as_slice(), &registry).unwrap(), 7);
+
+        // 实时成交接续回填
+        record_candle_trade(&history, &trade(12 * MINUTE_NS, 101.0));
+        assert!(KlinesHandler::matches("GET", "/api/spot/klines?symbol=BTCUSDT&interval=1m"));
+        assert!(!KlinesHandler::matches("POST", KLINES_PATH));
+        let (status, body) = handler.render("/api/spot/klines?symbol=BTCUSDT&interval=1m&limit=2");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0][0], 11 * 60_000);
+        assert_eq!(json[1][4], Price::from_f64(101.0).to_string());
+        assert_eq!(json[1][6], 13 * 60_000 - 1);
+
+        let (_, body) = handler.render("/api/spot/klines?symbol=BTCUSDT&interval=5m&startTime=0");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0][8], 3);
+
+        clock.set(Timestamp(13 * MINUTE_NS));
+        let (status, body) = handler.render("/api/spot/ticker/24hr?symbol=BTCUSDT");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["count"], 3);
+        assert_eq!(json["volume"], Quantity::from_f64(3.0).to_string());
+        assert_eq!(json["lastPrice"], Price::from_f64(101.0).to_string());
+        // 一天后滑出窗口
+        clock.set(Timestamp(13 * MINUTE_NS + DAY_NS));
+        let (_, body) = handler.render("/api/spot/ticker/24hr?symbol=BTCUSDT");
+        assert!(body.contains("\"count\":0"));
+
+        assert_eq!(handler.render("/api/spot/klines?symbol=BTCUSDT").0, 400);
+        assert_eq!(handler.render("/api/spot/klines?symbol=BTCUSDT&interval=2m").0, 400);
+        assert_eq!(handler.render("/api/spot/ticker/24hr?symbol=XYZ").0, 400);
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::{SystemClock, TimestampProvider};
+use prep::domain::service::{LeaderboardMetric, StatisticsProjection, StatsPeriod};
+
+use super::exchange_info::{json_response, query_param};
+
+/// 排行榜接口路径
+pub const LEADERBOARD_PATH: &str = "/api/prep/leaderboard";
+
+/// 默认每页条数
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// 最大每页条数
+const MAX_PAGE_SIZE: usize = 100;
+
+/// `GET /api/prep/leaderboard` 处理器
+///
+/// 读引擎事件流维护的成交量与盈亏统计投影；只返回主动上榜的账户，
+/// 匿名上榜的条目不含交易者ID
+pub struct LeaderboardHandler {
+    stats: Arc<RwLock<StatisticsProjection>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for LeaderboardHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(StatisticsProjection::new())))
+    }
+}
+
+impl LeaderboardHandler {
+    pub fn new(stats: Arc<RwLock<StatisticsProjection>>) -> Self {
+        Self { stats, clock: Arc::new(SystemClock) }
+    }
+
+    /// 替换时间来源（未指定 `time` 时按当前时间取周期，测试注入 `ManualClock`）
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 引擎事件与上榜设置写入投影的入口
+    pub fn stats(&self) -> Arc<RwLock<StatisticsProjection>> {
+        self.stats.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(LEADERBOARD_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        let (status, body) = self.render(path);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    ///
+    /// 参数：`period`（daily / weekly，默认 daily）、`metric`（volume / pnl，默认 volume）、
+    /// `time`（Unix 毫秒，所在周期，默认当前）、`page`（从 0 起）、`pageSize`（默认 20、最大 100）
+    fn render(&self, path: &str) -> (u16, String) {
+        let now = self.clock.now_millis();
+        let period = match query_param(path, "period") {
+            None | Some("daily") => StatsPeriod::Daily,
+            Some("weekly") => StatsPeriod::Weekly,
+            Some(other) => return Self::bad_request(format!("Invalid period: {}", other)),
+        };
+        let metric = match query_param(path, "metric") {
+            None | Some("volume") => LeaderboardMetric::Volume,
+            Some("pnl") => LeaderboardMetric::Pnl,
+            Some(other) => return Self::bad_request(format!("Invalid metric: {}", other)),
+        };
+        let (time, page, page_size) = match (
+            number_param(path, "time", now),
+            number_param(path, "page", 0),
+            number_param(path, "pageSize", DEFAULT_PAGE_SIZE as u64),
+        ) {
+            (Ok(time), Ok(page), Ok(page_size)) => (time, page as usize, page_size as usize),
+            (Err(msg), _, _) | (_, Err(msg), _) | (_, _, Err(msg)) => {
+                return Self::bad_request(msg);
+            }
+        };
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+
+        let stats = self.stats.read().unwrap_or_else(PoisonError::into_inner);
+        let board = stats.leaderboard(period, time, metric, page, page_size);
+        let entries: Vec<_> = board
+            .entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "rank": entry.rank,
+                    "traderId": entry.trader,
+                    "volume": entry.stats.volume,
+                    "tradeCount": entry.stats.trade_count,
+                    "realizedPnl": entry.stats.realized_pnl,
+                })
+            })
+            .collect();
+        let body = serde_json::json!({
+            "period": query_param(path, "period").unwrap_or("daily"),
+            "metric": query_param(path, "metric").unwrap_or("volume"),
+            "bucket": board.bucket,
+            "page": board.page,
+            "pageSize": board.page_size,
+            "total": board.total,
+            "entries": entries,
+        });
+        (200, body.to_string())
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+/// 数值查询参数，未指定取默认值
+fn number_param(path: &str, name: &str, default: u64) -> Result<u64, String> {
+    match query_param(path, name) {
+        None => Ok(default),
+        Some(value) => value.parse().map_err(|_| format!("Invalid parameter: {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+    use prep::domain::service::LeaderboardVisibility;
+
+    use super::*;
+
+    #[test]
+    fn test_leaderboard_pages() {
+        let clock = Arc::new(ManualClock::from_millis(1000));
+        let handler = LeaderboardHandler::default().with_clock(clock.clone());
+        {
+            let shared = handler.stats();
+            let mut stats = shared.write().unwrap();
+            for trader in 1..=3 {
+                stats.record_settlement(trader, trader as i64 * 10, 1000);
+            }
+            stats.set_visibility(1, LeaderboardVisibility::Public);
+            stats.set_visibility(2, LeaderboardVisibility::Anonymous);
+            stats.set_visibility(3, LeaderboardVisibility::Public);
+        }
+
+        assert!(LeaderboardHandler::matches("GET", "/api/prep/leaderboard?page=1"));
+        assert!(!LeaderboardHandler::matches("POST", LEADERBOARD_PATH));
+
+        let (status, body) = handler.render("/api/prep/leaderboard?metric=pnl&pageSize=2");
+        assert_eq!(status, 200);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["entries"][0]["traderId"], 3);
+        assert!(body["entries"][1]["traderId"].is_null());
+
+        let (_, body) = handler.render("/api/prep/leaderboard?metric=pnl&pageSize=2&page=1");
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["entries"][0]["rank"], 3);
+
+        // 跨过日边界后默认周期为新的一天，显式 `time` 仍可查询前一天
+        clock.advance_millis(86_400_000);
+        let (_, body) = handler.render(LEADERBOARD_PATH);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["total"], 0);
+        let (_, body) = handler.render("/api/prep/leaderboard?time=1000");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["total"], 3);
+
+        assert_eq!(handler.render("/api/prep/leaderboard?period=monthly").0, 400);
+        assert_eq!(handler.render("/api/prep/leaderboard?page=x").0, 400);
+    }
+}
@@ -0,0 +1,360 @@
+//! 行情组播接入
+//!
+//! 撮合引擎经组播发布行情（SBE 帧，一个数据报一条消息），网关加入组播组，
+//! 解码后写入本地行情接口：
+//! - BBO 快速通道 → [`TickerHandler::on_market_data`]：bookTicker 接口与推送、
+//!   合成交叉汇率，以及小额资产兑换使用的指数价
+//! - 成交通道 → 最近成交环（`/api/spot/trades`，见 [`MarketFeed::with_trades`]）、
+//!   K线与 24h 成交量（`/api/spot/klines`，见 [`MarketFeed::with_candles`]）
+//!   与 [`TickerHandler::on_market_data`] 的 avgPrice 窗口
+//! - 逐笔委托通道 → 订单簿热力图推送（`<symbol>@heatmap`，见 [`MarketFeed::with_heatmap`]）
+//!
+//! 通过环境变量启用：
+//! - `GATEWAY_MARKET_FEED_GROUP`: 组播组地址，如 `239.10.0.1:9100`
+//! - `GATEWAY_MARKET_FEED_INTERFACE`: 加入组播组的本机网卡 IPv4 地址（默认 `0.0.0.0`）
+//!
+//! 未配置组播组时不接入，行情接口保持为空。
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+
+use base_types::mark_data::spot::bbo_channel::{BboDecodeError, BboReceiver};
+use base_types::mark_data::spot::level_types::{MarketDataDelta, TradeEvent};
+use base_types::mark_data::spot::order_channel::{OrderDecodeError, OrderReceiver};
+use base_types::mark_data::spot::recent_trades::RecentTrades;
+use base_types::mark_data::spot::trade_channel::{TradeDecodeError, TradeReceiver};
+use tracing::warn;
+
+use super::klines::{CandleHistory, record_candle_trade};
+use super::market_ticker::TickerHandler;
+use super::trades::record_trade;
+use crate::websocket::heatmap::HeatmapStream;
+
+const ENV_GROUP: &str = "GATEWAY_MARKET_FEED_GROUP";
+const ENV_INTERFACE: &str = "GATEWAY_MARKET_FEED_INTERFACE";
+
+/// 单个数据报的最大长度
+const MAX_DATAGRAM: usize = 1500;
+
+/// 行情组播配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketFeedConfig {
+    /// 组播组
+    pub group: SocketAddrV4,
+    /// 接收网卡
+    pub interface: Ipv4Addr,
+}
+
+impl MarketFeedConfig {
+    /// 从环境变量读取，未配置组播组时返回 `None`
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Ok(group) = std::env::var(ENV_GROUP) else {
+            return Ok(None);
+        };
+        let group: SocketAddrV4 =
+            group.parse().map_err(|e| format!("invalid {} {}: {}", ENV_GROUP, group, e))?;
+        if !group.ip().is_multicast() {
+            return Err(format!("{} {} is not a multicast group", ENV_GROUP, group));
+        }
+        let interface = match std::env::var(ENV_INTERFACE) {
+            Ok(interface) => interface
+                .parse()
+                .map_err(|e| format!("invalid {} {}: {}", ENV_INTERFACE, interface, e))?,
+            Err(_) => Ipv4Addr::UNSPECIFIED,
+        };
+        Ok(Some(Self { group, interface }))
+    }
+}
+
+/// 数据报分发
+pub struct MarketFeed {
+    tickers: Arc<TickerHandler>,
+    recent_trades: Option<Arc<RwLock<RecentTrades>>>,
+    candles: Option<Arc<RwLock<CandleHistory>>>,
+    heatmap: Option<Arc<HeatmapStream>>,
+    bbo: BboReceiver,
+    trades: TradeReceiver,
+    orders: OrderReceiver,
+    /// 无法解码的数据报数
+    decode_errors: u64,
+}
+
+impl MarketFeed {
+    pub fn new(tickers: Arc<TickerHandler>) -> Self {
+        Self {
+            tickers,
+            recent_trades: None,
+            candles: None,
+            heatmap: None,
+            bbo: BboReceiver::new(),
+            trades: TradeReceiver::new(),
+            orders: OrderReceiver::new(),
+            decode_errors: 0,
+        }
+    }
+
+    /// 成交写入最近成交环（[`TradesHandler::recent_trades`](super::trades::TradesHandler::recent_trades)）
+    pub fn with_trades(mut self, recent_trades: Arc<RwLock<RecentTrades>>) -> Self {
+        self.recent_trades = Some(recent_trades);
+        self
+    }
+
+    /// 成交聚合K线与 24h 成交量（[`KlinesHandler::history`](super::klines::KlinesHandler::history)）
+    pub fn with_candles(mut self, candles: Arc<RwLock<CandleHistory>>) -> Self {
+        self.candles = Some(candles);
+        self
+    }
+
+    /// 逐笔委托写入热力图推送（未接入时不解码逐笔委托）
+    pub fn with_heatmap(mut self, heatmap: Arc<HeatmapStream>) -> Self {
+        self.heatmap = Some(heatmap);
+        self
+    }
+
+    /// 处理一个数据报
+    pub fn on_datagram(&mut self, datagram: &[u8]) {
+        match self.bbo.on_datagram(datagram) {
+            Ok(Some(event)) => self.tickers.on_market_data(&MarketDataDelta::BboChange(event)),
+            Ok(None) => {}
+            Err(BboDecodeError::UnknownTemplate { .. }) => self.on_trade_datagram(datagram),
+            Err(BboDecodeError::Truncated(_)) => self.decode_errors += 1,
+        }
+    }
+
+    fn on_trade_datagram(&mut self, datagram: &[u8]) {
+        match self.trades.on_datagram(datagram) {
+            Ok(Some(trade)) => self.on_trade(&trade),
+            Ok(None) => {}
+            Err(TradeDecodeError::UnknownTemplate { .. }) => self.on_order_datagram(datagram),
+            Err(TradeDecodeError::Truncated(_) | TradeDecodeError::InvalidSide(_)) => {
+                self.decode_errors += 1
+            }
+        }
+    }
+
+    fn on_order_datagram(&mut self, datagram: &[u8]) {
+        let Some(heatmap) = &self.heatmap else {
+            return;
+        };
+        match self.orders.on_datagram(datagram) {
+            Ok(Some(delta)) => {
+                heatmap.on_order_change(&delta);
+            }
+            Ok(None) => {}
+            // 同一组播组上的其他消息模板不属于本接入
+            Err(OrderDecodeError::UnknownTemplate { .. }) => {}
+            Err(
+                OrderDecodeError::Truncated(_)
+                | OrderDecodeError::InvalidChangeType(_)
+                | OrderDecodeError::InvalidSide(_),
+            ) => self.decode_errors += 1,
+        }
+    }
+
+    fn on_trade(&self, trade: &TradeEvent) {
+        if let Some(recent_trades) = &self.recent_trades {
+            record_trade(recent_trades, trade);
+        }
+        if let Some(candles) = &self.candles {
+            record_candle_trade(candles, trade);
+        }
+        self.tickers.on_market_data(&MarketDataDelta::Trade(*trade));
+    }
+
+    /// BBO 通道累计丢失的消息数
+    pub fn bbo_gaps(&self) -> u64 {
+        self.bbo.gaps()
+    }
+
+    /// 成交通道累计丢失的消息数
+    pub fn trade_gaps(&self) -> u64 {
+        self.trades.gaps()
+    }
+
+    /// 逐笔委托通道累计丢失的消息数
+    pub fn order_gaps(&self) -> u64 {
+        self.orders.gaps()
+    }
+
+    /// 无法解码的数据报数
+    pub fn decode_errors(&self) -> u64 {
+        self.decode_errors
+    }
+}
+
+/// 加入组播组并在独立线程中持续接收
+pub fn spawn_market_feed(
+    config: MarketFeedConfig,
+    mut feed: MarketFeed,
+) -> io::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.group.port()))?;
+    socket.join_multicast_v4(config.group.ip(), &config.interface)?;
+    std::thread::Builder::new().name("market-feed".to_string()).spawn(move || {
+        let mut buf = [0u8; MAX_DATAGRAM];
+        loop {
+            match socket.recv(&mut buf) {
+                Ok(len) => feed.on_datagram(&buf[..len]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    warn!("Market feed stopped: {}", e);
+                    return;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::mark_data::spot::bbo_channel::{BBO_MESSAGE_LEN, BboMessage};
+    use base_types::mark_data::spot::level_types::{OrderChangeType, OrderDelta};
+    use base_types::mark_data::spot::order_channel::{ORDER_MESSAGE_LEN, OrderMessage};
+    use base_types::mark_data::spot::trade_channel::{TRADE_MESSAGE_LEN, TradeMessage};
+    use base_types::{OrderSide, Price, Quantity, TradingPair};
+
+    use super::*;
+    use crate::http::codec::request_body;
+    use crate::http::klines::KlinesHandler;
+    use crate::http::trades::TradesHandler;
+    use crate::websocket::server::WebSocketGateway;
+
+    fn datagram(channel_seq: u64) -> [u8; BBO_MESSAGE_LEN] {
+        let mut buf = [0u8; BBO_MESSAGE_LEN];
+        BboMessage {
+            symbol_id: TradingPair::BtcUsdt as u32,
+            channel_seq,
+            engine_seq: 9,
+            timestamp: 1_000,
+            bid_price: Some(Price::from_f64(100.0)),
+            bid_qty: Quantity::from_f64(1.0),
+            ask_price: Some(Price::from_f64(101.0)),
+            ask_qty: Quantity::from_f64(2.0),
+        }
+        .encode(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn test_bbo_datagrams_drive_book_ticker() {
+        let tickers = Arc::new(TickerHandler::default());
+        let mut stream = tickers.book_ticker_stream().subscribe();
+        let mut feed = MarketFeed::new(tickers.clone());
+
+        feed.on_datagram(&datagram(1));
+        feed.on_datagram(&datagram(1));
+        feed.on_datagram(&datagram(3)[..12]);
+        assert!(stream.try_recv().is_ok());
+        assert!(stream.try_recv().is_err());
+        assert_eq!(feed.decode_errors(), 1);
+
+        let response = tickers.respond("/api/spot/bookTicker?symbol=BTCUSDT");
+        assert!(response.starts_with(b"HTTP/1.1 200"));
+        let json: serde_json::Value = serde_json::from_slice(request_body(&response)).unwrap();
+        assert_eq!(json["symbol"], "BTCUSDT");
+        assert_eq!(json["bidPrice"], serde_json::to_value(Price::from_f64(100.0)).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_bbo_datagrams_reach_websocket_subscribers() {
+        let tickers = Arc::new(TickerHandler::default());
+        let gateway = WebSocketGateway::default();
+        let mut hub = gateway.publisher().subscribe();
+        gateway.spawn_forward("bookTicker", tickers.book_ticker_stream().subscribe()).unwrap();
+
+        MarketFeed::new(tickers).on_datagram(&datagram(1));
+        let message = tokio::time::timeout(std::time::Duration::from_secs(1), hub.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.stream, "btcusdt@bookTicker");
+    }
+
+    fn trade_datagram(channel_seq: u64) -> [u8; TRADE_MESSAGE_LEN] {
+        let mut buf = [0u8; TRADE_MESSAGE_LEN];
+        TradeMessage {
+            channel_seq,
+            trade: TradeEvent {
+                symbol_id: TradingPair::BtcUsdt as u32,
+                timestamp: 1_000_000_000,
+                sequence: 10 + channel_seq,
+                trade_id: channel_seq,
+                buyer_order_id: 1,
+                seller_order_id: 2,
+                price: Price::from_f64(100.0),
+                quantity: Quantity::from_f64(1.0),
+                aggressor_side: OrderSide::Buy,
+            },
+        }
+        .encode(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn test_trade_datagrams_feed_recent_trades_and_avg_price() {
+        let tickers = Arc::new(TickerHandler::default());
+        let trades = TradesHandler::default();
+        let klines = KlinesHandler::default();
+        let mut feed = MarketFeed::new(tickers.clone())
+            .with_trades(trades.recent_trades())
+            .with_candles(klines.history());
+
+        feed.on_datagram(&trade_datagram(1));
+        feed.on_datagram(&trade_datagram(1));
+        feed.on_datagram(&trade_datagram(3));
+        feed.on_datagram(&trade_datagram(4)[..20]);
+        assert_eq!((feed.trade_gaps(), feed.decode_errors()), (1, 1));
+
+        let response = trades.respond("/api/spot/trades?symbol=BTCUSDT");
+        let json: serde_json::Value = serde_json::from_slice(request_body(&response)).unwrap();
+        let ids: Vec<_> = json.as_array().unwrap().iter().map(|t| t["id"].clone()).collect();
+        assert_eq!(ids, [1, 3]);
+
+        let response = klines.respond("/api/spot/klines?symbol=BTCUSDT&interval=1m");
+        let json: serde_json::Value = serde_json::from_slice(request_body(&response)).unwrap();
+        assert_eq!(json[0][8], 2);
+
+        let response = tickers.respond("/api/spot/avgPrice?symbol=BTCUSDT");
+        assert!(response.starts_with(b"HTTP/1.1 200"));
+        let json: serde_json::Value = serde_json::from_slice(request_body(&response)).unwrap();
+        assert_eq!(json["price"], serde_json::to_value(Price::from_f64(100.0)).unwrap());
+    }
+
+    #[test]
+    fn test_order_datagrams_feed_heatmap() {
+        let heatmap = Arc::new(HeatmapStream::default());
+        let mut stream = heatmap.subscribe();
+        let mut feed =
+            MarketFeed::new(Arc::new(TickerHandler::default())).with_heatmap(heatmap.clone());
+
+        let order_datagram = |channel_seq: u64, timestamp: u64| {
+            let mut buf = [0u8; ORDER_MESSAGE_LEN];
+            OrderMessage {
+                channel_seq,
+                delta: OrderDelta {
+                    symbol_id: TradingPair::BtcUsdt as u32,
+                    timestamp,
+                    sequence: channel_seq,
+                    change_type: OrderChangeType::Add,
+                    order_id: channel_seq,
+                    side: OrderSide::Buy,
+                    price: Price::from_f64(100.0),
+                    quantity: Quantity::from_f64(1.0),
+                    trader_id: None,
+                },
+            }
+            .encode(&mut buf);
+            buf
+        };
+        feed.on_datagram(&order_datagram(1, 1_000));
+        feed.on_datagram(&order_datagram(3, 1_000_000_000));
+        feed.on_datagram(&order_datagram(4, 1_000_000_001)[..20]);
+        assert_eq!((feed.order_gaps(), feed.decode_errors()), (1, 1));
+
+        let message = stream.try_recv().unwrap();
+        assert_eq!(message.stream, "btcusdt@heatmap");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["b"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,344 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::mark_data::spot::level_types::{MarketDataDelta, SymbolId};
+use base_types::mark_data::spot::synthetic::{SyntheticError, SyntheticTickers};
+use base_types::mark_data::spot::ticker::{AvgPrice, BookTicker, SpotTickers};
+use base_types::{AssetId, SystemClock, TimestampProvider, TradingPair};
+
+use super::codec::{self, CodecError, WireFormat, encoded_response};
+use super::exchange_info::{json_response, query_param};
+use crate::websocket::book_ticker::{BookTickerStream, symbol_of};
+use crate::websocket::synthetic_ticker::SyntheticTickerStream;
+
+/// 均价接口路径
+pub const AVG_PRICE_PATH: &str = "/api/spot/avgPrice";
+/// 最优挂单接口路径
+pub const BOOK_TICKER_PATH: &str = "/api/spot/bookTicker";
+
+/// 默认的合成交易对（没有直接订单簿，经 USDT 交叉）
+pub const DEFAULT_SYNTHETIC_PAIRS: &[(AssetId, AssetId)] = &[(AssetId::Eth, AssetId::Btc)];
+
+/// 行情响应体
+enum TickerBody {
+    AvgPrice(AvgPrice),
+    BookTicker(BookTicker),
+    BookTickers(Vec<BookTicker>),
+}
+
+/// `GET /api/spot/avgPrice` 与 `GET /api/spot/bookTicker` 处理器
+///
+/// 行情由撮合引擎的增量事件增量维护（经行情组播接入，见
+/// [`MarketFeed`](super::market_feed::MarketFeed)），最优挂单变化同时推送到 bookTicker 流，
+/// 并驱动合成交叉汇率（推送到 syntheticTicker 流）；两个流经 `/ws` 推送给订阅者
+pub struct TickerHandler {
+    tickers: Arc<RwLock<SpotTickers>>,
+    stream: BookTickerStream,
+    synthetic: Arc<RwLock<SyntheticTickers>>,
+    synthetic_stream: SyntheticTickerStream,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for TickerHandler {
+    fn default() -> Self {
+        let mut synthetic = SyntheticTickers::new(AssetId::Usdt);
+        for (base, quote) in DEFAULT_SYNTHETIC_PAIRS {
+            synthetic.register(*base, *quote).expect("default synthetic pairs have USDT legs");
+        }
+        Self {
+            tickers: Arc::new(RwLock::new(SpotTickers::new())),
+            stream: BookTickerStream::default(),
+            synthetic: Arc::new(RwLock::new(synthetic)),
+            synthetic_stream: SyntheticTickerStream::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl TickerHandler {
+    /// 替换时间来源（测试注入 `ManualClock`）
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 替换合成交易对（桥接资产不变）
+    ///
+    /// 原地替换，已经通过 [`TickerHandler::synthetic_tickers`] 取走的句柄（小额资产兑换的指数价）
+    /// 继续随行情更新
+    pub fn with_synthetic_pairs(
+        self,
+        pairs: &[(AssetId, AssetId)],
+    ) -> Result<Self, SyntheticError> {
+        let mut current = self.synthetic.write().unwrap_or_else(PoisonError::into_inner);
+        let mut synthetic = SyntheticTickers::new(current.bridge());
+        for (base, quote) in pairs {
+            synthetic.register(*base, *quote)?;
+        }
+        *current = synthetic;
+        drop(current);
+        Ok(self)
+    }
+
+    /// 行情管道写入增量事件的入口
+    pub fn on_market_data(&self, delta: &MarketDataDelta) {
+        let Ok(mut tickers) = self.tickers.write() else {
+            return;
+        };
+        let Some(ticker) = tickers.apply(delta) else {
+            return;
+        };
+        self.stream.publish(&ticker);
+
+        let Ok(mut synthetic) = self.synthetic.write() else {
+            return;
+        };
+        for update in synthetic.on_book_ticker(&ticker) {
+            self.synthetic_stream.publish(&update, synthetic.bridge());
+        }
+    }
+
+    /// bookTicker 流
+    pub fn book_ticker_stream(&self) -> &BookTickerStream {
+        &self.stream
+    }
+
+    /// syntheticTicker 流
+    pub fn synthetic_ticker_stream(&self) -> &SyntheticTickerStream {
+        &self.synthetic_stream
+    }
+
+    /// 合成行情（供闪兑 / RFQ 参考定价共享）
+    pub fn synthetic_tickers(&self) -> Arc<RwLock<SyntheticTickers>> {
+        self.synthetic.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "GET" && (route == Some(AVG_PRICE_PATH) || route == Some(BOOK_TICKER_PATH))
+    }
+
+    /// 生成完整的 JSON HTTP 响应
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        self.respond_as(path, None)
+    }
+
+    /// 按 `Accept` 头协商格式生成完整的 HTTP 响应（错误响应体始终为 JSON）
+    pub fn respond_as(&self, path: &str, accept: Option<&str>) -> Vec<u8> {
+        let Some(format) = WireFormat::negotiate(accept) else {
+            let msg = format!("Unsupported Accept: {}", accept.unwrap_or_default());
+            return json_response(406, &serde_json::json!({ "msg": msg }).to_string());
+        };
+        let (status, format, body) = self.render_as(path, self.clock.now().0, format);
+        encoded_response(status, format, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    #[cfg(test)]
+    fn render(&self, path: &str, now: u64) -> (u16, String) {
+        let (status, _, body) = self.render_as(path, now, WireFormat::Json);
+        (status, String::from_utf8(body).unwrap_or_default())
+    }
+
+    /// 返回 (状态码, 响应格式, 响应体)
+    ///
+    /// JSON 与 MessagePack 编码同一结构（最优挂单附带 `symbol`）；
+    /// SBE 只支持单个交易对，全部交易对的列表应答 406
+    fn render_as(&self, path: &str, now: u64, format: WireFormat) -> (u16, WireFormat, Vec<u8>) {
+        let body = match self.lookup(path, now) {
+            Ok(body) => body,
+            Err((status, msg)) => return Self::error(status, msg),
+        };
+        let encoded = match (format, &body) {
+            (WireFormat::Sbe, TickerBody::AvgPrice(avg)) => codec::encode(format, avg),
+            (WireFormat::Sbe, TickerBody::BookTicker(ticker)) => codec::encode(format, ticker),
+            (WireFormat::Sbe, TickerBody::BookTickers(_)) => Err(CodecError::Unsupported(format)),
+            _ => codec::encode(format, &Self::body_json(&body)),
+        };
+        match encoded {
+            Ok(bytes) => (200, format, bytes),
+            Err(e @ CodecError::Unsupported(_)) => Self::error(406, e.to_string()),
+            Err(e) => Self::error(500, e.to_string()),
+        }
+    }
+
+    /// avgPrice 的 `symbol` 必填；bookTicker 未指定 `symbol` 时返回全部交易对
+    fn lookup(&self, path: &str, now: u64) -> Result<TickerBody, (u16, String)> {
+        let symbol_id = match query_param(path, "symbol") {
+            None => None,
+            Some(symbol) => match TradingPair::from_symbol_str(symbol) {
+                Some(pair) => Some(pair as SymbolId),
+                None => return Err((400, format!("Invalid symbol: {}", symbol))),
+            },
+        };
+
+        if path.starts_with(AVG_PRICE_PATH) {
+            let Some(symbol_id) = symbol_id else {
+                return Err((400, "Missing parameter: symbol".to_string()));
+            };
+            let Ok(mut tickers) = self.tickers.write() else {
+                return Err((500, "Tickers unavailable".to_string()));
+            };
+            return Ok(TickerBody::AvgPrice(tickers.avg_price(symbol_id, now)));
+        }
+        let Ok(tickers) = self.tickers.read() else {
+            return Err((500, "Tickers unavailable".to_string()));
+        };
+        Ok(match symbol_id {
+            Some(symbol_id) => TickerBody::BookTicker(
+                tickers.book_ticker(symbol_id).copied().unwrap_or(BookTicker {
+                    symbol_id,
+                    update_id: 0,
+                    bid_price: None,
+                    bid_qty: Default::default(),
+                    ask_price: None,
+                    ask_qty: Default::default(),
+                }),
+            ),
+            None => TickerBody::BookTickers(tickers.book_tickers()),
+        })
+    }
+
+    fn body_json(body: &TickerBody) -> serde_json::Value {
+        match body {
+            TickerBody::AvgPrice(avg) => serde_json::to_value(avg).unwrap_or_default(),
+            TickerBody::BookTicker(ticker) => Self::book_ticker_json(ticker),
+            TickerBody::BookTickers(tickers) => {
+                tickers.iter().map(Self::book_ticker_json).collect()
+            }
+        }
+    }
+
+    /// 最优挂单响应附带交易对名称
+    fn book_ticker_json(ticker: &BookTicker) -> serde_json::Value {
+        let mut json = serde_json::to_value(ticker).unwrap_or_default();
+        if let Some(symbol) = symbol_of(ticker.symbol_id) {
+            json["symbol"] = symbol.into();
+        }
+        json
+    }
+
+    fn error(status: u16, msg: String) -> (u16, WireFormat, Vec<u8>) {
+        (status, WireFormat::Json, serde_json::json!({ "msg": msg }).to_string().into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::mark_data::spot::level_types::{BboChangeEvent, TradeEvent};
+    use base_types::{OrderSide, Price, Quantity};
+
+    use super::*;
+
+    const BTC_USDT: SymbolId = TradingPair::BtcUsdt as SymbolId;
+
+    #[test]
+    fn test_avg_price_and_book_ticker() {
+        let handler = TickerHandler::default();
+        let mut receiver = handler.book_ticker_stream().subscribe();
+        handler.on_market_data(&MarketDataDelta::BboChange(BboChangeEvent {
+            symbol_id: BTC_USDT,
+            timestamp: 1,
+            sequence: 1,
+            best_bid: Some(Price::from_f64(99.0)),
+            best_bid_quantity: Quantity::from_f64(1.0),
+            best_ask: Some(Price::from_f64(101.0)),
+            best_ask_quantity: Quantity::from_f64(2.0),
+        }));
+        handler.on_market_data(&MarketDataDelta::Trade(TradeEvent {
+            symbol_id: BTC_USDT,
+            timestamp: 1_000_000_000,
+            sequence: 2,
+            trade_id: 1,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(100.0),
+            quantity: Quantity::from_f64(1.0),
+            aggressor_side: OrderSide::Buy,
+        }));
+        assert_eq!(receiver.try_recv().unwrap().stream, "btcusdt@bookTicker");
+
+        let (status, body) = handler.render("/api/spot/bookTicker?symbol=BTCUSDT", 0);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["symbol"], "BTCUSDT");
+        let (_, body) = handler.render("/api/spot/bookTicker", 0);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+
+        let (status, body) = handler.render("/api/spot/avgPrice?symbol=BTCUSDT", 2_000_000_000);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!((json["mins"].as_u64(), json["closeTime"].as_u64()), (Some(5), Some(2000)));
+        assert!(json["price"].is_string());
+
+        assert_eq!(handler.render("/api/spot/avgPrice", 0).0, 400);
+        assert_eq!(handler.render("/api/spot/bookTicker?symbol=DOGEUSDT", 0).0, 400);
+    }
+
+    #[test]
+    fn test_synthetic_ticker_follows_legs() {
+        let handler = TickerHandler::default();
+        let mut receiver = handler.synthetic_ticker_stream().subscribe();
+        for (pair, bid, ask) in
+            [(TradingPair::EthUsdt, 2_000.0, 2_002.0), (TradingPair::BtcUsdt, 40_000.0, 40_040.0)]
+        {
+            handler.on_market_data(&MarketDataDelta::BboChange(BboChangeEvent {
+                symbol_id: pair as SymbolId,
+                timestamp: 1,
+                sequence: 1,
+                best_bid: Some(Price::from_f64(bid)),
+                best_bid_quantity: Quantity::from_f64(1.0),
+                best_ask: Some(Price::from_f64(ask)),
+                best_ask_quantity: Quantity::from_f64(1.0),
+            }));
+        }
+
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "ethbtc@syntheticTicker");
+        assert!(receiver.try_recv().is_err());
+        let synthetic = handler.synthetic_tickers();
+        let price =
+            synthetic.read().unwrap().indicative_price(AssetId::Eth, AssetId::Btc, OrderSide::Buy);
+        assert_eq!(price, Some(Price::from_raw(5_005_000)));
+
+        let handler =
+            TickerHandler::default().with_synthetic_pairs(&[(AssetId::Btc, AssetId::Btc)]);
+        assert!(handler.is_err());
+    }
+
+    #[test]
+    fn test_content_negotiation_keeps_semantics() {
+        let handler = TickerHandler::default();
+        handler.on_market_data(&MarketDataDelta::BboChange(BboChangeEvent {
+            symbol_id: BTC_USDT,
+            timestamp: 1,
+            sequence: 1,
+            best_bid: Some(Price::from_f64(99.0)),
+            best_bid_quantity: Quantity::from_f64(1.0),
+            best_ask: None,
+            best_ask_quantity: Quantity::default(),
+        }));
+        let path = "/api/spot/bookTicker?symbol=BTCUSDT";
+        let (_, _, json) = handler.render_as(path, 0, WireFormat::Json);
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let (status, format, msgpack) = handler.render_as(path, 0, WireFormat::MessagePack);
+        assert_eq!((status, format), (200, WireFormat::MessagePack));
+        assert_eq!(codec::decode::<serde_json::Value>(format, &msgpack).unwrap(), json);
+
+        let (status, format, sbe) = handler.render_as(path, 0, WireFormat::Sbe);
+        assert_eq!((status, format), (200, WireFormat::Sbe));
+        let ticker: BookTicker = codec::decode(format, &sbe).unwrap();
+        assert_eq!(json["bidPrice"], serde_json::to_value(ticker.bid_price).unwrap());
+        assert_eq!(ticker.ask_price, None);
+
+        // 列表不支持 SBE；不支持的 Accept 应答 406
+        let (status, format, _) = handler.render_as("/api/spot/bookTicker", 0, WireFormat::Sbe);
+        assert_eq!((status, format), (406, WireFormat::Json));
+        let response = handler.respond_as(path, Some("text/html"));
+        assert!(response.starts_with(b"HTTP/1.1 406 Not Acceptable"));
+        let response = handler.respond_as(path, Some("application/msgpack"));
+        assert!(String::from_utf8_lossy(&response).contains("Content-Type: application/msgpack"));
+    }
+}
@@ -1,2 +1,34 @@
+pub mod account_activity;
+pub mod account_status;
+pub mod algo_tca;
+pub mod api_keys;
+pub mod api_usage;
+pub mod block_trade;
+pub mod codec;
+pub mod compression;
+pub mod cost_basis;
+pub mod degradation;
+pub mod delegation;
+pub mod discovery;
+pub mod dust;
+pub mod entitlements;
+pub mod exchange_info;
 pub mod http_proxy;
+pub mod klines;
+pub mod leaderboard;
+pub mod market_feed;
+pub mod market_ticker;
+pub mod order_ingress;
+pub mod payload_keys;
+pub mod prep_account;
+pub mod prep_admin;
+pub mod prep_history;
+pub mod prep_quote;
+pub mod prep_recurring;
+pub mod prep_schedule;
+pub mod rfq;
 pub mod router;
+pub mod server_time;
+pub mod session_auth;
+pub mod signed_request;
+pub mod trades;
@@ -0,0 +1,174 @@
+//! 委托入口规范化
+//!
+//! 新委托与大宗交易报告在转发或受理前，价格与数量按产品注册表声明的精度规范化
+//! （见 `base_types::instrument::normalize`）：不合法或超精度的请求直接返回 400，
+//! 合法的请求体中价格与数量改写为固定小数位的十进制串，后端对同一输入只会看到同一种写法
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::instrument::normalize::{InstrumentScale, NormalizeError, format_units};
+use base_types::instrument::registry::InstrumentRegistry;
+use serde_json::Value;
+
+use super::block_trade::BLOCK_TRADE_PATH;
+use super::codec::{replace_body, request_body};
+use super::degradation::NEW_ORDER_PATH;
+use super::exchange_info::json_response;
+
+/// 按价格精度规范化的字段
+const PRICE_FIELDS: [&str; 2] = ["price", "stopPrice"];
+/// 按数量精度规范化的字段
+const QUANTITY_FIELDS: [&str; 1] = ["quantity"];
+
+/// 委托入口规范化
+pub struct OrderNormalizer {
+    registry: Arc<RwLock<InstrumentRegistry>>,
+}
+
+impl OrderNormalizer {
+    pub fn new(registry: Arc<RwLock<InstrumentRegistry>>) -> Self {
+        Self { registry }
+    }
+
+    /// 请求是否需要规范化（`path` 含查询串）
+    pub fn applies(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "POST" && (route == Some(NEW_ORDER_PATH) || route == Some(BLOCK_TRADE_PATH))
+    }
+
+    /// 规范化请求体中的价格与数量；拒绝时返回 400 响应
+    ///
+    /// 请求体须为带 `symbol` 的 JSON 对象，字段值可以是字符串或数字
+    pub fn normalize(
+        &self,
+        method: &str,
+        path: &str,
+        request: Vec<u8>,
+    ) -> Result<Vec<u8>, Vec<u8>> {
+        if !Self::applies(method, path) {
+            return Ok(request);
+        }
+        let Ok(Value::Object(mut body)) = serde_json::from_slice(request_body(&request)) else {
+            return Err(bad_request("Order body must be a JSON object".to_string()));
+        };
+        let Some(symbol) = body.get("symbol").and_then(Value::as_str) else {
+            return Err(bad_request("Missing parameter: symbol".to_string()));
+        };
+        let scale = {
+            let registry = self.registry.read().unwrap_or_else(PoisonError::into_inner);
+            match registry.get(&symbol.to_uppercase()) {
+                Some(spec) => spec.scale,
+                None => return Err(bad_request(format!("Invalid symbol: {}", symbol))),
+            }
+        };
+
+        let fields = PRICE_FIELDS
+            .iter()
+            .map(|name| (*name, scale.price_scale))
+            .chain(QUANTITY_FIELDS.iter().map(|name| (*name, scale.quantity_scale)));
+        for (name, field_scale) in fields {
+            let Some(value) = body.get_mut(name) else {
+                continue;
+            };
+            let units = normalize_field(&scale, name, value)
+                .map_err(|e| bad_request(format!("Invalid {}: {}", name, e)))?;
+            *value = Value::String(format_units(units, field_scale));
+        }
+
+        match serde_json::to_vec(&body) {
+            Ok(body) => Ok(replace_body(&request, &body)),
+            Err(e) => {
+                Err(json_response(500, &serde_json::json!({ "msg": e.to_string() }).to_string()))
+            }
+        }
+    }
+}
+
+/// 字段值 → 最小变动单位个数
+fn normalize_field(
+    scale: &InstrumentScale,
+    name: &str,
+    value: &Value,
+) -> Result<i64, NormalizeError> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        _ => return Err(NormalizeError::Invalid),
+    };
+    if QUANTITY_FIELDS.contains(&name) {
+        scale.normalize_quantity(&text)
+    } else {
+        scale.normalize_price(&text)
+    }
+}
+
+fn bad_request(msg: String) -> Vec<u8> {
+    json_response(400, &serde_json::json!({ "msg": msg }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::instrument::normalize::RoundingRule;
+    use base_types::instrument::registry::InstrumentSpec;
+    use base_types::{AssetId, InstrumentType};
+
+    use super::*;
+
+    fn normalizer() -> OrderNormalizer {
+        let mut registry = InstrumentRegistry::new();
+        let scale =
+            InstrumentScale::new(2, 6).with_rounding(RoundingRule::Reject, RoundingRule::Down);
+        let spec = InstrumentSpec::new(
+            1,
+            "BTCUSDT",
+            InstrumentType::Spot,
+            AssetId::Btc,
+            AssetId::Usdt,
+            scale,
+        );
+        registry.register(spec).unwrap();
+        OrderNormalizer::new(Arc::new(RwLock::new(registry)))
+    }
+
+    fn order(body: &str) -> Vec<u8> {
+        format!(
+            "POST /api/spot/v2/order HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_order_fields_rewritten_to_instrument_scale() {
+        let normalizer = normalizer();
+        let request = order(r#"{"symbol":"btcusdt","price":"65000.1","quantity":0.0012345}"#);
+        let normalized = normalizer.normalize("POST", NEW_ORDER_PATH, request).unwrap();
+        let text = String::from_utf8(normalized.clone()).unwrap();
+        let body: Value = serde_json::from_slice(request_body(&normalized)).unwrap();
+        assert_eq!(body["price"], "65000.10");
+        assert_eq!(body["quantity"], "0.001234");
+        assert!(text.contains(&format!("Content-Length: {}\r\n", request_body(&normalized).len())));
+
+        // 其他接口原样放行
+        let other = b"POST /api/spot/order/ HTTP/1.1\r\n\r\nnot json".to_vec();
+        assert_eq!(normalizer.normalize("POST", "/api/spot/order/", other.clone()), Ok(other));
+    }
+
+    #[test]
+    fn test_invalid_orders_rejected() {
+        let normalizer = normalizer();
+        for body in [
+            r#"{"symbol":"BTCUSDT","price":"65000.123","quantity":"1"}"#,
+            r#"{"symbol":"BTCUSDT","price":"-1","quantity":"1"}"#,
+            r#"{"symbol":"BTCUSDT","price":"1e3","quantity":"1"}"#,
+            r#"{"symbol":"BTCUSDT","quantity":true}"#,
+            r#"{"symbol":"DOGEUSDT","quantity":"1"}"#,
+            r#"{"price":"1"}"#,
+            "[]",
+        ] {
+            let response = normalizer.normalize("POST", NEW_ORDER_PATH, order(body)).unwrap_err();
+            assert!(response.starts_with(b"HTTP/1.1 400"), "{}", body);
+        }
+    }
+}
@@ -0,0 +1,674 @@
+//! 出站报告（Webhook、drop-copy 文件）的签名与加密
+//!
+//! - 签名：交易所持有 Ed25519 签名密钥，对投递的字节（加密时为密文）连同时间戳做分离签名，
+//!   接收方用公开的签名公钥验证报告确由交易所发出、未被篡改。轮换后旧公钥仍在列表中
+//!   （标记退役时间），用于验证历史报告
+//! - 加密（可选）：账户登记 X25519 公钥后，每份报告生成临时密钥做 ECDH，
+//!   经 HKDF-SHA256 派生一次性密钥与 nonce，以 ChaCha20-Poly1305 加密
+//!
+//! 签名内容为 `{timestamp}.{body}`，Webhook 以请求头携带签名信息（[`SealedPayload::headers`]），
+//! drop-copy 文件旁写入同名 `.sig` 文件（[`write_drop_copy`]）。
+//!
+//! 自助接口（按 JWT 会话鉴权得到的账户操作，未鉴权返回 401；
+//! 不接受 API Key 鉴权或代账户操作的请求）：
+//! - `GET /api/payloadKeys`：签名公钥列表与本账户登记的加密公钥
+//! - `POST /api/payloadKeys/encryption`：`{publicKey}` 登记加密公钥（base64，32 字节）
+//! - `POST /api/payloadKeys/encryption/remove`：移除加密公钥，之后的报告只签名不加密
+//!
+//! 管理接口（`X-Admin-Token` 鉴权，未配置令牌时关闭）：
+//! - `POST /api/admin/payloadKeys/rotate`：轮换签名密钥
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::{AccountId, SystemClock, Timestamp, TimestampProvider};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use hkdf::Hkdf;
+use rand_core::OsRng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::codec::{header_value, request_body};
+use super::delegation::ON_BEHALF_HEADER;
+use super::exchange_info::json_response;
+
+/// 自助接口路径
+pub const PAYLOAD_KEYS_PATH: &str = "/api/payloadKeys";
+pub const ENCRYPTION_KEY_PATH: &str = "/api/payloadKeys/encryption";
+pub const REMOVE_ENCRYPTION_KEY_PATH: &str = "/api/payloadKeys/encryption/remove";
+/// 管理接口路径
+pub const ADMIN_ROTATE_SIGNING_KEY_PATH: &str = "/api/admin/payloadKeys/rotate";
+
+/// 签名密钥种子（base64，32 字节）；未设置时启动时随机生成
+pub const ENV_PAYLOAD_SIGNING_KEY: &str = "GATEWAY_PAYLOAD_SIGNING_KEY";
+
+/// Webhook 签名请求头
+pub const KEY_ID_HEADER: &str = "X-Payload-Key-Id";
+pub const TIMESTAMP_HEADER: &str = "X-Payload-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Payload-Signature";
+/// 加密报告的临时公钥
+pub const EPHEMERAL_KEY_HEADER: &str = "X-Payload-Ephemeral-Key";
+
+/// drop-copy 签名文件后缀
+pub const SIGNATURE_FILE_SUFFIX: &str = ".sig";
+
+/// HKDF info，同时标识载荷格式版本
+const KDF_INFO: &[u8] = b"rustlob-payload-v1";
+
+/// 签名与加密错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadKeyError {
+    /// 公钥、种子或签名格式错误
+    InvalidKey(String),
+    /// 低阶点等无法协商出共享密钥的加密公钥
+    WeakKey,
+    /// 签名公钥不存在
+    UnknownKey(String),
+    /// 签名验证失败
+    BadSignature,
+    /// 报告未加密、密钥不匹配或密文被篡改
+    Decrypt,
+    /// 密钥派生或加密失败
+    Encrypt,
+}
+
+impl fmt::Display for PayloadKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadKeyError::InvalidKey(msg) => write!(f, "Invalid key: {}", msg),
+            PayloadKeyError::WeakKey => write!(f, "Encryption key is a low-order point"),
+            PayloadKeyError::UnknownKey(key_id) => write!(f, "Unknown signing key: {}", key_id),
+            PayloadKeyError::BadSignature => write!(f, "Signature verification failed"),
+            PayloadKeyError::Decrypt => write!(f, "Failed to decrypt payload"),
+            PayloadKeyError::Encrypt => write!(f, "Failed to encrypt payload"),
+        }
+    }
+}
+
+impl std::error::Error for PayloadKeyError {}
+
+/// 签名公钥信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningKeyInfo {
+    /// 公钥 SHA-256 的前 8 字节（十六进制）
+    pub key_id: String,
+    pub public_key: [u8; 32],
+    pub created_at: Timestamp,
+    /// 轮换退役时间（None 为当前签名密钥）
+    pub retired_at: Option<Timestamp>,
+}
+
+/// 账户登记的加密公钥
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecipientKey {
+    pub public_key: [u8; 32],
+    pub registered_at: Timestamp,
+}
+
+/// 签名（及加密）后的报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedPayload {
+    pub key_id: String,
+    /// 签名时间（毫秒）
+    pub timestamp: u64,
+    pub signature: [u8; 64],
+    /// 加密时的临时公钥
+    pub ephemeral_key: Option<[u8; 32]>,
+    /// 投递内容（加密时为密文）
+    pub body: Vec<u8>,
+}
+
+impl SealedPayload {
+    /// Webhook 请求头
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            (KEY_ID_HEADER, self.key_id.clone()),
+            (TIMESTAMP_HEADER, self.timestamp.to_string()),
+            (SIGNATURE_HEADER, STANDARD.encode(self.signature)),
+        ];
+        if let Some(ephemeral_key) = self.ephemeral_key {
+            headers.push((EPHEMERAL_KEY_HEADER, STANDARD.encode(ephemeral_key)));
+        }
+        headers
+    }
+
+    /// drop-copy 签名文件内容
+    pub fn signature_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "keyId": self.key_id,
+            "timestamp": self.timestamp,
+            "signature": STANDARD.encode(self.signature),
+            "ephemeralKey": self.ephemeral_key.map(|key| STANDARD.encode(key)),
+        })
+    }
+
+    /// 由接收到的请求头与内容还原
+    pub fn from_headers(
+        header: impl Fn(&str) -> Option<String>,
+        body: Vec<u8>,
+    ) -> Result<Self, PayloadKeyError> {
+        let missing = |name: &str| PayloadKeyError::InvalidKey(format!("missing {}", name));
+        let timestamp = header(TIMESTAMP_HEADER)
+            .ok_or_else(|| missing(TIMESTAMP_HEADER))?
+            .parse::<u64>()
+            .map_err(|e| PayloadKeyError::InvalidKey(e.to_string()))?;
+        let signature = header(SIGNATURE_HEADER).ok_or_else(|| missing(SIGNATURE_HEADER))?;
+        Ok(Self {
+            key_id: header(KEY_ID_HEADER).ok_or_else(|| missing(KEY_ID_HEADER))?,
+            timestamp,
+            signature: decode_fixed(&signature)?,
+            ephemeral_key: header(EPHEMERAL_KEY_HEADER)
+                .map(|key| decode_fixed(&key))
+                .transpose()?,
+            body,
+        })
+    }
+
+    fn signed_message(&self) -> Vec<u8> {
+        signed_message(self.timestamp, &self.body)
+    }
+}
+
+/// 签名密钥与账户加密公钥
+pub struct PayloadKeyring {
+    /// 当前签名密钥
+    signing: (SigningKeyInfo, SigningKey),
+    /// 已退役的签名公钥（按创建顺序）
+    retired: Vec<SigningKeyInfo>,
+    recipients: HashMap<AccountId, RecipientKey>,
+}
+
+impl PayloadKeyring {
+    /// 随机生成签名密钥
+    pub fn generate(now: Timestamp) -> Self {
+        Self::from_signing_key(SigningKey::generate(&mut OsRng), now)
+    }
+
+    /// 由 32 字节种子恢复签名密钥（跨重启保持公钥不变）
+    pub fn from_seed(seed: [u8; 32], now: Timestamp) -> Self {
+        Self::from_signing_key(SigningKey::from_bytes(&seed), now)
+    }
+
+    fn from_signing_key(key: SigningKey, now: Timestamp) -> Self {
+        Self {
+            signing: (signing_info(&key, now), key),
+            retired: Vec::new(),
+            recipients: HashMap::new(),
+        }
+    }
+
+    /// 当前签名公钥
+    pub fn active(&self) -> &SigningKeyInfo {
+        &self.signing.0
+    }
+
+    /// 全部签名公钥（含已退役，按创建顺序）
+    pub fn signing_keys(&self) -> impl Iterator<Item = &SigningKeyInfo> {
+        self.retired.iter().chain(std::iter::once(&self.signing.0))
+    }
+
+    /// 轮换签名密钥，旧密钥退役但公钥保留用于验证
+    pub fn rotate(&mut self, now: Timestamp) -> &SigningKeyInfo {
+        let key = SigningKey::generate(&mut OsRng);
+        let (mut retired, _) = std::mem::replace(&mut self.signing, (signing_info(&key, now), key));
+        retired.retired_at = Some(now);
+        self.retired.push(retired);
+        self.active()
+    }
+
+    /// 登记账户加密公钥（替换已有）
+    pub fn register_recipient(
+        &mut self,
+        account_id: AccountId,
+        public_key: [u8; 32],
+        now: Timestamp,
+    ) -> Result<RecipientKey, PayloadKeyError> {
+        // 低阶点与任何私钥协商都得到固定的共享密钥
+        let probe = EphemeralSecret::random_from_rng(OsRng);
+        if !probe.diffie_hellman(&PublicKey::from(public_key)).was_contributory() {
+            return Err(PayloadKeyError::WeakKey);
+        }
+        let recipient = RecipientKey { public_key, registered_at: now };
+        self.recipients.insert(account_id, recipient);
+        Ok(recipient)
+    }
+
+    /// 移除账户加密公钥，返回是否存在
+    pub fn remove_recipient(&mut self, account_id: AccountId) -> bool {
+        self.recipients.remove(&account_id).is_some()
+    }
+
+    pub fn recipient(&self, account_id: AccountId) -> Option<&RecipientKey> {
+        self.recipients.get(&account_id)
+    }
+
+    /// 签名发往账户的报告；账户登记了加密公钥时先加密
+    pub fn seal(
+        &self,
+        account_id: AccountId,
+        payload: &[u8],
+        now: Timestamp,
+    ) -> Result<SealedPayload, PayloadKeyError> {
+        let (info, key) = &self.signing;
+        let (ephemeral_key, body) = match self.recipients.get(&account_id) {
+            Some(recipient) => {
+                let (ephemeral_key, body) = encrypt(&recipient.public_key, payload)?;
+                (Some(ephemeral_key), body)
+            }
+            None => (None, payload.to_vec()),
+        };
+        let timestamp = millis(now);
+        let signature = key.sign(&signed_message(timestamp, &body)).to_bytes();
+        Ok(SealedPayload { key_id: info.key_id.clone(), timestamp, signature, ephemeral_key, body })
+    }
+
+    /// 按报告中的 key id 验证签名（含已退役的公钥）
+    pub fn verify(&self, sealed: &SealedPayload) -> Result<(), PayloadKeyError> {
+        let info = self
+            .signing_keys()
+            .find(|info| info.key_id == sealed.key_id)
+            .ok_or_else(|| PayloadKeyError::UnknownKey(sealed.key_id.clone()))?;
+        verify_payload(&info.public_key, sealed)
+    }
+}
+
+/// 接收方验证签名
+pub fn verify_payload(
+    public_key: &[u8; 32],
+    sealed: &SealedPayload,
+) -> Result<(), PayloadKeyError> {
+    let key = VerifyingKey::from_bytes(public_key)
+        .map_err(|e| PayloadKeyError::InvalidKey(e.to_string()))?;
+    key.verify_strict(&sealed.signed_message(), &Signature::from_bytes(&sealed.signature))
+        .map_err(|_| PayloadKeyError::BadSignature)
+}
+
+/// 接收方解密（应先验证签名）
+pub fn open_payload(
+    secret: &StaticSecret,
+    sealed: &SealedPayload,
+) -> Result<Vec<u8>, PayloadKeyError> {
+    let ephemeral_key = sealed.ephemeral_key.ok_or(PayloadKeyError::Decrypt)?;
+    let recipient = PublicKey::from(secret);
+    let shared = secret.diffie_hellman(&PublicKey::from(ephemeral_key));
+    let (key, nonce) = derive_key(shared.as_bytes(), &ephemeral_key, recipient.as_bytes())
+        .map_err(|_| PayloadKeyError::Decrypt)?;
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(Nonce::from_slice(&nonce), sealed.body.as_slice())
+        .map_err(|_| PayloadKeyError::Decrypt)
+}
+
+/// 写入 drop-copy 文件及同名 `.sig` 签名文件
+pub fn write_drop_copy(path: &Path, sealed: &SealedPayload) -> io::Result<()> {
+    let mut signature_path = path.as_os_str().to_owned();
+    signature_path.push(SIGNATURE_FILE_SUFFIX);
+    std::fs::write(path, &sealed.body)?;
+    std::fs::write(signature_path, sealed.signature_json().to_string())
+}
+
+fn signing_info(key: &SigningKey, now: Timestamp) -> SigningKeyInfo {
+    let public_key = key.verifying_key().to_bytes();
+    let digest = Sha256::digest(public_key);
+    let key_id = digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+    SigningKeyInfo { key_id, public_key, created_at: now, retired_at: None }
+}
+
+fn signed_message(timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// 临时密钥加密，返回 (临时公钥, 密文)
+fn encrypt(recipient: &[u8; 32], payload: &[u8]) -> Result<([u8; 32], Vec<u8>), PayloadKeyError> {
+    let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_key = PublicKey::from(&ephemeral).to_bytes();
+    let shared = ephemeral.diffie_hellman(&PublicKey::from(*recipient));
+    let (key, nonce) = derive_key(shared.as_bytes(), &ephemeral_key, recipient)?;
+    let body = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| PayloadKeyError::Encrypt)?;
+    Ok((ephemeral_key, body))
+}
+
+/// 每份报告的密钥只使用一次，nonce 随密钥一并派生
+fn derive_key(
+    shared: &[u8; 32],
+    ephemeral: &[u8; 32],
+    recipient: &[u8; 32],
+) -> Result<([u8; 32], [u8; 12]), PayloadKeyError> {
+    let salt = [ephemeral.as_slice(), recipient.as_slice()].concat();
+    let mut okm = [0u8; 44];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(KDF_INFO, &mut okm)
+        .map_err(|_| PayloadKeyError::Encrypt)?;
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    key.copy_from_slice(&okm[..32]);
+    nonce.copy_from_slice(&okm[32..]);
+    Ok((key, nonce))
+}
+
+fn decode_fixed<const N: usize>(value: &str) -> Result<[u8; N], PayloadKeyError> {
+    let bytes =
+        STANDARD.decode(value.trim()).map_err(|e| PayloadKeyError::InvalidKey(e.to_string()))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        PayloadKeyError::InvalidKey(format!("expected {} bytes, got {}", N, bytes.len()))
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionKeyRequest {
+    public_key: String,
+}
+
+/// 签名与加密密钥管理接口处理器
+pub struct PayloadKeyHandler {
+    keyring: Arc<RwLock<PayloadKeyring>>,
+    admin_token: Option<String>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for PayloadKeyHandler {
+    /// 随机签名密钥、未配置管理令牌
+    fn default() -> Self {
+        let clock = Arc::new(SystemClock);
+        let keyring = PayloadKeyring::generate(clock.now());
+        Self::new(Arc::new(RwLock::new(keyring)), clock)
+    }
+}
+
+impl PayloadKeyHandler {
+    pub fn new(keyring: Arc<RwLock<PayloadKeyring>>, clock: Arc<dyn TimestampProvider>) -> Self {
+        Self { keyring, admin_token: None, clock }
+    }
+
+    /// 从环境变量构建：签名密钥种子与管理令牌（与 API Key 管理接口共用）
+    pub fn from_env() -> Result<Self, PayloadKeyError> {
+        let clock = Arc::new(SystemClock);
+        let keyring = match std::env::var(ENV_PAYLOAD_SIGNING_KEY) {
+            Ok(seed) => PayloadKeyring::from_seed(decode_fixed(&seed)?, clock.now()),
+            Err(_) => PayloadKeyring::generate(clock.now()),
+        };
+        let handler = Self::new(Arc::new(RwLock::new(keyring)), clock);
+        Ok(match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        })
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// Webhook 与 drop-copy 投递方共享的密钥
+    pub fn keyring(&self) -> &Arc<RwLock<PayloadKeyring>> {
+        &self.keyring
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        match method {
+            "GET" => path == PAYLOAD_KEYS_PATH,
+            "POST" => matches!(
+                path,
+                ENCRYPTION_KEY_PATH | REMOVE_ENCRYPTION_KEY_PATH | ADMIN_ROTATE_SIGNING_KEY_PATH
+            ),
+            _ => false,
+        }
+    }
+
+    /// 生成完整的 HTTP 响应，`account` 为鉴权得到的账户（不可取自请求头）
+    pub fn respond(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+        account: Option<&str>,
+    ) -> Vec<u8> {
+        let (status, body) = self.render(method, path, request, account);
+        json_response(status, &body)
+    }
+
+    fn render(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+        account: Option<&str>,
+    ) -> (u16, String) {
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let route = path.split('?').next().unwrap_or(path);
+        let result = if route == ADMIN_ROTATE_SIGNING_KEY_PATH {
+            self.rotate(head)
+        } else if header_value(head, "X-Api-Key").is_some() {
+            Err((403, "API keys cannot manage payload keys".to_string()))
+        } else if header_value(head, ON_BEHALF_HEADER).is_some() {
+            Err((403, "Payload keys can only be managed by the account owner".to_string()))
+        } else {
+            match account.and_then(|id| id.parse::<u64>().ok()) {
+                Some(account_id) => {
+                    self.self_service(method, route, AccountId(account_id), request_body(request))
+                }
+                None => Err((401, "Authentication required".to_string())),
+            }
+        };
+        match result {
+            Ok(body) => (200, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn self_service(
+        &self,
+        method: &str,
+        route: &str,
+        account_id: AccountId,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        match (method, route) {
+            ("GET", _) => Ok(self.list(account_id)),
+            ("POST", ENCRYPTION_KEY_PATH) => {
+                let req: EncryptionKeyRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                let public_key = decode_fixed(&req.public_key).map_err(|e| (400, e.to_string()))?;
+                let recipient = self
+                    .keyring
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .register_recipient(account_id, public_key, self.clock.now())
+                    .map_err(|e| (400, e.to_string()))?;
+                Ok(recipient_json(&recipient))
+            }
+            _ => {
+                if !self
+                    .keyring
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .remove_recipient(account_id)
+                {
+                    return Err((404, "No encryption key registered".to_string()));
+                }
+                Ok(self.list(account_id))
+            }
+        }
+    }
+
+    fn rotate(&self, head: &str) -> Result<serde_json::Value, (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        let mut keyring = self.keyring.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(signing_json(keyring.rotate(self.clock.now())))
+    }
+
+    fn list(&self, account_id: AccountId) -> serde_json::Value {
+        let keyring = self.keyring.read().unwrap_or_else(PoisonError::into_inner);
+        serde_json::json!({
+            "signingKeys": keyring.signing_keys().map(signing_json).collect::<Vec<_>>(),
+            "encryptionKey": keyring.recipient(account_id).map(recipient_json),
+        })
+    }
+}
+
+fn signing_json(info: &SigningKeyInfo) -> serde_json::Value {
+    serde_json::json!({
+        "keyId": info.key_id,
+        "algorithm": "Ed25519",
+        "publicKey": STANDARD.encode(info.public_key),
+        "createdAt": millis(info.created_at),
+        "retiredAt": info.retired_at.map(millis),
+    })
+}
+
+fn recipient_json(recipient: &RecipientKey) -> serde_json::Value {
+    serde_json::json!({
+        "algorithm": "X25519",
+        "publicKey": STANDARD.encode(recipient.public_key),
+        "registeredAt": millis(recipient.registered_at),
+    })
+}
+
+fn millis(timestamp: Timestamp) -> u64 {
+    timestamp.0 / 1_000_000
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    fn now() -> Timestamp {
+        Timestamp(NOW_MS * 1_000_000)
+    }
+
+    fn handler() -> PayloadKeyHandler {
+        let keyring = PayloadKeyring::from_seed([7; 32], now());
+        PayloadKeyHandler::new(
+            Arc::new(RwLock::new(keyring)),
+            Arc::new(ManualClock::from_millis(NOW_MS)),
+        )
+        .with_admin_token("root")
+    }
+
+    fn post(handler: &PayloadKeyHandler, path: &str, headers: &str, body: &str) -> (u16, String) {
+        let request = format!("POST {} HTTP/1.1\r\n{}\r\n{}", path, headers, body);
+        handler.render("POST", path, request.as_bytes(), Some("10"))
+    }
+
+    #[test]
+    fn test_sign_encrypt_and_verify() {
+        let mut keyring = PayloadKeyring::from_seed([7; 32], now());
+        let report = br#"{"tradeId":7,"price":"100"}"#;
+
+        // 未登记加密公钥：只签名，篡改内容或时间戳后验证失败
+        let signed = keyring.seal(AccountId(10), report, now()).unwrap();
+        assert_eq!(signed.body, report);
+        assert_eq!(signed.timestamp, NOW_MS);
+        assert!(verify_payload(&keyring.active().public_key, &signed).is_ok());
+        let mut tampered = signed.clone();
+        tampered.timestamp += 1;
+        assert_eq!(keyring.verify(&tampered), Err(PayloadKeyError::BadSignature));
+
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public_key = PublicKey::from(&secret).to_bytes();
+        keyring.register_recipient(AccountId(10), public_key, now()).unwrap();
+        assert_eq!(
+            keyring.register_recipient(AccountId(10), [0; 32], now()),
+            Err(PayloadKeyError::WeakKey)
+        );
+        let sealed = keyring.seal(AccountId(10), report, now()).unwrap();
+        assert_ne!(sealed.body, report);
+        assert!(keyring.verify(&sealed).is_ok());
+        assert_eq!(open_payload(&secret, &sealed).unwrap(), report);
+        let other = StaticSecret::random_from_rng(OsRng);
+        assert_eq!(open_payload(&other, &sealed), Err(PayloadKeyError::Decrypt));
+
+        // 经请求头往返
+        let headers = sealed.headers();
+        let header = |name: &str| {
+            headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.clone())
+        };
+        assert_eq!(SealedPayload::from_headers(header, sealed.body.clone()).unwrap(), sealed);
+
+        // 轮换后旧报告仍可验证，新报告使用新密钥
+        let old_key_id = keyring.active().key_id.clone();
+        let new_key_id = keyring.rotate(now()).key_id.clone();
+        assert_ne!(old_key_id, new_key_id);
+        assert!(keyring.verify(&sealed).is_ok());
+        assert_eq!(keyring.seal(AccountId(11), report, now()).unwrap().key_id, new_key_id);
+
+        let path = std::env::temp_dir().join(format!("drop-copy-{}.json", uuid::Uuid::new_v4()));
+        write_drop_copy(&path, &sealed).unwrap();
+        let signature_path = format!("{}{}", path.display(), SIGNATURE_FILE_SUFFIX);
+        let signature: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&signature_path).unwrap()).unwrap();
+        assert_eq!(signature["keyId"], old_key_id);
+        assert_eq!(std::fs::read(&path).unwrap(), sealed.body);
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(signature_path).unwrap();
+    }
+
+    #[test]
+    fn test_key_management_endpoints() {
+        let handler = handler();
+        let public_key = PublicKey::from(&StaticSecret::random_from_rng(OsRng)).to_bytes();
+        let body = format!(r#"{{"publicKey":"{}"}}"#, STANDARD.encode(public_key));
+        let (status, registered) = post(&handler, ENCRYPTION_KEY_PATH, "", &body);
+        assert_eq!(status, 200);
+        let registered: serde_json::Value = serde_json::from_str(&registered).unwrap();
+        assert_eq!(registered["registeredAt"], NOW_MS);
+        assert_eq!(post(&handler, ENCRYPTION_KEY_PATH, "", r#"{"publicKey":"AAAA"}"#).0, 400);
+        assert_eq!(post(&handler, ENCRYPTION_KEY_PATH, "X-Api-Key: k\r\n", &body).0, 403);
+        assert_eq!(post(&handler, ENCRYPTION_KEY_PATH, "X-On-Behalf-Of: 11\r\n", &body).0, 403);
+        // 未鉴权的请求不能按请求头中的用户ID登记
+        let request =
+            format!("POST {} HTTP/1.1\r\nX-User-Id: 10\r\n\r\n{}", ENCRYPTION_KEY_PATH, body);
+        assert_eq!(handler.render("POST", ENCRYPTION_KEY_PATH, request.as_bytes(), None).0, 401);
+
+        let (_, listed) =
+            handler.render("GET", PAYLOAD_KEYS_PATH, b"GET / HTTP/1.1\r\n\r\n", Some("10"));
+        let listed: serde_json::Value = serde_json::from_str(&listed).unwrap();
+        assert_eq!(listed["encryptionKey"]["publicKey"], STANDARD.encode(public_key));
+        assert_eq!(listed["signingKeys"].as_array().unwrap().len(), 1);
+
+        assert_eq!(post(&handler, ADMIN_ROTATE_SIGNING_KEY_PATH, "", "").0, 401);
+        let (status, rotated) =
+            post(&handler, ADMIN_ROTATE_SIGNING_KEY_PATH, "X-Admin-Token: root\r\n", "");
+        assert_eq!(status, 200);
+        let rotated: serde_json::Value = serde_json::from_str(&rotated).unwrap();
+        assert!(rotated["retiredAt"].is_null());
+
+        let (status, listed) = post(&handler, REMOVE_ENCRYPTION_KEY_PATH, "", "");
+        assert_eq!(status, 200);
+        let listed: serde_json::Value = serde_json::from_str(&listed).unwrap();
+        assert!(listed["encryptionKey"].is_null());
+        assert_eq!(listed["signingKeys"][0]["retiredAt"], NOW_MS);
+        assert_eq!(post(&handler, REMOVE_ENCRYPTION_KEY_PATH, "", "").0, 404);
+    }
+}
@@ -0,0 +1,404 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use diff::EntityReplayableEvent;
+use prep::domain::entity::{MarginMode, Position, PositionSide, SETTING_HISTORY_LIMIT, Side};
+use prep::domain::service::{
+    AccountSnapshot, PositionRisk, PrepQueryHandler, QueuePosition, ReadModelProjection,
+};
+
+use super::exchange_info::{json_response, query_param};
+
+/// 账户快照接口路径
+pub const ACCOUNT_PATH: &str = "/api/account";
+/// 仓位风险接口路径
+pub const POSITION_RISK_PATH: &str = "/api/prep/positionRisk";
+/// 挂单队列位置接口路径
+pub const QUEUE_POSITION_PATH: &str = "/api/prep/order/queuePosition";
+/// 杠杆与保证金设置变更历史接口路径
+pub const SETTING_HISTORY_PATH: &str = "/api/prep/account/settingHistory";
+
+/// 变更历史默认条数
+const DEFAULT_SETTING_HISTORY: usize = 50;
+
+/// 合约账户查询处理器
+///
+/// 由消费撮合事件流的读侧投影应答，只返回已鉴权账户（JWT 或 API Key 签名）自己的数据：
+/// - `GET /api/account`：余额、持仓、挂单占用保证金与未实现盈亏，
+///   在投影同一序列号下组装（余额由账户服务经 [`ReadModelProjection::update_balance`] 写入）
+/// - `GET /api/prep/positionRisk`：按投影最新标记价格计算的强平价、保证金率与 ADL 档位，
+///   尚未收到标记价格时应答 503
+/// - `GET /api/prep/order/queuePosition?orderId=`：挂单在其价位上的排队位置（前方订单数与数量），
+///   他人订单与不存在的订单同样应答 404
+/// - `GET /api/prep/account/settingHistory`：杠杆、保证金模式与逐仓保证金的变更历史，
+///   以变更日志条目返回（最新在前）
+pub struct PrepAccountHandler {
+    projection: Arc<RwLock<ReadModelProjection>>,
+}
+
+impl Default for PrepAccountHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(ReadModelProjection::new())))
+    }
+}
+
+impl PrepAccountHandler {
+    pub fn new(projection: Arc<RwLock<ReadModelProjection>>) -> Self {
+        Self { projection }
+    }
+
+    /// 撮合事件写入投影的入口
+    pub fn projection(&self) -> Arc<RwLock<ReadModelProjection>> {
+        self.projection.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "GET"
+            && (route == Some(ACCOUNT_PATH)
+                || route == Some(POSITION_RISK_PATH)
+                || route == Some(QUEUE_POSITION_PATH)
+                || route == Some(SETTING_HISTORY_PATH))
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, path: &str, account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(trader) = account.parse::<u64>() else {
+            return Self::bad_request(format!("Invalid account: {}", account));
+        };
+        match path.split('?').next() {
+            Some(ACCOUNT_PATH) => {
+                let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+                let snapshot = projection.account(trader, projection.mark_price());
+                (200, Self::snapshot_json(&snapshot).to_string())
+            }
+            Some(POSITION_RISK_PATH) => self.position_risk(trader),
+            Some(QUEUE_POSITION_PATH) => self.queue_position(trader, path),
+            _ => self.setting_history(trader, path),
+        }
+    }
+
+    fn position_risk(&self, trader: u64) -> (u16, String) {
+        let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+        let Some(mark_price) = projection.mark_price() else {
+            return (503, serde_json::json!({ "msg": "Mark price unavailable" }).to_string());
+        };
+        let risks: Vec<serde_json::Value> =
+            projection.position_risk(trader, mark_price).iter().map(Self::risk_json).collect();
+        (200, serde_json::Value::Array(risks).to_string())
+    }
+
+    /// 参数：`orderId`（必填）
+    fn queue_position(&self, trader: u64, path: &str) -> (u16, String) {
+        let Some(order_id) = query_param(path, "orderId") else {
+            return Self::bad_request("Missing parameter: orderId".to_string());
+        };
+        let Ok(order_id) = order_id.parse::<u64>() else {
+            return Self::bad_request("Invalid parameter: orderId".to_string());
+        };
+        let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+        match projection.queue_position(trader, order_id) {
+            Ok(queue) => (200, Self::queue_json(&queue).to_string()),
+            Err(_) => (404, serde_json::json!({ "msg": "Unknown order" }).to_string()),
+        }
+    }
+
+    /// 参数：`limit`（默认 50，最大为引擎每个账户保留的条数）
+    fn setting_history(&self, trader: u64, path: &str) -> (u16, String) {
+        let limit = match query_param(path, "limit").map(str::parse::<usize>) {
+            None => DEFAULT_SETTING_HISTORY,
+            Some(Ok(limit)) if (1..=SETTING_HISTORY_LIMIT).contains(&limit) => limit,
+            Some(_) => return Self::bad_request("Invalid parameter: limit".to_string()),
+        };
+
+        let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+        let entries: Vec<serde_json::Value> = projection
+            .setting_history(trader, limit)
+            .iter()
+            .map(|record| Self::entry_json(&record.change_log_entry()))
+            .collect();
+        let body = serde_json::json!({
+            "accountId": trader,
+            "sequence": projection.sequence(),
+            "entries": entries,
+        });
+        (200, body.to_string())
+    }
+
+    fn snapshot_json(snapshot: &AccountSnapshot) -> serde_json::Value {
+        let balances: Vec<serde_json::Value> = snapshot
+            .balances
+            .iter()
+            .map(|balance| {
+                serde_json::json!({
+                    "asset": balance.asset,
+                    "available": balance.available,
+                    "frozen": balance.frozen,
+                })
+            })
+            .collect();
+        let positions: Vec<serde_json::Value> =
+            snapshot.positions.iter().filter(|p| !p.is_empty()).map(Self::position_json).collect();
+        serde_json::json!({
+            "accountId": snapshot.trader,
+            "sequence": snapshot.sequence,
+            "time": snapshot.timestamp,
+            "balances": balances,
+            "positions": positions,
+            "openOrderMargin": snapshot.open_order_margin,
+            "unrealizedPnl": snapshot.unrealized_pnl,
+        })
+    }
+
+    fn queue_json(queue: &QueuePosition) -> serde_json::Value {
+        serde_json::json!({
+            "orderId": queue.order_id,
+            "side": match queue.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            },
+            "price": queue.price,
+            "ordersAhead": queue.orders_ahead,
+            "quantityAhead": queue.quantity_ahead,
+            "remainingQuantity": queue.remaining_quantity,
+            "levelQuantity": queue.level_quantity,
+            "sequence": queue.sequence,
+        })
+    }
+
+    fn position_json(position: &Position) -> serde_json::Value {
+        serde_json::json!({
+            "positionId": position.id,
+            "positionSide": Self::position_side(position.position_side),
+            "quantity": position.quantity,
+            "entryPrice": position.entry_price,
+            "marginMode": match position.margin_mode {
+                MarginMode::Cross => "CROSS",
+                MarginMode::Isolated => "ISOLATED",
+            },
+            "leverage": position.leverage,
+            "margin": position.margin,
+            "unrealizedPnl": position.unrealized_pnl,
+            "liquidationPrice": position.liquidation_price,
+        })
+    }
+
+    fn risk_json(risk: &PositionRisk) -> serde_json::Value {
+        serde_json::json!({
+            "positionId": risk.position_id,
+            "positionSide": Self::position_side(risk.position_side),
+            "quantity": risk.quantity,
+            "entryPrice": risk.entry_price,
+            "markPrice": risk.mark_price,
+            "liquidationPrice": risk.liquidation_price,
+            "leverage": risk.leverage,
+            "margin": risk.margin,
+            "unrealizedPnl": risk.unrealized_pnl,
+            "maintenanceMargin": risk.maintenance_margin,
+            "marginRatioBps": risk.margin_ratio_bps,
+            "adlRank": risk.adl_rank,
+            "sequence": risk.sequence,
+        })
+    }
+
+    fn position_side(position_side: PositionSide) -> &'static str {
+        match position_side {
+            PositionSide::Both => "BOTH",
+            PositionSide::Long => "LONG",
+            PositionSide::Short => "SHORT",
+        }
+    }
+
+    fn entry_json(entry: &EntityReplayableEvent) -> serde_json::Value {
+        let fields: Vec<serde_json::Value> = entry
+            .field_changes
+            .iter()
+            .map(|field| {
+                serde_json::json!({
+                    "name": field.field_name_as_str().unwrap_or_default(),
+                    "from": String::from_utf8_lossy(field.old_value_bytes()),
+                    "to": String::from_utf8_lossy(field.new_value_bytes()),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "sequence": entry.sequence,
+            "time": entry.timestamp,
+            "oldVersion": entry.old_version,
+            "newVersion": entry.new_version,
+            "fields": fields,
+        })
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prep::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use prep::domain::entity::{AssetBalance, TimeInForce};
+    use prep::domain::service::{Command, MatchingService, PrepCommandHandler};
+
+    use super::*;
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn engine() -> Engine {
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.set_timestamp(1_000);
+        engine
+    }
+
+    fn limit(trader: u64, side: Side, quantity: u64) -> Command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price: 100,
+            quantity,
+            position_side: if side == Side::Buy { PositionSide::Long } else { PositionSide::Short },
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_account_snapshot_for_authenticated_account() {
+        let mut engine = engine();
+        engine.handle(limit(1, Side::Sell, 5));
+        engine.handle(limit(2, Side::Buy, 3));
+        engine.handle(limit(2, Side::Buy, 4));
+
+        let handler = PrepAccountHandler::default();
+        let projection = handler.projection();
+        projection.write().unwrap().apply_all(&engine.drain_events());
+        projection.write().unwrap().update_balance(2, AssetBalance::new("USDT", 900, 100));
+        assert!(PrepAccountHandler::matches("GET", ACCOUNT_PATH));
+        assert!(!PrepAccountHandler::matches("POST", ACCOUNT_PATH));
+
+        let (status, body) = handler.render(ACCOUNT_PATH, Some("2"));
+        assert_eq!(status, 200);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["accountId"], 2);
+        assert_eq!(body["sequence"], 3);
+        assert_eq!(body["balances"][0]["asset"], "USDT");
+        assert_eq!(body["balances"][0]["frozen"], 100);
+        let positions = body["positions"].as_array().unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(
+            (positions[0]["positionSide"].as_str(), positions[0]["quantity"].as_u64()),
+            (Some("LONG"), Some(5))
+        );
+        // 剩余 2 仍挂在簿上，占用保证金
+        assert!(body["openOrderMargin"].as_u64().unwrap() > 0);
+
+        let (_, body) = handler.render(ACCOUNT_PATH, Some("3"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["positions"].as_array().map(Vec::len), Some(0));
+        assert_eq!(handler.render(ACCOUNT_PATH, None).0, 401);
+    }
+
+    #[test]
+    fn test_position_risk_at_latest_mark_price() {
+        let mut engine = engine();
+        engine.handle(limit(1, Side::Sell, 5));
+        engine.handle(limit(2, Side::Buy, 3));
+        let handler = PrepAccountHandler::default();
+        let projection = handler.projection();
+        projection.write().unwrap().apply_all(&engine.drain_events());
+        assert!(PrepAccountHandler::matches("GET", POSITION_RISK_PATH));
+        assert_eq!(handler.render(POSITION_RISK_PATH, Some("2")).0, 503);
+
+        projection.write().unwrap().on_mark_price(90);
+        let (status, body) = handler.render(POSITION_RISK_PATH, Some("2"));
+        assert_eq!(status, 200, "{}", body);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let risks = body.as_array().unwrap();
+        assert_eq!(risks.len(), 1);
+        assert_eq!(
+            (risks[0]["positionSide"].as_str(), risks[0]["markPrice"].as_u64()),
+            (Some("LONG"), Some(90))
+        );
+        assert!(risks[0]["unrealizedPnl"].as_i64().unwrap() < 0);
+        assert_eq!(handler.render(POSITION_RISK_PATH, None).0, 401);
+    }
+
+    #[test]
+    fn test_queue_position_for_order_owner_only() {
+        let mut engine = engine();
+        engine.handle(limit(1, Side::Sell, 5));
+        engine.handle(limit(3, Side::Sell, 4));
+        let handler = PrepAccountHandler::default();
+        handler.projection().write().unwrap().apply_all(&engine.drain_events());
+        let order_id = handler.projection().read().unwrap().open_orders(3)[0].id;
+
+        let path = format!("{}?orderId={}", QUEUE_POSITION_PATH, order_id);
+        assert!(PrepAccountHandler::matches("GET", &path));
+        let (status, body) = handler.render(&path, Some("3"));
+        assert_eq!(status, 200, "{}", body);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            (body["ordersAhead"].as_u64(), body["quantityAhead"].as_u64()),
+            (Some(1), Some(5))
+        );
+        assert_eq!(
+            (body["side"].as_str(), body["levelQuantity"].as_u64()),
+            (Some("SELL"), Some(9))
+        );
+
+        // 他人订单与不存在的订单一样应答 404
+        assert_eq!(handler.render(&path, Some("1")).0, 404);
+        assert_eq!(
+            handler.render(&format!("{}?orderId=999", QUEUE_POSITION_PATH), Some("3")).0,
+            404
+        );
+        assert_eq!(handler.render(QUEUE_POSITION_PATH, Some("3")).0, 400);
+        assert_eq!(handler.render(&path, None).0, 401);
+    }
+
+    #[test]
+    fn test_setting_history_for_authenticated_account() {
+        let mut engine = engine();
+        engine.handle(Command::SwitchMarginMode { trader: 7, mode: MarginMode::Isolated });
+        engine.handle(Command::SetLeverage { trader: 7, leverage: 20, position_side: None });
+        engine.handle(Command::SetLeverage { trader: 8, leverage: 5, position_side: None });
+
+        let handler = PrepAccountHandler::default();
+        handler.projection().write().unwrap().apply_all(&engine.drain_events());
+
+        let (status, body) = handler.render(SETTING_HISTORY_PATH, Some("7"));
+        assert_eq!(status, 200);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let entries = body["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["newVersion"], 2);
+        assert_eq!(entries[0]["fields"][0]["name"], "leverage");
+        assert_eq!(entries[0]["fields"][0]["from"], "10");
+        assert_eq!(entries[0]["fields"][0]["to"], "20");
+        assert_eq!(entries[1]["fields"][0]["to"], "ISOLATED");
+
+        let (_, body) = handler.render(&format!("{}?limit=1", SETTING_HISTORY_PATH), Some("7"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap()["entries"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // 只按鉴权账户应答
+        assert_eq!(handler.render(SETTING_HISTORY_PATH, None).0, 401);
+        assert_eq!(handler.render(&format!("{}?limit=0", SETTING_HISTORY_PATH), Some("7")).0, 400);
+    }
+}
@@ -0,0 +1,509 @@
+//! 合约引擎管理接口
+//!
+//! 管理操作以命令形式提交到合约撮合分片，与其他命令一样写入命令日志、按序处理，
+//! 处理结果随分片输出（结果与引擎事件）发布：
+//! - `POST /api/admin/prep/tradeBust`：`{tradeId, reason, operator}` 撤销错误成交，
+//!   双方仓位反向恢复并发布冲正结算
+//! - `POST /api/admin/prep/riskProfile`：`{accountId, kind, maxOrderNotional?, maxOpenOrders?,
+//!   postTrade?, operator}` 设置账户风控档案，省略的限额为不限；做市商档案须给出 `postTrade`
+//! - `POST /api/admin/prep/killSwitch/reset`：`{accountId, operator}` 解除账户熔断
+//! - `POST /api/admin/prep/featureFlag`：`{feature, enabled, accounts, percent, operator}`
+//!   设置功能开关的灰度规则（三者皆空即关闭）
+//! - `GET /api/admin/engine/stats`：按交易对返回挂单数、盘口深度、最新序列号、每秒撮合数、
+//!   拒单数与订单池利用率；读取撮合分片发布的计数器，不经过命令队列
+//!
+//! 启动时 `PREP_FEATURE_FLAGS`（格式见 [`parse_flag_config`]）中的开关在接入撮合分片时
+//! 以 `config` 为操作员提交，与管理接口的变更一样写入命令日志
+//!
+//! 以 `X-Admin-Token` 鉴权，未配置令牌时关闭；未接入撮合分片时命令接口返回 503
+
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+
+use prep::domain::entity::{PostTradeLimits, RiskProfile};
+use prep::domain::service::{
+    Command, EngineStatsRegistry, Feature, FlagRule, SymbolStats, parse_flag_config,
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use super::api_keys::{ADMIN_TOKEN_HEADER, ENV_ADMIN_TOKEN};
+use super::codec::{header_value, request_body};
+use super::exchange_info::json_response;
+
+/// 撤销成交接口路径
+pub const ADMIN_TRADE_BUST_PATH: &str = "/api/admin/prep/tradeBust";
+/// 风控档案接口路径
+pub const ADMIN_RISK_PROFILE_PATH: &str = "/api/admin/prep/riskProfile";
+/// 解除熔断接口路径
+pub const ADMIN_KILL_SWITCH_RESET_PATH: &str = "/api/admin/prep/killSwitch/reset";
+/// 功能开关接口路径
+pub const ADMIN_FEATURE_FLAG_PATH: &str = "/api/admin/prep/featureFlag";
+/// 引擎运行统计接口路径
+pub const ADMIN_ENGINE_STATS_PATH: &str = "/api/admin/engine/stats";
+
+/// 启动功能开关配置的环境变量
+pub const ENV_FEATURE_FLAGS: &str = "PREP_FEATURE_FLAGS";
+
+/// 启动配置提交开关变更的操作员
+const CONFIG_OPERATOR: &str = "config";
+
+const ADMIN_PATHS: [&str; 4] = [
+    ADMIN_TRADE_BUST_PATH,
+    ADMIN_RISK_PROFILE_PATH,
+    ADMIN_KILL_SWITCH_RESET_PATH,
+    ADMIN_FEATURE_FLAG_PATH,
+];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TradeBustRequest {
+    trade_id: u64,
+    reason: String,
+    operator: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RiskProfileRequest {
+    account_id: u64,
+    /// `STANDARD` 或 `MARKET_MAKER`
+    kind: String,
+    max_order_notional: Option<u64>,
+    max_open_orders: Option<usize>,
+    post_trade: Option<PostTradeRequest>,
+    operator: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostTradeRequest {
+    max_position_quantity: u64,
+    max_realized_loss: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KillSwitchResetRequest {
+    account_id: u64,
+    operator: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeatureFlagRequest {
+    feature: String,
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    accounts: Vec<u64>,
+    #[serde(default)]
+    percent: u8,
+    operator: String,
+}
+
+/// 合约引擎管理接口处理器
+#[derive(Default)]
+pub struct PrepAdminHandler {
+    engine: Option<Sender<Command>>,
+    admin_token: Option<String>,
+    /// 启动功能开关，接入撮合分片时提交
+    startup_flags: Vec<(Feature, FlagRule)>,
+    /// 撮合分片的运行统计计数器
+    stats: Arc<EngineStatsRegistry>,
+}
+
+impl PrepAdminHandler {
+    /// 配置管理令牌时开放管理接口
+    ///
+    /// `PREP_FEATURE_FLAGS` 格式错误时忽略整项配置并告警
+    pub fn from_env() -> Self {
+        let mut handler = Self::default();
+        if let Ok(config) = std::env::var(ENV_FEATURE_FLAGS) {
+            match parse_flag_config(&config) {
+                Ok(flags) => handler = handler.with_startup_flags(flags),
+                Err(e) => warn!("Ignoring {}: {}", ENV_FEATURE_FLAGS, e),
+            }
+        }
+        match std::env::var(ENV_ADMIN_TOKEN).ok().filter(|token| !token.is_empty()) {
+            Some(admin_token) => handler.with_admin_token(admin_token),
+            None => handler,
+        }
+    }
+
+    /// 启用管理接口
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// 启动功能开关
+    pub fn with_startup_flags(mut self, flags: Vec<(Feature, FlagRule)>) -> Self {
+        self.startup_flags = flags;
+        self
+    }
+
+    /// 接入撮合分片的命令发送端，并提交启动功能开关
+    pub fn with_engine(mut self, engine: Sender<Command>) -> Self {
+        for (feature, rule) in std::mem::take(&mut self.startup_flags) {
+            let operator = CONFIG_OPERATOR.to_string();
+            if engine.send(Command::SetFeatureFlag { feature, rule, operator }).is_err() {
+                warn!("Matching engine stopped before startup flag {}", feature.as_str());
+            }
+        }
+        self.engine = Some(engine);
+        self
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next().unwrap_or(path);
+        match method {
+            "GET" => route == ADMIN_ENGINE_STATS_PATH,
+            "POST" => ADMIN_PATHS.contains(&route),
+            _ => false,
+        }
+    }
+
+    /// 运行统计注册表（撮合分片按交易对注册，并把计数器交给引擎）
+    pub fn engine_stats(&self) -> Arc<EngineStatsRegistry> {
+        Arc::clone(&self.stats)
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str, request: &[u8]) -> Vec<u8> {
+        let (status, body) = self.render(path, request);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，命令已提交返回 202
+    fn render(&self, path: &str, request: &[u8]) -> (u16, String) {
+        let route = path.split('?').next().unwrap_or(path);
+        let result = self.authorize(request).and_then(|()| match route {
+            ADMIN_ENGINE_STATS_PATH => Ok((200, self.stats_body())),
+            _ => self.admin(route, request).map(|body| (202, body)),
+        });
+        match result {
+            Ok((status, body)) => (status, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn authorize(&self, request: &[u8]) -> Result<(), (u16, String)> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err((403, "Admin API disabled".to_string()));
+        };
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let token = header_value(head, ADMIN_TOKEN_HEADER).unwrap_or_default();
+        if !constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+            return Err((401, "Invalid admin token".to_string()));
+        }
+        Ok(())
+    }
+
+    fn stats_body(&self) -> serde_json::Value {
+        let symbols: Vec<_> = self.stats.collect().iter().map(symbol_stats_json).collect();
+        serde_json::json!({ "symbols": symbols })
+    }
+
+    fn admin(&self, route: &str, request: &[u8]) -> Result<serde_json::Value, (u16, String)> {
+        let body = request_body(request);
+        let (command, accepted) = match route {
+            ADMIN_TRADE_BUST_PATH => {
+                let req: TradeBustRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                if req.reason.is_empty() || req.operator.is_empty() {
+                    return Err((400, "reason and operator are required".to_string()));
+                }
+                let accepted =
+                    serde_json::json!({ "command": "tradeBust", "tradeId": req.trade_id });
+                let command = Command::BustTrade {
+                    trade_id: req.trade_id,
+                    reason: req.reason,
+                    operator: req.operator,
+                };
+                (command, accepted)
+            }
+            ADMIN_RISK_PROFILE_PATH => {
+                let req: RiskProfileRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                if req.operator.is_empty() {
+                    return Err((400, "operator is required".to_string()));
+                }
+                let post_trade = req.post_trade.map(|limits| PostTradeLimits {
+                    max_position_quantity: limits.max_position_quantity,
+                    max_realized_loss: limits.max_realized_loss,
+                });
+                let profile = match (req.kind.as_str(), post_trade) {
+                    ("STANDARD", None) => RiskProfile::standard(),
+                    ("MARKET_MAKER", Some(limits)) => RiskProfile::market_maker(usize::MAX, limits),
+                    ("STANDARD", Some(_)) => {
+                        return Err((400, "postTrade applies to MARKET_MAKER only".to_string()));
+                    }
+                    ("MARKET_MAKER", None) => {
+                        return Err((400, "postTrade is required for MARKET_MAKER".to_string()));
+                    }
+                    (kind, _) => return Err((400, format!("Unknown profile kind: {}", kind))),
+                };
+                let profile = profile.with_limits(
+                    req.max_order_notional.unwrap_or(u64::MAX),
+                    req.max_open_orders.unwrap_or(usize::MAX),
+                );
+                let accepted = serde_json::json!({
+                    "command": "riskProfile",
+                    "accountId": req.account_id,
+                    "kind": req.kind,
+                });
+                let command = Command::SetRiskProfile {
+                    trader: req.account_id,
+                    profile,
+                    operator: req.operator,
+                };
+                (command, accepted)
+            }
+            ADMIN_KILL_SWITCH_RESET_PATH => {
+                let req: KillSwitchResetRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                if req.operator.is_empty() {
+                    return Err((400, "operator is required".to_string()));
+                }
+                let accepted = serde_json::json!({
+                    "command": "killSwitchReset",
+                    "accountId": req.account_id,
+                });
+                let command =
+                    Command::ResetKillSwitch { trader: req.account_id, operator: req.operator };
+                (command, accepted)
+            }
+            ADMIN_FEATURE_FLAG_PATH => {
+                let req: FeatureFlagRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                if req.operator.is_empty() {
+                    return Err((400, "operator is required".to_string()));
+                }
+                let feature = Feature::parse(&req.feature)
+                    .ok_or_else(|| (400, format!("Unknown feature: {}", req.feature)))?;
+                if req.percent > 100 {
+                    return Err((400, "percent must be within 0-100".to_string()));
+                }
+                let mut rule = FlagRule::accounts(req.accounts).with_percent(req.percent);
+                rule.enabled = req.enabled;
+                let accepted = serde_json::json!({
+                    "command": "featureFlag",
+                    "feature": feature.as_str(),
+                    "enabled": rule.enabled,
+                    "accounts": rule.accounts,
+                    "percent": rule.percent,
+                });
+                (Command::SetFeatureFlag { feature, rule, operator: req.operator }, accepted)
+            }
+            _ => return Err((404, format!("Unknown admin route: {}", route))),
+        };
+        self.submit(command)?;
+        Ok(accepted)
+    }
+
+    fn submit(&self, command: Command) -> Result<(), (u16, String)> {
+        let Some(engine) = &self.engine else {
+            return Err((503, "Matching engine not connected".to_string()));
+        };
+        engine.send(command).map_err(|_| (503, "Matching engine stopped".to_string()))
+    }
+}
+
+fn symbol_stats_json(stats: &SymbolStats) -> serde_json::Value {
+    serde_json::json!({
+        "symbol": stats.symbol,
+        "sequence": stats.sequence,
+        "restingOrders": stats.book.resting_orders,
+        "bidLevels": stats.book.bid_levels,
+        "askLevels": stats.book.ask_levels,
+        "bidQuantity": stats.book.bid_quantity,
+        "askQuantity": stats.book.ask_quantity,
+        "matchesTotal": stats.matches_total,
+        "matchesPerSec": stats.matches_per_sec,
+        "rejects": stats.rejects,
+        "poolUtilization": stats.pool_utilization,
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn admin_request(path: &str, body: &str) -> Vec<u8> {
+        format!("POST {} HTTP/1.1\r\nX-Admin-Token: secret\r\n\r\n{}", path, body).into_bytes()
+    }
+
+    #[test]
+    fn test_trade_bust_submitted_to_engine() {
+        let bust = r#"{"tradeId":42,"reason":"fat finger","operator":"ops"}"#;
+        let request = admin_request(ADMIN_TRADE_BUST_PATH, bust);
+        assert!(PrepAdminHandler::matches("POST", ADMIN_TRADE_BUST_PATH));
+
+        // 未配置令牌时关闭，未接入分片时不可用
+        assert_eq!(PrepAdminHandler::default().render(ADMIN_TRADE_BUST_PATH, &request).0, 403);
+        let detached = PrepAdminHandler::default().with_admin_token("secret");
+        assert_eq!(detached.render(ADMIN_TRADE_BUST_PATH, &request).0, 503);
+
+        let (engine, inbox) = mpsc::channel();
+        let handler = PrepAdminHandler::default().with_admin_token("secret").with_engine(engine);
+        let (status, body) = handler.render(ADMIN_TRADE_BUST_PATH, &request);
+        assert_eq!(status, 202);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["tradeId"], 42);
+        match inbox.try_recv().unwrap() {
+            Command::BustTrade { trade_id, reason, operator } => {
+                assert_eq!(
+                    (trade_id, reason.as_str(), operator.as_str()),
+                    (42, "fat finger", "ops")
+                );
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        let anonymous = format!("POST {} HTTP/1.1\r\n\r\n{}", ADMIN_TRADE_BUST_PATH, bust);
+        assert_eq!(handler.render(ADMIN_TRADE_BUST_PATH, anonymous.as_bytes()).0, 401);
+        let missing =
+            admin_request(ADMIN_TRADE_BUST_PATH, r#"{"tradeId":42,"reason":"","operator":"ops"}"#);
+        assert_eq!(handler.render(ADMIN_TRADE_BUST_PATH, &missing).0, 400);
+        assert!(inbox.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_risk_profile_and_kill_switch_reset() {
+        let (engine, inbox) = mpsc::channel();
+        let handler = PrepAdminHandler::default().with_admin_token("secret").with_engine(engine);
+        assert!(PrepAdminHandler::matches("POST", ADMIN_KILL_SWITCH_RESET_PATH));
+
+        // 未给出的限额为不限
+        let body = r#"{"accountId":7,"kind":"MARKET_MAKER","maxOpenOrders":500,
+            "postTrade":{"maxPositionQuantity":100,"maxRealizedLoss":1000},"operator":"ops"}"#;
+        let request = admin_request(ADMIN_RISK_PROFILE_PATH, body);
+        assert_eq!(handler.render(ADMIN_RISK_PROFILE_PATH, &request).0, 202);
+        match inbox.try_recv().unwrap() {
+            Command::SetRiskProfile { trader, profile, operator } => {
+                let limits =
+                    PostTradeLimits { max_position_quantity: 100, max_realized_loss: 1000 };
+                assert_eq!(
+                    profile,
+                    RiskProfile::market_maker(500, limits).with_limits(u64::MAX, 500)
+                );
+                assert_eq!((trader, operator.as_str()), (7, "ops"));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        let body =
+            r#"{"accountId":7,"kind":"STANDARD","maxOrderNotional":1000000,"operator":"ops"}"#;
+        let request = admin_request(ADMIN_RISK_PROFILE_PATH, body);
+        assert_eq!(handler.render(ADMIN_RISK_PROFILE_PATH, &request).0, 202);
+        match inbox.try_recv().unwrap() {
+            Command::SetRiskProfile { profile, .. } => {
+                assert_eq!(profile, RiskProfile::standard().with_limits(1_000_000, usize::MAX));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        // 做市商须配置事后监控
+        let body = r#"{"accountId":7,"kind":"MARKET_MAKER","operator":"ops"}"#;
+        let request = admin_request(ADMIN_RISK_PROFILE_PATH, body);
+        assert_eq!(handler.render(ADMIN_RISK_PROFILE_PATH, &request).0, 400);
+
+        let request =
+            admin_request(ADMIN_KILL_SWITCH_RESET_PATH, r#"{"accountId":7,"operator":"ops"}"#);
+        assert_eq!(handler.render(ADMIN_KILL_SWITCH_RESET_PATH, &request).0, 202);
+        assert!(matches!(inbox.try_recv().unwrap(), Command::ResetKillSwitch { trader: 7, .. }));
+        assert!(inbox.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_feature_flag_startup_config_and_admin() {
+        let flags = parse_flag_config("self_trade_prevention=accounts:7").unwrap();
+        let (engine, inbox) = mpsc::channel();
+        let handler = PrepAdminHandler::default()
+            .with_admin_token("secret")
+            .with_startup_flags(flags)
+            .with_engine(engine);
+
+        // 启动配置在接入分片时提交
+        match inbox.try_recv().unwrap() {
+            Command::SetFeatureFlag { feature, rule, operator } => {
+                assert_eq!(feature, Feature::SelfTradePrevention);
+                assert_eq!(rule, FlagRule::accounts([7]));
+                assert_eq!(operator, CONFIG_OPERATOR);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        let body = r#"{"feature":"self_trade_prevention","accounts":[9,3,9],"percent":5,"operator":"ops"}"#;
+        let request = admin_request(ADMIN_FEATURE_FLAG_PATH, body);
+        let (status, body) = handler.render(ADMIN_FEATURE_FLAG_PATH, &request);
+        assert_eq!(status, 202);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["accounts"][1], 9);
+        match inbox.try_recv().unwrap() {
+            Command::SetFeatureFlag { rule, .. } => {
+                assert_eq!(rule, FlagRule::accounts([3, 9]).with_percent(5));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        for body in [
+            r#"{"feature":"bogus","enabled":true,"operator":"ops"}"#,
+            r#"{"feature":"self_trade_prevention","percent":101,"operator":"ops"}"#,
+        ] {
+            let request = admin_request(ADMIN_FEATURE_FLAG_PATH, body);
+            assert_eq!(handler.render(ADMIN_FEATURE_FLAG_PATH, &request).0, 400);
+        }
+        assert!(inbox.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_engine_stats_endpoint() {
+        use prep::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+        use prep::domain::entity::{PositionSide, Side, TimeInForce};
+        use prep::domain::service::{MatchingService, PrepCommandHandler};
+
+        let request =
+            format!("GET {} HTTP/1.1\r\nX-Admin-Token: secret\r\n\r\n", ADMIN_ENGINE_STATS_PATH)
+                .into_bytes();
+        assert!(PrepAdminHandler::matches("GET", ADMIN_ENGINE_STATS_PATH));
+        assert!(!PrepAdminHandler::matches("POST", ADMIN_ENGINE_STATS_PATH));
+        assert_eq!(PrepAdminHandler::default().render(ADMIN_ENGINE_STATS_PATH, &request).0, 403);
+
+        let handler = PrepAdminHandler::default().with_admin_token("secret");
+        let unauthorized = format!("GET {} HTTP/1.1\r\n\r\n", ADMIN_ENGINE_STATS_PATH);
+        assert_eq!(handler.render(ADMIN_ENGINE_STATS_PATH, unauthorized.as_bytes()).0, 401);
+
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.attach_stats(handler.engine_stats().register("BTCUSDT"));
+        engine.handle(Command::LimitOrder {
+            trader: 1,
+            side: Side::Buy,
+            price: 100,
+            quantity: 3,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        engine.publish_book_stats();
+
+        let (status, body) = handler.render(ADMIN_ENGINE_STATS_PATH, &request);
+        assert_eq!(status, 200);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let btc = &body["symbols"][0];
+        assert_eq!(btc["symbol"], "BTCUSDT");
+        assert_eq!(btc["sequence"], 1);
+        assert_eq!(
+            (btc["restingOrders"].as_u64(), btc["bidQuantity"].as_u64()),
+            (Some(1), Some(3))
+        );
+    }
+}
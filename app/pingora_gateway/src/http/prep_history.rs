@@ -0,0 +1,181 @@
+use std::sync::{Arc, RwLock};
+
+use base_types::TradingPair;
+use base_types::mark_data::prep::history::{
+    DEFAULT_FUNDING_LIMIT, DEFAULT_KLINES_LIMIT, FundingRatePage, MAX_FUNDING_LIMIT,
+    MAX_KLINES_LIMIT, MarkPriceKlinePage, PrepMarketHistory, clamp_history_limit,
+};
+use base_types::mark_data::spot::candle::CandleInterval;
+use base_types::mark_data::spot::level_types::SymbolId;
+
+use super::exchange_info::{json_response, query_param};
+
+/// 资金费率历史接口路径
+pub const FUNDING_RATE_PATH: &str = "/api/prep/fundingRate";
+/// 标记价格K线接口路径
+pub const MARK_PRICE_KLINES_PATH: &str = "/api/prep/markPriceKlines";
+
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+/// `GET /api/prep/fundingRate` 与 `GET /api/prep/markPriceKlines` 处理器
+///
+/// 读资金费结算与标记价格管道维护的历史；按 `startTime` 翻页，响应附带下一页起点
+pub struct PrepHistoryHandler {
+    history: Arc<RwLock<PrepMarketHistory>>,
+}
+
+impl Default for PrepHistoryHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(PrepMarketHistory::default())))
+    }
+}
+
+impl PrepHistoryHandler {
+    pub fn new(history: Arc<RwLock<PrepMarketHistory>>) -> Self {
+        Self { history }
+    }
+
+    /// 资金费结算与标记价格写入历史的入口
+    pub fn history(&self) -> Arc<RwLock<PrepMarketHistory>> {
+        self.history.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "GET"
+            && (route == Some(FUNDING_RATE_PATH) || route == Some(MARK_PRICE_KLINES_PATH))
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        let (status, body) = self.render(path);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    ///
+    /// 参数：`symbol`（必填）、`startTime` / `endTime`（Unix 毫秒）、`limit`；
+    /// 标记价格K线另需 `interval`。资金费率 `limit` 默认 100、最大 1000，
+    /// K线默认 500、最大 1500
+    fn render(&self, path: &str) -> (u16, String) {
+        let Some(symbol) = query_param(path, "symbol") else {
+            return Self::bad_request("Missing parameter: symbol".to_string());
+        };
+        let Some(pair) = TradingPair::from_symbol_str(symbol) else {
+            return Self::bad_request(format!("Invalid symbol: {}", symbol));
+        };
+        let symbol_id = pair as SymbolId;
+        let mut times = [None, None];
+        for (slot, name) in times.iter_mut().zip(["startTime", "endTime"]) {
+            *slot = match query_param(path, name).map(str::parse::<u64>) {
+                None => None,
+                Some(Ok(millis)) => Some(millis.saturating_mul(NANOS_PER_MILLI)),
+                Some(Err(_)) => return Self::bad_request(format!("Invalid parameter: {}", name)),
+            };
+        }
+        let [start, end] = times;
+        // endTime 为毫秒，包含该毫秒内的全部记录
+        let end = end.map(|end| end.saturating_add(NANOS_PER_MILLI - 1));
+        let limit = match query_param(path, "limit").map(str::parse::<usize>) {
+            None => None,
+            Some(Ok(limit)) => Some(limit),
+            Some(Err(_)) => return Self::bad_request("Invalid parameter: limit".to_string()),
+        };
+
+        let Ok(history) = self.history.read() else {
+            return (500, serde_json::json!({ "msg": "History unavailable" }).to_string());
+        };
+        let body = if path.starts_with(MARK_PRICE_KLINES_PATH) {
+            let Some(interval) = query_param(path, "interval") else {
+                return Self::bad_request("Missing parameter: interval".to_string());
+            };
+            let Some(interval) = CandleInterval::parse(interval) else {
+                return Self::bad_request(format!("Invalid interval: {}", interval));
+            };
+            let limit = clamp_history_limit(limit, DEFAULT_KLINES_LIMIT, MAX_KLINES_LIMIT);
+            let page = history.mark_price_klines(symbol_id, interval, start, end, limit);
+            serde_json::to_string(&MarkPriceKlinePage::from(&page))
+        } else {
+            let limit = clamp_history_limit(limit, DEFAULT_FUNDING_LIMIT, MAX_FUNDING_LIMIT);
+            let page = history.funding_rates(symbol_id, start, end, limit);
+            serde_json::to_string(&FundingRatePage::new(symbol, &page))
+        };
+        match body {
+            Ok(body) => (200, body),
+            Err(e) => (500, serde_json::json!({ "msg": e.to_string() }).to_string()),
+        }
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::Price;
+    use base_types::mark_data::prep::history::FundingRateRecord;
+
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    #[test]
+    fn test_funding_rate_and_mark_price_klines() {
+        let handler = PrepHistoryHandler::default();
+        let history = handler.history();
+        {
+            let mut history = history.write().unwrap();
+            for hour in [0, 8, 16] {
+                history.record_funding(FundingRateRecord {
+                    symbol_id: TradingPair::BtcUsdt as SymbolId,
+                    funding_time: hour * HOUR_MS * NANOS_PER_MILLI,
+                    funding_rate: Price::from_f64(0.0001),
+                    mark_price: Price::from_f64(40_000.0),
+                });
+            }
+            for (minute, price) in [(0, 40_000.0), (30, 40_100.0), (61, 39_900.0)] {
+                history.on_mark_price(
+                    TradingPair::BtcUsdt as SymbolId,
+                    minute * 60_000 * NANOS_PER_MILLI,
+                    Price::from_f64(price),
+                );
+            }
+        }
+        assert!(PrepHistoryHandler::matches("GET", "/api/prep/fundingRate?symbol=BTCUSDT"));
+        assert!(!PrepHistoryHandler::matches("POST", "/api/prep/fundingRate"));
+
+        let (status, body) =
+            handler.render("/api/prep/fundingRate?symbol=BTCUSDT&startTime=0&limit=2");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["rates"].as_array().unwrap().len(), 2);
+        assert_eq!(json["rates"][1]["fundingTime"], 8 * HOUR_MS);
+        assert_eq!(json["rates"][1]["symbol"], "BTCUSDT");
+        assert_eq!(json["nextStartTime"], 8 * HOUR_MS + 1);
+
+        let next = format!(
+            "/api/prep/fundingRate?symbol=BTCUSDT&startTime={}&limit=2",
+            json["nextStartTime"]
+        );
+        let json: serde_json::Value = serde_json::from_str(&handler.render(&next).1).unwrap();
+        assert_eq!(json["rates"][0]["fundingTime"], 16 * HOUR_MS);
+        assert!(json["nextStartTime"].is_null());
+
+        let (status, body) =
+            handler.render("/api/prep/markPriceKlines?symbol=BTCUSDT&interval=1h&endTime=3599999");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let klines = json["klines"].as_array().unwrap();
+        assert_eq!(klines.len(), 1);
+        assert_eq!(klines[0]["openTime"], 0);
+        assert_eq!(klines[0]["closeTime"], HOUR_MS - 1);
+        assert_eq!(klines[0]["high"], serde_json::json!(Price::from_f64(40_100.0)));
+
+        assert_eq!(handler.render("/api/prep/fundingRate").0, 400);
+        assert_eq!(handler.render("/api/prep/fundingRate?symbol=BTCUSDT&startTime=x").0, 400);
+        assert_eq!(handler.render("/api/prep/markPriceKlines?symbol=BTCUSDT").0, 400);
+        assert_eq!(handler.render("/api/prep/markPriceKlines?symbol=BTCUSDT&interval=7m").0, 400);
+    }
+}
@@ -0,0 +1,218 @@
+//! 合约做市报价接口
+//!
+//! 只接受已鉴权账户（JWT 或 API Key 签名）操作自己的报价，价格与数量为引擎整数单位：
+//! - `POST /api/prep/massQuote`：`{quotes: [{bidPrice, bidQuantity, askPrice, askQuantity}]}`
+//!   原子替换账户全部报价，数量为 0 表示该方向不报价，空 `quotes` 撤回全部报价
+//! - `POST /api/prep/mmp`：`{window, fillLimit, freeze?}`（毫秒）设置做市商保护，空请求体关闭
+//! - `POST /api/prep/mmp/reset`：解除做市商保护冻结
+//!
+//! WebSocket 会话带 `cancelOnDisconnect=true` 时，断线后由 [`PrepQuoteHandler::disconnect_hook`]
+//! 提交空报价撤回该账户全部报价。命令接口受理后返回 202，结果随分片的命令结果返回；
+//! 未接入撮合分片时返回 503
+
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+
+use prep::domain::entity::{MmpConfig, QuoteEntry};
+use prep::domain::service::Command;
+use serde::Deserialize;
+
+use super::exchange_info::json_response;
+use crate::websocket::server::DisconnectHook;
+
+/// 批量报价接口路径
+pub const MASS_QUOTE_PATH: &str = "/api/prep/massQuote";
+/// 做市商保护设置接口路径
+pub const MMP_PATH: &str = "/api/prep/mmp";
+/// 解除做市商保护冻结接口路径
+pub const MMP_RESET_PATH: &str = "/api/prep/mmp/reset";
+
+/// 单档报价
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteLevel {
+    #[serde(default)]
+    bid_price: u64,
+    #[serde(default)]
+    bid_quantity: u64,
+    #[serde(default)]
+    ask_price: u64,
+    #[serde(default)]
+    ask_quantity: u64,
+}
+
+/// 批量报价请求体
+#[derive(Debug, Deserialize)]
+struct MassQuoteRequest {
+    quotes: Vec<QuoteLevel>,
+}
+
+/// 做市商保护请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MmpRequest {
+    window: u64,
+    fill_limit: u32,
+    freeze: Option<u64>,
+}
+
+/// 合约做市报价接口处理器
+#[derive(Default)]
+pub struct PrepQuoteHandler {
+    engine: Option<Sender<Command>>,
+}
+
+impl PrepQuoteHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 接入撮合分片的命令发送端
+    pub fn with_engine(mut self, engine: Sender<Command>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// 断线撤单回调：提交空报价撤回账户全部报价（未接入撮合分片时为 None）
+    pub fn disconnect_hook(&self) -> Option<DisconnectHook> {
+        let engine = self.engine.clone()?;
+        Some(Arc::new(move |account| {
+            // 分片已停止时无报价可撤
+            let _ = engine.send(Command::MassQuote { trader: account.0, quotes: Vec::new() });
+        }))
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next().unwrap_or(path);
+        method == "POST" && [MASS_QUOTE_PATH, MMP_PATH, MMP_RESET_PATH].contains(&route)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, body: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, body, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，命令已提交返回 202
+    fn render(&self, path: &str, body: &[u8], account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let route = path.split('?').next().unwrap_or(path);
+        let result = account
+            .parse::<u64>()
+            .map_err(|_| (400, format!("Invalid account: {}", account)))
+            .and_then(|trader| self.command(trader, route, body));
+        match result {
+            Ok(body) => (202, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn command(
+        &self,
+        trader: u64,
+        route: &str,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let (command, accepted) = match route {
+            MASS_QUOTE_PATH => {
+                let req: MassQuoteRequest =
+                    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                let quotes: Vec<QuoteEntry> = req
+                    .quotes
+                    .iter()
+                    .map(|level| {
+                        QuoteEntry::two_sided(
+                            level.bid_price,
+                            level.bid_quantity,
+                            level.ask_price,
+                            level.ask_quantity,
+                        )
+                    })
+                    .collect();
+                let accepted =
+                    serde_json::json!({ "command": "massQuote", "levels": quotes.len() });
+                (Command::MassQuote { trader, quotes }, accepted)
+            }
+            MMP_PATH => {
+                let config = if body.iter().all(u8::is_ascii_whitespace) {
+                    None
+                } else {
+                    let req: MmpRequest =
+                        serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+                    if req.window == 0 || req.fill_limit == 0 {
+                        return Err((400, "window and fillLimit must be positive".to_string()));
+                    }
+                    Some(MmpConfig {
+                        window: req.window,
+                        fill_limit: req.fill_limit,
+                        freeze: req.freeze,
+                    })
+                };
+                let accepted =
+                    serde_json::json!({ "command": "setMmp", "enabled": config.is_some() });
+                (Command::SetMmp { trader, config }, accepted)
+            }
+            _ => (Command::ResetMmp { trader }, serde_json::json!({ "command": "resetMmp" })),
+        };
+        let Some(engine) = &self.engine else {
+            return Err((503, "Matching engine not connected".to_string()));
+        };
+        engine.send(command).map_err(|_| (503, "Matching engine stopped".to_string()))?;
+        Ok(accepted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use base_types::AccountId;
+
+    use super::*;
+
+    #[test]
+    fn test_quote_commands_and_cancel_on_disconnect() {
+        assert!(PrepQuoteHandler::new().disconnect_hook().is_none());
+        assert_eq!(PrepQuoteHandler::new().render(MMP_RESET_PATH, b"", Some("7")).0, 503);
+
+        let (engine, commands) = mpsc::channel();
+        let handler = PrepQuoteHandler::new().with_engine(engine);
+        assert!(PrepQuoteHandler::matches("POST", MASS_QUOTE_PATH));
+        assert!(!PrepQuoteHandler::matches("GET", MMP_PATH));
+
+        let quotes = br#"{"quotes":[{"bidPrice":99,"bidQuantity":2,"askPrice":101,"askQuantity":2},{"askPrice":102,"askQuantity":1}]}"#;
+        assert_eq!(handler.render(MASS_QUOTE_PATH, quotes, None).0, 401);
+        let (status, body) = handler.render(MASS_QUOTE_PATH, quotes, Some("7"));
+        assert_eq!(status, 202, "{}", body);
+        let Ok(Command::MassQuote { trader: 7, quotes }) = commands.try_recv() else {
+            panic!("expected mass quote");
+        };
+        assert_eq!(quotes.len(), 2);
+        assert_eq!((quotes[1].bid(), quotes[1].ask()), (None, Some((102, 1))));
+
+        let mmp = br#"{"window":100,"fillLimit":3}"#;
+        assert_eq!(handler.render(MMP_PATH, mmp, Some("7")).0, 202);
+        assert!(matches!(
+            commands.try_recv(),
+            Ok(Command::SetMmp {
+                trader: 7,
+                config: Some(MmpConfig { fill_limit: 3, freeze: None, .. })
+            })
+        ));
+        assert_eq!(handler.render(MMP_PATH, b"", Some("7")).0, 202);
+        assert!(matches!(commands.try_recv(), Ok(Command::SetMmp { config: None, .. })));
+        assert_eq!(handler.render(MMP_PATH, br#"{"window":0,"fillLimit":3}"#, Some("7")).0, 400);
+        assert_eq!(handler.render(MMP_RESET_PATH, b"", Some("7")).0, 202);
+        assert!(matches!(commands.try_recv(), Ok(Command::ResetMmp { trader: 7 })));
+
+        // 断线撤回全部报价
+        handler.disconnect_hook().unwrap()(AccountId(9));
+        let Ok(Command::MassQuote { trader: 9, quotes }) = commands.try_recv() else {
+            panic!("expected empty mass quote");
+        };
+        assert!(quotes.is_empty());
+    }
+}
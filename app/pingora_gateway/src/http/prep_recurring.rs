@@ -0,0 +1,389 @@
+//! 合约定投接口
+//!
+//! 定投计划随合约引擎状态保存：登记、暂停、恢复、撤销以命令提交到撮合分片，
+//! 与委托一样写入命令日志；计划列表与执行历史由消费撮合事件流的读侧投影应答。
+//! 只接受已鉴权账户（JWT 或 API Key 签名）操作自己的计划：
+//! - `POST /api/prep/recurring`：`{symbol, side, quantity, type, price?, timeInForce?, interval,
+//!   startTime, maxRuns?}` 登记计划，`type` 为 `MARKET` 或 `LIMIT`（须给出 `price`），
+//!   价格与数量为引擎整数单位，`interval` 与 `startTime` 为毫秒
+//! - `POST /api/prep/recurring/pause`、`/resume`、`/cancel`：`{planId}` 暂停、恢复、撤销
+//! - `GET /api/prep/recurring`：账户全部计划
+//! - `GET /api/prep/recurring/history?planId=&limit=`：执行历史（最新在前）
+//!
+//! 命令接口受理后返回 202，计划ID与状态以列表接口为准；未接入撮合分片时返回 503
+
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use prep::domain::entity::{
+    RecurringExecution, RecurringOrderKind, RecurringPlan, RecurringSpec, RecurringStatus, Side,
+    TimeInForce,
+};
+use prep::domain::service::{Command, ReadModelProjection};
+use serde::Deserialize;
+
+use super::exchange_info::{json_response, query_param};
+
+/// 计划列表与登记接口路径
+pub const RECURRING_PATH: &str = "/api/prep/recurring";
+/// 暂停接口路径
+pub const RECURRING_PAUSE_PATH: &str = "/api/prep/recurring/pause";
+/// 恢复接口路径
+pub const RECURRING_RESUME_PATH: &str = "/api/prep/recurring/resume";
+/// 撤销接口路径
+pub const RECURRING_CANCEL_PATH: &str = "/api/prep/recurring/cancel";
+/// 执行历史接口路径
+pub const RECURRING_HISTORY_PATH: &str = "/api/prep/recurring/history";
+
+/// 执行历史默认条数
+const DEFAULT_HISTORY: usize = 50;
+/// 执行历史最大条数
+const MAX_HISTORY: usize = 1000;
+
+const POST_PATHS: [&str; 4] =
+    [RECURRING_PATH, RECURRING_PAUSE_PATH, RECURRING_RESUME_PATH, RECURRING_CANCEL_PATH];
+
+/// 登记请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePlanRequest {
+    symbol: String,
+    side: String,
+    quantity: u64,
+    #[serde(rename = "type")]
+    order_type: String,
+    price: Option<u64>,
+    time_in_force: Option<String>,
+    interval: u64,
+    start_time: u64,
+    max_runs: Option<u32>,
+}
+
+/// 暂停、恢复、撤销请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlanRequest {
+    plan_id: u64,
+}
+
+/// 合约定投接口处理器
+pub struct PrepRecurringHandler {
+    projection: Arc<RwLock<ReadModelProjection>>,
+    engine: Option<Sender<Command>>,
+}
+
+impl Default for PrepRecurringHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(ReadModelProjection::new())))
+    }
+}
+
+impl PrepRecurringHandler {
+    /// 与合约账户查询共用读侧投影
+    pub fn new(projection: Arc<RwLock<ReadModelProjection>>) -> Self {
+        Self { projection, engine: None }
+    }
+
+    /// 接入撮合分片的命令发送端
+    pub fn with_engine(mut self, engine: Sender<Command>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next().unwrap_or(path);
+        match method {
+            "GET" => route == RECURRING_PATH || route == RECURRING_HISTORY_PATH,
+            "POST" => POST_PATHS.contains(&route),
+            _ => false,
+        }
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, method: &str, path: &str, body: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(method, path, body, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，命令已提交返回 202
+    fn render(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        account: Option<&str>,
+    ) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let route = path.split('?').next().unwrap_or(path);
+        let result = account
+            .parse::<u64>()
+            .map_err(|_| (400, format!("Invalid account: {}", account)))
+            .and_then(|trader| match (method, route) {
+                ("GET", RECURRING_PATH) => Ok((200, self.plans(trader))),
+                ("GET", _) => self.history(trader, path).map(|body| (200, body)),
+                _ => self.command(trader, route, body).map(|body| (202, body)),
+            });
+        match result {
+            Ok((status, body)) => (status, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn plans(&self, trader: u64) -> serde_json::Value {
+        let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+        let plans: Vec<_> = projection.recurring_plans(trader).into_iter().map(plan_json).collect();
+        serde_json::json!({
+            "accountId": trader,
+            "sequence": projection.sequence(),
+            "plans": plans,
+        })
+    }
+
+    fn history(&self, trader: u64, path: &str) -> Result<serde_json::Value, (u16, String)> {
+        let plan_id = query_param(path, "planId")
+            .map(str::parse::<u64>)
+            .transpose()
+            .map_err(|_| (400, "Invalid parameter: planId".to_string()))?;
+        let limit = match query_param(path, "limit").map(str::parse::<usize>) {
+            None => DEFAULT_HISTORY,
+            Some(Ok(limit)) if (1..=MAX_HISTORY).contains(&limit) => limit,
+            Some(_) => return Err((400, "Invalid parameter: limit".to_string())),
+        };
+        let projection = self.projection.read().unwrap_or_else(PoisonError::into_inner);
+        let executions: Vec<_> = projection
+            .recurring_history(trader, plan_id, limit)
+            .iter()
+            .map(execution_json)
+            .collect();
+        Ok(serde_json::json!({
+            "accountId": trader,
+            "sequence": projection.sequence(),
+            "executions": executions,
+        }))
+    }
+
+    fn command(
+        &self,
+        trader: u64,
+        route: &str,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let (command, accepted) = if route == RECURRING_PATH {
+            let req: CreatePlanRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let spec = create_spec(trader, req)?;
+            let accepted = serde_json::json!({
+                "command": "createRecurringPlan",
+                "symbol": spec.symbol,
+            });
+            (Command::CreateRecurringPlan { spec }, accepted)
+        } else {
+            let req: PlanRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let plan_id = req.plan_id;
+            let (command, name) = match route {
+                RECURRING_PAUSE_PATH => {
+                    (Command::PauseRecurringPlan { trader, plan_id }, "pauseRecurringPlan")
+                }
+                RECURRING_RESUME_PATH => {
+                    (Command::ResumeRecurringPlan { trader, plan_id }, "resumeRecurringPlan")
+                }
+                RECURRING_CANCEL_PATH => {
+                    (Command::CancelRecurringPlan { trader, plan_id }, "cancelRecurringPlan")
+                }
+                _ => return Err((404, format!("Unknown route: {}", route))),
+            };
+            (command, serde_json::json!({ "command": name, "planId": plan_id }))
+        };
+        let Some(engine) = &self.engine else {
+            return Err((503, "Matching engine not connected".to_string()));
+        };
+        engine.send(command).map_err(|_| (503, "Matching engine stopped".to_string()))?;
+        Ok(accepted)
+    }
+}
+
+/// 校验登记请求并转换为计划定义（数量、价格、间隔的取值由引擎校验）
+fn create_spec(trader: u64, req: CreatePlanRequest) -> Result<RecurringSpec, (u16, String)> {
+    let side = match req.side.as_str() {
+        "BUY" => Side::Buy,
+        "SELL" => Side::Sell,
+        other => return Err((400, format!("Invalid side: {}", other))),
+    };
+    let kind = match (req.order_type.as_str(), req.price) {
+        ("MARKET", None) => RecurringOrderKind::Market,
+        ("MARKET", Some(_)) => return Err((400, "price applies to LIMIT only".to_string())),
+        ("LIMIT", Some(price)) => {
+            let time_in_force = match req.time_in_force.as_deref().unwrap_or("GTC") {
+                "GTC" => TimeInForce::GTC,
+                "IOC" => TimeInForce::IOC,
+                "FOK" => TimeInForce::FOK,
+                "POST_ONLY" => TimeInForce::PostOnly,
+                other => return Err((400, format!("Invalid timeInForce: {}", other))),
+            };
+            RecurringOrderKind::Limit { price, time_in_force }
+        }
+        ("LIMIT", None) => return Err((400, "price is required for LIMIT".to_string())),
+        (other, _) => return Err((400, format!("Invalid type: {}", other))),
+    };
+    Ok(RecurringSpec {
+        trader,
+        symbol: req.symbol.to_uppercase(),
+        side,
+        quantity: req.quantity,
+        kind,
+        interval: req.interval,
+        start_at: req.start_time,
+        max_runs: req.max_runs,
+    })
+}
+
+fn plan_json(plan: &RecurringPlan) -> serde_json::Value {
+    let spec = &plan.spec;
+    let (order_type, price, time_in_force) = match spec.kind {
+        RecurringOrderKind::Market => ("MARKET", None, None),
+        RecurringOrderKind::Limit { price, time_in_force } => {
+            let time_in_force = match time_in_force {
+                TimeInForce::GTC => "GTC",
+                TimeInForce::IOC => "IOC",
+                TimeInForce::FOK => "FOK",
+                TimeInForce::GTD { .. } => "GTD",
+                TimeInForce::PostOnly => "POST_ONLY",
+            };
+            ("LIMIT", Some(price), Some(time_in_force))
+        }
+    };
+    serde_json::json!({
+        "planId": plan.id,
+        "symbol": spec.symbol,
+        "side": match spec.side {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        },
+        "quantity": spec.quantity,
+        "type": order_type,
+        "price": price,
+        "timeInForce": time_in_force,
+        "interval": spec.interval,
+        "startTime": spec.start_at,
+        "maxRuns": spec.max_runs,
+        "status": match plan.status {
+            RecurringStatus::Active => "ACTIVE",
+            RecurringStatus::Paused => "PAUSED",
+            RecurringStatus::Cancelled => "CANCELLED",
+            RecurringStatus::Completed => "COMPLETED",
+        },
+        "runs": plan.runs,
+        "nextRunTime": plan.next_run,
+    })
+}
+
+fn execution_json(execution: &RecurringExecution) -> serde_json::Value {
+    serde_json::json!({
+        "planId": execution.plan_id,
+        "run": execution.run,
+        "scheduledTime": execution.scheduled_at,
+        "executedTime": execution.executed_at,
+        "orderId": execution.order_id,
+        "errorCode": execution.error_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use prep::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use prep::domain::service::{MatchingService, PrepCommandHandler};
+
+    use super::*;
+
+    fn json(body: &str) -> serde_json::Value {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn test_commands_are_submitted_for_authenticated_account() {
+        let (engine, commands) = mpsc::channel();
+        let handler = PrepRecurringHandler::default().with_engine(engine);
+        let create = br#"{"symbol":"btcusdt","side":"BUY","quantity":2,"type":"LIMIT","price":100,"interval":60000,"startTime":1000,"maxRuns":3}"#;
+
+        assert_eq!(handler.render("POST", RECURRING_PATH, create, None).0, 401);
+        let (status, body) = handler.render("POST", RECURRING_PATH, create, Some("7"));
+        assert_eq!(status, 202);
+        assert_eq!(json(&body)["symbol"], "BTCUSDT");
+        let Ok(Command::CreateRecurringPlan { spec }) = commands.try_recv() else {
+            panic!("expected create command");
+        };
+        assert_eq!(spec.trader, 7);
+        assert_eq!(
+            spec.kind,
+            RecurringOrderKind::Limit { price: 100, time_in_force: TimeInForce::GTC }
+        );
+
+        let (status, _) =
+            handler.render("POST", RECURRING_PAUSE_PATH, br#"{"planId":1}"#, Some("7"));
+        assert_eq!(status, 202);
+        assert!(matches!(
+            commands.try_recv(),
+            Ok(Command::PauseRecurringPlan { trader: 7, plan_id: 1 })
+        ));
+
+        let market_with_price = br#"{"symbol":"BTCUSDT","side":"BUY","quantity":2,"type":"MARKET","price":100,"interval":1,"startTime":0}"#;
+        assert_eq!(handler.render("POST", RECURRING_PATH, market_with_price, Some("7")).0, 400);
+        assert!(commands.try_recv().is_err());
+
+        let unconnected = PrepRecurringHandler::default();
+        assert_eq!(
+            unconnected.render("POST", RECURRING_CANCEL_PATH, br#"{"planId":1}"#, Some("7")).0,
+            503
+        );
+    }
+
+    #[test]
+    fn test_plans_and_history_from_projection() {
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        let spec = RecurringSpec {
+            trader: 7,
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            quantity: 1,
+            kind: RecurringOrderKind::Limit { price: 100, time_in_force: TimeInForce::GTC },
+            interval: 1_000,
+            start_at: 1_000,
+            max_runs: None,
+        };
+        engine.handle(Command::CreateRecurringPlan { spec });
+        engine.set_timestamp(1_000);
+        engine.handle(Command::RunRecurringPlans);
+        engine.handle(Command::PauseRecurringPlan { trader: 7, plan_id: 1 });
+
+        let handler = PrepRecurringHandler::default();
+        handler.projection.write().unwrap().apply_all(&engine.drain_events());
+
+        let (status, body) = handler.render("GET", RECURRING_PATH, b"", Some("7"));
+        assert_eq!(status, 200);
+        let plan = &json(&body)["plans"][0];
+        assert_eq!(plan["status"], "PAUSED");
+        assert_eq!(plan["runs"], 1);
+        assert_eq!(plan["nextRunTime"], serde_json::Value::Null);
+
+        let path = format!("{}?planId=1", RECURRING_HISTORY_PATH);
+        let (status, body) = handler.render("GET", &path, b"", Some("7"));
+        assert_eq!(status, 200);
+        let executions = json(&body)["executions"].as_array().unwrap().clone();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0]["scheduledTime"], 1_000);
+        assert_eq!(executions[0]["orderId"], 1);
+
+        // 其他账户看不到
+        let (_, body) = handler.render("GET", RECURRING_PATH, b"", Some("8"));
+        assert!(json(&body)["plans"].as_array().unwrap().is_empty());
+        let path = format!("{}?limit=0", RECURRING_HISTORY_PATH);
+        assert_eq!(handler.render("GET", &path, b"", Some("7")).0, 400);
+    }
+}
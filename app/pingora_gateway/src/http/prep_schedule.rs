@@ -0,0 +1,214 @@
+//! 合约定时委托接口
+//!
+//! 定时委托在撮合分片的命令队列中挂起，到达激活时间后才提交撮合（如参与开盘、
+//! 错峰执行）；登记、撤销与激活写入分片的定时委托日志，重启后恢复。
+//! 只接受已鉴权账户（JWT 或 API Key 签名）操作自己的委托：
+//! - `POST /api/prep/order/schedule`：`{side, positionSide, quantity, type, price?, timeInForce?,
+//!   reduceOnly?, activateTime}` 登记定时委托，`type` 为 `MARKET` 或 `LIMIT`（须给出 `price`），
+//!   价格与数量为引擎整数单位，`activateTime` 为毫秒
+//! - `POST /api/prep/order/schedule/cancel`：`{scheduleId}` 激活前撤销
+//!
+//! 命令接口受理后返回 202，定时ID与撤销结果随分片的命令结果返回；未接入撮合分片时返回 503
+
+use std::sync::mpsc::Sender;
+
+use prep::domain::entity::{PositionSide, Side, TimeInForce};
+use prep::domain::service::Command;
+use serde::Deserialize;
+
+use super::exchange_info::json_response;
+
+/// 登记接口路径
+pub const SCHEDULE_PATH: &str = "/api/prep/order/schedule";
+/// 撤销接口路径
+pub const SCHEDULE_CANCEL_PATH: &str = "/api/prep/order/schedule/cancel";
+
+/// 登记请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleRequest {
+    side: String,
+    position_side: String,
+    quantity: u64,
+    #[serde(rename = "type")]
+    order_type: String,
+    price: Option<u64>,
+    time_in_force: Option<String>,
+    #[serde(default)]
+    reduce_only: bool,
+    activate_time: u64,
+}
+
+/// 撤销请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelRequest {
+    schedule_id: u64,
+}
+
+/// 合约定时委托接口处理器
+#[derive(Default)]
+pub struct PrepScheduleHandler {
+    engine: Option<Sender<Command>>,
+}
+
+impl PrepScheduleHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 接入撮合分片的命令发送端
+    pub fn with_engine(mut self, engine: Sender<Command>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next().unwrap_or(path);
+        method == "POST" && (route == SCHEDULE_PATH || route == SCHEDULE_CANCEL_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户，未鉴权为 None）
+    pub fn respond(&self, path: &str, body: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, body, account);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)，命令已提交返回 202
+    fn render(&self, path: &str, body: &[u8], account: Option<&str>) -> (u16, String) {
+        let Some(account) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let route = path.split('?').next().unwrap_or(path);
+        let result = account
+            .parse::<u64>()
+            .map_err(|_| (400, format!("Invalid account: {}", account)))
+            .and_then(|trader| self.command(trader, route, body));
+        match result {
+            Ok(body) => (202, body.to_string()),
+            Err((status, msg)) => (status, serde_json::json!({ "msg": msg }).to_string()),
+        }
+    }
+
+    fn command(
+        &self,
+        trader: u64,
+        route: &str,
+        body: &[u8],
+    ) -> Result<serde_json::Value, (u16, String)> {
+        let (command, accepted) = if route == SCHEDULE_PATH {
+            let req: ScheduleRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let activate_at = req.activate_time;
+            let order = scheduled_order(trader, req)?;
+            let accepted = serde_json::json!({
+                "command": "scheduleOrder",
+                "activateTime": activate_at,
+            });
+            (Command::ScheduleOrder { order: Box::new(order), activate_at }, accepted)
+        } else {
+            let req: CancelRequest =
+                serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+            let schedule_id = req.schedule_id;
+            let accepted = serde_json::json!({
+                "command": "cancelScheduledOrder",
+                "scheduleId": schedule_id,
+            });
+            (Command::CancelScheduledOrder { trader, schedule_id }, accepted)
+        };
+        let Some(engine) = &self.engine else {
+            return Err((503, "Matching engine not connected".to_string()));
+        };
+        engine.send(command).map_err(|_| (503, "Matching engine stopped".to_string()))?;
+        Ok(accepted)
+    }
+}
+
+/// 校验登记请求并转换为激活时提交的委托（数量、价格的取值由引擎校验）
+fn scheduled_order(trader: u64, req: ScheduleRequest) -> Result<Command, (u16, String)> {
+    let side = match req.side.as_str() {
+        "BUY" => Side::Buy,
+        "SELL" => Side::Sell,
+        other => return Err((400, format!("Invalid side: {}", other))),
+    };
+    let position_side = match req.position_side.as_str() {
+        "BOTH" => PositionSide::Both,
+        "LONG" => PositionSide::Long,
+        "SHORT" => PositionSide::Short,
+        other => return Err((400, format!("Invalid positionSide: {}", other))),
+    };
+    match (req.order_type.as_str(), req.price) {
+        ("MARKET", None) => Ok(Command::MarketOrder {
+            trader,
+            side,
+            quantity: req.quantity,
+            position_side,
+            reduce_only: req.reduce_only,
+        }),
+        ("MARKET", Some(_)) => Err((400, "price applies to LIMIT only".to_string())),
+        ("LIMIT", Some(price)) => {
+            let time_in_force = match req.time_in_force.as_deref().unwrap_or("GTC") {
+                "GTC" => TimeInForce::GTC,
+                "IOC" => TimeInForce::IOC,
+                "FOK" => TimeInForce::FOK,
+                "POST_ONLY" => TimeInForce::PostOnly,
+                other => return Err((400, format!("Invalid timeInForce: {}", other))),
+            };
+            Ok(Command::LimitOrder {
+                trader,
+                side,
+                price,
+                quantity: req.quantity,
+                position_side,
+                reduce_only: req.reduce_only,
+                time_in_force,
+            })
+        }
+        ("LIMIT", None) => Err((400, "price is required for LIMIT".to_string())),
+        (other, _) => Err((400, format!("Invalid type: {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_schedule_commands_are_submitted_for_authenticated_account() {
+        let (engine, commands) = mpsc::channel();
+        let handler = PrepScheduleHandler::new().with_engine(engine);
+        let schedule = br#"{"side":"BUY","positionSide":"LONG","quantity":2,"type":"LIMIT","price":100,"activateTime":5000}"#;
+
+        assert!(PrepScheduleHandler::matches("POST", SCHEDULE_PATH));
+        assert!(!PrepScheduleHandler::matches("GET", SCHEDULE_PATH));
+        assert_eq!(handler.render(SCHEDULE_PATH, schedule, None).0, 401);
+        let (status, body) = handler.render(SCHEDULE_PATH, schedule, Some("7"));
+        assert_eq!(status, 202, "{}", body);
+        let Ok(Command::ScheduleOrder { order, activate_at: 5_000 }) = commands.try_recv() else {
+            panic!("expected schedule command");
+        };
+        assert!(matches!(
+            *order,
+            Command::LimitOrder { trader: 7, price: 100, time_in_force: TimeInForce::GTC, .. }
+        ));
+
+        let market = br#"{"side":"SELL","positionSide":"SHORT","quantity":2,"type":"MARKET","price":100,"activateTime":5000}"#;
+        assert_eq!(handler.render(SCHEDULE_PATH, market, Some("7")).0, 400);
+
+        let (status, _) = handler.render(SCHEDULE_CANCEL_PATH, br#"{"scheduleId":3}"#, Some("7"));
+        assert_eq!(status, 202);
+        assert!(matches!(
+            commands.try_recv(),
+            Ok(Command::CancelScheduledOrder { trader: 7, schedule_id: 3 })
+        ));
+
+        drop(commands);
+        let (status, _) = handler.render(SCHEDULE_CANCEL_PATH, br#"{"scheduleId":3}"#, Some("7"));
+        assert_eq!(status, 503);
+        let disconnected = PrepScheduleHandler::new();
+        assert_eq!(disconnected.render(SCHEDULE_PATH, schedule, Some("7")).0, 503);
+    }
+}
@@ -0,0 +1,336 @@
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::account::activity::ActivityLedger;
+use base_types::account::clearing::{ClearingContext, FeeProfile, clear_spot_trade};
+use base_types::exchange::spot::rfq::{RfqBook, RfqError, RfqId, RfqOutcome};
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{
+    AccountId, OrderSide, Price, Quantity, SequenceGenerator, SystemClock, TimestampProvider,
+    TradingPair,
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use super::block_trade::{BlockTradeHandler, SharedSettlementExporter};
+use super::exchange_info::json_response;
+use crate::websocket::rfq::RfqStream;
+
+/// 发起询价接口路径
+pub const RFQ_PATH: &str = "/api/spot/rfq";
+/// 提交报价接口路径
+pub const RFQ_QUOTE_PATH: &str = "/api/spot/rfq/quote";
+/// 撤销询价接口路径
+pub const RFQ_CANCEL_PATH: &str = "/api/spot/rfq/cancel";
+
+/// 默认结算检查间隔
+pub const DEFAULT_SETTLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 询价请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewRfqRequest {
+    symbol: String,
+    side: String,
+    quantity: Quantity,
+    makers: Vec<u64>,
+}
+
+/// 报价请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteRequestBody {
+    rfq_id: RfqId,
+    price: Price,
+    quantity: Quantity,
+}
+
+/// 撤销请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelRequest {
+    rfq_id: RfqId,
+}
+
+/// 询价（RFQ）处理器
+///
+/// - `POST /api/spot/rfq`：询价方发起询价，返回 `rfqId` 与报价窗口结束时间
+/// - `POST /api/spot/rfq/quote`：受邀做市商提交确定报价
+/// - `POST /api/spot/rfq/cancel`：询价方在窗口结束前撤销
+///
+/// 账户取自鉴权（JWT 或 API Key 签名）。窗口结束由 [`RfqHandler::spawn_settle`] 的定时器驱动，
+/// 按最优报价经清算入账（成交ID与大宗交易共用同一序列）；生命周期事件推送到各接收方的
+/// 私有流 `account@<ID>`（见 [`RfqStream`]）
+#[derive(Clone)]
+pub struct RfqHandler {
+    book: Arc<Mutex<RfqBook>>,
+    stream: Arc<RfqStream>,
+    ledger: Option<Arc<RwLock<ActivityLedger>>>,
+    settlement_export: Option<SharedSettlementExporter>,
+    fee_config: ProductFeeConfig,
+    /// 手续费收入账户
+    fee_account: AccountId,
+    clock: Arc<dyn TimestampProvider>,
+    trade_ids: Arc<dyn SequenceGenerator>,
+}
+
+impl Default for RfqHandler {
+    /// 不收手续费，部署时以 [`RfqHandler::new`] 指定费率与手续费账户
+    fn default() -> Self {
+        Self::new(RfqBook::default(), ProductFeeConfig::spot(0.0, 0.0), AccountId(0))
+    }
+}
+
+impl RfqHandler {
+    pub fn new(book: RfqBook, fee_config: ProductFeeConfig, fee_account: AccountId) -> Self {
+        let clock: Arc<dyn TimestampProvider> = Arc::new(SystemClock);
+        Self {
+            book: Arc::new(Mutex::new(book)),
+            stream: Arc::new(RfqStream::default()),
+            ledger: None,
+            settlement_export: None,
+            fee_config,
+            fee_account,
+            trade_ids: Arc::new(BlockTradeHandler::trade_id_sequence(clock.as_ref())),
+            clock,
+        }
+    }
+
+    /// 入账成交写入流水账本（与账户流水接口共用）
+    pub fn with_ledger(mut self, ledger: Arc<RwLock<ActivityLedger>>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// 入账成交导出结算分录（与大宗交易共用导出器）
+    pub fn with_settlement_export(mut self, exporter: SharedSettlementExporter) -> Self {
+        self.settlement_export = Some(exporter);
+        self
+    }
+
+    /// 替换时间与成交ID来源
+    pub fn with_sources(
+        mut self,
+        clock: Arc<dyn TimestampProvider>,
+        trade_ids: Arc<dyn SequenceGenerator>,
+    ) -> Self {
+        self.clock = clock;
+        self.trade_ids = trade_ids;
+        self
+    }
+
+    /// RFQ 私有流
+    pub fn stream(&self) -> &RfqStream {
+        &self.stream
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "POST"
+            && [RFQ_PATH, RFQ_QUOTE_PATH, RFQ_CANCEL_PATH].iter().any(|p| route == Some(*p))
+    }
+
+    /// 生成完整的 HTTP 响应（`account` 为鉴权得到的账户）
+    pub fn respond(&self, path: &str, body: &[u8], account: Option<&str>) -> Vec<u8> {
+        let (status, body) = self.render(path, body, account);
+        json_response(status, &body)
+    }
+
+    /// 结束到期的询价：按最优报价清算入账，推送生命周期事件，返回结束的询价数
+    pub fn settle_due(&self) -> usize {
+        let context = ClearingContext {
+            fee_config: &self.fee_config,
+            fee_account: self.fee_account,
+            buyer_profile: FeeProfile::default(),
+            seller_profile: FeeProfile::default(),
+        };
+        let mut book = self.book.lock().unwrap_or_else(PoisonError::into_inner);
+        let outcomes = book.settle_due(self.clock.now(), self.trade_ids.as_ref(), |trade| {
+            clear_spot_trade(trade, &context)
+        });
+        for outcome in &outcomes {
+            match outcome {
+                RfqOutcome::Filled { record, .. } => {
+                    if let Some(exporter) = &self.settlement_export {
+                        let mut exporter = exporter.lock().unwrap_or_else(PoisonError::into_inner);
+                        // 清算已校验不变量，导出不会因校验失败
+                        let _ = exporter.export(&record.settlement);
+                    }
+                    if let Some(ledger) = &self.ledger {
+                        let mut ledger = ledger.write().unwrap_or_else(PoisonError::into_inner);
+                        ledger.record(&record.settlement);
+                    }
+                }
+                RfqOutcome::Rejected { rfq_id, error } => {
+                    warn!("RFQ {} rejected by clearing: {:?}", rfq_id, error);
+                }
+                RfqOutcome::Expired { .. } => {}
+            }
+        }
+        self.publish(&mut book);
+        book.purge_closed();
+        outcomes.len()
+    }
+
+    /// 启动结算定时器：每隔 `interval` 结束到期的询价
+    pub fn spawn_settle(&self, interval: Duration) -> io::Result<JoinHandle<()>> {
+        let handler = self.clone();
+        std::thread::Builder::new().name("rfq-settle".to_string()).spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                handler.settle_due();
+            }
+        })
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    fn render(&self, path: &str, body: &[u8], account: Option<&str>) -> (u16, String) {
+        let Some(user_id) = account else {
+            return (401, serde_json::json!({ "msg": "Authentication required" }).to_string());
+        };
+        let Ok(account) = user_id.parse::<u64>().map(AccountId) else {
+            return Self::error(400, format!("Invalid account: {}", user_id));
+        };
+        let now = self.clock.now();
+        let mut book = self.book.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let response = match path.split('?').next() {
+            Some(RFQ_QUOTE_PATH) => {
+                let request: QuoteRequestBody = match serde_json::from_slice(body) {
+                    Ok(request) => request,
+                    Err(e) => return Self::error(400, format!("Invalid request: {}", e)),
+                };
+                book.quote(request.rfq_id, account, request.price, request.quantity, now).map(
+                    |quote_id| serde_json::json!({ "rfqId": request.rfq_id, "quoteId": quote_id }),
+                )
+            }
+            Some(RFQ_CANCEL_PATH) => {
+                let request: CancelRequest = match serde_json::from_slice(body) {
+                    Ok(request) => request,
+                    Err(e) => return Self::error(400, format!("Invalid request: {}", e)),
+                };
+                book.cancel(request.rfq_id, account, now)
+                    .map(|()| serde_json::json!({ "rfqId": request.rfq_id, "status": "CANCELLED" }))
+            }
+            _ => {
+                let request: NewRfqRequest = match serde_json::from_slice(body) {
+                    Ok(request) => request,
+                    Err(e) => return Self::error(400, format!("Invalid request: {}", e)),
+                };
+                let Some(pair) = TradingPair::from_symbol_str(&request.symbol) else {
+                    return Self::error(400, format!("Invalid symbol: {}", request.symbol));
+                };
+                let side = match request.side.to_uppercase().as_str() {
+                    "BUY" => OrderSide::Buy,
+                    "SELL" => OrderSide::Sell,
+                    _ => return Self::error(400, format!("Invalid side: {}", request.side)),
+                };
+                let makers: Vec<AccountId> = request.makers.into_iter().map(AccountId).collect();
+                book.request(account, pair, side, request.quantity, &makers, now).map(|rfq_id| {
+                    let expires_at = book.request_of(rfq_id).map(|r| r.expires_at.0 / 1_000_000);
+                    serde_json::json!({ "rfqId": rfq_id, "expiresAt": expires_at, "status": "OPEN" })
+                })
+            }
+        };
+        self.publish(&mut book);
+        match response {
+            Ok(json) => (200, json.to_string()),
+            Err(e) => Self::rejected(e),
+        }
+    }
+
+    /// 推送询价簿积压的生命周期事件
+    fn publish(&self, book: &mut RfqBook) {
+        for event in book.drain_events() {
+            self.stream.publish(&event);
+        }
+    }
+
+    fn rejected(e: RfqError) -> (u16, String) {
+        let status = match e {
+            RfqError::UnknownRfq(_) => 404,
+            RfqError::MakerNotInvited { .. } | RfqError::NotRequester { .. } => 403,
+            _ => 400,
+        };
+        Self::error(status, e.to_string())
+    }
+
+    fn error(status: u16, msg: String) -> (u16, String) {
+        (status, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::exchange::spot::rfq::RfqConfig;
+    use base_types::{AtomicSequence, ManualClock, Timestamp};
+
+    use super::*;
+
+    #[test]
+    fn test_request_quote_settle_and_publish() {
+        let clock = ManualClock::from_millis(0);
+        let ledger = Arc::new(RwLock::new(ActivityLedger::new()));
+        let handler = RfqHandler::new(
+            RfqBook::new(RfqConfig { quote_window: 1_000_000_000, max_makers: 4 }),
+            ProductFeeConfig::spot(0.0, 0.0),
+            AccountId(0),
+        )
+        .with_sources(Arc::new(clock.clone()), Arc::new(AtomicSequence::starting_at(70)))
+        .with_ledger(ledger.clone());
+        let mut receiver = handler.stream().subscribe();
+        assert!(RfqHandler::matches("POST", RFQ_QUOTE_PATH));
+        assert!(!RfqHandler::matches("GET", RFQ_PATH));
+
+        let request = br#"{"symbol":"BTCUSDT","side":"BUY","quantity":"2","makers":[10,11]}"#;
+        assert_eq!(handler.render(RFQ_PATH, request, None).0, 401);
+        let (status, body) = handler.render(RFQ_PATH, request, Some("1"));
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!((json["rfqId"].as_u64(), json["expiresAt"].as_u64()), (Some(1), Some(1_000)));
+        // 询价推送给两个受邀做市商
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "account@10");
+        assert!(message.payload.contains("rfqRequest"));
+        receiver.try_recv().unwrap();
+
+        let quote = br#"{"rfqId":1,"price":"100","quantity":"2"}"#;
+        assert_eq!(handler.render(RFQ_QUOTE_PATH, quote, Some("12")).0, 403);
+        assert_eq!(
+            handler
+                .render(RFQ_QUOTE_PATH, br#"{"rfqId":9,"price":"1","quantity":"2"}"#, Some("10"))
+                .0,
+            404
+        );
+        let (status, body) = handler.render(RFQ_QUOTE_PATH, quote, Some("10"));
+        assert_eq!(status, 200);
+        assert!(body.contains("\"quoteId\":1"));
+        assert_eq!(receiver.try_recv().unwrap().stream, "account@1");
+        receiver.try_recv().unwrap();
+        assert_eq!(handler.render(RFQ_CANCEL_PATH, br#"{"rfqId":1}"#, Some("10")).0, 403);
+
+        // 窗口未结束不结算
+        assert_eq!(handler.settle_due(), 0);
+        clock.set(Timestamp(1_000_000_000));
+        assert_eq!(handler.settle_due(), 1);
+        let filled: Vec<serde_json::Value> = (0..3)
+            .map(|_| serde_json::from_str(&receiver.try_recv().unwrap().payload).unwrap())
+            .collect();
+        assert_eq!(filled[0]["data"]["e"], "rfqFilled");
+        assert_eq!(filled[0]["data"]["tradeId"], 70);
+        assert_eq!(filled[1]["data"]["won"], true);
+        let ledger = ledger.read().unwrap();
+        assert!(ledger.len(AccountId(1)) > 0 && ledger.len(AccountId(10)) > 0);
+        drop(ledger);
+
+        // 结束的询价已清理
+        assert_eq!(handler.render(RFQ_CANCEL_PATH, br#"{"rfqId":1}"#, Some("1")).0, 404);
+        let (_, body) = handler.render(RFQ_PATH, request, Some("1"));
+        assert!(body.contains("\"rfqId\":2"));
+        let (status, body) = handler.render(RFQ_CANCEL_PATH, br#"{"rfqId":2}"#, Some("1"));
+        assert_eq!((status, body.contains("CANCELLED")), (200, true));
+    }
+}
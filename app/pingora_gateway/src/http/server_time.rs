@@ -0,0 +1,592 @@
+//! 服务器时间与时钟同步监控
+//!
+//! - `GET /api/time`：返回网关时间 `serverTime`（毫秒），客户端据此校准签名请求的时间戳；
+//!   启用同步监控时附带 `timeSync`（偏差、往返时延、采样与失败次数）
+//! - [`TimeSyncMonitor`] 定期向时间源采样本机时钟偏差：超过告警阈值记录告警，
+//!   超过上限视为失步；启动时偏差超过上限则拒绝启动
+//!
+//! 签名请求的 recvWindow 校验与资金费率结算时间都依赖本机时钟，偏差过大时宁可不启动。
+//! 内置 SNTP 时间源；PTP 部署（ptp4l / phc2sys）实现 [`ClockOffsetSource`] 接入
+//!
+//! 通过环境变量启用：
+//! - `GATEWAY_NTP_SERVER`：NTP 服务器地址（如 `pool.ntp.org:123`）
+//! - `GATEWAY_TIME_WARN_OFFSET_MS`：告警阈值（默认 100）
+//! - `GATEWAY_TIME_MAX_OFFSET_MS`：偏差上限（默认 1000）
+
+use std::fmt;
+use std::net::UdpSocket;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::{SystemClock, TimestampProvider};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::exchange_info::json_response;
+
+/// 服务器时间接口路径
+pub const SERVER_TIME_PATH: &str = "/api/time";
+
+/// 默认告警阈值（毫秒）
+pub const DEFAULT_WARN_OFFSET_MS: u64 = 100;
+/// 默认偏差上限（毫秒）
+pub const DEFAULT_MAX_OFFSET_MS: u64 = 1_000;
+/// 默认采样间隔
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// 连续采样失败达到该次数视为失步
+pub const DEFAULT_MAX_FAILURES: u32 = 3;
+
+const ENV_NTP_SERVER: &str = "GATEWAY_NTP_SERVER";
+const ENV_WARN_OFFSET_MS: &str = "GATEWAY_TIME_WARN_OFFSET_MS";
+const ENV_MAX_OFFSET_MS: &str = "GATEWAY_TIME_MAX_OFFSET_MS";
+
+/// NTP 纪元（1900-01-01）与 Unix 纪元之差（秒）
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+/// NTP 报文长度
+const NTP_PACKET_LEN: usize = 48;
+
+/// 时钟同步错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSyncError {
+    /// 时间源不可用或应答无效
+    Source(String),
+    /// 偏差超过上限
+    DriftExceeded { offset_ms: i64, max_offset_ms: u64 },
+    /// 配置错误
+    Config(String),
+}
+
+impl fmt::Display for TimeSyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeSyncError::Source(e) => write!(f, "Time source error: {}", e),
+            TimeSyncError::DriftExceeded { offset_ms, max_offset_ms } => {
+                write!(f, "Clock offset {}ms exceeds {}ms", offset_ms, max_offset_ms)
+            }
+            TimeSyncError::Config(e) => write!(f, "Invalid time sync config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TimeSyncError {}
+
+/// 一次偏差采样
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    /// 参考时间 - 本机时间（毫秒），正数表示本机慢
+    pub offset_ms: i64,
+    /// 往返时延（毫秒，不含服务端处理时间）
+    pub round_trip_ms: u64,
+}
+
+/// 参考时间源
+pub trait ClockOffsetSource: Send + Sync {
+    /// 时间源名称（用于日志与指标）
+    fn name(&self) -> &str;
+
+    /// 采样本机时钟偏差
+    fn sample(&self) -> Result<ClockSample, TimeSyncError>;
+}
+
+/// SNTP 时间源（RFC 4330，客户端模式单次请求）
+pub struct SntpSource {
+    server: String,
+    timeout: Duration,
+    /// 被测的本机时钟
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl SntpSource {
+    pub fn new(server: impl Into<String>) -> Self {
+        Self {
+            server: server.into(),
+            timeout: Duration::from_secs(2),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl ClockOffsetSource for SntpSource {
+    fn name(&self) -> &str {
+        &self.server
+    }
+
+    fn sample(&self) -> Result<ClockSample, TimeSyncError> {
+        let io = |e: std::io::Error| TimeSyncError::Source(format!("{}: {}", self.server, e));
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(io)?;
+        socket.set_read_timeout(Some(self.timeout)).map_err(io)?;
+        socket.connect(&self.server).map_err(io)?;
+
+        let sent_ms = self.clock.now_millis();
+        socket.send(&sntp_request(sent_ms)).map_err(io)?;
+        let mut reply = [0u8; NTP_PACKET_LEN];
+        let len = socket.recv(&mut reply).map_err(io)?;
+        let received_ms = self.clock.now_millis();
+        if len < NTP_PACKET_LEN {
+            return Err(TimeSyncError::Source(format!("{}: short reply", self.server)));
+        }
+        sntp_offset(sent_ms, received_ms, &reply)
+    }
+}
+
+/// 客户端请求报文：LI=0、VN=4、Mode=3，发送时间写入 Transmit Timestamp
+fn sntp_request(sent_ms: u64) -> [u8; NTP_PACKET_LEN] {
+    let mut packet = [0u8; NTP_PACKET_LEN];
+    packet[0] = 0x23;
+    packet[40..48].copy_from_slice(&to_ntp_timestamp(sent_ms).to_be_bytes());
+    packet
+}
+
+/// 由服务端应答计算偏差：`((t2 - t1) + (t3 - t4)) / 2`，往返时延 `(t4 - t1) - (t3 - t2)`
+fn sntp_offset(
+    sent_ms: u64,
+    received_ms: u64,
+    reply: &[u8; NTP_PACKET_LEN],
+) -> Result<ClockSample, TimeSyncError> {
+    let read = |offset: usize| {
+        reply
+            .get(offset..offset + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_be_bytes)
+            .ok_or_else(|| TimeSyncError::Source("truncated reply".to_string()))
+    };
+    if reply[0] & 0x07 != 4 {
+        return Err(TimeSyncError::Source("not a server reply".to_string()));
+    }
+    if reply[1] == 0 {
+        return Err(TimeSyncError::Source("kiss-of-death or unsynchronized server".to_string()));
+    }
+    if read(24)? != to_ntp_timestamp(sent_ms) {
+        return Err(TimeSyncError::Source("originate timestamp mismatch".to_string()));
+    }
+    let (t1, t4) = (sent_ms as i64, received_ms as i64);
+    let t2 = from_ntp_timestamp(read(32)?) as i64;
+    let t3 = from_ntp_timestamp(read(40)?) as i64;
+    Ok(ClockSample {
+        offset_ms: ((t2 - t1) + (t3 - t4)) / 2,
+        round_trip_ms: ((t4 - t1) - (t3 - t2)).max(0) as u64,
+    })
+}
+
+/// Unix 毫秒 -> NTP 32.32 定点时间
+fn to_ntp_timestamp(unix_ms: u64) -> u64 {
+    let secs = unix_ms / 1_000 + NTP_UNIX_OFFSET_SECS;
+    let fraction = ((unix_ms % 1_000) << 32) / 1_000;
+    (secs << 32) | fraction
+}
+
+/// NTP 32.32 定点时间 -> Unix 毫秒
+fn from_ntp_timestamp(ntp: u64) -> u64 {
+    let secs = (ntp >> 32).saturating_sub(NTP_UNIX_OFFSET_SECS);
+    let millis = ((ntp & 0xffff_ffff) * 1_000 + (1 << 31)) >> 32;
+    secs * 1_000 + millis
+}
+
+/// 同步状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SyncState {
+    /// 尚未采样
+    Unknown,
+    /// 偏差在告警阈值内
+    Synced,
+    /// 偏差超过告警阈值但未超上限
+    Drifting,
+    /// 偏差超过上限，或连续采样失败
+    Unsynced,
+}
+
+/// 时钟同步监控配置
+#[derive(Debug, Clone)]
+pub struct TimeSyncConfig {
+    /// NTP 服务器地址
+    pub ntp_server: String,
+    pub warn_offset_ms: u64,
+    pub max_offset_ms: u64,
+    pub interval: Duration,
+    pub max_failures: u32,
+}
+
+impl TimeSyncConfig {
+    pub fn new(ntp_server: impl Into<String>) -> Self {
+        Self {
+            ntp_server: ntp_server.into(),
+            warn_offset_ms: DEFAULT_WARN_OFFSET_MS,
+            max_offset_ms: DEFAULT_MAX_OFFSET_MS,
+            interval: DEFAULT_SYNC_INTERVAL,
+            max_failures: DEFAULT_MAX_FAILURES,
+        }
+    }
+
+    /// 从环境变量读取，未配置 NTP 服务器时返回 `None`
+    pub fn from_env() -> Result<Option<Self>, TimeSyncError> {
+        let Ok(ntp_server) = std::env::var(ENV_NTP_SERVER) else {
+            return Ok(None);
+        };
+        let mut config = Self::new(ntp_server);
+        if let Some(warn_offset_ms) = env_millis(ENV_WARN_OFFSET_MS)? {
+            config.warn_offset_ms = warn_offset_ms;
+        }
+        if let Some(max_offset_ms) = env_millis(ENV_MAX_OFFSET_MS)? {
+            config.max_offset_ms = max_offset_ms;
+        }
+        if config.warn_offset_ms > config.max_offset_ms {
+            return Err(TimeSyncError::Config(format!(
+                "warn offset {}ms above max offset {}ms",
+                config.warn_offset_ms, config.max_offset_ms
+            )));
+        }
+        Ok(Some(config))
+    }
+}
+
+fn env_millis(name: &str) -> Result<Option<u64>, TimeSyncError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| TimeSyncError::Config(format!("{} is not a number: {}", name, value))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// 时钟同步指标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSyncStatus {
+    pub source: String,
+    pub state: SyncState,
+    /// 最近一次成功采样的偏差
+    pub offset_ms: Option<i64>,
+    pub round_trip_ms: Option<u64>,
+    /// 历史最大偏差（绝对值）
+    pub max_abs_offset_ms: u64,
+    pub samples: u64,
+    pub failures: u64,
+    /// 当前连续失败次数
+    pub consecutive_failures: u32,
+    /// 最近一次采样时间（毫秒）
+    pub last_sample_ms: Option<u64>,
+}
+
+/// 时钟同步监控
+pub struct TimeSyncMonitor {
+    source: Arc<dyn ClockOffsetSource>,
+    config: TimeSyncConfig,
+    status: RwLock<TimeSyncStatus>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl fmt::Debug for TimeSyncMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeSyncMonitor")
+            .field("source", &self.source.name())
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TimeSyncMonitor {
+    pub fn new(source: Arc<dyn ClockOffsetSource>, config: TimeSyncConfig) -> Self {
+        let status = TimeSyncStatus {
+            source: source.name().to_string(),
+            state: SyncState::Unknown,
+            offset_ms: None,
+            round_trip_ms: None,
+            max_abs_offset_ms: 0,
+            samples: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            last_sample_ms: None,
+        };
+        Self { source, config, status: RwLock::new(status), clock: Arc::new(SystemClock) }
+    }
+
+    /// 按配置使用 SNTP 时间源
+    pub fn sntp(config: TimeSyncConfig) -> Self {
+        Self::new(Arc::new(SntpSource::new(config.ntp_server.clone())), config)
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn status(&self) -> TimeSyncStatus {
+        self.status.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// 采样一次并更新指标，返回最新状态
+    pub fn poll(&self) -> SyncState {
+        self.record(self.source.sample())
+    }
+
+    /// 记录一次采样结果；进入告警或失步时记录日志
+    fn record(&self, sample: Result<ClockSample, TimeSyncError>) -> SyncState {
+        let mut status = self.status.write().unwrap_or_else(PoisonError::into_inner);
+        let previous = status.state;
+        status.last_sample_ms = Some(self.clock.now_millis());
+        match sample {
+            Ok(sample) => {
+                let abs_offset = sample.offset_ms.unsigned_abs();
+                status.samples += 1;
+                status.consecutive_failures = 0;
+                status.offset_ms = Some(sample.offset_ms);
+                status.round_trip_ms = Some(sample.round_trip_ms);
+                status.max_abs_offset_ms = status.max_abs_offset_ms.max(abs_offset);
+                status.state = if abs_offset > self.config.max_offset_ms {
+                    SyncState::Unsynced
+                } else if abs_offset > self.config.warn_offset_ms {
+                    SyncState::Drifting
+                } else {
+                    SyncState::Synced
+                };
+            }
+            Err(e) => {
+                status.failures += 1;
+                status.consecutive_failures += 1;
+                warn!("⏱️  Time sync sample from {} failed: {}", status.source, e);
+                if status.consecutive_failures >= self.config.max_failures {
+                    status.state = SyncState::Unsynced;
+                }
+            }
+        }
+
+        let state = status.state;
+        if state != previous || state == SyncState::Unsynced {
+            match state {
+                SyncState::Drifting | SyncState::Unsynced => warn!(
+                    "⏱️  Clock {:?}: offset {:?}ms vs {} (warn {}ms, max {}ms)",
+                    state,
+                    status.offset_ms,
+                    status.source,
+                    self.config.warn_offset_ms,
+                    self.config.max_offset_ms
+                ),
+                _ => info!(
+                    "⏱️  Clock {:?}: offset {:?}ms vs {}",
+                    state, status.offset_ms, status.source
+                ),
+            }
+        }
+        state
+    }
+
+    /// 启动检查：采样失败或偏差超过上限时返回错误，调用方应拒绝启动
+    pub fn check_startup(&self) -> Result<TimeSyncStatus, TimeSyncError> {
+        let sample = self.source.sample();
+        self.record(sample.clone());
+        let sample = sample?;
+        if sample.offset_ms.unsigned_abs() > self.config.max_offset_ms {
+            return Err(TimeSyncError::DriftExceeded {
+                offset_ms: sample.offset_ms,
+                max_offset_ms: self.config.max_offset_ms,
+            });
+        }
+        Ok(self.status())
+    }
+
+    /// 在独立线程中按间隔持续采样
+    pub fn spawn(self: Arc<Self>) -> std::io::Result<JoinHandle<()>> {
+        std::thread::Builder::new().name("time-sync".to_string()).spawn(move || {
+            loop {
+                std::thread::sleep(self.config.interval);
+                self.poll();
+            }
+        })
+    }
+}
+
+/// `GET /api/time` 处理器
+pub struct ServerTimeHandler {
+    clock: Arc<dyn TimestampProvider>,
+    sync: Option<Arc<TimeSyncMonitor>>,
+}
+
+impl Default for ServerTimeHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl ServerTimeHandler {
+    pub fn new(clock: Arc<dyn TimestampProvider>) -> Self {
+        Self { clock, sync: None }
+    }
+
+    /// 在响应中附带时钟同步指标
+    pub fn with_sync_monitor(mut self, sync: Arc<TimeSyncMonitor>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(SERVER_TIME_PATH)
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self) -> Vec<u8> {
+        json_response(200, &self.render())
+    }
+
+    fn render(&self) -> String {
+        let server_time = self.clock.now_millis();
+        match &self.sync {
+            Some(sync) => {
+                serde_json::json!({ "serverTime": server_time, "timeSync": sync.status() })
+            }
+            None => serde_json::json!({ "serverTime": server_time }),
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use base_types::ManualClock;
+
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    struct ScriptedSource {
+        samples: Mutex<VecDeque<Result<ClockSample, TimeSyncError>>>,
+    }
+
+    impl ScriptedSource {
+        fn new(offsets: &[Option<i64>]) -> Arc<Self> {
+            let samples = offsets
+                .iter()
+                .map(|offset| match offset {
+                    Some(offset_ms) => Ok(ClockSample { offset_ms: *offset_ms, round_trip_ms: 4 }),
+                    None => Err(TimeSyncError::Source("timeout".to_string())),
+                })
+                .collect();
+            Arc::new(Self { samples: Mutex::new(samples) })
+        }
+    }
+
+    impl ClockOffsetSource for ScriptedSource {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn sample(&self) -> Result<ClockSample, TimeSyncError> {
+            self.samples.lock().unwrap().pop_front().unwrap()
+        }
+    }
+
+    fn monitor(offsets: &[Option<i64>]) -> TimeSyncMonitor {
+        TimeSyncMonitor::new(ScriptedSource::new(offsets), TimeSyncConfig::new("scripted"))
+            .with_clock(Arc::new(ManualClock::from_millis(NOW_MS)))
+    }
+
+    #[test]
+    fn test_sntp_offset_and_timestamps() {
+        assert_eq!(from_ntp_timestamp(to_ntp_timestamp(NOW_MS + 123)), NOW_MS + 123);
+
+        // 本机慢 250ms，单程 10ms
+        let request = sntp_request(NOW_MS);
+        let mut reply = [0u8; NTP_PACKET_LEN];
+        reply[0] = 0x24;
+        reply[1] = 2;
+        reply[24..32].copy_from_slice(&request[40..48]);
+        reply[32..40].copy_from_slice(&to_ntp_timestamp(NOW_MS + 260).to_be_bytes());
+        reply[40..48].copy_from_slice(&to_ntp_timestamp(NOW_MS + 261).to_be_bytes());
+        assert_eq!(
+            sntp_offset(NOW_MS, NOW_MS + 21, &reply),
+            Ok(ClockSample { offset_ms: 250, round_trip_ms: 20 })
+        );
+
+        assert!(sntp_offset(NOW_MS + 1, NOW_MS + 21, &reply).is_err());
+        reply[1] = 0;
+        assert!(sntp_offset(NOW_MS, NOW_MS + 21, &reply).is_err());
+    }
+
+    #[test]
+    fn test_sntp_source_against_local_server() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut request = [0u8; NTP_PACKET_LEN];
+            let (_, peer) = server.recv_from(&mut request).unwrap();
+            let mut reply = [0u8; NTP_PACKET_LEN];
+            reply[0] = 0x24;
+            reply[1] = 1;
+            reply[24..32].copy_from_slice(&request[40..48]);
+            let server_time = to_ntp_timestamp(NOW_MS - 400).to_be_bytes();
+            reply[32..40].copy_from_slice(&server_time);
+            reply[40..48].copy_from_slice(&server_time);
+            server.send_to(&reply, peer).unwrap();
+        });
+
+        let source = SntpSource::new(address.to_string())
+            .with_clock(Arc::new(ManualClock::from_millis(NOW_MS)));
+        assert_eq!(source.sample(), Ok(ClockSample { offset_ms: -400, round_trip_ms: 0 }));
+    }
+
+    #[test]
+    fn test_monitor_states_and_metrics() {
+        let monitor = monitor(&[Some(20), Some(-150), Some(1_500), None, None, None, Some(5)]);
+        assert_eq!(monitor.status().state, SyncState::Unknown);
+        assert_eq!(monitor.poll(), SyncState::Synced);
+        assert_eq!(monitor.poll(), SyncState::Drifting);
+        assert_eq!(monitor.poll(), SyncState::Unsynced);
+        // 连续失败达到上限前保持原状态
+        assert_eq!(monitor.poll(), SyncState::Unsynced);
+        let status = monitor.status();
+        assert_eq!((status.samples, status.failures, status.max_abs_offset_ms), (3, 1, 1_500));
+        assert_eq!(status.last_sample_ms, Some(NOW_MS));
+
+        let monitor = self::monitor(&[Some(5), None, None, None, Some(5)]);
+        monitor.poll();
+        assert_eq!(monitor.poll(), SyncState::Synced);
+        assert_eq!(monitor.poll(), SyncState::Synced);
+        assert_eq!(monitor.poll(), SyncState::Unsynced);
+        assert_eq!(monitor.poll(), SyncState::Synced);
+        assert_eq!(monitor.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_startup_refused_on_drift() {
+        assert_eq!(
+            monitor(&[Some(-1_001)]).check_startup(),
+            Err(TimeSyncError::DriftExceeded { offset_ms: -1_001, max_offset_ms: 1_000 })
+        );
+        assert!(matches!(monitor(&[None]).check_startup(), Err(TimeSyncError::Source(_))));
+        assert_eq!(monitor(&[Some(999)]).check_startup().unwrap().state, SyncState::Drifting);
+    }
+
+    #[test]
+    fn test_server_time_endpoint() {
+        let clock = Arc::new(ManualClock::from_millis(NOW_MS));
+        assert!(ServerTimeHandler::matches("GET", "/api/time"));
+        assert!(!ServerTimeHandler::matches("POST", "/api/time"));
+
+        let handler = ServerTimeHandler::new(clock.clone());
+        let json: serde_json::Value = serde_json::from_str(&handler.render()).unwrap();
+        assert_eq!(json, serde_json::json!({ "serverTime": NOW_MS }));
+
+        let sync = Arc::new(monitor(&[Some(-30)]));
+        sync.poll();
+        let handler = ServerTimeHandler::new(clock).with_sync_monitor(sync);
+        let json: serde_json::Value = serde_json::from_str(&handler.render()).unwrap();
+        assert_eq!(json["timeSync"]["state"], "SYNCED");
+        assert_eq!(json["timeSync"]["offsetMs"], -30);
+        assert_eq!(json["timeSync"]["source"], "scripted");
+    }
+}
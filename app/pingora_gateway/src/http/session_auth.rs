@@ -0,0 +1,781 @@
+//! 浏览器会话鉴权（JWT）
+//!
+//! 与 API Key HMAC 并存，面向浏览器端接口与 WebSocket 握手：
+//! - `POST /api/auth/login`：`{username, password}` 登录，签发访问令牌（JWT，HS256）与刷新令牌
+//! - `POST /api/auth/refresh`：`{refreshToken}` 换发新令牌对，旧刷新令牌立即作废；
+//!   已作废的刷新令牌再次出现视为泄露，整个会话被吊销
+//! - `POST /api/auth/logout`：携带 `Authorization: Bearer <访问令牌>`，吊销当前会话
+//! - `GET /api/auth/revocations?since=<序号>`：吊销列表增量，边缘代理据此同步，
+//!   在边缘即可拒绝已吊销会话的访问令牌
+//!
+//! 访问令牌的 `sub` 为账户ID、`sid` 为会话ID；外部 IdP 以 `iss` 区分，需登记其 HS256 密钥
+//!
+//! 通过环境变量配置（[`SessionAuth::from_env`]）：
+//! - `GATEWAY_JWT_SECRET`: HS256 签名密钥（至少 32 字节），多个网关实例须相同
+//! - `GATEWAY_JWT_ISSUER`: 本网关签发令牌的 `iss`（默认 `rustlob-gateway`）
+//! - `GATEWAY_JWT_TRUSTED_ISSUERS`: 外部 IdP，`iss=密钥`，逗号分隔
+//! - `GATEWAY_LOGIN_FILE`: 登录凭证文件（[`FileLoginVerifier`]），未配置时登录接口关闭
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::{AccountId, SystemClock, TimestampProvider};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use super::codec::{header_value, request_body};
+use super::exchange_info::{json_response, query_param};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 登录接口路径
+pub const LOGIN_PATH: &str = "/api/auth/login";
+/// 刷新接口路径
+pub const REFRESH_PATH: &str = "/api/auth/refresh";
+/// 注销接口路径
+pub const LOGOUT_PATH: &str = "/api/auth/logout";
+/// 吊销列表接口路径
+pub const REVOCATIONS_PATH: &str = "/api/auth/revocations";
+
+/// 本网关签发令牌的默认 `iss`
+pub const DEFAULT_ISSUER: &str = "rustlob-gateway";
+/// 签名密钥最短长度（字节）
+pub const MIN_SECRET_LEN: usize = 32;
+
+const ENV_JWT_SECRET: &str = "GATEWAY_JWT_SECRET";
+const ENV_JWT_ISSUER: &str = "GATEWAY_JWT_ISSUER";
+const ENV_JWT_TRUSTED_ISSUERS: &str = "GATEWAY_JWT_TRUSTED_ISSUERS";
+const ENV_LOGIN_FILE: &str = "GATEWAY_LOGIN_FILE";
+
+/// 默认访问令牌有效期（秒）
+pub const DEFAULT_ACCESS_TTL_SECS: u64 = 15 * 60;
+/// 默认刷新令牌有效期（秒），每次刷新顺延
+pub const DEFAULT_REFRESH_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// JWT 头部
+const JWT_HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+/// 令牌校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JwtError {
+    /// 格式错误
+    Malformed,
+    /// 不支持的签名算法（只接受 HS256）
+    UnsupportedAlgorithm(String),
+    /// 未登记的签发方
+    UnknownIssuer(String),
+    /// 签名不符
+    InvalidSignature,
+    /// 已过期
+    Expired,
+    /// 会话已吊销
+    Revoked,
+    /// 刷新令牌无效或已作废
+    InvalidRefreshToken,
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtError::Malformed => write!(f, "Malformed token"),
+            JwtError::UnsupportedAlgorithm(alg) => write!(f, "Unsupported algorithm: {}", alg),
+            JwtError::UnknownIssuer(iss) => write!(f, "Unknown issuer: {}", iss),
+            JwtError::InvalidSignature => write!(f, "Invalid signature"),
+            JwtError::Expired => write!(f, "Token expired"),
+            JwtError::Revoked => write!(f, "Session revoked"),
+            JwtError::InvalidRefreshToken => write!(f, "Invalid refresh token"),
+        }
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+/// 访问令牌载荷
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// 签发方
+    pub iss: String,
+    /// 账户ID
+    #[serde(with = "u64_string")]
+    pub sub: u64,
+    /// 会话ID（外部 IdP 签发的令牌可以没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// 签发时间（秒）
+    pub iat: u64,
+    /// 过期时间（秒）
+    pub exp: u64,
+}
+
+impl JwtClaims {
+    pub fn account_id(&self) -> AccountId {
+        AccountId(self.sub)
+    }
+}
+
+/// `sub` 按 JWT 惯例为字符串
+mod u64_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+/// 令牌对
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPair {
+    pub access_token: String,
+    /// `<会话ID>.<随机串>`，每次刷新轮换
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    /// 访问令牌有效期（秒）
+    pub expires_in: u64,
+}
+
+/// 一条吊销记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Revocation {
+    pub seq: u64,
+    pub sid: String,
+    /// 该会话签发的访问令牌最晚过期时间（秒），之后记录可清理
+    pub until: u64,
+}
+
+/// 吊销列表（网关与边缘代理共享）
+#[derive(Debug, Default)]
+pub struct RevocationList {
+    revocations: Vec<Revocation>,
+    next_seq: u64,
+}
+
+impl RevocationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 吊销会话，返回序号
+    pub fn revoke(&mut self, sid: &str, until: u64) -> u64 {
+        self.next_seq += 1;
+        self.revocations.push(Revocation { seq: self.next_seq, sid: sid.to_string(), until });
+        self.next_seq
+    }
+
+    pub fn is_revoked(&self, sid: &str) -> bool {
+        self.revocations.iter().any(|r| r.sid == sid)
+    }
+
+    /// 序号大于 `since` 的记录
+    pub fn since(&self, since: u64) -> &[Revocation] {
+        let start = self.revocations.partition_point(|r| r.seq <= since);
+        &self.revocations[start..]
+    }
+
+    /// 最新序号
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// 清理令牌已全部过期的记录
+    pub fn purge(&mut self, now_secs: u64) -> usize {
+        let before = self.revocations.len();
+        self.revocations.retain(|r| r.until > now_secs);
+        before - self.revocations.len()
+    }
+}
+
+/// 登录凭证校验（由账户服务实现）
+pub trait LoginVerifier: Send + Sync {
+    /// 校验用户名与密码，返回账户
+    fn verify(&self, username: &str, password: &str) -> Option<AccountId>;
+}
+
+/// 登录凭证文件
+///
+/// 每行 `用户名 账户ID 迭代次数 盐 口令哈希`（盐与哈希为 base64），`#` 开头为注释；
+/// 口令哈希为 PBKDF2-HMAC-SHA256，由 [`hash_password`] 生成
+#[derive(Debug, Default)]
+pub struct FileLoginVerifier {
+    users: HashMap<String, Credential>,
+}
+
+#[derive(Debug)]
+struct Credential {
+    account_id: AccountId,
+    iterations: u32,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl FileLoginVerifier {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let mut users = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                let msg = format!("invalid credential on line {}", index + 1);
+                io::Error::new(io::ErrorKind::InvalidData, msg)
+            };
+            let [username, account_id, iterations, salt, hash] =
+                line.split_whitespace().collect::<Vec<_>>()[..]
+            else {
+                return Err(invalid());
+            };
+            let credential = Credential {
+                account_id: AccountId(account_id.parse().map_err(|_| invalid())?),
+                iterations: iterations.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?,
+                salt: STANDARD.decode(salt).map_err(|_| invalid())?,
+                hash: STANDARD.decode(hash).map_err(|_| invalid())?,
+            };
+            users.insert(username.to_string(), credential);
+        }
+        Ok(Self { users })
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+}
+
+impl LoginVerifier for FileLoginVerifier {
+    fn verify(&self, username: &str, password: &str) -> Option<AccountId> {
+        let credential = self.users.get(username)?;
+        let hash = hash_password(password, &credential.salt, credential.iterations);
+        constant_time_eq(&hash, &credential.hash).then_some(credential.account_id)
+    }
+}
+
+/// PBKDF2-HMAC-SHA256 口令哈希（32 字节）
+pub fn hash_password(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let prf =
+        HmacSha256::new_from_slice(password.as_bytes()).expect("HMAC accepts keys of any length");
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut block = mac.finalize().into_bytes();
+    let mut hash: [u8; 32] = block.into();
+    for _ in 1..iterations {
+        let mut mac = prf.clone();
+        mac.update(&block);
+        block = mac.finalize().into_bytes();
+        for (out, byte) in hash.iter_mut().zip(block.iter()) {
+            *out ^= byte;
+        }
+    }
+    hash
+}
+
+/// 登录会话
+#[derive(Debug, Clone)]
+struct Session {
+    account_id: AccountId,
+    /// 当前有效的刷新令牌随机串
+    refresh_secret: String,
+    /// 刷新令牌过期时间（秒）
+    expires_at: u64,
+}
+
+/// 登录请求体
+#[derive(Debug, Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+/// 刷新请求体
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+/// 会话鉴权服务
+pub struct SessionAuth {
+    /// 本网关签发令牌使用的 `iss`
+    issuer: String,
+    secret: Vec<u8>,
+    /// 外部 IdP：`iss` -> HS256 密钥
+    trusted_issuers: HashMap<String, Vec<u8>>,
+    access_ttl_secs: u64,
+    refresh_ttl_secs: u64,
+    login: Option<Arc<dyn LoginVerifier>>,
+    /// 会话ID -> 会话
+    sessions: RwLock<HashMap<String, Session>>,
+    revocations: Arc<RwLock<RevocationList>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl fmt::Debug for SessionAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionAuth")
+            .field("issuer", &self.issuer)
+            .field("access_ttl_secs", &self.access_ttl_secs)
+            .field("refresh_ttl_secs", &self.refresh_ttl_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SessionAuth {
+    /// 随机密钥、未配置登录校验：只能校验外部 IdP 令牌，部署时以 [`SessionAuth::from_env`] 配置
+    fn default() -> Self {
+        Self::new(DEFAULT_ISSUER, uuid::Uuid::new_v4().as_bytes().to_vec(), Arc::new(SystemClock))
+    }
+}
+
+impl SessionAuth {
+    pub fn new(
+        issuer: impl Into<String>,
+        secret: impl Into<Vec<u8>>,
+        clock: Arc<dyn TimestampProvider>,
+    ) -> Self {
+        Self {
+            issuer: issuer.into(),
+            secret: secret.into(),
+            trusted_issuers: HashMap::new(),
+            access_ttl_secs: DEFAULT_ACCESS_TTL_SECS,
+            refresh_ttl_secs: DEFAULT_REFRESH_TTL_SECS,
+            login: None,
+            sessions: RwLock::new(HashMap::new()),
+            revocations: Arc::new(RwLock::new(RevocationList::new())),
+            clock,
+        }
+    }
+
+    /// 从环境变量构建，均未设置时返回 `Ok(None)`
+    ///
+    /// 配置了登录凭证文件或外部 IdP 时必须配置签名密钥
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.is_empty());
+        let login_file = var(ENV_LOGIN_FILE);
+        let trusted = var(ENV_JWT_TRUSTED_ISSUERS);
+        let Some(secret) = var(ENV_JWT_SECRET) else {
+            if login_file.is_some() || trusted.is_some() {
+                return Err(format!(
+                    "{} is required for login and trusted issuers",
+                    ENV_JWT_SECRET
+                ));
+            }
+            return Ok(None);
+        };
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(format!("{} must be at least {} bytes", ENV_JWT_SECRET, MIN_SECRET_LEN));
+        }
+        let issuer = var(ENV_JWT_ISSUER).unwrap_or_else(|| DEFAULT_ISSUER.to_string());
+        let mut auth = Self::new(issuer, secret.into_bytes(), Arc::new(SystemClock));
+        for entry in trusted.iter().flat_map(|list| list.split(',')) {
+            let (issuer, secret) = entry
+                .trim()
+                .split_once('=')
+                .ok_or_else(|| format!("invalid {} entry: {}", ENV_JWT_TRUSTED_ISSUERS, entry))?;
+            auth = auth.with_trusted_issuer(issuer, secret.as_bytes());
+        }
+        if let Some(path) = login_file {
+            let users = FileLoginVerifier::open(&path).map_err(|e| format!("{}: {}", path, e))?;
+            auth = auth.with_login(Arc::new(users));
+        }
+        Ok(Some(auth))
+    }
+
+    /// 登录凭证校验
+    pub fn with_login(mut self, login: Arc<dyn LoginVerifier>) -> Self {
+        self.login = Some(login);
+        self
+    }
+
+    /// 信任外部 IdP 签发的令牌
+    pub fn with_trusted_issuer(
+        mut self,
+        issuer: impl Into<String>,
+        secret: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.trusted_issuers.insert(issuer.into(), secret.into());
+        self
+    }
+
+    pub fn with_ttl(mut self, access_ttl_secs: u64, refresh_ttl_secs: u64) -> Self {
+        self.access_ttl_secs = access_ttl_secs;
+        self.refresh_ttl_secs = refresh_ttl_secs;
+        self
+    }
+
+    /// 与边缘代理共享的吊销列表
+    pub fn with_revocations(mut self, revocations: Arc<RwLock<RevocationList>>) -> Self {
+        self.revocations = revocations;
+        self
+    }
+
+    pub fn revocations(&self) -> &Arc<RwLock<RevocationList>> {
+        &self.revocations
+    }
+
+    pub fn matches(method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        match method {
+            "POST" => matches!(path, LOGIN_PATH | REFRESH_PATH | LOGOUT_PATH),
+            "GET" => path == REVOCATIONS_PATH,
+            _ => false,
+        }
+    }
+
+    /// 应答会话接口
+    pub fn respond(&self, path: &str, request: &[u8]) -> Vec<u8> {
+        let now_ms = self.now_ms();
+        let result = match path.split('?').next().unwrap_or(path) {
+            LOGIN_PATH => self.handle_login(request_body(request), now_ms),
+            REFRESH_PATH => serde_json::from_slice::<RefreshRequest>(request_body(request))
+                .map_err(|e| (400, e.to_string()))
+                .and_then(|req| {
+                    self.refresh(&req.refresh_token, now_ms).map_err(|e| (401, e.to_string()))
+                })
+                .and_then(|pair| to_json(&pair)),
+            LOGOUT_PATH => self.handle_logout(request, now_ms),
+            _ => Ok(self.revocation_feed(query_param(path, "since"))),
+        };
+        match result {
+            Ok(body) => json_response(200, &body),
+            Err((status, msg)) => {
+                json_response(status, &serde_json::json!({ "msg": msg }).to_string())
+            }
+        }
+    }
+
+    fn handle_login(&self, body: &[u8], now_ms: u64) -> Result<String, (u16, String)> {
+        let login = self.login.as_ref().ok_or((503, "Login disabled".to_string()))?;
+        let req: LoginRequest = serde_json::from_slice(body).map_err(|e| (400, e.to_string()))?;
+        let account_id = login
+            .verify(&req.username, &req.password)
+            .ok_or((401, "Invalid username or password".to_string()))?;
+        to_json(&self.login_session(account_id, now_ms))
+    }
+
+    fn handle_logout(&self, request: &[u8], now_ms: u64) -> Result<String, (u16, String)> {
+        let claims = self
+            .authenticate(request, now_ms)
+            .map_err(|e| (401, e.to_string()))?
+            .ok_or((401, "Missing bearer token".to_string()))?;
+        let sid = claims.sid.ok_or((400, "Token has no session".to_string()))?;
+        self.revoke_session(&sid, now_ms);
+        Ok("{}".to_string())
+    }
+
+    fn revocation_feed(&self, since: Option<&str>) -> String {
+        let since = since.and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+        let list = self.revocations.read().unwrap_or_else(PoisonError::into_inner);
+        serde_json::json!({ "last": list.last_seq(), "revocations": list.since(since) }).to_string()
+    }
+
+    /// 新建会话并签发令牌对
+    pub fn login_session(&self, account_id: AccountId, now_ms: u64) -> TokenPair {
+        let sid = uuid::Uuid::new_v4().simple().to_string();
+        let refresh_secret = uuid::Uuid::new_v4().simple().to_string();
+        let session = Session {
+            account_id,
+            refresh_secret: refresh_secret.clone(),
+            expires_at: now_ms / 1000 + self.refresh_ttl_secs,
+        };
+        self.sessions.write().unwrap_or_else(PoisonError::into_inner).insert(sid.clone(), session);
+        self.token_pair(account_id, &sid, &refresh_secret, now_ms)
+    }
+
+    /// 以刷新令牌换发令牌对（轮换刷新令牌，有效期顺延）
+    ///
+    /// 已轮换掉的刷新令牌再次使用说明令牌可能泄露，吊销整个会话
+    pub fn refresh(&self, refresh_token: &str, now_ms: u64) -> Result<TokenPair, JwtError> {
+        let (sid, secret) = refresh_token.split_once('.').ok_or(JwtError::InvalidRefreshToken)?;
+        let now_secs = now_ms / 1000;
+        let mut sessions = self.sessions.write().unwrap_or_else(PoisonError::into_inner);
+        let session = sessions.get_mut(sid).ok_or(JwtError::InvalidRefreshToken)?;
+        if session.expires_at <= now_secs {
+            sessions.remove(sid);
+            return Err(JwtError::Expired);
+        }
+        if !constant_time_eq(session.refresh_secret.as_bytes(), secret.as_bytes()) {
+            sessions.remove(sid);
+            drop(sessions);
+            self.revocations
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .revoke(sid, now_secs + self.access_ttl_secs);
+            return Err(JwtError::Revoked);
+        }
+        session.refresh_secret = uuid::Uuid::new_v4().simple().to_string();
+        session.expires_at = now_secs + self.refresh_ttl_secs;
+        let (account_id, refresh_secret) = (session.account_id, session.refresh_secret.clone());
+        drop(sessions);
+        Ok(self.token_pair(account_id, sid, &refresh_secret, now_ms))
+    }
+
+    /// 吊销会话：刷新令牌立即失效，已签发的访问令牌加入吊销列表
+    pub fn revoke_session(&self, sid: &str, now_ms: u64) {
+        self.sessions.write().unwrap_or_else(PoisonError::into_inner).remove(sid);
+        self.revocations
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .revoke(sid, now_ms / 1000 + self.access_ttl_secs);
+    }
+
+    fn token_pair(
+        &self,
+        account_id: AccountId,
+        sid: &str,
+        refresh_secret: &str,
+        now_ms: u64,
+    ) -> TokenPair {
+        let now_secs = now_ms / 1000;
+        let claims = JwtClaims {
+            iss: self.issuer.clone(),
+            sub: account_id.0,
+            sid: Some(sid.to_string()),
+            iat: now_secs,
+            exp: now_secs + self.access_ttl_secs,
+        };
+        TokenPair {
+            access_token: self.sign(&claims),
+            refresh_token: format!("{}.{}", sid, refresh_secret),
+            token_type: "Bearer",
+            expires_in: self.access_ttl_secs,
+        }
+    }
+
+    /// 签发访问令牌
+    pub fn sign(&self, claims: &JwtClaims) -> String {
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(JWT_HEADER),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).expect("claims serialize to JSON"))
+        );
+        let signature = hmac_sha256(&self.secret, signing_input.as_bytes());
+        format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    /// 校验访问令牌
+    pub fn verify(&self, token: &str, now_ms: u64) -> Result<JwtClaims, JwtError> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or(JwtError::Malformed)?;
+        let (header, payload) = signing_input.split_once('.').ok_or(JwtError::Malformed)?;
+        let header: JwtHeader = decode_segment(header)?;
+        if header.alg != "HS256" {
+            return Err(JwtError::UnsupportedAlgorithm(header.alg));
+        }
+        let claims: JwtClaims = decode_segment(payload)?;
+        let secret = if claims.iss == self.issuer {
+            &self.secret
+        } else {
+            self.trusted_issuers
+                .get(&claims.iss)
+                .ok_or_else(|| JwtError::UnknownIssuer(claims.iss.clone()))?
+        };
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| JwtError::Malformed)?;
+        if !hmac_verify(secret, signing_input.as_bytes(), &signature) {
+            return Err(JwtError::InvalidSignature);
+        }
+        if claims.exp <= now_ms / 1000 {
+            return Err(JwtError::Expired);
+        }
+        if claims.sid.as_deref().is_some_and(|sid| {
+            self.revocations.read().unwrap_or_else(PoisonError::into_inner).is_revoked(sid)
+        }) {
+            return Err(JwtError::Revoked);
+        }
+        Ok(claims)
+    }
+
+    /// 校验 HTTP 请求的 `Authorization: Bearer` 令牌，未携带时返回 `Ok(None)`
+    pub fn authenticate(&self, request: &[u8], now_ms: u64) -> Result<Option<JwtClaims>, JwtError> {
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        match header_value(head, "Authorization").and_then(|v| v.strip_prefix("Bearer ")) {
+            Some(token) => self.verify(token.trim(), now_ms).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 清理过期会话与吊销记录
+    pub fn purge_expired(&self, now_ms: u64) {
+        let now_secs = now_ms / 1000;
+        self.sessions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, session| session.expires_at > now_secs);
+        self.revocations.write().unwrap_or_else(PoisonError::into_inner).purge(now_secs);
+    }
+
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now().0 / 1_000_000
+    }
+}
+
+fn to_json(pair: &TokenPair) -> Result<String, (u16, String)> {
+    serde_json::to_string(pair).map_err(|e| (500, e.to_string()))
+}
+
+/// 是否为 JWT（三段、头部为 base64url 编码的 JSON）
+pub fn is_jwt(token: &str) -> bool {
+    token.starts_with("eyJ") && token.split('.').count() == 3
+}
+
+fn decode_segment<T: serde::de::DeserializeOwned>(segment: &str) -> Result<T, JwtError> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment).map_err(|_| JwtError::Malformed)?;
+    serde_json::from_slice(&bytes).map_err(|_| JwtError::Malformed)
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hmac_verify(key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.verify_slice(signature).is_ok()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    struct Users;
+
+    impl LoginVerifier for Users {
+        fn verify(&self, username: &str, password: &str) -> Option<AccountId> {
+            (username == "alice" && password == "pw").then_some(AccountId(42))
+        }
+    }
+
+    fn auth() -> SessionAuth {
+        SessionAuth::new("gw", "secret", Arc::new(SystemClock))
+            .with_login(Arc::new(Users))
+            .with_trusted_issuer("idp", "idp-secret")
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let auth = auth();
+        let pair = auth.login_session(AccountId(42), NOW_MS);
+        assert!(is_jwt(&pair.access_token));
+        let claims = auth.verify(&pair.access_token, NOW_MS).unwrap();
+        assert_eq!((claims.account_id(), claims.iss.as_str()), (AccountId(42), "gw"));
+        assert_eq!(
+            auth.verify(&pair.access_token, NOW_MS + DEFAULT_ACCESS_TTL_SECS * 1000),
+            Err(JwtError::Expired)
+        );
+
+        let mut tampered = pair.access_token.clone();
+        tampered.pop();
+        tampered.push(if pair.access_token.ends_with('A') { 'B' } else { 'A' });
+        assert!(auth.verify(&tampered, NOW_MS).is_err());
+
+        // 外部 IdP 令牌按 iss 选择密钥
+        let idp = SessionAuth::new("idp", "idp-secret", Arc::new(SystemClock));
+        let claims = JwtClaims { iss: "idp".into(), sub: 7, sid: None, iat: 0, exp: u64::MAX };
+        assert_eq!(auth.verify(&idp.sign(&claims), NOW_MS).unwrap().sub, 7);
+        let rogue = SessionAuth::new("other", "x", Arc::new(SystemClock));
+        let claims = JwtClaims { iss: "other".into(), ..claims };
+        assert_eq!(
+            auth.verify(&rogue.sign(&claims), NOW_MS),
+            Err(JwtError::UnknownIssuer("other".into()))
+        );
+    }
+
+    #[test]
+    fn test_refresh_rotation_and_reuse_detection() {
+        let auth = auth();
+        let first = auth.login_session(AccountId(42), NOW_MS);
+        let second = auth.refresh(&first.refresh_token, NOW_MS + 1_000).unwrap();
+        assert_ne!(first.refresh_token, second.refresh_token);
+        assert!(auth.verify(&second.access_token, NOW_MS + 1_000).is_ok());
+
+        // 重放已轮换的刷新令牌：会话吊销，所有令牌失效
+        assert_eq!(auth.refresh(&first.refresh_token, NOW_MS + 2_000), Err(JwtError::Revoked));
+        assert_eq!(auth.verify(&second.access_token, NOW_MS + 2_000), Err(JwtError::Revoked));
+        assert_eq!(
+            auth.refresh(&second.refresh_token, NOW_MS + 2_000),
+            Err(JwtError::InvalidRefreshToken)
+        );
+        assert_eq!(auth.revocations().read().unwrap().since(0).len(), 1);
+    }
+
+    #[test]
+    fn test_file_login_verifier() {
+        // RFC 7914 PBKDF2-HMAC-SHA256 测试向量的前 32 字节
+        let hash = hash_password("passwd", b"salt", 1);
+        assert_eq!(&hash[..4], &[0x55, 0xac, 0x04, 0x6e]);
+        assert_eq!(&hash[28..], &[0xc2, 0x0d, 0xac, 0xbc]);
+
+        let hash = STANDARD.encode(hash_password("pw", b"pepper", 1_000));
+        let content = format!("# users\nalice 42 1000 {} {}\n", STANDARD.encode("pepper"), hash);
+        let users = FileLoginVerifier::parse(&content).unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users.verify("alice", "pw"), Some(AccountId(42)));
+        assert_eq!(users.verify("alice", "bad"), None);
+        assert_eq!(users.verify("bob", "pw"), None);
+        assert!(FileLoginVerifier::parse("alice 42 0 c2FsdA== c2FsdA==").is_err());
+        assert!(FileLoginVerifier::parse("alice x 1 c2FsdA== c2FsdA==").is_err());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let auth = auth();
+        assert!(SessionAuth::matches("POST", LOGIN_PATH));
+        assert!(SessionAuth::matches("GET", "/api/auth/revocations?since=3"));
+        assert!(!SessionAuth::matches("GET", LOGIN_PATH));
+
+        let login = |password: &str| {
+            let body = format!(r#"{{"username":"alice","password":"{}"}}"#, password);
+            auth.respond(
+                LOGIN_PATH,
+                format!("POST {} HTTP/1.1\r\n\r\n{}", LOGIN_PATH, body).as_bytes(),
+            )
+        };
+        assert!(String::from_utf8(login("bad")).unwrap().starts_with("HTTP/1.1 401"));
+        let response = String::from_utf8(login("pw")).unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let pair: serde_json::Value = serde_json::from_str(body).unwrap();
+        let access = pair["accessToken"].as_str().unwrap();
+
+        let logout =
+            format!("POST {} HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", LOGOUT_PATH, access);
+        let now_ms = auth.now_ms();
+        assert_eq!(auth.authenticate(logout.as_bytes(), now_ms).unwrap().unwrap().sub, 42);
+        assert!(auth.respond(LOGOUT_PATH, logout.as_bytes()).starts_with(b"HTTP/1.1 200"));
+        assert_eq!(auth.authenticate(logout.as_bytes(), now_ms), Err(JwtError::Revoked));
+
+        let feed = auth.respond("/api/auth/revocations?since=0", b"GET / HTTP/1.1\r\n\r\n");
+        let feed = String::from_utf8(feed).unwrap();
+        assert!(feed.contains(r#""last":1"#));
+    }
+}
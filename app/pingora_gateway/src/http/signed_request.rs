@@ -0,0 +1,560 @@
+//! 签名 REST 请求鉴权与防重放
+//!
+//! 携带 `X-Api-Key` 的请求视为签名请求，须同时携带：
+//! - `X-Timestamp`：客户端时间（毫秒）
+//! - `X-Nonce`：客户端随机串，同一 API Key 在时间窗口内不得重复
+//! - `X-Recv-Window`：可选，请求有效期（毫秒），默认 5000，最大 60000
+//! - `X-Signature`：`hex(HMAC-SHA256(api_secret, "<METHOD>\n<路径含查询串>\n<X-Timestamp>\n<X-Nonce>\n<请求体>"))`
+//!
+//! 以网关时间为准：`timestamp` 超前超过允许偏差，或早于 `now - recvWindow` 的请求被拒绝；
+//! 签名通过后记录 nonce，直到该请求的有效期结束，期间同一 nonce 再次出现以 409 拒绝。
+//! 错误响应带 `code` 字段，重放与时间窗口、签名错误可以区分
+//!
+//! 静态登记的 Key 之外，可接入 [`ApiKeyStore`]：GET 请求要求只读权限，其余要求交易权限，
+//! 吊销、过期的 Key 立即拒绝，鉴权成功后记录使用时间
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use base_types::account::api_key::{ApiKeyError, ApiKeyScope, ApiKeyStore};
+use base_types::{AccountId, SystemClock, Timestamp, TimestampProvider};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::codec::{header_value, request_body};
+use super::exchange_info::json_response;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 默认请求有效期（毫秒）
+pub const DEFAULT_RECV_WINDOW_MS: u64 = 5_000;
+/// 请求有效期上限（毫秒）
+pub const MAX_RECV_WINDOW_MS: u64 = 60_000;
+/// 默认允许客户端时间超前网关的偏差（毫秒）
+pub const DEFAULT_MAX_CLOCK_AHEAD_MS: u64 = 1_000;
+/// nonce 最大长度
+pub const MAX_NONCE_LEN: usize = 64;
+
+/// 签名请求鉴权错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignedRequestError {
+    /// 未知的 API Key
+    UnknownApiKey,
+    /// 缺少或无法解析的请求头
+    MissingHeader(&'static str),
+    /// recvWindow 为 0 或超过上限
+    InvalidRecvWindow(u64),
+    /// 时间戳超出有效期
+    OutsideRecvWindow { timestamp: u64, server_time: u64 },
+    /// nonce 为空、过长或含非法字符
+    InvalidNonce,
+    /// 签名不符
+    InvalidSignature,
+    /// 有效期内 nonce 重复
+    Replayed,
+    /// Key 已吊销、过期或权限不足
+    ApiKey(ApiKeyError),
+}
+
+impl SignedRequestError {
+    /// 错误码（与时间窗口、签名错误区分，客户端据此决定是否换 nonce 重试）
+    pub fn code(&self) -> i32 {
+        match self {
+            SignedRequestError::UnknownApiKey | SignedRequestError::ApiKey(_) => -2015,
+            SignedRequestError::MissingHeader(_) => -1102,
+            SignedRequestError::InvalidRecvWindow(_) => -1131,
+            SignedRequestError::OutsideRecvWindow { .. } => -1021,
+            SignedRequestError::InvalidNonce => -1103,
+            SignedRequestError::InvalidSignature => -1022,
+            SignedRequestError::Replayed => -1025,
+        }
+    }
+
+    /// 拒绝时的 HTTP 状态码
+    pub fn status(&self) -> u16 {
+        match self {
+            SignedRequestError::MissingHeader(_)
+            | SignedRequestError::InvalidRecvWindow(_)
+            | SignedRequestError::InvalidNonce => 400,
+            SignedRequestError::Replayed => 409,
+            SignedRequestError::ApiKey(ApiKeyError::InsufficientScope { .. }) => 403,
+            _ => 401,
+        }
+    }
+
+    /// 生成拒绝响应
+    pub fn reject_response(&self) -> Vec<u8> {
+        let body = serde_json::json!({ "code": self.code(), "msg": self.to_string() });
+        json_response(self.status(), &body.to_string())
+    }
+}
+
+impl fmt::Display for SignedRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignedRequestError::UnknownApiKey => write!(f, "Unknown API key"),
+            SignedRequestError::MissingHeader(name) => {
+                write!(f, "Missing or malformed header: {}", name)
+            }
+            SignedRequestError::InvalidRecvWindow(window) => {
+                write!(f, "recvWindow must be in 1..={}, got {}", MAX_RECV_WINDOW_MS, window)
+            }
+            SignedRequestError::OutsideRecvWindow { timestamp, server_time } => write!(
+                f,
+                "Timestamp {} outside recvWindow (server time {})",
+                timestamp, server_time
+            ),
+            SignedRequestError::InvalidNonce => write!(f, "Invalid nonce"),
+            SignedRequestError::InvalidSignature => write!(f, "Invalid signature"),
+            SignedRequestError::Replayed => write!(f, "Replayed request: nonce already used"),
+            SignedRequestError::ApiKey(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SignedRequestError {}
+
+/// 单个 API Key 的 nonce 窗口
+#[derive(Debug, Default)]
+struct NonceWindow {
+    /// nonce -> 过期时间（毫秒）
+    nonces: HashMap<String, u64>,
+    /// (过期时间, nonce)，按过期时间清理
+    expiry: BTreeSet<(u64, String)>,
+}
+
+impl NonceWindow {
+    fn purge(&mut self, now_ms: u64) {
+        while self.expiry.first().is_some_and(|(expires_at, _)| *expires_at < now_ms) {
+            if let Some((_, nonce)) = self.expiry.pop_first() {
+                self.nonces.remove(&nonce);
+            }
+        }
+    }
+}
+
+/// 按 API Key 分组的滑动窗口 nonce 存储
+///
+/// nonce 只需保留到对应请求的有效期结束：之后同一请求会因时间戳过期被拒绝
+#[derive(Debug, Default)]
+pub struct NonceStore {
+    windows: Mutex<HashMap<String, NonceWindow>>,
+}
+
+impl NonceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录 nonce，有效期内已出现过时返回 false
+    pub fn check_and_insert(
+        &self,
+        api_key: &str,
+        nonce: &str,
+        expires_at: u64,
+        now_ms: u64,
+    ) -> bool {
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        let window = windows.entry(api_key.to_string()).or_default();
+        window.purge(now_ms);
+        if window.nonces.contains_key(nonce) {
+            return false;
+        }
+        window.nonces.insert(nonce.to_string(), expires_at);
+        window.expiry.insert((expires_at, nonce.to_string()));
+        true
+    }
+
+    /// 清理所有 API Key 的过期 nonce，返回清理条数
+    pub fn purge(&self, now_ms: u64) -> usize {
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        let before: usize = windows.values().map(|window| window.nonces.len()).sum();
+        windows.retain(|_, window| {
+            window.purge(now_ms);
+            !window.nonces.is_empty()
+        });
+        before - windows.values().map(|window| window.nonces.len()).sum::<usize>()
+    }
+
+    /// 当前记录的 nonce 数
+    pub fn len(&self) -> usize {
+        self.windows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|window| window.nonces.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// API Key 凭证
+#[derive(Debug, Clone)]
+struct ApiCredential {
+    account_id: AccountId,
+    secret: Vec<u8>,
+}
+
+/// 签名 REST 请求鉴权
+pub struct SignedRequestAuth {
+    /// API Key -> 凭证
+    api_keys: HashMap<String, ApiCredential>,
+    /// 允许客户端时间超前网关的偏差（毫秒）
+    max_clock_ahead_ms: u64,
+    nonces: Arc<NonceStore>,
+    /// 持久化的 API Key（静态登记之外）
+    key_store: Option<Arc<RwLock<ApiKeyStore>>>,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl fmt::Debug for SignedRequestAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedRequestAuth")
+            .field("api_keys", &self.api_keys.len())
+            .field("max_clock_ahead_ms", &self.max_clock_ahead_ms)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SignedRequestAuth {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl SignedRequestAuth {
+    pub fn new(clock: Arc<dyn TimestampProvider>) -> Self {
+        Self {
+            api_keys: HashMap::new(),
+            max_clock_ahead_ms: DEFAULT_MAX_CLOCK_AHEAD_MS,
+            nonces: Arc::new(NonceStore::new()),
+            key_store: None,
+            clock,
+        }
+    }
+
+    /// 登记 API Key
+    pub fn with_api_key(
+        mut self,
+        api_key: impl Into<String>,
+        account_id: AccountId,
+        secret: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.api_keys.insert(api_key.into(), ApiCredential { account_id, secret: secret.into() });
+        self
+    }
+
+    pub fn with_max_clock_ahead_ms(mut self, max_clock_ahead_ms: u64) -> Self {
+        self.max_clock_ahead_ms = max_clock_ahead_ms;
+        self
+    }
+
+    /// 同一进程内多个监听服务共享的 nonce 存储
+    pub fn with_nonce_store(mut self, nonces: Arc<NonceStore>) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// 与 API Key 管理接口共享的 Key 存储
+    pub fn with_key_store(mut self, key_store: Arc<RwLock<ApiKeyStore>>) -> Self {
+        self.key_store = Some(key_store);
+        self
+    }
+
+    pub fn nonces(&self) -> &Arc<NonceStore> {
+        &self.nonces
+    }
+
+    /// 校验签名请求，未携带 `X-Api-Key` 时返回 `Ok(None)`
+    pub fn authenticate(
+        &self,
+        method: &str,
+        path: &str,
+        request: &[u8],
+    ) -> Result<Option<AccountId>, SignedRequestError> {
+        let head = std::str::from_utf8(request).unwrap_or_else(|e| {
+            std::str::from_utf8(&request[..e.valid_up_to()]).unwrap_or_default()
+        });
+        let Some(api_key) = header_value(head, "X-Api-Key") else {
+            return Ok(None);
+        };
+        self.verify(api_key, method, path, head, request_body(request), self.now_ms()).map(Some)
+    }
+
+    fn verify(
+        &self,
+        api_key: &str,
+        method: &str,
+        path: &str,
+        head: &str,
+        body: &[u8],
+        now_ms: u64,
+    ) -> Result<AccountId, SignedRequestError> {
+        let (credential, stored) = match self.api_keys.get(api_key) {
+            Some(credential) => (credential.clone(), false),
+            None => (self.stored_credential(api_key, method, now_ms)?, true),
+        };
+        let timestamp = header_value(head, "X-Timestamp")
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or(SignedRequestError::MissingHeader("X-Timestamp"))?;
+        let recv_window = match header_value(head, "X-Recv-Window") {
+            Some(v) => {
+                v.parse::<u64>().map_err(|_| SignedRequestError::MissingHeader("X-Recv-Window"))?
+            }
+            None => DEFAULT_RECV_WINDOW_MS,
+        };
+        if recv_window == 0 || recv_window > MAX_RECV_WINDOW_MS {
+            return Err(SignedRequestError::InvalidRecvWindow(recv_window));
+        }
+        if timestamp > now_ms + self.max_clock_ahead_ms
+            || now_ms.saturating_sub(timestamp) > recv_window
+        {
+            return Err(SignedRequestError::OutsideRecvWindow { timestamp, server_time: now_ms });
+        }
+        let nonce =
+            header_value(head, "X-Nonce").ok_or(SignedRequestError::MissingHeader("X-Nonce"))?;
+        if nonce.is_empty()
+            || nonce.len() > MAX_NONCE_LEN
+            || !nonce.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(SignedRequestError::InvalidNonce);
+        }
+        let signature = header_value(head, "X-Signature")
+            .and_then(hex_decode)
+            .ok_or(SignedRequestError::MissingHeader("X-Signature"))?;
+        let payload = signing_payload(method, path, timestamp, nonce, body);
+        if !hmac_verify(&credential.secret, &payload, &signature) {
+            return Err(SignedRequestError::InvalidSignature);
+        }
+        // 签名通过后才记录 nonce，伪造请求无法占用合法客户端的 nonce
+        if !self.nonces.check_and_insert(api_key, nonce, timestamp + recv_window, now_ms) {
+            return Err(SignedRequestError::Replayed);
+        }
+        if let Some(store) = self.key_store.as_ref().filter(|_| stored) {
+            store
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .record_use(api_key, Timestamp(now_ms * 1_000_000));
+        }
+        Ok(credential.account_id)
+    }
+
+    /// 从 Key 存储取凭证：GET 要求只读权限，其余要求交易权限
+    fn stored_credential(
+        &self,
+        api_key: &str,
+        method: &str,
+        now_ms: u64,
+    ) -> Result<ApiCredential, SignedRequestError> {
+        let store = self.key_store.as_ref().ok_or(SignedRequestError::UnknownApiKey)?;
+        let required = if method == "GET" { ApiKeyScope::Read } else { ApiKeyScope::Trade };
+        let store = store.read().unwrap_or_else(PoisonError::into_inner);
+        match store.check(api_key, required, Timestamp(now_ms * 1_000_000)) {
+            Ok(key) => Ok(ApiCredential {
+                account_id: key.account_id,
+                secret: key.secret.as_bytes().to_vec(),
+            }),
+            Err(ApiKeyError::UnknownKey(_)) => Err(SignedRequestError::UnknownApiKey),
+            Err(e) => Err(SignedRequestError::ApiKey(e)),
+        }
+    }
+
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now().0 / 1_000_000
+    }
+}
+
+/// 待签名内容
+pub fn signing_payload(
+    method: &str,
+    path: &str,
+    timestamp: u64,
+    nonce: &str,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut payload = format!("{}\n{}\n{}\n{}\n", method, path, timestamp, nonce).into_bytes();
+    payload.extend_from_slice(body);
+    payload
+}
+
+fn hmac_verify(key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(mut mac) = HmacSha256::new_from_slice(key) else {
+        return false;
+    };
+    mac.update(message);
+    mac.verify_slice(signature).is_ok()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::account::api_key::{MemApiKeyRepo, NewApiKey};
+
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    struct FixedClock;
+
+    impl TimestampProvider for FixedClock {
+        fn now(&self) -> Timestamp {
+            Timestamp(NOW_MS * 1_000_000)
+        }
+    }
+
+    fn auth() -> SignedRequestAuth {
+        SignedRequestAuth::new(Arc::new(FixedClock)).with_api_key("key-1", AccountId(7), "secret")
+    }
+
+    fn sign(timestamp: u64, nonce: &str, body: &str) -> String {
+        let payload =
+            signing_payload("POST", "/api/spot/v2/order", timestamp, nonce, body.as_bytes());
+        let mut mac = HmacSha256::new_from_slice(b"secret").unwrap();
+        mac.update(&payload);
+        mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn request(timestamp: u64, nonce: &str, extra: &str) -> Vec<u8> {
+        let body = r#"{"qty":"1"}"#;
+        format!(
+            "POST /api/spot/v2/order HTTP/1.1\r\nX-Api-Key: key-1\r\nX-Timestamp: {}\r\n\
+             X-Nonce: {}\r\nX-Signature: {}\r\n{}\r\n{}",
+            timestamp,
+            nonce,
+            sign(timestamp, nonce, body),
+            extra,
+            body
+        )
+        .into_bytes()
+    }
+
+    fn authenticate(
+        auth: &SignedRequestAuth,
+        request: &[u8],
+    ) -> Result<Option<AccountId>, SignedRequestError> {
+        auth.authenticate("POST", "/api/spot/v2/order", request)
+    }
+
+    #[test]
+    fn test_replay_rejected_with_distinct_code() {
+        let auth = auth();
+        assert_eq!(authenticate(&auth, b"GET / HTTP/1.1\r\n\r\n"), Ok(None));
+
+        let signed = request(NOW_MS - 100, "n-1", "");
+        assert_eq!(authenticate(&auth, &signed), Ok(Some(AccountId(7))));
+        let replay = authenticate(&auth, &signed).unwrap_err();
+        assert_eq!(replay, SignedRequestError::Replayed);
+        assert_eq!((replay.status(), replay.code()), (409, -1025));
+        let response = String::from_utf8(replay.reject_response()).unwrap();
+        assert!(response.starts_with("HTTP/1.1 409"));
+        assert!(response.contains(r#""code":-1025"#));
+
+        // 新 nonce 正常通过；nonce 按 API Key 隔离
+        assert!(authenticate(&auth, &request(NOW_MS - 100, "n-2", "")).is_ok());
+        assert!(auth.nonces().check_and_insert("key-2", "n-1", NOW_MS + 5_000, NOW_MS));
+    }
+
+    #[test]
+    fn test_recv_window_against_server_time() {
+        let auth = auth();
+        let stale = authenticate(&auth, &request(NOW_MS - 5_001, "a", "")).unwrap_err();
+        assert_eq!(
+            stale,
+            SignedRequestError::OutsideRecvWindow {
+                timestamp: NOW_MS - 5_001,
+                server_time: NOW_MS
+            }
+        );
+        assert_eq!(stale.code(), -1021);
+        assert!(
+            authenticate(&auth, &request(NOW_MS - 5_001, "a", "X-Recv-Window: 10000\r\n")).is_ok()
+        );
+        assert!(matches!(
+            authenticate(&auth, &request(NOW_MS + 1_001, "b", "")),
+            Err(SignedRequestError::OutsideRecvWindow { .. })
+        ));
+        assert_eq!(
+            authenticate(&auth, &request(NOW_MS, "c", "X-Recv-Window: 60001\r\n")),
+            Err(SignedRequestError::InvalidRecvWindow(60_001))
+        );
+    }
+
+    #[test]
+    fn test_rejections_do_not_consume_nonce() {
+        let auth = auth();
+        let mut forged = request(NOW_MS, "n-1", "");
+        forged.pop();
+        forged.push(b'x');
+        assert_eq!(authenticate(&auth, &forged), Err(SignedRequestError::InvalidSignature));
+        assert!(auth.nonces().is_empty());
+        assert!(authenticate(&auth, &request(NOW_MS, "n-1", "")).is_ok());
+
+        assert_eq!(
+            authenticate(&auth, &request(NOW_MS, "bad nonce", "")),
+            Err(SignedRequestError::InvalidNonce)
+        );
+        let unknown =
+            String::from_utf8(request(NOW_MS, "n-3", "")).unwrap().replace("key-1", "key-9");
+        assert_eq!(authenticate(&auth, unknown.as_bytes()), Err(SignedRequestError::UnknownApiKey));
+    }
+
+    #[test]
+    fn test_stored_key_scope_and_revocation() {
+        let mut store = ApiKeyStore::new(Arc::new(MemApiKeyRepo::new())).unwrap();
+        let now = Timestamp(NOW_MS * 1_000_000);
+        for (api_key, scopes) in [
+            ("key-1", vec![ApiKeyScope::Read, ApiKeyScope::Trade]),
+            ("key-3", vec![ApiKeyScope::Read]),
+        ] {
+            let new = NewApiKey {
+                api_key: api_key.to_string(),
+                secret: "secret".to_string(),
+                account_id: AccountId(8),
+                label: String::new(),
+                scopes: scopes.into_iter().collect(),
+                expires_at: None,
+            };
+            store.create(new, now).unwrap();
+        }
+        let store = Arc::new(RwLock::new(store));
+        let auth = SignedRequestAuth::new(Arc::new(FixedClock)).with_key_store(store.clone());
+
+        assert_eq!(authenticate(&auth, &request(NOW_MS, "n-1", "")), Ok(Some(AccountId(8))));
+        assert_eq!(store.read().unwrap().get("key-1").unwrap().last_used_at, Some(now));
+
+        // 只读 Key 不能下单
+        let read_only =
+            String::from_utf8(request(NOW_MS, "n-2", "")).unwrap().replace("key-1", "key-3");
+        let denied = authenticate(&auth, read_only.as_bytes()).unwrap_err();
+        assert_eq!((denied.status(), denied.code()), (403, -2015));
+
+        // 吊销立即生效
+        store.write().unwrap().revoke("key-1", None, now).unwrap();
+        let revoked = authenticate(&auth, &request(NOW_MS, "n-3", "")).unwrap_err();
+        assert_eq!(revoked, SignedRequestError::ApiKey(ApiKeyError::Revoked("key-1".into())));
+        assert_eq!(revoked.status(), 401);
+    }
+
+    #[test]
+    fn test_nonce_window_slides() {
+        let store = NonceStore::new();
+        assert!(store.check_and_insert("k", "a", 1_000, 0));
+        assert!(store.check_and_insert("k", "b", 3_000, 0));
+        assert!(!store.check_and_insert("k", "a", 2_000, 500));
+        // a 过期后被清理，可以再次使用
+        assert!(store.check_and_insert("k", "a", 4_000, 1_500));
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.purge(5_000), 2);
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, RwLock};
+
+use base_types::TradingPair;
+use base_types::mark_data::spot::level_types::{SymbolId, TradeEvent};
+use base_types::mark_data::spot::recent_trades::{
+    MemTradeHistory, PublicTrade, RecentTrades, TradeHistoryRepo, clamp_trades_limit,
+    historical_trades,
+};
+
+use super::exchange_info::{json_response, query_param};
+
+/// 最近成交接口路径
+pub const TRADES_PATH: &str = "/api/spot/trades";
+/// 历史成交接口路径
+pub const HISTORICAL_TRADES_PATH: &str = "/api/spot/historicalTrades";
+
+/// 成交仓储（跨连接共享）
+pub type SharedTradeHistory = Arc<dyn TradeHistoryRepo + Send + Sync>;
+
+/// `GET /api/spot/trades` 与 `GET /api/spot/historicalTrades` 处理器
+///
+/// 最近成交读行情管道维护的内存环形缓冲，不查数据库；
+/// 历史成交按 `fromId` 翻页，起始ID已淘汰出环时回落到成交仓储
+pub struct TradesHandler {
+    recent: Arc<RwLock<RecentTrades>>,
+    history: SharedTradeHistory,
+}
+
+impl Default for TradesHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(RwLock::new(RecentTrades::default())), Arc::new(MemTradeHistory::new()))
+    }
+}
+
+impl TradesHandler {
+    pub fn new(recent: Arc<RwLock<RecentTrades>>, history: SharedTradeHistory) -> Self {
+        Self { recent, history }
+    }
+
+    /// 行情管道写入成交的入口
+    pub fn recent_trades(&self) -> Arc<RwLock<RecentTrades>> {
+        self.recent.clone()
+    }
+
+    /// 请求是否由本处理器应答（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        let route = path.split('?').next();
+        method == "GET" && (route == Some(TRADES_PATH) || route == Some(HISTORICAL_TRADES_PATH))
+    }
+
+    /// 生成完整的 HTTP 响应
+    pub fn respond(&self, path: &str) -> Vec<u8> {
+        let (status, body) = self.render(path);
+        json_response(status, &body)
+    }
+
+    /// 返回 (状态码, JSON 响应体)
+    ///
+    /// 参数：`symbol`（必填）、`limit`（默认 500，最大 1000）、`fromId`（仅历史成交）
+    fn render(&self, path: &str) -> (u16, String) {
+        let Some(symbol) = query_param(path, "symbol") else {
+            return Self::bad_request("Missing parameter: symbol".to_string());
+        };
+        let Some(pair) = TradingPair::from_symbol_str(symbol) else {
+            return Self::bad_request(format!("Invalid symbol: {}", symbol));
+        };
+        let symbol_id = pair as SymbolId;
+        let limit = match query_param(path, "limit").map(str::parse::<usize>) {
+            None => clamp_trades_limit(None),
+            Some(Ok(limit)) => clamp_trades_limit(Some(limit)),
+            Some(Err(_)) => return Self::bad_request("Invalid parameter: limit".to_string()),
+        };
+
+        let Ok(recent) = self.recent.read() else {
+            return (500, serde_json::json!({ "msg": "Recent trades unavailable" }).to_string());
+        };
+        let body = if path.starts_with(HISTORICAL_TRADES_PATH) {
+            let from_id = match query_param(path, "fromId").map(str::parse::<u64>) {
+                None => None,
+                Some(Ok(from_id)) => Some(from_id),
+                Some(Err(_)) => return Self::bad_request("Invalid parameter: fromId".to_string()),
+            };
+            match historical_trades(&recent, self.history.as_ref(), symbol_id, from_id, limit) {
+                Ok(page) => serde_json::to_string(&page),
+                Err(e) => return (500, serde_json::json!({ "msg": e.to_string() }).to_string()),
+            }
+        } else {
+            let trades: Vec<PublicTrade> =
+                recent.recent(symbol_id, limit).iter().map(PublicTrade::from).collect();
+            serde_json::to_string(&trades)
+        };
+        match body {
+            Ok(body) => (200, body),
+            Err(e) => (500, serde_json::json!({ "msg": e.to_string() }).to_string()),
+        }
+    }
+
+    fn bad_request(msg: String) -> (u16, String) {
+        (400, serde_json::json!({ "msg": msg }).to_string())
+    }
+}
+
+/// 成交事件写入最近成交环
+pub fn record_trade(recent: &RwLock<RecentTrades>, trade: &TradeEvent) {
+    if let Ok(mut recent) = recent.write() {
+        recent.on_trade(trade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::{OrderSide, Price, Quantity};
+
+    use super::*;
+
+    fn trade(trade_id: u64) -> TradeEvent {
+        TradeEvent {
+            symbol_id: TradingPair::BtcUsdt as SymbolId,
+            timestamp: trade_id * 1_000_000,
+            sequence: trade_id,
+            trade_id,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(100.0),
+            quantity: Quantity::from_f64(1.0),
+            aggressor_side: OrderSide::Sell,
+        }
+    }
+
+    #[test]
+    fn test_recent_and_historical_trades() {
+        let handler = TradesHandler::default();
+        let recent = handler.recent_trades();
+        for id in 1..=3 {
+            record_trade(&recent, &trade(id));
+        }
+        assert!(TradesHandler::matches("GET", "/api/spot/trades?symbol=BTCUSDT"));
+        assert!(!TradesHandler::matches("POST", "/api/spot/trades"));
+
+        let (status, body) = handler.render("/api/spot/trades?symbol=BTCUSDT&limit=2");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1]["id"], 3);
+        assert_eq!(json[1]["isBuyerMaker"], true);
+
+        let (status, body) =
+            handler.render("/api/spot/historicalTrades?symbol=BTCUSDT&fromId=1&limit=2");
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["nextFromId"], 3);
+
+        assert_eq!(handler.render("/api/spot/trades").0, 400);
+        assert_eq!(handler.render("/api/spot/trades?symbol=DOGEUSDT").0, 400);
+        assert_eq!(handler.render("/api/spot/trades?symbol=BTCUSDT&limit=x").0, 400);
+    }
+}
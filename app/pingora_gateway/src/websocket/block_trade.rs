@@ -0,0 +1,52 @@
+//! 大宗交易推送
+//!
+//! 已入账的大宗交易推送到独立的 `<symbol>@blockTrade` 流，不混入逐笔成交流，
+//! 消息不包含交易双方账户
+
+use base_types::exchange::spot::block_trade::BookedBlockTrade;
+use tokio::sync::broadcast;
+
+use super::book_ticker::StreamMessage;
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 1024;
+
+/// 大宗交易 WebSocket 流
+pub struct BlockTradeStream {
+    sender: broadcast::Sender<StreamMessage>,
+}
+
+impl Default for BlockTradeStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl BlockTradeStream {
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 流名称（如 `btcusdt@blockTrade`）
+    pub fn stream_name(symbol: &str) -> String {
+        format!("{}@blockTrade", symbol.to_lowercase())
+    }
+
+    /// 广播一笔已入账的大宗交易，返回收到的订阅者数
+    pub fn publish(&self, trade: &BookedBlockTrade) -> usize {
+        let symbol = trade.report.trading_pair.to_symbol_string();
+        let stream = Self::stream_name(symbol);
+        let data = serde_json::json!({
+            "e": "blockTrade",
+            "E": trade.booked_at.0 / 1_000_000,
+            "s": symbol,
+            "t": trade.trade_id,
+            "p": trade.report.price.to_string(),
+            "q": trade.report.quantity.to_string(),
+            "T": trade.report.reported_at.0 / 1_000_000,
+        });
+        let payload = serde_json::json!({ "stream": stream, "data": data }).to_string();
+        self.sender.send(StreamMessage { stream, payload }).unwrap_or(0)
+    }
+}
@@ -0,0 +1,86 @@
+use base_types::TradingPair;
+use base_types::mark_data::spot::ticker::BookTicker;
+use tokio::sync::broadcast;
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 1024;
+
+/// bookTicker 推送消息
+#[derive(Debug, Clone)]
+pub struct StreamMessage {
+    /// 流名称（如 `btcusdt@bookTicker`）
+    pub stream: String,
+    /// JSON 消息体：`{"stream": ..., "data": {...}}`
+    pub payload: String,
+}
+
+/// bookTicker WebSocket 流
+///
+/// 最优挂单变化时广播；连接按订阅的流名称过滤
+pub struct BookTickerStream {
+    sender: broadcast::Sender<StreamMessage>,
+}
+
+impl Default for BookTickerStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl BookTickerStream {
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 广播一次最优挂单变化，返回收到的订阅者数
+    pub fn publish(&self, ticker: &BookTicker) -> usize {
+        let Some(symbol) = symbol_of(ticker.symbol_id) else {
+            return 0;
+        };
+        let stream = BookTicker::stream_name(symbol);
+        let mut data = match serde_json::to_value(ticker) {
+            Ok(data) => data,
+            Err(_) => return 0,
+        };
+        data["symbol"] = symbol.into();
+        let payload = serde_json::json!({ "stream": stream, "data": data }).to_string();
+        self.sender.send(StreamMessage { stream, payload }).unwrap_or(0)
+    }
+}
+
+/// 交易对ID -> 名称
+pub fn symbol_of(symbol_id: u32) -> Option<&'static str> {
+    TradingPair::all()
+        .iter()
+        .find(|pair| **pair as u32 == symbol_id)
+        .map(|pair| pair.to_symbol_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::{Price, Quantity};
+
+    use super::*;
+
+    #[test]
+    fn test_publish_book_ticker() {
+        let stream = BookTickerStream::default();
+        let mut receiver = stream.subscribe();
+        let ticker = BookTicker {
+            symbol_id: TradingPair::BtcUsdt as u32,
+            update_id: 7,
+            bid_price: Some(Price::from_f64(99.0)),
+            bid_qty: Quantity::from_f64(1.0),
+            ask_price: Some(Price::from_f64(101.0)),
+            ask_qty: Quantity::from_f64(2.0),
+        };
+        assert_eq!(stream.publish(&ticker), 1);
+
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "btcusdt@bookTicker");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["symbol"], "BTCUSDT");
+        assert_eq!(json["data"]["updateId"], 7);
+    }
+}
@@ -0,0 +1,525 @@
+//! WebSocket 握手鉴权
+//!
+//! 在 HTTP Upgrade 阶段完成鉴权，失败时直接以 401/403 拒绝，不建立连接：
+//! - 浏览器客户端带 `Origin` 头，须在白名单内；不带 `Origin` 的程序化客户端不做该检查
+//! - 签名令牌：查询参数 `token` 或 `Authorization: Bearer <token>`，
+//!   格式 `<账户ID>.<过期时间ms>.<hex(HMAC-SHA256(secret, "<账户ID>.<过期时间ms>"))>`
+//! - API Key：`X-Api-Key`、`X-Timestamp`、`X-Signature` 三个头，签名为
+//!   `hex(HMAC-SHA256(api_secret, "<Sec-WebSocket-Key>:<X-Timestamp>"))`，
+//!   以客户端每次随机生成的 `Sec-WebSocket-Key` 作为挑战，时间戳须在允许偏差内；
+//!   静态登记之外可接入 [`ApiKeyStore`]，要求只读权限，吊销、过期的 Key 被拒绝
+//! - 浏览器会话 JWT：同样经 `token` 或 `Authorization: Bearer` 携带，由 [`SessionAuth`] 校验，
+//!   已吊销会话的令牌被拒绝
+//!
+//! 未携带凭证的连接为匿名会话，只能订阅公开流
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use base_types::account::api_key::{ApiKeyError, ApiKeyScope, ApiKeyStore};
+use base_types::{AccountId, Timestamp};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::http::exchange_info::json_response;
+use crate::http::session_auth::{JwtError, SessionAuth, is_jwt};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 默认允许的 API Key 时间戳偏差（毫秒）
+pub const DEFAULT_MAX_CLOCK_SKEW_MS: u64 = 5_000;
+
+/// 私有流前缀：`account@<账户ID>` 只推送给该账户的会话
+pub const ACCOUNT_STREAM_PREFIX: &str = "account@";
+
+/// 握手鉴权错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// 不是合法的 WebSocket Upgrade 请求
+    NotUpgrade,
+    /// Origin 不在白名单内
+    OriginNotAllowed(String),
+    /// 令牌格式错误或签名不符
+    InvalidToken,
+    /// 令牌已过期
+    TokenExpired,
+    /// 令牌所属会话已吊销
+    TokenRevoked,
+    /// 未知的 API Key
+    UnknownApiKey,
+    /// API Key 已吊销、过期或缺少只读权限
+    ApiKeyUnusable,
+    /// API Key 签名不符
+    InvalidSignature,
+    /// API Key 时间戳超出允许偏差
+    StaleTimestamp,
+}
+
+impl HandshakeError {
+    /// 拒绝时的 HTTP 状态码
+    pub fn status(&self) -> u16 {
+        match self {
+            HandshakeError::NotUpgrade => 400,
+            HandshakeError::OriginNotAllowed(_) => 403,
+            _ => 401,
+        }
+    }
+
+    /// 生成拒绝升级的 HTTP 响应
+    pub fn reject_response(&self) -> Vec<u8> {
+        json_response(self.status(), &serde_json::json!({ "msg": self.to_string() }).to_string())
+    }
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeError::NotUpgrade => write!(f, "Not a WebSocket upgrade request"),
+            HandshakeError::OriginNotAllowed(origin) => write!(f, "Origin not allowed: {}", origin),
+            HandshakeError::InvalidToken => write!(f, "Invalid token"),
+            HandshakeError::TokenExpired => write!(f, "Token expired"),
+            HandshakeError::TokenRevoked => write!(f, "Session revoked"),
+            HandshakeError::UnknownApiKey => write!(f, "Unknown API key"),
+            HandshakeError::ApiKeyUnusable => {
+                write!(f, "API key revoked, expired or missing read permission")
+            }
+            HandshakeError::InvalidSignature => write!(f, "Invalid signature"),
+            HandshakeError::StaleTimestamp => write!(f, "Timestamp outside recv window"),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+/// 解析后的 Upgrade 请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeRequest {
+    /// 请求路径（含查询串）
+    pub path: String,
+    /// 请求头（名称小写）
+    headers: HashMap<String, String>,
+}
+
+impl UpgradeRequest {
+    /// 解析 HTTP 请求头，只接受 `GET` + `Upgrade: websocket` + `Sec-WebSocket-Key`
+    pub fn parse(request: &[u8]) -> Result<Self, HandshakeError> {
+        let text = std::str::from_utf8(request).map_err(|_| HandshakeError::NotUpgrade)?;
+        let head = text.split("\r\n\r\n").next().unwrap_or_default();
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let (Some("GET"), Some(path)) = (request_line.next(), request_line.next()) else {
+            return Err(HandshakeError::NotUpgrade);
+        };
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        let request = Self { path: path.to_string(), headers };
+        let upgrade =
+            request.header("upgrade").is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+        if !upgrade || request.header("sec-websocket-key").is_none() {
+            return Err(HandshakeError::NotUpgrade);
+        }
+        Ok(request)
+    }
+
+    /// 读取请求头（名称不区分大小写）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    /// 读取查询参数（空值视为缺失）
+    pub fn query(&self, key: &str) -> Option<&str> {
+        let (_, query) = self.path.split_once('?')?;
+        query.split('&').find_map(|param| match param.split_once('=') {
+            Some((k, value)) if k == key && !value.is_empty() => Some(value),
+            _ => None,
+        })
+    }
+}
+
+/// 鉴权方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthMethod {
+    /// 未携带凭证
+    Anonymous,
+    /// 签名令牌
+    Token,
+    /// 浏览器会话 JWT
+    Jwt,
+    /// API Key 签名（记录使用的 Key）
+    ApiKey(String),
+}
+
+/// 会话身份（握手成功后挂在连接上，供下游按账户过滤推送）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionIdentity {
+    /// 已鉴权的账户（匿名会话为 None）
+    pub account_id: Option<AccountId>,
+    pub method: AuthMethod,
+    /// 浏览器客户端的 Origin
+    pub origin: Option<String>,
+}
+
+impl SessionIdentity {
+    /// 会话是否可以接收该流：公开流对所有会话开放，`account@<ID>` 只对本账户开放
+    pub fn can_receive(&self, stream: &str) -> bool {
+        match stream.strip_prefix(ACCOUNT_STREAM_PREFIX) {
+            None => true,
+            Some(account) => {
+                self.account_id.is_some_and(|AccountId(id)| account.parse::<u64>() == Ok(id))
+            }
+        }
+    }
+}
+
+/// API Key 凭证
+#[derive(Debug, Clone)]
+struct ApiCredential {
+    account_id: AccountId,
+    secret: Vec<u8>,
+}
+
+/// 握手鉴权配置
+#[derive(Debug, Clone)]
+pub struct HandshakeAuth {
+    /// 令牌签名密钥
+    token_secret: Vec<u8>,
+    /// API Key -> 凭证
+    api_keys: HashMap<String, ApiCredential>,
+    /// Origin 白名单（含 `*` 表示任意）
+    allowed_origins: Vec<String>,
+    /// 是否允许匿名会话
+    allow_anonymous: bool,
+    /// API Key 时间戳允许偏差（毫秒）
+    max_clock_skew_ms: u64,
+    /// 浏览器会话鉴权（未配置时不接受 JWT）
+    sessions: Option<Arc<SessionAuth>>,
+    /// 持久化的 API Key（静态登记之外，与签名 REST 请求共用）
+    key_store: Option<Arc<RwLock<ApiKeyStore>>>,
+}
+
+impl HandshakeAuth {
+    pub fn new(token_secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            token_secret: token_secret.into(),
+            api_keys: HashMap::new(),
+            allowed_origins: Vec::new(),
+            allow_anonymous: true,
+            max_clock_skew_ms: DEFAULT_MAX_CLOCK_SKEW_MS,
+            sessions: None,
+            key_store: None,
+        }
+    }
+
+    /// 登记 API Key
+    pub fn with_api_key(
+        mut self,
+        api_key: impl Into<String>,
+        account_id: AccountId,
+        secret: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.api_keys.insert(api_key.into(), ApiCredential { account_id, secret: secret.into() });
+        self
+    }
+
+    /// 允许的浏览器 Origin（如 `https://app.example.com`，`*` 表示任意）
+    pub fn with_allowed_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+
+    /// 是否允许未携带凭证的匿名会话
+    pub fn with_anonymous(mut self, allow: bool) -> Self {
+        self.allow_anonymous = allow;
+        self
+    }
+
+    pub fn with_max_clock_skew_ms(mut self, max_clock_skew_ms: u64) -> Self {
+        self.max_clock_skew_ms = max_clock_skew_ms;
+        self
+    }
+
+    /// 接受浏览器会话 JWT（与 HTTP 接口共用同一会话服务与吊销列表）
+    pub fn with_sessions(mut self, sessions: Arc<SessionAuth>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// 接受 Key 存储中的 API Key
+    pub fn with_key_store(mut self, key_store: Arc<RwLock<ApiKeyStore>>) -> Self {
+        self.key_store = Some(key_store);
+        self
+    }
+
+    /// 签发令牌
+    pub fn sign_token(&self, account_id: AccountId, expires_at_ms: u64) -> String {
+        let claims = format!("{}.{}", account_id.0, expires_at_ms);
+        let signature = hex_encode(&hmac_sha256(&self.token_secret, claims.as_bytes()));
+        format!("{}.{}", claims, signature)
+    }
+
+    /// 校验 Upgrade 请求，返回会话身份
+    pub fn authenticate(
+        &self,
+        request: &UpgradeRequest,
+        now_ms: u64,
+    ) -> Result<SessionIdentity, HandshakeError> {
+        let origin = request.header("origin").map(str::to_string);
+        if let Some(origin) = origin.as_deref().filter(|origin| !self.origin_allowed(origin)) {
+            return Err(HandshakeError::OriginNotAllowed(origin.to_string()));
+        }
+
+        let bearer = request.header("authorization").and_then(|v| v.strip_prefix("Bearer "));
+        let (account_id, method) = if let Some(token) = request.query("token").or(bearer) {
+            match self.sessions.as_ref().filter(|_| is_jwt(token)) {
+                Some(sessions) => {
+                    let claims = sessions.verify(token, now_ms).map_err(|e| match e {
+                        JwtError::Expired => HandshakeError::TokenExpired,
+                        JwtError::Revoked => HandshakeError::TokenRevoked,
+                        _ => HandshakeError::InvalidToken,
+                    })?;
+                    (Some(claims.account_id()), AuthMethod::Jwt)
+                }
+                None => (Some(self.verify_token(token, now_ms)?), AuthMethod::Token),
+            }
+        } else if let Some(api_key) = request.header("x-api-key") {
+            (
+                Some(self.verify_api_key(request, api_key, now_ms)?),
+                AuthMethod::ApiKey(api_key.into()),
+            )
+        } else if self.allow_anonymous {
+            (None, AuthMethod::Anonymous)
+        } else {
+            return Err(HandshakeError::InvalidToken);
+        };
+        Ok(SessionIdentity { account_id, method, origin })
+    }
+
+    fn origin_allowed(&self, origin: &str) -> bool {
+        self.allowed_origins.iter().any(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    fn verify_token(&self, token: &str, now_ms: u64) -> Result<AccountId, HandshakeError> {
+        let (claims, signature) = token.rsplit_once('.').ok_or(HandshakeError::InvalidToken)?;
+        let signature = hex_decode(signature).ok_or(HandshakeError::InvalidToken)?;
+        if !hmac_verify(&self.token_secret, claims.as_bytes(), &signature) {
+            return Err(HandshakeError::InvalidToken);
+        }
+        let (account, expires_at) = claims.split_once('.').ok_or(HandshakeError::InvalidToken)?;
+        let account = account.parse::<u64>().map_err(|_| HandshakeError::InvalidToken)?;
+        let expires_at = expires_at.parse::<u64>().map_err(|_| HandshakeError::InvalidToken)?;
+        if expires_at <= now_ms {
+            return Err(HandshakeError::TokenExpired);
+        }
+        Ok(AccountId(account))
+    }
+
+    fn verify_api_key(
+        &self,
+        request: &UpgradeRequest,
+        api_key: &str,
+        now_ms: u64,
+    ) -> Result<AccountId, HandshakeError> {
+        let credential = match self.api_keys.get(api_key) {
+            Some(credential) => credential.clone(),
+            None => self.stored_credential(api_key, now_ms)?,
+        };
+        let timestamp = request
+            .header("x-timestamp")
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or(HandshakeError::StaleTimestamp)?;
+        if timestamp.abs_diff(now_ms) > self.max_clock_skew_ms {
+            return Err(HandshakeError::StaleTimestamp);
+        }
+        let signature = request
+            .header("x-signature")
+            .and_then(hex_decode)
+            .ok_or(HandshakeError::InvalidSignature)?;
+        let challenge =
+            format!("{}:{}", request.header("sec-websocket-key").unwrap_or_default(), timestamp);
+        if !hmac_verify(&credential.secret, challenge.as_bytes(), &signature) {
+            return Err(HandshakeError::InvalidSignature);
+        }
+        Ok(credential.account_id)
+    }
+
+    /// 从 Key 存储取凭证（订阅推送只要求只读权限）
+    fn stored_credential(
+        &self,
+        api_key: &str,
+        now_ms: u64,
+    ) -> Result<ApiCredential, HandshakeError> {
+        let store = self.key_store.as_ref().ok_or(HandshakeError::UnknownApiKey)?;
+        let store = store.read().unwrap_or_else(PoisonError::into_inner);
+        match store.check(api_key, ApiKeyScope::Read, Timestamp(now_ms * 1_000_000)) {
+            Ok(key) => Ok(ApiCredential {
+                account_id: key.account_id,
+                secret: key.secret.as_bytes().to_vec(),
+            }),
+            Err(ApiKeyError::UnknownKey(_)) => Err(HandshakeError::UnknownApiKey),
+            Err(_) => Err(HandshakeError::ApiKeyUnusable),
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 常数时间比较签名
+fn hmac_verify(key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.verify_slice(signature).is_ok()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::SystemClock;
+    use base_types::account::api_key::{MemApiKeyRepo, NewApiKey};
+
+    use super::*;
+
+    const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+
+    fn upgrade(extra_headers: &str, path: &str) -> UpgradeRequest {
+        let raw = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\n{}\r\n",
+            path, KEY, extra_headers
+        );
+        UpgradeRequest::parse(raw.as_bytes()).unwrap()
+    }
+
+    fn auth() -> HandshakeAuth {
+        HandshakeAuth::new("token-secret")
+            .with_api_key("key-1", AccountId(42), "api-secret")
+            .with_allowed_origin("https://app.example.com")
+    }
+
+    #[test]
+    fn test_token_and_origin() {
+        let auth = auth();
+        let token = auth.sign_token(AccountId(7), 2_000);
+
+        let request =
+            upgrade("Origin: https://app.example.com\r\n", &format!("/ws?token={}", token));
+        let identity = auth.authenticate(&request, 1_000).unwrap();
+        assert_eq!(identity.account_id, Some(AccountId(7)));
+        assert_eq!(identity.method, AuthMethod::Token);
+        assert!(identity.can_receive("account@7"));
+        assert!(!identity.can_receive("account@8"));
+        assert!(identity.can_receive("btcusdt@bookTicker"));
+
+        let bearer = upgrade(&format!("Authorization: Bearer {}\r\n", token), "/ws");
+        assert_eq!(auth.authenticate(&bearer, 3_000), Err(HandshakeError::TokenExpired));
+        let forged = token.replace("7.2000", "8.2000");
+        let request = upgrade("", &format!("/ws?token={}", forged));
+        assert_eq!(auth.authenticate(&request, 1_000), Err(HandshakeError::InvalidToken));
+
+        let evil = upgrade("Origin: https://evil.example.com\r\n", "/ws");
+        let err = auth.authenticate(&evil, 1_000).unwrap_err();
+        assert_eq!(err.status(), 403);
+        assert!(err.reject_response().starts_with(b"HTTP/1.1 403 Forbidden"));
+
+        let anonymous = auth.authenticate(&upgrade("", "/ws"), 1_000).unwrap();
+        assert!(!anonymous.can_receive("account@7"));
+        let strict = HandshakeAuth::new("token-secret").with_anonymous(false);
+        assert_eq!(
+            strict.authenticate(&upgrade("", "/ws"), 1_000),
+            Err(HandshakeError::InvalidToken)
+        );
+        assert_eq!(
+            UpgradeRequest::parse(b"GET /ws HTTP/1.1\r\n\r\n"),
+            Err(HandshakeError::NotUpgrade)
+        );
+    }
+
+    #[test]
+    fn test_session_jwt() {
+        let sessions = Arc::new(SessionAuth::new("gw", "jwt-secret", Arc::new(SystemClock)));
+        let auth = auth().with_sessions(sessions.clone());
+        let now_ms = 1_700_000_000_000;
+        let pair = sessions.login_session(AccountId(9), now_ms);
+
+        let request = upgrade(&format!("Authorization: Bearer {}\r\n", pair.access_token), "/ws");
+        let identity = auth.authenticate(&request, now_ms).unwrap();
+        assert_eq!((identity.account_id, identity.method), (Some(AccountId(9)), AuthMethod::Jwt));
+
+        let sid = sessions.verify(&pair.access_token, now_ms).unwrap().sid.unwrap();
+        sessions.revoke_session(&sid, now_ms);
+        assert_eq!(auth.authenticate(&request, now_ms), Err(HandshakeError::TokenRevoked));
+        // 原有签名令牌不受影响
+        let token = auth.sign_token(AccountId(7), 2_000);
+        assert!(auth.authenticate(&upgrade("", &format!("/ws?token={}", token)), 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_api_key_challenge() {
+        let auth = auth();
+        let signature = hex_encode(&hmac_sha256(b"api-secret", format!("{}:1000", KEY).as_bytes()));
+        let headers =
+            format!("X-Api-Key: key-1\r\nX-Timestamp: 1000\r\nX-Signature: {}\r\n", signature);
+        let identity = auth.authenticate(&upgrade(&headers, "/ws"), 1_500).unwrap();
+        assert_eq!(identity.account_id, Some(AccountId(42)));
+        assert_eq!(identity.method, AuthMethod::ApiKey("key-1".to_string()));
+
+        assert_eq!(
+            auth.authenticate(&upgrade(&headers, "/ws"), 10_000),
+            Err(HandshakeError::StaleTimestamp)
+        );
+        let wrong = headers.replace("X-Timestamp: 1000", "X-Timestamp: 1001");
+        assert_eq!(
+            auth.authenticate(&upgrade(&wrong, "/ws"), 1_500),
+            Err(HandshakeError::InvalidSignature)
+        );
+        let unknown = headers.replace("key-1", "key-2");
+        assert_eq!(
+            auth.authenticate(&upgrade(&unknown, "/ws"), 1_500),
+            Err(HandshakeError::UnknownApiKey)
+        );
+    }
+
+    #[test]
+    fn test_stored_api_key() {
+        let mut store = ApiKeyStore::new(Arc::new(MemApiKeyRepo::new())).unwrap();
+        let new = NewApiKey {
+            api_key: "key-3".to_string(),
+            secret: "stored-secret".to_string(),
+            account_id: AccountId(8),
+            label: String::new(),
+            scopes: [ApiKeyScope::Read].into_iter().collect(),
+            expires_at: None,
+        };
+        store.create(new, Timestamp(0)).unwrap();
+        let store = Arc::new(RwLock::new(store));
+        let auth = auth().with_key_store(store.clone());
+
+        let signature =
+            hex_encode(&hmac_sha256(b"stored-secret", format!("{}:1000", KEY).as_bytes()));
+        let headers =
+            format!("X-Api-Key: key-3\r\nX-Timestamp: 1000\r\nX-Signature: {}\r\n", signature);
+        let identity = auth.authenticate(&upgrade(&headers, "/ws"), 1_000).unwrap();
+        assert_eq!(identity.account_id, Some(AccountId(8)));
+
+        store.write().unwrap().revoke("key-3", None, Timestamp(0)).unwrap();
+        assert_eq!(
+            auth.authenticate(&upgrade(&headers, "/ws"), 1_000),
+            Err(HandshakeError::ApiKeyUnusable)
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! 订单簿热力图推送
+//!
+//! 行情组播的逐笔委托通道（见 [`MarketFeed`](crate::http::market_feed::MarketFeed)）逐条写入
+//! 各交易对的 [`HeatmapAggregator`]，跨越采样边界时推送到 `<symbol>@heatmap` 流。
+//! 无增量时由 [`HeatmapStream::spawn_poll`] 的定时器推进采样，保证节奏稳定
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base_types::mark_data::spot::heatmap::{HeatmapAggregator, HeatmapConfig, HeatmapFrame};
+use base_types::mark_data::spot::level_types::{MarketDataDelta, OrderDelta, PriceLevel, SymbolId};
+use base_types::{SystemClock, TimestampProvider};
+use tokio::sync::broadcast;
+
+use super::book_ticker::{StreamMessage, symbol_of};
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 1024;
+/// 默认采样间隔
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+/// 默认每侧档位数
+pub const DEFAULT_MAX_LEVELS: usize = 20;
+
+/// 热力图 WebSocket 流
+pub struct HeatmapStream {
+    sender: broadcast::Sender<StreamMessage>,
+    /// 交易对ID -> 聚合器（收到该交易对第一条增量时创建）
+    aggregators: Mutex<HashMap<SymbolId, HeatmapAggregator>>,
+    interval: Duration,
+    max_levels: usize,
+    clock: Arc<dyn TimestampProvider>,
+}
+
+impl Default for HeatmapStream {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl HeatmapStream {
+    pub fn new(clock: Arc<dyn TimestampProvider>) -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self {
+            sender,
+            aggregators: Mutex::new(HashMap::new()),
+            interval: DEFAULT_INTERVAL,
+            max_levels: DEFAULT_MAX_LEVELS,
+            clock,
+        }
+    }
+
+    /// 采样间隔（只影响之后创建的聚合器）
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 每侧推送的最大档位数（只影响之后创建的聚合器）
+    pub fn with_max_levels(mut self, max_levels: usize) -> Self {
+        self.max_levels = max_levels;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 流名称（如 `btcusdt@heatmap`）
+    pub fn stream_name(symbol: &str) -> String {
+        format!("{}@heatmap", symbol.to_lowercase())
+    }
+
+    /// 处理一条订单簿增量，跨越采样边界时推送上一个桶，返回收到的订阅者数
+    ///
+    /// 未上线的交易对忽略
+    pub fn on_order_change(&self, delta: &OrderDelta) -> usize {
+        if symbol_of(delta.symbol_id).is_none() {
+            return 0;
+        }
+        let frame = {
+            let mut aggregators = self.aggregators.lock().unwrap_or_else(PoisonError::into_inner);
+            aggregators
+                .entry(delta.symbol_id)
+                .or_insert_with(|| {
+                    let interval_ns = self.interval.as_nanos() as u64;
+                    HeatmapAggregator::new(HeatmapConfig::new(
+                        delta.symbol_id,
+                        interval_ns,
+                        self.max_levels,
+                    ))
+                })
+                .on_delta(&MarketDataDelta::OrderChange(*delta))
+        };
+        frame.map_or(0, |frame| self.publish(&frame))
+    }
+
+    /// 按当前时间推进全部交易对的采样，返回推送的帧数
+    pub fn poll(&self) -> usize {
+        let now = self.clock.now().0;
+        let frames: Vec<HeatmapFrame> = self
+            .aggregators
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values_mut()
+            .filter_map(|aggregator| aggregator.poll(now))
+            .collect();
+        for frame in &frames {
+            self.publish(frame);
+        }
+        frames.len()
+    }
+
+    /// 在独立线程中按采样间隔推进
+    pub fn spawn_poll(self: &Arc<Self>) -> io::Result<JoinHandle<()>> {
+        let stream = self.clone();
+        std::thread::Builder::new().name("heatmap-poll".to_string()).spawn(move || {
+            loop {
+                std::thread::sleep(stream.interval);
+                stream.poll();
+            }
+        })
+    }
+
+    /// 广播一个采样帧，返回收到的订阅者数
+    fn publish(&self, frame: &HeatmapFrame) -> usize {
+        let Some(symbol) = symbol_of(frame.symbol_id) else {
+            return 0;
+        };
+        let stream = Self::stream_name(symbol);
+        let levels = |levels: &[PriceLevel]| -> Vec<serde_json::Value> {
+            levels
+                .iter()
+                .map(|level| {
+                    serde_json::json!([level.price.to_string(), level.quantity.to_string()])
+                })
+                .collect()
+        };
+        let data = serde_json::json!({
+            "e": "heatmap",
+            "E": frame.bucket_start / 1_000_000,
+            "s": symbol,
+            "u": frame.sequence,
+            "b": levels(&frame.bids),
+            "a": levels(&frame.asks),
+        });
+        let payload = serde_json::json!({ "stream": stream, "data": data }).to_string();
+        self.sender.send(StreamMessage { stream, payload }).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::ManualClock;
+    use base_types::mark_data::spot::level_types::OrderChangeType;
+    use base_types::{OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+    use super::*;
+
+    fn add(order_id: u64, side: OrderSide, price: f64, timestamp: u64) -> OrderDelta {
+        OrderDelta {
+            symbol_id: TradingPair::BtcUsdt as SymbolId,
+            timestamp,
+            sequence: order_id,
+            change_type: OrderChangeType::Add,
+            order_id,
+            side,
+            price: Price::from_f64(price),
+            quantity: Quantity::from_f64(1.0),
+            trader_id: None,
+        }
+    }
+
+    #[test]
+    fn test_order_changes_and_timer_publish_frames() {
+        let clock = ManualClock::from_millis(0);
+        let stream = HeatmapStream::new(Arc::new(clock.clone()));
+        let mut receiver = stream.subscribe();
+
+        assert_eq!(stream.on_order_change(&add(1, OrderSide::Buy, 99.0, 100)), 0);
+        assert_eq!(stream.on_order_change(&add(2, OrderSide::Sell, 101.0, 200)), 0);
+        // 跨越 1 秒边界：推送第一个桶（两个挂单都已生效）
+        stream.on_order_change(&add(3, OrderSide::Buy, 98.0, 1_000_000_100));
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "btcusdt@heatmap");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["s"], "BTCUSDT");
+        assert_eq!(json["data"]["u"], 2);
+        assert_eq!(json["data"]["b"][0][0], Price::from_f64(99.0).to_string());
+        assert_eq!(json["data"]["a"].as_array().unwrap().len(), 1);
+
+        // 无增量时由定时器推进
+        assert_eq!(stream.poll(), 0);
+        clock.set(Timestamp(2_000_000_000));
+        assert_eq!(stream.poll(), 1);
+        let json: serde_json::Value =
+            serde_json::from_str(&receiver.try_recv().unwrap().payload).unwrap();
+        assert_eq!(json["data"]["E"], 1_000);
+        assert_eq!(json["data"]["b"].as_array().unwrap().len(), 2);
+
+        // 未上线的交易对不建聚合器
+        let mut unknown = add(4, OrderSide::Buy, 1.0, 3_000_000_000);
+        unknown.symbol_id = u32::MAX;
+        assert_eq!(stream.on_order_change(&unknown), 0);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! 推送降级（Load Shedding）
+//!
+//! 事件循环延迟或 CPU 超过阈值时逐级降级低优先级流，保护下单与用户数据流：
+//! - `Normal`：全部按基础合并间隔推送
+//! - `Degraded`：普通流与低优先级流的合并间隔放大
+//! - `Shedding`：暂停 K 线、热力图等低优先级流，普通流继续放大合并间隔
+//!
+//! 升级立即生效；降级需连续若干个平稳样本，且每次只回退一级，避免在阈值附近抖动。
+//! 级别变化时生成 `!shedState` 消息推送给所有客户端。
+//!
+//! 采样与执行在 [`super::server::WebSocketGateway`]：首个连接到达后每 500ms 测量一次事件循环
+//! 延迟与进程 CPU，每条连接按 [`LoadShedder::decision`] 合并或暂停推送
+
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+
+use super::handshake::ACCOUNT_STREAM_PREFIX;
+use super::system_status::SYSTEM_STATUS_STREAM;
+
+/// 降级状态流名称
+pub const SHED_STATE_STREAM: &str = "!shedState";
+
+/// 流优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StreamPriority {
+    /// K 线、热力图等，压力下首先暂停
+    Low,
+    /// 行情流（bookTicker、成交、深度）
+    Normal,
+    /// 下单回报、用户数据流与状态流，永不降级
+    Critical,
+}
+
+impl StreamPriority {
+    /// 按流名称分类
+    pub fn classify(stream: &str) -> Self {
+        if stream.starts_with(ACCOUNT_STREAM_PREFIX)
+            || stream.starts_with("order@")
+            || stream == SHED_STATE_STREAM
+            || stream == SYSTEM_STATUS_STREAM
+        {
+            StreamPriority::Critical
+        } else if stream.contains("@kline") || stream.contains("@heatmap") {
+            StreamPriority::Low
+        } else {
+            StreamPriority::Normal
+        }
+    }
+}
+
+/// 降级级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShedLevel {
+    Normal,
+    Degraded,
+    Shedding,
+}
+
+impl ShedLevel {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ShedLevel::Normal => "NORMAL",
+            ShedLevel::Degraded => "DEGRADED",
+            ShedLevel::Shedding => "SHEDDING",
+        }
+    }
+
+    fn relaxed(self) -> Self {
+        match self {
+            ShedLevel::Shedding => ShedLevel::Degraded,
+            _ => ShedLevel::Normal,
+        }
+    }
+}
+
+/// 单个流的推送决策
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamDecision {
+    /// 按该合并间隔推送（0 表示逐条推送）
+    Deliver { conflation: Duration },
+    /// 暂停推送
+    Paused,
+}
+
+/// 降级阈值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadShedConfig {
+    /// 进入 Degraded 的事件循环延迟
+    pub degrade_lag: Duration,
+    /// 进入 Shedding 的事件循环延迟
+    pub shed_lag: Duration,
+    /// 进入 Degraded 的 CPU 占用（百分比，多核可超过 100）
+    pub degrade_cpu_pct: f64,
+    /// 进入 Shedding 的 CPU 占用
+    pub shed_cpu_pct: f64,
+    /// 回退一级所需的连续平稳样本数
+    pub recover_samples: u32,
+    /// 基础合并间隔
+    pub base_conflation: Duration,
+    /// 降级时合并间隔的放大倍数（Shedding 再翻倍）
+    pub conflation_multiplier: u32,
+}
+
+impl Default for LoadShedConfig {
+    fn default() -> Self {
+        Self {
+            degrade_lag: Duration::from_millis(5),
+            shed_lag: Duration::from_millis(20),
+            degrade_cpu_pct: 70.0,
+            shed_cpu_pct: 90.0,
+            recover_samples: 5,
+            base_conflation: Duration::from_millis(100),
+            conflation_multiplier: 4,
+        }
+    }
+}
+
+/// 一次负载采样
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadSample {
+    /// 事件循环延迟
+    pub event_loop_lag: Duration,
+    /// 进程 CPU 占用（百分比）
+    pub cpu_pct: f64,
+}
+
+/// 降级策略
+#[derive(Debug)]
+pub struct LoadShedder {
+    config: LoadShedConfig,
+    level: ShedLevel,
+    /// 连续低于当前级别阈值的样本数
+    calm_samples: u32,
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self::new(LoadShedConfig::default())
+    }
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadShedConfig) -> Self {
+        Self { config, level: ShedLevel::Normal, calm_samples: 0 }
+    }
+
+    pub fn level(&self) -> ShedLevel {
+        self.level
+    }
+
+    /// 输入一次采样，级别变化时返回新级别
+    pub fn observe(&mut self, sample: LoadSample) -> Option<ShedLevel> {
+        let target = self.target_level(sample);
+        let next = if target > self.level {
+            self.calm_samples = 0;
+            target
+        } else if target < self.level {
+            self.calm_samples += 1;
+            if self.calm_samples < self.config.recover_samples {
+                return None;
+            }
+            self.calm_samples = 0;
+            self.level.relaxed()
+        } else {
+            self.calm_samples = 0;
+            return None;
+        };
+        self.level = next;
+        Some(next)
+    }
+
+    /// 当前级别下某个流的推送决策
+    pub fn decision(&self, stream: &str) -> StreamDecision {
+        let priority = StreamPriority::classify(stream);
+        let base = self.config.base_conflation;
+        let multiplier = self.config.conflation_multiplier.max(1);
+        match (self.level, priority) {
+            (_, StreamPriority::Critical) => StreamDecision::Deliver { conflation: Duration::ZERO },
+            (ShedLevel::Shedding, StreamPriority::Low) => StreamDecision::Paused,
+            (ShedLevel::Normal, _) => StreamDecision::Deliver { conflation: base },
+            (ShedLevel::Degraded, _) => StreamDecision::Deliver { conflation: base * multiplier },
+            (ShedLevel::Shedding, StreamPriority::Normal) => {
+                StreamDecision::Deliver { conflation: base * multiplier * 2 }
+            }
+        }
+    }
+
+    /// 当前降级状态消息：`{"stream": "!shedState", "data": {...}}`
+    pub fn state_payload(&self) -> String {
+        let conflation_ms = |stream: &str| match self.decision(stream) {
+            StreamDecision::Deliver { conflation } => Some(conflation.as_millis() as u64),
+            StreamDecision::Paused => None,
+        };
+        serde_json::json!({
+            "stream": SHED_STATE_STREAM,
+            "data": {
+                "level": self.level.as_str(),
+                "normalConflationMs": conflation_ms("btcusdt@bookTicker"),
+                "lowPriorityPaused": self.decision("btcusdt@kline_1m") == StreamDecision::Paused,
+                "lowConflationMs": conflation_ms("btcusdt@kline_1m"),
+            }
+        })
+        .to_string()
+    }
+
+    fn target_level(&self, sample: LoadSample) -> ShedLevel {
+        let config = &self.config;
+        if sample.event_loop_lag >= config.shed_lag || sample.cpu_pct >= config.shed_cpu_pct {
+            ShedLevel::Shedding
+        } else if sample.event_loop_lag >= config.degrade_lag
+            || sample.cpu_pct >= config.degrade_cpu_pct
+        {
+            ShedLevel::Degraded
+        } else {
+            ShedLevel::Normal
+        }
+    }
+}
+
+/// 测量事件循环延迟：睡眠 `interval` 后实际唤醒时间超出的部分
+pub async fn measure_event_loop_lag(interval: Duration) -> Duration {
+    let started = Instant::now();
+    sleep(interval).await;
+    started.elapsed().saturating_sub(interval)
+}
+
+/// 进程 CPU 占用采样（读 `/proc/self/stat`，非 Linux 返回 None）
+#[derive(Debug, Default)]
+pub struct CpuSampler {
+    last: Option<(Instant, u64)>,
+}
+
+impl CpuSampler {
+    /// 距上次采样期间的 CPU 占用百分比（首次采样返回 None）
+    pub fn sample(&mut self) -> Option<f64> {
+        let now = Instant::now();
+        let cpu_ms = process_cpu_ms()?;
+        let previous = self.last.replace((now, cpu_ms));
+        let (at, before) = previous?;
+        let wall_ms = now.duration_since(at).as_secs_f64() * 1_000.0;
+        (wall_ms > 0.0).then(|| (cpu_ms - before) as f64 / wall_ms * 100.0)
+    }
+}
+
+/// 进程累计 CPU 时间（毫秒）
+fn process_cpu_ms() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // 进程名可能含空格，从右括号之后数：utime、stime 为第 14、15 个字段
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    // USER_HZ 固定为 100
+    Some((utime + stime) * 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(lag_ms: u64, cpu_pct: f64) -> LoadSample {
+        LoadSample { event_loop_lag: Duration::from_millis(lag_ms), cpu_pct }
+    }
+
+    #[test]
+    fn test_escalate_and_recover_with_hysteresis() {
+        let mut shedder =
+            LoadShedder::new(LoadShedConfig { recover_samples: 2, ..Default::default() });
+        assert_eq!(shedder.observe(sample(1, 10.0)), None);
+        assert_eq!(shedder.observe(sample(1, 75.0)), Some(ShedLevel::Degraded));
+        assert_eq!(shedder.observe(sample(30, 10.0)), Some(ShedLevel::Shedding));
+
+        // 平稳样本不足时保持，回退每次一级
+        assert_eq!(shedder.observe(sample(1, 10.0)), None);
+        assert_eq!(shedder.observe(sample(1, 10.0)), Some(ShedLevel::Degraded));
+        assert_eq!(shedder.observe(sample(1, 10.0)), None);
+        assert_eq!(shedder.observe(sample(6, 10.0)), None);
+        assert_eq!(shedder.observe(sample(1, 10.0)), None);
+        assert_eq!(shedder.observe(sample(1, 10.0)), Some(ShedLevel::Normal));
+    }
+
+    #[test]
+    fn test_stream_decisions_protect_critical_streams() {
+        let mut shedder = LoadShedder::default();
+        let base = Duration::from_millis(100);
+        assert_eq!(
+            shedder.decision("btcusdt@kline_1m"),
+            StreamDecision::Deliver { conflation: base }
+        );
+
+        shedder.observe(sample(50, 0.0));
+        assert_eq!(shedder.level(), ShedLevel::Shedding);
+        assert_eq!(shedder.decision("btcusdt@kline_1m"), StreamDecision::Paused);
+        assert_eq!(shedder.decision("btcusdt@heatmap"), StreamDecision::Paused);
+        assert_eq!(
+            shedder.decision("btcusdt@bookTicker"),
+            StreamDecision::Deliver { conflation: base * 8 }
+        );
+        assert_eq!(
+            shedder.decision("account@7"),
+            StreamDecision::Deliver { conflation: Duration::ZERO }
+        );
+
+        let state: serde_json::Value = serde_json::from_str(&shedder.state_payload()).unwrap();
+        assert_eq!(state["stream"], SHED_STATE_STREAM);
+        assert_eq!(state["data"]["level"], "SHEDDING");
+        assert_eq!(state["data"]["lowPriorityPaused"], true);
+        assert_eq!(state["data"]["normalConflationMs"], 800);
+    }
+}
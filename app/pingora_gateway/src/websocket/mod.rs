@@ -1 +1,11 @@
-
+pub mod block_trade;
+pub mod book_ticker;
+pub mod handshake;
+pub mod heatmap;
+pub mod load_shed;
+pub mod resume;
+pub mod rfq;
+pub mod server;
+pub mod subscription;
+pub mod synthetic_ticker;
+pub mod system_status;
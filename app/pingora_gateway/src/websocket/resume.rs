@@ -0,0 +1,373 @@
+//! 用户数据流断线续传
+//!
+//! 私有流（`account@<ID>`）的每条推送在会话内分配递增序号并写入续传存储；客户端重连时带上
+//! 会话ID与最后收到的序号，网关从存储补发其后的消息。存储独立于网关进程，
+//! 网关重启或故障切换到 pingora 后的另一实例后仍可续传：
+//! - [`MemoryResumeStore`]：进程内存储（单实例与测试）
+//! - [`ShmResumeStore`]：共享内存目录（默认 [`DEFAULT_SHM_RESUME_DIR`]）下每个会话一个追加文件，
+//!   同机网关实例共享，进程重启不丢失
+//!
+//! 其他共享存储（如 Redis 列表）实现 [`ResumeStore`] 即可接入。每个会话保留最近
+//! `capacity` 条，客户端的序号早于保留范围时返回 [`ResumeError::Gap`]，须重新拉取快照
+//!
+//! 连接入口在 [`super::server::WebSocketGateway`]：已鉴权会话以 `/ws?sessionId=<ID>` 连接时
+//! 私有流推送附带 `seq`，重连时追加 `lastSequence=<序号>` 补发；存储目录由
+//! `GATEWAY_WS_RESUME_DIR` 配置
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use base_types::AccountId;
+
+/// 默认每个会话保留的消息条数
+pub const DEFAULT_RESUME_CAPACITY: usize = 4096;
+
+/// 共享内存续传目录
+pub const DEFAULT_SHM_RESUME_DIR: &str = "/dev/shm/gateway-resume";
+
+/// 会话ID最大长度
+const MAX_SESSION_ID_LEN: usize = 64;
+
+/// 续传会话键（会话ID限定在账户内，其他账户无法续传）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResumeKey {
+    pub account_id: AccountId,
+    pub session_id: String,
+}
+
+impl ResumeKey {
+    /// 会话ID只允许字母、数字、`-` 与 `_`
+    pub fn new(account_id: AccountId, session_id: &str) -> Option<Self> {
+        let valid = !session_id.is_empty()
+            && session_id.len() <= MAX_SESSION_ID_LEN
+            && session_id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        valid.then(|| Self { account_id, session_id: session_id.to_string() })
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}-{}", self.account_id.0, self.session_id)
+    }
+}
+
+/// 续传存储
+pub trait ResumeStore: Send + Sync {
+    /// 追加一条消息
+    fn append(&self, key: &ResumeKey, sequence: u64, payload: &str) -> io::Result<()>;
+
+    /// 读取会话保留的全部消息（按序号升序）
+    fn read(&self, key: &ResumeKey) -> io::Result<Vec<(u64, String)>>;
+
+    /// 丢弃序号小于 `sequence` 的消息
+    fn truncate_before(&self, key: &ResumeKey, sequence: u64) -> io::Result<()>;
+
+    /// 删除会话
+    fn remove(&self, key: &ResumeKey) -> io::Result<()>;
+}
+
+/// 会话 → 保留的 (序号, 消息)
+type SessionRecords = HashMap<ResumeKey, Vec<(u64, String)>>;
+
+/// 进程内续传存储
+#[derive(Debug, Default)]
+pub struct MemoryResumeStore {
+    sessions: Mutex<SessionRecords>,
+}
+
+impl MemoryResumeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sessions(&self) -> io::Result<MutexGuard<'_, SessionRecords>> {
+        self.sessions.lock().map_err(|_| io::Error::other("resume store poisoned"))
+    }
+}
+
+impl ResumeStore for MemoryResumeStore {
+    fn append(&self, key: &ResumeKey, sequence: u64, payload: &str) -> io::Result<()> {
+        self.sessions()?.entry(key.clone()).or_default().push((sequence, payload.to_string()));
+        Ok(())
+    }
+
+    fn read(&self, key: &ResumeKey) -> io::Result<Vec<(u64, String)>> {
+        Ok(self.sessions()?.get(key).cloned().unwrap_or_default())
+    }
+
+    fn truncate_before(&self, key: &ResumeKey, sequence: u64) -> io::Result<()> {
+        if let Some(records) = self.sessions()?.get_mut(key) {
+            records.retain(|(seq, _)| *seq >= sequence);
+        }
+        Ok(())
+    }
+
+    fn remove(&self, key: &ResumeKey) -> io::Result<()> {
+        self.sessions()?.remove(key);
+        Ok(())
+    }
+}
+
+/// 共享内存目录续传存储
+///
+/// 每个会话一个文件，记录格式（小端）：`序号 u64 | 长度 u32 | 消息`。
+/// 末尾不完整的记录（写入中途进程退出）在读取时忽略；截断时写临时文件后原子替换
+#[derive(Debug)]
+pub struct ShmResumeStore {
+    dir: PathBuf,
+    /// 串行化本进程内对同一目录的写入
+    lock: Mutex<()>,
+}
+
+impl ShmResumeStore {
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, lock: Mutex::new(()) })
+    }
+
+    fn path(&self, key: &ResumeKey) -> PathBuf {
+        self.dir.join(key.file_name())
+    }
+
+    fn guard(&self) -> io::Result<MutexGuard<'_, ()>> {
+        self.lock.lock().map_err(|_| io::Error::other("resume store poisoned"))
+    }
+
+    fn encode(out: &mut Vec<u8>, sequence: u64, payload: &str) {
+        out.extend_from_slice(&sequence.to_le_bytes());
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(payload.as_bytes());
+    }
+
+    fn read_records(&self, key: &ResumeKey) -> io::Result<Vec<(u64, String)>> {
+        let mut bytes = Vec::new();
+        match File::open(self.path(key)) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut records = Vec::new();
+        let mut rest = bytes.as_slice();
+        while rest.len() >= 12 {
+            let sequence = u64::from_le_bytes(rest[..8].try_into().unwrap());
+            let len = u32::from_le_bytes(rest[8..12].try_into().unwrap()) as usize;
+            let Some(payload) = rest.get(12..12 + len) else {
+                break;
+            };
+            let payload = String::from_utf8(payload.to_vec())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid resume record"))?;
+            records.push((sequence, payload));
+            rest = &rest[12 + len..];
+        }
+        Ok(records)
+    }
+}
+
+impl ResumeStore for ShmResumeStore {
+    fn append(&self, key: &ResumeKey, sequence: u64, payload: &str) -> io::Result<()> {
+        let _guard = self.guard()?;
+        let mut record = Vec::with_capacity(12 + payload.len());
+        Self::encode(&mut record, sequence, payload);
+        let mut file = OpenOptions::new().create(true).append(true).open(self.path(key))?;
+        file.write_all(&record)
+    }
+
+    fn read(&self, key: &ResumeKey) -> io::Result<Vec<(u64, String)>> {
+        let _guard = self.guard()?;
+        self.read_records(key)
+    }
+
+    fn truncate_before(&self, key: &ResumeKey, sequence: u64) -> io::Result<()> {
+        let _guard = self.guard()?;
+        let mut bytes = Vec::new();
+        for (seq, payload) in self.read_records(key)?.iter().filter(|(seq, _)| *seq >= sequence) {
+            Self::encode(&mut bytes, *seq, payload);
+        }
+        let path = self.path(key);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, &bytes)?;
+        fs::rename(&tmp, &path)
+    }
+
+    fn remove(&self, key: &ResumeKey) -> io::Result<()> {
+        let _guard = self.guard()?;
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 续传错误
+#[derive(Debug)]
+pub enum ResumeError {
+    /// 请求的消息已不在保留范围内（`oldest` 为最早保留的序号），须重新拉取快照
+    Gap { oldest: u64 },
+    /// 客户端序号超过服务端已发送的最新序号
+    Ahead { latest: u64 },
+    /// 存储读写失败
+    Store(io::Error),
+}
+
+impl ResumeError {
+    /// 错误码
+    pub fn code(&self) -> i32 {
+        match self {
+            ResumeError::Gap { .. } => 4100,
+            ResumeError::Ahead { .. } => 4101,
+            ResumeError::Store(_) => 5000,
+        }
+    }
+
+    /// 续传失败的推送消息：`{"error": {"code": ..., "msg": ...}}`
+    pub fn response(&self) -> String {
+        serde_json::json!({ "error": { "code": self.code(), "msg": self.to_string() } }).to_string()
+    }
+}
+
+impl fmt::Display for ResumeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResumeError::Gap { oldest } => {
+                write!(f, "Messages before {} are no longer retained", oldest)
+            }
+            ResumeError::Ahead { latest } => write!(f, "Sequence is ahead of latest {}", latest),
+            ResumeError::Store(e) => write!(f, "Resume store error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ResumeError {}
+
+impl From<io::Error> for ResumeError {
+    fn from(e: io::Error) -> Self {
+        ResumeError::Store(e)
+    }
+}
+
+/// 单个会话的续传缓冲
+///
+/// 打开时从存储恢复序号，网关重启后同一会话继续编号
+pub struct ResumeBuffer {
+    key: ResumeKey,
+    store: Arc<dyn ResumeStore>,
+    capacity: usize,
+    /// 下一条消息的序号（从 1 开始）
+    next_sequence: u64,
+    /// 存储中保留的条数
+    retained: usize,
+}
+
+impl ResumeBuffer {
+    pub fn open(store: Arc<dyn ResumeStore>, key: ResumeKey, capacity: usize) -> io::Result<Self> {
+        let records = store.read(&key)?;
+        let next_sequence = records.last().map_or(1, |(seq, _)| seq + 1);
+        Ok(Self { key, store, capacity: capacity.max(1), next_sequence, retained: records.len() })
+    }
+
+    pub fn key(&self) -> &ResumeKey {
+        &self.key
+    }
+
+    /// 最新已分配的序号（尚无消息为 0）
+    pub fn latest_sequence(&self) -> u64 {
+        self.next_sequence - 1
+    }
+
+    /// 记录一条推送，返回分配的序号
+    ///
+    /// 保留条数超过容量两倍时截断到容量，摊销存储改写
+    pub fn push(&mut self, payload: &str) -> io::Result<u64> {
+        let sequence = self.next_sequence;
+        self.store.append(&self.key, sequence, payload)?;
+        self.next_sequence += 1;
+        self.retained += 1;
+        if self.retained > self.capacity * 2 {
+            self.store.truncate_before(&self.key, self.next_sequence - self.capacity as u64)?;
+            self.retained = self.capacity;
+        }
+        Ok(sequence)
+    }
+
+    /// 客户端最后收到 `last_sequence`，返回需要补发的消息
+    pub fn replay_after(&self, last_sequence: u64) -> Result<Vec<(u64, String)>, ResumeError> {
+        let latest = self.latest_sequence();
+        if last_sequence > latest {
+            return Err(ResumeError::Ahead { latest });
+        }
+        if last_sequence == latest {
+            return Ok(Vec::new());
+        }
+        let records = self.store.read(&self.key)?;
+        let oldest = records.first().map_or(self.next_sequence, |(seq, _)| *seq);
+        if last_sequence + 1 < oldest {
+            return Err(ResumeError::Gap { oldest });
+        }
+        Ok(records.into_iter().filter(|(seq, _)| *seq > last_sequence).collect())
+    }
+
+    /// 会话正常结束（客户端主动退出），删除续传数据
+    pub fn close(self) -> io::Result<()> {
+        self.store.remove(&self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(session: &str) -> ResumeKey {
+        ResumeKey::new(AccountId(7), session).unwrap()
+    }
+
+    #[test]
+    fn test_replay_and_gap() {
+        let store: Arc<dyn ResumeStore> = Arc::new(MemoryResumeStore::new());
+        let mut buffer = ResumeBuffer::open(store.clone(), key("s1"), 2).unwrap();
+        for i in 1..=5 {
+            assert_eq!(buffer.push(&format!("m{}", i)).unwrap(), i);
+        }
+        // 5 条超过容量两倍，截断为最近 2 条
+        assert_eq!(store.read(&key("s1")).unwrap().len(), 2);
+        assert_eq!(
+            buffer.replay_after(3).unwrap(),
+            vec![(4, "m4".to_string()), (5, "m5".to_string())]
+        );
+        assert!(buffer.replay_after(5).unwrap().is_empty());
+        assert!(matches!(buffer.replay_after(2), Err(ResumeError::Gap { oldest: 4 })));
+        assert!(matches!(buffer.replay_after(9), Err(ResumeError::Ahead { latest: 5 })));
+
+        // 会话ID不能用于拼接路径
+        assert!(ResumeKey::new(AccountId(7), "../etc").is_none());
+        assert!(ResumeKey::new(AccountId(7), "").is_none());
+    }
+
+    #[test]
+    fn test_shm_store_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("gw-resume-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        {
+            let store: Arc<dyn ResumeStore> = Arc::new(ShmResumeStore::open(&dir).unwrap());
+            let mut buffer = ResumeBuffer::open(store, key("s2"), 16).unwrap();
+            buffer.push("fill-1").unwrap();
+            buffer.push("fill-2").unwrap();
+        }
+        // 模拟网关重启：新实例打开同一目录，继续编号并补发
+        let store: Arc<dyn ResumeStore> = Arc::new(ShmResumeStore::open(&dir).unwrap());
+        let mut buffer = ResumeBuffer::open(store.clone(), key("s2"), 16).unwrap();
+        assert_eq!(buffer.latest_sequence(), 2);
+        assert_eq!(buffer.replay_after(1).unwrap(), vec![(2, "fill-2".to_string())]);
+        assert_eq!(buffer.push("fill-3").unwrap(), 3);
+
+        // 其他账户的同名会话互不可见
+        let other = ResumeKey::new(AccountId(8), "s2").unwrap();
+        assert!(store.read(&other).unwrap().is_empty());
+
+        buffer.close().unwrap();
+        assert!(store.read(&key("s2")).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
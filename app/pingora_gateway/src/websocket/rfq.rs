@@ -0,0 +1,149 @@
+//! RFQ 生命周期推送
+//!
+//! 询价事件按接收方推送到各自的私有流 `account@<ID>`：
+//! - `rfqRequest`：新询价，推送给受邀做市商
+//! - `rfqQuote`：确定报价，推送给询价方与报价的做市商
+//! - `rfqFilled` / `rfqExpired` / `rfqCancelled` / `rfqRejected`：询价结束，推送给询价方与全部受邀做市商
+//!
+//! 推送不包含对手方账户；成交消息对做市商附带 `won` 标记是否为成交方
+
+use base_types::AccountId;
+use base_types::exchange::spot::rfq::{RfqEvent, RfqEventKind};
+use tokio::sync::broadcast;
+
+use super::book_ticker::StreamMessage;
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 1024;
+
+/// RFQ WebSocket 推送
+pub struct RfqStream {
+    sender: broadcast::Sender<StreamMessage>,
+}
+
+impl Default for RfqStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl RfqStream {
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 推送一个事件给全部接收方，返回发出的消息数
+    pub fn publish(&self, event: &RfqEvent) -> usize {
+        let mut sent = 0;
+        for recipient in event.recipients() {
+            let stream = format!("account@{}", recipient.0);
+            let data = event_json(event, recipient);
+            let payload = serde_json::json!({ "stream": stream, "data": data }).to_string();
+            if self.sender.send(StreamMessage { stream, payload }).is_ok() {
+                sent += 1;
+            }
+        }
+        sent
+    }
+}
+
+/// 单个接收方看到的事件
+fn event_json(event: &RfqEvent, recipient: AccountId) -> serde_json::Value {
+    let request = &event.request;
+    let event_type = match event.kind {
+        RfqEventKind::Requested => "rfqRequest",
+        RfqEventKind::Quoted(_) => "rfqQuote",
+        RfqEventKind::Filled { .. } => "rfqFilled",
+        RfqEventKind::Expired => "rfqExpired",
+        RfqEventKind::Cancelled => "rfqCancelled",
+        RfqEventKind::Rejected => "rfqRejected",
+    };
+    let mut json = serde_json::json!({
+        "e": event_type,
+        "E": event.time.0 / 1_000_000,
+        "rfqId": request.rfq_id,
+        "symbol": request.trading_pair.to_symbol_string(),
+        "side": format!("{:?}", request.side).to_uppercase(),
+        "quantity": request.quantity.to_string(),
+        "expiresAt": request.expires_at.0 / 1_000_000,
+    });
+    match &event.kind {
+        RfqEventKind::Quoted(quote) => {
+            json["quoteId"] = quote.quote_id.into();
+            json["price"] = quote.price.to_string().into();
+        }
+        RfqEventKind::Filled { quote, trade_id } => {
+            json["quoteId"] = quote.quote_id.into();
+            json["price"] = quote.price.to_string().into();
+            json["tradeId"] = (*trade_id).into();
+            if recipient != request.taker {
+                json["won"] = (recipient == quote.maker).into();
+            }
+        }
+        _ => {}
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::exchange::spot::rfq::{FirmQuote, QuoteRequest};
+    use base_types::{OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+    use super::*;
+
+    #[test]
+    fn test_publish_rfq_lifecycle() {
+        let request = QuoteRequest {
+            rfq_id: 3,
+            taker: AccountId(1),
+            trading_pair: TradingPair::BtcUsdt,
+            side: OrderSide::Buy,
+            quantity: Quantity::from_f64(2.0),
+            makers: vec![AccountId(10), AccountId(11)],
+            created_at: Timestamp(0),
+            expires_at: Timestamp(2_000_000_000),
+        };
+        let stream = RfqStream::default();
+        let mut receiver = stream.subscribe();
+
+        let requested = RfqEvent {
+            request: request.clone(),
+            kind: RfqEventKind::Requested,
+            time: Timestamp(0),
+        };
+        assert_eq!(stream.publish(&requested), 2);
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "account@10");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["e"], "rfqRequest");
+        assert_eq!(json["data"]["expiresAt"], 2_000);
+        assert!(json["data"].get("taker").is_none());
+        receiver.try_recv().unwrap();
+
+        let quote = FirmQuote {
+            quote_id: 5,
+            rfq_id: 3,
+            maker: AccountId(11),
+            price: Price::from_f64(100.5),
+            quantity: request.quantity,
+            received_at: Timestamp(1_000_000),
+        };
+        let filled = RfqEvent {
+            request,
+            kind: RfqEventKind::Filled { quote, trade_id: 77 },
+            time: Timestamp(2_000_000_000),
+        };
+        assert_eq!(stream.publish(&filled), 3);
+        let payloads: Vec<serde_json::Value> = (0..3)
+            .map(|_| serde_json::from_str(&receiver.try_recv().unwrap().payload).unwrap())
+            .collect();
+        assert_eq!(payloads[0]["stream"], "account@1");
+        assert_eq!(payloads[0]["data"]["tradeId"], 77);
+        assert!(payloads[0]["data"].get("won").is_none());
+        assert_eq!(payloads[1]["data"]["won"], false);
+        assert_eq!(payloads[2]["data"]["won"], true);
+        assert_eq!(payloads[2]["data"]["price"], "100.5");
+    }
+}
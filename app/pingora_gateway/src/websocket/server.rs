@@ -0,0 +1,859 @@
+//! WebSocket 推送服务
+//!
+//! 网关在 `GET /ws` 的 Upgrade 请求上完成握手鉴权（[`HandshakeAuth`]），失败以 401/403 拒绝，
+//! 通过后应答 101，连接交给 tungstenite 处理帧协议（掩码、分片重组、Ping/Close 应答），
+//! 经 [`WsTransport`] 收发消息：
+//! - 客户端以 `{"method": "SUBSCRIBE" | "UNSUBSCRIBE" | "LIST_SUBSCRIPTIONS", "params": [...], "id": n}`
+//!   管理订阅，订阅经 [`SubscriptionManager`] 检查私有流归属与行情权限
+//! - 行情、大宗成交、系统状态等推送源并入连接共享的推送总线（[`WebSocketGateway::spawn_forward`]），
+//!   每条连接按订阅过滤，按 [`LoadShedder`] 的决策合并或暂停；`!shedState` 推送给所有连接
+//! - 已鉴权会话带 `sessionId` 查询参数时，私有流推送附带递增序号 `seq` 写入续传存储，
+//!   重连时带 `lastSequence` 补发其后的消息（见 [`super::resume`]）；客户端发送关闭帧时删除续传数据
+//! - 已鉴权会话带 `cancelOnDisconnect=true` 时，连接结束（含客户端关闭）后调用断线回调
+//!   （[`WebSocketGateway::with_disconnect_hook`]），做市账户借此撤回全部报价
+//!
+//! 环境变量：
+//! - `GATEWAY_WS_TOKEN_SECRET`：签名令牌密钥（未设置时随机生成，签名令牌在重启后失效）
+//! - `GATEWAY_WS_ALLOWED_ORIGINS`：浏览器 Origin 白名单，逗号分隔
+//! - `GATEWAY_WS_RESUME_DIR`：续传存储目录（默认 [`DEFAULT_SHM_RESUME_DIR`]，无法打开时退回进程内存储）
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Once, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use base_types::account::api_key::ApiKeyStore;
+use base_types::{AccountId, SystemClock, TimestampProvider};
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
+use tracing::{debug, warn};
+use websocket_sockudo::{FrameTransport, WsMessage, WsTransport};
+
+use super::book_ticker::StreamMessage;
+use super::handshake::{
+    ACCOUNT_STREAM_PREFIX, HandshakeAuth, HandshakeError, SessionIdentity, UpgradeRequest,
+};
+use super::load_shed::{
+    CpuSampler, LoadSample, LoadShedder, SHED_STATE_STREAM, ShedLevel, StreamDecision,
+    measure_event_loop_lag,
+};
+use super::resume::{
+    DEFAULT_RESUME_CAPACITY, DEFAULT_SHM_RESUME_DIR, MemoryResumeStore, ResumeBuffer, ResumeKey,
+    ResumeStore, ShmResumeStore,
+};
+use super::subscription::{EntitlementStore, SubscriptionManager};
+use super::system_status::{SYSTEM_STATUS_STREAM, SystemStatusStream};
+use crate::http::degradation::SharedDegradations;
+use crate::http::session_auth::SessionAuth;
+
+/// WebSocket 入口路径
+pub const WEBSOCKET_PATH: &str = "/ws";
+
+/// 客户端消息长度上限（分片重组后，订阅请求足够）
+pub const MAX_CLIENT_MESSAGE_LEN: usize = 64 * 1024;
+
+const ENV_TOKEN_SECRET: &str = "GATEWAY_WS_TOKEN_SECRET";
+const ENV_ALLOWED_ORIGINS: &str = "GATEWAY_WS_ALLOWED_ORIGINS";
+const ENV_RESUME_DIR: &str = "GATEWAY_WS_RESUME_DIR";
+
+/// 推送总线缓冲（慢连接落后超过该条数时丢弃旧消息）
+const HUB_CAPACITY: usize = 4096;
+
+/// 合并中的消息检查间隔
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 负载采样间隔
+const LOAD_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 订阅命令格式错误的错误码
+const INVALID_REQUEST_CODE: i32 = 4000;
+
+/// `Sec-WebSocket-Accept`：`base64(SHA-1(key + GUID))`
+pub fn accept_key(key: &str) -> String {
+    derive_accept_key(key.as_bytes())
+}
+
+/// 握手成功的 101 响应
+fn accept_response(key: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )
+    .into_bytes()
+}
+
+/// 握手完成后的连接，由 tungstenite 处理帧协议
+async fn accept_transport<S>(io: S) -> impl WsTransport
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(MAX_CLIENT_MESSAGE_LEN))
+        .max_frame_size(Some(MAX_CLIENT_MESSAGE_LEN));
+    let stream = WebSocketStream::from_raw_socket(io, Role::Server, Some(config)).await;
+    FrameTransport::new(stream, encode_message, decode_message)
+}
+
+fn encode_message(message: WsMessage) -> Message {
+    match message {
+        WsMessage::Text(text) => Message::text(text),
+        WsMessage::Binary(data) => Message::Binary(data),
+        WsMessage::Ping(data) => Message::Ping(data),
+        WsMessage::Pong(data) => Message::Pong(data),
+        WsMessage::Close => Message::Close(None),
+    }
+}
+
+/// 读取侧只会收到重组后的完整消息，原始帧跳过
+fn decode_message(message: Message) -> Option<WsMessage> {
+    match message {
+        Message::Text(text) => Some(WsMessage::Text(text.as_str().to_owned())),
+        Message::Binary(data) => Some(WsMessage::Binary(data)),
+        Message::Ping(data) => Some(WsMessage::Ping(data)),
+        Message::Pong(data) => Some(WsMessage::Pong(data)),
+        Message::Close(_) => Some(WsMessage::Close),
+        Message::Frame(_) => None,
+    }
+}
+
+/// 断线回调：参数为开启 `cancelOnDisconnect` 的会话账户
+pub type DisconnectHook = Arc<dyn Fn(AccountId) + Send + Sync>;
+
+/// 客户端订阅命令
+#[derive(Debug, Deserialize)]
+struct ClientCommand {
+    method: String,
+    #[serde(default)]
+    params: Vec<String>,
+    #[serde(default)]
+    id: u64,
+}
+
+/// WebSocket 推送服务（跨连接共享）
+pub struct WebSocketGateway {
+    auth: HandshakeAuth,
+    entitlements: EntitlementStore,
+    shedder: Arc<RwLock<LoadShedder>>,
+    /// 推送总线：各推送源经转发线程写入，每条连接订阅一份
+    hub: broadcast::Sender<StreamMessage>,
+    /// 降级登记表（订阅 `!systemStatus` 时先发送当前状态）
+    degradations: Option<SharedDegradations>,
+    resume: Arc<dyn ResumeStore>,
+    resume_capacity: usize,
+    /// 断线回调（未设置时 `cancelOnDisconnect` 不生效）
+    disconnect_hook: Option<DisconnectHook>,
+    clock: Arc<dyn TimestampProvider>,
+    /// 负载采样任务只在首个连接到达时启动一次（需要在服务运行时上测量延迟）
+    sampler: Once,
+}
+
+impl Default for WebSocketGateway {
+    /// 随机令牌密钥、进程内续传存储：部署时以 [`WebSocketGateway::from_env`] 配置
+    fn default() -> Self {
+        Self::new(HandshakeAuth::new(uuid::Uuid::new_v4().as_bytes().to_vec()))
+    }
+}
+
+impl WebSocketGateway {
+    pub fn new(auth: HandshakeAuth) -> Self {
+        let (hub, _) = broadcast::channel(HUB_CAPACITY);
+        Self {
+            auth,
+            entitlements: EntitlementStore::new(),
+            shedder: Arc::new(RwLock::new(LoadShedder::default())),
+            hub,
+            degradations: None,
+            resume: Arc::new(MemoryResumeStore::new()),
+            resume_capacity: DEFAULT_RESUME_CAPACITY,
+            disconnect_hook: None,
+            clock: Arc::new(SystemClock),
+            sampler: Once::new(),
+        }
+    }
+
+    /// 从环境变量构建
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.is_empty());
+        let mut gateway = match var(ENV_TOKEN_SECRET) {
+            Some(secret) => Self::new(HandshakeAuth::new(secret.into_bytes())),
+            None => Self::default(),
+        };
+        for origin in var(ENV_ALLOWED_ORIGINS).iter().flat_map(|list| list.split(',')) {
+            let origin = origin.trim();
+            if !origin.is_empty() {
+                gateway.auth = gateway.auth.with_allowed_origin(origin);
+            }
+        }
+        let resume_dir = var(ENV_RESUME_DIR);
+        match ShmResumeStore::open(resume_dir.as_deref().unwrap_or(DEFAULT_SHM_RESUME_DIR)) {
+            Ok(store) => gateway.resume = Arc::new(store),
+            Err(e) if resume_dir.is_some() => {
+                return Err(format!("{}: {}", ENV_RESUME_DIR, e));
+            }
+            Err(e) => warn!("Resume store falls back to memory, not shared across restarts: {}", e),
+        }
+        Ok(gateway)
+    }
+
+    /// 接受浏览器会话 JWT（与 HTTP 接口共用同一会话服务）
+    pub fn with_sessions(mut self, sessions: Arc<SessionAuth>) -> Self {
+        self.auth = self.auth.with_sessions(sessions);
+        self
+    }
+
+    /// 接受 Key 存储中的 API Key（与签名 REST 请求共用）
+    pub fn with_key_store(mut self, key_store: Arc<RwLock<ApiKeyStore>>) -> Self {
+        self.auth = self.auth.with_key_store(key_store);
+        self
+    }
+
+    /// 共享的 API Key 权限表
+    pub fn with_entitlements(mut self, entitlements: EntitlementStore) -> Self {
+        self.entitlements = entitlements;
+        self
+    }
+
+    pub fn with_load_shedder(mut self, shedder: LoadShedder) -> Self {
+        self.shedder = Arc::new(RwLock::new(shedder));
+        self
+    }
+
+    /// 订阅 `!systemStatus` 时先发送的降级状态
+    pub fn with_degradations(mut self, degradations: SharedDegradations) -> Self {
+        self.degradations = Some(degradations);
+        self
+    }
+
+    /// 续传存储与每个会话保留的条数
+    pub fn with_resume_store(mut self, store: Arc<dyn ResumeStore>, capacity: usize) -> Self {
+        self.resume = store;
+        self.resume_capacity = capacity;
+        self
+    }
+
+    /// 开启 `cancelOnDisconnect` 的会话结束后调用
+    pub fn with_disconnect_hook(mut self, hook: DisconnectHook) -> Self {
+        self.disconnect_hook = Some(hook);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// API Key 权限表（管理端 grant / revoke 后立即作用于已有连接）
+    pub fn entitlements(&self) -> &EntitlementStore {
+        &self.entitlements
+    }
+
+    /// 降级策略（负载采样任务写入）
+    pub fn shedder(&self) -> &Arc<RwLock<LoadShedder>> {
+        &self.shedder
+    }
+
+    /// 推送总线的发送端（私有流等没有独立广播的推送源直接写入）
+    pub fn publisher(&self) -> broadcast::Sender<StreamMessage> {
+        self.hub.clone()
+    }
+
+    /// 启动转发线程，把一个推送源并入推送总线（推送源关闭后线程退出）
+    pub fn spawn_forward(
+        &self,
+        name: &str,
+        mut source: broadcast::Receiver<StreamMessage>,
+    ) -> io::Result<JoinHandle<()>> {
+        let hub = self.hub.clone();
+        let name = name.to_string();
+        thread::Builder::new().name(format!("ws-forward-{}", name)).spawn(move || {
+            loop {
+                match source.blocking_recv() {
+                    // 没有连接时无人接收，直接丢弃
+                    Ok(message) => {
+                        let _ = hub.send(message);
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket forward {} lagged, skipped {} messages", name, skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// 请求是否由本服务处理（`path` 含查询串）
+    pub fn matches(method: &str, path: &str) -> bool {
+        method == "GET" && path.split('?').next() == Some(WEBSOCKET_PATH)
+    }
+
+    /// 处理一条连接：握手鉴权，通过后收发帧直到连接断开
+    pub async fn serve<S>(&self, mut io: S, request: &[u8])
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (upgrade, identity) = match self.handshake(request) {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("🚫 WebSocket handshake rejected: {}", e);
+                if let Err(e) = io.write_all(&e.reject_response()).await {
+                    warn!("Failed to write denial response: {}", e);
+                }
+                return;
+            }
+        };
+        let key = upgrade.header("sec-websocket-key").unwrap_or_default();
+        if let Err(e) = io.write_all(&accept_response(key)).await {
+            warn!("Failed to write upgrade response: {}", e);
+            return;
+        }
+        self.start_load_sampler();
+
+        let connection = Connection::new(
+            SubscriptionManager::new(identity.clone(), self.entitlements.clone()),
+            self.open_resume(&upgrade, &identity),
+        );
+        let mut outgoing = Vec::new();
+        {
+            // 降级中的新连接先收到当前状态
+            let shedder = self.shedder.read().unwrap_or_else(PoisonError::into_inner);
+            if shedder.level() != ShedLevel::Normal {
+                outgoing.push(shedder.state_payload());
+            }
+        }
+        if let Some(last_sequence) = upgrade.query("lastSequence").and_then(|v| v.parse().ok()) {
+            outgoing.extend(connection.replay_after(last_sequence));
+        }
+        self.run(accept_transport(io).await, connection, outgoing).await;
+
+        if upgrade.query("cancelOnDisconnect") == Some("true") {
+            if let (Some(account), Some(hook)) = (identity.account_id, &self.disconnect_hook) {
+                debug!(
+                    "WebSocket session of account {} ended, cancelling on disconnect",
+                    account.0
+                );
+                hook(account);
+            }
+        }
+    }
+
+    fn handshake(
+        &self,
+        request: &[u8],
+    ) -> Result<(UpgradeRequest, SessionIdentity), HandshakeError> {
+        let upgrade = UpgradeRequest::parse(request)?;
+        let identity = self.auth.authenticate(&upgrade, self.clock.now_millis())?;
+        Ok((upgrade, identity))
+    }
+
+    /// 已鉴权且带 `sessionId` 的会话打开续传缓冲
+    fn open_resume(
+        &self,
+        upgrade: &UpgradeRequest,
+        identity: &SessionIdentity,
+    ) -> Option<ResumeBuffer> {
+        let key = ResumeKey::new(identity.account_id?, upgrade.query("sessionId")?)?;
+        ResumeBuffer::open(self.resume.clone(), key, self.resume_capacity)
+            .inspect_err(|e| warn!("Failed to open resume buffer: {}", e))
+            .ok()
+    }
+
+    async fn run<T: WsTransport>(
+        &self,
+        mut transport: T,
+        mut connection: Connection,
+        mut outgoing: Vec<String>,
+    ) {
+        let mut messages = self.hub.subscribe();
+        let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut closed_by_client = false;
+        let mut done = false;
+
+        loop {
+            for text in outgoing.drain(..) {
+                if let Err(e) = transport.send(WsMessage::Text(text)).await {
+                    debug!("WebSocket write failed: {}", e);
+                    done = true;
+                    break;
+                }
+            }
+            if done {
+                break;
+            }
+            // 接收不会读到半条消息：tungstenite 在内部缓冲未完成的帧
+            select! {
+                incoming = transport.recv() => match incoming {
+                    Some(Ok(WsMessage::Text(text))) => outgoing.extend(
+                        connection.on_command(text.as_bytes(), self.degradations.as_ref()),
+                    ),
+                    // Ping 由 tungstenite 自动应答
+                    Some(Ok(WsMessage::Ping(_) | WsMessage::Pong(_))) => {}
+                    Some(Ok(WsMessage::Close)) => {
+                        closed_by_client = true;
+                        done = true;
+                    }
+                    Some(Ok(WsMessage::Binary(_))) => {
+                        debug!("WebSocket binary message not supported, closing");
+                        done = true;
+                    }
+                    Some(Err(e)) => {
+                        debug!("WebSocket read failed: {}", e);
+                        done = true;
+                    }
+                    None => done = true,
+                },
+                message = messages.recv() => match message {
+                    Ok(message) => {
+                        let shedder = self.shedder.read().unwrap_or_else(PoisonError::into_inner);
+                        outgoing.extend(connection.on_message(message, &shedder, Instant::now()));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("WebSocket connection lagged, skipped {} messages", skipped);
+                    }
+                    Err(RecvError::Closed) => done = true,
+                },
+                _ = flush.tick() => {
+                    let shedder = self.shedder.read().unwrap_or_else(PoisonError::into_inner);
+                    outgoing.extend(connection.flush(&shedder, Instant::now()));
+                }
+            }
+        }
+        if let Err(e) = transport.close().await {
+            debug!("WebSocket close failed: {}", e);
+        }
+
+        // 客户端正常退出时不再续传
+        if closed_by_client {
+            if let Some(buffer) = connection.resume.take() {
+                if let Err(e) = buffer.close() {
+                    warn!("Failed to remove resume buffer: {}", e);
+                }
+            }
+        }
+    }
+
+    fn start_load_sampler(&self) {
+        self.sampler.call_once(|| {
+            let shedder = self.shedder.clone();
+            let hub = self.hub.clone();
+            tokio::spawn(async move {
+                let mut cpu = CpuSampler::default();
+                loop {
+                    let event_loop_lag = measure_event_loop_lag(LOAD_SAMPLE_INTERVAL).await;
+                    let sample =
+                        LoadSample { event_loop_lag, cpu_pct: cpu.sample().unwrap_or(0.0) };
+                    let state = {
+                        let mut shedder = shedder.write().unwrap_or_else(PoisonError::into_inner);
+                        shedder.observe(sample).map(|level| (level, shedder.state_payload()))
+                    };
+                    if let Some((level, payload)) = state {
+                        warn!("⚠️  WebSocket load shedding level: {}", level.as_str());
+                        let stream = SHED_STATE_STREAM.to_string();
+                        let _ = hub.send(StreamMessage { stream, payload });
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// 单条连接的订阅、合并与续传状态
+struct Connection {
+    subscriptions: SubscriptionManager,
+    resume: Option<ResumeBuffer>,
+    /// 流 -> 上次推送时间
+    last_sent: HashMap<String, Instant>,
+    /// 流 -> 合并间隔内的最新消息
+    pending: HashMap<String, String>,
+}
+
+impl Connection {
+    fn new(subscriptions: SubscriptionManager, resume: Option<ResumeBuffer>) -> Self {
+        Self { subscriptions, resume, last_sent: HashMap::new(), pending: HashMap::new() }
+    }
+
+    /// 处理订阅命令，返回应答
+    fn on_command(
+        &mut self,
+        payload: &[u8],
+        degradations: Option<&SharedDegradations>,
+    ) -> Vec<String> {
+        let Ok(command) = serde_json::from_slice::<ClientCommand>(payload) else {
+            return vec![error_response(0, "Invalid request")];
+        };
+        let streams: Vec<&str> = command.params.iter().map(String::as_str).collect();
+        let id = command.id;
+        match command.method.as_str() {
+            "SUBSCRIBE" => {
+                if let Err(e) = self.subscriptions.subscribe(&streams) {
+                    return vec![e.response(id)];
+                }
+                let mut replies = vec![serde_json::json!({ "result": null, "id": id }).to_string()];
+                if let Some(registry) =
+                    degradations.filter(|_| streams.contains(&SYSTEM_STATUS_STREAM))
+                {
+                    let registry = registry.read().unwrap_or_else(PoisonError::into_inner);
+                    replies.push(SystemStatusStream::snapshot(&registry).payload);
+                }
+                replies
+            }
+            "UNSUBSCRIBE" => {
+                self.subscriptions.unsubscribe(&streams);
+                vec![serde_json::json!({ "result": null, "id": id }).to_string()]
+            }
+            "LIST_SUBSCRIPTIONS" => {
+                let streams: Vec<&str> = self.subscriptions.streams().collect();
+                vec![serde_json::json!({ "result": streams, "id": id }).to_string()]
+            }
+            method => vec![error_response(id, &format!("Unknown method {}", method))],
+        }
+    }
+
+    /// 按订阅与降级决策处理一条推送，返回需要立即发送的消息
+    fn on_message(
+        &mut self,
+        message: StreamMessage,
+        shedder: &LoadShedder,
+        now: Instant,
+    ) -> Option<String> {
+        // 降级状态推送给所有连接
+        if message.stream == SHED_STATE_STREAM {
+            return Some(message.payload);
+        }
+        if !self.subscriptions.wants(&message.stream) {
+            return None;
+        }
+        match shedder.decision(&message.stream) {
+            StreamDecision::Paused => {
+                self.pending.remove(&message.stream);
+                None
+            }
+            StreamDecision::Deliver { conflation } if conflation.is_zero() => {
+                Some(self.sequenced(&message.stream, message.payload))
+            }
+            StreamDecision::Deliver { conflation } => {
+                let due = self
+                    .last_sent
+                    .get(&message.stream)
+                    .is_none_or(|at| now.duration_since(*at) >= conflation);
+                if due {
+                    self.pending.remove(&message.stream);
+                    self.last_sent.insert(message.stream, now);
+                    Some(message.payload)
+                } else {
+                    self.pending.insert(message.stream, message.payload);
+                    None
+                }
+            }
+        }
+    }
+
+    /// 发送合并间隔已到的消息；已取消订阅或暂停的流丢弃
+    fn flush(&mut self, shedder: &LoadShedder, now: Instant) -> Vec<String> {
+        let mut due = Vec::new();
+        let mut dropped = Vec::new();
+        for stream in self.pending.keys() {
+            match shedder.decision(stream) {
+                _ if !self.subscriptions.wants(stream) => dropped.push(stream.clone()),
+                StreamDecision::Paused => dropped.push(stream.clone()),
+                StreamDecision::Deliver { conflation } => {
+                    let ready = self
+                        .last_sent
+                        .get(stream)
+                        .is_none_or(|at| now.duration_since(*at) >= conflation);
+                    if ready {
+                        due.push(stream.clone());
+                    }
+                }
+            }
+        }
+        for stream in dropped {
+            self.pending.remove(&stream);
+        }
+        due.into_iter()
+            .filter_map(|stream| {
+                let payload = self.pending.remove(&stream)?;
+                self.last_sent.insert(stream, now);
+                Some(payload)
+            })
+            .collect()
+    }
+
+    /// 私有流推送写入续传缓冲并附带序号
+    fn sequenced(&mut self, stream: &str, payload: String) -> String {
+        let Some(buffer) =
+            self.resume.as_mut().filter(|_| stream.starts_with(ACCOUNT_STREAM_PREFIX))
+        else {
+            return payload;
+        };
+        match buffer.push(&payload) {
+            Ok(sequence) => with_sequence(&payload, sequence),
+            Err(e) => {
+                warn!("Failed to record resumable message: {}", e);
+                payload
+            }
+        }
+    }
+
+    /// 重连补发：最后收到 `last_sequence` 之后的私有流消息，续传失败时返回错误消息
+    fn replay_after(&self, last_sequence: u64) -> Vec<String> {
+        let Some(buffer) = &self.resume else {
+            return Vec::new();
+        };
+        match buffer.replay_after(last_sequence) {
+            Ok(records) => records
+                .into_iter()
+                .map(|(sequence, payload)| with_sequence(&payload, sequence))
+                .collect(),
+            Err(e) => vec![e.response()],
+        }
+    }
+}
+
+/// 在推送消息中附加续传序号 `seq`
+fn with_sequence(payload: &str, sequence: u64) -> String {
+    let Ok(mut message) = serde_json::from_str::<serde_json::Value>(payload) else {
+        return payload.to_string();
+    };
+    match message.as_object_mut() {
+        Some(object) => {
+            object.insert("seq".to_string(), sequence.into());
+            message.to_string()
+        }
+        None => payload.to_string(),
+    }
+}
+
+fn error_response(id: u64, msg: &str) -> String {
+    serde_json::json!({ "id": id, "error": { "code": INVALID_REQUEST_CODE, "msg": msg } })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader, DuplexStream};
+
+    use super::*;
+    use crate::websocket::handshake::AuthMethod;
+    use crate::websocket::load_shed::LoadShedConfig;
+    use crate::websocket::subscription::Entitlement;
+
+    const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+
+    const OPCODE_CONTINUATION: u8 = 0x0;
+    const OPCODE_TEXT: u8 = 0x1;
+    const OPCODE_CLOSE: u8 = 0x8;
+    const OPCODE_PING: u8 = 0x9;
+    const OPCODE_PONG: u8 = 0xA;
+
+    /// 带掩码的客户端帧（`fin` 为 false 时是分片的非末帧）
+    fn masked_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![(u8::from(fin) << 7) | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    fn client_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        masked_frame(true, opcode, payload)
+    }
+
+    fn upgrade_request(path: &str, extra_headers: &str) -> Vec<u8> {
+        format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\n{}\r\n",
+            path, KEY, extra_headers
+        )
+        .into_bytes()
+    }
+
+    /// 读取服务端帧的文本
+    async fn read_text(client: &mut BufReader<DuplexStream>) -> String {
+        let mut head = [0u8; 2];
+        client.read_exact(&mut head).await.unwrap();
+        let len = match head[1] {
+            126 => client.read_u16().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut payload = vec![0u8; len];
+        client.read_exact(&mut payload).await.unwrap();
+        String::from_utf8(payload).unwrap()
+    }
+
+    fn message(stream: &str, data: u64) -> StreamMessage {
+        let payload = serde_json::json!({ "stream": stream, "data": data }).to_string();
+        StreamMessage { stream: stream.to_string(), payload }
+    }
+
+    fn connection(identity: SessionIdentity, resume: Option<ResumeBuffer>) -> Connection {
+        Connection::new(SubscriptionManager::new(identity, EntitlementStore::new()), resume)
+    }
+
+    fn account(id: u64) -> SessionIdentity {
+        SessionIdentity { account_id: Some(AccountId(id)), method: AuthMethod::Token, origin: None }
+    }
+
+    #[test]
+    fn test_accept_key() {
+        assert_eq!(accept_key(KEY), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_subscribe_and_push() {
+        let auth = HandshakeAuth::new("secret").with_allowed_origin("https://app.example.com");
+        let token = auth.sign_token(AccountId(7), u64::MAX);
+        let gateway = Arc::new(WebSocketGateway::new(auth));
+
+        // Origin 不在白名单内：拒绝升级
+        let (server, client) = tokio::io::duplex(4096);
+        let request = upgrade_request("/ws", "Origin: https://evil.example.com\r\n");
+        gateway.serve(server, &request).await;
+        let mut response = String::new();
+        BufReader::new(client).read_line(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        let (server, client) = tokio::io::duplex(4096);
+        let request = upgrade_request(&format!("/ws?token={}", token), "");
+        let serving = gateway.clone();
+        tokio::spawn(async move { serving.serve(server, &request).await });
+        let mut client = BufReader::new(client);
+        let mut line = String::new();
+        client.read_line(&mut line).await.unwrap();
+        assert!(line.starts_with("HTTP/1.1 101"));
+        while line != "\r\n" {
+            line.clear();
+            client.read_line(&mut line).await.unwrap();
+        }
+
+        let subscribe = r#"{"method":"SUBSCRIBE","params":["account@8"],"id":1}"#;
+        client.get_mut().write_all(&client_frame(OPCODE_TEXT, subscribe.as_bytes())).await.unwrap();
+        assert!(read_text(&mut client).await.contains("\"code\":4001"));
+        let subscribe = r#"{"method":"SUBSCRIBE","params":["account@7"],"id":2}"#;
+        client.get_mut().write_all(&client_frame(OPCODE_TEXT, subscribe.as_bytes())).await.unwrap();
+        assert_eq!(read_text(&mut client).await, r#"{"id":2,"result":null}"#);
+
+        gateway.publisher().send(message("account@8", 1)).unwrap();
+        gateway.publisher().send(message("account@7", 2)).unwrap();
+        assert_eq!(read_text(&mut client).await, r#"{"data":2,"stream":"account@7"}"#);
+
+        client.get_mut().write_all(&client_frame(OPCODE_PING, b"p")).await.unwrap();
+        let mut pong = [0u8; 3];
+        client.read_exact(&mut pong).await.unwrap();
+        assert_eq!(pong, [0x80 | OPCODE_PONG, 1, b'p']);
+
+        // 分片发送的命令重组后处理，分片之间可以插入控制帧
+        let list = br#"{"method":"LIST_SUBSCRIPTIONS","id":3}"#;
+        let (head, tail) = list.split_at(10);
+        client.get_mut().write_all(&masked_frame(false, OPCODE_TEXT, head)).await.unwrap();
+        client.get_mut().write_all(&client_frame(OPCODE_PING, b"q")).await.unwrap();
+        client.get_mut().write_all(&masked_frame(true, OPCODE_CONTINUATION, tail)).await.unwrap();
+        client.read_exact(&mut pong).await.unwrap();
+        assert_eq!(pong, [0x80 | OPCODE_PONG, 1, b'q']);
+        assert_eq!(read_text(&mut client).await, r#"{"id":3,"result":["account@7"]}"#);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_on_disconnect() {
+        let auth = HandshakeAuth::new("secret");
+        let token = auth.sign_token(AccountId(7), u64::MAX);
+        let (cancelled, accounts) = std::sync::mpsc::channel();
+        let gateway = WebSocketGateway::new(auth).with_disconnect_hook(Arc::new(move |account| {
+            let _ = cancelled.send(account);
+        }));
+
+        // 未开启时不回调；开启后客户端关闭连接即回调
+        for (query, expected) in [("", None), ("&cancelOnDisconnect=true", Some(AccountId(7)))] {
+            let (server, mut client) = tokio::io::duplex(4096);
+            client.write_all(&client_frame(OPCODE_CLOSE, &[])).await.unwrap();
+            let request = upgrade_request(&format!("/ws?token={}{}", token, query), "");
+            gateway.serve(server, &request).await;
+            assert_eq!(accounts.try_recv().ok(), expected);
+        }
+    }
+
+    #[test]
+    fn test_conflation_and_shedding() {
+        let mut shedder = LoadShedder::new(LoadShedConfig::default());
+        let mut connection = connection(account(7), None);
+        connection.on_command(
+            br#"{"method":"SUBSCRIBE","params":["btcusdt@bookTicker","btcusdt@kline_1m"],"id":1}"#,
+            None,
+        );
+        let start = Instant::now();
+
+        // 合并间隔内只保留最新一条，到期后发送
+        assert!(connection.on_message(message("btcusdt@bookTicker", 1), &shedder, start).is_some());
+        assert!(connection.on_message(message("btcusdt@bookTicker", 2), &shedder, start).is_none());
+        assert!(connection.on_message(message("btcusdt@bookTicker", 3), &shedder, start).is_none());
+        assert!(connection.on_message(message("ethusdt@bookTicker", 1), &shedder, start).is_none());
+        assert!(connection.flush(&shedder, start).is_empty());
+        let flushed = connection.flush(&shedder, start + Duration::from_millis(100));
+        assert_eq!(flushed, vec![message("btcusdt@bookTicker", 3).payload]);
+
+        // 压力下暂停 K 线，降级状态推送给所有连接
+        let overload = LoadSample { event_loop_lag: Duration::from_millis(50), cpu_pct: 0.0 };
+        assert_eq!(shedder.observe(overload), Some(ShedLevel::Shedding));
+        let later = start + Duration::from_secs(1);
+        assert!(connection.on_message(message("btcusdt@kline_1m", 1), &shedder, later).is_none());
+        assert!(connection.flush(&shedder, later + Duration::from_secs(1)).is_empty());
+        let state = StreamMessage {
+            stream: SHED_STATE_STREAM.to_string(),
+            payload: shedder.state_payload(),
+        };
+        assert!(connection.on_message(state, &shedder, later).unwrap().contains("SHEDDING"));
+    }
+
+    #[test]
+    fn test_private_stream_resume() {
+        let store: Arc<dyn ResumeStore> = Arc::new(MemoryResumeStore::new());
+        let key = ResumeKey::new(AccountId(7), "s1").unwrap();
+        let shedder = LoadShedder::default();
+        let now = Instant::now();
+        {
+            let buffer = ResumeBuffer::open(store.clone(), key.clone(), 16).unwrap();
+            let mut connection = connection(account(7), Some(buffer));
+            connection.on_command(br#"{"method":"SUBSCRIBE","params":["account@7"],"id":1}"#, None);
+            for data in 1..=3 {
+                let sent = connection.on_message(message("account@7", data), &shedder, now);
+                assert!(sent.unwrap().contains(&format!("\"seq\":{}", data)));
+            }
+        }
+
+        // 重连后补发最后收到的序号之后的消息
+        let buffer = ResumeBuffer::open(store, key, 16).unwrap();
+        let connection = connection(account(7), Some(buffer));
+        let replayed = connection.replay_after(1);
+        assert_eq!(replayed.len(), 2);
+        assert!(replayed[0].contains("\"seq\":2") && replayed[1].contains("\"seq\":3"));
+        assert!(connection.replay_after(9)[0].contains("\"code\":4101"));
+    }
+
+    #[test]
+    fn test_entitlement_errors_and_unknown_commands() {
+        let entitlements = EntitlementStore::new();
+        let identity = SessionIdentity {
+            account_id: Some(AccountId(7)),
+            method: AuthMethod::ApiKey("key-1".to_string()),
+            origin: None,
+        };
+        let mut connection =
+            Connection::new(SubscriptionManager::new(identity, entitlements.clone()), None);
+        let subscribe = br#"{"method":"SUBSCRIBE","params":["btcusdt@l3"],"id":3}"#;
+        assert!(connection.on_command(subscribe, None)[0].contains("\"code\":4003"));
+        entitlements.grant("key-1", Entitlement::Level3);
+        assert_eq!(connection.on_command(subscribe, None), vec![r#"{"id":3,"result":null}"#]);
+
+        let list = br#"{"method":"LIST_SUBSCRIPTIONS","id":4}"#;
+        assert_eq!(connection.on_command(list, None), vec![r#"{"id":4,"result":["btcusdt@l3"]}"#]);
+        assert!(connection.on_command(b"not json", None)[0].contains("Invalid request"));
+        assert!(connection.on_command(br#"{"method":"FOO","id":5}"#, None)[0].contains("FOO"));
+    }
+}
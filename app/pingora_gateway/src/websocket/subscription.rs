@@ -0,0 +1,372 @@
+//! 订阅管理与行情权限分级
+//!
+//! 每条连接一个 [`SubscriptionManager`]，订阅前按流名称检查所需权限：
+//! - `<symbol>@depth<N>`（N > 5）、全量 `<symbol>@depth` 与 `<symbol>@heatmap`（每侧超过 5 档）：
+//!   [`Entitlement::FullDepth`]
+//! - `<symbol>@l3`：[`Entitlement::Level3`]
+//! - `<symbol>@replay...`：[`Entitlement::HistoricalReplay`]
+//!
+//! 权限按 API Key 授予，由 [`EntitlementStore`] 管理（跨连接共享，经 [`crate::http::entitlements`] 管理接口增删）；
+//! 令牌与匿名会话只能订阅基础行情。一次订阅请求中任一流被拒绝则整体拒绝
+//!
+//! 除 `<symbol>@<channel>` 流名称外，也可订阅层级主题（`spot.BTC_USDT.trades`）及其通配模式
+//! （`perp.*.funding`、`spot.BTC_USDT.>`，见 [`base_types::spot_topic`]）。订阅按段存入
+//! [`TopicTrie`]，推送时按具体主题匹配；通配模式的频道权限在推送时按具体主题检查
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use base_types::spot_topic::{TOPIC_SEPARATOR, TopicError, TopicTrie, pattern_segments};
+
+use super::handshake::{AuthMethod, SessionIdentity};
+
+/// 单连接最多订阅的流数量
+pub const MAX_STREAMS_PER_CONNECTION: usize = 1024;
+
+/// 免费深度档位上限
+pub const FREE_DEPTH_LEVELS: u32 = 5;
+
+/// 行情权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Entitlement {
+    /// 超过前 5 档的深度
+    FullDepth,
+    /// 逐笔委托（L3）
+    Level3,
+    /// 历史回放
+    HistoricalReplay,
+}
+
+impl Entitlement {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Entitlement::FullDepth => "FULL_DEPTH",
+            Entitlement::Level3 => "L3",
+            Entitlement::HistoricalReplay => "HISTORICAL_REPLAY",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "FULL_DEPTH" => Some(Entitlement::FullDepth),
+            "L3" => Some(Entitlement::Level3),
+            "HISTORICAL_REPLAY" => Some(Entitlement::HistoricalReplay),
+            _ => None,
+        }
+    }
+
+    /// 订阅该流所需的权限（基础行情返回 None）
+    ///
+    /// 层级主题取第三段起的频道（`spot.BTC_USDT.depth20` 同 `btcusdt@depth20`）
+    pub fn required_for(stream: &str) -> Option<Self> {
+        let channel = match stream.split_once('@') {
+            Some((_, channel)) => channel,
+            None => stream.splitn(3, TOPIC_SEPARATOR).nth(2)?,
+        };
+        if channel == "l3" || channel.starts_with("l3@") {
+            return Some(Entitlement::Level3);
+        }
+        if channel.starts_with("replay") {
+            return Some(Entitlement::HistoricalReplay);
+        }
+        if channel == "heatmap" {
+            return Some(Entitlement::FullDepth);
+        }
+        let depth = channel.strip_prefix("depth")?;
+        // `depth` / `depth@100ms` 为全量深度，`depth10` / `depth20@100ms` 为前 N 档
+        let levels = depth.split('@').next().unwrap_or_default();
+        match levels.parse::<u32>() {
+            Ok(levels) if levels <= FREE_DEPTH_LEVELS => None,
+            _ => Some(Entitlement::FullDepth),
+        }
+    }
+}
+
+/// 订阅错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionError {
+    /// 缺少订阅该流所需的权限
+    NotEntitled { stream: String, required: Entitlement },
+    /// 私有流不属于本会话
+    PrivateStream(String),
+    /// 超过单连接订阅上限
+    TooManyStreams,
+    /// 主题模式无效
+    InvalidTopic(TopicError),
+}
+
+impl SubscriptionError {
+    /// 错误码
+    pub fn code(&self) -> i32 {
+        match self {
+            SubscriptionError::NotEntitled { .. } => 4003,
+            SubscriptionError::PrivateStream(_) => 4001,
+            SubscriptionError::TooManyStreams => 4029,
+            SubscriptionError::InvalidTopic(_) => 4000,
+        }
+    }
+
+    /// 订阅请求的错误应答：`{"id": ..., "error": {"code": ..., "msg": ...}}`
+    pub fn response(&self, request_id: u64) -> String {
+        serde_json::json!({
+            "id": request_id,
+            "error": { "code": self.code(), "msg": self.to_string() }
+        })
+        .to_string()
+    }
+}
+
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubscriptionError::NotEntitled { stream, required } => {
+                write!(f, "Stream {} requires entitlement {}", stream, required.as_str())
+            }
+            SubscriptionError::PrivateStream(stream) => {
+                write!(f, "Not authorized for private stream {}", stream)
+            }
+            SubscriptionError::TooManyStreams => {
+                write!(f, "Too many streams (max {})", MAX_STREAMS_PER_CONNECTION)
+            }
+            SubscriptionError::InvalidTopic(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubscriptionError {}
+
+/// API Key 权限表（跨连接共享）
+#[derive(Debug, Clone, Default)]
+pub struct EntitlementStore {
+    grants: Arc<RwLock<HashMap<String, HashSet<Entitlement>>>>,
+}
+
+impl EntitlementStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 授予权限，返回是否新增
+    pub fn grant(&self, api_key: &str, entitlement: Entitlement) -> bool {
+        match self.grants.write() {
+            Ok(mut grants) => grants.entry(api_key.to_string()).or_default().insert(entitlement),
+            Err(_) => false,
+        }
+    }
+
+    /// 收回权限，返回是否存在
+    pub fn revoke(&self, api_key: &str, entitlement: Entitlement) -> bool {
+        let Ok(mut grants) = self.grants.write() else {
+            return false;
+        };
+        let Some(entitlements) = grants.get_mut(api_key) else {
+            return false;
+        };
+        let removed = entitlements.remove(&entitlement);
+        if entitlements.is_empty() {
+            grants.remove(api_key);
+        }
+        removed
+    }
+
+    /// 查询 API Key 的全部权限（有序）
+    pub fn entitlements_of(&self, api_key: &str) -> Vec<Entitlement> {
+        let Ok(grants) = self.grants.read() else {
+            return Vec::new();
+        };
+        let mut entitlements: Vec<Entitlement> =
+            grants.get(api_key).map(|set| set.iter().copied().collect()).unwrap_or_default();
+        entitlements.sort_unstable();
+        entitlements
+    }
+
+    pub fn has(&self, api_key: &str, entitlement: Entitlement) -> bool {
+        self.grants
+            .read()
+            .is_ok_and(|grants| grants.get(api_key).is_some_and(|set| set.contains(&entitlement)))
+    }
+}
+
+/// 单连接订阅管理
+#[derive(Debug)]
+pub struct SubscriptionManager {
+    identity: SessionIdentity,
+    entitlements: EntitlementStore,
+    streams: BTreeSet<String>,
+    /// 订阅（含通配模式）的前缀树，与 `streams` 同步
+    patterns: TopicTrie<()>,
+}
+
+impl SubscriptionManager {
+    pub fn new(identity: SessionIdentity, entitlements: EntitlementStore) -> Self {
+        Self { identity, entitlements, streams: BTreeSet::new(), patterns: TopicTrie::new() }
+    }
+
+    /// 订阅一组流（全部通过才生效）
+    pub fn subscribe(&mut self, streams: &[&str]) -> Result<(), SubscriptionError> {
+        for stream in streams {
+            pattern_segments(stream).map_err(SubscriptionError::InvalidTopic)?;
+            self.check(stream)?;
+        }
+        let added: BTreeSet<&str> =
+            streams.iter().copied().filter(|s| !self.streams.contains(*s)).collect();
+        if self.streams.len() + added.len() > MAX_STREAMS_PER_CONNECTION {
+            return Err(SubscriptionError::TooManyStreams);
+        }
+        for stream in added {
+            // 已校验，不会失败
+            let _ = self.patterns.insert(stream, ());
+            self.streams.insert(stream.to_string());
+        }
+        Ok(())
+    }
+
+    /// 取消订阅
+    pub fn unsubscribe(&mut self, streams: &[&str]) {
+        for stream in streams {
+            if self.streams.remove(*stream) {
+                self.patterns.remove(stream, &());
+            }
+        }
+    }
+
+    /// 推送前过滤：命中订阅（含通配模式）且权限仍有效（权限被收回后立即停止推送）
+    pub fn wants(&self, stream: &str) -> bool {
+        self.patterns.is_match(stream) && self.check(stream).is_ok()
+    }
+
+    /// 当前订阅（有序，通配模式按原样返回）
+    pub fn streams(&self) -> impl Iterator<Item = &str> {
+        self.streams.iter().map(String::as_str)
+    }
+
+    fn check(&self, stream: &str) -> Result<(), SubscriptionError> {
+        if !self.identity.can_receive(stream) {
+            return Err(SubscriptionError::PrivateStream(stream.to_string()));
+        }
+        let Some(required) = Entitlement::required_for(stream) else {
+            return Ok(());
+        };
+        let entitled = match &self.identity.method {
+            AuthMethod::ApiKey(api_key) => self.entitlements.has(api_key, required),
+            AuthMethod::Token | AuthMethod::Jwt | AuthMethod::Anonymous => false,
+        };
+        if entitled {
+            Ok(())
+        } else {
+            Err(SubscriptionError::NotEntitled { stream: stream.to_string(), required })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::AccountId;
+
+    use super::*;
+
+    fn api_key_session(api_key: &str) -> SessionIdentity {
+        SessionIdentity {
+            account_id: Some(AccountId(42)),
+            method: AuthMethod::ApiKey(api_key.to_string()),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_required_entitlements() {
+        assert_eq!(Entitlement::required_for("btcusdt@bookTicker"), None);
+        assert_eq!(Entitlement::required_for("btcusdt@depth5"), None);
+        assert_eq!(Entitlement::required_for("btcusdt@depth5@100ms"), None);
+        assert_eq!(Entitlement::required_for("btcusdt@depth20"), Some(Entitlement::FullDepth));
+        assert_eq!(Entitlement::required_for("btcusdt@depth@100ms"), Some(Entitlement::FullDepth));
+        assert_eq!(Entitlement::required_for("btcusdt@heatmap"), Some(Entitlement::FullDepth));
+        assert_eq!(Entitlement::required_for("btcusdt@l3"), Some(Entitlement::Level3));
+        assert_eq!(
+            Entitlement::required_for("btcusdt@replay_20260101"),
+            Some(Entitlement::HistoricalReplay)
+        );
+        assert_eq!(Entitlement::parse("l3"), Some(Entitlement::Level3));
+    }
+
+    #[test]
+    fn test_subscribe_enforces_grants() {
+        let store = EntitlementStore::new();
+        let mut manager = SubscriptionManager::new(api_key_session("key-1"), store.clone());
+
+        let err = manager.subscribe(&["btcusdt@bookTicker", "btcusdt@depth20"]).unwrap_err();
+        assert_eq!(
+            err,
+            SubscriptionError::NotEntitled {
+                stream: "btcusdt@depth20".to_string(),
+                required: Entitlement::FullDepth
+            }
+        );
+        let response: serde_json::Value = serde_json::from_str(&err.response(7)).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], 4003);
+        // 整体拒绝：基础流也未订阅
+        assert_eq!(manager.streams().count(), 0);
+
+        assert!(store.grant("key-1", Entitlement::FullDepth));
+        assert!(!store.grant("key-1", Entitlement::FullDepth));
+        manager.subscribe(&["btcusdt@bookTicker", "btcusdt@depth20"]).unwrap();
+        assert!(manager.wants("btcusdt@depth20"));
+
+        // 收回后立即停止推送
+        assert!(store.revoke("key-1", Entitlement::FullDepth));
+        assert!(!manager.wants("btcusdt@depth20"));
+        assert!(manager.wants("btcusdt@bookTicker"));
+        assert!(store.entitlements_of("key-1").is_empty());
+
+        assert!(matches!(
+            manager.subscribe(&["account@43"]),
+            Err(SubscriptionError::PrivateStream(_))
+        ));
+        manager.subscribe(&["account@42"]).unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_topics() {
+        let store = EntitlementStore::new();
+        let mut manager = SubscriptionManager::new(api_key_session("key-1"), store.clone());
+        manager.subscribe(&["perp.*.funding", "spot.BTC_USDT.>", "spot.ETH_USDT.trades"]).unwrap();
+        assert!(manager.wants("perp.BTC_USDT.funding"));
+        assert!(manager.wants("perp.ETH_USDT.funding"));
+        assert!(manager.wants("spot.BTC_USDT.trades"));
+        assert!(!manager.wants("spot.ETH_USDT.kline"));
+
+        // 通配模式下的付费频道在推送时按具体主题检查权限
+        assert_eq!(
+            Entitlement::required_for("spot.BTC_USDT.depth20"),
+            Some(Entitlement::FullDepth)
+        );
+        assert!(!manager.wants("spot.BTC_USDT.depth20"));
+        assert!(manager.subscribe(&["spot.BTC_USDT.l3"]).is_err());
+        store.grant("key-1", Entitlement::FullDepth);
+        assert!(manager.wants("spot.BTC_USDT.depth20"));
+
+        manager.unsubscribe(&["spot.BTC_USDT.>"]);
+        assert!(!manager.wants("spot.BTC_USDT.trades"));
+        assert_eq!(manager.streams().count(), 2);
+        assert!(matches!(
+            manager.subscribe(&["spot.>.trades"]),
+            Err(SubscriptionError::InvalidTopic(TopicError::TailWildcardNotLast(_)))
+        ));
+    }
+
+    #[test]
+    fn test_token_session_has_no_entitlements() {
+        let store = EntitlementStore::new();
+        store.grant("key-1", Entitlement::Level3);
+        let identity = SessionIdentity {
+            account_id: Some(AccountId(42)),
+            method: AuthMethod::Token,
+            origin: None,
+        };
+        let mut manager = SubscriptionManager::new(identity, store);
+        assert!(manager.subscribe(&["btcusdt@l3"]).is_err());
+        manager.subscribe(&["btcusdt@depth5"]).unwrap();
+    }
+}
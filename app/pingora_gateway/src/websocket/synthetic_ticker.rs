@@ -0,0 +1,81 @@
+//! 合成行情推送
+//!
+//! 交叉汇率推导出的合成买卖价推送到独立的 `<symbol>@syntheticTicker` 流，
+//! 消息带 `synthetic: true` 与桥接资产，客户端据此区分不可直接成交的参考价
+
+use base_types::AssetId;
+use base_types::mark_data::spot::synthetic::SyntheticTicker;
+use tokio::sync::broadcast;
+
+use super::book_ticker::StreamMessage;
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 1024;
+
+/// 合成行情 WebSocket 流
+pub struct SyntheticTickerStream {
+    sender: broadcast::Sender<StreamMessage>,
+}
+
+impl Default for SyntheticTickerStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl SyntheticTickerStream {
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 广播一次合成行情变化，返回收到的订阅者数
+    pub fn publish(&self, ticker: &SyntheticTicker, bridge: AssetId) -> usize {
+        let symbol = ticker.symbol();
+        let stream = SyntheticTicker::stream_name(&symbol);
+        let payload = serde_json::json!({ "stream": stream, "data": ticker_json(ticker, bridge) })
+            .to_string();
+        self.sender.send(StreamMessage { stream, payload }).unwrap_or(0)
+    }
+}
+
+/// 合成行情消息体
+pub fn ticker_json(ticker: &SyntheticTicker, bridge: AssetId) -> serde_json::Value {
+    serde_json::json!({
+        "symbol": ticker.symbol(),
+        "synthetic": true,
+        "bridge": bridge.as_str(),
+        "updateId": ticker.update_id,
+        "bidPrice": ticker.bid_price.map(|price| price.to_string()),
+        "askPrice": ticker.ask_price.map(|price| price.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::Price;
+
+    use super::*;
+
+    #[test]
+    fn test_publish_synthetic_ticker() {
+        let stream = SyntheticTickerStream::default();
+        let mut receiver = stream.subscribe();
+        let ticker = SyntheticTicker {
+            base: AssetId::Eth,
+            quote: AssetId::Btc,
+            update_id: 3,
+            bid_price: Some(Price::from_f64(0.05)),
+            ask_price: None,
+        };
+        assert_eq!(stream.publish(&ticker, AssetId::Usdt), 1);
+
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, "ethbtc@syntheticTicker");
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["symbol"], "ETHBTC");
+        assert_eq!(json["data"]["synthetic"], true);
+        assert_eq!(json["data"]["bridge"], "USDT");
+        assert!(json["data"]["askPrice"].is_null());
+    }
+}
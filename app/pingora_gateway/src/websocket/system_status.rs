@@ -0,0 +1,84 @@
+//! 系统状态推送
+//!
+//! 交易对降级状态变化时向 `!systemStatus` 流广播完整的降级列表（与 `GET /api/systemStatus`
+//! 内容一致），客户端据此暂停受限操作；新连接订阅时先发送一次当前状态。
+//! 该流属于关键流，推送降级时不受影响
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use base_types::instrument::degradation::DegradationRegistry;
+use tokio::sync::broadcast;
+
+use super::book_ticker::StreamMessage;
+use crate::http::degradation::system_status_json;
+
+/// 系统状态流名称
+pub const SYSTEM_STATUS_STREAM: &str = "!systemStatus";
+
+/// 推送缓冲（慢订阅者落后超过该条数时丢弃旧消息）
+const STREAM_CAPACITY: usize = 64;
+
+/// 系统状态 WebSocket 流
+pub struct SystemStatusStream {
+    sender: broadcast::Sender<StreamMessage>,
+    /// 已广播的登记表版本
+    published_version: AtomicU64,
+}
+
+impl Default for SystemStatusStream {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { sender, published_version: AtomicU64::new(0) }
+    }
+}
+
+impl SystemStatusStream {
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.sender.subscribe()
+    }
+
+    /// 当前状态消息（新订阅时发送）
+    pub fn snapshot(registry: &DegradationRegistry) -> StreamMessage {
+        let payload = serde_json::json!({
+            "stream": SYSTEM_STATUS_STREAM,
+            "data": system_status_json(registry),
+        })
+        .to_string();
+        StreamMessage { stream: SYSTEM_STATUS_STREAM.to_string(), payload }
+    }
+
+    /// 登记表有变化时广播当前状态，返回收到的订阅者数（无变化返回 None）
+    pub fn publish(&self, registry: &DegradationRegistry) -> Option<usize> {
+        let version = registry.version();
+        if self.published_version.swap(version, Ordering::AcqRel) == version {
+            return None;
+        }
+        Some(self.sender.send(Self::snapshot(registry)).unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::instrument::degradation::DegradationCode;
+
+    use super::*;
+
+    #[test]
+    fn test_publish_on_change() {
+        let stream = SystemStatusStream::default();
+        let mut receiver = stream.subscribe();
+        let mut registry = DegradationRegistry::new();
+        assert_eq!(stream.publish(&registry), None);
+
+        registry.mark("ETHUSDT", "wallet", [DegradationCode::CancelOnly], "wallet offline", 1);
+        assert_eq!(stream.publish(&registry), Some(1));
+        assert_eq!(stream.publish(&registry), None);
+
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.stream, SYSTEM_STATUS_STREAM);
+        let json: serde_json::Value = serde_json::from_str(&message.payload).unwrap();
+        assert_eq!(json["data"]["status"], "DEGRADED");
+        assert_eq!(json["data"]["symbols"][0]["codes"][0], "CANCEL_ONLY");
+        assert_eq!(json["data"]["symbols"][0]["reason"], "wallet offline");
+    }
+}
@@ -0,0 +1,70 @@
+//! WebSocket 传输对比压测
+//!
+//! ```text
+//! WS_BENCH_CONNECTIONS=256 WS_BENCH_RATE=1000 WS_BENCH_MESSAGES=5000 \
+//! WS_BENCH_REPORT=target/ws_bench_report.json cargo bench -p websocket_sockudo
+//! ```
+//!
+//! 每种传输依次在同一负载下运行，报告写入 `WS_BENCH_REPORT`（默认 `target/ws_bench_report.json`）
+//! 并打印到标准输出。新增传输时在 `main` 中追加一次 `run_load`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use websocket_sockudo::bench::{BenchReport, LoadProfile, connect_loopback, run_load};
+
+/// 计数分配器：统计分配次数
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> u64 {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn main() {
+    let defaults = LoadProfile::default();
+    let profile = LoadProfile {
+        connections: env_or("WS_BENCH_CONNECTIONS", defaults.connections),
+        rate_per_connection: env_or("WS_BENCH_RATE", defaults.rate_per_connection),
+        messages_per_connection: env_or("WS_BENCH_MESSAGES", defaults.messages_per_connection),
+        payload_bytes: env_or("WS_BENCH_PAYLOAD", defaults.payload_bytes),
+    };
+    let report_path =
+        std::env::var("WS_BENCH_REPORT").unwrap_or_else(|_| "target/ws_bench_report.json".into());
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+    let mut report = BenchReport::default();
+    runtime.block_on(async {
+        report
+            .transports
+            .push(run_load("loopback", profile, connect_loopback, Some(allocations)).await);
+    });
+
+    let json = report.to_json();
+    println!("{}", json);
+    if let Err(e) = std::fs::write(&report_path, &json) {
+        eprintln!("failed to write {}: {}", report_path, e);
+    }
+}
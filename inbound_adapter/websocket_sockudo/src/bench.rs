@@ -0,0 +1,281 @@
+//! WebSocket 传输压测
+//!
+//! N 个连接 × 每连接 M 条/秒，客户端发送带序号的二进制消息，服务端经 [`run_session`] 原样回显，
+//! 以往返时间统计 p50/p99 延迟。每种传输只需提供建立连接的闭包，报告为 JSON，
+//! 便于比较不同传输、选定生产行情推送的实现
+//!
+//! 进程 CPU 时间读自 `/proc/self/stat`（非 Linux 为 None）；分配次数由压测二进制的
+//! 计数分配器提供
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::session::{WsHandler, run_session};
+use crate::transport::{WsMessage, WsTransport, WsTransportError};
+
+/// 压测负载
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LoadProfile {
+    /// 连接数
+    pub connections: usize,
+    /// 每连接每秒消息数
+    pub rate_per_connection: u32,
+    /// 每连接消息数
+    pub messages_per_connection: usize,
+    /// 消息大小（字节，至少 8 字节用于序号）
+    pub payload_bytes: usize,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            connections: 64,
+            rate_per_connection: 1_000,
+            messages_per_connection: 2_000,
+            payload_bytes: 128,
+        }
+    }
+}
+
+/// 延迟分位（微秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean_us: f64,
+    pub p50_us: f64,
+    pub p99_us: f64,
+    pub max_us: f64,
+}
+
+impl LatencySummary {
+    /// 由往返时间样本（纳秒）计算分位
+    pub fn from_samples(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let us = |ns: u64| ns as f64 / 1_000.0;
+        let rank = |q: f64| {
+            samples[((samples.len() as f64 * q).ceil() as usize).clamp(1, samples.len()) - 1]
+        };
+        Self {
+            samples: samples.len(),
+            mean_us: us(samples.iter().sum::<u64>() / samples.len() as u64),
+            p50_us: us(rank(0.50)),
+            p99_us: us(rank(0.99)),
+            max_us: us(samples[samples.len() - 1]),
+        }
+    }
+}
+
+/// 单个传输的压测结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransportReport {
+    pub transport: String,
+    pub profile: LoadProfile,
+    pub latency: LatencySummary,
+    /// 失败的连接数
+    pub failed_connections: usize,
+    pub elapsed_ms: u64,
+    /// 实际吞吐（条/秒）
+    pub throughput: f64,
+    /// 压测期间进程 CPU 时间（毫秒）
+    pub cpu_ms: Option<u64>,
+    /// 压测期间的内存分配次数
+    pub allocations: Option<u64>,
+}
+
+/// 压测报告
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub transports: Vec<TransportReport>,
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> String {
+        simd_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// 对一种传输施加负载
+///
+/// `connect` 每调用一次建立一条客户端连接（对端须回显二进制消息）；
+/// `allocations` 返回当前累计分配次数，未统计时传 None
+pub async fn run_load<T, F, Fut>(
+    transport: &str,
+    profile: LoadProfile,
+    connect: F,
+    allocations: Option<fn() -> u64>,
+) -> TransportReport
+where
+    T: WsTransport + 'static,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, WsTransportError>>,
+{
+    let cpu_before = process_cpu_ms();
+    let allocations_before = allocations.map(|count| count());
+    let started = Instant::now();
+
+    let mut tasks = Vec::with_capacity(profile.connections);
+    for _ in 0..profile.connections {
+        match connect().await {
+            Ok(client) => tasks.push(tokio::spawn(drive_connection(client, profile))),
+            Err(_) => tasks.push(tokio::spawn(async { Err(WsTransportError::Closed) })),
+        }
+    }
+    let mut samples = Vec::with_capacity(profile.connections * profile.messages_per_connection);
+    let mut failed_connections = 0;
+    for task in tasks {
+        match task.await {
+            Ok(Ok(rtts)) => samples.extend(rtts),
+            _ => failed_connections += 1,
+        }
+    }
+
+    let elapsed = started.elapsed();
+    let messages = samples.len();
+    TransportReport {
+        transport: transport.to_string(),
+        profile,
+        latency: LatencySummary::from_samples(samples),
+        failed_connections,
+        elapsed_ms: elapsed.as_millis() as u64,
+        throughput: messages as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        cpu_ms: cpu_before.zip(process_cpu_ms()).map(|(before, after)| after - before),
+        allocations: allocations.zip(allocations_before).map(|(count, before)| count() - before),
+    }
+}
+
+/// 单连接：按速率发送带序号的消息，等待回显并记录往返时间（纳秒）
+async fn drive_connection<T: WsTransport>(
+    mut client: T,
+    profile: LoadProfile,
+) -> Result<Vec<u64>, WsTransportError> {
+    let interval = Duration::from_secs(1) / profile.rate_per_connection.max(1);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut payload = vec![0u8; profile.payload_bytes.max(8)];
+    let mut rtts = Vec::with_capacity(profile.messages_per_connection);
+
+    for seq in 0..profile.messages_per_connection as u64 {
+        ticker.tick().await;
+        payload[..8].copy_from_slice(&seq.to_le_bytes());
+        let sent_at = Instant::now();
+        client.send(WsMessage::Binary(Bytes::copy_from_slice(&payload))).await?;
+        loop {
+            match client.recv().await {
+                Some(Ok(WsMessage::Binary(echo))) if echo.get(..8) == Some(&payload[..8]) => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e),
+                None => return Err(WsTransportError::Closed),
+            }
+        }
+        rtts.push(sent_at.elapsed().as_nanos() as u64);
+    }
+    client.close().await?;
+    Ok(rtts)
+}
+
+/// 回显处理器（压测服务端）
+#[derive(Debug, Default)]
+pub struct EchoHandler;
+
+impl WsHandler for EchoHandler {
+    fn on_text(&mut self, text: &str) -> Vec<WsMessage> {
+        vec![WsMessage::Text(text.to_string())]
+    }
+
+    fn on_binary(&mut self, data: &[u8]) -> Vec<WsMessage> {
+        vec![WsMessage::Binary(Bytes::copy_from_slice(data))]
+    }
+}
+
+/// 进程内回环传输（一对通道），作为压测基线，也用于测试
+pub struct ChannelTransport {
+    tx: Option<mpsc::Sender<WsMessage>>,
+    rx: mpsc::Receiver<WsMessage>,
+}
+
+impl ChannelTransport {
+    /// 创建一对互联的传输
+    pub fn pair(capacity: usize) -> (Self, Self) {
+        let (a_tx, a_rx) = mpsc::channel(capacity);
+        let (b_tx, b_rx) = mpsc::channel(capacity);
+        (Self { tx: Some(a_tx), rx: b_rx }, Self { tx: Some(b_tx), rx: a_rx })
+    }
+}
+
+impl WsTransport for ChannelTransport {
+    async fn send(&mut self, message: WsMessage) -> Result<(), WsTransportError> {
+        let tx = self.tx.as_ref().ok_or(WsTransportError::Closed)?;
+        tx.send(message).await.map_err(|_| WsTransportError::Closed)
+    }
+
+    async fn recv(&mut self) -> Option<Result<WsMessage, WsTransportError>> {
+        self.rx.recv().await.map(Ok)
+    }
+
+    async fn close(&mut self) -> Result<(), WsTransportError> {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(WsMessage::Close).await;
+        }
+        Ok(())
+    }
+}
+
+/// 建立一条回环连接，服务端在后台回显
+pub async fn connect_loopback() -> Result<ChannelTransport, WsTransportError> {
+    let (client, mut server) = ChannelTransport::pair(1024);
+    tokio::spawn(async move {
+        // 服务端不主动推送：保留发送端，推送通道不关闭
+        let (_pushes_tx, mut pushes) = mpsc::channel(1);
+        let _ = run_session(&mut server, &mut EchoHandler, &mut pushes).await;
+    });
+    Ok(client)
+}
+
+/// 进程累计 CPU 时间（用户态 + 内核态，毫秒）
+pub fn process_cpu_ms() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // 第 2 个字段（进程名）可能含空格，从右括号之后开始数：utime、stime 为第 14、15 个字段
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    // Linux 的 USER_HZ 固定为 100
+    Some((utime + stime) * 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let summary = LatencySummary::from_samples((1..=100).map(|i| i * 1_000).collect());
+        assert_eq!(summary.samples, 100);
+        assert_eq!((summary.p50_us, summary.p99_us, summary.max_us), (50.0, 99.0, 100.0));
+        assert_eq!(summary.mean_us, 50.5);
+        assert_eq!(LatencySummary::from_samples(Vec::new()), LatencySummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_loopback_load_report() {
+        let profile = LoadProfile {
+            connections: 4,
+            rate_per_connection: 10_000,
+            messages_per_connection: 20,
+            payload_bytes: 32,
+        };
+        let report = run_load("loopback", profile, connect_loopback, None).await;
+        assert_eq!(report.failed_connections, 0);
+        assert_eq!(report.latency.samples, 80);
+        assert!(report.latency.p50_us <= report.latency.p99_us);
+
+        let json = BenchReport { transports: vec![report] }.to_json();
+        assert!(json.contains(r#""transport":"loopback""#));
+    }
+}
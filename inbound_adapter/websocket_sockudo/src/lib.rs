@@ -0,0 +1,12 @@
+//! WebSocket 入口
+//!
+//! - [`transport`]：与底层库无关的传输抽象（sockudo / axum）
+//! - [`session`]：基于传输抽象的会话循环，网关处理逻辑只实现一次
+//! - [`bench`]：传输压测与报告
+
+pub mod bench;
+pub mod session;
+pub mod transport;
+
+pub use session::{WsHandler, run_session};
+pub use transport::{FrameTransport, WsMessage, WsTransport, WsTransportError};
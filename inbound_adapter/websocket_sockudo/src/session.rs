@@ -0,0 +1,179 @@
+//! 传输无关的会话循环
+//!
+//! 交易网关与行情网关的处理逻辑实现 [`WsHandler`]，由 [`run_session`] 驱动：
+//! 读取客户端消息交给处理器、转发服务端推送、自动应答 Ping，收到 Close 或推送通道关闭时结束
+
+use tokio::sync::mpsc;
+
+use crate::transport::{WsMessage, WsTransport, WsTransportError};
+
+/// 会话处理器
+pub trait WsHandler: Send {
+    /// 连接建立后发送的消息
+    fn on_open(&mut self) -> Vec<WsMessage> {
+        Vec::new()
+    }
+
+    /// 处理文本消息，返回应答
+    fn on_text(&mut self, text: &str) -> Vec<WsMessage>;
+
+    /// 处理二进制消息（如 SBE），返回应答
+    fn on_binary(&mut self, _data: &[u8]) -> Vec<WsMessage> {
+        Vec::new()
+    }
+
+    /// 连接结束
+    fn on_close(&mut self) {}
+}
+
+/// 驱动一个会话直到连接关闭
+///
+/// `pushes` 为服务端主动推送（如订阅的行情），发送端全部丢弃时关闭连接
+pub async fn run_session<T, H>(
+    transport: &mut T,
+    handler: &mut H,
+    pushes: &mut mpsc::Receiver<WsMessage>,
+) -> Result<(), WsTransportError>
+where
+    T: WsTransport,
+    H: WsHandler,
+{
+    let result = drive(transport, handler, pushes).await;
+    handler.on_close();
+    result
+}
+
+async fn drive<T, H>(
+    transport: &mut T,
+    handler: &mut H,
+    pushes: &mut mpsc::Receiver<WsMessage>,
+) -> Result<(), WsTransportError>
+where
+    T: WsTransport,
+    H: WsHandler,
+{
+    for message in handler.on_open() {
+        transport.send(message).await?;
+    }
+    loop {
+        let replies = tokio::select! {
+            incoming = transport.recv() => match incoming {
+                None | Some(Ok(WsMessage::Close)) => return transport.close().await,
+                Some(Err(e)) => return Err(e),
+                Some(Ok(WsMessage::Ping(payload))) => vec![WsMessage::Pong(payload)],
+                Some(Ok(WsMessage::Pong(_))) => Vec::new(),
+                Some(Ok(WsMessage::Text(text))) => handler.on_text(&text),
+                Some(Ok(WsMessage::Binary(data))) => handler.on_binary(&data),
+            },
+            push = pushes.recv() => match push {
+                Some(message) => vec![message],
+                None => return transport.close().await,
+            },
+        };
+        for message in replies {
+            transport.send(message).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use bytes::Bytes;
+
+    use super::*;
+
+    /// 内存传输：按顺序吐出预置消息，记录发送的消息
+    #[derive(Default)]
+    struct MemoryTransport {
+        incoming: VecDeque<WsMessage>,
+        sent: Vec<WsMessage>,
+        closed: bool,
+    }
+
+    impl WsTransport for MemoryTransport {
+        async fn send(&mut self, message: WsMessage) -> Result<(), WsTransportError> {
+            if self.closed {
+                return Err(WsTransportError::Closed);
+            }
+            self.sent.push(message);
+            Ok(())
+        }
+
+        async fn recv(&mut self) -> Option<Result<WsMessage, WsTransportError>> {
+            match self.incoming.pop_front() {
+                Some(message) => Some(Ok(message)),
+                // 模拟空闲连接，让推送分支有机会执行
+                None => std::future::pending().await,
+            }
+        }
+
+        async fn close(&mut self) -> Result<(), WsTransportError> {
+            self.closed = true;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct Echo {
+        closed: bool,
+    }
+
+    impl WsHandler for Echo {
+        fn on_open(&mut self) -> Vec<WsMessage> {
+            vec![WsMessage::Text("hello".to_string())]
+        }
+
+        fn on_text(&mut self, text: &str) -> Vec<WsMessage> {
+            vec![WsMessage::Text(text.to_uppercase())]
+        }
+
+        fn on_close(&mut self) {
+            self.closed = true;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_replies_and_close() {
+        let mut transport = MemoryTransport {
+            incoming: VecDeque::from(vec![
+                WsMessage::Text("ping me".to_string()),
+                WsMessage::Ping(Bytes::from_static(b"p")),
+                WsMessage::Binary(Bytes::from_static(b"ignored")),
+                WsMessage::Close,
+                WsMessage::Text("after close".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let (_push_tx, mut pushes) = mpsc::channel(8);
+        let mut handler = Echo::default();
+        run_session(&mut transport, &mut handler, &mut pushes).await.unwrap();
+
+        assert_eq!(
+            transport.sent,
+            vec![
+                WsMessage::Text("hello".to_string()),
+                WsMessage::Text("PING ME".to_string()),
+                WsMessage::Pong(Bytes::from_static(b"p")),
+            ]
+        );
+        assert!(transport.closed && handler.closed);
+        assert_eq!(transport.incoming.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_session_forwards_pushes() {
+        let mut transport = MemoryTransport::default();
+        let (push_tx, mut pushes) = mpsc::channel(8);
+        push_tx.send(WsMessage::Text("tick-1".to_string())).await.unwrap();
+        push_tx.send(WsMessage::Text("tick-2".to_string())).await.unwrap();
+        drop(push_tx);
+        let mut handler = Echo::default();
+        run_session(&mut transport, &mut handler, &mut pushes).await.unwrap();
+
+        assert_eq!(transport.sent.len(), 3);
+        assert_eq!(transport.sent[2], WsMessage::Text("tick-2".to_string()));
+        assert!(transport.closed);
+    }
+}
@@ -0,0 +1,109 @@
+//! WebSocket 传输抽象
+//!
+//! 网关的处理逻辑只依赖 [`WsTransport`]（send / recv / close），底层可以是 sockudo（低延迟）
+//! 或 axum（功能完整）。两者的连接都是 `Stream + Sink` 形式的帧流，通过 [`FrameTransport`]
+//! 加一对帧转换函数接入，不需要为每个库重复实现传输
+
+use std::fmt;
+use std::future::Future;
+
+use bytes::Bytes;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+
+/// 与底层库无关的 WebSocket 消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsMessage {
+    Text(String),
+    Binary(Bytes),
+    Ping(Bytes),
+    Pong(Bytes),
+    Close,
+}
+
+/// 传输错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsTransportError {
+    /// 连接已关闭
+    Closed,
+    /// 底层库返回的错误
+    Io(String),
+}
+
+impl fmt::Display for WsTransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WsTransportError::Closed => write!(f, "WebSocket closed"),
+            WsTransportError::Io(e) => write!(f, "WebSocket transport error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WsTransportError {}
+
+/// WebSocket 传输
+pub trait WsTransport: Send {
+    /// 发送一条消息
+    fn send(
+        &mut self,
+        message: WsMessage,
+    ) -> impl Future<Output = Result<(), WsTransportError>> + Send;
+
+    /// 接收下一条消息，连接关闭时返回 None
+    fn recv(&mut self) -> impl Future<Output = Option<Result<WsMessage, WsTransportError>>> + Send;
+
+    /// 发送关闭帧并关闭连接
+    fn close(&mut self) -> impl Future<Output = Result<(), WsTransportError>> + Send;
+}
+
+/// 基于 `Stream + Sink` 帧流的传输
+///
+/// `encode` / `decode` 负责底层库消息类型与 [`WsMessage`] 的互转；
+/// `decode` 返回 None 的帧（如分片中间帧）直接跳过
+pub struct FrameTransport<S, M> {
+    inner: S,
+    encode: fn(WsMessage) -> M,
+    decode: fn(M) -> Option<WsMessage>,
+}
+
+impl<S, M> FrameTransport<S, M> {
+    pub fn new(inner: S, encode: fn(WsMessage) -> M, decode: fn(M) -> Option<WsMessage>) -> Self {
+        Self { inner, encode, decode }
+    }
+
+    /// 取回底层连接
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, M, E> WsTransport for FrameTransport<S, M>
+where
+    S: Stream<Item = Result<M, E>> + Sink<M, Error = E> + Unpin + Send,
+    M: Send,
+    E: fmt::Display,
+{
+    async fn send(&mut self, message: WsMessage) -> Result<(), WsTransportError> {
+        let frame = (self.encode)(message);
+        self.inner.send(frame).await.map_err(|e| WsTransportError::Io(e.to_string()))
+    }
+
+    async fn recv(&mut self) -> Option<Result<WsMessage, WsTransportError>> {
+        loop {
+            match self.inner.next().await? {
+                Ok(frame) => {
+                    if let Some(message) = (self.decode)(frame) {
+                        return Some(Ok(message));
+                    }
+                }
+                Err(e) => return Some(Err(WsTransportError::Io(e.to_string()))),
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), WsTransportError> {
+        let frame = (self.encode)(WsMessage::Close);
+        // 对端可能已先关闭，关闭帧发送失败不视为错误
+        let _ = self.inner.send(frame).await;
+        self.inner.close().await.map_err(|e| WsTransportError::Io(e.to_string()))
+    }
+}
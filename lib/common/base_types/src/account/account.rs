@@ -1,4 +1,12 @@
 //! 账户实体定义
+//!
+//! 账户状态由 [`AccountStatusRegistry`] 按命令流转，每次变更连同原因写入审计日志：
+//! - 暂停（Suspended）：禁止下单，允许撤单与提现
+//! - 冻结（Frozen）：禁止一切操作
+//! - 恢复：暂停或冻结的账户回到正常；注销的账户不可再变更
+
+use std::collections::HashMap;
+use std::fmt;
 
 use crate::{AccountId, Timestamp, UserId};
 
@@ -52,6 +60,23 @@ impl Account {
         self.updated_at = now;
     }
 
+    /// 暂停交易（允许撤单）
+    pub fn suspend(&mut self, now: Timestamp) {
+        self.status = AccountStatus::Suspended;
+        self.updated_at = now;
+    }
+
+    /// 按命令变更状态
+    pub fn apply(
+        &mut self,
+        command: AccountStatusCommand,
+        now: Timestamp,
+    ) -> Result<(), AccountStatusError> {
+        self.status = self.status.transition(self.id, command)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
     /// 关闭账户
     pub fn close(&mut self, now: Timestamp) {
         self.status = AccountStatus::Closed;
@@ -79,8 +104,240 @@ pub enum AccountType {
 pub enum AccountStatus {
     /// 正常
     Active = 0,
-    /// 冻结（禁止交易）
+    /// 冻结（禁止一切操作）
     Frozen = 1,
     /// 注销
     Closed = 2,
+    /// 暂停（禁止下单，允许撤单与提现）
+    Suspended = 3,
+}
+
+impl AccountStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            AccountStatus::Active => "ACTIVE",
+            AccountStatus::Frozen => "FROZEN",
+            AccountStatus::Closed => "CLOSED",
+            AccountStatus::Suspended => "SUSPENDED",
+        }
+    }
+
+    /// 是否允许下单
+    #[inline]
+    pub const fn can_trade(self) -> bool {
+        matches!(self, AccountStatus::Active)
+    }
+
+    /// 是否允许撤单
+    #[inline]
+    pub const fn can_cancel(self) -> bool {
+        matches!(self, AccountStatus::Active | AccountStatus::Suspended)
+    }
+
+    /// 是否允许提现
+    #[inline]
+    pub const fn can_withdraw(self) -> bool {
+        matches!(self, AccountStatus::Active | AccountStatus::Suspended)
+    }
+
+    /// 命令执行后的状态
+    pub fn transition(
+        self,
+        account_id: AccountId,
+        command: AccountStatusCommand,
+    ) -> Result<AccountStatus, AccountStatusError> {
+        if self == AccountStatus::Closed {
+            return Err(AccountStatusError::Closed(account_id));
+        }
+        let to = command.target();
+        if to == self {
+            return Err(AccountStatusError::Unchanged { account_id, status: self });
+        }
+        Ok(to)
+    }
+}
+
+/// 账户状态命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatusCommand {
+    /// 暂停交易
+    Suspend,
+    /// 冻结
+    Freeze,
+    /// 恢复正常
+    Reinstate,
+}
+
+impl AccountStatusCommand {
+    /// 目标状态
+    pub const fn target(self) -> AccountStatus {
+        match self {
+            AccountStatusCommand::Suspend => AccountStatus::Suspended,
+            AccountStatusCommand::Freeze => AccountStatus::Frozen,
+            AccountStatusCommand::Reinstate => AccountStatus::Active,
+        }
+    }
+}
+
+/// 账户状态变更错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountStatusError {
+    /// 账户已注销
+    Closed(AccountId),
+    /// 已处于目标状态
+    Unchanged { account_id: AccountId, status: AccountStatus },
+    /// 未填写原因
+    MissingReason,
+}
+
+impl fmt::Display for AccountStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountStatusError::Closed(account_id) => {
+                write!(f, "Account {} is closed", account_id.0)
+            }
+            AccountStatusError::Unchanged { account_id, status } => {
+                write!(f, "Account {} is already {}", account_id.0, status.as_str())
+            }
+            AccountStatusError::MissingReason => write!(f, "A reason is required"),
+        }
+    }
+}
+
+impl std::error::Error for AccountStatusError {}
+
+/// 账户状态变更审计记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountStatusChange {
+    pub account_id: AccountId,
+    pub from: AccountStatus,
+    pub to: AccountStatus,
+    pub reason: String,
+    pub operator: String,
+    pub timestamp: Timestamp,
+}
+
+/// 账户状态登记表（未登记的账户为正常）
+#[derive(Debug, Default)]
+pub struct AccountStatusRegistry {
+    statuses: HashMap<AccountId, AccountStatus>,
+    /// 只追加的审计日志
+    audit_log: Vec<AccountStatusChange>,
+}
+
+impl AccountStatusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self, account_id: AccountId) -> AccountStatus {
+        self.statuses.get(&account_id).copied().unwrap_or(AccountStatus::Active)
+    }
+
+    /// 登记已有账户的状态（从账户服务加载，不记审计）
+    pub fn load(&mut self, account: &Account) {
+        self.statuses.insert(account.id, account.status);
+    }
+
+    /// 执行状态命令并记录审计，返回审计记录
+    pub fn apply(
+        &mut self,
+        account_id: AccountId,
+        command: AccountStatusCommand,
+        reason: impl Into<String>,
+        operator: impl Into<String>,
+        timestamp: Timestamp,
+    ) -> Result<AccountStatusChange, AccountStatusError> {
+        let reason = reason.into();
+        if reason.trim().is_empty() {
+            return Err(AccountStatusError::MissingReason);
+        }
+        let from = self.status(account_id);
+        let to = from.transition(account_id, command)?;
+        if to == AccountStatus::Active {
+            self.statuses.remove(&account_id);
+        } else {
+            self.statuses.insert(account_id, to);
+        }
+        let change = AccountStatusChange {
+            account_id,
+            from,
+            to,
+            reason,
+            operator: operator.into(),
+            timestamp,
+        };
+        self.audit_log.push(change.clone());
+        Ok(change)
+    }
+
+    /// 审计日志
+    pub fn audit_log(&self) -> &[AccountStatusChange] {
+        &self.audit_log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: AccountId = AccountId(7);
+
+    #[test]
+    fn test_status_permissions() {
+        assert!(AccountStatus::Active.can_trade());
+        assert!(!AccountStatus::Suspended.can_trade());
+        assert!(AccountStatus::Suspended.can_cancel());
+        assert!(AccountStatus::Suspended.can_withdraw());
+        assert!(!AccountStatus::Frozen.can_cancel());
+        assert!(!AccountStatus::Frozen.can_withdraw());
+        assert!(!AccountStatus::Closed.can_cancel());
+    }
+
+    #[test]
+    fn test_registry_transitions_and_audit() {
+        let mut registry = AccountStatusRegistry::new();
+        let change = registry
+            .apply(ACCOUNT, AccountStatusCommand::Suspend, "KYC review", "ops", Timestamp(1))
+            .unwrap();
+        assert_eq!((change.from, change.to), (AccountStatus::Active, AccountStatus::Suspended));
+        assert_eq!(
+            registry.apply(ACCOUNT, AccountStatusCommand::Suspend, "again", "ops", Timestamp(2)),
+            Err(AccountStatusError::Unchanged {
+                account_id: ACCOUNT,
+                status: AccountStatus::Suspended
+            })
+        );
+        assert_eq!(
+            registry.apply(ACCOUNT, AccountStatusCommand::Freeze, " ", "ops", Timestamp(2)),
+            Err(AccountStatusError::MissingReason)
+        );
+        registry
+            .apply(ACCOUNT, AccountStatusCommand::Freeze, "fraud", "ops", Timestamp(3))
+            .unwrap();
+        assert_eq!(registry.status(ACCOUNT), AccountStatus::Frozen);
+        registry
+            .apply(ACCOUNT, AccountStatusCommand::Reinstate, "cleared", "ops", Timestamp(4))
+            .unwrap();
+        assert_eq!(registry.status(ACCOUNT), AccountStatus::Active);
+
+        let reasons: Vec<&str> =
+            registry.audit_log().iter().map(|change| change.reason.as_str()).collect();
+        assert_eq!(reasons, ["KYC review", "fraud", "cleared"]);
+    }
+
+    #[test]
+    fn test_closed_account_cannot_be_reinstated() {
+        let mut account = Account::new(ACCOUNT, UserId(1), AccountType::Spot, Timestamp(0));
+        account.apply(AccountStatusCommand::Freeze, Timestamp(1)).unwrap();
+        account.close(Timestamp(2));
+
+        let mut registry = AccountStatusRegistry::new();
+        registry.load(&account);
+        assert_eq!(
+            registry.apply(ACCOUNT, AccountStatusCommand::Reinstate, "appeal", "ops", Timestamp(3)),
+            Err(AccountStatusError::Closed(ACCOUNT))
+        );
+        assert!(registry.audit_log().is_empty());
+    }
 }
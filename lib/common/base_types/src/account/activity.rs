@@ -0,0 +1,339 @@
+//! 账户流水（统一账本）
+//!
+//! 由结算分录按账户生成按时间排序的资金流水：成交、手续费、资金费用、划转、调整、利息。
+//! 流水ID全局单调递增，作为翻页游标（`fromId`）；同一账户的流水按记账顺序追加，
+//! 时间范围与游标定位均为二分查找
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::account::balance_change::BalanceChangeType;
+use crate::account::settlement::Settlement;
+use crate::{AccountId, AssetId, Quantity};
+
+/// 默认返回条数
+pub const DEFAULT_ACTIVITY_LIMIT: usize = 100;
+/// 最大返回条数
+pub const MAX_ACTIVITY_LIMIT: usize = 1000;
+
+/// 限制返回条数（0 或缺省取默认值）
+pub fn clamp_activity_limit(limit: Option<usize>) -> usize {
+    match limit {
+        None | Some(0) => DEFAULT_ACTIVITY_LIMIT,
+        Some(limit) => limit.min(MAX_ACTIVITY_LIMIT),
+    }
+}
+
+/// 流水类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityCategory {
+    /// 成交（含已实现盈亏结算）
+    Trade,
+    /// 手续费
+    Fee,
+    /// 资金费用
+    Funding,
+    /// 充值、提现与划转
+    Transfer,
+    /// 系统调整
+    Adjustment,
+    /// 利息
+    Interest,
+}
+
+impl ActivityCategory {
+    /// 由变更类型归类（冻结/解冻不是资金流水）
+    pub fn from_change_type(change_type: BalanceChangeType) -> Option<Self> {
+        match change_type {
+            BalanceChangeType::Trade | BalanceChangeType::Settlement => Some(Self::Trade),
+            BalanceChangeType::Fee => Some(Self::Fee),
+            BalanceChangeType::Funding => Some(Self::Funding),
+            BalanceChangeType::Deposit
+            | BalanceChangeType::Withdraw
+            | BalanceChangeType::Transfer => Some(Self::Transfer),
+            BalanceChangeType::Adjustment => Some(Self::Adjustment),
+            BalanceChangeType::Interest => Some(Self::Interest),
+            BalanceChangeType::Freeze | BalanceChangeType::Unfreeze => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Trade => "TRADE",
+            Self::Fee => "FEE",
+            Self::Funding => "FUNDING",
+            Self::Transfer => "TRANSFER",
+            Self::Adjustment => "ADJUSTMENT",
+            Self::Interest => "INTEREST",
+        }
+    }
+
+    /// 解析类别名（不区分大小写）
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "TRADE" => Some(Self::Trade),
+            "FEE" => Some(Self::Fee),
+            "FUNDING" => Some(Self::Funding),
+            "TRANSFER" => Some(Self::Transfer),
+            "ADJUSTMENT" => Some(Self::Adjustment),
+            "INTEREST" => Some(Self::Interest),
+            _ => None,
+        }
+    }
+
+    /// 解析逗号分隔的类别列表
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, ActivityQueryError> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::parse(name).ok_or_else(|| ActivityQueryError::UnknownCategory(name.into()))
+            })
+            .collect()
+    }
+}
+
+/// 流水查询错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityQueryError {
+    /// 未知的流水类别
+    UnknownCategory(String),
+    /// 起始时间晚于结束时间
+    InvalidTimeRange,
+}
+
+impl fmt::Display for ActivityQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActivityQueryError::UnknownCategory(name) => write!(f, "Unknown category: {}", name),
+            ActivityQueryError::InvalidTimeRange => write!(f, "startTime is after endTime"),
+        }
+    }
+}
+
+impl std::error::Error for ActivityQueryError {}
+
+/// 一条账户流水
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityEntry {
+    /// 流水ID（全局单调递增）
+    pub id: u64,
+    /// 来源结算ID
+    pub settlement_id: u64,
+    pub category: ActivityCategory,
+    /// 原始变更类型
+    pub change_type: BalanceChangeType,
+    pub asset_id: AssetId,
+    /// 金额（正数=入账，负数=出账）
+    pub amount: Quantity,
+    /// 记账时间（毫秒）
+    pub time: u64,
+}
+
+/// 流水查询条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityFilter {
+    /// 类别（为空表示全部）
+    pub categories: Vec<ActivityCategory>,
+    pub asset_id: Option<AssetId>,
+    /// 起始时间（毫秒，含）
+    pub start_time: Option<u64>,
+    /// 结束时间（毫秒，含）
+    pub end_time: Option<u64>,
+    /// 起始流水ID（含）
+    pub from_id: Option<u64>,
+    /// 返回条数，见 [`clamp_activity_limit`]
+    pub limit: Option<usize>,
+}
+
+impl ActivityFilter {
+    fn accepts(&self, entry: &ActivityEntry) -> bool {
+        (self.categories.is_empty() || self.categories.contains(&entry.category))
+            && self.asset_id.is_none_or(|asset_id| asset_id == entry.asset_id)
+    }
+}
+
+/// 一页流水
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityPage {
+    /// 按时间升序
+    pub entries: Vec<ActivityEntry>,
+    /// 下一页的 `fromId`（没有更多时为 None）
+    pub next_from_id: Option<u64>,
+}
+
+/// 账户流水账本
+#[derive(Debug, Default)]
+pub struct ActivityLedger {
+    accounts: HashMap<AccountId, Vec<ActivityEntry>>,
+    next_id: u64,
+}
+
+impl ActivityLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记入一笔结算，返回生成的流水条数
+    ///
+    /// 结算须按时间顺序记入；冻结/解冻与零金额分录不生成流水
+    pub fn record(&mut self, settlement: &Settlement) -> usize {
+        let time = settlement.timestamp.0 / 1_000_000;
+        let mut recorded = 0;
+        for entry in &settlement.entries {
+            let Some(category) = ActivityCategory::from_change_type(entry.change_type) else {
+                continue;
+            };
+            if entry.amount == Quantity::default() {
+                continue;
+            }
+            self.next_id += 1;
+            self.accounts.entry(entry.account_id).or_default().push(ActivityEntry {
+                id: self.next_id,
+                settlement_id: settlement.settlement_id,
+                category,
+                change_type: entry.change_type,
+                asset_id: entry.asset_id,
+                amount: entry.amount,
+                time,
+            });
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// 按条件查询账户流水
+    pub fn query(
+        &self,
+        account_id: AccountId,
+        filter: &ActivityFilter,
+    ) -> Result<ActivityPage, ActivityQueryError> {
+        if let (Some(start), Some(end)) = (filter.start_time, filter.end_time) {
+            if start > end {
+                return Err(ActivityQueryError::InvalidTimeRange);
+            }
+        }
+        let entries = self.accounts.get(&account_id).map(Vec::as_slice).unwrap_or_default();
+        let from = filter.from_id.map_or(0, |from_id| entries.partition_point(|e| e.id < from_id));
+        let start =
+            filter.start_time.map_or(0, |start| entries.partition_point(|e| e.time < start));
+        let end =
+            filter.end_time.map_or(entries.len(), |end| entries.partition_point(|e| e.time <= end));
+        let candidates = entries.get(from.max(start)..end).unwrap_or_default();
+
+        let limit = clamp_activity_limit(filter.limit);
+        let mut matched = candidates.iter().filter(|e| filter.accepts(e));
+        let page: Vec<ActivityEntry> = matched.by_ref().take(limit).copied().collect();
+        Ok(ActivityPage { entries: page, next_from_id: matched.next().map(|e| e.id) })
+    }
+
+    /// 账户流水条数
+    pub fn len(&self, account_id: AccountId) -> usize {
+        self.accounts.get(&account_id).map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Timestamp;
+
+    const ALICE: AccountId = AccountId(1);
+    const BOB: AccountId = AccountId(2);
+    const HOUSE: AccountId = AccountId(99);
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    fn ms(value: u64) -> Timestamp {
+        Timestamp(value * 1_000_000)
+    }
+
+    fn ledger() -> ActivityLedger {
+        let mut ledger = ActivityLedger::new();
+        let trade = Settlement::new(1, ms(1_000))
+            .with_entry(ALICE, AssetId::Usdt, q(-100.0), BalanceChangeType::Trade)
+            .with_entry(BOB, AssetId::Usdt, q(100.0), BalanceChangeType::Trade)
+            .with_entry(BOB, AssetId::Btc, q(-0.01), BalanceChangeType::Trade)
+            .with_entry(ALICE, AssetId::Btc, q(0.01), BalanceChangeType::Trade)
+            .with_entry(ALICE, AssetId::Usdt, q(-0.1), BalanceChangeType::Fee)
+            .with_entry(HOUSE, AssetId::Usdt, q(0.1), BalanceChangeType::Fee);
+        let funding = Settlement::new(2, ms(2_000))
+            .with_entry(ALICE, AssetId::Usdt, q(-0.5), BalanceChangeType::Funding)
+            .with_entry(BOB, AssetId::Usdt, q(0.5), BalanceChangeType::Funding);
+        let interest = Settlement::new(3, ms(3_000))
+            .with_entry(HOUSE, AssetId::Usdt, q(-0.2), BalanceChangeType::Interest)
+            .with_entry(ALICE, AssetId::Usdt, q(0.2), BalanceChangeType::Interest)
+            .with_entry(ALICE, AssetId::Usdt, q(1.0), BalanceChangeType::Freeze);
+        assert_eq!(ledger.record(&trade), 6);
+        ledger.record(&funding);
+        assert_eq!(ledger.record(&interest), 2);
+        ledger
+    }
+
+    #[test]
+    fn test_chronological_ledger_and_filters() {
+        let ledger = ledger();
+        assert_eq!(ledger.len(ALICE), 5);
+
+        let page = ledger.query(ALICE, &ActivityFilter::default()).unwrap();
+        let categories: Vec<_> = page.entries.iter().map(|e| e.category).collect();
+        assert_eq!(
+            categories,
+            vec![
+                ActivityCategory::Trade,
+                ActivityCategory::Trade,
+                ActivityCategory::Fee,
+                ActivityCategory::Funding,
+                ActivityCategory::Interest,
+            ]
+        );
+        assert_eq!((page.entries[3].time, page.next_from_id), (2_000, None));
+
+        let filter = ActivityFilter {
+            categories: ActivityCategory::parse_list("fee, funding").unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(ledger.query(ALICE, &filter).unwrap().entries.len(), 2);
+        let filter = ActivityFilter { asset_id: Some(AssetId::Btc), ..Default::default() };
+        assert_eq!(ledger.query(ALICE, &filter).unwrap().entries[0].amount, q(0.01));
+        let filter =
+            ActivityFilter { start_time: Some(1_500), end_time: Some(2_000), ..Default::default() };
+        let page = ledger.query(ALICE, &filter).unwrap();
+        assert_eq!(page.entries[0].category, ActivityCategory::Funding);
+        assert_eq!(page.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_cursor_paging() {
+        let ledger = ledger();
+        let mut filter = ActivityFilter { limit: Some(2), ..Default::default() };
+        let mut ids = Vec::new();
+        loop {
+            let page = ledger.query(ALICE, &filter).unwrap();
+            ids.extend(page.entries.iter().map(|e| e.id));
+            match page.next_from_id {
+                Some(from_id) => filter.from_id = Some(from_id),
+                None => break,
+            }
+        }
+        let all: Vec<_> = ledger
+            .query(ALICE, &ActivityFilter::default())
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, all);
+
+        assert!(ledger.query(AccountId(7), &ActivityFilter::default()).unwrap().entries.is_empty());
+        let invalid =
+            ActivityFilter { start_time: Some(2), end_time: Some(1), ..Default::default() };
+        assert_eq!(ledger.query(ALICE, &invalid), Err(ActivityQueryError::InvalidTimeRange));
+        assert_eq!(
+            ActivityCategory::parse_list("trade,bogus"),
+            Err(ActivityQueryError::UnknownCategory("bogus".to_string()))
+        );
+    }
+}
@@ -0,0 +1,648 @@
+//! API Key 生命周期
+//!
+//! - 创建：按账户签发，指定权限范围（只读 / 交易 / 提现）与可选到期时间
+//! - 轮换：签发继任 Key（权限、备注、到期时间不变），旧 Key 在宽限期内继续可用
+//! - 吊销：立即失效
+//! - 使用：鉴权成功后记录最近使用时间，由 [`ApiKeyStore::flush_last_used`] 批量回写仓储
+//!
+//! 每次变更写穿到 [`ApiKeyRepo`] 并产生 [`ApiKeyEvent`]，由调用方发布到
+//! `ApiKeyEventLog`；其他网关以 [`ApiKeyStore::apply_event`] 更新本地副本：
+//! 吊销立即生效，创建与轮换从共享仓储加载新 Key（事件不携带密钥）
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use crate::{AccountId, Timestamp};
+
+/// 默认轮换宽限期：旧 Key 在轮换后继续可用的时长
+pub const DEFAULT_ROTATION_GRACE: Duration = Duration::from_secs(24 * 3600);
+
+/// 权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ApiKeyScope {
+    /// 查询余额、订单、成交
+    Read = 1,
+    /// 下单、撤单
+    Trade = 2,
+    /// 提现
+    Withdraw = 4,
+}
+
+impl ApiKeyScope {
+    pub const ALL: [ApiKeyScope; 3] =
+        [ApiKeyScope::Read, ApiKeyScope::Trade, ApiKeyScope::Withdraw];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "READ",
+            ApiKeyScope::Trade => "TRADE",
+            ApiKeyScope::Withdraw => "WITHDRAW",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str().eq_ignore_ascii_case(s))
+    }
+}
+
+/// 权限集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApiKeyScopes(u8);
+
+impl ApiKeyScopes {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn with(self, scope: ApiKeyScope) -> Self {
+        Self(self.0 | scope as u8)
+    }
+
+    pub const fn contains(self, scope: ApiKeyScope) -> bool {
+        self.0 & scope as u8 != 0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = ApiKeyScope> {
+        ApiKeyScope::ALL.into_iter().filter(move |scope| self.contains(*scope))
+    }
+}
+
+impl FromIterator<ApiKeyScope> for ApiKeyScopes {
+    fn from_iter<I: IntoIterator<Item = ApiKeyScope>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+/// Key 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyStatus {
+    Active,
+    Expired,
+    Revoked,
+}
+
+impl ApiKeyStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ApiKeyStatus::Active => "ACTIVE",
+            ApiKeyStatus::Expired => "EXPIRED",
+            ApiKeyStatus::Revoked => "REVOKED",
+        }
+    }
+}
+
+/// API Key 记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApiKey {
+    pub key_id: u64,
+    /// 公开的 Key
+    pub api_key: String,
+    /// HMAC 密钥：仓储层应加密存放，不出现在事件与列表接口中
+    pub secret: String,
+    pub account_id: AccountId,
+    pub label: String,
+    pub scopes: ApiKeyScopes,
+    pub created_at: Timestamp,
+    /// 到期时间（None 表示长期有效）
+    pub expires_at: Option<Timestamp>,
+    pub last_used_at: Option<Timestamp>,
+    pub revoked_at: Option<Timestamp>,
+    /// 轮换后的继任 Key
+    pub rotated_to: Option<String>,
+}
+
+impl ApiKey {
+    pub fn status(&self, now: Timestamp) -> ApiKeyStatus {
+        if self.revoked_at.is_some() {
+            ApiKeyStatus::Revoked
+        } else if self.expires_at.is_some_and(|expires_at| now.0 >= expires_at.0) {
+            ApiKeyStatus::Expired
+        } else {
+            ApiKeyStatus::Active
+        }
+    }
+}
+
+/// 签发参数（`api_key`、`secret` 由调用方随机生成）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewApiKey {
+    pub api_key: String,
+    pub secret: String,
+    pub account_id: AccountId,
+    pub label: String,
+    pub scopes: ApiKeyScopes,
+    pub expires_at: Option<Timestamp>,
+}
+
+/// API Key 错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeyError {
+    /// Key 不存在
+    UnknownKey(String),
+    /// Key 已存在
+    DuplicateKey(String),
+    /// Key 不属于该账户
+    NotOwner { api_key: String, account_id: AccountId },
+    /// Key 已吊销
+    Revoked(String),
+    /// Key 已过期
+    Expired(String),
+    /// Key 没有所需权限
+    InsufficientScope { api_key: String, required: ApiKeyScope },
+    /// 未指定任何权限
+    EmptyScopes,
+    /// 到期时间不晚于当前时间
+    AlreadyExpired,
+    /// 仓储读写失败
+    Storage(String),
+}
+
+impl std::fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeyError::UnknownKey(api_key) => write!(f, "Unknown API key: {}", api_key),
+            ApiKeyError::DuplicateKey(api_key) => write!(f, "Duplicate API key: {}", api_key),
+            ApiKeyError::NotOwner { api_key, account_id } => {
+                write!(f, "API key {} does not belong to account {}", api_key, account_id.0)
+            }
+            ApiKeyError::Revoked(api_key) => write!(f, "API key revoked: {}", api_key),
+            ApiKeyError::Expired(api_key) => write!(f, "API key expired: {}", api_key),
+            ApiKeyError::InsufficientScope { api_key, required } => {
+                write!(f, "API key {} lacks {} permission", api_key, required.as_str())
+            }
+            ApiKeyError::EmptyScopes => write!(f, "At least one scope is required"),
+            ApiKeyError::AlreadyExpired => write!(f, "Expiry must be in the future"),
+            ApiKeyError::Storage(e) => write!(f, "API key storage error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiKeyError {}
+
+/// API Key 仓储（多个网关共享）
+pub trait ApiKeyRepo: Send + Sync {
+    /// 新增或覆盖
+    fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError>;
+
+    fn load(&self, api_key: &str) -> Result<Option<ApiKey>, ApiKeyError>;
+
+    fn load_all(&self) -> Result<Vec<ApiKey>, ApiKeyError>;
+}
+
+impl<T: ApiKeyRepo + ?Sized> ApiKeyRepo for Arc<T> {
+    fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError> {
+        (**self).save(key)
+    }
+
+    fn load(&self, api_key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        (**self).load(api_key)
+    }
+
+    fn load_all(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        (**self).load_all()
+    }
+}
+
+/// 内存仓储
+#[derive(Debug, Default)]
+pub struct MemApiKeyRepo {
+    keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl MemApiKeyRepo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ApiKeyRepo for MemApiKeyRepo {
+    fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError> {
+        self.keys
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.api_key.clone(), key.clone());
+        Ok(())
+    }
+
+    fn load(&self, api_key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        Ok(self.keys.read().unwrap_or_else(PoisonError::into_inner).get(api_key).cloned())
+    }
+
+    fn load_all(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        Ok(self.keys.read().unwrap_or_else(PoisonError::into_inner).values().cloned().collect())
+    }
+}
+
+/// 变更类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ApiKeyEventKind {
+    Created,
+    /// 轮换：`api_key` 为旧 Key，进入宽限期
+    Rotated {
+        successor: String,
+    },
+    Revoked,
+}
+
+/// API Key 变更事件（不含密钥）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApiKeyEvent {
+    /// 本实例内递增的序号
+    pub seq: u64,
+    pub api_key: String,
+    pub account_id: AccountId,
+    pub kind: ApiKeyEventKind,
+    pub at: Timestamp,
+}
+
+/// API Key 存储：内存索引 + 仓储写穿
+pub struct ApiKeyStore {
+    repo: Arc<dyn ApiKeyRepo>,
+    keys: HashMap<String, ApiKey>,
+    next_key_id: u64,
+    next_seq: u64,
+    /// 待发布的事件
+    events: Vec<ApiKeyEvent>,
+    /// 最近使用时间尚未回写的 Key
+    dirty: HashSet<String>,
+}
+
+impl std::fmt::Debug for ApiKeyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyStore")
+            .field("keys", &self.keys.len())
+            .field("next_key_id", &self.next_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ApiKeyStore {
+    /// 从仓储加载全部 Key
+    pub fn new(repo: Arc<dyn ApiKeyRepo>) -> Result<Self, ApiKeyError> {
+        let keys: HashMap<String, ApiKey> =
+            repo.load_all()?.into_iter().map(|key| (key.api_key.clone(), key)).collect();
+        let next_key_id = keys.values().map(|key| key.key_id).max().unwrap_or(0) + 1;
+        Ok(Self { repo, keys, next_key_id, next_seq: 0, events: Vec::new(), dirty: HashSet::new() })
+    }
+
+    /// 签发新 Key
+    pub fn create(&mut self, new: NewApiKey, now: Timestamp) -> Result<ApiKey, ApiKeyError> {
+        let key = self.issue(new, now)?;
+        self.emit(&key, ApiKeyEventKind::Created, now);
+        Ok(key)
+    }
+
+    /// 轮换：签发继任 Key，旧 Key 在 `grace` 后失效（不晚于原到期时间）
+    ///
+    /// `owner` 为 None 表示管理员操作
+    pub fn rotate(
+        &mut self,
+        api_key: &str,
+        owner: Option<AccountId>,
+        successor: String,
+        secret: String,
+        grace: Duration,
+        now: Timestamp,
+    ) -> Result<ApiKey, ApiKeyError> {
+        let mut old = self.usable(api_key, owner, now)?.clone();
+        let new = self.issue(
+            NewApiKey {
+                api_key: successor,
+                secret,
+                account_id: old.account_id,
+                label: old.label.clone(),
+                scopes: old.scopes,
+                expires_at: old.expires_at,
+            },
+            now,
+        )?;
+
+        let grace_end = Timestamp(now.0.saturating_add(grace.as_nanos() as u64));
+        old.expires_at =
+            Some(old.expires_at.map_or(grace_end, |e| Timestamp(e.0.min(grace_end.0))));
+        old.rotated_to = Some(new.api_key.clone());
+        self.repo.save(&old)?;
+        self.emit(&old, ApiKeyEventKind::Rotated { successor: new.api_key.clone() }, now);
+        self.keys.insert(old.api_key.clone(), old);
+        Ok(new)
+    }
+
+    /// 吊销，立即生效；`owner` 为 None 表示管理员操作
+    pub fn revoke(
+        &mut self,
+        api_key: &str,
+        owner: Option<AccountId>,
+        now: Timestamp,
+    ) -> Result<ApiKey, ApiKeyError> {
+        let key = self.owned(api_key, owner)?;
+        if key.revoked_at.is_some() {
+            return Err(ApiKeyError::Revoked(api_key.to_string()));
+        }
+        let mut key = key.clone();
+        key.revoked_at = Some(now);
+        self.repo.save(&key)?;
+        self.dirty.remove(api_key);
+        self.emit(&key, ApiKeyEventKind::Revoked, now);
+        self.keys.insert(key.api_key.clone(), key.clone());
+        Ok(key)
+    }
+
+    /// 鉴权前检查：Key 有效且具备 `required` 权限（不记录使用）
+    pub fn check(
+        &self,
+        api_key: &str,
+        required: ApiKeyScope,
+        now: Timestamp,
+    ) -> Result<&ApiKey, ApiKeyError> {
+        let key = self.usable(api_key, None, now)?;
+        if !key.scopes.contains(required) {
+            return Err(ApiKeyError::InsufficientScope { api_key: api_key.to_string(), required });
+        }
+        Ok(key)
+    }
+
+    /// 鉴权成功后记录使用时间
+    pub fn record_use(&mut self, api_key: &str, now: Timestamp) {
+        if let Some(key) = self.keys.get_mut(api_key) {
+            key.last_used_at = Some(now);
+            self.dirty.insert(api_key.to_string());
+        }
+    }
+
+    /// 回写最近使用时间，返回回写条数
+    pub fn flush_last_used(&mut self) -> Result<usize, ApiKeyError> {
+        let mut flushed = 0;
+        for api_key in std::mem::take(&mut self.dirty) {
+            if let Some(key) = self.keys.get(&api_key) {
+                self.repo.save(key)?;
+                flushed += 1;
+            }
+        }
+        Ok(flushed)
+    }
+
+    pub fn get(&self, api_key: &str) -> Option<&ApiKey> {
+        self.keys.get(api_key)
+    }
+
+    /// 账户的全部 Key（按签发顺序）
+    pub fn list(&self, account_id: AccountId) -> Vec<&ApiKey> {
+        let mut keys: Vec<&ApiKey> =
+            self.keys.values().filter(|key| key.account_id == account_id).collect();
+        keys.sort_by_key(|key| key.key_id);
+        keys
+    }
+
+    /// 取出待发布的事件
+    pub fn drain_events(&mut self) -> Vec<ApiKeyEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 应用其他实例发布的事件
+    pub fn apply_event(&mut self, event: &ApiKeyEvent) -> Result<(), ApiKeyError> {
+        match &event.kind {
+            ApiKeyEventKind::Revoked => {
+                // 先在本地失效，不等仓储
+                if let Some(key) = self.keys.get_mut(&event.api_key) {
+                    key.revoked_at.get_or_insert(event.at);
+                }
+                self.reload(&event.api_key)
+            }
+            ApiKeyEventKind::Created => self.reload(&event.api_key),
+            ApiKeyEventKind::Rotated { successor } => {
+                self.reload(successor)?;
+                self.reload(&event.api_key)
+            }
+        }
+    }
+
+    fn reload(&mut self, api_key: &str) -> Result<(), ApiKeyError> {
+        if let Some(mut key) = self.repo.load(api_key)? {
+            if let Some(local) = self.keys.get(api_key) {
+                // 仓储尚未写入吊销时保留本地吊销
+                key.revoked_at = key.revoked_at.or(local.revoked_at);
+                if local.last_used_at.map(|t| t.0) > key.last_used_at.map(|t| t.0) {
+                    key.last_used_at = local.last_used_at;
+                }
+            }
+            self.next_key_id = self.next_key_id.max(key.key_id + 1);
+            self.keys.insert(key.api_key.clone(), key);
+        }
+        Ok(())
+    }
+
+    fn issue(&mut self, new: NewApiKey, now: Timestamp) -> Result<ApiKey, ApiKeyError> {
+        if new.scopes.is_empty() {
+            return Err(ApiKeyError::EmptyScopes);
+        }
+        if new.expires_at.is_some_and(|expires_at| expires_at.0 <= now.0) {
+            return Err(ApiKeyError::AlreadyExpired);
+        }
+        if self.keys.contains_key(&new.api_key) {
+            return Err(ApiKeyError::DuplicateKey(new.api_key));
+        }
+        let key = ApiKey {
+            key_id: self.next_key_id,
+            api_key: new.api_key,
+            secret: new.secret,
+            account_id: new.account_id,
+            label: new.label,
+            scopes: new.scopes,
+            created_at: now,
+            expires_at: new.expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            rotated_to: None,
+        };
+        self.repo.save(&key)?;
+        self.next_key_id += 1;
+        self.keys.insert(key.api_key.clone(), key.clone());
+        Ok(key)
+    }
+
+    fn owned(&self, api_key: &str, owner: Option<AccountId>) -> Result<&ApiKey, ApiKeyError> {
+        let key =
+            self.keys.get(api_key).ok_or_else(|| ApiKeyError::UnknownKey(api_key.to_string()))?;
+        match owner {
+            Some(account_id) if key.account_id != account_id => {
+                Err(ApiKeyError::NotOwner { api_key: api_key.to_string(), account_id })
+            }
+            _ => Ok(key),
+        }
+    }
+
+    fn usable(
+        &self,
+        api_key: &str,
+        owner: Option<AccountId>,
+        now: Timestamp,
+    ) -> Result<&ApiKey, ApiKeyError> {
+        let key = self.owned(api_key, owner)?;
+        match key.status(now) {
+            ApiKeyStatus::Active => Ok(key),
+            ApiKeyStatus::Expired => Err(ApiKeyError::Expired(api_key.to_string())),
+            ApiKeyStatus::Revoked => Err(ApiKeyError::Revoked(api_key.to_string())),
+        }
+    }
+
+    fn emit(&mut self, key: &ApiKey, kind: ApiKeyEventKind, now: Timestamp) {
+        self.next_seq += 1;
+        self.events.push(ApiKeyEvent {
+            seq: self.next_seq,
+            api_key: key.api_key.clone(),
+            account_id: key.account_id,
+            kind,
+            at: now,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: AccountId = AccountId(10);
+    const SECOND: u64 = 1_000_000_000;
+
+    fn trade_scopes() -> ApiKeyScopes {
+        [ApiKeyScope::Read, ApiKeyScope::Trade].into_iter().collect()
+    }
+
+    fn store(repo: &Arc<MemApiKeyRepo>) -> ApiKeyStore {
+        ApiKeyStore::new(repo.clone()).unwrap()
+    }
+
+    fn create(store: &mut ApiKeyStore, api_key: &str, expires_at: Option<Timestamp>) -> ApiKey {
+        let new = NewApiKey {
+            api_key: api_key.to_string(),
+            secret: format!("{}-secret", api_key),
+            account_id: ACCOUNT,
+            label: "bot".to_string(),
+            scopes: trade_scopes(),
+            expires_at,
+        };
+        store.create(new, Timestamp(0)).unwrap()
+    }
+
+    #[test]
+    fn test_create_scopes_expiry_and_last_used() {
+        let repo = Arc::new(MemApiKeyRepo::new());
+        let mut store = store(&repo);
+        let key = create(&mut store, "k1", Some(Timestamp(100 * SECOND)));
+        assert_eq!(key.key_id, 1);
+        assert_eq!(store.drain_events()[0].kind, ApiKeyEventKind::Created);
+
+        assert!(store.check("k1", ApiKeyScope::Trade, Timestamp(SECOND)).is_ok());
+        assert_eq!(
+            store.check("k1", ApiKeyScope::Withdraw, Timestamp(SECOND)),
+            Err(ApiKeyError::InsufficientScope {
+                api_key: "k1".to_string(),
+                required: ApiKeyScope::Withdraw
+            })
+        );
+        assert_eq!(
+            store.check("k1", ApiKeyScope::Read, Timestamp(100 * SECOND)),
+            Err(ApiKeyError::Expired("k1".to_string()))
+        );
+        let new = NewApiKey {
+            api_key: "k2".to_string(),
+            secret: "s".to_string(),
+            account_id: ACCOUNT,
+            label: String::new(),
+            scopes: ApiKeyScopes::empty(),
+            expires_at: None,
+        };
+        assert_eq!(store.create(new, Timestamp(0)), Err(ApiKeyError::EmptyScopes));
+
+        // 最近使用时间批量回写，重启后可见
+        store.record_use("k1", Timestamp(5 * SECOND));
+        assert_eq!(repo.load("k1").unwrap().unwrap().last_used_at, None);
+        assert_eq!(store.flush_last_used(), Ok(1));
+        let reloaded = self::store(&repo);
+        assert_eq!(reloaded.get("k1").unwrap().last_used_at, Some(Timestamp(5 * SECOND)));
+        assert_eq!(create(&mut self::store(&repo), "k3", None).key_id, 2);
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_key_for_grace_period() {
+        let repo = Arc::new(MemApiKeyRepo::new());
+        let mut store = store(&repo);
+        create(&mut store, "old", None);
+        store.drain_events();
+
+        assert!(matches!(
+            store.rotate(
+                "old",
+                Some(AccountId(99)),
+                "new".into(),
+                "s".into(),
+                DEFAULT_ROTATION_GRACE,
+                Timestamp(0)
+            ),
+            Err(ApiKeyError::NotOwner { .. })
+        ));
+        let now = Timestamp(10 * SECOND);
+        let new = store
+            .rotate("old", Some(ACCOUNT), "new".into(), "s2".into(), Duration::from_secs(60), now)
+            .unwrap();
+        assert_eq!((new.scopes, new.label.as_str()), (trade_scopes(), "bot"));
+        assert_eq!(
+            store.drain_events().iter().map(|e| e.kind.clone()).collect::<Vec<_>>(),
+            vec![ApiKeyEventKind::Rotated { successor: "new".to_string() }]
+        );
+
+        assert!(store.check("old", ApiKeyScope::Trade, Timestamp(69 * SECOND)).is_ok());
+        assert!(store.check("old", ApiKeyScope::Trade, Timestamp(70 * SECOND)).is_err());
+        assert!(store.check("new", ApiKeyScope::Trade, Timestamp(70 * SECOND)).is_ok());
+        assert_eq!(repo.load("old").unwrap().unwrap().rotated_to.as_deref(), Some("new"));
+        assert_eq!(store.list(ACCOUNT).len(), 2);
+    }
+
+    #[test]
+    fn test_revocation_propagates_to_replica() {
+        let repo = Arc::new(MemApiKeyRepo::new());
+        let mut primary = store(&repo);
+        let mut replica = store(&repo);
+
+        create(&mut primary, "k1", None);
+        for event in primary.drain_events() {
+            replica.apply_event(&event).unwrap();
+        }
+        assert!(replica.check("k1", ApiKeyScope::Read, Timestamp(SECOND)).is_ok());
+
+        // 副本在仓储写入前收到吊销事件也立即失效
+        let event = ApiKeyEvent {
+            seq: 9,
+            api_key: "k1".to_string(),
+            account_id: ACCOUNT,
+            kind: ApiKeyEventKind::Revoked,
+            at: Timestamp(2 * SECOND),
+        };
+        replica.apply_event(&event).unwrap();
+        assert_eq!(
+            replica.check("k1", ApiKeyScope::Read, Timestamp(3 * SECOND)),
+            Err(ApiKeyError::Revoked("k1".to_string()))
+        );
+
+        primary.revoke("k1", None, Timestamp(2 * SECOND)).unwrap();
+        assert_eq!(primary.drain_events()[0].kind, ApiKeyEventKind::Revoked);
+        assert_eq!(
+            primary.revoke("k1", None, Timestamp(3 * SECOND)),
+            Err(ApiKeyError::Revoked("k1".to_string()))
+        );
+        assert_eq!(
+            self::store(&repo).get("k1").unwrap().status(Timestamp(0)),
+            ApiKeyStatus::Revoked
+        );
+    }
+}
@@ -67,12 +67,34 @@ pub struct Balance {
     /// 冻结余额（已锁定用于挂单、保证金）
     /// 使用 Price 类型保证 8 位小数精度
     pub frozen: Quantity,
+    /// 是否启用持仓成本追踪
+    pub cost_tracking: bool,
+    /// 持仓成本（计价资产金额，仅 cost_tracking 时由结算维护）
+    pub cost_basis: Quantity,
+    /// 累计已实现盈亏（计价资产金额，仅 cost_tracking 时由结算维护）
+    pub realized_pnl: Quantity,
     /// 乐观锁版本号（每次修改 +1）
     pub version: u64,
     /// 最后更新时间
     pub updated_at: Timestamp,
 }
 
+/// 持仓成本视图（现货 PnL 展示用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BalanceCostBasis {
+    pub account_id: AccountId,
+    pub asset_id: AssetId,
+    /// 持有数量（可用 + 冻结）
+    pub quantity: Quantity,
+    /// 持仓成本
+    pub cost_basis: Quantity,
+    /// 平均取得价格（无持仓时为 None）
+    pub avg_cost_price: Option<Price>,
+    /// 累计已实现盈亏
+    pub realized_pnl: Quantity,
+}
+
 impl Balance {
     /// 创建新余额记录
     pub fn new(account_id: AccountId, asset_id: AssetId, now: Timestamp) -> Self {
@@ -82,6 +104,9 @@ impl Balance {
             asset_id,
             available: Quantity::default(),
             frozen: Quantity::default(),
+            cost_tracking: false,
+            cost_basis: Quantity::default(),
+            realized_pnl: Quantity::default(),
             version: 0,
             updated_at: now,
         }
@@ -100,6 +125,9 @@ impl Balance {
             asset_id,
             available: Quantity::from_raw(available),
             frozen: Quantity::default(),
+            cost_tracking: false,
+            cost_basis: Quantity::default(),
+            realized_pnl: Quantity::default(),
             version: 0,
             updated_at: now,
         }
@@ -334,6 +362,70 @@ impl Balance {
     pub fn is_empty(&self) -> bool {
         self.available.is_zero() && self.frozen.is_zero()
     }
+
+    /// 启用持仓成本追踪，已有余额以 `initial_cost` 作为成本
+    pub fn with_cost_tracking(mut self, initial_cost: Quantity) -> Self {
+        self.cost_tracking = true;
+        self.cost_basis = initial_cost;
+        self
+    }
+
+    /// 持有总量（可用 + 冻结）
+    #[inline]
+    pub fn total(&self) -> Quantity {
+        self.available + self.frozen
+    }
+
+    /// 平均取得价格（未启用追踪或无持仓时为 None）
+    pub fn avg_cost_price(&self) -> Option<Price> {
+        let total = self.total();
+        if !self.cost_tracking || total.is_zero() {
+            return None;
+        }
+        Some(self.cost_basis / total)
+    }
+
+    /// 买入结算：按成交价累加成本
+    ///
+    /// 与余额入账顺序无关，只依赖成交数量与价格
+    #[inline]
+    pub fn record_acquisition(&mut self, quantity: Quantity, price: Price) {
+        if self.cost_tracking {
+            self.cost_basis += quantity * price;
+        }
+    }
+
+    /// 卖出结算：按平均成本结转，返回本笔已实现盈亏并计入累计
+    ///
+    /// 须在余额扣减前调用，平均成本按扣减前的持有总量计算
+    pub fn record_disposal(&mut self, quantity: Quantity, price: Price) -> Option<Price> {
+        let avg_cost = self.avg_cost_price()?;
+        let released = if quantity >= self.total() { self.cost_basis } else { quantity * avg_cost };
+        self.cost_basis -= released;
+        let pnl = quantity * price - released;
+        self.realized_pnl += pnl;
+        Some(pnl)
+    }
+
+    /// 按标记价格计算未实现盈亏
+    pub fn unrealized_pnl(&self, mark_price: Price) -> Option<Price> {
+        self.avg_cost_price().map(|_| self.total() * mark_price - self.cost_basis)
+    }
+
+    /// 持仓成本视图（未启用追踪时为 None；清仓后仍保留已实现盈亏）
+    pub fn cost_basis_view(&self) -> Option<BalanceCostBasis> {
+        if !self.cost_tracking {
+            return None;
+        }
+        Some(BalanceCostBasis {
+            account_id: self.account_id,
+            asset_id: self.asset_id,
+            quantity: self.total(),
+            cost_basis: self.cost_basis,
+            avg_cost_price: self.avg_cost_price(),
+            realized_pnl: self.realized_pnl,
+        })
+    }
 }
 
 /// 余额操作（用于 BalanceStore）
@@ -352,3 +444,59 @@ pub enum BalanceOp {
     /// 结算盈亏（可正可负）
     SettlePnl(Price),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc_balance() -> Balance {
+        Balance::new(AccountId(1), AssetId::Btc, Timestamp(0))
+            .with_cost_tracking(Quantity::default())
+    }
+
+    #[test]
+    fn test_cost_basis_average_on_acquisition() {
+        let mut balance = btc_balance();
+
+        balance.record_acquisition(Quantity::from_f64(1.0), Price::from_f64(40000.0));
+        balance.add_balance(Quantity::from_f64(1.0), Timestamp(1));
+        balance.record_acquisition(Quantity::from_f64(1.0), Price::from_f64(50000.0));
+        balance.add_balance(Quantity::from_f64(1.0), Timestamp(2));
+
+        assert_eq!(balance.avg_cost_price().unwrap().to_f64(), 45000.0);
+        assert_eq!(balance.unrealized_pnl(Price::from_f64(46000.0)).unwrap().to_f64(), 2000.0);
+    }
+
+    #[test]
+    fn test_disposal_realizes_pnl_and_keeps_average() {
+        let mut balance = btc_balance();
+        balance.record_acquisition(Quantity::from_f64(2.0), Price::from_f64(45000.0));
+        balance.add_balance(Quantity::from_f64(2.0), Timestamp(1));
+        balance.frozen(Quantity::from_f64(0.5), Timestamp(2)).unwrap();
+
+        // 卖出前结转成本，再扣减冻结余额
+        let pnl = balance.record_disposal(Quantity::from_f64(0.5), Price::from_f64(47000.0));
+        balance.frozen2pay(Quantity::from_f64(0.5), Timestamp(3)).unwrap();
+
+        assert_eq!(pnl.unwrap().to_f64(), 1000.0);
+        assert_eq!(balance.avg_cost_price().unwrap().to_f64(), 45000.0);
+        assert_eq!(balance.cost_basis.to_f64(), 67500.0);
+
+        // 清仓后成本归零，已实现盈亏累计保留
+        balance.record_disposal(Quantity::from_f64(1.5), Price::from_f64(44000.0));
+        balance.add_balance(Quantity::from_f64(-1.5), Timestamp(4));
+        let view = balance.cost_basis_view().unwrap();
+        assert_eq!((view.quantity, view.cost_basis), (Quantity::default(), Quantity::default()));
+        assert_eq!((view.avg_cost_price, view.realized_pnl.to_f64()), (None, -500.0));
+    }
+
+    #[test]
+    fn test_untracked_balance_has_no_cost_basis() {
+        let mut balance = Balance::new(AccountId(1), AssetId::Btc, Timestamp(0));
+        balance.record_acquisition(Quantity::from_f64(1.0), Price::from_f64(40000.0));
+        balance.add_balance(Quantity::from_f64(1.0), Timestamp(1));
+
+        assert!(balance.cost_basis.is_zero());
+        assert_eq!(balance.cost_basis_view(), None);
+    }
+}
@@ -29,6 +29,12 @@ pub enum BalanceChangeType {
     Settlement = 7,
     /// 系统调整
     Adjustment = 8,
+    /// 资金费用
+    Funding = 9,
+    /// 划转（账户间、钱包间）
+    Transfer = 10,
+    /// 利息（借贷计息、理财收益）
+    Interest = 11,
 }
 
 /// Balance变更原因
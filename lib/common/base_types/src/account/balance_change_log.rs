@@ -255,6 +255,9 @@ impl BalanceChangeLog {
             6 => Some(BalanceChangeType::Fee),
             7 => Some(BalanceChangeType::Settlement),
             8 => Some(BalanceChangeType::Adjustment),
+            9 => Some(BalanceChangeType::Funding),
+            10 => Some(BalanceChangeType::Transfer),
+            11 => Some(BalanceChangeType::Interest),
             _ => None,
         }
     }
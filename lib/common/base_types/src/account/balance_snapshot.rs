@@ -0,0 +1,615 @@
+//! 定时余额快照
+//!
+//! 按配置的周期（每小时 / 每天 / 自定义间隔）在整点边界截取全部账户的余额向量，
+//! 并标记截取时撮合引擎已处理的序列号。用途：
+//! - 利息计提：按快照余额计算区间利息
+//! - 储备金证明（PoR）：同一批次内各资产负债合计
+//! - 争议处理：查询账户在某一时刻的余额
+//!
+//! 一致性由调用方保证：须在两条已排序事件之间（即 `sequence` 对应的状态上）
+//! 传入余额，批次内所有账户对应同一序列号
+//!
+//! 批次经 [`BalanceSnapshotStore`] 持久化，重启后从存储恢复；
+//! [`spawn_balance_snapshots`] 在独立线程按周期检查并截取
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use decimal::{Decimal128, PrecisionError, PrecisionRegistry};
+
+use crate::account::balance::Balance;
+use crate::{AccountId, AssetId, Quantity, Timestamp};
+
+/// 一小时的纳秒数
+const HOUR_NANOS: u64 = 3_600_000_000_000;
+
+/// 快照周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotSchedule {
+    /// 每小时整点
+    Hourly,
+    /// 每天 UTC 零点
+    Daily,
+    /// 自定义间隔（纳秒，按 Unix 纪元对齐）
+    Every { interval_nanos: u64 },
+}
+
+impl SnapshotSchedule {
+    /// 周期长度（纳秒，至少为 1）
+    pub fn interval_nanos(self) -> u64 {
+        match self {
+            SnapshotSchedule::Hourly => HOUR_NANOS,
+            SnapshotSchedule::Daily => 24 * HOUR_NANOS,
+            SnapshotSchedule::Every { interval_nanos } => interval_nanos.max(1),
+        }
+    }
+
+    /// `now` 之后（不含）的第一个边界
+    pub fn next_after(self, now: Timestamp) -> Timestamp {
+        let interval = self.interval_nanos();
+        Timestamp((now.0 / interval + 1) * interval)
+    }
+}
+
+/// 单个资产的余额
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetBalanceEntry {
+    pub asset_id: AssetId,
+    /// 可用余额
+    pub available: Quantity,
+    /// 冻结余额
+    pub frozen: Quantity,
+}
+
+impl AssetBalanceEntry {
+    /// 总余额
+    pub fn total(&self) -> Quantity {
+        self.available + self.frozen
+    }
+}
+
+/// 账户余额快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountBalanceSnapshot {
+    pub account_id: AccountId,
+    /// 撮合引擎序列号
+    pub sequence: u64,
+    /// 快照时间（周期边界）
+    pub taken_at: Timestamp,
+    /// 余额向量（按资产ID排序）
+    pub balances: Vec<AssetBalanceEntry>,
+}
+
+impl AccountBalanceSnapshot {
+    /// 某资产余额（无记录返回 None）
+    pub fn balance(&self, asset_id: AssetId) -> Option<&AssetBalanceEntry> {
+        self.balances.iter().find(|b| b.asset_id == asset_id)
+    }
+}
+
+/// 同一时刻截取的快照批次
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceSnapshotBatch {
+    /// 撮合引擎序列号
+    pub sequence: u64,
+    /// 快照时间（周期边界）
+    pub taken_at: Timestamp,
+    /// 账户快照（按账户ID排序）
+    pub accounts: Vec<AccountBalanceSnapshot>,
+}
+
+impl BalanceSnapshotBatch {
+    /// 账户快照
+    pub fn account(&self, account_id: AccountId) -> Option<&AccountBalanceSnapshot> {
+        self.accounts
+            .binary_search_by_key(&account_id.0, |a| a.account_id.0)
+            .ok()
+            .map(|index| &self.accounts[index])
+    }
+
+    /// 某资产的负债合计（储备金证明）
+    pub fn total_liabilities(&self, asset_id: AssetId) -> Quantity {
+        self.accounts.iter().filter_map(|a| a.balance(asset_id)).map(|b| b.total()).sum()
+    }
+
+    /// 某资产的负债合计，换算为该资产链上最小计量单位，用于与链上储备逐单位核对
+    ///
+    /// 以 128 位累加，账户数多、资产发行量大时合计也不会溢出 `Quantity`
+    pub fn total_liabilities_units(
+        &self,
+        asset_id: AssetId,
+        registry: &PrecisionRegistry,
+    ) -> Result<i128, PrecisionError> {
+        let overflow = PrecisionError::Overflow { asset: asset_id.as_u32() };
+        let total = self
+            .accounts
+            .iter()
+            .filter_map(|a| a.balance(asset_id))
+            .try_fold(Decimal128::ZERO, |sum, b| {
+                sum.checked_add(b.available.to_wide())?.checked_add(b.frozen.to_wide())
+            })
+            .ok_or(overflow)?;
+        registry.to_units(asset_id.as_u32(), total)
+    }
+}
+
+/// 快照批次存储
+pub trait BalanceSnapshotStore: Send {
+    /// 保存批次（同一时间重复保存时覆盖）
+    fn save(&mut self, batch: &BalanceSnapshotBatch) -> io::Result<()>;
+
+    /// 加载全部批次（启动恢复）
+    fn load_all(&self) -> io::Result<Vec<BalanceSnapshotBatch>>;
+
+    /// 删除早于 `before` 的批次
+    fn prune_before(&mut self, before: Timestamp) -> io::Result<()>;
+}
+
+/// 内存存储（不持久化，重启后为空）
+#[derive(Debug, Default)]
+pub struct MemBalanceSnapshotStore;
+
+impl BalanceSnapshotStore for MemBalanceSnapshotStore {
+    fn save(&mut self, _batch: &BalanceSnapshotBatch) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn load_all(&self) -> io::Result<Vec<BalanceSnapshotBatch>> {
+        Ok(Vec::new())
+    }
+
+    fn prune_before(&mut self, _before: Timestamp) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 快照文件表头
+const CSV_HEADER: &str = "account_id,asset_id,available,frozen";
+
+/// 目录存储：每个批次一个 CSV 文件 `{taken_at}-{sequence}.csv`
+///
+/// 余额写原始整数（8 位小数定点），先写临时文件再改名，崩溃不会留下半个批次
+#[derive(Debug)]
+pub struct CsvBalanceSnapshotStore {
+    dir: PathBuf,
+}
+
+impl CsvBalanceSnapshotStore {
+    /// 打开目录（不存在则创建）
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// 目录中的批次文件：(快照时间, 序列号, 路径)
+    fn files(&self) -> io::Result<Vec<(u64, u64, PathBuf)>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let parsed = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".csv"))
+                .and_then(|stem| stem.split_once('-'))
+                .and_then(|(taken_at, sequence)| {
+                    Some((taken_at.parse().ok()?, sequence.parse().ok()?))
+                });
+            if let Some((taken_at, sequence)) = parsed {
+                files.push((taken_at, sequence, path));
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn read_batch(
+        taken_at: u64,
+        sequence: u64,
+        path: &PathBuf,
+    ) -> io::Result<BalanceSnapshotBatch> {
+        let invalid = |line: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid balance snapshot {} at line {}", path.display(), line),
+            )
+        };
+        let taken_at = Timestamp(taken_at);
+        let mut accounts: Vec<AccountBalanceSnapshot> = Vec::new();
+        for (index, line) in BufReader::new(fs::File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if index == 0 || line.trim().is_empty() {
+                continue;
+            }
+            let (account_id, entry) = parse_entry(&line).ok_or_else(|| invalid(index + 1))?;
+            match accounts.last_mut() {
+                Some(last) if last.account_id == account_id => last.balances.push(entry),
+                _ => accounts.push(AccountBalanceSnapshot {
+                    account_id,
+                    sequence,
+                    taken_at,
+                    balances: vec![entry],
+                }),
+            }
+        }
+        Ok(BalanceSnapshotBatch { sequence, taken_at, accounts })
+    }
+}
+
+impl BalanceSnapshotStore for CsvBalanceSnapshotStore {
+    fn save(&mut self, batch: &BalanceSnapshotBatch) -> io::Result<()> {
+        // 同一时间的旧批次（序列号可能不同）先删除
+        for (taken_at, _, path) in self.files()? {
+            if taken_at == batch.taken_at.0 {
+                fs::remove_file(path)?;
+            }
+        }
+        let name = format!("{}-{}.csv", batch.taken_at.0, batch.sequence);
+        let tmp = self.dir.join(format!("{}.tmp", name));
+        let mut writer = BufWriter::new(fs::File::create(&tmp)?);
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for account in &batch.accounts {
+            for entry in &account.balances {
+                writeln!(
+                    writer,
+                    "{},{},{},{}",
+                    account.account_id.0,
+                    entry.asset_id.as_u32(),
+                    entry.available.raw(),
+                    entry.frozen.raw()
+                )?;
+            }
+        }
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp, self.dir.join(name))
+    }
+
+    fn load_all(&self) -> io::Result<Vec<BalanceSnapshotBatch>> {
+        self.files()?
+            .iter()
+            .map(|(taken_at, sequence, path)| Self::read_batch(*taken_at, *sequence, path))
+            .collect()
+    }
+
+    fn prune_before(&mut self, before: Timestamp) -> io::Result<()> {
+        for (taken_at, _, path) in self.files()? {
+            if taken_at < before.0 {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_entry(line: &str) -> Option<(AccountId, AssetBalanceEntry)> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [account_id, asset_id, available, frozen] = fields.as_slice() else {
+        return None;
+    };
+    let entry = AssetBalanceEntry {
+        asset_id: AssetId::try_from(asset_id.parse::<u32>().ok()?).ok()?,
+        available: Quantity::from_raw(available.parse().ok()?),
+        frozen: Quantity::from_raw(frozen.parse().ok()?),
+    };
+    Some((AccountId(account_id.parse().ok()?), entry))
+}
+
+/// 定时余额快照服务
+#[derive(Debug)]
+pub struct BalanceSnapshotService<S = MemBalanceSnapshotStore> {
+    schedule: SnapshotSchedule,
+    /// 下一个待截取的边界
+    next_due: Timestamp,
+    /// 快照时间 -> 批次
+    batches: BTreeMap<u64, BalanceSnapshotBatch>,
+    store: S,
+}
+
+impl BalanceSnapshotService {
+    /// 从 `now` 之后的第一个边界开始调度（内存存储）
+    pub fn new(schedule: SnapshotSchedule, now: Timestamp) -> Self {
+        Self {
+            schedule,
+            next_due: schedule.next_after(now),
+            batches: BTreeMap::new(),
+            store: MemBalanceSnapshotStore,
+        }
+    }
+}
+
+impl<S: BalanceSnapshotStore> BalanceSnapshotService<S> {
+    /// 使用指定存储，并加载其中已有的批次
+    pub fn with_store(schedule: SnapshotSchedule, now: Timestamp, store: S) -> io::Result<Self> {
+        let batches =
+            store.load_all()?.into_iter().map(|batch| (batch.taken_at.0, batch)).collect();
+        Ok(Self { schedule, next_due: schedule.next_after(now), batches, store })
+    }
+
+    /// 下一个待截取的边界
+    pub fn next_due(&self) -> Timestamp {
+        self.next_due
+    }
+
+    /// 是否已到截取时间
+    pub fn is_due(&self, now: Timestamp) -> bool {
+        now.0 >= self.next_due.0
+    }
+
+    /// 到期则截取，返回批次时间
+    ///
+    /// 批次标记为实际截取时间 `now`（余额对应的就是这一时刻的状态）；
+    /// 停机期间错过的边界不补截，调度直接推进到 `now` 之后的下一个边界。
+    /// 保存失败时不推进调度，下次检查时重试
+    pub fn capture_if_due<'a>(
+        &mut self,
+        sequence: u64,
+        now: Timestamp,
+        balances: impl IntoIterator<Item = &'a Balance>,
+    ) -> io::Result<Option<Timestamp>> {
+        if !self.is_due(now) {
+            return Ok(None);
+        }
+        self.capture(sequence, now, balances)?;
+        self.next_due = self.schedule.next_after(now);
+        Ok(Some(now))
+    }
+
+    /// 立即截取并保存（同一时间重复截取时覆盖旧批次）
+    pub fn capture<'a>(
+        &mut self,
+        sequence: u64,
+        taken_at: Timestamp,
+        balances: impl IntoIterator<Item = &'a Balance>,
+    ) -> io::Result<&BalanceSnapshotBatch> {
+        let mut by_account: BTreeMap<u64, Vec<AssetBalanceEntry>> = BTreeMap::new();
+        for balance in balances {
+            by_account.entry(balance.account_id.0).or_default().push(AssetBalanceEntry {
+                asset_id: balance.asset_id,
+                available: balance.available,
+                frozen: balance.frozen,
+            });
+        }
+        let accounts = by_account
+            .into_iter()
+            .map(|(account_id, mut balances)| {
+                balances.sort_by_key(|b| b.asset_id.as_u32());
+                AccountBalanceSnapshot {
+                    account_id: AccountId(account_id),
+                    sequence,
+                    taken_at,
+                    balances,
+                }
+            })
+            .collect();
+
+        let batch = BalanceSnapshotBatch { sequence, taken_at, accounts };
+        self.store.save(&batch)?;
+        self.batches.insert(taken_at.0, batch);
+        Ok(&self.batches[&taken_at.0])
+    }
+
+    /// 指定时间的批次
+    pub fn batch_at(&self, taken_at: Timestamp) -> Option<&BalanceSnapshotBatch> {
+        self.batches.get(&taken_at.0)
+    }
+
+    /// 区间 `[from, to]` 内的批次（利息计提）
+    pub fn batches_between(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> impl Iterator<Item = &BalanceSnapshotBatch> {
+        self.batches.range(from.0..=to.0.max(from.0)).map(|(_, batch)| batch)
+    }
+
+    /// 账户在 `at` 时刻的余额：不晚于 `at` 的最近一次快照（争议处理）
+    ///
+    /// 该批次中没有此账户说明当时余额为空
+    pub fn account_at(
+        &self,
+        account_id: AccountId,
+        at: Timestamp,
+    ) -> Option<&AccountBalanceSnapshot> {
+        self.batches.range(..=at.0).next_back()?.1.account(account_id)
+    }
+
+    /// 删除早于 `before` 的批次（含存储），返回删除数量
+    pub fn prune_before(&mut self, before: Timestamp) -> io::Result<usize> {
+        self.store.prune_before(before)?;
+        let kept = self.batches.split_off(&before.0);
+        Ok(std::mem::replace(&mut self.batches, kept).len())
+    }
+}
+
+/// 运行中的定时快照线程
+#[derive(Debug)]
+pub struct BalanceSnapshotScheduler {
+    stop: mpsc::Sender<()>,
+    failures: Arc<AtomicU64>,
+    thread: JoinHandle<()>,
+}
+
+impl BalanceSnapshotScheduler {
+    /// 截取或保存失败的次数
+    pub fn failures(&self) -> u64 {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// 停止并等待线程退出
+    pub fn shutdown(self) -> io::Result<()> {
+        let _ = self.stop.send(());
+        self.thread.join().map_err(|_| io::Error::other("balance snapshot thread panicked"))
+    }
+}
+
+/// 启动定时快照线程
+///
+/// 每隔 `tick` 按 `clock` 检查一次；到期时调用 `source` 取得同一序列号上的
+/// 一致余额视图（序列号, 全部余额）并截取。`source` 只在到期时调用
+pub fn spawn_balance_snapshots<S, F>(
+    service: Arc<Mutex<BalanceSnapshotService<S>>>,
+    tick: Duration,
+    clock: fn() -> Timestamp,
+    mut source: F,
+) -> io::Result<BalanceSnapshotScheduler>
+where
+    S: BalanceSnapshotStore + 'static,
+    F: FnMut() -> (u64, Vec<Balance>) + Send + 'static,
+{
+    let (stop, stopped) = mpsc::channel();
+    let failures = Arc::new(AtomicU64::new(0));
+    let failed = Arc::clone(&failures);
+    let thread =
+        std::thread::Builder::new().name("balance-snapshots".to_string()).spawn(move || {
+            loop {
+                match stopped.recv_timeout(tick) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+                }
+                let now = clock();
+                let mut service = service.lock().unwrap_or_else(PoisonError::into_inner);
+                if !service.is_due(now) {
+                    continue;
+                }
+                let (sequence, balances) = source();
+                if service.capture_if_due(sequence, now, &balances).is_err() {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        })?;
+    Ok(BalanceSnapshotScheduler { stop, failures, thread })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(account: u64, asset_id: AssetId, available: f64, frozen: f64) -> Balance {
+        let mut balance = Balance::new(AccountId(account), asset_id, Timestamp(0));
+        balance.available = Quantity::from_f64(available);
+        balance.frozen = Quantity::from_f64(frozen);
+        balance
+    }
+
+    #[test]
+    fn test_schedule_boundaries() {
+        assert_eq!(SnapshotSchedule::Hourly.next_after(Timestamp(1)), Timestamp(HOUR_NANOS));
+        assert_eq!(
+            SnapshotSchedule::Hourly.next_after(Timestamp(HOUR_NANOS)),
+            Timestamp(2 * HOUR_NANOS)
+        );
+        assert_eq!(SnapshotSchedule::Daily.next_after(Timestamp(0)), Timestamp(24 * HOUR_NANOS));
+        let every = SnapshotSchedule::Every { interval_nanos: 10 };
+        assert_eq!(every.next_after(Timestamp(25)), Timestamp(30));
+    }
+
+    #[test]
+    fn test_scheduled_capture_and_lookup() {
+        let schedule = SnapshotSchedule::Every { interval_nanos: 100 };
+        let mut service = BalanceSnapshotService::new(schedule, Timestamp(50));
+        let balances = vec![
+            balance(2, AssetId::Usdt, 10.0, 5.0),
+            balance(1, AssetId::Btc, 1.0, 0.0),
+            balance(1, AssetId::Usdt, 100.0, 0.0),
+        ];
+
+        assert_eq!(service.capture_if_due(7, Timestamp(99), &balances).unwrap(), None);
+        // 批次标记为实际截取时间
+        assert_eq!(
+            service.capture_if_due(8, Timestamp(120), &balances).unwrap(),
+            Some(Timestamp(120))
+        );
+        assert_eq!(service.next_due(), Timestamp(200));
+
+        // 停机跨过多个边界，只截取一次并推进到下一个边界
+        let later = vec![balance(1, AssetId::Usdt, 40.0, 0.0)];
+        assert_eq!(
+            service.capture_if_due(9, Timestamp(450), &later).unwrap(),
+            Some(Timestamp(450))
+        );
+        assert_eq!(service.next_due(), Timestamp(500));
+
+        let batch = service.batch_at(Timestamp(120)).unwrap();
+        assert_eq!(batch.sequence, 8);
+        assert_eq!(batch.accounts.len(), 2);
+        assert_eq!(batch.total_liabilities(AssetId::Usdt), Quantity::from_f64(115.0));
+        let registry = AssetId::precision_registry();
+        assert_eq!(batch.total_liabilities_units(AssetId::Usdt, &registry), Ok(115_000_000));
+        assert_eq!(batch.total_liabilities_units(AssetId::Btc, &registry), Ok(100_000_000));
+        let first = batch.account(AccountId(1)).unwrap();
+        assert_eq!(first.balances[0].asset_id, AssetId::Usdt);
+        assert_eq!(first.balance(AssetId::Btc).unwrap().total(), Quantity::from_f64(1.0));
+
+        let disputed = service.account_at(AccountId(1), Timestamp(199)).unwrap();
+        assert_eq!((disputed.sequence, disputed.taken_at), (8, Timestamp(120)));
+        assert_eq!(service.account_at(AccountId(2), Timestamp(500)), None);
+        assert_eq!(service.account_at(AccountId(1), Timestamp(99)), None);
+
+        assert_eq!(service.batches_between(Timestamp(0), Timestamp(1000)).count(), 2);
+        assert_eq!(service.prune_before(Timestamp(200)).unwrap(), 1);
+        assert!(service.batch_at(Timestamp(120)).is_none());
+    }
+
+    #[test]
+    fn test_csv_store_reloads_batches() {
+        let dir = std::env::temp_dir().join(format!(
+            "balance-snapshots-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        let schedule = SnapshotSchedule::Every { interval_nanos: 100 };
+        let balances =
+            vec![balance(2, AssetId::Usdt, 10.0, 5.0), balance(1, AssetId::Btc, 1.0, 0.0)];
+
+        let store = CsvBalanceSnapshotStore::open(&dir).unwrap();
+        let mut service =
+            BalanceSnapshotService::with_store(schedule, Timestamp(0), store).unwrap();
+        service.capture(3, Timestamp(50), &balances).unwrap();
+        service.capture(4, Timestamp(150), &balances[..1]).unwrap();
+        let expected = service.batch_at(Timestamp(50)).unwrap().clone();
+
+        let store = CsvBalanceSnapshotStore::open(&dir).unwrap();
+        let mut reloaded =
+            BalanceSnapshotService::with_store(schedule, Timestamp(200), store).unwrap();
+        assert_eq!(reloaded.batch_at(Timestamp(50)), Some(&expected));
+        assert_eq!(reloaded.batch_at(Timestamp(150)).unwrap().sequence, 4);
+
+        assert_eq!(reloaded.prune_before(Timestamp(100)).unwrap(), 1);
+        let store = CsvBalanceSnapshotStore::open(&dir).unwrap();
+        assert_eq!(store.load_all().unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_scheduler_captures_when_due() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn clock() -> Timestamp {
+            Timestamp(NOW.load(Ordering::Relaxed))
+        }
+
+        let schedule = SnapshotSchedule::Every { interval_nanos: 100 };
+        let service = Arc::new(Mutex::new(BalanceSnapshotService::new(schedule, Timestamp(0))));
+        let scheduler =
+            spawn_balance_snapshots(Arc::clone(&service), Duration::from_millis(1), clock, || {
+                (9, vec![balance(1, AssetId::Usdt, 1.0, 0.0)])
+            })
+            .unwrap();
+
+        NOW.store(130, Ordering::Relaxed);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while service.lock().unwrap().batch_at(Timestamp(130)).is_none() {
+            assert!(std::time::Instant::now() < deadline, "snapshot not captured");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(service.lock().unwrap().next_due(), Timestamp(200));
+        assert_eq!(scheduler.failures(), 0);
+        scheduler.shutdown().unwrap();
+    }
+}
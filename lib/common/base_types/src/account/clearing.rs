@@ -0,0 +1,309 @@
+//! 现货成交清算
+//!
+//! 由一笔成交生成清算记录：成交额、双方手续费明细（沿用 [`ProductFeeConfig`] 的费率计算）
+//! 与结算分录。只计算不记账，供记账管道与排查工具（dry-run）共用同一条路径：
+//! - 买方付出计价资产、收到基础资产，卖方相反
+//! - 双方手续费均以计价资产收取并记入手续费账户；挂单返佣（负费率）由手续费账户支付
+//! - 结算须满足各资产净额为零，见 [`Settlement::check_invariants`]
+
+use std::fmt;
+
+use crate::account::balance_change::BalanceChangeType;
+use crate::account::error::SettlementError;
+use crate::account::settlement::Settlement;
+use crate::fee::fee_types::{FeeCalculationResult, FeeError, FeeType, ProductFeeConfig};
+use crate::{AccountId, AssetId, OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+/// 待清算的成交
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeInput {
+    pub trade_id: u64,
+    pub trading_pair: TradingPair,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub buyer: AccountId,
+    pub seller: AccountId,
+    /// 挂单方
+    pub maker_side: OrderSide,
+    pub timestamp: Timestamp,
+}
+
+/// 账户的费率档位信息
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeProfile {
+    /// 30 天交易量
+    pub volume_30d: Option<f64>,
+    /// VIP 等级
+    pub vip_level: Option<u32>,
+    /// 是否做市商
+    pub market_maker: bool,
+}
+
+/// 清算参数
+#[derive(Debug, Clone, Copy)]
+pub struct ClearingContext<'a> {
+    pub fee_config: &'a ProductFeeConfig,
+    /// 手续费收入账户
+    pub fee_account: AccountId,
+    pub buyer_profile: FeeProfile,
+    pub seller_profile: FeeProfile,
+}
+
+/// 一方的手续费明细
+#[derive(Debug, Clone)]
+pub struct FeeLine {
+    pub account_id: AccountId,
+    pub fee_type: FeeType,
+    /// 费率计算过程
+    pub calculation: FeeCalculationResult,
+    /// 记账金额（计价资产，负数为返佣）
+    pub amount: Quantity,
+}
+
+/// 清算记录
+#[derive(Debug, Clone)]
+pub struct ClearingRecord {
+    pub trade: TradeInput,
+    pub base_asset: AssetId,
+    pub quote_asset: AssetId,
+    /// 成交额（计价资产）
+    pub notional: Quantity,
+    pub buyer_fee: FeeLine,
+    pub seller_fee: FeeLine,
+    pub settlement: Settlement,
+}
+
+/// 清算错误
+#[derive(Debug, PartialEq)]
+pub enum ClearingError {
+    /// 价格或数量不为正
+    InvalidTrade { trade_id: u64 },
+    /// 成交额溢出
+    NotionalOverflow { trade_id: u64 },
+    /// 手续费计算失败
+    Fee(FeeError),
+    /// 结算不变量不满足
+    Settlement(SettlementError),
+}
+
+impl fmt::Display for ClearingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClearingError::InvalidTrade { trade_id } => {
+                write!(f, "Trade {} has non-positive price or quantity", trade_id)
+            }
+            ClearingError::NotionalOverflow { trade_id } => {
+                write!(f, "Trade {} notional overflows", trade_id)
+            }
+            ClearingError::Fee(e) => write!(f, "{}", e),
+            ClearingError::Settlement(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClearingError {}
+
+impl From<FeeError> for ClearingError {
+    fn from(e: FeeError) -> Self {
+        ClearingError::Fee(e)
+    }
+}
+
+impl From<SettlementError> for ClearingError {
+    fn from(e: SettlementError) -> Self {
+        ClearingError::Settlement(e)
+    }
+}
+
+/// 清算一笔现货成交（不记账）
+pub fn clear_spot_trade(
+    trade: &TradeInput,
+    context: &ClearingContext<'_>,
+) -> Result<ClearingRecord, ClearingError> {
+    let trade_id = trade.trade_id;
+    if !trade.price.is_positive() || !trade.quantity.is_positive() {
+        return Err(ClearingError::InvalidTrade { trade_id });
+    }
+    let notional = trade
+        .price
+        .checked_mul(trade.quantity)
+        .ok_or(ClearingError::NotionalOverflow { trade_id })?;
+    let base_asset = trade.trading_pair.base_asset();
+    let quote_asset = trade.trading_pair.quote_asset();
+
+    let fee_line = |account_id: AccountId, side: OrderSide, profile: &FeeProfile| {
+        let fee_type = if side == trade.maker_side { FeeType::Maker } else { FeeType::Taker };
+        let calculation = context.fee_config.calculate_trading_fee(
+            fee_type,
+            quote_asset.as_str(),
+            trade.quantity.to_f64(),
+            trade.price.to_f64(),
+            profile.volume_30d,
+            profile.vip_level,
+            profile.market_maker,
+            trade.timestamp,
+        )?;
+        let amount = Quantity::from_f64(calculation.fee_amount);
+        Ok::<_, FeeError>(FeeLine { account_id, fee_type, calculation, amount })
+    };
+    let buyer_fee = fee_line(trade.buyer, OrderSide::Buy, &context.buyer_profile)?;
+    let seller_fee = fee_line(trade.seller, OrderSide::Sell, &context.seller_profile)?;
+
+    let mut settlement = Settlement::new(trade_id, trade.timestamp)
+        .with_entry(trade.buyer, quote_asset, neg(notional), BalanceChangeType::Trade)
+        .with_entry(trade.seller, quote_asset, notional, BalanceChangeType::Trade)
+        .with_entry(trade.seller, base_asset, neg(trade.quantity), BalanceChangeType::Trade)
+        .with_entry(trade.buyer, base_asset, trade.quantity, BalanceChangeType::Trade);
+    for fee in [&buyer_fee, &seller_fee] {
+        if !fee.amount.is_zero() {
+            settlement = settlement
+                .with_entry(fee.account_id, quote_asset, neg(fee.amount), BalanceChangeType::Fee)
+                .with_entry(context.fee_account, quote_asset, fee.amount, BalanceChangeType::Fee);
+        }
+    }
+    settlement.check_invariants()?;
+
+    Ok(ClearingRecord {
+        trade: *trade,
+        base_asset,
+        quote_asset,
+        notional,
+        buyer_fee,
+        seller_fee,
+        settlement,
+    })
+}
+
+fn neg(amount: Quantity) -> Quantity {
+    Quantity::default() - amount
+}
+
+impl fmt::Display for ClearingRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let trade = &self.trade;
+        writeln!(
+            f,
+            "Trade {} {} {} @ {} (maker: {:?})",
+            trade.trade_id,
+            trade.trading_pair.to_symbol_string(),
+            trade.quantity,
+            trade.price,
+            trade.maker_side
+        )?;
+        writeln!(
+            f,
+            "  buyer {}  seller {}  time {}",
+            trade.buyer.0, trade.seller.0, trade.timestamp.0
+        )?;
+        writeln!(f, "  notional {} {}", self.notional, self.quote_asset)?;
+        writeln!(f, "Fees")?;
+        for (role, fee) in [("buyer", &self.buyer_fee), ("seller", &self.seller_fee)] {
+            writeln!(
+                f,
+                "  {:<6} {:?}  {} {}  {}",
+                role,
+                fee.fee_type,
+                fee.amount,
+                self.quote_asset,
+                fee.calculation.calculation_details
+            )?;
+        }
+        writeln!(f, "Settlement {}", self.settlement.settlement_id)?;
+        for entry in &self.settlement.entries {
+            writeln!(
+                f,
+                "  account {:<8} {:<5} {:>20}  {:?}",
+                entry.account_id.0,
+                entry.asset_id.as_str(),
+                entry.amount.to_string(),
+                entry.change_type
+            )?;
+        }
+        writeln!(f, "Subtotals")?;
+        for subtotal in self.settlement.subtotals() {
+            writeln!(
+                f,
+                "  {:<5} credits {}  debits {}  net {}",
+                subtotal.asset_id.as_str(),
+                subtotal.credits,
+                subtotal.debits,
+                subtotal.net()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUYER: AccountId = AccountId(1);
+    const SELLER: AccountId = AccountId(2);
+    const FEE: AccountId = AccountId(99);
+
+    fn trade(maker_side: OrderSide) -> TradeInput {
+        TradeInput {
+            trade_id: 7,
+            trading_pair: TradingPair::BtcUsdt,
+            price: Price::from_f64(40_000.0),
+            quantity: Quantity::from_f64(0.5),
+            buyer: BUYER,
+            seller: SELLER,
+            maker_side,
+            timestamp: Timestamp(1_000),
+        }
+    }
+
+    #[test]
+    fn test_clear_spot_trade_entries_and_fees() {
+        let config = ProductFeeConfig::spot(0.001, 0.002);
+        let context = ClearingContext {
+            fee_config: &config,
+            fee_account: FEE,
+            buyer_profile: FeeProfile::default(),
+            seller_profile: FeeProfile::default(),
+        };
+        let record = clear_spot_trade(&trade(OrderSide::Sell), &context).unwrap();
+
+        assert_eq!(record.notional, Quantity::from_f64(20_000.0));
+        assert_eq!(record.buyer_fee.fee_type, FeeType::Taker);
+        assert_eq!(record.buyer_fee.amount, Quantity::from_f64(40.0));
+        assert_eq!(record.seller_fee.fee_type, FeeType::Maker);
+        assert_eq!(record.seller_fee.amount, Quantity::from_f64(20.0));
+        assert_eq!(record.settlement.entries.len(), 8);
+        let fee_income = record.settlement.entries.iter().filter(|e| e.account_id == FEE);
+        assert_eq!(fee_income.map(|e| e.amount.to_f64()).sum::<f64>(), 60.0);
+
+        let report = record.to_string();
+        assert!(report.contains("Trade 7 BTCUSDT"));
+        assert!(report.contains("Settlement 7"));
+    }
+
+    #[test]
+    fn test_maker_rebate_paid_by_fee_account() {
+        let config = ProductFeeConfig::spot(-0.0001, 0.001);
+        let context = ClearingContext {
+            fee_config: &config,
+            fee_account: FEE,
+            buyer_profile: FeeProfile::default(),
+            seller_profile: FeeProfile::default(),
+        };
+        let record = clear_spot_trade(&trade(OrderSide::Buy), &context).unwrap();
+        assert!(record.buyer_fee.amount.is_negative());
+        let buyer_fee_entry = record
+            .settlement
+            .entries
+            .iter()
+            .find(|e| e.account_id == BUYER && e.change_type == BalanceChangeType::Fee)
+            .unwrap();
+        assert!(buyer_fee_entry.amount.is_positive());
+
+        let mut invalid = trade(OrderSide::Buy);
+        invalid.quantity = Quantity::default();
+        assert_eq!(
+            clear_spot_trade(&invalid, &context).unwrap_err(),
+            ClearingError::InvalidTrade { trade_id: 7 }
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! 联名账户与代理交易授权
+//!
+//! 用户访问某账户的三种途径：
+//! - 账户所有人：全部权限
+//! - 联名持有人：与所有人同等的交易权限，可为账户授权他人
+//! - 授权：所有人或联名持有人把只读或交易权限授予另一用户，可设到期时间，可随时撤销
+//!
+//! 鉴权时以 [`AccessRegistry::authorize`] 得到 [`AuthContext`]，其中 `effective_user`
+//! 为实际操作的用户，下单时写入订单（见 `SpotOrder::effective_user`），随订单变更日志进入审计记录
+
+use std::collections::HashMap;
+
+use crate::account::account::Account;
+use crate::{AccountId, Timestamp, UserId};
+
+/// 授权范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum DelegationScope {
+    /// 只读（查询余额、订单、成交）
+    Read = 1,
+    /// 交易（下单、撤单，包含只读）
+    Trade = 2,
+}
+
+impl DelegationScope {
+    /// 是否覆盖 `required`
+    #[inline]
+    pub fn allows(self, required: DelegationScope) -> bool {
+        self as u8 >= required as u8
+    }
+}
+
+/// 用户对账户的访问途径
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    /// 账户所有人
+    Owner,
+    /// 联名持有人
+    JointHolder,
+    /// 授权（授权ID）
+    Delegated(u64),
+}
+
+/// 鉴权结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthContext {
+    /// 被操作的账户
+    pub account_id: AccountId,
+    /// 实际操作的用户
+    pub effective_user: UserId,
+    /// 访问途径
+    pub access: AccessKind,
+}
+
+impl AuthContext {
+    /// 是否代他人操作（联名持有人或被授权人）
+    #[inline]
+    pub fn is_on_behalf(&self) -> bool {
+        self.access != AccessKind::Owner
+    }
+}
+
+/// 授权记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delegation {
+    /// 授权ID
+    pub grant_id: u64,
+    pub account_id: AccountId,
+    /// 授权人（所有人或联名持有人）
+    pub grantor: UserId,
+    /// 被授权人
+    pub grantee: UserId,
+    pub scope: DelegationScope,
+    /// 到期时间（None 表示长期有效）
+    pub expires_at: Option<Timestamp>,
+    pub granted_at: Timestamp,
+}
+
+impl Delegation {
+    /// 在 `now` 是否仍有效
+    #[inline]
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.expires_at.is_none_or(|expires_at| now.0 < expires_at.0)
+    }
+}
+
+/// 授权错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegationError {
+    /// 账户未登记
+    UnknownAccount(AccountId),
+    /// 授权不存在
+    UnknownGrant(u64),
+    /// 用户无权访问该账户
+    AccessDenied { user: UserId, account_id: AccountId },
+    /// 授权范围不足
+    InsufficientScope { user: UserId, account_id: AccountId, required: DelegationScope },
+    /// 只有所有人或联名持有人可以授权、撤销
+    NotAccountHolder { user: UserId, account_id: AccountId },
+    /// 到期时间不晚于当前时间
+    AlreadyExpired,
+    /// 不能授权给自己或已有持有人
+    RedundantGrant(UserId),
+}
+
+impl std::fmt::Display for DelegationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DelegationError::UnknownAccount(account_id) => {
+                write!(f, "Unknown account: {}", account_id.0)
+            }
+            DelegationError::UnknownGrant(grant_id) => write!(f, "Unknown grant: {}", grant_id),
+            DelegationError::AccessDenied { user, account_id } => {
+                write!(f, "User {} has no access to account {}", user.0, account_id.0)
+            }
+            DelegationError::InsufficientScope { user, account_id, required } => write!(
+                f,
+                "User {} lacks {:?} permission on account {}",
+                user.0, required, account_id.0
+            ),
+            DelegationError::NotAccountHolder { user, account_id } => {
+                write!(f, "User {} is not a holder of account {}", user.0, account_id.0)
+            }
+            DelegationError::AlreadyExpired => write!(f, "Expiry must be in the future"),
+            DelegationError::RedundantGrant(user) => {
+                write!(f, "User {} already holds the account", user.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DelegationError {}
+
+/// 账户访问登记表
+#[derive(Debug, Default)]
+pub struct AccessRegistry {
+    /// 账户 -> 所有人
+    owners: HashMap<AccountId, UserId>,
+    /// 账户 -> 联名持有人
+    joint_holders: HashMap<AccountId, Vec<UserId>>,
+    /// 授权ID -> 授权
+    grants: HashMap<u64, Delegation>,
+    next_grant_id: u64,
+}
+
+impl AccessRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记账户所有人
+    pub fn register_account(&mut self, account: &Account) {
+        self.owners.insert(account.id, account.user_id);
+    }
+
+    /// 添加联名持有人（由开户流程在核验后调用）
+    pub fn add_joint_holder(
+        &mut self,
+        account_id: AccountId,
+        user: UserId,
+    ) -> Result<(), DelegationError> {
+        if self.holder_access(account_id, user)?.is_some() {
+            return Err(DelegationError::RedundantGrant(user));
+        }
+        self.joint_holders.entry(account_id).or_default().push(user);
+        Ok(())
+    }
+
+    /// 移除联名持有人，其授出的授权一并失效
+    pub fn remove_joint_holder(&mut self, account_id: AccountId, user: UserId) -> bool {
+        let Some(holders) = self.joint_holders.get_mut(&account_id) else {
+            return false;
+        };
+        let before = holders.len();
+        holders.retain(|holder| *holder != user);
+        let removed = holders.len() != before;
+        if removed {
+            self.grants.retain(|_, g| !(g.account_id == account_id && g.grantor == user));
+        }
+        removed
+    }
+
+    /// 授权，返回授权ID
+    pub fn grant(
+        &mut self,
+        account_id: AccountId,
+        grantor: UserId,
+        grantee: UserId,
+        scope: DelegationScope,
+        expires_at: Option<Timestamp>,
+        now: Timestamp,
+    ) -> Result<u64, DelegationError> {
+        if self.holder_access(account_id, grantor)?.is_none() {
+            return Err(DelegationError::NotAccountHolder { user: grantor, account_id });
+        }
+        if self.holder_access(account_id, grantee)?.is_some() {
+            return Err(DelegationError::RedundantGrant(grantee));
+        }
+        if expires_at.is_some_and(|expires_at| expires_at.0 <= now.0) {
+            return Err(DelegationError::AlreadyExpired);
+        }
+        self.next_grant_id += 1;
+        let grant_id = self.next_grant_id;
+        self.grants.insert(
+            grant_id,
+            Delegation {
+                grant_id,
+                account_id,
+                grantor,
+                grantee,
+                scope,
+                expires_at,
+                granted_at: now,
+            },
+        );
+        Ok(grant_id)
+    }
+
+    /// 撤销授权（所有人、联名持有人或被授权人本人）
+    pub fn revoke(&mut self, grant_id: u64, by: UserId) -> Result<Delegation, DelegationError> {
+        let grant = *self.grants.get(&grant_id).ok_or(DelegationError::UnknownGrant(grant_id))?;
+        if by != grant.grantee && self.holder_access(grant.account_id, by)?.is_none() {
+            return Err(DelegationError::NotAccountHolder {
+                user: by,
+                account_id: grant.account_id,
+            });
+        }
+        self.grants.remove(&grant_id);
+        Ok(grant)
+    }
+
+    /// 鉴权：`user` 以 `required` 权限访问 `account_id`
+    ///
+    /// 同一用户有多条授权时取范围最大的有效授权
+    pub fn authorize(
+        &self,
+        user: UserId,
+        account_id: AccountId,
+        required: DelegationScope,
+        now: Timestamp,
+    ) -> Result<AuthContext, DelegationError> {
+        if let Some(access) = self.holder_access(account_id, user)? {
+            return Ok(AuthContext { account_id, effective_user: user, access });
+        }
+        let best = self
+            .grants
+            .values()
+            .filter(|g| g.account_id == account_id && g.grantee == user && g.is_active(now))
+            .max_by_key(|g| (g.scope as u8, g.grant_id));
+        match best {
+            Some(grant) if grant.scope.allows(required) => Ok(AuthContext {
+                account_id,
+                effective_user: user,
+                access: AccessKind::Delegated(grant.grant_id),
+            }),
+            Some(_) => Err(DelegationError::InsufficientScope { user, account_id, required }),
+            None => Err(DelegationError::AccessDenied { user, account_id }),
+        }
+    }
+
+    /// 用户收到的有效授权
+    pub fn grants_for(&self, grantee: UserId, now: Timestamp) -> Vec<Delegation> {
+        let mut grants: Vec<Delegation> = self
+            .grants
+            .values()
+            .filter(|g| g.grantee == grantee && g.is_active(now))
+            .copied()
+            .collect();
+        grants.sort_by_key(|g| g.grant_id);
+        grants
+    }
+
+    /// 账户的全部有效授权
+    pub fn grants_on(&self, account_id: AccountId, now: Timestamp) -> Vec<Delegation> {
+        let mut grants: Vec<Delegation> = self
+            .grants
+            .values()
+            .filter(|g| g.account_id == account_id && g.is_active(now))
+            .copied()
+            .collect();
+        grants.sort_by_key(|g| g.grant_id);
+        grants
+    }
+
+    /// 清理已到期授权，返回清理数
+    pub fn purge_expired(&mut self, now: Timestamp) -> usize {
+        let before = self.grants.len();
+        self.grants.retain(|_, g| g.is_active(now));
+        before - self.grants.len()
+    }
+
+    /// 所有人或联名持有人的访问途径
+    fn holder_access(
+        &self,
+        account_id: AccountId,
+        user: UserId,
+    ) -> Result<Option<AccessKind>, DelegationError> {
+        let owner =
+            self.owners.get(&account_id).ok_or(DelegationError::UnknownAccount(account_id))?;
+        if *owner == user {
+            return Ok(Some(AccessKind::Owner));
+        }
+        let joint =
+            self.joint_holders.get(&account_id).is_some_and(|holders| holders.contains(&user));
+        Ok(joint.then_some(AccessKind::JointHolder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::account::AccountType;
+
+    const ACCOUNT: AccountId = AccountId(10);
+    const OWNER: UserId = UserId(1);
+    const PARTNER: UserId = UserId(2);
+    const ADVISOR: UserId = UserId(3);
+
+    fn registry() -> AccessRegistry {
+        let mut registry = AccessRegistry::new();
+        registry.register_account(&Account::new(ACCOUNT, OWNER, AccountType::Spot, Timestamp(0)));
+        registry
+    }
+
+    #[test]
+    fn test_joint_holder_and_scoped_grant() {
+        let mut registry = registry();
+        let owner =
+            registry.authorize(OWNER, ACCOUNT, DelegationScope::Trade, Timestamp(0)).unwrap();
+        assert!(!owner.is_on_behalf());
+
+        registry.add_joint_holder(ACCOUNT, PARTNER).unwrap();
+        let joint =
+            registry.authorize(PARTNER, ACCOUNT, DelegationScope::Trade, Timestamp(0)).unwrap();
+        assert_eq!((joint.access, joint.effective_user), (AccessKind::JointHolder, PARTNER));
+
+        let grant_id = registry
+            .grant(
+                ACCOUNT,
+                PARTNER,
+                ADVISOR,
+                DelegationScope::Read,
+                Some(Timestamp(100)),
+                Timestamp(0),
+            )
+            .unwrap();
+        let read =
+            registry.authorize(ADVISOR, ACCOUNT, DelegationScope::Read, Timestamp(50)).unwrap();
+        assert_eq!(read.access, AccessKind::Delegated(grant_id));
+        assert_eq!(
+            registry.authorize(ADVISOR, ACCOUNT, DelegationScope::Trade, Timestamp(50)),
+            Err(DelegationError::InsufficientScope {
+                user: ADVISOR,
+                account_id: ACCOUNT,
+                required: DelegationScope::Trade
+            })
+        );
+        assert!(matches!(
+            registry.authorize(ADVISOR, ACCOUNT, DelegationScope::Read, Timestamp(100)),
+            Err(DelegationError::AccessDenied { .. })
+        ));
+        assert_eq!(registry.grants_for(ADVISOR, Timestamp(50)).len(), 1);
+        assert_eq!(registry.purge_expired(Timestamp(100)), 1);
+    }
+
+    #[test]
+    fn test_grant_rules_and_revocation() {
+        let mut registry = registry();
+        assert!(matches!(
+            registry.grant(ACCOUNT, ADVISOR, PARTNER, DelegationScope::Trade, None, Timestamp(0)),
+            Err(DelegationError::NotAccountHolder { .. })
+        ));
+        assert_eq!(
+            registry.grant(ACCOUNT, OWNER, OWNER, DelegationScope::Trade, None, Timestamp(0)),
+            Err(DelegationError::RedundantGrant(OWNER))
+        );
+        assert_eq!(
+            registry.grant(
+                ACCOUNT,
+                OWNER,
+                ADVISOR,
+                DelegationScope::Trade,
+                Some(Timestamp(5)),
+                Timestamp(5)
+            ),
+            Err(DelegationError::AlreadyExpired)
+        );
+
+        let grant_id = registry
+            .grant(ACCOUNT, OWNER, ADVISOR, DelegationScope::Trade, None, Timestamp(0))
+            .unwrap();
+        assert!(registry.authorize(ADVISOR, ACCOUNT, DelegationScope::Trade, Timestamp(9)).is_ok());
+        assert!(matches!(
+            registry.revoke(grant_id, PARTNER),
+            Err(DelegationError::NotAccountHolder { .. })
+        ));
+        assert_eq!(registry.revoke(grant_id, ADVISOR).unwrap().grantee, ADVISOR);
+        assert!(registry.authorize(ADVISOR, ACCOUNT, DelegationScope::Read, Timestamp(9)).is_err());
+
+        // 联名持有人被移除后，其授出的授权失效
+        registry.add_joint_holder(ACCOUNT, PARTNER).unwrap();
+        registry
+            .grant(ACCOUNT, PARTNER, ADVISOR, DelegationScope::Read, None, Timestamp(0))
+            .unwrap();
+        assert!(registry.remove_joint_holder(ACCOUNT, PARTNER));
+        assert!(registry.grants_on(ACCOUNT, Timestamp(0)).is_empty());
+        assert!(registry.authorize(PARTNER, ACCOUNT, DelegationScope::Read, Timestamp(0)).is_err());
+        assert_eq!(
+            registry.authorize(OWNER, AccountId(99), DelegationScope::Read, Timestamp(0)),
+            Err(DelegationError::UnknownAccount(AccountId(99)))
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! 小额资产兑换
+//!
+//! 可用余额按指数价折合目标资产低于阈值的资产，经闪兑引擎（[`ConvertEngine`]）
+//! 兑换为目标资产。按账户成批处理：一个账户的全部小额资产合并为一笔结算。
+//! - 目标资产本身、有冻结余额（挂单中）的资产、没有指数价的资产不兑换
+//! - 预览与执行使用同一计算，执行前可先向用户展示预览
+
+use std::fmt;
+
+use crate::account::balance::Balance;
+use crate::account::settlement::Settlement;
+use crate::exchange::spot::convert::{ConvertEngine, ConvertError, ConvertQuote, IndexPriceSource};
+use crate::{AccountId, AssetId, Quantity, Timestamp};
+
+/// 小额资产兑换配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DustConfig {
+    /// 目标资产
+    pub target: AssetId,
+    /// 阈值：折合目标资产低于此值的余额视为小额资产
+    pub threshold: Quantity,
+}
+
+/// 不兑换的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DustSkipReason {
+    /// 折合价值不低于阈值
+    AboveThreshold { value: Quantity },
+    /// 有冻结余额
+    Frozen,
+    /// 无指数价
+    NoIndexPrice,
+    /// 数量过小，兑换所得为零
+    TooSmall,
+}
+
+impl DustSkipReason {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            DustSkipReason::AboveThreshold { .. } => "ABOVE_THRESHOLD",
+            DustSkipReason::Frozen => "FROZEN",
+            DustSkipReason::NoIndexPrice => "NO_INDEX_PRICE",
+            DustSkipReason::TooSmall => "TOO_SMALL",
+        }
+    }
+}
+
+/// 未兑换的资产
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DustSkip {
+    pub asset: AssetId,
+    pub amount: Quantity,
+    pub reason: DustSkipReason,
+}
+
+/// 兑换预览
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DustPreview {
+    pub account_id: AccountId,
+    pub target: AssetId,
+    /// 将兑换的资产（按资产ID排序）
+    pub quotes: Vec<ConvertQuote>,
+    pub skipped: Vec<DustSkip>,
+}
+
+impl DustPreview {
+    /// 手续费合计（目标资产）
+    pub fn total_fee(&self) -> Quantity {
+        self.quotes.iter().map(|quote| quote.fee).sum()
+    }
+
+    /// 实得合计（目标资产）
+    pub fn total_net(&self) -> Quantity {
+        self.quotes.iter().map(ConvertQuote::net).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.quotes.is_empty()
+    }
+}
+
+/// 小额资产兑换错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DustError {
+    /// 没有可兑换的小额资产
+    NothingToConvert(AccountId),
+}
+
+impl fmt::Display for DustError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DustError::NothingToConvert(account_id) => {
+                write!(f, "Account {} has no small balances to convert", account_id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DustError {}
+
+/// 小额资产兑换
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DustSweeper {
+    engine: ConvertEngine,
+    config: DustConfig,
+}
+
+impl DustSweeper {
+    pub fn new(engine: ConvertEngine, config: DustConfig) -> Self {
+        Self { engine, config }
+    }
+
+    pub fn config(&self) -> &DustConfig {
+        &self.config
+    }
+
+    /// 目标资产替换为 `target`（用户在预览时选择）
+    pub fn with_target(mut self, target: AssetId) -> Self {
+        self.config.target = target;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: Quantity) -> Self {
+        self.config.threshold = threshold;
+        self
+    }
+
+    /// 计算一个账户的兑换预览（`balances` 为该账户的余额）
+    pub fn preview<'a>(
+        &self,
+        account_id: AccountId,
+        balances: impl IntoIterator<Item = &'a Balance>,
+        prices: &dyn IndexPriceSource,
+    ) -> DustPreview {
+        let target = self.config.target;
+        let mut balances: Vec<&Balance> = balances
+            .into_iter()
+            .filter(|b| b.account_id == account_id && b.asset_id != target)
+            .filter(|b| b.available.is_positive())
+            .collect();
+        balances.sort_by_key(|b| b.asset_id.as_u32());
+
+        let mut preview =
+            DustPreview { account_id, target, quotes: Vec::new(), skipped: Vec::new() };
+        for balance in balances {
+            let asset = balance.asset_id;
+            let amount = balance.available;
+            let skip = |reason| DustSkip { asset, amount, reason };
+            if !balance.frozen.is_zero() {
+                preview.skipped.push(skip(DustSkipReason::Frozen));
+                continue;
+            }
+            match self.engine.quote(asset, target, amount, prices) {
+                Ok(quote) if quote.gross < self.config.threshold => preview.quotes.push(quote),
+                Ok(quote) => preview
+                    .skipped
+                    .push(skip(DustSkipReason::AboveThreshold { value: quote.gross })),
+                Err(ConvertError::NoIndexPrice { .. }) => {
+                    preview.skipped.push(skip(DustSkipReason::NoIndexPrice))
+                }
+                Err(_) => preview.skipped.push(skip(DustSkipReason::TooSmall)),
+            }
+        }
+        preview
+    }
+
+    /// 按预览生成结算
+    pub fn sweep(
+        &self,
+        preview: &DustPreview,
+        settlement_id: u64,
+        timestamp: Timestamp,
+    ) -> Result<Settlement, DustError> {
+        if preview.is_empty() {
+            return Err(DustError::NothingToConvert(preview.account_id));
+        }
+        Ok(self.engine.settle(preview.account_id, &preview.quotes, settlement_id, timestamp))
+    }
+
+    /// 成批兑换：每个有小额资产的账户一笔结算，结算ID从 `first_settlement_id` 起递增
+    pub fn sweep_batch<'a>(
+        &self,
+        accounts: impl IntoIterator<Item = (AccountId, Vec<&'a Balance>)>,
+        prices: &dyn IndexPriceSource,
+        first_settlement_id: u64,
+        timestamp: Timestamp,
+    ) -> Vec<(DustPreview, Settlement)> {
+        let mut settlement_id = first_settlement_id;
+        let mut swept = Vec::new();
+        for (account_id, balances) in accounts {
+            let preview = self.preview(account_id, balances, prices);
+            if let Ok(settlement) = self.sweep(&preview, settlement_id, timestamp) {
+                settlement_id += 1;
+                swept.push((preview, settlement));
+            }
+        }
+        swept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::Price;
+
+    const ALICE: AccountId = AccountId(1);
+    const BOB: AccountId = AccountId(2);
+    const DESK: AccountId = AccountId(500);
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    fn balance(account_id: AccountId, asset: AssetId, available: f64, frozen: f64) -> Balance {
+        let mut balance = Balance::new(account_id, asset, Timestamp(0));
+        balance.available = q(available);
+        balance.frozen = q(frozen);
+        balance
+    }
+
+    fn sweeper() -> DustSweeper {
+        let config = DustConfig { target: AssetId::Usdt, threshold: q(10.0) };
+        DustSweeper::new(ConvertEngine::new(DESK).with_fee_rate(0.02), config)
+    }
+
+    fn prices() -> HashMap<(AssetId, AssetId), Price> {
+        HashMap::from([
+            ((AssetId::Btc, AssetId::Usdt), q(40_000.0)),
+            ((AssetId::Eth, AssetId::Usdt), q(2_000.0)),
+        ])
+    }
+
+    #[test]
+    fn test_preview_selects_small_balances() {
+        let balances = [
+            balance(ALICE, AssetId::Btc, 0.0001, 0.0),
+            balance(ALICE, AssetId::Eth, 1.0, 0.0),
+            balance(ALICE, AssetId::Usdt, 3.0, 0.0),
+        ];
+        let preview = sweeper().preview(ALICE, &balances, &prices());
+        assert_eq!(preview.quotes.len(), 1);
+        assert_eq!((preview.quotes[0].from, preview.quotes[0].gross), (AssetId::Btc, q(4.0)));
+        assert_eq!((preview.total_fee(), preview.total_net()), (q(0.08), q(3.92)));
+        assert_eq!(
+            preview.skipped,
+            [DustSkip {
+                asset: AssetId::Eth,
+                amount: q(1.0),
+                reason: DustSkipReason::AboveThreshold { value: q(2_000.0) }
+            }]
+        );
+
+        let frozen = [balance(ALICE, AssetId::Eth, 0.001, 0.5)];
+        let preview = sweeper().preview(ALICE, &frozen, &prices());
+        assert_eq!(preview.skipped[0].reason, DustSkipReason::Frozen);
+        assert_eq!(
+            sweeper().sweep(&preview, 1, Timestamp(0)),
+            Err(DustError::NothingToConvert(ALICE))
+        );
+        let no_price = sweeper().with_target(AssetId::Btc).preview(ALICE, &balances, &prices());
+        assert!(no_price.skipped.iter().all(|s| s.reason == DustSkipReason::NoIndexPrice));
+    }
+
+    #[test]
+    fn test_batch_one_settlement_per_account() {
+        let alice =
+            [balance(ALICE, AssetId::Btc, 0.0001, 0.0), balance(ALICE, AssetId::Eth, 0.001, 0.0)];
+        let bob = [balance(BOB, AssetId::Eth, 5.0, 0.0)];
+        let swept = sweeper().sweep_batch(
+            [(ALICE, alice.iter().collect()), (BOB, bob.iter().collect())],
+            &prices(),
+            100,
+            Timestamp(1),
+        );
+        // BOB 没有小额资产，不生成结算
+        assert_eq!(swept.len(), 1);
+        let (preview, settlement) = &swept[0];
+        assert_eq!(settlement.settlement_id, 100);
+        assert!(settlement.check_invariants().is_ok());
+        assert_eq!(preview.total_net(), q(5.88));
+        let desk_usdt: Quantity = settlement
+            .entries
+            .iter()
+            .filter(|e| e.account_id == DESK && e.asset_id == AssetId::Usdt)
+            .map(|e| e.amount)
+            .sum();
+        assert_eq!(desk_usdt, q(-5.88));
+    }
+}
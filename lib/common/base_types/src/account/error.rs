@@ -1,6 +1,6 @@
 //! 错误类型定义
 
-use crate::{AccountId, AssetId};
+use crate::{AccountId, AssetId, Quantity};
 
 /// 余额错误
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,3 +57,38 @@ impl std::fmt::Display for BalanceError {
 }
 
 impl std::error::Error for BalanceError {}
+
+/// 结算错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementError {
+    /// 结算没有分录
+    Empty { settlement_id: u64 },
+    /// 分录金额为零
+    ZeroAmount { settlement_id: u64, index: usize },
+    /// 某资产分录净额不为零
+    Unbalanced { settlement_id: u64, asset_id: AssetId, net: Quantity },
+}
+
+impl std::fmt::Display for SettlementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettlementError::Empty { settlement_id } => {
+                write!(f, "Settlement {} has no entries", settlement_id)
+            }
+            SettlementError::ZeroAmount { settlement_id, index } => {
+                write!(f, "Settlement {} entry {} has zero amount", settlement_id, index)
+            }
+            SettlementError::Unbalanced { settlement_id, asset_id, net } => {
+                write!(
+                    f,
+                    "Settlement {} unbalanced for {}: net {}",
+                    settlement_id,
+                    asset_id.as_str(),
+                    net
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettlementError {}
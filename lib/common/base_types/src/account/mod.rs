@@ -1,8 +1,16 @@
 pub mod account;
+pub mod activity;
+pub mod api_key;
 pub mod balance;
 pub mod balance_change;
 pub mod balance_change_log;
 pub mod balance_simd;
+pub mod balance_snapshot;
 pub mod balance_soa;
+pub mod clearing;
+pub mod delegation;
+pub mod dust;
 pub mod error;
+pub mod settlement;
+pub mod settlement_export;
 pub mod user;
@@ -0,0 +1,189 @@
+//! 多币种结算
+//!
+//! 一笔结算可包含多个资产的分录：例如 BTC/USDT 成交时手续费以 ETH 收取，
+//! 同一结算同时出现 BTC、USDT、ETH 三种资产。结算按资产分别汇总小计，
+//! 不变量按资产逐一校验：每种资产的分录净额必须为零（资金只在账户间转移，
+//! 手续费记入手续费账户），不同资产之间不能互相抵消
+
+use crate::account::balance_change::BalanceChangeType;
+use crate::account::error::SettlementError;
+use crate::{AccountId, AssetId, Quantity, Timestamp};
+
+/// 结算分录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettlementEntry {
+    /// 账户ID
+    pub account_id: AccountId,
+    /// 资产ID
+    pub asset_id: AssetId,
+    /// 变更金额（正数=入账，负数=出账）
+    pub amount: Quantity,
+    /// 变更类型（成交、手续费等）
+    pub change_type: BalanceChangeType,
+}
+
+/// 单一资产小计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetSubtotal {
+    pub asset_id: AssetId,
+    /// 入账合计
+    pub credits: Quantity,
+    /// 出账合计（正数）
+    pub debits: Quantity,
+}
+
+impl AssetSubtotal {
+    /// 净额（入账 - 出账）
+    pub fn net(&self) -> Quantity {
+        self.credits - self.debits
+    }
+}
+
+/// 结算（可含多个资产的分录）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    /// 结算ID
+    pub settlement_id: u64,
+    /// 分录（按添加顺序）
+    pub entries: Vec<SettlementEntry>,
+    /// 结算时间
+    pub timestamp: Timestamp,
+}
+
+impl Settlement {
+    /// 创建空结算
+    pub fn new(settlement_id: u64, timestamp: Timestamp) -> Self {
+        Self { settlement_id, entries: Vec::new(), timestamp }
+    }
+
+    /// 追加分录
+    pub fn with_entry(
+        mut self,
+        account_id: AccountId,
+        asset_id: AssetId,
+        amount: Quantity,
+        change_type: BalanceChangeType,
+    ) -> Self {
+        self.entries.push(SettlementEntry { account_id, asset_id, amount, change_type });
+        self
+    }
+
+    /// 涉及的资产（按资产ID排序）
+    pub fn assets(&self) -> Vec<AssetId> {
+        let mut assets: Vec<AssetId> = self.entries.iter().map(|e| e.asset_id).collect();
+        assets.sort_by_key(|a| a.as_u32());
+        assets.dedup();
+        assets
+    }
+
+    /// 各资产小计（按资产ID排序）
+    pub fn subtotals(&self) -> Vec<AssetSubtotal> {
+        let zero = Quantity::default();
+        self.assets()
+            .into_iter()
+            .map(|asset_id| {
+                let mut subtotal = AssetSubtotal { asset_id, credits: zero, debits: zero };
+                for entry in self.entries.iter().filter(|e| e.asset_id == asset_id) {
+                    if entry.amount > zero {
+                        subtotal.credits += entry.amount;
+                    } else {
+                        subtotal.debits -= entry.amount;
+                    }
+                }
+                subtotal
+            })
+            .collect()
+    }
+
+    /// 某资产小计
+    pub fn subtotal(&self, asset_id: AssetId) -> Option<AssetSubtotal> {
+        self.subtotals().into_iter().find(|s| s.asset_id == asset_id)
+    }
+
+    /// 校验结算不变量
+    ///
+    /// - 至少一条分录，且分录金额非零
+    /// - 每种资产净额为零
+    pub fn check_invariants(&self) -> Result<(), SettlementError> {
+        let zero = Quantity::default();
+        if self.entries.is_empty() {
+            return Err(SettlementError::Empty { settlement_id: self.settlement_id });
+        }
+        if let Some(index) = self.entries.iter().position(|e| e.amount == zero) {
+            return Err(SettlementError::ZeroAmount { settlement_id: self.settlement_id, index });
+        }
+        for subtotal in self.subtotals() {
+            if subtotal.net() != zero {
+                return Err(SettlementError::Unbalanced {
+                    settlement_id: self.settlement_id,
+                    asset_id: subtotal.asset_id,
+                    net: subtotal.net(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUYER: AccountId = AccountId(1);
+    const SELLER: AccountId = AccountId(2);
+    const FEE: AccountId = AccountId(99);
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    /// BTC/USDT 成交 0.5 BTC @ 40000，买方手续费以 ETH 支付
+    fn trade_with_eth_fee() -> Settlement {
+        Settlement::new(1, Timestamp(0))
+            .with_entry(BUYER, AssetId::Usdt, q(-20000.0), BalanceChangeType::Trade)
+            .with_entry(SELLER, AssetId::Usdt, q(20000.0), BalanceChangeType::Trade)
+            .with_entry(SELLER, AssetId::Btc, q(-0.5), BalanceChangeType::Trade)
+            .with_entry(BUYER, AssetId::Btc, q(0.5), BalanceChangeType::Trade)
+            .with_entry(BUYER, AssetId::Eth, q(-0.01), BalanceChangeType::Fee)
+            .with_entry(FEE, AssetId::Eth, q(0.01), BalanceChangeType::Fee)
+    }
+
+    #[test]
+    fn test_multi_asset_subtotals() {
+        let settlement = trade_with_eth_fee();
+        assert_eq!(settlement.assets(), vec![AssetId::Usdt, AssetId::Btc, AssetId::Eth]);
+
+        let usdt = settlement.subtotal(AssetId::Usdt).unwrap();
+        assert_eq!((usdt.credits, usdt.debits), (q(20000.0), q(20000.0)));
+        let eth = settlement.subtotal(AssetId::Eth).unwrap();
+        assert_eq!((eth.credits, eth.debits, eth.net()), (q(0.01), q(0.01), q(0.0)));
+        assert!(settlement.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_invariant_checked_per_asset() {
+        // ETH 少记一笔、BTC 多记一笔：合计金额可能相互抵消，但按资产校验必须失败
+        let mut settlement = trade_with_eth_fee();
+        settlement.entries.pop();
+        settlement = settlement.with_entry(FEE, AssetId::Btc, q(0.01), BalanceChangeType::Fee);
+        assert!(matches!(
+            settlement.check_invariants(),
+            Err(SettlementError::Unbalanced { asset_id: AssetId::Btc, .. })
+        ));
+
+        assert_eq!(
+            Settlement::new(2, Timestamp(0)).check_invariants(),
+            Err(SettlementError::Empty { settlement_id: 2 })
+        );
+        let zero = Settlement::new(3, Timestamp(0)).with_entry(
+            BUYER,
+            AssetId::Usdt,
+            q(0.0),
+            BalanceChangeType::Fee,
+        );
+        assert_eq!(
+            zero.check_invariants(),
+            Err(SettlementError::ZeroAmount { settlement_id: 3, index: 0 })
+        );
+    }
+}
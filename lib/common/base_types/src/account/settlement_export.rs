@@ -0,0 +1,276 @@
+//! 结算流式导出
+//!
+//! 每笔完成的结算连同全部分录编码为一条 SBE 消息，发布到消息系统（Kafka / Redpanda），
+//! 供数据仓库、合规系统近实时消费账本：
+//! - 消息键为结算ID（十进制字符串），消费方按结算ID去重
+//! - 消息体为标准 8 字节消息头 + 根块 + 分录重复组（小端）
+//! - 模式定义见 [`SETTLEMENT_SBE_SCHEMA`]，部署时注册到 schema registry
+//!
+//! 消息布局：
+//!
+//! ```text
+//! 根块（16 字节）：0 settlement_id u64 | 8 timestamp u64（纳秒）
+//! 组头（4 字节）：0 block_length u16 | 2 num_in_group u16
+//! 分录（24 字节）：0 account_id u64 | 8 asset_id u32 | 12 change_type u8 | 13 填充 3 | 16 amount i64
+//! ```
+
+use std::io;
+
+use crate::account::error::SettlementError;
+use crate::account::settlement::Settlement;
+use crate::spot_topic::SpotTopic;
+
+/// SBE 模式ID
+pub const SETTLEMENT_SCHEMA_ID: u16 = 1;
+/// SBE 模式版本
+pub const SETTLEMENT_SCHEMA_VERSION: u16 = 0;
+/// 结算消息模板ID
+pub const SETTLEMENT_TEMPLATE_ID: u16 = 30;
+/// SBE 消息头长度
+const HEADER_LEN: usize = 8;
+/// 根块长度
+pub const SETTLEMENT_BLOCK_LENGTH: u16 = 16;
+/// 重复组头长度
+const GROUP_HEADER_LEN: usize = 4;
+/// 分录块长度
+pub const ENTRY_BLOCK_LENGTH: u16 = 24;
+
+/// 结算消息的 SBE 模式（XML），与 [`encode_settlement`] 的布局一致
+pub const SETTLEMENT_SBE_SCHEMA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe"
+                   package="rustlob.settlement" id="1" version="0" byteOrder="littleEndian">
+    <types>
+        <composite name="messageHeader">
+            <type name="blockLength" primitiveType="uint16"/>
+            <type name="templateId" primitiveType="uint16"/>
+            <type name="schemaId" primitiveType="uint16"/>
+            <type name="version" primitiveType="uint16"/>
+        </composite>
+        <composite name="groupSizeEncoding">
+            <type name="blockLength" primitiveType="uint16"/>
+            <type name="numInGroup" primitiveType="uint16"/>
+        </composite>
+        <composite name="Decimal8">
+            <type name="mantissa" primitiveType="int64"/>
+            <type name="exponent" primitiveType="int8" presence="constant">-8</type>
+        </composite>
+    </types>
+    <sbe:message name="Settlement" id="30" blockLength="16">
+        <field name="settlementId" id="1" type="uint64"/>
+        <field name="timestamp" id="2" type="uint64" semanticType="UTCTimestamp"/>
+        <group name="entries" id="3" blockLength="24" dimensionType="groupSizeEncoding">
+            <field name="accountId" id="4" type="uint64"/>
+            <field name="assetId" id="5" type="uint32"/>
+            <field name="changeType" id="6" type="uint8"/>
+            <field name="amount" id="7" type="Decimal8" offset="16"/>
+        </group>
+    </sbe:message>
+</sbe:messageSchema>
+"#;
+
+/// 将结算编码为一条 SBE 消息
+///
+/// 分录数超过 `u16::MAX` 时截断（单笔结算实际只有个位数分录）
+pub fn encode_settlement(settlement: &Settlement) -> Vec<u8> {
+    let entries = &settlement.entries[..settlement.entries.len().min(u16::MAX as usize)];
+    let mut buf = Vec::with_capacity(
+        HEADER_LEN
+            + SETTLEMENT_BLOCK_LENGTH as usize
+            + GROUP_HEADER_LEN
+            + entries.len() * ENTRY_BLOCK_LENGTH as usize,
+    );
+    for field in [
+        SETTLEMENT_BLOCK_LENGTH,
+        SETTLEMENT_TEMPLATE_ID,
+        SETTLEMENT_SCHEMA_ID,
+        SETTLEMENT_SCHEMA_VERSION,
+    ] {
+        buf.extend_from_slice(&field.to_le_bytes());
+    }
+    buf.extend_from_slice(&settlement.settlement_id.to_le_bytes());
+    buf.extend_from_slice(&settlement.timestamp.0.to_le_bytes());
+
+    buf.extend_from_slice(&ENTRY_BLOCK_LENGTH.to_le_bytes());
+    buf.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for entry in entries {
+        buf.extend_from_slice(&entry.account_id.0.to_le_bytes());
+        buf.extend_from_slice(&entry.asset_id.as_u32().to_le_bytes());
+        buf.push(entry.change_type as u8);
+        buf.extend_from_slice(&[0; 3]);
+        buf.extend_from_slice(&entry.amount.raw().to_le_bytes());
+    }
+    buf
+}
+
+/// 消息发送端（Kafka / Redpanda 生产者等）
+pub trait SettlementSink {
+    /// 发送一条消息；投递可异步完成，返回错误表示消息未能进入发送队列
+    fn send(&mut self, topic: &str, key: &[u8], payload: &[u8]) -> io::Result<()>;
+}
+
+impl<S: SettlementSink + ?Sized> SettlementSink for Box<S> {
+    fn send(&mut self, topic: &str, key: &[u8], payload: &[u8]) -> io::Result<()> {
+        (**self).send(topic, key, payload)
+    }
+}
+
+/// 导出配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementExportConfig {
+    /// 是否导出
+    pub enabled: bool,
+    /// 目标 topic
+    pub topic: String,
+}
+
+impl Default for SettlementExportConfig {
+    fn default() -> Self {
+        Self { enabled: true, topic: SpotTopic::SettlementEntryLog.name().to_string() }
+    }
+}
+
+/// 结算导出器
+///
+/// 在结算入账后调用 [`SettlementExporter::export`]；发送失败不阻塞记账主流程，
+/// 计入 `send_errors`，由监控告警后从账本补发
+#[derive(Debug)]
+pub struct SettlementExporter<S: SettlementSink> {
+    config: SettlementExportConfig,
+    sink: S,
+    exported: u64,
+    send_errors: u64,
+}
+
+impl<S: SettlementSink> SettlementExporter<S> {
+    pub fn new(config: SettlementExportConfig, sink: S) -> Self {
+        Self { config, sink, exported: 0, send_errors: 0 }
+    }
+
+    /// 导出一笔结算，返回是否已交给发送端
+    ///
+    /// 不满足不变量的结算不导出，返回校验错误
+    pub fn export(&mut self, settlement: &Settlement) -> Result<bool, SettlementError> {
+        if !self.config.enabled {
+            return Ok(false);
+        }
+        settlement.check_invariants()?;
+        let key = settlement.settlement_id.to_string();
+        let payload = encode_settlement(settlement);
+        match self.sink.send(&self.config.topic, key.as_bytes(), &payload) {
+            Ok(()) => {
+                self.exported += 1;
+                Ok(true)
+            }
+            Err(_) => {
+                self.send_errors += 1;
+                Ok(false)
+            }
+        }
+    }
+
+    /// 已导出的结算数
+    pub fn exported(&self) -> u64 {
+        self.exported
+    }
+
+    /// 发送失败次数
+    pub fn send_errors(&self) -> u64 {
+        self.send_errors
+    }
+
+    pub fn config(&self) -> &SettlementExportConfig {
+        &self.config
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::balance_change::BalanceChangeType;
+    use crate::{AccountId, AssetId, Quantity, Timestamp};
+
+    #[derive(Default)]
+    struct Captured {
+        messages: Vec<(String, Vec<u8>, Vec<u8>)>,
+        fail: bool,
+    }
+
+    impl SettlementSink for Captured {
+        fn send(&mut self, topic: &str, key: &[u8], payload: &[u8]) -> io::Result<()> {
+            if self.fail {
+                return Err(io::Error::other("broker unavailable"));
+            }
+            self.messages.push((topic.to_string(), key.to_vec(), payload.to_vec()));
+            Ok(())
+        }
+    }
+
+    fn settlement() -> Settlement {
+        Settlement::new(7, Timestamp(1_000))
+            .with_entry(
+                AccountId(1),
+                AssetId::Usdt,
+                Quantity::from_f64(-100.0),
+                BalanceChangeType::Trade,
+            )
+            .with_entry(
+                AccountId(2),
+                AssetId::Usdt,
+                Quantity::from_f64(100.0),
+                BalanceChangeType::Trade,
+            )
+    }
+
+    #[test]
+    fn test_encode_layout() {
+        let buf = encode_settlement(&settlement());
+        let u16_at = |at: usize| u16::from_le_bytes([buf[at], buf[at + 1]]);
+        let u64_at = |at: usize| u64::from_le_bytes(buf[at..at + 8].try_into().unwrap());
+        assert_eq!(buf.len(), 8 + 16 + 4 + 2 * 24);
+        assert_eq!([u16_at(0), u16_at(2), u16_at(4)], [16, 30, 1]);
+        assert_eq!((u64_at(8), u64_at(16)), (7, 1_000));
+        assert_eq!((u16_at(24), u16_at(26)), (24, 2));
+
+        let second = 28 + 24;
+        assert_eq!(u64_at(second), 2);
+        assert_eq!(u32::from_le_bytes(buf[second + 8..second + 12].try_into().unwrap()), 1);
+        assert_eq!(buf[second + 12], BalanceChangeType::Trade as u8);
+        let amount = i64::from_le_bytes(buf[second + 16..second + 24].try_into().unwrap());
+        assert_eq!(Quantity::from_raw(amount), Quantity::from_f64(100.0));
+    }
+
+    #[test]
+    fn test_export_to_sink() {
+        let mut exporter =
+            SettlementExporter::new(SettlementExportConfig::default(), Captured::default());
+        assert_eq!(exporter.export(&settlement()), Ok(true));
+        let (topic, key, payload) = &exporter.sink().messages[0];
+        assert_eq!((topic.as_str(), key.as_slice()), ("SettlementEntryLog", b"7".as_slice()));
+        assert_eq!(payload, &encode_settlement(&settlement()));
+
+        let unbalanced = settlement().with_entry(
+            AccountId(3),
+            AssetId::Btc,
+            Quantity::from_f64(1.0),
+            BalanceChangeType::Fee,
+        );
+        assert!(exporter.export(&unbalanced).is_err());
+
+        exporter.sink_mut().fail = true;
+        assert_eq!(exporter.export(&settlement()), Ok(false));
+        assert_eq!((exporter.exported(), exporter.send_errors()), (1, 1));
+
+        let disabled = SettlementExportConfig { enabled: false, ..Default::default() };
+        let mut exporter = SettlementExporter::new(disabled, Captured::default());
+        assert_eq!(exporter.export(&settlement()), Ok(false));
+        assert!(exporter.sink().messages.is_empty());
+    }
+}
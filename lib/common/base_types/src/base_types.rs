@@ -6,7 +6,7 @@
 
 use std::{default, fmt};
 
-use decimal::Decimal;
+use decimal::{Decimal, PrecisionRegistry};
 
 // ============================================================================
 // 类型别名：为了语义清晰，保留 Price 和 Quantity 作为类型别名
@@ -87,13 +87,6 @@ impl From<u64> for AccountId {
 pub struct PositionId(pub u64);
 
 impl PositionId {
-    /// 生成新的持仓ID（简化实现，实际应使用雪花算法等）
-    pub fn generate() -> Self {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
-        Self(nanos)
-    }
-
     pub fn as_u64(&self) -> u64 {
         self.0
     }
@@ -133,6 +126,21 @@ impl TraderId {
     }
 }
 
+/// 账户下单时以账户ID（小端）作为交易员ID
+impl From<AccountId> for TraderId {
+    #[inline]
+    fn from(account: AccountId) -> Self {
+        Self(account.0.to_le_bytes())
+    }
+}
+
+impl From<TraderId> for AccountId {
+    #[inline]
+    fn from(trader: TraderId) -> Self {
+        Self(u64::from_le_bytes(trader.0))
+    }
+}
+
 /// 成交ID
 /// todo 要改
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -145,13 +153,6 @@ impl TradeId {
         Self(id.into())
     }
 
-    /// 生成随机成交ID
-    pub fn generate() -> Self {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-        Self(format!("TRD-{}", timestamp))
-    }
-
     /// 获取字符串表示
     pub fn as_str(&self) -> &str {
         &self.0
@@ -194,6 +195,25 @@ impl AssetId {
         }
     }
 
+    /// 链上最小计量单位的小数位数
+    pub const fn scale(self) -> u32 {
+        match self {
+            AssetId::Usdt => 6,
+            AssetId::Btc => 8,
+            AssetId::Eth => 18,
+        }
+    }
+
+    /// 全部资产的精度注册表
+    pub fn precision_registry() -> PrecisionRegistry {
+        let mut registry = PrecisionRegistry::new();
+        for asset in [AssetId::Usdt, AssetId::Btc, AssetId::Eth] {
+            // 各资产精度均不超过 18 位，注册不会失败
+            let _ = registry.register(asset.as_u32(), asset.scale());
+        }
+        registry
+    }
+
     /// 从字符串表示转换为资产ID（如果匹配）
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_uppercase().as_str() {
@@ -0,0 +1,105 @@
+//! 历史成交回填工具
+//!
+//! ```text
+//! trade_backfill --instrument <SYMBOL>:<symbol-id>:<price-scale>:<quantity-scale> ...
+//!                --out <candles.csv> <trades.csv>...
+//! ```
+//!
+//! 按顺序导入成交文件（须按时间排列），校验不合格的行跳过并打印前若干条，
+//! 全部 K 线写入 `--out`。网关启动时加载 `GATEWAY_CANDLE_FILE` 指向的该文件，
+//! 交易对须已在产品注册表上线；24h 滚动成交量由 1 分钟 K 线种子化。只支持 CSV 输入
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::process::ExitCode;
+
+use base_types::instrument::normalize::InstrumentScale;
+use base_types::mark_data::spot::backfill::{BackfillImporter, BackfillInstrument};
+use base_types::mark_data::spot::candle::{CandleInterval, CandleStore};
+
+const USAGE: &str = "usage:
+  trade_backfill --instrument <SYMBOL>:<symbol-id>:<price-scale>:<quantity-scale> ...
+                 --out <candles.csv> <trades.csv>...";
+
+const DAY_NS: u64 = 86_400_000_000_000;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut instruments = Vec::new();
+    let mut out = None;
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--instrument" => {
+                instruments.push(parse_instrument(args.next().ok_or(USAGE)?)?);
+            }
+            "--out" => out = Some(args.next().ok_or(USAGE)?),
+            input => inputs.push(input),
+        }
+    }
+    let out = out.ok_or(USAGE)?;
+    if instruments.is_empty() || inputs.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let symbols: Vec<_> = instruments.iter().map(|i| (i.symbol.clone(), i.symbol_id)).collect();
+    let mut importer =
+        BackfillImporter::new(instruments, CandleStore::new(&CandleInterval::ALL), DAY_NS);
+    for input in inputs {
+        let file = File::open(input).map_err(|e| format!("open {}: {}", input, e))?;
+        let report = importer
+            .import_csv(BufReader::new(file))
+            .map_err(|e| format!("import {}: {}", input, e))?;
+        println!("{}: imported {}, rejected {}", input, report.imported, report.rejected);
+        for rejection in &report.rejections {
+            println!("  line {}: {}", rejection.line, rejection.error);
+        }
+    }
+
+    for (symbol, symbol_id) in symbols {
+        let Some(last) = importer.last_timestamp(symbol_id) else {
+            println!("{}: no trades", symbol);
+            continue;
+        };
+        let totals = importer.volume(symbol_id).map(|v| v.totals(last)).unwrap_or_default();
+        println!(
+            "{}: last trade {} ns, 24h volume {}, quote volume {}, trades {}",
+            symbol, last, totals.volume, totals.quote_volume, totals.trade_count
+        );
+    }
+
+    let file = File::create(out).map_err(|e| format!("create {}: {}", out, e))?;
+    let count = importer
+        .candles()
+        .write_csv(BufWriter::new(file))
+        .map_err(|e| format!("write {}: {}", out, e))?;
+    println!("wrote {} candles to {}", count, out);
+    Ok(())
+}
+
+fn parse_instrument(spec: &str) -> Result<BackfillInstrument, String> {
+    let invalid = || format!("invalid instrument: {}", spec);
+    let [symbol, symbol_id, price_scale, quantity_scale] = spec.split(':').collect::<Vec<_>>()[..]
+    else {
+        return Err(invalid());
+    };
+    Ok(BackfillInstrument {
+        symbol: symbol.to_string(),
+        symbol_id: symbol_id.parse().map_err(|_| invalid())?,
+        scale: InstrumentScale::new(
+            price_scale.parse().map_err(|_| invalid())?,
+            quantity_scale.parse().map_err(|_| invalid())?,
+        ),
+    })
+}
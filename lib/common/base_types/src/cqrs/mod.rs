@@ -1 +1,2 @@
 pub mod cqrs_types;
+pub mod query_bus;
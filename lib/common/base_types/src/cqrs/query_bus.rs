@@ -0,0 +1,450 @@
+//! 查询总线
+//!
+//! 命令总线的读侧对应：按查询类型路由到处理器，并统一施加策略
+//! - 超时：处理器在固定大小的工作线程池执行，超时立即返回 `QueryError::Timeout`；
+//!   线程池与等待队列都满时返回 `QueryError::Overloaded`
+//! - 缓存：按 `Query::cache_key` 缓存响应，TTL 到期后重新查询；条目数有上限，
+//!   满时先清理过期条目，仍满则淘汰最早过期的条目
+//! - 并发上限：同一查询类型的在途数量超过上限时直接拒绝
+//!
+//! 深度、行情等读多写少的接口在总线注册一次策略，无需每个处理器各自实现
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, mpsc};
+use std::time::{Duration, Instant};
+
+/// 查询
+pub trait Query: Send + 'static {
+    /// 响应类型
+    type Response: Clone + Send + 'static;
+
+    /// 缓存键（None=不缓存）
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// 查询处理器
+pub trait QueryHandler<Q: Query>: Send + Sync + 'static {
+    fn handle(&self, query: Q) -> Result<Q::Response, QueryError>;
+}
+
+impl<Q, F> QueryHandler<Q> for F
+where
+    Q: Query,
+    F: Fn(Q) -> Result<Q::Response, QueryError> + Send + Sync + 'static,
+{
+    fn handle(&self, query: Q) -> Result<Q::Response, QueryError> {
+        self(query)
+    }
+}
+
+/// 查询错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// 查询类型未注册
+    NotRegistered,
+    /// 超时
+    Timeout,
+    /// 在途查询数超过上限
+    Overloaded,
+    /// 处理器错误
+    Handler(String),
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::NotRegistered => write!(f, "Query handler not registered"),
+            QueryError::Timeout => write!(f, "Query timed out"),
+            QueryError::Overloaded => write!(f, "Too many queries in flight"),
+            QueryError::Handler(msg) => write!(f, "Query handler error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// 查询策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryPolicy {
+    /// 超时（None=在调用线程同步执行）
+    pub timeout: Option<Duration>,
+    /// 缓存有效期（None=不缓存）
+    pub cache_ttl: Option<Duration>,
+    /// 最大在途查询数（None=不限）
+    pub max_concurrency: Option<usize>,
+}
+
+impl QueryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit);
+        self
+    }
+}
+
+/// 单个查询类型的路由
+struct Route<Q: Query> {
+    handler: Arc<dyn QueryHandler<Q>>,
+    policy: QueryPolicy,
+    in_flight: Arc<AtomicUsize>,
+}
+
+/// 在途计数守卫，处理器真正结束时释放（超时后仍占用名额）
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// 默认工作线程数
+const DEFAULT_WORKERS: usize = 4;
+/// 默认等待队列长度（每个工作线程）
+const QUEUE_PER_WORKER: usize = 64;
+/// 默认缓存条目上限
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// 带超时查询的工作线程池，首次需要时启动
+struct WorkerPool {
+    jobs: mpsc::SyncSender<Job>,
+    workers: usize,
+}
+
+impl WorkerPool {
+    fn spawn(size: usize) -> Self {
+        let (jobs, inbox) = mpsc::sync_channel::<Job>(size * QUEUE_PER_WORKER);
+        let inbox = Arc::new(Mutex::new(inbox));
+        let workers = (0..size)
+            .filter(|i| {
+                let inbox = Arc::clone(&inbox);
+                std::thread::Builder::new()
+                    .name(format!("query-worker-{}", i))
+                    .spawn(move || {
+                        loop {
+                            // 只在取任务时持锁，处理器执行期间其他线程可继续取任务
+                            let job = inbox.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                            match job {
+                                Ok(job) => job(),
+                                Err(_) => return,
+                            }
+                        }
+                    })
+                    .is_ok()
+            })
+            .count();
+        Self { jobs, workers }
+    }
+
+    fn submit(&self, job: Job) -> Result<(), QueryError> {
+        if self.workers == 0 {
+            return Err(QueryError::Handler("no query worker threads".into()));
+        }
+        self.jobs.try_send(job).map_err(|_| QueryError::Overloaded)
+    }
+}
+
+/// 缓存条目
+struct CacheEntry {
+    expires_at: Instant,
+    response: Box<dyn Any + Send>,
+}
+
+/// 有界 TTL 缓存
+struct QueryCache {
+    entries: HashMap<(TypeId, String), CacheEntry>,
+    capacity: usize,
+}
+
+impl QueryCache {
+    fn get<Q: Query>(&mut self, key: &str, now: Instant) -> Option<Q::Response> {
+        let cache_key = (TypeId::of::<Q>(), key.to_string());
+        let entry = self.entries.get(&cache_key)?;
+        if entry.expires_at <= now {
+            self.entries.remove(&cache_key);
+            return None;
+        }
+        entry.response.downcast_ref::<Q::Response>().cloned()
+    }
+
+    fn insert(&mut self, key: (TypeId, String), entry: CacheEntry, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.entries.retain(|_, entry| entry.expires_at > now);
+            if self.entries.len() >= self.capacity {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.entries.insert(key, entry);
+    }
+}
+
+/// 查询总线
+pub struct QueryBus {
+    routes: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    cache: Mutex<QueryCache>,
+    workers: usize,
+    pool: OnceLock<WorkerPool>,
+}
+
+impl Default for QueryBus {
+    fn default() -> Self {
+        Self {
+            routes: HashMap::new(),
+            cache: Mutex::new(QueryCache {
+                entries: HashMap::new(),
+                capacity: DEFAULT_CACHE_CAPACITY,
+            }),
+            workers: DEFAULT_WORKERS,
+            pool: OnceLock::new(),
+        }
+    }
+}
+
+impl QueryBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 带超时查询的工作线程数（至少 1）
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// 缓存条目上限（0=不缓存）
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache.get_mut().unwrap_or_else(PoisonError::into_inner).capacity = capacity;
+        self
+    }
+
+    /// 注册查询处理器，同一查询类型重复注册时覆盖
+    pub fn register<Q, H>(&mut self, handler: H, policy: QueryPolicy)
+    where
+        Q: Query,
+        H: QueryHandler<Q>,
+    {
+        let route: Route<Q> =
+            Route { handler: Arc::new(handler), policy, in_flight: Arc::new(AtomicUsize::new(0)) };
+        self.routes.insert(TypeId::of::<Q>(), Box::new(route));
+    }
+
+    /// 执行查询
+    pub fn dispatch<Q: Query>(&self, query: Q) -> Result<Q::Response, QueryError> {
+        let route = self
+            .routes
+            .get(&TypeId::of::<Q>())
+            .and_then(|route| route.downcast_ref::<Route<Q>>())
+            .ok_or(QueryError::NotRegistered)?;
+
+        let cache_key = route.policy.cache_ttl.and(query.cache_key());
+        if let Some(key) = &cache_key {
+            if let Some(response) = self.cache().get::<Q>(key, Instant::now()) {
+                return Ok(response);
+            }
+        }
+
+        let guard = Self::acquire(route)?;
+        let response = match route.policy.timeout {
+            None => {
+                let response = route.handler.handle(query);
+                drop(guard);
+                response
+            }
+            Some(timeout) => {
+                let handler = Arc::clone(&route.handler);
+                let (tx, rx) = mpsc::channel();
+                let pool = self.pool.get_or_init(|| WorkerPool::spawn(self.workers));
+                // 未能入队时任务连同守卫一起释放
+                pool.submit(Box::new(move || {
+                    let _guard = guard;
+                    let _ = tx.send(handler.handle(query));
+                }))?;
+                rx.recv_timeout(timeout).map_err(|_| QueryError::Timeout)?
+            }
+        }?;
+
+        if let (Some(key), Some(ttl)) = (cache_key, route.policy.cache_ttl) {
+            let now = Instant::now();
+            let entry = CacheEntry { expires_at: now + ttl, response: Box::new(response.clone()) };
+            self.cache().insert((TypeId::of::<Q>(), key), entry, now);
+        }
+        Ok(response)
+    }
+
+    /// 当前在途查询数
+    pub fn in_flight<Q: Query>(&self) -> usize {
+        self.routes
+            .get(&TypeId::of::<Q>())
+            .and_then(|route| route.downcast_ref::<Route<Q>>())
+            .map_or(0, |route| route.in_flight.load(Ordering::Acquire))
+    }
+
+    /// 清除某查询类型的缓存
+    pub fn invalidate<Q: Query>(&self) {
+        let type_id = TypeId::of::<Q>();
+        self.cache().entries.retain(|(id, _), _| *id != type_id);
+    }
+
+    /// 当前缓存条目数
+    pub fn cached_len(&self) -> usize {
+        self.cache().entries.len()
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, QueryCache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn acquire<Q: Query>(route: &Route<Q>) -> Result<InFlightGuard, QueryError> {
+        let previous = route.in_flight.fetch_add(1, Ordering::AcqRel);
+        let guard = InFlightGuard(Arc::clone(&route.in_flight));
+        match route.policy.max_concurrency {
+            Some(limit) if previous >= limit => Err(QueryError::Overloaded),
+            _ => Ok(guard),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use super::*;
+
+    struct Depth {
+        symbol: &'static str,
+    }
+
+    impl Query for Depth {
+        type Response = u32;
+
+        fn cache_key(&self) -> Option<String> {
+            Some(self.symbol.to_string())
+        }
+    }
+
+    struct Slow;
+
+    impl Query for Slow {
+        type Response = ();
+    }
+
+    #[test]
+    fn test_cache_with_ttl() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let mut bus = QueryBus::new();
+        bus.register(
+            move |_: Depth| Ok(counter.fetch_add(1, Ordering::SeqCst) + 1),
+            QueryPolicy::new().with_cache_ttl(Duration::from_secs(60)),
+        );
+
+        assert_eq!(bus.dispatch(Depth { symbol: "BTCUSDT" }), Ok(1));
+        assert_eq!(bus.dispatch(Depth { symbol: "BTCUSDT" }), Ok(1));
+        assert_eq!(bus.dispatch(Depth { symbol: "ETHUSDT" }), Ok(2));
+
+        bus.invalidate::<Depth>();
+        assert_eq!(bus.dispatch(Depth { symbol: "BTCUSDT" }), Ok(3));
+    }
+
+    #[test]
+    fn test_timeout_and_concurrency_limit() {
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let mut bus = QueryBus::new();
+        bus.register(
+            move |_: Slow| {
+                let _ = release_rx.lock().unwrap().recv();
+                Ok(())
+            },
+            QueryPolicy::new().with_timeout(Duration::from_millis(20)).with_max_concurrency(1),
+        );
+
+        // 超时后处理器仍在执行，继续占用并发名额
+        assert_eq!(bus.dispatch(Slow), Err(QueryError::Timeout));
+        assert_eq!(bus.in_flight::<Slow>(), 1);
+        assert_eq!(bus.dispatch(Slow), Err(QueryError::Overloaded));
+
+        release_tx.send(()).unwrap();
+        while bus.in_flight::<Slow>() > 0 {
+            std::thread::yield_now();
+        }
+        release_tx.send(()).unwrap();
+        assert_eq!(bus.dispatch(Slow), Ok(()));
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let mut bus = QueryBus::new().with_cache_capacity(2);
+        bus.register(
+            |query: Depth| Ok(query.symbol.len() as u32),
+            QueryPolicy::new().with_cache_ttl(Duration::from_secs(60)),
+        );
+
+        for symbol in ["BTCUSDT", "ETHUSDT", "SOLUSDT", "BNBUSDT"] {
+            assert_eq!(bus.dispatch(Depth { symbol }), Ok(7));
+        }
+        assert_eq!(bus.cached_len(), 2);
+    }
+
+    #[test]
+    fn test_timed_queries_share_fixed_workers() {
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let mut bus = QueryBus::new().with_workers(1);
+        bus.register(
+            move |_: Slow| {
+                let _ = release_rx.lock().unwrap().recv();
+                Ok(())
+            },
+            QueryPolicy::new().with_timeout(Duration::from_millis(20)),
+        );
+
+        // 唯一的工作线程被占用，后续查询排队等待直至超时
+        assert_eq!(bus.dispatch(Slow), Err(QueryError::Timeout));
+        assert_eq!(bus.dispatch(Slow), Err(QueryError::Timeout));
+        assert_eq!(bus.in_flight::<Slow>(), 2);
+
+        release_tx.send(()).unwrap();
+        release_tx.send(()).unwrap();
+        while bus.in_flight::<Slow>() > 0 {
+            std::thread::yield_now();
+        }
+        release_tx.send(()).unwrap();
+        assert_eq!(bus.dispatch(Slow), Ok(()));
+    }
+
+    #[test]
+    fn test_not_registered() {
+        let bus = QueryBus::new();
+        assert_eq!(bus.dispatch(Slow), Err(QueryError::NotRegistered));
+    }
+}
@@ -130,10 +130,15 @@ pub struct PrepPosition {
 
 impl PrepPosition {
     /// 创建空持仓
-    pub fn empty(trading_pair: TradingPair, position_side: PositionSide) -> Self {
+    pub fn empty(
+        trading_pair: TradingPair,
+        position_side: PositionSide,
+        position_id: PositionId,
+        now: Timestamp,
+    ) -> Self {
         Self {
             user_id: UserId(0),
-            position_id: PositionId::generate(),
+            position_id,
             trading_pair,
             position_side,
             quantity: Quantity::from_raw(0),
@@ -156,7 +161,7 @@ impl PrepPosition {
             adl: 0,
             bid_notional: Price::from_raw(0),
             ask_notional: Price::from_raw(0),
-            updated_at: Timestamp::now_as_nanos(),
+            updated_at: now,
         }
     }
 
@@ -247,6 +252,7 @@ impl PrepPosition {
     /// - `leverage`: 杠杆倍数
     /// - `side`: 订单方向
     /// - `position_side`: 持仓方向
+    /// - `now`: 更新时间
     pub fn add(
         &mut self,
         new_quantity: Quantity,
@@ -254,6 +260,7 @@ impl PrepPosition {
         leverage: u8,
         _side: crate::OrderSide,
         _position_side: crate::PositionSide,
+        now: Timestamp,
     ) {
         // 计算新的持仓数量和均价（加权平均）
         let old_qty = self.quantity.to_f64();
@@ -307,7 +314,7 @@ impl PrepPosition {
         self.liquidation_price = self.calculate_liquidation_price_value();
 
         // 更新时间戳
-        self.updated_at = Timestamp::now_as_nanos();
+        self.updated_at = now;
     }
 
     /// 计算未实现盈亏值
@@ -414,6 +421,7 @@ impl PrepTrade {
         fee: Price,
         fee_asset: AssetId,
         is_maker: bool,
+        timestamp: Timestamp,
     ) -> Self {
         Self {
             trade_id,
@@ -426,7 +434,7 @@ impl PrepTrade {
             fee,
             fee_asset,
             is_maker,
-            timestamp: Timestamp::now_as_nanos(),
+            timestamp,
         }
     }
 
@@ -3,7 +3,7 @@ use crate::exchange::spot::spot_types::OrderType;
 use crate::lob::lob::LobOrder;
 use crate::{
     AccountId, AssetId, OrderId, OrderSide, PositionSide, PrepPosition, PrepTrade, Price, Quantity,
-    Timestamp, TradeId, TradingPair,
+    SequenceGenerator, Timestamp, TradeId, TradingPair,
 };
 
 /// 订单有效期
@@ -176,6 +176,7 @@ impl PrepOrder {
         quantity: Quantity,
         price: Option<Price>,
         leverage: u8,
+        now: Timestamp,
     ) -> PrepOrder {
         let internal_order = PrepOrder {
             order_id,
@@ -187,10 +188,7 @@ impl PrepOrder {
             price,
             filled_quantity: Quantity::from_raw(0),
             status: FutureOrderStatus::Pending,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
+            created_at: now.0 / 1_000_000,
             frozen_margin: Price::from_raw(0),
             leverage,
             client_order_id: None,
@@ -265,7 +263,7 @@ impl PrepOrder {
         let fill_price = self.price.unwrap_or_else(|| Price::from_f64(50000.0));
 
         // 直接调用 position.update() 更新持仓
-        match_p.add(fill_qty, fill_price, self.leverage, self.side, crate::PositionSide::Long);
+        match_p.add(fill_qty, fill_price, self.leverage, self.side, crate::PositionSide::Long, now);
 
         if self.remaining_qty() == 0 {
             self.status = FutureOrderStatus::Filled
@@ -283,6 +281,7 @@ impl PrepOrder {
         matched_p: &mut PrepPosition,
         my_b: &mut Balance,
         my_p: &mut PrepPosition,
+        ids: &dyn SequenceGenerator,
         now: Timestamp,
     ) -> PrepTrade {
         let filled = self.remaining_qty().min(matched_order.quantity.raw());
@@ -299,7 +298,7 @@ impl PrepOrder {
 
         // 创建成交记录
         let trade = PrepTrade::new(
-            TradeId::generate(),
+            TradeId::new(format!("TRD-{}", ids.next_id())),
             self.order_id.clone(),
             matched_order.order_id.clone(),
             self.trading_pair,
@@ -309,6 +308,7 @@ impl PrepOrder {
             fee,
             AssetId::Usdt,
             true, // Maker
+            now,
         );
 
         // position 变化已在 filled_qty 方法中处理
@@ -0,0 +1,340 @@
+//! 算法执行的交易成本分析（TCA）
+//!
+//! 按母单记录算法执行过程：母单到达时的参考价（到达价）、各子单成交、执行期间该交易对的
+//! 市场成交量，母单结束后生成报告：
+//! - 到达价滑点：成交均价相对到达价的偏离（基点，正数表示不利）
+//! - 执行落差（Implementation Shortfall）：以到达价为基准的已成交成本（含手续费）
+//!   加上未成交部分按最新成交价计算的机会成本
+//! - 参与率：母单成交量占执行期间市场成交量的比例
+//!
+//! 市场成交量由行情成交流写入，包含母单自身的子单成交
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::exchange::spot::spot_types::AlgorithmStrategy;
+use crate::{AccountId, OrderSide, Price, Quantity, Timestamp, TradingPair};
+
+/// 一个基点
+const BPS: f64 = 10_000.0;
+
+/// TCA 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcaError {
+    /// 母单已登记
+    DuplicateParent(u64),
+    /// 母单不存在
+    UnknownParent(u64),
+    /// 母单已结束，不再接受成交
+    ParentCompleted(u64),
+    /// 数量或价格非正
+    InvalidAmount,
+}
+
+impl fmt::Display for TcaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcaError::DuplicateParent(id) => write!(f, "Parent order {} already registered", id),
+            TcaError::UnknownParent(id) => write!(f, "Unknown parent order: {}", id),
+            TcaError::ParentCompleted(id) => write!(f, "Parent order {} is completed", id),
+            TcaError::InvalidAmount => write!(f, "Price and quantity must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for TcaError {}
+
+/// 母单（算法执行的目标）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParentOrder {
+    pub parent_order_id: u64,
+    pub account_id: AccountId,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    pub strategy: AlgorithmStrategy,
+    /// 目标数量
+    pub quantity: Quantity,
+    /// 到达价（母单进入算法时的中间价）
+    pub arrival_price: Price,
+    /// 开始时间
+    pub start_time: Timestamp,
+}
+
+/// 子单成交
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildFill {
+    pub price: Price,
+    pub quantity: Quantity,
+    /// 手续费（计价资产）
+    pub fee: Quantity,
+    pub time: Timestamp,
+}
+
+/// 一个母单的执行记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParentExecution {
+    parent: ParentOrder,
+    fills: Vec<ChildFill>,
+    /// 执行期间的市场成交量
+    market_volume: Quantity,
+    /// 执行期间最新的市场成交价
+    last_price: Option<Price>,
+    end_time: Option<Timestamp>,
+}
+
+/// 母单 TCA 报告
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TcaReport {
+    pub parent_order_id: u64,
+    pub account_id: AccountId,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    pub strategy: AlgorithmStrategy,
+    pub quantity: Quantity,
+    pub filled_quantity: Quantity,
+    /// 成交均价（未成交为 None）
+    pub average_price: Option<Price>,
+    pub arrival_price: Price,
+    /// 到达价滑点（基点，未成交为 None）
+    pub arrival_slippage_bps: Option<f64>,
+    /// 执行落差（计价资产金额，正数为成本）
+    pub implementation_shortfall: f64,
+    /// 执行落差相对目标名义金额（基点）
+    pub implementation_shortfall_bps: f64,
+    /// 参与率（0~1，市场无成交为 None）
+    pub participation_rate: Option<f64>,
+    pub market_volume: Quantity,
+    pub fees: Quantity,
+    pub start_time: Timestamp,
+    /// 结束时间（执行中为 None）
+    pub end_time: Option<Timestamp>,
+}
+
+impl ParentExecution {
+    fn report(&self) -> TcaReport {
+        let parent = &self.parent;
+        // 买入价格越高越不利，卖出相反
+        let sign = match parent.side {
+            OrderSide::Buy => 1.0,
+            OrderSide::Sell => -1.0,
+        };
+        let arrival = parent.arrival_price.to_f64();
+        let filled: Quantity = self.fills.iter().map(|fill| fill.quantity).sum();
+        let fees: Quantity = self.fills.iter().map(|fill| fill.fee).sum();
+        let notional: f64 =
+            self.fills.iter().map(|fill| fill.price.to_f64() * fill.quantity.to_f64()).sum();
+
+        let average_price = (!filled.is_zero()).then(|| notional / filled.to_f64());
+        let arrival_slippage_bps = average_price.map(|avg| sign * (avg - arrival) / arrival * BPS);
+
+        let execution_cost = sign * (notional - filled.to_f64() * arrival) + fees.to_f64();
+        let unfilled = (parent.quantity.to_f64() - filled.to_f64()).max(0.0);
+        let opportunity_cost =
+            self.last_price.map_or(0.0, |last| sign * unfilled * (last.to_f64() - arrival));
+        let implementation_shortfall = execution_cost + opportunity_cost;
+        let paper_notional = parent.quantity.to_f64() * arrival;
+
+        TcaReport {
+            parent_order_id: parent.parent_order_id,
+            account_id: parent.account_id,
+            trading_pair: parent.trading_pair,
+            side: parent.side,
+            strategy: parent.strategy,
+            quantity: parent.quantity,
+            filled_quantity: filled,
+            average_price: average_price.map(Price::from_f64),
+            arrival_price: parent.arrival_price,
+            arrival_slippage_bps,
+            implementation_shortfall,
+            implementation_shortfall_bps: implementation_shortfall / paper_notional * BPS,
+            participation_rate: (!self.market_volume.is_zero())
+                .then(|| filled.to_f64() / self.market_volume.to_f64()),
+            market_volume: self.market_volume,
+            fees,
+            start_time: parent.start_time,
+            end_time: self.end_time,
+        }
+    }
+}
+
+/// 按母单保存的 TCA 记录
+#[derive(Debug, Default)]
+pub struct TcaStore {
+    executions: HashMap<u64, ParentExecution>,
+}
+
+impl TcaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记母单（算法开始执行时调用）
+    pub fn register(&mut self, parent: ParentOrder) -> Result<(), TcaError> {
+        if !parent.quantity.is_positive() || !parent.arrival_price.is_positive() {
+            return Err(TcaError::InvalidAmount);
+        }
+        if self.executions.contains_key(&parent.parent_order_id) {
+            return Err(TcaError::DuplicateParent(parent.parent_order_id));
+        }
+        self.executions.insert(
+            parent.parent_order_id,
+            ParentExecution {
+                parent,
+                fills: Vec::new(),
+                market_volume: Quantity::default(),
+                last_price: None,
+                end_time: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// 记录子单成交
+    pub fn record_fill(&mut self, parent_order_id: u64, fill: ChildFill) -> Result<(), TcaError> {
+        if !fill.quantity.is_positive() || !fill.price.is_positive() {
+            return Err(TcaError::InvalidAmount);
+        }
+        let execution = self.active_mut(parent_order_id)?;
+        execution.fills.push(fill);
+        Ok(())
+    }
+
+    /// 记录一笔市场成交：计入该交易对所有执行中母单的市场成交量
+    pub fn record_market_trade(
+        &mut self,
+        trading_pair: TradingPair,
+        price: Price,
+        quantity: Quantity,
+    ) {
+        for execution in self.executions.values_mut() {
+            if execution.end_time.is_none() && execution.parent.trading_pair == trading_pair {
+                execution.market_volume += quantity;
+                execution.last_price = Some(price);
+            }
+        }
+    }
+
+    /// 母单结束（全部成交、撤销或到期），返回最终报告
+    pub fn complete(
+        &mut self,
+        parent_order_id: u64,
+        now: Timestamp,
+    ) -> Result<TcaReport, TcaError> {
+        let execution = self.active_mut(parent_order_id)?;
+        execution.end_time = Some(now);
+        Ok(execution.report())
+    }
+
+    /// 母单报告（执行中的母单返回截至当前的报告）
+    pub fn report(&self, parent_order_id: u64) -> Option<TcaReport> {
+        self.executions.get(&parent_order_id).map(ParentExecution::report)
+    }
+
+    /// 账户的全部母单报告（按母单ID升序）
+    pub fn reports_for(&self, account_id: AccountId) -> Vec<TcaReport> {
+        let mut reports: Vec<TcaReport> = self
+            .executions
+            .values()
+            .filter(|execution| execution.parent.account_id == account_id)
+            .map(ParentExecution::report)
+            .collect();
+        reports.sort_by_key(|report| report.parent_order_id);
+        reports
+    }
+
+    fn active_mut(&mut self, parent_order_id: u64) -> Result<&mut ParentExecution, TcaError> {
+        let execution = self
+            .executions
+            .get_mut(&parent_order_id)
+            .ok_or(TcaError::UnknownParent(parent_order_id))?;
+        if execution.end_time.is_some() {
+            return Err(TcaError::ParentCompleted(parent_order_id));
+        }
+        Ok(execution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(id: u64, side: OrderSide) -> ParentOrder {
+        ParentOrder {
+            parent_order_id: id,
+            account_id: AccountId(7),
+            trading_pair: TradingPair::BtcUsdt,
+            side,
+            strategy: AlgorithmStrategy::TWAP,
+            quantity: Quantity::from_f64(10.0),
+            arrival_price: Price::from_f64(100.0),
+            start_time: Timestamp(1_000),
+        }
+    }
+
+    fn fill(price: f64, quantity: f64) -> ChildFill {
+        ChildFill {
+            price: Price::from_f64(price),
+            quantity: Quantity::from_f64(quantity),
+            fee: Quantity::from_f64(0.5),
+            time: Timestamp(2_000),
+        }
+    }
+
+    #[test]
+    fn test_shortfall_slippage_and_participation() {
+        let mut store = TcaStore::new();
+        store.register(parent(1, OrderSide::Buy)).unwrap();
+        assert_eq!(store.register(parent(1, OrderSide::Buy)), Err(TcaError::DuplicateParent(1)));
+
+        store.record_fill(1, fill(101.0, 4.0)).unwrap();
+        store.record_fill(1, fill(103.0, 4.0)).unwrap();
+        store.record_market_trade(
+            TradingPair::BtcUsdt,
+            Price::from_f64(104.0),
+            Quantity::from_f64(40.0),
+        );
+        store.record_market_trade(
+            TradingPair::EthUsdt,
+            Price::from_f64(5.0),
+            Quantity::from_f64(99.0),
+        );
+
+        let report = store.complete(1, Timestamp(9_000)).unwrap();
+        assert_eq!(report.filled_quantity, Quantity::from_f64(8.0));
+        assert_eq!(report.average_price, Some(Price::from_f64(102.0)));
+        // (102 - 100) / 100 = 200bp
+        assert!((report.arrival_slippage_bps.unwrap() - 200.0).abs() < 1e-9);
+        // 已成交 8 × 2 + 手续费 1 = 17；未成交 2 × (104 - 100) = 8
+        assert!((report.implementation_shortfall - 25.0).abs() < 1e-9);
+        assert!((report.implementation_shortfall_bps - 250.0).abs() < 1e-9);
+        assert!((report.participation_rate.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(report.end_time, Some(Timestamp(9_000)));
+
+        // 结束后不再接受成交，也不再计入市场成交量
+        assert_eq!(store.record_fill(1, fill(100.0, 1.0)), Err(TcaError::ParentCompleted(1)));
+        store.record_market_trade(
+            TradingPair::BtcUsdt,
+            Price::from_f64(90.0),
+            Quantity::from_f64(10.0),
+        );
+        assert_eq!(store.report(1), Some(report));
+        assert_eq!(store.record_fill(2, fill(100.0, 1.0)), Err(TcaError::UnknownParent(2)));
+    }
+
+    #[test]
+    fn test_sell_side_improvement_is_negative_cost() {
+        let mut store = TcaStore::new();
+        store.register(parent(2, OrderSide::Sell)).unwrap();
+        assert_eq!(store.report(2).unwrap().arrival_slippage_bps, None);
+        assert_eq!(store.report(2).unwrap().participation_rate, None);
+
+        let mut better = fill(101.0, 10.0);
+        better.fee = Quantity::default();
+        store.record_fill(2, better).unwrap();
+        let report = store.report(2).unwrap();
+        assert!((report.arrival_slippage_bps.unwrap() + 100.0).abs() < 1e-9);
+        assert!((report.implementation_shortfall + 10.0).abs() < 1e-9);
+        assert_eq!(store.reports_for(AccountId(7)).len(), 1);
+        assert!(store.reports_for(AccountId(8)).is_empty());
+    }
+}
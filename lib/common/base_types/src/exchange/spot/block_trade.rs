@@ -0,0 +1,375 @@
+//! 大宗交易（场外协商成交）报告
+//!
+//! 双方在场外协商好价格与数量后由其中一方报告，另一方确认后入账：
+//! - 交易对须配置大宗交易规则：数量不低于 `min_quantity`，价格偏离参考价不超过 `price_band_bps`
+//! - 参考价由行情管道写入（最新成交价），没有参考价时拒绝报告
+//! - 报告方须为买方或卖方，确认方须为另一方；确认时按确认时刻的参考价重新检查价格带
+//! - 确认后经现货清算路径生成清算记录：报告方按挂单方计费，确认方按吃单方计费
+//!
+//! 大宗交易不进入订单簿，不影响最优挂单与最新成交价
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::account::clearing::{ClearingError, ClearingRecord, TradeInput};
+use crate::{AccountId, OrderSide, Price, Quantity, SequenceGenerator, Timestamp, TradingPair};
+
+/// 一个基点
+const BPS: f64 = 10_000.0;
+
+/// 交易对的大宗交易规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTradeRules {
+    /// 最小数量
+    pub min_quantity: Quantity,
+    /// 相对参考价的最大偏离（基点）
+    pub price_band_bps: u32,
+}
+
+/// 大宗交易错误
+#[derive(Debug, PartialEq)]
+pub enum BlockTradeError {
+    /// 交易对未开放大宗交易
+    NotEnabled(TradingPair),
+    /// 数量或价格非正
+    InvalidAmount,
+    /// 数量低于最小数量
+    BelowMinimum { min_quantity: Quantity },
+    /// 交易对没有参考价
+    NoReferencePrice(TradingPair),
+    /// 价格超出价格带
+    OutsidePriceBand { reference: Price, price: Price },
+    /// 买卖双方为同一账户
+    SelfTrade,
+    /// 报告方或确认方不是对应的交易方
+    NotCounterparty(AccountId),
+    /// 报告不存在或已入账
+    UnknownReport(u64),
+    /// 清算失败
+    Clearing(ClearingError),
+}
+
+impl fmt::Display for BlockTradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockTradeError::NotEnabled(pair) => {
+                write!(f, "Block trades are not enabled for {}", pair.to_symbol_string())
+            }
+            BlockTradeError::InvalidAmount => write!(f, "Price and quantity must be positive"),
+            BlockTradeError::BelowMinimum { min_quantity } => {
+                write!(f, "Quantity is below the block minimum {}", min_quantity)
+            }
+            BlockTradeError::NoReferencePrice(pair) => {
+                write!(f, "No reference price for {}", pair.to_symbol_string())
+            }
+            BlockTradeError::OutsidePriceBand { reference, price } => {
+                write!(f, "Price {} is outside the band around {}", price, reference)
+            }
+            BlockTradeError::SelfTrade => write!(f, "Buyer and seller must differ"),
+            BlockTradeError::NotCounterparty(account) => {
+                write!(f, "Account {} is not the expected counterparty", account.0)
+            }
+            BlockTradeError::UnknownReport(id) => write!(f, "Unknown block trade report: {}", id),
+            BlockTradeError::Clearing(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BlockTradeError {}
+
+/// 待确认的大宗交易报告
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTradeReport {
+    pub report_id: u64,
+    /// 报告方
+    pub reporter: AccountId,
+    pub trading_pair: TradingPair,
+    pub buyer: AccountId,
+    pub seller: AccountId,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub reported_at: Timestamp,
+}
+
+impl BlockTradeReport {
+    /// 应确认的一方
+    pub fn affirming_party(&self) -> AccountId {
+        if self.reporter == self.buyer { self.seller } else { self.buyer }
+    }
+}
+
+/// 已入账的大宗交易
+#[derive(Debug, Clone)]
+pub struct BookedBlockTrade {
+    pub report: BlockTradeReport,
+    pub trade_id: u64,
+    pub booked_at: Timestamp,
+    pub record: ClearingRecord,
+}
+
+/// 大宗交易台
+#[derive(Debug, Default)]
+pub struct BlockTradeDesk {
+    rules: HashMap<TradingPair, BlockTradeRules>,
+    reference_prices: HashMap<TradingPair, Price>,
+    next_report_id: u64,
+    pending: HashMap<u64, BlockTradeReport>,
+    /// 已入账、等待记账管道取走的成交
+    booked: Vec<BookedBlockTrade>,
+}
+
+impl BlockTradeDesk {
+    pub fn new() -> Self {
+        Self { next_report_id: 1, ..Self::default() }
+    }
+
+    /// 开放交易对的大宗交易
+    pub fn set_rules(&mut self, trading_pair: TradingPair, rules: BlockTradeRules) {
+        self.rules.insert(trading_pair, rules);
+    }
+
+    /// 更新参考价（行情管道调用）
+    pub fn set_reference_price(&mut self, trading_pair: TradingPair, price: Price) {
+        if price.is_positive() {
+            self.reference_prices.insert(trading_pair, price);
+        }
+    }
+
+    /// 报告大宗交易，返回报告ID
+    #[allow(clippy::too_many_arguments)]
+    pub fn report(
+        &mut self,
+        reporter: AccountId,
+        trading_pair: TradingPair,
+        buyer: AccountId,
+        seller: AccountId,
+        price: Price,
+        quantity: Quantity,
+        now: Timestamp,
+    ) -> Result<u64, BlockTradeError> {
+        if buyer == seller {
+            return Err(BlockTradeError::SelfTrade);
+        }
+        if reporter != buyer && reporter != seller {
+            return Err(BlockTradeError::NotCounterparty(reporter));
+        }
+        self.validate(trading_pair, price, quantity)?;
+
+        let report_id = self.next_report_id.max(1);
+        self.next_report_id = report_id + 1;
+        self.pending.insert(
+            report_id,
+            BlockTradeReport {
+                report_id,
+                reporter,
+                trading_pair,
+                buyer,
+                seller,
+                price,
+                quantity,
+                reported_at: now,
+            },
+        );
+        Ok(report_id)
+    }
+
+    /// 对手方确认并入账
+    ///
+    /// 成交ID取自 `trade_ids`，经 `clear` 清算；价格带检查失败或清算失败时报告保持待确认。
+    /// 返回入账的成交，同一笔同时留待 [`drain_booked`](Self::drain_booked) 取走记账
+    pub fn affirm(
+        &mut self,
+        report_id: u64,
+        account: AccountId,
+        now: Timestamp,
+        trade_ids: &dyn SequenceGenerator,
+        clear: impl FnOnce(&TradeInput) -> Result<ClearingRecord, ClearingError>,
+    ) -> Result<BookedBlockTrade, BlockTradeError> {
+        let report =
+            *self.pending.get(&report_id).ok_or(BlockTradeError::UnknownReport(report_id))?;
+        if account != report.affirming_party() {
+            return Err(BlockTradeError::NotCounterparty(account));
+        }
+        self.validate(report.trading_pair, report.price, report.quantity)?;
+
+        let trade = TradeInput {
+            trade_id: trade_ids.next_id(),
+            trading_pair: report.trading_pair,
+            price: report.price,
+            quantity: report.quantity,
+            buyer: report.buyer,
+            seller: report.seller,
+            maker_side: if report.reporter == report.buyer {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            },
+            timestamp: now,
+        };
+        let record = clear(&trade).map_err(BlockTradeError::Clearing)?;
+        self.pending.remove(&report_id);
+        let booked = BookedBlockTrade { report, trade_id: trade.trade_id, booked_at: now, record };
+        self.booked.push(booked.clone());
+        Ok(booked)
+    }
+
+    /// 待确认报告（只有交易双方可见）
+    pub fn pending_report(&self, report_id: u64, account: AccountId) -> Option<&BlockTradeReport> {
+        self.pending
+            .get(&report_id)
+            .filter(|report| report.buyer == account || report.seller == account)
+    }
+
+    /// 取走已入账的成交（记账管道调用）
+    pub fn drain_booked(&mut self) -> Vec<BookedBlockTrade> {
+        std::mem::take(&mut self.booked)
+    }
+
+    fn validate(
+        &self,
+        trading_pair: TradingPair,
+        price: Price,
+        quantity: Quantity,
+    ) -> Result<(), BlockTradeError> {
+        let rules =
+            self.rules.get(&trading_pair).ok_or(BlockTradeError::NotEnabled(trading_pair))?;
+        if !price.is_positive() || !quantity.is_positive() {
+            return Err(BlockTradeError::InvalidAmount);
+        }
+        if quantity < rules.min_quantity {
+            return Err(BlockTradeError::BelowMinimum { min_quantity: rules.min_quantity });
+        }
+        let reference = *self
+            .reference_prices
+            .get(&trading_pair)
+            .ok_or(BlockTradeError::NoReferencePrice(trading_pair))?;
+        let deviation_bps = (price.to_f64() - reference.to_f64()).abs() / reference.to_f64() * BPS;
+        if deviation_bps > f64::from(rules.price_band_bps) {
+            return Err(BlockTradeError::OutsidePriceBand { reference, price });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AtomicSequence;
+    use crate::account::clearing::{ClearingContext, FeeProfile, clear_spot_trade};
+    use crate::fee::fee_types::{FeeType, ProductFeeConfig};
+
+    const BUYER: AccountId = AccountId(1);
+    const SELLER: AccountId = AccountId(2);
+
+    fn desk() -> BlockTradeDesk {
+        let mut desk = BlockTradeDesk::new();
+        desk.set_rules(
+            TradingPair::BtcUsdt,
+            BlockTradeRules { min_quantity: Quantity::from_f64(10.0), price_band_bps: 100 },
+        );
+        desk
+    }
+
+    #[test]
+    fn test_report_validation() {
+        let mut desk = desk();
+        let report = |desk: &mut BlockTradeDesk, price: f64, qty: f64| {
+            desk.report(
+                SELLER,
+                TradingPair::BtcUsdt,
+                BUYER,
+                SELLER,
+                Price::from_f64(price),
+                Quantity::from_f64(qty),
+                Timestamp(0),
+            )
+        };
+        assert_eq!(
+            report(&mut desk, 100.0, 10.0),
+            Err(BlockTradeError::NoReferencePrice(TradingPair::BtcUsdt))
+        );
+        desk.set_reference_price(TradingPair::BtcUsdt, Price::from_f64(100.0));
+        assert_eq!(
+            report(&mut desk, 100.0, 5.0),
+            Err(BlockTradeError::BelowMinimum { min_quantity: Quantity::from_f64(10.0) })
+        );
+        assert!(matches!(
+            report(&mut desk, 101.5, 10.0),
+            Err(BlockTradeError::OutsidePriceBand { .. })
+        ));
+        assert_eq!(report(&mut desk, 99.0, 10.0), Ok(1));
+        assert_eq!(
+            desk.report(
+                AccountId(3),
+                TradingPair::BtcUsdt,
+                BUYER,
+                SELLER,
+                Price::from_f64(100.0),
+                Quantity::from_f64(10.0),
+                Timestamp(0),
+            ),
+            Err(BlockTradeError::NotCounterparty(AccountId(3)))
+        );
+        assert!(matches!(
+            desk.report(
+                BUYER,
+                TradingPair::EthUsdt,
+                BUYER,
+                SELLER,
+                Price::from_f64(100.0),
+                Quantity::from_f64(10.0),
+                Timestamp(0),
+            ),
+            Err(BlockTradeError::NotEnabled(TradingPair::EthUsdt))
+        ));
+    }
+
+    #[test]
+    fn test_affirm_books_through_clearing() {
+        let mut desk = desk();
+        desk.set_reference_price(TradingPair::BtcUsdt, Price::from_f64(100.0));
+        let report_id = desk
+            .report(
+                SELLER,
+                TradingPair::BtcUsdt,
+                BUYER,
+                SELLER,
+                Price::from_f64(100.5),
+                Quantity::from_f64(20.0),
+                Timestamp(0),
+            )
+            .unwrap();
+        assert!(desk.pending_report(report_id, BUYER).is_some());
+        assert!(desk.pending_report(report_id, AccountId(3)).is_none());
+
+        let config = ProductFeeConfig::spot(0.0005, 0.001);
+        let context = ClearingContext {
+            fee_config: &config,
+            fee_account: AccountId(99),
+            buyer_profile: FeeProfile::default(),
+            seller_profile: FeeProfile::default(),
+        };
+        let trade_ids = AtomicSequence::starting_at(900);
+        // 报告方不能自行确认
+        assert_eq!(
+            desk.affirm(report_id, SELLER, Timestamp(5), &trade_ids, |t| clear_spot_trade(
+                t, &context
+            ))
+            .unwrap_err(),
+            BlockTradeError::NotCounterparty(SELLER)
+        );
+        let booked = desk
+            .affirm(report_id, BUYER, Timestamp(5), &trade_ids, |t| clear_spot_trade(t, &context))
+            .unwrap();
+        assert_eq!(booked.trade_id, 900);
+        assert_eq!(booked.record.notional, Quantity::from_f64(2_010.0));
+        assert_eq!(booked.record.seller_fee.fee_type, FeeType::Maker);
+        assert_eq!(booked.record.buyer_fee.fee_type, FeeType::Taker);
+
+        assert_eq!(desk.drain_booked().len(), 1);
+        assert!(matches!(
+            desk.affirm(report_id, BUYER, Timestamp(6), &trade_ids, |_| unreachable!()),
+            Err(BlockTradeError::UnknownReport(_))
+        ));
+    }
+}
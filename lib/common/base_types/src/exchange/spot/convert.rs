@@ -0,0 +1,209 @@
+//! 闪兑引擎
+//!
+//! 不经订单簿，按指数价与平台流动性账户直接兑换：
+//! - 报价：`gross = amount × 指数价`，手续费 `fee = gross × fee_rate`，用户实得 `gross - fee`
+//! - 结算：用户付出源资产、收入目标资产，流动性账户为对手方，手续费留在流动性账户；
+//!   同一账户的多笔兑换合并为一笔结算，每种资产净额为零
+//!
+//! 指数价来源见 [`IndexPriceSource`]，通常为合成行情
+//! （[`SyntheticTickers::index_price`](crate::mark_data::spot::synthetic::SyntheticTickers::index_price)）
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::account::balance_change::BalanceChangeType;
+use crate::account::settlement::Settlement;
+use crate::mark_data::spot::synthetic::SyntheticTickers;
+use crate::{AccountId, AssetId, Price, Quantity, Timestamp};
+
+/// 默认闪兑手续费率
+pub const DEFAULT_CONVERT_FEE_RATE: f64 = 0.002;
+
+/// 指数价来源
+pub trait IndexPriceSource {
+    /// `base` 以 `quote` 计的指数价
+    fn index_price(&self, base: AssetId, quote: AssetId) -> Option<Price>;
+}
+
+/// 固定价格表（运营配置或测试）
+impl IndexPriceSource for HashMap<(AssetId, AssetId), Price> {
+    fn index_price(&self, base: AssetId, quote: AssetId) -> Option<Price> {
+        self.get(&(base, quote)).copied()
+    }
+}
+
+impl IndexPriceSource for SyntheticTickers {
+    fn index_price(&self, base: AssetId, quote: AssetId) -> Option<Price> {
+        SyntheticTickers::index_price(self, base, quote)
+    }
+}
+
+/// 闪兑错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// 源资产与目标资产相同
+    SameAsset(AssetId),
+    /// 兑换数量不为正
+    NonPositiveAmount,
+    /// 无指数价
+    NoIndexPrice { from: AssetId, to: AssetId },
+    /// 兑换所得为零（数量过小）
+    ZeroProceeds { from: AssetId, amount: Quantity },
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::SameAsset(asset) => {
+                write!(f, "Cannot convert {} into itself", asset.as_str())
+            }
+            ConvertError::NonPositiveAmount => write!(f, "Convert amount must be positive"),
+            ConvertError::NoIndexPrice { from, to } => {
+                write!(f, "No index price for {}/{}", from.as_str(), to.as_str())
+            }
+            ConvertError::ZeroProceeds { from, amount } => {
+                write!(f, "{} {} is too small to convert", amount.to_f64(), from.as_str())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// 闪兑报价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConvertQuote {
+    pub from: AssetId,
+    pub to: AssetId,
+    /// 付出的源资产数量
+    pub amount: Quantity,
+    pub index_price: Price,
+    /// 按指数价折算的目标资产数量
+    pub gross: Quantity,
+    /// 手续费（目标资产）
+    pub fee: Quantity,
+}
+
+impl ConvertQuote {
+    /// 用户实得目标资产数量
+    pub fn net(&self) -> Quantity {
+        self.gross - self.fee
+    }
+}
+
+/// 闪兑引擎
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvertEngine {
+    /// 流动性账户（兑换对手方，收取手续费）
+    liquidity_account: AccountId,
+    fee_rate: f64,
+}
+
+impl ConvertEngine {
+    pub fn new(liquidity_account: AccountId) -> Self {
+        Self { liquidity_account, fee_rate: DEFAULT_CONVERT_FEE_RATE }
+    }
+
+    pub fn with_fee_rate(mut self, fee_rate: f64) -> Self {
+        self.fee_rate = fee_rate.max(0.0);
+        self
+    }
+
+    pub fn liquidity_account(&self) -> AccountId {
+        self.liquidity_account
+    }
+
+    /// 按指数价报价
+    pub fn quote(
+        &self,
+        from: AssetId,
+        to: AssetId,
+        amount: Quantity,
+        prices: &dyn IndexPriceSource,
+    ) -> Result<ConvertQuote, ConvertError> {
+        if from == to {
+            return Err(ConvertError::SameAsset(from));
+        }
+        if !amount.is_positive() {
+            return Err(ConvertError::NonPositiveAmount);
+        }
+        let index_price =
+            prices.index_price(from, to).ok_or(ConvertError::NoIndexPrice { from, to })?;
+        let gross = index_price
+            .checked_mul(amount)
+            .filter(Quantity::is_positive)
+            .ok_or(ConvertError::ZeroProceeds { from, amount })?;
+        let fee = Quantity::from_f64(gross.to_f64() * self.fee_rate).min(gross);
+        Ok(ConvertQuote { from, to, amount, index_price, gross, fee })
+    }
+
+    /// 一个账户的多笔兑换合并为一笔结算
+    pub fn settle(
+        &self,
+        account_id: AccountId,
+        quotes: &[ConvertQuote],
+        settlement_id: u64,
+        timestamp: Timestamp,
+    ) -> Settlement {
+        let liquidity = self.liquidity_account;
+        let zero = Quantity::default();
+        let mut settlement = Settlement::new(settlement_id, timestamp);
+        for quote in quotes {
+            settlement = settlement
+                .with_entry(
+                    account_id,
+                    quote.from,
+                    zero - quote.amount,
+                    BalanceChangeType::Transfer,
+                )
+                .with_entry(liquidity, quote.from, quote.amount, BalanceChangeType::Transfer)
+                .with_entry(liquidity, quote.to, zero - quote.gross, BalanceChangeType::Transfer)
+                .with_entry(account_id, quote.to, quote.gross, BalanceChangeType::Transfer);
+            if quote.fee.is_positive() {
+                settlement = settlement
+                    .with_entry(account_id, quote.to, zero - quote.fee, BalanceChangeType::Fee)
+                    .with_entry(liquidity, quote.to, quote.fee, BalanceChangeType::Fee);
+            }
+        }
+        settlement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: AccountId = AccountId(1);
+    const DESK: AccountId = AccountId(500);
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    #[test]
+    fn test_quote_and_settle_balanced() {
+        let prices = HashMap::from([((AssetId::Btc, AssetId::Usdt), q(40_000.0))]);
+        let engine = ConvertEngine::new(DESK).with_fee_rate(0.01);
+        let quote = engine.quote(AssetId::Btc, AssetId::Usdt, q(0.0001), &prices).unwrap();
+        assert_eq!((quote.gross, quote.fee, quote.net()), (q(4.0), q(0.04), q(3.96)));
+
+        let settlement = engine.settle(ALICE, &[quote], 7, Timestamp(1));
+        assert!(settlement.check_invariants().is_ok());
+        let alice_usdt: Quantity = settlement
+            .entries
+            .iter()
+            .filter(|e| e.account_id == ALICE && e.asset_id == AssetId::Usdt)
+            .map(|e| e.amount)
+            .sum();
+        assert_eq!(alice_usdt, q(3.96));
+
+        assert_eq!(
+            engine.quote(AssetId::Eth, AssetId::Usdt, q(1.0), &prices),
+            Err(ConvertError::NoIndexPrice { from: AssetId::Eth, to: AssetId::Usdt })
+        );
+        assert_eq!(
+            engine.quote(AssetId::Usdt, AssetId::Usdt, q(1.0), &prices),
+            Err(ConvertError::SameAsset(AssetId::Usdt))
+        );
+    }
+}
@@ -1,3 +1,8 @@
+pub mod algo_tca;
+pub mod block_trade;
+pub mod convert;
+pub mod rfq;
+pub mod spot_conditional;
 pub mod spot_order_base;
 pub mod spot_order_soa;
 pub mod spot_types;
@@ -0,0 +1,574 @@
+//! 询价（RFQ）/ 价格改善竞价
+//!
+//! 流程：
+//! 1. 询价方（taker）指定交易对、方向、数量与受邀做市商，发起询价
+//! 2. 受邀做市商在报价窗口内提交确定报价（firm quote），须覆盖询价全部数量；
+//!    同一做市商重复报价时以最新报价为准
+//! 3. 窗口结束时按最优报价成交：买入取最低价，卖出取最高价，同价先到先得；
+//!    成交经现货清算路径（[`clear_spot_trade`](crate::account::clearing::clear_spot_trade)）
+//!    生成清算记录，做市商按挂单方计费
+//! 4. 窗口内无报价则询价过期；询价方可在窗口结束前撤销
+//!
+//! 生命周期的每一步产生 [`RfqEvent`]，由网关按接收方推送
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::account::clearing::{ClearingError, ClearingRecord, TradeInput};
+use crate::mark_data::spot::synthetic::SyntheticTickers;
+use crate::{AccountId, OrderSide, Price, Quantity, SequenceGenerator, Timestamp, TradingPair};
+
+/// 询价ID
+pub type RfqId = u64;
+
+/// 询价配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RfqConfig {
+    /// 报价窗口（纳秒）
+    pub quote_window: u64,
+    /// 单次询价最多邀请的做市商数
+    pub max_makers: usize,
+}
+
+impl Default for RfqConfig {
+    fn default() -> Self {
+        Self { quote_window: 2_000_000_000, max_makers: 16 }
+    }
+}
+
+/// 询价错误
+#[derive(Debug, PartialEq)]
+pub enum RfqError {
+    /// 询价不存在
+    UnknownRfq(RfqId),
+    /// 询价已结束
+    Closed(RfqId),
+    /// 报价窗口已结束
+    WindowElapsed(RfqId),
+    /// 做市商未受邀
+    MakerNotInvited { rfq_id: RfqId, maker: AccountId },
+    /// 不是询价方
+    NotRequester { rfq_id: RfqId, account: AccountId },
+    /// 未邀请做市商或超过上限
+    InvalidMakers,
+    /// 数量或价格非正
+    InvalidAmount,
+    /// 报价数量不足询价数量
+    InsufficientSize { rfq_id: RfqId },
+}
+
+impl fmt::Display for RfqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RfqError::UnknownRfq(id) => write!(f, "Unknown RFQ: {}", id),
+            RfqError::Closed(id) => write!(f, "RFQ {} is closed", id),
+            RfqError::WindowElapsed(id) => write!(f, "RFQ {} quote window has elapsed", id),
+            RfqError::MakerNotInvited { rfq_id, maker } => {
+                write!(f, "Maker {} is not invited to RFQ {}", maker.0, rfq_id)
+            }
+            RfqError::NotRequester { rfq_id, account } => {
+                write!(f, "Account {} did not request RFQ {}", account.0, rfq_id)
+            }
+            RfqError::InvalidMakers => write!(f, "Invalid maker selection"),
+            RfqError::InvalidAmount => write!(f, "Price and quantity must be positive"),
+            RfqError::InsufficientSize { rfq_id } => {
+                write!(f, "Quote does not cover the size of RFQ {}", rfq_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RfqError {}
+
+/// 询价请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteRequest {
+    pub rfq_id: RfqId,
+    pub taker: AccountId,
+    pub trading_pair: TradingPair,
+    /// 询价方方向
+    pub side: OrderSide,
+    pub quantity: Quantity,
+    /// 受邀做市商
+    pub makers: Vec<AccountId>,
+    pub created_at: Timestamp,
+    /// 报价窗口结束时间
+    pub expires_at: Timestamp,
+}
+
+impl QuoteRequest {
+    /// 合成行情给出的参考成交价（询价方买入取合成卖价，卖出取合成买价）
+    ///
+    /// 供询价方评估报价，或在直接订单簿流动性不足时作为价格改善的基准
+    pub fn reference_price(&self, tickers: &SyntheticTickers) -> Option<Price> {
+        let pair = self.trading_pair;
+        tickers.indicative_price(pair.base_asset(), pair.quote_asset(), self.side)
+    }
+}
+
+/// 确定报价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmQuote {
+    pub quote_id: u64,
+    pub rfq_id: RfqId,
+    pub maker: AccountId,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub received_at: Timestamp,
+}
+
+/// 询价状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfqStatus {
+    Open,
+    Filled,
+    /// 窗口内无报价
+    Expired,
+    /// 询价方撤销
+    Cancelled,
+    /// 清算失败
+    Rejected,
+}
+
+/// 生命周期事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RfqEventKind {
+    Requested,
+    Quoted(FirmQuote),
+    Filled { quote: FirmQuote, trade_id: u64 },
+    Expired,
+    Cancelled,
+    Rejected,
+}
+
+/// RFQ 事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RfqEvent {
+    pub request: QuoteRequest,
+    pub kind: RfqEventKind,
+    pub time: Timestamp,
+}
+
+impl RfqEvent {
+    /// 事件接收方：询价推送给受邀做市商，报价推送给询价方与报价的做市商，
+    /// 结束事件推送给询价方与全部受邀做市商
+    pub fn recipients(&self) -> Vec<AccountId> {
+        match &self.kind {
+            RfqEventKind::Requested => self.request.makers.clone(),
+            RfqEventKind::Quoted(quote) => vec![self.request.taker, quote.maker],
+            _ => std::iter::once(self.request.taker)
+                .chain(self.request.makers.iter().copied())
+                .collect(),
+        }
+    }
+}
+
+/// 窗口结束的询价结果
+#[derive(Debug)]
+pub enum RfqOutcome {
+    Filled { rfq_id: RfqId, quote: FirmQuote, record: Box<ClearingRecord> },
+    Expired { rfq_id: RfqId },
+    Rejected { rfq_id: RfqId, error: ClearingError },
+}
+
+#[derive(Debug)]
+struct RfqState {
+    request: QuoteRequest,
+    /// 做市商 → 最新报价
+    quotes: BTreeMap<u64, FirmQuote>,
+    status: RfqStatus,
+}
+
+impl RfqState {
+    /// 最优报价：买入最低价、卖出最高价，同价按报价时间与报价ID
+    fn best_quote(&self) -> Option<FirmQuote> {
+        let side = self.request.side;
+        self.quotes.values().copied().min_by(|a, b| {
+            let by_price = match side {
+                OrderSide::Buy => a.price.cmp(&b.price),
+                OrderSide::Sell => b.price.cmp(&a.price),
+            };
+            by_price.then(a.received_at.0.cmp(&b.received_at.0)).then(a.quote_id.cmp(&b.quote_id))
+        })
+    }
+}
+
+/// 询价簿
+#[derive(Debug)]
+pub struct RfqBook {
+    config: RfqConfig,
+    next_rfq_id: RfqId,
+    next_quote_id: u64,
+    rfqs: BTreeMap<RfqId, RfqState>,
+    events: Vec<RfqEvent>,
+}
+
+impl Default for RfqBook {
+    fn default() -> Self {
+        Self::new(RfqConfig::default())
+    }
+}
+
+impl RfqBook {
+    pub fn new(config: RfqConfig) -> Self {
+        Self { config, next_rfq_id: 1, next_quote_id: 1, rfqs: BTreeMap::new(), events: Vec::new() }
+    }
+
+    /// 发起询价
+    pub fn request(
+        &mut self,
+        taker: AccountId,
+        trading_pair: TradingPair,
+        side: OrderSide,
+        quantity: Quantity,
+        makers: &[AccountId],
+        now: Timestamp,
+    ) -> Result<RfqId, RfqError> {
+        if !quantity.is_positive() {
+            return Err(RfqError::InvalidAmount);
+        }
+        let mut makers: Vec<AccountId> =
+            makers.iter().copied().filter(|maker| *maker != taker).collect();
+        makers.sort_by_key(|maker| maker.0);
+        makers.dedup();
+        if makers.is_empty() || makers.len() > self.config.max_makers {
+            return Err(RfqError::InvalidMakers);
+        }
+
+        let rfq_id = self.next_rfq_id;
+        self.next_rfq_id += 1;
+        let request = QuoteRequest {
+            rfq_id,
+            taker,
+            trading_pair,
+            side,
+            quantity,
+            makers,
+            created_at: now,
+            expires_at: Timestamp(now.0 + self.config.quote_window),
+        };
+        self.emit(&request, RfqEventKind::Requested, now);
+        self.rfqs
+            .insert(rfq_id, RfqState { request, quotes: BTreeMap::new(), status: RfqStatus::Open });
+        Ok(rfq_id)
+    }
+
+    /// 做市商提交确定报价，返回报价ID
+    pub fn quote(
+        &mut self,
+        rfq_id: RfqId,
+        maker: AccountId,
+        price: Price,
+        quantity: Quantity,
+        now: Timestamp,
+    ) -> Result<u64, RfqError> {
+        let state = Self::open_state(&mut self.rfqs, rfq_id)?;
+        if now.0 >= state.request.expires_at.0 {
+            return Err(RfqError::WindowElapsed(rfq_id));
+        }
+        if !state.request.makers.contains(&maker) {
+            return Err(RfqError::MakerNotInvited { rfq_id, maker });
+        }
+        if !price.is_positive() || !quantity.is_positive() {
+            return Err(RfqError::InvalidAmount);
+        }
+        if quantity < state.request.quantity {
+            return Err(RfqError::InsufficientSize { rfq_id });
+        }
+
+        let quote = FirmQuote {
+            quote_id: self.next_quote_id,
+            rfq_id,
+            maker,
+            price,
+            quantity,
+            received_at: now,
+        };
+        self.next_quote_id += 1;
+        state.quotes.insert(maker.0, quote);
+        let request = state.request.clone();
+        self.emit(&request, RfqEventKind::Quoted(quote), now);
+        Ok(quote.quote_id)
+    }
+
+    /// 询价方在窗口结束前撤销询价
+    pub fn cancel(
+        &mut self,
+        rfq_id: RfqId,
+        account: AccountId,
+        now: Timestamp,
+    ) -> Result<(), RfqError> {
+        let state = Self::open_state(&mut self.rfqs, rfq_id)?;
+        if state.request.taker != account {
+            return Err(RfqError::NotRequester { rfq_id, account });
+        }
+        if now.0 >= state.request.expires_at.0 {
+            return Err(RfqError::WindowElapsed(rfq_id));
+        }
+        state.status = RfqStatus::Cancelled;
+        let request = state.request.clone();
+        self.emit(&request, RfqEventKind::Cancelled, now);
+        Ok(())
+    }
+
+    /// 结束到期的询价（按询价ID顺序）
+    ///
+    /// 有报价的询价按最优报价生成成交，经 `clear` 清算；成交ID取自 `trade_ids`
+    pub fn settle_due(
+        &mut self,
+        now: Timestamp,
+        trade_ids: &dyn SequenceGenerator,
+        mut clear: impl FnMut(&TradeInput) -> Result<ClearingRecord, ClearingError>,
+    ) -> Vec<RfqOutcome> {
+        let due: Vec<RfqId> = self
+            .rfqs
+            .values()
+            .filter(|state| state.status == RfqStatus::Open && state.request.expires_at.0 <= now.0)
+            .map(|state| state.request.rfq_id)
+            .collect();
+
+        let mut outcomes = Vec::with_capacity(due.len());
+        for rfq_id in due {
+            let Some(state) = self.rfqs.get_mut(&rfq_id) else {
+                continue;
+            };
+            let Some(quote) = state.best_quote() else {
+                state.status = RfqStatus::Expired;
+                let request = state.request.clone();
+                self.emit(&request, RfqEventKind::Expired, now);
+                outcomes.push(RfqOutcome::Expired { rfq_id });
+                continue;
+            };
+
+            let request = &state.request;
+            let (buyer, seller) = match request.side {
+                OrderSide::Buy => (request.taker, quote.maker),
+                OrderSide::Sell => (quote.maker, request.taker),
+            };
+            let trade = TradeInput {
+                trade_id: trade_ids.next_id(),
+                trading_pair: request.trading_pair,
+                price: quote.price,
+                quantity: request.quantity,
+                buyer,
+                seller,
+                maker_side: request.side.opposite(),
+                timestamp: now,
+            };
+            let request = request.clone();
+            match clear(&trade) {
+                Ok(record) => {
+                    state.status = RfqStatus::Filled;
+                    let kind = RfqEventKind::Filled { quote, trade_id: trade.trade_id };
+                    self.emit(&request, kind, now);
+                    outcomes.push(RfqOutcome::Filled { rfq_id, quote, record: Box::new(record) });
+                }
+                Err(error) => {
+                    state.status = RfqStatus::Rejected;
+                    self.emit(&request, RfqEventKind::Rejected, now);
+                    outcomes.push(RfqOutcome::Rejected { rfq_id, error });
+                }
+            }
+        }
+        outcomes
+    }
+
+    /// 询价请求
+    pub fn request_of(&self, rfq_id: RfqId) -> Option<&QuoteRequest> {
+        self.rfqs.get(&rfq_id).map(|state| &state.request)
+    }
+
+    /// 询价状态
+    pub fn status(&self, rfq_id: RfqId) -> Option<RfqStatus> {
+        self.rfqs.get(&rfq_id).map(|state| state.status)
+    }
+
+    /// 当前最优报价
+    pub fn best_quote(&self, rfq_id: RfqId) -> Option<FirmQuote> {
+        self.rfqs.get(&rfq_id).and_then(RfqState::best_quote)
+    }
+
+    /// 取出待推送的事件
+    pub fn drain_events(&mut self) -> Vec<RfqEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 清理已结束的询价
+    pub fn purge_closed(&mut self) {
+        self.rfqs.retain(|_, state| state.status == RfqStatus::Open);
+    }
+
+    fn open_state(
+        rfqs: &mut BTreeMap<RfqId, RfqState>,
+        rfq_id: RfqId,
+    ) -> Result<&mut RfqState, RfqError> {
+        let state = rfqs.get_mut(&rfq_id).ok_or(RfqError::UnknownRfq(rfq_id))?;
+        if state.status != RfqStatus::Open {
+            return Err(RfqError::Closed(rfq_id));
+        }
+        Ok(state)
+    }
+
+    fn emit(&mut self, request: &QuoteRequest, kind: RfqEventKind, time: Timestamp) {
+        self.events.push(RfqEvent { request: request.clone(), kind, time });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::clearing::{ClearingContext, FeeProfile, clear_spot_trade};
+    use crate::fee::fee_types::{FeeType, ProductFeeConfig};
+    use crate::{AssetId, AtomicSequence};
+
+    const TAKER: AccountId = AccountId(1);
+    const MAKER_A: AccountId = AccountId(10);
+    const MAKER_B: AccountId = AccountId(11);
+    const FEE: AccountId = AccountId(99);
+
+    fn book() -> RfqBook {
+        RfqBook::new(RfqConfig { quote_window: 1_000, max_makers: 4 })
+    }
+
+    #[test]
+    fn test_rfq_crosses_best_quote_through_clearing() {
+        let mut book = book();
+        let qty = Quantity::from_f64(2.0);
+        let rfq_id = book
+            .request(
+                TAKER,
+                TradingPair::BtcUsdt,
+                OrderSide::Buy,
+                qty,
+                &[MAKER_A, MAKER_B],
+                Timestamp(0),
+            )
+            .unwrap();
+
+        book.quote(rfq_id, MAKER_A, Price::from_f64(101.0), qty, Timestamp(100)).unwrap();
+        book.quote(rfq_id, MAKER_B, Price::from_f64(100.5), qty, Timestamp(200)).unwrap();
+        // 同价先到先得：A 改价到 100.5，但晚于 B
+        book.quote(rfq_id, MAKER_A, Price::from_f64(100.5), qty, Timestamp(300)).unwrap();
+        assert_eq!(book.best_quote(rfq_id).unwrap().maker, MAKER_B);
+
+        assert_eq!(
+            book.quote(rfq_id, AccountId(12), Price::from_f64(99.0), qty, Timestamp(300)),
+            Err(RfqError::MakerNotInvited { rfq_id, maker: AccountId(12) })
+        );
+        assert_eq!(
+            book.quote(
+                rfq_id,
+                MAKER_A,
+                Price::from_f64(99.0),
+                Quantity::from_f64(1.0),
+                Timestamp(300)
+            ),
+            Err(RfqError::InsufficientSize { rfq_id })
+        );
+
+        let config = ProductFeeConfig::spot(0.001, 0.002);
+        let context = ClearingContext {
+            fee_config: &config,
+            fee_account: FEE,
+            buyer_profile: FeeProfile::default(),
+            seller_profile: FeeProfile::default(),
+        };
+        let trade_ids = AtomicSequence::starting_at(500);
+        assert!(
+            book.settle_due(Timestamp(999), &trade_ids, |t| clear_spot_trade(t, &context))
+                .is_empty()
+        );
+        let outcomes =
+            book.settle_due(Timestamp(1_000), &trade_ids, |t| clear_spot_trade(t, &context));
+        match outcomes.as_slice() {
+            [RfqOutcome::Filled { quote, record, .. }] => {
+                assert_eq!(quote.maker, MAKER_B);
+                assert_eq!(record.trade.trade_id, 500);
+                assert_eq!((record.trade.buyer, record.trade.seller), (TAKER, MAKER_B));
+                assert_eq!(record.notional, Quantity::from_f64(201.0));
+                assert_eq!(record.buyer_fee.fee_type, FeeType::Taker);
+                assert_eq!(record.seller_fee.fee_type, FeeType::Maker);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(book.status(rfq_id), Some(RfqStatus::Filled));
+
+        let events = book.drain_events();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0].recipients(), vec![MAKER_A, MAKER_B]);
+        assert_eq!(events[1].recipients(), vec![TAKER, MAKER_A]);
+        assert!(matches!(events[4].kind, RfqEventKind::Filled { trade_id: 500, .. }));
+        assert_eq!(events[4].recipients(), vec![TAKER, MAKER_A, MAKER_B]);
+    }
+
+    #[test]
+    fn test_rfq_expires_or_is_cancelled() {
+        let mut book = book();
+        let qty = Quantity::from_f64(1.0);
+        let expiring = book
+            .request(TAKER, TradingPair::BtcUsdt, OrderSide::Sell, qty, &[MAKER_A], Timestamp(0))
+            .unwrap();
+        let cancelled = book
+            .request(TAKER, TradingPair::BtcUsdt, OrderSide::Sell, qty, &[MAKER_A], Timestamp(0))
+            .unwrap();
+        assert_eq!(
+            book.cancel(cancelled, MAKER_A, Timestamp(10)),
+            Err(RfqError::NotRequester { rfq_id: cancelled, account: MAKER_A })
+        );
+        book.cancel(cancelled, TAKER, Timestamp(10)).unwrap();
+        assert_eq!(
+            book.quote(cancelled, MAKER_A, Price::from_f64(1.0), qty, Timestamp(20)),
+            Err(RfqError::Closed(cancelled))
+        );
+
+        let outcomes =
+            book.settle_due(Timestamp(1_000), &AtomicSequence::default(), |_| unreachable!());
+        assert!(
+            matches!(outcomes.as_slice(), [RfqOutcome::Expired { rfq_id }] if *rfq_id == expiring)
+        );
+        assert_eq!(
+            book.quote(expiring, MAKER_A, Price::from_f64(1.0), qty, Timestamp(1_000)),
+            Err(RfqError::Closed(expiring))
+        );
+
+        assert_eq!(
+            book.request(TAKER, TradingPair::BtcUsdt, OrderSide::Buy, qty, &[TAKER], Timestamp(0)),
+            Err(RfqError::InvalidMakers)
+        );
+        book.purge_closed();
+        assert_eq!(book.status(expiring), None);
+    }
+
+    #[test]
+    fn test_reference_price_from_synthetic_tickers() {
+        use crate::mark_data::spot::ticker::BookTicker;
+
+        let mut tickers = SyntheticTickers::default();
+        tickers.register(AssetId::Btc, AssetId::Eth).unwrap();
+        for (pair, bid, ask) in
+            [(TradingPair::BtcUsdt, 40_000.0, 40_100.0), (TradingPair::EthUsdt, 2_000.0, 2_005.0)]
+        {
+            tickers.on_book_ticker(&BookTicker {
+                symbol_id: pair as u32,
+                update_id: 1,
+                bid_price: Some(Price::from_f64(bid)),
+                bid_qty: Quantity::from_f64(1.0),
+                ask_price: Some(Price::from_f64(ask)),
+                ask_qty: Quantity::from_f64(1.0),
+            });
+        }
+
+        let mut book = book();
+        let qty = Quantity::from_f64(1.0);
+        let buy = book
+            .request(TAKER, TradingPair::BtcEth, OrderSide::Buy, qty, &[MAKER_A], Timestamp(0))
+            .unwrap();
+        let sell = book
+            .request(TAKER, TradingPair::BtcEth, OrderSide::Sell, qty, &[MAKER_A], Timestamp(0))
+            .unwrap();
+        // 买入取 40100 / 2000，卖出取 40000 / 2005
+        let reference = |rfq_id| book.request_of(rfq_id).unwrap().reference_price(&tickers);
+        assert_eq!(reference(buy), Some(Price::from_f64(20.05)));
+        assert_eq!(reference(sell), Some(Price::from_raw(1_995_012_468)));
+    }
+}
@@ -0,0 +1,222 @@
+//! 现货条件单（止损 / 止盈 / OCO）
+//!
+//! 基于共享的条件单引擎按交易对监控成交价，触发方向由条件类型与买卖方向推导：
+//! - 止损：卖单价格跌至触发价、买单价格涨至触发价
+//! - 止盈：卖单价格涨至触发价、买单价格跌至触发价
+//!
+//! 价格以 [`Price::raw`] 接入引擎
+
+use std::fmt;
+
+use conditional_order::{
+    ConditionalEngine, ConditionalError, ConditionalEvent, ConditionalId, Direction, TriggerSpec,
+};
+
+use crate::exchange::spot::spot_types::{ConditionalType, SpotOrder};
+use crate::{OrderId, OrderSide, Price, TradingPair};
+
+/// 条件单登记错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpotConditionalError {
+    /// 订单不是条件单（conditional_type 为 None）
+    NotConditional,
+    /// 缺少触发价
+    MissingStopPrice,
+    /// OCO 关联失败
+    Link(ConditionalError),
+}
+
+impl fmt::Display for SpotConditionalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpotConditionalError::NotConditional => write!(f, "Order is not conditional"),
+            SpotConditionalError::MissingStopPrice => write!(f, "Missing stop price"),
+            SpotConditionalError::Link(e) => write!(f, "OCO link failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpotConditionalError {}
+
+impl From<ConditionalError> for SpotConditionalError {
+    fn from(e: ConditionalError) -> Self {
+        SpotConditionalError::Link(e)
+    }
+}
+
+/// 触发事件（`Submit` 中为待提交的订单）
+pub type SpotConditionalEvent = ConditionalEvent<i64, SpotOrder>;
+
+/// 现货条件单簿
+#[derive(Debug, Default)]
+pub struct SpotConditionalOrders {
+    engine: ConditionalEngine<TradingPair, i64, SpotOrder>,
+}
+
+impl SpotConditionalOrders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记止损/止盈单
+    pub fn submit(&mut self, order: SpotOrder) -> Result<ConditionalId, SpotConditionalError> {
+        let direction = Self::direction(order.conditional_type, order.side)
+            .ok_or(SpotConditionalError::NotConditional)?;
+        let stop_price = order.stop_price.ok_or(SpotConditionalError::MissingStopPrice)?;
+        let spec = TriggerSpec::Stop { direction, price: stop_price.raw() };
+        Ok(self.engine.submit(order.trading_pair, spec, order))
+    }
+
+    /// 登记一组止损 + 止盈 OCO：一腿触发时另一腿撤销
+    pub fn submit_oco(
+        &mut self,
+        stop_loss: SpotOrder,
+        take_profit: SpotOrder,
+    ) -> Result<(ConditionalId, ConditionalId), SpotConditionalError> {
+        let first = self.submit(stop_loss)?;
+        let second = match self.submit(take_profit) {
+            Ok(id) => id,
+            Err(e) => {
+                self.engine.cancel(first);
+                return Err(e);
+            }
+        };
+        if let Err(e) = self.engine.link_oco(first, second) {
+            self.engine.cancel(first);
+            self.engine.cancel(second);
+            return Err(e.into());
+        }
+        Ok((first, second))
+    }
+
+    /// 将条件单与已挂在订单簿上的限价单关联为 OCO
+    ///
+    /// 条件单触发时返回 `CancelSibling` 事件撤销该限价单；限价单成交时由调用方撤销条件单
+    pub fn link_limit_order(
+        &mut self,
+        id: ConditionalId,
+        limit_order_id: OrderId,
+    ) -> Result<(), SpotConditionalError> {
+        Ok(self.engine.set_external_sibling(id, limit_order_id)?)
+    }
+
+    /// 撤销条件单，返回原订单
+    pub fn cancel(&mut self, id: ConditionalId) -> Option<SpotOrder> {
+        self.engine.cancel(id)
+    }
+
+    /// 成交价更新，返回触发事件
+    pub fn on_trade(
+        &mut self,
+        trading_pair: TradingPair,
+        price: Price,
+    ) -> Vec<SpotConditionalEvent> {
+        let mut events = Vec::new();
+        self.engine.on_price(trading_pair, price.raw(), &mut events);
+        events
+    }
+
+    pub fn len(&self) -> usize {
+        self.engine.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.engine.is_empty()
+    }
+
+    fn direction(conditional_type: ConditionalType, side: OrderSide) -> Option<Direction> {
+        match (conditional_type, side) {
+            (ConditionalType::None, _) => None,
+            (ConditionalType::StopLoss, OrderSide::Sell)
+            | (ConditionalType::TakeProfit, OrderSide::Buy) => Some(Direction::Falling),
+            (ConditionalType::StopLoss, OrderSide::Buy)
+            | (ConditionalType::TakeProfit, OrderSide::Sell) => Some(Direction::Rising),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use conditional_order::Sibling;
+
+    use super::*;
+    use crate::base_types::TraderId;
+    use crate::exchange::spot::spot_types::TimeInForce;
+    use crate::{Quantity, Timestamp};
+
+    fn conditional(
+        order_id: OrderId,
+        side: OrderSide,
+        conditional_type: ConditionalType,
+        stop: f64,
+    ) -> SpotOrder {
+        let mut order = SpotOrder::create_order(
+            order_id,
+            TraderId::default(),
+            TradingPair::BtcUsdt,
+            side,
+            Price::from_f64(stop),
+            Quantity::from_f64(1.0),
+            TimeInForce::GTC,
+            None,
+            Quantity::default(),
+            Timestamp(0),
+        );
+        order.conditional_type = conditional_type;
+        order.stop_price = Some(Price::from_f64(stop));
+        order
+    }
+
+    fn submitted(events: &[SpotConditionalEvent]) -> Vec<OrderId> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ConditionalEvent::Submit(fired) => Some(fired.action.order_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stop_loss_and_take_profit_directions() {
+        let mut orders = SpotConditionalOrders::new();
+        orders.submit(conditional(1, OrderSide::Sell, ConditionalType::StopLoss, 90.0)).unwrap();
+        orders.submit(conditional(2, OrderSide::Buy, ConditionalType::StopLoss, 110.0)).unwrap();
+        orders.submit(conditional(3, OrderSide::Buy, ConditionalType::TakeProfit, 95.0)).unwrap();
+        let plain = conditional(4, OrderSide::Buy, ConditionalType::None, 95.0);
+        assert_eq!(orders.submit(plain), Err(SpotConditionalError::NotConditional));
+
+        assert!(orders.on_trade(TradingPair::EthUsdt, Price::from_f64(50.0)).is_empty());
+        let events = orders.on_trade(TradingPair::BtcUsdt, Price::from_f64(94.0));
+        assert_eq!(submitted(&events), vec![3]);
+        let events = orders.on_trade(TradingPair::BtcUsdt, Price::from_f64(120.0));
+        assert_eq!(submitted(&events), vec![2]);
+        assert_eq!(orders.len(), 1);
+    }
+
+    #[test]
+    fn test_oco_and_limit_sibling() {
+        let mut orders = SpotConditionalOrders::new();
+        let (stop, _) = orders
+            .submit_oco(
+                conditional(1, OrderSide::Sell, ConditionalType::StopLoss, 90.0),
+                conditional(2, OrderSide::Sell, ConditionalType::TakeProfit, 110.0),
+            )
+            .unwrap();
+        let events = orders.on_trade(TradingPair::BtcUsdt, Price::from_f64(89.0));
+        assert_eq!(submitted(&events), vec![1]);
+        assert!(orders.is_empty());
+        assert!(matches!(events[0], ConditionalEvent::CancelSibling { id, .. } if id == stop));
+
+        let id = orders
+            .submit(conditional(3, OrderSide::Buy, ConditionalType::StopLoss, 100.0))
+            .unwrap();
+        orders.link_limit_order(id, 77).unwrap();
+        let events = orders.on_trade(TradingPair::BtcUsdt, Price::from_f64(100.0));
+        assert_eq!(
+            events[0],
+            ConditionalEvent::CancelSibling { id, sibling: Sibling::External(77) }
+        );
+        assert_eq!(submitted(&events), vec![3]);
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use entity_derive::Entity;
 
 use crate::account::balance::Balance;
-use crate::base_types::TraderId;
+use crate::base_types::{TraderId, UserId};
 use crate::fee::fee_types::{CexFeeEntity, FeeType};
 use crate::lob::lob::LobOrder;
 pub use crate::{
@@ -580,6 +580,74 @@ impl fmt::Display for OrderStatus {
     }
 }
 
+impl OrderStatus {
+    /// 是否终态（Filled / Cancelled / Rejected / Expired）
+    #[inline]
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Filled | Self::Cancelled | Self::Rejected | Self::Expired)
+    }
+
+    /// 状态迁移是否合法
+    ///
+    /// | 当前状态 | 允许迁移到 |
+    /// |---------|-----------|
+    /// | ConditionalPending | New, Pending, Cancelled, Rejected, Expired |
+    /// | New | Pending, PartiallyFilled, Filled, Cancelled, Rejected, Expired |
+    /// | Pending | PartiallyFilled, Filled, Cancelled, Expired |
+    /// | PartiallyFilled | PartiallyFilled, Filled, Cancelled, Expired |
+    /// | 终态 | 无 |
+    pub fn can_transition_to(self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+        match self {
+            ConditionalPending => {
+                matches!(next, New | Pending | Cancelled | Rejected | Expired)
+            }
+            New => {
+                matches!(next, Pending | PartiallyFilled | Filled | Cancelled | Rejected | Expired)
+            }
+            Pending => matches!(next, PartiallyFilled | Filled | Cancelled | Expired),
+            PartiallyFilled => matches!(next, PartiallyFilled | Filled | Cancelled | Expired),
+            Filled | Cancelled | Rejected | Expired => false,
+        }
+    }
+}
+
+/// 订单状态变更事件
+///
+/// 每次合法迁移产生一条，由调用方发布到用户数据流
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderStatusChanged {
+    pub order_id: OrderId,
+    pub trader_id: TraderId,
+    pub trading_pair: TradingPair,
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+    pub timestamp: Timestamp,
+    /// 实际下单用户（代下单时用于审计）
+    pub effective_user: Option<UserId>,
+}
+
+/// 非法订单状态迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderStatusTransitionError {
+    pub order_id: OrderId,
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+}
+
+impl fmt::Display for OrderStatusTransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid order status transition: order {} {} -> {}",
+            self.order_id, self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for OrderStatusTransitionError {}
+
 /// 有效期类型 - 定义订单在订单簿中的存续时间和执行策略
 ///
 /// ## 限价单 (Limit Order) 与市价单 (Market Order) 的使用对比
@@ -795,6 +863,7 @@ pub struct SpotOrder {
     pub conditional_type: ConditionalType, // 条件类型 (None/StopLoss/TakeProfit) (1字节)
     pub algorithm_strategy: AlgorithmStrategy, // 算法策略 (None/TWAP/VWAP/...) (1字节)
     pub self_trade_prevention: SelfTradePrevention, // 自交易防护 (1字节，固定ExpireTaker)
+    pub effective_user: Option<UserId>, // 实际下单用户（联名持有人、被授权人代下单时记录；None 为系统内部订单）
 
     // ===== 可选触发条件 =====
     pub stop_price: Option<Price>, // 止损/止盈触发价（仅conditional_type != None时有效）
@@ -806,6 +875,41 @@ pub struct SpotOrder {
     pub state: ExecutionState,
 }
 
+/// 现货执行回报
+///
+/// 每次订单状态变更推送到用户数据流，携带累计成交统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpotExecutionReport {
+    pub order_id: OrderId,
+    pub trader_id: TraderId,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    pub status: OrderStatus,
+    pub last_fill_price: Price,          // 本次成交价
+    pub last_fill_qty: Quantity,         // 本次成交量
+    pub cumulative_filled_qty: Quantity, // 累计成交量
+    pub average_price: Price,            // 成交均价
+    pub cumulative_quote_qty: Quantity,  // 累计成交金额
+    pub remaining_qty: Quantity,         // 剩余数量
+    pub timestamp: Timestamp,
+    pub effective_user: Option<UserId>, // 实际下单用户
+}
+
+/// 一次撮合的结果：成交记录与双方执行回报
+#[derive(Debug, Clone, Copy)]
+pub struct SpotFill {
+    pub trade: SpotTrade,
+    /// Taker 执行回报
+    pub taker_report: SpotExecutionReport,
+    /// Maker 执行回报
+    pub maker_report: SpotExecutionReport,
+    /// Taker 卖出的已实现盈亏（买入或基础资产未启用成本追踪时为 None）
+    pub taker_realized_pnl: Option<Price>,
+    /// Maker 卖出的已实现盈亏
+    pub maker_realized_pnl: Option<Price>,
+}
+
 impl SpotOrder {
     /// 获取冻结资产（通过 side + trading_pair 推导）
     #[inline]
@@ -879,6 +983,7 @@ impl SpotOrder {
     /// * `is_market_maker` - 是否为做市商用户
     /// * `filled` - 成交数量
     /// * `price` - 成交价格
+    /// * `now` - 成交时间（判断促销是否生效）
     ///
     /// # 返回
     /// (手续费率基点数, 手续费数量)
@@ -893,6 +998,7 @@ impl SpotOrder {
         user_tier: Option<u32>,
         filled: Quantity,
         price: Price,
+        now: Timestamp,
     ) -> (i32, Quantity) {
         // 确定交易对的基础和报价资产
         let base_asset = self.trading_pair.base_asset().as_str().to_string();
@@ -912,6 +1018,7 @@ impl SpotOrder {
             user_tier.map(|t| t as f64), // 30天交易量（用户分层）
             user_vip_level,
             is_market_maker,
+            now,
         ) {
             Ok(result) => {
                 // 计算手续费数量 = 成交金额 * 费率
@@ -943,18 +1050,22 @@ impl SpotOrder {
         base_asset_balance: &mut Balance,
         o_quote_asset_balance: &mut Balance,
         o_base_asset_balance: &mut Balance,
-    ) -> SpotTrade {
+        now: Timestamp,
+    ) -> SpotFill {
         let filled = self.unfilled_qty().min(matched_order.unfilled_qty());
 
-        // 更新双方订单的成交数量（只更新 filled_qty，unfilled_qty 自动计算）
-        self.state.filled_base_qty += filled;
-        matched_order.state.filled_base_qty += filled;
-
         let transaction_price = match self.price {
             None => matched_order.price.unwrap(),
             Some(price) => price,
         };
 
+        // 更新双方订单的成交统计（unfilled_qty 自动计算）并同步状态
+        self.record_fill(filled, transaction_price, now);
+        matched_order.record_fill(filled, transaction_price, now);
+        // 已终结的订单不会进入撮合，迁移失败时保留原状态，回报如实反映
+        let _ = self.sync_fill_status(now);
+        let _ = matched_order.sync_fill_status(now);
+
         // 计算 Taker 的手续费
         let (taker_commission_rate, taker_commission_qty) = self.calculate_fee_with_amount(
             &CexFeeEntity::new(),
@@ -964,6 +1075,7 @@ impl SpotOrder {
             None,  // user_tier
             filled,
             transaction_price,
+            now,
         );
 
         // 计算 Maker 的手续费
@@ -976,33 +1088,38 @@ impl SpotOrder {
                 None,  // user_tier
                 filled,
                 transaction_price,
+                now,
             );
 
-        // 更新 Taker 的余额
-        match self.side {
+        // 更新 Taker 的余额（基础资产同步持仓成本）
+        let taker_realized_pnl = match self.side {
             OrderSide::Buy => {
-                let _ = quote_asset_balance
-                    .frozen2pay(filled * transaction_price, Timestamp::now_as_nanos());
-                base_asset_balance.add_balance(filled, Timestamp::now_as_nanos());
+                let _ = quote_asset_balance.frozen2pay(filled * transaction_price, now);
+                base_asset_balance.record_acquisition(filled, transaction_price);
+                base_asset_balance.add_balance(filled, now);
+                None
             }
             OrderSide::Sell => {
-                let _ = base_asset_balance.frozen2pay(filled, Timestamp::now_as_nanos());
-                quote_asset_balance
-                    .add_balance(filled * transaction_price, Timestamp::now_as_nanos());
+                let pnl = base_asset_balance.record_disposal(filled, transaction_price);
+                let _ = base_asset_balance.frozen2pay(filled, now);
+                quote_asset_balance.add_balance(filled * transaction_price, now);
+                pnl
             }
         };
 
-        // 更新 Maker 的余额
-        match matched_order.side {
+        // 更新 Maker 的余额（基础资产同步持仓成本）
+        let maker_realized_pnl = match matched_order.side {
             OrderSide::Buy => {
-                let _ = o_quote_asset_balance
-                    .frozen2pay(filled * transaction_price, Timestamp::now_as_nanos());
-                o_base_asset_balance.add_balance(filled, Timestamp::now_as_nanos());
+                let _ = o_quote_asset_balance.frozen2pay(filled * transaction_price, now);
+                o_base_asset_balance.record_acquisition(filled, transaction_price);
+                o_base_asset_balance.add_balance(filled, now);
+                None
             }
             OrderSide::Sell => {
-                let _ = o_base_asset_balance.frozen2pay(filled, Timestamp::now_as_nanos());
-                o_quote_asset_balance
-                    .add_balance(filled * transaction_price, Timestamp::now_as_nanos());
+                let pnl = o_base_asset_balance.record_disposal(filled, transaction_price);
+                let _ = o_base_asset_balance.frozen2pay(filled, now);
+                o_quote_asset_balance.add_balance(filled * transaction_price, now);
+                pnl
             }
         };
 
@@ -1010,12 +1127,12 @@ impl SpotOrder {
         let trade_id = (self.timestamp.0 << 32) | (self.order_id & 0xFFFFFFFF) as u64;
 
         // 创建一条 trade 记录（包含买卖双方信息）
-        SpotTrade::new(
+        let trade = SpotTrade::new(
             trade_id,
             self.trading_pair,
             self.order_id,
             matched_order.order_id,
-            Timestamp::now_as_nanos(),
+            now,
             transaction_price,
             filled,
             self.side,
@@ -1024,7 +1141,52 @@ impl SpotOrder {
             self.frozen_asset(), // 使用计算方法
             taker_commission_rate,
             maker_commission_rate,
-        )
+        );
+
+        SpotFill {
+            trade,
+            taker_report: self.execution_report(transaction_price, filled),
+            maker_report: matched_order.execution_report(transaction_price, filled),
+            taker_realized_pnl,
+            maker_realized_pnl,
+        }
+    }
+
+    /// 记录一笔成交：累计成交数量、成交金额并重算均价
+    ///
+    /// 零数量成交不改变统计，避免累计成交为 0 时计算均价
+    #[inline]
+    pub fn record_fill(&mut self, filled: Quantity, price: Price, now: Timestamp) {
+        if filled == Quantity::default() {
+            return;
+        }
+        self.state.filled_base_qty += filled;
+        self.state.cumulative_quote_qty += filled * price;
+        self.state.average_price = self.state.cumulative_quote_qty / self.state.filled_base_qty;
+        self.state.last_updated = now;
+    }
+
+    /// 生成执行回报（非成交事件传入零成交）
+    pub fn execution_report(
+        &self,
+        last_fill_price: Price,
+        last_fill_qty: Quantity,
+    ) -> SpotExecutionReport {
+        SpotExecutionReport {
+            order_id: self.order_id,
+            trader_id: self.trader_id,
+            trading_pair: self.trading_pair,
+            side: self.side,
+            status: self.state.status,
+            last_fill_price,
+            last_fill_qty,
+            cumulative_filled_qty: self.state.filled_base_qty,
+            average_price: self.state.average_price,
+            cumulative_quote_qty: self.state.cumulative_quote_qty,
+            remaining_qty: self.unfilled_qty(),
+            timestamp: self.state.last_updated,
+            effective_user: self.effective_user,
+        }
     }
 
     pub fn frozen_margin(&mut self, balance: &mut Balance, now: Timestamp) {
@@ -1054,9 +1216,8 @@ impl SpotOrder {
         time_in_force: TimeInForce,
         client_order_id: Option<String>,
         quote_order_qty: Quantity,
+        timestamp: Timestamp,
     ) -> Self {
-        let timestamp = Timestamp::now_as_nanos();
-
         Self {
             order_id,
             trader_id,
@@ -1076,6 +1237,7 @@ impl SpotOrder {
             expire_time: None, // GTD 过期时间，默认为 None
             source: OrderSource::API,
             client_order_id,
+            effective_user: None,
             state: ExecutionState {
                 status: OrderStatus::Pending,
                 filled_base_qty: Quantity::default(),
@@ -1088,6 +1250,13 @@ impl SpotOrder {
         }
     }
 
+    /// 记录实际下单用户（取自鉴权结果的 `effective_user`）
+    #[inline]
+    pub fn with_effective_user(mut self, user: UserId) -> Self {
+        self.effective_user = Some(user);
+        self
+    }
+
     /// 检查订单是否仍然有效（有未成交数量）
     #[inline]
     pub fn is_active(&self) -> bool {
@@ -1110,9 +1279,50 @@ impl SpotOrder {
 
     /// 取消订单（通过将状态置为 Cancelled，单次内存写入，速度快）
     #[inline]
-    pub fn cancel(&mut self) {
-        self.state.status = OrderStatus::Cancelled;
-        self.state.last_updated = Timestamp::now_as_nanos();
+    pub fn cancel(
+        &mut self,
+        now: Timestamp,
+    ) -> Result<OrderStatusChanged, OrderStatusTransitionError> {
+        self.transition_to(OrderStatus::Cancelled, now)
+    }
+
+    /// 状态迁移，非法迁移返回错误且不修改订单
+    pub fn transition_to(
+        &mut self,
+        next: OrderStatus,
+        now: Timestamp,
+    ) -> Result<OrderStatusChanged, OrderStatusTransitionError> {
+        let from = self.state.status;
+        if !from.can_transition_to(next) {
+            return Err(OrderStatusTransitionError { order_id: self.order_id, from, to: next });
+        }
+
+        self.state.status = next;
+        self.state.last_updated = now;
+        Ok(OrderStatusChanged {
+            order_id: self.order_id,
+            trader_id: self.trader_id,
+            trading_pair: self.trading_pair,
+            from,
+            to: next,
+            timestamp: now,
+            effective_user: self.effective_user,
+        })
+    }
+
+    /// 按已成交数量同步状态（成交后调用）
+    ///
+    /// 无成交时不迁移，返回 `Ok(None)`
+    pub fn sync_fill_status(
+        &mut self,
+        now: Timestamp,
+    ) -> Result<Option<OrderStatusChanged>, OrderStatusTransitionError> {
+        if self.state.filled_base_qty == Quantity::default() {
+            return Ok(None);
+        }
+        let next =
+            if self.is_all_filled() { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+        self.transition_to(next, now).map(Some)
     }
 
     /// 设置订单来源（Phase 3）
@@ -1187,6 +1397,17 @@ impl SpotOrder {
     }
 }
 
+/// 流动性标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LiquidityIndicator {
+    /// 挂单方，提供流动性
+    Maker = 0,
+    /// 吃单方，消耗流动性
+    Taker = 1,
+}
+
 /// 交易执行记录
 ///
 /// 记录一次撮合成交的完整信息，用于：
@@ -1221,7 +1442,7 @@ pub struct SpotTrade {
     pub quote_qty: Quantity,
 
     // ===== 交易方向（1字节）=====
-    /// Taker方向（Buy=Taker买入, Sell=Taker卖出）
+    /// Taker方向（Buy=Taker买入, Sell=Taker卖出），即主动方方向
     pub taker_side: OrderSide,
 
     // ===== 手续费字段（32字节）=====
@@ -1274,6 +1495,42 @@ impl SpotTrade {
             maker_commission_rate,
         }
     }
+
+    /// 主动方方向
+    #[inline]
+    pub fn aggressor_side(&self) -> OrderSide {
+        self.taker_side
+    }
+
+    /// 买方是否为 Maker（提供流动性一方），由 Taker 方向推导
+    #[inline]
+    pub fn is_buyer_maker(&self) -> bool {
+        self.taker_side == OrderSide::Sell
+    }
+
+    /// 指定订单在本笔成交中的流动性角色（非本成交订单返回 None）
+    #[inline]
+    pub fn liquidity_of(&self, order_id: OrderId) -> Option<LiquidityIndicator> {
+        if order_id == self.taker_order_id {
+            Some(LiquidityIndicator::Taker)
+        } else if order_id == self.maker_order_id {
+            Some(LiquidityIndicator::Maker)
+        } else {
+            None
+        }
+    }
+
+    /// 买方订单ID
+    #[inline]
+    pub fn buyer_order_id(&self) -> OrderId {
+        if self.is_buyer_maker() { self.maker_order_id } else { self.taker_order_id }
+    }
+
+    /// 卖方订单ID
+    #[inline]
+    pub fn seller_order_id(&self) -> OrderId {
+        if self.is_buyer_maker() { self.taker_order_id } else { self.maker_order_id }
+    }
 }
 
 #[cfg(test)]
@@ -1303,6 +1560,7 @@ mod tests {
             TimeInForce::GTC,
             None,
             Quantity::default(),
+            Timestamp(0),
         );
 
         let frozen_buy = buy_order.frozen_qty();
@@ -1319,6 +1577,7 @@ mod tests {
             TimeInForce::GTC,
             None,
             Quantity::default(),
+            Timestamp(0),
         );
 
         let frozen_sell = sell_order.frozen_qty();
@@ -1335,6 +1594,7 @@ mod tests {
             TimeInForce::GTC,
             None,
             Quantity::default(),
+            Timestamp(0),
         );
 
         // 模拟成交0.3
@@ -1346,4 +1606,151 @@ mod tests {
             "Partial filled order should freeze 0.7 * 50000.0"
         );
     }
+
+    fn create_test_order() -> SpotOrder {
+        SpotOrder::create_order(
+            1,
+            TraderId::default(),
+            TradingPair::BtcUsdt,
+            OrderSide::Buy,
+            Price::from_f64(50000.0),
+            Quantity::from_f64(1.0),
+            TimeInForce::GTC,
+            None,
+            Quantity::default(),
+            Timestamp(0),
+        )
+    }
+
+    #[test]
+    fn test_status_transition_through_fills() {
+        let mut order = create_test_order();
+
+        order.state.filled_base_qty = Quantity::from_f64(0.4);
+        let event = order.sync_fill_status(Timestamp(1)).unwrap().unwrap();
+        assert_eq!((event.from, event.to), (OrderStatus::Pending, OrderStatus::PartiallyFilled));
+
+        order.state.filled_base_qty = Quantity::from_f64(1.0);
+        let event = order.sync_fill_status(Timestamp(2)).unwrap().unwrap();
+        assert_eq!(event.to, OrderStatus::Filled);
+        assert_eq!(order.state.last_updated, Timestamp(2));
+    }
+
+    #[test]
+    fn test_filled_order_cannot_be_cancelled() {
+        let mut order = create_test_order();
+        order.state.filled_base_qty = Quantity::from_f64(1.0);
+        order.sync_fill_status(Timestamp(1)).unwrap();
+
+        let err = order.cancel(Timestamp(2)).unwrap_err();
+        assert_eq!((err.from, err.to), (OrderStatus::Filled, OrderStatus::Cancelled));
+        assert_eq!(order.state.status, OrderStatus::Filled);
+    }
+
+    #[test]
+    fn test_no_fill_no_transition() {
+        let mut order = create_test_order();
+        assert_eq!(order.sync_fill_status(Timestamp(1)).unwrap(), None);
+        assert_eq!(order.state.status, OrderStatus::Pending);
+    }
+
+    #[test]
+    fn test_effective_user_carried_on_order_events() {
+        let mut order = create_test_order().with_effective_user(UserId(7));
+        let event = order.cancel(Timestamp(1)).unwrap();
+        assert_eq!(event.effective_user, Some(UserId(7)));
+        let report = order.execution_report(Price::default(), Quantity::default());
+        assert_eq!(report.effective_user, Some(UserId(7)));
+        assert_eq!(create_test_order().effective_user, None);
+    }
+
+    #[test]
+    fn test_record_fill_tracks_average_price() {
+        let mut order = create_test_order();
+
+        order.record_fill(Quantity::from_f64(0.25), Price::from_f64(50000.0), Timestamp(1));
+        order.record_fill(Quantity::from_f64(0.75), Price::from_f64(50400.0), Timestamp(2));
+
+        let report = order.execution_report(Price::from_f64(50400.0), Quantity::from_f64(0.75));
+        assert_eq!(report.cumulative_filled_qty.to_f64(), 1.0);
+        assert_eq!(report.average_price.to_f64(), 50300.0);
+        assert_eq!(report.cumulative_quote_qty.to_f64(), 50300.0);
+        assert_eq!(report.remaining_qty, Quantity::default());
+        assert_eq!(report.timestamp, Timestamp(2));
+
+        order.record_fill(Quantity::default(), Price::from_f64(1.0), Timestamp(3));
+        assert_eq!(order.state.average_price.to_f64(), 50300.0);
+        assert_eq!(order.state.last_updated, Timestamp(2));
+    }
+
+    #[test]
+    fn test_make_trade_emits_execution_reports() {
+        let mut taker = create_test_order();
+        let mut maker = SpotOrder::create_order(
+            2,
+            TraderId::default(),
+            TradingPair::BtcUsdt,
+            OrderSide::Sell,
+            Price::from_f64(50000.0),
+            Quantity::from_f64(0.4),
+            TimeInForce::GTC,
+            None,
+            Quantity::default(),
+            Timestamp(0),
+        );
+        let balance = |asset| Balance::new(crate::AccountId(1), asset, Timestamp(0));
+        let (mut quote, mut base) = (balance(AssetId::Usdt), balance(AssetId::Btc));
+        let mut o_quote = balance(AssetId::Usdt);
+        // Maker 以均价 40000 持有 1 BTC，挂单冻结 0.4
+        let mut o_base = balance(AssetId::Btc).with_cost_tracking(Quantity::from_f64(40000.0));
+        o_base.add_balance(Quantity::from_f64(1.0), Timestamp(0));
+        o_base.frozen(Quantity::from_f64(0.4), Timestamp(0)).unwrap();
+
+        let fill = taker.make_trade(
+            &mut maker,
+            &mut quote,
+            &mut base,
+            &mut o_quote,
+            &mut o_base,
+            Timestamp(5),
+        );
+        assert_eq!(fill.trade.base_qty, Quantity::from_f64(0.4));
+        assert_eq!(fill.taker_report.status, OrderStatus::PartiallyFilled);
+        assert_eq!(fill.taker_report.remaining_qty, Quantity::from_f64(0.6));
+        assert_eq!(fill.taker_report.last_fill_qty, Quantity::from_f64(0.4));
+        assert_eq!(fill.maker_report.status, OrderStatus::Filled);
+        assert_eq!(fill.maker_report.average_price, Price::from_f64(50000.0));
+        assert_eq!(fill.maker_report.timestamp, Timestamp(5));
+        // 卖方按均价结转成本，买方无已实现盈亏
+        assert_eq!(fill.taker_realized_pnl, None);
+        assert_eq!(fill.maker_realized_pnl, Some(Price::from_f64(4000.0)));
+        assert_eq!(o_base.realized_pnl, Quantity::from_f64(4000.0));
+    }
+
+    #[test]
+    fn test_trade_liquidity_indicators() {
+        let trade = SpotTrade::new(
+            1,
+            TradingPair::BtcUsdt,
+            10, // taker
+            20, // maker
+            Timestamp(1),
+            Price::from_f64(50000.0),
+            Quantity::from_f64(1.0),
+            OrderSide::Sell,
+            Quantity::default(),
+            Quantity::default(),
+            AssetId::Usdt,
+            10,
+            5,
+        );
+
+        assert_eq!(trade.aggressor_side(), OrderSide::Sell);
+        assert!(trade.is_buyer_maker());
+        assert_eq!(trade.buyer_order_id(), 20);
+        assert_eq!(trade.seller_order_id(), 10);
+        assert_eq!(trade.liquidity_of(10), Some(LiquidityIndicator::Taker));
+        assert_eq!(trade.liquidity_of(20), Some(LiquidityIndicator::Maker));
+        assert_eq!(trade.liquidity_of(30), None);
+    }
 }
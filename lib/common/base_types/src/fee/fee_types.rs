@@ -29,7 +29,7 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 
-use crate::InstrumentType;
+use crate::{InstrumentType, Timestamp};
 
 /// 产品特定的分层费率配置
 #[derive(Debug, Clone)]
@@ -207,31 +207,37 @@ impl ProductFeeConfig {
         user_volume_30d: Option<f64>,
         user_vip_level: Option<u32>,
         is_market_maker: bool,
+        now: Timestamp,
     ) -> Result<FeeCalculationResult, FeeError> {
         let trade_value = quantity * price;
 
         // 1. 确定基础费率
         let base_rate = match fee_type {
             FeeType::Maker => {
-                if is_market_maker {
+                let rate = if is_market_maker {
                     self.default_maker_fee * 0.5
                 } else {
                     self.get_tiered_fee(user_volume_30d, user_vip_level, FeeType::Maker)
-                }
+                };
+                rate.max(-self.max_rebate_rate())
             }
             FeeType::Taker => self.get_tiered_fee(user_volume_30d, user_vip_level, FeeType::Taker),
             _ => return Err(FeeError::InvalidFeeTypeForTrade),
         };
 
         // 2. 应用促销折扣
-        let discount_rate = self.get_promotion_discount();
+        let discount_rate = self.get_promotion_discount(now);
         let final_rate = base_rate * (1.0 - discount_rate);
 
         // 3. 计算手续费金额
         let fee_amount = trade_value * final_rate;
 
-        // 4. 应用最小值
-        let final_fee = fee_amount.max(self.min_trading_fee);
+        // 4. 应用最小值：返佣（负费率）不受最小值约束，收费不超过成交额
+        let final_fee = if fee_amount < 0.0 {
+            fee_amount
+        } else {
+            fee_amount.max(self.min_trading_fee).min(trade_value)
+        };
 
         Ok(FeeCalculationResult {
             base_rate,
@@ -289,9 +295,24 @@ impl ProductFeeConfig {
         base_fee
     }
 
+    /// 最大返佣率（私有方法）
+    ///
+    /// 任一对手方可能适用的最低吃单费率，返佣不超过对手方支付的手续费
+    fn max_rebate_rate(&self) -> f64 {
+        let lowest_taker = self
+            .product_tiers
+            .iter()
+            .filter(|tier| tier.is_active)
+            .map(|tier| tier.taker_fee)
+            .fold(self.default_taker_fee, f64::min);
+        let max_discount =
+            self.product_vip_levels.iter().map(|vip| vip.fee_discount).fold(0.0, f64::max);
+        (lowest_taker * (1.0 - max_discount)).max(0.0)
+    }
+
     /// 获取促销折扣（私有方法）
-    fn get_promotion_discount(&self) -> f64 {
-        let now = Utc::now();
+    fn get_promotion_discount(&self, now: Timestamp) -> f64 {
+        let now = DateTime::<Utc>::from_timestamp_nanos(now.0 as i64);
         let mut max_discount: f64 = 0.0;
 
         for promotion in &self.product_promotions {
@@ -341,6 +362,7 @@ impl CexFeeEntity {
         user_volume_30d: Option<f64>,
         user_vip_level: Option<u32>,
         is_market_maker: bool,
+        now: Timestamp,
     ) -> Result<FeeCalculationResult, FeeError> {
         let product_config =
             self.get_product_config(instrument_type).ok_or(FeeError::CalculationError(format!(
@@ -356,6 +378,7 @@ impl CexFeeEntity {
             user_volume_30d,
             user_vip_level,
             is_market_maker,
+            now,
         )
     }
 }
@@ -452,6 +475,7 @@ mod tests {
                 None,
                 None,
                 false,
+                Timestamp(0),
             )
             .unwrap();
 
@@ -470,6 +494,7 @@ mod tests {
                 None,
                 None,
                 false,
+                Timestamp(0),
             )
             .unwrap();
 
@@ -488,6 +513,7 @@ mod tests {
                 None,
                 None,
                 false,
+                Timestamp(0),
             )
             .unwrap();
 
@@ -506,6 +532,7 @@ mod tests {
                 None,
                 None,
                 true, // is_market_maker = true
+                Timestamp(0),
             )
             .unwrap();
 
@@ -532,6 +559,7 @@ mod tests {
                 None,
                 None,
                 false,
+                Timestamp(0),
             )
             .unwrap();
 
@@ -554,6 +582,7 @@ mod tests {
             None,
             None,
             false,
+            Timestamp(0),
         );
 
         assert!(result.is_err());
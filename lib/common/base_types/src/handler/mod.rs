@@ -10,3 +10,4 @@ pub mod event_actor;
 pub mod event_handler;
 mod exmaple;
 pub mod handler_update2;
+pub mod registry;
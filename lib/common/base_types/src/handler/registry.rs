@@ -0,0 +1,309 @@
+//! 命令处理器注册表
+//!
+//! 按命令类型路由到对应的 `CmdHandler`：同步调用方经 `HandlerRegistry::dispatch`
+//! 分发，队列消费侧由 `HandlerRegistry::actor` 构建的 [`RegistryActor`] 在
+//! `handle_event` 中调用同一个处理器，不再各自手写 match 分发
+//!
+//! 唯一性分两层保证：
+//! - 编译期：`register_handlers!` 同一次调用中重复的命令类型会产生冲突实现，无法编译
+//! - 运行期：跨多次注册的重复由 `register` 返回 `RegistryError::Duplicate`
+//!
+//! ```compile_fail
+//! use base_types::handler::registry::{HandlerCommand, HandlerRegistry};
+//! use base_types::register_handlers;
+//!
+//! struct Ping;
+//! impl HandlerCommand for Ping {
+//!     type Response = ();
+//!     type Error = ();
+//! }
+//!
+//! let mut registry = HandlerRegistry::new();
+//! // 同一命令类型注册两次，编译失败
+//! let _ = register_handlers!(registry, {
+//!     Ping => |_: Ping| Ok(()),
+//!     Ping => |_: Ping| Ok(()),
+//! });
+//! ```
+
+use std::any::{Any, TypeId, type_name};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+
+use crate::handler::event_actor::EventRecvActor;
+use crate::handler::handler::CmdHandler;
+
+/// 可注册的命令
+pub trait HandlerCommand: Send + 'static {
+    /// 响应类型
+    type Response: 'static;
+    /// 错误类型
+    type Error: 'static;
+}
+
+impl<C, F> CmdHandler<C, C::Response, C::Error> for F
+where
+    C: HandlerCommand,
+    F: Fn(C) -> Result<C::Response, C::Error> + Send + Sync,
+{
+    fn cmd_handle(&self, cmd: C) -> Result<C::Response, C::Error> {
+        self(cmd)
+    }
+}
+
+/// 注册错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// 命令类型已注册
+    Duplicate(&'static str),
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::Duplicate(name) => write!(f, "Handler already registered: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// 分发错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchError<E> {
+    /// 命令类型未注册
+    NotRegistered(&'static str),
+    /// 处理器错误
+    Handler(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for DispatchError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchError::NotRegistered(name) => write!(f, "Handler not registered: {}", name),
+            DispatchError::Handler(err) => write!(f, "Handler error: {}", err),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for DispatchError<E> {}
+
+/// 单个命令类型的处理器
+type Slot<C> =
+    Arc<dyn CmdHandler<C, <C as HandlerCommand>::Response, <C as HandlerCommand>::Error>>;
+
+/// 命令处理器注册表
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册处理器，同一命令类型只能注册一次
+    pub fn register<C, H>(&mut self, handler: H) -> Result<(), RegistryError>
+    where
+        C: HandlerCommand,
+        H: CmdHandler<C, C::Response, C::Error> + 'static,
+    {
+        let type_id = TypeId::of::<C>();
+        if self.handlers.contains_key(&type_id) {
+            return Err(RegistryError::Duplicate(type_name::<C>()));
+        }
+        let slot: Slot<C> = Arc::new(handler);
+        self.handlers.insert(type_id, Box::new(slot));
+        Ok(())
+    }
+
+    /// 分发命令
+    pub fn dispatch<C: HandlerCommand>(
+        &self,
+        cmd: C,
+    ) -> Result<C::Response, DispatchError<C::Error>> {
+        let handler = self.handler::<C>().ok_or(DispatchError::NotRegistered(type_name::<C>()))?;
+        handler.cmd_handle(cmd).map_err(DispatchError::Handler)
+    }
+
+    /// 取出处理器（跨线程共享给 actor 使用）
+    pub fn handler<C: HandlerCommand>(&self) -> Option<Slot<C>> {
+        self.handlers
+            .get(&TypeId::of::<C>())
+            .and_then(|slot| slot.downcast_ref::<Slot<C>>())
+            .cloned()
+    }
+
+    /// 构建消费 `inbox` 的 actor，命令交给已注册的处理器
+    pub fn actor<C: HandlerCommand>(
+        &self,
+        inbox: Receiver<C>,
+    ) -> Result<RegistryActor<C>, DispatchError<C::Error>> {
+        let handler = self.handler::<C>().ok_or(DispatchError::NotRegistered(type_name::<C>()))?;
+        Ok(RegistryActor { inbox, handler })
+    }
+
+    /// 命令类型是否已注册
+    pub fn contains<C: HandlerCommand>(&self) -> bool {
+        self.handlers.contains_key(&TypeId::of::<C>())
+    }
+
+    /// 已注册的命令类型数
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+/// 经注册表分发的单线程 actor
+///
+/// 发送端全部关闭时消费循环结束；处理器返回错误时按 `EventRecvActor::run`
+/// 的约定停止，响应值丢弃
+pub struct RegistryActor<C: HandlerCommand> {
+    inbox: Receiver<C>,
+    handler: Slot<C>,
+}
+
+impl<C: HandlerCommand> EventRecvActor<C, DispatchError<C::Error>> for RegistryActor<C> {
+    fn recv_event(&mut self) -> Result<Option<C>, DispatchError<C::Error>> {
+        Ok(self.inbox.recv().ok())
+    }
+
+    fn handle_event(&self, event: C) -> Result<(), DispatchError<C::Error>> {
+        self.handler.cmd_handle(event).map(drop).map_err(DispatchError::Handler)
+    }
+}
+
+/// 批量注册处理器
+///
+/// 同一次调用中命令类型重复时编译失败；与注册表中已有的类型重复时返回
+/// `Err(RegistryError::Duplicate)`，此前的处理器已注册、此后的不再注册
+///
+/// ```ignore
+/// register_handlers!(registry, {
+///     PlaceOrder => place_order_handler,
+///     CancelOrder => cancel_order_handler,
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! register_handlers {
+    ($registry:expr, { $($cmd:ty => $handler:expr),+ $(,)? }) => {{
+        #[allow(dead_code)]
+        const _: () = {
+            trait UniqueHandler<T: ?Sized> {}
+            struct Registered;
+            $(impl UniqueHandler<$cmd> for Registered {})+
+        };
+        let registry: &mut $crate::handler::registry::HandlerRegistry = &mut $registry;
+        let mut result = Ok(());
+        $(
+            if result.is_ok() {
+                result = registry.register::<$cmd, _>($handler);
+            }
+        )+
+        result
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Place {
+        quantity: u64,
+    }
+
+    impl HandlerCommand for Place {
+        type Response = u64;
+        type Error = String;
+    }
+
+    struct Cancel;
+
+    impl HandlerCommand for Cancel {
+        type Response = ();
+        type Error = String;
+    }
+
+    struct CancelHandler;
+
+    impl CmdHandler<Cancel, (), String> for CancelHandler {
+        fn cmd_handle(&self, _cmd: Cancel) -> Result<(), String> {
+            Err("nothing to cancel".to_string())
+        }
+    }
+
+    #[test]
+    fn test_register_and_dispatch() {
+        let mut registry = HandlerRegistry::new();
+        register_handlers!(registry, {
+            Place => |cmd: Place| Ok(cmd.quantity * 2),
+            Cancel => CancelHandler,
+        })
+        .unwrap();
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.dispatch(Place { quantity: 3 }), Ok(6));
+        assert_eq!(
+            registry.dispatch(Cancel),
+            Err(DispatchError::Handler("nothing to cancel".to_string()))
+        );
+
+        let handler = registry.handler::<Place>().unwrap();
+        let worker = std::thread::spawn(move || handler.cmd_handle(Place { quantity: 5 }));
+        assert_eq!(worker.join().unwrap(), Ok(10));
+    }
+
+    #[test]
+    fn test_actor_dispatches_through_registry() {
+        let processed = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let counter = Arc::clone(&processed);
+        let mut registry = HandlerRegistry::new();
+        register_handlers!(registry, {
+            Place => move |cmd: Place| {
+                counter.fetch_add(cmd.quantity, std::sync::atomic::Ordering::SeqCst);
+                Ok(cmd.quantity)
+            },
+            Cancel => CancelHandler,
+        })
+        .unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut actor = registry.actor::<Place>(rx).unwrap();
+        for quantity in [1, 2, 3] {
+            tx.send(Place { quantity }).unwrap();
+        }
+        drop(tx);
+        assert_eq!(actor.run(), Ok(()));
+        assert_eq!(processed.load(std::sync::atomic::Ordering::SeqCst), 6);
+
+        // 处理器出错时消费循环停止
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut actor = registry.actor::<Cancel>(rx).unwrap();
+        tx.send(Cancel).unwrap();
+        assert_eq!(actor.run(), Err(DispatchError::Handler("nothing to cancel".to_string())));
+        assert!(HandlerRegistry::new().actor::<Cancel>(std::sync::mpsc::channel().1).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_and_missing() {
+        let mut registry = HandlerRegistry::new();
+        assert_eq!(
+            registry.dispatch(Cancel),
+            Err(DispatchError::NotRegistered(type_name::<Cancel>()))
+        );
+
+        registry.register::<Cancel, _>(CancelHandler).unwrap();
+        let result = register_handlers!(registry, {
+            Place => |_: Place| Ok(1),
+            Cancel => CancelHandler,
+        });
+        assert_eq!(result, Err(RegistryError::Duplicate(type_name::<Cancel>())));
+        assert!(registry.contains::<Place>());
+    }
+}
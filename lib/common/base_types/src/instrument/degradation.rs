@@ -0,0 +1,248 @@
+//! 交易对降级状态
+//!
+//! 分片或依赖（行情源、风控、钱包等）不健康时，受影响的交易对标记为降级，
+//! 以功能受限代码说明哪些操作暂不可用：
+//! - `CANCEL_ONLY`：只接受撤单，新委托一律拒绝
+//! - `LIMIT_ONLY`：拒绝市价单（盘口不可靠时避免滑点）
+//! - `MARKET_DATA_DELAYED`：行情延迟，委托照常接受
+//!
+//! 同一交易对可由多个来源同时降级，生效状态为各来源的并集；来源恢复后只撤销自己的标记。
+//! 降级信息随 exchangeInfo 公布（[`ExchangeInfo::apply_degradations`](super::exchange_info::ExchangeInfo::apply_degradations)），
+//! 被拒绝的委托携带 [`DegradationRejection`] 说明原因
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// 功能受限代码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum DegradationCode {
+    /// 只接受撤单
+    CancelOnly,
+    /// 拒绝市价单
+    LimitOnly,
+    /// 行情延迟
+    MarketDataDelayed,
+}
+
+impl DegradationCode {
+    pub const ALL: [DegradationCode; 3] = [
+        DegradationCode::CancelOnly,
+        DegradationCode::LimitOnly,
+        DegradationCode::MarketDataDelayed,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            DegradationCode::CancelOnly => "CANCEL_ONLY",
+            DegradationCode::LimitOnly => "LIMIT_ONLY",
+            DegradationCode::MarketDataDelayed => "MARKET_DATA_DELAYED",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|code| code.as_str().eq_ignore_ascii_case(s))
+    }
+}
+
+/// 单个来源对交易对的降级标记
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DegradationMark {
+    pub codes: BTreeSet<DegradationCode>,
+    /// 对外说明
+    pub reason: String,
+    /// 标记时间（Unix 毫秒）
+    pub since_ms: u64,
+}
+
+/// 交易对的生效降级状态（各来源的并集）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SymbolDegradation {
+    pub codes: Vec<DegradationCode>,
+    /// 各来源的说明，按来源排序以 `; ` 连接
+    pub reason: String,
+    /// 降级来源（分片、依赖名称）
+    pub sources: Vec<String>,
+    /// 最早的标记时间（Unix 毫秒）
+    pub since: u64,
+}
+
+impl SymbolDegradation {
+    pub fn has(&self, code: DegradationCode) -> bool {
+        self.codes.contains(&code)
+    }
+}
+
+/// 因降级被拒绝的委托
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DegradationRejection {
+    pub symbol: String,
+    /// 导致拒绝的代码
+    pub code: DegradationCode,
+    pub reason: String,
+}
+
+impl fmt::Display for DegradationRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is degraded ({}): {}", self.symbol, self.code.as_str(), self.reason)
+    }
+}
+
+impl std::error::Error for DegradationRejection {}
+
+/// 降级登记表
+#[derive(Debug, Clone, Default)]
+pub struct DegradationRegistry {
+    /// 交易对 -> 来源 -> 标记
+    marks: BTreeMap<String, BTreeMap<String, DegradationMark>>,
+    /// 每次变更递增，推送方据此判断是否需要广播
+    version: u64,
+}
+
+impl DegradationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// 来源标记交易对降级（同一来源重复标记时覆盖），`codes` 为空时等同于撤销
+    pub fn mark(
+        &mut self,
+        symbol: &str,
+        source: &str,
+        codes: impl IntoIterator<Item = DegradationCode>,
+        reason: &str,
+        now_ms: u64,
+    ) {
+        let codes: BTreeSet<DegradationCode> = codes.into_iter().collect();
+        if codes.is_empty() {
+            self.clear(symbol, source);
+            return;
+        }
+        let marks = self.marks.entry(symbol.to_string()).or_default();
+        let since_ms = marks.get(source).map_or(now_ms, |mark| mark.since_ms);
+        marks.insert(
+            source.to_string(),
+            DegradationMark { codes, reason: reason.to_string(), since_ms },
+        );
+        self.version += 1;
+    }
+
+    /// 撤销来源对交易对的标记，返回是否存在
+    pub fn clear(&mut self, symbol: &str, source: &str) -> bool {
+        let Some(marks) = self.marks.get_mut(symbol) else {
+            return false;
+        };
+        let removed = marks.remove(source).is_some();
+        if marks.is_empty() {
+            self.marks.remove(symbol);
+        }
+        if removed {
+            self.version += 1;
+        }
+        removed
+    }
+
+    /// 来源恢复：撤销其全部标记，返回受影响的交易对
+    pub fn clear_source(&mut self, source: &str) -> Vec<String> {
+        let symbols: Vec<String> = self
+            .marks
+            .iter()
+            .filter(|(_, marks)| marks.contains_key(source))
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        for symbol in &symbols {
+            self.clear(symbol, source);
+        }
+        symbols
+    }
+
+    /// 交易对的生效降级状态（未降级为 None）
+    pub fn get(&self, symbol: &str) -> Option<SymbolDegradation> {
+        let marks = self.marks.get(symbol)?;
+        let codes: BTreeSet<DegradationCode> =
+            marks.values().flat_map(|mark| mark.codes.iter().copied()).collect();
+        Some(SymbolDegradation {
+            codes: codes.into_iter().collect(),
+            reason: marks.values().map(|mark| mark.reason.as_str()).collect::<Vec<_>>().join("; "),
+            sources: marks.keys().cloned().collect(),
+            since: marks.values().map(|mark| mark.since_ms).min().unwrap_or_default(),
+        })
+    }
+
+    /// 全部降级的交易对（按名称排序）
+    pub fn degraded(&self) -> Vec<(String, SymbolDegradation)> {
+        self.marks.keys().filter_map(|symbol| Some((symbol.clone(), self.get(symbol)?))).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// 新委托是否可接受；`is_market` 为市价单。撤单不经过此检查
+    pub fn check_order(&self, symbol: &str, is_market: bool) -> Result<(), DegradationRejection> {
+        let Some(degradation) = self.get(symbol) else {
+            return Ok(());
+        };
+        let code = if degradation.has(DegradationCode::CancelOnly) {
+            DegradationCode::CancelOnly
+        } else if is_market && degradation.has(DegradationCode::LimitOnly) {
+            DegradationCode::LimitOnly
+        } else {
+            return Ok(());
+        };
+        Err(DegradationRejection { symbol: symbol.to_string(), code, reason: degradation.reason })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_combine_and_clear() {
+        let mut registry = DegradationRegistry::new();
+        registry.mark("BTCUSDT", "shard-2", [DegradationCode::LimitOnly], "shard 2 lagging", 100);
+        registry.mark(
+            "BTCUSDT",
+            "market-data",
+            [DegradationCode::MarketDataDelayed],
+            "feed delayed",
+            50,
+        );
+        registry.mark("ETHUSDT", "shard-2", [DegradationCode::CancelOnly], "shard 2 lagging", 100);
+
+        let btc = registry.get("BTCUSDT").unwrap();
+        assert_eq!(btc.codes, vec![DegradationCode::LimitOnly, DegradationCode::MarketDataDelayed]);
+        assert_eq!(btc.reason, "feed delayed; shard 2 lagging");
+        assert_eq!(btc.since, 50);
+
+        assert_eq!(registry.check_order("BTCUSDT", false), Ok(()));
+        assert_eq!(
+            registry.check_order("BTCUSDT", true).unwrap_err().code,
+            DegradationCode::LimitOnly
+        );
+        let rejection = registry.check_order("ETHUSDT", false).unwrap_err();
+        assert_eq!(rejection.code, DegradationCode::CancelOnly);
+        assert_eq!(rejection.to_string(), "ETHUSDT is degraded (CANCEL_ONLY): shard 2 lagging");
+        assert_eq!(registry.check_order("SOLUSDT", true), Ok(()));
+
+        let version = registry.version();
+        assert_eq!(registry.clear_source("shard-2"), vec!["BTCUSDT", "ETHUSDT"]);
+        assert!(registry.version() > version);
+        assert_eq!(
+            registry.get("BTCUSDT").unwrap().codes,
+            vec![DegradationCode::MarketDataDelayed]
+        );
+        assert!(registry.get("ETHUSDT").is_none());
+        registry.mark("BTCUSDT", "market-data", [], "", 200);
+        assert!(registry.is_empty());
+        assert_eq!(DegradationCode::parse("limit_only"), Some(DegradationCode::LimitOnly));
+    }
+}
@@ -0,0 +1,225 @@
+//! exchangeInfo 响应模型
+//!
+//! 公开接口 `GET /api/exchangeInfo` 的返回内容：可交易产品列表（精度、最小变动价位、
+//! 数量步长、最小名义价值、状态、费率方案引用）与限频规则，供客户端 SDK 在本地做下单校验。
+//! 数值以固定小数位的字符串表示，避免浮点误差。降级中的产品附带 `degradation`（见 [`super::degradation`]）
+
+use super::degradation::{DegradationRegistry, SymbolDegradation};
+use super::normalize::format_units;
+use super::registry::{InstrumentRegistry, InstrumentSpec, InstrumentStatus};
+
+/// `Decimal` 的小数位数
+const DECIMAL_SCALE: u32 = 8;
+
+/// 限频类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum RateLimitType {
+    /// 请求权重
+    RequestWeight,
+    /// 下单次数
+    Orders,
+    /// 原始请求次数
+    RawRequests,
+}
+
+/// 限频时间单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum RateLimitInterval {
+    Second,
+    Minute,
+    Day,
+}
+
+/// 限频规则：每 `interval_num` 个 `interval` 内不超过 `limit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RateLimitDescriptor {
+    pub rate_limit_type: RateLimitType,
+    pub interval: RateLimitInterval,
+    pub interval_num: u32,
+    pub limit: u32,
+}
+
+/// 单个产品的公开元数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub symbol_id: u32,
+    /// 产品类型（Spot / Perpetual / Futures / Options）
+    pub instrument_type: String,
+    /// 交易状态（TRADING / HALT / PRE_TRADING / DELISTED）
+    pub status: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    /// 价格小数位数
+    pub price_precision: u32,
+    /// 数量小数位数
+    pub quantity_precision: u32,
+    /// 最小变动价位
+    pub tick_size: String,
+    /// 数量步长
+    pub step_size: String,
+    pub min_qty: String,
+    pub max_qty: String,
+    pub min_notional: String,
+    /// 费率方案引用
+    pub fee_schedule: String,
+    /// 交割时间（Unix 毫秒，仅交割合约）
+    pub delivery_date: Option<u64>,
+    /// 降级状态（正常时不输出）
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub degradation: Option<SymbolDegradation>,
+}
+
+impl From<&InstrumentSpec> for SymbolInfo {
+    fn from(spec: &InstrumentSpec) -> Self {
+        let price_scale = spec.scale.price_scale;
+        let quantity_scale = spec.scale.quantity_scale;
+        Self {
+            symbol: spec.symbol.clone(),
+            symbol_id: spec.symbol_id,
+            instrument_type: spec.instrument_type.to_string(),
+            status: spec.status.as_str().to_string(),
+            base_asset: spec.base_asset.as_str().to_string(),
+            quote_asset: spec.quote_asset.as_str().to_string(),
+            price_precision: price_scale,
+            quantity_precision: quantity_scale,
+            tick_size: format_units(spec.tick_size, price_scale),
+            step_size: format_units(spec.lot_size, quantity_scale),
+            min_qty: format_units(spec.min_quantity, quantity_scale),
+            max_qty: format_units(spec.max_quantity, quantity_scale),
+            min_notional: format_units(spec.min_notional.raw(), DECIMAL_SCALE),
+            fee_schedule: spec.fee_schedule.clone(),
+            delivery_date: spec.expiry.map(|expiry| expiry.0 / 1_000_000),
+            degradation: None,
+        }
+    }
+}
+
+/// exchangeInfo 响应
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ExchangeInfo {
+    pub timezone: String,
+    /// 服务器时间（Unix 毫秒）
+    pub server_time: u64,
+    pub rate_limits: Vec<RateLimitDescriptor>,
+    pub symbols: Vec<SymbolInfo>,
+}
+
+impl ExchangeInfo {
+    /// 由注册表生成；`symbol` 指定时只返回该产品（不存在返回 None）
+    ///
+    /// 已下线的产品不公布
+    pub fn build(
+        registry: &InstrumentRegistry,
+        rate_limits: &[RateLimitDescriptor],
+        server_time: u64,
+        symbol: Option<&str>,
+    ) -> Option<Self> {
+        let symbols = match symbol {
+            Some(symbol) => vec![SymbolInfo::from(registry.get(symbol)?)],
+            None => registry
+                .iter()
+                .filter(|spec| spec.status != InstrumentStatus::Delisted)
+                .map(SymbolInfo::from)
+                .collect(),
+        };
+        Some(Self {
+            timezone: "UTC".to_string(),
+            server_time,
+            rate_limits: rate_limits.to_vec(),
+            symbols,
+        })
+    }
+
+    /// 为降级中的产品附上降级状态
+    pub fn apply_degradations(&mut self, registry: &DegradationRegistry) {
+        for symbol in &mut self.symbols {
+            symbol.degradation = registry.get(&symbol.symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::degradation::DegradationCode;
+    use crate::instrument::instrument_types::InstrumentType;
+    use crate::instrument::normalize::InstrumentScale;
+    use crate::{AssetId, Decimal};
+
+    #[test]
+    fn test_build_exchange_info() {
+        let mut registry = InstrumentRegistry::new();
+        registry
+            .register(
+                InstrumentSpec::new(
+                    1,
+                    "BTCUSDT",
+                    InstrumentType::Spot,
+                    AssetId::Btc,
+                    AssetId::Usdt,
+                    InstrumentScale::new(2, 6),
+                )
+                .with_tick_size(10)
+                .with_lot_size(10, 10, 9_000_000_000)
+                .with_min_notional(Decimal::from_raw(5_000_000_000))
+                .with_fee_schedule("spot-vip"),
+            )
+            .unwrap();
+        registry
+            .register(
+                InstrumentSpec::new(
+                    3,
+                    "BTCETH",
+                    InstrumentType::Spot,
+                    AssetId::Btc,
+                    AssetId::Eth,
+                    InstrumentScale::new(5, 4),
+                )
+                .with_status(InstrumentStatus::Delisted),
+            )
+            .unwrap();
+        let limits = [RateLimitDescriptor {
+            rate_limit_type: RateLimitType::RequestWeight,
+            interval: RateLimitInterval::Minute,
+            interval_num: 1,
+            limit: 1200,
+        }];
+
+        let info = ExchangeInfo::build(&registry, &limits, 42, None).unwrap();
+        assert_eq!((info.server_time, info.rate_limits.len()), (42, 1));
+        assert_eq!(info.symbols.len(), 1);
+        let btc = &info.symbols[0];
+        assert_eq!((btc.tick_size.as_str(), btc.step_size.as_str()), ("0.10", "0.000010"));
+        assert_eq!((btc.min_qty.as_str(), btc.max_qty.as_str()), ("0.000010", "9000.000000"));
+        assert_eq!(btc.min_notional, "50.00000000");
+        assert_eq!((btc.status.as_str(), btc.fee_schedule.as_str()), ("TRADING", "spot-vip"));
+        assert_eq!((btc.base_asset.as_str(), btc.quote_asset.as_str()), ("BTC", "USDT"));
+
+        // 指定产品时下线产品也可查询
+        let single = ExchangeInfo::build(&registry, &limits, 42, Some("BTCETH")).unwrap();
+        assert_eq!(single.symbols[0].status, "DELISTED");
+        assert!(ExchangeInfo::build(&registry, &limits, 42, Some("DOGEUSDT")).is_none());
+
+        // 降级中的产品附带降级状态
+        let mut degradations = DegradationRegistry::new();
+        degradations.mark("BTCUSDT", "shard-1", [DegradationCode::CancelOnly], "shard down", 7);
+        let mut info = info;
+        info.apply_degradations(&degradations);
+        let degradation = info.symbols[0].degradation.as_ref().unwrap();
+        assert_eq!(
+            (degradation.codes.as_slice(), degradation.since),
+            (&[DegradationCode::CancelOnly][..], 7)
+        );
+    }
+}
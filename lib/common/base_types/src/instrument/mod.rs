@@ -1 +1,5 @@
+pub mod degradation;
+pub mod exchange_info;
 pub mod instrument_types;
+pub mod normalize;
+pub mod registry;
@@ -0,0 +1,216 @@
+//! 价格 / 数量规范化
+//!
+//! REST、WS、FIX 入口收到的价格与数量都是十进制字符串，统一经此转换为
+//! 按产品声明精度表示的整数（最小变动单位个数），各入口对同一输入得到相同结果：
+//! - 不接受科学计数法、正负号与空串，首尾空白忽略
+//! - 小数位超过产品精度时按产品规则处理（拒绝 / 截断 / 四舍五入）
+//! - 结果必须为正且不超过 i64
+
+use crate::Decimal;
+
+/// `Decimal` 的小数位数
+const DECIMAL_SCALE: u32 = 8;
+
+/// 超出精度部分的处理规则
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingRule {
+    /// 拒绝（默认，避免客户端以为按原值下单）
+    #[default]
+    Reject,
+    /// 向零截断
+    Down,
+    /// 四舍五入
+    HalfUp,
+}
+
+/// 规范化错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NormalizeError {
+    /// 不是合法的十进制数
+    Invalid,
+    /// 结果为零
+    Zero,
+    /// 小数位超过产品精度（规则为拒绝时）
+    TooPrecise { scale: u32 },
+    /// 超出整数表示范围
+    Overflow,
+}
+
+impl std::fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NormalizeError::Invalid => write!(f, "Invalid decimal"),
+            NormalizeError::Zero => write!(f, "Value must be positive"),
+            NormalizeError::TooPrecise { scale } => {
+                write!(f, "Too many decimal places: at most {}", scale)
+            }
+            NormalizeError::Overflow => write!(f, "Value out of range"),
+        }
+    }
+}
+
+impl std::error::Error for NormalizeError {}
+
+/// 产品精度声明
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstrumentScale {
+    /// 价格小数位数
+    pub price_scale: u32,
+    /// 数量小数位数
+    pub quantity_scale: u32,
+    /// 价格超精度规则
+    pub price_rounding: RoundingRule,
+    /// 数量超精度规则
+    pub quantity_rounding: RoundingRule,
+}
+
+impl InstrumentScale {
+    /// 超精度一律拒绝
+    pub const fn new(price_scale: u32, quantity_scale: u32) -> Self {
+        Self {
+            price_scale,
+            quantity_scale,
+            price_rounding: RoundingRule::Reject,
+            quantity_rounding: RoundingRule::Reject,
+        }
+    }
+
+    pub const fn with_rounding(mut self, price: RoundingRule, quantity: RoundingRule) -> Self {
+        self.price_rounding = price;
+        self.quantity_rounding = quantity;
+        self
+    }
+
+    /// 价格字符串 → 价格最小变动单位个数
+    pub fn normalize_price(&self, input: &str) -> Result<i64, NormalizeError> {
+        normalize(input, self.price_scale, self.price_rounding)
+    }
+
+    /// 数量字符串 → 数量最小变动单位个数
+    pub fn normalize_quantity(&self, input: &str) -> Result<i64, NormalizeError> {
+        normalize(input, self.quantity_scale, self.quantity_rounding)
+    }
+
+    /// 价格字符串 → `Decimal`（精度不超过 8 位的产品）
+    pub fn price_decimal(&self, input: &str) -> Result<Decimal, NormalizeError> {
+        to_decimal(self.normalize_price(input)?, self.price_scale)
+    }
+
+    /// 数量字符串 → `Decimal`（精度不超过 8 位的产品）
+    pub fn quantity_decimal(&self, input: &str) -> Result<Decimal, NormalizeError> {
+        to_decimal(self.normalize_quantity(input)?, self.quantity_scale)
+    }
+}
+
+/// 十进制字符串 → `scale` 位小数的整数
+pub fn normalize(input: &str, scale: u32, rounding: RoundingRule) -> Result<i64, NormalizeError> {
+    let input = input.trim();
+    let (int, frac) = input.split_once('.').unwrap_or((input, ""));
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (int.is_empty() && frac.is_empty()) || !all_digits(int) || !all_digits(frac) {
+        return Err(NormalizeError::Invalid);
+    }
+
+    let keep = frac.len().min(scale as usize);
+    let (kept, dropped) = frac.split_at(keep);
+    let round_up = if dropped.bytes().all(|b| b == b'0') {
+        false
+    } else {
+        match rounding {
+            RoundingRule::Reject => return Err(NormalizeError::TooPrecise { scale }),
+            RoundingRule::Down => false,
+            RoundingRule::HalfUp => dropped.as_bytes()[0] >= b'5',
+        }
+    };
+
+    let mut units: i64 = 0;
+    for digit in
+        int.bytes().chain(kept.bytes()).chain(std::iter::repeat_n(b'0', scale as usize - keep))
+    {
+        units = units
+            .checked_mul(10)
+            .and_then(|u| u.checked_add((digit - b'0') as i64))
+            .ok_or(NormalizeError::Overflow)?;
+    }
+    if round_up {
+        units = units.checked_add(1).ok_or(NormalizeError::Overflow)?;
+    }
+    if units == 0 {
+        return Err(NormalizeError::Zero);
+    }
+    Ok(units)
+}
+
+/// `scale` 位小数的整数 → 十进制字符串（固定 `scale` 位小数，`normalize` 的逆运算）
+pub fn format_units(units: i64, scale: u32) -> String {
+    let digits = units.unsigned_abs().to_string();
+    let scale = scale as usize;
+    let padded = format!("{:0>width$}", digits, width = scale + 1);
+    let (int, frac) = padded.split_at(padded.len() - scale);
+    let sign = if units < 0 { "-" } else { "" };
+    if frac.is_empty() { format!("{}{}", sign, int) } else { format!("{}{}.{}", sign, int, frac) }
+}
+
+fn to_decimal(units: i64, scale: u32) -> Result<Decimal, NormalizeError> {
+    let factor = DECIMAL_SCALE.checked_sub(scale).ok_or(NormalizeError::TooPrecise { scale })?;
+    units.checked_mul(10i64.pow(factor)).map(Decimal::from_raw).ok_or(NormalizeError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_rules() {
+        assert_eq!(normalize("123.45", 2, RoundingRule::Reject), Ok(12345));
+        assert_eq!(normalize(" 0.5 ", 2, RoundingRule::Reject), Ok(50));
+        assert_eq!(normalize("7", 3, RoundingRule::Reject), Ok(7000));
+        assert_eq!(normalize(".25", 2, RoundingRule::Reject), Ok(25));
+        assert_eq!(normalize("1.230000", 2, RoundingRule::Reject), Ok(123));
+
+        assert_eq!(
+            normalize("1.235", 2, RoundingRule::Reject),
+            Err(NormalizeError::TooPrecise { scale: 2 })
+        );
+        assert_eq!(normalize("1.235", 2, RoundingRule::Down), Ok(123));
+        assert_eq!(normalize("1.235", 2, RoundingRule::HalfUp), Ok(124));
+        assert_eq!(normalize("1.2349", 2, RoundingRule::HalfUp), Ok(123));
+
+        for invalid in ["", ".", "-1", "+1", "1e5", "1.2.3", "1,5", "abc"] {
+            assert_eq!(normalize(invalid, 2, RoundingRule::Down), Err(NormalizeError::Invalid));
+        }
+        assert_eq!(normalize("0.001", 2, RoundingRule::Down), Err(NormalizeError::Zero));
+        assert_eq!(
+            normalize("99999999999999999999", 0, RoundingRule::Reject),
+            Err(NormalizeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(1, 2), "0.01");
+        assert_eq!(format_units(6_500_010, 2), "65000.10");
+        assert_eq!(format_units(7, 0), "7");
+        assert_eq!(format_units(-5, 3), "-0.005");
+        for (input, scale) in [("0.000001", 6), ("123.45", 2), ("1000", 0)] {
+            let units = normalize(input, scale, RoundingRule::Reject).unwrap();
+            assert_eq!(format_units(units, scale), input);
+        }
+    }
+
+    #[test]
+    fn test_instrument_scale_to_decimal() {
+        let btc_usdt =
+            InstrumentScale::new(2, 6).with_rounding(RoundingRule::Reject, RoundingRule::Down);
+        assert_eq!(btc_usdt.normalize_price("65000.10"), Ok(6_500_010));
+        assert_eq!(btc_usdt.normalize_quantity("0.0012345"), Ok(1234));
+        assert_eq!(btc_usdt.price_decimal("65000.1"), Ok(Decimal::from_raw(6_500_010_000_000)));
+        assert_eq!(btc_usdt.quantity_decimal("0.5"), Ok(Decimal::from_raw(50_000_000)));
+
+        let meme = InstrumentScale::new(10, 0);
+        assert_eq!(
+            meme.price_decimal("0.0000000001"),
+            Err(NormalizeError::TooPrecise { scale: 10 })
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! 产品注册表
+//!
+//! 记录可交易产品的元数据：精度、最小变动价位、数量步长与上下限、最小名义价值、
+//! 交易状态及费率方案引用。下单校验与对外公布的 exchangeInfo 均以此为准。
+//! 交割合约另记到期时间，到期后由 [`InstrumentRegistry::halt_expired`] 停止交易，
+//! 等待撮合引擎按交割价格结算全部持仓
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::instrument_types::InstrumentType;
+use super::normalize::InstrumentScale;
+use crate::{AssetId, Decimal, Timestamp};
+
+/// 产品交易状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InstrumentStatus {
+    /// 已上线，尚未开放交易
+    PreTrading,
+    /// 正常交易
+    #[default]
+    Trading,
+    /// 暂停交易
+    Halted,
+    /// 已下线
+    Delisted,
+}
+
+impl InstrumentStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            InstrumentStatus::PreTrading => "PRE_TRADING",
+            InstrumentStatus::Trading => "TRADING",
+            InstrumentStatus::Halted => "HALT",
+            InstrumentStatus::Delisted => "DELISTED",
+        }
+    }
+}
+
+/// 产品元数据
+///
+/// 价格类字段以价格精度的整数单位表示，数量类字段以数量精度的整数单位表示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstrumentSpec {
+    /// 交易对ID
+    pub symbol_id: u32,
+    /// 交易对名称（如 BTCUSDT）
+    pub symbol: String,
+    /// 产品类型
+    pub instrument_type: InstrumentType,
+    /// 基础资产
+    pub base_asset: AssetId,
+    /// 计价资产
+    pub quote_asset: AssetId,
+    /// 交易状态
+    pub status: InstrumentStatus,
+    /// 价格 / 数量精度
+    pub scale: InstrumentScale,
+    /// 最小变动价位
+    pub tick_size: i64,
+    /// 数量步长
+    pub lot_size: i64,
+    /// 最小下单数量
+    pub min_quantity: i64,
+    /// 最大下单数量
+    pub max_quantity: i64,
+    /// 最小名义价值（计价资产）
+    pub min_notional: Decimal,
+    /// 费率方案引用
+    pub fee_schedule: String,
+    /// 到期时间（仅交割合约）
+    pub expiry: Option<Timestamp>,
+}
+
+impl InstrumentSpec {
+    /// 默认：正常交易，最小变动与步长为 1 个精度单位，不限数量与名义价值，
+    /// 费率方案按产品类型
+    pub fn new(
+        symbol_id: u32,
+        symbol: &str,
+        instrument_type: InstrumentType,
+        base_asset: AssetId,
+        quote_asset: AssetId,
+        scale: InstrumentScale,
+    ) -> Self {
+        Self {
+            symbol_id,
+            symbol: symbol.to_string(),
+            instrument_type,
+            base_asset,
+            quote_asset,
+            status: InstrumentStatus::Trading,
+            scale,
+            tick_size: 1,
+            lot_size: 1,
+            min_quantity: 1,
+            max_quantity: i64::MAX,
+            min_notional: Decimal::default(),
+            fee_schedule: instrument_type.to_string().to_lowercase(),
+            expiry: None,
+        }
+    }
+
+    pub fn with_tick_size(mut self, tick_size: i64) -> Self {
+        self.tick_size = tick_size.max(1);
+        self
+    }
+
+    /// 数量步长与上下限
+    pub fn with_lot_size(mut self, lot_size: i64, min_quantity: i64, max_quantity: i64) -> Self {
+        self.lot_size = lot_size.max(1);
+        self.min_quantity = min_quantity;
+        self.max_quantity = max_quantity;
+        self
+    }
+
+    pub fn with_min_notional(mut self, min_notional: Decimal) -> Self {
+        self.min_notional = min_notional;
+        self
+    }
+
+    pub fn with_fee_schedule(mut self, fee_schedule: &str) -> Self {
+        self.fee_schedule = fee_schedule.to_string();
+        self
+    }
+
+    pub fn with_status(mut self, status: InstrumentStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// 到期时间（只对有到期日的产品类型生效）
+    pub fn with_expiry(mut self, expiry: Timestamp) -> Self {
+        self.expiry = self.instrument_type.has_expiry().then_some(expiry);
+        self
+    }
+
+    /// 到 `now` 是否已到期
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expiry.is_some_and(|expiry| now.0 >= expiry.0)
+    }
+}
+
+/// 注册表错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// 交易对名称已存在
+    DuplicateSymbol(String),
+    /// 交易对ID已存在
+    DuplicateId(u32),
+    /// 交易对不存在
+    UnknownSymbol(String),
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::DuplicateSymbol(symbol) => write!(f, "Duplicate symbol: {}", symbol),
+            RegistryError::DuplicateId(id) => write!(f, "Duplicate symbol id: {}", id),
+            RegistryError::UnknownSymbol(symbol) => write!(f, "Unknown symbol: {}", symbol),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// 产品注册表（按交易对名称排序）
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    by_symbol: BTreeMap<String, InstrumentSpec>,
+    /// 交易对ID -> 名称
+    symbols: HashMap<u32, String>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册产品（名称与ID均不可重复）
+    pub fn register(&mut self, spec: InstrumentSpec) -> Result<(), RegistryError> {
+        if self.by_symbol.contains_key(&spec.symbol) {
+            return Err(RegistryError::DuplicateSymbol(spec.symbol));
+        }
+        if self.symbols.contains_key(&spec.symbol_id) {
+            return Err(RegistryError::DuplicateId(spec.symbol_id));
+        }
+        self.symbols.insert(spec.symbol_id, spec.symbol.clone());
+        self.by_symbol.insert(spec.symbol.clone(), spec);
+        Ok(())
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&InstrumentSpec> {
+        self.by_symbol.get(symbol)
+    }
+
+    pub fn by_id(&self, symbol_id: u32) -> Option<&InstrumentSpec> {
+        self.get(self.symbols.get(&symbol_id)?)
+    }
+
+    /// 变更交易状态
+    pub fn set_status(
+        &mut self,
+        symbol: &str,
+        status: InstrumentStatus,
+    ) -> Result<(), RegistryError> {
+        let spec = self
+            .by_symbol
+            .get_mut(symbol)
+            .ok_or_else(|| RegistryError::UnknownSymbol(symbol.to_string()))?;
+        spec.status = status;
+        Ok(())
+    }
+
+    /// 停止已到期产品的交易，返回本次停止的交易对
+    ///
+    /// 只处理交易中与预开放的产品，已暂停或已下线的保持原状态；重复调用不会重复返回
+    pub fn halt_expired(&mut self, now: Timestamp) -> Vec<String> {
+        let mut halted = Vec::new();
+        for spec in self.by_symbol.values_mut() {
+            let open =
+                matches!(spec.status, InstrumentStatus::Trading | InstrumentStatus::PreTrading);
+            if open && spec.is_expired(now) {
+                spec.status = InstrumentStatus::Halted;
+                halted.push(spec.symbol.clone());
+            }
+        }
+        halted
+    }
+
+    /// 全部产品（按名称排序）
+    pub fn iter(&self) -> impl Iterator<Item = &InstrumentSpec> {
+        self.by_symbol.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_symbol.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_symbol.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc_usdt() -> InstrumentSpec {
+        InstrumentSpec::new(
+            1,
+            "BTCUSDT",
+            InstrumentType::Spot,
+            AssetId::Btc,
+            AssetId::Usdt,
+            InstrumentScale::new(2, 6),
+        )
+    }
+
+    #[test]
+    fn test_registry_lookup_and_status() {
+        let mut registry = InstrumentRegistry::new();
+        registry.register(btc_usdt()).unwrap();
+        assert_eq!(
+            registry.register(btc_usdt()),
+            Err(RegistryError::DuplicateSymbol("BTCUSDT".to_string()))
+        );
+        let mut other = btc_usdt();
+        other.symbol = "BTCUSDC".to_string();
+        assert_eq!(registry.register(other), Err(RegistryError::DuplicateId(1)));
+
+        assert_eq!(registry.by_id(1).unwrap().fee_schedule, "spot");
+        registry.set_status("BTCUSDT", InstrumentStatus::Halted).unwrap();
+        assert_eq!(registry.get("BTCUSDT").unwrap().status, InstrumentStatus::Halted);
+        assert!(registry.set_status("ETHUSDT", InstrumentStatus::Trading).is_err());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_halt_expired_futures() {
+        let mut registry = InstrumentRegistry::new();
+        registry.register(btc_usdt().with_expiry(Timestamp(1_000))).unwrap();
+        let quarterly = InstrumentSpec::new(
+            2,
+            "BTCUSDT_240628",
+            InstrumentType::Futures,
+            AssetId::Btc,
+            AssetId::Usdt,
+            InstrumentScale::new(2, 6),
+        )
+        .with_expiry(Timestamp(1_000));
+        registry.register(quarterly).unwrap();
+        // 现货没有到期日
+        assert_eq!(registry.get("BTCUSDT").unwrap().expiry, None);
+
+        assert!(registry.halt_expired(Timestamp(999)).is_empty());
+        assert_eq!(registry.halt_expired(Timestamp(1_000)), ["BTCUSDT_240628"]);
+        assert_eq!(registry.get("BTCUSDT_240628").unwrap().status, InstrumentStatus::Halted);
+        assert!(registry.halt_expired(Timestamp(2_000)).is_empty());
+    }
+}
@@ -8,6 +8,7 @@ extern crate decimal;
 
 pub mod account;
 pub mod base_types;
+pub mod clock;
 pub mod exchange;
 pub mod fee;
 pub mod mark_data;
@@ -30,6 +31,7 @@ pub use base_types::{
     AccountId, AssetId, OrderId, OrderSide, PositionId, Price, Quantity, Timestamp, TradeId,
     TradingPair, UserId,
 };
+pub use clock::{AtomicSequence, ManualClock, SequenceGenerator, SystemClock, TimestampProvider};
 pub use decimal::Decimal;
 pub use exchange::prep::perp_types::{PositionSide, PrepPosition, PrepTrade};
 pub use exchange::prep::prep_order::{FutureOrderStatus, TimeInForce};
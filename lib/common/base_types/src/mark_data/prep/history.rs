@@ -0,0 +1,534 @@
+//! 资金费率与标记价格历史
+//!
+//! 资金费率按结算周期逐条保存，标记价格按 K线周期聚合（只有 OHLC，没有成交量），
+//! 供 `/api/prep/fundingRate` 与 `/api/prep/markPriceKlines` 分页查询，用户据此核对资金费。
+//!
+//! 时间均为纳秒，对外响应换算为 Unix 毫秒。两类历史各自导出为带表头的 CSV，
+//! 价格与费率写原始整数（8 位小数定点），导出再加载无损
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+
+use crate::Price;
+use crate::mark_data::spot::candle::CandleInterval;
+use crate::mark_data::spot::level_types::SymbolId;
+
+/// 资金费率单次查询默认条数
+pub const DEFAULT_FUNDING_LIMIT: usize = 100;
+/// 资金费率单次查询最大条数
+pub const MAX_FUNDING_LIMIT: usize = 1000;
+/// 标记价格K线单次查询默认条数
+pub const DEFAULT_KLINES_LIMIT: usize = 500;
+/// 标记价格K线单次查询最大条数
+pub const MAX_KLINES_LIMIT: usize = 1500;
+
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+/// 资金费率文件表头
+const FUNDING_CSV_HEADER: &str = "symbol_id,funding_time,funding_rate,mark_price";
+/// 标记价格K线文件表头
+const KLINE_CSV_HEADER: &str = "symbol_id,interval,open_time,open,high,low,close";
+
+/// 查询条数：未指定取默认值，超出范围截断到 `[1, max]`
+pub fn clamp_history_limit(limit: Option<usize>, default: usize, max: usize) -> usize {
+    limit.unwrap_or(default).clamp(1, max)
+}
+
+/// 一次资金费率结算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FundingRateRecord {
+    pub symbol_id: SymbolId,
+    /// 结算时间（纳秒）
+    pub funding_time: u64,
+    /// 资金费率（正=多付空）
+    pub funding_rate: Price,
+    /// 结算时的标记价格
+    pub mark_price: Price,
+}
+
+/// 标记价格K线
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkPriceKline {
+    pub symbol_id: SymbolId,
+    pub interval: CandleInterval,
+    /// 开盘时间（纳秒）
+    pub open_time: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+}
+
+impl MarkPriceKline {
+    /// 收盘时间（不含）
+    pub fn close_time(&self) -> u64 {
+        self.open_time + self.interval.nanos()
+    }
+}
+
+/// 分页结果（时间升序）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryPage<T> {
+    pub items: Vec<T>,
+    /// 下一页的 `startTime`（纳秒，本页不足 `limit` 时为空）
+    pub next_start_time: Option<u64>,
+}
+
+/// 资金费率与标记价格历史
+#[derive(Debug)]
+pub struct PrepMarketHistory {
+    /// 标记价格聚合的周期
+    intervals: Vec<CandleInterval>,
+    /// 每个K线序列保留的最大根数（0=不限，资金费率始终全部保留）
+    retention: usize,
+    funding: HashMap<SymbolId, BTreeMap<u64, FundingRateRecord>>,
+    klines: HashMap<(SymbolId, CandleInterval), BTreeMap<u64, MarkPriceKline>>,
+}
+
+impl Default for PrepMarketHistory {
+    fn default() -> Self {
+        Self::new(&CandleInterval::ALL)
+    }
+}
+
+impl PrepMarketHistory {
+    /// 标记价格按指定周期聚合，不限保留数量
+    pub fn new(intervals: &[CandleInterval]) -> Self {
+        Self {
+            intervals: intervals.to_vec(),
+            retention: 0,
+            funding: HashMap::new(),
+            klines: HashMap::new(),
+        }
+    }
+
+    /// 每个K线序列最多保留 `retention` 根，超出淘汰最早的
+    pub fn with_retention(mut self, retention: usize) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// 记录一次资金费率结算（同一结算时间覆盖旧值）
+    pub fn record_funding(&mut self, record: FundingRateRecord) {
+        self.funding.entry(record.symbol_id).or_default().insert(record.funding_time, record);
+    }
+
+    /// 处理一次标记价格更新（同一交易对须按时间顺序到达）
+    pub fn on_mark_price(&mut self, symbol_id: SymbolId, timestamp: u64, price: Price) {
+        for &interval in &self.intervals {
+            let open_time = interval.open_time(timestamp);
+            let series = self.klines.entry((symbol_id, interval)).or_default();
+            series
+                .entry(open_time)
+                .and_modify(|kline| {
+                    kline.high = kline.high.max(price);
+                    kline.low = kline.low.min(price);
+                    kline.close = price;
+                })
+                .or_insert(MarkPriceKline {
+                    symbol_id,
+                    interval,
+                    open_time,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                });
+            Self::evict(series, self.retention);
+        }
+    }
+
+    /// 写入整根K线（加载历史，覆盖同一开盘时间的旧值）
+    pub fn insert_kline(&mut self, kline: MarkPriceKline) {
+        let series = self.klines.entry((kline.symbol_id, kline.interval)).or_default();
+        series.insert(kline.open_time, kline);
+        Self::evict(series, self.retention);
+    }
+
+    /// 结算时间在 `[start, end]` 内的资金费率
+    ///
+    /// 未指定 `start` 时返回截至 `end` 的最近 `limit` 条
+    pub fn funding_rates(
+        &self,
+        symbol_id: SymbolId,
+        start: Option<u64>,
+        end: Option<u64>,
+        limit: usize,
+    ) -> HistoryPage<FundingRateRecord> {
+        let items = self
+            .funding
+            .get(&symbol_id)
+            .map(|series| page(series, start, end, limit))
+            .unwrap_or_default();
+        Self::paged(items, limit, |record| record.funding_time)
+    }
+
+    /// 开盘时间在 `[start, end]` 内的标记价格K线
+    ///
+    /// 未指定 `start` 时返回截至 `end` 的最近 `limit` 根
+    pub fn mark_price_klines(
+        &self,
+        symbol_id: SymbolId,
+        interval: CandleInterval,
+        start: Option<u64>,
+        end: Option<u64>,
+        limit: usize,
+    ) -> HistoryPage<MarkPriceKline> {
+        let items = self
+            .klines
+            .get(&(symbol_id, interval))
+            .map(|series| page(series, start, end, limit))
+            .unwrap_or_default();
+        Self::paged(items, limit, |kline| kline.open_time)
+    }
+
+    /// 导出资金费率为 CSV（按交易对、时间排序），返回条数
+    pub fn write_funding_csv<W: Write>(&self, mut writer: W) -> io::Result<usize> {
+        writeln!(writer, "{}", FUNDING_CSV_HEADER)?;
+        let mut symbols: Vec<_> = self.funding.keys().copied().collect();
+        symbols.sort();
+        let mut count = 0;
+        for symbol_id in symbols {
+            for r in self.funding[&symbol_id].values() {
+                writeln!(
+                    writer,
+                    "{},{},{},{}",
+                    r.symbol_id,
+                    r.funding_time,
+                    r.funding_rate.raw(),
+                    r.mark_price.raw()
+                )?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// 加载 `write_funding_csv` 导出的文件，返回条数
+    pub fn load_funding_csv<R: BufRead>(&mut self, reader: R) -> io::Result<usize> {
+        load_csv(reader, "funding rate", parse_funding, |record| self.record_funding(record))
+    }
+
+    /// 导出标记价格K线为 CSV（按交易对、周期、时间排序），返回根数
+    pub fn write_klines_csv<W: Write>(&self, mut writer: W) -> io::Result<usize> {
+        writeln!(writer, "{}", KLINE_CSV_HEADER)?;
+        let mut keys: Vec<_> = self.klines.keys().copied().collect();
+        keys.sort();
+        let mut count = 0;
+        for key in keys {
+            for k in self.klines[&key].values() {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{}",
+                    k.symbol_id,
+                    k.interval.as_str(),
+                    k.open_time,
+                    k.open.raw(),
+                    k.high.raw(),
+                    k.low.raw(),
+                    k.close.raw()
+                )?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// 加载 `write_klines_csv` 导出的文件，返回根数
+    pub fn load_klines_csv<R: BufRead>(&mut self, reader: R) -> io::Result<usize> {
+        load_csv(reader, "mark price kline", parse_kline, |kline| self.insert_kline(kline))
+    }
+
+    fn paged<T>(items: Vec<T>, limit: usize, time: impl Fn(&T) -> u64) -> HistoryPage<T> {
+        let next_start_time = match items.last() {
+            Some(last) if items.len() == limit => Some(time(last) + 1),
+            _ => None,
+        };
+        HistoryPage { items, next_start_time }
+    }
+
+    fn evict(series: &mut BTreeMap<u64, MarkPriceKline>, retention: usize) {
+        while retention > 0 && series.len() > retention {
+            series.pop_first();
+        }
+    }
+}
+
+/// 按时间范围取一页（升序）
+fn page<T: Copy>(
+    series: &BTreeMap<u64, T>,
+    start: Option<u64>,
+    end: Option<u64>,
+    limit: usize,
+) -> Vec<T> {
+    let end = end.unwrap_or(u64::MAX);
+    match start {
+        Some(start) => series.range(start..=end.max(start)).take(limit).map(|(_, v)| *v).collect(),
+        None => {
+            let mut items: Vec<T> =
+                series.range(..=end).rev().take(limit).map(|(_, v)| *v).collect();
+            items.reverse();
+            items
+        }
+    }
+}
+
+fn load_csv<R: BufRead, T>(
+    reader: R,
+    kind: &str,
+    parse: fn(&str) -> Option<T>,
+    mut insert: impl FnMut(T),
+) -> io::Result<usize> {
+    let mut count = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if index == 0 || line.trim().is_empty() {
+            continue;
+        }
+        let item = parse(&line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {} at line {}", kind, index + 1),
+            )
+        })?;
+        insert(item);
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn parse_funding(line: &str) -> Option<FundingRateRecord> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [symbol_id, funding_time, funding_rate, mark_price] = fields.as_slice() else {
+        return None;
+    };
+    Some(FundingRateRecord {
+        symbol_id: symbol_id.parse().ok()?,
+        funding_time: funding_time.parse().ok()?,
+        funding_rate: Price::from_raw(funding_rate.parse().ok()?),
+        mark_price: Price::from_raw(mark_price.parse().ok()?),
+    })
+}
+
+fn parse_kline(line: &str) -> Option<MarkPriceKline> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [symbol_id, interval, open_time, open, high, low, close] = fields.as_slice() else {
+        return None;
+    };
+    let price = |s: &str| s.parse().ok().map(Price::from_raw);
+    Some(MarkPriceKline {
+        symbol_id: symbol_id.parse().ok()?,
+        interval: CandleInterval::parse(interval)?,
+        open_time: open_time.parse().ok()?,
+        open: price(open)?,
+        high: price(high)?,
+        low: price(low)?,
+        close: price(close)?,
+    })
+}
+
+/// 资金费率（对外响应）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FundingRateEntry {
+    pub symbol: String,
+    pub funding_rate: Price,
+    /// 结算时间（Unix 毫秒）
+    pub funding_time: u64,
+    pub mark_price: Price,
+}
+
+impl FundingRateEntry {
+    pub fn new(symbol: &str, record: &FundingRateRecord) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            funding_rate: record.funding_rate,
+            funding_time: record.funding_time / NANOS_PER_MILLI,
+            mark_price: record.mark_price,
+        }
+    }
+}
+
+/// 标记价格K线（对外响应）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MarkPriceKlineEntry {
+    /// 开盘时间（Unix 毫秒）
+    pub open_time: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// 收盘时间（Unix 毫秒，含）
+    pub close_time: u64,
+}
+
+impl From<&MarkPriceKline> for MarkPriceKlineEntry {
+    fn from(kline: &MarkPriceKline) -> Self {
+        Self {
+            open_time: kline.open_time / NANOS_PER_MILLI,
+            open: kline.open,
+            high: kline.high,
+            low: kline.low,
+            close: kline.close,
+            close_time: kline.close_time() / NANOS_PER_MILLI - 1,
+        }
+    }
+}
+
+/// 资金费率分页（对外响应）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FundingRatePage {
+    pub rates: Vec<FundingRateEntry>,
+    /// 下一页的 `startTime`（Unix 毫秒）
+    pub next_start_time: Option<u64>,
+}
+
+impl FundingRatePage {
+    pub fn new(symbol: &str, page: &HistoryPage<FundingRateRecord>) -> Self {
+        Self {
+            rates: page.items.iter().map(|r| FundingRateEntry::new(symbol, r)).collect(),
+            next_start_time: page.next_start_time.map(next_start_millis),
+        }
+    }
+}
+
+/// 标记价格K线分页（对外响应）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MarkPriceKlinePage {
+    pub klines: Vec<MarkPriceKlineEntry>,
+    /// 下一页的 `startTime`（Unix 毫秒）
+    pub next_start_time: Option<u64>,
+}
+
+impl From<&HistoryPage<MarkPriceKline>> for MarkPriceKlinePage {
+    fn from(page: &HistoryPage<MarkPriceKline>) -> Self {
+        Self {
+            klines: page.items.iter().map(MarkPriceKlineEntry::from).collect(),
+            next_start_time: page.next_start_time.map(next_start_millis),
+        }
+    }
+}
+
+/// 纳秒的下一页起点换算为毫秒（向上取整，避免同一毫秒内的记录重复返回）
+fn next_start_millis(nanos: u64) -> u64 {
+    nanos.div_ceil(NANOS_PER_MILLI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_NS: u64 = 3_600_000_000_000;
+
+    fn funding(symbol_id: SymbolId, hour: u64, rate: f64) -> FundingRateRecord {
+        FundingRateRecord {
+            symbol_id,
+            funding_time: hour * HOUR_NS,
+            funding_rate: Price::from_f64(rate),
+            mark_price: Price::from_f64(100.0),
+        }
+    }
+
+    #[test]
+    fn test_funding_rates_paginate() {
+        let mut history = PrepMarketHistory::default();
+        for hour in [0, 8, 16, 24, 32] {
+            history.record_funding(funding(1, hour, 0.0001));
+        }
+        history.record_funding(funding(2, 8, -0.0002));
+
+        let page = history.funding_rates(1, Some(0), None, 2);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.next_start_time, Some(8 * HOUR_NS + 1));
+        let page = history.funding_rates(1, page.next_start_time, None, 2);
+        let times: Vec<u64> = page.items.iter().map(|r| r.funding_time / HOUR_NS).collect();
+        assert_eq!(times, vec![16, 24]);
+        let page = history.funding_rates(1, page.next_start_time, None, 2);
+        assert_eq!((page.items.len(), page.next_start_time), (1, None));
+
+        // 未指定起点取最近的记录
+        let page = history.funding_rates(1, None, Some(24 * HOUR_NS), 2);
+        let times: Vec<u64> = page.items.iter().map(|r| r.funding_time / HOUR_NS).collect();
+        assert_eq!(times, vec![16, 24]);
+        assert_eq!(
+            history.funding_rates(2, None, None, 10).items[0].funding_rate.to_f64(),
+            -0.0002
+        );
+        assert!(history.funding_rates(3, None, None, 10).items.is_empty());
+
+        let entry = FundingRatePage::new("BTCUSDT", &history.funding_rates(1, Some(0), None, 1));
+        assert_eq!(entry.rates[0].symbol, "BTCUSDT");
+        assert_eq!(entry.next_start_time, Some(1));
+    }
+
+    #[test]
+    fn test_mark_price_klines_aggregate() {
+        let mut history = PrepMarketHistory::new(&[CandleInterval::Hour1]).with_retention(2);
+        let minute = HOUR_NS / 60;
+        history.on_mark_price(1, 0, Price::from_f64(100.0));
+        history.on_mark_price(1, 10 * minute, Price::from_f64(104.0));
+        history.on_mark_price(1, 20 * minute, Price::from_f64(98.0));
+        history.on_mark_price(1, 30 * minute, Price::from_f64(101.0));
+        history.on_mark_price(1, HOUR_NS, Price::from_f64(102.0));
+
+        let page = history.mark_price_klines(1, CandleInterval::Hour1, None, None, 10);
+        assert_eq!(page.items.len(), 2);
+        let first = page.items[0];
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (
+                Price::from_f64(100.0),
+                Price::from_f64(104.0),
+                Price::from_f64(98.0),
+                Price::from_f64(101.0)
+            )
+        );
+        let entry = MarkPriceKlineEntry::from(&first);
+        assert_eq!((entry.open_time, entry.close_time), (0, 3_599_999));
+
+        // 超出保留数淘汰最早的
+        history.on_mark_price(1, 2 * HOUR_NS, Price::from_f64(103.0));
+        let page = history.mark_price_klines(1, CandleInterval::Hour1, Some(0), None, 10);
+        assert_eq!(page.items[0].open_time, HOUR_NS);
+        assert!(
+            history.mark_price_klines(1, CandleInterval::Minute1, None, None, 10).items.is_empty()
+        );
+    }
+
+    #[test]
+    fn test_history_csv_round_trip() {
+        let mut history = PrepMarketHistory::new(&[CandleInterval::Minute1, CandleInterval::Hour1]);
+        history.record_funding(funding(1, 8, -0.000125));
+        history.record_funding(funding(1, 16, 0.0003));
+        history.on_mark_price(1, 0, Price::from_f64(100.5));
+        history.on_mark_price(1, HOUR_NS, Price::from_f64(99.25));
+
+        let mut funding_csv = Vec::new();
+        let mut klines_csv = Vec::new();
+        assert_eq!(history.write_funding_csv(&mut funding_csv).unwrap(), 2);
+        assert_eq!(history.write_klines_csv(&mut klines_csv).unwrap(), 4);
+
+        let mut loaded = PrepMarketHistory::new(&[]);
+        assert_eq!(loaded.load_funding_csv(funding_csv.as_slice()).unwrap(), 2);
+        assert_eq!(loaded.load_klines_csv(klines_csv.as_slice()).unwrap(), 4);
+        assert_eq!(
+            loaded.funding_rates(1, None, None, 10),
+            history.funding_rates(1, None, None, 10)
+        );
+        assert_eq!(
+            loaded.mark_price_klines(1, CandleInterval::Hour1, None, None, 10),
+            history.mark_price_klines(1, CandleInterval::Hour1, None, None, 10)
+        );
+
+        let broken = format!("{}\n1,8,oops,1\n", FUNDING_CSV_HEADER);
+        let err = loaded.load_funding_csv(broken.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
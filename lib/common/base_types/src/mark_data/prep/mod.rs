@@ -1 +1 @@
-
+pub mod history;
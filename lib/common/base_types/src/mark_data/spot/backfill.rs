@@ -0,0 +1,392 @@
+//! 历史成交回填
+//!
+//! 新部署的行情服务没有历史K线与滚动成交量。回填读取历史成交文件，按产品配置
+//! 逐行校验后驱动与实时行情相同的聚合逻辑（[`CandleStore`]、[`RollingVolume`]），
+//! 导出的K线文件在服务启动时加载即可带着历史开始
+//!
+//! 输入为带表头的 CSV，列按表头名称匹配（顺序不限，多余列忽略）：
+//! - 必需：`timestamp`（Unix 毫秒）、`symbol`、`price`、`quantity`
+//! - 可选：`trade_id`、`side`（`buy` / `sell`，主动方方向，缺省按买方）
+//!
+//! 逐行校验：交易对已配置、价格与数量为正且不超过产品精度、同一交易对内时间不倒退、
+//! 成交ID严格递增（有该列时；重复导入重叠文件时已导入的成交被跳过）。
+//! 不合格的行跳过并记入报告，文件本身不可读或缺列时整体失败
+
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use super::candle::CandleStore;
+use super::level_types::{SymbolId, TradeEvent};
+use super::rolling_volume::RollingVolume;
+use crate::OrderSide;
+use crate::instrument::normalize::{InstrumentScale, NormalizeError};
+
+/// 滚动成交量桶长：1 分钟
+const VOLUME_BUCKET_NS: u64 = 60_000_000_000;
+
+/// 报告中保留的被拒行数上限
+pub const MAX_REPORTED_REJECTIONS: usize = 100;
+
+/// 回填产品配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillInstrument {
+    /// 文件中的交易对名称
+    pub symbol: String,
+    /// 交易对ID
+    pub symbol_id: SymbolId,
+    /// 价格 / 数量精度
+    pub scale: InstrumentScale,
+}
+
+/// 单行校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowError {
+    /// 列数与表头不一致
+    ColumnCount { expected: usize, found: usize },
+    /// 未配置的交易对
+    UnknownSymbol(String),
+    /// 时间戳不合法
+    InvalidTimestamp,
+    /// 价格不合法
+    Price(NormalizeError),
+    /// 数量不合法
+    Quantity(NormalizeError),
+    /// 方向不合法
+    InvalidSide,
+    /// 成交ID不合法
+    InvalidTradeId,
+    /// 时间倒退
+    OutOfOrder { previous: u64, timestamp: u64 },
+    /// 成交ID未递增（重复导入）
+    StaleTradeId { previous: u64, trade_id: u64 },
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowError::ColumnCount { expected, found } => {
+                write!(f, "Expected {} columns, found {}", expected, found)
+            }
+            RowError::UnknownSymbol(symbol) => write!(f, "Unknown symbol: {}", symbol),
+            RowError::InvalidTimestamp => write!(f, "Invalid timestamp"),
+            RowError::Price(e) => write!(f, "Invalid price: {}", e),
+            RowError::Quantity(e) => write!(f, "Invalid quantity: {}", e),
+            RowError::InvalidSide => write!(f, "Invalid side"),
+            RowError::InvalidTradeId => write!(f, "Invalid trade id"),
+            RowError::OutOfOrder { previous, timestamp } => {
+                write!(f, "Timestamp {} before previous trade at {}", timestamp, previous)
+            }
+            RowError::StaleTradeId { previous, trade_id } => {
+                write!(f, "Trade id {} not after previous trade id {}", trade_id, previous)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RowError {}
+
+/// 回填错误（整个文件失败）
+#[derive(Debug)]
+pub enum BackfillError {
+    /// 读取失败
+    Io(io::Error),
+    /// 文件为空
+    MissingHeader,
+    /// 表头缺少必需列
+    MissingColumn(&'static str),
+}
+
+impl std::fmt::Display for BackfillError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackfillError::Io(e) => write!(f, "Read failed: {}", e),
+            BackfillError::MissingHeader => write!(f, "Missing header row"),
+            BackfillError::MissingColumn(column) => write!(f, "Missing column: {}", column),
+        }
+    }
+}
+
+impl std::error::Error for BackfillError {}
+
+impl From<io::Error> for BackfillError {
+    fn from(e: io::Error) -> Self {
+        BackfillError::Io(e)
+    }
+}
+
+/// 被拒的行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRow {
+    /// 行号（从 1 开始，含表头）
+    pub line: usize,
+    pub error: RowError,
+}
+
+/// 单个文件的回填报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    /// 导入的成交数
+    pub imported: u64,
+    /// 被拒的行数
+    pub rejected: u64,
+    /// 前若干条被拒行（最多 `MAX_REPORTED_REJECTIONS` 条）
+    pub rejections: Vec<RejectedRow>,
+}
+
+/// 表头中各列的位置
+struct Columns {
+    count: usize,
+    timestamp: usize,
+    symbol: usize,
+    price: usize,
+    quantity: usize,
+    trade_id: Option<usize>,
+    side: Option<usize>,
+}
+
+impl Columns {
+    fn parse(header: &str) -> Result<Self, BackfillError> {
+        let names: Vec<String> = split(header).map(str::to_ascii_lowercase).collect();
+        let find = |name: &str| names.iter().position(|n| n == name);
+        let require = |name: &'static str| find(name).ok_or(BackfillError::MissingColumn(name));
+        Ok(Self {
+            count: names.len(),
+            timestamp: require("timestamp")?,
+            symbol: require("symbol")?,
+            price: require("price")?,
+            quantity: require("quantity")?,
+            trade_id: find("trade_id"),
+            side: find("side"),
+        })
+    }
+}
+
+/// 交易对导入进度
+#[derive(Debug, Clone, Copy, Default)]
+struct Cursor {
+    last_timestamp: u64,
+    last_trade_id: Option<u64>,
+}
+
+/// 历史成交导入器
+#[derive(Debug)]
+pub struct BackfillImporter {
+    /// 交易对名称 -> 产品配置
+    instruments: HashMap<String, BackfillInstrument>,
+    candles: CandleStore,
+    volumes: HashMap<SymbolId, RollingVolume>,
+    cursors: HashMap<SymbolId, Cursor>,
+    /// 滚动成交量窗口（纳秒）
+    volume_window_ns: u64,
+    /// 合成的序列号
+    sequence: u64,
+}
+
+impl BackfillImporter {
+    pub fn new(
+        instruments: impl IntoIterator<Item = BackfillInstrument>,
+        candles: CandleStore,
+        volume_window_ns: u64,
+    ) -> Self {
+        Self {
+            instruments: instruments.into_iter().map(|i| (i.symbol.clone(), i)).collect(),
+            candles,
+            volumes: HashMap::new(),
+            cursors: HashMap::new(),
+            volume_window_ns,
+            sequence: 0,
+        }
+    }
+
+    /// 导入一个 CSV 文件；多个文件须按时间顺序依次导入
+    pub fn import_csv<R: BufRead>(&mut self, reader: R) -> Result<BackfillReport, BackfillError> {
+        let mut lines = reader.lines();
+        let header = lines.next().ok_or(BackfillError::MissingHeader)??;
+        let columns = Columns::parse(&header)?;
+
+        let mut report = BackfillReport::default();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match self.import_row(&columns, &line) {
+                Ok(()) => report.imported += 1,
+                Err(error) => {
+                    report.rejected += 1;
+                    if report.rejections.len() < MAX_REPORTED_REJECTIONS {
+                        report.rejections.push(RejectedRow { line: index + 2, error });
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// K线存储
+    pub fn candles(&self) -> &CandleStore {
+        &self.candles
+    }
+
+    /// 交易对的滚动成交量
+    pub fn volume(&mut self, symbol_id: SymbolId) -> Option<&mut RollingVolume> {
+        self.volumes.get_mut(&symbol_id)
+    }
+
+    /// 交易对最后一笔成交的时间（纳秒）
+    pub fn last_timestamp(&self, symbol_id: SymbolId) -> Option<u64> {
+        self.cursors.get(&symbol_id).map(|c| c.last_timestamp)
+    }
+
+    pub fn into_parts(self) -> (CandleStore, HashMap<SymbolId, RollingVolume>) {
+        (self.candles, self.volumes)
+    }
+
+    fn import_row(&mut self, columns: &Columns, line: &str) -> Result<(), RowError> {
+        let fields: Vec<&str> = split(line).collect();
+        if fields.len() != columns.count {
+            return Err(RowError::ColumnCount { expected: columns.count, found: fields.len() });
+        }
+
+        let symbol = fields[columns.symbol];
+        let instrument = self
+            .instruments
+            .get(symbol)
+            .ok_or_else(|| RowError::UnknownSymbol(symbol.to_string()))?;
+        let timestamp = fields[columns.timestamp]
+            .parse::<u64>()
+            .ok()
+            .and_then(|ms| ms.checked_mul(1_000_000))
+            .ok_or(RowError::InvalidTimestamp)?;
+        let price =
+            instrument.scale.price_decimal(fields[columns.price]).map_err(RowError::Price)?;
+        let quantity = instrument
+            .scale
+            .quantity_decimal(fields[columns.quantity])
+            .map_err(RowError::Quantity)?;
+        let side = match columns.side.map(|i| fields[i].to_ascii_lowercase()) {
+            None => OrderSide::Buy,
+            Some(side) if side == "buy" => OrderSide::Buy,
+            Some(side) if side == "sell" => OrderSide::Sell,
+            Some(_) => return Err(RowError::InvalidSide),
+        };
+        let trade_id = columns
+            .trade_id
+            .map(|i| fields[i].parse::<u64>().map_err(|_| RowError::InvalidTradeId))
+            .transpose()?;
+
+        let symbol_id = instrument.symbol_id;
+        let cursor = self.cursors.get(&symbol_id).copied();
+        if let Some(cursor) = cursor {
+            if let (Some(previous), Some(trade_id)) = (cursor.last_trade_id, trade_id) {
+                if trade_id <= previous {
+                    return Err(RowError::StaleTradeId { previous, trade_id });
+                }
+            }
+            if timestamp < cursor.last_timestamp {
+                return Err(RowError::OutOfOrder { previous: cursor.last_timestamp, timestamp });
+            }
+        }
+
+        self.sequence += 1;
+        let trade = TradeEvent {
+            symbol_id,
+            timestamp,
+            sequence: self.sequence,
+            trade_id: trade_id.unwrap_or(self.sequence),
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price,
+            quantity,
+            aggressor_side: side,
+        };
+        self.candles.on_trade(&trade);
+        let window = self.volume_window_ns;
+        self.volumes
+            .entry(symbol_id)
+            .or_insert_with(|| RollingVolume::new(window, VOLUME_BUCKET_NS))
+            .on_trade(&trade);
+        self.cursors
+            .insert(symbol_id, Cursor { last_timestamp: timestamp, last_trade_id: trade_id });
+        Ok(())
+    }
+}
+
+fn split(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').map(|field| field.trim().trim_matches('"'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mark_data::spot::candle::CandleInterval;
+    use crate::{Price, Quantity};
+
+    const DAY_NS: u64 = 86_400_000_000_000;
+
+    fn importer() -> BackfillImporter {
+        let btc = BackfillInstrument {
+            symbol: "BTCUSDT".to_string(),
+            symbol_id: 1,
+            scale: InstrumentScale::new(2, 6),
+        };
+        BackfillImporter::new([btc], CandleStore::new(&[CandleInterval::Minute1]), DAY_NS)
+    }
+
+    #[test]
+    fn test_import_validates_rows() {
+        let csv = "trade_id,timestamp,symbol,price,quantity,side
+1,60000,BTCUSDT,65000.10,0.5,buy
+2,60500,BTCUSDT,65010.00,0.25,sell
+3,61000,ETHUSDT,3000,1,buy
+4,61000,BTCUSDT,65000.123,1,buy
+5,59000,BTCUSDT,64990,1,buy
+6,120000,BTCUSDT,64990,0,buy
+7,120000,BTCUSDT,64990,1,hold
+8,120000,BTCUSDT,64990
+
+9,120000,BTCUSDT,64980,2,SELL
+";
+        let mut importer = importer();
+        let report = importer.import_csv(csv.as_bytes()).unwrap();
+        assert_eq!((report.imported, report.rejected), (3, 6));
+        let errors: Vec<_> = report.rejections.iter().map(|r| (r.line, r.error.clone())).collect();
+        assert_eq!(errors[0], (4, RowError::UnknownSymbol("ETHUSDT".to_string())));
+        assert_eq!(errors[1], (5, RowError::Price(NormalizeError::TooPrecise { scale: 2 })));
+        assert_eq!(
+            errors[2],
+            (6, RowError::OutOfOrder { previous: 60_500_000_000, timestamp: 59_000_000_000 })
+        );
+        assert_eq!(errors[3], (7, RowError::Quantity(NormalizeError::Zero)));
+        assert_eq!(errors[4], (8, RowError::InvalidSide));
+        assert_eq!(errors[5], (9, RowError::ColumnCount { expected: 6, found: 4 }));
+
+        let minute = importer.candles().latest(1, CandleInterval::Minute1).unwrap();
+        assert_eq!(minute.open_time, 120_000_000_000);
+        assert_eq!(
+            (minute.close, minute.volume),
+            (Price::from_f64(64980.0), Quantity::from_f64(2.0))
+        );
+        let totals = importer.volume(1).unwrap().totals(120_000_000_000);
+        assert_eq!((totals.volume, totals.trade_count), (Quantity::from_f64(2.75), 3));
+
+        // 重叠文件再次导入：已导入的成交被跳过
+        let overlap = "timestamp,symbol,price,quantity,trade_id
+120000,BTCUSDT,64980,2,9
+180000,BTCUSDT,64970,1,10
+";
+        let report = importer.import_csv(overlap.as_bytes()).unwrap();
+        assert_eq!((report.imported, report.rejected), (1, 1));
+        assert_eq!(importer.last_timestamp(1), Some(180_000_000_000));
+        assert_eq!(importer.candles().len(), 3);
+    }
+
+    #[test]
+    fn test_import_requires_columns() {
+        assert!(matches!(importer().import_csv("".as_bytes()), Err(BackfillError::MissingHeader)));
+        assert!(matches!(
+            importer().import_csv("timestamp,symbol,price\n".as_bytes()),
+            Err(BackfillError::MissingColumn("quantity"))
+        ));
+    }
+}
@@ -0,0 +1,407 @@
+//! BBO 快速通道
+//!
+//! 只发布最优买卖价变化，面向对延迟敏感的吃单方：
+//! - 每条消息为一个 SBE 帧（标准 8 字节消息头 + 定长块，小端），一个数据报一条消息
+//! - 通道序列号逐条加一，接收方据此发现丢包；引擎序列号用于与深度流对齐
+//! - 同一批增量中 BBO 先于深度更新发布（见 [`BboFastChannel::publish_batch`]）
+//!
+//! 定长块布局（64 字节）：
+//!
+//! ```text
+//! 0  symbol_id u32 | 4 flags u8 | 5 填充 3
+//! 8  channel_seq u64 | 16 engine_seq u64 | 24 timestamp u64（纳秒）
+//! 32 bid_price i64 | 40 bid_qty i64 | 48 ask_price i64 | 56 ask_qty i64
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+use super::level_types::{BboChangeEvent, MarketDataDelta, SymbolId};
+use crate::{Price, Quantity};
+
+/// SBE 模式ID
+pub const BBO_SCHEMA_ID: u16 = 1;
+/// SBE 模式版本
+pub const BBO_SCHEMA_VERSION: u16 = 0;
+/// BBO 消息模板ID
+pub const BBO_TEMPLATE_ID: u16 = 20;
+/// SBE 消息头长度
+const HEADER_LEN: usize = 8;
+/// 定长块长度
+pub const BBO_BLOCK_LENGTH: u16 = 64;
+/// 一条消息的总长度
+pub const BBO_MESSAGE_LEN: usize = HEADER_LEN + BBO_BLOCK_LENGTH as usize;
+
+/// flags：有买价
+const FLAG_BID: u8 = 0b01;
+/// flags：有卖价
+const FLAG_ASK: u8 = 0b10;
+
+/// BBO 消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BboMessage {
+    pub symbol_id: SymbolId,
+    /// 通道序列号（从 1 开始，逐条加一）
+    pub channel_seq: u64,
+    /// 引擎序列号
+    pub engine_seq: u64,
+    /// 事件时间戳（纳秒）
+    pub timestamp: u64,
+    pub bid_price: Option<Price>,
+    pub bid_qty: Quantity,
+    pub ask_price: Option<Price>,
+    pub ask_qty: Quantity,
+}
+
+/// 解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BboDecodeError {
+    /// 长度不足
+    Truncated(usize),
+    /// 模式或模板不匹配
+    UnknownTemplate { schema_id: u16, template_id: u16 },
+}
+
+impl std::fmt::Display for BboDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BboDecodeError::Truncated(len) => write!(f, "BBO message truncated: {} bytes", len),
+            BboDecodeError::UnknownTemplate { schema_id, template_id } => {
+                write!(f, "Unknown template: schema {} template {}", schema_id, template_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BboDecodeError {}
+
+impl BboMessage {
+    /// 编码到 `buf`
+    pub fn encode(&self, buf: &mut [u8; BBO_MESSAGE_LEN]) {
+        buf[0..2].copy_from_slice(&BBO_BLOCK_LENGTH.to_le_bytes());
+        buf[2..4].copy_from_slice(&BBO_TEMPLATE_ID.to_le_bytes());
+        buf[4..6].copy_from_slice(&BBO_SCHEMA_ID.to_le_bytes());
+        buf[6..8].copy_from_slice(&BBO_SCHEMA_VERSION.to_le_bytes());
+
+        let body = &mut buf[HEADER_LEN..];
+        let mut flags = 0;
+        if self.bid_price.is_some() {
+            flags |= FLAG_BID;
+        }
+        if self.ask_price.is_some() {
+            flags |= FLAG_ASK;
+        }
+        body[0..4].copy_from_slice(&self.symbol_id.to_le_bytes());
+        body[4] = flags;
+        body[5..8].fill(0);
+        body[8..16].copy_from_slice(&self.channel_seq.to_le_bytes());
+        body[16..24].copy_from_slice(&self.engine_seq.to_le_bytes());
+        body[24..32].copy_from_slice(&self.timestamp.to_le_bytes());
+        let bid = self.bid_price.map_or(0, |p| p.raw());
+        let ask = self.ask_price.map_or(0, |p| p.raw());
+        body[32..40].copy_from_slice(&bid.to_le_bytes());
+        body[40..48].copy_from_slice(&self.bid_qty.raw().to_le_bytes());
+        body[48..56].copy_from_slice(&ask.to_le_bytes());
+        body[56..64].copy_from_slice(&self.ask_qty.raw().to_le_bytes());
+    }
+
+    /// 从数据报解码（块长大于本版本时忽略多出的字段）
+    pub fn decode(buf: &[u8]) -> Result<Self, BboDecodeError> {
+        if buf.len() < HEADER_LEN {
+            return Err(BboDecodeError::Truncated(buf.len()));
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([buf[at], buf[at + 1]]);
+        let (block_length, template_id, schema_id) = (u16_at(0), u16_at(2), u16_at(4));
+        if schema_id != BBO_SCHEMA_ID || template_id != BBO_TEMPLATE_ID {
+            return Err(BboDecodeError::UnknownTemplate { schema_id, template_id });
+        }
+        if block_length < BBO_BLOCK_LENGTH || buf.len() < HEADER_LEN + block_length as usize {
+            return Err(BboDecodeError::Truncated(buf.len()));
+        }
+
+        let body = &buf[HEADER_LEN..];
+        let word = |at: usize| field::<8>(body, at);
+        let flags = body[4];
+        let bid_price = i64::from_le_bytes(word(32)?);
+        let ask_price = i64::from_le_bytes(word(48)?);
+        Ok(Self {
+            symbol_id: u32::from_le_bytes(field::<4>(body, 0)?),
+            channel_seq: u64::from_le_bytes(word(8)?),
+            engine_seq: u64::from_le_bytes(word(16)?),
+            timestamp: u64::from_le_bytes(word(24)?),
+            bid_price: (flags & FLAG_BID != 0).then(|| Price::from_raw(bid_price)),
+            bid_qty: Quantity::from_raw(i64::from_le_bytes(word(40)?)),
+            ask_price: (flags & FLAG_ASK != 0).then(|| Price::from_raw(ask_price)),
+            ask_qty: Quantity::from_raw(i64::from_le_bytes(word(56)?)),
+        })
+    }
+
+    /// 还原为最优买卖价变更事件
+    pub fn to_event(&self) -> BboChangeEvent {
+        BboChangeEvent {
+            symbol_id: self.symbol_id,
+            timestamp: self.timestamp,
+            sequence: self.engine_seq,
+            best_bid: self.bid_price,
+            best_bid_quantity: self.bid_qty,
+            best_ask: self.ask_price,
+            best_ask_quantity: self.ask_qty,
+        }
+    }
+}
+
+/// 定长块中 `at` 起的 `N` 字节
+fn field<const N: usize>(body: &[u8], at: usize) -> Result<[u8; N], BboDecodeError> {
+    body.get(at..at + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(BboDecodeError::Truncated(HEADER_LEN + body.len()))
+}
+
+/// 数据报发送端（组播 socket、共享内存队列等）
+pub trait DatagramSink {
+    fn send(&mut self, datagram: &[u8]) -> io::Result<()>;
+}
+
+/// UDP 组播发送端
+#[derive(Debug)]
+pub struct UdpMulticastSink {
+    socket: UdpSocket,
+    group: SocketAddr,
+}
+
+impl UdpMulticastSink {
+    /// 绑定 `bind` 并向组播组 `group` 发送，`ttl` 控制跨越的路由跳数
+    pub fn new(bind: SocketAddr, group: SocketAddr, ttl: u32) -> io::Result<Self> {
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_multicast_ttl_v4(ttl)?;
+        socket.set_multicast_loop_v4(false)?;
+        Ok(Self { socket, group })
+    }
+}
+
+impl DatagramSink for UdpMulticastSink {
+    fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+        self.socket.send_to(datagram, self.group).map(|_| ())
+    }
+}
+
+/// 每个交易对发布的最优挂单（用于去重）
+type Top = (Option<Price>, Quantity, Option<Price>, Quantity);
+
+/// BBO 快速通道
+///
+/// 只在最优挂单变化时发布；发送失败不阻塞行情主流程，计入 `send_errors`
+#[derive(Debug)]
+pub struct BboFastChannel<S: DatagramSink> {
+    sink: S,
+    channel_seq: u64,
+    last: HashMap<SymbolId, (u64, Top)>,
+    send_errors: u64,
+}
+
+impl<S: DatagramSink> BboFastChannel<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink, channel_seq: 0, last: HashMap::new(), send_errors: 0 }
+    }
+
+    /// 发布一次最优挂单变化；挂单未变或引擎序列号回退时不发布，返回是否发布
+    pub fn on_bbo(&mut self, event: &BboChangeEvent) -> bool {
+        let top =
+            (event.best_bid, event.best_bid_quantity, event.best_ask, event.best_ask_quantity);
+        if let Some(&(sequence, last)) = self.last.get(&event.symbol_id) {
+            if event.sequence < sequence || last == top {
+                return false;
+            }
+        }
+        self.last.insert(event.symbol_id, (event.sequence, top));
+
+        self.channel_seq += 1;
+        let message = BboMessage {
+            symbol_id: event.symbol_id,
+            channel_seq: self.channel_seq,
+            engine_seq: event.sequence,
+            timestamp: event.timestamp,
+            bid_price: event.best_bid,
+            bid_qty: event.best_bid_quantity,
+            ask_price: event.best_ask,
+            ask_qty: event.best_ask_quantity,
+        };
+        let mut buf = [0u8; BBO_MESSAGE_LEN];
+        message.encode(&mut buf);
+        if self.sink.send(&buf).is_err() {
+            self.send_errors += 1;
+        }
+        true
+    }
+
+    /// 发布一批增量：先发 BBO，再把其余增量按原顺序交给深度发布
+    pub fn publish_batch<F>(&mut self, deltas: &[MarketDataDelta], mut depth: F)
+    where
+        F: FnMut(&MarketDataDelta),
+    {
+        for delta in deltas {
+            if let MarketDataDelta::BboChange(event) = delta {
+                self.on_bbo(event);
+            }
+        }
+        deltas.iter().filter(|d| !matches!(d, MarketDataDelta::BboChange(_))).for_each(&mut depth);
+    }
+
+    /// 已发布的最后一个通道序列号
+    pub fn channel_seq(&self) -> u64 {
+        self.channel_seq
+    }
+
+    /// 发送失败次数
+    pub fn send_errors(&self) -> u64 {
+        self.send_errors
+    }
+}
+
+/// BBO 快速通道接收端
+///
+/// 按通道序列号去重（组播可能重复投递），序列号跳跃计为丢包；
+/// 丢包后不等待补发，下一条 BBO 即为最新状态
+#[derive(Debug, Default)]
+pub struct BboReceiver {
+    last_channel_seq: u64,
+    gaps: u64,
+}
+
+impl BboReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一个数据报，返回新的最优买卖价（重复或过期的消息返回 `None`）
+    pub fn on_datagram(
+        &mut self,
+        datagram: &[u8],
+    ) -> Result<Option<BboChangeEvent>, BboDecodeError> {
+        let message = BboMessage::decode(datagram)?;
+        if message.channel_seq <= self.last_channel_seq {
+            return Ok(None);
+        }
+        self.gaps += message.channel_seq - self.last_channel_seq - 1;
+        self.last_channel_seq = message.channel_seq;
+        Ok(Some(message.to_event()))
+    }
+
+    /// 最后处理的通道序列号
+    pub fn last_channel_seq(&self) -> u64 {
+        self.last_channel_seq
+    }
+
+    /// 累计丢失的消息数
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderSide;
+    use crate::mark_data::spot::level_types::TradeEvent;
+
+    #[derive(Default)]
+    struct Captured(Vec<Vec<u8>>);
+
+    impl DatagramSink for Captured {
+        fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+            self.0.push(datagram.to_vec());
+            Ok(())
+        }
+    }
+
+    fn bbo(sequence: u64, bid: Option<f64>, ask: f64) -> BboChangeEvent {
+        BboChangeEvent {
+            symbol_id: 7,
+            timestamp: sequence * 10,
+            sequence,
+            best_bid: bid.map(Price::from_f64),
+            best_bid_quantity: Quantity::from_f64(1.5),
+            best_ask: Some(Price::from_f64(ask)),
+            best_ask_quantity: Quantity::from_f64(2.0),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let mut channel = BboFastChannel::new(Captured::default());
+        assert!(channel.on_bbo(&bbo(5, None, 101.0)));
+        let datagram = &channel.sink.0[0];
+        assert_eq!(datagram.len(), BBO_MESSAGE_LEN);
+
+        let message = BboMessage::decode(datagram).unwrap();
+        assert_eq!((message.symbol_id, message.channel_seq, message.engine_seq), (7, 1, 5));
+        assert_eq!(message.bid_price, None);
+        assert_eq!(message.ask_price, Some(Price::from_f64(101.0)));
+        assert_eq!(message.bid_qty, Quantity::from_f64(1.5));
+
+        assert_eq!(BboMessage::decode(&datagram[..20]), Err(BboDecodeError::Truncated(20)));
+        let mut other = datagram.clone();
+        other[2] = 99;
+        assert!(matches!(BboMessage::decode(&other), Err(BboDecodeError::UnknownTemplate { .. })));
+    }
+
+    #[test]
+    fn test_bbo_published_ahead_of_depth() {
+        let mut channel = BboFastChannel::new(Captured::default());
+        let trade = MarketDataDelta::Trade(TradeEvent {
+            symbol_id: 7,
+            timestamp: 0,
+            sequence: 1,
+            trade_id: 1,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(100.0),
+            quantity: Quantity::from_f64(1.0),
+            aggressor_side: OrderSide::Buy,
+        });
+        let batch = [
+            trade,
+            MarketDataDelta::BboChange(bbo(1, Some(99.0), 101.0)),
+            MarketDataDelta::BboChange(bbo(2, Some(99.0), 101.0)),
+        ];
+        let mut depth = Vec::new();
+        channel.publish_batch(&batch, |delta| depth.push(sequence_of(delta)));
+
+        // 重复的最优挂单只发一次，深度流只收到非 BBO 增量
+        assert_eq!(channel.channel_seq(), 1);
+        assert_eq!(depth, vec![1]);
+        assert!(!channel.on_bbo(&bbo(0, Some(98.0), 101.0)));
+        assert!(channel.on_bbo(&bbo(3, Some(100.0), 101.0)));
+        assert_eq!(BboMessage::decode(&channel.sink.0[1]).unwrap().channel_seq, 2);
+        assert_eq!(channel.send_errors(), 0);
+    }
+
+    #[test]
+    fn test_receiver_skips_duplicates_and_counts_gaps() {
+        let mut channel = BboFastChannel::new(Captured::default());
+        for sequence in 1..=4 {
+            channel.on_bbo(&bbo(sequence, Some(99.0 + sequence as f64), 110.0));
+        }
+        let datagrams = &channel.sink.0;
+
+        let mut receiver = BboReceiver::new();
+        let first = receiver.on_datagram(&datagrams[0]).unwrap().unwrap();
+        assert_eq!((first.symbol_id, first.sequence), (7, 1));
+        assert_eq!(first.best_bid, Some(Price::from_f64(100.0)));
+        assert!(receiver.on_datagram(&datagrams[0]).unwrap().is_none());
+
+        let latest = receiver.on_datagram(&datagrams[3]).unwrap().unwrap();
+        assert_eq!(latest.sequence, 4);
+        assert!(receiver.on_datagram(&datagrams[2]).unwrap().is_none());
+        assert_eq!((receiver.last_channel_seq(), receiver.gaps()), (4, 2));
+        assert!(receiver.on_datagram(&datagrams[0][..12]).is_err());
+    }
+
+    fn sequence_of(delta: &MarketDataDelta) -> u64 {
+        match delta {
+            MarketDataDelta::Trade(trade) => trade.sequence,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! K线（Candlestick）
+//!
+//! 由成交事件聚合各周期 OHLCV，实时行情与历史回填共用同一聚合逻辑。
+//! 同一交易对的成交须按时间顺序到达
+//!
+//! K线文件为带表头的 CSV，价格与数量写原始整数（8 位小数定点），
+//! 成交额写 `Decimal128` 原始整数（18 位小数定点），保证导出再加载无损
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+
+use decimal::Decimal128;
+
+use super::level_types::{SymbolId, TradeEvent};
+use crate::{Price, Quantity};
+
+/// 一分钟的纳秒数
+const MINUTE_NS: u64 = 60_000_000_000;
+
+/// K线文件表头
+const CSV_HEADER: &str =
+    "symbol_id,interval,open_time,open,high,low,close,volume,quote_volume,trade_count";
+
+/// K线周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CandleInterval {
+    Minute1,
+    Minute5,
+    Minute15,
+    Hour1,
+    Hour4,
+    Day1,
+}
+
+impl CandleInterval {
+    /// 全部周期
+    pub const ALL: [CandleInterval; 6] = [
+        CandleInterval::Minute1,
+        CandleInterval::Minute5,
+        CandleInterval::Minute15,
+        CandleInterval::Hour1,
+        CandleInterval::Hour4,
+        CandleInterval::Day1,
+    ];
+
+    /// 周期长度（纳秒）
+    pub const fn nanos(self) -> u64 {
+        match self {
+            CandleInterval::Minute1 => MINUTE_NS,
+            CandleInterval::Minute5 => 5 * MINUTE_NS,
+            CandleInterval::Minute15 => 15 * MINUTE_NS,
+            CandleInterval::Hour1 => 60 * MINUTE_NS,
+            CandleInterval::Hour4 => 240 * MINUTE_NS,
+            CandleInterval::Day1 => 1440 * MINUTE_NS,
+        }
+    }
+
+    /// 时间戳所在K线的开盘时间（按 Unix 纪元对齐，日线为 UTC 零点）
+    pub const fn open_time(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.nanos()
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            CandleInterval::Minute1 => "1m",
+            CandleInterval::Minute5 => "5m",
+            CandleInterval::Minute15 => "15m",
+            CandleInterval::Hour1 => "1h",
+            CandleInterval::Hour4 => "4h",
+            CandleInterval::Day1 => "1d",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|interval| interval.as_str() == s)
+    }
+}
+
+/// K线
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candle {
+    /// 交易对ID
+    pub symbol_id: SymbolId,
+    /// 周期
+    pub interval: CandleInterval,
+    /// 开盘时间（纳秒）
+    pub open_time: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// 成交量（基础资产）
+    pub volume: Quantity,
+    /// 成交额（计价资产）
+    pub quote_volume: Decimal128,
+    /// 成交笔数
+    pub trade_count: u64,
+}
+
+impl Candle {
+    fn open_with(
+        symbol_id: SymbolId,
+        interval: CandleInterval,
+        open_time: u64,
+        price: Price,
+    ) -> Self {
+        Self {
+            symbol_id,
+            interval,
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Quantity::default(),
+            quote_volume: Decimal128::ZERO,
+            trade_count: 0,
+        }
+    }
+
+    fn apply(&mut self, price: Price, quantity: Quantity, quote_volume: Decimal128) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += quantity;
+        self.quote_volume += quote_volume;
+        self.trade_count += 1;
+    }
+
+    /// 收盘时间（不含）
+    pub fn close_time(&self) -> u64 {
+        self.open_time + self.interval.nanos()
+    }
+}
+
+/// K线存储
+#[derive(Debug)]
+pub struct CandleStore {
+    /// 聚合的周期
+    intervals: Vec<CandleInterval>,
+    /// 每个序列保留的最大K线数（0=不限）
+    retention: usize,
+    /// (交易对, 周期) -> 开盘时间 -> K线
+    series: HashMap<(SymbolId, CandleInterval), BTreeMap<u64, Candle>>,
+}
+
+impl CandleStore {
+    /// 聚合指定周期，不限保留数量
+    pub fn new(intervals: &[CandleInterval]) -> Self {
+        Self { intervals: intervals.to_vec(), retention: 0, series: HashMap::new() }
+    }
+
+    /// 每个序列最多保留 `retention` 根K线，超出淘汰最早的
+    pub fn with_retention(mut self, retention: usize) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// 聚合的周期
+    pub fn intervals(&self) -> &[CandleInterval] {
+        &self.intervals
+    }
+
+    /// 处理一笔成交
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        let quote_volume = trade.price.wide_notional(trade.quantity).unwrap_or(Decimal128::MAX);
+        for &interval in &self.intervals {
+            let open_time = interval.open_time(trade.timestamp);
+            let series = self.series.entry((trade.symbol_id, interval)).or_default();
+            series
+                .entry(open_time)
+                .or_insert_with(|| {
+                    Candle::open_with(trade.symbol_id, interval, open_time, trade.price)
+                })
+                .apply(trade.price, trade.quantity, quote_volume);
+            Self::evict(series, self.retention);
+        }
+    }
+
+    /// 写入整根K线（加载历史，覆盖同一开盘时间的旧值）
+    pub fn insert(&mut self, candle: Candle) {
+        let series = self.series.entry((candle.symbol_id, candle.interval)).or_default();
+        series.insert(candle.open_time, candle);
+        Self::evict(series, self.retention);
+    }
+
+    /// 开盘时间在 `[from, to]` 内的K线（按时间升序）
+    pub fn candles(
+        &self,
+        symbol_id: SymbolId,
+        interval: CandleInterval,
+        from: u64,
+        to: u64,
+    ) -> impl Iterator<Item = &Candle> {
+        self.series
+            .get(&(symbol_id, interval))
+            .into_iter()
+            .flat_map(move |series| series.range(from..=to.max(from)).map(|(_, candle)| candle))
+    }
+
+    /// 最新一根K线
+    pub fn latest(&self, symbol_id: SymbolId, interval: CandleInterval) -> Option<&Candle> {
+        self.series.get(&(symbol_id, interval))?.values().next_back()
+    }
+
+    /// K线总数
+    pub fn len(&self) -> usize {
+        self.series.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 有K线的交易对（升序去重）
+    pub fn symbol_ids(&self) -> Vec<SymbolId> {
+        let mut symbol_ids: Vec<_> = self.series.keys().map(|&(symbol_id, _)| symbol_id).collect();
+        symbol_ids.sort_unstable();
+        symbol_ids.dedup();
+        symbol_ids
+    }
+
+    /// 导出为 CSV（按交易对、周期、时间排序），返回K线数
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<usize> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        let mut keys: Vec<_> = self.series.keys().copied().collect();
+        keys.sort();
+        let mut count = 0;
+        for key in keys {
+            for c in self.series[&key].values() {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{},{},{}",
+                    c.symbol_id,
+                    c.interval.as_str(),
+                    c.open_time,
+                    c.open.raw(),
+                    c.high.raw(),
+                    c.low.raw(),
+                    c.close.raw(),
+                    c.volume.raw(),
+                    c.quote_volume.raw(),
+                    c.trade_count
+                )?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// 加载 `write_csv` 导出的文件，返回K线数
+    pub fn load_csv<R: BufRead>(&mut self, reader: R) -> io::Result<usize> {
+        let invalid = |line: usize| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid candle at line {}", line))
+        };
+        let mut count = 0;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if index == 0 || line.trim().is_empty() {
+                continue;
+            }
+            let candle = parse_candle(&line).ok_or_else(|| invalid(index + 1))?;
+            self.insert(candle);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn evict(series: &mut BTreeMap<u64, Candle>, retention: usize) {
+        while retention > 0 && series.len() > retention {
+            series.pop_first();
+        }
+    }
+}
+
+fn parse_candle(line: &str) -> Option<Candle> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [symbol_id, interval, open_time, open, high, low, close, volume, quote_volume, trade_count] =
+        fields.as_slice()
+    else {
+        return None;
+    };
+    let price = |s: &str| s.parse().ok().map(Price::from_raw);
+    Some(Candle {
+        symbol_id: symbol_id.parse().ok()?,
+        interval: CandleInterval::parse(interval)?,
+        open_time: open_time.parse().ok()?,
+        open: price(open)?,
+        high: price(high)?,
+        low: price(low)?,
+        close: price(close)?,
+        volume: Quantity::from_raw(volume.parse().ok()?),
+        quote_volume: Decimal128::from_raw(quote_volume.parse().ok()?),
+        trade_count: trade_count.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderSide;
+
+    fn trade(timestamp: u64, price: f64, quantity: f64) -> TradeEvent {
+        TradeEvent {
+            symbol_id: 1,
+            timestamp,
+            sequence: 0,
+            trade_id: 0,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(price),
+            quantity: Quantity::from_f64(quantity),
+            aggressor_side: OrderSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_candle_aggregation_and_csv_roundtrip() {
+        let mut store = CandleStore::new(&[CandleInterval::Minute1, CandleInterval::Minute5]);
+        store.on_trade(&trade(10, 100.0, 1.0));
+        store.on_trade(&trade(20, 105.0, 2.0));
+        store.on_trade(&trade(30, 95.0, 1.0));
+        store.on_trade(&trade(MINUTE_NS + 1, 101.0, 0.5));
+
+        let first = store.candles(1, CandleInterval::Minute1, 0, 0).next().unwrap();
+        assert_eq!((first.open, first.high), (Price::from_f64(100.0), Price::from_f64(105.0)));
+        assert_eq!((first.low, first.close), (Price::from_f64(95.0), Price::from_f64(95.0)));
+        assert_eq!(first.volume, Quantity::from_f64(4.0));
+        assert_eq!(first.quote_volume, Decimal128::from_int(405));
+        assert_eq!(first.trade_count, 3);
+        assert_eq!(store.candles(1, CandleInterval::Minute1, 0, u64::MAX).count(), 2);
+
+        let five = store.latest(1, CandleInterval::Minute5).unwrap();
+        assert_eq!((five.trade_count, five.close), (4, Price::from_f64(101.0)));
+        assert_eq!(five.close_time(), 5 * MINUTE_NS);
+
+        let mut file = Vec::new();
+        assert_eq!(store.write_csv(&mut file).unwrap(), 3);
+        let mut loaded = CandleStore::new(&[CandleInterval::Minute1]).with_retention(1);
+        assert_eq!(loaded.load_csv(file.as_slice()).unwrap(), 3);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.symbol_ids(), [1]);
+        assert_eq!(loaded.latest(1, CandleInterval::Minute5), Some(five));
+        assert!(loaded.load_csv("header\n1,2m,0,0,0,0,0,0,0,0".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! 订单簿热力图（Book Heatmap）
+//!
+//! 由 Level 3 增量事件派生：增量维护每个价位的挂单量，按固定节奏采样，
+//! 输出各价位挂单量的时间序列，供前端绘制盘口压力热力图
+//!
+//! 采样时刻取桶边界之前最后一个状态；两次增量之间跨越多个桶时只输出
+//! 最近一个桶，前端按前值填充即可
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::level_types::{MarketDataDelta, OrderChangeType, OrderDelta, PriceLevel, SymbolId};
+use crate::{OrderId, OrderSide, Price, Quantity};
+
+/// 热力图配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeatmapConfig {
+    /// 交易对ID
+    pub symbol_id: SymbolId,
+    /// 采样间隔（纳秒）
+    pub interval_ns: u64,
+    /// 每侧输出的最大档位数
+    pub max_levels: usize,
+}
+
+impl HeatmapConfig {
+    /// 创建配置
+    #[inline]
+    pub fn new(symbol_id: SymbolId, interval_ns: u64, max_levels: usize) -> Self {
+        Self { symbol_id, interval_ns: interval_ns.max(1), max_levels }
+    }
+}
+
+/// 热力图采样帧
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapFrame {
+    /// 交易对ID
+    pub symbol_id: SymbolId,
+    /// 采样桶起始时间（纳秒）
+    pub bucket_start: u64,
+    /// 采样时最后处理的序列号
+    pub sequence: u64,
+    /// 买方档位（从高到低）
+    pub bids: Vec<PriceLevel>,
+    /// 卖方档位（从低到高）
+    pub asks: Vec<PriceLevel>,
+}
+
+/// 热力图聚合器
+#[derive(Debug)]
+pub struct HeatmapAggregator {
+    /// 配置
+    config: HeatmapConfig,
+    /// 挂单：订单ID -> (方向, 价格, 剩余数量)
+    orders: HashMap<OrderId, (OrderSide, Price, Quantity)>,
+    /// 买方价位聚合
+    bids: BTreeMap<Price, PriceLevel>,
+    /// 卖方价位聚合
+    asks: BTreeMap<Price, PriceLevel>,
+    /// 当前采样桶起始时间（None=尚未收到事件）
+    bucket_start: Option<u64>,
+    /// 最后处理的序列号
+    sequence: u64,
+}
+
+impl HeatmapAggregator {
+    /// 创建聚合器
+    #[inline]
+    pub fn new(config: HeatmapConfig) -> Self {
+        Self {
+            config,
+            orders: HashMap::new(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            bucket_start: None,
+            sequence: 0,
+        }
+    }
+
+    /// 处理一条增量事件，跨越采样边界时返回上一个桶的采样帧
+    pub fn on_delta(&mut self, delta: &MarketDataDelta) -> Option<HeatmapFrame> {
+        let MarketDataDelta::OrderChange(change) = delta else {
+            // 成交导致的挂单量变化已体现在 Modify/Delete 事件中
+            return None;
+        };
+        let frame = self.poll(change.timestamp);
+        self.apply(change);
+        frame
+    }
+
+    /// 按时间推进，跨越采样边界时返回上一个桶的采样帧
+    ///
+    /// 无增量时由定时器调用，保证节奏稳定
+    pub fn poll(&mut self, now: u64) -> Option<HeatmapFrame> {
+        let interval = self.config.interval_ns;
+        let current = now - now % interval;
+        match self.bucket_start {
+            None => {
+                self.bucket_start = Some(current);
+                None
+            }
+            Some(start) if current > start => {
+                let frame = self.snapshot(start);
+                self.bucket_start = Some(current);
+                Some(frame)
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// 当前状态的采样帧
+    pub fn snapshot(&self, bucket_start: u64) -> HeatmapFrame {
+        let max_levels = self.config.max_levels;
+        HeatmapFrame {
+            symbol_id: self.config.symbol_id,
+            bucket_start,
+            sequence: self.sequence,
+            bids: self.bids.values().rev().take(max_levels).copied().collect(),
+            asks: self.asks.values().take(max_levels).copied().collect(),
+        }
+    }
+
+    fn apply(&mut self, change: &OrderDelta) {
+        if change.symbol_id != self.config.symbol_id {
+            return;
+        }
+        self.sequence = change.sequence;
+
+        // Modify 的数量为新的剩余数量
+        if let Some((side, price, quantity)) = self.orders.remove(&change.order_id) {
+            self.adjust(side, price, quantity, false);
+        }
+        if change.change_type != OrderChangeType::Delete && change.quantity > Quantity::default() {
+            self.orders.insert(change.order_id, (change.side, change.price, change.quantity));
+            self.adjust(change.side, change.price, change.quantity, true);
+        }
+    }
+
+    fn adjust(&mut self, side: OrderSide, price: Price, quantity: Quantity, add: bool) {
+        let book = match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let level =
+            book.entry(price).or_insert_with(|| PriceLevel::new(price, Quantity::default(), 0));
+        if add {
+            level.quantity += quantity;
+            level.order_count += 1;
+        } else {
+            level.quantity -= quantity;
+            level.order_count = level.order_count.saturating_sub(1);
+        }
+        if level.order_count == 0 {
+            book.remove(&price);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(
+        timestamp: u64,
+        sequence: u64,
+        change_type: OrderChangeType,
+        order_id: OrderId,
+        side: OrderSide,
+        price: i64,
+        quantity: i64,
+    ) -> MarketDataDelta {
+        MarketDataDelta::OrderChange(OrderDelta {
+            symbol_id: 1,
+            timestamp,
+            sequence,
+            change_type,
+            order_id,
+            side,
+            price: Price::from_raw(price),
+            quantity: Quantity::from_raw(quantity),
+            trader_id: None,
+        })
+    }
+
+    #[test]
+    fn test_heatmap_sampling() {
+        let mut heatmap = HeatmapAggregator::new(HeatmapConfig::new(1, 100, 2));
+
+        assert!(
+            heatmap
+                .on_delta(&delta(10, 1, OrderChangeType::Add, 1, OrderSide::Buy, 99, 5))
+                .is_none()
+        );
+        heatmap.on_delta(&delta(20, 2, OrderChangeType::Add, 2, OrderSide::Buy, 99, 3));
+        heatmap.on_delta(&delta(30, 3, OrderChangeType::Add, 3, OrderSide::Buy, 98, 7));
+        heatmap.on_delta(&delta(40, 4, OrderChangeType::Add, 4, OrderSide::Buy, 97, 1));
+        heatmap.on_delta(&delta(50, 5, OrderChangeType::Add, 5, OrderSide::Sell, 101, 4));
+
+        // 跨越边界：输出 [0, 100) 桶，且事件本身计入下一个桶
+        let frame = heatmap
+            .on_delta(&delta(120, 6, OrderChangeType::Modify, 1, OrderSide::Buy, 99, 2))
+            .unwrap();
+        assert_eq!(frame.bucket_start, 0);
+        assert_eq!(frame.sequence, 5);
+        assert_eq!(frame.bids.len(), 2);
+        assert_eq!(frame.bids[0], PriceLevel::new(Price::from_raw(99), Quantity::from_raw(8), 2));
+        assert_eq!(frame.bids[1].price, Price::from_raw(98));
+        assert_eq!(
+            frame.asks,
+            vec![PriceLevel::new(Price::from_raw(101), Quantity::from_raw(4), 1)]
+        );
+
+        heatmap.on_delta(&delta(130, 7, OrderChangeType::Delete, 2, OrderSide::Buy, 99, 0));
+        heatmap.on_delta(&delta(140, 8, OrderChangeType::Delete, 5, OrderSide::Sell, 101, 0));
+
+        // 定时器推进
+        let frame = heatmap.poll(250).unwrap();
+        assert_eq!(frame.bucket_start, 100);
+        assert_eq!(frame.bids[0], PriceLevel::new(Price::from_raw(99), Quantity::from_raw(2), 1));
+        assert!(frame.asks.is_empty());
+        assert!(heatmap.poll(299).is_none());
+    }
+}
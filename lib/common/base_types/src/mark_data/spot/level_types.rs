@@ -389,7 +389,7 @@ pub struct OrderDelta {
 }
 
 /// 成交事件
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TradeEvent {
     /// 交易对ID
     pub symbol_id: SymbolId,
@@ -411,6 +411,14 @@ pub struct TradeEvent {
     pub aggressor_side: OrderSide,
 }
 
+impl TradeEvent {
+    /// 买方是否为 Maker（卖方主动成交）
+    #[inline]
+    pub fn is_buyer_maker(&self) -> bool {
+        self.aggressor_side == OrderSide::Sell
+    }
+}
+
 /// 最优买卖价变更事件
 #[derive(Debug, Clone, Copy)]
 pub struct BboChangeEvent {
@@ -1 +1,11 @@
+pub mod backfill;
+pub mod bbo_channel;
+pub mod candle;
+pub mod heatmap;
 pub mod level_types;
+pub mod order_channel;
+pub mod recent_trades;
+pub mod rolling_volume;
+pub mod synthetic;
+pub mod ticker;
+pub mod trade_channel;
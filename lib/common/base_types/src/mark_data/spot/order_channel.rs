@@ -0,0 +1,308 @@
+//! 逐笔委托组播通道（Level 3）
+//!
+//! 与 [`BboFastChannel`](super::bbo_channel::BboFastChannel) 共用组播组与帧格式
+//! （SBE 标准 8 字节消息头 + 定长块，小端，一个数据报一条消息），逐条发布订单簿增量：
+//! - 不携带交易者ID，公开行情只暴露订单ID、方向、价格与剩余数量
+//! - 通道序列号逐条加一，接收方据此发现丢包；丢包后派生的盘口（如热力图）需等快照重建
+//!
+//! 定长块布局（56 字节）：
+//!
+//! ```text
+//! 0  symbol_id u32 | 4 change_type u8（0 新增 / 1 修改 / 2 删除）| 5 side u8（0 买 / 1 卖）| 6 填充 2
+//! 8  channel_seq u64 | 16 engine_seq u64 | 24 timestamp u64（纳秒）
+//! 32 order_id u64 | 40 price i64 | 48 qty i64
+//! ```
+
+use super::bbo_channel::{BBO_SCHEMA_ID, BBO_SCHEMA_VERSION, DatagramSink};
+use super::level_types::{MarketDataDelta, OrderChangeType, OrderDelta};
+use crate::{OrderSide, Price, Quantity};
+
+/// 逐笔委托消息模板ID
+pub const ORDER_TEMPLATE_ID: u16 = 22;
+/// SBE 消息头长度
+const HEADER_LEN: usize = 8;
+/// 定长块长度
+pub const ORDER_BLOCK_LENGTH: u16 = 56;
+/// 一条消息的总长度
+pub const ORDER_MESSAGE_LEN: usize = HEADER_LEN + ORDER_BLOCK_LENGTH as usize;
+
+/// 逐笔委托消息
+#[derive(Debug, Clone, Copy)]
+pub struct OrderMessage {
+    /// 通道序列号（从 1 开始，逐条加一）
+    pub channel_seq: u64,
+    /// 订单簿增量（解码后 `trader_id` 总为 None）
+    pub delta: OrderDelta,
+}
+
+/// 解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderDecodeError {
+    /// 长度不足
+    Truncated(usize),
+    /// 模式或模板不匹配
+    UnknownTemplate { schema_id: u16, template_id: u16 },
+    /// 变更类型取值非法
+    InvalidChangeType(u8),
+    /// 方向取值非法
+    InvalidSide(u8),
+}
+
+impl std::fmt::Display for OrderDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderDecodeError::Truncated(len) => {
+                write!(f, "Order message truncated: {} bytes", len)
+            }
+            OrderDecodeError::UnknownTemplate { schema_id, template_id } => {
+                write!(f, "Unknown template: schema {} template {}", schema_id, template_id)
+            }
+            OrderDecodeError::InvalidChangeType(change_type) => {
+                write!(f, "Invalid change type: {}", change_type)
+            }
+            OrderDecodeError::InvalidSide(side) => write!(f, "Invalid side: {}", side),
+        }
+    }
+}
+
+impl std::error::Error for OrderDecodeError {}
+
+impl OrderMessage {
+    /// 编码到 `buf`
+    pub fn encode(&self, buf: &mut [u8; ORDER_MESSAGE_LEN]) {
+        buf[0..2].copy_from_slice(&ORDER_BLOCK_LENGTH.to_le_bytes());
+        buf[2..4].copy_from_slice(&ORDER_TEMPLATE_ID.to_le_bytes());
+        buf[4..6].copy_from_slice(&BBO_SCHEMA_ID.to_le_bytes());
+        buf[6..8].copy_from_slice(&BBO_SCHEMA_VERSION.to_le_bytes());
+
+        let delta = &self.delta;
+        let body = &mut buf[HEADER_LEN..];
+        body[0..4].copy_from_slice(&delta.symbol_id.to_le_bytes());
+        body[4] = match delta.change_type {
+            OrderChangeType::Add => 0,
+            OrderChangeType::Modify => 1,
+            OrderChangeType::Delete => 2,
+        };
+        body[5] = delta.side as u8;
+        body[6..8].fill(0);
+        body[8..16].copy_from_slice(&self.channel_seq.to_le_bytes());
+        body[16..24].copy_from_slice(&delta.sequence.to_le_bytes());
+        body[24..32].copy_from_slice(&delta.timestamp.to_le_bytes());
+        body[32..40].copy_from_slice(&delta.order_id.to_le_bytes());
+        body[40..48].copy_from_slice(&delta.price.raw().to_le_bytes());
+        body[48..56].copy_from_slice(&delta.quantity.raw().to_le_bytes());
+    }
+
+    /// 从数据报解码（块长大于本版本时忽略多出的字段）
+    pub fn decode(buf: &[u8]) -> Result<Self, OrderDecodeError> {
+        if buf.len() < HEADER_LEN {
+            return Err(OrderDecodeError::Truncated(buf.len()));
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([buf[at], buf[at + 1]]);
+        let (block_length, template_id, schema_id) = (u16_at(0), u16_at(2), u16_at(4));
+        if schema_id != BBO_SCHEMA_ID || template_id != ORDER_TEMPLATE_ID {
+            return Err(OrderDecodeError::UnknownTemplate { schema_id, template_id });
+        }
+        if block_length < ORDER_BLOCK_LENGTH || buf.len() < HEADER_LEN + block_length as usize {
+            return Err(OrderDecodeError::Truncated(buf.len()));
+        }
+
+        let body = &buf[HEADER_LEN..];
+        let word = |at: usize| field::<8>(body, at);
+        let change_type = match body[4] {
+            0 => OrderChangeType::Add,
+            1 => OrderChangeType::Modify,
+            2 => OrderChangeType::Delete,
+            change_type => return Err(OrderDecodeError::InvalidChangeType(change_type)),
+        };
+        let side = match body[5] {
+            0 => OrderSide::Buy,
+            1 => OrderSide::Sell,
+            side => return Err(OrderDecodeError::InvalidSide(side)),
+        };
+        Ok(Self {
+            channel_seq: u64::from_le_bytes(word(8)?),
+            delta: OrderDelta {
+                symbol_id: u32::from_le_bytes(field::<4>(body, 0)?),
+                timestamp: u64::from_le_bytes(word(24)?),
+                sequence: u64::from_le_bytes(word(16)?),
+                change_type,
+                order_id: u64::from_le_bytes(word(32)?),
+                side,
+                price: Price::from_raw(i64::from_le_bytes(word(40)?)),
+                quantity: Quantity::from_raw(i64::from_le_bytes(word(48)?)),
+                trader_id: None,
+            },
+        })
+    }
+}
+
+/// 定长块中 `at` 起的 `N` 字节
+fn field<const N: usize>(body: &[u8], at: usize) -> Result<[u8; N], OrderDecodeError> {
+    body.get(at..at + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(OrderDecodeError::Truncated(HEADER_LEN + body.len()))
+}
+
+/// 逐笔委托组播发布端
+///
+/// 发送失败不阻塞行情主流程，计入 `send_errors`
+#[derive(Debug)]
+pub struct OrderChannel<S: DatagramSink> {
+    sink: S,
+    channel_seq: u64,
+    send_errors: u64,
+}
+
+impl<S: DatagramSink> OrderChannel<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink, channel_seq: 0, send_errors: 0 }
+    }
+
+    /// 发布一条订单簿增量
+    pub fn on_order_change(&mut self, delta: &OrderDelta) {
+        self.channel_seq += 1;
+        let mut buf = [0u8; ORDER_MESSAGE_LEN];
+        OrderMessage { channel_seq: self.channel_seq, delta: *delta }.encode(&mut buf);
+        if self.sink.send(&buf).is_err() {
+            self.send_errors += 1;
+        }
+    }
+
+    /// 发布一批增量中的全部订单簿变更（按原顺序）
+    pub fn publish_batch(&mut self, deltas: &[MarketDataDelta]) {
+        for delta in deltas {
+            if let MarketDataDelta::OrderChange(change) = delta {
+                self.on_order_change(change);
+            }
+        }
+    }
+
+    /// 已发布的最后一个通道序列号
+    pub fn channel_seq(&self) -> u64 {
+        self.channel_seq
+    }
+
+    /// 发送失败次数
+    pub fn send_errors(&self) -> u64 {
+        self.send_errors
+    }
+}
+
+/// 逐笔委托组播接收端
+///
+/// 按通道序列号去重（组播可能重复投递），序列号跳跃计为丢包
+#[derive(Debug, Default)]
+pub struct OrderReceiver {
+    last_channel_seq: u64,
+    gaps: u64,
+}
+
+impl OrderReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一个数据报，返回新增量（重复或过期的消息返回 `None`）
+    pub fn on_datagram(&mut self, datagram: &[u8]) -> Result<Option<OrderDelta>, OrderDecodeError> {
+        let message = OrderMessage::decode(datagram)?;
+        if message.channel_seq <= self.last_channel_seq {
+            return Ok(None);
+        }
+        self.gaps += message.channel_seq - self.last_channel_seq - 1;
+        self.last_channel_seq = message.channel_seq;
+        Ok(Some(message.delta))
+    }
+
+    /// 最后处理的通道序列号
+    pub fn last_channel_seq(&self) -> u64 {
+        self.last_channel_seq
+    }
+
+    /// 累计丢失的消息数
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::mark_data::spot::trade_channel::TradeMessage;
+
+    #[derive(Default)]
+    struct Captured(Vec<Vec<u8>>);
+
+    impl DatagramSink for Captured {
+        fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+            self.0.push(datagram.to_vec());
+            Ok(())
+        }
+    }
+
+    fn delta(order_id: u64, change_type: OrderChangeType) -> OrderDelta {
+        OrderDelta {
+            symbol_id: 7,
+            timestamp: order_id * 10,
+            sequence: order_id + 100,
+            change_type,
+            order_id,
+            side: OrderSide::Sell,
+            price: Price::from_f64(100.5),
+            quantity: Quantity::from_f64(0.25),
+            trader_id: None,
+        }
+    }
+
+    fn order_id(delta: Option<OrderDelta>) -> Option<u64> {
+        delta.map(|delta| delta.order_id)
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let mut channel = OrderChannel::new(Captured::default());
+        channel.publish_batch(&[MarketDataDelta::OrderChange(delta(1, OrderChangeType::Modify))]);
+        let datagram = &channel.sink.0[0];
+        assert_eq!(datagram.len(), ORDER_MESSAGE_LEN);
+
+        let message = OrderMessage::decode(datagram).unwrap();
+        assert_eq!(message.channel_seq, 1);
+        let decoded = message.delta;
+        assert_eq!((decoded.order_id, decoded.sequence, decoded.timestamp), (1, 101, 10));
+        assert_eq!((decoded.change_type, decoded.side), (OrderChangeType::Modify, OrderSide::Sell));
+        assert_eq!(
+            (decoded.price, decoded.quantity),
+            (Price::from_f64(100.5), Quantity::from_f64(0.25))
+        );
+        assert_eq!(
+            OrderMessage::decode(&datagram[..20]).unwrap_err(),
+            OrderDecodeError::Truncated(20)
+        );
+        let mut other = datagram.clone();
+        other[12] = 9;
+        assert_eq!(
+            OrderMessage::decode(&other).unwrap_err(),
+            OrderDecodeError::InvalidChangeType(9)
+        );
+        // 同一组播组上的委托帧不是成交
+        assert!(TradeMessage::decode(datagram).is_err());
+    }
+
+    #[test]
+    fn test_receiver_skips_duplicates_and_counts_gaps() {
+        let mut channel = OrderChannel::new(Captured::default());
+        for id in 1..=4 {
+            channel.on_order_change(&delta(id, OrderChangeType::Add));
+        }
+        let datagrams = &channel.sink.0;
+
+        let mut receiver = OrderReceiver::new();
+        assert_eq!(order_id(receiver.on_datagram(&datagrams[0]).unwrap()), Some(1));
+        assert!(receiver.on_datagram(&datagrams[0]).unwrap().is_none());
+        assert_eq!(order_id(receiver.on_datagram(&datagrams[3]).unwrap()), Some(4));
+        assert!(receiver.on_datagram(&datagrams[2]).unwrap().is_none());
+        assert_eq!((receiver.last_channel_seq(), receiver.gaps()), (4, 2));
+    }
+}
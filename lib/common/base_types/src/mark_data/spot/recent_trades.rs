@@ -0,0 +1,273 @@
+//! 最近成交
+//!
+//! 行情管道为每个交易对维护一个固定容量的成交环形缓冲，`/api/spot/trades` 直接读内存，
+//! 不查数据库。`historicalTrades` 按成交ID翻页：起始ID仍在环内时读环，
+//! 否则回落到成交仓储
+
+use std::collections::{HashMap, VecDeque};
+
+use decimal::Decimal128;
+
+use super::level_types::{SymbolId, TradeEvent};
+use crate::{Price, Quantity};
+
+/// 每个交易对默认保留的成交数
+pub const DEFAULT_RECENT_TRADES: usize = 1000;
+/// 单次查询默认条数
+pub const DEFAULT_TRADES_LIMIT: usize = 500;
+/// 单次查询最大条数
+pub const MAX_TRADES_LIMIT: usize = 1000;
+
+/// 查询条数：未指定取默认值，超出范围截断到 `[1, MAX_TRADES_LIMIT]`
+pub fn clamp_trades_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_TRADES_LIMIT).clamp(1, MAX_TRADES_LIMIT)
+}
+
+/// 公开成交（对外响应）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PublicTrade {
+    /// 成交ID
+    pub id: u64,
+    pub price: Price,
+    pub qty: Quantity,
+    /// 成交额
+    pub quote_qty: Decimal128,
+    /// 成交时间（Unix 毫秒）
+    pub time: u64,
+    pub is_buyer_maker: bool,
+}
+
+impl From<&TradeEvent> for PublicTrade {
+    fn from(trade: &TradeEvent) -> Self {
+        Self {
+            id: trade.trade_id,
+            price: trade.price,
+            qty: trade.quantity,
+            quote_qty: trade.price.wide_notional(trade.quantity).unwrap_or(Decimal128::MAX),
+            time: trade.timestamp / 1_000_000,
+            is_buyer_maker: trade.is_buyer_maker(),
+        }
+    }
+}
+
+/// 最近成交环形缓冲（按交易对）
+///
+/// 同一交易对的成交ID须递增
+#[derive(Debug)]
+pub struct RecentTrades {
+    capacity: usize,
+    rings: HashMap<SymbolId, VecDeque<TradeEvent>>,
+}
+
+impl Default for RecentTrades {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECENT_TRADES)
+    }
+}
+
+impl RecentTrades {
+    /// 每个交易对最多保留 `capacity` 笔
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), rings: HashMap::new() }
+    }
+
+    /// 处理一笔成交，超出容量淘汰最早的
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        let ring = self.rings.entry(trade.symbol_id).or_default();
+        if ring.len() == self.capacity {
+            ring.pop_front();
+        }
+        ring.push_back(*trade);
+    }
+
+    /// 最近 `limit` 笔（按成交ID升序）
+    pub fn recent(&self, symbol_id: SymbolId, limit: usize) -> Vec<TradeEvent> {
+        let Some(ring) = self.rings.get(&symbol_id) else {
+            return Vec::new();
+        };
+        ring.iter().skip(ring.len().saturating_sub(limit)).copied().collect()
+    }
+
+    /// 从 `from_id` 起（含）最多 `limit` 笔；`from_id` 早于环内最早成交时返回 None
+    pub fn from_id(
+        &self,
+        symbol_id: SymbolId,
+        from_id: u64,
+        limit: usize,
+    ) -> Option<Vec<TradeEvent>> {
+        let ring = self.rings.get(&symbol_id)?;
+        if ring.front()?.trade_id > from_id {
+            return None;
+        }
+        let start = ring.partition_point(|trade| trade.trade_id < from_id);
+        Some(ring.range(start..).take(limit).copied().collect())
+    }
+
+    /// 环内最早的成交ID
+    pub fn oldest_id(&self, symbol_id: SymbolId) -> Option<u64> {
+        Some(self.rings.get(&symbol_id)?.front()?.trade_id)
+    }
+}
+
+/// 成交仓储查询错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TradeQueryError {
+    /// 仓储不可用
+    Unavailable(String),
+}
+
+impl std::fmt::Display for TradeQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeQueryError::Unavailable(reason) => {
+                write!(f, "Trade history unavailable: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TradeQueryError {}
+
+/// 成交历史仓储
+pub trait TradeHistoryRepo {
+    /// 从 `from_id` 起（含）最多 `limit` 笔（按成交ID升序）
+    fn trades_from(
+        &self,
+        symbol_id: SymbolId,
+        from_id: u64,
+        limit: usize,
+    ) -> Result<Vec<TradeEvent>, TradeQueryError>;
+}
+
+/// 内存成交仓储（按交易对、成交ID升序追加）
+#[derive(Debug, Default)]
+pub struct MemTradeHistory {
+    trades: HashMap<SymbolId, Vec<TradeEvent>>,
+}
+
+impl MemTradeHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn append(&mut self, trade: &TradeEvent) {
+        self.trades.entry(trade.symbol_id).or_default().push(*trade);
+    }
+}
+
+impl TradeHistoryRepo for MemTradeHistory {
+    fn trades_from(
+        &self,
+        symbol_id: SymbolId,
+        from_id: u64,
+        limit: usize,
+    ) -> Result<Vec<TradeEvent>, TradeQueryError> {
+        let Some(trades) = self.trades.get(&symbol_id) else {
+            return Ok(Vec::new());
+        };
+        let start = trades.partition_point(|trade| trade.trade_id < from_id);
+        Ok(trades[start..].iter().take(limit).copied().collect())
+    }
+}
+
+/// 历史成交分页
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HistoricalTradesPage {
+    pub trades: Vec<PublicTrade>,
+    /// 下一页的起始ID（本页不足 `limit` 时为空）
+    pub next_from_id: Option<u64>,
+}
+
+/// 历史成交查询
+///
+/// 未指定 `from_id` 时返回最近成交；起始ID仍在环内时读环，否则查仓储
+pub fn historical_trades<R: TradeHistoryRepo + ?Sized>(
+    recent: &RecentTrades,
+    repo: &R,
+    symbol_id: SymbolId,
+    from_id: Option<u64>,
+    limit: usize,
+) -> Result<HistoricalTradesPage, TradeQueryError> {
+    let trades = match from_id {
+        None => recent.recent(symbol_id, limit),
+        Some(from_id) => match recent.from_id(symbol_id, from_id, limit) {
+            Some(trades) => trades,
+            None => repo.trades_from(symbol_id, from_id, limit)?,
+        },
+    };
+    let next_from_id = match trades.last() {
+        Some(last) if trades.len() == limit => Some(last.trade_id + 1),
+        _ => None,
+    };
+    Ok(HistoricalTradesPage {
+        trades: trades.iter().map(PublicTrade::from).collect(),
+        next_from_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderSide;
+
+    fn trade(trade_id: u64) -> TradeEvent {
+        TradeEvent {
+            symbol_id: 1,
+            timestamp: trade_id * 1_000_000,
+            sequence: trade_id,
+            trade_id,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(100.0),
+            quantity: Quantity::from_f64(0.5),
+            aggressor_side: OrderSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_ring_keeps_latest_trades() {
+        let mut recent = RecentTrades::new(3);
+        for id in 1..=5 {
+            recent.on_trade(&trade(id));
+        }
+        let ids = |trades: Vec<TradeEvent>| trades.iter().map(|t| t.trade_id).collect::<Vec<_>>();
+        assert_eq!(ids(recent.recent(1, 10)), vec![3, 4, 5]);
+        assert_eq!(ids(recent.recent(1, 2)), vec![4, 5]);
+        assert!(recent.recent(2, 2).is_empty());
+        assert_eq!(recent.oldest_id(1), Some(3));
+        assert_eq!(recent.from_id(1, 4, 10).map(ids), Some(vec![4, 5]));
+        assert!(recent.from_id(1, 2, 10).is_none());
+        assert_eq!(clamp_trades_limit(None), DEFAULT_TRADES_LIMIT);
+        assert_eq!(clamp_trades_limit(Some(0)), 1);
+        assert_eq!(clamp_trades_limit(Some(5000)), MAX_TRADES_LIMIT);
+    }
+
+    #[test]
+    fn test_historical_trades_falls_back_to_repo() {
+        let mut recent = RecentTrades::new(3);
+        let mut repo = MemTradeHistory::new();
+        for id in 1..=6 {
+            recent.on_trade(&trade(id));
+            repo.append(&trade(id));
+        }
+
+        // 起始ID已淘汰出环，查仓储并翻页
+        let page = historical_trades(&recent, &repo, 1, Some(1), 2).unwrap();
+        assert_eq!(page.trades.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(page.next_from_id, Some(3));
+        let page = historical_trades(&recent, &repo, 1, page.next_from_id, 2).unwrap();
+        assert_eq!(page.trades.iter().map(|t| t.id).collect::<Vec<_>>(), vec![3, 4]);
+
+        let last = historical_trades(&recent, &repo, 1, Some(5), 5).unwrap();
+        assert_eq!((last.trades.len(), last.next_from_id), (2, None));
+        assert_eq!(last.trades[0].time, 5);
+        assert_eq!(last.trades[0].quote_qty, Decimal128::from_int(50));
+
+        let latest = historical_trades(&recent, &repo, 1, None, 1).unwrap();
+        assert_eq!(latest.trades[0].id, 6);
+    }
+}
@@ -0,0 +1,166 @@
+//! 滚动成交量
+//!
+//! 按固定时间桶累计成交，统计最近一个窗口（如 24 小时）的成交量、成交额与笔数，
+//! 供行情 ticker 使用。桶长取 1 分钟时与 1 分钟K线对齐，可由K线直接种子化
+
+use std::collections::VecDeque;
+
+use decimal::Decimal128;
+
+use super::candle::Candle;
+use super::level_types::TradeEvent;
+use crate::Quantity;
+
+/// 窗口合计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeTotals {
+    /// 成交量（基础资产）
+    pub volume: Quantity,
+    /// 成交额（计价资产）
+    pub quote_volume: Decimal128,
+    /// 成交笔数
+    pub trade_count: u64,
+}
+
+impl Default for VolumeTotals {
+    fn default() -> Self {
+        Self { volume: Quantity::default(), quote_volume: Decimal128::ZERO, trade_count: 0 }
+    }
+}
+
+impl VolumeTotals {
+    fn add(&mut self, other: &VolumeTotals) {
+        self.volume += other.volume;
+        self.quote_volume += other.quote_volume;
+        self.trade_count += other.trade_count;
+    }
+
+    fn sub(&mut self, other: &VolumeTotals) {
+        self.volume -= other.volume;
+        self.quote_volume -= other.quote_volume;
+        self.trade_count -= other.trade_count;
+    }
+}
+
+/// 滚动窗口成交量
+#[derive(Debug)]
+pub struct RollingVolume {
+    /// 窗口长度（纳秒）
+    window_ns: u64,
+    /// 桶长（纳秒）
+    bucket_ns: u64,
+    /// (桶起始时间, 桶内合计)，按时间升序
+    buckets: VecDeque<(u64, VolumeTotals)>,
+    /// 窗口内合计
+    totals: VolumeTotals,
+}
+
+impl RollingVolume {
+    /// 窗口按桶长向上取整
+    pub fn new(window_ns: u64, bucket_ns: u64) -> Self {
+        let bucket_ns = bucket_ns.max(1);
+        Self {
+            window_ns: window_ns.div_ceil(bucket_ns).max(1) * bucket_ns,
+            bucket_ns,
+            buckets: VecDeque::new(),
+            totals: VolumeTotals::default(),
+        }
+    }
+
+    /// 记录一笔成交
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        let quote_volume = trade.price.wide_notional(trade.quantity).unwrap_or(Decimal128::MAX);
+        self.record(
+            trade.timestamp,
+            &VolumeTotals { volume: trade.quantity, quote_volume, trade_count: 1 },
+        );
+    }
+
+    /// 由K线种子化（K线周期不应长于桶长）
+    pub fn seed(&mut self, candle: &Candle) {
+        self.record(
+            candle.open_time,
+            &VolumeTotals {
+                volume: candle.volume,
+                quote_volume: candle.quote_volume,
+                trade_count: candle.trade_count,
+            },
+        );
+    }
+
+    /// 计入 `timestamp` 所在桶；早于最新桶的记录并入已有桶，已滑出窗口的忽略
+    pub fn record(&mut self, timestamp: u64, amount: &VolumeTotals) {
+        let start = timestamp - timestamp % self.bucket_ns;
+        match self.buckets.back() {
+            Some(&(last, _)) if start < last => {
+                if start + self.window_ns <= last {
+                    return;
+                }
+                match self.buckets.binary_search_by_key(&start, |&(s, _)| s) {
+                    Ok(index) => self.buckets[index].1.add(amount),
+                    Err(index) => self.buckets.insert(index, (start, *amount)),
+                }
+            }
+            Some(&(last, _)) if start == last => {
+                if let Some((_, bucket)) = self.buckets.back_mut() {
+                    bucket.add(amount);
+                }
+            }
+            _ => self.buckets.push_back((start, *amount)),
+        }
+        self.totals.add(amount);
+        self.advance(timestamp);
+    }
+
+    /// 截至 `now` 的窗口合计（`now` 所在桶及之前共一个窗口）
+    pub fn totals(&mut self, now: u64) -> VolumeTotals {
+        self.advance(now);
+        self.totals
+    }
+
+    /// 淘汰滑出窗口的桶
+    fn advance(&mut self, now: u64) {
+        let current = now - now % self.bucket_ns;
+        let cutoff = (current + self.bucket_ns).saturating_sub(self.window_ns);
+        while let Some(&(start, ref bucket)) = self.buckets.front() {
+            if start >= cutoff {
+                break;
+            }
+            self.totals.sub(bucket);
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(volume: i64) -> VolumeTotals {
+        VolumeTotals {
+            volume: Quantity::from_raw(volume),
+            quote_volume: Decimal128::from_int(volume),
+            trade_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        // 窗口 3 个桶
+        let mut rolling = RollingVolume::new(30, 10);
+        rolling.record(5, &amount(1));
+        rolling.record(15, &amount(2));
+        rolling.record(12, &amount(4));
+        rolling.record(25, &amount(8));
+        assert_eq!(rolling.totals(29).volume, Quantity::from_raw(15));
+        assert_eq!(rolling.totals(29).trade_count, 4);
+
+        // 进入第 4 个桶，最早的桶滑出
+        assert_eq!(rolling.totals(30).volume, Quantity::from_raw(14));
+        // 已滑出窗口的迟到记录忽略
+        rolling.record(35, &amount(16));
+        rolling.record(3, &amount(100));
+        assert_eq!(rolling.totals(39).volume, Quantity::from_raw(30));
+        assert_eq!(rolling.totals(1000), VolumeTotals::default());
+    }
+}
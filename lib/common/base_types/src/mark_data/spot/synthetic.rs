@@ -0,0 +1,370 @@
+//! 合成交叉汇率行情
+//!
+//! 没有直接订单簿的资产组合（如 ETH/BTC）经共同的桥接资产（通常为 USDT）由两条直接
+//! 交易对的最优挂单推导买卖价。记资产 A 以桥接资产计的买价 / 卖价为 `bid(A)` / `ask(A)`：
+//! - 合成买价 = `bid(B) / ask(Q)`：卖出 B 换得桥接资产，再以桥接资产买入 Q
+//! - 合成卖价 = `ask(B) / bid(Q)`
+//!
+//! 直接交易对方向相反（桥接资产为基础资产）时取倒数并交换买卖两侧；桥接资产自身的价格恒为 1。
+//! 合成行情不可直接成交，只用于展示以及闪兑 / RFQ 的参考定价
+//! （[`QuoteRequest::reference_price`](crate::exchange::spot::rfq::QuoteRequest::reference_price)）。
+
+use std::collections::HashMap;
+use std::fmt;
+
+use decimal::Decimal128;
+
+use super::level_types::SymbolId;
+use super::ticker::BookTicker;
+use crate::{AssetId, OrderSide, Price, TradingPair};
+
+/// 合成行情错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntheticError {
+    /// 基础资产与计价资产相同
+    SameAsset(AssetId),
+    /// 资产与桥接资产之间没有直接交易对
+    NoLeg { asset: AssetId, bridge: AssetId },
+    /// 已注册
+    AlreadyRegistered { base: AssetId, quote: AssetId },
+}
+
+impl fmt::Display for SyntheticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntheticError::SameAsset(asset) => {
+                write!(f, "Synthetic pair needs two assets, got {} twice", asset.as_str())
+            }
+            SyntheticError::NoLeg { asset, bridge } => {
+                write!(f, "No direct pair between {} and {}", asset.as_str(), bridge.as_str())
+            }
+            SyntheticError::AlreadyRegistered { base, quote } => {
+                write!(f, "Synthetic pair {}{} already registered", base.as_str(), quote.as_str())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SyntheticError {}
+
+/// 合成最优买卖价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SyntheticTicker {
+    pub base: AssetId,
+    pub quote: AssetId,
+    /// 更新序列号（同一合成交易对内递增）
+    pub update_id: u64,
+    pub bid_price: Option<Price>,
+    pub ask_price: Option<Price>,
+}
+
+impl SyntheticTicker {
+    /// 交易对名称（如 `ETHBTC`）
+    pub fn symbol(&self) -> String {
+        format!("{}{}", self.base.as_str(), self.quote.as_str())
+    }
+
+    /// WebSocket 流名称（如 `ethbtc@syntheticTicker`）
+    pub fn stream_name(symbol: &str) -> String {
+        format!("{}@syntheticTicker", symbol.to_lowercase())
+    }
+}
+
+/// 资产以桥接资产计价的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Leg {
+    /// 资产即桥接资产
+    Bridge,
+    /// `资产/桥接` 交易对
+    Direct(SymbolId),
+    /// `桥接/资产` 交易对
+    Inverse(SymbolId),
+}
+
+impl Leg {
+    fn find(asset: AssetId, bridge: AssetId) -> Option<Leg> {
+        if asset == bridge {
+            return Some(Leg::Bridge);
+        }
+        TradingPair::all().iter().find_map(|pair| match (pair.base_asset(), pair.quote_asset()) {
+            (base, quote) if base == asset && quote == bridge => {
+                Some(Leg::Direct(*pair as SymbolId))
+            }
+            (base, quote) if base == bridge && quote == asset => {
+                Some(Leg::Inverse(*pair as SymbolId))
+            }
+            _ => None,
+        })
+    }
+
+    fn symbol_id(&self) -> Option<SymbolId> {
+        match self {
+            Leg::Bridge => None,
+            Leg::Direct(symbol_id) | Leg::Inverse(symbol_id) => Some(*symbol_id),
+        }
+    }
+
+    /// (买价, 卖价)，以桥接资产计
+    fn quote(
+        &self,
+        books: &HashMap<SymbolId, BookTicker>,
+    ) -> (Option<Decimal128>, Option<Decimal128>) {
+        let book = match self.symbol_id() {
+            None => return (Some(Decimal128::ONE), Some(Decimal128::ONE)),
+            Some(symbol_id) => match books.get(&symbol_id) {
+                Some(book) => book,
+                None => return (None, None),
+            },
+        };
+        let bid = positive(book.bid_price);
+        let ask = positive(book.ask_price);
+        match self {
+            Leg::Inverse(_) => (ask.and_then(invert), bid.and_then(invert)),
+            _ => (bid, ask),
+        }
+    }
+}
+
+fn positive(price: Option<Price>) -> Option<Decimal128> {
+    price.filter(Price::is_positive).map(|price| price.to_wide())
+}
+
+fn invert(price: Decimal128) -> Option<Decimal128> {
+    Decimal128::ONE.checked_div(price)
+}
+
+/// 单个合成交易对的状态
+#[derive(Debug)]
+struct SyntheticState {
+    base_leg: Leg,
+    quote_leg: Leg,
+    ticker: SyntheticTicker,
+}
+
+impl SyntheticState {
+    fn uses(&self, symbol_id: SymbolId) -> bool {
+        self.base_leg.symbol_id() == Some(symbol_id)
+            || self.quote_leg.symbol_id() == Some(symbol_id)
+    }
+
+    /// 按当前两条腿的挂单重算；价格变化时返回新的行情
+    fn recompute(&mut self, books: &HashMap<SymbolId, BookTicker>) -> Option<SyntheticTicker> {
+        let (base_bid, base_ask) = self.base_leg.quote(books);
+        let (quote_bid, quote_ask) = self.quote_leg.quote(books);
+        let cross = |numerator: Option<Decimal128>, denominator: Option<Decimal128>| {
+            numerator?.checked_div(denominator?)?.to_narrow().filter(Price::is_positive)
+        };
+        let bid_price = cross(base_bid, quote_ask);
+        let ask_price = cross(base_ask, quote_bid);
+        if (bid_price, ask_price) == (self.ticker.bid_price, self.ticker.ask_price) {
+            return None;
+        }
+        self.ticker.update_id += 1;
+        self.ticker.bid_price = bid_price;
+        self.ticker.ask_price = ask_price;
+        Some(self.ticker)
+    }
+}
+
+/// 合成行情服务
+///
+/// 接收直接交易对的最优挂单（[`BookTicker`]），维护已注册的合成交易对
+#[derive(Debug)]
+pub struct SyntheticTickers {
+    bridge: AssetId,
+    books: HashMap<SymbolId, BookTicker>,
+    pairs: Vec<SyntheticState>,
+}
+
+impl Default for SyntheticTickers {
+    fn default() -> Self {
+        Self::new(AssetId::Usdt)
+    }
+}
+
+impl SyntheticTickers {
+    pub fn new(bridge: AssetId) -> Self {
+        Self { bridge, books: HashMap::new(), pairs: Vec::new() }
+    }
+
+    pub fn bridge(&self) -> AssetId {
+        self.bridge
+    }
+
+    /// 注册合成交易对 `base/quote`
+    pub fn register(&mut self, base: AssetId, quote: AssetId) -> Result<(), SyntheticError> {
+        if base == quote {
+            return Err(SyntheticError::SameAsset(base));
+        }
+        if self.state(base, quote).is_some() {
+            return Err(SyntheticError::AlreadyRegistered { base, quote });
+        }
+        let leg = |asset| {
+            Leg::find(asset, self.bridge)
+                .ok_or(SyntheticError::NoLeg { asset, bridge: self.bridge })
+        };
+        let mut state = SyntheticState {
+            base_leg: leg(base)?,
+            quote_leg: leg(quote)?,
+            ticker: SyntheticTicker { base, quote, update_id: 0, bid_price: None, ask_price: None },
+        };
+        state.recompute(&self.books);
+        self.pairs.push(state);
+        Ok(())
+    }
+
+    /// 处理一次直接交易对的最优挂单变化，返回价格变化的合成行情（供推送）
+    pub fn on_book_ticker(&mut self, book: &BookTicker) -> Vec<SyntheticTicker> {
+        self.books.insert(book.symbol_id, *book);
+        let books = &self.books;
+        self.pairs
+            .iter_mut()
+            .filter(|state| state.uses(book.symbol_id))
+            .filter_map(|state| state.recompute(books))
+            .collect()
+    }
+
+    pub fn ticker(&self, base: AssetId, quote: AssetId) -> Option<SyntheticTicker> {
+        self.state(base, quote).map(|state| state.ticker)
+    }
+
+    /// 全部合成行情（按注册顺序）
+    pub fn tickers(&self) -> Vec<SyntheticTicker> {
+        self.pairs.iter().map(|state| state.ticker).collect()
+    }
+
+    /// 参考成交价：询价方买入 `base` 取合成卖价，卖出取合成买价
+    ///
+    /// 也接受反向注册的交易对（如已注册 ETH/BTC 时查询 BTC/ETH），价格取倒数并交换两侧
+    pub fn indicative_price(
+        &self,
+        base: AssetId,
+        quote: AssetId,
+        side: OrderSide,
+    ) -> Option<Price> {
+        if let Some(ticker) = self.ticker(base, quote) {
+            return match side {
+                OrderSide::Buy => ticker.ask_price,
+                OrderSide::Sell => ticker.bid_price,
+            };
+        }
+        let inverse = self.ticker(quote, base)?;
+        let price = match side {
+            OrderSide::Buy => inverse.bid_price,
+            OrderSide::Sell => inverse.ask_price,
+        }?;
+        invert(price.to_wide())?.to_narrow().filter(Price::is_positive)
+    }
+
+    /// 指数价：`base` 以 `quote` 计的中间价，取两条腿各自买卖中间价之比（无需注册）
+    ///
+    /// 供闪兑、小额资产兑换等不经订单簿成交的场景定价
+    pub fn index_price(&self, base: AssetId, quote: AssetId) -> Option<Price> {
+        let mid = |asset| {
+            let (bid, ask) = Leg::find(asset, self.bridge)?.quote(&self.books);
+            bid?.checked_add(ask?)?.checked_div(Decimal128::from_int(2))
+        };
+        mid(base)?.checked_div(mid(quote)?)?.to_narrow().filter(Price::is_positive)
+    }
+
+    fn state(&self, base: AssetId, quote: AssetId) -> Option<&SyntheticState> {
+        self.pairs.iter().find(|state| state.ticker.base == base && state.ticker.quote == quote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Quantity;
+
+    fn book(pair: TradingPair, update_id: u64, bid: f64, ask: f64) -> BookTicker {
+        BookTicker {
+            symbol_id: pair as SymbolId,
+            update_id,
+            bid_price: Some(Price::from_f64(bid)),
+            bid_qty: Quantity::from_f64(1.0),
+            ask_price: Some(Price::from_f64(ask)),
+            ask_qty: Quantity::from_f64(1.0),
+        }
+    }
+
+    #[test]
+    fn test_cross_rate_from_two_legs() {
+        let mut tickers = SyntheticTickers::default();
+        tickers.register(AssetId::Eth, AssetId::Btc).unwrap();
+        assert_eq!(tickers.ticker(AssetId::Eth, AssetId::Btc).unwrap().bid_price, None);
+
+        // 只有一条腿时无法报价
+        assert!(
+            tickers.on_book_ticker(&book(TradingPair::EthUsdt, 1, 2_000.0, 2_002.0)).is_empty()
+        );
+        let updates = tickers.on_book_ticker(&book(TradingPair::BtcUsdt, 1, 40_000.0, 40_040.0));
+        assert_eq!(updates.len(), 1);
+        let ticker = updates[0];
+        assert_eq!(ticker.symbol(), "ETHBTC");
+        assert_eq!(ticker.update_id, 1);
+        // 2000 / 40040 与 2002 / 40000
+        assert_eq!(ticker.bid_price, Some(Price::from_raw(4_995_004)));
+        assert_eq!(ticker.ask_price, Some(Price::from_raw(5_005_000)));
+
+        // 与合成交易对无关的挂单不触发更新；价格未变也不推送
+        assert!(tickers.on_book_ticker(&book(TradingPair::BtcEth, 1, 19.0, 21.0)).is_empty());
+        assert!(
+            tickers.on_book_ticker(&book(TradingPair::BtcUsdt, 2, 40_000.0, 40_040.0)).is_empty()
+        );
+        assert_eq!(SyntheticTicker::stream_name("ETHBTC"), "ethbtc@syntheticTicker");
+    }
+
+    #[test]
+    fn test_inverse_and_bridge_legs() {
+        let mut tickers = SyntheticTickers::new(AssetId::Eth);
+        // ETH 作桥接：BTC 经 BTC/ETH 直接计价，USDT 经 ETH/USDT 反向计价
+        tickers.register(AssetId::Btc, AssetId::Usdt).unwrap();
+        tickers.register(AssetId::Usdt, AssetId::Eth).unwrap();
+        tickers.on_book_ticker(&book(TradingPair::BtcEth, 1, 20.0, 20.5));
+        tickers.on_book_ticker(&book(TradingPair::EthUsdt, 1, 2_000.0, 2_500.0));
+
+        let btc = tickers.ticker(AssetId::Btc, AssetId::Usdt).unwrap();
+        // 买价 20 / (1/2000)，卖价 20.5 / (1/2500)
+        assert_eq!(btc.bid_price, Some(Price::from_f64(40_000.0)));
+        assert_eq!(btc.ask_price, Some(Price::from_f64(51_250.0)));
+        let usdt = tickers.ticker(AssetId::Usdt, AssetId::Eth).unwrap();
+        assert_eq!(usdt.bid_price, Some(Price::from_f64(0.0004)));
+        assert_eq!(usdt.ask_price, Some(Price::from_f64(0.0005)));
+
+        assert_eq!(
+            tickers.indicative_price(AssetId::Btc, AssetId::Usdt, OrderSide::Buy),
+            Some(Price::from_f64(51_250.0))
+        );
+        // 反向查询：买入 ETH（以 USDT 计）取 1 / 合成买价
+        assert_eq!(
+            tickers.indicative_price(AssetId::Eth, AssetId::Usdt, OrderSide::Buy),
+            Some(Price::from_f64(2_500.0))
+        );
+        assert_eq!(tickers.indicative_price(AssetId::Btc, AssetId::Eth, OrderSide::Buy), None);
+
+        // 指数价取中间价之比，未注册的组合同样可查：20.25 / ((1/2500 + 1/2000) / 2)
+        assert_eq!(
+            tickers.index_price(AssetId::Btc, AssetId::Usdt),
+            Some(Price::from_f64(45_000.0))
+        );
+        assert_eq!(tickers.index_price(AssetId::Btc, AssetId::Eth), Some(Price::from_f64(20.25)));
+    }
+
+    #[test]
+    fn test_register_rejects_invalid_pairs() {
+        let mut tickers = SyntheticTickers::default();
+        assert_eq!(
+            tickers.register(AssetId::Btc, AssetId::Btc),
+            Err(SyntheticError::SameAsset(AssetId::Btc))
+        );
+        tickers.register(AssetId::Eth, AssetId::Btc).unwrap();
+        assert_eq!(
+            tickers.register(AssetId::Eth, AssetId::Btc),
+            Err(SyntheticError::AlreadyRegistered { base: AssetId::Eth, quote: AssetId::Btc })
+        );
+        assert_eq!(tickers.tickers().len(), 1);
+    }
+}
@@ -0,0 +1,211 @@
+//! 最优挂单与均价行情
+//!
+//! 由撮合引擎的增量事件增量维护：`bookTicker` 取最新的最优买卖价变更，
+//! `avgPrice` 为最近 5 分钟的成交量加权均价（窗口内无成交时取最新成交价）
+
+use std::collections::HashMap;
+
+use super::level_types::{BboChangeEvent, MarketDataDelta, SymbolId, TradeEvent};
+use super::rolling_volume::RollingVolume;
+use crate::{Price, Quantity};
+
+/// 均价窗口（分钟）
+pub const AVG_PRICE_WINDOW_MINS: u32 = 5;
+
+/// 均价窗口的桶长（纳秒）
+const AVG_PRICE_BUCKET_NS: u64 = 1_000_000_000;
+
+/// 最优挂单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BookTicker {
+    pub symbol_id: SymbolId,
+    /// 更新序列号
+    pub update_id: u64,
+    pub bid_price: Option<Price>,
+    pub bid_qty: Quantity,
+    pub ask_price: Option<Price>,
+    pub ask_qty: Quantity,
+}
+
+impl From<&BboChangeEvent> for BookTicker {
+    fn from(event: &BboChangeEvent) -> Self {
+        Self {
+            symbol_id: event.symbol_id,
+            update_id: event.sequence,
+            bid_price: event.best_bid,
+            bid_qty: event.best_bid_quantity,
+            ask_price: event.best_ask,
+            ask_qty: event.best_ask_quantity,
+        }
+    }
+}
+
+impl BookTicker {
+    /// WebSocket 流名称（如 `btcusdt@bookTicker`）
+    pub fn stream_name(symbol: &str) -> String {
+        format!("{}@bookTicker", symbol.to_lowercase())
+    }
+}
+
+/// 均价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AvgPrice {
+    /// 窗口（分钟）
+    pub mins: u32,
+    /// 均价（从未成交时为空）
+    pub price: Option<Price>,
+    /// 窗口结束时间（Unix 毫秒）
+    pub close_time: u64,
+}
+
+/// 单个交易对的均价状态
+#[derive(Debug)]
+struct AvgPriceState {
+    window: RollingVolume,
+    last_price: Price,
+}
+
+/// 现货行情快照（按交易对）
+#[derive(Debug, Default)]
+pub struct SpotTickers {
+    books: HashMap<SymbolId, BookTicker>,
+    averages: HashMap<SymbolId, AvgPriceState>,
+}
+
+impl SpotTickers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一条增量事件；最优挂单变化时返回新的 `BookTicker`（供推送）
+    pub fn apply(&mut self, delta: &MarketDataDelta) -> Option<BookTicker> {
+        match delta {
+            MarketDataDelta::BboChange(event) => self.on_bbo(event),
+            MarketDataDelta::Trade(trade) => {
+                self.on_trade(trade);
+                None
+            }
+            MarketDataDelta::OrderChange(_) => None,
+        }
+    }
+
+    /// 最优买卖价变更；序列号回退或挂单未变时返回 None
+    pub fn on_bbo(&mut self, event: &BboChangeEvent) -> Option<BookTicker> {
+        let ticker = BookTicker::from(event);
+        if let Some(current) = self.books.get(&event.symbol_id) {
+            let unchanged = BookTicker { update_id: current.update_id, ..ticker } == *current;
+            if event.sequence < current.update_id || unchanged {
+                return None;
+            }
+        }
+        self.books.insert(event.symbol_id, ticker);
+        Some(ticker)
+    }
+
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        let state = self.averages.entry(trade.symbol_id).or_insert_with(|| AvgPriceState {
+            window: RollingVolume::new(
+                AVG_PRICE_WINDOW_MINS as u64 * 60 * AVG_PRICE_BUCKET_NS,
+                AVG_PRICE_BUCKET_NS,
+            ),
+            last_price: trade.price,
+        });
+        state.window.on_trade(trade);
+        state.last_price = trade.price;
+    }
+
+    pub fn book_ticker(&self, symbol_id: SymbolId) -> Option<&BookTicker> {
+        self.books.get(&symbol_id)
+    }
+
+    /// 全部交易对的最优挂单（按交易对ID排序）
+    pub fn book_tickers(&self) -> Vec<BookTicker> {
+        let mut tickers: Vec<_> = self.books.values().copied().collect();
+        tickers.sort_by_key(|ticker| ticker.symbol_id);
+        tickers
+    }
+
+    /// 截至 `now`（纳秒）的均价
+    pub fn avg_price(&mut self, symbol_id: SymbolId, now: u64) -> AvgPrice {
+        let price = self.averages.get_mut(&symbol_id).map(|state| {
+            let totals = state.window.totals(now);
+            // 成交额 18 位小数 / 成交量 8 位小数 = 10 位小数，再缩为 8 位
+            let divisor = totals.volume.raw() as i128 * 100;
+            if divisor > 0 {
+                Price::from_raw((totals.quote_volume.raw() / divisor) as i64)
+            } else {
+                state.last_price
+            }
+        });
+        AvgPrice { mins: AVG_PRICE_WINDOW_MINS, price, close_time: now / 1_000_000 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderSide;
+
+    const MINUTE_NS: u64 = 60_000_000_000;
+
+    fn trade(timestamp: u64, price: f64, quantity: f64) -> MarketDataDelta {
+        MarketDataDelta::Trade(TradeEvent {
+            symbol_id: 1,
+            timestamp,
+            sequence: 0,
+            trade_id: 0,
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            price: Price::from_f64(price),
+            quantity: Quantity::from_f64(quantity),
+            aggressor_side: OrderSide::Buy,
+        })
+    }
+
+    fn bbo(sequence: u64, bid: f64, ask: f64) -> MarketDataDelta {
+        MarketDataDelta::BboChange(BboChangeEvent {
+            symbol_id: 1,
+            timestamp: sequence,
+            sequence,
+            best_bid: Some(Price::from_f64(bid)),
+            best_bid_quantity: Quantity::from_f64(1.0),
+            best_ask: Some(Price::from_f64(ask)),
+            best_ask_quantity: Quantity::from_f64(2.0),
+        })
+    }
+
+    #[test]
+    fn test_book_ticker_updates() {
+        let mut tickers = SpotTickers::new();
+        let ticker = tickers.apply(&bbo(1, 99.0, 101.0)).unwrap();
+        assert_eq!((ticker.update_id, ticker.ask_qty), (1, Quantity::from_f64(2.0)));
+        // 挂单未变与序列号回退都不推送
+        assert!(tickers.apply(&bbo(2, 99.0, 101.0)).is_none());
+        assert!(tickers.apply(&bbo(3, 100.0, 101.0)).is_some());
+        assert!(tickers.apply(&bbo(2, 98.0, 101.0)).is_none());
+        assert_eq!(tickers.book_ticker(1).unwrap().bid_price, Some(Price::from_f64(100.0)));
+        assert_eq!(tickers.book_tickers().len(), 1);
+        assert_eq!(BookTicker::stream_name("BTCUSDT"), "btcusdt@bookTicker");
+    }
+
+    #[test]
+    fn test_avg_price_window() {
+        let mut tickers = SpotTickers::new();
+        assert_eq!(tickers.avg_price(1, 0).price, None);
+
+        tickers.apply(&trade(0, 100.0, 1.0));
+        tickers.apply(&trade(MINUTE_NS, 110.0, 3.0));
+        let avg = tickers.avg_price(1, 2 * MINUTE_NS);
+        assert_eq!(avg.price, Some(Price::from_f64(107.5)));
+        assert_eq!((avg.mins, avg.close_time), (5, 120_000));
+
+        // 第一笔滑出窗口
+        assert_eq!(tickers.avg_price(1, 5 * MINUTE_NS).price, Some(Price::from_f64(110.0)));
+        // 窗口内无成交时取最新成交价
+        assert_eq!(tickers.avg_price(1, 20 * MINUTE_NS).price, Some(Price::from_f64(110.0)));
+    }
+}
@@ -0,0 +1,277 @@
+//! 成交组播通道
+//!
+//! 与 [`BboFastChannel`](super::bbo_channel::BboFastChannel) 共用组播组与帧格式
+//! （SBE 标准 8 字节消息头 + 定长块，小端，一个数据报一条消息），逐笔发布成交：
+//! - 每笔成交都发布，不做去重；通道序列号逐条加一，接收方据此发现丢包
+//! - 接收方用于维护最近成交环与均价窗口，丢包只影响这两处的完整性
+//!
+//! 定长块布局（72 字节）：
+//!
+//! ```text
+//! 0  symbol_id u32 | 4 aggressor_side u8（0 买 / 1 卖）| 5 填充 3
+//! 8  channel_seq u64 | 16 engine_seq u64 | 24 timestamp u64（纳秒）
+//! 32 trade_id u64 | 40 price i64 | 48 qty i64
+//! 56 buyer_order_id u64 | 64 seller_order_id u64
+//! ```
+
+use super::bbo_channel::{BBO_SCHEMA_ID, BBO_SCHEMA_VERSION, DatagramSink};
+use super::level_types::{MarketDataDelta, TradeEvent};
+use crate::{OrderSide, Price, Quantity};
+
+/// 成交消息模板ID
+pub const TRADE_TEMPLATE_ID: u16 = 21;
+/// SBE 消息头长度
+const HEADER_LEN: usize = 8;
+/// 定长块长度
+pub const TRADE_BLOCK_LENGTH: u16 = 72;
+/// 一条消息的总长度
+pub const TRADE_MESSAGE_LEN: usize = HEADER_LEN + TRADE_BLOCK_LENGTH as usize;
+
+/// 成交消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeMessage {
+    /// 通道序列号（从 1 开始，逐条加一）
+    pub channel_seq: u64,
+    pub trade: TradeEvent,
+}
+
+/// 解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeDecodeError {
+    /// 长度不足
+    Truncated(usize),
+    /// 模式或模板不匹配
+    UnknownTemplate { schema_id: u16, template_id: u16 },
+    /// 主动方取值非法
+    InvalidSide(u8),
+}
+
+impl std::fmt::Display for TradeDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeDecodeError::Truncated(len) => {
+                write!(f, "Trade message truncated: {} bytes", len)
+            }
+            TradeDecodeError::UnknownTemplate { schema_id, template_id } => {
+                write!(f, "Unknown template: schema {} template {}", schema_id, template_id)
+            }
+            TradeDecodeError::InvalidSide(side) => write!(f, "Invalid aggressor side: {}", side),
+        }
+    }
+}
+
+impl std::error::Error for TradeDecodeError {}
+
+impl TradeMessage {
+    /// 编码到 `buf`
+    pub fn encode(&self, buf: &mut [u8; TRADE_MESSAGE_LEN]) {
+        buf[0..2].copy_from_slice(&TRADE_BLOCK_LENGTH.to_le_bytes());
+        buf[2..4].copy_from_slice(&TRADE_TEMPLATE_ID.to_le_bytes());
+        buf[4..6].copy_from_slice(&BBO_SCHEMA_ID.to_le_bytes());
+        buf[6..8].copy_from_slice(&BBO_SCHEMA_VERSION.to_le_bytes());
+
+        let trade = &self.trade;
+        let body = &mut buf[HEADER_LEN..];
+        body[0..4].copy_from_slice(&trade.symbol_id.to_le_bytes());
+        body[4] = trade.aggressor_side as u8;
+        body[5..8].fill(0);
+        body[8..16].copy_from_slice(&self.channel_seq.to_le_bytes());
+        body[16..24].copy_from_slice(&trade.sequence.to_le_bytes());
+        body[24..32].copy_from_slice(&trade.timestamp.to_le_bytes());
+        body[32..40].copy_from_slice(&trade.trade_id.to_le_bytes());
+        body[40..48].copy_from_slice(&trade.price.raw().to_le_bytes());
+        body[48..56].copy_from_slice(&trade.quantity.raw().to_le_bytes());
+        body[56..64].copy_from_slice(&trade.buyer_order_id.to_le_bytes());
+        body[64..72].copy_from_slice(&trade.seller_order_id.to_le_bytes());
+    }
+
+    /// 从数据报解码（块长大于本版本时忽略多出的字段）
+    pub fn decode(buf: &[u8]) -> Result<Self, TradeDecodeError> {
+        if buf.len() < HEADER_LEN {
+            return Err(TradeDecodeError::Truncated(buf.len()));
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([buf[at], buf[at + 1]]);
+        let (block_length, template_id, schema_id) = (u16_at(0), u16_at(2), u16_at(4));
+        if schema_id != BBO_SCHEMA_ID || template_id != TRADE_TEMPLATE_ID {
+            return Err(TradeDecodeError::UnknownTemplate { schema_id, template_id });
+        }
+        if block_length < TRADE_BLOCK_LENGTH || buf.len() < HEADER_LEN + block_length as usize {
+            return Err(TradeDecodeError::Truncated(buf.len()));
+        }
+
+        let body = &buf[HEADER_LEN..];
+        let word = |at: usize| field::<8>(body, at);
+        let aggressor_side = match body[4] {
+            0 => OrderSide::Buy,
+            1 => OrderSide::Sell,
+            side => return Err(TradeDecodeError::InvalidSide(side)),
+        };
+        Ok(Self {
+            channel_seq: u64::from_le_bytes(word(8)?),
+            trade: TradeEvent {
+                symbol_id: u32::from_le_bytes(field::<4>(body, 0)?),
+                timestamp: u64::from_le_bytes(word(24)?),
+                sequence: u64::from_le_bytes(word(16)?),
+                trade_id: u64::from_le_bytes(word(32)?),
+                buyer_order_id: u64::from_le_bytes(word(56)?),
+                seller_order_id: u64::from_le_bytes(word(64)?),
+                price: Price::from_raw(i64::from_le_bytes(word(40)?)),
+                quantity: Quantity::from_raw(i64::from_le_bytes(word(48)?)),
+                aggressor_side,
+            },
+        })
+    }
+}
+
+/// 定长块中 `at` 起的 `N` 字节
+fn field<const N: usize>(body: &[u8], at: usize) -> Result<[u8; N], TradeDecodeError> {
+    body.get(at..at + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(TradeDecodeError::Truncated(HEADER_LEN + body.len()))
+}
+
+/// 成交组播发布端
+///
+/// 发送失败不阻塞行情主流程，计入 `send_errors`
+#[derive(Debug)]
+pub struct TradeChannel<S: DatagramSink> {
+    sink: S,
+    channel_seq: u64,
+    send_errors: u64,
+}
+
+impl<S: DatagramSink> TradeChannel<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink, channel_seq: 0, send_errors: 0 }
+    }
+
+    /// 发布一笔成交
+    pub fn on_trade(&mut self, trade: &TradeEvent) {
+        self.channel_seq += 1;
+        let mut buf = [0u8; TRADE_MESSAGE_LEN];
+        TradeMessage { channel_seq: self.channel_seq, trade: *trade }.encode(&mut buf);
+        if self.sink.send(&buf).is_err() {
+            self.send_errors += 1;
+        }
+    }
+
+    /// 发布一批增量中的全部成交（按原顺序）
+    pub fn publish_batch(&mut self, deltas: &[MarketDataDelta]) {
+        for delta in deltas {
+            if let MarketDataDelta::Trade(trade) = delta {
+                self.on_trade(trade);
+            }
+        }
+    }
+
+    /// 已发布的最后一个通道序列号
+    pub fn channel_seq(&self) -> u64 {
+        self.channel_seq
+    }
+
+    /// 发送失败次数
+    pub fn send_errors(&self) -> u64 {
+        self.send_errors
+    }
+}
+
+/// 成交组播接收端
+///
+/// 按通道序列号去重（组播可能重复投递），序列号跳跃计为丢包
+#[derive(Debug, Default)]
+pub struct TradeReceiver {
+    last_channel_seq: u64,
+    gaps: u64,
+}
+
+impl TradeReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一个数据报，返回新成交（重复或过期的消息返回 `None`）
+    pub fn on_datagram(&mut self, datagram: &[u8]) -> Result<Option<TradeEvent>, TradeDecodeError> {
+        let message = TradeMessage::decode(datagram)?;
+        if message.channel_seq <= self.last_channel_seq {
+            return Ok(None);
+        }
+        self.gaps += message.channel_seq - self.last_channel_seq - 1;
+        self.last_channel_seq = message.channel_seq;
+        Ok(Some(message.trade))
+    }
+
+    /// 最后处理的通道序列号
+    pub fn last_channel_seq(&self) -> u64 {
+        self.last_channel_seq
+    }
+
+    /// 累计丢失的消息数
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::mark_data::spot::bbo_channel::BboMessage;
+
+    #[derive(Default)]
+    struct Captured(Vec<Vec<u8>>);
+
+    impl DatagramSink for Captured {
+        fn send(&mut self, datagram: &[u8]) -> io::Result<()> {
+            self.0.push(datagram.to_vec());
+            Ok(())
+        }
+    }
+
+    fn trade(trade_id: u64) -> TradeEvent {
+        TradeEvent {
+            symbol_id: 7,
+            timestamp: trade_id * 10,
+            sequence: trade_id + 100,
+            trade_id,
+            buyer_order_id: 11,
+            seller_order_id: 12,
+            price: Price::from_f64(100.5),
+            quantity: Quantity::from_f64(0.25),
+            aggressor_side: OrderSide::Sell,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let mut channel = TradeChannel::new(Captured::default());
+        channel.publish_batch(&[MarketDataDelta::Trade(trade(1))]);
+        let datagram = &channel.sink.0[0];
+        assert_eq!(datagram.len(), TRADE_MESSAGE_LEN);
+
+        let message = TradeMessage::decode(datagram).unwrap();
+        assert_eq!(message, TradeMessage { channel_seq: 1, trade: trade(1) });
+        assert_eq!(TradeMessage::decode(&datagram[..20]), Err(TradeDecodeError::Truncated(20)));
+        let mut other = datagram.clone();
+        other[12] = 9;
+        assert_eq!(TradeMessage::decode(&other), Err(TradeDecodeError::InvalidSide(9)));
+        // 同一组播组上的 BBO 帧不是成交
+        assert!(BboMessage::decode(datagram).is_err());
+    }
+
+    #[test]
+    fn test_receiver_skips_duplicates_and_counts_gaps() {
+        let mut channel = TradeChannel::new(Captured::default());
+        for trade_id in 1..=4 {
+            channel.on_trade(&trade(trade_id));
+        }
+        let datagrams = &channel.sink.0;
+
+        let mut receiver = TradeReceiver::new();
+        assert_eq!(receiver.on_datagram(&datagrams[0]).unwrap(), Some(trade(1)));
+        assert!(receiver.on_datagram(&datagrams[0]).unwrap().is_none());
+        assert_eq!(receiver.on_datagram(&datagrams[3]).unwrap(), Some(trade(4)));
+        assert!(receiver.on_datagram(&datagrams[2]).unwrap().is_none());
+        assert_eq!((receiver.last_channel_seq(), receiver.gaps()), (4, 2));
+    }
+}
@@ -1,3 +1,25 @@
+//! 消息主题
+//!
+//! [`SpotTopic`] 为现货变更日志主题。行情按层级主题发布：`<市场>.<交易对>.<频道>`，
+//! 如 `spot.BTC_USDT.trades`、`perp.ETH_USDT.funding`。订阅方可使用通配符：
+//! - `*` 匹配一段（`perp.*.funding`：全部永续合约的资金费率）
+//! - `>` 只能位于末尾，匹配其后一段或多段（`spot.BTC_USDT.>`：该交易对的全部频道）
+//!
+//! [`TopicTrie`] 按段组织订阅，匹配开销与主题段数及命中的通配分支相关，与订阅总数无关，
+//! 多交易对订阅只需一条通配规则
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::TradingPair;
+
+/// 主题分段符
+pub const TOPIC_SEPARATOR: char = '.';
+/// 单段通配符
+pub const SINGLE_WILDCARD: &str = "*";
+/// 尾部多段通配符
+pub const TAIL_WILDCARD: &str = ">";
+
 pub enum SpotTopic {
     OrderChangeLog,
     TradeChangeLog,
@@ -5,6 +27,12 @@ pub enum SpotTopic {
     KLineChangeLog,
     KUserDataChangeLog,
     KMarketChangeLog,
+    /// BBO 快速通道（只含最优买卖价变化，先于深度发布）
+    BboFastChannel,
+    /// 结算及其分录（供数据仓库、合规系统消费）
+    SettlementEntryLog,
+    /// API Key 变更（吊销经此通知各网关立即生效）
+    ApiKeyEventLog,
 }
 
 impl SpotTopic {
@@ -16,6 +44,285 @@ impl SpotTopic {
             SpotTopic::BalanceChangeLog => "BalanceChangeLog",
             SpotTopic::KUserDataChangeLog => "KUserDataChangeLog",
             SpotTopic::KMarketChangeLog => "KMarketChangeLog",
+            SpotTopic::BboFastChannel => "BboFastChannel",
+            SpotTopic::SettlementEntryLog => "SettlementEntryLog",
+            SpotTopic::ApiKeyEventLog => "ApiKeyEventLog",
+        }
+    }
+
+    /// 层级主题中的频道段
+    pub fn channel(&self) -> &'static str {
+        match self {
+            SpotTopic::OrderChangeLog => "orders",
+            SpotTopic::KLineChangeLog => "kline",
+            SpotTopic::TradeChangeLog => "trades",
+            SpotTopic::BalanceChangeLog => "balances",
+            SpotTopic::KUserDataChangeLog => "userData",
+            SpotTopic::KMarketChangeLog => "market",
+            SpotTopic::BboFastChannel => "bbo",
+            SpotTopic::SettlementEntryLog => "settlements",
+            SpotTopic::ApiKeyEventLog => "apiKeys",
+        }
+    }
+
+    /// 交易对的层级主题，如 `spot.BTC_USDT.trades`
+    pub fn topic(&self, pair: TradingPair) -> String {
+        market_topic(TopicMarket::Spot, pair, self.channel())
+    }
+}
+
+/// 主题的市场段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TopicMarket {
+    Spot,
+    Perp,
+}
+
+impl TopicMarket {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            TopicMarket::Spot => "spot",
+            TopicMarket::Perp => "perp",
+        }
+    }
+}
+
+/// 层级主题 `<市场>.<BASE_QUOTE>.<频道>`
+pub fn market_topic(market: TopicMarket, pair: TradingPair, channel: &str) -> String {
+    format!(
+        "{}{sep}{}_{}{sep}{}",
+        market.as_str(),
+        pair.base_asset().as_str(),
+        pair.quote_asset().as_str(),
+        channel,
+        sep = TOPIC_SEPARATOR
+    )
+}
+
+/// 主题模式错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicError {
+    /// 空主题或空段
+    EmptySegment(String),
+    /// `>` 不在末尾
+    TailWildcardNotLast(String),
+}
+
+impl fmt::Display for TopicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopicError::EmptySegment(pattern) => write!(f, "Empty segment in topic {}", pattern),
+            TopicError::TailWildcardNotLast(pattern) => {
+                write!(f, "'{}' must be the last segment in topic {}", TAIL_WILDCARD, pattern)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopicError {}
+
+/// 校验订阅模式，返回各段
+pub fn pattern_segments(pattern: &str) -> Result<Vec<&str>, TopicError> {
+    let segments: Vec<&str> = pattern.split(TOPIC_SEPARATOR).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(TopicError::EmptySegment(pattern.to_string()));
+    }
+    if segments[..segments.len() - 1].contains(&TAIL_WILDCARD) {
+        return Err(TopicError::TailWildcardNotLast(pattern.to_string()));
+    }
+    Ok(segments)
+}
+
+/// 主题模式是否含通配符
+pub fn is_wildcard(pattern: &str) -> bool {
+    pattern
+        .split(TOPIC_SEPARATOR)
+        .any(|segment| segment == SINGLE_WILDCARD || segment == TAIL_WILDCARD)
+}
+
+#[derive(Debug, Clone)]
+struct TrieNode<T> {
+    children: HashMap<String, TrieNode<T>>,
+    /// 模式在此结束的订阅
+    values: Vec<T>,
+}
+
+impl<T> Default for TrieNode<T> {
+    fn default() -> Self {
+        Self { children: HashMap::new(), values: Vec::new() }
+    }
+}
+
+impl<T> TrieNode<T> {
+    fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.children.is_empty()
+    }
+}
+
+/// 按主题段组织的订阅前缀树（值通常为连接或订阅者ID）
+#[derive(Debug, Clone)]
+pub struct TopicTrie<T> {
+    root: TrieNode<T>,
+    len: usize,
+}
+
+impl<T> Default for TopicTrie<T> {
+    fn default() -> Self {
+        Self { root: TrieNode::default(), len: 0 }
+    }
+}
+
+impl<T: PartialEq> TopicTrie<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记订阅，返回是否新增（同一模式下的相同值只登记一次）
+    pub fn insert(&mut self, pattern: &str, value: T) -> Result<bool, TopicError> {
+        let mut node = &mut self.root;
+        for segment in pattern_segments(pattern)? {
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        if node.values.contains(&value) {
+            return Ok(false);
+        }
+        node.values.push(value);
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// 取消订阅，返回是否存在（清理空分支）
+    pub fn remove(&mut self, pattern: &str, value: &T) -> bool {
+        let Ok(segments) = pattern_segments(pattern) else {
+            return false;
+        };
+        let removed = Self::remove_at(&mut self.root, &segments, value);
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_at(node: &mut TrieNode<T>, segments: &[&str], value: &T) -> bool {
+        let Some((segment, rest)) = segments.split_first() else {
+            let Some(index) = node.values.iter().position(|v| v == value) else {
+                return false;
+            };
+            node.values.remove(index);
+            return true;
+        };
+        let Some(child) = node.children.get_mut(*segment) else {
+            return false;
+        };
+        let removed = Self::remove_at(child, rest, value);
+        if child.is_empty() {
+            node.children.remove(*segment);
+        }
+        removed
+    }
+
+    /// 具体主题命中的全部订阅值（同一值经多条模式命中时只出现一次）
+    pub fn matches(&self, topic: &str) -> Vec<&T> {
+        let segments: Vec<&str> = topic.split(TOPIC_SEPARATOR).collect();
+        let mut matched = Vec::new();
+        Self::collect(&self.root, &segments, &mut |value| {
+            if !matched.contains(&value) {
+                matched.push(value);
+            }
+            false
+        });
+        matched
+    }
+
+    /// 具体主题是否命中任一订阅
+    pub fn is_match(&self, topic: &str) -> bool {
+        let segments: Vec<&str> = topic.split(TOPIC_SEPARATOR).collect();
+        Self::collect(&self.root, &segments, &mut |_| true)
+    }
+
+    /// 深度优先访问命中的订阅值，`visit` 返回 true 时提前结束
+    fn collect<'a>(
+        node: &'a TrieNode<T>,
+        segments: &[&str],
+        visit: &mut dyn FnMut(&'a T) -> bool,
+    ) -> bool {
+        let Some((segment, rest)) = segments.split_first() else {
+            return node.values.iter().any(&mut *visit);
+        };
+        if let Some(tail) = node.children.get(TAIL_WILDCARD) {
+            if tail.values.iter().any(&mut *visit) {
+                return true;
+            }
+        }
+        if let Some(child) = node.children.get(*segment) {
+            if Self::collect(child, rest, visit) {
+                return true;
+            }
+        }
+        match node.children.get(SINGLE_WILDCARD) {
+            Some(child) if *segment != SINGLE_WILDCARD => Self::collect(child, rest, visit),
+            _ => false,
         }
     }
+
+    /// 订阅数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hierarchical_topics() {
+        assert_eq!(SpotTopic::TradeChangeLog.topic(TradingPair::BtcUsdt), "spot.BTC_USDT.trades");
+        assert_eq!(
+            market_topic(TopicMarket::Perp, TradingPair::EthUsdt, "funding"),
+            "perp.ETH_USDT.funding"
+        );
+        assert!(is_wildcard("perp.*.funding"));
+        assert!(!is_wildcard("spot.BTC_USDT.trades"));
+        assert_eq!(
+            pattern_segments("spot.>.trades"),
+            Err(TopicError::TailWildcardNotLast("spot.>.trades".to_string()))
+        );
+        assert!(matches!(pattern_segments("spot..trades"), Err(TopicError::EmptySegment(_))));
+    }
+
+    #[test]
+    fn test_trie_wildcard_matching() {
+        let mut trie = TopicTrie::new();
+        trie.insert("spot.BTC_USDT.trades", 1).unwrap();
+        trie.insert("perp.*.funding", 2).unwrap();
+        trie.insert("spot.BTC_USDT.>", 3).unwrap();
+        trie.insert("spot.*.trades", 1).unwrap();
+        assert!(!trie.insert("perp.*.funding", 2).unwrap());
+        assert_eq!(trie.len(), 4);
+
+        let mut matched: Vec<i32> =
+            trie.matches("spot.BTC_USDT.trades").into_iter().copied().collect();
+        matched.sort_unstable();
+        assert_eq!(matched, vec![1, 3]);
+        assert_eq!(trie.matches("perp.ETH_USDT.funding"), vec![&2]);
+        assert_eq!(trie.matches("spot.ETH_USDT.trades"), vec![&1]);
+        // `>` 至少匹配一段；`*` 不跨段
+        assert!(!trie.is_match("spot.BTC_USDT"));
+        assert!(trie.is_match("spot.BTC_USDT.depth.100ms"));
+        assert!(!trie.is_match("perp.ETH_USDT.funding.8h"));
+
+        assert!(trie.remove("perp.*.funding", &2));
+        assert!(!trie.remove("perp.*.funding", &2));
+        assert!(!trie.is_match("perp.ETH_USDT.funding"));
+        assert!(trie.remove("spot.BTC_USDT.>", &3));
+        assert!(trie.remove("spot.BTC_USDT.trades", &1));
+        assert!(trie.remove("spot.*.trades", &1));
+        assert!(trie.is_empty());
+        assert!(trie.root.children.is_empty());
+    }
 }
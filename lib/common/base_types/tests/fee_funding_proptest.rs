@@ -0,0 +1,205 @@
+//! 手续费与资金费公式的性质测试
+//!
+//! 在贴近实盘的取值范围内随机生成产品费率配置、成交与持仓，校验：
+//! - 手续费非负且不超过成交额（含最小手续费兜底）
+//! - 做市返佣不超过同一成交的吃单手续费
+//! - 多空持仓总量相等时，资金费支付合计约为零
+//! - 相同输入重复计算结果一致
+
+use base_types::fee::fee_types::{FeeType, ProductFeeConfig, ProductTierConfig, ProductVIPLevel};
+use base_types::{PositionId, PositionSide, PrepPosition, Price, Quantity, Timestamp, TradingPair};
+use proptest::prelude::*;
+
+/// 固定的计费时间（不在任何促销期内）
+const NOW: Timestamp = Timestamp(0);
+
+/// 费率配置：maker 可为负（返佣）
+#[derive(Debug, Clone)]
+struct FeeSetup {
+    maker: f64,
+    taker: f64,
+    tier: Option<(f64, f64, f64)>,
+    vip_discount: Option<f64>,
+}
+
+impl FeeSetup {
+    fn config(&self) -> ProductFeeConfig {
+        let mut config = ProductFeeConfig::perpetual(self.maker, self.taker, 0.0075);
+        if let Some((min_volume_30d, maker_fee, taker_fee)) = self.tier {
+            config.add_tier(ProductTierConfig {
+                tier_id: 1,
+                tier_name: "T1".to_string(),
+                min_volume_30d,
+                maker_fee,
+                taker_fee,
+                is_active: true,
+            });
+        }
+        if let Some(fee_discount) = self.vip_discount {
+            config.add_vip_level(ProductVIPLevel {
+                level_id: 1,
+                level_name: "VIP1".to_string(),
+                fee_discount,
+                special_benefits: Vec::new(),
+            });
+        }
+        config
+    }
+}
+
+/// (maker, taker)：maker -0.5% ~ 0.5%，taker 0 ~ 0.5%
+fn rate_pair() -> impl Strategy<Value = (f64, f64)> {
+    (-0.005..0.005f64, 0.0..0.005f64)
+}
+
+fn fee_setup() -> impl Strategy<Value = FeeSetup> {
+    (
+        rate_pair(),
+        proptest::option::of((0.0..1e9f64, rate_pair())),
+        proptest::option::of(0.0..=1.0f64),
+    )
+        .prop_map(|((maker, taker), tier, vip_discount)| FeeSetup {
+            maker,
+            taker,
+            tier: tier.map(|(volume, (maker, taker))| (volume, maker, taker)),
+            vip_discount,
+        })
+}
+
+/// 成交：数量 0.00001 ~ 1000，价格 0.0001 ~ 200000
+fn trade() -> impl Strategy<Value = (f64, f64)> {
+    (1e-5..1e3f64, 1e-4..2e5f64)
+}
+
+fn user() -> impl Strategy<Value = (Option<f64>, Option<u32>, bool)> {
+    (proptest::option::of(0.0..2e9f64), proptest::option::of(0u32..3), any::<bool>())
+}
+
+/// 资金费率：±0.75%
+fn funding_rate() -> impl Strategy<Value = Price> {
+    (-750_000i64..=750_000).prop_map(Price::from_raw)
+}
+
+/// 标记价格：0.01 ~ 100000，两位小数
+fn mark_price() -> impl Strategy<Value = Price> {
+    (1i64..10_000_000).prop_map(|cents| Price::from_raw(cents * 1_000_000))
+}
+
+/// 持仓数量（千分之一张）：0.001 ~ 1000
+fn lots() -> impl Strategy<Value = Vec<i64>> {
+    prop::collection::vec(1i64..1_000_000, 1..20)
+}
+
+fn position(side: PositionSide, lots: i64, mark_price: Price) -> PrepPosition {
+    let mut position = PrepPosition::empty(TradingPair::BtcUsdt, side, PositionId(1), NOW);
+    position.quantity = Quantity::from_raw(lots * 100_000);
+    position.mark_price = mark_price;
+    position
+}
+
+proptest! {
+    #[test]
+    fn fee_never_exceeds_notional(
+        setup in fee_setup(),
+        (quantity, price) in trade(),
+        (volume, vip, market_maker) in user(),
+        maker in any::<bool>(),
+    ) {
+        let fee_type = if maker { FeeType::Maker } else { FeeType::Taker };
+        let result = setup
+            .config()
+            .calculate_trading_fee(
+                fee_type, "USDT", quantity, price, volume, vip, market_maker, NOW,
+            )
+            .unwrap();
+
+        prop_assert!(result.fee_amount.is_finite());
+        prop_assert!(result.fee_amount <= quantity * price);
+        if result.final_rate >= 0.0 {
+            prop_assert!(result.fee_amount >= 0.0);
+        }
+    }
+
+    #[test]
+    fn rebate_never_exceeds_taker_fee(
+        setup in fee_setup(),
+        (quantity, price) in trade(),
+        (maker_volume, maker_vip, market_maker) in user(),
+        (taker_volume, taker_vip, _) in user(),
+    ) {
+        let config = setup.config();
+        let maker = config
+            .calculate_trading_fee(
+                FeeType::Maker, "USDT", quantity, price, maker_volume, maker_vip, market_maker, NOW,
+            )
+            .unwrap();
+        let taker = config
+            .calculate_trading_fee(
+                FeeType::Taker, "USDT", quantity, price, taker_volume, taker_vip, false, NOW,
+            )
+            .unwrap();
+
+        // 任意挂单方与吃单方撮合，交易所净收入不为负
+        prop_assert!(taker.fee_amount >= 0.0);
+        if maker.fee_amount < 0.0 {
+            prop_assert!(-maker.fee_amount <= taker.fee_amount);
+        }
+    }
+
+    #[test]
+    fn fee_calculation_is_idempotent(
+        setup in fee_setup(),
+        (quantity, price) in trade(),
+        (volume, vip, market_maker) in user(),
+    ) {
+        let config = setup.config();
+        let first = config
+            .calculate_trading_fee(
+                FeeType::Maker, "USDT", quantity, price, volume, vip, market_maker, NOW,
+            )
+            .unwrap();
+        let second = config
+            .calculate_trading_fee(
+                FeeType::Maker, "USDT", quantity, price, volume, vip, market_maker, NOW,
+            )
+            .unwrap();
+
+        prop_assert_eq!(first.fee_amount.to_bits(), second.fee_amount.to_bits());
+        prop_assert_eq!(first.final_rate.to_bits(), second.final_rate.to_bits());
+    }
+
+    #[test]
+    fn funding_payments_sum_to_zero(
+        long_lots in lots(),
+        mark_price in mark_price(),
+        funding_rate in funding_rate(),
+    ) {
+        // 空头持仓为多头持仓两两合并，总量相等但分布不同
+        let short_lots: Vec<i64> = long_lots.chunks(2).map(|pair| pair.iter().sum()).collect();
+        let positions: Vec<PrepPosition> = long_lots
+            .iter()
+            .map(|&lots| position(PositionSide::Long, lots, mark_price))
+            .chain(short_lots.iter().map(|&lots| position(PositionSide::Short, lots, mark_price)))
+            .collect();
+
+        let payments: Vec<i64> = positions
+            .iter()
+            .map(|p| p.calculate_next_funding_fee(funding_rate).raw())
+            .collect();
+        let total: i64 = payments.iter().sum();
+
+        // 每笔换算截断误差不足 1 个最小单位
+        prop_assert!(total.abs() <= positions.len() as i64, "total = {}", total);
+        if funding_rate.is_positive() {
+            // 正费率多头付费、空头收费
+            prop_assert!(payments[..long_lots.len()].iter().all(|&p| p <= 0));
+            prop_assert!(payments[long_lots.len()..].iter().all(|&p| p >= 0));
+        }
+
+        let rerun: Vec<i64> = positions
+            .iter()
+            .map(|p| p.calculate_next_funding_fee(funding_rate).raw())
+            .collect();
+        prop_assert_eq!(payments, rerun);
+    }
+}
@@ -0,0 +1,232 @@
+//! 静态布局估算
+//!
+//! 只凭源码估算结构体布局（不经编译），供代码分析工具扫描整个工作区使用。
+//! `#[repr(C)]` / `#[repr(packed)]` 按声明顺序排布字段，默认 repr 按编译器的重排规则
+//! （对齐降序）排布；任一字段类型无法估算时不给出布局。
+
+use syn::{Attribute, DeriveInput, Type};
+
+use crate::validation::{FieldInfo, extract_fields, is_atomic_or_sync_type};
+use crate::{CacheAnalysisReport, FieldAnalysis};
+
+/// `#[repr(...)]` 属性
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReprAttributes {
+    /// repr(C)
+    pub c: bool,
+    /// repr(packed) / repr(packed(N))
+    pub packed: bool,
+    /// repr(align(N))
+    pub align: Option<usize>,
+}
+
+impl ReprAttributes {
+    /// 是否标注了任一 repr
+    pub fn is_annotated(&self) -> bool {
+        self.c || self.packed || self.align.is_some()
+    }
+}
+
+/// 解析 `#[repr(...)]` 属性
+pub fn parse_repr(attrs: &[Attribute]) -> ReprAttributes {
+    let mut repr = ReprAttributes::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") {
+                    repr.c = true;
+                } else if meta.path.is_ident("packed") {
+                    repr.packed = true;
+                    if meta.input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        content.parse::<syn::LitInt>()?;
+                    }
+                } else if meta.path.is_ident("align") {
+                    // 解析 align(N) 的值
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let lit: syn::LitInt = content.parse()?;
+                    repr.align = lit.base10_parse().ok();
+                }
+                Ok(())
+            });
+        }
+    }
+
+    repr
+}
+
+/// 位于同一缓存行的一对原子/同步字段（伪共享风险）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FalseSharingPair {
+    pub first: String,
+    pub second: String,
+    /// 所在缓存行
+    pub cache_line: usize,
+}
+
+/// 静态估算的结构体布局
+#[derive(Debug)]
+pub struct StaticLayout {
+    pub repr: ReprAttributes,
+    /// 布局报告（字段按声明顺序，偏移为估算的实际偏移）
+    pub report: CacheAnalysisReport,
+    /// 伪共享风险
+    pub false_sharing: Vec<FalseSharingPair>,
+    /// 跨越缓存行边界的 #[hot] 字段
+    pub split_hot_fields: Vec<String>,
+}
+
+/// 估算结构体布局
+pub fn estimate_layout(ast: &DeriveInput, cache_line_size: usize) -> Result<StaticLayout, String> {
+    let repr = parse_repr(&ast.attrs);
+    let fields = extract_fields(ast)?;
+
+    let mut field_analyses = Vec::with_capacity(fields.len());
+    for field in &fields {
+        let size =
+            field_size(field).ok_or_else(|| format!("无法估算字段 '{}' 的类型大小", field.name))?;
+        let alignment = if repr.packed { 1 } else { field_alignment(field).unwrap_or(size.max(1)) };
+        field_analyses.push(FieldAnalysis {
+            name: field.name.clone(),
+            offset: 0,
+            size,
+            alignment,
+            is_hot: field.is_hot,
+        });
+    }
+
+    // 默认 repr 由编译器重排字段
+    let optimal_order = CacheAnalysisReport::calculate_optimal_field_order(&field_analyses);
+    let layout_order: Vec<usize> = if repr.c || repr.packed {
+        (0..field_analyses.len()).collect()
+    } else {
+        optimal_order.clone()
+    };
+
+    let end = place_fields(&mut field_analyses, &layout_order);
+
+    let field_alignment = field_analyses.iter().map(|f| f.alignment).max().unwrap_or(1);
+    let alignment = repr.align.map_or(field_alignment, |align| align.max(field_alignment));
+    let total_size = end.div_ceil(alignment) * alignment;
+    let padding_bytes = total_size - field_analyses.iter().map(|f| f.size).sum::<usize>();
+    let padding_percentage =
+        if total_size > 0 { padding_bytes as f32 / total_size as f32 * 100.0 } else { 0.0 };
+    let cache_lines_needed = total_size.div_ceil(cache_line_size);
+    // 声明顺序排布时，与最优顺序比较大小
+    let is_current_order_optimal = layout_order == optimal_order || {
+        let mut reordered = field_analyses.clone();
+        let optimal_end = place_fields(&mut reordered, &optimal_order);
+        total_size <= optimal_end.div_ceil(alignment) * alignment
+    };
+
+    let false_sharing = false_sharing_pairs(&fields, &field_analyses, cache_line_size);
+    let split_hot_fields = field_analyses
+        .iter()
+        .filter(|f| f.is_hot && f.size > 0)
+        .filter(|f| f.offset / cache_line_size != (f.offset + f.size - 1) / cache_line_size)
+        .map(|f| f.name.clone())
+        .collect();
+
+    let mut suggestions = Vec::new();
+    if padding_percentage > 20.0 {
+        suggestions.push(format!(
+            "结构体 {} 有 {:.1}% 的填充空间，考虑重新排列字段",
+            ast.ident, padding_percentage
+        ));
+    }
+    if !is_current_order_optimal {
+        suggestions.push("当前字段顺序不是最优的，建议按照对齐和大小降序排列".to_string());
+    }
+    if cache_lines_needed > 1 && alignment < cache_line_size {
+        suggestions.push(format!(
+            "结构体大小 {} 字节跨越 {} 个缓存行，考虑 #[repr(align({}))] 或拆分冷热字段",
+            total_size, cache_lines_needed, cache_line_size
+        ));
+    }
+
+    Ok(StaticLayout {
+        repr,
+        report: CacheAnalysisReport {
+            struct_name: ast.ident.to_string(),
+            total_size,
+            alignment,
+            cache_line_size,
+            cache_lines_needed,
+            field_count: field_analyses.len(),
+            field_analyses,
+            padding_bytes,
+            padding_percentage,
+            optimal_field_order: optimal_order,
+            is_current_order_optimal,
+            suggestions,
+        },
+        false_sharing,
+        split_hot_fields,
+    })
+}
+
+/// 按 `order` 依次排布字段并写入偏移，返回末尾偏移
+fn place_fields(fields: &mut [FieldAnalysis], order: &[usize]) -> usize {
+    let mut offset = 0;
+    for &idx in order {
+        let field = &mut fields[idx];
+        offset += (field.alignment - offset % field.alignment) % field.alignment;
+        field.offset = offset;
+        offset += field.size;
+    }
+    offset
+}
+
+/// 同一缓存行内的原子/同步字段对
+fn false_sharing_pairs(
+    fields: &[FieldInfo],
+    analyses: &[FieldAnalysis],
+    cache_line_size: usize,
+) -> Vec<FalseSharingPair> {
+    let shared: Vec<&FieldAnalysis> = fields
+        .iter()
+        .zip(analyses)
+        .filter(|(field, _)| is_atomic_or_sync_type(&field.ty))
+        .map(|(_, analysis)| analysis)
+        .collect();
+
+    let mut pairs = Vec::new();
+    for (i, first) in shared.iter().enumerate() {
+        for second in &shared[i + 1..] {
+            let cache_line = first.offset / cache_line_size;
+            if cache_line == second.offset / cache_line_size {
+                pairs.push(FalseSharingPair {
+                    first: first.name.clone(),
+                    second: second.name.clone(),
+                    cache_line,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+fn field_size(field: &FieldInfo) -> Option<usize> {
+    field.estimated_size().or_else(|| atomic_size(&field.ty))
+}
+
+fn field_alignment(field: &FieldInfo) -> Option<usize> {
+    field.estimated_alignment().or_else(|| atomic_size(&field.ty))
+}
+
+/// 原子类型的大小（与对齐相同）
+fn atomic_size(ty: &Type) -> Option<usize> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    match type_path.path.segments.last()?.ident.to_string().as_str() {
+        "AtomicBool" | "AtomicI8" | "AtomicU8" => Some(1),
+        "AtomicI16" | "AtomicU16" => Some(2),
+        "AtomicI32" | "AtomicU32" => Some(4),
+        "AtomicI64" | "AtomicU64" | "AtomicIsize" | "AtomicUsize" | "AtomicPtr" => Some(8),
+        _ => None,
+    }
+}
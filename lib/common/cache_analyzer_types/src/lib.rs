@@ -1,3 +1,4 @@
+pub mod layout;
 pub mod validation;
 
 /// 缓存分析公共类型定义
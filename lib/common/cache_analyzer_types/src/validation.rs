@@ -1,5 +1,7 @@
 use syn::{Data, DeriveInput, Fields, Ident, Type};
 
+use crate::layout::parse_repr;
+
 /// 编译时验证配置
 #[derive(Debug, Clone)]
 pub struct CompileTimeValidation {
@@ -147,33 +149,9 @@ fn check_cache_line_alignment(
     cache_line_size: usize,
 ) -> Result<(), String> {
     // 1. 检查是否有 repr(align) 属性
-    let mut explicit_alignment: Option<usize> = None;
-    let mut has_repr_c = false;
-    let mut has_repr_packed = false;
-
-    for attr in &ast.attrs {
-        if attr.path().is_ident("repr") {
-            // 解析 repr 属性
-            let _ = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("C") {
-                    has_repr_c = true;
-                    Ok(())
-                } else if meta.path.is_ident("packed") {
-                    has_repr_packed = true;
-                    Ok(())
-                } else if meta.path.is_ident("align") {
-                    // 解析 align(N) 的值
-                    let content;
-                    syn::parenthesized!(content in meta.input);
-                    let lit: syn::LitInt = content.parse()?;
-                    explicit_alignment = lit.base10_parse().ok();
-                    Ok(())
-                } else {
-                    Ok(())
-                }
-            });
-        }
-    }
+    let repr = parse_repr(&ast.attrs);
+    let explicit_alignment = repr.align;
+    let has_repr_packed = repr.packed;
 
     // 2. 估算结构体的实际大小和自然对齐
     let struct_size = estimate_struct_size(fields);
@@ -346,7 +324,7 @@ fn check_hot_fields_cache_alignment(
 }
 
 /// 提取结构体字段
-fn extract_fields(ast: &DeriveInput) -> Result<Vec<FieldInfo>, String> {
+pub(crate) fn extract_fields(ast: &DeriveInput) -> Result<Vec<FieldInfo>, String> {
     let mut fields_info = Vec::new();
 
     if let Data::Struct(data_struct) = &ast.data {
@@ -677,7 +655,7 @@ fn check_false_sharing_risk(
 }
 
 /// 检查类型是否为原子类型或同步原语
-fn is_atomic_or_sync_type(ty: &Type) -> bool {
+pub(crate) fn is_atomic_or_sync_type(ty: &Type) -> bool {
     if let Type::Path(type_path) = ty {
         let segments = &type_path.path.segments;
         if segments.is_empty() {
@@ -0,0 +1,126 @@
+//! ClickHouse HTTP 接口客户端
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use crate::AnalyticsError;
+
+/// ClickHouse 客户端
+pub trait ClickHouseClient {
+    /// 执行语句，`body` 为随请求发送的数据（如 `INSERT ... FORMAT JSONEachRow` 的行），返回响应体
+    fn execute(&mut self, sql: &str, body: &str) -> Result<String, AnalyticsError>;
+}
+
+/// 基于 HTTP 接口（默认 8123 端口）的客户端
+///
+/// 每次请求新建连接并使用 HTTP/1.0，响应不会分块；攒批写入时请求频率很低，无需连接池
+#[derive(Debug, Clone)]
+pub struct HttpClickHouseClient {
+    /// 服务地址（`host:port`）
+    addr: String,
+    user: String,
+    password: String,
+    timeout: Duration,
+}
+
+impl HttpClickHouseClient {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            user: "default".to_string(),
+            password: String::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_credentials(
+        mut self,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.user = user.into();
+        self.password = password.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn request(&self, sql: &str, body: &str) -> String {
+        format!(
+            "POST /?query={} HTTP/1.0\r\nHost: {}\r\nX-ClickHouse-User: {}\r\nX-ClickHouse-Key: {}\r\nContent-Length: {}\r\n\r\n{}",
+            percent_encode(sql),
+            self.addr,
+            self.user,
+            self.password,
+            body.len(),
+            body
+        )
+    }
+}
+
+impl ClickHouseClient for HttpClickHouseClient {
+    fn execute(&mut self, sql: &str, body: &str) -> Result<String, AnalyticsError> {
+        let mut stream = TcpStream::connect(&self.addr)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.write_all(self.request(sql, body).as_bytes())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        parse_response(&response)
+    }
+}
+
+/// 解析 HTTP 响应，非 200 时把响应体（ClickHouse 的错误信息）作为错误返回
+fn parse_response(response: &[u8]) -> Result<String, AnalyticsError> {
+    let text = String::from_utf8_lossy(response);
+    let (head, body) = text
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| AnalyticsError::Decode("Missing header terminator".to_string()))?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| AnalyticsError::Decode("Missing status code".to_string()))?;
+    if status != 200 {
+        return Err(AnalyticsError::Http { status, body: body.trim().to_string() });
+    }
+    Ok(body.to_string())
+}
+
+/// URL 查询参数编码
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() * 3);
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_and_response() {
+        let client = HttpClickHouseClient::new("localhost:8123").with_credentials("etl", "secret");
+        let request = client.request("SELECT 1", "");
+        assert!(request.starts_with("POST /?query=SELECT%201 HTTP/1.0\r\n"));
+        assert!(request.contains("X-ClickHouse-User: etl\r\n"));
+
+        assert_eq!(parse_response(b"HTTP/1.0 200 OK\r\nX: y\r\n\r\n1\n"), Ok("1\n".to_string()));
+        assert_eq!(
+            parse_response(b"HTTP/1.0 404 Not Found\r\n\r\nCode: 60. Table missing\n"),
+            Err(AnalyticsError::Http { status: 404, body: "Code: 60. Table missing".to_string() })
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! 历史表 DDL
+//!
+//! 每个实体一张 `<table_name>_history` 表，一行对应一条变更：
+//! - 元数据列以 `_` 开头（`_entity_id`、`_change_type`、`_timestamp`、`_sequence`），避免与实体字段重名
+//! - 实体字段列均为 `Nullable`：创建事件写入全部初始值，更新事件只写入变更的字段，删除事件全为 NULL
+//! - 按 `(_entity_id, _sequence)` 排序，单实体历史查询只扫描连续区间
+
+use diff::TableSchema;
+
+use crate::AnalyticsError;
+
+/// 变更类型列的枚举定义
+pub const CHANGE_TYPE_ENUM: &str = "Enum8('Created' = 1, 'Updated' = 2, 'Deleted' = 3)";
+
+/// 历史表名
+pub fn history_table(schema: &TableSchema) -> String {
+    format!("{}_history", schema.table_name)
+}
+
+/// 带库名、转义后的完整表名
+pub fn qualified_table(database: &str, schema: &TableSchema) -> String {
+    format!("{}.{}", quote_ident(database), quote_ident(&history_table(schema)))
+}
+
+/// Rust 字段类型对应的 ClickHouse 列类型（不含 `Nullable`）
+///
+/// 未识别的类型按 `Debug` 文本存为 `String`
+pub fn column_type(field_type: &str) -> &'static str {
+    let compact: String = field_type.chars().filter(|c| !c.is_whitespace()).collect();
+    let inner =
+        compact.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')).unwrap_or(&compact);
+    match inner {
+        "u8" => "UInt8",
+        "u16" => "UInt16",
+        "u32" => "UInt32",
+        "u64" | "usize" => "UInt64",
+        "i8" => "Int8",
+        "i16" => "Int16",
+        "i32" => "Int32",
+        "i64" | "isize" => "Int64",
+        "f32" => "Float32",
+        "f64" => "Float64",
+        "bool" => "Bool",
+        "Price" | "Quantity" | "Decimal" => "Decimal64(8)",
+        _ => "String",
+    }
+}
+
+/// 建表语句（`IF NOT EXISTS`，可重复执行）
+pub fn create_table_sql(database: &str, schema: &TableSchema) -> Result<String, AnalyticsError> {
+    schema.validate().map_err(AnalyticsError::InvalidSchema)?;
+    if let Some(field) = schema.fields.iter().find(|f| f.field_name.starts_with('_')) {
+        return Err(AnalyticsError::InvalidSchema(format!(
+            "Field '{}' clashes with metadata columns",
+            field.field_name
+        )));
+    }
+
+    let mut columns = vec![
+        "`_entity_id` String".to_string(),
+        format!("`_change_type` {}", CHANGE_TYPE_ENUM),
+        "`_timestamp` UInt64".to_string(),
+        "`_sequence` UInt64".to_string(),
+    ];
+    columns.extend(schema.fields.iter().map(|field| {
+        format!("{} Nullable({})", quote_ident(&field.field_name), column_type(&field.field_type))
+    }));
+    Ok(format!(
+        "CREATE TABLE IF NOT EXISTS {} ({}) ENGINE = MergeTree ORDER BY (`_entity_id`, `_sequence`)",
+        qualified_table(database, schema),
+        columns.join(", ")
+    ))
+}
+
+/// 标识符转义
+pub fn quote_ident(ident: &str) -> String {
+    format!("`{}`", ident.replace('\\', "\\\\").replace('`', "\\`"))
+}
+
+/// 字符串字面量转义
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use diff::FieldSchema;
+
+    use super::*;
+
+    pub(crate) fn order_schema() -> TableSchema {
+        let mut schema = TableSchema::new("order");
+        for (name, ty) in
+            [("id", "u64"), ("symbol", "String"), ("price", "Price"), ("filled", "Option < u64 >")]
+        {
+            schema.add_field(FieldSchema {
+                field_name: name.to_string(),
+                field_type: ty.to_string(),
+                default_value: String::new(),
+            });
+        }
+        schema
+    }
+
+    #[test]
+    fn test_create_table_sql() {
+        let sql = create_table_sql("analytics", &order_schema()).unwrap();
+        assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS `analytics`.`order_history` ("));
+        assert!(sql.contains("`price` Nullable(Decimal64(8))"));
+        assert!(sql.contains("`filled` Nullable(UInt64)"));
+        assert!(sql.contains("`symbol` Nullable(String)"));
+        assert!(sql.ends_with("ORDER BY (`_entity_id`, `_sequence`)"));
+
+        let mut clash = order_schema();
+        clash.add_field(FieldSchema {
+            field_name: "_sequence".to_string(),
+            field_type: "u64".to_string(),
+            default_value: String::new(),
+        });
+        assert!(matches!(
+            create_table_sql("analytics", &clash),
+            Err(AnalyticsError::InvalidSchema(_))
+        ));
+        assert!(create_table_sql("analytics", &TableSchema::new("empty")).is_err());
+    }
+}
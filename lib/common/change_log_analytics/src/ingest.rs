@@ -0,0 +1,275 @@
+//! 变更日志攒批写入
+//!
+//! 按实体类型匹配已注册的表结构（`entity_type` 小写后与 `table_name` 相同），
+//! 每张表攒满 `batch_size` 行写入一次；调用方定时 [`ChangeLogIngestor::flush`] 写出不足一批的尾部
+
+use std::collections::HashMap;
+
+use diff::{ChangeLog, ChangeType, FieldChange, TableSchema};
+use serde_json::{Map, Value};
+
+use crate::AnalyticsError;
+use crate::client::ClickHouseClient;
+use crate::ddl::{column_type, create_table_sql, qualified_table};
+
+/// 写入配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestConfig {
+    /// 目标库
+    pub database: String,
+    /// 每张表每批行数
+    pub batch_size: usize,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self { database: "analytics".to_string(), batch_size: 10_000 }
+    }
+}
+
+/// 变更日志写入器
+pub struct ChangeLogIngestor<C: ClickHouseClient> {
+    client: C,
+    config: IngestConfig,
+    /// 表名 -> 表结构
+    schemas: HashMap<String, TableSchema>,
+    /// 表名 -> 待写入的行（JSONEachRow）
+    pending: HashMap<String, Vec<String>>,
+    inserted: u64,
+    skipped: u64,
+}
+
+impl<C: ClickHouseClient> ChangeLogIngestor<C> {
+    pub fn new(client: C, config: IngestConfig) -> Self {
+        Self {
+            client,
+            config,
+            schemas: HashMap::new(),
+            pending: HashMap::new(),
+            inserted: 0,
+            skipped: 0,
+        }
+    }
+
+    /// 注册实体表结构并建表
+    pub fn register(&mut self, schema: TableSchema) -> Result<(), AnalyticsError> {
+        let sql = create_table_sql(&self.config.database, &schema)?;
+        self.client.execute(&sql, "")?;
+        self.schemas.insert(schema.table_name.clone(), schema);
+        Ok(())
+    }
+
+    /// 加入一条变更，返回是否被接收（未注册表结构的实体类型跳过）
+    ///
+    /// 该表攒满一批时立即写入，写入失败时行保留在缓冲中，下次写入重试
+    pub fn push(&mut self, entry: &ChangeLog) -> Result<bool, AnalyticsError> {
+        let table = entry.entity_type().to_lowercase();
+        let Some(schema) = self.schemas.get(&table) else {
+            self.skipped += 1;
+            return Ok(false);
+        };
+        let row = encode_row(schema, entry);
+        let rows = self.pending.entry(table.clone()).or_default();
+        rows.push(row);
+        if rows.len() >= self.config.batch_size {
+            self.flush_table(&table)?;
+        }
+        Ok(true)
+    }
+
+    /// 写出全部缓冲，返回写入的行数
+    pub fn flush(&mut self) -> Result<usize, AnalyticsError> {
+        let tables: Vec<String> = self.pending.keys().cloned().collect();
+        let mut written = 0;
+        for table in tables {
+            written += self.flush_table(&table)?;
+        }
+        Ok(written)
+    }
+
+    fn flush_table(&mut self, table: &str) -> Result<usize, AnalyticsError> {
+        let Some(rows) = self.pending.remove(table).filter(|rows| !rows.is_empty()) else {
+            return Ok(0);
+        };
+        let schema = &self.schemas[table];
+        let sql = format!(
+            "INSERT INTO {} FORMAT JSONEachRow",
+            qualified_table(&self.config.database, schema)
+        );
+        let mut body = rows.join("\n");
+        body.push('\n');
+        if let Err(e) = self.client.execute(&sql, &body) {
+            self.pending.insert(table.to_string(), rows);
+            return Err(e);
+        }
+        self.inserted += rows.len() as u64;
+        Ok(rows.len())
+    }
+
+    /// 待写入的行数
+    pub fn pending_len(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    /// 已写入的行数
+    pub fn inserted(&self) -> u64 {
+        self.inserted
+    }
+
+    /// 未注册表结构而跳过的变更数
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    pub fn schema(&self, table: &str) -> Option<&TableSchema> {
+        self.schemas.get(table)
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    pub fn client_mut(&mut self) -> &mut C {
+        &mut self.client
+    }
+}
+
+/// 一条变更编码为一行 JSON
+fn encode_row(schema: &TableSchema, entry: &ChangeLog) -> String {
+    let (change_type, fields): (&str, &[FieldChange]) = match entry.change_type() {
+        ChangeType::Created { fields } => ("Created", fields),
+        ChangeType::Updated { changed_fields } => ("Updated", changed_fields),
+        ChangeType::Deleted => ("Deleted", &[]),
+    };
+    let mut row = Map::new();
+    row.insert("_entity_id".to_string(), entry.entity_id().clone().into());
+    row.insert("_change_type".to_string(), change_type.into());
+    row.insert("_timestamp".to_string(), (*entry.timestamp()).into());
+    row.insert("_sequence".to_string(), (*entry.sequence()).into());
+    for change in fields {
+        if let Some(field) = schema.find_field(&change.field_name) {
+            let value = column_value(column_type(&field.field_type), &change.new_value);
+            row.insert(field.field_name.clone(), value);
+        }
+    }
+    Value::Object(row).to_string()
+}
+
+/// 字段新值（`Debug` 文本）转换为列值，无法解析时为 NULL
+fn column_value(column_type: &str, value: &str) -> Value {
+    let value = value.trim();
+    if value == "None" {
+        return Value::Null;
+    }
+    let value = value.strip_prefix("Some(").and_then(|v| v.strip_suffix(')')).unwrap_or(value);
+    match column_type {
+        "String" => {
+            let unquoted =
+                serde_json::from_str::<String>(value).unwrap_or_else(|_| value.to_string());
+            Value::String(unquoted)
+        }
+        "Bool" => value.parse::<bool>().map(Value::Bool).unwrap_or(Value::Null),
+        _ => match serde_json::from_str::<Value>(value) {
+            Ok(number @ Value::Number(_)) => number,
+            _ => Value::Null,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddl::tests::order_schema;
+
+    /// 记录执行过的语句，可模拟写入失败
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<(String, String)>,
+        fail: bool,
+    }
+
+    impl ClickHouseClient for Recorder {
+        fn execute(&mut self, sql: &str, body: &str) -> Result<String, AnalyticsError> {
+            if self.fail {
+                return Err(AnalyticsError::Io("connection refused".to_string()));
+            }
+            self.calls.push((sql.to_string(), body.to_string()));
+            Ok(String::new())
+        }
+    }
+
+    fn created(id: &str, sequence: u64) -> ChangeLog {
+        ChangeLog::new(
+            id.to_string(),
+            "Order".to_string(),
+            ChangeType::Created {
+                fields: vec![
+                    FieldChange::new("id", "", id),
+                    FieldChange::new("symbol", "", "\"BTCUSDT\""),
+                    FieldChange::new("price", "", "100.5"),
+                    FieldChange::new("filled", "", "None"),
+                ],
+            },
+            1_000,
+            sequence,
+        )
+    }
+
+    #[test]
+    fn test_encode_row() {
+        let row: Value =
+            serde_json::from_str(&encode_row(&order_schema(), &created("7", 1))).unwrap();
+        assert_eq!(row["_change_type"], "Created");
+        assert_eq!((row["id"].as_u64(), row["symbol"].as_str()), (Some(7), Some("BTCUSDT")));
+        assert_eq!(row["price"].as_f64(), Some(100.5));
+        assert!(row["filled"].is_null());
+
+        let updated = ChangeLog::new(
+            "7".to_string(),
+            "Order".to_string(),
+            ChangeType::Updated {
+                changed_fields: vec![FieldChange::new("filled", "None", "Some(3)")],
+            },
+            2_000,
+            2,
+        );
+        let row: Value = serde_json::from_str(&encode_row(&order_schema(), &updated)).unwrap();
+        assert_eq!(row["filled"], 3);
+        assert!(row.get("price").is_none());
+    }
+
+    #[test]
+    fn test_batches_and_retries() {
+        let config = IngestConfig { database: "analytics".to_string(), batch_size: 2 };
+        let mut ingestor = ChangeLogIngestor::new(Recorder::default(), config);
+        ingestor.register(order_schema()).unwrap();
+        assert!(ingestor.client().calls[0].0.starts_with("CREATE TABLE IF NOT EXISTS"));
+
+        let mut other = created("1", 1);
+        other = ChangeLog::new(
+            other.entity_id().clone(),
+            "Trade".to_string(),
+            other.change_type().clone(),
+            0,
+            0,
+        );
+        assert_eq!(ingestor.push(&other), Ok(false));
+        assert_eq!(ingestor.skipped(), 1);
+
+        assert_eq!(ingestor.push(&created("1", 1)), Ok(true));
+        assert_eq!(ingestor.pending_len(), 1);
+        assert_eq!(ingestor.push(&created("2", 2)), Ok(true));
+        assert_eq!((ingestor.pending_len(), ingestor.inserted()), (0, 2));
+        let (sql, body) = &ingestor.client().calls[1];
+        assert_eq!(sql, "INSERT INTO `analytics`.`order_history` FORMAT JSONEachRow");
+        assert_eq!(body.lines().count(), 2);
+
+        ingestor.push(&created("3", 3)).unwrap();
+        ingestor.client_mut().fail = true;
+        assert!(ingestor.flush().is_err());
+        assert_eq!(ingestor.pending_len(), 1);
+        ingestor.client_mut().fail = false;
+        assert_eq!(ingestor.flush(), Ok(1));
+        assert_eq!(ingestor.inserted(), 3);
+    }
+}
@@ -0,0 +1,54 @@
+//! 实体变更历史分析管道
+//!
+//! 把 diff crate 产生的 [`diff::ChangeLog`] 批量写入 ClickHouse，供对实体历史做即席分析，
+//! 不占用 OLTP 数据库：
+//! - [`ddl`]：由实体的 `TableSchema` 自动生成历史表 DDL
+//! - [`ingest`]：按表攒批，`JSONEachRow` 格式写入
+//! - [`query`]：历史查询构造器
+//! - [`client`]：ClickHouse HTTP 接口客户端
+
+pub mod client;
+pub mod ddl;
+pub mod ingest;
+pub mod query;
+
+pub use client::{ClickHouseClient, HttpClickHouseClient};
+pub use ingest::{ChangeLogIngestor, IngestConfig};
+pub use query::HistoryQuery;
+
+/// 分析管道错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalyticsError {
+    /// 表结构不合法
+    InvalidSchema(String),
+    /// 查询引用了表结构中不存在的列
+    UnknownColumn(String),
+    /// 连接或读写失败
+    Io(String),
+    /// ClickHouse 返回非 200 状态
+    Http { status: u16, body: String },
+    /// 响应无法解析
+    Decode(String),
+}
+
+impl std::fmt::Display for AnalyticsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalyticsError::InvalidSchema(msg) => write!(f, "Invalid schema: {}", msg),
+            AnalyticsError::UnknownColumn(column) => write!(f, "Unknown column: {}", column),
+            AnalyticsError::Io(msg) => write!(f, "ClickHouse I/O error: {}", msg),
+            AnalyticsError::Http { status, body } => {
+                write!(f, "ClickHouse returned {}: {}", status, body)
+            }
+            AnalyticsError::Decode(msg) => write!(f, "Invalid ClickHouse response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AnalyticsError {}
+
+impl From<std::io::Error> for AnalyticsError {
+    fn from(e: std::io::Error) -> Self {
+        AnalyticsError::Io(e.to_string())
+    }
+}
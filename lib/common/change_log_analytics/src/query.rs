@@ -0,0 +1,183 @@
+//! 历史查询
+//!
+//! 构造针对历史表的 `SELECT`，列名按表结构校验、取值统一转义，结果以 `JSONEachRow` 返回
+
+use diff::TableSchema;
+use serde_json::Value;
+
+use crate::AnalyticsError;
+use crate::client::ClickHouseClient;
+use crate::ddl::{qualified_table, quote_ident, quote_literal};
+
+/// 元数据列
+const META_COLUMNS: [&str; 4] = ["_entity_id", "_change_type", "_timestamp", "_sequence"];
+
+/// 历史查询构造器
+///
+/// ```ignore
+/// let rows = HistoryQuery::new("analytics", &Order::table_schema())
+///     .entity("42")
+///     .between(start, end)
+///     .columns(&["price", "status"])?
+///     .fetch(&mut client)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct HistoryQuery {
+    table: String,
+    schema: TableSchema,
+    columns: Vec<String>,
+    conditions: Vec<String>,
+    limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn new(database: &str, schema: &TableSchema) -> Self {
+        Self {
+            table: qualified_table(database, schema),
+            schema: schema.clone(),
+            columns: Vec::new(),
+            conditions: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// 只查询某个实体
+    pub fn entity(mut self, entity_id: &str) -> Self {
+        self.conditions.push(format!("`_entity_id` = {}", quote_literal(entity_id)));
+        self
+    }
+
+    /// 时间范围 `[since, until)`（与变更日志时间戳单位一致）
+    pub fn between(mut self, since: u64, until: u64) -> Self {
+        self.conditions.push(format!("`_timestamp` >= {} AND `_timestamp` < {}", since, until));
+        self
+    }
+
+    /// 只查询某种变更（`Created` / `Updated` / `Deleted`）
+    pub fn change_type(mut self, change_type: &str) -> Self {
+        self.conditions.push(format!("`_change_type` = {}", quote_literal(change_type)));
+        self
+    }
+
+    /// 只查询该字段有变化的行
+    pub fn changed(mut self, field: &str) -> Result<Self, AnalyticsError> {
+        let column = self.column(field)?;
+        self.conditions.push(format!("{} IS NOT NULL", column));
+        Ok(self)
+    }
+
+    /// 选择的实体字段（元数据列总是返回）
+    pub fn columns(mut self, fields: &[&str]) -> Result<Self, AnalyticsError> {
+        for field in fields {
+            let column = self.column(field)?;
+            self.columns.push(column);
+        }
+        Ok(self)
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn column(&self, field: &str) -> Result<String, AnalyticsError> {
+        if META_COLUMNS.contains(&field) || self.schema.has_field(field) {
+            Ok(quote_ident(field))
+        } else {
+            Err(AnalyticsError::UnknownColumn(field.to_string()))
+        }
+    }
+
+    /// 生成 SQL，按 `(_entity_id, _sequence)` 排序
+    pub fn to_sql(&self) -> String {
+        let mut columns: Vec<String> = META_COLUMNS.iter().map(|c| quote_ident(c)).collect();
+        if self.columns.is_empty() {
+            columns.extend(self.schema.fields.iter().map(|f| quote_ident(&f.field_name)));
+        } else {
+            columns.extend(self.columns.iter().cloned());
+        }
+        let mut sql = format!("SELECT {} FROM {}", columns.join(", "), self.table);
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY `_entity_id`, `_sequence`");
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        sql.push_str(" FORMAT JSONEachRow");
+        sql
+    }
+
+    /// 执行查询，每行解析为一个 JSON 对象
+    pub fn fetch<C: ClickHouseClient>(&self, client: &mut C) -> Result<Vec<Value>, AnalyticsError> {
+        parse_rows(&client.execute(&self.to_sql(), "")?)
+    }
+}
+
+/// 某字段的取值时间线（只含该字段发生变化的行）
+pub fn field_timeline(
+    database: &str,
+    schema: &TableSchema,
+    entity_id: &str,
+    field: &str,
+) -> Result<HistoryQuery, AnalyticsError> {
+    HistoryQuery::new(database, schema).entity(entity_id).columns(&[field])?.changed(field)
+}
+
+/// 各变更类型的行数统计
+pub fn change_counts_sql(database: &str, schema: &TableSchema) -> String {
+    format!(
+        "SELECT `_change_type`, count() AS `changes`, uniqExact(`_entity_id`) AS `entities` FROM {} GROUP BY `_change_type` ORDER BY `_change_type` FORMAT JSONEachRow",
+        qualified_table(database, schema)
+    )
+}
+
+/// 解析 `JSONEachRow` 响应
+pub fn parse_rows(body: &str) -> Result<Vec<Value>, AnalyticsError> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| AnalyticsError::Decode(e.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddl::tests::order_schema;
+
+    #[test]
+    fn test_history_query_sql() {
+        let sql = HistoryQuery::new("analytics", &order_schema())
+            .entity("4'2")
+            .between(100, 200)
+            .columns(&["price"])
+            .unwrap()
+            .limit(10)
+            .to_sql();
+        assert_eq!(
+            sql,
+            "SELECT `_entity_id`, `_change_type`, `_timestamp`, `_sequence`, `price` \
+             FROM `analytics`.`order_history` \
+             WHERE `_entity_id` = '4\\'2' AND `_timestamp` >= 100 AND `_timestamp` < 200 \
+             ORDER BY `_entity_id`, `_sequence` LIMIT 10 FORMAT JSONEachRow"
+        );
+
+        assert_eq!(
+            HistoryQuery::new("analytics", &order_schema()).columns(&["price; DROP TABLE x"]).err(),
+            Some(AnalyticsError::UnknownColumn("price; DROP TABLE x".to_string()))
+        );
+        let timeline = field_timeline("analytics", &order_schema(), "42", "filled").unwrap();
+        assert!(timeline.to_sql().contains("`_entity_id` = '42' AND `filled` IS NOT NULL"));
+    }
+
+    #[test]
+    fn test_parse_rows() {
+        let rows =
+            parse_rows("{\"_sequence\":1,\"price\":\"100.5\"}\n{\"_sequence\":2,\"price\":null}\n")
+                .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1]["price"].is_null());
+        assert!(matches!(parse_rows("not json"), Err(AnalyticsError::Decode(_))));
+    }
+}
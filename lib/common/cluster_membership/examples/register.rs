@@ -0,0 +1,61 @@
+//! 注册一个集群成员并打印成员变化
+//!
+//! ```text
+//! # 第一个节点（作为引导节点），记下输出的监听地址
+//! cargo run -p cluster_membership --example register -- --role engine_shard --shard 1 --endpoint http=127.0.0.1:3001
+//! # 其他节点
+//! cargo run -p cluster_membership --example register -- --role engine_shard --shard 2 \
+//!     --endpoint http=127.0.0.1:3002 --bootstrap /ip4/127.0.0.1/tcp/<port>/p2p/<peer_id>
+//! ```
+
+use std::error::Error;
+
+use cluster_membership::{
+    HTTP_ENDPOINT, MemberRole, MembershipConfig, MembershipService, Registration,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
+
+    let mut registration = Registration::new(MemberRole::EngineShard);
+    let mut config = MembershipConfig::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--role" => registration.role = value.parse()?,
+            "--shard" => registration = registration.with_shards([value.parse()?]),
+            "--endpoint" => {
+                let (name, address) =
+                    value.split_once('=').ok_or("endpoint must be <name>=<address>")?;
+                registration = registration.with_endpoint(name, address);
+            }
+            "--listen" => config.listen_addr = value.parse()?,
+            "--bootstrap" => config.bootstrap.push(value.parse()?),
+            _ => return Err(format!("unknown flag {}", flag).into()),
+        }
+    }
+
+    let handle = MembershipService::start(registration, config).await?;
+    for address in handle.listen_addrs() {
+        println!("listening on {}", address);
+    }
+
+    let mut changes = handle.subscribe();
+    while changes.changed().await.is_ok() {
+        let view = changes.borrow_and_update().clone();
+        println!("members ({}):", view.len());
+        for member in view.members() {
+            println!(
+                "  {} {} shards={:?} {}={}",
+                member.node_id,
+                member.role,
+                member.shard_ids,
+                HTTP_ENDPOINT,
+                member.endpoint(HTTP_ENDPOINT).unwrap_or("-")
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! 集群成员发现
+//!
+//! 由 `study/web3` 的 kaemon Kademlia 示例提炼而来。网关与撮合引擎分片启动时注册自己的
+//! 元数据（角色、负责的分片、服务端点），其他成员通过 DHT 动态发现，取代静态后端配置：
+//! - [`Member`] / [`Registration`]: 成员记录与注册信息，JSON 编码后作为 DHT 记录值
+//! - [`MembershipView`]: 本地成员视图，按心跳淘汰离开的成员，可按角色 / 分片查询
+//! - [`MembershipService`]: libp2p Kademlia 服务，周期性发布心跳并发现关注角色的成员，
+//!   成员变化经 [`MembershipHandle::subscribe`] 推送
+//!
+//! 心跳使用发布方墙钟，集群节点需保持时钟同步（误差应远小于心跳有效期）。
+
+mod member;
+mod service;
+mod view;
+
+pub use libp2p::Multiaddr;
+pub use member::{Member, MemberRole, Registration};
+pub use service::{MembershipConfig, MembershipHandle, MembershipService};
+pub use view::MembershipView;
+
+/// 常用端点名：HTTP 服务地址
+pub const HTTP_ENDPOINT: &str = "http";
+
+/// 成员服务错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipError {
+    /// 监听、拨号等网络错误
+    Transport(String),
+    /// 无法解析或与键不一致的成员记录
+    InvalidRecord(String),
+    Config(String),
+}
+
+impl std::fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MembershipError::Transport(msg) => write!(f, "Transport error: {}", msg),
+            MembershipError::InvalidRecord(msg) => write!(f, "Invalid member record: {}", msg),
+            MembershipError::Config(msg) => write!(f, "Invalid config: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {}
@@ -0,0 +1,185 @@
+//! 成员记录
+//!
+//! 每个成员在 DHT 中发布两类条目：
+//! - 记录 `/rustlob/cluster/member/<node_id>`：JSON 编码的 [`Member`]（角色、分片、端点、心跳）
+//! - 提供者 `/rustlob/cluster/role/<role>`：按角色索引，发现方先查提供者再逐个取记录
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::MembershipError;
+
+const ROLE_KEY_PREFIX: &str = "/rustlob/cluster/role/";
+const MEMBER_KEY_PREFIX: &str = "/rustlob/cluster/member/";
+
+/// 成员角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberRole {
+    /// 接入网关
+    Gateway,
+    /// 撮合引擎分片
+    EngineShard,
+}
+
+impl MemberRole {
+    pub const ALL: [MemberRole; 2] = [MemberRole::Gateway, MemberRole::EngineShard];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemberRole::Gateway => "gateway",
+            MemberRole::EngineShard => "engine_shard",
+        }
+    }
+
+    /// 角色索引键（提供者记录）
+    pub fn provider_key(&self) -> Vec<u8> {
+        format!("{}{}", ROLE_KEY_PREFIX, self.as_str()).into_bytes()
+    }
+}
+
+impl fmt::Display for MemberRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MemberRole {
+    type Err = MembershipError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MemberRole::ALL
+            .into_iter()
+            .find(|role| role.as_str() == s)
+            .ok_or_else(|| MembershipError::Config(format!("unknown role: {}", s)))
+    }
+}
+
+/// 注册信息：成员对外声明的元数据，节点 ID 与心跳由服务填写
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    pub role: MemberRole,
+    /// 负责的分片（网关为空）
+    pub shard_ids: Vec<u32>,
+    /// 端点名 → 地址，例如 `http` → `10.0.0.5:3001`
+    pub endpoints: BTreeMap<String, String>,
+}
+
+impl Registration {
+    pub fn new(role: MemberRole) -> Self {
+        Self { role, shard_ids: Vec::new(), endpoints: BTreeMap::new() }
+    }
+
+    pub fn with_shards(mut self, shard_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.shard_ids.extend(shard_ids);
+        self.shard_ids.sort_unstable();
+        self.shard_ids.dedup();
+        self
+    }
+
+    pub fn with_endpoint(mut self, name: impl Into<String>, address: impl Into<String>) -> Self {
+        self.endpoints.insert(name.into(), address.into());
+        self
+    }
+}
+
+/// 成员记录（DHT 记录值）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Member {
+    /// 节点 ID（libp2p PeerId 的字符串形式）
+    pub node_id: String,
+    pub role: MemberRole,
+    pub shard_ids: Vec<u32>,
+    pub endpoints: BTreeMap<String, String>,
+    /// 最近一次心跳（发布方墙钟，Unix 毫秒）
+    pub heartbeat_ms: u64,
+    /// 心跳有效期（毫秒），超过 `heartbeat_ms + ttl_ms` 视为离开
+    pub ttl_ms: u64,
+}
+
+impl Member {
+    pub fn new(node_id: impl Into<String>, registration: Registration, ttl_ms: u64) -> Self {
+        Self {
+            node_id: node_id.into(),
+            role: registration.role,
+            shard_ids: registration.shard_ids,
+            endpoints: registration.endpoints,
+            heartbeat_ms: 0,
+            ttl_ms,
+        }
+    }
+
+    /// 成员记录键
+    pub fn record_key(node_id: &str) -> Vec<u8> {
+        format!("{}{}", MEMBER_KEY_PREFIX, node_id).into_bytes()
+    }
+
+    pub fn endpoint(&self, name: &str) -> Option<&str> {
+        self.endpoints.get(name).map(String::as_str)
+    }
+
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        now_ms > self.heartbeat_ms.saturating_add(self.ttl_ms)
+    }
+
+    /// 除心跳外的元数据是否相同
+    pub fn same_metadata(&self, other: &Member) -> bool {
+        self.node_id == other.node_id
+            && self.role == other.role
+            && self.shard_ids == other.shard_ids
+            && self.endpoints == other.endpoints
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("member record is always serializable")
+    }
+
+    /// 解码记录并校验其与记录键一致（防止以他人的键发布）
+    pub fn decode(key: &[u8], value: &[u8]) -> Result<Self, MembershipError> {
+        let member: Member = serde_json::from_slice(value)
+            .map_err(|e| MembershipError::InvalidRecord(e.to_string()))?;
+        if key != Self::record_key(&member.node_id).as_slice() {
+            return Err(MembershipError::InvalidRecord(format!(
+                "record of {} stored under foreign key",
+                member.node_id
+            )));
+        }
+        Ok(member)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(node_id: &str) -> Member {
+        let registration = Registration::new(MemberRole::EngineShard)
+            .with_shards([2, 1, 2])
+            .with_endpoint("http", "127.0.0.1:3001");
+        Member::new(node_id, registration, 15_000)
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let member = shard("peer-a");
+        assert_eq!(member.shard_ids, vec![1, 2]);
+
+        let decoded = Member::decode(&Member::record_key("peer-a"), &member.encode()).unwrap();
+        assert_eq!(decoded, member);
+        assert_eq!(decoded.endpoint("http"), Some("127.0.0.1:3001"));
+
+        // 记录值中的节点 ID 必须与键一致
+        let forged = Member::decode(&Member::record_key("peer-b"), &member.encode());
+        assert!(matches!(forged, Err(MembershipError::InvalidRecord(_))));
+    }
+
+    #[test]
+    fn test_role_keys_and_parsing() {
+        assert_eq!(MemberRole::EngineShard.provider_key(), b"/rustlob/cluster/role/engine_shard");
+        assert_eq!("gateway".parse::<MemberRole>().unwrap(), MemberRole::Gateway);
+        assert!("matcher".parse::<MemberRole>().is_err());
+    }
+}
@@ -0,0 +1,446 @@
+//! 基于 libp2p Kademlia 的成员服务
+//!
+//! 每个刷新周期：
+//! 1. 更新本地心跳，重新发布成员记录（记录过期时间 = 心跳有效期）
+//! 2. 重新声明本角色的提供者记录
+//! 3. 对关注的角色查询提供者，再逐个获取其成员记录合并进视图
+//! 4. 淘汰心跳过期的成员；与所有节点断开时重拨引导节点
+//!
+//! identify 协议用于获取对端的监听地址并加入 Kademlia 路由表。
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use libp2p::kad::store::MemoryStore;
+use libp2p::kad::{self, GetProvidersOk, GetRecordOk, Mode, QueryResult, Record, RecordKey};
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
+use libp2p::{
+    Multiaddr, StreamProtocol, Swarm, SwarmBuilder, identify, identity, noise, tcp, yamux,
+};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::MembershipError;
+use crate::member::{Member, MemberRole, Registration};
+use crate::view::MembershipView;
+
+/// 集群专用的 Kademlia 协议名，与公共 DHT 隔离
+const KAD_PROTOCOL: StreamProtocol = StreamProtocol::new("/rustlob/cluster/kad/1.0.0");
+const IDENTIFY_PROTOCOL: &str = "/rustlob/cluster/id/1.0.0";
+
+/// 成员服务配置
+#[derive(Debug, Clone)]
+pub struct MembershipConfig {
+    /// 本地监听地址
+    pub listen_addr: Multiaddr,
+    /// 引导节点地址（可带 `/p2p/<peer_id>` 后缀）
+    pub bootstrap: Vec<Multiaddr>,
+    /// 需要发现的角色
+    pub watch_roles: Vec<MemberRole>,
+    /// 心跳与发现的刷新周期
+    pub refresh_interval: Duration,
+    /// 心跳有效期，应为刷新周期的数倍
+    pub member_ttl: Duration,
+    /// 单次 DHT 查询超时
+    pub query_timeout: Duration,
+}
+
+impl Default for MembershipConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "/ip4/0.0.0.0/tcp/0".parse().expect("valid multiaddr"),
+            bootstrap: Vec::new(),
+            watch_roles: MemberRole::ALL.to_vec(),
+            refresh_interval: Duration::from_secs(5),
+            member_ttl: Duration::from_secs(15),
+            query_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl MembershipConfig {
+    pub fn validate(&self) -> Result<(), MembershipError> {
+        if self.refresh_interval.is_zero() || self.member_ttl <= self.refresh_interval {
+            return Err(MembershipError::Config(
+                "member_ttl must exceed a non-zero refresh_interval".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(NetworkBehaviour)]
+struct Behaviour {
+    kad: kad::Behaviour<MemoryStore>,
+    identify: identify::Behaviour,
+}
+
+/// 成员服务句柄，丢弃或调用 [`MembershipHandle::shutdown`] 即退出集群
+pub struct MembershipHandle {
+    local: Member,
+    listen_addrs: Vec<Multiaddr>,
+    view: watch::Receiver<MembershipView>,
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl MembershipHandle {
+    /// 本节点的成员记录（心跳为启动时刻）
+    pub fn local(&self) -> &Member {
+        &self.local
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.local.node_id
+    }
+
+    /// 本地监听地址（带 `/p2p/<peer_id>` 后缀，可直接作为其他节点的引导地址）
+    pub fn listen_addrs(&self) -> &[Multiaddr] {
+        &self.listen_addrs
+    }
+
+    /// 当前成员视图快照
+    pub fn view(&self) -> MembershipView {
+        self.view.borrow().clone()
+    }
+
+    /// 订阅成员变化（加入 / 离开 / 元数据变更）
+    pub fn subscribe(&self) -> watch::Receiver<MembershipView> {
+        self.view.clone()
+    }
+
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        let _ = self.task.await;
+    }
+}
+
+/// 成员服务
+pub struct MembershipService {
+    swarm: Swarm<Behaviour>,
+    local: Member,
+    config: MembershipConfig,
+    view: MembershipView,
+    view_tx: watch::Sender<MembershipView>,
+}
+
+impl MembershipService {
+    /// 以新生成的身份加入集群（须在 tokio 运行时中调用）
+    pub async fn start(
+        registration: Registration,
+        config: MembershipConfig,
+    ) -> Result<MembershipHandle, MembershipError> {
+        Self::start_with_identity(identity::Keypair::generate_ed25519(), registration, config).await
+    }
+
+    /// 以指定身份加入集群，节点 ID 为该身份的 PeerId
+    pub async fn start_with_identity(
+        keypair: identity::Keypair,
+        registration: Registration,
+        config: MembershipConfig,
+    ) -> Result<MembershipHandle, MembershipError> {
+        config.validate()?;
+        let mut swarm = build_swarm(keypair, &config)?;
+        let peer_id = *swarm.local_peer_id();
+        swarm.listen_on(config.listen_addr.clone()).map_err(transport_error)?;
+
+        // 等待监听就绪，使调用方拿到可用的引导地址
+        let first = loop {
+            match swarm.select_next_some().await {
+                SwarmEvent::NewListenAddr { address, .. } => break address,
+                SwarmEvent::ListenerClosed { reason: Err(e), .. }
+                | SwarmEvent::ListenerError { error: e, .. } => {
+                    return Err(transport_error(e));
+                }
+                _ => {}
+            }
+        };
+        let mut listen_addrs: Vec<Multiaddr> = swarm.listeners().cloned().collect();
+        if !listen_addrs.contains(&first) {
+            listen_addrs.push(first);
+        }
+        let listen_addrs =
+            listen_addrs.into_iter().map(|addr| addr.with(Protocol::P2p(peer_id))).collect();
+
+        let mut local =
+            Member::new(peer_id.to_string(), registration, config.member_ttl.as_millis() as u64);
+        local.heartbeat_ms = now_ms();
+        let (view_tx, view) = watch::channel(MembershipView::new());
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let mut service =
+            Self { swarm, local: local.clone(), config, view: MembershipView::new(), view_tx };
+        service.dial_bootstrap();
+        let task = tokio::spawn(service.run(shutdown_rx));
+        info!("🌐 Cluster member {} started", peer_id);
+
+        Ok(MembershipHandle { local, listen_addrs, view, shutdown, task })
+    }
+
+    async fn run(mut self, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(self.config.refresh_interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.refresh(),
+                event = self.swarm.select_next_some() => self.handle_event(event),
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+        debug!("Cluster member {} stopped", self.local.node_id);
+    }
+
+    /// 心跳、重新发布与发现
+    fn refresh(&mut self) {
+        let now = now_ms();
+        self.local.heartbeat_ms = now;
+        let mut changed = self.view.apply(self.local.clone(), now);
+
+        let kad = &mut self.swarm.behaviour_mut().kad;
+        let record = Record {
+            key: RecordKey::new(&Member::record_key(&self.local.node_id)),
+            value: self.local.encode(),
+            publisher: None,
+            expires: Some(Instant::now() + self.config.member_ttl),
+        };
+        if let Err(e) = kad.put_record(record, kad::Quorum::One) {
+            warn!("Failed to store member record: {:?}", e);
+        }
+        if let Err(e) = kad.start_providing(RecordKey::new(&self.local.role.provider_key())) {
+            warn!("Failed to announce role {}: {:?}", self.local.role, e);
+        }
+        for role in &self.config.watch_roles {
+            kad.get_providers(RecordKey::new(&role.provider_key()));
+        }
+
+        for node_id in self.view.expire(now) {
+            info!("👋 Cluster member {} expired", node_id);
+            changed = true;
+        }
+        if changed {
+            self.publish();
+        }
+
+        if self.swarm.connected_peers().next().is_none() {
+            self.dial_bootstrap();
+        } else {
+            // 路由表为空时返回 NoKnownPeers，下个周期重试
+            let _ = self.swarm.behaviour_mut().kad.bootstrap();
+        }
+    }
+
+    fn handle_event(&mut self, event: SwarmEvent<BehaviourEvent>) {
+        match event {
+            SwarmEvent::Behaviour(BehaviourEvent::Identify(identify::Event::Received {
+                peer_id,
+                info,
+                ..
+            })) if info.protocols.contains(&KAD_PROTOCOL) => {
+                for address in info.listen_addrs {
+                    self.swarm.behaviour_mut().kad.add_address(&peer_id, address);
+                }
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::Kad(kad::Event::OutboundQueryProgressed {
+                result,
+                ..
+            })) => self.handle_query(result),
+            SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                debug!("Connected to {} ({})", peer_id, endpoint.get_remote_address());
+                if endpoint.is_dialer() {
+                    let address = endpoint.get_remote_address().clone();
+                    self.swarm.behaviour_mut().kad.add_address(&peer_id, address);
+                }
+            }
+            SwarmEvent::OutgoingConnectionError { error, .. } => {
+                debug!("Dial failed: {}", error);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_query(&mut self, result: QueryResult) {
+        match result {
+            QueryResult::GetProviders(Ok(GetProvidersOk::FoundProviders { providers, .. })) => {
+                let local = self.swarm.local_peer_id().to_string();
+                for provider in providers {
+                    let node_id = provider.to_string();
+                    if node_id != local {
+                        let key = RecordKey::new(&Member::record_key(&node_id));
+                        self.swarm.behaviour_mut().kad.get_record(key);
+                    }
+                }
+            }
+            QueryResult::GetRecord(Ok(GetRecordOk::FoundRecord(peer_record))) => {
+                let record = peer_record.record;
+                match Member::decode(record.key.as_ref(), &record.value) {
+                    // 记录发布者须为成员本身
+                    Ok(member)
+                        if record.publisher.is_none_or(|p| p.to_string() == member.node_id) =>
+                    {
+                        let node_id = member.node_id.clone();
+                        if self.view.apply(member, now_ms()) {
+                            info!("🤝 Cluster member {} joined or changed", node_id);
+                            self.publish();
+                        }
+                    }
+                    Ok(member) => {
+                        warn!("Ignoring member record {} from other publisher", member.node_id)
+                    }
+                    Err(e) => warn!("Ignoring invalid member record: {}", e),
+                }
+            }
+            // 单节点或查询超时属于正常情况，下个周期重试
+            QueryResult::GetProviders(Err(e)) => debug!("Provider lookup failed: {:?}", e),
+            QueryResult::GetRecord(Err(e)) => debug!("Record lookup failed: {:?}", e),
+            QueryResult::PutRecord(Err(e)) => debug!("Record publication failed: {:?}", e),
+            _ => {}
+        }
+    }
+
+    fn dial_bootstrap(&mut self) {
+        for address in self.config.bootstrap.clone() {
+            if let Some(Protocol::P2p(peer_id)) = address.iter().last() {
+                if peer_id == *self.swarm.local_peer_id() {
+                    continue;
+                }
+                self.swarm.behaviour_mut().kad.add_address(&peer_id, address.clone());
+            }
+            if let Err(e) = self.swarm.dial(address.clone()) {
+                debug!("Failed to dial bootstrap {}: {}", address, e);
+            }
+        }
+    }
+
+    fn publish(&self) {
+        self.view_tx.send_replace(self.view.clone());
+    }
+}
+
+fn build_swarm(
+    keypair: identity::Keypair,
+    config: &MembershipConfig,
+) -> Result<Swarm<Behaviour>, MembershipError> {
+    let ttl = config.member_ttl;
+    let query_timeout = config.query_timeout;
+    let mut swarm = SwarmBuilder::with_existing_identity(keypair)
+        .with_tokio()
+        .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)
+        .map_err(transport_error)?
+        .with_behaviour(|key| {
+            let peer_id = key.public().to_peer_id();
+            let mut kad_config = kad::Config::new(KAD_PROTOCOL);
+            kad_config
+                .set_query_timeout(query_timeout)
+                .set_record_ttl(Some(ttl))
+                .set_provider_record_ttl(Some(ttl))
+                // 由刷新周期主动重新发布，关闭库内的长周期发布任务
+                .set_publication_interval(None)
+                .set_provider_publication_interval(None);
+            let kad = kad::Behaviour::with_config(peer_id, MemoryStore::new(peer_id), kad_config);
+            let identify = identify::Behaviour::new(identify::Config::new(
+                IDENTIFY_PROTOCOL.to_string(),
+                key.public(),
+            ));
+            Behaviour { kad, identify }
+        })
+        .map_err(transport_error)?
+        .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(60)))
+        .build();
+    swarm.behaviour_mut().kad.set_mode(Some(Mode::Server));
+    Ok(swarm)
+}
+
+fn transport_error(e: impl std::fmt::Display) -> MembershipError {
+    MembershipError::Transport(e.to_string())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// 便于日志输出的本地节点标识
+impl std::fmt::Debug for MembershipHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MembershipHandle")
+            .field("node_id", &self.local.node_id)
+            .field("listen_addrs", &self.listen_addrs)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HTTP_ENDPOINT;
+
+    fn test_config(bootstrap: Vec<Multiaddr>) -> MembershipConfig {
+        MembershipConfig {
+            listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
+            bootstrap,
+            refresh_interval: Duration::from_millis(200),
+            member_ttl: Duration::from_millis(1_000),
+            query_timeout: Duration::from_secs(2),
+            ..MembershipConfig::default()
+        }
+    }
+
+    fn shard(shard_id: u32, port: u16) -> Registration {
+        Registration::new(MemberRole::EngineShard)
+            .with_shards([shard_id])
+            .with_endpoint(HTTP_ENDPOINT, format!("127.0.0.1:{}", port))
+    }
+
+    /// 等待视图满足条件
+    async fn wait_for(handle: &MembershipHandle, condition: impl Fn(&MembershipView) -> bool) {
+        let mut changes = handle.subscribe();
+        tokio::time::timeout(Duration::from_secs(15), async {
+            loop {
+                if condition(&changes.borrow_and_update()) {
+                    return;
+                }
+                changes.changed().await.unwrap();
+            }
+        })
+        .await
+        .expect("membership view never converged");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_discover_and_expire_members() {
+        let gateway = MembershipService::start(
+            Registration::new(MemberRole::Gateway).with_endpoint(HTTP_ENDPOINT, "127.0.0.1:8080"),
+            test_config(Vec::new()),
+        )
+        .await
+        .unwrap();
+        let bootstrap = gateway.listen_addrs().to_vec();
+        let shard1 =
+            MembershipService::start(shard(1, 3001), test_config(bootstrap.clone())).await.unwrap();
+        let shard2 =
+            MembershipService::start(shard(2, 3002), test_config(bootstrap)).await.unwrap();
+
+        wait_for(&gateway, |view| view.shard_endpoints(HTTP_ENDPOINT).len() == 2).await;
+        let shards = gateway.view().shard_endpoints(HTTP_ENDPOINT);
+        assert_eq!(shards[&1], vec!["127.0.0.1:3001"]);
+        assert_eq!(shards[&2], vec!["127.0.0.1:3002"]);
+        // 分片之间经引导节点互相发现
+        wait_for(&shard2, |view| view.get(shard1.node_id()).is_some()).await;
+
+        // 分片离开后在心跳有效期内被淘汰
+        let departed = shard1.node_id().to_string();
+        shard1.shutdown().await;
+        wait_for(&gateway, |view| view.get(&departed).is_none()).await;
+        assert_eq!(
+            gateway.view().shard_endpoints(HTTP_ENDPOINT).keys().copied().collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        shard2.shutdown().await;
+        gateway.shutdown().await;
+    }
+}
@@ -0,0 +1,141 @@
+//! 成员视图
+//!
+//! 本地维护的已知成员集合。只接受心跳更新的记录，按心跳有效期淘汰离开的成员；
+//! 仅心跳刷新不算成员变化，订阅方只在加入 / 离开 / 元数据变更时收到通知。
+
+use std::collections::BTreeMap;
+
+use crate::member::{Member, MemberRole};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MembershipView {
+    members: BTreeMap<String, Member>,
+}
+
+impl MembershipView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 合并一条成员记录，返回成员集合是否发生变化
+    ///
+    /// 已过期或心跳不比现有记录新的记录被忽略。
+    pub fn apply(&mut self, member: Member, now_ms: u64) -> bool {
+        if member.is_expired(now_ms) {
+            return false;
+        }
+        match self.members.get_mut(&member.node_id) {
+            Some(existing) if existing.heartbeat_ms >= member.heartbeat_ms => false,
+            Some(existing) => {
+                let changed = !existing.same_metadata(&member);
+                *existing = member;
+                changed
+            }
+            None => {
+                self.members.insert(member.node_id.clone(), member);
+                true
+            }
+        }
+    }
+
+    /// 淘汰心跳过期的成员，返回被淘汰的节点 ID
+    pub fn expire(&mut self, now_ms: u64) -> Vec<String> {
+        let expired: Vec<String> = self
+            .members
+            .values()
+            .filter(|member| member.is_expired(now_ms))
+            .map(|member| member.node_id.clone())
+            .collect();
+        for node_id in &expired {
+            self.members.remove(node_id);
+        }
+        expired
+    }
+
+    pub fn get(&self, node_id: &str) -> Option<&Member> {
+        self.members.get(node_id)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = &Member> {
+        self.members.values()
+    }
+
+    pub fn with_role(&self, role: MemberRole) -> impl Iterator<Item = &Member> {
+        self.members.values().filter(move |member| member.role == role)
+    }
+
+    /// 引擎分片 → 指定端点地址列表（去重、有序）
+    pub fn shard_endpoints(&self, endpoint: &str) -> BTreeMap<u32, Vec<String>> {
+        let mut shards: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for member in self.with_role(MemberRole::EngineShard) {
+            let Some(address) = member.endpoint(endpoint) else { continue };
+            for &shard_id in &member.shard_ids {
+                shards.entry(shard_id).or_default().push(address.to_string());
+            }
+        }
+        for addresses in shards.values_mut() {
+            addresses.sort();
+            addresses.dedup();
+        }
+        shards
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::member::Registration;
+
+    fn member(node_id: &str, registration: Registration, heartbeat_ms: u64) -> Member {
+        let mut member = Member::new(node_id, registration, 1_000);
+        member.heartbeat_ms = heartbeat_ms;
+        member
+    }
+
+    fn shard(node_id: &str, shards: &[u32], http: &str, heartbeat_ms: u64) -> Member {
+        let registration = Registration::new(MemberRole::EngineShard)
+            .with_shards(shards.iter().copied())
+            .with_endpoint("http", http);
+        member(node_id, registration, heartbeat_ms)
+    }
+
+    #[test]
+    fn test_apply_only_reports_membership_changes() {
+        let mut view = MembershipView::new();
+        assert!(view.apply(shard("a", &[1], "10.0.0.1:3001", 100), 100));
+        // 心跳刷新不算变化，旧心跳被忽略
+        assert!(!view.apply(shard("a", &[1], "10.0.0.1:3001", 200), 200));
+        assert!(!view.apply(shard("a", &[1, 2], "10.0.0.1:3001", 150), 200));
+        assert_eq!(view.get("a").unwrap().heartbeat_ms, 200);
+        // 元数据变更
+        assert!(view.apply(shard("a", &[1, 2], "10.0.0.1:3001", 300), 300));
+        // 已过期的记录不会加入
+        assert!(!view.apply(shard("b", &[3], "10.0.0.2:3001", 0), 5_000));
+        assert_eq!(view.len(), 1);
+    }
+
+    #[test]
+    fn test_expire_and_shard_endpoints() {
+        let mut view = MembershipView::new();
+        view.apply(shard("a", &[1, 2], "10.0.0.1:3001", 1_000), 1_000);
+        view.apply(shard("b", &[2], "10.0.0.2:3001", 1_500), 1_500);
+        view.apply(member("gw", Registration::new(MemberRole::Gateway), 1_500), 1_500);
+
+        let shards = view.shard_endpoints("http");
+        assert_eq!(shards[&1], vec!["10.0.0.1:3001"]);
+        assert_eq!(shards[&2], vec!["10.0.0.1:3001", "10.0.0.2:3001"]);
+        assert_eq!(view.with_role(MemberRole::Gateway).count(), 1);
+
+        assert_eq!(view.expire(2_200), vec!["a".to_string()]);
+        assert_eq!(view.shard_endpoints("http").keys().copied().collect::<Vec<_>>(), vec![2]);
+        assert!(view.shard_endpoints("grpc").is_empty());
+    }
+}
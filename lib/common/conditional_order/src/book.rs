@@ -0,0 +1,656 @@
+//! 单交易对的条件单监控索引
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Bound;
+
+use crate::ConditionalId;
+use crate::trigger::{ConditionalActions, Direction, Trail, TriggerPrice, TriggerSpec};
+
+/// OCO 的另一腿
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sibling {
+    /// 同一监控索引中的条件单
+    Conditional(ConditionalId),
+    /// 索引外的订单（如已挂在订单簿上的限价单）
+    External(u64),
+}
+
+/// 已触发的条件单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fired<P, A> {
+    pub id: ConditionalId,
+    /// 触发价
+    pub trigger_price: P,
+    /// 引起触发的最新价
+    pub last_price: P,
+    /// 下单时登记的动作
+    pub action: A,
+}
+
+/// 收集式动作事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionalEvent<P, A> {
+    Submit(Fired<P, A>),
+    CancelSibling { id: ConditionalId, sibling: Sibling },
+    AdjustTrail { id: ConditionalId, trigger_price: P },
+}
+
+impl<P, A> ConditionalActions<P, A> for Vec<ConditionalEvent<P, A>> {
+    fn submit(&mut self, fired: Fired<P, A>) {
+        self.push(ConditionalEvent::Submit(fired));
+    }
+
+    fn cancel_sibling(&mut self, id: ConditionalId, sibling: Sibling) {
+        self.push(ConditionalEvent::CancelSibling { id, sibling });
+    }
+
+    fn adjust_trail(&mut self, id: ConditionalId, trigger_price: P) {
+        self.push(ConditionalEvent::AdjustTrail { id, trigger_price });
+    }
+}
+
+/// 条件单错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionalError {
+    /// 条件单不存在（或已触发/已撤销）
+    UnknownId(ConditionalId),
+    /// 条件单ID重复
+    DuplicateId(ConditionalId),
+    /// OCO 两腿不属于同一交易对
+    SymbolMismatch,
+    /// 条件单已有 OCO 另一腿
+    AlreadyLinked(ConditionalId),
+}
+
+impl fmt::Display for ConditionalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionalError::UnknownId(id) => write!(f, "Unknown conditional order: {}", id),
+            ConditionalError::DuplicateId(id) => write!(f, "Duplicate conditional order: {}", id),
+            ConditionalError::SymbolMismatch => write!(f, "OCO legs must share a symbol"),
+            ConditionalError::AlreadyLinked(id) => {
+                write!(f, "Conditional order {} is already linked", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConditionalError {}
+
+/// 条件单状态（[`ConditionalBook::export`] 导出、[`ConditionalBook::import`] 恢复）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionalState<P> {
+    /// 固定触发价，位于触发阶梯
+    Armed { direction: Direction, trigger: P },
+    /// 追踪中：参考价位于最高价/最低价索引，触发价位于触发阶梯
+    Tracking { direction: Direction, trail: Trail<P>, reference: P, trigger: P },
+    /// 等待价格到达激活价
+    Pending { direction: Direction, trail: Trail<P>, activation: P },
+    /// 无激活价且尚无成交价，下一次价格更新时开始追踪
+    Unanchored { direction: Direction, trail: Trail<P> },
+}
+
+#[derive(Debug)]
+struct Entry<P, A> {
+    state: ConditionalState<P>,
+    action: A,
+    sibling: Option<Sibling>,
+}
+
+/// 导出的单个条件单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionalEntry<P, A> {
+    pub id: ConditionalId,
+    pub state: ConditionalState<P>,
+    pub action: A,
+    pub sibling: Option<Sibling>,
+}
+
+/// 单交易对的条件单监控索引
+///
+/// 所有索引均为 `(价格, ID)` 有序集合，一次价格更新只取越过该价格的区间：
+/// - `rising` / `falling`：两个方向的触发阶梯
+/// - `activate_up` / `activate_down`：追踪止损的激活价
+/// - `peaks` / `troughs`：追踪止损的参考最高价/最低价
+#[derive(Debug)]
+pub struct ConditionalBook<P, A> {
+    rising: BTreeSet<(P, ConditionalId)>,
+    falling: BTreeSet<(P, ConditionalId)>,
+    activate_up: BTreeSet<(P, ConditionalId)>,
+    activate_down: BTreeSet<(P, ConditionalId)>,
+    peaks: BTreeSet<(P, ConditionalId)>,
+    troughs: BTreeSet<(P, ConditionalId)>,
+    unanchored: Vec<ConditionalId>,
+    entries: HashMap<ConditionalId, Entry<P, A>>,
+    last_price: Option<P>,
+}
+
+impl<P: TriggerPrice, A> Default for ConditionalBook<P, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: TriggerPrice, A> ConditionalBook<P, A> {
+    pub fn new() -> Self {
+        Self {
+            rising: BTreeSet::new(),
+            falling: BTreeSet::new(),
+            activate_up: BTreeSet::new(),
+            activate_down: BTreeSet::new(),
+            peaks: BTreeSet::new(),
+            troughs: BTreeSet::new(),
+            unanchored: Vec::new(),
+            entries: HashMap::new(),
+            last_price: None,
+        }
+    }
+
+    /// 登记条件单
+    ///
+    /// 固定触发价的条件单在下一次价格更新时评估；追踪止损无激活价（或激活价已被最新价越过）时
+    /// 以最新价为参考价立即开始追踪
+    pub fn insert(
+        &mut self,
+        id: ConditionalId,
+        spec: TriggerSpec<P>,
+        action: A,
+    ) -> Result<(), ConditionalError> {
+        if self.entries.contains_key(&id) {
+            return Err(ConditionalError::DuplicateId(id));
+        }
+        let state = match spec {
+            TriggerSpec::Stop { direction, price } => {
+                ConditionalState::Armed { direction, trigger: price }
+            }
+            TriggerSpec::Trailing { direction, trail, activation } => {
+                match (activation, self.last_price) {
+                    // 跟踪最高价的追踪止损需先涨至激活价，反之亦然
+                    (Some(activation), last)
+                        if !last
+                            .is_some_and(|last| direction.opposite().crossed(last, activation)) =>
+                    {
+                        ConditionalState::Pending { direction, trail, activation }
+                    }
+                    (_, Some(reference)) => Self::tracking(direction, trail, reference),
+                    (_, None) => ConditionalState::Unanchored { direction, trail },
+                }
+            }
+        };
+        self.index(id, &state);
+        self.entries.insert(id, Entry { state, action, sibling: None });
+        Ok(())
+    }
+
+    /// 将两个条件单关联为 OCO：一腿触发时另一腿被撤销
+    pub fn link_oco(&mut self, a: ConditionalId, b: ConditionalId) -> Result<(), ConditionalError> {
+        for id in [a, b] {
+            match self.entries.get(&id) {
+                None => return Err(ConditionalError::UnknownId(id)),
+                Some(entry) if entry.sibling.is_some() => {
+                    return Err(ConditionalError::AlreadyLinked(id));
+                }
+                Some(_) => {}
+            }
+        }
+        if a == b {
+            return Err(ConditionalError::AlreadyLinked(a));
+        }
+        self.set_sibling(a, Sibling::Conditional(b));
+        self.set_sibling(b, Sibling::Conditional(a));
+        Ok(())
+    }
+
+    /// 将条件单与索引外的订单关联为 OCO
+    ///
+    /// 外部订单成交或撤销时由调用方 [`cancel`](Self::cancel) 本条件单
+    pub fn set_external_sibling(
+        &mut self,
+        id: ConditionalId,
+        external: u64,
+    ) -> Result<(), ConditionalError> {
+        match self.entries.get_mut(&id) {
+            None => Err(ConditionalError::UnknownId(id)),
+            Some(entry) if entry.sibling.is_some() => Err(ConditionalError::AlreadyLinked(id)),
+            Some(entry) => {
+                entry.sibling = Some(Sibling::External(external));
+                Ok(())
+            }
+        }
+    }
+
+    /// 撤销条件单，返回其动作
+    ///
+    /// OCO 另一腿仅解除关联，不会被撤销
+    pub fn cancel(&mut self, id: ConditionalId) -> Option<A> {
+        let entry = self.remove(id)?;
+        if let Some(Sibling::Conditional(sibling)) = entry.sibling {
+            if let Some(sibling) = self.entries.get_mut(&sibling) {
+                sibling.sibling = None;
+            }
+        }
+        Some(entry.action)
+    }
+
+    /// 当前触发价（追踪止损未激活时为 `None`）
+    pub fn trigger_price(&self, id: ConditionalId) -> Option<P> {
+        match self.entries.get(&id)?.state {
+            ConditionalState::Armed { trigger, .. }
+            | ConditionalState::Tracking { trigger, .. } => Some(trigger),
+            ConditionalState::Pending { .. } | ConditionalState::Unanchored { .. } => None,
+        }
+    }
+
+    pub fn contains(&self, id: ConditionalId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 最近一次价格更新
+    pub fn last_price(&self) -> Option<P> {
+        self.last_price
+    }
+
+    /// 导出全部条件单（按ID升序），与 [`last_price`](Self::last_price) 一起可完整恢复索引
+    pub fn export(&self) -> Vec<ConditionalEntry<P, A>>
+    where
+        A: Clone,
+    {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(id, entry)| ConditionalEntry {
+                id: *id,
+                state: entry.state,
+                action: entry.action.clone(),
+                sibling: entry.sibling,
+            })
+            .collect();
+        entries.sort_unstable_by_key(|entry| entry.id);
+        entries
+    }
+
+    /// 由导出的条件单重建索引
+    ///
+    /// ID 重复或 OCO 另一腿不在导出中时报错
+    pub fn import(
+        last_price: Option<P>,
+        entries: Vec<ConditionalEntry<P, A>>,
+    ) -> Result<Self, ConditionalError> {
+        let mut book = Self::new();
+        book.last_price = last_price;
+        for entry in entries {
+            if book.entries.contains_key(&entry.id) {
+                return Err(ConditionalError::DuplicateId(entry.id));
+            }
+            book.index(entry.id, &entry.state);
+            book.entries.insert(
+                entry.id,
+                Entry { state: entry.state, action: entry.action, sibling: entry.sibling },
+            );
+        }
+        for (id, entry) in &book.entries {
+            if let Some(Sibling::Conditional(sibling)) = entry.sibling {
+                if book.entries.get(&sibling).and_then(|other| other.sibling)
+                    != Some(Sibling::Conditional(*id))
+                {
+                    return Err(ConditionalError::UnknownId(sibling));
+                }
+            }
+        }
+        Ok(book)
+    }
+
+    /// 处理一次价格更新
+    ///
+    /// 依次：激活追踪止损 → 移动追踪参考价 → 触发越过该价格的条件单。
+    /// 同一次更新中按价格越过触发价的先后顺序触发
+    pub fn on_price<X: ConditionalActions<P, A>>(&mut self, price: P, actions: &mut X) {
+        self.last_price = Some(price);
+
+        let mut activated = std::mem::take(&mut self.unanchored);
+        activated.extend(Self::take_range(&mut self.activate_up, ..=(price, ConditionalId::MAX)));
+        activated.extend(Self::take_range(&mut self.activate_down, (price, ConditionalId::MIN)..));
+        for id in activated {
+            let Some(entry) = self.entries.get_mut(&id) else {
+                continue;
+            };
+            let (ConditionalState::Pending { direction, trail, .. }
+            | ConditionalState::Unanchored { direction, trail }) = entry.state
+            else {
+                continue;
+            };
+            let state = Self::tracking(direction, trail, price);
+            entry.state = state;
+            self.index(id, &state);
+            if let ConditionalState::Tracking { trigger, .. } = state {
+                actions.adjust_trail(id, trigger);
+            }
+        }
+
+        let mut moved = Self::take_range(&mut self.peaks, ..(price, ConditionalId::MIN));
+        moved.extend(Self::take_range(
+            &mut self.troughs,
+            (Bound::Excluded((price, ConditionalId::MAX)), Bound::Unbounded),
+        ));
+        for id in moved {
+            if let Some(trigger) = self.move_reference(id, price) {
+                actions.adjust_trail(id, trigger);
+            }
+        }
+
+        let mut fired = Self::take_range(&mut self.rising, ..=(price, ConditionalId::MAX));
+        let mut falling = Self::take_range(&mut self.falling, (price, ConditionalId::MIN)..);
+        falling.reverse();
+        fired.extend(falling);
+        // 取出的区间已不在阶梯中，其余索引由 remove 清理
+        for id in fired {
+            let Some(entry) = self.remove(id) else {
+                continue;
+            };
+            let trigger_price = match entry.state {
+                ConditionalState::Armed { trigger, .. }
+                | ConditionalState::Tracking { trigger, .. } => trigger,
+                ConditionalState::Pending { .. } | ConditionalState::Unanchored { .. } => continue,
+            };
+            if let Some(sibling) = entry.sibling {
+                if let Sibling::Conditional(sibling_id) = sibling {
+                    self.remove(sibling_id);
+                }
+                actions.cancel_sibling(id, sibling);
+            }
+            actions.submit(Fired { id, trigger_price, last_price: price, action: entry.action });
+        }
+    }
+
+    fn tracking(direction: Direction, trail: Trail<P>, reference: P) -> ConditionalState<P> {
+        ConditionalState::Tracking {
+            direction,
+            trail,
+            reference,
+            trigger: trail.trigger_from(direction, reference),
+        }
+    }
+
+    /// 参考价移动到 `price`，触发价只收紧不放宽；返回新的触发价
+    fn move_reference(&mut self, id: ConditionalId, price: P) -> Option<P> {
+        let entry = self.entries.get_mut(&id)?;
+        let ConditionalState::Tracking { direction, trail, reference, trigger } = &mut entry.state
+        else {
+            return None;
+        };
+        *reference = price;
+        let candidate = trail.trigger_from(*direction, price);
+        let (ladder, references) = match direction {
+            Direction::Falling => (&mut self.falling, &mut self.peaks),
+            Direction::Rising => (&mut self.rising, &mut self.troughs),
+        };
+        references.insert((price, id));
+        let tightened = match direction {
+            Direction::Falling => candidate > *trigger,
+            Direction::Rising => candidate < *trigger,
+        };
+        if !tightened {
+            return None;
+        }
+        ladder.remove(&(*trigger, id));
+        ladder.insert((candidate, id));
+        *trigger = candidate;
+        Some(candidate)
+    }
+
+    fn set_sibling(&mut self, id: ConditionalId, sibling: Sibling) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.sibling = Some(sibling);
+        }
+    }
+
+    fn index(&mut self, id: ConditionalId, state: &ConditionalState<P>) {
+        match *state {
+            ConditionalState::Armed { direction, trigger } => {
+                self.ladder(direction).insert((trigger, id));
+            }
+            ConditionalState::Tracking { direction, reference, trigger, .. } => {
+                self.ladder(direction).insert((trigger, id));
+                self.references(direction).insert((reference, id));
+            }
+            ConditionalState::Pending { direction, activation, .. } => {
+                self.activations(direction).insert((activation, id));
+            }
+            ConditionalState::Unanchored { .. } => self.unanchored.push(id),
+        }
+    }
+
+    fn remove(&mut self, id: ConditionalId) -> Option<Entry<P, A>> {
+        let entry = self.entries.remove(&id)?;
+        match entry.state {
+            ConditionalState::Armed { direction, trigger } => {
+                self.ladder(direction).remove(&(trigger, id));
+            }
+            ConditionalState::Tracking { direction, reference, trigger, .. } => {
+                self.ladder(direction).remove(&(trigger, id));
+                self.references(direction).remove(&(reference, id));
+            }
+            ConditionalState::Pending { direction, activation, .. } => {
+                self.activations(direction).remove(&(activation, id));
+            }
+            ConditionalState::Unanchored { .. } => self.unanchored.retain(|other| *other != id),
+        }
+        Some(entry)
+    }
+
+    fn ladder(&mut self, direction: Direction) -> &mut BTreeSet<(P, ConditionalId)> {
+        match direction {
+            Direction::Rising => &mut self.rising,
+            Direction::Falling => &mut self.falling,
+        }
+    }
+
+    /// 跟踪最高价（`Falling`）或最低价（`Rising`）
+    fn references(&mut self, direction: Direction) -> &mut BTreeSet<(P, ConditionalId)> {
+        match direction {
+            Direction::Falling => &mut self.peaks,
+            Direction::Rising => &mut self.troughs,
+        }
+    }
+
+    /// `Falling` 涨至激活价、`Rising` 跌至激活价时激活
+    fn activations(&mut self, direction: Direction) -> &mut BTreeSet<(P, ConditionalId)> {
+        match direction {
+            Direction::Falling => &mut self.activate_up,
+            Direction::Rising => &mut self.activate_down,
+        }
+    }
+
+    /// 取出并移除区间内的ID（按价格升序）
+    fn take_range<R>(set: &mut BTreeSet<(P, ConditionalId)>, range: R) -> Vec<ConditionalId>
+    where
+        R: std::ops::RangeBounds<(P, ConditionalId)>,
+    {
+        let keys: Vec<_> = set.range(range).copied().collect();
+        for key in &keys {
+            set.remove(key);
+        }
+        keys.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Events = Vec<ConditionalEvent<u64, &'static str>>;
+
+    fn submitted(events: &Events) -> Vec<(ConditionalId, u64)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ConditionalEvent::Submit(fired) => Some((fired.id, fired.trigger_price)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn stop(direction: Direction, price: u64) -> TriggerSpec<u64> {
+        TriggerSpec::Stop { direction, price }
+    }
+
+    #[test]
+    fn test_stop_ladders_fire_closest_first() {
+        let mut book = ConditionalBook::new();
+        book.insert(1, stop(Direction::Falling, 95), "sl-95").unwrap();
+        book.insert(2, stop(Direction::Falling, 90), "sl-90").unwrap();
+        book.insert(3, stop(Direction::Rising, 110), "tp-110").unwrap();
+        assert_eq!(
+            book.insert(3, stop(Direction::Rising, 120), "dup"),
+            Err(ConditionalError::DuplicateId(3))
+        );
+
+        let mut events = Events::new();
+        book.on_price(100, &mut events);
+        assert!(events.is_empty());
+
+        book.on_price(89, &mut events);
+        assert_eq!(submitted(&events), vec![(1, 95), (2, 90)]);
+        assert_eq!(book.len(), 1);
+
+        events.clear();
+        book.on_price(110, &mut events);
+        assert_eq!(submitted(&events), vec![(3, 110)]);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_oco_cancels_sibling() {
+        let mut book = ConditionalBook::new();
+        book.insert(1, stop(Direction::Falling, 90), "sl").unwrap();
+        book.insert(2, stop(Direction::Rising, 110), "tp").unwrap();
+        book.link_oco(1, 2).unwrap();
+        assert_eq!(book.link_oco(1, 2), Err(ConditionalError::AlreadyLinked(1)));
+
+        let mut events = Events::new();
+        book.on_price(111, &mut events);
+        assert_eq!(
+            events[0],
+            ConditionalEvent::CancelSibling { id: 2, sibling: Sibling::Conditional(1) }
+        );
+        assert_eq!(submitted(&events), vec![(2, 110)]);
+        assert!(book.is_empty());
+
+        book.insert(3, stop(Direction::Falling, 90), "sl").unwrap();
+        book.set_external_sibling(3, 77).unwrap();
+        events.clear();
+        book.on_price(80, &mut events);
+        assert_eq!(
+            events[0],
+            ConditionalEvent::CancelSibling { id: 3, sibling: Sibling::External(77) }
+        );
+    }
+
+    #[test]
+    fn test_cancel_unlinks_sibling() {
+        let mut book = ConditionalBook::new();
+        book.insert(1, stop(Direction::Falling, 90), "sl").unwrap();
+        book.insert(2, stop(Direction::Rising, 110), "tp").unwrap();
+        book.link_oco(1, 2).unwrap();
+        assert_eq!(book.cancel(1), Some("sl"));
+        assert_eq!(book.cancel(1), None);
+
+        let mut events = Events::new();
+        book.on_price(80, &mut events);
+        assert!(events.is_empty());
+        book.on_price(120, &mut events);
+        assert_eq!(submitted(&events), vec![(2, 110)]);
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_peak() {
+        let mut book = ConditionalBook::new();
+        let mut events = Events::new();
+        book.on_price(100, &mut events);
+        let spec = TriggerSpec::Trailing {
+            direction: Direction::Falling,
+            trail: Trail::Absolute(10),
+            activation: None,
+        };
+        book.insert(1, spec, "trail").unwrap();
+        assert_eq!(book.trigger_price(1), Some(90));
+
+        book.on_price(120, &mut events);
+        assert_eq!(events, vec![ConditionalEvent::AdjustTrail { id: 1, trigger_price: 110 }]);
+        // 回落不放宽触发价
+        book.on_price(115, &mut events);
+        assert_eq!(book.trigger_price(1), Some(110));
+
+        book.on_price(110, &mut events);
+        assert_eq!(submitted(&events), vec![(1, 110)]);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_trailing_stop_activation() {
+        let mut book = ConditionalBook::new();
+        let spec = TriggerSpec::Trailing {
+            direction: Direction::Rising,
+            trail: Trail::Bps(100),
+            activation: Some(1000),
+        };
+        book.insert(1, spec, "short-trail").unwrap();
+        assert_eq!(book.trigger_price(1), None);
+
+        let mut events = Events::new();
+        book.on_price(1100, &mut events);
+        assert_eq!(book.trigger_price(1), None);
+
+        book.on_price(1000, &mut events);
+        assert_eq!(book.trigger_price(1), Some(1010));
+        book.on_price(900, &mut events);
+        assert_eq!(book.trigger_price(1), Some(909));
+        book.on_price(909, &mut events);
+        assert_eq!(submitted(&events), vec![(1, 909)]);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut book = ConditionalBook::new();
+        let mut events = Events::new();
+        book.on_price(100, &mut events);
+        book.insert(1, stop(Direction::Falling, 90), "sl").unwrap();
+        book.insert(2, stop(Direction::Rising, 110), "tp").unwrap();
+        book.link_oco(1, 2).unwrap();
+        let trailing = TriggerSpec::Trailing {
+            direction: Direction::Falling,
+            trail: Trail::Absolute(5),
+            activation: Some(105),
+        };
+        book.insert(3, trailing, "trail").unwrap();
+
+        let exported = book.export();
+        assert_eq!(exported.iter().map(|entry| entry.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        let mut restored = ConditionalBook::import(book.last_price(), exported.clone()).unwrap();
+        assert_eq!(restored.export(), exported);
+
+        // 恢复后的索引与原索引对同一价格序列的反应一致
+        let mut restored_events = Events::new();
+        for price in [106, 112] {
+            book.on_price(price, &mut events);
+            restored.on_price(price, &mut restored_events);
+        }
+        assert_eq!(events, restored_events);
+        assert_eq!(submitted(&events), vec![(2, 110)]);
+
+        // OCO 另一腿缺失
+        assert_eq!(
+            ConditionalBook::import(None, exported[..1].to_vec()).err(),
+            Some(ConditionalError::UnknownId(2))
+        );
+    }
+}
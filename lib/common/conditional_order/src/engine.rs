@@ -0,0 +1,160 @@
+//! 多交易对条件单引擎
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ConditionalId;
+use crate::book::{ConditionalBook, ConditionalError, Fired, Sibling};
+use crate::trigger::{ConditionalActions, TriggerPrice, TriggerSpec};
+
+/// 多交易对条件单引擎
+///
+/// 每个交易对一个 [`ConditionalBook`]，条件单ID全局分配
+#[derive(Debug)]
+pub struct ConditionalEngine<S, P, A> {
+    books: HashMap<S, ConditionalBook<P, A>>,
+    symbols: HashMap<ConditionalId, S>,
+    next_id: ConditionalId,
+}
+
+impl<S: Copy + Eq + Hash, P: TriggerPrice, A> Default for ConditionalEngine<S, P, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Copy + Eq + Hash, P: TriggerPrice, A> ConditionalEngine<S, P, A> {
+    pub fn new() -> Self {
+        Self { books: HashMap::new(), symbols: HashMap::new(), next_id: 1 }
+    }
+
+    /// 登记条件单，返回条件单ID
+    pub fn submit(&mut self, symbol: S, spec: TriggerSpec<P>, action: A) -> ConditionalId {
+        let id = self.next_id;
+        self.next_id += 1;
+        // ID 单调分配，不会重复
+        let _ = self.books.entry(symbol).or_default().insert(id, spec, action);
+        self.symbols.insert(id, symbol);
+        id
+    }
+
+    /// 将同一交易对的两个条件单关联为 OCO
+    pub fn link_oco(&mut self, a: ConditionalId, b: ConditionalId) -> Result<(), ConditionalError> {
+        let symbol = *self.symbols.get(&a).ok_or(ConditionalError::UnknownId(a))?;
+        if *self.symbols.get(&b).ok_or(ConditionalError::UnknownId(b))? != symbol {
+            return Err(ConditionalError::SymbolMismatch);
+        }
+        self.book_mut(symbol).link_oco(a, b)
+    }
+
+    /// 将条件单与索引外的订单关联为 OCO
+    pub fn set_external_sibling(
+        &mut self,
+        id: ConditionalId,
+        external: u64,
+    ) -> Result<(), ConditionalError> {
+        let symbol = *self.symbols.get(&id).ok_or(ConditionalError::UnknownId(id))?;
+        self.book_mut(symbol).set_external_sibling(id, external)
+    }
+
+    /// 撤销条件单，返回其动作
+    pub fn cancel(&mut self, id: ConditionalId) -> Option<A> {
+        let symbol = self.symbols.remove(&id)?;
+        self.book_mut(symbol).cancel(id)
+    }
+
+    /// 处理交易对的一次价格更新
+    pub fn on_price<X: ConditionalActions<P, A>>(&mut self, symbol: S, price: P, actions: &mut X) {
+        let Some(book) = self.books.get_mut(&symbol) else {
+            return;
+        };
+        let mut tracked = Removed { inner: actions, ids: Vec::new() };
+        book.on_price(price, &mut tracked);
+        for id in tracked.ids {
+            self.symbols.remove(&id);
+        }
+    }
+
+    pub fn trigger_price(&self, id: ConditionalId) -> Option<P> {
+        self.books.get(self.symbols.get(&id)?)?.trigger_price(id)
+    }
+
+    /// 条件单所属交易对
+    pub fn symbol_of(&self, id: ConditionalId) -> Option<S> {
+        self.symbols.get(&id).copied()
+    }
+
+    pub fn book(&self, symbol: S) -> Option<&ConditionalBook<P, A>> {
+        self.books.get(&symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    fn book_mut(&mut self, symbol: S) -> &mut ConditionalBook<P, A> {
+        self.books.entry(symbol).or_default()
+    }
+}
+
+/// 记录触发与被撤销的条件单ID，用于清理ID索引
+struct Removed<'a, X> {
+    inner: &'a mut X,
+    ids: Vec<ConditionalId>,
+}
+
+impl<P, A, X: ConditionalActions<P, A>> ConditionalActions<P, A> for Removed<'_, X> {
+    fn submit(&mut self, fired: Fired<P, A>) {
+        self.ids.push(fired.id);
+        self.inner.submit(fired);
+    }
+
+    fn cancel_sibling(&mut self, id: ConditionalId, sibling: Sibling) {
+        if let Sibling::Conditional(sibling_id) = sibling {
+            self.ids.push(sibling_id);
+        }
+        self.inner.cancel_sibling(id, sibling);
+    }
+
+    fn adjust_trail(&mut self, id: ConditionalId, trigger_price: P) {
+        self.inner.adjust_trail(id, trigger_price);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::ConditionalEvent;
+    use crate::trigger::Direction;
+
+    #[test]
+    fn test_engine_routes_by_symbol() {
+        let mut engine = ConditionalEngine::<u32, i64, &str>::new();
+        let sl =
+            engine.submit(1, TriggerSpec::Stop { direction: Direction::Falling, price: 90 }, "sl");
+        let tp =
+            engine.submit(1, TriggerSpec::Stop { direction: Direction::Rising, price: 110 }, "tp");
+        let other = engine.submit(
+            2,
+            TriggerSpec::Stop { direction: Direction::Falling, price: 90 },
+            "other",
+        );
+        assert_eq!(engine.link_oco(sl, other), Err(ConditionalError::SymbolMismatch));
+        engine.link_oco(sl, tp).unwrap();
+
+        let mut events = Vec::new();
+        engine.on_price(1, 85, &mut events);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[1], ConditionalEvent::Submit(fired) if fired.id == sl));
+        assert_eq!(engine.len(), 1);
+        assert_eq!(engine.symbol_of(tp), None);
+        assert_eq!(engine.trigger_price(other), Some(90));
+
+        assert_eq!(engine.cancel(other), Some("other"));
+        assert!(engine.is_empty());
+    }
+}
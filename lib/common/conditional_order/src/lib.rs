@@ -0,0 +1,22 @@
+//! 条件单引擎
+//!
+//! 止损/止盈、OCO 与追踪止损共用的触发监控：
+//! - 每个交易对一个价格监控索引（[`ConditionalBook`]），上涨触发与下跌触发各一条按触发价排序的阶梯
+//! - 每次价格更新只做区间查询，触发评估 O(log n + 触发数)
+//! - 触发后的动作（提交订单、撤销另一腿、调整追踪价）通过 [`ConditionalActions`] 交给调用方
+//!
+//! 价格类型只要求有序与饱和加减（[`TriggerPrice`]），现货与永续各自以原始整数价格接入
+
+pub mod book;
+pub mod engine;
+pub mod trigger;
+
+pub use book::{
+    ConditionalBook, ConditionalEntry, ConditionalError, ConditionalEvent, ConditionalState, Fired,
+    Sibling,
+};
+pub use engine::ConditionalEngine;
+pub use trigger::{ConditionalActions, Direction, Trail, TriggerPrice, TriggerSpec};
+
+/// 条件单ID
+pub type ConditionalId = u64;
@@ -0,0 +1,128 @@
+//! 触发条件与动作接口
+
+use crate::ConditionalId;
+use crate::book::{Fired, Sibling};
+
+/// 触发价格类型
+pub trait TriggerPrice: Copy + Ord + std::fmt::Debug {
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+    /// 按基点（1/10000）取比例
+    fn mul_bps(self, bps: u32) -> Self;
+}
+
+macro_rules! impl_trigger_price {
+    ($($t:ty),*) => {$(
+        impl TriggerPrice for $t {
+            #[inline]
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs)
+            }
+
+            #[inline]
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$t>::saturating_sub(self, rhs)
+            }
+
+            #[inline]
+            fn mul_bps(self, bps: u32) -> Self {
+                (self as i128 * bps as i128 / 10_000) as $t
+            }
+        }
+    )*};
+}
+
+impl_trigger_price!(u64, i64);
+
+/// 触发方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// 价格上涨至触发价（>=）时触发
+    Rising,
+    /// 价格下跌至触发价（<=）时触发
+    Falling,
+}
+
+impl Direction {
+    #[inline]
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Rising => Direction::Falling,
+            Direction::Falling => Direction::Rising,
+        }
+    }
+
+    /// `price` 是否已越过 `trigger`
+    #[inline]
+    pub fn crossed<P: TriggerPrice>(self, price: P, trigger: P) -> bool {
+        match self {
+            Direction::Rising => price >= trigger,
+            Direction::Falling => price <= trigger,
+        }
+    }
+}
+
+/// 追踪距离
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trail<P> {
+    /// 固定价差
+    Absolute(P),
+    /// 参考价的比例（基点）
+    Bps(u32),
+}
+
+impl<P: TriggerPrice> Trail<P> {
+    /// 由参考价（最高价/最低价）计算触发价
+    pub fn trigger_from(&self, direction: Direction, reference: P) -> P {
+        let distance = match *self {
+            Trail::Absolute(distance) => distance,
+            Trail::Bps(bps) => reference.mul_bps(bps),
+        };
+        match direction {
+            Direction::Falling => reference.saturating_sub(distance),
+            Direction::Rising => reference.saturating_add(distance),
+        }
+    }
+}
+
+/// 触发条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerSpec<P> {
+    /// 固定触发价（止损 / 止盈）
+    Stop { direction: Direction, price: P },
+    /// 追踪止损
+    ///
+    /// `Falling` 跟踪最高价，自最高价回撤 `trail` 时触发（保护多头）；
+    /// `Rising` 跟踪最低价，自最低价反弹 `trail` 时触发（保护空头）。
+    /// 指定 `activation` 时，价格先到达激活价（`Falling` 为涨至、`Rising` 为跌至）才开始跟踪，
+    /// 否则从当前价开始跟踪
+    Trailing { direction: Direction, trail: Trail<P>, activation: Option<P> },
+}
+
+/// 触发后的动作
+///
+/// `Vec<ConditionalEvent>` 实现了本接口，可先收集再执行，避免与调用方状态的借用冲突
+pub trait ConditionalActions<P, A> {
+    /// 条件单触发：提交其订单
+    fn submit(&mut self, fired: Fired<P, A>);
+
+    /// OCO：`id` 触发后撤销另一腿（条件单另一腿已从监控索引移除）
+    fn cancel_sibling(&mut self, id: ConditionalId, sibling: Sibling);
+
+    /// 追踪止损的触发价调整
+    fn adjust_trail(&mut self, _id: ConditionalId, _trigger_price: P) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trail_trigger() {
+        assert_eq!(Trail::Absolute(50u64).trigger_from(Direction::Falling, 1000), 950);
+        assert_eq!(Trail::<u64>::Bps(100).trigger_from(Direction::Rising, 1000), 1010);
+        assert_eq!(Trail::Absolute(50u64).trigger_from(Direction::Falling, 10), 0);
+        assert!(Direction::Rising.crossed(100i64, 100));
+        assert!(!Direction::Falling.crossed(101i64, 100));
+    }
+}
@@ -0,0 +1,171 @@
+//! 结算导出的 Kafka / Redpanda 发送端
+//!
+//! 消息头携带 SBE 模式坐标（schema/template/version），消费方据此从 schema registry
+//! 取回 [`SETTLEMENT_SBE_SCHEMA`] 解码；Redpanda 兼容 Kafka 协议，同样适用。
+//! 本地发送队列满时处理回执腾出空间后退避重试；持续满说明 broker 不可达，熔断后快速失败
+
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base_types::account::settlement_export::{
+    SETTLEMENT_SBE_SCHEMA, SETTLEMENT_SCHEMA_ID, SETTLEMENT_SCHEMA_VERSION, SETTLEMENT_TEMPLATE_ID,
+    SettlementSink,
+};
+use rdkafka::config::ClientConfig;
+use rdkafka::error::KafkaError;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer};
+use rdkafka::types::RDKafkaErrorCode;
+use resilience::{CircuitBreaker, CircuitBreakerConfig, Resilience, RetryPolicy};
+
+use crate::core::event_publish::PublishError;
+
+const ENV_BROKERS: &str = "SETTLEMENT_KAFKA_BROKERS";
+const ENV_MESSAGE_TIMEOUT_MS: &str = "SETTLEMENT_KAFKA_MESSAGE_TIMEOUT_MS";
+const ENV_FLUSH_TIMEOUT_MS: &str = "SETTLEMENT_KAFKA_FLUSH_TIMEOUT_MS";
+
+/// Kafka 发送端配置
+#[derive(Debug, Clone)]
+pub struct KafkaSettlementSinkConfig {
+    /// brokers 地址（逗号分隔）
+    pub brokers: String,
+    /// 投递超时 (毫秒)
+    pub message_timeout_ms: u32,
+    /// 关闭时等待在途消息的时间 (毫秒)
+    pub flush_timeout_ms: u64,
+}
+
+impl Default for KafkaSettlementSinkConfig {
+    fn default() -> Self {
+        Self {
+            brokers: "localhost:9092".to_string(),
+            message_timeout_ms: 30_000,
+            flush_timeout_ms: 5_000,
+        }
+    }
+}
+
+impl KafkaSettlementSinkConfig {
+    /// 从环境变量读取，未配置 `SETTLEMENT_KAFKA_BROKERS` 时返回 `None`（不导出）；
+    /// `SETTLEMENT_KAFKA_MESSAGE_TIMEOUT_MS`、`SETTLEMENT_KAFKA_FLUSH_TIMEOUT_MS` 可选
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let value = |name: &str| {
+            std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+        let Some(brokers) = value(ENV_BROKERS) else {
+            return Ok(None);
+        };
+        let mut config = Self { brokers, ..Self::default() };
+        if let Some(ms) = value(ENV_MESSAGE_TIMEOUT_MS) {
+            config.message_timeout_ms = ms.parse().map_err(|_| {
+                format!("{} must be a number of milliseconds: {}", ENV_MESSAGE_TIMEOUT_MS, ms)
+            })?;
+        }
+        if let Some(ms) = value(ENV_FLUSH_TIMEOUT_MS) {
+            config.flush_timeout_ms = ms.parse().map_err(|_| {
+                format!("{} must be a number of milliseconds: {}", ENV_FLUSH_TIMEOUT_MS, ms)
+            })?;
+        }
+        Ok(Some(config))
+    }
+}
+
+/// Kafka 结算发送端
+///
+/// 账本消息要求不丢不乱：开启幂等生产者并等待全部副本确认
+pub struct KafkaSettlementSink {
+    producer: BaseProducer,
+    flush_timeout: Duration,
+    schema_id: String,
+    template_id: String,
+    version: String,
+    /// 队列满时的重试与熔断
+    send_policy: Resilience,
+}
+
+impl KafkaSettlementSink {
+    pub fn new(config: &KafkaSettlementSinkConfig) -> Result<Self, PublishError> {
+        let producer: BaseProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", config.message_timeout_ms.to_string())
+            .set("enable.idempotence", "true")
+            .set("acks", "all")
+            .create()?;
+        Ok(Self {
+            producer,
+            flush_timeout: Duration::from_millis(config.flush_timeout_ms),
+            schema_id: SETTLEMENT_SCHEMA_ID.to_string(),
+            template_id: SETTLEMENT_TEMPLATE_ID.to_string(),
+            version: SETTLEMENT_SCHEMA_VERSION.to_string(),
+            send_policy: default_send_policy(),
+        })
+    }
+
+    /// 发送的重试与熔断策略
+    pub fn with_send_policy(mut self, send_policy: Resilience) -> Self {
+        self.send_policy = send_policy;
+        self
+    }
+
+    /// 待注册到 schema registry 的模式定义
+    pub fn schema() -> &'static str {
+        SETTLEMENT_SBE_SCHEMA
+    }
+
+    /// SBE 模式坐标消息头
+    fn headers(&self) -> OwnedHeaders {
+        OwnedHeaders::new()
+            .insert(Header { key: "content-type", value: Some("application/sbe") })
+            .insert(Header { key: "sbe-schema-id", value: Some(&self.schema_id) })
+            .insert(Header { key: "sbe-template-id", value: Some(&self.template_id) })
+            .insert(Header { key: "sbe-version", value: Some(&self.version) })
+    }
+
+    /// 等待在途消息投递完成
+    pub fn flush(&self) -> Result<(), PublishError> {
+        self.producer.flush(self.flush_timeout)?;
+        Ok(())
+    }
+}
+
+impl SettlementSink for KafkaSettlementSink {
+    fn send(&mut self, topic: &str, key: &[u8], payload: &[u8]) -> io::Result<()> {
+        let producer = &self.producer;
+        // 每次尝试重新构造记录，失败时交还的记录直接丢弃
+        let result = self.send_policy.call(is_queue_full, |attempt| {
+            if attempt > 1 {
+                // 处理已完成的回执，腾出队列
+                producer.poll(Duration::ZERO);
+            }
+            let record = BaseRecord::to(topic).key(key).payload(payload).headers(self.headers());
+            producer.send(record).map_err(|(e, _)| e)
+        });
+        // 处理投递回执，避免内部队列积压
+        self.producer.poll(Duration::ZERO);
+        result.map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+/// 默认发送策略：队列满时最多 5 次、总计不超过 2s，连续 10 次失败熔断 10s
+fn default_send_policy() -> Resilience {
+    let retry = RetryPolicy::default()
+        .with_max_attempts(5)
+        .with_delays(Duration::from_millis(20), Duration::from_millis(500));
+    let breaker = CircuitBreakerConfig::default()
+        .with_failure_threshold(10)
+        .with_open_timeout(Duration::from_secs(10));
+    Resilience::new(retry)
+        .with_budget(Duration::from_secs(2))
+        .with_circuit_breaker(Arc::new(CircuitBreaker::new(breaker)))
+}
+
+fn is_queue_full(error: &KafkaError) -> bool {
+    matches!(error, KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull))
+}
+
+impl Drop for KafkaSettlementSink {
+    fn drop(&mut self) {
+        let _ = self.producer.flush(self.flush_timeout);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod kafka_settlement_sink;
 pub mod mem_repo;
 pub mod mysql_db_repo;
 
@@ -1,6 +1,12 @@
 use num_traits::ToPrimitive;
 use rust_decimal::Decimal as Rd;
 
+pub mod precision;
+pub mod wide;
+
+pub use precision::{DEFAULT_ASSET_SCALE, PrecisionError, PrecisionRegistry};
+pub use wide::{Decimal128, WIDE_SCALE};
+
 #[derive(Clone, Copy, Default)]
 // #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DecimalWrapper(i64);
@@ -59,7 +65,8 @@ impl DecimalWrapper {
 
     #[inline]
     pub fn from_rd(rd: Rd) -> Self {
-        Self(rd.to_i64().unwrap_or(0))
+        // 按 8 位小数缩放为内部整数，超出精度的部分截断
+        Self((rd * Rd::new(100_000_000, 0)).to_i64().unwrap_or(0))
     }
 }
 
@@ -173,7 +180,7 @@ impl<'de> serde::Deserialize<'de> for DecimalWrapper {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let s = String::deserialize(deserializer)?;
         let rd: Rd = s.parse().unwrap_or(Rd::ZERO);
-        Ok(Self(rd.to_i64().unwrap_or(0)))
+        Ok(Self::from_rd(rd))
     }
 }
 
@@ -0,0 +1,105 @@
+//! 资产精度注册表
+//!
+//! 各资产的最小计量单位不同：BTC 8 位、USDT 6 位、部分低价代币 18 位。
+//! 统一以资产精度换算链上/账本整数单位与 `Decimal128`，
+//! 避免一律按 8 位截断导致低价高发行量资产丢失精度
+
+use std::collections::HashMap;
+
+use crate::wide::{Decimal128, WIDE_SCALE};
+
+/// 未注册资产的默认精度（与 `DecimalWrapper` 一致）
+pub const DEFAULT_ASSET_SCALE: u32 = 8;
+
+/// 精度错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrecisionError {
+    /// 精度超过 `Decimal128` 的 18 位
+    ScaleTooLarge { asset: u32, scale: u32 },
+    /// 换算结果超出 128 位
+    Overflow { asset: u32 },
+}
+
+impl std::fmt::Display for PrecisionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecisionError::ScaleTooLarge { asset, scale } => {
+                write!(f, "Asset {} scale {} exceeds {}", asset, scale, WIDE_SCALE)
+            }
+            PrecisionError::Overflow { asset } => write!(f, "Asset {} amount overflow", asset),
+        }
+    }
+}
+
+impl std::error::Error for PrecisionError {}
+
+/// 资产精度注册表（键为资产数值ID）
+#[derive(Debug, Clone, Default)]
+pub struct PrecisionRegistry {
+    scales: HashMap<u32, u32>,
+}
+
+impl PrecisionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册资产精度
+    pub fn register(&mut self, asset: u32, scale: u32) -> Result<(), PrecisionError> {
+        if scale > WIDE_SCALE {
+            return Err(PrecisionError::ScaleTooLarge { asset, scale });
+        }
+        self.scales.insert(asset, scale);
+        Ok(())
+    }
+
+    /// 资产精度（未注册返回默认 8 位）
+    pub fn scale(&self, asset: u32) -> u32 {
+        self.scales.get(&asset).copied().unwrap_or(DEFAULT_ASSET_SCALE)
+    }
+
+    /// 最小计量单位整数 → 小数
+    pub fn from_units(&self, asset: u32, units: i128) -> Result<Decimal128, PrecisionError> {
+        Decimal128::from_scaled(units, self.scale(asset)).ok_or(PrecisionError::Overflow { asset })
+    }
+
+    /// 小数 → 最小计量单位整数（不足一个单位的部分截断）
+    pub fn to_units(&self, asset: u32, value: Decimal128) -> Result<i128, PrecisionError> {
+        value.to_scaled(self.scale(asset)).ok_or(PrecisionError::Overflow { asset })
+    }
+
+    /// 截断到资产精度
+    pub fn truncate(&self, asset: u32, value: Decimal128) -> Decimal128 {
+        value.truncate(self.scale(asset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BTC: u32 = 2;
+    const MEME: u32 = 100;
+
+    #[test]
+    fn test_per_asset_scale_conversions() {
+        let mut registry = PrecisionRegistry::new();
+        registry.register(MEME, 18).unwrap();
+        assert_eq!(
+            registry.register(MEME, 19),
+            Err(PrecisionError::ScaleTooLarge { asset: MEME, scale: 19 })
+        );
+
+        // 1e12 枚、18 位精度的代币：最小单位 1e30，超出 i64 / 8 位模型
+        let supply = registry.from_units(MEME, 10i128.pow(30)).unwrap();
+        assert_eq!(supply, Decimal128::from_int(1_000_000_000_000));
+        assert_eq!(registry.to_units(MEME, supply), Ok(10i128.pow(30)));
+
+        // 未注册资产按 8 位精度截断
+        let amount: Decimal128 = "0.123456789".parse().unwrap();
+        assert_eq!(registry.scale(BTC), DEFAULT_ASSET_SCALE);
+        assert_eq!(registry.to_units(BTC, amount), Ok(12_345_678));
+        assert_eq!(registry.truncate(BTC, amount).to_string(), "0.12345678");
+        assert_eq!(registry.truncate(MEME, amount), amount);
+    }
+}
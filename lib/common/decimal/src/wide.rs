@@ -0,0 +1,349 @@
+//! 128 位定点小数
+//!
+//! `DecimalWrapper` 以 i64 存 8 位小数，名义价值约 922 亿单位即溢出；
+//! 低价、高发行量资产（数量动辄 1e12 以上）连单笔数量都存不下。
+//! `Decimal128` 以 i128 存 18 位小数，可表示约 ±1.7e20 单位，
+//! 乘除法在 i128 中间结果溢出时退化为 256 位中间结果，不丢精度
+
+use crate::DecimalWrapper;
+
+/// 小数位数
+pub const WIDE_SCALE: u32 = 18;
+
+/// 10^18
+const WIDE_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// `DecimalWrapper` 到 `Decimal128` 的放大倍数（10^(18-8)）
+const NARROW_FACTOR: i128 = 10_000_000_000;
+
+/// 128 位定点小数（18 位小数）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal128(i128);
+
+impl Decimal128 {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(WIDE_ONE);
+    pub const MAX: Self = Self(i128::MAX);
+    pub const MIN: Self = Self(i128::MIN);
+
+    #[inline]
+    pub const fn from_raw(raw: i128) -> Self {
+        Self(raw)
+    }
+
+    #[inline]
+    pub const fn raw(&self) -> i128 {
+        self.0
+    }
+
+    /// 整数单位
+    pub fn from_int(value: i64) -> Self {
+        Self(value as i128 * WIDE_ONE)
+    }
+
+    /// 按指定小数位数解释整数（如 `from_scaled(150, 2)` = 1.50）
+    ///
+    /// 小数位数超过 18 时截断多余位数；溢出返回 None
+    pub fn from_scaled(value: i128, scale: u32) -> Option<Self> {
+        if scale <= WIDE_SCALE {
+            value.checked_mul(10i128.pow(WIDE_SCALE - scale)).map(Self)
+        } else {
+            10i128.checked_pow(scale - WIDE_SCALE).map(|div| Self(value / div))
+        }
+    }
+
+    /// 转为指定小数位数的整数（向零截断）
+    pub fn to_scaled(&self, scale: u32) -> Option<i128> {
+        if scale <= WIDE_SCALE {
+            Some(self.0 / 10i128.pow(WIDE_SCALE - scale))
+        } else {
+            self.0.checked_mul(10i128.checked_pow(scale - WIDE_SCALE)?)
+        }
+    }
+
+    /// 截断到指定小数位数
+    pub fn truncate(&self, scale: u32) -> Self {
+        if scale >= WIDE_SCALE {
+            return *self;
+        }
+        let step = 10i128.pow(WIDE_SCALE - scale);
+        Self(self.0 / step * step)
+    }
+
+    #[inline]
+    pub fn from_f64(value: f64) -> Self {
+        Self((value * WIDE_ONE as f64) as i128)
+    }
+
+    #[inline]
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / WIDE_ONE as f64
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_add(&self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(&self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    pub fn checked_mul(&self, rhs: Self) -> Option<Self> {
+        mul_div(self.0, rhs.0, WIDE_ONE).map(Self)
+    }
+
+    /// 溢出时饱和到 `MAX` / `MIN`
+    pub fn saturating_mul(&self, rhs: Self) -> Self {
+        self.checked_mul(rhs).unwrap_or(if self.is_negative() != rhs.is_negative() {
+            Self::MIN
+        } else {
+            Self::MAX
+        })
+    }
+
+    /// 除数为 0 返回 None
+    pub fn checked_div(&self, rhs: Self) -> Option<Self> {
+        mul_div(self.0, WIDE_ONE, rhs.0).map(Self)
+    }
+
+    /// 收窄为 `DecimalWrapper`（截断到 8 位小数，超出 i64 返回 None）
+    pub fn to_narrow(&self) -> Option<DecimalWrapper> {
+        i64::try_from(self.0 / NARROW_FACTOR).ok().map(DecimalWrapper::from_raw)
+    }
+}
+
+impl From<DecimalWrapper> for Decimal128 {
+    fn from(value: DecimalWrapper) -> Self {
+        Self(value.raw() as i128 * NARROW_FACTOR)
+    }
+}
+
+impl DecimalWrapper {
+    /// 放宽为 `Decimal128`
+    #[inline]
+    pub fn to_wide(&self) -> Decimal128 {
+        Decimal128::from(*self)
+    }
+
+    /// 名义价值（价格 × 数量），以 128 位计算
+    ///
+    /// 仅当两者都接近 i64 上限时溢出返回 None
+    pub fn wide_notional(&self, quantity: DecimalWrapper) -> Option<Decimal128> {
+        // 两者均不超过 i64，乘积在 i128 内；8+8 位小数再放大到 18 位
+        (self.raw() as i128 * quantity.raw() as i128).checked_mul(100).map(Decimal128)
+    }
+}
+
+impl std::ops::Add for Decimal128 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::AddAssign for Decimal128 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl std::ops::Sub for Decimal128 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::SubAssign for Decimal128 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl std::ops::Mul for Decimal128 {
+    type Output = Self;
+    /// 溢出时饱和到 `MAX` / `MIN`；需要感知溢出时用 [`Decimal128::checked_mul`]
+    fn mul(self, rhs: Self) -> Self {
+        self.saturating_mul(rhs)
+    }
+}
+
+impl std::ops::Div for Decimal128 {
+    type Output = Self;
+    /// 与 `DecimalWrapper` 一致，除数为 0 时结果为 0
+    fn div(self, rhs: Self) -> Self {
+        self.checked_div(rhs).unwrap_or(Self::ZERO)
+    }
+}
+
+impl std::iter::Sum for Decimal128 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl std::fmt::Display for Decimal128 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let one = WIDE_ONE as u128;
+        let frac = format!("{:018}", abs % one);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            write!(f, "{}{}", sign, abs / one)
+        } else {
+            write!(f, "{}{}.{}", sign, abs / one, frac)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Decimal128 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Decimal128 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid decimal: {}", s)))
+    }
+}
+
+impl std::str::FromStr for Decimal128 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s).ok_or_else(|| format!("invalid decimal: {}", s))
+    }
+}
+
+fn parse(s: &str) -> Option<Decimal128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    // 只接受十进制数字：整数 parse 会接受 "+5" / "-5"，不能放过 "1.-5"、"--1" 之类输入
+    if (int.is_empty() && frac.is_empty())
+        || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let int: i128 = if int.is_empty() { 0 } else { int.parse().ok()? };
+    let frac = &frac[..frac.len().min(WIDE_SCALE as usize)];
+    let frac_value: i128 = if frac.is_empty() { 0 } else { frac.parse().ok()? };
+    let raw = int
+        .checked_mul(WIDE_ONE)?
+        .checked_add(frac_value * 10i128.pow(WIDE_SCALE - frac.len() as u32))?;
+    Some(Decimal128(if negative { -raw } else { raw }))
+}
+
+/// `a * b / d`（向零截断），中间结果按 256 位计算
+fn mul_div(a: i128, b: i128, d: i128) -> Option<i128> {
+    if d == 0 {
+        return None;
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Some(product / d);
+    }
+    let negative = (a < 0) ^ (b < 0) ^ (d < 0);
+    let (hi, lo) = widening_mul(a.unsigned_abs(), b.unsigned_abs());
+    let quotient = div_256_by_128(hi, lo, d.unsigned_abs())?;
+    if negative { 0i128.checked_sub_unsigned(quotient) } else { i128::try_from(quotient).ok() }
+}
+
+/// 128 × 128 → 256 位乘法，返回 (高 128 位, 低 128 位)
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let cross = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (cross << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+    (hi, lo)
+}
+
+/// 256 位除以 128 位，商超出 128 位返回 None
+fn div_256_by_128(hi: u128, lo: u128, d: u128) -> Option<u128> {
+    if hi >= d {
+        return None;
+    }
+    // 逐位长除法：余数始终小于 d，左移时最高位溢出单独处理
+    let mut remainder = hi;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= d {
+            remainder = remainder.wrapping_sub(d);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notional_beyond_i64_range() {
+        // 1e12 枚低价币 × 0.5 = 5e11，DecimalWrapper 无法表示
+        let quantity = Decimal128::from_int(1_000_000_000_000);
+        let price: Decimal128 = "0.5".parse().unwrap();
+        assert_eq!(quantity * price, Decimal128::from_int(500_000_000_000));
+        assert_eq!((quantity * price).to_narrow(), None);
+
+        let price = DecimalWrapper::from_f64(60_000.0);
+        let qty = DecimalWrapper::from_f64(2_000_000.0);
+        assert_eq!(price.wide_notional(qty), Some(Decimal128::from_int(120_000_000_000)));
+        assert_eq!(price.to_wide().to_narrow(), Some(price));
+    }
+
+    #[test]
+    fn test_large_operands_use_wide_intermediate() {
+        let a = Decimal128::from_int(100_000_000_000); // 1e11 × 1e18 = 1e29
+        let b: Decimal128 = "123456.000000000000000001".parse().unwrap();
+        assert_eq!((a * b).to_string(), "12345600000000000.0000001");
+        assert_eq!((a * b) / b, a);
+        assert_eq!(Decimal128::MAX.checked_mul(Decimal128::from_int(2)), None);
+        assert_eq!(Decimal128::ONE.checked_div(Decimal128::ZERO), None);
+        assert_eq!(-(a.raw()), (Decimal128::from_int(-1) * a).raw());
+        assert_eq!(Decimal128::MAX * Decimal128::from_int(2), Decimal128::MAX);
+        assert_eq!(Decimal128::MAX * Decimal128::from_int(-2), Decimal128::MIN);
+    }
+
+    #[test]
+    fn test_scaled_conversions() {
+        let value = Decimal128::from_scaled(12_345, 4).unwrap();
+        assert_eq!(value.to_string(), "1.2345");
+        assert_eq!(value.to_scaled(2), Some(123));
+        assert_eq!(value.truncate(1).to_string(), "1.2");
+        assert_eq!("-0.1".parse::<Decimal128>().unwrap().to_string(), "-0.1");
+        assert!("abc".parse::<Decimal128>().is_err());
+        for invalid in ["1.-5", "1.+5", "+1", "--1", "-+1", ".", "-", "1 .5"] {
+            assert!(invalid.parse::<Decimal128>().is_err(), "{}", invalid);
+        }
+        assert_eq!(".5".parse::<Decimal128>().unwrap().to_string(), "0.5");
+    }
+}
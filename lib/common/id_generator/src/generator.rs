@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+
+use base_types::{SequenceGenerator, SystemClock, TimestampProvider};
 
 /// Snowflake ID生成器
 ///
@@ -16,6 +18,8 @@ pub struct IdGenerator {
     epoch: i64,
     /// 节点ID (0-31)
     node_id: u8,
+    /// 时间来源
+    clock: Arc<dyn TimestampProvider>,
 }
 
 impl IdGenerator {
@@ -35,10 +39,18 @@ impl IdGenerator {
     /// let generator = IdGenerator::new(0);
     /// ```
     pub fn new(node_id: u8) -> Self {
+        Self::with_clock(node_id, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时间来源创建（测试注入 `ManualClock` 使 ID 可复现）
+    ///
+    /// 同一毫秒内序列号用尽时会等待时间前进，手动时钟须在分配超过 4096 个 ID 前拨动
+    pub fn with_clock(node_id: u8, clock: Arc<dyn TimestampProvider>) -> Self {
         Self {
             epoch: 1704067200000, // 2024-01-01 00:00:00 UTC
             node_id: node_id & Self::MAX_NODE_ID,
             ts_and_seq: AtomicU64::new(0),
+            clock,
         }
     }
 
@@ -124,12 +136,21 @@ impl IdGenerator {
     /// 获取当前时间戳(毫秒)
     #[inline]
     fn current_millis(&self) -> i64 {
-        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
+        self.clock.now_millis() as i64
+    }
+}
+
+impl SequenceGenerator for IdGenerator {
+    fn next_id(&self) -> u64 {
+        IdGenerator::next_id(self) as u64
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use base_types::ManualClock;
     use once_cell::sync::Lazy;
 
     use super::*;
@@ -175,6 +196,22 @@ mod tests {
         assert!((timestamp - now).abs() < 1000);
     }
 
+    #[test]
+    fn test_ids_reproducible_with_manual_clock() {
+        let generate = || {
+            let clock = ManualClock::from_millis(1_704_067_200_000 + 5);
+            let generator = IdGenerator::with_clock(3, Arc::new(clock.clone()));
+            let first = generator.next_id();
+            clock.advance_millis(1);
+            (first, generator.next_id(), generator.next_id())
+        };
+        let (first, second, third) = generate();
+        assert_eq!(generate(), (first, second, third));
+        assert_eq!(first, (5 << 17) | (3 << 12));
+        assert_eq!(second, (6 << 17) | (3 << 12));
+        assert_eq!(third, second + 1);
+    }
+
     #[test]
     fn test_concurrent() {
         use std::sync::Arc;
@@ -0,0 +1,39 @@
+use std::fmt;
+
+use crate::Hash;
+
+/// MPT 错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MptError {
+    /// 节点不存在（存储不完整或根哈希错误）
+    NodeNotFound(Hash),
+    /// 节点解码失败
+    Decode(String),
+    /// 证明中的节点哈希与父节点记录不一致
+    HashMismatch,
+    /// 证明不完整或包含多余节点
+    InvalidProof,
+    /// 存储后端错误
+    Storage(String),
+}
+
+impl fmt::Display for MptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MptError::NodeNotFound(hash) => write!(f, "Node not found: {}", hex(hash)),
+            MptError::Decode(msg) => write!(f, "Decoding error: {}", msg),
+            MptError::HashMismatch => write!(f, "Hash mismatch"),
+            MptError::InvalidProof => write!(f, "Invalid proof"),
+            MptError::Storage(msg) => write!(f, "Storage error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MptError {}
+
+/// MPT 结果类型
+pub type MptResult<T> = Result<T, MptError>;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,33 @@
+//! Merkle Patricia Trie 状态承诺库
+//!
+//! 由 `study/web3` 的 MPT 学习代码提炼而来，面向储备金证明与链上结算等需要
+//! 对账户状态做承诺的模块：
+//! - [`MerklePatriciaTrie`]: 插入 / 查询 / 删除，相同键值集合得到相同根哈希（与写入顺序无关）
+//! - 批量提交：修改先累积在内存中，[`MerklePatriciaTrie::commit`] 一次性原子写入新节点与根
+//! - [`MerkleProof`]: 存在 / 不存在证明，验证方只需根哈希
+//! - [`NodeStore`]: 节点存储抽象，内置 [`MemoryNodeStore`]，`rocksdb` 特性提供 `RocksDbNodeStore`
+//!
+//! 节点按内容寻址（哈希为编码的 Keccak-256）且从不删除，历史根始终可查询与证明
+
+mod error;
+mod node;
+mod proof;
+mod storage;
+mod trie;
+
+#[cfg(feature = "rocksdb")]
+mod rocksdb_store;
+
+pub use error::{MptError, MptResult};
+pub use node::Node;
+pub use proof::MerkleProof;
+#[cfg(feature = "rocksdb")]
+pub use rocksdb_store::RocksDbNodeStore;
+pub use storage::{MemoryNodeStore, NodeBatch, NodeStore};
+pub use trie::MerklePatriciaTrie;
+
+/// 节点哈希（Keccak-256）
+pub type Hash = [u8; 32];
+
+/// 空树的根哈希
+pub const EMPTY_ROOT: Hash = [0u8; 32];
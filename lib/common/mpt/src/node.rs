@@ -0,0 +1,167 @@
+//! MPT 节点与编码
+//!
+//! 路径以 nibble（半字节）为单位。编码格式（整数小端）：
+//! - Leaf: `1 | path_len:u32 | path | value_len:u32 | value`
+//! - Extension: `2 | path_len:u32 | path | child:32`
+//! - Branch: `3 | 16 × (0 | 1 child:32) | 0 | 1 value_len:u32 value`
+
+use sha3::{Digest, Keccak256};
+
+use crate::{Hash, MptError, MptResult};
+
+/// MPT 节点（空树由 [`crate::EMPTY_ROOT`] 表示，不存储空节点）
+#[allow(clippy::large_enum_variant)] // 节点只在读写时临时解码，不长期持有
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// 叶子节点：剩余路径与值
+    Leaf { path: Vec<u8>, value: Vec<u8> },
+    /// 扩展节点：公共路径与子节点哈希
+    Extension { path: Vec<u8>, child: Hash },
+    /// 分支节点：16 个子节点与终止于此的值
+    Branch { children: [Option<Hash>; 16], value: Option<Vec<u8>> },
+}
+
+impl Node {
+    /// 编码
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self {
+            Node::Leaf { path, value } => {
+                data.push(1);
+                put_bytes(&mut data, path);
+                put_bytes(&mut data, value);
+            }
+            Node::Extension { path, child } => {
+                data.push(2);
+                put_bytes(&mut data, path);
+                data.extend_from_slice(child);
+            }
+            Node::Branch { children, value } => {
+                data.push(3);
+                for child in children {
+                    match child {
+                        Some(hash) => {
+                            data.push(1);
+                            data.extend_from_slice(hash);
+                        }
+                        None => data.push(0),
+                    }
+                }
+                match value {
+                    Some(value) => {
+                        data.push(1);
+                        put_bytes(&mut data, value);
+                    }
+                    None => data.push(0),
+                }
+            }
+        }
+        data
+    }
+
+    /// 解码
+    pub fn decode(data: &[u8]) -> MptResult<Self> {
+        let mut reader = Reader { data, offset: 0 };
+        let node = match reader.byte()? {
+            1 => Node::Leaf { path: reader.bytes()?, value: reader.bytes()? },
+            2 => Node::Extension { path: reader.bytes()?, child: reader.hash()? },
+            3 => {
+                let mut children = [None; 16];
+                for child in children.iter_mut() {
+                    if reader.flag()? {
+                        *child = Some(reader.hash()?);
+                    }
+                }
+                let value = if reader.flag()? { Some(reader.bytes()?) } else { None };
+                Node::Branch { children, value }
+            }
+            tag => return Err(MptError::Decode(format!("unknown node type {}", tag))),
+        };
+        if reader.offset != data.len() {
+            return Err(MptError::Decode("trailing bytes".to_string()));
+        }
+        Ok(node)
+    }
+}
+
+/// 编码的哈希
+pub(crate) fn hash_encoded(encoded: &[u8]) -> Hash {
+    Keccak256::digest(encoded).into()
+}
+
+/// 键转 nibble 路径
+pub(crate) fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|byte| [byte >> 4, byte & 0x0F]).collect()
+}
+
+/// 公共前缀长度
+pub(crate) fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+fn put_bytes(data: &mut Vec<u8>, bytes: &[u8]) {
+    data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> MptResult<&[u8]> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.data.len());
+        let end = end.ok_or_else(|| MptError::Decode("unexpected end of data".to_string()))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> MptResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn flag(&mut self) -> MptResult<bool> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            flag => Err(MptError::Decode(format!("invalid flag {}", flag))),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> MptResult<[u8; N]> {
+        self.take(N)?.try_into().map_err(|_| MptError::Decode(format!("expected {} bytes", N)))
+    }
+
+    fn bytes(&mut self) -> MptResult<Vec<u8>> {
+        let len = u32::from_le_bytes(self.array()?) as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn hash(&mut self) -> MptResult<Hash> {
+        self.array()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let mut children = [None; 16];
+        children[3] = Some([7u8; 32]);
+        let nodes = [
+            Node::Leaf { path: vec![1, 2, 3], value: b"value".to_vec() },
+            Node::Extension { path: vec![0xA], child: [9u8; 32] },
+            Node::Branch { children, value: Some(vec![]) },
+        ];
+        for node in nodes {
+            let encoded = node.encode();
+            assert_eq!(Node::decode(&encoded).unwrap(), node);
+            assert!(Node::decode(&encoded[..encoded.len() - 1]).is_err());
+        }
+        assert_eq!(nibbles(&[0x12, 0xAB]), vec![1, 2, 0xA, 0xB]);
+    }
+}
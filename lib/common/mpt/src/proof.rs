@@ -0,0 +1,70 @@
+use crate::node::{hash_encoded, nibbles};
+use crate::{EMPTY_ROOT, Hash, MptError, MptResult, Node};
+
+/// Merkle 证明：从根到键所在位置路径上的节点编码
+///
+/// 同时用于存在证明（得到值）与不存在证明（路径在某节点处中断）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// 被证明的键
+    pub key: Vec<u8>,
+    /// 路径上的节点编码（根在前）
+    pub nodes: Vec<Vec<u8>>,
+}
+
+impl MerkleProof {
+    /// 对照根哈希验证证明，返回键对应的值（None=证明键不存在）
+    ///
+    /// 节点哈希与父节点记录不一致返回 [`MptError::HashMismatch`]，
+    /// 节点缺失或多余返回 [`MptError::InvalidProof`]
+    pub fn verify(&self, root: &Hash) -> MptResult<Option<Vec<u8>>> {
+        if *root == EMPTY_ROOT {
+            return if self.nodes.is_empty() { Ok(None) } else { Err(MptError::InvalidProof) };
+        }
+
+        let path = nibbles(&self.key);
+        let mut path = path.as_slice();
+        let mut expected = *root;
+        for (depth, encoded) in self.nodes.iter().enumerate() {
+            if hash_encoded(encoded) != expected {
+                return Err(MptError::HashMismatch);
+            }
+            let step = match Node::decode(encoded)? {
+                Node::Leaf { path: leaf_path, value } => {
+                    Step::End((leaf_path.as_slice() == path).then_some(value))
+                }
+                Node::Extension { path: ext_path, child } => match path.strip_prefix(&*ext_path) {
+                    Some(rest) => Step::Descend(rest, child),
+                    None => Step::End(None),
+                },
+                Node::Branch { children, value } => match path.split_first() {
+                    None => Step::End(value),
+                    Some((&index, rest)) => match children[index as usize] {
+                        Some(child) => Step::Descend(rest, child),
+                        None => Step::End(None),
+                    },
+                },
+            };
+            match step {
+                Step::Descend(rest, child) => (path, expected) = (rest, child),
+                // 到达终点，之后不应再有节点
+                Step::End(value) if depth + 1 == self.nodes.len() => return Ok(value),
+                Step::End(_) => return Err(MptError::InvalidProof),
+            }
+        }
+        Err(MptError::InvalidProof)
+    }
+
+    /// 证明的序列化大小（字节）
+    pub fn size(&self) -> usize {
+        self.key.len() + self.nodes.iter().map(Vec::len).sum::<usize>()
+    }
+}
+
+/// 验证时沿路径前进的一步
+enum Step<'a> {
+    /// 进入子节点：(剩余路径, 子节点哈希)
+    Descend(&'a [u8], Hash),
+    /// 路径终止：键对应的值
+    End(Option<Vec<u8>>),
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use rocksdb::{DB, Options, WriteBatch};
+
+use crate::{Hash, MptError, MptResult, NodeBatch, NodeStore};
+
+/// 节点键前缀
+const NODE_PREFIX: u8 = b'n';
+/// 已提交根的键
+const ROOT_KEY: &[u8] = b"root";
+
+/// RocksDB 节点存储
+///
+/// 节点以 `n | hash` 为键，已提交的根存于 `root`；一次提交对应一个 `WriteBatch`
+pub struct RocksDbNodeStore {
+    db: DB,
+}
+
+impl RocksDbNodeStore {
+    /// 打开（不存在则创建）数据库
+    pub fn open(path: impl AsRef<Path>) -> MptResult<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        let db = DB::open(&options, path).map_err(storage_error)?;
+        Ok(Self { db })
+    }
+
+    fn node_key(hash: &Hash) -> [u8; 33] {
+        let mut key = [0u8; 33];
+        key[0] = NODE_PREFIX;
+        key[1..].copy_from_slice(hash);
+        key
+    }
+}
+
+impl NodeStore for RocksDbNodeStore {
+    fn get(&self, hash: &Hash) -> MptResult<Option<Vec<u8>>> {
+        self.db.get(Self::node_key(hash)).map_err(storage_error)
+    }
+
+    fn write_batch(&mut self, batch: NodeBatch) -> MptResult<()> {
+        let mut write = WriteBatch::default();
+        for (hash, encoded) in &batch.nodes {
+            write.put(Self::node_key(hash), encoded);
+        }
+        write.put(ROOT_KEY, batch.root);
+        self.db.write(write).map_err(storage_error)
+    }
+
+    fn committed_root(&self) -> MptResult<Option<Hash>> {
+        match self.db.get(ROOT_KEY).map_err(storage_error)? {
+            Some(bytes) => bytes
+                .as_slice()
+                .try_into()
+                .map(Some)
+                .map_err(|_| MptError::Decode("invalid committed root".to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+fn storage_error(error: rocksdb::Error) -> MptError {
+    MptError::Storage(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerklePatriciaTrie;
+
+    #[test]
+    fn test_reopen_committed_trie() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = {
+            let mut trie =
+                MerklePatriciaTrie::open(RocksDbNodeStore::open(dir.path()).unwrap()).unwrap();
+            trie.insert(b"alice", b"100").unwrap();
+            trie.insert(b"bob", b"200").unwrap();
+            trie.commit().unwrap()
+        };
+
+        let trie = MerklePatriciaTrie::open(RocksDbNodeStore::open(dir.path()).unwrap()).unwrap();
+        assert_eq!(trie.root_hash(), root);
+        assert_eq!(trie.get(b"bob").unwrap(), Some(b"200".to_vec()));
+        assert_eq!(trie.prove(b"alice").unwrap().verify(&root).unwrap(), Some(b"100".to_vec()));
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use crate::{Hash, MptResult};
+
+/// 一次提交写入的节点与新根
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeBatch {
+    /// (节点哈希, 节点编码)
+    pub nodes: Vec<(Hash, Vec<u8>)>,
+    /// 提交后的根哈希
+    pub root: Hash,
+}
+
+/// 节点存储
+///
+/// 节点按哈希寻址、只增不删；`write_batch` 须原子地写入全部节点并更新已提交的根
+pub trait NodeStore {
+    /// 读取节点编码
+    fn get(&self, hash: &Hash) -> MptResult<Option<Vec<u8>>>;
+
+    /// 原子写入一批节点并更新已提交的根
+    fn write_batch(&mut self, batch: NodeBatch) -> MptResult<()>;
+
+    /// 最近一次提交的根（从未提交为 None）
+    fn committed_root(&self) -> MptResult<Option<Hash>>;
+}
+
+/// 内存节点存储（测试与临时计算）
+#[derive(Debug, Clone, Default)]
+pub struct MemoryNodeStore {
+    nodes: HashMap<Hash, Vec<u8>>,
+    root: Option<Hash>,
+}
+
+impl MemoryNodeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 存储的节点数
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl NodeStore for MemoryNodeStore {
+    fn get(&self, hash: &Hash) -> MptResult<Option<Vec<u8>>> {
+        Ok(self.nodes.get(hash).cloned())
+    }
+
+    fn write_batch(&mut self, batch: NodeBatch) -> MptResult<()> {
+        self.nodes.extend(batch.nodes);
+        self.root = Some(batch.root);
+        Ok(())
+    }
+
+    fn committed_root(&self) -> MptResult<Option<Hash>> {
+        Ok(self.root)
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+
+use crate::node::{common_prefix, hash_encoded, nibbles};
+use crate::{EMPTY_ROOT, Hash, MerkleProof, MptError, MptResult, Node, NodeBatch, NodeStore};
+
+/// Merkle Patricia Trie
+///
+/// 修改产生的新节点先缓存在内存中（此时 [`root_hash`](Self::root_hash) 已是新状态的根），
+/// [`commit`](Self::commit) 把从新根可达的新节点与根一次性写入存储，
+/// [`rollback`](Self::rollback) 丢弃未提交的修改。
+///
+/// 结构是规范的：删除后分支节点会合并收缩，相同的键值集合总是得到相同的根
+pub struct MerklePatriciaTrie<S: NodeStore> {
+    store: S,
+    /// 当前根（含未提交的修改）
+    root: Hash,
+    /// 最近一次提交的根
+    committed_root: Hash,
+    /// 未提交的节点（哈希 -> 编码）
+    pending: HashMap<Hash, Vec<u8>>,
+}
+
+impl<S: NodeStore> MerklePatriciaTrie<S> {
+    /// 打开存储中最近一次提交的树（从未提交则为空树）
+    pub fn open(store: S) -> MptResult<Self> {
+        let root = store.committed_root()?.unwrap_or(EMPTY_ROOT);
+        Ok(Self::at_root(store, root))
+    }
+
+    /// 打开指定根的树（历史状态）
+    pub fn at_root(store: S, root: Hash) -> Self {
+        Self { store, root, committed_root: root, pending: HashMap::new() }
+    }
+
+    /// 当前根哈希（含未提交的修改）
+    pub fn root_hash(&self) -> Hash {
+        self.root
+    }
+
+    /// 最近一次提交的根哈希
+    pub fn committed_root(&self) -> Hash {
+        self.committed_root
+    }
+
+    /// 是否有未提交的修改
+    pub fn is_dirty(&self) -> bool {
+        self.root != self.committed_root
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// 查询
+    pub fn get(&self, key: &[u8]) -> MptResult<Option<Vec<u8>>> {
+        let path = nibbles(key);
+        let mut path = path.as_slice();
+        let mut hash = self.root;
+        while hash != EMPTY_ROOT {
+            match self.load(&hash)? {
+                Node::Leaf { path: leaf_path, value } => {
+                    return Ok((leaf_path == path).then_some(value));
+                }
+                Node::Extension { path: ext_path, child } => match path.strip_prefix(&*ext_path) {
+                    Some(rest) => (path, hash) = (rest, child),
+                    None => return Ok(None),
+                },
+                Node::Branch { children, value } => match path.split_first() {
+                    None => return Ok(value),
+                    Some((&index, rest)) => match children[index as usize] {
+                        Some(child) => (path, hash) = (rest, child),
+                        None => return Ok(None),
+                    },
+                },
+            }
+        }
+        Ok(None)
+    }
+
+    /// 插入或更新
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> MptResult<()> {
+        let root = (self.root != EMPTY_ROOT).then_some(self.root);
+        self.root = self.insert_at(root, &nibbles(key), value.to_vec())?;
+        Ok(())
+    }
+
+    /// 删除，返回被删除的值
+    pub fn remove(&mut self, key: &[u8]) -> MptResult<Option<Vec<u8>>> {
+        if self.root == EMPTY_ROOT {
+            return Ok(None);
+        }
+        let (root, removed) = self.remove_at(self.root, &nibbles(key))?;
+        self.root = root.unwrap_or(EMPTY_ROOT);
+        Ok(removed)
+    }
+
+    /// 生成键的存在 / 不存在证明（基于当前根）
+    pub fn prove(&self, key: &[u8]) -> MptResult<MerkleProof> {
+        let path = nibbles(key);
+        let mut path = path.as_slice();
+        let mut nodes = Vec::new();
+        let mut hash = self.root;
+        while hash != EMPTY_ROOT {
+            let encoded = self.load_encoded(&hash)?;
+            let next = match Node::decode(&encoded)? {
+                Node::Leaf { .. } => None,
+                Node::Extension { path: ext_path, child } => {
+                    path.strip_prefix(&*ext_path).map(|rest| (rest, child))
+                }
+                Node::Branch { children, .. } => path
+                    .split_first()
+                    .and_then(|(&index, rest)| children[index as usize].map(|child| (rest, child))),
+            };
+            nodes.push(encoded);
+            match next {
+                Some(next) => (path, hash) = next,
+                None => break,
+            }
+        }
+        Ok(MerkleProof { key: key.to_vec(), nodes })
+    }
+
+    /// 提交：把从当前根可达的未提交节点与根作为一批原子写入存储，返回新根
+    pub fn commit(&mut self) -> MptResult<Hash> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(hash) = stack.pop() {
+            // 未在 pending 中的子树已在存储中
+            let Some(encoded) = self.pending.remove(&hash) else {
+                continue;
+            };
+            match Node::decode(&encoded)? {
+                Node::Leaf { .. } => {}
+                Node::Extension { child, .. } => stack.push(child),
+                Node::Branch { children, .. } => stack.extend(children.into_iter().flatten()),
+            }
+            nodes.push((hash, encoded));
+        }
+
+        self.store.write_batch(NodeBatch { nodes, root: self.root })?;
+        // 剩余的是被覆盖的中间节点
+        self.pending.clear();
+        self.committed_root = self.root;
+        Ok(self.root)
+    }
+
+    /// 丢弃未提交的修改
+    pub fn rollback(&mut self) {
+        self.pending.clear();
+        self.root = self.committed_root;
+    }
+
+    fn load_encoded(&self, hash: &Hash) -> MptResult<Vec<u8>> {
+        if let Some(encoded) = self.pending.get(hash) {
+            return Ok(encoded.clone());
+        }
+        self.store.get(hash)?.ok_or(MptError::NodeNotFound(*hash))
+    }
+
+    fn load(&self, hash: &Hash) -> MptResult<Node> {
+        Node::decode(&self.load_encoded(hash)?)
+    }
+
+    fn save(&mut self, node: Node) -> Hash {
+        let encoded = node.encode();
+        let hash = hash_encoded(&encoded);
+        self.pending.insert(hash, encoded);
+        hash
+    }
+
+    fn insert_at(&mut self, hash: Option<Hash>, path: &[u8], value: Vec<u8>) -> MptResult<Hash> {
+        let Some(hash) = hash else {
+            return Ok(self.save(Node::Leaf { path: path.to_vec(), value }));
+        };
+
+        match self.load(&hash)? {
+            Node::Leaf { path: leaf_path, value: leaf_value } => {
+                if leaf_path == path {
+                    return Ok(self.save(Node::Leaf { path: leaf_path, value }));
+                }
+                // 分裂为分支节点（公共前缀不为空时再加扩展节点）
+                let common = common_prefix(&leaf_path, path);
+                let mut children = [None; 16];
+                let mut branch_value = None;
+                for (rest, value) in [(&leaf_path[common..], leaf_value), (&path[common..], value)]
+                {
+                    match rest.split_first() {
+                        None => branch_value = Some(value),
+                        Some((&index, rest)) => {
+                            let leaf = Node::Leaf { path: rest.to_vec(), value };
+                            children[index as usize] = Some(self.save(leaf));
+                        }
+                    }
+                }
+                let branch = self.save(Node::Branch { children, value: branch_value });
+                Ok(self.wrap_extension(&path[..common], branch))
+            }
+
+            Node::Extension { path: ext_path, child } => {
+                let common = common_prefix(&ext_path, path);
+                if common == ext_path.len() {
+                    let child = self.insert_at(Some(child), &path[common..], value)?;
+                    return Ok(self.save(Node::Extension { path: ext_path, child }));
+                }
+
+                // 在分歧处分裂扩展节点
+                let mut children = [None; 16];
+                let ext_rest = &ext_path[common + 1..];
+                children[ext_path[common] as usize] = Some(self.wrap_extension(ext_rest, child));
+                let mut branch_value = None;
+                match path[common..].split_first() {
+                    None => branch_value = Some(value),
+                    Some((&index, rest)) => {
+                        let leaf = Node::Leaf { path: rest.to_vec(), value };
+                        children[index as usize] = Some(self.save(leaf));
+                    }
+                }
+                let branch = self.save(Node::Branch { children, value: branch_value });
+                Ok(self.wrap_extension(&path[..common], branch))
+            }
+
+            Node::Branch { mut children, value: branch_value } => match path.split_first() {
+                None => Ok(self.save(Node::Branch { children, value: Some(value) })),
+                Some((&index, rest)) => {
+                    let child = self.insert_at(children[index as usize], rest, value)?;
+                    children[index as usize] = Some(child);
+                    Ok(self.save(Node::Branch { children, value: branch_value }))
+                }
+            },
+        }
+    }
+
+    /// 返回 (删除后的子树根，None=子树已空, 被删除的值)；键不存在时子树不变
+    fn remove_at(&mut self, hash: Hash, path: &[u8]) -> MptResult<(Option<Hash>, Option<Vec<u8>>)> {
+        let unchanged = Ok((Some(hash), None));
+
+        match self.load(&hash)? {
+            Node::Leaf { path: leaf_path, value } => {
+                if leaf_path == path {
+                    Ok((None, Some(value)))
+                } else {
+                    unchanged
+                }
+            }
+
+            Node::Extension { path: ext_path, child } => {
+                let Some(rest) = path.strip_prefix(&*ext_path) else {
+                    return unchanged;
+                };
+                match self.remove_at(child, rest)? {
+                    (_, None) => unchanged,
+                    (None, removed) => Ok((None, removed)),
+                    (Some(child), removed) => {
+                        Ok((Some(self.join_path(&ext_path, child)?), removed))
+                    }
+                }
+            }
+
+            Node::Branch { mut children, value } => {
+                let removed = match path.split_first() {
+                    None => {
+                        let Some(value) = value else {
+                            return unchanged;
+                        };
+                        let collapsed = self.collapse_branch(children, None)?;
+                        return Ok((collapsed, Some(value)));
+                    }
+                    Some((&index, rest)) => {
+                        let Some(child) = children[index as usize] else {
+                            return unchanged;
+                        };
+                        let (child, removed) = self.remove_at(child, rest)?;
+                        if removed.is_none() {
+                            return unchanged;
+                        }
+                        children[index as usize] = child;
+                        removed
+                    }
+                };
+                Ok((self.collapse_branch(children, value)?, removed))
+            }
+        }
+    }
+
+    /// 删除后规范化分支：无子节点退化为叶子，只剩一个子节点且无值时与子节点合并
+    fn collapse_branch(
+        &mut self,
+        children: [Option<Hash>; 16],
+        value: Option<Vec<u8>>,
+    ) -> MptResult<Option<Hash>> {
+        let mut remaining = children.iter().enumerate().filter_map(|(i, c)| c.map(|c| (i, c)));
+        let first = remaining.next();
+        let single = remaining.next().is_none();
+
+        match (first, value) {
+            (None, None) => Ok(None),
+            (None, Some(value)) => Ok(Some(self.save(Node::Leaf { path: Vec::new(), value }))),
+            (Some((index, child)), None) if single => {
+                Ok(Some(self.join_path(&[index as u8], child)?))
+            }
+            (_, value) => Ok(Some(self.save(Node::Branch { children, value }))),
+        }
+    }
+
+    /// 在子节点前拼接路径（与叶子 / 扩展节点合并）
+    fn join_path(&mut self, prefix: &[u8], child: Hash) -> MptResult<Hash> {
+        let joined = |path: &[u8]| [prefix, path].concat();
+        Ok(match self.load(&child)? {
+            Node::Leaf { path, value } => self.save(Node::Leaf { path: joined(&path), value }),
+            Node::Extension { path, child } => {
+                self.save(Node::Extension { path: joined(&path), child })
+            }
+            Node::Branch { .. } => self.save(Node::Extension { path: prefix.to_vec(), child }),
+        })
+    }
+
+    /// 公共前缀不为空时在子节点前加扩展节点
+    fn wrap_extension(&mut self, prefix: &[u8], child: Hash) -> Hash {
+        if prefix.is_empty() {
+            child
+        } else {
+            self.save(Node::Extension { path: prefix.to_vec(), child })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryNodeStore;
+
+    fn trie() -> MerklePatriciaTrie<MemoryNodeStore> {
+        MerklePatriciaTrie::open(MemoryNodeStore::new()).unwrap()
+    }
+
+    const ENTRIES: [(&[u8], &[u8]); 6] = [
+        (b"do", b"verb"),
+        (b"dog", b"puppy"),
+        (b"doge", b"coin"),
+        (b"horse", b"stallion"),
+        (b"d", b"short"),
+        (b"\x00\x01", b"binary"),
+    ];
+
+    #[test]
+    fn test_insert_get_remove_canonical() {
+        let mut forward = trie();
+        for (key, value) in ENTRIES {
+            forward.insert(key, value).unwrap();
+        }
+        for (key, value) in ENTRIES {
+            assert_eq!(forward.get(key).unwrap().as_deref(), Some(value));
+        }
+        assert_eq!(forward.get(b"dogs").unwrap(), None);
+        assert_eq!(forward.get(b"").unwrap(), None);
+
+        // 写入顺序不影响根
+        let mut backward = trie();
+        for (key, value) in ENTRIES.iter().rev() {
+            backward.insert(key, value).unwrap();
+        }
+        assert_eq!(forward.root_hash(), backward.root_hash());
+
+        // 删除后与从未插入的树一致
+        let mut partial = trie();
+        for (key, value) in &ENTRIES[..3] {
+            partial.insert(key, value).unwrap();
+        }
+        for (key, value) in &ENTRIES[3..] {
+            assert_eq!(forward.remove(key).unwrap().as_deref(), Some(*value));
+        }
+        assert_eq!(forward.remove(b"missing").unwrap(), None);
+        assert_eq!(forward.root_hash(), partial.root_hash());
+
+        for (key, _) in &ENTRIES[..3] {
+            forward.remove(key).unwrap();
+        }
+        assert_eq!(forward.root_hash(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn test_batched_commit_and_history() {
+        let mut trie = trie();
+        trie.insert(b"alice", b"100").unwrap();
+        trie.insert(b"alice", b"150").unwrap();
+        trie.insert(b"bob", b"200").unwrap();
+        assert!(trie.is_dirty());
+        let first = trie.commit().unwrap();
+        // 只写入从新根可达的节点（被覆盖的 alice=100 叶子不落盘）
+        let written = trie.store().len();
+        assert_eq!(trie.prove(b"alice").unwrap().nodes.len() + 1, written);
+
+        trie.insert(b"carol", b"300").unwrap();
+        trie.rollback();
+        assert_eq!(trie.root_hash(), first);
+        assert_eq!(trie.get(b"carol").unwrap(), None);
+
+        trie.remove(b"alice").unwrap();
+        let second = trie.commit().unwrap();
+        assert_eq!(trie.store().committed_root().unwrap(), Some(second));
+
+        // 历史根仍可查询
+        let history = MerklePatriciaTrie::at_root(trie.into_store(), first);
+        assert_eq!(history.get(b"alice").unwrap(), Some(b"150".to_vec()));
+    }
+
+    #[test]
+    fn test_proofs() {
+        let mut trie = trie();
+        for (key, value) in ENTRIES {
+            trie.insert(key, value).unwrap();
+        }
+        let root = trie.commit().unwrap();
+
+        for (key, value) in ENTRIES {
+            let proof = trie.prove(key).unwrap();
+            assert_eq!(proof.verify(&root).unwrap().as_deref(), Some(value));
+        }
+        // 不存在证明
+        for key in [&b"dogs"[..], b"cat", b"do\x00"] {
+            assert_eq!(trie.prove(key).unwrap().verify(&root).unwrap(), None);
+        }
+
+        let mut proof = trie.prove(b"doge").unwrap();
+        assert!(matches!(proof.verify(&[1u8; 32]), Err(MptError::HashMismatch)));
+        // 篡改值
+        let last = proof.nodes.len() - 1;
+        *proof.nodes[last].last_mut().unwrap() ^= 1;
+        assert!(matches!(proof.verify(&root), Err(MptError::HashMismatch)));
+        // 截断
+        proof.nodes.pop();
+        assert!(matches!(proof.verify(&root), Err(MptError::InvalidProof)));
+    }
+}
@@ -0,0 +1,66 @@
+//! 并发隔离舱：限制对同一依赖同时在途的调用数
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 并发上限
+#[derive(Debug)]
+pub struct Bulkhead {
+    max_concurrent: usize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Bulkhead {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self { max_concurrent: max_concurrent.max(1), in_flight: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// 占用一个名额，已满返回 None；名额随许可释放
+    pub fn try_acquire(&self) -> Option<BulkheadPermit> {
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                (current < self.max_concurrent).then_some(current + 1)
+            })
+            .ok()
+            .map(|_| BulkheadPermit { in_flight: self.in_flight.clone() })
+    }
+}
+
+/// 隔离舱名额，drop 时归还
+#[derive(Debug)]
+pub struct BulkheadPermit {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for BulkheadPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits_are_capped_and_released() {
+        let bulkhead = Bulkhead::new(2);
+        let first = bulkhead.try_acquire().unwrap();
+        let _second = bulkhead.try_acquire().unwrap();
+        assert!(bulkhead.try_acquire().is_none());
+        assert_eq!(bulkhead.in_flight(), 2);
+
+        drop(first);
+        assert_eq!(bulkhead.in_flight(), 1);
+        assert!(bulkhead.try_acquire().is_some());
+        assert_eq!(bulkhead.in_flight(), 1);
+    }
+}
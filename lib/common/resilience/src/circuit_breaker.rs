@@ -0,0 +1,253 @@
+//! 熔断器
+//!
+//! - Closed: 正常放行，连续失败达到 `failure_threshold` 转为 Open
+//! - Open: 拒绝调用，经过 `open_timeout` 后转为 HalfOpen
+//! - HalfOpen: 最多放行 `half_open_probes` 个探测调用；全部成功转为 Closed，任一失败重新 Open
+
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::{Clock, SystemClock};
+
+/// 熔断状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "CLOSED",
+            CircuitState::Open => "OPEN",
+            CircuitState::HalfOpen => "HALF_OPEN",
+        }
+    }
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 熔断配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// 连续失败多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断后多久进入半开
+    pub open_timeout: Duration,
+    /// 半开时放行的探测调用数，全部成功才恢复
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    /// 连续 5 次失败熔断，30s 后放行 1 个探测
+    fn default() -> Self {
+        Self { failure_threshold: 5, open_timeout: Duration::from_secs(30), half_open_probes: 1 }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    pub fn with_open_timeout(mut self, open_timeout: Duration) -> Self {
+        self.open_timeout = open_timeout;
+        self
+    }
+
+    pub fn with_half_open_probes(mut self, half_open_probes: u32) -> Self {
+        self.half_open_probes = half_open_probes.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    /// Closed 下的连续失败数
+    consecutive_failures: u32,
+    /// 进入 Open 的时间
+    opened_at: Duration,
+    /// HalfOpen 下已放行 / 已成功的探测数
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+/// 熔断器，可在多个线程间共享
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerInner>,
+    clock: Arc<dyn Clock>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("config", &self.config)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock::default()))
+    }
+
+    pub fn with_clock(config: CircuitBreakerConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: Duration::ZERO,
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+            clock,
+        }
+    }
+
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// 当前状态（Open 且冷却已过时报告为 HalfOpen）
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// 是否放行一次调用；放行后须以 `record_success` / `record_failure` 报告结果
+    pub fn allow(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        self.refresh(&mut inner);
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if inner.probes_in_flight + inner.probe_successes >= self.config.half_open_probes {
+                    return false;
+                }
+                inner.probes_in_flight += 1;
+                true
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        match inner.state {
+            CircuitState::Closed => inner.consecutive_failures = 0,
+            CircuitState::HalfOpen => {
+                inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+                inner.probe_successes += 1;
+                if inner.probe_successes >= self.config.half_open_probes {
+                    inner.state = CircuitState::Closed;
+                    inner.consecutive_failures = 0;
+                    inner.probe_successes = 0;
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        match inner.state {
+            CircuitState::Closed => {
+                inner.consecutive_failures += 1;
+                if inner.consecutive_failures >= self.config.failure_threshold {
+                    self.open(&mut inner);
+                }
+            }
+            CircuitState::HalfOpen => self.open(&mut inner),
+            CircuitState::Open => {}
+        }
+    }
+
+    fn open(&self, inner: &mut BreakerInner) {
+        inner.state = CircuitState::Open;
+        inner.opened_at = self.clock.now();
+        inner.probes_in_flight = 0;
+        inner.probe_successes = 0;
+    }
+
+    fn refresh(&self, inner: &mut BreakerInner) {
+        if inner.state == CircuitState::Open
+            && self.clock.now().saturating_sub(inner.opened_at) >= self.config.open_timeout
+        {
+            inner.state = CircuitState::HalfOpen;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    #[test]
+    fn test_open_half_open_close() {
+        let clock = Arc::new(ManualClock::new());
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(3)
+            .with_open_timeout(Duration::from_secs(10))
+            .with_half_open_probes(2);
+        let breaker = CircuitBreaker::with_clock(config, clock.clone());
+
+        // 成功会清零连续失败数
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allow());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allow());
+        assert!(breaker.allow());
+        // 探测名额已满
+        assert!(!breaker.allow());
+        breaker.record_success();
+        assert!(!breaker.allow());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn test_probe_failure_reopens() {
+        let clock = Arc::new(ManualClock::new());
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(1)
+            .with_open_timeout(Duration::from_secs(5));
+        let breaker = CircuitBreaker::with_clock(config, clock.clone());
+
+        breaker.record_failure();
+        clock.advance(Duration::from_secs(5));
+        assert!(breaker.allow());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // 重新计时
+        clock.advance(Duration::from_secs(4));
+        assert!(!breaker.allow());
+        clock.advance(Duration::from_secs(1));
+        assert!(breaker.allow());
+    }
+}
@@ -0,0 +1,70 @@
+//! 时间来源
+//!
+//! 只需要单调时间：熔断冷却、时间预算与退避等待都以相对时长计算
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 单调时钟与等待
+pub trait Clock: Send + Sync {
+    /// 自任意固定起点经过的时间
+    fn now(&self) -> Duration;
+
+    /// 阻塞等待
+    fn sleep(&self, duration: Duration);
+}
+
+/// 系统单调时钟
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// 手动推进的时钟（测试用）：`sleep` 立即返回并推进时间，同时记录每次等待
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+    sleeps: Mutex<Vec<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+
+    /// 已发生的等待
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.sleeps.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.lock().unwrap_or_else(PoisonError::into_inner).push(duration);
+        self.advance(duration);
+    }
+}
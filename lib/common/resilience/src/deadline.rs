@@ -0,0 +1,31 @@
+//! 调用时间预算
+
+use std::time::Duration;
+
+use crate::Clock;
+
+/// 截止时间（以 [`Clock::now`] 计）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    at: Duration,
+}
+
+impl Deadline {
+    /// 从现在起 `budget` 后到期
+    pub fn after(clock: &dyn Clock, budget: Duration) -> Self {
+        Self { at: clock.now().saturating_add(budget) }
+    }
+
+    pub fn remaining(&self, clock: &dyn Clock) -> Duration {
+        self.at.saturating_sub(clock.now())
+    }
+
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        self.remaining(clock).is_zero()
+    }
+
+    /// 等待 `delay` 后是否仍有剩余时间可再调用一次
+    pub fn allows_wait(&self, clock: &dyn Clock, delay: Duration) -> bool {
+        self.remaining(clock) > delay
+    }
+}
@@ -0,0 +1,98 @@
+//! 出站调用的容错工具
+//!
+//! 数据库、消息队列、通知投递等出站适配器共用的重试与保护策略，取代各处零散的错误处理：
+//! - [`RetryPolicy`] —— 指数退避重试，带抖动，避免大量客户端同时重试
+//! - [`Deadline`] —— 整次调用（含全部重试与等待）的时间预算，剩余时间不足以再等一次退避时放弃
+//! - [`CircuitBreaker`] —— 连续失败达到阈值后熔断，冷却后进入半开状态放行少量探测请求，
+//!   探测成功才恢复，避免持续冲击已故障的依赖
+//! - [`Bulkhead`] —— 限制同时在途的调用数，一个依赖变慢不会占满调用方的全部线程
+//! - [`Resilience`] —— 组合以上策略执行一次调用
+//!
+//! 只有调用方判定为暂时性的错误（超时、连接断开、队列满等）才重试并计入熔断；
+//! 其余错误说明依赖已正常应答，直接返回。时间经 [`Clock`] 注入，测试使用 [`ManualClock`]。
+//! 调用为同步阻塞，等待退避时阻塞当前线程
+
+mod bulkhead;
+mod circuit_breaker;
+mod clock;
+mod deadline;
+mod policy;
+mod retry;
+
+use std::{fmt, io};
+
+pub use bulkhead::{Bulkhead, BulkheadPermit};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use deadline::Deadline;
+pub use policy::Resilience;
+pub use retry::{Jitter, JitterRng, RetryPolicy};
+
+/// 容错调用的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResilienceError<E> {
+    /// 不可重试的错误，或重试次数用尽，携带最后一次错误
+    Failed { error: E, attempts: u32 },
+    /// 时间预算耗尽，携带最后一次错误
+    DeadlineExceeded { error: Option<E>, attempts: u32 },
+    /// 熔断中，未执行调用
+    CircuitOpen,
+    /// 在途调用已满，未执行调用
+    BulkheadFull,
+}
+
+impl<E> ResilienceError<E> {
+    /// 最后一次调用的错误（未执行调用时为 None）
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            ResilienceError::Failed { error, .. } => Some(error),
+            ResilienceError::DeadlineExceeded { error, .. } => error,
+            ResilienceError::CircuitOpen | ResilienceError::BulkheadFull => None,
+        }
+    }
+
+    /// 已执行的调用次数
+    pub fn attempts(&self) -> u32 {
+        match self {
+            ResilienceError::Failed { attempts, .. }
+            | ResilienceError::DeadlineExceeded { attempts, .. } => *attempts,
+            ResilienceError::CircuitOpen | ResilienceError::BulkheadFull => 0,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ResilienceError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResilienceError::Failed { error, attempts } => {
+                write!(f, "Failed after {} attempt(s): {}", attempts, error)
+            }
+            ResilienceError::DeadlineExceeded { error: Some(error), attempts } => {
+                write!(f, "Deadline exceeded after {} attempt(s): {}", attempts, error)
+            }
+            ResilienceError::DeadlineExceeded { error: None, attempts } => {
+                write!(f, "Deadline exceeded after {} attempt(s)", attempts)
+            }
+            ResilienceError::CircuitOpen => write!(f, "Circuit breaker is open"),
+            ResilienceError::BulkheadFull => write!(f, "Too many concurrent calls"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ResilienceError<E> {}
+
+/// IO 错误是否为暂时性的（超时、连接被拒绝或断开、资源暂不可用），可作为 [`Resilience::call`] 的重试判定
+pub fn is_transient_io(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+    )
+}
@@ -0,0 +1,239 @@
+//! 组合策略
+
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::{
+    Bulkhead, CircuitBreaker, Clock, Deadline, JitterRng, ResilienceError, RetryPolicy, SystemClock,
+};
+
+/// 一个出站依赖的容错策略：隔离舱 → 熔断 → 调用 → 按退避重试，受总时间预算约束
+///
+/// 熔断器与隔离舱以 `Arc` 持有，同一依赖的多个调用方可共享
+pub struct Resilience {
+    retry: RetryPolicy,
+    budget: Option<Duration>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    bulkhead: Option<Arc<Bulkhead>>,
+    clock: Arc<dyn Clock>,
+    rng: Mutex<JitterRng>,
+}
+
+impl fmt::Debug for Resilience {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resilience")
+            .field("retry", &self.retry)
+            .field("budget", &self.budget)
+            .field("breaker", &self.breaker)
+            .field("bulkhead", &self.bulkhead)
+            .finish()
+    }
+}
+
+impl Default for Resilience {
+    fn default() -> Self {
+        Self::new(RetryPolicy::default())
+    }
+}
+
+impl Resilience {
+    pub fn new(retry: RetryPolicy) -> Self {
+        Self {
+            retry,
+            budget: None,
+            breaker: None,
+            bulkhead: None,
+            clock: Arc::new(SystemClock::default()),
+            rng: Mutex::new(JitterRng::default()),
+        }
+    }
+
+    /// 整次调用的时间预算
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    pub fn with_bulkhead(mut self, bulkhead: Arc<Bulkhead>) -> Self {
+        self.bulkhead = Some(bulkhead);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 固定抖动种子（测试用）
+    pub fn with_rng(mut self, rng: JitterRng) -> Self {
+        self.rng = Mutex::new(rng);
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.breaker.as_ref()
+    }
+
+    /// 执行调用：`op` 收到从 1 开始的调用序号；`is_retryable` 判定错误是否为暂时性的。
+    /// 不可重试的错误视为依赖已正常应答，不计入熔断
+    pub fn call<T, E>(
+        &self,
+        is_retryable: impl Fn(&E) -> bool,
+        mut op: impl FnMut(u32) -> Result<T, E>,
+    ) -> Result<T, ResilienceError<E>> {
+        let _permit = match &self.bulkhead {
+            Some(bulkhead) => Some(bulkhead.try_acquire().ok_or(ResilienceError::BulkheadFull)?),
+            None => None,
+        };
+        let deadline = self.budget.map(|budget| Deadline::after(self.clock.as_ref(), budget));
+        let mut attempt = 0;
+        loop {
+            if let Some(breaker) = &self.breaker {
+                if !breaker.allow() {
+                    return Err(ResilienceError::CircuitOpen);
+                }
+            }
+            attempt += 1;
+            let error = match op(attempt) {
+                Ok(value) => {
+                    if let Some(breaker) = &self.breaker {
+                        breaker.record_success();
+                    }
+                    return Ok(value);
+                }
+                Err(error) => error,
+            };
+            if !is_retryable(&error) {
+                if let Some(breaker) = &self.breaker {
+                    breaker.record_success();
+                }
+                return Err(ResilienceError::Failed { error, attempts: attempt });
+            }
+            if let Some(breaker) = &self.breaker {
+                breaker.record_failure();
+            }
+            if attempt >= self.retry.max_attempts {
+                return Err(ResilienceError::Failed { error, attempts: attempt });
+            }
+            let delay = self
+                .retry
+                .delay(attempt, &mut self.rng.lock().unwrap_or_else(PoisonError::into_inner));
+            if let Some(deadline) = deadline {
+                if !deadline.allows_wait(self.clock.as_ref(), delay) {
+                    return Err(ResilienceError::DeadlineExceeded {
+                        error: Some(error),
+                        attempts: attempt,
+                    });
+                }
+            }
+            self.clock.sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CircuitBreakerConfig, CircuitState, Jitter, ManualClock};
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        Timeout,
+        Rejected,
+    }
+
+    fn is_timeout(error: &TestError) -> bool {
+        *error == TestError::Timeout
+    }
+
+    fn policy(clock: &Arc<ManualClock>) -> Resilience {
+        let retry = RetryPolicy::default()
+            .with_max_attempts(4)
+            .with_delays(Duration::from_millis(100), Duration::from_secs(1))
+            .with_jitter(Jitter::None);
+        Resilience::new(retry).with_clock(clock.clone())
+    }
+
+    #[test]
+    fn test_retries_transient_errors_with_backoff() {
+        let clock = Arc::new(ManualClock::new());
+        let resilience = policy(&clock);
+
+        let result = resilience.call(is_timeout, |attempt| {
+            if attempt < 3 { Err(TestError::Timeout) } else { Ok(attempt) }
+        });
+        assert_eq!(result, Ok(3));
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(100), Duration::from_millis(200)]);
+
+        // 不可重试的错误立即返回
+        let result: Result<(), _> = resilience.call(is_timeout, |_| Err(TestError::Rejected));
+        assert_eq!(
+            result,
+            Err(ResilienceError::Failed { error: TestError::Rejected, attempts: 1 })
+        );
+
+        // 次数用尽
+        let result: Result<(), _> = resilience.call(is_timeout, |_| Err(TestError::Timeout));
+        assert_eq!(result, Err(ResilienceError::Failed { error: TestError::Timeout, attempts: 4 }));
+    }
+
+    #[test]
+    fn test_budget_stops_retrying() {
+        let clock = Arc::new(ManualClock::new());
+        let resilience = policy(&clock).with_budget(Duration::from_millis(250));
+
+        let result: Result<(), _> = resilience.call(is_timeout, |_| {
+            clock.advance(Duration::from_millis(20));
+            Err(TestError::Timeout)
+        });
+        // 20 + 100 + 20 = 140ms 后剩余 110ms，不足以再等 200ms
+        assert_eq!(
+            result,
+            Err(ResilienceError::DeadlineExceeded { error: Some(TestError::Timeout), attempts: 2 })
+        );
+        assert_eq!(clock.now(), Duration::from_millis(140));
+    }
+
+    #[test]
+    fn test_breaker_and_bulkhead() {
+        let clock = Arc::new(ManualClock::new());
+        let breaker = Arc::new(CircuitBreaker::with_clock(
+            CircuitBreakerConfig::default()
+                .with_failure_threshold(2)
+                .with_open_timeout(Duration::from_secs(30)),
+            clock.clone(),
+        ));
+        let resilience = policy(&clock).with_circuit_breaker(breaker.clone());
+
+        let mut calls = 0;
+        let result: Result<(), _> = resilience.call(is_timeout, |_| {
+            calls += 1;
+            Err(TestError::Timeout)
+        });
+        // 第 2 次失败后熔断，不再重试
+        assert_eq!(result, Err(ResilienceError::CircuitOpen));
+        assert_eq!(calls, 2);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // 冷却后探测成功恢复
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(resilience.call(is_timeout, |_| Ok::<_, TestError>(1)), Ok(1));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let bulkhead = Arc::new(Bulkhead::new(1));
+        let resilience = policy(&clock).with_bulkhead(bulkhead.clone());
+        let _held = bulkhead.try_acquire().unwrap();
+        let result = resilience.call(is_timeout, |_| Ok::<_, TestError>(()));
+        assert_eq!(result, Err(ResilienceError::BulkheadFull));
+    }
+}
@@ -0,0 +1,148 @@
+//! 指数退避重试策略
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 退避抖动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jitter {
+    /// 不抖动
+    None,
+    /// 在 `[0, 退避]` 内均匀取值
+    Full,
+    /// 在 `[退避/2, 退避]` 内均匀取值
+    Equal,
+}
+
+/// 重试策略：第 n 次重试前等待 `min(initial_delay * multiplier^(n-1), max_delay)`，再按抖动取值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最多调用次数（含首次，至少为 1）
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: u32,
+    pub jitter: Jitter,
+}
+
+impl Default for RetryPolicy {
+    /// 最多 3 次，50ms 起每次翻倍、最长 2s，全抖动
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+            multiplier: 2,
+            jitter: Jitter::Full,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 只调用一次，不重试
+    pub fn no_retry() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_delays(mut self, initial_delay: Duration, max_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay.max(initial_delay);
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 未抖动的退避时长，`retry` 从 1 开始
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1).saturating_pow(retry.saturating_sub(1));
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// 第 `retry` 次重试前的等待时长
+    pub fn delay(&self, retry: u32, rng: &mut JitterRng) -> Duration {
+        let backoff = self.backoff(retry);
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => rng.up_to(backoff),
+            Jitter::Equal => backoff / 2 + rng.up_to(backoff - backoff / 2),
+        }
+    }
+}
+
+/// 抖动用的伪随机数（SplitMix64，不用于安全场景）
+#[derive(Debug, Clone)]
+pub struct JitterRng {
+    state: u64,
+}
+
+impl Default for JitterRng {
+    /// 以当前时间为种子
+    fn default() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        Self::new(nanos as u64)
+    }
+}
+
+impl JitterRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// `[0, max]` 内均匀取值（纳秒粒度）
+    pub fn up_to(&mut self, max: Duration) -> Duration {
+        let max_nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+        if max_nanos == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.next_u64() % (max_nanos + 1).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_jitter_bounds() {
+        let policy = RetryPolicy::default()
+            .with_delays(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(Jitter::None);
+        let delays: Vec<u64> =
+            (1..=5).map(|retry| policy.backoff(retry).as_millis() as u64).collect();
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
+
+        let mut rng = JitterRng::new(7);
+        let full = policy.with_jitter(Jitter::Full);
+        let equal = policy.with_jitter(Jitter::Equal);
+        for _ in 0..1_000 {
+            assert!(full.delay(3, &mut rng) <= Duration::from_millis(400));
+            let delay = equal.delay(3, &mut rng);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        }
+        // 同一种子的抖动序列可复现
+        let (mut a, mut b) = (JitterRng::new(42), JitterRng::new(42));
+        assert_eq!(full.delay(2, &mut a), full.delay(2, &mut b));
+        assert_eq!(RetryPolicy::default().with_max_attempts(0).max_attempts, 1);
+    }
+}
@@ -6,19 +6,23 @@ use syn::visit::Visit;
 use syn::{File, Item};
 use walkdir::WalkDir;
 
+use crate::layout::{LayoutAnalyzer, StructLayout};
 use crate::patterns::PatternDetector;
 use crate::scorer::OptimizationScore;
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AnalysisResult {
     pub files_analyzed: usize,
     pub total_lines: usize,
     pub issues: Vec<OptimizationIssue>,
     pub score: OptimizationScore,
     pub statistics: Statistics,
+    /// 热点结构体的缓存布局
+    #[serde(default)]
+    pub layouts: Vec<StructLayout>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OptimizationIssue {
     pub file: PathBuf,
     pub line: Option<usize>,
@@ -29,7 +33,7 @@ pub struct OptimizationIssue {
     pub estimated_impact: f32, // 0.0 - 1.0
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
 pub enum IssueCategory {
     Vectorization,
     MemoryAllocation,
@@ -41,7 +45,7 @@ pub enum IssueCategory {
     Algorithmic,
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Severity {
     Critical, // 严重性能问题
     High,     // 高优先级
@@ -50,7 +54,7 @@ pub enum Severity {
     Info,     // 信息提示
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Statistics {
     pub total_functions: usize,
     pub inline_candidates: usize,
@@ -60,17 +64,93 @@ pub struct Statistics {
     pub vectorizable_loops: usize,
 }
 
+/// 单个文件的分析结果（增量分析按文件缓存）
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FileAnalysis {
+    pub lines: usize,
+    pub issues: Vec<OptimizationIssue>,
+    pub functions: usize,
+    pub heap_allocations: usize,
+    pub clone_operations: usize,
+    pub loop_count: usize,
+    #[serde(default)]
+    pub layouts: Vec<StructLayout>,
+}
+
 pub struct RustCodeAnalyzer {
     root_path: PathBuf,
     pattern_detector: PatternDetector,
+    layout_analyzer: LayoutAnalyzer,
 }
 
 impl RustCodeAnalyzer {
     pub fn new(root_path: PathBuf) -> Result<Self> {
-        Ok(Self { root_path, pattern_detector: PatternDetector::new() })
+        Ok(Self {
+            root_path,
+            pattern_detector: PatternDetector::new(),
+            layout_analyzer: LayoutAnalyzer::new(),
+        })
+    }
+
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
     }
 
     pub fn analyze(&self) -> Result<AnalysisResult> {
+        let mut files = Vec::new();
+        for path in self.source_files() {
+            files.push(self.analyze_file(&path)?);
+        }
+
+        Ok(self.aggregate(files.iter()))
+    }
+
+    /// 项目下所有Rust源文件（跳过target目录）
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        WalkDir::new(&self.root_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
+            .filter(|e| !e.path().components().any(|c| c.as_os_str() == "target"))
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    /// 分析单个文件
+    pub fn analyze_file(&self, path: &Path) -> Result<FileAnalysis> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("读取文件失败: {:?}", path))?;
+        Ok(self.analyze_content(path, &content))
+    }
+
+    /// 分析已读入的文件内容
+    pub fn analyze_content(&self, path: &Path, content: &str) -> FileAnalysis {
+        let mut file = FileAnalysis { lines: content.lines().count(), ..Default::default() };
+
+        // 解析Rust代码
+        if let Ok(ast) = syn::parse_file(content) {
+            let mut visitor = CodeVisitor::new(path.to_path_buf());
+            visitor.visit_file(&ast);
+
+            file.issues.extend(visitor.issues);
+            file.functions = visitor.function_count;
+            file.heap_allocations = visitor.heap_allocations;
+            file.clone_operations = visitor.clone_count;
+            file.loop_count = visitor.loop_count;
+
+            // 热点结构体的缓存布局
+            let (layouts, layout_issues) = self.layout_analyzer.analyze(&ast, content, path);
+            file.layouts = layouts;
+            file.issues.extend(layout_issues);
+        }
+
+        // 使用正则模式检测
+        file.issues.extend(self.pattern_detector.detect_patterns(content, path));
+        file
+    }
+
+    /// 汇总各文件的分析结果并计算优化分数
+    pub fn aggregate<'a>(&self, files: impl Iterator<Item = &'a FileAnalysis>) -> AnalysisResult {
         let mut issues = Vec::new();
         let mut statistics = Statistics {
             total_functions: 0,
@@ -83,47 +163,23 @@ impl RustCodeAnalyzer {
 
         let mut files_analyzed = 0;
         let mut total_lines = 0;
+        let mut layouts = Vec::new();
 
-        // 遍历所有Rust文件
-        for entry in WalkDir::new(&self.root_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-        {
-            let path = entry.path();
-
-            // 跳过target目录
-            if path.components().any(|c| c.as_os_str() == "target") {
-                continue;
-            }
-
+        for file in files {
             files_analyzed += 1;
-            let content =
-                fs::read_to_string(path).with_context(|| format!("读取文件失败: {:?}", path))?;
-
-            total_lines += content.lines().count();
-
-            // 解析Rust代码
-            if let Ok(ast) = syn::parse_file(&content) {
-                let mut visitor = CodeVisitor::new(path.to_path_buf());
-                visitor.visit_file(&ast);
-
-                issues.extend(visitor.issues);
-                statistics.total_functions += visitor.function_count;
-                statistics.heap_allocations += visitor.heap_allocations;
-                statistics.clone_operations += visitor.clone_count;
-                statistics.loop_count += visitor.loop_count;
-            }
-
-            // 使用正则模式检测
-            let pattern_issues = self.pattern_detector.detect_patterns(&content, path);
-            issues.extend(pattern_issues);
+            total_lines += file.lines;
+            issues.extend(file.issues.iter().cloned());
+            statistics.total_functions += file.functions;
+            statistics.heap_allocations += file.heap_allocations;
+            statistics.clone_operations += file.clone_operations;
+            statistics.loop_count += file.loop_count;
+            layouts.extend(file.layouts.iter().cloned());
         }
 
         // 计算优化分数
         let score = self.calculate_score(&issues, &statistics);
 
-        Ok(AnalysisResult { files_analyzed, total_lines, issues, score, statistics })
+        AnalysisResult { files_analyzed, total_lines, issues, score, statistics, layouts }
     }
 
     fn calculate_score(
@@ -0,0 +1,302 @@
+//! 增量分析与基线对比
+//!
+//! 每次分析后按提交保存基线：`<项目>/.rust-opt-analyzer/baselines/<commit>.json`，
+//! 记录每个文件的内容哈希与分析结果，`LATEST` 指向最近一次保存的提交。
+//! 再次分析时只重新分析内容哈希变化的文件，其余复用基线结果；
+//! 新旧结果按 (文件, 类别, 描述) 对比得到新增与已修复的问题（不比较行号，避免代码移动造成误报）。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{
+    AnalysisResult, FileAnalysis, IssueCategory, OptimizationIssue, RustCodeAnalyzer,
+};
+
+/// 基线目录（相对项目根目录）
+pub const BASELINE_DIR: &str = ".rust-opt-analyzer";
+
+/// 无法获取git提交时使用的基线名
+const WORKDIR_COMMIT: &str = "workdir";
+
+/// 单个文件的基线记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// 文件内容哈希（FNV-1a）
+    pub hash: u64,
+    pub analysis: FileAnalysis,
+}
+
+/// 某次提交的分析基线
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub commit: String,
+    /// 相对项目根目录的文件路径 -> 基线记录
+    pub files: BTreeMap<PathBuf, FileEntry>,
+}
+
+/// 基线存储
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    pub fn new(root_path: &Path) -> Self {
+        Self { dir: root_path.join(BASELINE_DIR) }
+    }
+
+    fn baseline_path(&self, commit: &str) -> PathBuf {
+        self.dir.join("baselines").join(format!("{}.json", commit))
+    }
+
+    /// 读取指定提交的基线
+    pub fn load(&self, commit: &str) -> Result<Option<Baseline>> {
+        let path = self.baseline_path(commit);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json =
+            fs::read_to_string(&path).with_context(|| format!("读取基线失败: {:?}", path))?;
+        let baseline =
+            serde_json::from_str(&json).with_context(|| format!("解析基线失败: {:?}", path))?;
+        Ok(Some(baseline))
+    }
+
+    /// 读取最近一次保存的基线
+    pub fn latest(&self) -> Result<Option<Baseline>> {
+        match fs::read_to_string(self.dir.join("LATEST")) {
+            Ok(commit) => self.load(commit.trim()),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// 保存基线并更新 `LATEST`
+    pub fn save(&self, baseline: &Baseline) -> Result<PathBuf> {
+        let path = self.baseline_path(&baseline.commit);
+        fs::create_dir_all(path.parent().unwrap_or(&self.dir))
+            .with_context(|| format!("创建基线目录失败: {:?}", self.dir))?;
+        fs::write(&path, serde_json::to_string(baseline)?)
+            .with_context(|| format!("写入基线失败: {:?}", path))?;
+        fs::write(self.dir.join("LATEST"), &baseline.commit)?;
+        Ok(path)
+    }
+}
+
+/// 新旧基线之间的问题差异
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FindingsDiff {
+    /// 对比的基线提交（None=没有基线，全部问题视为新增）
+    pub base_commit: Option<String>,
+    pub commit: String,
+    /// 新增的问题
+    pub new: Vec<OptimizationIssue>,
+    /// 已修复的问题
+    pub fixed: Vec<OptimizationIssue>,
+}
+
+impl FindingsDiff {
+    pub fn is_regression(&self) -> bool {
+        !self.new.is_empty()
+    }
+}
+
+/// 一次增量分析的结果
+pub struct IncrementalRun {
+    /// 全量结果（未变化的文件复用基线）
+    pub result: AnalysisResult,
+    /// 本次分析得到的新基线
+    pub baseline: Baseline,
+    pub diff: FindingsDiff,
+    /// 重新分析的文件数
+    pub reanalyzed: usize,
+    /// 复用基线的文件数
+    pub reused: usize,
+}
+
+/// 增量分析：只分析相对 `previous` 内容有变化的文件，并与 `previous` 对比问题
+pub fn run_incremental(
+    analyzer: &RustCodeAnalyzer,
+    previous: Option<&Baseline>,
+    commit: &str,
+) -> Result<IncrementalRun> {
+    let root = analyzer.root_path();
+    let sources = analyzer.source_files();
+
+    let files = sources
+        .par_iter()
+        .map(|path| {
+            let content =
+                fs::read_to_string(path).with_context(|| format!("读取文件失败: {:?}", path))?;
+            let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            let hash = content_hash(content.as_bytes());
+
+            let cached = previous
+                .and_then(|b| b.files.get(&relative))
+                .filter(|entry| entry.hash == hash)
+                .map(|entry| {
+                    // 复用的结果指向本次的文件路径
+                    let mut analysis = entry.analysis.clone();
+                    for issue in &mut analysis.issues {
+                        issue.file = path.clone();
+                    }
+                    analysis
+                });
+            let reused = cached.is_some();
+            let analysis = cached.unwrap_or_else(|| analyzer.analyze_content(path, &content));
+            Ok((relative, FileEntry { hash, analysis }, reused))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let reused = files.iter().filter(|(_, _, reused)| *reused).count();
+    let baseline = Baseline {
+        commit: commit.to_string(),
+        files: files.into_iter().map(|(relative, entry, _)| (relative, entry)).collect(),
+    };
+    let result = analyzer.aggregate(baseline.files.values().map(|entry| &entry.analysis));
+    let diff = diff_findings(previous, &baseline);
+
+    Ok(IncrementalRun { result, reanalyzed: baseline.files.len() - reused, reused, baseline, diff })
+}
+
+/// 对比两份基线的问题
+///
+/// 同一文件内相同 (类别, 描述) 的问题按数量对比，多出的部分即新增或已修复
+pub fn diff_findings(previous: Option<&Baseline>, current: &Baseline) -> FindingsDiff {
+    let empty = BTreeMap::new();
+    let previous_files = previous.map_or(&empty, |b| &b.files);
+
+    let mut diff = FindingsDiff {
+        base_commit: previous.map(|b| b.commit.clone()),
+        commit: current.commit.clone(),
+        ..Default::default()
+    };
+    for (path, entry) in &current.files {
+        let before = previous_files.get(path).map_or(&[][..], |e| &e.analysis.issues[..]);
+        diff.new.extend(surplus(path, &entry.analysis.issues, before));
+        diff.fixed.extend(surplus(path, before, &entry.analysis.issues));
+    }
+    for (path, entry) in previous_files {
+        if !current.files.contains_key(path) {
+            diff.fixed.extend(surplus(path, &entry.analysis.issues, &[]));
+        }
+    }
+    diff
+}
+
+/// `issues` 中比 `other` 多出的问题（文件路径改为相对路径）
+fn surplus(
+    path: &Path,
+    issues: &[OptimizationIssue],
+    other: &[OptimizationIssue],
+) -> Vec<OptimizationIssue> {
+    let mut remaining: HashMap<(&IssueCategory, &str), usize> = HashMap::new();
+    for issue in other {
+        *remaining.entry((&issue.category, issue.message.as_str())).or_default() += 1;
+    }
+    issues
+        .iter()
+        .filter(|issue| match remaining.get_mut(&(&issue.category, issue.message.as_str())) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .map(|issue| OptimizationIssue { file: path.to_path_buf(), ..issue.clone() })
+        .collect()
+}
+
+/// 项目当前的git提交（不在git仓库中时为 `workdir`）
+pub fn current_commit(root_path: &Path) -> String {
+    Command::new("git")
+        .arg("-C")
+        .arg(root_path)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| WORKDIR_COMMIT.to_string())
+}
+
+/// FNV-1a 64位哈希（跨版本稳定，用于持久化）
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, name: &str, content: &str) {
+        fs::write(root.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_incremental_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a.rs", "fn a() {\n    let v = Vec::new();\n}\n");
+        write(root, "b.rs", "fn b() -> u32 { 1 }\n");
+
+        let analyzer = RustCodeAnalyzer::new(root.to_path_buf()).unwrap();
+        let first = run_incremental(&analyzer, None, "c1").unwrap();
+        assert_eq!((first.reanalyzed, first.reused), (2, 0));
+        assert_eq!(first.diff.new.len(), first.result.issues.len());
+
+        let store = BaselineStore::new(root);
+        store.save(&first.baseline).unwrap();
+        let previous = store.latest().unwrap().unwrap();
+        assert_eq!(previous.commit, "c1");
+
+        // 修复 a.rs 的堆分配，b.rs 引入循环
+        write(root, "a.rs", "fn a() {\n    let v = [0u8; 8];\n}\n");
+        write(root, "b.rs", "fn b(v: &[u32]) { for x in v { let _ = x; } }\n");
+        write(root, "c.rs", "#[inline]\nfn c() -> u32 { 2 }\n");
+        let second = run_incremental(&analyzer, Some(&previous), "c2").unwrap();
+        assert_eq!((second.reanalyzed, second.reused), (3, 0));
+        assert!(second.diff.is_regression());
+        assert!(second.diff.new.iter().all(|i| i.file == Path::new("b.rs")));
+        assert!(second.diff.fixed.iter().any(|i| i.file == Path::new("a.rs")));
+
+        let third = run_incremental(&analyzer, Some(&second.baseline), "c3").unwrap();
+        assert_eq!((third.reanalyzed, third.reused), (0, 3));
+        assert!(third.diff.new.is_empty() && third.diff.fixed.is_empty());
+        assert_eq!(third.result.issues.len(), second.result.issues.len());
+    }
+
+    #[test]
+    fn test_diff_ignores_moved_lines() {
+        let issue = |line| OptimizationIssue {
+            file: PathBuf::from("x.rs"),
+            line: Some(line),
+            category: IssueCategory::Cloning,
+            severity: crate::analyzer::Severity::Low,
+            message: "clone".to_string(),
+            suggestion: String::new(),
+            estimated_impact: 0.1,
+        };
+        let baseline = |commit: &str, issues| Baseline {
+            commit: commit.to_string(),
+            files: BTreeMap::from([(
+                PathBuf::from("x.rs"),
+                FileEntry { hash: 0, analysis: FileAnalysis { issues, ..Default::default() } },
+            )]),
+        };
+
+        let before = baseline("c1", vec![issue(1)]);
+        let after = baseline("c2", vec![issue(5), issue(9)]);
+        let diff = diff_findings(Some(&before), &after);
+        assert_eq!(diff.new.len(), 1);
+        assert!(diff.fixed.is_empty());
+        assert_eq!(diff_findings(Some(&after), &before).fixed.len(), 1);
+    }
+}
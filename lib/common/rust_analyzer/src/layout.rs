@@ -0,0 +1,299 @@
+//! 缓存布局分析
+//!
+//! 找出标注了 `#[repr(...)]` 或派生 `CacheAnalyzer` 的热点结构体，用 `cache_analyzer_types`
+//! 静态估算其布局（不经编译），报告填充浪费、字段顺序、缓存行分割与伪共享问题。
+//! 字段类型无法估算（如自定义类型）的结构体跳过。
+
+use std::path::{Path, PathBuf};
+
+use cache_analyzer_types::layout::{StaticLayout, estimate_layout};
+use cache_analyzer_types::validation::CompileTimeValidation;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{DeriveInput, ItemStruct, Token};
+
+use crate::analyzer::{IssueCategory, OptimizationIssue, Severity};
+
+/// 字段布局
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub alignment: usize,
+    pub is_hot: bool,
+}
+
+/// 单个结构体的布局报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructLayout {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub name: String,
+    pub size: usize,
+    pub alignment: usize,
+    pub cache_lines: usize,
+    pub padding_bytes: usize,
+    pub padding_percentage: f32,
+    pub fields: Vec<FieldLayout>,
+    /// 该结构体发现的问题数
+    pub findings: usize,
+}
+
+/// 缓存布局分析器
+pub struct LayoutAnalyzer {
+    cache_line_size: usize,
+    max_padding_percentage: f32,
+}
+
+impl LayoutAnalyzer {
+    /// 阈值沿用 `CacheAnalyzer` 编译时检查的默认配置
+    pub fn new() -> Self {
+        let config = CompileTimeValidation::default();
+        Self {
+            cache_line_size: config.cache_line_size,
+            max_padding_percentage: config.max_padding_percentage,
+        }
+    }
+
+    /// 分析文件中的热点结构体
+    pub fn analyze(
+        &self,
+        ast: &syn::File,
+        content: &str,
+        file_path: &Path,
+    ) -> (Vec<StructLayout>, Vec<OptimizationIssue>) {
+        let mut collector = HotStructCollector::default();
+        collector.visit_file(ast);
+
+        let mut layouts = Vec::new();
+        let mut issues = Vec::new();
+        for item in collector.structs {
+            let input = DeriveInput::from(item);
+            let Ok(layout) = estimate_layout(&input, self.cache_line_size) else {
+                continue;
+            };
+            let line = struct_line(content, &layout.report.struct_name);
+            let findings = self.findings(&layout, file_path, line);
+
+            let report = layout.report;
+            layouts.push(StructLayout {
+                file: file_path.to_path_buf(),
+                line,
+                name: report.struct_name,
+                size: report.total_size,
+                alignment: report.alignment,
+                cache_lines: report.cache_lines_needed,
+                padding_bytes: report.padding_bytes,
+                padding_percentage: report.padding_percentage,
+                fields: report
+                    .field_analyses
+                    .into_iter()
+                    .map(|f| FieldLayout {
+                        name: f.name,
+                        offset: f.offset,
+                        size: f.size,
+                        alignment: f.alignment,
+                        is_hot: f.is_hot,
+                    })
+                    .collect(),
+                findings: findings.len(),
+            });
+            issues.extend(findings);
+        }
+
+        (layouts, issues)
+    }
+
+    fn findings(
+        &self,
+        layout: &StaticLayout,
+        file_path: &Path,
+        line: Option<usize>,
+    ) -> Vec<OptimizationIssue> {
+        let report = &layout.report;
+        let name = &report.struct_name;
+        let issue =
+            |category, severity, message: String, suggestion: String, impact| OptimizationIssue {
+                file: file_path.to_path_buf(),
+                line,
+                category,
+                severity,
+                message,
+                suggestion,
+                estimated_impact: impact,
+            };
+        let mut issues = Vec::new();
+
+        for pair in &layout.false_sharing {
+            issues.push(issue(
+                IssueCategory::Concurrency,
+                Severity::High,
+                format!(
+                    "结构体 {} 的字段 '{}' 与 '{}' 位于同一缓存行，存在伪共享风险",
+                    name, pair.first, pair.second
+                ),
+                format!(
+                    "将多线程访问的字段分别包装为 #[repr(align({}))] 类型，或在其间填充隔离",
+                    self.cache_line_size
+                ),
+                0.8,
+            ));
+        }
+
+        for field in &layout.split_hot_fields {
+            issues.push(issue(
+                IssueCategory::CacheAlignment,
+                Severity::High,
+                format!("结构体 {} 的热点字段 '{}' 跨越缓存行边界", name, field),
+                "将热点字段移到结构体开头，确保其位于同一缓存行内".to_string(),
+                0.6,
+            ));
+        }
+
+        // 显式对齐产生的填充是有意为之
+        if layout.repr.align.is_none() && report.padding_percentage > self.max_padding_percentage {
+            issues.push(issue(
+                IssueCategory::CacheAlignment,
+                Severity::Medium,
+                format!(
+                    "结构体 {} 填充 {} 字节，占 {:.1}%",
+                    name, report.padding_bytes, report.padding_percentage
+                ),
+                "减小显式对齐，或将小字段聚合以减少填充".to_string(),
+                0.4,
+            ));
+        }
+
+        if !report.is_current_order_optimal {
+            let order: Vec<&str> = report
+                .optimal_field_order
+                .iter()
+                .map(|&idx| report.field_analyses[idx].name.as_str())
+                .collect();
+            issues.push(issue(
+                IssueCategory::CacheAlignment,
+                Severity::Low,
+                format!("结构体 {} 的 repr(C) 字段顺序产生了多余的填充", name),
+                format!("按对齐降序排列字段: {}", order.join(", ")),
+                0.3,
+            ));
+        }
+
+        if report.cache_lines_needed > 1 && report.alignment < self.cache_line_size {
+            issues.push(issue(
+                IssueCategory::CacheAlignment,
+                Severity::Low,
+                format!(
+                    "结构体 {} 大小 {} 字节，跨越 {} 个缓存行且未按缓存行对齐",
+                    name, report.total_size, report.cache_lines_needed
+                ),
+                format!(
+                    "使用 #[repr(align({}))] 对齐到缓存行，或拆分冷热字段",
+                    self.cache_line_size
+                ),
+                0.3,
+            ));
+        }
+
+        issues
+    }
+}
+
+/// 收集标注了 repr 或派生 CacheAnalyzer 的结构体（含内联模块）
+#[derive(Default)]
+struct HotStructCollector {
+    structs: Vec<ItemStruct>,
+}
+
+impl<'ast> Visit<'ast> for HotStructCollector {
+    fn visit_item_struct(&mut self, node: &'ast ItemStruct) {
+        let hot = node
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("repr") || derives_cache_analyzer(attr));
+        if hot {
+            self.structs.push(node.clone());
+        }
+
+        syn::visit::visit_item_struct(self, node);
+    }
+}
+
+fn derives_cache_analyzer(attr: &syn::Attribute) -> bool {
+    attr.path().is_ident("derive")
+        && attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated).is_ok_and(
+            |paths| {
+                paths.iter().any(|p| p.segments.last().is_some_and(|s| s.ident == "CacheAnalyzer"))
+            },
+        )
+}
+
+/// 结构体定义所在行
+fn struct_line(content: &str, name: &str) -> Option<usize> {
+    let pattern = Regex::new(&format!(r"\bstruct\s+{}\b", regex::escape(name))).ok()?;
+    let start = pattern.find(content)?.start();
+    Some(content[..start].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(source: &str) -> (Vec<StructLayout>, Vec<OptimizationIssue>) {
+        let ast = syn::parse_file(source).unwrap();
+        LayoutAnalyzer::new().analyze(&ast, source, Path::new("hot.rs"))
+    }
+
+    #[test]
+    fn test_detects_padding_and_false_sharing() {
+        let (layouts, issues) = analyze(
+            r#"
+struct Plain { a: u8, b: u64 }
+
+#[repr(C)]
+pub struct Header {
+    flag: u8,
+    seq: u64,
+    kind: u8,
+}
+
+#[derive(Debug, CacheAnalyzer)]
+struct Counters {
+    head: AtomicU64,
+    tail: AtomicU64,
+}
+"#,
+        );
+
+        // 未标注的结构体不分析
+        assert_eq!(
+            layouts.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(),
+            ["Header", "Counters"]
+        );
+        let header = &layouts[0];
+        assert_eq!((header.size, header.padding_bytes, header.line), (24, 14, Some(5)));
+        assert_eq!(header.fields[1].offset, 8);
+        assert!(issues.iter().any(|i| i.message.contains("Header") && i.message.contains("填充")));
+        assert!(issues.iter().any(|i| i.message.contains("repr(C) 字段顺序")));
+        assert!(issues.iter().any(|i| {
+            i.category == IssueCategory::Concurrency && i.message.contains("'head' 与 'tail'")
+        }));
+    }
+
+    #[test]
+    fn test_aligned_struct_without_findings() {
+        let (layouts, issues) = analyze(
+            r#"
+#[repr(align(64))]
+struct Padded {
+    value: AtomicU64,
+}
+"#,
+        );
+        assert_eq!((layouts[0].size, layouts[0].padding_bytes), (64, 56));
+        assert!(issues.is_empty());
+    }
+}
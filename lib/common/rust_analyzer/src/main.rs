@@ -4,6 +4,8 @@ use clap::{Parser, Subcommand};
 use colored::Colorize;
 
 mod analyzer;
+mod baseline;
+mod layout;
 mod llvm_analyzer;
 mod optimizer;
 mod patterns;
@@ -11,6 +13,7 @@ mod reporter;
 mod scorer;
 
 use analyzer::RustCodeAnalyzer;
+use baseline::BaselineStore;
 use llvm_analyzer::LLVMAnalyzer;
 use reporter::Reporter;
 
@@ -43,6 +46,37 @@ enum Commands {
         deep: bool,
     },
 
+    /// 增量分析：只分析相对基线变化的文件，并报告新增与已修复的问题
+    Diff {
+        /// 要分析的项目路径
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+
+        /// 对比的基线提交（默认为最近一次保存的基线）
+        #[arg(short, long)]
+        base: Option<String>,
+
+        /// 本次结果保存的提交（默认为当前git HEAD）
+        #[arg(short, long)]
+        commit: Option<String>,
+
+        /// 输出格式 (json, yaml, terminal)
+        #[arg(short, long, default_value = "terminal")]
+        output: String,
+
+        /// 输出文件路径
+        #[arg(short = 'f', long)]
+        output_file: Option<PathBuf>,
+
+        /// 不保存本次结果为基线
+        #[arg(long)]
+        no_save: bool,
+
+        /// 出现新增问题时以非零状态退出
+        #[arg(long)]
+        fail_on_new: bool,
+    },
+
     /// 生成并分析LLVM IR
     LlvmAnalyze {
         /// 项目路径
@@ -82,10 +116,41 @@ fn main() -> anyhow::Result<()> {
                 let llvm_result = llvm_analyzer.generate_and_analyze()?;
 
                 let reporter = Reporter::new(analysis_result, Some(llvm_result));
-                reporter.generate_report(&output, output_file.as_ref().map(|v| &**v))?;
+                reporter.generate_report(&output, output_file.as_deref())?;
             } else {
                 let reporter = Reporter::new(analysis_result, None);
-                reporter.generate_report(&output, output_file.as_ref().map(|v| &**v))?;
+                reporter.generate_report(&output, output_file.as_deref())?;
+            }
+
+            println!("{}", "✅ 分析完成!".green().bold());
+        }
+
+        Commands::Diff { path, base, commit, output, output_file, no_save, fail_on_new } => {
+            println!("{}", "🔍 开始增量分析...".green().bold());
+
+            let store = BaselineStore::new(&path);
+            let previous = match &base {
+                Some(commit) => Some(
+                    store
+                        .load(commit)?
+                        .ok_or_else(|| anyhow::anyhow!("未找到提交 {} 的基线", commit))?,
+                ),
+                None => store.latest()?,
+            };
+            let commit = commit.unwrap_or_else(|| baseline::current_commit(&path));
+
+            let analyzer = RustCodeAnalyzer::new(path)?;
+            let run = baseline::run_incremental(&analyzer, previous.as_ref(), &commit)?;
+            reporter::generate_diff_report(&run, &output, output_file.as_deref())?;
+
+            if !no_save {
+                let saved = store.save(&run.baseline)?;
+                println!("💾 基线已保存到: {:?}", saved);
+            }
+
+            if fail_on_new && run.diff.is_regression() {
+                println!("{}", format!("❌ 新增 {} 个问题", run.diff.new.len()).red().bold());
+                std::process::exit(1);
             }
 
             println!("{}", "✅ 分析完成!".green().bold());
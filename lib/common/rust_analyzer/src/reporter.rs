@@ -4,6 +4,7 @@ use anyhow::Result;
 use colored::Colorize;
 
 use crate::analyzer::{AnalysisResult, IssueCategory, Severity};
+use crate::baseline::IncrementalRun;
 use crate::llvm_analyzer::LLVMAnalysisResult;
 
 pub struct Reporter {
@@ -136,6 +137,27 @@ impl Reporter {
             }
         }
 
+        // 缓存布局
+        if !self.analysis_result.layouts.is_empty() {
+            println!("\n{}", "🧱 热点结构体缓存布局:".yellow().bold());
+            for layout in self.analysis_result.layouts.iter().take(20) {
+                println!(
+                    "  • {} ({}): {} 字节, 对齐 {}, {} 个缓存行, 填充 {} 字节 ({:.1}%), 问题 {} 个",
+                    layout.name.bold(),
+                    layout.file.display(),
+                    layout.size,
+                    layout.alignment,
+                    layout.cache_lines,
+                    layout.padding_bytes,
+                    layout.padding_percentage,
+                    layout.findings
+                );
+            }
+            if self.analysis_result.layouts.len() > 20 {
+                println!("  ... 还有 {} 个结构体", self.analysis_result.layouts.len() - 20);
+            }
+        }
+
         // 优化建议
         println!("\n{}", "💡 优化建议:".green().bold());
         let suggestions = self.generate_suggestions();
@@ -348,6 +370,11 @@ impl Reporter {
             <h2>🔍 发现的问题</h2>
             {}
         </div>
+
+        <div class="stats">
+            <h2>🧱 热点结构体缓存布局</h2>
+            {}
+        </div>
     </div>
 </body>
 </html>"#,
@@ -367,7 +394,8 @@ impl Reporter {
             score.optimization_potential(),
             score.optimization_potential(),
             score.optimization_potential(),
-            self.build_issues_html()
+            self.build_issues_html(),
+            self.build_layouts_html()
         )
     }
 
@@ -403,6 +431,46 @@ impl Reporter {
         html
     }
 
+    fn build_layouts_html(&self) -> String {
+        let mut html = String::new();
+
+        for layout in &self.analysis_result.layouts {
+            let fields: Vec<String> = layout
+                .fields
+                .iter()
+                .map(|f| {
+                    format!(
+                        "{}{} @{} ({}B)",
+                        if f.is_hot { "🔥" } else { "" },
+                        f.name,
+                        f.offset,
+                        f.size
+                    )
+                })
+                .collect();
+
+            html.push_str(&format!(
+                r#"<div class="stat-item">
+                    <strong>{}</strong>: {} 字节 | 对齐 {} | {} 个缓存行 | 填充 {} 字节 ({:.1}%) | 问题 {} 个
+                    <div style="margin-top: 5px; font-size: 0.9em; color: #666;">
+                        📁 {:?} | {}
+                    </div>
+                </div>"#,
+                layout.name,
+                layout.size,
+                layout.alignment,
+                layout.cache_lines,
+                layout.padding_bytes,
+                layout.padding_percentage,
+                layout.findings,
+                layout.file,
+                fields.join(", ")
+            ));
+        }
+
+        html
+    }
+
     fn generate_suggestions(&self) -> Vec<String> {
         let mut suggestions = Vec::new();
 
@@ -450,3 +518,56 @@ impl Reporter {
         }
     }
 }
+
+/// 增量分析的基线对比报告 (json, yaml, terminal)
+pub fn generate_diff_report(
+    run: &IncrementalRun,
+    format: &str,
+    output_file: Option<&Path>,
+) -> Result<()> {
+    let text = match format {
+        "json" => serde_json::to_string_pretty(&run.diff)?,
+        "yaml" => serde_yaml::to_string(&run.diff)?,
+        _ => {
+            print_diff_terminal(run);
+            return Ok(());
+        }
+    };
+
+    if let Some(path) = output_file {
+        std::fs::write(path, text)?;
+        println!("✅ 对比报告已保存到: {:?}", path);
+    } else {
+        println!("{}", text);
+    }
+
+    Ok(())
+}
+
+fn print_diff_terminal(run: &IncrementalRun) {
+    let diff = &run.diff;
+    println!("\n{}", "📊 增量分析:".yellow().bold());
+    println!("  • 当前提交: {}", diff.commit.green());
+    println!("  • 对比基线: {}", diff.base_commit.as_deref().unwrap_or("无").green());
+    println!("  • 重新分析文件数: {}", run.reanalyzed.to_string().yellow());
+    println!("  • 复用基线文件数: {}", run.reused.to_string().green());
+    println!("  • 总体评分: {:.1}/100", run.result.score.overall);
+
+    for (title, issues, icon) in [("新增问题", &diff.new, "🔴"), ("已修复问题", &diff.fixed, "🟢")]
+    {
+        println!("\n{} {} ({} 个):", icon, title.bold(), issues.len());
+        for issue in issues.iter().take(20) {
+            let location = match issue.line {
+                Some(line) => format!("{}:{}", issue.file.display(), line),
+                None => issue.file.display().to_string(),
+            };
+            println!(
+                "  • [{:?}/{:?}] {} ({})",
+                issue.category, issue.severity, issue.message, location
+            );
+        }
+        if issues.len() > 20 {
+            println!("  ... 还有 {} 个", issues.len() - 20);
+        }
+    }
+}
@@ -97,8 +97,9 @@ pub fn derive_sbe_enum(input: TokenStream) -> TokenStream {
 
 /// Derive macro for zero-copy SBE view
 ///
-/// Generates a view type that borrows from the input buffer without allocation.
-/// This is truly zero-copy - no heap allocation, no stack copy.
+/// Generates a flyweight view type that borrows from the input buffer without allocation.
+/// This is truly zero-copy - no heap allocation, no stack copy. Byte arrays and
+/// variable-length data are returned as slices borrowed for the buffer's lifetime.
 ///
 /// # Example
 /// ```ignore
@@ -117,6 +118,8 @@ pub fn derive_sbe_enum(input: TokenStream) -> TokenStream {
 ///
 /// // Usage - zero allocation!
 /// let view = TradeView::from_bytes(&buffer).unwrap();
+/// // Or wrap a full message, checking the header's template and schema IDs
+/// let view = TradeView::from_message(&message).unwrap();
 /// let trade_id = view.trade_id();  // Reads directly from buffer
 /// ```
 #[proc_macro_derive(SbeView, attributes(sbe))]
@@ -1,16 +1,29 @@
 //! Zero-copy view code generation
+//!
+//! The generated `{Name}View<'a>` is a flyweight over `&'a [u8]`: every
+//! accessor reads straight from the borrowed buffer, byte arrays and
+//! variable-length data are returned as borrowed slices tied to `'a`, and
+//! nothing is materialized into an owned struct.
+//!
+//! Repeating groups and variable-length data are located from the message
+//! header's blockLength rather than this version's fixed block size, so
+//! messages from a newer schema version that appends fixed fields still decode.
 
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Data, DeriveInput, Fields, Result};
 
-use crate::attrs::SbeFieldAttrs;
+use crate::attrs::{SbeContainerAttrs, SbeFieldAttrs};
 use crate::types::{OffsetCalculator, TypeMapper};
 
+/// Message header length (blockLength + templateId + schemaId + version)
+const HEADER_LENGTH: usize = 8;
+
 /// Generate zero-copy view type
 pub fn generate_view(input: &DeriveInput) -> Result<TokenStream> {
     let name = &input.ident;
     let view_name = quote::format_ident!("{}View", name);
+    let container_attrs = SbeContainerAttrs::from_attributes(&input.attrs)?;
 
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
@@ -22,22 +35,26 @@ pub fn generate_view(input: &DeriveInput) -> Result<TokenStream> {
 
     let mut offset_calc = OffsetCalculator::new();
     let mut field_methods = Vec::new();
+    let mut var_data_fields = Vec::new();
+    let mut group_fields = Vec::new();
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
         let field_ty = &field.ty;
 
         if TypeMapper::is_repeating_group(field_ty) {
+            group_fields.push(field_name.clone());
             continue;
         }
         if TypeMapper::is_var_data(field_ty) {
+            var_data_fields.push(field_name.clone());
             continue;
         }
 
         // Get offset for this field (advances the counter)
         let offset = offset_calc.next_offset(field_ty).unwrap_or_else(|| offset_calc.total_size());
 
-        let accessor = generate_view_accessor(field_name, field_ty, offset);
+        let accessor = generate_view_accessor(field_name, field_ty, offset)?;
         field_methods.push(accessor);
 
         // For custom types with explicit size, we need to skip additional bytes
@@ -54,24 +71,142 @@ pub fn generate_view(input: &DeriveInput) -> Result<TokenStream> {
 
     let block_length = offset_calc.total_size();
 
+    // Repeating groups follow the fixed block, each prefixed by a dimension
+    // header: blockLength (u16) + numInGroup (u16)
+    let group_count = group_fields.len();
+    for (index, group_field_name) in group_fields.iter().enumerate() {
+        field_methods.push(quote! {
+            /// repeating group field - one borrowed slice per entry, None if truncated
+            #[inline]
+            pub fn #group_field_name(&self) -> Option<core::slice::ChunksExact<'a, u8>> {
+                let (start, block_length, count) = self.group_at(#index)?;
+                if block_length == 0 {
+                    return None;
+                }
+                Some(self.data.get(start..start + block_length * count)?.chunks_exact(block_length))
+            }
+        });
+    }
+
+    // Variable-length data follows the groups, each entry prefixed by a u16 length
+    let var_data_start = match group_count.checked_sub(1) {
+        None => quote! { self.acting_block_length },
+        Some(last) => quote! {{
+            let (start, block_length, count) = self.group_at(#last)?;
+            start + block_length * count
+        }},
+    };
+    for (index, var_field_name) in var_data_fields.iter().enumerate() {
+        field_methods.push(quote! {
+            /// variable-length data field - borrowed from the buffer, None if truncated
+            #[inline]
+            pub fn #var_field_name(&self) -> Option<&'a [u8]> {
+                self.var_data_at(#index)
+            }
+        });
+    }
+
+    let template_id = container_attrs.template_id.unwrap_or(1);
+    let schema_id = container_attrs.schema_id.unwrap_or(1);
+    let version = container_attrs.version.unwrap_or(0);
+
     let output = quote! {
         #[derive(Debug, Clone, Copy)]
-        pub struct #view_name<'a> { data: &'a [u8] }
+        pub struct #view_name<'a> {
+            data: &'a [u8],
+            /// blockLength of the wrapped message: variable-length data starts here
+            acting_block_length: usize,
+        }
 
         impl<'a> #view_name<'a> {
+            pub const SBE_TEMPLATE_ID: u16 = #template_id;
+            pub const SBE_SCHEMA_ID: u16 = #schema_id;
+            pub const SBE_SCHEMA_VERSION: u16 = #version;
+
             #[inline]
             pub fn from_bytes(buffer: &'a [u8]) -> Option<Self> {
-                if buffer.len() >= #block_length { Some(Self { data: buffer }) } else { None }
+                if buffer.len() >= #block_length {
+                    Some(Self { data: buffer, acting_block_length: #block_length })
+                } else {
+                    None
+                }
             }
 
+            /// Wrap a message that starts with the 8-byte SBE message header.
+            ///
+            /// Variable-length data is located after the header's blockLength,
+            /// so messages from a newer schema version with extra fixed fields
+            /// still decode. Returns None if the buffer is truncated, the
+            /// header names a different template or schema, or blockLength is
+            /// shorter than this version's fixed block.
             #[inline]
-            pub unsafe fn from_bytes_unchecked(buffer: &'a [u8]) -> Self { Self { data: buffer } }
+            pub fn from_message(buffer: &'a [u8]) -> Option<Self> {
+                let header = buffer.get(..#HEADER_LENGTH)?;
+                let acting_block_length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let template_id = u16::from_le_bytes([header[2], header[3]]);
+                let schema_id = u16::from_le_bytes([header[4], header[5]]);
+                if template_id != Self::SBE_TEMPLATE_ID || schema_id != Self::SBE_SCHEMA_ID {
+                    return None;
+                }
+                let data = buffer.get(#HEADER_LENGTH..)?;
+                if acting_block_length < #block_length || data.len() < acting_block_length {
+                    return None;
+                }
+                Some(Self { data, acting_block_length })
+            }
+
+            /// # Safety
+            ///
+            /// `buffer` must be at least `block_length()` bytes long.
+            #[inline]
+            pub unsafe fn from_bytes_unchecked(buffer: &'a [u8]) -> Self {
+                Self { data: buffer, acting_block_length: #block_length }
+            }
 
             #[inline]
             pub fn as_bytes(&self) -> &'a [u8] { self.data }
 
             pub const fn block_length() -> usize { #block_length }
 
+            /// blockLength of the wrapped message (`block_length()` unless
+            /// created by `from_message` from a newer schema version)
+            #[inline]
+            pub fn acting_block_length(&self) -> usize { self.acting_block_length }
+
+            /// (entries offset, entry blockLength, numInGroup) of the `index`-th group
+            #[inline]
+            #[allow(dead_code)]
+            fn group_at(&self, index: usize) -> Option<(usize, usize, usize)> {
+                let mut offset = self.acting_block_length;
+                for _ in 0..index {
+                    let (start, block_length, count) = self.group_header(offset)?;
+                    offset = start + block_length * count;
+                }
+                self.group_header(offset)
+            }
+
+            #[inline]
+            #[allow(dead_code)]
+            fn group_header(&self, offset: usize) -> Option<(usize, usize, usize)> {
+                let header = self.data.get(offset..offset + 4)?;
+                let block_length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let count = u16::from_le_bytes([header[2], header[3]]) as usize;
+                Some((offset + 4, block_length, count))
+            }
+
+            #[inline]
+            #[allow(dead_code)]
+            fn var_data_at(&self, index: usize) -> Option<&'a [u8]> {
+                let mut offset = #var_data_start;
+                for _ in 0..index {
+                    let prefix = self.data.get(offset..offset + 2)?;
+                    offset += 2 + u16::from_le_bytes([prefix[0], prefix[1]]) as usize;
+                }
+                let prefix = self.data.get(offset..offset + 2)?;
+                let length = u16::from_le_bytes([prefix[0], prefix[1]]) as usize;
+                self.data.get(offset + 2..offset + 2 + length)
+            }
+
             #(#field_methods)*
         }
     };
@@ -81,36 +216,61 @@ pub fn generate_view(input: &DeriveInput) -> Result<TokenStream> {
 
 /// Generate view accessor for a field type
 ///
-/// Uses ZeroCopyDecode trait if available, otherwise falls back to primitive handling
+/// - `Option<T>`: reads `T` and maps the SBE null value to `None`
+/// - `[u8; N]`: borrows the bytes instead of copying them
+/// - everything else goes through the `ZeroCopyDecode` trait; custom types
+///   fail at compile time if they don't implement it
 fn generate_view_accessor(
     field_name: &syn::Ident,
     field_ty: &syn::Type,
     offset: usize,
-) -> TokenStream {
-    let ty_str = quote!(#field_ty).to_string();
-
-    // If type implements ZeroCopyDecode, use the trait method
-    // Otherwise, use primitive handling
-    match ty_str.as_str() {
-        // Primitive types that have ZeroCopyDecode implementation
-        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "f32" | "f64" | "bool"
-        | "char" => {
-            quote! {
-                #[inline]
-                pub fn #field_name(&self) -> #field_ty {
-                    <#field_ty as sbe::ZeroCopyDecode>::zero_copy_decode(self.data, #offset)
-                }
+) -> Result<TokenStream> {
+    if let Some(inner_ty) = TypeMapper::inner_type(field_ty) {
+        let inner_str = quote!(#inner_ty).to_string();
+        let is_null = if matches!(inner_str.as_str(), "f32" | "f64") {
+            quote! { value.is_nan() }
+        } else {
+            let null_value = TypeMapper::null_value(inner_ty)
+                .ok_or_else(|| syn::Error::new_spanned(inner_ty, "No null value for type"))?;
+            let null_value: TokenStream = null_value.parse().unwrap();
+            quote! { value == #null_value }
+        };
+        let read = if inner_str == "bool" {
+            quote! { <u8 as sbe::ZeroCopyDecode>::zero_copy_decode(self.data, #offset) }
+        } else {
+            quote! { <#inner_ty as sbe::ZeroCopyDecode>::zero_copy_decode(self.data, #offset) }
+        };
+        let wrap = if inner_str == "bool" {
+            quote! { value != 0 }
+        } else {
+            quote! { value }
+        };
+        return Ok(quote! {
+            #[inline]
+            pub fn #field_name(&self) -> #field_ty {
+                let value = #read;
+                if #is_null { None } else { Some(#wrap) }
             }
-        }
-        // Custom types - generate code that uses ZeroCopyDecode trait
-        // The generated code will fail at compile time if the type doesn't implement ZeroCopyDecode
-        _ => {
-            quote! {
+        });
+    }
+
+    if let syn::Type::Array(arr) = field_ty {
+        let elem_ty = &arr.elem;
+        if quote!(#elem_ty).to_string() == "u8" {
+            let len = &arr.len;
+            return Ok(quote! {
                 #[inline]
-                pub fn #field_name(&self) -> #field_ty {
-                    <#field_ty as sbe::ZeroCopyDecode>::zero_copy_decode(self.data, #offset)
+                pub fn #field_name(&self) -> &'a #field_ty {
+                    self.data[#offset..#offset + #len].try_into().unwrap()
                 }
-            }
+            });
         }
     }
+
+    Ok(quote! {
+        #[inline]
+        pub fn #field_name(&self) -> #field_ty {
+            <#field_ty as sbe::ZeroCopyDecode>::zero_copy_decode(self.data, #offset)
+        }
+    })
 }
@@ -0,0 +1,146 @@
+use sbe_derive::SbeView;
+
+#[derive(SbeView)]
+#[sbe(template_id = 7, schema_id = 1, version = 0)]
+pub struct Quote {
+    #[sbe(id = 0)]
+    pub instrument_id: u32,
+    #[sbe(id = 1)]
+    pub bid: Option<i64>,
+    #[sbe(id = 2)]
+    pub ask: Option<f64>,
+    #[sbe(id = 3)]
+    pub symbol: [u8; 8],
+    #[sbe(id = 4)]
+    pub active: Option<bool>,
+    #[sbe(id = 5)]
+    pub note: Vec<u8>,
+    #[sbe(id = 6)]
+    pub venue: Vec<u8>,
+}
+
+fn quote_message() -> Vec<u8> {
+    quote_message_with_block(29)
+}
+
+/// Message whose fixed block is padded to `block_length` bytes, as a newer
+/// schema version with additional fixed fields would encode it
+fn quote_message_with_block(block_length: u16) -> Vec<u8> {
+    // Header: blockLength, templateId, schemaId, version
+    let mut buffer = Vec::new();
+    buffer.extend(block_length.to_le_bytes());
+    buffer.extend(7u16.to_le_bytes());
+    buffer.extend(1u16.to_le_bytes());
+    buffer.extend(0u16.to_le_bytes());
+
+    // Fixed block
+    buffer.extend(42u32.to_le_bytes());
+    buffer.extend(i64::MIN.to_le_bytes()); // null bid
+    buffer.extend(1.5f64.to_le_bytes());
+    buffer.extend(*b"BTCUSDT\0");
+    buffer.push(1);
+    buffer.resize(8 + block_length as usize, 0xEE);
+
+    // Variable-length data
+    buffer.extend(2u16.to_le_bytes());
+    buffer.extend(b"hi");
+    buffer.extend(3u16.to_le_bytes());
+    buffer.extend(b"XYZ");
+    buffer
+}
+
+#[test]
+fn test_view_reads_from_buffer() {
+    let buffer = quote_message();
+    let view = QuoteView::from_message(&buffer).unwrap();
+
+    assert_eq!(QuoteView::block_length(), 29);
+    assert_eq!(view.instrument_id(), 42);
+    assert_eq!(view.bid(), None);
+    assert_eq!(view.ask(), Some(1.5));
+    assert_eq!(view.symbol(), b"BTCUSDT\0");
+    assert_eq!(view.active(), Some(true));
+    assert_eq!(view.note(), Some(&b"hi"[..]));
+    assert_eq!(view.venue(), Some(&b"XYZ"[..]));
+}
+
+#[test]
+fn test_view_rejects_mismatched_or_truncated_messages() {
+    let buffer = quote_message();
+
+    let mut wrong_template = buffer.clone();
+    wrong_template[2] = 9;
+    assert!(QuoteView::from_message(&wrong_template).is_none());
+    assert!(QuoteView::from_message(&buffer[..20]).is_none());
+
+    // Truncated var-data returns None instead of panicking
+    let view = QuoteView::from_bytes(&buffer[8..buffer.len() - 1]).unwrap();
+    assert_eq!(view.note(), Some(&b"hi"[..]));
+    assert!(view.venue().is_none());
+}
+
+#[test]
+fn test_view_skips_to_header_block_length() {
+    let buffer = quote_message_with_block(33);
+    let view = QuoteView::from_message(&buffer).unwrap();
+
+    assert_eq!(view.acting_block_length(), 33);
+    assert_eq!(view.active(), Some(true));
+    assert_eq!(view.note(), Some(&b"hi"[..]));
+    assert_eq!(view.venue(), Some(&b"XYZ"[..]));
+
+    // A blockLength shorter than the fixed fields cannot be read
+    assert!(QuoteView::from_message(&quote_message_with_block(28)).is_none());
+}
+
+pub struct Level {
+    pub price: i64,
+    pub qty: i64,
+}
+
+#[derive(SbeView)]
+#[sbe(template_id = 8, schema_id = 1, version = 0)]
+pub struct Depth {
+    #[sbe(id = 0)]
+    pub instrument_id: u32,
+    #[sbe(id = 1)]
+    pub bids: Vec<Level>,
+    #[sbe(id = 2)]
+    pub asks: Vec<Level>,
+    #[sbe(id = 3)]
+    pub venue: Vec<u8>,
+}
+
+#[test]
+fn test_view_groups_start_after_header_block_length() {
+    let level = |price: i64, qty: i64| [price.to_le_bytes(), qty.to_le_bytes()].concat();
+
+    let mut buffer = Vec::new();
+    buffer.extend(6u16.to_le_bytes()); // blockLength: 4 + 2 bytes from a newer version
+    buffer.extend(8u16.to_le_bytes());
+    buffer.extend(1u16.to_le_bytes());
+    buffer.extend(0u16.to_le_bytes());
+    buffer.extend(42u32.to_le_bytes());
+    buffer.extend([0xEE, 0xEE]);
+    // bids: 2 entries of 16 bytes
+    buffer.extend(16u16.to_le_bytes());
+    buffer.extend(2u16.to_le_bytes());
+    buffer.extend(level(100, 1));
+    buffer.extend(level(99, 2));
+    // asks: 1 entry
+    buffer.extend(16u16.to_le_bytes());
+    buffer.extend(1u16.to_le_bytes());
+    buffer.extend(level(101, 3));
+    buffer.extend(3u16.to_le_bytes());
+    buffer.extend(b"XYZ");
+
+    let view = DepthView::from_message(&buffer).unwrap();
+    assert_eq!(view.instrument_id(), 42);
+    let bids: Vec<_> = view.bids().unwrap().collect();
+    assert_eq!(bids, [&level(100, 1)[..], &level(99, 2)[..]]);
+    assert_eq!(view.asks().unwrap().next(), Some(&level(101, 3)[..]));
+    assert_eq!(view.venue(), Some(&b"XYZ"[..]));
+
+    // Truncated group entries return None
+    assert!(DepthView::from_message(&buffer[..30]).unwrap().bids().is_none());
+}
@@ -0,0 +1,336 @@
+//! 单交易对盘口
+//!
+//! 价位以 `BTreeMap` 排序、同价位 `VecDeque` 保持时间优先并维护数量合计；撮合分配由 [`plan_fills`] 计算。
+//! debug 构建在每次变更后校验盘口不变量（见 [`OrderBook::check_invariants`]）
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use crate::matcher::{Fill, Resting, available_quantity, crosses, plan_fills};
+use crate::types::{OrderId, Price, Quantity, Side, TimeInForce, TraderId};
+
+mod invariants;
+
+pub use invariants::InvariantViolation;
+
+/// 新订单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewOrder {
+    pub order_id: OrderId,
+    pub trader: TraderId,
+    pub side: Side,
+    /// 限价（None 为市价，未成交部分不挂单）
+    pub price: Option<Price>,
+    pub quantity: Quantity,
+    pub time_in_force: TimeInForce,
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// 数量为 0
+    ZeroQuantity,
+    /// 订单ID已在盘口中
+    DuplicateOrderId,
+    /// PostOnly 订单会立即成交（或为市价单）
+    WouldCross,
+}
+
+/// 撮合结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// 剩余数量已挂单（可能已部分成交）
+    Rested,
+    /// 全部成交
+    Filled,
+    /// 剩余数量被取消（IOC、市价单吃不完，或 FOK 流动性不足）
+    Cancelled,
+    /// 拒绝，盘口不变
+    Rejected(RejectReason),
+}
+
+/// 一次提交的执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execution {
+    pub order_id: OrderId,
+    pub fills: Vec<Fill>,
+    /// 未成交数量
+    pub remaining: Quantity,
+    pub outcome: Outcome,
+}
+
+/// 同价位挂单
+#[derive(Debug, Clone, Default)]
+struct Level {
+    /// 按时间排序
+    orders: VecDeque<Resting>,
+    /// 挂单数量合计
+    total: Quantity,
+}
+
+impl Level {
+    fn push_back(&mut self, resting: Resting) {
+        self.total += resting.quantity;
+        self.orders.push_back(resting);
+    }
+
+    fn remove(&mut self, position: usize) -> Option<Resting> {
+        let resting = self.orders.remove(position)?;
+        self.total -= resting.quantity;
+        Some(resting)
+    }
+}
+
+/// 盘口
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    /// 买盘：价格 -> 同价位挂单
+    bids: BTreeMap<Price, Level>,
+    /// 卖盘：价格 -> 同价位挂单
+    asks: BTreeMap<Price, Level>,
+    /// 订单ID -> (方向, 价格)
+    index: BTreeMap<OrderId, (Side, Price)>,
+    /// 最后成交价
+    last_price: Option<Price>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 提交订单：先按价格-时间优先撮合，剩余部分按有效期挂单或取消
+    pub fn submit(&mut self, order: NewOrder) -> Execution {
+        let reject = |reason| Execution {
+            order_id: order.order_id,
+            fills: Vec::new(),
+            remaining: order.quantity,
+            outcome: Outcome::Rejected(reason),
+        };
+        if order.quantity == 0 {
+            return reject(RejectReason::ZeroQuantity);
+        }
+        if self.index.contains_key(&order.order_id) {
+            return reject(RejectReason::DuplicateOrderId);
+        }
+        match order.time_in_force {
+            TimeInForce::PostOnly => {
+                let best = match order.side {
+                    Side::Buy => self.best_ask(),
+                    Side::Sell => self.best_bid(),
+                };
+                let crossing = best.is_some_and(|best| crosses(order.side, order.price, best));
+                if order.price.is_none() || crossing {
+                    return reject(RejectReason::WouldCross);
+                }
+            }
+            TimeInForce::FOK => {
+                let available = available_quantity(
+                    order.side,
+                    order.price,
+                    order.quantity,
+                    self.opposite(order.side),
+                );
+                if available < order.quantity {
+                    return Execution {
+                        order_id: order.order_id,
+                        fills: Vec::new(),
+                        remaining: order.quantity,
+                        outcome: Outcome::Cancelled,
+                    };
+                }
+            }
+            TimeInForce::GTC | TimeInForce::IOC => {}
+        }
+
+        let (fills, remaining) =
+            plan_fills(order.side, order.price, order.quantity, self.opposite(order.side));
+        self.apply_fills(order.side.opposite(), &fills);
+
+        let outcome = match (remaining, order.price, order.time_in_force) {
+            (0, _, _) => Outcome::Filled,
+            (_, Some(price), TimeInForce::GTC | TimeInForce::PostOnly) => {
+                let resting = Resting {
+                    order_id: order.order_id,
+                    trader: order.trader,
+                    price,
+                    quantity: remaining,
+                };
+                self.levels_mut(order.side).entry(price).or_default().push_back(resting);
+                self.index.insert(order.order_id, (order.side, price));
+                Outcome::Rested
+            }
+            _ => Outcome::Cancelled,
+        };
+        self.debug_check_invariants();
+        Execution { order_id: order.order_id, fills, remaining, outcome }
+    }
+
+    /// 撤单，返回被撤的挂单
+    pub fn cancel(&mut self, order_id: OrderId) -> Option<Resting> {
+        let (side, price) = self.index.remove(&order_id)?;
+        let levels = self.levels_mut(side);
+        let level = levels.get_mut(&price)?;
+        let position = level.orders.iter().position(|r| r.order_id == order_id)?;
+        let resting = level.remove(position);
+        if level.orders.is_empty() {
+            levels.remove(&price);
+        }
+        self.debug_check_invariants();
+        resting
+    }
+
+    /// 查询挂单
+    pub fn order(&self, order_id: OrderId) -> Option<Resting> {
+        let (side, price) = self.index.get(&order_id)?;
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.get(price)?.orders.iter().find(|r| r.order_id == order_id).copied()
+    }
+
+    pub fn best_bid(&self) -> Option<Price> {
+        self.bids.keys().next_back().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<Price> {
+        self.asks.keys().next().copied()
+    }
+
+    pub fn last_price(&self) -> Option<Price> {
+        self.last_price
+    }
+
+    /// 挂单数
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// 前 `levels` 档深度 (价格, 数量)，买盘从高到低、卖盘从低到高
+    pub fn depth(&self, side: Side, levels: usize) -> Vec<(Price, Quantity)> {
+        let total = |(price, level): (&Price, &Level)| (*price, level.total);
+        match side {
+            Side::Buy => self.bids.iter().rev().take(levels).map(total).collect(),
+            Side::Sell => self.asks.iter().take(levels).map(total).collect(),
+        }
+    }
+
+    /// Taker 方向对应的对手盘，按优先级排序
+    fn opposite(&self, taker_side: Side) -> Box<dyn Iterator<Item = Resting> + '_> {
+        match taker_side {
+            Side::Buy => {
+                Box::new(self.asks.values().flat_map(|level| level.orders.iter().copied()))
+            }
+            Side::Sell => {
+                Box::new(self.bids.values().rev().flat_map(|level| level.orders.iter().copied()))
+            }
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Price, Level> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    /// 按分配结果扣减 Maker 挂单（分配按优先级顺序，总是作用于各价位队首）
+    fn apply_fills(&mut self, maker_side: Side, fills: &[Fill]) {
+        let levels = match maker_side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        for fill in fills {
+            let level = levels.get_mut(&fill.price).expect("fill planned against a resting level");
+            let front = level.orders.front_mut().expect("resting level is never empty");
+            debug_assert_eq!(front.order_id, fill.maker_order_id);
+            front.quantity -= fill.quantity;
+            level.total -= fill.quantity;
+            if front.quantity == 0 {
+                level.orders.pop_front();
+                self.index.remove(&fill.maker_order_id);
+            }
+            if level.orders.is_empty() {
+                levels.remove(&fill.price);
+            }
+            self.last_price = Some(fill.price);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(order_id: OrderId, side: Side, price: Price, quantity: Quantity) -> NewOrder {
+        NewOrder {
+            order_id,
+            trader: order_id,
+            side,
+            price: Some(price),
+            quantity,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_submit_matches_and_rests() {
+        let mut book = OrderBook::new();
+        assert_eq!(book.submit(limit(1, Side::Sell, 101, 5)).outcome, Outcome::Rested);
+        assert_eq!(book.submit(limit(2, Side::Sell, 100, 5)).outcome, Outcome::Rested);
+        assert_eq!(book.submit(limit(3, Side::Sell, 100, 5)).outcome, Outcome::Rested);
+        assert_eq!((book.best_ask(), book.len()), (Some(100), 3));
+
+        let execution = book.submit(limit(4, Side::Buy, 101, 12));
+        let makers: Vec<_> =
+            execution.fills.iter().map(|f| (f.maker_order_id, f.quantity)).collect();
+        assert_eq!(makers, [(2, 5), (3, 5), (1, 2)]);
+        assert_eq!((execution.remaining, execution.outcome), (0, Outcome::Filled));
+        assert_eq!(book.order(1).map(|r| r.quantity), Some(3));
+        assert_eq!(book.last_price(), Some(101));
+
+        let execution = book.submit(limit(5, Side::Buy, 102, 10));
+        assert_eq!((execution.remaining, execution.outcome), (7, Outcome::Rested));
+        assert_eq!(book.depth(Side::Buy, 5), [(102, 7)]);
+        assert!(book.depth(Side::Sell, 5).is_empty());
+        assert_eq!(book.cancel(5).map(|r| r.quantity), Some(7));
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_time_in_force() {
+        let mut book = OrderBook::new();
+        book.submit(limit(1, Side::Sell, 100, 5));
+
+        let post_only =
+            NewOrder { time_in_force: TimeInForce::PostOnly, ..limit(2, Side::Buy, 100, 1) };
+        assert_eq!(book.submit(post_only).outcome, Outcome::Rejected(RejectReason::WouldCross));
+        let fok = NewOrder { time_in_force: TimeInForce::FOK, ..limit(3, Side::Buy, 100, 6) };
+        let execution = book.submit(fok);
+        assert_eq!((execution.fills.len(), execution.outcome), (0, Outcome::Cancelled));
+        assert_eq!(book.order(1).map(|r| r.quantity), Some(5));
+
+        let ioc = NewOrder { time_in_force: TimeInForce::IOC, ..limit(4, Side::Buy, 100, 8) };
+        let execution = book.submit(ioc);
+        assert_eq!((execution.remaining, execution.outcome), (3, Outcome::Cancelled));
+        assert!(book.is_empty());
+
+        let market = NewOrder { price: None, ..limit(5, Side::Sell, 0, 1) };
+        assert_eq!(book.submit(market).outcome, Outcome::Cancelled);
+        assert_eq!(
+            book.submit(limit(6, Side::Buy, 99, 0)).outcome,
+            Outcome::Rejected(RejectReason::ZeroQuantity)
+        );
+        book.submit(limit(7, Side::Buy, 99, 1));
+        assert_eq!(
+            book.submit(limit(7, Side::Buy, 98, 1)).outcome,
+            Outcome::Rejected(RejectReason::DuplicateOrderId)
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! 盘口不变量
+//!
+//! 每次变更后应成立：
+//! 1. 最优买价 < 最优卖价（盘口不交叉）
+//! 2. 价位严格有序、非空，挂单价格等于所在价位，数量为正
+//! 3. 价位数量合计等于该价位挂单数量之和
+//! 4. 订单索引与挂单一一对应（方向、价格一致）
+//!
+//! debug 构建中 `submit` / `cancel` 结束时自动校验，违反时 panic 并附带 JSON 格式的盘口快照，
+//! 便于直接贴进缺陷报告；release 构建不做校验
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use super::{Level, OrderBook};
+use crate::types::{OrderId, Price, Quantity, Side};
+
+/// 违反的不变量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// 最优买价不低于最优卖价
+    Crossed { best_bid: Price, best_ask: Price },
+    /// 价位未严格有序
+    Unsorted { side: Side, price: Price },
+    /// 空价位未删除
+    EmptyLevel { side: Side, price: Price },
+    /// 挂单价格与所在价位不符，或数量为 0
+    MisplacedOrder { order_id: OrderId, level: Price },
+    /// 价位合计与挂单数量之和不符
+    LevelTotal { side: Side, price: Price, total: Quantity, sum: Quantity },
+    /// 挂单不在索引中，或索引记录的方向 / 价格不符
+    MissingIndex { order_id: OrderId },
+    /// 索引条目没有对应挂单（或同一订单出现多次）
+    StaleIndex { indexed: usize, resting: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::Crossed { best_bid, best_ask } => {
+                write!(f, "book crossed: best bid {} >= best ask {}", best_bid, best_ask)
+            }
+            InvariantViolation::Unsorted { side, price } => {
+                write!(f, "{:?} levels out of order at {}", side, price)
+            }
+            InvariantViolation::EmptyLevel { side, price } => {
+                write!(f, "empty {:?} level left at {}", side, price)
+            }
+            InvariantViolation::MisplacedOrder { order_id, level } => {
+                write!(f, "order {} does not belong to level {}", order_id, level)
+            }
+            InvariantViolation::LevelTotal { side, price, total, sum } => {
+                write!(f, "{:?} level {} total {} != resting sum {}", side, price, total, sum)
+            }
+            InvariantViolation::MissingIndex { order_id } => {
+                write!(f, "order {} missing from index or indexed elsewhere", order_id)
+            }
+            InvariantViolation::StaleIndex { indexed, resting } => {
+                write!(f, "index has {} entries but {} orders rest", indexed, resting)
+            }
+        }
+    }
+}
+
+impl OrderBook {
+    /// 校验盘口不变量，返回发现的第一处违反
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if let (Some(best_bid), Some(best_ask)) = (self.best_bid(), self.best_ask()) {
+            if best_bid >= best_ask {
+                return Err(InvariantViolation::Crossed { best_bid, best_ask });
+            }
+        }
+        let resting =
+            self.check_side(Side::Buy, &self.bids)? + self.check_side(Side::Sell, &self.asks)?;
+        if resting != self.index.len() {
+            return Err(InvariantViolation::StaleIndex { indexed: self.index.len(), resting });
+        }
+        Ok(())
+    }
+
+    /// 盘口快照（JSON），用于缺陷报告
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        // 写入 String 不会失败
+        let _ = self.write_dump(&mut out);
+        out
+    }
+
+    /// debug 构建中校验不变量，违反时 panic 并附带盘口快照
+    #[inline]
+    #[allow(clippy::disallowed_macros)] // 仅 debug 构建：不变量被破坏后继续撮合只会扩大损坏
+    pub(super) fn debug_check_invariants(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(violation) = self.check_invariants() {
+            panic!("order book invariant violated: {}\nbook: {}", violation, self.dump());
+        }
+    }
+
+    /// 校验单侧价位，返回挂单数
+    fn check_side(
+        &self,
+        side: Side,
+        levels: &BTreeMap<Price, Level>,
+    ) -> Result<usize, InvariantViolation> {
+        let mut previous: Option<Price> = None;
+        let mut count = 0;
+        for (&price, level) in levels {
+            if previous.is_some_and(|previous| previous >= price) {
+                return Err(InvariantViolation::Unsorted { side, price });
+            }
+            previous = Some(price);
+            if level.orders.is_empty() {
+                return Err(InvariantViolation::EmptyLevel { side, price });
+            }
+
+            let mut sum: Quantity = 0;
+            for resting in &level.orders {
+                if resting.price != price || resting.quantity == 0 {
+                    return Err(InvariantViolation::MisplacedOrder {
+                        order_id: resting.order_id,
+                        level: price,
+                    });
+                }
+                if self.index.get(&resting.order_id) != Some(&(side, price)) {
+                    return Err(InvariantViolation::MissingIndex { order_id: resting.order_id });
+                }
+                sum += resting.quantity;
+            }
+            if sum != level.total {
+                return Err(InvariantViolation::LevelTotal {
+                    side,
+                    price,
+                    total: level.total,
+                    sum,
+                });
+            }
+            count += level.orders.len();
+        }
+        Ok(count)
+    }
+
+    fn write_dump(&self, out: &mut impl Write) -> fmt::Result {
+        out.write_str("{\"bids\":")?;
+        Self::write_levels(out, self.bids.iter().rev())?;
+        out.write_str(",\"asks\":")?;
+        Self::write_levels(out, self.asks.iter())?;
+        out.write_str(",\"index\":[")?;
+        for (i, (order_id, (side, price))) in self.index.iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+            write!(out, "[{},\"{:?}\",{}]", order_id, side, price)?;
+        }
+        out.write_str("],\"last_price\":")?;
+        match self.last_price {
+            Some(price) => write!(out, "{}", price)?,
+            None => out.write_str("null")?,
+        }
+        out.write_char('}')
+    }
+
+    fn write_levels<'a>(
+        out: &mut impl Write,
+        levels: impl Iterator<Item = (&'a Price, &'a Level)>,
+    ) -> fmt::Result {
+        out.write_char('[')?;
+        for (i, (price, level)) in levels.enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+            write!(out, "{{\"price\":{},\"total\":{},\"orders\":[", price, level.total)?;
+            for (j, resting) in level.orders.iter().enumerate() {
+                if j > 0 {
+                    out.write_char(',')?;
+                }
+                write!(
+                    out,
+                    "{{\"id\":{},\"trader\":{},\"price\":{},\"qty\":{}}}",
+                    resting.order_id, resting.trader, resting.price, resting.quantity
+                )?;
+            }
+            out.write_str("]}")?;
+        }
+        out.write_char(']')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::NewOrder;
+    use crate::types::TimeInForce;
+
+    fn book() -> OrderBook {
+        let mut book = OrderBook::new();
+        for (order_id, side, price, quantity) in
+            [(1, Side::Buy, 99, 5), (2, Side::Buy, 99, 3), (3, Side::Sell, 101, 4)]
+        {
+            book.submit(NewOrder {
+                order_id,
+                trader: 7,
+                side,
+                price: Some(price),
+                quantity,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+        book
+    }
+
+    #[test]
+    fn test_detects_each_violation() {
+        assert_eq!(book().check_invariants(), Ok(()));
+
+        let mut crossed = book();
+        let level = crossed.bids.remove(&99).unwrap();
+        crossed.bids.insert(101, level);
+        assert_eq!(
+            crossed.check_invariants(),
+            Err(InvariantViolation::Crossed { best_bid: 101, best_ask: 101 })
+        );
+
+        let mut total = book();
+        total.bids.get_mut(&99).unwrap().total = 9;
+        assert_eq!(
+            total.check_invariants(),
+            Err(InvariantViolation::LevelTotal { side: Side::Buy, price: 99, total: 9, sum: 8 })
+        );
+
+        let mut misplaced = book();
+        misplaced.asks.get_mut(&101).unwrap().orders[0].price = 102;
+        assert_eq!(
+            misplaced.check_invariants(),
+            Err(InvariantViolation::MisplacedOrder { order_id: 3, level: 101 })
+        );
+
+        let mut missing = book();
+        missing.index.insert(2, (Side::Sell, 99));
+        assert_eq!(
+            missing.check_invariants(),
+            Err(InvariantViolation::MissingIndex { order_id: 2 })
+        );
+
+        let mut stale = book();
+        stale.index.insert(42, (Side::Buy, 50));
+        assert_eq!(
+            stale.check_invariants(),
+            Err(InvariantViolation::StaleIndex { indexed: 4, resting: 3 })
+        );
+
+        let mut empty = book();
+        empty.asks.insert(120, Level::default());
+        assert_eq!(
+            empty.check_invariants(),
+            Err(InvariantViolation::EmptyLevel { side: Side::Sell, price: 120 })
+        );
+    }
+
+    #[test]
+    fn test_dump_is_json() {
+        assert_eq!(
+            book().dump(),
+            "{\"bids\":[{\"price\":99,\"total\":8,\"orders\":[\
+             {\"id\":1,\"trader\":7,\"price\":99,\"qty\":5},\
+             {\"id\":2,\"trader\":7,\"price\":99,\"qty\":3}]}],\
+             \"asks\":[{\"price\":101,\"total\":4,\"orders\":[\
+             {\"id\":3,\"trader\":7,\"price\":101,\"qty\":4}]}],\
+             \"index\":[[1,\"Buy\",99],[2,\"Buy\",99],[3,\"Sell\",101]],\"last_price\":null}"
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "order book invariant violated")]
+    fn test_mutation_panics_on_corrupted_book() {
+        let mut book = book();
+        book.bids.get_mut(&99).unwrap().total = 1;
+        book.cancel(3);
+    }
+}
@@ -0,0 +1,22 @@
+//! 纯撮合内核
+//!
+//! 只包含盘口与价格-时间优先撮合，不依赖数据库、IO 与系统时间，`no_std`（仅需 `alloc`）：
+//! - 永续引擎（`prep`）用 [`plan_fills`] 计算成交分配，仓位、手续费、风控仍在引擎内完成
+//! - `wasm` 特性导出 `wasm::SimBook`，供前端与文档站点在浏览器内运行模拟盘口
+//! - 性质测试以同一内核与朴素参考实现做差分比对（见 `tests/differential.rs`）
+//!
+//! 价格与数量均为最小单位的整数（与 `prep` 一致），精度换算由调用方完成
+
+#![cfg_attr(not(feature = "wasm"), no_std)]
+
+extern crate alloc;
+
+pub mod book;
+pub mod matcher;
+pub mod types;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use book::{Execution, InvariantViolation, NewOrder, OrderBook, Outcome, RejectReason};
+pub use matcher::{Fill, Resting, available_quantity, crosses, plan_fills};
+pub use types::{OrderId, Price, Quantity, Side, TimeInForce, TraderId};
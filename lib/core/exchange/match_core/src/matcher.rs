@@ -0,0 +1,124 @@
+//! 价格-时间优先撮合
+//!
+//! 输入为按优先级排好序的对手盘（买盘价格从高到低、卖盘价格从低到高，同价按时间），
+//! 输出成交分配；不修改任何状态，调用方按分配结果更新订单
+
+use alloc::vec::Vec;
+
+use crate::types::{OrderId, Price, Quantity, Side, TraderId};
+
+/// 对手盘挂单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resting {
+    pub order_id: OrderId,
+    pub trader: TraderId,
+    pub price: Price,
+    /// 剩余数量
+    pub quantity: Quantity,
+}
+
+/// 一笔成交（以 Maker 挂单价成交）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    pub maker_order_id: OrderId,
+    pub maker_trader: TraderId,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// Taker 是否可与该价位的挂单成交（`limit` 为 None 表示市价）
+pub fn crosses(side: Side, limit: Option<Price>, resting_price: Price) -> bool {
+    match (side, limit) {
+        (_, None) => true,
+        (Side::Buy, Some(limit)) => resting_price <= limit,
+        (Side::Sell, Some(limit)) => resting_price >= limit,
+    }
+}
+
+/// 计算 Taker 的成交分配，返回 (成交列表, 未成交数量)
+///
+/// 对手盘须已按优先级排序，遇到第一个不可成交的价位即停止
+pub fn plan_fills<I>(
+    side: Side,
+    limit: Option<Price>,
+    quantity: Quantity,
+    opposite: I,
+) -> (Vec<Fill>, Quantity)
+where
+    I: IntoIterator<Item = Resting>,
+{
+    let mut fills = Vec::new();
+    let mut remaining = quantity;
+    for resting in opposite {
+        if remaining == 0 || !crosses(side, limit, resting.price) {
+            break;
+        }
+        if resting.quantity == 0 {
+            continue;
+        }
+        let quantity = remaining.min(resting.quantity);
+        fills.push(Fill {
+            maker_order_id: resting.order_id,
+            maker_trader: resting.trader,
+            price: resting.price,
+            quantity,
+        });
+        remaining -= quantity;
+    }
+    (fills, remaining)
+}
+
+/// 可成交的对手盘数量（最多统计到 `cap`，用于 FOK 预检）
+pub fn available_quantity<I>(
+    side: Side,
+    limit: Option<Price>,
+    cap: Quantity,
+    opposite: I,
+) -> Quantity
+where
+    I: IntoIterator<Item = Resting>,
+{
+    let mut available: Quantity = 0;
+    for resting in opposite {
+        if available >= cap || !crosses(side, limit, resting.price) {
+            break;
+        }
+        available = available.saturating_add(resting.quantity);
+    }
+    available.min(cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask(order_id: OrderId, price: Price, quantity: Quantity) -> Resting {
+        Resting { order_id, trader: order_id * 10, price, quantity }
+    }
+
+    #[test]
+    fn test_plan_fills_price_time_priority() {
+        let asks = [ask(1, 100, 5), ask(2, 100, 5), ask(3, 101, 10), ask(4, 105, 10)];
+
+        let (fills, remaining) = plan_fills(Side::Buy, Some(101), 12, asks);
+        assert_eq!(remaining, 0);
+        let allocation: Vec<_> =
+            fills.iter().map(|f| (f.maker_order_id, f.price, f.quantity)).collect();
+        assert_eq!(allocation, [(1, 100, 5), (2, 100, 5), (3, 101, 2)]);
+
+        // 限价之外的价位不成交
+        let (fills, remaining) = plan_fills(Side::Buy, Some(100), 30, asks);
+        assert_eq!((fills.len(), remaining), (2, 20));
+        // 市价吃穿全部价位
+        let (_, remaining) = plan_fills(Side::Buy, None, 40, asks);
+        assert_eq!(remaining, 10);
+    }
+
+    #[test]
+    fn test_available_quantity() {
+        let bids = [ask(1, 100, 5), ask(2, 99, 5), ask(3, 98, 5)];
+        assert_eq!(available_quantity(Side::Sell, Some(99), 100, bids), 10);
+        assert_eq!(available_quantity(Side::Sell, Some(99), 7, bids), 7);
+        assert!(!crosses(Side::Sell, Some(101), 100));
+    }
+}
@@ -0,0 +1,43 @@
+//! 撮合内核基础类型
+
+/// 交易者ID
+pub type TraderId = u64;
+/// 订单ID
+pub type OrderId = u64;
+/// 价格（最小价格单位）
+pub type Price = u64;
+/// 数量（最小数量单位）
+pub type Quantity = u64;
+
+/// 订单方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// 买入
+    Buy,
+    /// 卖出
+    Sell,
+}
+
+impl Side {
+    /// 对手方向
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+/// 订单有效期（模拟盘口支持的子集，过期由调用方以撤单实现）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeInForce {
+    /// 成交为止
+    #[default]
+    GTC,
+    /// 立即成交或取消
+    IOC,
+    /// 全部成交或取消
+    FOK,
+    /// 只做Maker
+    PostOnly,
+}
@@ -0,0 +1,129 @@
+//! 浏览器模拟盘口
+//!
+//! ```js
+//! import init, { SimBook } from "./pkg/match_core.js";
+//! await init();
+//! const book = new SimBook();
+//! book.submit(1n, 7n, false, 10100n, 5n, "GTC");
+//! JSON.parse(book.submit(2n, 8n, true, 0n, 2n, "IOC")); // price 为 0 表示市价
+//! JSON.parse(book.depth(10)); // { bids: [[price, qty]], asks: [...] }
+//! ```
+//!
+//! 结果以 JSON 字符串返回，避免引入序列化依赖
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::book::{Execution, NewOrder, OrderBook, Outcome};
+use crate::types::{Price, Quantity, Side, TimeInForce};
+
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct SimBook {
+    book: OrderBook,
+}
+
+#[wasm_bindgen]
+impl SimBook {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SimBook {
+        SimBook::default()
+    }
+
+    /// 提交订单，`price` 为 0 表示市价；`tif` 为 `GTC`/`IOC`/`FOK`/`PostOnly`，返回执行结果 JSON
+    pub fn submit(
+        &mut self,
+        order_id: u64,
+        trader: u64,
+        is_buy: bool,
+        price: u64,
+        quantity: u64,
+        tif: &str,
+    ) -> Result<String, JsValue> {
+        let time_in_force = match tif {
+            "GTC" => TimeInForce::GTC,
+            "IOC" => TimeInForce::IOC,
+            "FOK" => TimeInForce::FOK,
+            "PostOnly" => TimeInForce::PostOnly,
+            other => return Err(JsValue::from_str(&format!("Unknown time in force: {}", other))),
+        };
+        let execution = self.book.submit(NewOrder {
+            order_id,
+            trader,
+            side: if is_buy { Side::Buy } else { Side::Sell },
+            price: (price > 0).then_some(price),
+            quantity,
+            time_in_force,
+        });
+        Ok(execution_json(&execution))
+    }
+
+    /// 撤单，返回是否撤销成功
+    pub fn cancel(&mut self, order_id: u64) -> bool {
+        self.book.cancel(order_id).is_some()
+    }
+
+    /// 前 `levels` 档深度 JSON
+    pub fn depth(&self, levels: usize) -> String {
+        format!(
+            r#"{{"bids":{},"asks":{}}}"#,
+            levels_json(&self.book.depth(Side::Buy, levels)),
+            levels_json(&self.book.depth(Side::Sell, levels))
+        )
+    }
+
+    #[wasm_bindgen(js_name = bestBid)]
+    pub fn best_bid(&self) -> Option<u64> {
+        self.book.best_bid()
+    }
+
+    #[wasm_bindgen(js_name = bestAsk)]
+    pub fn best_ask(&self) -> Option<u64> {
+        self.book.best_ask()
+    }
+
+    #[wasm_bindgen(js_name = lastPrice)]
+    pub fn last_price(&self) -> Option<u64> {
+        self.book.last_price()
+    }
+
+    #[wasm_bindgen(js_name = orderCount)]
+    pub fn order_count(&self) -> usize {
+        self.book.len()
+    }
+}
+
+fn execution_json(execution: &Execution) -> String {
+    let fills: Vec<String> = execution
+        .fills
+        .iter()
+        .map(|f| {
+            format!(
+                r#"{{"makerOrderId":{},"makerTrader":{},"price":{},"quantity":{}}}"#,
+                f.maker_order_id, f.maker_trader, f.price, f.quantity
+            )
+        })
+        .collect();
+    let outcome = match execution.outcome {
+        Outcome::Rested => String::from("Rested"),
+        Outcome::Filled => String::from("Filled"),
+        Outcome::Cancelled => String::from("Cancelled"),
+        Outcome::Rejected(reason) => format!("Rejected:{:?}", reason),
+    };
+    format!(
+        r#"{{"orderId":{},"fills":[{}],"remaining":{},"outcome":"{}"}}"#,
+        execution.order_id,
+        fills.join(","),
+        execution.remaining,
+        outcome
+    )
+}
+
+fn levels_json(levels: &[(Price, Quantity)]) -> String {
+    let levels: Vec<String> =
+        levels.iter().map(|(price, quantity)| format!("[{},{}]", price, quantity)).collect();
+    format!("[{}]", levels.join(","))
+}
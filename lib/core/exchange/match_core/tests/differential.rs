@@ -0,0 +1,182 @@
+//! 撮合内核差分测试
+//!
+//! 随机生成下单/撤单序列，同时驱动 [`OrderBook`] 与朴素参考实现（线性扫描、每次排序），
+//! 逐步比对成交分配、执行结果与盘口深度
+
+use match_core::{
+    Fill, NewOrder, OrderBook, OrderId, Outcome, Price, Quantity, RejectReason, Side, TimeInForce,
+};
+use proptest::prelude::*;
+
+/// 参考实现：挂单存于 Vec，撮合时按价格-时间优先排序后逐笔成交
+#[derive(Default)]
+struct ReferenceBook {
+    /// (到达序号, 订单)
+    resting: Vec<(u64, NewOrder, Quantity)>,
+    arrivals: u64,
+}
+
+impl ReferenceBook {
+    fn priority_queue(&self, taker_side: Side, limit: Option<Price>) -> Vec<usize> {
+        let mut candidates: Vec<usize> = (0..self.resting.len())
+            .filter(|&i| {
+                let (_, order, _) = &self.resting[i];
+                let price = order.price.unwrap();
+                order.side != taker_side
+                    && match (taker_side, limit) {
+                        (_, None) => true,
+                        (Side::Buy, Some(limit)) => price <= limit,
+                        (Side::Sell, Some(limit)) => price >= limit,
+                    }
+            })
+            .collect();
+        candidates.sort_by_key(|&i| {
+            let (seq, order, _) = &self.resting[i];
+            let price = order.price.unwrap();
+            let key = if taker_side == Side::Buy { price } else { u64::MAX - price };
+            (key, *seq)
+        });
+        candidates
+    }
+
+    fn submit(&mut self, order: NewOrder) -> (Vec<Fill>, Quantity, Outcome) {
+        if order.quantity == 0 {
+            return (vec![], 0, Outcome::Rejected(RejectReason::ZeroQuantity));
+        }
+        if self.resting.iter().any(|(_, o, _)| o.order_id == order.order_id) {
+            return (vec![], order.quantity, Outcome::Rejected(RejectReason::DuplicateOrderId));
+        }
+        let queue = self.priority_queue(order.side, order.price);
+        let liquidity: Quantity = queue.iter().map(|&i| self.resting[i].2).sum();
+        match order.time_in_force {
+            TimeInForce::PostOnly if order.price.is_none() || !queue.is_empty() => {
+                return (vec![], order.quantity, Outcome::Rejected(RejectReason::WouldCross));
+            }
+            TimeInForce::FOK if liquidity < order.quantity => {
+                return (vec![], order.quantity, Outcome::Cancelled);
+            }
+            _ => {}
+        }
+
+        let mut fills = Vec::new();
+        let mut remaining = order.quantity;
+        for i in queue {
+            if remaining == 0 {
+                break;
+            }
+            let (_, maker, maker_remaining) = &mut self.resting[i];
+            let quantity = remaining.min(*maker_remaining);
+            *maker_remaining -= quantity;
+            remaining -= quantity;
+            fills.push(Fill {
+                maker_order_id: maker.order_id,
+                maker_trader: maker.trader,
+                price: maker.price.unwrap(),
+                quantity,
+            });
+        }
+        self.resting.retain(|(_, _, remaining)| *remaining > 0);
+
+        let rests = order.price.is_some()
+            && matches!(order.time_in_force, TimeInForce::GTC | TimeInForce::PostOnly);
+        let outcome = if remaining == 0 {
+            Outcome::Filled
+        } else if rests {
+            self.arrivals += 1;
+            self.resting.push((self.arrivals, order, remaining));
+            Outcome::Rested
+        } else {
+            Outcome::Cancelled
+        };
+        (fills, remaining, outcome)
+    }
+
+    fn cancel(&mut self, order_id: OrderId) -> Option<Quantity> {
+        let position = self.resting.iter().position(|(_, o, _)| o.order_id == order_id)?;
+        Some(self.resting.remove(position).2)
+    }
+
+    fn depth(&self, side: Side) -> Vec<(Price, Quantity)> {
+        let mut levels: Vec<(Price, Quantity)> = Vec::new();
+        let mut orders: Vec<_> = self.resting.iter().filter(|(_, o, _)| o.side == side).collect();
+        orders.sort_by_key(|(_, o, _)| o.price.unwrap());
+        if side == Side::Buy {
+            orders.reverse();
+        }
+        for (_, order, remaining) in orders {
+            match levels.last_mut() {
+                Some((price, quantity)) if *price == order.price.unwrap() => *quantity += remaining,
+                _ => levels.push((order.price.unwrap(), *remaining)),
+            }
+        }
+        levels
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Submit {
+        side: Side,
+        price: Option<Price>,
+        quantity: Quantity,
+        tif: TimeInForce,
+    },
+    /// 撤销第 n 个已提交的订单（可能已成交或不存在）
+    Cancel(usize),
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    let side = prop_oneof![Just(Side::Buy), Just(Side::Sell)];
+    let tif = prop_oneof![
+        4 => Just(TimeInForce::GTC),
+        1 => Just(TimeInForce::IOC),
+        1 => Just(TimeInForce::FOK),
+        1 => Just(TimeInForce::PostOnly),
+    ];
+    // 价格集中在少数价位，制造大量交叉与同价排队
+    let price = prop_oneof![9 => (95u64..105).prop_map(Some), 1 => Just(None)];
+    prop_oneof![
+        5 => (side, price, 0u64..20, tif).prop_map(|(side, price, quantity, tif)| {
+            Action::Submit { side, price, quantity, tif }
+        }),
+        1 => (0usize..64).prop_map(Action::Cancel),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn book_matches_reference(actions in proptest::collection::vec(action(), 1..200)) {
+        let mut book = OrderBook::new();
+        let mut reference = ReferenceBook::default();
+
+        for (i, action) in actions.into_iter().enumerate() {
+            match action {
+                Action::Submit { side, price, quantity, tif } => {
+                    let order = NewOrder {
+                        order_id: i as OrderId,
+                        trader: (i % 7) as u64,
+                        side,
+                        price,
+                        quantity,
+                        time_in_force: tif,
+                    };
+                    let execution = book.submit(order);
+                    let (fills, remaining, outcome) = reference.submit(order);
+                    prop_assert_eq!(&execution.fills, &fills);
+                    prop_assert_eq!(execution.remaining, remaining);
+                    prop_assert_eq!(execution.outcome, outcome);
+                }
+                Action::Cancel(n) => {
+                    let cancelled = book.cancel(n as OrderId).map(|r| r.quantity);
+                    prop_assert_eq!(cancelled, reference.cancel(n as OrderId));
+                }
+            }
+            prop_assert_eq!(book.depth(Side::Buy, usize::MAX), reference.depth(Side::Buy));
+            prop_assert_eq!(book.depth(Side::Sell, usize::MAX), reference.depth(Side::Sell));
+            prop_assert_eq!(book.len(), reference.resting.len());
+            if let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) {
+                prop_assert!(bid < ask, "crossed book: bid {} >= ask {}", bid, ask);
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! 成交确认消费线程
+//!
+//! 作为 [`ProjectionFeed`](super::ProjectionFeed) 的下游消费分片输出：成交与成交撤销事件
+//! 交给 [`TradeConfirmationService`] 生成确认，按投递间隔执行日切（UTC 自然日结束后发出
+//! 日终汇总）并投递发件箱。服务与投递端只在本线程内使用，投递超时不影响投影更新。
+//! 输出发送端全部关闭后做最后一次日切与投递，线程退出并返回累计投递结果
+
+use std::io;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::adaptor::inbound::shard_runner::{ShardOutput, unix_millis};
+use crate::domain::entity::Timestamp;
+use crate::domain::service::confirmation::{
+    DispatchReport, NotificationSender, TradeConfirmationService,
+};
+
+/// 默认投递间隔
+pub const DEFAULT_DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 成交确认消费配置
+pub struct ConfirmationFeed<S: NotificationSender> {
+    service: TradeConfirmationService,
+    sender: S,
+    interval: Duration,
+    clock: fn() -> Timestamp,
+    forward: Option<Sender<ShardOutput>>,
+}
+
+impl<S: NotificationSender + Send + 'static> ConfirmationFeed<S> {
+    pub fn new(service: TradeConfirmationService, sender: S) -> Self {
+        Self {
+            service,
+            sender,
+            interval: DEFAULT_DISPATCH_INTERVAL,
+            clock: unix_millis,
+            forward: None,
+        }
+    }
+
+    /// 日切与投递的间隔
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 时钟（毫秒），决定日终汇总的日切
+    pub fn with_clock(mut self, clock: fn() -> Timestamp) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 应用后转交下游（下游关闭后不再转交）
+    pub fn forward_to(mut self, downstream: Sender<ShardOutput>) -> Self {
+        self.forward = Some(downstream);
+        self
+    }
+
+    /// 日切并投递发件箱
+    fn flush(&mut self, total: &mut DispatchReport) {
+        self.service.close_day((self.clock)());
+        let report = self.service.dispatch(&mut self.sender);
+        total.sent += report.sent;
+        total.failed = report.failed;
+    }
+
+    /// 启动消费线程，返回累计投递成功数与最后一次投递后仍待重试的数量
+    pub fn spawn(
+        mut self,
+        outputs: Receiver<ShardOutput>,
+    ) -> io::Result<JoinHandle<DispatchReport>> {
+        thread::Builder::new().name("prep-confirmations".to_string()).spawn(move || {
+            let mut total = DispatchReport::default();
+            let mut next_flush = Instant::now() + self.interval;
+            loop {
+                let timeout = next_flush.saturating_duration_since(Instant::now());
+                match outputs.recv_timeout(timeout) {
+                    Ok(output) => {
+                        for envelope in &output.events {
+                            self.service.apply(envelope);
+                        }
+                        if let Some(downstream) = &self.forward {
+                            if downstream.send(output).is_err() {
+                                self.forward = None;
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if Instant::now() >= next_flush {
+                    self.flush(&mut total);
+                    next_flush = Instant::now() + self.interval;
+                }
+            }
+            self.flush(&mut total);
+            total
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+    use crate::domain::service::command::{Command, PrepCommandHandler};
+    use crate::domain::service::confirmation::{
+        ConfirmationMode, ConfirmationPreference, DeliveryChannel, Notification,
+    };
+    use crate::domain::service::matching::MatchingService;
+
+    struct Captured(mpsc::Sender<String>);
+
+    impl NotificationSender for Captured {
+        fn send(&mut self, notification: &Notification) -> Result<(), String> {
+            self.0.send(notification.subject.clone()).map_err(|e| e.to_string())
+        }
+    }
+
+    fn limit(trader: u64, side: Side) -> Command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price: 100,
+            quantity: 2,
+            position_side: if side == Side::Buy { PositionSide::Long } else { PositionSide::Short },
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_fills_are_confirmed_and_digest_flushed_after_day_end() {
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.set_timestamp(1_000);
+        engine.handle(limit(1, Side::Sell));
+        engine.handle(limit(2, Side::Buy));
+
+        let mut service = TradeConfirmationService::default();
+        let preference = |mode, address: &str| ConfirmationPreference {
+            mode,
+            channel: DeliveryChannel::Email(address.to_string()),
+            locale: "en".to_string(),
+        };
+        service.set_preference(1, preference(ConfirmationMode::Immediate, "maker@example.com"));
+        service.set_preference(2, preference(ConfirmationMode::Digest, "taker@example.com"));
+        let (delivered, subjects) = mpsc::channel();
+        let (output, outputs) = mpsc::channel();
+        let (downstream, forwarded) = mpsc::channel();
+        // 时钟在次日：汇总在下一次投递时发出
+        let feed = ConfirmationFeed::new(service, Captured(delivered))
+            .with_interval(Duration::from_millis(10))
+            .with_clock(|| 86_400_000 + 1_000)
+            .forward_to(downstream)
+            .spawn(outputs)
+            .unwrap();
+
+        output
+            .send(ShardOutput {
+                results: Vec::new(),
+                events: engine.drain_events(),
+                violations: Vec::new(),
+            })
+            .unwrap();
+        assert!(!forwarded.recv().unwrap().events.is_empty());
+        // 即时确认与次日发出的汇总各一条
+        subjects.recv().unwrap();
+        subjects.recv().unwrap();
+        drop(output);
+
+        let report = feed.join().unwrap();
+        assert_eq!((report.sent, report.failed), (2, 0));
+    }
+}
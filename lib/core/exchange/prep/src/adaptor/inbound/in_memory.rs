@@ -1,19 +1,84 @@
 //! 内存仓储实现
+//!
+//! 订单按价位与账户建索引，同价位按 (创建时间, 订单ID) 排序，价位查询与盘口遍历
+//! 不扫描全部订单；仓位按账户建索引。订单经 `get_order_mut` 变更状态后仍留在索引中，
+//! 查询时过滤非活跃订单，移除时同步清理索引（价格、方向、创建时间与所属账户创建后不变）
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use crate::domain::entity::{Order, OrderId, Position, PositionId, PositionSide, Price, TraderId};
+use crate::domain::entity::{
+    Order, OrderId, Position, PositionId, PositionSide, Price, Side, Timestamp, TraderId,
+};
 use crate::domain::repository::{OrderRepository, PositionRepository, RepositoryError};
 
+/// 同价位挂单：(创建时间, 订单ID)
+type Level = BTreeSet<(Timestamp, OrderId)>;
+
 /// 内存订单仓储
 pub struct InMemoryOrderRepository {
     orders: HashMap<OrderId, Order>,
+    /// 买方价位
+    bids: BTreeMap<Price, Level>,
+    /// 卖方价位
+    asks: BTreeMap<Price, Level>,
+    /// 账户订单
+    by_trader: HashMap<TraderId, BTreeSet<OrderId>>,
     next_id: OrderId,
 }
 
 impl InMemoryOrderRepository {
     pub fn new() -> Self {
-        Self { orders: HashMap::new(), next_id: 1 }
+        Self {
+            orders: HashMap::new(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            by_trader: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Price, Level> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    fn unindex(&mut self, order: &Order) {
+        let levels = self.levels_mut(order.side);
+        if let Some(level) = levels.get_mut(&order.price) {
+            level.remove(&(order.created_at, order.id));
+            if level.is_empty() {
+                levels.remove(&order.price);
+            }
+        }
+        if let Some(ids) = self.by_trader.get_mut(&order.trader) {
+            ids.remove(&order.id);
+            if ids.is_empty() {
+                self.by_trader.remove(&order.trader);
+            }
+        }
+    }
+
+    /// 一组价位上的活跃订单（按价位顺序，同价位时间优先）
+    fn active<'a>(&'a self, levels: impl Iterator<Item = &'a Level>) -> Vec<&'a Order> {
+        levels
+            .flatten()
+            .filter_map(|(_, id)| self.orders.get(id))
+            .filter(|o| o.is_active())
+            .collect()
+    }
+
+    /// 第一个含活跃订单的价位
+    fn first_active<'a>(
+        &self,
+        mut levels: impl Iterator<Item = (&'a Price, &'a Level)>,
+    ) -> Option<Price> {
+        levels
+            .find(|(_, level)| {
+                level.iter().any(|(_, id)| self.orders.get(id).is_some_and(Order::is_active))
+            })
+            .map(|(price, _)| *price)
     }
 }
 
@@ -31,6 +96,12 @@ impl OrderRepository for InMemoryOrderRepository {
     }
 
     fn save_order(&mut self, order: Order) -> Result<(), RepositoryError> {
+        if let Some(previous) = self.orders.remove(&order.id) {
+            self.unindex(&previous);
+        }
+        let (side, price, key) = (order.side, order.price, (order.created_at, order.id));
+        self.levels_mut(side).entry(price).or_default().insert(key);
+        self.by_trader.entry(order.trader).or_default().insert(order.id);
         self.orders.insert(order.id, order);
         Ok(())
     }
@@ -44,49 +115,81 @@ impl OrderRepository for InMemoryOrderRepository {
     }
 
     fn remove_order(&mut self, id: OrderId) -> Option<Order> {
-        self.orders.remove(&id)
+        let order = self.orders.remove(&id)?;
+        self.unindex(&order);
+        Some(order)
     }
 
     fn get_bids_at_price(&self, price: Price) -> Vec<&Order> {
-        self.orders.values().filter(|o| o.is_active() && o.is_buy() && o.price == price).collect()
+        self.active(self.bids.get(&price).into_iter())
     }
 
     fn get_asks_at_price(&self, price: Price) -> Vec<&Order> {
-        self.orders.values().filter(|o| o.is_active() && !o.is_buy() && o.price == price).collect()
+        self.active(self.asks.get(&price).into_iter())
     }
 
     fn best_bid(&self) -> Option<Price> {
-        self.orders.values().filter(|o| o.is_active() && o.is_buy()).map(|o| o.price).max()
+        self.first_active(self.bids.iter().rev())
     }
 
     fn best_ask(&self) -> Option<Price> {
-        self.orders.values().filter(|o| o.is_active() && !o.is_buy()).map(|o| o.price).min()
+        self.first_active(self.asks.iter())
     }
 
     fn get_bids(&self) -> Vec<&Order> {
-        let mut bids: Vec<_> =
-            self.orders.values().filter(|o| o.is_active() && o.is_buy()).collect();
-        bids.sort_by(|a, b| b.price.cmp(&a.price).then(a.created_at.cmp(&b.created_at)));
-        bids
+        self.active(self.bids.values().rev())
     }
 
     fn get_asks(&self) -> Vec<&Order> {
-        let mut asks: Vec<_> =
-            self.orders.values().filter(|o| o.is_active() && !o.is_buy()).collect();
-        asks.sort_by(|a, b| a.price.cmp(&b.price).then(a.created_at.cmp(&b.created_at)));
-        asks
+        self.active(self.asks.values())
+    }
+
+    fn get_orders_by_trader(&self, trader: TraderId) -> Vec<&Order> {
+        self.by_trader
+            .get(&trader)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.orders.get(id))
+            .filter(|o| o.is_active())
+            .collect()
+    }
+
+    fn peek_next_order_id(&self) -> OrderId {
+        self.next_id
+    }
+
+    fn set_next_order_id(&mut self, id: OrderId) {
+        self.next_id = id;
+    }
+
+    fn reserve_orders(&mut self, additional: usize) {
+        self.orders.reserve(additional);
+    }
+
+    fn order_capacity(&self) -> usize {
+        self.orders.capacity()
     }
 }
 
 /// 内存仓位仓储
 pub struct InMemoryPositionRepository {
     positions: HashMap<PositionId, Position>,
+    /// 账户仓位
+    by_trader: HashMap<TraderId, BTreeSet<PositionId>>,
     next_id: PositionId,
 }
 
 impl InMemoryPositionRepository {
     pub fn new() -> Self {
-        Self { positions: HashMap::new(), next_id: 1 }
+        Self { positions: HashMap::new(), by_trader: HashMap::new(), next_id: 1 }
+    }
+
+    fn position_id_of(&self, trader: TraderId, position_side: PositionSide) -> Option<PositionId> {
+        self.by_trader
+            .get(&trader)?
+            .iter()
+            .copied()
+            .find(|id| self.positions.get(id).is_some_and(|p| p.position_side == position_side))
     }
 }
 
@@ -104,6 +207,7 @@ impl PositionRepository for InMemoryPositionRepository {
     }
 
     fn save_position(&mut self, position: Position) -> Result<(), RepositoryError> {
+        self.by_trader.entry(position.trader).or_default().insert(position.id);
         self.positions.insert(position.id, position);
         Ok(())
     }
@@ -117,7 +221,14 @@ impl PositionRepository for InMemoryPositionRepository {
     }
 
     fn remove_position(&mut self, id: PositionId) -> Option<Position> {
-        self.positions.remove(&id)
+        let position = self.positions.remove(&id)?;
+        if let Some(ids) = self.by_trader.get_mut(&position.trader) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.by_trader.remove(&position.trader);
+            }
+        }
+        Some(position)
     }
 
     fn get_position_by_trader_side(
@@ -125,7 +236,7 @@ impl PositionRepository for InMemoryPositionRepository {
         trader: TraderId,
         position_side: PositionSide,
     ) -> Option<&Position> {
-        self.positions.values().find(|p| p.trader == trader && p.position_side == position_side)
+        self.position_id_of(trader, position_side).and_then(|id| self.positions.get(&id))
     }
 
     fn get_position_by_trader_side_mut(
@@ -133,15 +244,52 @@ impl PositionRepository for InMemoryPositionRepository {
         trader: TraderId,
         position_side: PositionSide,
     ) -> Option<&mut Position> {
-        self.positions.values_mut().find(|p| p.trader == trader && p.position_side == position_side)
+        self.position_id_of(trader, position_side).and_then(|id| self.positions.get_mut(&id))
+    }
+
+    fn get_positions_by_trader(&self, trader: TraderId) -> Vec<&Position> {
+        self.by_trader
+            .get(&trader)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.positions.get(id))
+            .collect()
+    }
+
+    fn get_all_positions(&self) -> Vec<&Position> {
+        let mut positions: Vec<_> = self.positions.values().collect();
+        positions.sort_by_key(|p| p.id);
+        positions
+    }
+
+    fn peek_next_position_id(&self) -> PositionId {
+        self.next_id
+    }
+
+    fn set_next_position_id(&mut self, id: PositionId) {
+        self.next_id = id;
+    }
+
+    fn reserve_positions(&mut self, additional: usize) {
+        self.positions.reserve(additional);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entity::{OrderStatus, Side, TimeInForce};
-    use crate::domain::service::{Command, CommandResult, MatchingService, PrepCommandHandler};
+    use crate::domain::ErrorCode;
+    use crate::domain::entity::{
+        AccountSettingChange, AccountStatus, AssetBalance, EngineEvent, MarginMode, MmpConfig,
+        OrderStatus, PositionSettlement, PostTradeLimits, QuoteEntry, RiskAuditAction, RiskProfile,
+        SETTING_HISTORY_LIMIT, SettlementType, Side, TimeInForce,
+    };
+    use crate::domain::repository::BalanceReader;
+    use crate::domain::service::matching::initial_margin;
+    use crate::domain::service::{
+        Command, CommandQueue, CommandResult, MatchingService, PrepCommandHandler,
+        PrepQueryHandler, QuoteLifeConfig,
+    };
 
     fn create_service() -> MatchingService<InMemoryOrderRepository, InMemoryPositionRepository> {
         let order_repo = InMemoryOrderRepository::new();
@@ -149,6 +297,39 @@ mod tests {
         MatchingService::new(order_repo, position_repo)
     }
 
+    #[test]
+    fn test_order_repository_indexes_levels() {
+        let mut repo = InMemoryOrderRepository::new();
+        let order = |id, trader, side, price, ts| {
+            let position_side =
+                if side == Side::Buy { PositionSide::Long } else { PositionSide::Short };
+            Order::new(id, trader, side, price, 10, position_side, false, TimeInForce::GTC, ts)
+        };
+        for o in [
+            order(1, 1, Side::Buy, 100, 3),
+            order(2, 2, Side::Buy, 100, 1),
+            order(3, 1, Side::Buy, 101, 2),
+            order(4, 2, Side::Sell, 105, 1),
+        ] {
+            repo.save_order(o).unwrap();
+        }
+        // 同价位按创建时间优先，盘口按价格优先
+        let ids = |orders: Vec<&Order>| orders.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(ids(repo.get_bids_at_price(100)), [2, 1]);
+        assert_eq!(ids(repo.get_bids()), [3, 2, 1]);
+        assert_eq!(ids(repo.get_orders_by_trader(1)), [1, 3]);
+        assert_eq!((repo.best_bid(), repo.best_ask()), (Some(101), Some(105)));
+
+        // 经可变引用成交完毕的订单不再出现在盘口，移除后索引清空
+        repo.get_order_mut(3).unwrap().status = OrderStatus::Filled;
+        assert_eq!(repo.best_bid(), Some(100));
+        assert_eq!(ids(repo.get_orders_by_trader(1)), [1]);
+        for id in 1..=4 {
+            repo.remove_order(id);
+        }
+        assert!(repo.bids.is_empty() && repo.asks.is_empty() && repo.by_trader.is_empty());
+    }
+
     #[test]
     fn test_limit_order_no_match() {
         let mut service = create_service();
@@ -368,4 +549,1118 @@ mod tests {
             _ => panic!("Expected CancelOrder result"),
         }
     }
+
+    struct FixedBalances;
+
+    impl BalanceReader for FixedBalances {
+        fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance> {
+            vec![AssetBalance::new("USDT", trader * 1000, 0)]
+        }
+    }
+
+    #[test]
+    fn test_account_snapshot() {
+        let mut service = create_service();
+        service.set_timestamp(1000);
+
+        // trader 1 开空 100 @ 50000，trader 2 开多 100
+        service.handle(Command::LimitOrder {
+            trader: 1,
+            side: Side::Sell,
+            price: 50000,
+            quantity: 100,
+            position_side: PositionSide::Short,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        service.handle(Command::LimitOrder {
+            trader: 2,
+            side: Side::Buy,
+            price: 50000,
+            quantity: 100,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+
+        // trader 2 再挂一笔未成交买单
+        service.handle(Command::LimitOrder {
+            trader: 2,
+            side: Side::Buy,
+            price: 40000,
+            quantity: 10,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+
+        let snapshot = service.account_snapshot(2, Some(51000), &FixedBalances);
+
+        assert_eq!(snapshot.sequence, 3);
+        assert_eq!(snapshot.balances, vec![AssetBalance::new("USDT", 2000, 0)]);
+        assert_eq!(snapshot.positions.len(), 1);
+        assert_eq!(snapshot.positions[0].quantity, 100);
+        // (51000 - 50000) * 100
+        assert_eq!(snapshot.unrealized_pnl, 100_000);
+        // 10 * 40000 / 10x
+        assert_eq!(snapshot.open_order_margin, 40_000);
+
+        // 平仓方向的卖单由 100 多仓抵消，不新增保证金
+        let check = |quantity, available| {
+            service.check_collateral(
+                2,
+                Side::Sell,
+                60000,
+                quantity,
+                PositionSide::Long,
+                false,
+                available,
+            )
+        };
+        assert_eq!(check(100, 0), Ok(0));
+        // 超出持仓 20 @ 60000 / 10x，与开仓方向挂单取较大值：120_000 - 40_000
+        assert_eq!(check(120, 80_000), Ok(80_000));
+        assert_eq!(check(120, 50_000), Err(ErrorCode::InsufficientMargin));
+
+        // 查询不推进序列号
+        assert_eq!(service.account_snapshot(2, None, &FixedBalances).sequence, 3);
+
+        // 同步保证金余额后撮合路径预占：持仓 500_000 + 挂单 40_000，尚余 60_000
+        service.handle(Command::SetCollateral { trader: 2, collateral: 600_000 });
+        let buy = |quantity| Command::LimitOrder {
+            trader: 2,
+            side: Side::Buy,
+            price: 40000,
+            quantity,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        // 20 @ 40000 / 10x 新增 80_000
+        assert!(matches!(
+            service.handle(buy(20)),
+            CommandResult::Error { code: ErrorCode::InsufficientMargin, .. }
+        ));
+        assert!(matches!(service.handle(buy(10)), CommandResult::LimitOrder { .. }));
+        // 空头方向的报价 10 @ 70000 需 70_000，仅余 20_000
+        let quotes = vec![QuoteEntry::two_sided(39000, 1, 70000, 10)];
+        assert!(matches!(
+            service.handle(Command::MassQuote { trader: 2, quotes }),
+            CommandResult::Error { code: ErrorCode::InsufficientMargin, .. }
+        ));
+        let quotes = vec![QuoteEntry::two_sided(39000, 1, 70000, 2)];
+        assert!(matches!(
+            service.handle(Command::MassQuote { trader: 2, quotes }),
+            CommandResult::MassQuote { .. }
+        ));
+        // 未同步余额的账户不做预占检查
+        assert!(matches!(
+            service.handle(Command::LimitOrder {
+                trader: 9,
+                side: Side::Buy,
+                price: 40000,
+                quantity: 1_000,
+                position_side: PositionSide::Long,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            }),
+            CommandResult::LimitOrder { .. }
+        ));
+    }
+
+    #[test]
+    fn test_execution_reports_published() {
+        let mut service = create_service();
+        service.set_timestamp(1000);
+
+        // 两笔 Maker 卖单 30 @ 50000、20 @ 50100
+        for (price, quantity) in [(50000, 30), (50100, 20)] {
+            service.handle(Command::LimitOrder {
+                trader: 1,
+                side: Side::Sell,
+                price,
+                quantity,
+                position_side: PositionSide::Short,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+
+        // Taker 买单 100，吃掉两档后剩余挂单
+        let result = service.handle(Command::LimitOrder {
+            trader: 2,
+            side: Side::Buy,
+            price: 50100,
+            quantity: 100,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        let taker_id = match result {
+            CommandResult::LimitOrder { order_id, .. } => order_id,
+            _ => panic!("Expected LimitOrder result"),
+        };
+
+        let reports = service.drain_execution_reports();
+        let transitions: Vec<_> = reports.iter().map(|r| (r.order_id, r.from, r.status)).collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (1, OrderStatus::New, OrderStatus::Filled),
+                (taker_id, OrderStatus::New, OrderStatus::PartiallyFilled),
+                (2, OrderStatus::New, OrderStatus::Filled),
+                (taker_id, OrderStatus::PartiallyFilled, OrderStatus::PartiallyFilled),
+            ]
+        );
+
+        // 每条 Taker 回报都带累计统计
+        let last = reports[3];
+        assert_eq!((last.last_fill_price, last.last_fill_quantity), (50100, 20));
+        assert_eq!(last.cumulative_filled_quantity, 50);
+        assert_eq!(last.remaining_quantity, 50);
+        // (30*50000 + 20*50100) / 50
+        assert_eq!(last.avg_fill_price, 50040);
+
+        // 挂单保留部分成交状态，取消时从 PartiallyFilled 迁移
+        service.handle(Command::CancelOrder { order_id: taker_id });
+        let reports = service.drain_execution_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].from, OrderStatus::PartiallyFilled);
+        assert_eq!(reports[0].status, OrderStatus::Cancelled);
+        assert!(!reports[0].is_fill());
+        assert_eq!(reports[0].cumulative_filled_quantity, 50);
+        assert!(service.drain_execution_reports().is_empty());
+    }
+
+    #[test]
+    fn test_bust_trade_restores_positions() {
+        struct NoBalances;
+        impl BalanceReader for NoBalances {
+            fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+                vec![]
+            }
+        }
+
+        let mut service = create_service();
+        service.set_timestamp(1000);
+
+        let place =
+            |service: &mut MatchingService<_, _>, trader, side, price, quantity, pos_side| {
+                service.handle(Command::LimitOrder {
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side: pos_side,
+                    reduce_only: false,
+                    time_in_force: TimeInForce::GTC,
+                })
+            };
+
+        // 交易者1 开多 10 @ 100（对手为交易者3）
+        place(&mut service, 3, Side::Sell, 100, 10, PositionSide::Short);
+        place(&mut service, 1, Side::Buy, 100, 10, PositionSide::Long);
+
+        // 交易者1 平多 4 @ 120，交易者2 开多
+        place(&mut service, 2, Side::Buy, 120, 4, PositionSide::Long);
+        let trade_id = match place(&mut service, 1, Side::Sell, 120, 4, PositionSide::Long) {
+            CommandResult::LimitOrder { trades, .. } => trades[0].id(),
+            _ => panic!("Expected LimitOrder result"),
+        };
+        let before = service.account_snapshot(1, None, &NoBalances);
+        assert_eq!(before.positions[0].quantity, 6);
+        assert_eq!(before.positions[0].realized_pnl, 80);
+
+        service.set_timestamp(2000);
+        service.drain_events();
+        let result = service.handle(Command::BustTrade {
+            trade_id,
+            reason: "错误价格".to_string(),
+            operator: "ops".to_string(),
+        });
+        match result {
+            CommandResult::BustTrade { quantity, price, taker, maker, .. } => {
+                assert_eq!((quantity, price, taker, maker), (4, 120, 1, 2));
+            }
+            _ => panic!("Expected BustTrade result"),
+        }
+
+        // 双方仓位恢复到成交前
+        let trader1 = service.account_snapshot(1, None, &NoBalances);
+        assert_eq!(trader1.positions[0].quantity, 10);
+        assert_eq!(trader1.positions[0].entry_price, 100);
+        assert_eq!(trader1.positions[0].realized_pnl, 0);
+        assert!(service.account_snapshot(2, None, &NoBalances).positions.is_empty());
+        assert_eq!(service.account_snapshot(3, None, &NoBalances).positions[0].quantity, 10);
+
+        // 冲正结算：减仓腿冲回盈亏，开仓腿按比例释放保证金
+        let settlements: Vec<PositionSettlement> = service
+            .drain_events()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                EngineEvent::PositionSettled(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(settlements.len(), 2);
+        assert!(settlements.iter().all(|s| s.settlement_type == SettlementType::TradeBust));
+        assert_eq!((settlements[0].trader, settlements[0].realized_pnl), (1, -80));
+        assert_eq!(settlements[0].released_margin, 0);
+        assert_eq!((settlements[1].trader, settlements[1].quantity), (2, 4));
+        assert_eq!(settlements[1].realized_pnl, 0);
+        assert_eq!(settlements[1].released_margin, initial_margin(4, 120, 10));
+
+        // 撤销加仓：均价按 (数量·均价 − 成交量·成交价) / (数量 − 成交量) 还原
+        place(&mut service, 5, Side::Sell, 110, 10, PositionSide::Short);
+        let add_id = match place(&mut service, 1, Side::Buy, 110, 10, PositionSide::Long) {
+            CommandResult::LimitOrder { trades, .. } => trades[0].id(),
+            _ => panic!("Expected LimitOrder result"),
+        };
+        let added = service.account_snapshot(1, None, &NoBalances).positions[0].clone();
+        assert_eq!((added.quantity, added.entry_price), (20, 105));
+        service.handle(Command::BustTrade {
+            trade_id: add_id,
+            reason: "错误价格".to_string(),
+            operator: "ops".to_string(),
+        });
+        let restored = &service.account_snapshot(1, None, &NoBalances).positions[0];
+        assert_eq!((restored.quantity, restored.entry_price), (10, 100));
+        assert_eq!(restored.margin, added.margin / 2);
+
+        // 审计记录
+        let log = service.bust_log();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].trade.trade_id, trade_id);
+        assert_eq!(log[0].operator, "ops");
+        assert_eq!(log[0].busted_at, 2000);
+
+        // 同一成交不能重复撤销
+        let result = service.handle(Command::BustTrade {
+            trade_id,
+            reason: "重复".to_string(),
+            operator: "ops".to_string(),
+        });
+        assert!(matches!(result, CommandResult::Error { code: ErrorCode::TradeNotFound, .. }));
+    }
+
+    #[test]
+    fn test_stop_loss_and_trailing_stop() {
+        struct NoBalances;
+        impl BalanceReader for NoBalances {
+            fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+                vec![]
+            }
+        }
+
+        let mut service = create_service();
+        let place =
+            |service: &mut MatchingService<_, _>, trader, side, price, quantity, pos_side| {
+                service.handle(Command::LimitOrder {
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side: pos_side,
+                    reduce_only: false,
+                    time_in_force: TimeInForce::GTC,
+                })
+            };
+        let position_of = |service: &MatchingService<_, _>, trader| {
+            service.account_snapshot(trader, None, &NoBalances).positions.first().map(|p| p.id)
+        };
+
+        // 交易者1 开多 10 @ 100，交易者3 开空 10 @ 100
+        place(&mut service, 3, Side::Sell, 100, 10, PositionSide::Short);
+        place(&mut service, 1, Side::Buy, 100, 10, PositionSide::Long);
+        let long = position_of(&service, 1).unwrap();
+        let short = position_of(&service, 3).unwrap();
+
+        // 多头止损 90（市价）与止盈 130 互为 OCO
+        let result = service.handle(Command::SetStopLoss {
+            trader: 1,
+            position_id: long,
+            trigger_price: 90,
+            close_price: None,
+        });
+        assert!(matches!(result, CommandResult::SetStopLoss { success: true, .. }));
+        let result = service.handle(Command::SetTakeProfit {
+            trader: 1,
+            position_id: long,
+            trigger_price: 130,
+            close_price: Some(130),
+        });
+        assert!(matches!(result, CommandResult::SetTakeProfit { success: true, .. }));
+        let result = service.handle(Command::SetStopLoss {
+            trader: 2,
+            position_id: long,
+            trigger_price: 90,
+            close_price: None,
+        });
+        assert!(matches!(result, CommandResult::Error { code: ErrorCode::PositionNotFound, .. }));
+
+        // 空头追踪止损回调 10%，从最新成交价 100 开始跟踪
+        let result = service.handle(Command::TrailingStop {
+            trader: 3,
+            position_id: short,
+            callback_rate: 1000,
+            activation_price: None,
+        });
+        assert!(matches!(result, CommandResult::TrailingStop { current_trigger_price: 110, .. }));
+
+        // 成交价跌到 89：止损以最差买价平多；空头追踪触发价收紧到 97
+        place(&mut service, 4, Side::Buy, 89, 20, PositionSide::Long);
+        place(&mut service, 5, Side::Sell, 89, 1, PositionSide::Short);
+        assert_eq!(position_of(&service, 1), None);
+        assert!(position_of(&service, 3).is_some());
+
+        // 成交价反弹到 97：追踪止损平空
+        place(&mut service, 6, Side::Sell, 97, 20, PositionSide::Short);
+        place(&mut service, 7, Side::Buy, 97, 1, PositionSide::Long);
+        assert_eq!(position_of(&service, 3), None);
+    }
+
+    #[test]
+    fn test_market_maker_kill_switch() {
+        let mut service = create_service();
+        service.set_timestamp(1000);
+
+        let limits = PostTradeLimits { max_position_quantity: 15, max_realized_loss: 1_000_000 };
+        service.handle(Command::SetRiskProfile {
+            trader: 1,
+            profile: RiskProfile::market_maker(100, limits),
+            operator: "admin".to_string(),
+        });
+
+        let order = |trader, side, price, quantity, position_side| Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+
+        // 做市商跳过名义价值检查；配置了限额的普通账户同样的委托被拒绝
+        let huge = 10_000_000_000;
+        service.handle(Command::SetRiskProfile {
+            trader: 2,
+            profile: RiskProfile::standard().with_limits(huge, 200),
+            operator: "admin".to_string(),
+        });
+        let result = service.handle(order(2, Side::Buy, huge, 2, PositionSide::Long));
+        assert!(matches!(
+            result,
+            CommandResult::Error { code: ErrorCode::MaxPositionSizeExceeded, .. }
+        ));
+        let result = service.handle(order(1, Side::Sell, huge, 2, PositionSide::Short));
+        assert!(matches!(result, CommandResult::LimitOrder { .. }));
+
+        // 做市商双边挂单，买单被吃掉后持仓超限
+        service.handle(order(1, Side::Buy, 100, 20, PositionSide::Long));
+        service.handle(order(2, Side::Sell, 100, 20, PositionSide::Short));
+
+        let risk = service.risk();
+        assert!(risk.is_killed(1));
+        let actions: Vec<_> =
+            risk.audit_log().iter().map(|e| (e.action, e.operator.as_str())).collect();
+        assert!(matches!(actions[2], (RiskAuditAction::KillSwitchTriggered(_), "system")));
+
+        // 剩余挂单被撤销，新委托被拒绝
+        let reports = service.drain_execution_reports();
+        assert!(reports.iter().any(|r| r.trader == 1 && r.status == OrderStatus::Cancelled));
+        let result = service.handle(order(1, Side::Sell, 200, 1, PositionSide::Long));
+        assert!(matches!(result, CommandResult::Error { code: ErrorCode::KillSwitchActive, .. }));
+
+        // 管理员解除熔断
+        let result =
+            service.handle(Command::ResetKillSwitch { trader: 1, operator: "admin".to_string() });
+        assert!(matches!(result, CommandResult::ResetKillSwitch { success: true, .. }));
+        assert!(!service.risk().is_killed(1));
+    }
+
+    #[test]
+    fn test_account_status_enforced() {
+        let mut service = create_service();
+        service.set_timestamp(1000);
+
+        let order = |trader, side, price| Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity: 10,
+            position_side: PositionSide::Both,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        let status = |status, reason: &str| Command::SetAccountStatus {
+            trader: 1,
+            status,
+            reason: reason.to_string(),
+            operator: "ops".to_string(),
+        };
+        let restricted = |result: &CommandResult| {
+            matches!(result, CommandResult::Error { code: ErrorCode::AccountRestricted, .. })
+        };
+        let CommandResult::LimitOrder { order_id: first, .. } =
+            service.handle(order(1, Side::Buy, 100))
+        else {
+            panic!("limit order rejected");
+        };
+        let CommandResult::LimitOrder { order_id: second, .. } =
+            service.handle(order(1, Side::Buy, 99))
+        else {
+            panic!("limit order rejected");
+        };
+
+        // 暂停：禁止新委托与报价，允许撤单
+        assert!(matches!(
+            service.handle(status(AccountStatus::Suspended, "KYC review")),
+            CommandResult::SetAccountStatus { from: AccountStatus::Active, success: true, .. }
+        ));
+        assert!(restricted(&service.handle(order(1, Side::Buy, 100))));
+        let quote = QuoteEntry { bid_price: 98, bid_quantity: 1, ask_price: 101, ask_quantity: 1 };
+        assert!(restricted(&service.handle(Command::MassQuote { trader: 1, quotes: vec![quote] })));
+        assert!(matches!(
+            service.handle(Command::CancelOrder { order_id: first }),
+            CommandResult::CancelOrder { success: true, .. }
+        ));
+
+        // 冻结：撤销剩余挂单
+        service.handle(status(AccountStatus::Frozen, "fraud"));
+        assert_eq!(service.queue_position(1, second).unwrap_err(), ErrorCode::OrderNotFound);
+        assert!(restricted(&service.handle(order(1, Side::Sell, 105))));
+        assert!(matches!(
+            service.handle(status(AccountStatus::Frozen, "again")),
+            CommandResult::SetAccountStatus { success: false, .. }
+        ));
+
+        service.handle(status(AccountStatus::Active, "cleared"));
+        assert!(matches!(
+            service.handle(order(1, Side::Sell, 105)),
+            CommandResult::LimitOrder { .. }
+        ));
+        let reasons: Vec<&str> =
+            service.risk().audit_log().iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["KYC review", "fraud", "cleared"]);
+    }
+
+    #[test]
+    fn test_queue_position() {
+        let mut service = create_service();
+
+        // 同价位三笔买单，随后部分成交第一笔
+        let mut order_ids = Vec::new();
+        for (ts, trader, quantity) in [(1000, 1, 10), (1001, 2, 20), (1002, 3, 30)] {
+            service.set_timestamp(ts);
+            match service.handle(Command::LimitOrder {
+                trader,
+                side: Side::Buy,
+                price: 50000,
+                quantity,
+                position_side: PositionSide::Long,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            }) {
+                CommandResult::LimitOrder { order_id, .. } => order_ids.push(order_id),
+                _ => panic!("Expected LimitOrder result"),
+            }
+        }
+        service.handle(Command::LimitOrder {
+            trader: 4,
+            side: Side::Sell,
+            price: 50000,
+            quantity: 4,
+            position_side: PositionSide::Short,
+            reduce_only: false,
+            time_in_force: TimeInForce::IOC,
+        });
+
+        let position = service.queue_position(3, order_ids[2]).unwrap();
+        assert_eq!(position.orders_ahead, 2);
+        assert_eq!(position.quantity_ahead, 6 + 20);
+        assert_eq!(position.remaining_quantity, 30);
+        assert_eq!(position.level_quantity, 56);
+
+        assert_eq!(service.queue_position(1, order_ids[0]).unwrap().orders_ahead, 0);
+
+        // 仅订单所有者可查询
+        assert_eq!(service.queue_position(1, order_ids[2]), Err(ErrorCode::OrderNotFound));
+    }
+
+    #[test]
+    fn test_min_quote_life_delays_early_cancel() {
+        let mut service = create_service();
+        let mut queue = CommandQueue::new().with_min_quote_life(QuoteLifeConfig::delay(100));
+        let order = |trader| Command::LimitOrder {
+            trader,
+            side: Side::Buy,
+            price: 50000,
+            quantity: 10,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        queue.push_at(order(1), 1_000, false).unwrap();
+        let order_id = match queue.drain_at(&mut service, 8, 1_000).as_slice() {
+            [CommandResult::LimitOrder { order_id, status: OrderStatus::New, .. }] => *order_id,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        // 挂单 30ms 后撤单：延迟到挂满 100ms
+        assert_eq!(queue.push_at(Command::CancelOrder { order_id }, 1_030, false), Ok(None));
+        assert_eq!(queue.release_delayed(1_099), 0);
+        assert!(queue.drain_at(&mut service, 8, 1_099).is_empty());
+
+        assert_eq!(queue.release_delayed(1_100), 1);
+        let results = queue.drain_at(&mut service, 8, 1_100);
+        assert!(matches!(results.as_slice(), [CommandResult::CancelOrder { success: true, .. }]));
+
+        // 拒绝策略：未满时间的撤单直接拒绝
+        let mut queue = CommandQueue::new().with_min_quote_life(QuoteLifeConfig::reject(100));
+        queue.push_at(order(2), 2_000, false).unwrap();
+        let CommandResult::LimitOrder { order_id, .. } = queue.drain_at(&mut service, 8, 2_000)[0]
+        else {
+            panic!("limit order rejected");
+        };
+        assert_eq!(
+            queue.push_at(Command::CancelOrder { order_id }, 2_050, false),
+            Err(ErrorCode::MinQuoteLifeNotElapsed)
+        );
+        assert!(queue.push_at(Command::CancelOrder { order_id }, 2_100, false).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_compress_positions_between_sub_accounts() {
+        use crate::domain::service::{CompressionJob, SubAccountGroups};
+
+        let mut service = create_service();
+        service.set_timestamp(1000);
+        // trader 1 开空 10 @ 100，trader 2 开多 10 @ 100，同属母账户 100
+        for (trader, side, position_side) in
+            [(1, Side::Sell, PositionSide::Short), (2, Side::Buy, PositionSide::Long)]
+        {
+            service.handle(Command::LimitOrder {
+                trader,
+                side,
+                price: 100,
+                quantity: 10,
+                position_side,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+
+        // 作业按账户服务的分组生成计划，引擎按自身的分组复核：未同意前整体拒绝
+        let mut groups = SubAccountGroups::new();
+        for sub in [1, 2] {
+            groups.link(sub, 100);
+            groups.set_consent(sub, true);
+        }
+        let mut job = CompressionJob::new(60_000, 1000, "ops");
+        let command = job.poll(1000, &groups, service.positions(), 110).unwrap();
+        for sub_account in [1, 2] {
+            service.handle(Command::LinkSubAccount { sub_account, parent: Some(100) });
+        }
+        service.handle(Command::SetCompressionConsent { sub_account: 1, consent: true });
+        match service.handle(command.clone()) {
+            CommandResult::Error { code, .. } => {
+                assert_eq!(code, ErrorCode::CompressionNotEligible)
+            }
+            other => panic!("Expected error, got {:?}", other),
+        }
+        assert_eq!(service.positions().len(), 2);
+
+        service.handle(Command::SetCompressionConsent { sub_account: 2, consent: true });
+        service.drain_events();
+        let settlements = match service.handle(command.clone()) {
+            CommandResult::CompressPositions { settlements } => settlements,
+            other => panic!("Expected CompressPositions, got {:?}", other),
+        };
+        assert_eq!(settlements.len(), 1);
+        assert_eq!(settlements[0].long_realized_pnl, 100);
+        assert_eq!(settlements[0].short_realized_pnl, -100);
+        // 双方各 10 * 100 / 10x
+        assert_eq!(settlements[0].released_margin, 200);
+        assert!(service.positions().is_empty());
+        assert_eq!(service.compression_log()[0].operator, "ops");
+        // 双方各一条压缩结算事件
+        let settled: Vec<_> = service
+            .drain_events()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                EngineEvent::PositionSettled(s) => {
+                    Some((s.settlement_type, s.trader, s.realized_pnl))
+                }
+                _ => None,
+            })
+            .collect();
+        let compression = SettlementType::Compression;
+        assert_eq!(settled, vec![(compression, 2, 100), (compression, 1, -100)]);
+
+        // 仓位已平，重复执行整体拒绝
+        match service.handle(command) {
+            CommandResult::Error { code, .. } => assert_eq!(code, ErrorCode::PositionNotFound),
+            other => panic!("Expected error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deliver_futures_at_expiry() {
+        use crate::domain::entity::{EngineEvent, SettlementType};
+
+        let mut service = create_service();
+        service.set_timestamp(1000);
+        let set_expiry = |expiry| Command::SetExpiry { expiry, operator: "ops".to_string() };
+        assert!(matches!(
+            service.handle(set_expiry(Some(5000))),
+            CommandResult::SetExpiry { old_expiry: None, new_expiry: Some(5000) }
+        ));
+        let order = |trader, side, price, position_side| Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity: 10,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        service.handle(order(1, Side::Sell, 100, PositionSide::Short));
+        service.handle(order(2, Side::Buy, 100, PositionSide::Long));
+        service.handle(order(3, Side::Buy, 90, PositionSide::Long));
+
+        let deliver = Command::DeliverFutures { delivery_price: 120, operator: "ops".to_string() };
+        match service.handle(deliver.clone()) {
+            CommandResult::Error { code, .. } => assert_eq!(code, ErrorCode::InstrumentNotExpired),
+            other => panic!("Expected error, got {:?}", other),
+        }
+
+        // 到期后停止交易
+        service.set_timestamp(5000);
+        match service.handle(order(4, Side::Sell, 90, PositionSide::Short)) {
+            CommandResult::Error { code, .. } => assert_eq!(code, ErrorCode::InstrumentExpired),
+            other => panic!("Expected error, got {:?}", other),
+        }
+        // 到期后不能推迟到期时间重新开放交易
+        assert!(matches!(
+            service.handle(set_expiry(Some(9000))),
+            CommandResult::Error { code: ErrorCode::InstrumentExpired, .. }
+        ));
+        assert_eq!(service.expiry(), Some(5000));
+
+        service.drain_events();
+        let CommandResult::DeliverFutures { cancelled_orders, settlements } =
+            service.handle(deliver)
+        else {
+            panic!("delivery rejected");
+        };
+        assert_eq!(cancelled_orders.len(), 1);
+        let pnl: Vec<_> = settlements.iter().map(|s| (s.trader, s.realized_pnl)).collect();
+        assert_eq!(pnl.len(), 2);
+        assert!(pnl.contains(&(1, -200)) && pnl.contains(&(2, 200)));
+        assert!(settlements.iter().all(
+            |s| s.settlement_type == SettlementType::FutureDelivery && s.released_margin == 100
+        ));
+        assert!(service.positions().is_empty());
+        assert_eq!(service.delivery_log()[0].operator, "ops");
+        let settled = service
+            .drain_events()
+            .iter()
+            .filter(|e| matches!(e.event, EngineEvent::PositionSettled(_)))
+            .count();
+        assert_eq!(settled, 2);
+    }
+
+    #[test]
+    fn test_price_spike_trips_circuit_breaker() {
+        use crate::domain::entity::{EngineEvent, MarketAlertKind, PriceFeed};
+        use crate::domain::service::AnomalyConfig;
+
+        let mut service = create_service();
+        service.enable_anomaly_detection(AnomalyConfig {
+            min_samples: 3,
+            auto_halt: true,
+            ..AnomalyConfig::default()
+        });
+        for ts in 1..=3 {
+            service.set_timestamp(ts * 1000);
+            service.handle(Command::UpdateMarkPrice { mark_price: 100 });
+        }
+        service.drain_events();
+
+        service.set_timestamp(4000);
+        let CommandResult::UpdateMarkPrice { alerts, .. } =
+            service.handle(Command::UpdateMarkPrice { mark_price: 150 })
+        else {
+            panic!("mark price rejected");
+        };
+        assert!(matches!(
+            alerts[0].kind,
+            MarketAlertKind::PriceSpike { feed: PriceFeed::Mark, price: 150, mean: 100, .. }
+        ));
+        assert!(service.is_halted());
+        assert!(
+            service.drain_events().iter().any(|e| matches!(e.event, EngineEvent::MarketAlert(_)))
+        );
+
+        let order = Command::LimitOrder {
+            trader: 1,
+            side: Side::Buy,
+            price: 100,
+            quantity: 10,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        match service.handle(order.clone()) {
+            CommandResult::Error { code, .. } => {
+                assert_eq!(code, ErrorCode::CircuitBreakerTripped)
+            }
+            other => panic!("Expected error, got {:?}", other),
+        }
+
+        let resume = Command::ResumeTrading { operator: "ops".to_string() };
+        assert!(matches!(
+            service.handle(resume.clone()),
+            CommandResult::ResumeTrading { success: true }
+        ));
+        assert!(matches!(service.handle(resume), CommandResult::ResumeTrading { success: false }));
+        assert_eq!(service.circuit_breaker_log()[0].resumed_by.as_deref(), Some("ops"));
+        assert!(matches!(service.handle(order), CommandResult::LimitOrder { .. }));
+
+        // 标记价格停滞超过 5 秒，再次触发熔断
+        service.set_timestamp(9001);
+        service.handle(Command::CancelOrder { order_id: 999 });
+        assert!(matches!(
+            service.alert_log().last().unwrap().kind,
+            MarketAlertKind::StaleFeed { feed: PriceFeed::Mark, last_update: 4000 }
+        ));
+        assert_eq!(service.circuit_breaker_log().len(), 2);
+    }
+
+    #[test]
+    fn test_position_risk() {
+        use crate::domain::service::ReadModelProjection;
+
+        let mut service = create_service();
+        service.set_timestamp(1000);
+        // trader 1 开空 10 @ 10000，trader 2 开多 10（10x，保证金各 10000）
+        for (trader, side, position_side) in
+            [(1, Side::Sell, PositionSide::Short), (2, Side::Buy, PositionSide::Long)]
+        {
+            service.handle(Command::LimitOrder {
+                trader,
+                side,
+                price: 10000,
+                quantity: 10,
+                position_side,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+
+        let long = service.position_risk(2, 10400);
+        assert_eq!(long.len(), 1);
+        let risk = long[0];
+        assert_eq!((risk.entry_price, risk.mark_price, risk.quantity), (10000, 10400, 10));
+        // 10000 * (1 - (1/10 - 0.5%))
+        assert_eq!(risk.liquidation_price, 9050);
+        assert_eq!((risk.margin, risk.unrealized_pnl), (10000, 4000));
+        // 10 * 10400 * 0.5%
+        assert_eq!(risk.maintenance_margin, 520);
+        // 520 / (10000 + 4000)
+        assert_eq!(risk.margin_ratio_bps, 371);
+        assert_eq!((risk.adl_rank, risk.sequence), (5, 2));
+
+        let short = service.position_risk(1, 10400)[0];
+        assert_eq!(short.liquidation_price, 10950);
+        assert_eq!((short.unrealized_pnl, short.margin_ratio_bps), (-4000, 866));
+        // 标记价格越过强平价，保证金余额耗尽
+        assert_eq!(service.position_risk(1, 11000)[0].margin_ratio_bps, u64::MAX);
+        assert!(service.position_risk(3, 10400).is_empty());
+
+        // 读侧投影给出相同结果
+        let mut projection = ReadModelProjection::new();
+        projection.apply_all(&service.drain_events());
+        assert_eq!(projection.position_risk(2, 10400), long);
+    }
+
+    #[test]
+    fn test_account_setting_history() {
+        use crate::domain::service::ReadModelProjection;
+
+        let mut service = create_service();
+        service.set_timestamp(1000);
+        let error_code = |result: CommandResult| match result {
+            CommandResult::Error { code, .. } => code,
+            other => panic!("unexpected result {:?}", other),
+        };
+
+        let switch = Command::SwitchMarginMode { trader: 1, mode: MarginMode::Isolated };
+        assert!(matches!(
+            service.handle(switch.clone()),
+            CommandResult::SwitchMarginMode { success: true, .. }
+        ));
+        assert!(matches!(
+            service.handle(switch),
+            CommandResult::SwitchMarginMode { success: false, .. }
+        ));
+        let set_leverage =
+            |leverage| Command::SetLeverage { trader: 1, leverage, position_side: None };
+        assert_eq!(error_code(service.handle(set_leverage(0))), ErrorCode::InvalidLeverage);
+        assert!(matches!(
+            service.handle(set_leverage(20)),
+            CommandResult::SetLeverage { old_leverage: 10, new_leverage: 20, success: true, .. }
+        ));
+
+        // trader 1 逐仓 20x 开多 10 @ 10000，保证金 5000
+        for (trader, side, position_side) in
+            [(2, Side::Sell, PositionSide::Short), (1, Side::Buy, PositionSide::Long)]
+        {
+            service.handle(Command::LimitOrder {
+                trader,
+                side,
+                price: 10000,
+                quantity: 10,
+                position_side,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+        let position_id = service.position_risk(1, 10000)[0].position_id;
+        assert_eq!(service.position_risk(1, 10000)[0].liquidation_price, 9550);
+        assert_eq!(
+            error_code(
+                service.handle(Command::SwitchMarginMode { trader: 1, mode: MarginMode::Cross })
+            ),
+            ErrorCode::PositionOrOrderExists
+        );
+        // 降到 5x 需要 20000 起始保证金
+        assert_eq!(error_code(service.handle(set_leverage(5))), ErrorCode::InsufficientMargin);
+
+        let adjust = |trader, amount| Command::AdjustMargin { trader, position_id, amount };
+        match service.handle(adjust(1, 5000)) {
+            CommandResult::AdjustMargin {
+                old_margin, new_margin, new_liquidation_price, ..
+            } => {
+                assert_eq!((old_margin, new_margin, new_liquidation_price), (5000, 10000, 9050));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(
+            service.handle(set_leverage(10)),
+            CommandResult::SetLeverage { old_leverage: 20, new_leverage: 10, success: true, .. }
+        ));
+        let risk = service.position_risk(1, 10000)[0];
+        assert_eq!((risk.leverage, risk.margin, risk.liquidation_price), (10, 10000, 9050));
+        assert_eq!(error_code(service.handle(adjust(1, -1))), ErrorCode::InsufficientMargin);
+        assert_eq!(error_code(service.handle(adjust(2, 1))), ErrorCode::PositionNotFound);
+
+        let history = service.setting_history(1, 10);
+        assert_eq!(
+            history.iter().map(|r| r.change).collect::<Vec<_>>(),
+            vec![
+                AccountSettingChange::Leverage { position_side: None, from: 20, to: 10 },
+                AccountSettingChange::Margin { position_id, from: 5000, to: 10000 },
+                AccountSettingChange::Leverage { position_side: None, from: 10, to: 20 },
+                AccountSettingChange::MarginMode {
+                    from: MarginMode::Cross,
+                    to: MarginMode::Isolated
+                },
+            ]
+        );
+        assert_eq!((history[3].sequence, history[3].timestamp), (1, 1000));
+        assert_eq!(history.iter().map(|r| r.version).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(service.setting_history(1, 2).len(), 2);
+        assert!(service.setting_history(2, 10).is_empty());
+
+        // 变更日志条目：账户设置实体，版本号逐条递增，按仓位生效的变更附带仓位ID
+        let entry = history[1].change_log_entry();
+        assert!(entry.is_updated());
+        assert_eq!((entry.entity_id, entry.old_version, entry.new_version), (1, 2, 3));
+        let fields: Vec<_> = entry
+            .field_changes
+            .iter()
+            .map(|f| (f.field_name_as_str().unwrap(), f.old_value_bytes(), f.new_value_bytes()))
+            .collect();
+        let position_id = position_id.to_string();
+        assert_eq!(
+            fields,
+            vec![
+                ("positionId", position_id.as_bytes(), position_id.as_bytes()),
+                ("isolatedMargin", &b"5000"[..], &b"10000"[..]),
+            ]
+        );
+        let entry = history[3].change_log_entry();
+        assert_eq!(entry.field_changes[0].new_value_bytes(), b"ISOLATED");
+
+        // 读侧投影给出相同结果
+        let mut projection = ReadModelProjection::new();
+        projection.apply_all(&service.drain_events());
+        assert_eq!(projection.setting_history(1, 10), history);
+        assert_eq!(projection.position_risk(1, 10000)[0], risk);
+
+        // 每个账户只保留最近 SETTING_HISTORY_LIMIT 条
+        for i in 0..SETTING_HISTORY_LIMIT {
+            service.handle(set_leverage(11 + (i % 2) as u32));
+        }
+        projection.apply_all(&service.drain_events());
+        let history = service.setting_history(1, usize::MAX);
+        assert_eq!(history.len(), SETTING_HISTORY_LIMIT);
+        assert_eq!(history[0].version, 4 + SETTING_HISTORY_LIMIT as u64);
+        assert_eq!(projection.setting_history(1, usize::MAX), history);
+    }
+
+    #[test]
+    fn test_mass_quote_and_mmp() {
+        let mut service = create_service();
+        service.set_timestamp(1000);
+        let config = MmpConfig { window: 1000, fill_limit: 2, freeze: Some(5000) };
+        service.handle(Command::SetMmp { trader: 1, config: Some(config) });
+        let mass_quote = |levels: &[(Price, Price)]| Command::MassQuote {
+            trader: 1,
+            quotes: levels
+                .iter()
+                .map(|&(bid, ask)| QuoteEntry::two_sided(bid, 5, ask, 5))
+                .collect(),
+        };
+        let error_code = |result: CommandResult| match result {
+            CommandResult::Error { code, .. } => code,
+            other => panic!("unexpected result {:?}", other),
+        };
+
+        let CommandResult::MassQuote { order_ids: first, cancelled, .. } =
+            service.handle(mass_quote(&[(99, 101), (98, 102), (97, 103)]))
+        else {
+            panic!("mass quote rejected");
+        };
+        assert_eq!((first.len(), cancelled.len()), (6, 0));
+        // 整组替换
+        let CommandResult::MassQuote { order_ids, cancelled, .. } =
+            service.handle(mass_quote(&[(99, 101), (98, 102)]))
+        else {
+            panic!("mass quote rejected");
+        };
+        assert_eq!((order_ids.len(), cancelled), (4, first));
+        assert_eq!(service.mmp().quotes(1), order_ids.as_slice());
+
+        // 与他人挂单或自身交叉的报价整条拒绝，旧报价不变
+        service.handle(Command::LimitOrder {
+            trader: 2,
+            side: Side::Sell,
+            price: 105,
+            quantity: 1,
+            position_side: PositionSide::Short,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        assert_eq!(
+            error_code(service.handle(mass_quote(&[(99, 101), (106, 107)]))),
+            ErrorCode::InvalidPrice
+        );
+        assert_eq!(error_code(service.handle(mass_quote(&[(102, 101)]))), ErrorCode::InvalidPrice);
+        assert_eq!(service.mmp().quotes(1), order_ids.as_slice());
+        service.drain_events();
+
+        // 一笔吃掉两档卖报价，达到 2 笔触发保护，撤回剩余报价
+        let result = service.handle(Command::LimitOrder {
+            trader: 3,
+            side: Side::Buy,
+            price: 102,
+            quantity: 10,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        assert!(
+            matches!(result, CommandResult::LimitOrder { ref trades, .. } if trades.len() == 2)
+        );
+        let trigger = service
+            .drain_events()
+            .into_iter()
+            .find_map(|e| match e.event {
+                EngineEvent::MmpTriggered(trigger) => Some(trigger),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!((trigger.trader, trigger.fills, trigger.frozen_until), (1, 2, Some(6000)));
+        assert_eq!(trigger.pulled, order_ids);
+        assert!(service.mmp().quotes(1).is_empty());
+        assert_eq!(service.mmp().log().len(), 1);
+
+        assert_eq!(error_code(service.handle(mass_quote(&[(99, 101)]))), ErrorCode::MmpFrozen);
+        service.set_timestamp(6000);
+        assert!(matches!(
+            service.handle(mass_quote(&[(99, 101)])),
+            CommandResult::MassQuote { .. }
+        ));
+        // 空报价撤回全部报价
+        let CommandResult::MassQuote { order_ids, cancelled, .. } = service.handle(mass_quote(&[]))
+        else {
+            panic!("mass quote rejected");
+        };
+        assert_eq!((order_ids.len(), cancelled.len()), (0, 2));
+        assert!(matches!(
+            service.handle(Command::ResetMmp { trader: 1 }),
+            CommandResult::ResetMmp { success: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_feature_flags_at_decision_points() {
+        use crate::adaptor::outbound::command_codec::CommandRecord;
+        use crate::domain::service::{Feature, FlagRule};
+
+        let limit = |trader, side, price| Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity: 100,
+            position_side: PositionSide::Both,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        };
+        let flag =
+            |feature, rule| Command::SetFeatureFlag { feature, rule, operator: "ops".to_string() };
+        let commands = vec![
+            limit(1, Side::Sell, 10_000),
+            limit(2, Side::Buy, 10_000),
+            flag(Feature::SelfTradePrevention, FlagRule::accounts([3])),
+            limit(3, Side::Sell, 10_000),
+            limit(3, Side::Sell, 10_100),
+            limit(4, Side::Sell, 10_000),
+            limit(3, Side::Buy, 10_000),
+        ];
+        // 开关命令经命令日志编解码后回放，结果与原始执行一致
+        let replayed: Vec<Command> = commands
+            .iter()
+            .map(|command| {
+                let record = CommandRecord { timestamp: 0, command: command.clone() };
+                CommandRecord::decode(&record.encode()).unwrap().command
+            })
+            .collect();
+
+        for commands in [commands, replayed] {
+            let mut service = create_service();
+            let results: Vec<CommandResult> =
+                commands.into_iter().map(|command| service.handle(command)).collect();
+            let CommandResult::LimitOrder { trades, .. } = &results[1] else {
+                panic!("limit order rejected");
+            };
+            // 0.05% taker
+            assert_eq!(trades[0].fee(), 50);
+            assert!(service.feature_flags().is_enabled(Feature::SelfTradePrevention, 3));
+            assert!(!service.feature_flags().is_enabled(Feature::SelfTradePrevention, 4));
+            assert_eq!(service.feature_flags().log()[0].sequence, 3);
+
+            // 自成交防护：账户 3 可成交价位内的卖单被撤销，买单与账户 4 成交
+            let CommandResult::LimitOrder { trades, status, .. } = &results[6] else {
+                panic!("limit order rejected");
+            };
+            assert_eq!(*status, OrderStatus::Filled);
+            assert_eq!((trades.len(), trades[0].fee()), (1, 50));
+            assert_eq!(service.queue_position(3, 3).unwrap_err(), ErrorCode::OrderNotFound);
+            assert!(service.queue_position(3, 4).is_ok());
+        }
+    }
 }
@@ -0,0 +1,205 @@
+//! 不变量后台检查线程
+//!
+//! 撮合线程按间隔生成采样副本（见 [`InvariantSampler`](crate::domain::service::InvariantSampler)），
+//! 分片线程把副本转交本线程；检查与违例格式化都在本线程完成，不占用撮合时间。
+//! 违例连同现场写入告警出口，采样发送端全部关闭后线程退出。
+//!
+//! 分片配置了不变量抽样时由分片启动本线程，违例写入分片输出的 `violations`，
+//! 经投影消费线程转交下游审计与告警
+
+use std::io;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+
+use crate::adaptor::inbound::shard_runner::ShardOutput;
+use crate::domain::entity::{AssetBalance, InvariantViolation, TraderId};
+use crate::domain::repository::BalanceReader;
+use crate::domain::service::invariant::{InvariantChecker, InvariantProbe, SamplerStats};
+
+/// 违例告警出口
+pub trait ViolationSink: Send + 'static {
+    /// 写入一条违例（出口关闭时丢弃）
+    fn alert(&self, violation: InvariantViolation);
+}
+
+impl ViolationSink for Sender<InvariantViolation> {
+    fn alert(&self, violation: InvariantViolation) {
+        let _ = self.send(violation);
+    }
+}
+
+/// 写入分片输出
+impl ViolationSink for Sender<ShardOutput> {
+    fn alert(&self, violation: InvariantViolation) {
+        let output =
+            ShardOutput { results: Vec::new(), events: Vec::new(), violations: vec![violation] };
+        let _ = self.send(output);
+    }
+}
+
+/// 共享的余额来源（如账户服务写入的读侧投影）
+impl<B: BalanceReader> BalanceReader for Arc<RwLock<B>> {
+    fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance> {
+        self.read().unwrap_or_else(PoisonError::into_inner).balances_of(trader)
+    }
+}
+
+/// 运行中的检查线程
+#[derive(Debug)]
+pub struct InvariantCheckerHandle {
+    probes: Sender<InvariantProbe>,
+    thread: JoinHandle<SamplerStats>,
+}
+
+impl InvariantCheckerHandle {
+    /// 采样发送端（交给分片配置）
+    pub fn sender(&self) -> Sender<InvariantProbe> {
+        self.probes.clone()
+    }
+
+    /// 关闭本句柄的发送端并等待线程退出（分片线程须先退出），返回检查统计
+    pub fn shutdown(self) -> io::Result<SamplerStats> {
+        drop(self.probes);
+        self.thread.join().map_err(|_| io::Error::other("invariant checker thread panicked"))
+    }
+}
+
+/// 启动检查线程
+///
+/// `balances` 读取账户服务的余额，用于核对冻结保证金；告警出口关闭后继续检查并统计
+pub fn spawn_invariant_checker<B, V>(
+    margin_asset: impl Into<String>,
+    balances: B,
+    violations: V,
+) -> io::Result<InvariantCheckerHandle>
+where
+    B: BalanceReader + Send + 'static,
+    V: ViolationSink,
+{
+    let (probes, inbox) = mpsc::channel::<InvariantProbe>();
+    let mut checker = InvariantChecker::new(margin_asset);
+    let thread = thread::Builder::new().name("prep-invariants".to_string()).spawn(move || {
+        for probe in inbox {
+            for violation in checker.check(&probe, &balances) {
+                violations.alert(violation);
+            }
+        }
+        checker.stats()
+    })?;
+    Ok(InvariantCheckerHandle { probes, thread })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::adaptor::inbound::{
+        InMemoryOrderRepository, InMemoryPositionRepository, ShardConfig, spawn_shard,
+    };
+    use crate::adaptor::outbound::numa::{CorePinning, NumaTopology};
+    use crate::domain::entity::{
+        AssetBalance, InvariantKind, PositionSide, Side, TimeInForce, TraderId,
+    };
+    use crate::domain::service::InvariantSamplerConfig;
+    use crate::domain::service::command::Command;
+    use crate::domain::service::matching::MatchingService;
+    use crate::domain::service::warmup::WarmUpConfig;
+
+    struct Unfrozen;
+
+    impl BalanceReader for Unfrozen {
+        fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+            Vec::new()
+        }
+    }
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    #[test]
+    fn test_shard_hands_probes_to_checker_thread() {
+        let (alerts, violations) = mpsc::channel::<InvariantViolation>();
+        let checker = spawn_invariant_checker("USDT", Unfrozen, alerts).unwrap();
+        let warm_up =
+            WarmUpConfig { order_capacity: 16, position_capacity: 4, synthetic_rounds: 2 };
+        let config = ShardConfig::new(0)
+            .with_pinning(CorePinning::default(), NumaTopology::from_core_nodes([(0, 0)]))
+            .with_warm_up(warm_up)
+            .with_clock(|| 1_000)
+            .with_invariant_checker(checker.sender());
+        let build = || {
+            let mut engine = engine();
+            engine.enable_invariant_sampling(
+                InvariantSamplerConfig::default().with_interval(1).with_probes(8, 0),
+            );
+            engine
+        };
+        let (output, results) = mpsc::channel();
+        let shard = spawn_shard(config, build, engine, output).unwrap();
+        shard
+            .submit(Command::LimitOrder {
+                trader: 7,
+                side: Side::Buy,
+                price: 100,
+                quantity: 10,
+                position_side: PositionSide::Long,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            })
+            .unwrap();
+        results.recv().unwrap();
+
+        // 账户服务未冻结保证金，后台线程发现交易者 7 的挂单
+        let violation = violations.recv().unwrap();
+        assert_eq!(violation.kind, InvariantKind::FrozenMargin);
+        assert_eq!(violation.sequence, 1);
+
+        shard.shutdown().unwrap();
+        let stats = checker.shutdown().unwrap();
+        assert_eq!((stats.runs, stats.violations), (1, 1));
+    }
+
+    #[test]
+    fn test_shard_spawns_checker_and_outputs_violations() {
+        use crate::domain::service::projection::ReadModelProjection;
+
+        let warm_up =
+            WarmUpConfig { order_capacity: 16, position_capacity: 4, synthetic_rounds: 2 };
+        let sampler = InvariantSamplerConfig::default().with_interval(1).with_probes(8, 0);
+        // 读侧投影中没有余额：交易者 7 的挂单未冻结保证金
+        let balances = Arc::new(RwLock::new(ReadModelProjection::new()));
+        let config = ShardConfig::new(0)
+            .with_pinning(CorePinning::default(), NumaTopology::from_core_nodes([(0, 0)]))
+            .with_warm_up(warm_up)
+            .with_clock(|| 1_000)
+            .with_invariant_sampling(sampler, balances);
+        let (output, outputs) = mpsc::channel();
+        let shard = spawn_shard(config, engine, engine, output).unwrap();
+        shard
+            .submit(Command::LimitOrder {
+                trader: 7,
+                side: Side::Buy,
+                price: 100,
+                quantity: 10,
+                position_side: PositionSide::Long,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            })
+            .unwrap();
+
+        let violation = loop {
+            if let Some(violation) = outputs.recv().unwrap().violations.pop() {
+                break violation;
+            }
+        };
+        assert_eq!((violation.kind, violation.sequence), (InvariantKind::FrozenMargin, 1));
+
+        let exit = shard.shutdown().unwrap();
+        assert_eq!(exit.invariants.map(|stats| stats.violations), Some(1));
+    }
+}
@@ -1,5 +1,13 @@
 //! Inbound adapters
 
+mod confirmation_feed;
 mod in_memory;
+mod invariant_checker;
+mod projection_feed;
+mod shard_runner;
 
+pub use confirmation_feed::*;
 pub use in_memory::*;
+pub use invariant_checker::*;
+pub use projection_feed::*;
+pub use shard_runner::*;
@@ -0,0 +1,163 @@
+//! 投影消费线程
+//!
+//! 分片线程每轮输出一个 [`ShardOutput`]，本线程把其中的引擎事件按序应用到
+//! 读侧投影（账户查询、定投）与统计投影（排行榜），再把输出原样转交下游（结果回执、成交确认等）。
+//! 标记价格不产生引擎事件，从命令结果中取出写入读侧投影（仓位风险查询）。
+//! 投影在写锁内逐轮更新，查询看到的总是某一轮处理完毕后的状态。
+//! 输出发送端全部关闭后线程退出，返回已应用的最大序列号
+
+use std::io;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+
+use crate::adaptor::inbound::shard_runner::ShardOutput;
+use crate::domain::service::command::CommandResult;
+use crate::domain::service::leaderboard::StatisticsProjection;
+use crate::domain::service::projection::ReadModelProjection;
+
+/// 投影消费配置
+#[derive(Debug, Default)]
+pub struct ProjectionFeed {
+    read_model: Option<Arc<RwLock<ReadModelProjection>>>,
+    statistics: Option<Arc<RwLock<StatisticsProjection>>>,
+    forward: Option<Sender<ShardOutput>>,
+}
+
+impl ProjectionFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入读侧投影
+    pub fn with_read_model(mut self, projection: Arc<RwLock<ReadModelProjection>>) -> Self {
+        self.read_model = Some(projection);
+        self
+    }
+
+    /// 写入统计投影
+    pub fn with_statistics(mut self, projection: Arc<RwLock<StatisticsProjection>>) -> Self {
+        self.statistics = Some(projection);
+        self
+    }
+
+    /// 应用后转交下游（下游关闭后不再转交，继续更新投影）
+    pub fn forward_to(mut self, downstream: Sender<ShardOutput>) -> Self {
+        self.forward = Some(downstream);
+        self
+    }
+
+    /// 应用一轮输出，返回本轮最大序列号
+    pub fn apply(&self, output: &ShardOutput) -> Option<u64> {
+        if let Some(projection) = &self.read_model {
+            let mut projection = projection.write().unwrap_or_else(PoisonError::into_inner);
+            projection.apply_all(&output.events);
+            for result in &output.results {
+                if let CommandResult::UpdateMarkPrice { mark_price, .. } = result {
+                    projection.on_mark_price(*mark_price);
+                }
+            }
+        }
+        if let Some(projection) = &self.statistics {
+            let mut projection = projection.write().unwrap_or_else(PoisonError::into_inner);
+            for envelope in &output.events {
+                projection.apply(envelope);
+            }
+        }
+        output.events.iter().map(|e| e.sequence).max()
+    }
+
+    /// 启动消费线程
+    pub fn spawn(mut self, outputs: Receiver<ShardOutput>) -> io::Result<JoinHandle<u64>> {
+        thread::Builder::new().name("prep-projection".to_string()).spawn(move || {
+            let mut sequence = 0;
+            for output in outputs {
+                sequence = sequence.max(self.apply(&output).unwrap_or(0));
+                if let Some(downstream) = &self.forward {
+                    if downstream.send(output).is_err() {
+                        self.forward = None;
+                    }
+                }
+            }
+            sequence
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::adaptor::inbound::{
+        InMemoryOrderRepository, InMemoryPositionRepository, ShardConfig, spawn_shard,
+    };
+    use crate::adaptor::outbound::numa::{CorePinning, NumaTopology};
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+    use crate::domain::service::command::Command;
+    use crate::domain::service::leaderboard::StatsPeriod;
+    use crate::domain::service::matching::MatchingService;
+    use crate::domain::service::query::PrepQueryHandler;
+    use crate::domain::service::warmup::WarmUpConfig;
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    fn limit(trader: u64, side: Side, quantity: u64) -> Command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price: 100,
+            quantity,
+            position_side: if side == Side::Buy { PositionSide::Long } else { PositionSide::Short },
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_shard_events_reach_projections() {
+        let warm_up =
+            WarmUpConfig { order_capacity: 16, position_capacity: 4, synthetic_rounds: 2 };
+        let config = ShardConfig::new(0)
+            .with_pinning(CorePinning::default(), NumaTopology::from_core_nodes([(0, 0)]))
+            .with_warm_up(warm_up)
+            .with_clock(|| 1_000);
+        let read_model = Arc::new(RwLock::new(ReadModelProjection::new()));
+        let statistics = Arc::new(RwLock::new(StatisticsProjection::new()));
+        let (output, outputs) = mpsc::channel();
+        let (downstream, forwarded) = mpsc::channel();
+        let feed = ProjectionFeed::new()
+            .with_read_model(read_model.clone())
+            .with_statistics(statistics.clone())
+            .forward_to(downstream)
+            .spawn(outputs)
+            .unwrap();
+
+        let shard = spawn_shard(config, engine, engine, output).unwrap();
+        shard.submit(limit(1, Side::Sell, 5)).unwrap();
+        shard.submit(limit(3, Side::Sell, 4)).unwrap();
+        shard.submit(limit(2, Side::Buy, 3)).unwrap();
+        shard.submit(Command::UpdateMarkPrice { mark_price: 110 }).unwrap();
+        let mut results = 0;
+        while results < 4 {
+            results += forwarded.recv().unwrap().results.len();
+        }
+        shard.shutdown().unwrap();
+        assert_eq!(feed.join().unwrap(), 3);
+
+        let read_model = read_model.read().unwrap();
+        assert_eq!(read_model.sequence(), 3);
+        assert_eq!(read_model.positions(2)[0].quantity, 3);
+        // trader 1 剩余 2 排在 trader 3 之前
+        let queue = read_model.queue_position(3, 2).unwrap();
+        assert_eq!((queue.orders_ahead, queue.quantity_ahead), (1, 2));
+        assert_eq!(read_model.mark_price(), Some(110));
+
+        let stats = statistics.read().unwrap().account_stats(2, StatsPeriod::Daily, 1_000);
+        assert_eq!((stats.volume, stats.trade_count), (300, 1));
+    }
+}
@@ -0,0 +1,696 @@
+//! 分片撮合线程
+//!
+//! 每个分片独占一个撮合线程，启动顺序：
+//! 1. 绑定核心绑定配置中的核心（未配置则由操作系统调度）
+//! 2. 在该核心所属 NUMA 节点上构建引擎，订单簿与仓储落在本地内存
+//! 3. 配置了定时委托日志时回放日志，恢复等待激活的定时委托
+//! 4. 预热后翻转就绪标志，网关据此放行流量
+//! 5. 循环：收命令进入双通道队列（配置了速度缓冲时主动委托先延迟，配置了最短挂单
+//!    时间时未满时间的撤单先延迟或拒绝；定时委托的登记与撤销在调度器中处理并落盘）→
+//!    释放到期的定时 / 延迟命令（激活记录先落盘），定投到期时提交
+//!    `RunRecurringPlans`，配置了仓位压缩作业时按周期提交 `CompressPositions` →
+//!    按出队顺序处理 → 输出结果与事件，不变量采样副本转交后台检查线程（配置了不变量
+//!    抽样时由分片启动，违例随分片输出交给下游审计与告警）
+//!
+//! 命令发送端全部关闭后线程退出（未落盘的定时委托须等到全部激活）
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::adaptor::inbound::invariant_checker::{InvariantCheckerHandle, spawn_invariant_checker};
+use crate::adaptor::outbound::journal::JournalConfig;
+use crate::adaptor::outbound::numa::{CorePinning, NumaTopology};
+use crate::adaptor::outbound::schedule_log::ScheduleLog;
+use crate::domain::entity::{EventEnvelope, InvariantViolation, Timestamp};
+use crate::domain::repository::{OrderRepository, PositionRepository};
+use crate::domain::service::command::{Command, CommandResult, ErrorCode};
+use crate::domain::service::command_queue::CommandQueue;
+use crate::domain::service::compression::CompressionJob;
+use crate::domain::service::invariant::{InvariantProbe, InvariantSamplerConfig, SamplerStats};
+use crate::domain::service::matching::MatchingService;
+use crate::domain::service::projection::ReadModelProjection;
+use crate::domain::service::quote_life::QuoteLifeConfig;
+use crate::domain::service::scheduler::OrderScheduler;
+use crate::domain::service::speed_bump::SpeedBumpConfig;
+use crate::domain::service::warmup::{Readiness, WarmUpConfig, WarmUpReport, warm_up};
+
+/// 分片配置
+#[derive(Debug, Clone)]
+pub struct ShardConfig {
+    /// 分片序号
+    pub shard: usize,
+    /// 核心绑定配置
+    pub pinning: CorePinning,
+    /// CPU 拓扑
+    pub topology: NumaTopology,
+    /// 预热配置
+    pub warm_up: WarmUpConfig,
+    /// 每轮最多处理的命令数
+    pub batch: usize,
+    /// 空闲时等待新命令的最长时间，定时与延迟命令按此粒度释放
+    pub tick: Duration,
+    /// 时钟（毫秒）
+    pub clock: fn() -> Timestamp,
+    /// 不变量采样的后台检查线程（未配置时丢弃采样）
+    pub invariants: Option<Sender<InvariantProbe>>,
+    /// 不变量抽样：分片启用引擎采样并自行启动检查线程，余额取自读侧投影
+    pub invariant_sampling: Option<(InvariantSamplerConfig, Arc<RwLock<ReadModelProjection>>)>,
+    /// 子账户仓位压缩作业（按引擎中的分组与最近标记价格生成计划，未配置不运行）
+    pub compression: Option<CompressionJob>,
+    /// 主动委托的速度缓冲（未配置时直接入队）
+    pub speed_bump: Option<SpeedBumpConfig>,
+    /// 最短挂单时间（未配置时撤单直接入队）
+    pub min_quote_life: Option<QuoteLifeConfig>,
+    /// 定时委托日志目录（未配置时定时委托只在内存中等待，重启丢失）
+    pub schedule_log: Option<JournalConfig>,
+}
+
+impl ShardConfig {
+    /// 不绑核、探测本机拓扑的默认配置
+    pub fn new(shard: usize) -> Self {
+        Self {
+            shard,
+            pinning: CorePinning::default(),
+            topology: NumaTopology::detect(),
+            warm_up: WarmUpConfig::default(),
+            batch: 1024,
+            tick: Duration::from_millis(1),
+            clock: unix_millis,
+            invariants: None,
+            invariant_sampling: None,
+            compression: None,
+            speed_bump: None,
+            min_quote_life: None,
+            schedule_log: None,
+        }
+    }
+
+    pub fn with_pinning(mut self, pinning: CorePinning, topology: NumaTopology) -> Self {
+        self.pinning = pinning;
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_warm_up(mut self, warm_up: WarmUpConfig) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    pub fn with_batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: fn() -> Timestamp) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_invariant_checker(mut self, probes: Sender<InvariantProbe>) -> Self {
+        self.invariants = Some(probes);
+        self
+    }
+
+    /// 启用不变量抽样，违例随分片输出的事件流进入审计与告警
+    pub fn with_invariant_sampling(
+        mut self,
+        sampler: InvariantSamplerConfig,
+        balances: Arc<RwLock<ReadModelProjection>>,
+    ) -> Self {
+        self.invariant_sampling = Some((sampler, balances));
+        self
+    }
+
+    pub fn with_compression(mut self, job: CompressionJob) -> Self {
+        self.compression = Some(job);
+        self
+    }
+
+    pub fn with_speed_bump(mut self, config: SpeedBumpConfig) -> Self {
+        self.speed_bump = Some(config);
+        self
+    }
+
+    pub fn with_min_quote_life(mut self, config: QuoteLifeConfig) -> Self {
+        self.min_quote_life = Some(config);
+        self
+    }
+
+    pub fn with_schedule_log(mut self, journal: JournalConfig) -> Self {
+        self.schedule_log = Some(journal);
+        self
+    }
+
+    /// 按配置构建命令队列，配置了定时委托日志时回放日志恢复等待激活的委托
+    fn command_queue(&self) -> io::Result<(CommandQueue, Option<ScheduleLog>)> {
+        let mut queue = CommandQueue::new();
+        if let Some(bump) = self.speed_bump {
+            queue = queue.with_speed_bump(bump);
+        }
+        if let Some(quote_life) = self.min_quote_life {
+            queue = queue.with_min_quote_life(quote_life);
+        }
+        let Some(journal) = &self.schedule_log else {
+            return Ok((queue, None));
+        };
+        let (log, scheduler) = ScheduleLog::open(journal.clone(), OrderScheduler::default())?;
+        Ok((queue.with_scheduler(scheduler), Some(log)))
+    }
+}
+
+/// 一轮处理的输出
+#[derive(Debug, Clone)]
+pub struct ShardOutput {
+    /// 按出队顺序的命令结果
+    pub results: Vec<CommandResult>,
+    /// 本轮产生的引擎事件
+    pub events: Vec<EventEnvelope>,
+    /// 后台检查线程发现的不变量违例（不属于引擎事件，不计入序列号）
+    pub violations: Vec<InvariantViolation>,
+}
+
+/// 分片线程退出信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardExit {
+    /// 实际绑定的核心
+    pub core: Option<usize>,
+    /// 预热结果
+    pub warm_up: WarmUpReport,
+    /// 退出时的引擎序列号
+    pub sequence: u64,
+    /// 不变量检查统计（未启用抽样为 None）
+    pub invariants: Option<SamplerStats>,
+}
+
+/// 运行中的分片
+#[derive(Debug)]
+pub struct ShardHandle {
+    commands: Sender<Command>,
+    readiness: Readiness,
+    thread: JoinHandle<io::Result<ShardExit>>,
+    checker: Option<InvariantCheckerHandle>,
+}
+
+impl ShardHandle {
+    /// 提交命令（线程已退出时原样返回）
+    pub fn submit(&self, command: Command) -> Result<(), Command> {
+        self.commands.send(command).map_err(|e| e.0)
+    }
+
+    /// 命令发送端（供多个网关连接共享）
+    pub fn sender(&self) -> Sender<Command> {
+        self.commands.clone()
+    }
+
+    /// 就绪标志
+    pub fn readiness(&self) -> &Readiness {
+        &self.readiness
+    }
+
+    /// 关闭本句柄的发送端并等待线程退出（其他发送端须先关闭），随后关闭检查线程
+    pub fn shutdown(self) -> io::Result<ShardExit> {
+        drop(self.commands);
+        let mut exit =
+            self.thread.join().map_err(|_| io::Error::other("shard thread panicked"))??;
+        if let Some(checker) = self.checker {
+            exit.invariants = Some(checker.shutdown()?);
+        }
+        Ok(exit)
+    }
+}
+
+/// 启动分片线程
+///
+/// `build` 与 `scratch` 在绑核后的分片线程上调用，引擎状态因此分配在分片所属节点；
+/// `scratch` 构建的临时引擎只用于预热
+pub fn spawn_shard<O, P, SO, SP, B, S>(
+    mut config: ShardConfig,
+    build: B,
+    scratch: S,
+    output: Sender<ShardOutput>,
+) -> io::Result<ShardHandle>
+where
+    O: OrderRepository + 'static,
+    P: PositionRepository + 'static,
+    SO: OrderRepository + 'static,
+    SP: PositionRepository + 'static,
+    B: FnOnce() -> MatchingService<O, P> + Send + 'static,
+    S: FnOnce() -> MatchingService<SO, SP> + Send + 'static,
+{
+    let checker = match &config.invariant_sampling {
+        Some((sampler, balances)) => {
+            let checker = spawn_invariant_checker(
+                sampler.margin_asset.clone(),
+                balances.clone(),
+                output.clone(),
+            )?;
+            config.invariants = Some(checker.sender());
+            Some(checker)
+        }
+        None => None,
+    };
+    let (commands, inbox) = mpsc::channel();
+    let readiness = Readiness::new();
+    let ready = readiness.clone();
+    let thread =
+        thread::Builder::new().name(format!("prep-shard-{}", config.shard)).spawn(move || {
+            let core = config.pinning.pin_current(config.shard)?;
+            let (mut engine, scratch) =
+                config
+                    .topology
+                    .init_on_shard_node(&config.pinning, config.shard, || (build(), scratch()));
+            let (queue, schedule_log) = config.command_queue()?;
+            let report = warm_up(&mut engine, scratch, config.warm_up, &ready);
+            if let Some((sampler, _)) = &config.invariant_sampling {
+                engine.enable_invariant_sampling(sampler.clone());
+            }
+            run(&config, &mut engine, queue, schedule_log, &inbox, &output);
+            Ok(ShardExit { core, warm_up: report, sequence: engine.sequence(), invariants: None })
+        })?;
+    Ok(ShardHandle { commands, readiness, thread, checker })
+}
+
+/// 撮合循环，命令发送端全部关闭且队列处理完毕、或输出端关闭时返回
+fn run<O, P>(
+    config: &ShardConfig,
+    engine: &mut MatchingService<O, P>,
+    mut queue: CommandQueue,
+    mut schedule_log: Option<ScheduleLog>,
+    inbox: &Receiver<Command>,
+    output: &Sender<ShardOutput>,
+) where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    let mut connected = true;
+    // 已提交执行命令的定投期次时间，处理前不重复提交
+    let mut recurring_submitted = None;
+    let mut compression = config.compression.clone();
+    loop {
+        let now = (config.clock)();
+        let mut results = Vec::new();
+        // 新命令与到期命令先入队，再统一按通道顺序出队
+        while let Ok(command) = inbox.try_recv() {
+            accept(&mut queue, &mut schedule_log, engine, command, now, &mut results);
+        }
+        match schedule_log.as_mut() {
+            Some(log) => {
+                if let Err(e) = log.release(&mut queue, now) {
+                    results.push(schedule_log_error(e));
+                }
+            }
+            None => {
+                queue.release_scheduled(now);
+            }
+        }
+        queue.release_delayed(now);
+        let recurring_due = engine.recurring().next_due().filter(|at| *at <= now);
+        if recurring_due.is_some() && recurring_due != recurring_submitted {
+            recurring_submitted = recurring_due;
+            enqueue(&mut queue, engine, Command::RunRecurringPlans, now, &mut results);
+        }
+        // 未收到标记价格前不运行压缩作业
+        if let (Some(job), Some(mark_price)) = (compression.as_mut(), engine.mark_price()) {
+            if let Some(command) =
+                job.poll(now, engine.sub_accounts(), engine.positions(), mark_price)
+            {
+                enqueue(&mut queue, engine, command, now, &mut results);
+            }
+        }
+        engine.set_timestamp(now);
+        results.extend(queue.drain_at(engine, config.batch, now));
+
+        if !results.is_empty() {
+            let events = engine.drain_events();
+            if output.send(ShardOutput { results, events, violations: Vec::new() }).is_err() {
+                return;
+            }
+            let probes = engine.drain_invariant_probes();
+            if let Some(checker) = &config.invariants {
+                for probe in probes {
+                    let _ = checker.send(probe);
+                }
+            }
+        }
+        if !queue.is_empty() {
+            continue;
+        }
+        if !connected {
+            // 落盘的定时委托在重启后恢复，不必等待激活
+            let scheduled = if schedule_log.is_some() { 0 } else { queue.scheduled_len() };
+            if queue.delayed_len() == 0 && scheduled == 0 {
+                return;
+            }
+            thread::sleep(config.tick);
+            continue;
+        }
+        match inbox.recv_timeout(config.tick) {
+            Ok(command) => {
+                let now = (config.clock)();
+                let mut rejected = Vec::new();
+                accept(&mut queue, &mut schedule_log, engine, command, now, &mut rejected);
+                if !rejected.is_empty()
+                    && output
+                        .send(ShardOutput {
+                            results: rejected,
+                            events: Vec::new(),
+                            violations: Vec::new(),
+                        })
+                        .is_err()
+                {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => connected = false,
+        }
+    }
+}
+
+/// 接收网关命令：定时委托的登记与撤销在队列中处理并直接记录结果，其他命令入队
+fn accept<O, P>(
+    queue: &mut CommandQueue,
+    schedule_log: &mut Option<ScheduleLog>,
+    engine: &MatchingService<O, P>,
+    command: Command,
+    now: Timestamp,
+    results: &mut Vec<CommandResult>,
+) where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    match command {
+        Command::ScheduleOrder { order, activate_at } => {
+            if !matches!(*order, Command::LimitOrder { .. } | Command::MarketOrder { .. }) {
+                results.push(CommandResult::Error {
+                    code: ErrorCode::InvalidScheduledOrder,
+                    message: "only limit and market orders can be scheduled".into(),
+                });
+                return;
+            }
+            let scheduled = match schedule_log.as_mut() {
+                Some(log) => log.schedule(queue, *order, activate_at),
+                None => Ok(queue.schedule_at(*order, activate_at)),
+            };
+            results.push(match scheduled {
+                Ok(schedule_id) => CommandResult::ScheduleOrder { schedule_id, activate_at },
+                Err(e) => schedule_log_error(e),
+            });
+        }
+        Command::CancelScheduledOrder { trader, schedule_id } => {
+            let owned = queue.scheduled(schedule_id).is_some_and(|order| {
+                matches!(
+                    order.command,
+                    Command::LimitOrder { trader: owner, .. }
+                        | Command::MarketOrder { trader: owner, .. } if owner == trader
+                )
+            });
+            let cancelled = match (owned, schedule_log.as_mut()) {
+                (false, _) => Ok(None),
+                (true, Some(log)) => log.cancel(queue, schedule_id),
+                (true, None) => Ok(queue.cancel_scheduled(schedule_id)),
+            };
+            results.push(match cancelled {
+                Ok(order) => {
+                    CommandResult::CancelScheduledOrder { schedule_id, success: order.is_some() }
+                }
+                Err(e) => schedule_log_error(e),
+            });
+        }
+        command => enqueue(queue, engine, command, now, results),
+    }
+}
+
+fn schedule_log_error(e: io::Error) -> CommandResult {
+    CommandResult::Error {
+        code: ErrorCode::SystemError,
+        message: format!("schedule log write failed: {}", e),
+    }
+}
+
+/// 命令入队，入队前被拒绝的命令直接记为错误结果
+fn enqueue<O, P>(
+    queue: &mut CommandQueue,
+    engine: &MatchingService<O, P>,
+    command: Command,
+    now: Timestamp,
+    results: &mut Vec<CommandResult>,
+) where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    let aggressive = engine.is_aggressive(&command);
+    if let Err(code) = queue.push_at(command, now, aggressive) {
+        results.push(CommandResult::Error { code, message: "rejected before matching".into() });
+    }
+}
+
+/// 系统时钟（Unix 毫秒）
+pub fn unix_millis() -> Timestamp {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as Timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::domain::entity::{EngineEvent, PositionSide, Side, TimeInForce};
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    fn limit(trader: u64, side: Side, position_side: PositionSide) -> Command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price: 100,
+            quantity: 2,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    fn config() -> ShardConfig {
+        let warm_up =
+            WarmUpConfig { order_capacity: 16, position_capacity: 4, synthetic_rounds: 2 };
+        ShardConfig::new(0)
+            .with_pinning(CorePinning::default(), NumaTopology::from_core_nodes([(0, 0)]))
+            .with_warm_up(warm_up)
+            .with_clock(|| 1_000)
+    }
+
+    #[test]
+    fn test_shard_matches_submitted_commands_in_order() {
+        let (output, results) = mpsc::channel();
+        let handle = spawn_shard(config(), engine, engine, output).unwrap();
+
+        handle.submit(limit(1, Side::Sell, PositionSide::Short)).unwrap();
+        handle.submit(limit(2, Side::Buy, PositionSide::Long)).unwrap();
+
+        let mut outputs = Vec::new();
+        while outputs.iter().map(|o: &ShardOutput| o.results.len()).sum::<usize>() < 2 {
+            outputs.push(results.recv().unwrap());
+        }
+        let results: Vec<_> = outputs.iter().flat_map(|o| &o.results).collect();
+        assert!(
+            matches!(results[0], CommandResult::LimitOrder { trades, .. } if trades.is_empty())
+        );
+        assert!(
+            matches!(results[1], CommandResult::LimitOrder { trades, .. } if trades.len() == 1)
+        );
+        assert!(
+            outputs
+                .iter()
+                .flat_map(|o| &o.events)
+                .any(|e| matches!(e.event, EngineEvent::Trade(_)))
+        );
+
+        assert!(handle.readiness().is_ready());
+        let exit = handle.shutdown().unwrap();
+        assert_eq!(exit.core, None);
+        assert_eq!(exit.sequence, 2);
+        assert_eq!(exit.warm_up.synthetic_trades, 2);
+    }
+
+    #[test]
+    fn test_shard_delays_aggressive_orders_behind_speed_bump() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NOW: AtomicU64 = AtomicU64::new(1_000);
+        let (output, results) = mpsc::channel();
+        let config = config()
+            .with_clock(|| NOW.load(Ordering::SeqCst))
+            .with_speed_bump(SpeedBumpConfig::fixed(3));
+        let handle = spawn_shard(config, engine, engine, output).unwrap();
+
+        // 挂单不吃单，直接入队
+        handle.submit(limit(1, Side::Sell, PositionSide::Short)).unwrap();
+        assert!(matches!(results.recv().unwrap().results[0], CommandResult::LimitOrder { .. }));
+        handle.submit(limit(2, Side::Buy, PositionSide::Long)).unwrap();
+        assert!(results.recv_timeout(Duration::from_millis(50)).is_err());
+
+        NOW.store(1_003, Ordering::SeqCst);
+        let output = results.recv().unwrap();
+        assert!(matches!(
+            &output.results[0],
+            CommandResult::LimitOrder { trades, .. } if trades.len() == 1
+        ));
+        assert_eq!(handle.shutdown().unwrap().sequence, 2);
+    }
+
+    #[test]
+    fn test_shard_rejects_cancels_inside_min_quote_life() {
+        let (output, results) = mpsc::channel();
+        let config = config().with_min_quote_life(QuoteLifeConfig::reject(5));
+        let handle = spawn_shard(config, engine, engine, output).unwrap();
+
+        handle.submit(limit(1, Side::Sell, PositionSide::Short)).unwrap();
+        let CommandResult::LimitOrder { order_id, .. } = results.recv().unwrap().results[0] else {
+            panic!("expected LimitOrder result");
+        };
+        // 时钟固定，挂单未满 5 毫秒
+        handle.submit(Command::CancelOrder { order_id }).unwrap();
+        assert!(matches!(results.recv().unwrap().results[0], CommandResult::Error { .. }));
+        assert_eq!(handle.shutdown().unwrap().sequence, 1);
+    }
+
+    #[test]
+    fn test_shard_restores_scheduled_orders_from_log() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        use crate::adaptor::outbound::journal::Durability;
+
+        static NOW: AtomicU64 = AtomicU64::new(1_000);
+        let dir = std::env::temp_dir().join(format!("prep-shard-schedule-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = config()
+            .with_clock(|| NOW.load(Ordering::SeqCst))
+            .with_schedule_log(JournalConfig::new(&dir, Durability::Batch));
+        let schedule = |trader| Command::ScheduleOrder {
+            order: Box::new(limit(trader, Side::Sell, PositionSide::Short)),
+            activate_at: 5_000,
+        };
+
+        let (output, results) = mpsc::channel();
+        let handle = spawn_shard(config.clone(), engine, engine, output).unwrap();
+        handle.submit(schedule(1)).unwrap();
+        handle.submit(schedule(2)).unwrap();
+        handle.submit(Command::CancelScheduledOrder { trader: 1, schedule_id: 2 }).unwrap();
+        handle.submit(Command::CancelScheduledOrder { trader: 2, schedule_id: 2 }).unwrap();
+        let mut outputs = Vec::new();
+        while outputs.iter().map(|o: &ShardOutput| o.results.len()).sum::<usize>() < 4 {
+            outputs.push(results.recv().unwrap());
+        }
+        let results: Vec<_> = outputs.iter().flat_map(|o| &o.results).collect();
+        assert!(matches!(
+            results[0],
+            CommandResult::ScheduleOrder { schedule_id: 1, activate_at: 5_000 }
+        ));
+        // 只能撤销自己的定时委托
+        assert!(matches!(
+            results[2],
+            CommandResult::CancelScheduledOrder { schedule_id: 2, success: false }
+        ));
+        assert!(matches!(
+            results[3],
+            CommandResult::CancelScheduledOrder { schedule_id: 2, success: true }
+        ));
+        // 等待激活的委托已落盘，关闭时不必等待
+        assert_eq!(handle.shutdown().unwrap().sequence, 0);
+
+        NOW.store(5_000, Ordering::SeqCst);
+        let (output, results) = mpsc::channel();
+        let handle = spawn_shard(config, engine, engine, output).unwrap();
+        assert!(matches!(results.recv().unwrap().results[0], CommandResult::LimitOrder { .. }));
+        assert_eq!(handle.shutdown().unwrap().sequence, 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_shard_runs_due_recurring_plans() {
+        use crate::domain::entity::{RecurringOrderKind, RecurringSpec};
+
+        let (output, results) = mpsc::channel();
+        let handle = spawn_shard(config(), engine, engine, output).unwrap();
+        let spec = RecurringSpec {
+            trader: 1,
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            quantity: 1,
+            kind: RecurringOrderKind::Limit { price: 100, time_in_force: TimeInForce::GTC },
+            interval: 60_000,
+            start_at: 1_000,
+            max_runs: None,
+        };
+        handle.submit(Command::CreateRecurringPlan { spec }).unwrap();
+
+        // 时钟固定在首期时间：登记后立即执行一期，下一期未到期不再提交
+        let mut outputs = Vec::new();
+        while outputs.iter().map(|o: &ShardOutput| o.results.len()).sum::<usize>() < 2 {
+            outputs.push(results.recv().unwrap());
+        }
+        let results: Vec<_> = outputs.iter().flat_map(|o| &o.results).collect();
+        assert!(matches!(
+            results[0],
+            CommandResult::CreateRecurringPlan { plan_id: 1, next_run: 1_000 }
+        ));
+        assert!(matches!(
+            results[1],
+            CommandResult::RunRecurringPlans { executions, .. }
+                if executions.len() == 1 && executions[0].order_id.is_some()
+        ));
+
+        let exit = handle.shutdown().unwrap();
+        assert_eq!(exit.sequence, 2);
+    }
+
+    #[test]
+    fn test_shard_runs_compression_job() {
+        use crate::domain::service::CompressionJob;
+
+        let (output, results) = mpsc::channel();
+        let config = config().with_compression(CompressionJob::new(60_000, 0, "ops"));
+        let handle = spawn_shard(config, engine, engine, output).unwrap();
+        for sub_account in [1, 2] {
+            handle.submit(Command::LinkSubAccount { sub_account, parent: Some(100) }).unwrap();
+            handle.submit(Command::SetCompressionConsent { sub_account, consent: true }).unwrap();
+        }
+        handle.submit(limit(1, Side::Sell, PositionSide::Short)).unwrap();
+        handle.submit(limit(2, Side::Buy, PositionSide::Long)).unwrap();
+        // 收到标记价格后作业按该价格对冲两个子账户的仓位
+        handle.submit(Command::UpdateMarkPrice { mark_price: 110 }).unwrap();
+
+        let settlements = loop {
+            let output = results.recv().unwrap();
+            let compressed = output.results.into_iter().find_map(|result| match result {
+                CommandResult::CompressPositions { settlements } => Some(settlements),
+                _ => None,
+            });
+            if let Some(settlements) = compressed {
+                break settlements;
+            }
+        };
+        assert_eq!(settlements.len(), 1);
+        assert_eq!(settlements[0].price, 110);
+        assert_eq!(
+            (settlements[0].long_realized_pnl, settlements[0].short_realized_pnl),
+            (20, -20)
+        );
+
+        let exit = handle.shutdown().unwrap();
+        assert_eq!(exit.sequence, 8);
+    }
+}
@@ -0,0 +1,937 @@
+//! 命令编解码
+//!
+//! 命令日志与定时委托日志共用的命令二进制格式（整数均为小端）：`类型 u8 | 字段`，
+//! 字段按命令定义的顺序依次写出：
+//! - 整数按宽度写出（ID、价格、数量 u64，杠杆、比例 u32，有符号金额 i64）
+//! - 枚举 u8，布尔 u8
+//! - 可选值 `有无 u8 | 值`，列表 `个数 u64 | 元素...`，字符串 `长度 u64 | UTF-8`
+//! - 有效期：`种类 u8`，GTD 附加 `到期时间 u64`
+//!
+//! 每种命令都有编码，引擎处理的任何命令都能写入命令日志并回放
+//!
+//! 命令日志每条记录为 `时间戳 u64 | 命令`，记录序列号即引擎命令序列号
+
+use std::io;
+
+use crate::domain::entity::{
+    AccountStatus, MarginMode, MmpConfig, PositionMode, PositionSide, PostTradeLimits, QuoteEntry,
+    RecurringOrderKind, RecurringSpec, RiskProfile, RiskProfileKind, Side, TimeInForce, Timestamp,
+};
+use crate::domain::service::{Command, CompressionLeg, Feature, FlagRule};
+
+const COMMAND_LIMIT: u8 = 1;
+const COMMAND_MARKET: u8 = 2;
+const COMMAND_CANCEL: u8 = 3;
+const COMMAND_FEATURE_FLAG: u8 = 4;
+const COMMAND_SET_LEVERAGE: u8 = 5;
+const COMMAND_ADJUST_MARGIN: u8 = 6;
+const COMMAND_LIQUIDATE: u8 = 7;
+const COMMAND_BUST_TRADE: u8 = 8;
+const COMMAND_SET_RISK_PROFILE: u8 = 9;
+const COMMAND_RESET_KILL_SWITCH: u8 = 10;
+const COMMAND_SET_STOP_LOSS: u8 = 11;
+const COMMAND_SET_TAKE_PROFIT: u8 = 12;
+const COMMAND_SWITCH_MARGIN_MODE: u8 = 13;
+const COMMAND_SWITCH_POSITION_MODE: u8 = 14;
+const COMMAND_SETTLE_FUNDING_RATE: u8 = 15;
+const COMMAND_ADL: u8 = 16;
+const COMMAND_TRAILING_STOP: u8 = 17;
+const COMMAND_FLASH_CLOSE: u8 = 18;
+const COMMAND_REVERSE_POSITION: u8 = 19;
+const COMMAND_BATCH_CANCEL: u8 = 20;
+const COMMAND_CANCEL_ALL: u8 = 21;
+const COMMAND_COMPRESS_POSITIONS: u8 = 22;
+const COMMAND_DELIVER_FUTURES: u8 = 23;
+const COMMAND_UPDATE_MARK_PRICE: u8 = 24;
+const COMMAND_RESUME_TRADING: u8 = 25;
+const COMMAND_MASS_QUOTE: u8 = 26;
+const COMMAND_SET_MMP: u8 = 27;
+const COMMAND_RESET_MMP: u8 = 28;
+const COMMAND_SET_EXPIRY: u8 = 29;
+const COMMAND_SET_COLLATERAL: u8 = 30;
+const COMMAND_SET_ACCOUNT_STATUS: u8 = 31;
+const COMMAND_CREATE_RECURRING: u8 = 32;
+const COMMAND_PAUSE_RECURRING: u8 = 33;
+const COMMAND_RESUME_RECURRING: u8 = 34;
+const COMMAND_CANCEL_RECURRING: u8 = 35;
+const COMMAND_RUN_RECURRING: u8 = 36;
+const COMMAND_LINK_SUB_ACCOUNT: u8 = 37;
+const COMMAND_COMPRESSION_CONSENT: u8 = 38;
+const COMMAND_SCHEDULE_ORDER: u8 = 39;
+const COMMAND_CANCEL_SCHEDULED: u8 = 40;
+
+/// 命令日志记录
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    /// 命令进入引擎时的时间戳
+    pub timestamp: Timestamp,
+    pub command: Command,
+}
+
+impl CommandRecord {
+    /// 编码
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(48);
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        encode_command(&self.command, &mut out);
+        out
+    }
+
+    /// 解码
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader(bytes);
+        let timestamp = reader.u64()?;
+        let command = reader.command()?;
+        Ok(Self { timestamp, command })
+    }
+}
+
+/// 编码命令
+pub(crate) fn encode_command(command: &Command, out: &mut Vec<u8>) {
+    let mut w = Writer(out);
+    match command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only,
+            time_in_force,
+        } => {
+            w.u8(COMMAND_LIMIT);
+            w.u64(*trader);
+            w.u8(encode_side(*side));
+            w.u64(*price);
+            w.u64(*quantity);
+            w.u8(encode_position_side(*position_side));
+            w.u8(*reduce_only as u8);
+            w.time_in_force(*time_in_force);
+        }
+        Command::MarketOrder { trader, side, quantity, position_side, reduce_only } => {
+            w.u8(COMMAND_MARKET);
+            w.u64(*trader);
+            w.u8(encode_side(*side));
+            w.u64(*quantity);
+            w.u8(encode_position_side(*position_side));
+            w.u8(*reduce_only as u8);
+        }
+        Command::CancelOrder { order_id } => {
+            w.u8(COMMAND_CANCEL);
+            w.u64(*order_id);
+        }
+        Command::SetLeverage { trader, leverage, position_side } => {
+            w.u8(COMMAND_SET_LEVERAGE);
+            w.u64(*trader);
+            w.u32(*leverage);
+            w.option(position_side.as_ref(), |w, side| w.u8(encode_position_side(*side)));
+        }
+        Command::AdjustMargin { trader, position_id, amount } => {
+            w.u8(COMMAND_ADJUST_MARGIN);
+            w.u64(*trader);
+            w.u64(*position_id);
+            w.i64(*amount);
+        }
+        Command::Liquidate { position_id, mark_price, bankruptcy_price } => {
+            w.u8(COMMAND_LIQUIDATE);
+            w.u64(*position_id);
+            w.u64(*mark_price);
+            w.u64(*bankruptcy_price);
+        }
+        Command::BustTrade { trade_id, reason, operator } => {
+            w.u8(COMMAND_BUST_TRADE);
+            w.u64(*trade_id);
+            w.str(reason);
+            w.str(operator);
+        }
+        Command::SetRiskProfile { trader, profile, operator } => {
+            w.u8(COMMAND_SET_RISK_PROFILE);
+            w.u64(*trader);
+            w.u8(match profile.kind {
+                RiskProfileKind::Standard => 0,
+                RiskProfileKind::MarketMaker => 1,
+            });
+            w.u64(profile.max_order_notional);
+            w.u64(profile.max_open_orders as u64);
+            w.u8(profile.bypass_notional_check as u8);
+            w.option(profile.post_trade.as_ref(), |w, limits| {
+                w.u64(limits.max_position_quantity);
+                w.u64(limits.max_realized_loss);
+            });
+            w.str(operator);
+        }
+        Command::ResetKillSwitch { trader, operator } => {
+            w.u8(COMMAND_RESET_KILL_SWITCH);
+            w.u64(*trader);
+            w.str(operator);
+        }
+        Command::SetAccountStatus { trader, status, reason, operator } => {
+            w.u8(COMMAND_SET_ACCOUNT_STATUS);
+            w.u64(*trader);
+            w.u8(status.code());
+            w.str(reason);
+            w.str(operator);
+        }
+        Command::SetStopLoss { trader, position_id, trigger_price, close_price } => {
+            w.u8(COMMAND_SET_STOP_LOSS);
+            w.u64(*trader);
+            w.u64(*position_id);
+            w.u64(*trigger_price);
+            w.option(close_price.as_ref(), |w, price| w.u64(*price));
+        }
+        Command::SetTakeProfit { trader, position_id, trigger_price, close_price } => {
+            w.u8(COMMAND_SET_TAKE_PROFIT);
+            w.u64(*trader);
+            w.u64(*position_id);
+            w.u64(*trigger_price);
+            w.option(close_price.as_ref(), |w, price| w.u64(*price));
+        }
+        Command::SwitchMarginMode { trader, mode } => {
+            w.u8(COMMAND_SWITCH_MARGIN_MODE);
+            w.u64(*trader);
+            w.u8(match mode {
+                MarginMode::Cross => 0,
+                MarginMode::Isolated => 1,
+            });
+        }
+        Command::SwitchPositionMode { trader, mode } => {
+            w.u8(COMMAND_SWITCH_POSITION_MODE);
+            w.u64(*trader);
+            w.u8(match mode {
+                PositionMode::OneWay => 0,
+                PositionMode::Hedge => 1,
+            });
+        }
+        Command::SettleFundingRate { position_id, funding_rate, mark_price } => {
+            w.u8(COMMAND_SETTLE_FUNDING_RATE);
+            w.u64(*position_id);
+            w.i64(*funding_rate);
+            w.u64(*mark_price);
+        }
+        Command::ADL { position_id, counterparty_position_id, quantity, price } => {
+            w.u8(COMMAND_ADL);
+            w.u64(*position_id);
+            w.u64(*counterparty_position_id);
+            w.u64(*quantity);
+            w.u64(*price);
+        }
+        Command::TrailingStop { trader, position_id, callback_rate, activation_price } => {
+            w.u8(COMMAND_TRAILING_STOP);
+            w.u64(*trader);
+            w.u64(*position_id);
+            w.u32(*callback_rate);
+            w.option(activation_price.as_ref(), |w, price| w.u64(*price));
+        }
+        Command::FlashClose { trader, position_id } => {
+            w.u8(COMMAND_FLASH_CLOSE);
+            w.u64(*trader);
+            w.u64(*position_id);
+        }
+        Command::ReversePosition { trader, position_id, new_quantity, price } => {
+            w.u8(COMMAND_REVERSE_POSITION);
+            w.u64(*trader);
+            w.u64(*position_id);
+            w.u64(*new_quantity);
+            w.option(price.as_ref(), |w, price| w.u64(*price));
+        }
+        Command::BatchCancelOrders { trader, order_ids } => {
+            w.u8(COMMAND_BATCH_CANCEL);
+            w.u64(*trader);
+            w.list(order_ids, |w, order_id| w.u64(*order_id));
+        }
+        Command::CancelAllOrders { trader, position_side } => {
+            w.u8(COMMAND_CANCEL_ALL);
+            w.u64(*trader);
+            w.option(position_side.as_ref(), |w, side| w.u8(encode_position_side(*side)));
+        }
+        Command::CompressPositions { legs, price, operator } => {
+            w.u8(COMMAND_COMPRESS_POSITIONS);
+            w.list(legs, |w, leg| {
+                w.u64(leg.long_trader);
+                w.u64(leg.long_position_id);
+                w.u64(leg.short_trader);
+                w.u64(leg.short_position_id);
+                w.u64(leg.quantity);
+            });
+            w.u64(*price);
+            w.str(operator);
+        }
+        Command::LinkSubAccount { sub_account, parent } => {
+            w.u8(COMMAND_LINK_SUB_ACCOUNT);
+            w.u64(*sub_account);
+            w.option(parent.as_ref(), |w, parent| w.u64(*parent));
+        }
+        Command::SetCompressionConsent { sub_account, consent } => {
+            w.u8(COMMAND_COMPRESSION_CONSENT);
+            w.u64(*sub_account);
+            w.u8(*consent as u8);
+        }
+        Command::DeliverFutures { delivery_price, operator } => {
+            w.u8(COMMAND_DELIVER_FUTURES);
+            w.u64(*delivery_price);
+            w.str(operator);
+        }
+        Command::SetExpiry { expiry, operator } => {
+            w.u8(COMMAND_SET_EXPIRY);
+            w.option(expiry.as_ref(), |w, expiry| w.u64(*expiry));
+            w.str(operator);
+        }
+        Command::SetCollateral { trader, collateral } => {
+            w.u8(COMMAND_SET_COLLATERAL);
+            w.u64(*trader);
+            w.u64(*collateral);
+        }
+        Command::UpdateMarkPrice { mark_price } => {
+            w.u8(COMMAND_UPDATE_MARK_PRICE);
+            w.u64(*mark_price);
+        }
+        Command::ResumeTrading { operator } => {
+            w.u8(COMMAND_RESUME_TRADING);
+            w.str(operator);
+        }
+        Command::MassQuote { trader, quotes } => {
+            w.u8(COMMAND_MASS_QUOTE);
+            w.u64(*trader);
+            w.list(quotes, |w, quote| {
+                w.u64(quote.bid_price);
+                w.u64(quote.bid_quantity);
+                w.u64(quote.ask_price);
+                w.u64(quote.ask_quantity);
+            });
+        }
+        Command::SetMmp { trader, config } => {
+            w.u8(COMMAND_SET_MMP);
+            w.u64(*trader);
+            w.option(config.as_ref(), |w, config| {
+                w.u64(config.window);
+                w.u32(config.fill_limit);
+                w.option(config.freeze.as_ref(), |w, freeze| w.u64(*freeze));
+            });
+        }
+        Command::ResetMmp { trader } => {
+            w.u8(COMMAND_RESET_MMP);
+            w.u64(*trader);
+        }
+        Command::SetFeatureFlag { feature, rule, operator } => {
+            w.u8(COMMAND_FEATURE_FLAG);
+            w.u8(feature.code());
+            w.u8(rule.enabled as u8);
+            w.u8(rule.percent);
+            w.list(&rule.accounts, |w, account| w.u64(*account));
+            w.str(operator);
+        }
+        Command::CreateRecurringPlan { spec } => {
+            w.u8(COMMAND_CREATE_RECURRING);
+            w.u64(spec.trader);
+            w.str(&spec.symbol);
+            w.u8(encode_side(spec.side));
+            w.u64(spec.quantity);
+            match spec.kind {
+                RecurringOrderKind::Market => w.u8(0),
+                RecurringOrderKind::Limit { price, time_in_force } => {
+                    w.u8(1);
+                    w.u64(price);
+                    w.time_in_force(time_in_force);
+                }
+            }
+            w.u64(spec.interval);
+            w.u64(spec.start_at);
+            w.option(spec.max_runs.as_ref(), |w, max| w.u32(*max));
+        }
+        Command::PauseRecurringPlan { trader, plan_id } => {
+            w.u8(COMMAND_PAUSE_RECURRING);
+            w.u64(*trader);
+            w.u64(*plan_id);
+        }
+        Command::ResumeRecurringPlan { trader, plan_id } => {
+            w.u8(COMMAND_RESUME_RECURRING);
+            w.u64(*trader);
+            w.u64(*plan_id);
+        }
+        Command::CancelRecurringPlan { trader, plan_id } => {
+            w.u8(COMMAND_CANCEL_RECURRING);
+            w.u64(*trader);
+            w.u64(*plan_id);
+        }
+        Command::RunRecurringPlans => w.u8(COMMAND_RUN_RECURRING),
+        Command::ScheduleOrder { order, activate_at } => {
+            w.u8(COMMAND_SCHEDULE_ORDER);
+            w.u64(*activate_at);
+            encode_command(order, w.0);
+        }
+        Command::CancelScheduledOrder { trader, schedule_id } => {
+            w.u8(COMMAND_CANCEL_SCHEDULED);
+            w.u64(*trader);
+            w.u64(*schedule_id);
+        }
+    }
+}
+
+/// 小端字段写入器
+struct Writer<'a>(&'a mut Vec<u8>);
+
+impl Writer<'_> {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn option<T>(&mut self, value: Option<&T>, write: impl FnOnce(&mut Self, &T)) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                write(self, value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn list<T>(&mut self, values: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        self.u64(values.len() as u64);
+        for value in values {
+            write(self, value);
+        }
+    }
+
+    fn time_in_force(&mut self, time_in_force: TimeInForce) {
+        match time_in_force {
+            TimeInForce::GTC => self.u8(0),
+            TimeInForce::IOC => self.u8(1),
+            TimeInForce::FOK => self.u8(2),
+            TimeInForce::GTD { expire_time } => {
+                self.u8(3);
+                self.u64(expire_time);
+            }
+            TimeInForce::PostOnly => self.u8(4),
+        }
+    }
+}
+
+fn encode_side(side: Side) -> u8 {
+    match side {
+        Side::Buy => 0,
+        Side::Sell => 1,
+    }
+}
+
+fn encode_position_side(position_side: PositionSide) -> u8 {
+    match position_side {
+        PositionSide::Both => 0,
+        PositionSide::Long => 1,
+        PositionSide::Short => 2,
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 小端字段读取器
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid("truncated record"));
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> io::Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.u64()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("string is not utf-8"))
+    }
+
+    fn option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> io::Result<T>,
+    ) -> io::Result<Option<T>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            _ => Err(invalid("invalid option tag")),
+        }
+    }
+
+    /// 列表（个数超过剩余字节数的记录视为损坏，不预分配）
+    fn list<T>(&mut self, mut read: impl FnMut(&mut Self) -> io::Result<T>) -> io::Result<Vec<T>> {
+        let len = self.u64()?;
+        if len > self.0.len() as u64 {
+            return Err(invalid("truncated record"));
+        }
+        (0..len).map(|_| read(self)).collect()
+    }
+
+    fn side(&mut self) -> io::Result<Side> {
+        match self.u8()? {
+            0 => Ok(Side::Buy),
+            1 => Ok(Side::Sell),
+            _ => Err(invalid("invalid side")),
+        }
+    }
+
+    fn position_side(&mut self) -> io::Result<PositionSide> {
+        match self.u8()? {
+            0 => Ok(PositionSide::Both),
+            1 => Ok(PositionSide::Long),
+            2 => Ok(PositionSide::Short),
+            _ => Err(invalid("invalid position side")),
+        }
+    }
+
+    fn time_in_force(&mut self) -> io::Result<TimeInForce> {
+        match self.u8()? {
+            0 => Ok(TimeInForce::GTC),
+            1 => Ok(TimeInForce::IOC),
+            2 => Ok(TimeInForce::FOK),
+            3 => Ok(TimeInForce::GTD { expire_time: self.u64()? }),
+            4 => Ok(TimeInForce::PostOnly),
+            _ => Err(invalid("invalid time in force")),
+        }
+    }
+
+    pub(crate) fn command(&mut self) -> io::Result<Command> {
+        match self.u8()? {
+            COMMAND_LIMIT => Ok(Command::LimitOrder {
+                trader: self.u64()?,
+                side: self.side()?,
+                price: self.u64()?,
+                quantity: self.u64()?,
+                position_side: self.position_side()?,
+                reduce_only: self.bool()?,
+                time_in_force: self.time_in_force()?,
+            }),
+            COMMAND_MARKET => Ok(Command::MarketOrder {
+                trader: self.u64()?,
+                side: self.side()?,
+                quantity: self.u64()?,
+                position_side: self.position_side()?,
+                reduce_only: self.bool()?,
+            }),
+            COMMAND_CANCEL => Ok(Command::CancelOrder { order_id: self.u64()? }),
+            COMMAND_SET_LEVERAGE => Ok(Command::SetLeverage {
+                trader: self.u64()?,
+                leverage: self.u32()?,
+                position_side: self.option(Self::position_side)?,
+            }),
+            COMMAND_ADJUST_MARGIN => Ok(Command::AdjustMargin {
+                trader: self.u64()?,
+                position_id: self.u64()?,
+                amount: self.i64()?,
+            }),
+            COMMAND_LIQUIDATE => Ok(Command::Liquidate {
+                position_id: self.u64()?,
+                mark_price: self.u64()?,
+                bankruptcy_price: self.u64()?,
+            }),
+            COMMAND_BUST_TRADE => Ok(Command::BustTrade {
+                trade_id: self.u64()?,
+                reason: self.string()?,
+                operator: self.string()?,
+            }),
+            COMMAND_SET_RISK_PROFILE => Ok(Command::SetRiskProfile {
+                trader: self.u64()?,
+                profile: RiskProfile {
+                    kind: match self.u8()? {
+                        0 => RiskProfileKind::Standard,
+                        1 => RiskProfileKind::MarketMaker,
+                        _ => return Err(invalid("invalid risk profile kind")),
+                    },
+                    max_order_notional: self.u64()?,
+                    max_open_orders: self.u64()? as usize,
+                    bypass_notional_check: self.bool()?,
+                    post_trade: self.option(|r| {
+                        Ok(PostTradeLimits {
+                            max_position_quantity: r.u64()?,
+                            max_realized_loss: r.u64()?,
+                        })
+                    })?,
+                },
+                operator: self.string()?,
+            }),
+            COMMAND_RESET_KILL_SWITCH => {
+                Ok(Command::ResetKillSwitch { trader: self.u64()?, operator: self.string()? })
+            }
+            COMMAND_SET_ACCOUNT_STATUS => Ok(Command::SetAccountStatus {
+                trader: self.u64()?,
+                status: AccountStatus::from_code(self.u8()?)
+                    .ok_or_else(|| invalid("invalid account status"))?,
+                reason: self.string()?,
+                operator: self.string()?,
+            }),
+            COMMAND_SET_STOP_LOSS => Ok(Command::SetStopLoss {
+                trader: self.u64()?,
+                position_id: self.u64()?,
+                trigger_price: self.u64()?,
+                close_price: self.option(Self::u64)?,
+            }),
+            COMMAND_SET_TAKE_PROFIT => Ok(Command::SetTakeProfit {
+                trader: self.u64()?,
+                position_id: self.u64()?,
+                trigger_price: self.u64()?,
+                close_price: self.option(Self::u64)?,
+            }),
+            COMMAND_SWITCH_MARGIN_MODE => Ok(Command::SwitchMarginMode {
+                trader: self.u64()?,
+                mode: match self.u8()? {
+                    0 => MarginMode::Cross,
+                    1 => MarginMode::Isolated,
+                    _ => return Err(invalid("invalid margin mode")),
+                },
+            }),
+            COMMAND_SWITCH_POSITION_MODE => Ok(Command::SwitchPositionMode {
+                trader: self.u64()?,
+                mode: match self.u8()? {
+                    0 => PositionMode::OneWay,
+                    1 => PositionMode::Hedge,
+                    _ => return Err(invalid("invalid position mode")),
+                },
+            }),
+            COMMAND_SETTLE_FUNDING_RATE => Ok(Command::SettleFundingRate {
+                position_id: self.u64()?,
+                funding_rate: self.i64()?,
+                mark_price: self.u64()?,
+            }),
+            COMMAND_ADL => Ok(Command::ADL {
+                position_id: self.u64()?,
+                counterparty_position_id: self.u64()?,
+                quantity: self.u64()?,
+                price: self.u64()?,
+            }),
+            COMMAND_TRAILING_STOP => Ok(Command::TrailingStop {
+                trader: self.u64()?,
+                position_id: self.u64()?,
+                callback_rate: self.u32()?,
+                activation_price: self.option(Self::u64)?,
+            }),
+            COMMAND_FLASH_CLOSE => {
+                Ok(Command::FlashClose { trader: self.u64()?, position_id: self.u64()? })
+            }
+            COMMAND_REVERSE_POSITION => Ok(Command::ReversePosition {
+                trader: self.u64()?,
+                position_id: self.u64()?,
+                new_quantity: self.u64()?,
+                price: self.option(Self::u64)?,
+            }),
+            COMMAND_BATCH_CANCEL => Ok(Command::BatchCancelOrders {
+                trader: self.u64()?,
+                order_ids: self.list(Self::u64)?,
+            }),
+            COMMAND_CANCEL_ALL => Ok(Command::CancelAllOrders {
+                trader: self.u64()?,
+                position_side: self.option(Self::position_side)?,
+            }),
+            COMMAND_COMPRESS_POSITIONS => Ok(Command::CompressPositions {
+                legs: self.list(|r| {
+                    Ok(CompressionLeg {
+                        long_trader: r.u64()?,
+                        long_position_id: r.u64()?,
+                        short_trader: r.u64()?,
+                        short_position_id: r.u64()?,
+                        quantity: r.u64()?,
+                    })
+                })?,
+                price: self.u64()?,
+                operator: self.string()?,
+            }),
+            COMMAND_DELIVER_FUTURES => Ok(Command::DeliverFutures {
+                delivery_price: self.u64()?,
+                operator: self.string()?,
+            }),
+            COMMAND_SET_EXPIRY => {
+                Ok(Command::SetExpiry { expiry: self.option(Self::u64)?, operator: self.string()? })
+            }
+            COMMAND_SET_COLLATERAL => {
+                Ok(Command::SetCollateral { trader: self.u64()?, collateral: self.u64()? })
+            }
+            COMMAND_UPDATE_MARK_PRICE => Ok(Command::UpdateMarkPrice { mark_price: self.u64()? }),
+            COMMAND_RESUME_TRADING => Ok(Command::ResumeTrading { operator: self.string()? }),
+            COMMAND_MASS_QUOTE => Ok(Command::MassQuote {
+                trader: self.u64()?,
+                quotes: self.list(|r| {
+                    Ok(QuoteEntry {
+                        bid_price: r.u64()?,
+                        bid_quantity: r.u64()?,
+                        ask_price: r.u64()?,
+                        ask_quantity: r.u64()?,
+                    })
+                })?,
+            }),
+            COMMAND_SET_MMP => Ok(Command::SetMmp {
+                trader: self.u64()?,
+                config: self.option(|r| {
+                    Ok(MmpConfig {
+                        window: r.u64()?,
+                        fill_limit: r.u32()?,
+                        freeze: r.option(Self::u64)?,
+                    })
+                })?,
+            }),
+            COMMAND_RESET_MMP => Ok(Command::ResetMmp { trader: self.u64()? }),
+            COMMAND_FEATURE_FLAG => {
+                let feature =
+                    Feature::from_code(self.u8()?).ok_or_else(|| invalid("unknown feature"))?;
+                let enabled = self.bool()?;
+                let percent = self.u8()?;
+                let accounts = self.list(Self::u64)?;
+                let operator = self.string()?;
+                Ok(Command::SetFeatureFlag {
+                    feature,
+                    rule: FlagRule { enabled, accounts, percent },
+                    operator,
+                })
+            }
+            COMMAND_CREATE_RECURRING => Ok(Command::CreateRecurringPlan {
+                spec: RecurringSpec {
+                    trader: self.u64()?,
+                    symbol: self.string()?,
+                    side: self.side()?,
+                    quantity: self.u64()?,
+                    kind: match self.u8()? {
+                        0 => RecurringOrderKind::Market,
+                        1 => RecurringOrderKind::Limit {
+                            price: self.u64()?,
+                            time_in_force: self.time_in_force()?,
+                        },
+                        _ => return Err(invalid("invalid recurring order kind")),
+                    },
+                    interval: self.u64()?,
+                    start_at: self.u64()?,
+                    max_runs: self.option(Self::u32)?,
+                },
+            }),
+            COMMAND_PAUSE_RECURRING => {
+                Ok(Command::PauseRecurringPlan { trader: self.u64()?, plan_id: self.u64()? })
+            }
+            COMMAND_RESUME_RECURRING => {
+                Ok(Command::ResumeRecurringPlan { trader: self.u64()?, plan_id: self.u64()? })
+            }
+            COMMAND_CANCEL_RECURRING => {
+                Ok(Command::CancelRecurringPlan { trader: self.u64()?, plan_id: self.u64()? })
+            }
+            COMMAND_RUN_RECURRING => Ok(Command::RunRecurringPlans),
+            COMMAND_LINK_SUB_ACCOUNT => Ok(Command::LinkSubAccount {
+                sub_account: self.u64()?,
+                parent: self.option(Self::u64)?,
+            }),
+            COMMAND_COMPRESSION_CONSENT => Ok(Command::SetCompressionConsent {
+                sub_account: self.u64()?,
+                consent: self.bool()?,
+            }),
+            COMMAND_SCHEDULE_ORDER => {
+                let activate_at = self.u64()?;
+                Ok(Command::ScheduleOrder { order: Box::new(self.command()?), activate_at })
+            }
+            COMMAND_CANCEL_SCHEDULED => {
+                Ok(Command::CancelScheduledOrder { trader: self.u64()?, schedule_id: self.u64()? })
+            }
+            _ => Err(invalid("unknown command")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_command_round_trips() {
+        let operator = || "ops".to_string();
+        let commands = vec![
+            Command::LimitOrder {
+                trader: 1,
+                side: Side::Buy,
+                price: 10_000,
+                quantity: 5,
+                position_side: PositionSide::Long,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTD { expire_time: 99 },
+            },
+            Command::MarketOrder {
+                trader: 1,
+                side: Side::Sell,
+                quantity: 5,
+                position_side: PositionSide::Both,
+                reduce_only: true,
+            },
+            Command::CancelOrder { order_id: 7 },
+            Command::SetLeverage {
+                trader: 1,
+                leverage: 20,
+                position_side: Some(PositionSide::Short),
+            },
+            Command::AdjustMargin { trader: 1, position_id: 2, amount: -300 },
+            Command::Liquidate { position_id: 2, mark_price: 9_000, bankruptcy_price: 8_500 },
+            Command::BustTrade {
+                trade_id: 3,
+                reason: "错价成交".to_string(),
+                operator: operator(),
+            },
+            Command::SetRiskProfile {
+                trader: 1,
+                profile: RiskProfile {
+                    kind: RiskProfileKind::MarketMaker,
+                    max_order_notional: 1_000,
+                    max_open_orders: 10,
+                    bypass_notional_check: true,
+                    post_trade: Some(PostTradeLimits {
+                        max_position_quantity: 50,
+                        max_realized_loss: 500,
+                    }),
+                },
+                operator: operator(),
+            },
+            Command::ResetKillSwitch { trader: 1, operator: operator() },
+            Command::SetAccountStatus {
+                trader: 1,
+                status: AccountStatus::Frozen,
+                reason: "fraud".to_string(),
+                operator: operator(),
+            },
+            Command::SetStopLoss {
+                trader: 1,
+                position_id: 2,
+                trigger_price: 9_500,
+                close_price: None,
+            },
+            Command::SetTakeProfit {
+                trader: 1,
+                position_id: 2,
+                trigger_price: 11_000,
+                close_price: Some(10_900),
+            },
+            Command::SwitchMarginMode { trader: 1, mode: MarginMode::Isolated },
+            Command::SwitchPositionMode { trader: 1, mode: PositionMode::Hedge },
+            Command::SettleFundingRate { position_id: 2, funding_rate: -10, mark_price: 10_000 },
+            Command::ADL { position_id: 2, counterparty_position_id: 4, quantity: 1, price: 9_000 },
+            Command::TrailingStop {
+                trader: 1,
+                position_id: 2,
+                callback_rate: 100,
+                activation_price: Some(10_500),
+            },
+            Command::FlashClose { trader: 1, position_id: 2 },
+            Command::ReversePosition { trader: 1, position_id: 2, new_quantity: 3, price: None },
+            Command::BatchCancelOrders { trader: 1, order_ids: vec![7, 8] },
+            Command::CancelAllOrders { trader: 1, position_side: None },
+            Command::CompressPositions {
+                legs: vec![CompressionLeg {
+                    long_trader: 1,
+                    long_position_id: 2,
+                    short_trader: 3,
+                    short_position_id: 4,
+                    quantity: 5,
+                }],
+                price: 10_000,
+                operator: operator(),
+            },
+            Command::DeliverFutures { delivery_price: 10_000, operator: operator() },
+            Command::SetExpiry { expiry: Some(1_000), operator: operator() },
+            Command::SetExpiry { expiry: None, operator: operator() },
+            Command::SetCollateral { trader: 7, collateral: 600_000 },
+            Command::UpdateMarkPrice { mark_price: 10_000 },
+            Command::ResumeTrading { operator: operator() },
+            Command::MassQuote {
+                trader: 1,
+                quotes: vec![QuoteEntry::two_sided(9_990, 1, 10_010, 2)],
+            },
+            Command::SetMmp {
+                trader: 1,
+                config: Some(MmpConfig { window: 1_000, fill_limit: 3, freeze: Some(5_000) }),
+            },
+            Command::ResetMmp { trader: 1 },
+            Command::SetFeatureFlag {
+                feature: Feature::SelfTradePrevention,
+                rule: FlagRule::accounts([1, 2]),
+                operator: operator(),
+            },
+            Command::CreateRecurringPlan {
+                spec: RecurringSpec {
+                    trader: 1,
+                    symbol: "BTCUSDT".to_string(),
+                    side: Side::Buy,
+                    quantity: 5,
+                    kind: RecurringOrderKind::Limit {
+                        price: 10_000,
+                        time_in_force: TimeInForce::PostOnly,
+                    },
+                    interval: 60_000,
+                    start_at: 1_000,
+                    max_runs: Some(12),
+                },
+            },
+            Command::PauseRecurringPlan { trader: 1, plan_id: 2 },
+            Command::ResumeRecurringPlan { trader: 1, plan_id: 2 },
+            Command::CancelRecurringPlan { trader: 1, plan_id: 2 },
+            Command::RunRecurringPlans,
+            Command::LinkSubAccount { sub_account: 3, parent: Some(100) },
+            Command::LinkSubAccount { sub_account: 3, parent: None },
+            Command::SetCompressionConsent { sub_account: 3, consent: true },
+            Command::ScheduleOrder {
+                order: Box::new(Command::MarketOrder {
+                    trader: 1,
+                    side: Side::Sell,
+                    quantity: 4,
+                    position_side: PositionSide::Short,
+                    reduce_only: true,
+                }),
+                activate_at: 5_000,
+            },
+            Command::CancelScheduledOrder { trader: 1, schedule_id: 7 },
+        ];
+
+        for command in commands {
+            let record = CommandRecord { timestamp: 42, command };
+            let bytes = record.encode();
+            let decoded = CommandRecord::decode(&bytes).unwrap();
+            assert_eq!(decoded.timestamp, 42);
+            assert_eq!(format!("{:?}", decoded.command), format!("{:?}", record.command));
+            // 截断的记录报错而不是 panic
+            assert!(CommandRecord::decode(&bytes[..bytes.len() - 1]).is_err());
+        }
+    }
+}
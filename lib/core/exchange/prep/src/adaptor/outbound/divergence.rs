@@ -0,0 +1,285 @@
+//! 回放分歧定位
+//!
+//! 把同一段命令日志回放到两个引擎，逐条比对输出摘要（成交、订单簿、仓位，见
+//! [`StepDigest`]），报告第一处分歧的命令序列号，用于排查不确定性与回归：
+//! - 同一构建的两种配置（或同一配置回放两次查不确定性）：[`replay_pair`] 两个引擎各占一个线程并行回放
+//! - 两个构建：各自用 [`replay_digests`] 产出摘要轨迹并 [`write_trace`]，再以
+//!   [`read_trace`] 读回后交给 [`first_divergence`]
+//!
+//! 日志记录按 [`CommandRecord`] 解码，引擎的已处理序列号须紧接回放区间的起点
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::command_codec::CommandRecord;
+use super::journal::Journal;
+use crate::domain::repository::{OrderRepository, PositionRepository};
+use crate::domain::service::{MatchingService, PrepCommandHandler, StepDigest};
+
+/// 读取日志区间 `[from, until]` 的命令记录（`until` 为 None 表示到日志末尾）
+pub fn read_records(
+    dir: &Path,
+    from: u64,
+    until: Option<u64>,
+) -> io::Result<Vec<(u64, CommandRecord)>> {
+    let mut records = Vec::new();
+    let mut error = None;
+    Journal::replay(dir, from, |seq, payload| {
+        if error.is_some() || until.is_some_and(|until| seq > until) {
+            return;
+        }
+        match CommandRecord::decode(payload) {
+            Ok(record) => records.push((seq, record)),
+            Err(e) => {
+                error = Some(io::Error::new(e.kind(), format!("record {}: {}", seq, e)));
+            }
+        }
+    })?;
+    match error {
+        Some(e) => Err(e),
+        None => Ok(records),
+    }
+}
+
+/// 在一个引擎上回放命令记录，返回逐条输出摘要
+pub fn replay_digests<O, P>(
+    engine: &mut MatchingService<O, P>,
+    records: &[(u64, CommandRecord)],
+) -> io::Result<Vec<StepDigest>>
+where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    let mut digests = Vec::with_capacity(records.len());
+    for (seq, record) in records {
+        if engine.sequence() + 1 != *seq {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("engine at sequence {} cannot replay record {}", engine.sequence(), seq),
+            ));
+        }
+        engine.set_timestamp(record.timestamp);
+        let result = engine.handle(record.command.clone());
+        digests.push(engine.step_digest(&result));
+        // 回放只比对摘要，丢弃待发布的回报与事件
+        engine.drain_execution_reports();
+        engine.drain_events();
+    }
+    Ok(digests)
+}
+
+/// 两个引擎并行回放同一段记录（引擎在各自线程内构建）
+pub fn replay_pair<L, R, OL, PL, OR, PR>(
+    records: &[(u64, CommandRecord)],
+    left: L,
+    right: R,
+) -> io::Result<(Vec<StepDigest>, Vec<StepDigest>)>
+where
+    L: FnOnce() -> MatchingService<OL, PL> + Send,
+    R: FnOnce() -> MatchingService<OR, PR> + Send,
+    OL: OrderRepository,
+    PL: PositionRepository,
+    OR: OrderRepository,
+    PR: PositionRepository,
+{
+    std::thread::scope(|scope| {
+        let left = scope.spawn(|| replay_digests(&mut left(), records));
+        let right = scope.spawn(|| replay_digests(&mut right(), records));
+        let join = |handle: std::thread::ScopedJoinHandle<'_, io::Result<Vec<StepDigest>>>| {
+            handle.join().map_err(|_| io::Error::other("replay thread panicked"))?
+        };
+        Ok((join(left)?, join(right)?))
+    })
+}
+
+/// 第一处分歧
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// 分歧处的命令序列号
+    pub sequence: u64,
+    /// 左侧摘要（左侧已提前结束为 None）
+    pub left: Option<StepDigest>,
+    /// 右侧摘要（右侧已提前结束为 None）
+    pub right: Option<StepDigest>,
+}
+
+impl Divergence {
+    /// 不一致的字段名
+    pub fn fields(&self) -> Vec<&'static str> {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) if left.sequence != right.sequence => vec!["sequence"],
+            (Some(left), Some(right)) => left.diff(right),
+            _ => vec!["length"],
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "first divergence at sequence {} ({})",
+            self.sequence,
+            self.fields().join(", ")
+        )?;
+        for (name, step) in [("left", &self.left), ("right", &self.right)] {
+            match step {
+                Some(step) => writeln!(f, "  {:<5}  {}", name, step)?,
+                None => writeln!(f, "  {:<5}  <ended>", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 逐条比对两条摘要轨迹，返回第一处分歧（完全一致返回 None）
+pub fn first_divergence(left: &[StepDigest], right: &[StepDigest]) -> Option<Divergence> {
+    let len = left.len().max(right.len());
+    (0..len).find_map(|i| {
+        let (l, r) = (left.get(i).copied(), right.get(i).copied());
+        if l == r {
+            return None;
+        }
+        let sequence = l.or(r).map_or(0, |step| step.sequence);
+        Some(Divergence { sequence, left: l, right: r })
+    })
+}
+
+/// 写出摘要轨迹（每行一条，见 [`StepDigest::parse`]）
+pub fn write_trace(path: &Path, digests: &[StepDigest]) -> io::Result<()> {
+    let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+    for step in digests {
+        writeln!(out, "{}", step)?;
+    }
+    out.flush()
+}
+
+/// 读取摘要轨迹
+pub fn read_trace(path: &Path) -> io::Result<Vec<StepDigest>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(number, line)| {
+            StepDigest::parse(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: invalid digest", number + 1),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::adaptor::outbound::journal::{Durability, JournalConfig};
+    use crate::domain::entity::{Order, PositionSide, Side, TimeInForce};
+    use crate::domain::service::{Command, EngineSnapshot};
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prep-div-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    fn limit(trader: u64, side: Side, price: u64, quantity: u64) -> Command {
+        let position_side =
+            if side == Side::Buy { PositionSide::Long } else { PositionSide::Short };
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    fn write_journal(dir: &Path) {
+        let (mut journal, _) = Journal::open(JournalConfig::new(dir, Durability::Batch)).unwrap();
+        let commands = [
+            limit(1, Side::Sell, 100, 10),
+            limit(2, Side::Sell, 101, 5),
+            limit(3, Side::Buy, 101, 12),
+            Command::CancelOrder { order_id: 2 },
+        ];
+        for (i, command) in commands.into_iter().enumerate() {
+            let record = CommandRecord { timestamp: 1_000 + i as u64, command };
+            journal.append(&record.encode()).unwrap();
+        }
+        journal.commit().unwrap();
+    }
+
+    #[test]
+    fn test_identical_engines_do_not_diverge() {
+        let dir = temp_dir("same");
+        write_journal(&dir);
+        let records = read_records(&dir, 1, None).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(read_records(&dir, 2, Some(3)).unwrap().len(), 2);
+
+        let (left, right) = replay_pair(&records, engine, engine).unwrap();
+        assert_eq!(left.len(), 4);
+        assert_eq!(first_divergence(&left, &right), None);
+
+        // 轨迹文件往返后仍一致（跨构建比对路径）
+        let trace = dir.join("left.trace");
+        write_trace(&trace, &left).unwrap();
+        assert_eq!(read_trace(&trace).unwrap(), left);
+        assert_eq!(
+            first_divergence(&read_trace(&trace).unwrap(), &right[..3]).unwrap().fields(),
+            ["length"]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reports_first_divergent_sequence() {
+        let dir = temp_dir("diverge");
+        write_journal(&dir);
+        let records = read_records(&dir, 1, None).unwrap();
+
+        // 右侧起始状态多一笔远离盘口的买单：订单簿从第一条起不同，成交不受影响
+        let seeded = || {
+            let order =
+                Order::new(900, 9, Side::Buy, 1, 1, PositionSide::Long, false, TimeInForce::GTC, 0);
+            let snapshot = EngineSnapshot {
+                next_order_id: 1,
+                next_position_id: 1,
+                orders: vec![order],
+                ..Default::default()
+            };
+            MatchingService::restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                snapshot,
+            )
+            .unwrap()
+        };
+        let (left, right) = replay_pair(&records, engine, seeded).unwrap();
+        let divergence = first_divergence(&left, &right).unwrap();
+        assert_eq!(divergence.sequence, 1);
+        assert_eq!(divergence.fields(), ["book"]);
+        assert!(divergence.to_string().contains("first divergence at sequence 1 (book)"));
+
+        // 引擎序列号与日志区间不衔接时拒绝回放
+        let mut late = engine();
+        assert!(replay_digests(&mut late, &records[1..]).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,706 @@
+//! 引擎分片滚动升级交接
+//!
+//! 用新构建替换运行中的引擎分片而不停止交易：
+//! 1. 握手：新分片发送 `Hello`（构建标识、支持的协议版本范围、可读取的归档版本），
+//!    旧分片协商协议版本并回复 `Accept`，不兼容时回复 `Abort`
+//! 2. 追赶：旧分片继续撮合，发送快照归档（快照 + 日志尾部），之后逐条转发新写入的日志记录
+//! 3. 排空：路由暂停本分片交易对的命令（见 [`ShardRouter`]），旧分片处理完在途命令后
+//!    发送 `Drained`（最后序列号）
+//! 4. 切换：新分片应用到该序列号并落盘后回复 `Ready`，路由原子地把交易对切到新分片，
+//!    暂停期间积压的命令按序转发给新分片；其他分片的交易对全程不受影响
+//!
+//! 新分片只重放状态，不重复发布回报与事件（旧分片已发布）。日志记录按 [`CommandRecord`]
+//! 解码，记录序列号即引擎命令序列号，交接后新分片的日志从快照序列号起与旧分片一致。
+//!
+//! 帧格式（整数均为小端）：`类型 u8 | 正文长度 u32 | 正文 | CRC32(类型 | 正文)`
+//!
+//! 本模块只定义协议类型：帧编解码、版本协商与两端的交接状态机，不含传输与调度。
+//! 分片运行线程（[`spawn_shard`]）目前不写命令日志，尚不能作为交接源；由持有日志与
+//! 快照的部署方驱动 [`HandoffSource`]，新分片侧用 [`run_target`] 完成交接后再启动运行线程。
+//!
+//! [`ShardRouter`]: crate::domain::service::ShardRouter
+//! [`CommandRecord`]: super::command_codec::CommandRecord
+//! [`spawn_shard`]: crate::adaptor::inbound::spawn_shard
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use super::journal::{Journal, JournalConfig, crc32};
+use super::snapshot::{ARCHIVE_VERSION, SnapshotArchive, replay_record};
+use crate::domain::repository::{OrderRepository, PositionRepository};
+use crate::domain::service::MatchingService;
+
+/// 当前交接协议版本
+pub const HANDOFF_PROTOCOL_VERSION: u32 = 1;
+/// 仍兼容的最低交接协议版本
+pub const MIN_HANDOFF_PROTOCOL_VERSION: u32 = 1;
+
+/// 单帧正文上限（快照归档也在一帧内发送）
+const MAX_FRAME_LEN: usize = 1 << 30;
+/// 类型 + 正文长度
+const FRAME_HEADER_LEN: usize = 5;
+
+const FRAME_HELLO: u8 = 1;
+const FRAME_ACCEPT: u8 = 2;
+const FRAME_ABORT: u8 = 3;
+const FRAME_SNAPSHOT: u8 = 4;
+const FRAME_RECORD: u8 = 5;
+const FRAME_DRAINED: u8 = 6;
+const FRAME_READY: u8 = 7;
+
+/// 分片构建版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardVersion {
+    /// 构建标识（如 git 提交）
+    pub build: String,
+    /// 支持的交接协议版本范围
+    pub min_protocol: u32,
+    pub max_protocol: u32,
+    /// 写出 / 可读取的最高快照归档版本
+    pub archive_version: u32,
+}
+
+impl ShardVersion {
+    /// 本构建的版本
+    pub fn current(build: impl Into<String>) -> Self {
+        Self {
+            build: build.into(),
+            min_protocol: MIN_HANDOFF_PROTOCOL_VERSION,
+            max_protocol: HANDOFF_PROTOCOL_VERSION,
+            archive_version: ARCHIVE_VERSION,
+        }
+    }
+
+    /// 作为旧分片与新分片协商：取双方共同支持的最高协议版本，且新分片须能读取本分片写出的归档
+    pub fn negotiate(&self, target: &ShardVersion) -> Result<u32, String> {
+        if target.archive_version < self.archive_version {
+            return Err(format!(
+                "target {} reads archive version up to {}, source writes {}",
+                target.build, target.archive_version, self.archive_version
+            ));
+        }
+        let protocol = self.max_protocol.min(target.max_protocol);
+        if protocol < self.min_protocol.max(target.min_protocol) {
+            return Err(format!(
+                "no common handoff protocol: source {}..={}, target {}..={}",
+                self.min_protocol, self.max_protocol, target.min_protocol, target.max_protocol
+            ));
+        }
+        Ok(protocol)
+    }
+}
+
+/// 交接帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandoffFrame {
+    /// 新分片 → 旧分片：握手
+    Hello(ShardVersion),
+    /// 旧分片 → 新分片：协商后的协议版本
+    Accept { protocol: u32 },
+    /// 任一方：中止交接，旧分片继续服务
+    Abort { reason: String },
+    /// 旧分片 → 新分片：快照归档（[`SnapshotArchive::encode`]）
+    Snapshot(Vec<u8>),
+    /// 旧分片 → 新分片：归档之后的日志记录
+    Record { sequence: u64, payload: Vec<u8> },
+    /// 旧分片 → 新分片：已排空，不再有新记录
+    Drained { last_sequence: u64 },
+    /// 新分片 → 旧分片：已应用并落盘到该序列号，可以切换
+    Ready { sequence: u64 },
+}
+
+impl HandoffFrame {
+    /// 编码
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        let kind = match self {
+            HandoffFrame::Hello(version) => {
+                body.extend_from_slice(&version.min_protocol.to_le_bytes());
+                body.extend_from_slice(&version.max_protocol.to_le_bytes());
+                body.extend_from_slice(&version.archive_version.to_le_bytes());
+                body.extend_from_slice(version.build.as_bytes());
+                FRAME_HELLO
+            }
+            HandoffFrame::Accept { protocol } => {
+                body.extend_from_slice(&protocol.to_le_bytes());
+                FRAME_ACCEPT
+            }
+            HandoffFrame::Abort { reason } => {
+                body.extend_from_slice(reason.as_bytes());
+                FRAME_ABORT
+            }
+            HandoffFrame::Snapshot(archive) => {
+                body.extend_from_slice(archive);
+                FRAME_SNAPSHOT
+            }
+            HandoffFrame::Record { sequence, payload } => {
+                body.extend_from_slice(&sequence.to_le_bytes());
+                body.extend_from_slice(payload);
+                FRAME_RECORD
+            }
+            HandoffFrame::Drained { last_sequence } => {
+                body.extend_from_slice(&last_sequence.to_le_bytes());
+                FRAME_DRAINED
+            }
+            HandoffFrame::Ready { sequence } => {
+                body.extend_from_slice(&sequence.to_le_bytes());
+                FRAME_READY
+            }
+        };
+
+        let mut out = Vec::with_capacity(FRAME_HEADER_LEN + body.len() + 4);
+        out.push(kind);
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out.extend_from_slice(&crc32(&out).to_le_bytes());
+        out
+    }
+
+    /// 解码一帧正文
+    fn decode(kind: u8, body: &[u8]) -> io::Result<Self> {
+        Ok(match kind {
+            FRAME_HELLO => {
+                let fixed = body.get(..12).ok_or_else(|| invalid("truncated hello"))?;
+                HandoffFrame::Hello(ShardVersion {
+                    min_protocol: u32_body(&fixed[0..4])?,
+                    max_protocol: u32_body(&fixed[4..8])?,
+                    archive_version: u32_body(&fixed[8..12])?,
+                    build: utf8(&body[12..])?,
+                })
+            }
+            FRAME_ACCEPT => HandoffFrame::Accept { protocol: u32_body(body)? },
+            FRAME_ABORT => HandoffFrame::Abort { reason: utf8(body)? },
+            FRAME_SNAPSHOT => HandoffFrame::Snapshot(body.to_vec()),
+            FRAME_RECORD => {
+                let sequence = body.get(..8).ok_or_else(|| invalid("truncated record"))?;
+                HandoffFrame::Record { sequence: u64_body(sequence)?, payload: body[8..].to_vec() }
+            }
+            FRAME_DRAINED => HandoffFrame::Drained { last_sequence: u64_body(body)? },
+            FRAME_READY => HandoffFrame::Ready { sequence: u64_body(body)? },
+            other => return Err(invalid(&format!("unknown handoff frame type {}", other))),
+        })
+    }
+}
+
+/// 写入一帧
+pub fn write_frame(channel: &mut impl Write, frame: &HandoffFrame) -> io::Result<()> {
+    channel.write_all(&frame.encode())?;
+    channel.flush()
+}
+
+/// 读取并校验一帧
+pub fn read_frame(channel: &mut impl Read) -> io::Result<HandoffFrame> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    channel.read_exact(&mut header)?;
+    let body_len = u32_body(&header[1..5])? as usize;
+    if body_len > MAX_FRAME_LEN {
+        return Err(invalid("handoff frame too large"));
+    }
+    let mut rest = vec![0u8; body_len + 4];
+    channel.read_exact(&mut rest)?;
+    let (body, crc) = rest.split_at(body_len);
+    let mut checked = header.to_vec();
+    checked.extend_from_slice(body);
+    if crc32(&checked).to_le_bytes() != crc {
+        return Err(invalid("handoff frame checksum mismatch"));
+    }
+    HandoffFrame::decode(header[0], body)
+}
+
+/// 交接阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffPhase {
+    /// 等待握手
+    Handshake,
+    /// 已协商协议，等待快照
+    Accepted,
+    /// 快照已发送 / 应用，转发日志记录中
+    Transfer,
+    /// 旧分片已排空，等待新分片确认
+    Drained,
+    /// 交接完成
+    Completed,
+    /// 已中止
+    Aborted,
+}
+
+impl fmt::Display for HandoffPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandoffPhase::Handshake => "HANDSHAKE",
+            HandoffPhase::Accepted => "ACCEPTED",
+            HandoffPhase::Transfer => "TRANSFER",
+            HandoffPhase::Drained => "DRAINED",
+            HandoffPhase::Completed => "COMPLETED",
+            HandoffPhase::Aborted => "ABORTED",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 旧分片一侧的交接状态
+///
+/// 帧的收发由调用方完成：`on_frame` 处理新分片发来的帧，`snapshot` / `record` / `drain`
+/// 生成待发送的帧。转发的记录须与快照归档首尾相接
+#[derive(Debug)]
+pub struct HandoffSource {
+    local: ShardVersion,
+    phase: HandoffPhase,
+    protocol: Option<u32>,
+    /// 已发送的最后序列号
+    last_sequence: u64,
+}
+
+impl HandoffSource {
+    pub fn new(local: ShardVersion) -> Self {
+        Self { local, phase: HandoffPhase::Handshake, protocol: None, last_sequence: 0 }
+    }
+
+    pub fn phase(&self) -> HandoffPhase {
+        self.phase
+    }
+
+    /// 协商后的协议版本
+    pub fn protocol(&self) -> Option<u32> {
+        self.protocol
+    }
+
+    /// 已发送的最后序列号
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// 处理新分片发来的帧，需要回复时返回应答帧；新分片中止时返回 `ConnectionAborted`
+    pub fn on_frame(&mut self, frame: HandoffFrame) -> io::Result<Option<HandoffFrame>> {
+        match (self.phase, frame) {
+            (_, HandoffFrame::Abort { reason }) => {
+                self.phase = HandoffPhase::Aborted;
+                Err(io::Error::new(io::ErrorKind::ConnectionAborted, reason))
+            }
+            (HandoffPhase::Handshake, HandoffFrame::Hello(target)) => {
+                match self.local.negotiate(&target) {
+                    Ok(protocol) => {
+                        self.protocol = Some(protocol);
+                        self.phase = HandoffPhase::Accepted;
+                        Ok(Some(HandoffFrame::Accept { protocol }))
+                    }
+                    Err(reason) => {
+                        self.phase = HandoffPhase::Aborted;
+                        Ok(Some(HandoffFrame::Abort { reason }))
+                    }
+                }
+            }
+            (HandoffPhase::Drained, HandoffFrame::Ready { sequence }) => {
+                if sequence != self.last_sequence {
+                    return Err(invalid_input(&format!(
+                        "target ready at {}, source drained at {}",
+                        sequence, self.last_sequence
+                    )));
+                }
+                self.phase = HandoffPhase::Completed;
+                Ok(None)
+            }
+            (phase, frame) => Err(unexpected(phase, &frame)),
+        }
+    }
+
+    /// 发送快照归档
+    pub fn snapshot(&mut self, archive: &SnapshotArchive) -> io::Result<HandoffFrame> {
+        self.expect(HandoffPhase::Accepted)?;
+        self.last_sequence = archive
+            .journal_tail
+            .last()
+            .map_or(archive.snapshot.sequence, |(sequence, _)| *sequence);
+        self.phase = HandoffPhase::Transfer;
+        Ok(HandoffFrame::Snapshot(archive.encode()))
+    }
+
+    /// 转发归档之后写入的日志记录
+    pub fn record(&mut self, sequence: u64, payload: &[u8]) -> io::Result<HandoffFrame> {
+        self.expect(HandoffPhase::Transfer)?;
+        if sequence != self.last_sequence + 1 {
+            return Err(invalid_input(&format!(
+                "record {} does not follow {}",
+                sequence, self.last_sequence
+            )));
+        }
+        self.last_sequence = sequence;
+        Ok(HandoffFrame::Record { sequence, payload: payload.to_vec() })
+    }
+
+    /// 排空：路由已暂停本分片的交易对、在途命令均已转发后调用
+    pub fn drain(&mut self) -> io::Result<HandoffFrame> {
+        self.expect(HandoffPhase::Transfer)?;
+        self.phase = HandoffPhase::Drained;
+        Ok(HandoffFrame::Drained { last_sequence: self.last_sequence })
+    }
+
+    /// 主动中止（如排空超时），旧分片恢复服务
+    pub fn abort(&mut self, reason: impl Into<String>) -> HandoffFrame {
+        self.phase = HandoffPhase::Aborted;
+        HandoffFrame::Abort { reason: reason.into() }
+    }
+
+    fn expect(&self, phase: HandoffPhase) -> io::Result<()> {
+        if self.phase != phase {
+            return Err(invalid_input(&format!("handoff is {}, expected {}", self.phase, phase)));
+        }
+        Ok(())
+    }
+}
+
+/// 新分片一侧的交接状态：由快照归档重建引擎与本地日志，随后应用转发的记录
+pub struct HandoffTarget<O: OrderRepository, P: PositionRepository> {
+    local: ShardVersion,
+    phase: HandoffPhase,
+    protocol: Option<u32>,
+    /// 快照到达前的空仓储
+    repos: Option<(O, P)>,
+    journal_config: JournalConfig,
+    state: Option<(MatchingService<O, P>, Journal)>,
+}
+
+impl<O: OrderRepository, P: PositionRepository> HandoffTarget<O, P> {
+    /// `journal` 为新分片的日志目录（须为空）
+    pub fn new(
+        local: ShardVersion,
+        order_repo: O,
+        position_repo: P,
+        journal: JournalConfig,
+    ) -> Self {
+        Self {
+            local,
+            phase: HandoffPhase::Handshake,
+            protocol: None,
+            repos: Some((order_repo, position_repo)),
+            journal_config: journal,
+            state: None,
+        }
+    }
+
+    pub fn phase(&self) -> HandoffPhase {
+        self.phase
+    }
+
+    /// 已应用的引擎序列号（快照到达前为 None）
+    pub fn sequence(&self) -> Option<u64> {
+        self.state.as_ref().map(|(engine, _)| engine.sequence())
+    }
+
+    /// 握手帧
+    pub fn hello(&self) -> HandoffFrame {
+        HandoffFrame::Hello(self.local.clone())
+    }
+
+    /// 处理旧分片发来的帧，需要回复时返回应答帧；旧分片中止时返回 `ConnectionAborted`，
+    /// 本地失败时返回错误，调用方应向旧分片发送 `Abort`
+    pub fn on_frame(&mut self, frame: HandoffFrame) -> io::Result<Option<HandoffFrame>> {
+        match (self.phase, frame) {
+            (_, HandoffFrame::Abort { reason }) => {
+                self.phase = HandoffPhase::Aborted;
+                Err(io::Error::new(io::ErrorKind::ConnectionAborted, reason))
+            }
+            (HandoffPhase::Handshake, HandoffFrame::Accept { protocol }) => {
+                if !(self.local.min_protocol..=self.local.max_protocol).contains(&protocol) {
+                    return Err(invalid(&format!("unsupported handoff protocol {}", protocol)));
+                }
+                self.protocol = Some(protocol);
+                self.phase = HandoffPhase::Accepted;
+                Ok(None)
+            }
+            (HandoffPhase::Accepted, HandoffFrame::Snapshot(bytes)) => {
+                let archive = SnapshotArchive::decode(&bytes)?;
+                let (order_repo, position_repo) =
+                    self.repos.take().ok_or_else(|| invalid_input("snapshot already applied"))?;
+                let (engine, journal) =
+                    archive.restore(order_repo, position_repo, self.journal_config.clone())?;
+                self.state = Some((engine, journal));
+                self.phase = HandoffPhase::Transfer;
+                Ok(None)
+            }
+            (HandoffPhase::Transfer, HandoffFrame::Record { sequence, payload }) => {
+                let (engine, journal) = self.state.as_mut().expect("transfer has state");
+                if journal.append(&payload)? != sequence {
+                    return Err(invalid(&format!("record {} is not contiguous", sequence)));
+                }
+                replay_record(engine, sequence, &payload)?;
+                Ok(None)
+            }
+            (HandoffPhase::Transfer, HandoffFrame::Drained { last_sequence }) => {
+                let (engine, journal) = self.state.as_mut().expect("transfer has state");
+                if engine.sequence() != last_sequence {
+                    return Err(invalid(&format!(
+                        "source drained at {}, target applied {}",
+                        last_sequence,
+                        engine.sequence()
+                    )));
+                }
+                journal.commit()?;
+                journal.sync()?;
+                self.phase = HandoffPhase::Completed;
+                Ok(Some(HandoffFrame::Ready { sequence: last_sequence }))
+            }
+            (phase, frame) => Err(unexpected(phase, &frame)),
+        }
+    }
+
+    /// 交接完成后取出引擎与日志，开始服务
+    pub fn into_parts(self) -> io::Result<(MatchingService<O, P>, Journal)> {
+        match (self.phase, self.state) {
+            (HandoffPhase::Completed, Some(state)) => Ok(state),
+            (phase, _) => Err(invalid_input(&format!("handoff is {}, not completed", phase))),
+        }
+    }
+}
+
+/// 在新分片上驱动整个交接：发送握手，应用收到的帧直到完成；本地失败时通知旧分片中止
+pub fn run_target<C, O, P>(
+    channel: &mut C,
+    mut target: HandoffTarget<O, P>,
+) -> io::Result<(MatchingService<O, P>, Journal)>
+where
+    C: Read + Write,
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    write_frame(channel, &target.hello())?;
+    while target.phase() != HandoffPhase::Completed {
+        let frame = read_frame(channel)?;
+        match target.on_frame(frame) {
+            Ok(Some(reply)) => write_frame(channel, &reply)?,
+            Ok(None) => {}
+            Err(e) if e.kind() == io::ErrorKind::ConnectionAborted => return Err(e),
+            Err(e) => {
+                let _ = write_frame(channel, &HandoffFrame::Abort { reason: e.to_string() });
+                return Err(e);
+            }
+        }
+    }
+    target.into_parts()
+}
+
+fn u32_body(body: &[u8]) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(body.try_into().map_err(|_| invalid("bad frame length"))?))
+}
+
+fn u64_body(body: &[u8]) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(body.try_into().map_err(|_| invalid("bad frame length"))?))
+}
+
+fn utf8(bytes: &[u8]) -> io::Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid utf-8 in handoff frame"))
+}
+
+fn unexpected(phase: HandoffPhase, frame: &HandoffFrame) -> io::Error {
+    let name = match frame {
+        HandoffFrame::Hello(_) => "HELLO",
+        HandoffFrame::Accept { .. } => "ACCEPT",
+        HandoffFrame::Abort { .. } => "ABORT",
+        HandoffFrame::Snapshot(_) => "SNAPSHOT",
+        HandoffFrame::Record { .. } => "RECORD",
+        HandoffFrame::Drained { .. } => "DRAINED",
+        HandoffFrame::Ready { .. } => "READY",
+    };
+    invalid_input(&format!("unexpected {} frame while {}", name, phase))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::adaptor::outbound::command_codec::CommandRecord;
+    use crate::adaptor::outbound::journal::Durability;
+    use crate::domain::entity::{AssetBalance, PositionSide, Side, TimeInForce, TraderId};
+    use crate::domain::repository::BalanceReader;
+    use crate::domain::service::{Command, Dispatch, PrepCommandHandler, ShardRouter};
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    struct NoBalances;
+
+    impl BalanceReader for NoBalances {
+        fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+            Vec::new()
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("prep-handoff-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    fn limit(trader: TraderId, side: Side, price: u64, quantity: u64) -> Command {
+        let position_side =
+            if side == Side::Buy { PositionSide::Long } else { PositionSide::Short };
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    /// 旧分片处理一条命令：先写日志再撮合
+    fn execute(engine: &mut Engine, journal: &mut Journal, command: Command) -> (u64, Vec<u8>) {
+        let timestamp = 1_000 + engine.sequence();
+        let payload = CommandRecord { timestamp, command: command.clone() }.encode();
+        let sequence = journal.append(&payload).unwrap();
+        journal.commit().unwrap();
+        engine.set_timestamp(timestamp);
+        engine.handle(command);
+        assert_eq!(engine.sequence(), sequence);
+        (sequence, payload)
+    }
+
+    #[test]
+    fn test_frame_round_trip_and_checksum() {
+        let frames = [
+            HandoffFrame::Hello(ShardVersion::current("v2")),
+            HandoffFrame::Accept { protocol: 1 },
+            HandoffFrame::Abort { reason: "incompatible".to_string() },
+            HandoffFrame::Snapshot(vec![1, 2, 3]),
+            HandoffFrame::Record { sequence: 9, payload: b"cmd".to_vec() },
+            HandoffFrame::Drained { last_sequence: 9 },
+            HandoffFrame::Ready { sequence: 9 },
+        ];
+        let mut stream = Vec::new();
+        for frame in &frames {
+            write_frame(&mut stream, frame).unwrap();
+        }
+        let mut reader = stream.as_slice();
+        for frame in &frames {
+            assert_eq!(&read_frame(&mut reader).unwrap(), frame);
+        }
+
+        let mut corrupted = HandoffFrame::Ready { sequence: 9 }.encode();
+        corrupted[6] ^= 1;
+        let err = read_frame(&mut corrupted.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_incompatible_version_is_rejected() {
+        let mut source = HandoffSource::new(ShardVersion::current("v2"));
+        let old_reader = ShardVersion { archive_version: 1, ..ShardVersion::current("v1") };
+        let reply = source.on_frame(HandoffFrame::Hello(old_reader)).unwrap().unwrap();
+        assert!(matches!(reply, HandoffFrame::Abort { .. }));
+        assert_eq!(source.phase(), HandoffPhase::Aborted);
+
+        let future =
+            ShardVersion { min_protocol: 2, max_protocol: 3, ..ShardVersion::current("v9") };
+        assert!(ShardVersion::current("v2").negotiate(&future).is_err());
+        let overlapping = ShardVersion { max_protocol: 3, ..ShardVersion::current("v3") };
+        assert_eq!(ShardVersion::current("v2").negotiate(&overlapping), Ok(1));
+
+        // 新分片收到中止后报告 ConnectionAborted
+        let dir = temp_dir("reject");
+        let mut target = HandoffTarget::new(
+            ShardVersion::current("v1"),
+            InMemoryOrderRepository::new(),
+            InMemoryPositionRepository::new(),
+            JournalConfig::new(&dir, Durability::Batch),
+        );
+        let err = target.on_frame(reply).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionAborted);
+        assert!(target.into_parts().is_err());
+    }
+
+    #[test]
+    fn test_rolling_handoff_with_router_cutover() {
+        let root = temp_dir("rolling");
+        let source_dir = root.join("source");
+        let (mut journal, _) =
+            Journal::open(JournalConfig::new(&source_dir, Durability::Batch)).unwrap();
+        let mut old = engine();
+        execute(&mut old, &mut journal, limit(1, Side::Sell, 100, 10));
+        execute(&mut old, &mut journal, limit(2, Side::Buy, 100, 4));
+        let snapshot = old.snapshot(&NoBalances);
+        execute(&mut old, &mut journal, limit(3, Side::Sell, 101, 5));
+
+        let mut router = ShardRouter::new();
+        router.assign("BTCUSDT", 1);
+        router.assign("ETHUSDT", 2);
+
+        let (mut source_channel, mut target_channel) = UnixStream::pair().unwrap();
+        let target_dir = root.join("target");
+        let target = std::thread::spawn(move || {
+            let target = HandoffTarget::new(
+                ShardVersion::current("v2"),
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                JournalConfig::new(target_dir, Durability::Batch),
+            );
+            run_target(&mut target_channel, target)
+        });
+
+        let mut source = HandoffSource::new(ShardVersion::current("v1"));
+        let hello = read_frame(&mut source_channel).unwrap();
+        let accept = source.on_frame(hello).unwrap().unwrap();
+        assert_eq!(accept, HandoffFrame::Accept { protocol: HANDOFF_PROTOCOL_VERSION });
+        write_frame(&mut source_channel, &accept).unwrap();
+
+        // 归档含快照之后已写入的记录 3
+        let archive = SnapshotArchive::capture(snapshot, &source_dir, None).unwrap();
+        write_frame(&mut source_channel, &source.snapshot(&archive).unwrap()).unwrap();
+
+        // 追赶期间旧分片继续撮合并转发记录
+        for command in [limit(4, Side::Buy, 101, 2), Command::CancelOrder { order_id: 1 }] {
+            let Dispatch::Forward(1, command) = router.dispatch("BTCUSDT", command) else {
+                panic!("shard 1 should still be serving");
+            };
+            let (sequence, payload) = execute(&mut old, &mut journal, command);
+            write_frame(&mut source_channel, &source.record(sequence, &payload).unwrap()).unwrap();
+        }
+        assert!(source.record(9, b"gap").is_err());
+
+        // 排空：暂停分片 1，其他分片照常
+        router.begin_drain(1);
+        assert!(matches!(router.dispatch("BTCUSDT", limit(5, Side::Sell, 99, 1)), Dispatch::Held));
+        assert!(matches!(
+            router.dispatch("ETHUSDT", limit(6, Side::Buy, 1, 1)),
+            Dispatch::Forward(2, _)
+        ));
+        write_frame(&mut source_channel, &source.drain().unwrap()).unwrap();
+
+        let ready = read_frame(&mut source_channel).unwrap();
+        assert_eq!(ready, HandoffFrame::Ready { sequence: 5 });
+        assert_eq!(source.on_frame(ready).unwrap(), None);
+        assert_eq!(source.phase(), HandoffPhase::Completed);
+
+        let (mut new, new_journal) = target.join().unwrap().unwrap();
+        assert_eq!(new.sequence(), 5);
+        assert_eq!(new_journal.next_sequence(), 6);
+
+        // 切换后暂存命令转发给新分片，结果与旧分片一致
+        let held = router.cutover(1, 3).unwrap();
+        assert_eq!(router.route("BTCUSDT"), Some(3));
+        for (_, command) in held {
+            new.set_timestamp(2_000);
+            old.set_timestamp(2_000);
+            let expected = old.handle(command.clone());
+            let actual = new.handle(command);
+            assert_eq!(new.step_digest(&actual), old.step_digest(&expected));
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
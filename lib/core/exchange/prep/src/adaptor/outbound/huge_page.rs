@@ -0,0 +1,332 @@
+//! 大页内存映射
+//!
+//! 日志段与共享内存队列通常有数百 MB，按 4KB 页映射时 TLB 频繁未命中。
+//! 这里按策略申请大页，失败时逐级退化，保证在未预留大页的机器上也能启动：
+//! - 显式大页（`MAP_HUGETLB`，2MB / 1GB）：需预先在 `vm.nr_hugepages` 中预留
+//! - 透明大页（`madvise(MADV_HUGEPAGE)`）：由内核尽力合并
+//! - 普通页
+//!
+//! 文件映射的显式大页取决于文件是否位于 hugetlbfs 挂载点，这里只做透明大页提示
+
+use std::fs::File;
+use std::io;
+use std::ptr::NonNull;
+
+/// 普通页大小
+const PAGE_SIZE: usize = 4096;
+
+/// 显式大页规格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HugePageSize {
+    /// 2MB
+    Size2M,
+    /// 1GB
+    Size1G,
+}
+
+impl HugePageSize {
+    /// 字节数
+    pub fn bytes(self) -> usize {
+        match self {
+            HugePageSize::Size2M => 2 << 20,
+            HugePageSize::Size1G => 1 << 30,
+        }
+    }
+}
+
+/// 大页策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HugePagePolicy {
+    /// 普通页
+    #[default]
+    Disabled,
+    /// 透明大页
+    Transparent,
+    /// 显式大页，失败时退化为透明大页
+    Explicit(HugePageSize),
+}
+
+/// 实际使用的页面类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageBacking {
+    /// 普通页
+    Normal,
+    /// 透明大页（内核已接受提示）
+    Transparent,
+    /// 显式大页
+    Explicit(HugePageSize),
+}
+
+/// 大页映射区域
+pub struct HugePageRegion {
+    ptr: NonNull<u8>,
+    /// 映射长度（已按页大小向上取整）
+    len: usize,
+    backing: PageBacking,
+}
+
+// 映射区域独占其内存，可在线程间转移
+unsafe impl Send for HugePageRegion {}
+
+impl HugePageRegion {
+    /// 匿名映射（环形缓冲、对象池）
+    pub fn anonymous(len: usize, policy: HugePagePolicy) -> io::Result<Self> {
+        if let HugePagePolicy::Explicit(size) = policy {
+            let len = round_up(len, size.bytes());
+            if let Ok(ptr) = sys::map_anonymous(len, Some(size)) {
+                return Ok(Self { ptr, len, backing: PageBacking::Explicit(size) });
+            }
+        }
+        let len = round_up(len, PAGE_SIZE);
+        let ptr = sys::map_anonymous(len, None)?;
+        Ok(Self::with_hint(ptr, len, policy))
+    }
+
+    /// 文件共享映射（日志段、跨进程共享内存队列）
+    ///
+    /// 文件长度不足 `len` 时先扩展
+    pub fn map_file(file: &File, len: usize, policy: HugePagePolicy) -> io::Result<Self> {
+        let len = round_up(len, PAGE_SIZE);
+        if file.metadata()?.len() < len as u64 {
+            file.set_len(len as u64)?;
+        }
+        let ptr = sys::map_file(file, len)?;
+        Ok(Self::with_hint(ptr, len, policy))
+    }
+
+    fn with_hint(ptr: NonNull<u8>, len: usize, policy: HugePagePolicy) -> Self {
+        let backing = if policy != HugePagePolicy::Disabled && sys::advise_huge(ptr, len) {
+            PageBacking::Transparent
+        } else {
+            PageBacking::Normal
+        };
+        Self { ptr, len, backing }
+    }
+
+    /// 实际使用的页面类型
+    pub fn backing(&self) -> PageBacking {
+        self.backing
+    }
+
+    /// 映射长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: 映射区域有效且长度为 len
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: 映射区域有效且长度为 len，&mut self 保证独占
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// 逐页预缺页（读后原值写回，不改变内容），避免热路径首次写入时缺页
+    pub fn pre_fault(&mut self) {
+        let step = match self.backing {
+            PageBacking::Explicit(size) => size.bytes(),
+            _ => PAGE_SIZE,
+        };
+        let base = self.ptr.as_ptr();
+        for offset in (0..self.len).step_by(step) {
+            // SAFETY: offset < len
+            unsafe {
+                let byte = base.add(offset);
+                std::ptr::write_volatile(byte, std::ptr::read_volatile(byte));
+            }
+        }
+    }
+
+    /// 将修改同步到文件（匿名映射无操作）
+    pub fn flush(&self) -> io::Result<()> {
+        sys::sync(self.ptr, self.len)
+    }
+}
+
+impl Drop for HugePageRegion {
+    fn drop(&mut self) {
+        sys::unmap(self.ptr, self.len);
+    }
+}
+
+impl std::fmt::Debug for HugePageRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HugePageRegion")
+            .field("len", &self.len)
+            .field("backing", &self.backing)
+            .finish()
+    }
+}
+
+fn round_up(len: usize, page: usize) -> usize {
+    len.max(1).div_ceil(page) * page
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::ffi::{c_int, c_long, c_void};
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::ptr::NonNull;
+
+    use super::HugePageSize;
+
+    const PROT_READ: c_int = 0x1;
+    const PROT_WRITE: c_int = 0x2;
+    const MAP_SHARED: c_int = 0x01;
+    const MAP_PRIVATE: c_int = 0x02;
+    const MAP_ANONYMOUS: c_int = 0x20;
+    const MAP_HUGETLB: c_int = 0x40000;
+    const MAP_HUGE_SHIFT: c_int = 26;
+    const MADV_HUGEPAGE: c_int = 14;
+    const MS_SYNC: c_int = 4;
+    const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    unsafe extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: c_long,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+        fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+        fn msync(addr: *mut c_void, len: usize, flags: c_int) -> c_int;
+    }
+
+    fn map(len: usize, flags: c_int, fd: c_int) -> io::Result<NonNull<u8>> {
+        // SAFETY: 由内核选择地址，不覆盖已有映射
+        let ptr = unsafe { mmap(std::ptr::null_mut(), len, PROT_READ | PROT_WRITE, flags, fd, 0) };
+        if ptr == MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        NonNull::new(ptr.cast()).ok_or_else(|| io::Error::other("mmap returned null"))
+    }
+
+    pub(super) fn map_anonymous(len: usize, huge: Option<HugePageSize>) -> io::Result<NonNull<u8>> {
+        let mut flags = MAP_PRIVATE | MAP_ANONYMOUS;
+        if let Some(size) = huge {
+            flags |= MAP_HUGETLB | ((size.bytes().trailing_zeros() as c_int) << MAP_HUGE_SHIFT);
+        }
+        map(len, flags, -1)
+    }
+
+    pub(super) fn map_file(file: &File, len: usize) -> io::Result<NonNull<u8>> {
+        map(len, MAP_SHARED, file.as_raw_fd())
+    }
+
+    pub(super) fn advise_huge(ptr: NonNull<u8>, len: usize) -> bool {
+        // SAFETY: 区域由 mmap 映射
+        unsafe { madvise(ptr.as_ptr().cast(), len, MADV_HUGEPAGE) == 0 }
+    }
+
+    pub(super) fn sync(ptr: NonNull<u8>, len: usize) -> io::Result<()> {
+        // SAFETY: 区域由 mmap 映射
+        if unsafe { msync(ptr.as_ptr().cast(), len, MS_SYNC) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub(super) fn unmap(ptr: NonNull<u8>, len: usize) {
+        // SAFETY: 区域由 mmap 以相同长度映射，之后不再访问
+        unsafe { munmap(ptr.as_ptr().cast(), len) };
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use std::alloc::{Layout, alloc_zeroed, dealloc};
+    use std::fs::File;
+    use std::io;
+    use std::ptr::NonNull;
+
+    use super::{HugePageSize, PAGE_SIZE};
+
+    fn layout(len: usize) -> io::Result<Layout> {
+        Layout::from_size_align(len, PAGE_SIZE)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+
+    pub(super) fn map_anonymous(len: usize, huge: Option<HugePageSize>) -> io::Result<NonNull<u8>> {
+        if huge.is_some() {
+            return Err(io::ErrorKind::Unsupported.into());
+        }
+        // SAFETY: len 非零
+        NonNull::new(unsafe { alloc_zeroed(layout(len)?) }).ok_or(io::ErrorKind::OutOfMemory.into())
+    }
+
+    pub(super) fn map_file(_file: &File, _len: usize) -> io::Result<NonNull<u8>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub(super) fn advise_huge(_ptr: NonNull<u8>, _len: usize) -> bool {
+        false
+    }
+
+    pub(super) fn sync(_ptr: NonNull<u8>, _len: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(super) fn unmap(ptr: NonNull<u8>, len: usize) {
+        // 分配时 layout 已校验通过，这里不会失败
+        if let Ok(layout) = layout(len) {
+            // SAFETY: 由 alloc_zeroed 以相同 layout 分配
+            unsafe { dealloc(ptr.as_ptr(), layout) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymous_falls_back_gracefully() {
+        // 测试机通常未预留 1GB 大页，必须退化而不是失败
+        let mut region = HugePageRegion::anonymous(
+            PAGE_SIZE + 1,
+            HugePagePolicy::Explicit(HugePageSize::Size1G),
+        )
+        .unwrap();
+        match region.backing() {
+            PageBacking::Explicit(size) => assert_eq!(region.len(), size.bytes()),
+            _ => assert_eq!(region.len(), 2 * PAGE_SIZE),
+        }
+
+        region.pre_fault();
+        region.as_mut_slice()[PAGE_SIZE] = 9;
+        assert_eq!(region.as_slice()[PAGE_SIZE], 9);
+
+        let normal = HugePageRegion::anonymous(10, HugePagePolicy::Disabled).unwrap();
+        assert_eq!(normal.backing(), PageBacking::Normal);
+        assert!(normal.as_slice().iter().all(|b| *b == 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_file_mapping_persists() {
+        let path = std::env::temp_dir().join(format!("prep-journal-{}.seg", std::process::id()));
+        let file =
+            File::options().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+
+        let mut region = HugePageRegion::map_file(&file, 100, HugePagePolicy::Transparent).unwrap();
+        region.as_mut_slice()[..5].copy_from_slice(b"hello");
+        region.flush().unwrap();
+        drop(region);
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(bytes.len(), PAGE_SIZE);
+        assert_eq!(&bytes[..5], b"hello");
+    }
+}
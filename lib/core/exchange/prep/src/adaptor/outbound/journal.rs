@@ -0,0 +1,654 @@
+//! 事件日志（Journal）
+//!
+//! 只追加的分段日志，记录内容由调用方序列化，这里只负责落盘与崩溃恢复：
+//! - 段文件 `{起始序列号:020}.journal`，以带校验的段头开始
+//! - 每条记录：`长度 u32 | CRC32 u32 | 序列号 u64 | 负载`，CRC 覆盖序列号与负载
+//! - 恢复时逐条校验，最后一段中第一条不完整或校验失败的记录及其后内容视为
+//!   撕裂写入（torn write）并截断；非最后一段损坏属于数据错误，拒绝启动
+//!
+//! 持久化策略：
+//! - `Batch`：每次 `commit` 后 fsync
+//! - `Interval`：距上次 fsync 超过间隔时才 fsync，崩溃最多丢失一个间隔
+//! - `Direct`：`O_DIRECT | O_DSYNC` 直写，按块对齐写入，不经过页缓存；
+//!   文件系统不支持 `O_DIRECT` 时退化为 `O_DSYNC`
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::huge_page::{HugePagePolicy, HugePageRegion};
+
+/// 段头魔数
+const SEGMENT_MAGIC: [u8; 4] = *b"PJNL";
+/// 段格式版本
+const SEGMENT_VERSION: u32 = 1;
+/// 段头长度：魔数 4 | 版本 4 | 起始序列号 8 | 保留 4 | CRC32 4
+const SEGMENT_HEADER_LEN: usize = 24;
+/// 记录头长度：长度 4 | CRC32 4 | 序列号 8
+const RECORD_HEADER_LEN: usize = 16;
+/// 直写块大小
+const BLOCK_SIZE: usize = 4096;
+/// 段文件扩展名
+const SEGMENT_EXT: &str = "journal";
+
+/// 持久化策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    /// 每批 fsync
+    Batch,
+    /// 按时间间隔 fsync
+    Interval(Duration),
+    /// O_DIRECT 直写
+    Direct,
+}
+
+/// 日志配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalConfig {
+    /// 日志目录
+    pub dir: PathBuf,
+    /// 单段最大字节数（单条超长记录独占一段）
+    pub segment_size: u64,
+    /// 持久化策略
+    pub durability: Durability,
+    /// 直写缓冲的大页策略
+    pub huge_pages: HugePagePolicy,
+}
+
+impl JournalConfig {
+    pub fn new(dir: impl Into<PathBuf>, durability: Durability) -> Self {
+        Self {
+            dir: dir.into(),
+            segment_size: 256 << 20,
+            durability,
+            huge_pages: HugePagePolicy::Disabled,
+        }
+    }
+
+    pub fn with_segment_size(mut self, segment_size: u64) -> Self {
+        self.segment_size = segment_size;
+        self
+    }
+
+    pub fn with_huge_pages(mut self, huge_pages: HugePagePolicy) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// 段数
+    pub segments: usize,
+    /// 最后一条完整记录的序列号
+    pub last_sequence: Option<u64>,
+    /// 截断的字节数（含直写的块填充）
+    pub truncated_bytes: u64,
+    /// 是否发现撕裂写入
+    pub torn: bool,
+}
+
+/// 当前写入段
+struct Segment {
+    file: File,
+    /// 有效数据长度
+    len: u64,
+}
+
+/// 直写状态
+struct DirectWriter {
+    /// 最后一个未写满块中已有的数据（从块边界开始）
+    tail: Vec<u8>,
+    /// 对齐的写缓冲（打开时预缺页）
+    scratch: HugePageRegion,
+    huge_pages: HugePagePolicy,
+}
+
+/// 事件日志
+pub struct Journal {
+    config: JournalConfig,
+    segment: Segment,
+    /// 下一条记录的序列号
+    next_sequence: u64,
+    /// 本批待写入的记录
+    pending: Vec<u8>,
+    /// 直写状态（None=经页缓存写入）
+    direct: Option<DirectWriter>,
+    last_sync: Instant,
+    unsynced: bool,
+}
+
+impl Journal {
+    /// 打开日志，恢复并截断撕裂的尾部
+    pub fn open(config: JournalConfig) -> io::Result<(Self, RecoveryReport)> {
+        Self::open_at(config, 1)
+    }
+
+    /// 打开日志，目录为空时从 `first_sequence` 开始编号（从快照恢复的新节点）
+    pub fn open_at(
+        config: JournalConfig,
+        first_sequence: u64,
+    ) -> io::Result<(Self, RecoveryReport)> {
+        std::fs::create_dir_all(&config.dir)?;
+        let segments = list_segments(&config.dir)?;
+        let mut report = RecoveryReport { segments: segments.len(), ..Default::default() };
+
+        let Some((&last_base, sealed)) = segments.split_last() else {
+            report.segments = 1;
+            let journal = Self::create(config, first_sequence, 0)?;
+            return Ok((journal, report));
+        };
+
+        for &base in sealed {
+            let scan = scan_segment(&segment_path(&config.dir, base), base, |_, _| {})?;
+            if scan.torn {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("journal segment {} is corrupted", base),
+                ));
+            }
+            report.last_sequence = scan.last_sequence.or(report.last_sequence);
+        }
+
+        let path = segment_path(&config.dir, last_base);
+        let scan = scan_segment(&path, last_base, |_, _| {})?;
+        report.torn = scan.torn;
+        report.truncated_bytes = scan.file_len - scan.valid_end;
+        report.last_sequence = scan.last_sequence.or(report.last_sequence);
+        if scan.file_len != scan.valid_end {
+            let file = OpenOptions::new().write(true).open(&path)?;
+            file.set_len(scan.valid_end)?;
+            file.sync_all()?;
+        }
+
+        let next_sequence = report.last_sequence.map_or(last_base, |seq| seq + 1);
+        let journal = Self::create(config, last_base, scan.valid_end)?;
+        Ok((Self { next_sequence, ..journal }, report))
+    }
+
+    /// 打开（或新建）段文件作为当前写入段
+    fn create(config: JournalConfig, base_sequence: u64, valid_end: u64) -> io::Result<Self> {
+        let path = segment_path(&config.dir, base_sequence);
+        let (file, direct) = open_segment(&path, config.durability)?;
+
+        let mut journal = Self {
+            config,
+            segment: Segment { file, len: valid_end },
+            next_sequence: base_sequence,
+            pending: Vec::new(),
+            direct: None,
+            last_sync: Instant::now(),
+            unsynced: false,
+        };
+        if direct {
+            let block_start = valid_end - valid_end % BLOCK_SIZE as u64;
+            let mut tail = vec![0; (valid_end - block_start) as usize];
+            File::open(&path)?.read_exact_at(&mut tail, block_start)?;
+            let huge_pages = journal.config.huge_pages;
+            let mut scratch = HugePageRegion::anonymous(BLOCK_SIZE, huge_pages)?;
+            scratch.pre_fault();
+            journal.direct = Some(DirectWriter { tail, scratch, huge_pages });
+        }
+        if valid_end == 0 {
+            journal.write(&segment_header(base_sequence))?;
+            journal.sync()?;
+        }
+        Ok(journal)
+    }
+
+    /// 追加一条记录（在 `commit` 前不保证落盘），返回序列号
+    pub fn append(&mut self, payload: &[u8]) -> io::Result<u64> {
+        let record_len = (RECORD_HEADER_LEN + payload.len()) as u64;
+        let segment_used = self.segment.len + self.pending.len() as u64;
+        if segment_used > SEGMENT_HEADER_LEN as u64
+            && segment_used + record_len > self.config.segment_size
+        {
+            self.commit()?;
+            self.roll()?;
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let payload_len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+        self.pending.extend_from_slice(&payload_len.to_le_bytes());
+        self.pending.extend_from_slice(&record_crc(sequence, payload).to_le_bytes());
+        self.pending.extend_from_slice(&sequence.to_le_bytes());
+        self.pending.extend_from_slice(payload);
+        Ok(sequence)
+    }
+
+    /// 写入本批记录，并按持久化策略 fsync
+    pub fn commit(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let batch = std::mem::take(&mut self.pending);
+            self.write(&batch)?;
+        }
+        match self.config.durability {
+            Durability::Batch => self.sync(),
+            Durability::Interval(interval) if self.last_sync.elapsed() >= interval => self.sync(),
+            Durability::Interval(_) | Durability::Direct => Ok(()),
+        }
+    }
+
+    /// 立即 fsync 已写入的记录
+    pub fn sync(&mut self) -> io::Result<()> {
+        if self.unsynced {
+            self.segment.file.sync_data()?;
+            self.unsynced = false;
+        }
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    /// 下一条记录的序列号
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// 是否以 O_DIRECT 写入
+    pub fn is_direct(&self) -> bool {
+        self.direct.is_some()
+    }
+
+    /// 按序回放 `from_sequence` 起的记录，止于第一处撕裂写入，返回最后回放的序列号
+    pub fn replay(
+        dir: &Path,
+        from_sequence: u64,
+        mut visit: impl FnMut(u64, &[u8]),
+    ) -> io::Result<Option<u64>> {
+        let mut last = None;
+        for base in list_segments(dir)? {
+            let scan = scan_segment(&segment_path(dir, base), base, |seq, payload| {
+                if seq >= from_sequence {
+                    visit(seq, payload);
+                    last = Some(seq);
+                }
+            })?;
+            if scan.torn {
+                break;
+            }
+        }
+        Ok(last)
+    }
+
+    /// 切换到新段
+    fn roll(&mut self) -> io::Result<()> {
+        self.sync()?;
+        let next = Self::create(self.config.clone(), self.next_sequence, 0)?;
+        *self = next;
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match &mut self.direct {
+            None => self.segment.file.write_all_at(bytes, self.segment.len)?,
+            Some(direct) => direct.write(&self.segment, bytes)?,
+        }
+        self.segment.len += bytes.len() as u64;
+        self.unsynced = true;
+        Ok(())
+    }
+}
+
+impl DirectWriter {
+    /// 从最后一个块边界开始重写，末块以零填充（恢复时按长度为 0 的记录头识别结尾）
+    fn write(&mut self, segment: &Segment, bytes: &[u8]) -> io::Result<()> {
+        let block_start = segment.len - self.tail.len() as u64;
+        self.tail.extend_from_slice(bytes);
+        let total = self.tail.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+        if self.scratch.len() < total {
+            self.scratch = HugePageRegion::anonymous(total, self.huge_pages)?;
+        }
+        let scratch = self.scratch.as_mut_slice();
+        scratch[..self.tail.len()].copy_from_slice(&self.tail);
+        scratch[self.tail.len()..total].fill(0);
+        segment.file.write_all_at(&scratch[..total], block_start)?;
+
+        let full = self.tail.len() - self.tail.len() % BLOCK_SIZE;
+        self.tail.drain(..full);
+        Ok(())
+    }
+}
+
+/// 段扫描结果
+struct SegmentScan {
+    file_len: u64,
+    valid_end: u64,
+    last_sequence: Option<u64>,
+    torn: bool,
+}
+
+/// 逐条校验段内记录
+fn scan_segment(
+    path: &Path,
+    base_sequence: u64,
+    mut visit: impl FnMut(u64, &[u8]),
+) -> io::Result<SegmentScan> {
+    let data = std::fs::read(path)?;
+    let file_len = data.len() as u64;
+    let header_valid = data.get(..SEGMENT_HEADER_LEN) == Some(&segment_header(base_sequence)[..]);
+    if !header_valid {
+        // 段头未完整写入：整段作废，重新写段头
+        return Ok(SegmentScan { file_len, valid_end: 0, last_sequence: None, torn: file_len > 0 });
+    }
+
+    let mut offset = SEGMENT_HEADER_LEN;
+    let mut expected = base_sequence;
+    let mut last_sequence = None;
+    let mut torn = false;
+    while let Some(header) = data.get(offset..offset + RECORD_HEADER_LEN) {
+        let len = u32::from_le_bytes(le_bytes(header, 0)?) as usize;
+        let crc = u32::from_le_bytes(le_bytes(header, 4)?);
+        let sequence = u64::from_le_bytes(le_bytes(header, 8)?);
+        if len == 0 && crc == 0 && sequence == 0 {
+            // 直写的块填充
+            torn = data[offset..].iter().any(|b| *b != 0);
+            break;
+        }
+        let start = offset + RECORD_HEADER_LEN;
+        let Some(payload) = data.get(start..start + len) else {
+            torn = true;
+            break;
+        };
+        if sequence != expected || record_crc(sequence, payload) != crc {
+            torn = true;
+            break;
+        }
+        visit(sequence, payload);
+        last_sequence = Some(sequence);
+        expected += 1;
+        offset = start + len;
+    }
+    if !torn && offset < data.len() && data.len() - offset < RECORD_HEADER_LEN {
+        torn = data[offset..].iter().any(|b| *b != 0);
+    }
+    Ok(SegmentScan { file_len, valid_end: offset as u64, last_sequence, torn })
+}
+
+/// 取 `at` 起的 `N` 字节（越界视为数据损坏）
+fn le_bytes<const N: usize>(bytes: &[u8], at: usize) -> io::Result<[u8; N]> {
+    bytes
+        .get(at..at + N)
+        .and_then(|field| field.try_into().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated record header"))
+}
+
+/// 按起始序列号排序的段列表
+fn list_segments(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut segments = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXT) {
+            continue;
+        }
+        if let Some(base) = path.file_stem().and_then(|s| s.to_str()?.parse::<u64>().ok()) {
+            segments.push(base);
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+fn segment_path(dir: &Path, base_sequence: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", base_sequence, SEGMENT_EXT))
+}
+
+fn segment_header(base_sequence: u64) -> [u8; SEGMENT_HEADER_LEN] {
+    let mut header = [0u8; SEGMENT_HEADER_LEN];
+    header[0..4].copy_from_slice(&SEGMENT_MAGIC);
+    header[4..8].copy_from_slice(&SEGMENT_VERSION.to_le_bytes());
+    header[8..16].copy_from_slice(&base_sequence.to_le_bytes());
+    let crc = crc32(&header[..20]);
+    header[20..24].copy_from_slice(&crc.to_le_bytes());
+    header
+}
+
+/// 打开段文件，返回是否启用了 O_DIRECT
+#[cfg(target_os = "linux")]
+fn open_segment(path: &Path, durability: Durability) -> io::Result<(File, bool)> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    #[cfg(any(target_arch = "aarch64", target_arch = "arm"))]
+    const O_DIRECT: i32 = 0o200000;
+    #[cfg(not(any(target_arch = "aarch64", target_arch = "arm")))]
+    const O_DIRECT: i32 = 0o40000;
+    const O_DSYNC: i32 = 0o10000;
+
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    if durability != Durability::Direct {
+        return Ok((options.open(path)?, false));
+    }
+    match options.clone().custom_flags(O_DIRECT | O_DSYNC).open(path) {
+        Ok(file) => Ok((file, true)),
+        Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+            Ok((options.custom_flags(O_DSYNC).open(path)?, false))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_segment(path: &Path, _durability: Durability) -> io::Result<(File, bool)> {
+    let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+    Ok((file, false))
+}
+
+fn record_crc(sequence: u64, payload: &[u8]) -> u32 {
+    crc32_update(crc32_update(!0, &sequence.to_le_bytes()), payload) ^ !0
+}
+
+pub(super) fn crc32(bytes: &[u8]) -> u32 {
+    crc32_update(!0, bytes) ^ !0
+}
+
+/// CRC-32（IEEE 802.3）
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for &byte in bytes {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Stdio};
+
+    use super::*;
+
+    /// 子进程写日志时使用的环境变量
+    const CHILD_DIR_ENV: &str = "PREP_JOURNAL_CHILD_DIR";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prep-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn payload(sequence: u64) -> Vec<u8> {
+        let len = 1 + (sequence * 37 % 300) as usize;
+        (0..len).map(|i| (sequence as usize + i) as u8).collect()
+    }
+
+    fn replay_all(dir: &Path) -> Vec<(u64, Vec<u8>)> {
+        let mut records = Vec::new();
+        Journal::replay(dir, 0, |seq, bytes| records.push((seq, bytes.to_vec()))).unwrap();
+        records
+    }
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_append_roll_and_replay() {
+        for durability in
+            [Durability::Batch, Durability::Interval(Duration::from_millis(5)), Durability::Direct]
+        {
+            let dir = temp_dir("roll");
+            let config = JournalConfig::new(&dir, durability)
+                .with_segment_size(2048)
+                .with_huge_pages(HugePagePolicy::Transparent);
+            let (mut journal, report) = Journal::open(config.clone()).unwrap();
+            assert_eq!(report.last_sequence, None);
+
+            for seq in 1..=40 {
+                assert_eq!(journal.append(&payload(seq)).unwrap(), seq);
+                if seq % 3 == 0 {
+                    journal.commit().unwrap();
+                }
+            }
+            journal.commit().unwrap();
+            journal.sync().unwrap();
+            drop(journal);
+
+            assert!(list_segments(&dir).unwrap().len() > 1);
+            let records = replay_all(&dir);
+            assert_eq!(records.len(), 40);
+            assert!(records.iter().all(|(seq, bytes)| *bytes == payload(*seq)));
+
+            let (mut journal, report) = Journal::open(config).unwrap();
+            assert_eq!(report.last_sequence, Some(40));
+            assert!(!report.torn);
+            assert_eq!(journal.append(b"next").unwrap(), 41);
+            journal.commit().unwrap();
+            drop(journal);
+            assert_eq!(replay_all(&dir).len(), 41);
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_torn_tail_is_truncated() {
+        let dir = temp_dir("torn");
+        let config = JournalConfig::new(&dir, Durability::Batch);
+        let (mut journal, _) = Journal::open(config.clone()).unwrap();
+        for seq in 1..=5 {
+            journal.append(&payload(seq)).unwrap();
+        }
+        journal.commit().unwrap();
+        drop(journal);
+
+        // 模拟最后一条记录只写入一半
+        let path = segment_path(&dir, 1);
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 3).unwrap();
+        drop(file);
+
+        let (mut journal, report) = Journal::open(config).unwrap();
+        assert!(report.torn);
+        assert_eq!(report.last_sequence, Some(4));
+        assert_eq!(journal.append(b"after").unwrap(), 5);
+        journal.commit().unwrap();
+        drop(journal);
+
+        let records = replay_all(&dir);
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[4], (5, b"after".to_vec()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_sealed_segment_is_rejected() {
+        let dir = temp_dir("sealed");
+        let config = JournalConfig::new(&dir, Durability::Batch).with_segment_size(512);
+        let (mut journal, _) = Journal::open(config.clone()).unwrap();
+        for seq in 1..=20 {
+            journal.append(&payload(seq)).unwrap();
+        }
+        journal.commit().unwrap();
+        drop(journal);
+
+        let path = segment_path(&dir, 1);
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[SEGMENT_HEADER_LEN + RECORD_HEADER_LEN] ^= 0xFF;
+        std::fs::write(&path, bytes).unwrap();
+
+        let err = Journal::open(config).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 子进程：持续写入直到被杀
+    #[test]
+    fn test_journal_child_writer() {
+        let Ok(dir) = std::env::var(CHILD_DIR_ENV) else {
+            return;
+        };
+        let durability = match std::env::var("PREP_JOURNAL_CHILD_MODE").as_deref() {
+            Ok("direct") => Durability::Direct,
+            _ => Durability::Batch,
+        };
+        let config = JournalConfig::new(dir, durability).with_segment_size(64 << 10);
+        let (mut journal, _) = Journal::open(config).unwrap();
+        loop {
+            let seq = journal.next_sequence();
+            journal.append(&payload(seq)).unwrap();
+            if seq % 7 == 0 {
+                journal.commit().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_recovery_after_kill_mid_write() {
+        if std::env::var(CHILD_DIR_ENV).is_ok() {
+            return;
+        }
+        for mode in ["batch", "direct"] {
+            let dir = temp_dir(&format!("kill-{}", mode));
+            std::fs::create_dir_all(&dir).unwrap();
+            let mut child = Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "adaptor::outbound::journal::tests::test_journal_child_writer"])
+                .env(CHILD_DIR_ENV, &dir)
+                .env("PREP_JOURNAL_CHILD_MODE", mode)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+            child.kill().unwrap();
+            child.wait().unwrap();
+
+            let config = JournalConfig::new(&dir, Durability::Batch);
+            let (mut journal, report) = Journal::open(config).unwrap();
+            let last = report.last_sequence.expect("child wrote nothing");
+
+            let records = replay_all(&dir);
+            assert_eq!(records.len() as u64, last);
+            for (index, (seq, bytes)) in records.iter().enumerate() {
+                assert_eq!(*seq, index as u64 + 1);
+                assert_eq!(*bytes, payload(*seq));
+            }
+
+            // 恢复后可继续追加
+            assert_eq!(journal.append(b"resume").unwrap(), last + 1);
+            journal.commit().unwrap();
+            drop(journal);
+            assert_eq!(replay_all(&dir).len() as u64, last + 1);
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -1 +1,11 @@
 //! Outbound adapters
+
+pub mod command_codec;
+pub mod divergence;
+pub mod handoff;
+pub mod huge_page;
+pub mod journal;
+pub mod notification;
+pub mod numa;
+pub mod schedule_log;
+pub mod snapshot;
@@ -0,0 +1,577 @@
+//! 通知投递适配器
+//!
+//! [`NotificationSender`] 的邮件与 Webhook 实现，只依赖标准库的阻塞 TCP：
+//! - [`SmtpSender`]：经内网 SMTP 中继投递（明文协议，TLS 与鉴权由中继负责），
+//!   标题按 RFC 2047 编码，正文 UTF-8
+//! - [`WebhookSender`]：以 JSON 向 `http://` 端点 POST，2xx 视为成功；
+//!   外部 HTTPS 端点经出口代理（配置后以绝对 URI 发给代理）
+//! - [`ChannelSender`]：按通知的投递渠道分派给上面两者，可由环境变量配置
+//!   （见 [`ChannelSender::from_env`]）
+//!
+//! 连接、读写均有超时，失败原因原样返回，通知留在发件箱等待重试
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::domain::service::confirmation::{
+    DeliveryChannel, Notification, NotificationKind, NotificationSender,
+};
+
+/// 默认超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// SMTP 中继地址（host:port）
+const ENV_SMTP_RELAY: &str = "PREP_SMTP_RELAY";
+/// 发件人地址
+const ENV_SMTP_FROM: &str = "PREP_SMTP_FROM";
+/// EHLO 主机名
+const ENV_SMTP_HELO: &str = "PREP_SMTP_HELO";
+/// Webhook 出口代理（host:port）
+const ENV_WEBHOOK_PROXY: &str = "PREP_WEBHOOK_PROXY";
+/// Webhook `Authorization: Bearer` 令牌
+const ENV_WEBHOOK_TOKEN: &str = "PREP_WEBHOOK_TOKEN";
+/// 投递超时（毫秒）
+const ENV_NOTIFY_TIMEOUT_MS: &str = "PREP_NOTIFY_TIMEOUT_MS";
+
+/// 建立带读写超时的连接
+fn connect(addr: &str, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound, format!("unresolved: {}", addr));
+    for socket in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+/// SMTP 中继配置
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    /// 中继地址（host:port）
+    pub relay: String,
+    /// 发件人地址
+    pub from: String,
+    /// EHLO 主机名
+    pub helo: String,
+    pub timeout: Duration,
+}
+
+impl SmtpConfig {
+    pub fn new(relay: impl Into<String>, from: impl Into<String>) -> Self {
+        Self {
+            relay: relay.into(),
+            from: from.into(),
+            helo: "localhost".to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_helo(mut self, helo: impl Into<String>) -> Self {
+        self.helo = helo.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// 邮件投递（每条通知一个 SMTP 会话）
+#[derive(Debug, Clone)]
+pub struct SmtpSender {
+    config: SmtpConfig,
+}
+
+impl SmtpSender {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { config }
+    }
+
+    fn deliver(&self, to: &str, notification: &Notification) -> Result<(), String> {
+        if [to, self.config.from.as_str()].iter().any(|a| a.contains(['\r', '\n', '<', '>'])) {
+            return Err("invalid mail address".to_string());
+        }
+        let stream = connect(&self.config.relay, self.config.timeout).map_err(|e| e.to_string())?;
+        let mut session = SmtpSession {
+            reader: BufReader::new(stream.try_clone().map_err(|e| e.to_string())?),
+            writer: stream,
+        };
+        session.expect(&[220])?;
+        session.command(&format!("EHLO {}", self.config.helo), &[250])?;
+        session.command(&format!("MAIL FROM:<{}>", self.config.from), &[250])?;
+        session.command(&format!("RCPT TO:<{}>", to), &[250, 251])?;
+        session.command("DATA", &[354])?;
+        let message = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nMIME-Version: 1.0\r\n\
+             Content-Type: text/plain; charset=UTF-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}\r\n.",
+            self.config.from,
+            to,
+            encode_header(&notification.subject),
+            dot_stuff(&notification.body),
+        );
+        session.command(&message, &[250])?;
+        // 邮件已被中继接受，QUIT 失败不影响结果
+        let _ = session.command("QUIT", &[221]);
+        Ok(())
+    }
+}
+
+impl NotificationSender for SmtpSender {
+    fn send(&mut self, notification: &Notification) -> Result<(), String> {
+        match &notification.channel {
+            DeliveryChannel::Email(to) => self.deliver(to, notification),
+            DeliveryChannel::Webhook(_) => Err("not an email channel".to_string()),
+        }
+    }
+}
+
+struct SmtpSession {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl SmtpSession {
+    fn command(&mut self, line: &str, accepted: &[u16]) -> Result<(), String> {
+        write!(self.writer, "{}\r\n", line).map_err(|e| e.to_string())?;
+        self.expect(accepted)
+    }
+
+    /// 读取一个（可能多行的）应答
+    fn expect(&mut self, accepted: &[u16]) -> Result<(), String> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("smtp connection closed".to_string());
+            }
+            let code = line.get(..3).and_then(|c| c.parse::<u16>().ok());
+            let Some(code) = code else {
+                return Err(format!("malformed smtp reply: {}", line.trim_end()));
+            };
+            if line.as_bytes().get(3) == Some(&b'-') {
+                continue;
+            }
+            return if accepted.contains(&code) {
+                Ok(())
+            } else {
+                Err(format!("smtp rejected: {}", line.trim_end()))
+            };
+        }
+    }
+}
+
+/// 非 ASCII 标题按 RFC 2047 Q 编码
+fn encode_header(value: &str) -> String {
+    if value.bytes().all(|b| (0x20..0x7f).contains(&b)) {
+        return value.to_string();
+    }
+    let mut encoded = String::from("=?UTF-8?Q?");
+    for byte in value.bytes() {
+        match byte {
+            b' ' => encoded.push('_'),
+            b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'!' | b'*' | b'+' | b'-' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("={:02X}", byte)),
+        }
+    }
+    encoded.push_str("?=");
+    encoded
+}
+
+/// 正文换行统一为 CRLF，行首的 `.` 加倍
+fn dot_stuff(body: &str) -> String {
+    body.lines()
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// Webhook 投递
+#[derive(Debug, Clone)]
+pub struct WebhookSender {
+    /// 出口代理（host:port），HTTPS 端点必须配置
+    proxy: Option<String>,
+    /// 附加的 `Authorization: Bearer` 令牌
+    token: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for WebhookSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookSender {
+    pub fn new() -> Self {
+        Self { proxy: None, token: None, timeout: DEFAULT_TIMEOUT }
+    }
+
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn deliver(&self, url: &str, notification: &Notification) -> Result<(), String> {
+        let endpoint = Endpoint::parse(url)?;
+        let (addr, target) = match (&self.proxy, endpoint.tls) {
+            (Some(proxy), _) => (proxy.clone(), url.to_string()),
+            (None, false) => (endpoint.authority.clone(), endpoint.path.clone()),
+            (None, true) => return Err("https webhook requires an egress proxy".to_string()),
+        };
+        let body = notification_json(notification);
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n",
+            target,
+            endpoint.authority,
+            body.len()
+        );
+        if let Some(token) = &self.token {
+            request.push_str(&format!("Authorization: Bearer {}\r\n", token));
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+
+        let mut stream = connect(&addr, self.timeout).map_err(|e| e.to_string())?;
+        stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+        let mut status_line = String::new();
+        BufReader::new((&mut stream).take(1024))
+            .read_line(&mut status_line)
+            .map_err(|e| e.to_string())?;
+        let status = status_line.split_whitespace().nth(1).and_then(|s| s.parse::<u16>().ok());
+        match status {
+            Some(200..=299) => Ok(()),
+            Some(status) => Err(format!("webhook returned {}", status)),
+            None => Err("malformed webhook response".to_string()),
+        }
+    }
+}
+
+impl NotificationSender for WebhookSender {
+    fn send(&mut self, notification: &Notification) -> Result<(), String> {
+        match &notification.channel {
+            DeliveryChannel::Webhook(url) => self.deliver(url, notification),
+            DeliveryChannel::Email(_) => Err("not a webhook channel".to_string()),
+        }
+    }
+}
+
+/// 解析后的 Webhook 地址
+struct Endpoint {
+    tls: bool,
+    /// host[:port]（无端口时补默认端口）
+    authority: String,
+    path: String,
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else {
+            return Err(format!("unsupported webhook url: {}", url));
+        };
+        let (authority, path) = rest.find('/').map_or((rest, "/"), |i| rest.split_at(i));
+        if authority.is_empty() || url.contains(['\r', '\n', ' ']) {
+            return Err(format!("invalid webhook url: {}", url));
+        }
+        let authority = if authority.contains(':') {
+            authority.to_string()
+        } else {
+            format!("{}:{}", authority, if tls { 443 } else { 80 })
+        };
+        Ok(Self { tls, authority, path: path.to_string() })
+    }
+}
+
+/// Webhook 请求体
+fn notification_json(notification: &Notification) -> String {
+    let kind = match notification.kind {
+        NotificationKind::Trade { trade_id } => {
+            format!("\"kind\":\"trade\",\"tradeId\":{}", trade_id)
+        }
+        NotificationKind::Digest { day } => format!("\"kind\":\"digest\",\"day\":{}", day),
+    };
+    format!(
+        "{{\"accountId\":{},{},\"subject\":{},\"body\":{}}}",
+        notification.trader,
+        kind,
+        json_string(&notification.subject),
+        json_string(&notification.body)
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// 按投递渠道分派
+#[derive(Debug, Clone)]
+pub struct ChannelSender {
+    email: Option<SmtpSender>,
+    webhook: Option<WebhookSender>,
+}
+
+impl ChannelSender {
+    pub fn new(email: Option<SmtpSender>, webhook: Option<WebhookSender>) -> Self {
+        Self { email, webhook }
+    }
+
+    /// 读取环境变量
+    ///
+    /// - `PREP_SMTP_RELAY` 与 `PREP_SMTP_FROM` 同时设置时启用邮件，`PREP_SMTP_HELO` 可选
+    /// - Webhook 总是启用，`PREP_WEBHOOK_PROXY`、`PREP_WEBHOOK_TOKEN` 可选
+    /// - `PREP_NOTIFY_TIMEOUT_MS` 为两者的超时（未设置用默认 5 秒）
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let value =
+            |name: &str| lookup(name).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let timeout = match value(ENV_NOTIFY_TIMEOUT_MS) {
+            Some(ms) => ms.parse().map(Duration::from_millis).map_err(|_| {
+                format!("{} must be a number of milliseconds: {}", ENV_NOTIFY_TIMEOUT_MS, ms)
+            })?,
+            None => DEFAULT_TIMEOUT,
+        };
+        let email = match (value(ENV_SMTP_RELAY), value(ENV_SMTP_FROM)) {
+            (Some(relay), Some(from)) => {
+                let mut config = SmtpConfig::new(relay, from).with_timeout(timeout);
+                if let Some(helo) = value(ENV_SMTP_HELO) {
+                    config = config.with_helo(helo);
+                }
+                Some(SmtpSender::new(config))
+            }
+            (None, None) => None,
+            _ => {
+                return Err(format!(
+                    "{} and {} must be set together",
+                    ENV_SMTP_RELAY, ENV_SMTP_FROM
+                ));
+            }
+        };
+        let mut webhook = WebhookSender::new().with_timeout(timeout);
+        if let Some(proxy) = value(ENV_WEBHOOK_PROXY) {
+            webhook = webhook.with_proxy(proxy);
+        }
+        if let Some(token) = value(ENV_WEBHOOK_TOKEN) {
+            webhook = webhook.with_token(token);
+        }
+        Ok(Self::new(email, Some(webhook)))
+    }
+}
+
+impl NotificationSender for ChannelSender {
+    fn send(&mut self, notification: &Notification) -> Result<(), String> {
+        match (&notification.channel, &mut self.email, &mut self.webhook) {
+            (DeliveryChannel::Email(_), Some(email), _) => email.send(notification),
+            (DeliveryChannel::Webhook(_), _, Some(webhook)) => webhook.send(notification),
+            _ => Err("delivery channel not configured".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn notification(channel: DeliveryChannel) -> Notification {
+        Notification {
+            trader: 7,
+            kind: NotificationKind::Trade { trade_id: 42 },
+            channel,
+            subject: "成交确认 #42".to_string(),
+            body: "Buy 1 @ 100\n.hidden \"line\"".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_smtp_sender_speaks_smtp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let relay = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut transcript = Vec::new();
+            let reply = |writer: &mut TcpStream, line: &str| {
+                write!(writer, "{}\r\n", line).unwrap();
+            };
+            reply(&mut writer, "220 relay ready");
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                transcript.push(line.clone());
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        reply(&mut writer, "250 queued");
+                    }
+                    continue;
+                }
+                match line.split(' ').next().unwrap() {
+                    "EHLO" => reply(&mut writer, "250-relay\r\n250 8BITMIME"),
+                    "DATA" => {
+                        in_data = true;
+                        reply(&mut writer, "354 go ahead");
+                    }
+                    "QUIT" => {
+                        reply(&mut writer, "221 bye");
+                        break;
+                    }
+                    _ => reply(&mut writer, "250 ok"),
+                }
+            }
+            transcript
+        });
+
+        let mut sender = SmtpSender::new(SmtpConfig::new(relay, "noreply@example.com"));
+        let email = notification(DeliveryChannel::Email("trader@example.com".to_string()));
+        sender.send(&email).unwrap();
+        let transcript = server.join().unwrap();
+        assert_eq!(transcript[1], "MAIL FROM:<noreply@example.com>");
+        assert_eq!(transcript[2], "RCPT TO:<trader@example.com>");
+        assert!(
+            transcript
+                .iter()
+                .any(|l| l.starts_with("Subject: =?UTF-8?Q?") && l.ends_with("_=2342?="))
+        );
+        assert!(transcript.iter().any(|l| l == "..hidden \"line\""));
+        assert_eq!(transcript.last().unwrap(), "QUIT");
+
+        let webhook = notification(DeliveryChannel::Webhook("http://127.0.0.1:1/".to_string()));
+        assert!(sender.send(&webhook).is_err());
+    }
+
+    #[test]
+    fn test_webhook_sender_posts_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in ["204 No Content", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.strip_prefix("Content-Length: ") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    head.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+                requests.push((head, String::from_utf8(body).unwrap()));
+            }
+            requests
+        });
+
+        let mut sender = WebhookSender::new().with_token("secret");
+        let url = format!("http://{}/hooks/trades", addr);
+        let webhook = notification(DeliveryChannel::Webhook(url));
+        sender.send(&webhook).unwrap();
+        assert_eq!(sender.send(&webhook), Err("webhook returned 500".to_string()));
+
+        let requests = server.join().unwrap();
+        let (head, body) = &requests[0];
+        assert!(head.starts_with("POST /hooks/trades HTTP/1.1\r\n"));
+        assert!(head.contains("Authorization: Bearer secret\r\n"));
+        assert_eq!(
+            body,
+            "{\"accountId\":7,\"kind\":\"trade\",\"tradeId\":42,\"subject\":\"成交确认 #42\",\
+             \"body\":\"Buy 1 @ 100\\n.hidden \\\"line\\\"\"}"
+        );
+
+        // HTTPS 端点未配置出口代理时直接失败，通知留待重试
+        let https = notification(DeliveryChannel::Webhook("https://example.com/h".to_string()));
+        assert!(sender.send(&https).unwrap_err().contains("egress proxy"));
+    }
+
+    #[test]
+    fn test_channel_sender_routes_by_channel() {
+        let mut sender = ChannelSender::new(None, Some(WebhookSender::new()));
+        let email = notification(DeliveryChannel::Email("trader@example.com".to_string()));
+        assert_eq!(sender.send(&email), Err("delivery channel not configured".to_string()));
+        let bad = notification(DeliveryChannel::Webhook("ftp://example.com".to_string()));
+        assert!(sender.send(&bad).unwrap_err().contains("unsupported webhook url"));
+    }
+
+    #[test]
+    fn test_channel_sender_from_lookup() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+            }
+        };
+
+        let sender = ChannelSender::from_lookup(vars(&[])).unwrap();
+        assert!(sender.email.is_none());
+        assert_eq!(sender.webhook.map(|w| w.timeout), Some(DEFAULT_TIMEOUT));
+
+        let sender = ChannelSender::from_lookup(vars(&[
+            (ENV_SMTP_RELAY, "relay:25"),
+            (ENV_SMTP_FROM, "noreply@example.com"),
+            (ENV_WEBHOOK_TOKEN, "secret"),
+            (ENV_NOTIFY_TIMEOUT_MS, "1500"),
+        ]))
+        .unwrap();
+        let email = sender.email.unwrap();
+        assert_eq!(
+            (email.config.relay.as_str(), email.config.timeout),
+            ("relay:25", Duration::from_millis(1500))
+        );
+        assert_eq!(sender.webhook.unwrap().token.as_deref(), Some("secret"));
+
+        assert!(ChannelSender::from_lookup(vars(&[(ENV_SMTP_RELAY, "relay:25")])).is_err());
+        assert!(ChannelSender::from_lookup(vars(&[(ENV_NOTIFY_TIMEOUT_MS, "soon")])).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! NUMA 感知的内存放置
+//!
+//! 双路服务器上，分片线程若访问另一颗 CPU 所挂内存，每次缓存未命中都要跨
+//! socket，延迟明显抬高。分片按核心绑定配置运行，其订单簿、池与环形缓冲
+//! 也应放在该核心所属的 NUMA 节点上：
+//! - 默认：从 sysfs 读取 CPU → 节点拓扑，依赖 Linux first-touch 策略，
+//!   在已绑核的分片线程上分配并预写内存
+//! - `numa` feature：链接 libnuma，分配时显式指定节点（`numa_alloc_onnode`），
+//!   初始化分片期间把线程内存策略设为节点优先
+//!
+//! 单节点机器、非 Linux 或 libnuma 不可用时全部退化为普通分配
+
+use std::alloc::{Layout, alloc_zeroed, dealloc};
+use std::collections::HashMap;
+use std::io;
+use std::ptr::NonNull;
+
+/// NUMA 节点编号
+pub type NumaNode = u32;
+
+/// CPU 核心编号
+pub type CoreId = usize;
+
+/// 预写内存的步长（按最小页大小）
+const PAGE_SIZE: usize = 4096;
+
+/// 核心绑定配置：分片序号 -> 核心
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorePinning {
+    shard_cores: Vec<CoreId>,
+}
+
+impl CorePinning {
+    pub fn new(shard_cores: Vec<CoreId>) -> Self {
+        Self { shard_cores }
+    }
+
+    /// 分片绑定的核心（未配置返回 None）
+    pub fn core_of(&self, shard: usize) -> Option<CoreId> {
+        self.shard_cores.get(shard).copied()
+    }
+
+    /// 分片数
+    pub fn shard_count(&self) -> usize {
+        self.shard_cores.len()
+    }
+
+    /// 把当前线程绑定到分片配置的核心，返回绑定的核心（未配置返回 None）
+    pub fn pin_current(&self, shard: usize) -> io::Result<Option<CoreId>> {
+        let Some(core) = self.core_of(shard) else {
+            return Ok(None);
+        };
+        sys::pin_current_thread(core)?;
+        Ok(Some(core))
+    }
+}
+
+/// CPU 拓扑
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumaTopology {
+    /// 核心 -> 节点
+    core_nodes: HashMap<CoreId, NumaNode>,
+}
+
+impl NumaTopology {
+    /// 探测本机拓扑（读取 `/sys/devices/system/cpu/cpu*/node*`）
+    ///
+    /// 读取失败时视为单节点
+    pub fn detect() -> Self {
+        let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu") else {
+            return Self::default();
+        };
+        let mut core_nodes = HashMap::new();
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(core) = name.to_str().and_then(|n| n.strip_prefix("cpu")) else {
+                continue;
+            };
+            let Ok(core) = core.parse::<CoreId>() else {
+                continue;
+            };
+            let Ok(links) = std::fs::read_dir(entry.path()) else {
+                continue;
+            };
+            let node = links.flatten().find_map(|link| {
+                link.file_name().to_str()?.strip_prefix("node")?.parse::<NumaNode>().ok()
+            });
+            if let Some(node) = node {
+                core_nodes.insert(core, node);
+            }
+        }
+        Self { core_nodes }
+    }
+
+    /// 由已知映射构造
+    pub fn from_core_nodes(core_nodes: impl IntoIterator<Item = (CoreId, NumaNode)>) -> Self {
+        Self { core_nodes: core_nodes.into_iter().collect() }
+    }
+
+    /// 核心所属节点（未知核心视为节点 0）
+    pub fn node_of_core(&self, core: CoreId) -> NumaNode {
+        self.core_nodes.get(&core).copied().unwrap_or(0)
+    }
+
+    /// 节点数（至少为 1）
+    pub fn node_count(&self) -> usize {
+        let mut nodes: Vec<_> = self.core_nodes.values().copied().collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes.len().max(1)
+    }
+
+    /// 分片应使用的节点（未配置绑核的分片返回 None，由操作系统决定）
+    pub fn shard_node(&self, pinning: &CorePinning, shard: usize) -> Option<NumaNode> {
+        pinning.core_of(shard).map(|core| self.node_of_core(core))
+    }
+
+    /// 在分片所属节点上初始化分片状态（订单簿、池等）
+    ///
+    /// 须在已绑核的分片线程上调用；`numa` feature 下初始化期间内存策略为节点优先
+    pub fn init_on_shard_node<T>(
+        &self,
+        pinning: &CorePinning,
+        shard: usize,
+        init: impl FnOnce() -> T,
+    ) -> T {
+        let Some(node) = self.shard_node(pinning, shard) else {
+            return init();
+        };
+        let preferred = sys::prefer_node(node);
+        let state = init();
+        if preferred {
+            sys::reset_policy();
+        }
+        state
+    }
+}
+
+/// 节点本地缓冲（环形缓冲、对象池的底层存储）
+///
+/// 分配后逐页预写，缺页在初始化阶段完成，不留到热路径
+pub struct NodeBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    node: Option<NumaNode>,
+    /// 全局分配器使用的布局（None=由 libnuma 分配，决定释放方式）
+    layout: Option<Layout>,
+}
+
+// 缓冲独占其内存，可在线程间转移
+unsafe impl Send for NodeBuffer {}
+
+impl NodeBuffer {
+    /// 分配 `len` 字节（`len` 为 0 时按 1 字节分配）
+    ///
+    /// `node` 为 None 或 libnuma 不可用时使用全局分配器，页面落在首次写入线程所在节点；
+    /// 长度按页对齐后溢出时返回 `InvalidInput`
+    pub fn allocate(len: usize, node: Option<NumaNode>) -> io::Result<Self> {
+        let len = len.max(1);
+        let (ptr, layout) = match node.and_then(|node| sys::alloc_on_node(len, node)) {
+            Some(ptr) => (ptr, None),
+            None => {
+                let layout = Layout::from_size_align(len, PAGE_SIZE)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                // SAFETY: layout 大小非零
+                let ptr = unsafe { alloc_zeroed(layout) };
+                let ptr =
+                    NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+                (ptr, Some(layout))
+            }
+        };
+        let mut buffer = Self { ptr, len, node, layout };
+        buffer.pre_touch();
+        Ok(buffer)
+    }
+
+    /// 缓冲长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 请求的节点
+    pub fn node(&self) -> Option<NumaNode> {
+        self.node
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: ptr 指向 len 字节且已初始化
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: ptr 指向 len 字节且已初始化，&mut self 保证独占
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// 逐页写入，触发缺页
+    fn pre_touch(&mut self) {
+        let bytes = self.as_mut_slice();
+        for offset in (0..bytes.len()).step_by(PAGE_SIZE) {
+            // volatile 写防止被优化掉
+            // SAFETY: offset < len
+            unsafe { std::ptr::write_volatile(bytes.as_mut_ptr().add(offset), 0) };
+        }
+    }
+}
+
+impl Drop for NodeBuffer {
+    fn drop(&mut self) {
+        match self.layout {
+            // SAFETY: 由 alloc_zeroed 以相同 layout 分配
+            Some(layout) => unsafe { dealloc(self.ptr.as_ptr(), layout) },
+            None => sys::free_on_node(self.ptr, self.len),
+        }
+    }
+}
+
+impl std::fmt::Debug for NodeBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeBuffer")
+            .field("len", &self.len)
+            .field("node", &self.node)
+            .field("numa_allocated", &self.layout.is_none())
+            .finish()
+    }
+}
+
+#[cfg(feature = "numa")]
+mod sys {
+    use std::ffi::{c_int, c_void};
+    use std::ptr::NonNull;
+
+    use super::NumaNode;
+    pub(super) use super::affinity::pin_current_thread;
+
+    #[link(name = "numa")]
+    unsafe extern "C" {
+        fn numa_available() -> c_int;
+        fn numa_set_preferred(node: c_int);
+        fn numa_set_localalloc();
+        fn numa_alloc_onnode(size: usize, node: c_int) -> *mut c_void;
+        fn numa_free(start: *mut c_void, size: usize);
+    }
+
+    fn available() -> bool {
+        // SAFETY: 无参数，仅查询
+        unsafe { numa_available() >= 0 }
+    }
+
+    pub(super) fn prefer_node(node: NumaNode) -> bool {
+        if !available() {
+            return false;
+        }
+        // SAFETY: 只影响当前线程的内存策略
+        unsafe { numa_set_preferred(node as c_int) };
+        true
+    }
+
+    pub(super) fn reset_policy() {
+        // SAFETY: 只影响当前线程的内存策略
+        unsafe { numa_set_localalloc() };
+    }
+
+    pub(super) fn alloc_on_node(len: usize, node: NumaNode) -> Option<NonNull<u8>> {
+        if !available() {
+            return None;
+        }
+        // SAFETY: 返回页对齐、已清零的内存或空指针
+        NonNull::new(unsafe { numa_alloc_onnode(len, node as c_int) }.cast())
+    }
+
+    pub(super) fn free_on_node(ptr: NonNull<u8>, len: usize) {
+        // SAFETY: ptr 由 numa_alloc_onnode 以相同长度分配
+        unsafe { numa_free(ptr.as_ptr().cast(), len) };
+    }
+}
+
+#[cfg(not(feature = "numa"))]
+mod sys {
+    use std::ptr::NonNull;
+
+    use super::NumaNode;
+    pub(super) use super::affinity::pin_current_thread;
+
+    pub(super) fn prefer_node(_node: NumaNode) -> bool {
+        false
+    }
+
+    pub(super) fn reset_policy() {}
+
+    pub(super) fn alloc_on_node(_len: usize, _node: NumaNode) -> Option<NonNull<u8>> {
+        None
+    }
+
+    /// 未启用 libnuma 时 `alloc_on_node` 从不成功，不存在需要这里释放的缓冲
+    pub(super) fn free_on_node(_ptr: NonNull<u8>, _len: usize) {}
+}
+
+/// 线程绑核（`sched_setaffinity`）
+#[cfg(target_os = "linux")]
+mod affinity {
+    use std::ffi::c_int;
+    use std::io;
+
+    use super::CoreId;
+
+    /// `cpu_set_t` 固定 1024 位
+    const CPU_SET_WORDS: usize = 1024 / 64;
+
+    unsafe extern "C" {
+        fn sched_setaffinity(pid: c_int, cpusetsize: usize, mask: *const u64) -> c_int;
+    }
+
+    pub(crate) fn pin_current_thread(core: CoreId) -> io::Result<()> {
+        let mut mask = [0u64; CPU_SET_WORDS];
+        let word = mask.get_mut(core / 64).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("core {} out of range", core))
+        })?;
+        *word |= 1 << (core % 64);
+        // SAFETY: pid 0 表示当前线程，mask 为完整的 cpu_set_t
+        let rc = unsafe { sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) };
+        if rc == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod affinity {
+    use std::io;
+
+    use super::CoreId;
+
+    pub(crate) fn pin_current_thread(_core: CoreId) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "core pinning requires Linux"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_node_follows_pinning() {
+        let topology = NumaTopology::from_core_nodes([(0, 0), (1, 0), (2, 1), (3, 1)]);
+        let pinning = CorePinning::new(vec![1, 3]);
+
+        assert_eq!(topology.node_count(), 2);
+        assert_eq!(topology.shard_node(&pinning, 0), Some(0));
+        assert_eq!(topology.shard_node(&pinning, 1), Some(1));
+        assert_eq!(topology.shard_node(&pinning, 2), None);
+
+        let book = topology.init_on_shard_node(&pinning, 1, || vec![0u64; 16]);
+        assert_eq!(book.len(), 16);
+    }
+
+    #[test]
+    fn test_node_buffer_is_zeroed_and_writable() {
+        let mut buffer = NodeBuffer::allocate(3 * PAGE_SIZE + 1, Some(0)).unwrap();
+        assert_eq!(buffer.len(), 3 * PAGE_SIZE + 1);
+        assert!(buffer.as_slice().iter().all(|b| *b == 0));
+
+        buffer.as_mut_slice()[PAGE_SIZE] = 7;
+        assert_eq!(buffer.as_slice()[PAGE_SIZE], 7);
+    }
+
+    #[test]
+    fn test_oversized_buffer_is_rejected() {
+        let err = NodeBuffer::allocate(usize::MAX, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_detect_never_reports_zero_nodes() {
+        assert!(NumaTopology::detect().node_count() >= 1);
+    }
+}
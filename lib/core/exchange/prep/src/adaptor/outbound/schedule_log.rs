@@ -0,0 +1,244 @@
+//! 定时委托日志
+//!
+//! 定时委托的登记、撤销、激活写入独立的事件日志目录，重启时回放得到仍在等待激活的
+//! 委托，以原定时ID恢复到调度器。
+//!
+//! 激活记录在命令出队前提交：提交前崩溃会在重启后重新激活；提交后、撮合处理前崩溃
+//! 与命令队列中其他未处理的命令一样丢失。
+//!
+//! 记录格式（整数均为小端）：`类型 u8 | 定时ID u64 | 附加字段`
+//! - 登记：`激活时间 u64 | 命令`（见 [`command_codec`](super::command_codec)），仅支持限价、市价委托
+//! - 撤销 / 激活：无附加字段
+
+use std::collections::BTreeMap;
+use std::io;
+
+use super::command_codec::{Reader, encode_command};
+use super::journal::{Journal, JournalConfig};
+use crate::domain::entity::Timestamp;
+use crate::domain::service::{Command, CommandQueue, OrderScheduler, ScheduleId, ScheduledOrder};
+
+const RECORD_SCHEDULED: u8 = 1;
+const RECORD_CANCELLED: u8 = 2;
+const RECORD_ACTIVATED: u8 = 3;
+
+/// 定时委托日志记录
+#[derive(Debug, Clone)]
+pub enum ScheduleRecord {
+    /// 登记
+    Scheduled(ScheduledOrder),
+    /// 激活前撤销
+    Cancelled(ScheduleId),
+    /// 已激活并入队
+    Activated(ScheduleId),
+}
+
+impl ScheduleRecord {
+    /// 编码（不支持的命令返回 `InvalidInput`）
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(64);
+        match self {
+            ScheduleRecord::Scheduled(order) => {
+                if !matches!(
+                    order.command,
+                    Command::LimitOrder { .. } | Command::MarketOrder { .. }
+                ) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "only limit and market orders can be scheduled",
+                    ));
+                }
+                out.push(RECORD_SCHEDULED);
+                out.extend_from_slice(&order.id.to_le_bytes());
+                out.extend_from_slice(&order.activate_at.to_le_bytes());
+                encode_command(&order.command, &mut out);
+            }
+            ScheduleRecord::Cancelled(id) => {
+                out.push(RECORD_CANCELLED);
+                out.extend_from_slice(&id.to_le_bytes());
+            }
+            ScheduleRecord::Activated(id) => {
+                out.push(RECORD_ACTIVATED);
+                out.extend_from_slice(&id.to_le_bytes());
+            }
+        }
+        Ok(out)
+    }
+
+    /// 解码
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader(bytes);
+        let kind = reader.u8()?;
+        let id = reader.u64()?;
+        match kind {
+            RECORD_SCHEDULED => {
+                let activate_at = reader.u64()?;
+                let command = reader.command()?;
+                Ok(ScheduleRecord::Scheduled(ScheduledOrder { id, activate_at, command }))
+            }
+            RECORD_CANCELLED => Ok(ScheduleRecord::Cancelled(id)),
+            RECORD_ACTIVATED => Ok(ScheduleRecord::Activated(id)),
+            _ => Err(invalid("unknown schedule record")),
+        }
+    }
+}
+
+/// 带日志的定时委托入口
+pub struct ScheduleLog {
+    journal: Journal,
+}
+
+impl ScheduleLog {
+    /// 打开日志并回放，把等待激活的委托恢复到 `scheduler`
+    pub fn open(
+        config: JournalConfig,
+        mut scheduler: OrderScheduler,
+    ) -> io::Result<(Self, OrderScheduler)> {
+        let dir = config.dir.clone();
+        let (journal, _) = Journal::open(config)?;
+
+        let mut pending = BTreeMap::new();
+        let mut error = None;
+        Journal::replay(&dir, 0, |_, payload| match ScheduleRecord::decode(payload) {
+            Ok(ScheduleRecord::Scheduled(order)) => {
+                pending.insert(order.id, order);
+            }
+            Ok(ScheduleRecord::Cancelled(id) | ScheduleRecord::Activated(id)) => {
+                pending.remove(&id);
+            }
+            Err(e) => {
+                error.get_or_insert(e);
+            }
+        })?;
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        for order in pending.into_values() {
+            scheduler.restore(order);
+        }
+        Ok((Self { journal }, scheduler))
+    }
+
+    /// 登记定时委托并落盘
+    pub fn schedule(
+        &mut self,
+        queue: &mut CommandQueue,
+        command: Command,
+        activate_at: Timestamp,
+    ) -> io::Result<ScheduleId> {
+        let id = queue.schedule_at(command, activate_at);
+        let record = match queue.scheduled(id) {
+            Some(order) => ScheduleRecord::Scheduled(order.clone()),
+            None => return Err(invalid("scheduled order missing")),
+        };
+        if let Err(e) = self.write(&[record]) {
+            queue.cancel_scheduled(id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// 激活前撤销并落盘
+    pub fn cancel(
+        &mut self,
+        queue: &mut CommandQueue,
+        id: ScheduleId,
+    ) -> io::Result<Option<ScheduledOrder>> {
+        let Some(order) = queue.cancel_scheduled(id) else {
+            return Ok(None);
+        };
+        self.write(&[ScheduleRecord::Cancelled(id)])?;
+        Ok(Some(order))
+    }
+
+    /// 激活到期委托并落盘激活记录，返回激活的定时ID
+    pub fn release(
+        &mut self,
+        queue: &mut CommandQueue,
+        now: Timestamp,
+    ) -> io::Result<Vec<ScheduleId>> {
+        let released = queue.release_scheduled(now);
+        if !released.is_empty() {
+            let records: Vec<_> =
+                released.iter().map(|id| ScheduleRecord::Activated(*id)).collect();
+            self.write(&records)?;
+        }
+        Ok(released)
+    }
+
+    fn write(&mut self, records: &[ScheduleRecord]) -> io::Result<()> {
+        for record in records {
+            self.journal.append(&record.encode()?)?;
+        }
+        self.journal.commit()
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::adaptor::outbound::journal::Durability;
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prep-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn limit(trader: u64) -> Command {
+        Command::LimitOrder {
+            trader,
+            side: Side::Sell,
+            price: 50_000,
+            quantity: 3,
+            position_side: PositionSide::Short,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTD { expire_time: 9_000 },
+        }
+    }
+
+    #[test]
+    fn test_pending_orders_survive_restart() {
+        let dir = temp_dir("schedule-log");
+        let config = JournalConfig::new(&dir, Durability::Batch);
+
+        let (mut log, scheduler) =
+            ScheduleLog::open(config.clone(), OrderScheduler::default()).unwrap();
+        let mut queue = CommandQueue::new().with_scheduler(scheduler);
+        log.schedule(&mut queue, limit(1), 100).unwrap();
+        let withdrawn = log.schedule(&mut queue, limit(2), 200).unwrap();
+        let later = log.schedule(&mut queue, limit(3), 300).unwrap();
+        let unsupported = log.schedule(&mut queue, Command::CancelOrder { order_id: 1 }, 300);
+        assert_eq!(unsupported.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(queue.scheduled_len(), 3);
+
+        assert!(log.cancel(&mut queue, withdrawn).unwrap().is_some());
+        assert_eq!(log.release(&mut queue, 150).unwrap().len(), 1);
+        drop(log);
+
+        let (mut log, scheduler) = ScheduleLog::open(config, OrderScheduler::default()).unwrap();
+        let mut queue = CommandQueue::new().with_scheduler(scheduler);
+        assert_eq!(queue.scheduled_len(), 1);
+        assert_eq!(log.release(&mut queue, 300).unwrap(), vec![later]);
+        assert!(matches!(
+            queue.pop(),
+            Some(Command::LimitOrder {
+                trader: 3,
+                time_in_force: TimeInForce::GTD { expire_time: 9_000 },
+                ..
+            })
+        ));
+        // 新登记的定时ID 不与恢复的冲突
+        assert!(log.schedule(&mut queue, limit(4), 400).unwrap() > later);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,1719 @@
+//! 快照归档
+//!
+//! 把一致性时间点的引擎快照（订单簿、仓位、余额、序列号）与其后的日志尾部
+//! 打包为单个归档文件，新节点据此恢复，用于灾备与环境克隆。
+//!
+//! 日志记录序列号与引擎命令序列号一一对应：快照序列号为 N 时，尾部为
+//! 序列号 N+1 起的记录（按 [`CommandRecord`] 编码），恢复时按序重放。
+//! 指定截止序列号即可恢复到任意时间点。
+//!
+//! 归档格式（整数均为小端）：`魔数 "PSNP" | 版本 u32 | 正文长度 u64 | 正文 | CRC32(正文)`
+//!
+//! 版本 2 起快照末尾附带生效中的功能开关；版本 3 起再附带仓位保护单（条件单索引）；
+//! 版本 4 起再附带账户杠杆与保证金模式设置及其变更记录；版本 5 起再附带合约到期时间；
+//! 版本 6、7 起依次附带保证金余额与账户状态；版本 8 起再附带风控档案、熔断账户与累计盈亏、
+//! 做市商保护状态、市场熔断状态及成交台账；版本 9 起再附带定投计划与执行历史；
+//! 版本 10 起再附带子账户分组、压缩同意标记与最近标记价格。
+//! 读取旧版本时缺少的部分为空
+
+use std::io;
+use std::path::Path;
+
+use conditional_order::{ConditionalEntry, ConditionalState, Direction, Sibling, Trail};
+
+use super::command_codec::CommandRecord;
+use super::journal::{Journal, JournalConfig, crc32};
+use crate::domain::entity::{
+    AccountSettingChange, AccountSettingRecord, AccountSettings, AccountStatus, AssetBalance,
+    MarginMode, MarketAlert, MarketAlertKind, MmpConfig, MmpState, Order, OrderStatus, Position,
+    PositionSide, PostTradeLimits, Price, PriceFeed, RecurringExecution, RecurringOrderKind,
+    RecurringPlan, RecurringSpec, RecurringStatus, RiskProfile, RiskProfileKind, Side, TimeInForce,
+    TradeLeg, TradeRecord,
+};
+use crate::domain::repository::{BalanceReader, OrderRepository, PositionRepository};
+use crate::domain::service::{
+    CloseOrder, EngineSnapshot, Feature, FlagRule, MatchingService, PrepCommandHandler,
+    ProtectionKind, RecurringState,
+};
+
+/// 归档魔数
+const ARCHIVE_MAGIC: [u8; 4] = *b"PSNP";
+/// 归档格式版本
+pub(super) const ARCHIVE_VERSION: u32 = 10;
+/// 不含功能开关的旧版本
+const ARCHIVE_VERSION_V1: u32 = 1;
+/// 不含仓位保护单的旧版本
+const ARCHIVE_VERSION_V2: u32 = 2;
+/// 不含账户设置的旧版本
+const ARCHIVE_VERSION_V3: u32 = 3;
+/// 不含合约到期时间的旧版本
+const ARCHIVE_VERSION_V4: u32 = 4;
+/// 不含保证金余额的旧版本
+const ARCHIVE_VERSION_V5: u32 = 5;
+/// 不含账户状态的旧版本
+const ARCHIVE_VERSION_V6: u32 = 6;
+/// 不含风控、做市商保护、市场熔断与成交台账的旧版本
+const ARCHIVE_VERSION_V7: u32 = 7;
+/// 不含定投计划的旧版本
+const ARCHIVE_VERSION_V8: u32 = 8;
+/// 不含子账户分组与标记价格的旧版本
+const ARCHIVE_VERSION_V9: u32 = 9;
+/// 魔数 + 版本 + 正文长度
+const ARCHIVE_HEADER_LEN: usize = 16;
+
+/// 快照归档
+#[derive(Debug, Clone)]
+pub struct SnapshotArchive {
+    /// 引擎快照
+    pub snapshot: EngineSnapshot,
+    /// 快照之后的日志记录（序列号, 负载）
+    pub journal_tail: Vec<(u64, Vec<u8>)>,
+}
+
+impl SnapshotArchive {
+    /// 打包快照与日志尾部
+    ///
+    /// `until` 为截止序列号（含），None 表示打包到日志末尾
+    pub fn capture(
+        snapshot: EngineSnapshot,
+        journal_dir: &Path,
+        until: Option<u64>,
+    ) -> io::Result<Self> {
+        let mut journal_tail = Vec::new();
+        Journal::replay(journal_dir, snapshot.sequence + 1, |seq, payload| {
+            if until.is_none_or(|until| seq <= until) {
+                journal_tail.push((seq, payload.to_vec()));
+            }
+        })?;
+        Ok(Self { snapshot, journal_tail })
+    }
+
+    /// 无已有归档时从头引导：在空引擎上重放日志（须从序列号 1 起完整保留）到 `until`（含），
+    /// 截取此时的快照，日志尾部为空
+    ///
+    /// 余额不在命令日志中，由 `balances` 提供
+    pub fn bootstrap<O, P>(
+        order_repo: O,
+        position_repo: P,
+        journal_dir: &Path,
+        until: Option<u64>,
+        balances: &dyn BalanceReader,
+    ) -> io::Result<Self>
+    where
+        O: OrderRepository,
+        P: PositionRepository,
+    {
+        let mut engine = MatchingService::new(order_repo, position_repo);
+        let mut replayed = Ok(());
+        Journal::replay(journal_dir, 1, |seq, payload| {
+            if replayed.is_ok() && until.is_none_or(|until| seq <= until) {
+                replayed = replay_record(&mut engine, seq, payload);
+            }
+        })?;
+        replayed?;
+        Ok(Self { snapshot: engine.snapshot(balances), journal_tail: Vec::new() })
+    }
+
+    /// 恢复到新节点：重建引擎，把日志尾部写入本地日志并按序重放
+    ///
+    /// 返回的引擎处于尾部最后一条记录之后（无尾部时即快照序列号）；
+    /// 重放不重复发布回报与事件（归档来源已发布）
+    pub fn restore<O, P>(
+        &self,
+        order_repo: O,
+        position_repo: P,
+        journal: JournalConfig,
+    ) -> io::Result<(MatchingService<O, P>, Journal)>
+    where
+        O: OrderRepository,
+        P: PositionRepository,
+    {
+        if journal.dir.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "restore target journal directory is not empty",
+            ));
+        }
+
+        let mut engine = MatchingService::restore(order_repo, position_repo, self.snapshot.clone())
+            .map_err(|err| io::Error::other(format!("restore snapshot: {:?}", err)))?;
+        for (seq, payload) in &self.journal_tail {
+            replay_record(&mut engine, *seq, payload)?;
+        }
+
+        let (mut journal, _) = Journal::open_at(journal, self.snapshot.sequence + 1)?;
+        for (seq, payload) in &self.journal_tail {
+            if journal.append(payload)? != *seq {
+                return Err(invalid("journal tail is not contiguous"));
+            }
+        }
+        journal.commit()?;
+        journal.sync()?;
+        Ok((engine, journal))
+    }
+
+    /// 写入归档文件（先写临时文件再改名，避免留下半个归档）
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.encode())?;
+        std::fs::File::open(&tmp)?.sync_all()?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// 读取归档文件
+    pub fn read_from(path: &Path) -> io::Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    /// 编码
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Encoder::default();
+        body.snapshot(&self.snapshot);
+        body.u64(self.journal_tail.len() as u64);
+        for (seq, payload) in &self.journal_tail {
+            body.u64(*seq);
+            body.bytes(payload);
+        }
+
+        let body = body.0;
+        let mut out = Vec::with_capacity(ARCHIVE_HEADER_LEN + body.len() + 4);
+        out.extend_from_slice(&ARCHIVE_MAGIC);
+        out.extend_from_slice(&ARCHIVE_VERSION.to_le_bytes());
+        out.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        out.extend_from_slice(&body);
+        out.extend_from_slice(&crc32(&body).to_le_bytes());
+        out
+    }
+
+    /// 解码并校验
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let header = bytes.get(..ARCHIVE_HEADER_LEN).ok_or_else(|| invalid("truncated archive"))?;
+        if header[0..4] != ARCHIVE_MAGIC {
+            return Err(invalid("not a snapshot archive"));
+        }
+        let version = u32::from_le_bytes(le_bytes(&header[4..8])?);
+        if !(ARCHIVE_VERSION_V1..=ARCHIVE_VERSION).contains(&version) {
+            return Err(invalid(&format!("unsupported archive version {}", version)));
+        }
+        let body_len = u64::from_le_bytes(le_bytes(&header[8..16])?) as usize;
+        let body = bytes
+            .get(ARCHIVE_HEADER_LEN..ARCHIVE_HEADER_LEN + body_len)
+            .ok_or_else(|| invalid("truncated archive"))?;
+        let crc = bytes
+            .get(ARCHIVE_HEADER_LEN + body_len..ARCHIVE_HEADER_LEN + body_len + 4)
+            .ok_or_else(|| invalid("truncated archive"))?;
+        if crc32(body).to_le_bytes() != crc {
+            return Err(invalid("archive checksum mismatch"));
+        }
+
+        let mut body = Decoder(body);
+        let snapshot = body.snapshot(version)?;
+        let tail_len = body.u64()?;
+        let mut journal_tail = Vec::new();
+        for _ in 0..tail_len {
+            let seq = body.u64()?;
+            journal_tail.push((seq, body.bytes()?.to_vec()));
+        }
+        Ok(Self { snapshot, journal_tail })
+    }
+}
+
+/// 在引擎上重放一条日志记录（回报与事件已由记录来源发布，丢弃）
+///
+/// 记录须紧接引擎的已处理序列号
+pub(super) fn replay_record<O, P>(
+    engine: &mut MatchingService<O, P>,
+    sequence: u64,
+    payload: &[u8],
+) -> io::Result<()>
+where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    if engine.sequence() + 1 != sequence {
+        return Err(invalid(&format!(
+            "engine at sequence {} cannot apply record {}",
+            engine.sequence(),
+            sequence
+        )));
+    }
+    let record = CommandRecord::decode(payload)
+        .map_err(|e| io::Error::new(e.kind(), format!("record {}: {}", sequence, e)))?;
+    engine.set_timestamp(record.timestamp);
+    engine.handle(record.command);
+    engine.drain_execution_reports();
+    engine.drain_events();
+    Ok(())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 定长小端字段
+fn le_bytes<const N: usize>(bytes: &[u8]) -> io::Result<[u8; N]> {
+    bytes.try_into().map_err(|_| invalid("truncated archive"))
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.0.extend_from_slice(value);
+    }
+
+    fn snapshot(&mut self, snapshot: &EngineSnapshot) {
+        self.u64(snapshot.sequence);
+        self.u64(snapshot.timestamp);
+        self.u64(snapshot.trade_id_counter);
+        self.u64(snapshot.next_order_id);
+        self.u64(snapshot.next_position_id);
+
+        self.u64(snapshot.orders.len() as u64);
+        for order in &snapshot.orders {
+            self.order(order);
+        }
+        self.u64(snapshot.positions.len() as u64);
+        for position in &snapshot.positions {
+            self.position(position);
+        }
+        self.u64(snapshot.balances.len() as u64);
+        for (trader, balances) in &snapshot.balances {
+            self.u64(*trader);
+            self.u64(balances.len() as u64);
+            for balance in balances {
+                self.bytes(balance.asset.as_bytes());
+                self.u64(balance.available);
+                self.u64(balance.frozen);
+            }
+        }
+        self.u64(snapshot.feature_flags.len() as u64);
+        for (feature, rule) in &snapshot.feature_flags {
+            self.u8(feature.code());
+            self.u8(rule.enabled as u8);
+            self.u8(rule.percent);
+            self.u64(rule.accounts.len() as u64);
+            for account in &rule.accounts {
+                self.u64(*account);
+            }
+        }
+        match snapshot.conditional_last_price {
+            Some(price) => {
+                self.u8(1);
+                self.u64(price);
+            }
+            None => self.u8(0),
+        }
+        self.u64(snapshot.conditional_id_counter);
+        self.u64(snapshot.protections.len() as u64);
+        for entry in &snapshot.protections {
+            self.protection(entry);
+        }
+        self.u64(snapshot.account_settings.len() as u64);
+        for (trader, settings) in &snapshot.account_settings {
+            self.u64(*trader);
+            self.u32(settings.leverage);
+            self.margin_mode(settings.margin_mode);
+        }
+        self.u64(snapshot.setting_log.len() as u64);
+        for record in &snapshot.setting_log {
+            self.setting_record(record);
+        }
+        match snapshot.expiry {
+            Some(expiry) => {
+                self.u8(1);
+                self.u64(expiry);
+            }
+            None => self.u8(0),
+        }
+        self.u64(snapshot.collateral.len() as u64);
+        for (trader, collateral) in &snapshot.collateral {
+            self.u64(*trader);
+            self.u64(*collateral);
+        }
+        self.u64(snapshot.account_statuses.len() as u64);
+        for (trader, status) in &snapshot.account_statuses {
+            self.u64(*trader);
+            self.u8(status.code());
+        }
+        self.u64(snapshot.risk_profiles.len() as u64);
+        for (trader, profile) in &snapshot.risk_profiles {
+            self.u64(*trader);
+            self.risk_profile(profile);
+        }
+        self.u64(snapshot.killed_accounts.len() as u64);
+        for trader in &snapshot.killed_accounts {
+            self.u64(*trader);
+        }
+        self.u64(snapshot.realized_pnl.len() as u64);
+        for (trader, pnl) in &snapshot.realized_pnl {
+            self.u64(*trader);
+            self.i64(*pnl);
+        }
+        self.u64(snapshot.mmp.len() as u64);
+        for state in &snapshot.mmp {
+            self.mmp_state(state);
+        }
+        match &snapshot.circuit_breaker {
+            Some(alert) => {
+                self.u8(1);
+                self.market_alert(alert);
+            }
+            None => self.u8(0),
+        }
+        self.u64(snapshot.trade_journal.len() as u64);
+        for record in &snapshot.trade_journal {
+            self.trade_record(record);
+        }
+        self.u64(snapshot.recurring.plans.len() as u64);
+        for plan in &snapshot.recurring.plans {
+            self.recurring_plan(plan);
+        }
+        self.u64(snapshot.recurring.history.len() as u64);
+        for execution in &snapshot.recurring.history {
+            self.recurring_execution(execution);
+        }
+        self.u64(snapshot.recurring.next_id);
+        self.u64(snapshot.sub_account_links.len() as u64);
+        for (sub_account, parent) in &snapshot.sub_account_links {
+            self.u64(*sub_account);
+            self.u64(*parent);
+        }
+        self.u64(snapshot.compression_consent.len() as u64);
+        for sub_account in &snapshot.compression_consent {
+            self.u64(*sub_account);
+        }
+        self.option_u64(snapshot.mark_price);
+    }
+
+    fn option_u64(&mut self, value: Option<u64>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.u64(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn recurring_plan(&mut self, plan: &RecurringPlan) {
+        self.u64(plan.id);
+        let spec = &plan.spec;
+        self.u64(spec.trader);
+        self.bytes(spec.symbol.as_bytes());
+        self.side(spec.side);
+        self.u64(spec.quantity);
+        match spec.kind {
+            RecurringOrderKind::Market => self.u8(0),
+            RecurringOrderKind::Limit { price, time_in_force } => {
+                self.u8(1);
+                self.u64(price);
+                self.time_in_force(time_in_force);
+            }
+        }
+        self.u64(spec.interval);
+        self.u64(spec.start_at);
+        self.option_u64(spec.max_runs.map(u64::from));
+        self.u8(match plan.status {
+            RecurringStatus::Active => 0,
+            RecurringStatus::Paused => 1,
+            RecurringStatus::Cancelled => 2,
+            RecurringStatus::Completed => 3,
+        });
+        self.u32(plan.runs);
+        self.option_u64(plan.next_run);
+    }
+
+    fn recurring_execution(&mut self, execution: &RecurringExecution) {
+        self.u64(execution.plan_id);
+        self.u32(execution.run);
+        self.u64(execution.scheduled_at);
+        self.u64(execution.executed_at);
+        self.option_u64(execution.order_id);
+        self.option_u64(execution.error_code.map(u64::from));
+    }
+
+    fn risk_profile(&mut self, profile: &RiskProfile) {
+        self.u8(match profile.kind {
+            RiskProfileKind::Standard => 0,
+            RiskProfileKind::MarketMaker => 1,
+        });
+        self.u64(profile.max_order_notional);
+        self.u64(profile.max_open_orders as u64);
+        self.u8(profile.bypass_notional_check as u8);
+        match &profile.post_trade {
+            Some(limits) => {
+                self.u8(1);
+                self.u64(limits.max_position_quantity);
+                self.u64(limits.max_realized_loss);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn mmp_state(&mut self, state: &MmpState) {
+        self.u64(state.trader);
+        match &state.config {
+            Some(config) => {
+                self.u8(1);
+                self.u64(config.window);
+                self.u32(config.fill_limit);
+                self.option_u64(config.freeze);
+            }
+            None => self.u8(0),
+        }
+        self.u64(state.quotes.len() as u64);
+        for order_id in &state.quotes {
+            self.u64(*order_id);
+        }
+        self.u64(state.fills.len() as u64);
+        for fill in &state.fills {
+            self.u64(*fill);
+        }
+        match state.frozen {
+            Some(until) => {
+                self.u8(1);
+                self.option_u64(until);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn market_alert(&mut self, alert: &MarketAlert) {
+        match alert.kind {
+            MarketAlertKind::PriceSpike { feed, price, mean, lower, upper } => {
+                self.u8(0);
+                self.price_feed(feed);
+                self.u64(price);
+                self.u64(mean);
+                self.u64(lower);
+                self.u64(upper);
+            }
+            MarketAlertKind::StaleFeed { feed, last_update } => {
+                self.u8(1);
+                self.price_feed(feed);
+                self.u64(last_update);
+            }
+            MarketAlertKind::CrossedBook { best_bid, best_ask } => {
+                self.u8(2);
+                self.u64(best_bid);
+                self.u64(best_ask);
+            }
+        }
+        self.u64(alert.raised_at);
+    }
+
+    fn price_feed(&mut self, feed: PriceFeed) {
+        self.u8(match feed {
+            PriceFeed::Trade => 0,
+            PriceFeed::Mark => 1,
+        });
+    }
+
+    fn trade_record(&mut self, record: &TradeRecord) {
+        self.u64(record.trade_id);
+        self.u64(record.price);
+        self.u64(record.quantity);
+        self.trade_leg(&record.taker);
+        self.trade_leg(&record.maker);
+        self.u64(record.timestamp);
+    }
+
+    fn trade_leg(&mut self, leg: &TradeLeg) {
+        self.u64(leg.trader);
+        self.side(leg.side);
+        self.position_side(leg.position_side);
+        self.u8(leg.opened as u8);
+        self.u64(leg.quantity);
+        self.u64(leg.entry_price_before);
+        self.i64(leg.realized_pnl);
+    }
+
+    fn setting_record(&mut self, record: &AccountSettingRecord) {
+        self.u64(record.trader);
+        self.u64(record.sequence);
+        self.u64(record.timestamp);
+        self.u64(record.version);
+        match record.change {
+            AccountSettingChange::Leverage { position_side, from, to } => {
+                self.u8(0);
+                match position_side {
+                    Some(position_side) => {
+                        self.u8(1);
+                        self.position_side(position_side);
+                    }
+                    None => self.u8(0),
+                }
+                self.u32(from);
+                self.u32(to);
+            }
+            AccountSettingChange::MarginMode { from, to } => {
+                self.u8(1);
+                self.margin_mode(from);
+                self.margin_mode(to);
+            }
+            AccountSettingChange::Margin { position_id, from, to } => {
+                self.u8(2);
+                self.u64(position_id);
+                self.u64(from);
+                self.u64(to);
+            }
+        }
+    }
+
+    fn protection(&mut self, entry: &ConditionalEntry<Price, CloseOrder>) {
+        self.u64(entry.id);
+        match entry.state {
+            ConditionalState::Armed { direction, trigger } => {
+                self.u8(0);
+                self.direction(direction);
+                self.u64(trigger);
+            }
+            ConditionalState::Tracking { direction, trail, reference, trigger } => {
+                self.u8(1);
+                self.direction(direction);
+                self.trail(trail);
+                self.u64(reference);
+                self.u64(trigger);
+            }
+            ConditionalState::Pending { direction, trail, activation } => {
+                self.u8(2);
+                self.direction(direction);
+                self.trail(trail);
+                self.u64(activation);
+            }
+            ConditionalState::Unanchored { direction, trail } => {
+                self.u8(3);
+                self.direction(direction);
+                self.trail(trail);
+            }
+        }
+        let action = &entry.action;
+        self.u64(action.trader);
+        self.u64(action.position_id);
+        self.u8(match action.kind {
+            ProtectionKind::StopLoss => 0,
+            ProtectionKind::TakeProfit => 1,
+            ProtectionKind::TrailingStop => 2,
+        });
+        match action.close_price {
+            Some(price) => {
+                self.u8(1);
+                self.u64(price);
+            }
+            None => self.u8(0),
+        }
+        match entry.sibling {
+            None => self.u8(0),
+            Some(Sibling::Conditional(id)) => {
+                self.u8(1);
+                self.u64(id);
+            }
+            Some(Sibling::External(id)) => {
+                self.u8(2);
+                self.u64(id);
+            }
+        }
+    }
+
+    fn direction(&mut self, direction: Direction) {
+        self.u8(match direction {
+            Direction::Rising => 0,
+            Direction::Falling => 1,
+        });
+    }
+
+    fn trail(&mut self, trail: Trail<Price>) {
+        match trail {
+            Trail::Absolute(distance) => {
+                self.u8(0);
+                self.u64(distance);
+            }
+            Trail::Bps(bps) => {
+                self.u8(1);
+                self.u32(bps);
+            }
+        }
+    }
+
+    fn order(&mut self, order: &Order) {
+        self.u64(order.id);
+        self.u64(order.trader);
+        self.side(order.side);
+        self.u64(order.price);
+        self.u64(order.original_quantity);
+        self.u64(order.remaining_quantity);
+        self.u64(order.filled_quantity);
+        self.u64(order.cumulative_quote);
+        self.position_side(order.position_side);
+        self.u8(order.reduce_only as u8);
+        self.time_in_force(order.time_in_force);
+        self.u8(match order.status {
+            OrderStatus::New => 0,
+            OrderStatus::PartiallyFilled => 1,
+            OrderStatus::Filled => 2,
+            OrderStatus::Cancelled => 3,
+            OrderStatus::Rejected => 4,
+            OrderStatus::Expired => 5,
+        });
+        self.u64(order.created_at);
+        self.u64(order.updated_at);
+    }
+
+    fn time_in_force(&mut self, time_in_force: TimeInForce) {
+        match time_in_force {
+            TimeInForce::GTC => self.u8(0),
+            TimeInForce::IOC => self.u8(1),
+            TimeInForce::FOK => self.u8(2),
+            TimeInForce::GTD { expire_time } => {
+                self.u8(3);
+                self.u64(expire_time);
+            }
+            TimeInForce::PostOnly => self.u8(4),
+        }
+    }
+
+    fn position(&mut self, position: &Position) {
+        self.u64(position.id);
+        self.u64(position.trader);
+        self.position_side(position.position_side);
+        self.u64(position.quantity);
+        self.u64(position.entry_price);
+        self.margin_mode(position.margin_mode);
+        self.u32(position.leverage);
+        self.u64(position.margin);
+        self.i64(position.unrealized_pnl);
+        self.i64(position.realized_pnl);
+        self.u64(position.liquidation_price);
+        self.u64(position.created_at);
+        self.u64(position.updated_at);
+    }
+
+    fn side(&mut self, side: Side) {
+        self.u8(match side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        });
+    }
+
+    fn position_side(&mut self, position_side: PositionSide) {
+        self.u8(match position_side {
+            PositionSide::Both => 0,
+            PositionSide::Long => 1,
+            PositionSide::Short => 2,
+        });
+    }
+
+    fn margin_mode(&mut self, margin_mode: MarginMode) {
+        self.u8(match margin_mode {
+            MarginMode::Cross => 0,
+            MarginMode::Isolated => 1,
+        });
+    }
+}
+
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid("truncated archive body"));
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(le_bytes(self.take(4)?)?))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(le_bytes(self.take(8)?)?))
+    }
+
+    fn i64(&mut self) -> io::Result<i64> {
+        Ok(i64::from_le_bytes(le_bytes(self.take(8)?)?))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn snapshot(&mut self, version: u32) -> io::Result<EngineSnapshot> {
+        let sequence = self.u64()?;
+        let timestamp = self.u64()?;
+        let trade_id_counter = self.u64()?;
+        let next_order_id = self.u64()?;
+        let next_position_id = self.u64()?;
+
+        let orders = (0..self.u64()?).map(|_| self.order()).collect::<io::Result<_>>()?;
+        let positions = (0..self.u64()?).map(|_| self.position()).collect::<io::Result<_>>()?;
+        let mut balances = Vec::new();
+        for _ in 0..self.u64()? {
+            let trader = self.u64()?;
+            let mut assets = Vec::new();
+            for _ in 0..self.u64()? {
+                let asset = String::from_utf8(self.bytes()?.to_vec())
+                    .map_err(|_| invalid("asset code is not utf-8"))?;
+                assets.push(AssetBalance::new(asset, self.u64()?, self.u64()?));
+            }
+            balances.push((trader, assets));
+        }
+        let mut feature_flags = Vec::new();
+        if version != ARCHIVE_VERSION_V1 {
+            for _ in 0..self.u64()? {
+                let feature =
+                    Feature::from_code(self.u8()?).ok_or_else(|| invalid("unknown feature"))?;
+                let enabled = self.u8()? != 0;
+                let percent = self.u8()?;
+                let accounts = (0..self.u64()?).map(|_| self.u64()).collect::<io::Result<_>>()?;
+                feature_flags.push((feature, FlagRule { enabled, accounts, percent }));
+            }
+        }
+        let (mut conditional_last_price, mut conditional_id_counter) = (None, 0);
+        let mut protections = Vec::new();
+        if version > ARCHIVE_VERSION_V2 {
+            conditional_last_price = self.option_u64()?;
+            conditional_id_counter = self.u64()?;
+            for _ in 0..self.u64()? {
+                protections.push(self.protection()?);
+            }
+        }
+        let (mut account_settings, mut setting_log) = (Vec::new(), Vec::new());
+        if version > ARCHIVE_VERSION_V3 {
+            for _ in 0..self.u64()? {
+                let trader = self.u64()?;
+                let settings =
+                    AccountSettings { leverage: self.u32()?, margin_mode: self.margin_mode()? };
+                account_settings.push((trader, settings));
+            }
+            for _ in 0..self.u64()? {
+                setting_log.push(self.setting_record()?);
+            }
+        }
+        let expiry = if version > ARCHIVE_VERSION_V4 { self.option_u64()? } else { None };
+        let mut collateral = Vec::new();
+        if version > ARCHIVE_VERSION_V5 {
+            for _ in 0..self.u64()? {
+                collateral.push((self.u64()?, self.u64()?));
+            }
+        }
+        let mut account_statuses = Vec::new();
+        if version > ARCHIVE_VERSION_V6 {
+            for _ in 0..self.u64()? {
+                let trader = self.u64()?;
+                let status = AccountStatus::from_code(self.u8()?)
+                    .ok_or_else(|| invalid("invalid account status"))?;
+                account_statuses.push((trader, status));
+            }
+        }
+        let (mut risk_profiles, mut killed_accounts, mut realized_pnl) =
+            (Vec::new(), Vec::new(), Vec::new());
+        let (mut mmp, mut circuit_breaker, mut trade_journal) = (Vec::new(), None, Vec::new());
+        if version > ARCHIVE_VERSION_V7 {
+            for _ in 0..self.u64()? {
+                risk_profiles.push((self.u64()?, self.risk_profile()?));
+            }
+            for _ in 0..self.u64()? {
+                killed_accounts.push(self.u64()?);
+            }
+            for _ in 0..self.u64()? {
+                realized_pnl.push((self.u64()?, self.i64()?));
+            }
+            for _ in 0..self.u64()? {
+                mmp.push(self.mmp_state()?);
+            }
+            circuit_breaker = match self.u8()? {
+                0 => None,
+                1 => Some(self.market_alert()?),
+                _ => return Err(invalid("invalid option tag")),
+            };
+            for _ in 0..self.u64()? {
+                trade_journal.push(self.trade_record()?);
+            }
+        }
+        let mut recurring = RecurringState::default();
+        if version > ARCHIVE_VERSION_V8 {
+            for _ in 0..self.u64()? {
+                recurring.plans.push(self.recurring_plan()?);
+            }
+            for _ in 0..self.u64()? {
+                recurring.history.push(self.recurring_execution()?);
+            }
+            recurring.next_id = self.u64()?;
+        }
+        let (mut sub_account_links, mut compression_consent, mut mark_price) =
+            (Vec::new(), Vec::new(), None);
+        if version > ARCHIVE_VERSION_V9 {
+            for _ in 0..self.u64()? {
+                sub_account_links.push((self.u64()?, self.u64()?));
+            }
+            for _ in 0..self.u64()? {
+                compression_consent.push(self.u64()?);
+            }
+            mark_price = self.option_u64()?;
+        }
+
+        Ok(EngineSnapshot {
+            sequence,
+            timestamp,
+            trade_id_counter,
+            next_order_id,
+            next_position_id,
+            orders,
+            positions,
+            balances,
+            feature_flags,
+            protections,
+            conditional_last_price,
+            conditional_id_counter,
+            account_settings,
+            setting_log,
+            expiry,
+            collateral,
+            account_statuses,
+            risk_profiles,
+            killed_accounts,
+            realized_pnl,
+            mmp,
+            circuit_breaker,
+            trade_journal,
+            recurring,
+            sub_account_links,
+            compression_consent,
+            mark_price,
+        })
+    }
+
+    fn recurring_plan(&mut self) -> io::Result<RecurringPlan> {
+        let id = self.u64()?;
+        let spec = RecurringSpec {
+            trader: self.u64()?,
+            symbol: String::from_utf8(self.bytes()?.to_vec())
+                .map_err(|_| invalid("symbol is not utf-8"))?,
+            side: self.side()?,
+            quantity: self.u64()?,
+            kind: match self.u8()? {
+                0 => RecurringOrderKind::Market,
+                1 => RecurringOrderKind::Limit {
+                    price: self.u64()?,
+                    time_in_force: self.time_in_force()?,
+                },
+                _ => return Err(invalid("unknown recurring order kind")),
+            },
+            interval: self.u64()?,
+            start_at: self.u64()?,
+            max_runs: self
+                .option_u64()?
+                .map(u32::try_from)
+                .transpose()
+                .map_err(|_| invalid("recurring max runs out of range"))?,
+        };
+        let status = match self.u8()? {
+            0 => RecurringStatus::Active,
+            1 => RecurringStatus::Paused,
+            2 => RecurringStatus::Cancelled,
+            3 => RecurringStatus::Completed,
+            _ => return Err(invalid("unknown recurring status")),
+        };
+        Ok(RecurringPlan { id, spec, status, runs: self.u32()?, next_run: self.option_u64()? })
+    }
+
+    fn recurring_execution(&mut self) -> io::Result<RecurringExecution> {
+        Ok(RecurringExecution {
+            plan_id: self.u64()?,
+            run: self.u32()?,
+            scheduled_at: self.u64()?,
+            executed_at: self.u64()?,
+            order_id: self.option_u64()?,
+            error_code: self
+                .option_u64()?
+                .map(u32::try_from)
+                .transpose()
+                .map_err(|_| invalid("error code out of range"))?,
+        })
+    }
+
+    fn risk_profile(&mut self) -> io::Result<RiskProfile> {
+        Ok(RiskProfile {
+            kind: match self.u8()? {
+                0 => RiskProfileKind::Standard,
+                1 => RiskProfileKind::MarketMaker,
+                _ => return Err(invalid("unknown risk profile kind")),
+            },
+            max_order_notional: self.u64()?,
+            max_open_orders: self.u64()? as usize,
+            bypass_notional_check: self.u8()? != 0,
+            post_trade: match self.u8()? {
+                0 => None,
+                1 => Some(PostTradeLimits {
+                    max_position_quantity: self.u64()?,
+                    max_realized_loss: self.u64()?,
+                }),
+                _ => return Err(invalid("invalid option tag")),
+            },
+        })
+    }
+
+    fn mmp_state(&mut self) -> io::Result<MmpState> {
+        let trader = self.u64()?;
+        let config = match self.u8()? {
+            0 => None,
+            1 => Some(MmpConfig {
+                window: self.u64()?,
+                fill_limit: self.u32()?,
+                freeze: self.option_u64()?,
+            }),
+            _ => return Err(invalid("invalid option tag")),
+        };
+        let quotes = (0..self.u64()?).map(|_| self.u64()).collect::<io::Result<_>>()?;
+        let fills = (0..self.u64()?).map(|_| self.u64()).collect::<io::Result<_>>()?;
+        let frozen = match self.u8()? {
+            0 => None,
+            1 => Some(self.option_u64()?),
+            _ => return Err(invalid("invalid option tag")),
+        };
+        Ok(MmpState { trader, config, quotes, fills, frozen })
+    }
+
+    fn market_alert(&mut self) -> io::Result<MarketAlert> {
+        let kind = match self.u8()? {
+            0 => MarketAlertKind::PriceSpike {
+                feed: self.price_feed()?,
+                price: self.u64()?,
+                mean: self.u64()?,
+                lower: self.u64()?,
+                upper: self.u64()?,
+            },
+            1 => MarketAlertKind::StaleFeed { feed: self.price_feed()?, last_update: self.u64()? },
+            2 => MarketAlertKind::CrossedBook { best_bid: self.u64()?, best_ask: self.u64()? },
+            _ => return Err(invalid("unknown market alert")),
+        };
+        Ok(MarketAlert { kind, raised_at: self.u64()? })
+    }
+
+    fn price_feed(&mut self) -> io::Result<PriceFeed> {
+        match self.u8()? {
+            0 => Ok(PriceFeed::Trade),
+            1 => Ok(PriceFeed::Mark),
+            _ => Err(invalid("unknown price feed")),
+        }
+    }
+
+    fn trade_record(&mut self) -> io::Result<TradeRecord> {
+        Ok(TradeRecord {
+            trade_id: self.u64()?,
+            price: self.u64()?,
+            quantity: self.u64()?,
+            taker: self.trade_leg()?,
+            maker: self.trade_leg()?,
+            timestamp: self.u64()?,
+        })
+    }
+
+    fn trade_leg(&mut self) -> io::Result<TradeLeg> {
+        Ok(TradeLeg {
+            trader: self.u64()?,
+            side: self.side()?,
+            position_side: self.position_side()?,
+            opened: self.u8()? != 0,
+            quantity: self.u64()?,
+            entry_price_before: self.u64()?,
+            realized_pnl: self.i64()?,
+        })
+    }
+
+    fn setting_record(&mut self) -> io::Result<AccountSettingRecord> {
+        let (trader, sequence, timestamp, version) =
+            (self.u64()?, self.u64()?, self.u64()?, self.u64()?);
+        let change = match self.u8()? {
+            0 => AccountSettingChange::Leverage {
+                position_side: match self.u8()? {
+                    0 => None,
+                    1 => Some(self.position_side()?),
+                    _ => return Err(invalid("invalid option tag")),
+                },
+                from: self.u32()?,
+                to: self.u32()?,
+            },
+            1 => AccountSettingChange::MarginMode {
+                from: self.margin_mode()?,
+                to: self.margin_mode()?,
+            },
+            2 => AccountSettingChange::Margin {
+                position_id: self.u64()?,
+                from: self.u64()?,
+                to: self.u64()?,
+            },
+            _ => return Err(invalid("unknown account setting change")),
+        };
+        Ok(AccountSettingRecord { trader, change, sequence, timestamp, version })
+    }
+
+    fn option_u64(&mut self) -> io::Result<Option<u64>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u64()?)),
+            _ => Err(invalid("invalid option tag")),
+        }
+    }
+
+    fn protection(&mut self) -> io::Result<ConditionalEntry<Price, CloseOrder>> {
+        let id = self.u64()?;
+        let state = match self.u8()? {
+            0 => ConditionalState::Armed { direction: self.direction()?, trigger: self.u64()? },
+            1 => ConditionalState::Tracking {
+                direction: self.direction()?,
+                trail: self.trail()?,
+                reference: self.u64()?,
+                trigger: self.u64()?,
+            },
+            2 => ConditionalState::Pending {
+                direction: self.direction()?,
+                trail: self.trail()?,
+                activation: self.u64()?,
+            },
+            3 => {
+                ConditionalState::Unanchored { direction: self.direction()?, trail: self.trail()? }
+            }
+            _ => return Err(invalid("unknown conditional state")),
+        };
+        let action = CloseOrder {
+            trader: self.u64()?,
+            position_id: self.u64()?,
+            kind: match self.u8()? {
+                0 => ProtectionKind::StopLoss,
+                1 => ProtectionKind::TakeProfit,
+                2 => ProtectionKind::TrailingStop,
+                _ => return Err(invalid("unknown protection kind")),
+            },
+            close_price: self.option_u64()?,
+        };
+        let sibling = match self.u8()? {
+            0 => None,
+            1 => Some(Sibling::Conditional(self.u64()?)),
+            2 => Some(Sibling::External(self.u64()?)),
+            _ => return Err(invalid("unknown sibling kind")),
+        };
+        Ok(ConditionalEntry { id, state, action, sibling })
+    }
+
+    fn direction(&mut self) -> io::Result<Direction> {
+        match self.u8()? {
+            0 => Ok(Direction::Rising),
+            1 => Ok(Direction::Falling),
+            _ => Err(invalid("unknown trigger direction")),
+        }
+    }
+
+    fn trail(&mut self) -> io::Result<Trail<Price>> {
+        match self.u8()? {
+            0 => Ok(Trail::Absolute(self.u64()?)),
+            1 => Ok(Trail::Bps(self.u32()?)),
+            _ => Err(invalid("unknown trail kind")),
+        }
+    }
+
+    fn order(&mut self) -> io::Result<Order> {
+        Ok(Order {
+            id: self.u64()?,
+            trader: self.u64()?,
+            side: self.side()?,
+            price: self.u64()?,
+            original_quantity: self.u64()?,
+            remaining_quantity: self.u64()?,
+            filled_quantity: self.u64()?,
+            cumulative_quote: self.u64()?,
+            position_side: self.position_side()?,
+            reduce_only: self.u8()? != 0,
+            time_in_force: self.time_in_force()?,
+            status: match self.u8()? {
+                0 => OrderStatus::New,
+                1 => OrderStatus::PartiallyFilled,
+                2 => OrderStatus::Filled,
+                3 => OrderStatus::Cancelled,
+                4 => OrderStatus::Rejected,
+                5 => OrderStatus::Expired,
+                _ => return Err(invalid("unknown order status")),
+            },
+            created_at: self.u64()?,
+            updated_at: self.u64()?,
+        })
+    }
+
+    fn time_in_force(&mut self) -> io::Result<TimeInForce> {
+        match self.u8()? {
+            0 => Ok(TimeInForce::GTC),
+            1 => Ok(TimeInForce::IOC),
+            2 => Ok(TimeInForce::FOK),
+            3 => Ok(TimeInForce::GTD { expire_time: self.u64()? }),
+            4 => Ok(TimeInForce::PostOnly),
+            _ => Err(invalid("unknown time in force")),
+        }
+    }
+
+    fn position(&mut self) -> io::Result<Position> {
+        Ok(Position {
+            id: self.u64()?,
+            trader: self.u64()?,
+            position_side: self.position_side()?,
+            quantity: self.u64()?,
+            entry_price: self.u64()?,
+            margin_mode: self.margin_mode()?,
+            leverage: self.u32()?,
+            margin: self.u64()?,
+            unrealized_pnl: self.i64()?,
+            realized_pnl: self.i64()?,
+            liquidation_price: self.u64()?,
+            created_at: self.u64()?,
+            updated_at: self.u64()?,
+        })
+    }
+
+    fn side(&mut self) -> io::Result<Side> {
+        match self.u8()? {
+            0 => Ok(Side::Buy),
+            1 => Ok(Side::Sell),
+            _ => Err(invalid("unknown side")),
+        }
+    }
+
+    fn position_side(&mut self) -> io::Result<PositionSide> {
+        match self.u8()? {
+            0 => Ok(PositionSide::Both),
+            1 => Ok(PositionSide::Long),
+            2 => Ok(PositionSide::Short),
+            _ => Err(invalid("unknown position side")),
+        }
+    }
+
+    fn margin_mode(&mut self) -> io::Result<MarginMode> {
+        match self.u8()? {
+            0 => Ok(MarginMode::Cross),
+            1 => Ok(MarginMode::Isolated),
+            _ => Err(invalid("unknown margin mode")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::adaptor::outbound::journal::Durability;
+    use crate::domain::entity::TraderId;
+    use crate::domain::repository::BalanceReader;
+    use crate::domain::service::{Command, PrepCommandHandler, PrepQueryHandler};
+
+    struct Balances;
+
+    impl BalanceReader for Balances {
+        fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance> {
+            vec![AssetBalance::new("USDT", trader * 1000, 0)]
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prep-snap-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn engine() -> Engine {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    fn limit(trader: TraderId, side: Side, price: u64, quantity: u64) -> Command {
+        let position_side =
+            if side == Side::Buy { PositionSide::Long } else { PositionSide::Short };
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTD { expire_time: 99 },
+        }
+    }
+
+    /// 不过期的限价单（保护单跨越多笔成交）
+    fn gtc(trader: TraderId, side: Side, price: u64, quantity: u64) -> Command {
+        let position_side =
+            if side == Side::Buy { PositionSide::Long } else { PositionSide::Short };
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    /// 先写日志再撮合
+    fn execute(engine: &mut Engine, journal: &mut Journal, command: Command) {
+        let timestamp = 1_000 + engine.sequence();
+        let payload = CommandRecord { timestamp, command: command.clone() }.encode();
+        let sequence = journal.append(&payload).unwrap();
+        engine.set_timestamp(timestamp);
+        engine.handle(command);
+        assert_eq!(engine.sequence(), sequence);
+    }
+
+    #[test]
+    fn test_capture_and_restore() {
+        let root = temp_dir("restore");
+        let source_journal = root.join("source");
+        let (mut journal, _) =
+            Journal::open(JournalConfig::new(&source_journal, Durability::Batch)).unwrap();
+
+        let mut source = engine();
+        execute(&mut source, &mut journal, limit(1, Side::Sell, 100, 10));
+        execute(&mut source, &mut journal, limit(2, Side::Buy, 100, 4));
+        execute(&mut source, &mut journal, limit(3, Side::Buy, 90, 5));
+        let snapshot = source.snapshot(&Balances);
+        assert_eq!(snapshot.sequence, 3);
+        execute(&mut source, &mut journal, limit(4, Side::Buy, 100, 1));
+        execute(&mut source, &mut journal, limit(5, Side::Buy, 100, 1));
+        journal.commit().unwrap();
+
+        let archive = SnapshotArchive::capture(snapshot, &source_journal, Some(4)).unwrap();
+        assert_eq!(archive.journal_tail.len(), 1);
+        assert_eq!(archive.journal_tail[0].0, 4);
+
+        let path = root.join("node.snapshot");
+        archive.write_to(&path).unwrap();
+        let archive = SnapshotArchive::read_from(&path).unwrap();
+        assert_eq!(archive.snapshot.balances.len(), 3);
+        assert_eq!(archive.snapshot.balances[1].1[0].available, 2000);
+
+        let target_journal = JournalConfig::new(root.join("target"), Durability::Batch);
+        let (mut restored, mut journal) = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                target_journal.clone(),
+            )
+            .unwrap();
+        assert_eq!(journal.append(b"cmd-5").unwrap(), 5);
+
+        // 尾部已重放：恢复到序列号 4，与源引擎当时的状态一致，且订单ID继续递增
+        assert_eq!(restored.sequence(), 4);
+        let queue = restored.queue_position(1, 1).unwrap();
+        assert_eq!(queue.remaining_quantity, 5);
+        assert_eq!(
+            restored.account_snapshot(4, Some(100), &Balances).positions[0].quantity,
+            source.account_snapshot(4, Some(100), &Balances).positions[0].quantity
+        );
+        assert!(matches!(
+            restored.handle(limit(5, Side::Buy, 100, 1)),
+            crate::domain::service::CommandResult::LimitOrder { order_id: 5, .. }
+        ));
+
+        // 目标日志目录非空时拒绝恢复
+        let err = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                target_journal,
+            )
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_bootstrap_from_journal() {
+        let root = temp_dir("bootstrap");
+        let (mut journal, _) = Journal::open(JournalConfig::new(&root, Durability::Batch)).unwrap();
+        let mut source = engine();
+        execute(&mut source, &mut journal, limit(1, Side::Sell, 100, 10));
+        execute(&mut source, &mut journal, limit(2, Side::Buy, 100, 4));
+        execute(&mut source, &mut journal, limit(3, Side::Buy, 100, 6));
+        journal.commit().unwrap();
+
+        let archive = SnapshotArchive::bootstrap(
+            InMemoryOrderRepository::new(),
+            InMemoryPositionRepository::new(),
+            &root,
+            Some(2),
+            &Balances,
+        )
+        .unwrap();
+        assert_eq!(archive.snapshot.sequence, 2);
+        assert_eq!(archive.snapshot.timestamp, 1_001);
+        assert_eq!(archive.snapshot.orders.len(), 1);
+        assert_eq!(archive.snapshot.orders[0].remaining_quantity, 6);
+        assert!(archive.journal_tail.is_empty());
+
+        // 不可解码的记录中止引导
+        journal.append(b"garbage").unwrap();
+        journal.commit().unwrap();
+        let err = SnapshotArchive::bootstrap(
+            InMemoryOrderRepository::new(),
+            InMemoryPositionRepository::new(),
+            &root,
+            None,
+            &Balances,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_protections_survive_restore() {
+        let root = temp_dir("protections");
+        let position_of = |engine: &Engine, trader| {
+            engine.account_snapshot(trader, None, &Balances).positions.first().map(|p| p.id)
+        };
+
+        // 多头止损 90 / 止盈 130 互为 OCO，空头追踪止损回调 10%
+        let mut source = engine();
+        source.handle(gtc(3, Side::Sell, 100, 10));
+        source.handle(gtc(1, Side::Buy, 100, 10));
+        let long = position_of(&source, 1).unwrap();
+        let short = position_of(&source, 3).unwrap();
+        source.handle(Command::SetStopLoss {
+            trader: 1,
+            position_id: long,
+            trigger_price: 90,
+            close_price: None,
+        });
+        source.handle(Command::SetTakeProfit {
+            trader: 1,
+            position_id: long,
+            trigger_price: 130,
+            close_price: Some(130),
+        });
+        source.handle(Command::TrailingStop {
+            trader: 3,
+            position_id: short,
+            callback_rate: 1000,
+            activation_price: None,
+        });
+
+        let snapshot = source.snapshot(&Balances);
+        assert_eq!(snapshot.protections.len(), 3);
+        let archive = SnapshotArchive { snapshot, journal_tail: vec![] };
+        let archive = SnapshotArchive::decode(&archive.encode()).unwrap();
+        let (mut restored, _) = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                JournalConfig::new(&root, Durability::Batch),
+            )
+            .unwrap();
+        assert_eq!(
+            restored.snapshot(&Balances).protections,
+            source.snapshot(&Balances).protections
+        );
+
+        // 同样的行情在两个引擎上触发同样的平仓：成交价 89 止损平多，反弹到 97 追踪止损平空
+        for engine in [&mut source, &mut restored] {
+            engine.handle(gtc(4, Side::Buy, 89, 20));
+            engine.handle(gtc(5, Side::Sell, 89, 1));
+            assert_eq!(position_of(engine, 1), None);
+            assert!(position_of(engine, 3).is_some());
+            engine.handle(gtc(6, Side::Sell, 97, 20));
+            engine.handle(gtc(7, Side::Buy, 97, 1));
+            assert_eq!(position_of(engine, 3), None);
+        }
+        assert_eq!(
+            restored.snapshot(&Balances).protections,
+            source.snapshot(&Balances).protections
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_account_settings_and_expiry_survive_restore() {
+        let root = temp_dir("settings");
+        let mut source = engine();
+        source.handle(Command::SwitchMarginMode { trader: 1, mode: MarginMode::Isolated });
+        source.handle(Command::SetLeverage { trader: 1, leverage: 20, position_side: None });
+        source.handle(Command::SetLeverage { trader: 2, leverage: 5, position_side: None });
+        source.handle(Command::SetExpiry { expiry: Some(5_000), operator: "ops".to_string() });
+        source.handle(Command::SetCollateral { trader: 2, collateral: 600_000 });
+        source.handle(Command::SetAccountStatus {
+            trader: 3,
+            status: AccountStatus::Suspended,
+            reason: "KYC review".to_string(),
+            operator: "ops".to_string(),
+        });
+
+        let archive =
+            SnapshotArchive { snapshot: source.snapshot(&Balances), journal_tail: vec![] };
+        let archive = SnapshotArchive::decode(&archive.encode()).unwrap();
+        assert_eq!(archive.snapshot.account_settings.len(), 2);
+        assert_eq!(archive.snapshot.setting_log.len(), 3);
+        assert_eq!(archive.snapshot.collateral, [(2, 600_000)]);
+        let (mut restored, _) = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                JournalConfig::new(&root, Durability::Batch),
+            )
+            .unwrap();
+        assert_eq!(restored.expiry(), Some(5_000));
+        assert_eq!(restored.risk().status(3), AccountStatus::Suspended);
+        for trader in [1, 2, 3] {
+            assert_eq!(restored.account_settings(trader), source.account_settings(trader));
+            assert_eq!(restored.setting_history(trader, 10), source.setting_history(trader, 10));
+        }
+
+        // 恢复后的变更版本号接着快照继续
+        let command = Command::SetLeverage { trader: 1, leverage: 25, position_side: None };
+        source.handle(command.clone());
+        restored.handle(command);
+        assert_eq!(restored.setting_history(1, 1)[0].version, 3);
+        assert_eq!(restored.setting_history(1, 1), source.setting_history(1, 1));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_risk_mmp_halt_and_trade_journal_survive_restore() {
+        use crate::domain::entity::QuoteEntry;
+        use crate::domain::service::{AnomalyConfig, CommandResult};
+
+        let root = temp_dir("risk");
+        let mut source = engine();
+        source.set_timestamp(1_000);
+        let operator = || "ops".to_string();
+        source.handle(Command::SetRiskProfile {
+            trader: 1,
+            profile: RiskProfile::market_maker(
+                10,
+                PostTradeLimits { max_position_quantity: 1_000, max_realized_loss: 50 },
+            ),
+            operator: operator(),
+        });
+        source.handle(Command::SetRiskProfile {
+            trader: 5,
+            profile: RiskProfile::standard().with_limits(1_000_000, 1),
+            operator: operator(),
+        });
+
+        // 多头 100 开仓、90 平仓，亏损 100 超过限额，账户 1 熔断
+        source.handle(gtc(2, Side::Sell, 100, 10));
+        source.handle(gtc(1, Side::Buy, 100, 10));
+        source.handle(gtc(3, Side::Buy, 90, 10));
+        source.handle(Command::LimitOrder {
+            trader: 1,
+            side: Side::Sell,
+            price: 90,
+            quantity: 10,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        });
+        assert!(source.risk().is_killed(1));
+
+        // 报价成交一笔即撤回全部报价并冻结（需手动解除）
+        let config = MmpConfig { window: 1_000, fill_limit: 1, freeze: None };
+        source.handle(Command::SetMmp { trader: 4, config: Some(config) });
+        source.handle(Command::MassQuote {
+            trader: 4,
+            quotes: vec![QuoteEntry::two_sided(80, 5, 120, 5)],
+        });
+        source.handle(gtc(6, Side::Sell, 80, 1));
+        assert!(source.mmp().is_frozen(4, 1_000));
+
+        // 标记价格突破 sigma 带，市场熔断
+        source.enable_anomaly_detection(AnomalyConfig {
+            min_samples: 3,
+            auto_halt: true,
+            ..AnomalyConfig::default()
+        });
+        for mark_price in [100, 100, 100, 150] {
+            source.handle(Command::UpdateMarkPrice { mark_price });
+        }
+        assert!(source.is_halted());
+
+        let expected = source.snapshot(&Balances);
+        assert_eq!(expected.trade_journal.len(), 3);
+        let archive = SnapshotArchive { snapshot: expected.clone(), journal_tail: vec![] };
+        let archive = SnapshotArchive::decode(&archive.encode()).unwrap();
+        let (mut restored, _) = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                JournalConfig::new(&root, Durability::Batch),
+            )
+            .unwrap();
+        let actual = restored.snapshot(&Balances);
+        assert_eq!(actual.risk_profiles, expected.risk_profiles);
+        assert_eq!(actual.killed_accounts, [1]);
+        assert_eq!(actual.realized_pnl, expected.realized_pnl);
+        assert_eq!(actual.mmp, expected.mmp);
+        assert_eq!(actual.circuit_breaker, expected.circuit_breaker);
+        assert_eq!(actual.trade_journal, expected.trade_journal);
+
+        assert!(restored.is_halted());
+        assert!(matches!(
+            restored.handle(Command::ResumeTrading { operator: operator() }),
+            CommandResult::ResumeTrading { success: true }
+        ));
+        assert!(restored.mmp().is_frozen(4, 1_000));
+        assert!(matches!(
+            restored.handle(Command::MassQuote {
+                trader: 4,
+                quotes: vec![QuoteEntry::two_sided(80, 5, 120, 5)],
+            }),
+            CommandResult::Error { .. }
+        ));
+        assert!(matches!(restored.handle(gtc(1, Side::Buy, 50, 1)), CommandResult::Error { .. }));
+        assert!(matches!(
+            restored.handle(gtc(5, Side::Buy, 50, 1)),
+            CommandResult::LimitOrder { .. }
+        ));
+        assert!(matches!(restored.handle(gtc(5, Side::Buy, 49, 1)), CommandResult::Error { .. }));
+
+        // 快照前的报价成交仍可撤销
+        let trade_id = expected.trade_journal[2].trade_id;
+        assert!(matches!(
+            restored.handle(Command::BustTrade {
+                trade_id,
+                reason: "erroneous".to_string(),
+                operator: operator(),
+            }),
+            CommandResult::BustTrade { .. }
+        ));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_recurring_plans_survive_restore() {
+        use crate::domain::service::CommandResult;
+
+        let root = temp_dir("recurring");
+        let source_journal = root.join("source");
+        let (mut journal, _) =
+            Journal::open(JournalConfig::new(&source_journal, Durability::Batch)).unwrap();
+        let spec = |max_runs| RecurringSpec {
+            trader: 1,
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            quantity: 1,
+            kind: RecurringOrderKind::Limit { price: 90, time_in_force: TimeInForce::GTC },
+            interval: 2,
+            start_at: 1_000,
+            max_runs,
+        };
+
+        // execute 的时间戳为 1000 + 已处理序列号
+        let mut source = engine();
+        execute(&mut source, &mut journal, Command::CreateRecurringPlan { spec: spec(Some(3)) });
+        execute(&mut source, &mut journal, Command::CreateRecurringPlan { spec: spec(None) });
+        execute(&mut source, &mut journal, Command::RunRecurringPlans);
+        execute(&mut source, &mut journal, Command::PauseRecurringPlan { trader: 1, plan_id: 2 });
+        let snapshot = source.snapshot(&Balances);
+        assert_eq!(snapshot.recurring.plans.len(), 2);
+        assert_eq!(snapshot.recurring.history.len(), 2);
+        execute(&mut source, &mut journal, Command::RunRecurringPlans);
+        execute(&mut source, &mut journal, Command::ResumeRecurringPlan { trader: 1, plan_id: 2 });
+        journal.commit().unwrap();
+
+        let archive = SnapshotArchive::capture(snapshot, &source_journal, None).unwrap();
+        let archive = SnapshotArchive::decode(&archive.encode()).unwrap();
+        let (mut restored, _) = archive
+            .restore(
+                InMemoryOrderRepository::new(),
+                InMemoryPositionRepository::new(),
+                JournalConfig::new(root.join("target"), Durability::Batch),
+            )
+            .unwrap();
+        assert_eq!(restored.snapshot(&Balances).recurring, source.snapshot(&Balances).recurring);
+
+        // 恢复后继续按原计划执行：计划 1 完成第 3 期，计划 2 从恢复后的下一期继续
+        restored.set_timestamp(1_006);
+        let CommandResult::RunRecurringPlans { executions, .. } =
+            restored.handle(Command::RunRecurringPlans)
+        else {
+            panic!("expected recurring run");
+        };
+        let runs: Vec<_> = executions.iter().map(|e| (e.plan_id, e.run, e.error_code)).collect();
+        assert_eq!(runs, vec![(1, 3, None), (2, 2, None)]);
+        assert_eq!(restored.recurring().get(1).unwrap().status, RecurringStatus::Completed);
+        assert_eq!(restored.recurring().history(2).count(), 2);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sub_account_groups_survive_restore() {
+        let mut source = engine();
+        for (sub_account, parent) in [(2, 100), (1, 100), (3, 200)] {
+            source.handle(Command::LinkSubAccount { sub_account, parent: Some(parent) });
+        }
+        source.handle(Command::LinkSubAccount { sub_account: 3, parent: None });
+        for sub_account in [2, 1] {
+            source.handle(Command::SetCompressionConsent { sub_account, consent: true });
+        }
+        source.handle(Command::UpdateMarkPrice { mark_price: 105 });
+
+        let archive =
+            SnapshotArchive { snapshot: source.snapshot(&Balances), journal_tail: vec![] };
+        let decoded = SnapshotArchive::decode(&archive.encode()).unwrap();
+        assert_eq!(decoded.snapshot.sub_account_links, [(1, 100), (2, 100)]);
+        assert_eq!(decoded.snapshot.compression_consent, [1, 2]);
+        assert_eq!(decoded.snapshot.mark_price, Some(105));
+
+        let restored = MatchingService::restore(
+            InMemoryOrderRepository::new(),
+            InMemoryPositionRepository::new(),
+            decoded.snapshot,
+        )
+        .unwrap();
+        assert_eq!(restored.sub_accounts(), source.sub_accounts());
+        assert!(restored.sub_accounts().can_compress(1, 2));
+        assert_eq!(restored.mark_price(), Some(105));
+    }
+
+    #[test]
+    fn test_corrupted_archive_is_rejected() {
+        let archive =
+            SnapshotArchive { snapshot: engine().snapshot(&Balances), journal_tail: vec![] };
+        let mut bytes = archive.encode();
+        assert!(SnapshotArchive::decode(&bytes).is_ok());
+
+        let last = bytes.len() - 5;
+        bytes[last] ^= 1;
+        assert_eq!(
+            SnapshotArchive::decode(&bytes).err().unwrap().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(SnapshotArchive::decode(&bytes[..10]).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! 回放分歧定位工具
+//!
+//! ```text
+//! prep_divergence compare <journal-dir> <until-sequence> [archive]
+//! prep_divergence trace <journal-dir> <until-sequence> <out-trace> [archive]
+//! prep_divergence diff <left-trace> <right-trace>
+//! ```
+//!
+//! - `compare`：同一构建的两个引擎并行回放，报告第一处分歧（排查不确定性）
+//! - `trace`：回放并写出逐条输出摘要，分别用两个构建执行
+//! - `diff`：比较两个构建的摘要轨迹，报告第一处分歧（排查回归）
+//!
+//! 指定快照归档时从快照序列号之后开始回放，否则从日志起点开始
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use prep::adaptor::outbound::command_codec::CommandRecord;
+use prep::adaptor::outbound::divergence::{
+    Divergence, first_divergence, read_records, read_trace, replay_digests, replay_pair,
+    write_trace,
+};
+use prep::adaptor::outbound::snapshot::SnapshotArchive;
+use prep::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+use prep::domain::service::{EngineSnapshot, MatchingService};
+
+const USAGE: &str = "usage:
+  prep_divergence compare <journal-dir> <until-sequence> [archive]
+  prep_divergence trace <journal-dir> <until-sequence> <out-trace> [archive]
+  prep_divergence diff <left-trace> <right-trace>";
+
+type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["compare", journal, until] => compare(journal, until, None),
+        ["compare", journal, until, archive] => compare(journal, until, Some(archive)),
+        ["trace", journal, until, out] => trace(journal, until, out, None),
+        ["trace", journal, until, out, archive] => trace(journal, until, out, Some(archive)),
+        ["diff", left, right] => diff(left, right),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(None) => ExitCode::SUCCESS,
+        Ok(Some(divergence)) => {
+            print!("{}", divergence);
+            ExitCode::FAILURE
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 起始快照（None 为空引擎）
+fn base_snapshot(archive: Option<&str>) -> Result<Option<EngineSnapshot>, String> {
+    archive
+        .map(|path| {
+            SnapshotArchive::read_from(Path::new(path))
+                .map(|archive| archive.snapshot)
+                .map_err(|e| format!("read {}: {}", path, e))
+        })
+        .transpose()
+}
+
+fn build(snapshot: Option<EngineSnapshot>) -> Engine {
+    let (orders, positions) = (InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+    match snapshot {
+        // 归档已通过解码校验，恢复到空仓储不会失败
+        Some(snapshot) => MatchingService::restore(orders, positions, snapshot)
+            .unwrap_or_else(|e| panic!("restore snapshot: {:?}", e)),
+        None => MatchingService::new(orders, positions),
+    }
+}
+
+fn load(
+    journal: &str,
+    until: &str,
+    snapshot: &Option<EngineSnapshot>,
+) -> Result<Vec<(u64, CommandRecord)>, String> {
+    let until: u64 = until.parse().map_err(|_| format!("invalid sequence: {}", until))?;
+    let from = snapshot.as_ref().map_or(1, |snapshot| snapshot.sequence + 1);
+    let records = read_records(Path::new(journal), from, Some(until))
+        .map_err(|e| format!("read journal {}: {}", journal, e))?;
+    println!("replaying {} records from sequence {}", records.len(), from);
+    Ok(records)
+}
+
+fn compare(
+    journal: &str,
+    until: &str,
+    archive: Option<&str>,
+) -> Result<Option<Divergence>, String> {
+    let snapshot = base_snapshot(archive)?;
+    let records = load(journal, until, &snapshot)?;
+    let (left, right) =
+        replay_pair(&records, || build(snapshot.clone()), || build(snapshot.clone()))
+            .map_err(|e| format!("replay: {}", e))?;
+    let divergence = first_divergence(&left, &right);
+    if divergence.is_none() {
+        println!("no divergence in {} steps", left.len());
+    }
+    Ok(divergence)
+}
+
+fn trace(
+    journal: &str,
+    until: &str,
+    out: &str,
+    archive: Option<&str>,
+) -> Result<Option<Divergence>, String> {
+    let snapshot = base_snapshot(archive)?;
+    let records = load(journal, until, &snapshot)?;
+    let digests =
+        replay_digests(&mut build(snapshot), &records).map_err(|e| format!("replay: {}", e))?;
+    write_trace(Path::new(out), &digests).map_err(|e| format!("write {}: {}", out, e))?;
+    println!("wrote {} digests to {}", digests.len(), out);
+    Ok(None)
+}
+
+fn diff(left: &str, right: &str) -> Result<Option<Divergence>, String> {
+    let read =
+        |path: &str| read_trace(Path::new(path)).map_err(|e| format!("read {}: {}", path, e));
+    let (left, right) = (read(left)?, read(right)?);
+    let divergence = first_divergence(&left, &right);
+    if divergence.is_none() {
+        println!("no divergence in {} steps", left.len());
+    }
+    Ok(divergence)
+}
@@ -0,0 +1,126 @@
+//! 快照运维工具
+//!
+//! ```text
+//! prep_snapshot inspect <archive>
+//! prep_snapshot capture <base-archive> <journal-dir> <out-archive> [until-sequence]
+//! prep_snapshot bootstrap <journal-dir> <out-archive> [until-sequence]
+//! prep_snapshot restore <archive> <journal-dir>
+//! ```
+//!
+//! - `inspect`：查看归档内容
+//! - `capture`：以已有归档的快照为基准，重新截取日志尾部（可指定截止序列号，做时间点恢复）
+//! - `bootstrap`：还没有归档时，从序列号 1 起重放完整日志生成第一个归档；
+//!   余额不在命令日志中，归档的余额为空，由账户服务在恢复后重新同步
+//! - `restore`：在新节点上重建引擎并重放日志尾部，写入本地日志
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use prep::adaptor::outbound::journal::{Durability, JournalConfig};
+use prep::adaptor::outbound::snapshot::SnapshotArchive;
+use prep::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+use prep::domain::entity::{AssetBalance, TraderId};
+use prep::domain::repository::BalanceReader;
+
+const USAGE: &str = "usage:
+  prep_snapshot inspect <archive>
+  prep_snapshot capture <base-archive> <journal-dir> <out-archive> [until-sequence]
+  prep_snapshot bootstrap <journal-dir> <out-archive> [until-sequence]
+  prep_snapshot restore <archive> <journal-dir>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["inspect", archive] => inspect(Path::new(archive)),
+        ["capture", base, journal, out] => capture(base, journal, out, None),
+        ["capture", base, journal, out, until] => match until.parse() {
+            Ok(until) => capture(base, journal, out, Some(until)),
+            Err(_) => Err(format!("invalid sequence: {}", until)),
+        },
+        ["bootstrap", journal, out] => bootstrap(journal, out, None),
+        ["bootstrap", journal, out, until] => match until.parse() {
+            Ok(until) => bootstrap(journal, out, Some(until)),
+            Err(_) => Err(format!("invalid sequence: {}", until)),
+        },
+        ["restore", archive, journal] => restore(Path::new(archive), Path::new(journal)),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<SnapshotArchive, String> {
+    SnapshotArchive::read_from(path).map_err(|e| format!("read {}: {}", path.display(), e))
+}
+
+fn inspect(path: &Path) -> Result<(), String> {
+    let archive = read(path)?;
+    let snapshot = &archive.snapshot;
+    println!("sequence:        {}", snapshot.sequence);
+    println!("timestamp:       {}", snapshot.timestamp);
+    println!("resting orders:  {}", snapshot.orders.len());
+    println!("positions:       {}", snapshot.positions.len());
+    println!("traders:         {}", snapshot.balances.len());
+    match (archive.journal_tail.first(), archive.journal_tail.last()) {
+        (Some((first, _)), Some((last, _))) => println!("journal tail:    {}..={}", first, last),
+        _ => println!("journal tail:    empty"),
+    }
+    Ok(())
+}
+
+fn capture(base: &str, journal: &str, out: &str, until: Option<u64>) -> Result<(), String> {
+    let snapshot = read(Path::new(base))?.snapshot;
+    let archive = SnapshotArchive::capture(snapshot, Path::new(journal), until)
+        .map_err(|e| format!("read journal {}: {}", journal, e))?;
+    archive.write_to(Path::new(out)).map_err(|e| format!("write {}: {}", out, e))?;
+    println!("captured {} journal records into {}", archive.journal_tail.len(), out);
+    Ok(())
+}
+
+/// 引导归档不含余额
+struct NoBalances;
+
+impl BalanceReader for NoBalances {
+    fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+        Vec::new()
+    }
+}
+
+fn bootstrap(journal: &str, out: &str, until: Option<u64>) -> Result<(), String> {
+    let archive = SnapshotArchive::bootstrap(
+        InMemoryOrderRepository::new(),
+        InMemoryPositionRepository::new(),
+        Path::new(journal),
+        until,
+        &NoBalances,
+    )
+    .map_err(|e| format!("replay journal {}: {}", journal, e))?;
+    archive.write_to(Path::new(out)).map_err(|e| format!("write {}: {}", out, e))?;
+    println!(
+        "bootstrapped snapshot at sequence {} into {} (balances not captured)",
+        archive.snapshot.sequence, out
+    );
+    Ok(())
+}
+
+fn restore(path: &Path, journal: &Path) -> Result<(), String> {
+    let archive = read(path)?;
+    let config = JournalConfig::new(journal, Durability::Batch);
+    let (engine, journal) = archive
+        .restore(InMemoryOrderRepository::new(), InMemoryPositionRepository::new(), config)
+        .map_err(|e| format!("restore: {}", e))?;
+    println!(
+        "restored snapshot at sequence {}, replayed {} journal records to sequence {}, journal continues at {}",
+        archive.snapshot.sequence,
+        archive.journal_tail.len(),
+        engine.sequence(),
+        journal.next_sequence()
+    );
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! 账户杠杆与保证金设置，及其变更记录
+//!
+//! 变更记录按账户保留最近 [`SETTING_HISTORY_LIMIT`] 条，对外以 `diff` 变更日志条目
+//! （[`EntityReplayableEvent`]）的形式提供：实体为账户设置，版本号按账户逐条递增
+
+use diff::{EntityReplayableEvent, ReplayFieldChange};
+
+use super::types::{Leverage, Margin, MarginMode, PositionId, PositionSide, Timestamp, TraderId};
+
+/// 最大杠杆倍数
+pub const MAX_LEVERAGE: Leverage = 125;
+
+/// 每个账户保留的设置变更记录条数（更早的记录只在命令日志中）
+pub const SETTING_HISTORY_LIMIT: usize = 256;
+
+/// 变更日志条目中账户设置的实体类型标签
+pub const ACCOUNT_SETTING_ENTITY: u8 = 1;
+
+/// 变更日志字段类型：字符串
+const FIELD_STRING: u8 = 0;
+/// 变更日志字段类型：整数
+const FIELD_INT: u8 = 1;
+
+/// 账户设置（新开仓位沿用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSettings {
+    /// 杠杆倍数
+    pub leverage: Leverage,
+    /// 保证金模式
+    pub margin_mode: MarginMode,
+}
+
+/// 设置变更内容（变更前后的值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountSettingChange {
+    /// 调整杠杆
+    Leverage {
+        /// 持仓方向（None=双边）
+        position_side: Option<PositionSide>,
+        from: Leverage,
+        to: Leverage,
+    },
+    /// 切换保证金模式
+    MarginMode { from: MarginMode, to: MarginMode },
+    /// 调整逐仓保证金
+    Margin { position_id: PositionId, from: Margin, to: Margin },
+}
+
+/// 账户设置变更记录（只追加，不可修改）
+///
+/// 只记录实际生效的变更，被拒绝或前后值相同的命令不产生记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSettingRecord {
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 变更内容
+    pub change: AccountSettingChange,
+    /// 命令序列号
+    pub sequence: u64,
+    /// 变更时间
+    pub timestamp: Timestamp,
+    /// 变更后的账户设置版本号（账户第一条变更为 1）
+    pub version: u64,
+}
+
+impl AccountSettingRecord {
+    /// 转为变更日志条目
+    ///
+    /// 变更字段之外，按仓位或持仓方向生效的变更附带前后相同的上下文字段
+    /// （`positionId` / `positionSide`）
+    pub fn change_log_entry(&self) -> EntityReplayableEvent {
+        let mut entry = EntityReplayableEvent::new_updated(
+            self.timestamp,
+            self.sequence,
+            self.version - 1,
+            self.version,
+            self.trader as i64,
+            ACCOUNT_SETTING_ENTITY,
+        );
+        let field = |name: &str, from: &str, to: &str, field_type| {
+            let name = ReplayFieldChange::field_name_from_str(name);
+            ReplayFieldChange::new(name, from.as_bytes(), to.as_bytes(), field_type)
+        };
+        match self.change {
+            AccountSettingChange::Leverage { position_side, from, to } => {
+                if let Some(side) = position_side {
+                    let side = position_side_name(side);
+                    entry.add_field_change(field("positionSide", side, side, FIELD_STRING));
+                }
+                entry.add_field_change(field(
+                    "leverage",
+                    &from.to_string(),
+                    &to.to_string(),
+                    FIELD_INT,
+                ));
+            }
+            AccountSettingChange::MarginMode { from, to } => {
+                entry.add_field_change(field(
+                    "marginMode",
+                    margin_mode_name(from),
+                    margin_mode_name(to),
+                    FIELD_STRING,
+                ));
+            }
+            AccountSettingChange::Margin { position_id, from, to } => {
+                let position_id = position_id.to_string();
+                entry.add_field_change(field("positionId", &position_id, &position_id, FIELD_INT));
+                entry.add_field_change(field(
+                    "isolatedMargin",
+                    &from.to_string(),
+                    &to.to_string(),
+                    FIELD_INT,
+                ));
+            }
+        }
+        entry
+    }
+}
+
+fn margin_mode_name(mode: MarginMode) -> &'static str {
+    match mode {
+        MarginMode::Cross => "CROSSED",
+        MarginMode::Isolated => "ISOLATED",
+    }
+}
+
+fn position_side_name(side: PositionSide) -> &'static str {
+    match side {
+        PositionSide::Long => "LONG",
+        PositionSide::Short => "SHORT",
+        PositionSide::Both => "BOTH",
+    }
+}
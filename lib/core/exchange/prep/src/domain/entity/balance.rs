@@ -0,0 +1,26 @@
+//! 资产余额视图
+
+/// 资产余额视图
+///
+/// 由账户/结算模块提供，撮合引擎只读，用于组装账户快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetBalance {
+    /// 资产代码（如 USDT）
+    pub asset: String,
+    /// 可用余额
+    pub available: u64,
+    /// 冻结余额
+    pub frozen: u64,
+}
+
+impl AssetBalance {
+    /// 创建余额视图
+    pub fn new(asset: impl Into<String>, available: u64, frozen: u64) -> Self {
+        Self { asset: asset.into(), available, frozen }
+    }
+
+    /// 总余额
+    pub fn total(&self) -> u64 {
+        self.available + self.frozen
+    }
+}
@@ -0,0 +1,86 @@
+//! 引擎事件流
+//!
+//! 撮合引擎在处理命令时按发生顺序产出事件，读侧投影据此维护查询模型，
+//! 查询无需再访问撮合路径上的仓储
+
+use super::account_setting::AccountSettingRecord;
+use super::execution_report::ExecutionReport;
+use super::market_alert::MarketAlert;
+use super::order::Order;
+use super::position::Position;
+use super::quote::MmpTrigger;
+use super::recurring::{RecurringExecution, RecurringPlan};
+use super::trade_bust::TradeRecord;
+use super::types::{Margin, PositionId, PositionSide, Price, Quantity, Timestamp, TraderId};
+
+/// 系统按固定价格平仓的结算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementType {
+    /// 子账户仓位压缩（按标记价格对冲）
+    Compression,
+    /// 交割合约到期交割（按交割价格平掉全部持仓）
+    FutureDelivery,
+    /// 成交撤销冲正：开仓腿按成交价退回数量并按比例释放保证金，
+    /// 减仓腿加回数量并冲回已实现盈亏（`realized_pnl` 为冲正金额）
+    TradeBust,
+}
+
+/// 一个仓位的系统平仓结算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionSettlement {
+    pub settlement_type: SettlementType,
+    pub trader: TraderId,
+    pub position_id: PositionId,
+    pub position_side: PositionSide,
+    /// 平仓数量
+    pub quantity: Quantity,
+    /// 平仓前开仓均价
+    pub entry_price: Price,
+    /// 结算价格
+    pub price: Price,
+    /// 已实现盈亏
+    pub realized_pnl: i64,
+    /// 释放的保证金
+    pub released_margin: Margin,
+    pub settled_at: Timestamp,
+}
+
+/// 引擎事件
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    /// 订单挂入订单簿（含已部分成交的剩余部分）
+    OrderAccepted(Order),
+    /// 订单状态迁移
+    Execution(ExecutionReport),
+    /// 成交
+    Trade(TradeRecord),
+    /// 成交被撤销
+    TradeBusted(TradeRecord),
+    /// 仓位变更后的最新状态
+    PositionChanged(Position),
+    /// 仓位已平
+    PositionClosed { trader: TraderId, position_side: PositionSide },
+    /// 系统按结算价格平仓（压缩、到期交割）
+    PositionSettled(PositionSettlement),
+    /// 行情异常告警
+    MarketAlert(MarketAlert),
+    /// 账户杠杆、保证金模式或逐仓保证金变更
+    AccountSettingChanged(AccountSettingRecord),
+    /// 做市商保护触发，报价已撤回
+    MmpTriggered(MmpTrigger),
+    /// 定投计划登记或状态变更后的最新状态
+    RecurringPlanChanged(RecurringPlan),
+    /// 定投执行一期
+    RecurringExecuted(RecurringExecution),
+}
+
+/// 带序列号的事件
+///
+/// `sequence` 为产生事件的命令序列号，同一命令的多个事件序列号相同
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    /// 命令序列号
+    pub sequence: u64,
+    /// 事件
+    pub event: EngineEvent,
+}
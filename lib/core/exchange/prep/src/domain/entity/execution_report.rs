@@ -0,0 +1,68 @@
+//! 执行回报
+
+use super::order::Order;
+use super::types::{OrderId, OrderStatus, Price, Quantity, Side, Timestamp, TraderId};
+
+/// 执行回报
+///
+/// 每次订单状态迁移生成一条（即订单状态变更事件），携带累计成交统计，
+/// 客户端无需自行聚合成交记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// 订单ID
+    pub order_id: OrderId,
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 方向
+    pub side: Side,
+    /// 原状态
+    pub from: OrderStatus,
+    /// 新状态
+    pub status: OrderStatus,
+    /// 本次成交价格（非成交事件为 0）
+    pub last_fill_price: Price,
+    /// 本次成交数量（非成交事件为 0）
+    pub last_fill_quantity: Quantity,
+    /// 累计成交数量
+    pub cumulative_filled_quantity: Quantity,
+    /// 成交均价
+    pub avg_fill_price: Price,
+    /// 剩余数量
+    pub remaining_quantity: Quantity,
+    /// 事件时间
+    pub timestamp: Timestamp,
+}
+
+impl ExecutionReport {
+    /// 成交回报（`from` 为迁移前状态，订单已完成迁移）
+    pub fn fill(
+        order: &Order,
+        from: OrderStatus,
+        last_fill_price: Price,
+        last_fill_quantity: Quantity,
+    ) -> Self {
+        Self {
+            order_id: order.id,
+            trader: order.trader,
+            side: order.side,
+            from,
+            status: order.status,
+            last_fill_price,
+            last_fill_quantity,
+            cumulative_filled_quantity: order.filled_quantity,
+            avg_fill_price: order.avg_fill_price(),
+            remaining_quantity: order.remaining_quantity,
+            timestamp: order.updated_at,
+        }
+    }
+
+    /// 非成交状态变更回报（取消、过期等）
+    pub fn status_change(order: &Order, from: OrderStatus) -> Self {
+        Self::fill(order, from, 0, 0)
+    }
+
+    /// 是否为成交回报
+    pub fn is_fill(&self) -> bool {
+        self.last_fill_quantity > 0
+    }
+}
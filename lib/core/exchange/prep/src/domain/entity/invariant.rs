@@ -0,0 +1,70 @@
+//! 不变量违例记录
+//!
+//! 抽样检查发现的状态不一致，附带被检查对象的完整快照，供审计与告警排查
+
+use super::balance::AssetBalance;
+use super::order::Order;
+use super::position::Position;
+use super::types::{Margin, Price, Timestamp, TraderId};
+
+/// 不变量类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvariantKind {
+    /// 剩余数量 + 已成交数量 != 原始数量
+    OrderQuantity,
+    /// 簿上订单状态与剩余数量不符（可成交但剩余为 0）
+    OrderStatus,
+    /// 订单不在所属价格档位的队列中
+    OrderIndexed,
+    /// 盘口交叉（最优买价不低于最优卖价）
+    BookCrossed,
+    /// 仓位数量为 0 却未移除
+    PositionQuantity,
+    /// 按交易者与持仓方向查不到该仓位
+    PositionIndexed,
+    /// 冻结保证金低于挂单最坏情况保证金
+    FrozenMargin,
+}
+
+impl InvariantKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvariantKind::OrderQuantity => "ORDER_QUANTITY",
+            InvariantKind::OrderStatus => "ORDER_STATUS",
+            InvariantKind::OrderIndexed => "ORDER_INDEXED",
+            InvariantKind::BookCrossed => "BOOK_CROSSED",
+            InvariantKind::PositionQuantity => "POSITION_QUANTITY",
+            InvariantKind::PositionIndexed => "POSITION_INDEXED",
+            InvariantKind::FrozenMargin => "FROZEN_MARGIN",
+        }
+    }
+}
+
+/// 违例现场
+#[derive(Debug, Clone)]
+pub enum ViolationContext {
+    Order(Order),
+    Position(Position),
+    Book {
+        best_bid: Price,
+        best_ask: Price,
+    },
+    Balance {
+        trader: TraderId,
+        balances: Vec<AssetBalance>,
+        /// 挂单最坏情况保证金
+        required_margin: Margin,
+    },
+}
+
+/// 不变量违例
+#[derive(Debug, Clone)]
+pub struct InvariantViolation {
+    pub kind: InvariantKind,
+    /// 检查时的引擎序列号
+    pub sequence: u64,
+    pub detected_at: Timestamp,
+    /// 可读描述
+    pub detail: String,
+    pub context: ViolationContext,
+}
@@ -0,0 +1,61 @@
+//! 行情异常告警与市场熔断记录
+
+use super::types::{Price, Timestamp};
+
+/// 被监控的价格流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceFeed {
+    /// 成交价
+    Trade,
+    /// 标记价格
+    Mark,
+}
+
+/// 告警类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketAlertKind {
+    /// 价格突破 sigma 带
+    PriceSpike {
+        feed: PriceFeed,
+        /// 触发价格
+        price: Price,
+        /// 窗口均价
+        mean: Price,
+        /// 允许区间下沿
+        lower: Price,
+        /// 允许区间上沿
+        upper: Price,
+    },
+    /// 价格流长时间未更新
+    StaleFeed {
+        feed: PriceFeed,
+        /// 最后一次更新时间
+        last_update: Timestamp,
+    },
+    /// 盘口交叉（最优买价不低于最优卖价）
+    CrossedBook { best_bid: Price, best_ask: Price },
+}
+
+/// 行情异常告警
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketAlert {
+    pub kind: MarketAlertKind,
+    pub raised_at: Timestamp,
+}
+
+/// 市场熔断记录（只追加，恢复交易时补记操作员与时间）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerRecord {
+    /// 触发熔断的告警
+    pub alert: MarketAlert,
+    /// 恢复交易的操作员（熔断中为 None）
+    pub resumed_by: Option<String>,
+    pub resumed_at: Option<Timestamp>,
+}
+
+impl CircuitBreakerRecord {
+    /// 是否仍在熔断中
+    pub fn is_active(&self) -> bool {
+        self.resumed_at.is_none()
+    }
+}
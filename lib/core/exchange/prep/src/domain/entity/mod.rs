@@ -1,11 +1,31 @@
 //! Domain entities
 
+mod account_setting;
+mod balance;
+mod engine_event;
+mod execution_report;
+mod invariant;
+mod market_alert;
 mod order;
 mod position;
+mod quote;
+mod recurring;
+mod risk_profile;
 mod trade;
+mod trade_bust;
 mod types;
 
+pub use account_setting::*;
+pub use balance::*;
+pub use engine_event::*;
+pub use execution_report::*;
+pub use invariant::*;
+pub use market_alert::*;
 pub use order::*;
 pub use position::*;
+pub use quote::*;
+pub use recurring::*;
+pub use risk_profile::*;
 pub use trade::*;
+pub use trade_bust::*;
 pub use types::*;
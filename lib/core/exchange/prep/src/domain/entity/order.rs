@@ -4,6 +4,17 @@ use super::types::{
     OrderId, OrderStatus, PositionSide, Price, Quantity, Side, TimeInForce, Timestamp, TraderId,
 };
 
+/// 非法状态迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderTransitionError {
+    /// 订单ID
+    pub order_id: OrderId,
+    /// 当前状态
+    pub from: OrderStatus,
+    /// 目标状态
+    pub to: OrderStatus,
+}
+
 /// 订单实体
 #[derive(Debug, Clone)]
 pub struct Order {
@@ -21,6 +32,8 @@ pub struct Order {
     pub remaining_quantity: Quantity,
     /// 已成交数量
     pub filled_quantity: Quantity,
+    /// 累计成交金额（价格 × 数量）
+    pub cumulative_quote: u64,
     /// 持仓方向
     pub position_side: PositionSide,
     /// 只减仓
@@ -56,6 +69,7 @@ impl Order {
             original_quantity: quantity,
             remaining_quantity: quantity,
             filled_quantity: 0,
+            cumulative_quote: 0,
             position_side,
             reduce_only,
             time_in_force,
@@ -65,24 +79,53 @@ impl Order {
         }
     }
 
-    /// 成交（部分或全部）
-    pub fn fill(&mut self, quantity: Quantity, timestamp: Timestamp) {
+    /// 成交（部分或全部），返回迁移前的状态
+    pub fn fill(
+        &mut self,
+        quantity: Quantity,
+        price: Price,
+        timestamp: Timestamp,
+    ) -> Result<OrderStatus, OrderTransitionError> {
         let fill_qty = quantity.min(self.remaining_quantity);
+        let next = if fill_qty == self.remaining_quantity {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+
+        let from = self.transition_to(next, timestamp)?;
         self.filled_quantity += fill_qty;
         self.remaining_quantity -= fill_qty;
-        self.updated_at = timestamp;
+        self.cumulative_quote += fill_qty * price;
+        Ok(from)
+    }
 
-        if self.remaining_quantity == 0 {
-            self.status = OrderStatus::Filled;
-        } else {
-            self.status = OrderStatus::PartiallyFilled;
-        }
+    /// 取消订单，返回迁移前的状态
+    pub fn cancel(&mut self, timestamp: Timestamp) -> Result<OrderStatus, OrderTransitionError> {
+        self.transition_to(OrderStatus::Cancelled, timestamp)
     }
 
-    /// 取消订单
-    pub fn cancel(&mut self, timestamp: Timestamp) {
-        self.status = OrderStatus::Cancelled;
+    /// 状态迁移，返回迁移前的状态
+    ///
+    /// 非法迁移返回错误且不修改订单；迁移事件由 [`ExecutionReport`](super::ExecutionReport) 发布
+    pub fn transition_to(
+        &mut self,
+        next: OrderStatus,
+        timestamp: Timestamp,
+    ) -> Result<OrderStatus, OrderTransitionError> {
+        let from = self.status;
+        if !from.can_transition_to(next) {
+            return Err(OrderTransitionError { order_id: self.id, from, to: next });
+        }
+
+        self.status = next;
         self.updated_at = timestamp;
+        Ok(from)
+    }
+
+    /// 成交均价（未成交为 0）
+    pub fn avg_fill_price(&self) -> Price {
+        self.cumulative_quote.checked_div(self.filled_quantity).unwrap_or(0)
     }
 
     /// 是否可成交
@@ -103,3 +146,61 @@ impl Order {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_order(quantity: Quantity) -> Order {
+        Order::new(
+            1,
+            7,
+            Side::Buy,
+            50000,
+            quantity,
+            PositionSide::Long,
+            false,
+            TimeInForce::GTC,
+            1000,
+        )
+    }
+
+    #[test]
+    fn test_fill_transitions() {
+        let mut order = new_order(100);
+
+        assert_eq!(order.fill(40, 50000, 2000), Ok(OrderStatus::New));
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+
+        assert_eq!(order.fill(30, 50100, 3000), Ok(OrderStatus::PartiallyFilled));
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+
+        assert_eq!(order.fill(30, 50200, 4000), Ok(OrderStatus::PartiallyFilled));
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.remaining_quantity, 0);
+        assert_eq!(order.updated_at, 4000);
+        // (40*50000 + 30*50100 + 30*50200) / 100
+        assert_eq!(order.avg_fill_price(), 50090);
+    }
+
+    #[test]
+    fn test_terminal_state_rejects_transition() {
+        let mut order = new_order(100);
+        order.fill(100, 50000, 2000).unwrap();
+
+        // 已成交订单不可取消，也不可再成交
+        let err = order.cancel(3000).unwrap_err();
+        assert_eq!((err.from, err.to), (OrderStatus::Filled, OrderStatus::Cancelled));
+        assert!(order.fill(10, 50000, 3000).is_err());
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled_quantity, 100);
+        assert_eq!(order.updated_at, 2000);
+    }
+
+    #[test]
+    fn test_new_cannot_return_to_new() {
+        assert!(!OrderStatus::New.can_transition_to(OrderStatus::New));
+        assert!(OrderStatus::New.can_transition_to(OrderStatus::Rejected));
+        assert!(!OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::Rejected));
+    }
+}
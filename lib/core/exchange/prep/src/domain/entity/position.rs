@@ -4,6 +4,9 @@ use super::types::{
     Leverage, Margin, MarginMode, PositionId, PositionSide, Price, Quantity, Timestamp, TraderId,
 };
 
+/// 维持保证金率（基点 1/10000）
+pub const MAINTENANCE_MARGIN_RATE_BPS: u64 = 50;
+
 /// 仓位实体
 #[derive(Debug, Clone)]
 pub struct Position {
@@ -109,7 +112,32 @@ impl Position {
 
     /// 更新未实现盈亏
     pub fn update_unrealized_pnl(&mut self, mark_price: Price) {
-        self.unrealized_pnl = self.calc_pnl(self.quantity, mark_price);
+        self.unrealized_pnl = self.unrealized_pnl_at(mark_price);
+    }
+
+    /// 按标记价格计算未实现盈亏（不修改仓位）
+    pub fn unrealized_pnl_at(&self, mark_price: Price) -> i64 {
+        self.calc_pnl(self.quantity, mark_price)
+    }
+
+    /// 按标记价格的维持保证金
+    pub fn maintenance_margin(&self, mark_price: Price) -> Margin {
+        self.quantity.saturating_mul(mark_price).saturating_mul(MAINTENANCE_MARGIN_RATE_BPS) / 10000
+    }
+
+    /// 保证金余额：保证金 + 按标记价格的未实现盈亏
+    pub fn margin_balance(&self, mark_price: Price) -> i64 {
+        self.margin as i64 + self.unrealized_pnl_at(mark_price)
+    }
+
+    /// 保证金率（基点）：维持保证金 / 保证金余额，达到 10000 即应强平；
+    /// 保证金余额耗尽时为 `u64::MAX`
+    pub fn margin_ratio_bps(&self, mark_price: Price) -> u64 {
+        let balance = self.margin_balance(mark_price);
+        if balance <= 0 {
+            return u64::MAX;
+        }
+        (self.maintenance_margin(mark_price) as u128 * 10000 / balance as u128) as u64
     }
 
     /// 计算强平价格
@@ -119,9 +147,8 @@ impl Position {
         leverage: Leverage,
         _margin_mode: MarginMode,
     ) -> Price {
-        // 简化公式：强平价 = 开仓价 * (1 ± 1/杠杆 * 维持保证金率)
-        // 维持保证金率假设为 0.5%
-        let maintenance_margin_rate = 50u64; // 0.5% = 50/10000
+        // 简化公式：强平价 = 开仓价 * (1 ∓ (1/杠杆 - 维持保证金率))
+        let maintenance_margin_rate = MAINTENANCE_MARGIN_RATE_BPS;
         let leverage_factor = 10000 / leverage as u64;
 
         match position_side {
@@ -138,14 +165,78 @@ impl Position {
         }
     }
 
+    /// 退回一笔开仓成交（成交撤销时使用），返回释放的保证金
+    ///
+    /// 按成交价移出数量，均价还原为 (数量·均价 − 成交量·成交价) / (数量 − 成交量)，
+    /// 保证金按移出数量的比例释放，不产生已实现盈亏
+    pub fn remove_fill(
+        &mut self,
+        quantity: Quantity,
+        price: Price,
+        timestamp: Timestamp,
+    ) -> Margin {
+        let quantity = quantity.min(self.quantity);
+        let released =
+            (self.margin as u128 * quantity as u128 / self.quantity.max(1) as u128) as Margin;
+        let remaining = self.quantity - quantity;
+        if remaining > 0 {
+            let value = (self.entry_price as u128 * self.quantity as u128)
+                .saturating_sub(price as u128 * quantity as u128);
+            self.entry_price = (value / remaining as u128) as Price;
+        }
+        self.quantity = remaining;
+        self.margin -= released;
+        self.updated_at = timestamp;
+        if remaining > 0 {
+            self.update_liquidation_price();
+        }
+        released
+    }
+
+    /// 调整杠杆（保证金不变，重算强平价）
+    pub fn set_leverage(&mut self, leverage: Leverage, timestamp: Timestamp) {
+        self.leverage = leverage;
+        self.updated_at = timestamp;
+        self.update_liquidation_price();
+    }
+
+    /// 设置保证金（逐仓追加/减少保证金），重算强平价
+    pub fn set_margin(&mut self, margin: Margin, timestamp: Timestamp) {
+        self.margin = margin;
+        self.updated_at = timestamp;
+        self.update_liquidation_price();
+    }
+
+    /// 按开仓均价与当前杠杆的起始保证金
+    pub fn initial_margin(&self) -> Margin {
+        self.initial_margin_at(self.leverage)
+    }
+
+    /// 按开仓均价与指定杠杆的起始保证金
+    pub fn initial_margin_at(&self, leverage: Leverage) -> Margin {
+        self.quantity.saturating_mul(self.entry_price) / leverage.max(1) as u64
+    }
+
     /// 更新强平价格
+    ///
+    /// 逐仓仓位超出起始保证金的部分按每单位数量推远强平价
     fn update_liquidation_price(&mut self) {
-        self.liquidation_price = Self::calc_liquidation_price(
+        let base = Self::calc_liquidation_price(
             self.entry_price,
             self.position_side,
             self.leverage,
             self.margin_mode,
         );
+        let buffer = match self.margin_mode {
+            MarginMode::Isolated if self.quantity > 0 => {
+                self.margin.saturating_sub(self.initial_margin()) / self.quantity
+            }
+            _ => 0,
+        };
+        self.liquidation_price = match self.position_side {
+            PositionSide::Long | PositionSide::Both => base.saturating_sub(buffer),
+            PositionSide::Short => base.saturating_add(buffer),
+        };
     }
 
     /// 是否空仓
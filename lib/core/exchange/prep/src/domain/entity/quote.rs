@@ -0,0 +1,78 @@
+//! 做市报价与做市商保护（MMP）
+
+use super::types::{OrderId, Price, Quantity, Timestamp, TraderId};
+
+/// 一档双边报价（数量为 0 表示该方向不报价）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteEntry {
+    /// 买价
+    pub bid_price: Price,
+    /// 买量
+    pub bid_quantity: Quantity,
+    /// 卖价
+    pub ask_price: Price,
+    /// 卖量
+    pub ask_quantity: Quantity,
+}
+
+impl QuoteEntry {
+    /// 双边报价
+    pub fn two_sided(
+        bid_price: Price,
+        bid_quantity: Quantity,
+        ask_price: Price,
+        ask_quantity: Quantity,
+    ) -> Self {
+        Self { bid_price, bid_quantity, ask_price, ask_quantity }
+    }
+
+    /// 报价的买方向 (价格, 数量)
+    pub fn bid(&self) -> Option<(Price, Quantity)> {
+        (self.bid_quantity > 0).then_some((self.bid_price, self.bid_quantity))
+    }
+
+    /// 报价的卖方向 (价格, 数量)
+    pub fn ask(&self) -> Option<(Price, Quantity)> {
+        (self.ask_quantity > 0).then_some((self.ask_price, self.ask_quantity))
+    }
+}
+
+/// 做市商保护配置：`window` 毫秒内报价成交达到 `fill_limit` 笔即撤回全部报价
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MmpConfig {
+    /// 统计窗口（毫秒）
+    pub window: Timestamp,
+    /// 触发笔数
+    pub fill_limit: u32,
+    /// 触发后冻结报价的时长（毫秒，None=需手动解除）
+    pub freeze: Option<Timestamp>,
+}
+
+/// 单个做市账户的保护状态（快照与交接用）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmpState {
+    /// 做市账户
+    pub trader: TraderId,
+    pub config: Option<MmpConfig>,
+    /// 当前报价订单
+    pub quotes: Vec<OrderId>,
+    /// 窗口内的报价成交时间（由旧到新）
+    pub fills: Vec<Timestamp>,
+    /// 冻结中：Some(冻结截止时间，None=需手动解除)
+    pub frozen: Option<Option<Timestamp>>,
+}
+
+/// 做市商保护触发记录（只追加）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmpTrigger {
+    /// 做市账户
+    pub trader: TraderId,
+    /// 窗口内的报价成交笔数
+    pub fills: u32,
+    /// 撤回的报价订单
+    pub pulled: Vec<OrderId>,
+    /// 触发时间
+    pub triggered_at: Timestamp,
+    /// 冻结截止时间（None=需手动解除）
+    pub frozen_until: Option<Timestamp>,
+}
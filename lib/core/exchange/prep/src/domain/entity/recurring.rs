@@ -0,0 +1,99 @@
+//! 定投计划（Recurring Orders / DCA）
+//!
+//! 计划定义、状态与每期执行记录，由撮合引擎维护并随引擎事件发布
+
+use super::types::{OrderId, Price, Quantity, Side, TimeInForce, Timestamp, TraderId};
+
+/// 定投计划ID
+pub type RecurringPlanId = u64;
+
+/// 每期委托类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurringOrderKind {
+    /// 市价
+    Market,
+    /// 限价
+    Limit {
+        /// 委托价格
+        price: Price,
+        /// 有效期
+        time_in_force: TimeInForce,
+    },
+}
+
+/// 定投计划定义
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurringSpec {
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 交易对（网关路由用）
+    pub symbol: String,
+    /// 订单方向
+    pub side: Side,
+    /// 每期数量
+    pub quantity: Quantity,
+    /// 每期委托类型
+    pub kind: RecurringOrderKind,
+    /// 间隔（毫秒）
+    pub interval: Timestamp,
+    /// 首期时间
+    pub start_at: Timestamp,
+    /// 总期数（None=不限）
+    pub max_runs: Option<u32>,
+}
+
+impl RecurringSpec {
+    /// 不早于 `now` 的第一期
+    pub fn next_run_from(&self, now: Timestamp) -> Timestamp {
+        if now <= self.start_at {
+            return self.start_at;
+        }
+        let elapsed = now - self.start_at;
+        self.start_at + elapsed.div_ceil(self.interval) * self.interval
+    }
+}
+
+/// 定投计划状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurringStatus {
+    /// 执行中
+    Active,
+    /// 已暂停
+    Paused,
+    /// 已撤销
+    Cancelled,
+    /// 已完成全部期数
+    Completed,
+}
+
+/// 定投计划
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurringPlan {
+    /// 计划ID
+    pub id: RecurringPlanId,
+    /// 计划定义
+    pub spec: RecurringSpec,
+    /// 状态
+    pub status: RecurringStatus,
+    /// 已执行期数
+    pub runs: u32,
+    /// 下一期时间（仅执行中）
+    pub next_run: Option<Timestamp>,
+}
+
+/// 一期执行记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecurringExecution {
+    /// 计划ID
+    pub plan_id: RecurringPlanId,
+    /// 期次（从 1 开始）
+    pub run: u32,
+    /// 计划时间
+    pub scheduled_at: Timestamp,
+    /// 实际执行时间
+    pub executed_at: Timestamp,
+    /// 提交的订单ID（被拒绝为 None）
+    pub order_id: Option<OrderId>,
+    /// 拒绝原因（错误码）
+    pub error_code: Option<u32>,
+}
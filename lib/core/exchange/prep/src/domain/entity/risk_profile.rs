@@ -0,0 +1,193 @@
+//! 账户风控档案
+
+use super::types::{Quantity, Timestamp, TraderId};
+
+/// 风控档案类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskProfileKind {
+    /// 普通账户
+    Standard,
+    /// 指定做市商
+    MarketMaker,
+}
+
+/// 事后监控限额
+///
+/// 任一限额被突破即触发熔断（kill switch）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostTradeLimits {
+    /// 最大持仓数量（多空合计）
+    pub max_position_quantity: Quantity,
+    /// 最大已实现亏损
+    pub max_realized_loss: u64,
+}
+
+/// 账户风控档案
+///
+/// 默认不设事前限额，由管理员按账户显式配置（[`RiskProfile::with_limits`]）；
+/// 做市商可跳过部分同步事前检查（如单笔名义价值上限），
+/// 代价是启用更严格的事后监控与自动熔断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RiskProfile {
+    /// 档案类型
+    pub kind: RiskProfileKind,
+    /// 单笔委托名义价值上限（价格 × 数量，`u64::MAX`=不限）
+    pub max_order_notional: u64,
+    /// 最大挂单数（`usize::MAX`=不限）
+    pub max_open_orders: usize,
+    /// 是否跳过名义价值检查
+    pub bypass_notional_check: bool,
+    /// 事后监控限额（None=不监控）
+    pub post_trade: Option<PostTradeLimits>,
+}
+
+impl RiskProfile {
+    /// 普通账户档案（不设事前限额）
+    pub fn standard() -> Self {
+        Self {
+            kind: RiskProfileKind::Standard,
+            max_order_notional: u64::MAX,
+            max_open_orders: usize::MAX,
+            bypass_notional_check: false,
+            post_trade: None,
+        }
+    }
+
+    /// 做市商档案：跳过名义价值检查，启用事后监控
+    pub fn market_maker(max_open_orders: usize, post_trade: PostTradeLimits) -> Self {
+        Self {
+            kind: RiskProfileKind::MarketMaker,
+            max_order_notional: u64::MAX,
+            max_open_orders,
+            bypass_notional_check: true,
+            post_trade: Some(post_trade),
+        }
+    }
+
+    /// 设置单笔名义价值上限与最大挂单数
+    pub fn with_limits(mut self, max_order_notional: u64, max_open_orders: usize) -> Self {
+        self.max_order_notional = max_order_notional;
+        self.max_open_orders = max_open_orders;
+        self
+    }
+}
+
+impl Default for RiskProfile {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// 账户状态
+///
+/// 暂停禁止新委托，允许撤单与减少逐仓保证金；冻结禁止一切操作，挂单随之撤销
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccountStatus {
+    /// 正常
+    #[default]
+    Active,
+    /// 暂停
+    Suspended,
+    /// 冻结
+    Frozen,
+}
+
+impl AccountStatus {
+    pub const ALL: [AccountStatus; 3] =
+        [AccountStatus::Active, AccountStatus::Suspended, AccountStatus::Frozen];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            AccountStatus::Active => "ACTIVE",
+            AccountStatus::Suspended => "SUSPENDED",
+            AccountStatus::Frozen => "FROZEN",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == s)
+    }
+
+    /// 编码值
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.code() == code)
+    }
+
+    /// 是否允许新委托
+    pub const fn can_trade(self) -> bool {
+        matches!(self, AccountStatus::Active)
+    }
+
+    /// 是否允许撤单
+    pub const fn can_cancel(self) -> bool {
+        matches!(self, AccountStatus::Active | AccountStatus::Suspended)
+    }
+
+    /// 是否允许转出保证金
+    pub const fn can_withdraw(self) -> bool {
+        matches!(self, AccountStatus::Active | AccountStatus::Suspended)
+    }
+}
+
+/// 熔断原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillSwitchReason {
+    /// 持仓超限
+    PositionLimitBreached {
+        /// 当前持仓
+        quantity: Quantity,
+        /// 限额
+        limit: Quantity,
+    },
+    /// 亏损超限
+    LossLimitBreached {
+        /// 已实现盈亏
+        realized_pnl: i64,
+        /// 限额
+        limit: u64,
+    },
+    /// 管理员手动触发
+    Manual,
+}
+
+/// 风控审计动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskAuditAction {
+    /// 变更风控档案
+    ProfileChanged {
+        /// 原档案
+        from: RiskProfile,
+        /// 新档案
+        to: RiskProfile,
+    },
+    /// 触发熔断
+    KillSwitchTriggered(KillSwitchReason),
+    /// 解除熔断
+    KillSwitchReset,
+    /// 变更账户状态
+    StatusChanged {
+        /// 原状态
+        from: AccountStatus,
+        /// 新状态
+        to: AccountStatus,
+    },
+}
+
+/// 风控审计记录（只追加，不可修改）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskAuditEntry {
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 动作
+    pub action: RiskAuditAction,
+    /// 操作员（自动触发为 `system`）
+    pub operator: String,
+    /// 变更原因（账户状态变更必填，其余为空）
+    pub reason: String,
+    /// 时间
+    pub timestamp: Timestamp,
+}
@@ -0,0 +1,57 @@
+//! 成交台账与成交撤销（Trade Bust）记录
+
+use super::types::{PositionSide, Price, Quantity, Side, Timestamp, TradeId, TraderId};
+
+/// 成交台账保留的成交条数，超出后最早的成交移出台账，不再可撤销
+pub const TRADE_JOURNAL_LIMIT: usize = 100_000;
+
+/// 成交一方对仓位的影响
+///
+/// 撤销成交时据此反向恢复仓位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeLeg {
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 方向
+    pub side: Side,
+    /// 持仓方向
+    pub position_side: PositionSide,
+    /// 是否开仓（false=减仓）
+    pub opened: bool,
+    /// 实际作用于仓位的数量（减仓时不超过原持仓）
+    pub quantity: Quantity,
+    /// 成交前开仓均价（无仓位为 0）
+    pub entry_price_before: Price,
+    /// 本次成交产生的已实现盈亏
+    pub realized_pnl: i64,
+}
+
+/// 成交台账记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeRecord {
+    /// 成交ID
+    pub trade_id: TradeId,
+    /// 成交价格
+    pub price: Price,
+    /// 成交数量
+    pub quantity: Quantity,
+    /// Taker 一方
+    pub taker: TradeLeg,
+    /// Maker 一方
+    pub maker: TradeLeg,
+    /// 成交时间
+    pub timestamp: Timestamp,
+}
+
+/// 成交撤销审计记录（只追加，不可修改）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeBustRecord {
+    /// 被撤销的成交
+    pub trade: TradeRecord,
+    /// 撤销原因
+    pub reason: String,
+    /// 操作员
+    pub operator: String,
+    /// 撤销时间
+    pub busted_at: Timestamp,
+}
@@ -34,14 +34,8 @@ pub type Timestamp = u64;
 // 核心枚举
 // ============================================================================
 
-/// 订单方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Side {
-    /// 买入
-    Buy,
-    /// 卖出
-    Sell,
-}
+/// 订单方向（与撮合内核共用）
+pub use match_core::Side;
 
 /// 持仓方向（双向持仓模式）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -109,3 +103,32 @@ pub enum OrderStatus {
     /// 已过期
     Expired,
 }
+
+impl OrderStatus {
+    /// 是否终态
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Filled | Self::Cancelled | Self::Rejected | Self::Expired)
+    }
+
+    /// 状态迁移是否合法
+    ///
+    /// ```text
+    /// New ──┬─> PartiallyFilled ──┬─> Filled
+    ///       │        │ (继续部分成交) │
+    ///       ├────────┴──────────────┼─> Cancelled / Expired
+    ///       ├─> Filled              │
+    ///       └─> Rejected            │
+    /// ```
+    pub fn can_transition_to(self, next: OrderStatus) -> bool {
+        match self {
+            Self::New => !matches!(next, Self::New),
+            Self::PartiallyFilled => {
+                matches!(
+                    next,
+                    Self::PartiallyFilled | Self::Filled | Self::Cancelled | Self::Expired
+                )
+            }
+            Self::Filled | Self::Cancelled | Self::Rejected | Self::Expired => false,
+        }
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! 遵循 Clean Architecture，仓储接口定义在领域层
 
-use crate::domain::entity::{Order, OrderId, Position, PositionId, PositionSide, Price, TraderId};
+use crate::domain::entity::{
+    AssetBalance, Order, OrderId, Position, PositionId, PositionSide, Price, TraderId,
+};
 
 /// 仓储错误
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -49,6 +51,23 @@ pub trait OrderRepository: Send + Sync {
 
     /// 获取卖单（价格从低到高）
     fn get_asks(&self) -> Vec<&Order>;
+
+    /// 获取用户的活跃挂单
+    fn get_orders_by_trader(&self, trader: TraderId) -> Vec<&Order>;
+
+    /// 下一个将分配的订单ID（快照使用）
+    fn peek_next_order_id(&self) -> OrderId;
+
+    /// 设置下一个订单ID（从快照恢复）
+    fn set_next_order_id(&mut self, id: OrderId);
+
+    /// 预分配订单容量（默认不做处理）
+    fn reserve_orders(&mut self, _additional: usize) {}
+
+    /// 已分配的订单容量（未知返回 0）
+    fn order_capacity(&self) -> usize {
+        0
+    }
 }
 
 /// 仓位仓储接口
@@ -81,4 +100,27 @@ pub trait PositionRepository: Send + Sync {
         trader: TraderId,
         position_side: PositionSide,
     ) -> Option<&mut Position>;
+
+    /// 获取用户全部仓位
+    fn get_positions_by_trader(&self, trader: TraderId) -> Vec<&Position>;
+
+    /// 获取全部仓位（按ID排序）
+    fn get_all_positions(&self) -> Vec<&Position>;
+
+    /// 下一个将分配的仓位ID（快照使用）
+    fn peek_next_position_id(&self) -> PositionId;
+
+    /// 设置下一个仓位ID（从快照恢复）
+    fn set_next_position_id(&mut self, id: PositionId);
+
+    /// 预分配仓位容量（默认不做处理）
+    fn reserve_positions(&mut self, _additional: usize) {}
+}
+
+/// 余额读取接口
+///
+/// 由账户/结算模块实现，撮合引擎不持有余额
+pub trait BalanceReader {
+    /// 获取用户全部资产余额
+    fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance>;
 }
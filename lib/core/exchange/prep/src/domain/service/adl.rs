@@ -0,0 +1,89 @@
+//! 自动减仓（ADL）排序
+//!
+//! 穿仓损失无法由保险基金覆盖时，按排序分从高到低对反方向盈利仓位减仓。
+//! 排序分与主流交易所一致：
+//! - 盈利：收益率 × 有效杠杆
+//! - 亏损：收益率 ÷ 有效杠杆
+//!
+//! 其中收益率 = 未实现盈亏 / 保证金，有效杠杆 = 名义价值 / 保证金余额。
+//! 同方向仓位按排序分分为五档（1~5），5 档最先被减仓
+
+use std::collections::HashMap;
+
+use crate::domain::entity::{Position, PositionId, Price};
+
+/// 最高档位
+pub const ADL_MAX_RANK: u8 = 5;
+
+/// 基点换算
+const BPS: i128 = 10000;
+
+/// 仓位的 ADL 排序分（基点）
+pub fn adl_score(position: &Position, mark_price: Price) -> i128 {
+    let pnl = position.unrealized_pnl_at(mark_price) as i128;
+    let margin = (position.margin as i128).max(1);
+    let pnl_bps = pnl * BPS / margin;
+    let balance = position.margin_balance(mark_price) as i128;
+    if balance <= 0 {
+        // 保证金余额耗尽，排在最后
+        return i128::MIN;
+    }
+    let leverage_bps = (position.quantity as i128 * mark_price as i128 * BPS / balance).max(1);
+    if pnl > 0 { pnl_bps * leverage_bps / BPS } else { pnl_bps * BPS / leverage_bps }
+}
+
+/// 全部仓位的 ADL 档位（多空分别排序）
+pub fn adl_ranks<'a>(
+    positions: impl IntoIterator<Item = &'a Position>,
+    mark_price: Price,
+) -> HashMap<PositionId, u8> {
+    let (mut long, mut short): (Vec<_>, Vec<_>) = positions
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| (adl_score(p, mark_price), p.id, p.is_long()))
+        .partition(|(_, _, is_long)| *is_long);
+
+    let mut ranks = HashMap::new();
+    for side in [&mut long, &mut short] {
+        side.sort_unstable();
+        let n = side.len();
+        for (i, (_, id, _)) in side.iter().enumerate() {
+            ranks.insert(*id, ((i + 1) * ADL_MAX_RANK as usize).div_ceil(n) as u8);
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{MarginMode, PositionSide};
+
+    fn position(id: PositionId, side: PositionSide, entry: Price, leverage: u32) -> Position {
+        let margin = 10 * entry / leverage as u64;
+        Position::new(id, id, side, 10, entry, MarginMode::Isolated, leverage, margin, 0)
+    }
+
+    #[test]
+    fn test_adl_ranks_by_profit_and_leverage() {
+        // 标记价格 120：多头均盈利，高杠杆、低成本的排在前面
+        let positions = [
+            position(1, PositionSide::Long, 100, 10),
+            position(2, PositionSide::Long, 100, 2),
+            position(3, PositionSide::Long, 110, 10),
+            position(4, PositionSide::Long, 130, 10),
+            position(5, PositionSide::Long, 125, 20),
+            position(6, PositionSide::Short, 100, 10),
+        ];
+        let ranks = adl_ranks(&positions, 120);
+        assert_eq!(ranks[&1], 5);
+        assert_eq!(ranks[&3], 4);
+        assert_eq!(ranks[&2], 3);
+        // 亏损仓位排在盈利仓位之后；有效杠杆越高，排序分越接近 0
+        assert_eq!(ranks[&4], 1);
+        assert_eq!(ranks[&5], 2);
+        // 空头独立排序
+        assert_eq!(ranks[&6], 5);
+        assert!(adl_score(&positions[0], 120) > adl_score(&positions[1], 120));
+    }
+}
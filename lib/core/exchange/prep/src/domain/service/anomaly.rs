@@ -0,0 +1,241 @@
+//! 行情异常监控
+//!
+//! 监控成交价与标记价格两路价格流：
+//! - 价格突变：偏离最近 `window` 个样本均值超过 `sigma_band` 倍标准差
+//!   （区间半宽至少为均值的 `min_band_bps`，避免价格长期不变时标准差为 0 导致误报）
+//! - 行情停滞：价格流超过 `stale_after` 毫秒没有更新，每次停滞只告警一次
+//! - 盘口交叉：最优买价不低于最优卖价，撮合正确时不会出现，出现即说明订单簿状态损坏
+//!
+//! 检测器只产出告警；由撮合服务写入事件流，并在 `auto_halt` 开启时触发市场熔断
+
+use std::collections::VecDeque;
+
+use crate::domain::entity::{MarketAlert, MarketAlertKind, Price, PriceFeed, Timestamp};
+
+/// 异常监控配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyConfig {
+    /// 均值与标准差的滑动窗口（样本数）
+    pub window: usize,
+    /// 样本数不足时不做突变检测
+    pub min_samples: usize,
+    /// 允许偏离的标准差倍数
+    pub sigma_band: f64,
+    /// 区间半宽下限（基点 1/10000）
+    pub min_band_bps: u32,
+    /// 成交价停滞阈值（毫秒，None=不检测）
+    pub trade_stale_after: Option<u64>,
+    /// 标记价格停滞阈值（毫秒，None=不检测）
+    pub mark_stale_after: Option<u64>,
+    /// 告警时自动触发市场熔断
+    pub auto_halt: bool,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            min_samples: 20,
+            sigma_band: 6.0,
+            min_band_bps: 50,
+            trade_stale_after: None,
+            mark_stale_after: Some(5_000),
+            auto_halt: false,
+        }
+    }
+}
+
+/// 单路价格流的状态
+#[derive(Debug, Clone, Default)]
+struct FeedMonitor {
+    samples: VecDeque<Price>,
+    last_update: Option<Timestamp>,
+    stale_alerted: bool,
+}
+
+impl FeedMonitor {
+    /// 当前窗口允许的价格区间 (均值, 下沿, 上沿)
+    fn band(&self, config: &AnomalyConfig) -> Option<(Price, Price, Price)> {
+        if self.samples.len() < config.min_samples.max(2) {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let mean = self.samples.iter().map(|&p| p as f64).sum::<f64>() / n;
+        let variance = self.samples.iter().map(|&p| (p as f64 - mean).powi(2)).sum::<f64>() / n;
+        let half_width = (variance.sqrt() * config.sigma_band)
+            .max(mean * f64::from(config.min_band_bps) / 10_000.0);
+        Some((
+            mean.round() as Price,
+            (mean - half_width).max(0.0).floor() as Price,
+            (mean + half_width).ceil() as Price,
+        ))
+    }
+}
+
+/// 行情异常检测器
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    trade: FeedMonitor,
+    mark: FeedMonitor,
+    /// 盘口交叉已告警（恢复正常后重置）
+    crossed: bool,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self { config, trade: FeedMonitor::default(), mark: FeedMonitor::default(), crossed: false }
+    }
+
+    pub fn config(&self) -> &AnomalyConfig {
+        &self.config
+    }
+
+    fn feed_mut(&mut self, feed: PriceFeed) -> &mut FeedMonitor {
+        match feed {
+            PriceFeed::Trade => &mut self.trade,
+            PriceFeed::Mark => &mut self.mark,
+        }
+    }
+
+    /// 记录一个价格，突破区间时返回告警（价格仍计入窗口，持续的新价位会逐步成为新均值）
+    pub fn observe(
+        &mut self,
+        feed: PriceFeed,
+        price: Price,
+        timestamp: Timestamp,
+    ) -> Option<MarketAlert> {
+        let config = self.config;
+        let monitor = self.feed_mut(feed);
+        let alert = monitor.band(&config).and_then(|(mean, lower, upper)| {
+            (price < lower || price > upper).then_some(MarketAlert {
+                kind: MarketAlertKind::PriceSpike { feed, price, mean, lower, upper },
+                raised_at: timestamp,
+            })
+        });
+
+        monitor.samples.push_back(price);
+        if monitor.samples.len() > config.window.max(1) {
+            monitor.samples.pop_front();
+        }
+        monitor.last_update = Some(timestamp);
+        monitor.stale_alerted = false;
+        alert
+    }
+
+    /// 检查盘口是否交叉，每次交叉只告警一次
+    pub fn check_book(
+        &mut self,
+        best_bid: Option<Price>,
+        best_ask: Option<Price>,
+        now: Timestamp,
+    ) -> Option<MarketAlert> {
+        let (Some(best_bid), Some(best_ask)) = (best_bid, best_ask) else {
+            self.crossed = false;
+            return None;
+        };
+        if best_bid < best_ask {
+            self.crossed = false;
+            return None;
+        }
+        if std::mem::replace(&mut self.crossed, true) {
+            return None;
+        }
+        Some(MarketAlert {
+            kind: MarketAlertKind::CrossedBook { best_bid, best_ask },
+            raised_at: now,
+        })
+    }
+
+    /// 检查价格流是否停滞（只检查已有过更新的价格流）
+    pub fn check_stale(&mut self, now: Timestamp) -> Vec<MarketAlert> {
+        let mut alerts = Vec::new();
+        for (feed, stale_after) in [
+            (PriceFeed::Trade, self.config.trade_stale_after),
+            (PriceFeed::Mark, self.config.mark_stale_after),
+        ] {
+            let Some(stale_after) = stale_after else {
+                continue;
+            };
+            let monitor = self.feed_mut(feed);
+            let Some(last_update) = monitor.last_update else {
+                continue;
+            };
+            if monitor.stale_alerted || now.saturating_sub(last_update) <= stale_after {
+                continue;
+            }
+            monitor.stale_alerted = true;
+            alerts.push(MarketAlert {
+                kind: MarketAlertKind::StaleFeed { feed, last_update },
+                raised_at: now,
+            });
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> AnomalyDetector {
+        AnomalyDetector::new(AnomalyConfig {
+            window: 10,
+            min_samples: 5,
+            sigma_band: 3.0,
+            min_band_bps: 100,
+            trade_stale_after: Some(1_000),
+            mark_stale_after: None,
+            auto_halt: false,
+        })
+    }
+
+    #[test]
+    fn test_price_spike_outside_band() {
+        let mut detector = detector();
+        for (i, price) in [1000, 1002, 998, 1001, 999].into_iter().enumerate() {
+            assert_eq!(detector.observe(PriceFeed::Trade, price, i as u64), None);
+        }
+        // 标准差过小，区间由 1% 下限决定：[990, 1010]
+        assert_eq!(detector.observe(PriceFeed::Trade, 1009, 5), None);
+        let alert = detector.observe(PriceFeed::Trade, 1100, 6).unwrap();
+        let MarketAlertKind::PriceSpike { feed, price, mean, upper, .. } = alert.kind else {
+            panic!("unexpected alert {:?}", alert);
+        };
+        assert_eq!((feed, price, mean), (PriceFeed::Trade, 1100, 1002));
+        assert!(upper < 1100);
+        // 标记价格独立统计，样本不足不检测
+        assert_eq!(detector.observe(PriceFeed::Mark, 5000, 7), None);
+    }
+
+    #[test]
+    fn test_stale_feed_alerts_once() {
+        let mut detector = detector();
+        assert!(detector.check_stale(10_000).is_empty());
+        detector.observe(PriceFeed::Trade, 1000, 100);
+        detector.observe(PriceFeed::Mark, 1000, 100);
+        assert!(detector.check_stale(1_100).is_empty());
+
+        let alerts = detector.check_stale(1_101);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].kind,
+            MarketAlertKind::StaleFeed { feed: PriceFeed::Trade, last_update: 100 }
+        );
+        assert!(detector.check_stale(5_000).is_empty());
+
+        // 恢复更新后重新计时
+        detector.observe(PriceFeed::Trade, 1000, 5_000);
+        assert_eq!(detector.check_stale(6_001).len(), 1);
+    }
+
+    #[test]
+    fn test_crossed_book_alerts_once() {
+        let mut detector = detector();
+        assert_eq!(detector.check_book(Some(99), Some(100), 1), None);
+        assert!(detector.check_book(Some(100), Some(100), 2).is_some());
+        assert_eq!(detector.check_book(Some(101), Some(100), 3), None);
+        assert_eq!(detector.check_book(Some(99), None, 4), None);
+        assert!(detector.check_book(Some(101), Some(100), 5).is_some());
+    }
+}
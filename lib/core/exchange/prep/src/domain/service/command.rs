@@ -11,9 +11,14 @@
 //! - P3: 扩展功能 (FlashClose, ReversePosition, BatchCancelOrders)
 
 use crate::domain::entity::{
-    Leverage, Margin, MarginMode, OrderId, OrderStatus, PositionId, PositionMode, PositionSide,
-    Price, Quantity, Side, TimeInForce, Trade, TraderId,
+    AccountStatus, Leverage, Margin, MarginMode, MarketAlert, MmpConfig, OrderId, OrderStatus,
+    PositionId, PositionMode, PositionSettlement, PositionSide, Price, Quantity, QuoteEntry,
+    RecurringExecution, RecurringPlanId, RecurringSpec, RiskProfile, Side, TimeInForce, Timestamp,
+    Trade, TradeId, TraderId,
 };
+use crate::domain::service::compression::{CompressionLeg, CompressionSettlement, ParentAccountId};
+use crate::domain::service::feature_flag::{Feature, FlagRule};
+use crate::domain::service::scheduler::ScheduleId;
 
 // ============================================================================
 // P0 - 核心交易命令（统一委托模型）
@@ -88,6 +93,16 @@ pub enum Command {
         amount: i64,
     },
 
+    /// 同步账户可用于保证金的余额（账户服务触发）
+    ///
+    /// 同步过的账户下单与批量报价前按最坏情况保证金预占检查，不足时拒绝
+    SetCollateral {
+        /// 交易者ID
+        trader: TraderId,
+        /// 保证金余额（含已被持仓与挂单占用的部分）
+        collateral: Margin,
+    },
+
     /// 强制平仓（系统触发）
     Liquidate {
         /// 仓位ID
@@ -98,6 +113,48 @@ pub enum Command {
         bankruptcy_price: Price,
     },
 
+    /// 撤销成交（管理员触发）
+    ///
+    /// 反向恢复双方仓位，并记录不可变的审计记录
+    BustTrade {
+        /// 成交ID
+        trade_id: TradeId,
+        /// 撤销原因
+        reason: String,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 设置账户风控档案（管理员）
+    SetRiskProfile {
+        /// 交易者ID
+        trader: TraderId,
+        /// 风控档案
+        profile: RiskProfile,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 解除账户熔断（管理员）
+    ResetKillSwitch {
+        /// 交易者ID
+        trader: TraderId,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 暂停、冻结或恢复账户（管理员）
+    SetAccountStatus {
+        /// 交易者ID
+        trader: TraderId,
+        /// 目标状态
+        status: AccountStatus,
+        /// 变更原因
+        reason: String,
+        /// 操作员
+        operator: String,
+    },
+
     /// 设置止损
     SetStopLoss {
         /// 交易者ID
@@ -209,6 +266,149 @@ pub enum Command {
         /// 持仓方向筛选
         position_side: Option<PositionSide>,
     },
+
+    /// 子账户仓位压缩（管理员/定时作业）
+    ///
+    /// 按结算价格对冲平掉子账户之间相反方向的仓位，全部对冲校验通过才执行
+    CompressPositions {
+        /// 对冲计划
+        legs: Vec<CompressionLeg>,
+        /// 结算价格（标记价格）
+        price: Price,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 登记或解除子账户所属的母账户（账户服务同步，仓位压缩按此复核）
+    LinkSubAccount {
+        /// 子账户
+        sub_account: TraderId,
+        /// 母账户（None=解除）
+        parent: Option<ParentAccountId>,
+    },
+
+    /// 设置子账户是否同意参与仓位压缩（账户服务同步）
+    SetCompressionConsent {
+        /// 子账户
+        sub_account: TraderId,
+        /// 是否同意
+        consent: bool,
+    },
+
+    /// 设置交割合约到期时间（管理员）
+    ///
+    /// 到期时刻起拒绝新委托；已到期后不能再修改
+    SetExpiry {
+        /// 到期时间（None=永续，无到期）
+        expiry: Option<Timestamp>,
+        /// 操作员（随命令日志留痕）
+        operator: String,
+    },
+
+    /// 交割合约到期交割（管理员）
+    ///
+    /// 到期后撤销全部挂单，按交割价格平掉所有持仓
+    DeliverFutures {
+        /// 交割价格
+        delivery_price: Price,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 标记价格更新（系统触发，供行情异常监控）
+    UpdateMarkPrice {
+        /// 标记价格
+        mark_price: Price,
+    },
+
+    /// 解除市场熔断，恢复交易（管理员）
+    ResumeTrading {
+        /// 操作员
+        operator: String,
+    },
+
+    /// 批量报价（做市商）
+    ///
+    /// 原子替换账户全部报价，空报价即撤回全部报价
+    MassQuote {
+        /// 交易者ID
+        trader: TraderId,
+        /// 报价（每档双边）
+        quotes: Vec<QuoteEntry>,
+    },
+
+    /// 设置做市商保护（None=关闭）
+    SetMmp {
+        /// 交易者ID
+        trader: TraderId,
+        /// 保护配置
+        config: Option<MmpConfig>,
+    },
+
+    /// 解除做市商保护冻结
+    ResetMmp {
+        /// 交易者ID
+        trader: TraderId,
+    },
+
+    /// 设置功能开关（管理员，随命令日志回放）
+    SetFeatureFlag {
+        /// 功能
+        feature: Feature,
+        /// 开关规则
+        rule: FlagRule,
+        /// 操作员
+        operator: String,
+    },
+
+    /// 登记定投计划
+    CreateRecurringPlan {
+        /// 计划定义
+        spec: RecurringSpec,
+    },
+
+    /// 暂停定投计划
+    PauseRecurringPlan {
+        /// 交易者ID
+        trader: TraderId,
+        /// 计划ID
+        plan_id: RecurringPlanId,
+    },
+
+    /// 恢复定投计划
+    ResumeRecurringPlan {
+        /// 交易者ID
+        trader: TraderId,
+        /// 计划ID
+        plan_id: RecurringPlanId,
+    },
+
+    /// 撤销定投计划
+    CancelRecurringPlan {
+        /// 交易者ID
+        trader: TraderId,
+        /// 计划ID
+        plan_id: RecurringPlanId,
+    },
+
+    /// 执行到期的定投期次（系统触发）
+    RunRecurringPlans,
+
+    /// 登记定时委托（由分片命令队列挂起，激活时间到达后提交 `order`）
+    ScheduleOrder {
+        /// 限价或市价委托
+        order: Box<Command>,
+        /// 激活时间
+        activate_at: Timestamp,
+    },
+
+    /// 激活前撤销定时委托
+    CancelScheduledOrder {
+        /// 交易者ID
+        trader: TraderId,
+        /// 定时ID
+        schedule_id: ScheduleId,
+    },
 }
 
 // ============================================================================
@@ -242,6 +442,34 @@ pub enum ErrorCode {
     MaxPositionSizeExceeded = 1011,
     /// 会触发强平
     WouldTriggerLiquidation = 1012,
+    /// 成交不存在或已撤销
+    TradeNotFound = 1013,
+    /// 账户已熔断
+    KillSwitchActive = 1014,
+    /// 未满最短挂单时间
+    MinQuoteLifeNotElapsed = 1015,
+    /// 合约已到期，停止交易
+    InstrumentExpired = 1016,
+    /// 合约尚未到期，不能交割
+    InstrumentNotExpired = 1017,
+    /// 市场熔断中，暂停交易
+    CircuitBreakerTripped = 1018,
+    /// 仅逐仓仓位可调整保证金
+    MarginModeMismatch = 1019,
+    /// 有持仓或挂单，不能切换保证金模式
+    PositionOrOrderExists = 1020,
+    /// 做市商保护触发，报价冻结中
+    MmpFrozen = 1021,
+    /// 定投计划不存在
+    RecurringPlanNotFound = 1022,
+    /// 定投计划无效或当前状态不允许该操作
+    InvalidRecurringPlan = 1023,
+    /// 账户已暂停或冻结
+    AccountRestricted = 1024,
+    /// 对冲双方未同属一个母账户或未同意压缩
+    CompressionNotEligible = 1025,
+    /// 定时委托无效（仅支持限价、市价委托，须经分片命令队列登记）
+    InvalidScheduledOrder = 1026,
     /// 系统错误
     SystemError = 9999,
 }
@@ -309,6 +537,14 @@ pub enum CommandResult {
         new_liquidation_price: Price,
     },
 
+    /// 同步保证金余额结果
+    SetCollateral {
+        /// 交易者ID
+        trader: TraderId,
+        /// 保证金余额
+        collateral: Margin,
+    },
+
     /// 强平结果
     Liquidate {
         /// 仓位ID
@@ -321,6 +557,48 @@ pub enum CommandResult {
         insurance_fund_contribution: u64,
     },
 
+    /// 撤销成交结果
+    ///
+    /// 作为更正消息发布到行情与 drop-copy
+    BustTrade {
+        /// 成交ID
+        trade_id: TradeId,
+        /// 成交价格
+        price: Price,
+        /// 撤销数量
+        quantity: Quantity,
+        /// Taker 交易者
+        taker: TraderId,
+        /// Maker 交易者
+        maker: TraderId,
+    },
+
+    /// 设置风控档案结果
+    SetRiskProfile {
+        /// 交易者ID
+        trader: TraderId,
+    },
+
+    /// 解除熔断结果
+    ResetKillSwitch {
+        /// 交易者ID
+        trader: TraderId,
+        /// 是否曾处于熔断
+        success: bool,
+    },
+
+    /// 账户状态变更结果
+    SetAccountStatus {
+        /// 交易者ID
+        trader: TraderId,
+        /// 原状态
+        from: AccountStatus,
+        /// 当前状态
+        to: AccountStatus,
+        /// 状态是否变化
+        success: bool,
+    },
+
     /// 止损结果
     SetStopLoss {
         /// 仓位ID
@@ -441,6 +719,130 @@ pub enum CommandResult {
         order_ids: Vec<OrderId>,
     },
 
+    /// 仓位压缩结果
+    CompressPositions {
+        /// 逐笔对冲结算
+        settlements: Vec<CompressionSettlement>,
+    },
+
+    /// 子账户关系变更结果
+    LinkSubAccount { sub_account: TraderId, parent: Option<ParentAccountId> },
+
+    /// 压缩同意标记变更结果
+    SetCompressionConsent { sub_account: TraderId, consent: bool },
+
+    /// 设置到期时间结果
+    SetExpiry {
+        /// 原到期时间
+        old_expiry: Option<Timestamp>,
+        /// 新到期时间
+        new_expiry: Option<Timestamp>,
+    },
+
+    /// 到期交割结果
+    DeliverFutures {
+        /// 撤销的挂单
+        cancelled_orders: Vec<OrderId>,
+        /// 逐仓位结算
+        settlements: Vec<PositionSettlement>,
+    },
+
+    /// 标记价格更新结果
+    UpdateMarkPrice {
+        /// 标记价格
+        mark_price: Price,
+        /// 本次触发的告警
+        alerts: Vec<MarketAlert>,
+    },
+
+    /// 恢复交易结果
+    ResumeTrading {
+        /// 是否曾处于熔断
+        success: bool,
+    },
+
+    /// 批量报价结果
+    MassQuote {
+        /// 交易者ID
+        trader: TraderId,
+        /// 新挂出的报价订单
+        order_ids: Vec<OrderId>,
+        /// 撤掉的旧报价订单
+        cancelled: Vec<OrderId>,
+    },
+
+    /// 设置做市商保护结果
+    SetMmp {
+        /// 交易者ID
+        trader: TraderId,
+    },
+
+    /// 解除做市商保护冻结结果
+    ResetMmp {
+        /// 交易者ID
+        trader: TraderId,
+        /// 是否曾处于冻结
+        success: bool,
+    },
+
+    /// 设置功能开关结果
+    SetFeatureFlag {
+        /// 功能
+        feature: Feature,
+    },
+
+    /// 登记定投计划结果
+    CreateRecurringPlan {
+        /// 计划ID
+        plan_id: RecurringPlanId,
+        /// 首期时间
+        next_run: Timestamp,
+    },
+
+    /// 暂停定投计划结果
+    PauseRecurringPlan {
+        /// 计划ID
+        plan_id: RecurringPlanId,
+    },
+
+    /// 恢复定投计划结果
+    ResumeRecurringPlan {
+        /// 计划ID
+        plan_id: RecurringPlanId,
+        /// 下一期时间
+        next_run: Timestamp,
+    },
+
+    /// 撤销定投计划结果
+    CancelRecurringPlan {
+        /// 计划ID
+        plan_id: RecurringPlanId,
+    },
+
+    /// 执行定投期次结果
+    RunRecurringPlans {
+        /// 本次执行的期次
+        executions: Vec<RecurringExecution>,
+        /// 期次委托产生的成交
+        trades: Vec<Trade>,
+    },
+
+    /// 登记定时委托结果
+    ScheduleOrder {
+        /// 定时ID
+        schedule_id: ScheduleId,
+        /// 激活时间
+        activate_at: Timestamp,
+    },
+
+    /// 撤销定时委托结果
+    CancelScheduledOrder {
+        /// 定时ID
+        schedule_id: ScheduleId,
+        /// 是否成功（已激活或不属于本账户时为 false）
+        success: bool,
+    },
+
     /// 错误
     Error {
         /// 错误码
@@ -0,0 +1,409 @@
+//! 双通道命令队列
+//!
+//! 撤单与风控命令进入优先通道，先于新订单出队，避免高负载下撤单排在
+//! 新订单之后。为防止新订单被饿死，优先通道连续出队达到上限后，
+//! 若普通通道非空则让出一次
+//!
+//! 出队顺序决定命令序列号，由单线程撮合 worker 消费。开启速度缓冲时，
+//! 主动委托先在缓冲中延迟，到期后再进入普通通道。定时委托在激活时间到达后
+//! 直接进入通道，不再经过速度缓冲。配置最短挂单时间时，未满时间的撤单
+//! 在入队前延迟或拒绝
+
+use std::collections::VecDeque;
+
+use crate::domain::entity::Timestamp;
+use crate::domain::service::command::{Command, CommandResult, ErrorCode, PrepCommandHandler};
+use crate::domain::service::quote_life::{CancelScreen, QuoteLifeConfig, QuoteLifeGuard};
+use crate::domain::service::scheduler::{OrderScheduler, ScheduleId, ScheduledOrder};
+use crate::domain::service::speed_bump::{SpeedBump, SpeedBumpConfig};
+
+/// 命令通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandLane {
+    /// 优先通道（撤单、风控）
+    Priority,
+    /// 普通通道（新订单及其他）
+    Normal,
+}
+
+impl Command {
+    /// 命令所属通道
+    pub fn lane(&self) -> CommandLane {
+        match self {
+            Command::CancelOrder { .. }
+            | Command::BatchCancelOrders { .. }
+            | Command::CancelAllOrders { .. }
+            | Command::Liquidate { .. }
+            | Command::ADL { .. }
+            | Command::AdjustMargin { .. }
+            | Command::SetCollateral { .. }
+            | Command::BustTrade { .. }
+            | Command::SetRiskProfile { .. }
+            | Command::ResetKillSwitch { .. }
+            | Command::SetAccountStatus { .. }
+            | Command::SetMmp { .. }
+            | Command::ResetMmp { .. }
+            | Command::PauseRecurringPlan { .. }
+            | Command::CancelRecurringPlan { .. } => CommandLane::Priority,
+            _ => CommandLane::Normal,
+        }
+    }
+}
+
+/// 通道深度指标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LaneMetrics {
+    /// 优先通道当前深度
+    pub priority_depth: usize,
+    /// 普通通道当前深度
+    pub normal_depth: usize,
+    /// 优先通道深度峰值
+    pub priority_high_watermark: usize,
+    /// 普通通道深度峰值
+    pub normal_high_watermark: usize,
+    /// 优先通道累计出队数
+    pub priority_dequeued: u64,
+    /// 普通通道累计出队数
+    pub normal_dequeued: u64,
+    /// 因公平性上限让出给普通通道的次数
+    pub fairness_yields: u64,
+}
+
+/// 双通道命令队列
+#[derive(Debug)]
+pub struct CommandQueue {
+    /// 优先通道
+    priority: VecDeque<Command>,
+    /// 普通通道
+    normal: VecDeque<Command>,
+    /// 优先通道连续出队上限
+    max_priority_burst: usize,
+    /// 当前优先通道连续出队数
+    priority_streak: usize,
+    /// 指标
+    metrics: LaneMetrics,
+    /// 速度缓冲（None=关闭）
+    speed_bump: Option<SpeedBump>,
+    /// 定时委托
+    scheduler: OrderScheduler,
+    /// 最短挂单时间（None=关闭）
+    quote_life: Option<QuoteLifeGuard>,
+}
+
+impl CommandQueue {
+    /// 默认优先通道连续出队上限
+    pub const DEFAULT_MAX_PRIORITY_BURST: usize = 32;
+
+    /// 创建队列
+    pub fn new() -> Self {
+        Self::with_max_priority_burst(Self::DEFAULT_MAX_PRIORITY_BURST)
+    }
+
+    /// 创建队列，指定优先通道连续出队上限（至少为 1）
+    pub fn with_max_priority_burst(max_priority_burst: usize) -> Self {
+        Self {
+            priority: VecDeque::new(),
+            normal: VecDeque::new(),
+            max_priority_burst: max_priority_burst.max(1),
+            priority_streak: 0,
+            metrics: LaneMetrics::default(),
+            speed_bump: None,
+            scheduler: OrderScheduler::default(),
+            quote_life: None,
+        }
+    }
+
+    /// 开启速度缓冲
+    pub fn with_speed_bump(mut self, config: SpeedBumpConfig) -> Self {
+        self.speed_bump = Some(SpeedBump::new(config));
+        self
+    }
+
+    /// 开启最短挂单时间，出队结果需经 [`drain_at`](Self::drain_at) 记录
+    pub fn with_min_quote_life(mut self, config: QuoteLifeConfig) -> Self {
+        self.quote_life = Some(QuoteLifeGuard::new(config));
+        self
+    }
+
+    /// 带时间戳入队
+    ///
+    /// 开启速度缓冲且为主动委托时进入缓冲，返回 `Ok(None)`；未满最短挂单时间的撤单
+    /// 按策略延迟（`Ok(None)`）或拒绝；否则立即入队
+    pub fn push_at(
+        &mut self,
+        command: Command,
+        now: Timestamp,
+        aggressive: bool,
+    ) -> Result<Option<CommandLane>, ErrorCode> {
+        let command = match self.quote_life.as_mut() {
+            Some(guard) => match guard.screen(command, now) {
+                CancelScreen::Pass(command) => command,
+                CancelScreen::Delayed => return Ok(None),
+                CancelScreen::Rejected(code) => return Err(code),
+            },
+            None => command,
+        };
+        let command = match self.speed_bump.as_mut() {
+            Some(bump) => match bump.submit(command, now, aggressive) {
+                Some(command) => command,
+                None => return Ok(None),
+            },
+            None => command,
+        };
+        Ok(Some(self.push(command)))
+    }
+
+    /// 将速度缓冲与最短挂单时间中到期的命令移入队列，返回移入数量
+    pub fn release_delayed(&mut self, now: Timestamp) -> usize {
+        let mut released = Vec::new();
+        if let Some(bump) = self.speed_bump.as_mut() {
+            released.extend(bump.release_due(now));
+        }
+        if let Some(guard) = self.quote_life.as_mut() {
+            released.extend(guard.release_due(now));
+        }
+        let count = released.len();
+        for command in released {
+            self.push(command);
+        }
+        count
+    }
+
+    /// 速度缓冲与最短挂单时间中延迟的命令数
+    pub fn delayed_len(&self) -> usize {
+        self.speed_bump.as_ref().map_or(0, SpeedBump::pending_len)
+            + self.quote_life.as_ref().map_or(0, QuoteLifeGuard::pending_len)
+    }
+
+    /// 指定定时委托调度器（如从日志恢复的调度器）
+    pub fn with_scheduler(mut self, scheduler: OrderScheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// 登记定时委托，`activate_at` 到达后由 [`release_scheduled`](Self::release_scheduled) 入队
+    pub fn schedule_at(&mut self, command: Command, activate_at: Timestamp) -> ScheduleId {
+        self.scheduler.schedule(command, activate_at)
+    }
+
+    /// 查询等待激活的定时委托
+    pub fn scheduled(&self, id: ScheduleId) -> Option<&ScheduledOrder> {
+        self.scheduler.get(id)
+    }
+
+    /// 激活前撤销定时委托
+    pub fn cancel_scheduled(&mut self, id: ScheduleId) -> Option<ScheduledOrder> {
+        self.scheduler.cancel(id)
+    }
+
+    /// 将到达激活时间的定时委托移入队列，返回激活的定时ID（按激活顺序）
+    pub fn release_scheduled(&mut self, now: Timestamp) -> Vec<ScheduleId> {
+        let released = self.scheduler.release_due(now);
+        let mut ids = Vec::with_capacity(released.len());
+        for order in released {
+            ids.push(order.id);
+            self.push(order.command);
+        }
+        ids
+    }
+
+    /// 等待激活的定时委托数
+    pub fn scheduled_len(&self) -> usize {
+        self.scheduler.pending_len()
+    }
+
+    /// 入队，返回命令所属通道
+    pub fn push(&mut self, command: Command) -> CommandLane {
+        let lane = command.lane();
+        match lane {
+            CommandLane::Priority => {
+                self.priority.push_back(command);
+                self.metrics.priority_high_watermark =
+                    self.metrics.priority_high_watermark.max(self.priority.len());
+            }
+            CommandLane::Normal => {
+                self.normal.push_back(command);
+                self.metrics.normal_high_watermark =
+                    self.metrics.normal_high_watermark.max(self.normal.len());
+            }
+        }
+        self.refresh_depths();
+        lane
+    }
+
+    /// 出队
+    pub fn pop(&mut self) -> Option<Command> {
+        let burst_exhausted = self.priority_streak >= self.max_priority_burst;
+        let take_priority =
+            !self.priority.is_empty() && (!burst_exhausted || self.normal.is_empty());
+
+        let command = if take_priority {
+            self.priority_streak += 1;
+            self.metrics.priority_dequeued += 1;
+            self.priority.pop_front()
+        } else {
+            let command = self.normal.pop_front()?;
+            if !self.priority.is_empty() {
+                self.metrics.fairness_yields += 1;
+            }
+            self.priority_streak = 0;
+            self.metrics.normal_dequeued += 1;
+            Some(command)
+        };
+
+        self.refresh_depths();
+        command
+    }
+
+    /// 按出队顺序交给处理器，最多处理 `budget` 条
+    pub fn drain_into<H: PrepCommandHandler>(
+        &mut self,
+        handler: &mut H,
+        budget: usize,
+    ) -> Vec<CommandResult> {
+        let mut results = Vec::new();
+        while results.len() < budget {
+            let Some(command) = self.pop() else {
+                break;
+            };
+            results.push(handler.handle(command));
+        }
+        results
+    }
+
+    /// 在 `now` 按出队顺序交给处理器，最多处理 `budget` 条，并记录挂单开始时间
+    pub fn drain_at<H: PrepCommandHandler>(
+        &mut self,
+        handler: &mut H,
+        budget: usize,
+        now: Timestamp,
+    ) -> Vec<CommandResult> {
+        let results = self.drain_into(handler, budget);
+        if let Some(guard) = self.quote_life.as_mut() {
+            for result in &results {
+                guard.on_result(result, now);
+            }
+        }
+        results
+    }
+
+    /// 待处理命令总数
+    pub fn len(&self) -> usize {
+        self.priority.len() + self.normal.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 通道指标
+    pub fn metrics(&self) -> LaneMetrics {
+        self.metrics
+    }
+
+    fn refresh_depths(&mut self) {
+        self.metrics.priority_depth = self.priority.len();
+        self.metrics.normal_depth = self.normal.len();
+    }
+}
+
+impl Default for CommandQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+
+    fn new_order(trader: u64) -> Command {
+        Command::LimitOrder {
+            trader,
+            side: Side::Buy,
+            price: 50000,
+            quantity: 1,
+            position_side: PositionSide::Long,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    fn cancel(order_id: u64) -> Command {
+        Command::CancelOrder { order_id }
+    }
+
+    #[test]
+    fn test_cancel_jumps_new_orders() {
+        let mut queue = CommandQueue::new();
+        assert_eq!(queue.push(new_order(1)), CommandLane::Normal);
+        assert_eq!(queue.push(new_order(2)), CommandLane::Normal);
+        assert_eq!(queue.push(cancel(7)), CommandLane::Priority);
+
+        assert!(matches!(queue.pop(), Some(Command::CancelOrder { order_id: 7 })));
+        assert!(matches!(queue.pop(), Some(Command::LimitOrder { trader: 1, .. })));
+        assert!(matches!(queue.pop(), Some(Command::LimitOrder { trader: 2, .. })));
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn test_speed_bump_delays_aggressive_orders() {
+        let mut queue = CommandQueue::new().with_speed_bump(SpeedBumpConfig::fixed(3));
+        assert_eq!(queue.push_at(new_order(1), 100, true), Ok(None));
+        assert_eq!(queue.push_at(new_order(2), 101, false), Ok(Some(CommandLane::Normal)));
+        assert_eq!(queue.delayed_len(), 1);
+
+        assert_eq!(queue.release_delayed(102), 0);
+        assert!(matches!(queue.pop(), Some(Command::LimitOrder { trader: 2, .. })));
+        assert_eq!(queue.release_delayed(103), 1);
+        assert!(matches!(queue.pop(), Some(Command::LimitOrder { trader: 1, .. })));
+    }
+
+    #[test]
+    fn test_scheduled_orders_enter_at_activation() {
+        let mut queue = CommandQueue::new().with_speed_bump(SpeedBumpConfig::fixed(3));
+        let auction = queue.schedule_at(new_order(1), 1_000);
+        let withdrawn = queue.schedule_at(new_order(2), 1_000);
+        assert!(queue.cancel_scheduled(withdrawn).is_some());
+        assert_eq!(queue.scheduled_len(), 1);
+
+        assert!(queue.release_scheduled(999).is_empty());
+        assert!(queue.is_empty());
+        assert_eq!(queue.release_scheduled(1_000), vec![auction]);
+        assert_eq!(queue.delayed_len(), 0);
+        assert!(matches!(queue.pop(), Some(Command::LimitOrder { trader: 1, .. })));
+        assert!(queue.cancel_scheduled(auction).is_none());
+    }
+
+    #[test]
+    fn test_fairness_bound() {
+        let mut queue = CommandQueue::with_max_priority_burst(2);
+        queue.push(new_order(1));
+        for id in 1..=5 {
+            queue.push(cancel(id));
+        }
+
+        let lanes: Vec<_> = std::iter::from_fn(|| queue.pop()).map(|c| c.lane()).collect();
+        assert_eq!(
+            lanes,
+            vec![
+                CommandLane::Priority,
+                CommandLane::Priority,
+                CommandLane::Normal,
+                CommandLane::Priority,
+                CommandLane::Priority,
+                CommandLane::Priority,
+            ]
+        );
+
+        let metrics = queue.metrics();
+        assert_eq!(metrics.fairness_yields, 1);
+        assert_eq!(metrics.priority_dequeued, 5);
+        assert_eq!(metrics.normal_dequeued, 1);
+        assert_eq!(metrics.priority_high_watermark, 5);
+        assert_eq!(metrics.normal_high_watermark, 1);
+        assert_eq!((metrics.priority_depth, metrics.normal_depth), (0, 0));
+    }
+}
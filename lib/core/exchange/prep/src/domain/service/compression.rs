@@ -0,0 +1,276 @@
+//! 子账户仓位压缩（Portfolio Compression）
+//!
+//! 同一母账户下的子账户互相持有方向相反的仓位时，双方各自占用保证金，合并来看却没有净风险。
+//! 压缩作业按母账户分组，把已同意压缩的子账户之间相反方向的仓位按标记价格两两对冲平掉：
+//! 双方按标记价格实现盈亏、按比例释放保证金，净敞口不变而总保证金占用下降。
+//!
+//! 子账户关系与同意标记（[`SubAccountGroups`]）由账户服务以 `Command::LinkSubAccount` /
+//! `Command::SetCompressionConsent` 写入撮合引擎，随命令日志与快照保存。分片线程按固定周期
+//! 运行作业（[`CompressionJob`]），读取引擎中的分组与最新标记价格生成对冲计划，
+//! 作为命令 `Command::CompressPositions` 进入撮合引擎；引擎按同一规则逐笔复核后原子执行，
+//! 双方各产生一条 `SettlementType::Compression` 结算事件，重放时结果一致
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::domain::entity::{Margin, Position, PositionId, Price, Quantity, Timestamp, TraderId};
+use crate::domain::service::command::Command;
+
+/// 母账户ID
+pub type ParentAccountId = u64;
+
+/// 子账户分组与压缩同意标记
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubAccountGroups {
+    parents: HashMap<TraderId, ParentAccountId>,
+    consented: HashSet<TraderId>,
+}
+
+impl SubAccountGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记子账户所属的母账户
+    pub fn link(&mut self, sub_account: TraderId, parent: ParentAccountId) {
+        self.parents.insert(sub_account, parent);
+    }
+
+    /// 解除子账户与母账户的关系
+    pub fn unlink(&mut self, sub_account: TraderId) {
+        self.parents.remove(&sub_account);
+    }
+
+    /// 设置子账户是否同意参与压缩（默认不参与）
+    pub fn set_consent(&mut self, sub_account: TraderId, consent: bool) {
+        if consent {
+            self.consented.insert(sub_account);
+        } else {
+            self.consented.remove(&sub_account);
+        }
+    }
+
+    /// 参与压缩的母账户（未登记或未同意为 None）
+    pub fn eligible_parent(&self, sub_account: TraderId) -> Option<ParentAccountId> {
+        self.parents.get(&sub_account).copied().filter(|_| self.consented.contains(&sub_account))
+    }
+
+    /// 两个子账户是否可以互相压缩（不同账户、同属一个母账户且均已同意）
+    pub fn can_compress(&self, a: TraderId, b: TraderId) -> bool {
+        a != b && self.eligible_parent(a).is_some_and(|p| self.eligible_parent(b) == Some(p))
+    }
+
+    /// 子账户与母账户关系（按子账户ID排序，供快照）
+    pub fn links(&self) -> Vec<(TraderId, ParentAccountId)> {
+        let mut links: Vec<_> = self.parents.iter().map(|(sub, parent)| (*sub, *parent)).collect();
+        links.sort_unstable();
+        links
+    }
+
+    /// 已同意压缩的子账户（按ID排序，供快照）
+    pub fn consented(&self) -> Vec<TraderId> {
+        let mut consented: Vec<_> = self.consented.iter().copied().collect();
+        consented.sort_unstable();
+        consented
+    }
+}
+
+/// 一笔对冲：多仓与空仓各平掉 `quantity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionLeg {
+    pub long_trader: TraderId,
+    pub long_position_id: PositionId,
+    pub short_trader: TraderId,
+    pub short_position_id: PositionId,
+    pub quantity: Quantity,
+}
+
+/// 一笔对冲的结算结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionSettlement {
+    pub leg: CompressionLeg,
+    /// 结算价格（标记价格）
+    pub price: Price,
+    /// 多仓已实现盈亏
+    pub long_realized_pnl: i64,
+    /// 空仓已实现盈亏
+    pub short_realized_pnl: i64,
+    /// 双方合计释放的保证金
+    pub released_margin: Margin,
+}
+
+/// 压缩审计记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionRecord {
+    pub settlements: Vec<CompressionSettlement>,
+    /// 操作员
+    pub operator: String,
+    pub compressed_at: Timestamp,
+}
+
+/// 一个母账户下的 (多仓, 空仓)，附剩余可对冲数量
+type GroupBook<'a> = (Vec<(&'a Position, Quantity)>, Vec<(&'a Position, Quantity)>);
+
+/// 生成压缩计划
+///
+/// 每个母账户内，多仓按仓位ID顺序依次与其他子账户的空仓对冲（同一子账户的双向持仓不对冲），
+/// 对冲数量取双方剩余数量的较小值
+pub fn plan_compression<'a>(
+    groups: &SubAccountGroups,
+    positions: impl IntoIterator<Item = &'a Position>,
+) -> Vec<CompressionLeg> {
+    // 母账户 → (多仓, 空仓)，按母账户ID有序，计划可复现
+    let mut books: BTreeMap<ParentAccountId, GroupBook<'_>> = BTreeMap::new();
+    for position in positions {
+        let Some(parent) = groups.eligible_parent(position.trader) else {
+            continue;
+        };
+        if position.is_empty() {
+            continue;
+        }
+        let (longs, shorts) = books.entry(parent).or_default();
+        let side = if position.is_long() { longs } else { shorts };
+        side.push((position, position.quantity));
+    }
+
+    let mut legs = Vec::new();
+    for (longs, shorts) in books.values_mut() {
+        longs.sort_by_key(|(p, _)| p.id);
+        shorts.sort_by_key(|(p, _)| p.id);
+        for (long, long_remaining) in longs.iter_mut() {
+            for (short, short_remaining) in shorts.iter_mut() {
+                if *long_remaining == 0 {
+                    break;
+                }
+                if *short_remaining == 0 || short.trader == long.trader {
+                    continue;
+                }
+                let quantity = (*long_remaining).min(*short_remaining);
+                *long_remaining -= quantity;
+                *short_remaining -= quantity;
+                legs.push(CompressionLeg {
+                    long_trader: long.trader,
+                    long_position_id: long.id,
+                    short_trader: short.trader,
+                    short_position_id: short.id,
+                    quantity,
+                });
+            }
+        }
+    }
+    legs
+}
+
+/// 定时压缩作业（分组与标记价格取自撮合引擎）
+#[derive(Debug, Clone)]
+pub struct CompressionJob {
+    /// 运行周期（毫秒）
+    interval: Timestamp,
+    next_run: Timestamp,
+    operator: String,
+}
+
+impl CompressionJob {
+    /// 创建作业，首次运行时间为 `first_run`
+    pub fn new(interval: Timestamp, first_run: Timestamp, operator: impl Into<String>) -> Self {
+        Self { interval: interval.max(1), next_run: first_run, operator: operator.into() }
+    }
+
+    /// 下次运行时间
+    pub fn next_run(&self) -> Timestamp {
+        self.next_run
+    }
+
+    /// 到达运行时间时生成压缩命令（无可对冲仓位时为 None）
+    pub fn poll<'a>(
+        &mut self,
+        now: Timestamp,
+        groups: &SubAccountGroups,
+        positions: impl IntoIterator<Item = &'a Position>,
+        mark_price: Price,
+    ) -> Option<Command> {
+        if now < self.next_run {
+            return None;
+        }
+        // 错过的周期不补跑
+        self.next_run = now + self.interval;
+        let legs = plan_compression(groups, positions);
+        if legs.is_empty() {
+            return None;
+        }
+        Some(Command::CompressPositions {
+            legs,
+            price: mark_price,
+            operator: self.operator.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{MarginMode, PositionSide};
+
+    fn position(id: PositionId, trader: TraderId, side: PositionSide, quantity: u64) -> Position {
+        Position::new(id, trader, side, quantity, 100, MarginMode::Cross, 10, quantity * 10, 0)
+    }
+
+    #[test]
+    fn test_plan_nets_consented_sub_accounts_of_same_parent() {
+        let mut groups = SubAccountGroups::new();
+        for (sub, parent) in [(1, 100), (2, 100), (3, 100), (4, 200)] {
+            groups.link(sub, parent);
+            groups.set_consent(sub, true);
+        }
+        groups.link(5, 100);
+
+        let positions = [
+            position(10, 1, PositionSide::Long, 30),
+            position(11, 1, PositionSide::Short, 5),
+            position(12, 2, PositionSide::Short, 10),
+            position(13, 3, PositionSide::Short, 50),
+            // 不同母账户、未同意的子账户不参与
+            position(14, 4, PositionSide::Short, 50),
+            position(15, 5, PositionSide::Short, 50),
+        ];
+        let legs = plan_compression(&groups, &positions);
+        let summary: Vec<_> =
+            legs.iter().map(|l| (l.long_position_id, l.short_position_id, l.quantity)).collect();
+        // 子账户 1 自身的空仓不对冲
+        assert_eq!(summary, vec![(10, 12, 10), (10, 13, 20)]);
+
+        assert!(groups.can_compress(1, 3));
+        assert!(!groups.can_compress(1, 1));
+        assert!(!groups.can_compress(1, 4));
+        assert!(!groups.can_compress(1, 5));
+
+        groups.set_consent(2, false);
+        groups.set_consent(3, false);
+        assert!(plan_compression(&groups, &positions).is_empty());
+        assert!(!groups.can_compress(1, 3));
+    }
+
+    #[test]
+    fn test_job_runs_on_schedule() {
+        let mut groups = SubAccountGroups::new();
+        for sub in [1, 2] {
+            groups.link(sub, 100);
+            groups.set_consent(sub, true);
+        }
+        let positions =
+            [position(1, 1, PositionSide::Long, 10), position(2, 2, PositionSide::Short, 4)];
+        let mut job = CompressionJob::new(60_000, 1_000, "ops");
+
+        assert!(job.poll(999, &groups, &positions, 120).is_none());
+        match job.poll(1_500, &groups, &positions, 120) {
+            Some(Command::CompressPositions { legs, price, operator }) => {
+                assert_eq!(legs.len(), 1);
+                assert_eq!(legs[0].quantity, 4);
+                assert_eq!(price, 120);
+                assert_eq!(operator, "ops");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(job.next_run(), 61_500);
+        assert!(job.poll(2_000, &groups, &positions, 120).is_none());
+    }
+}
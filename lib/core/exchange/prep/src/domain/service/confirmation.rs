@@ -0,0 +1,573 @@
+//! 成交确认通知
+//!
+//! 由成交事件为双方各生成一份成交确认，按账户偏好投递：
+//! - 即时：每笔成交立即生成一条通知
+//! - 日终汇总：按 UTC 自然日累积，日切后合并为一条通知
+//! - 关闭（默认）：不发送
+//!
+//! 通知正文由模板渲染，模板按账户语言选择，未注册的语言回退到默认语言。
+//! 生成的通知先进入发件箱，由 [`NotificationSender`] 实现投递（邮件与 Webhook 适配器见
+//! `adaptor::outbound::notification`），
+//! 投递失败的保留在发件箱等待重试。可配置熔断器：投递端持续失败时暂停投递，
+//! 冷却后先以少量通知探测，避免每次投递都逐条等待超时
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use resilience::CircuitBreaker;
+
+use super::leaderboard::StatsPeriod;
+use crate::domain::entity::{
+    EngineEvent, EventEnvelope, Price, Quantity, Side, Timestamp, TradeId, TradeLeg, TradeRecord,
+    TraderId,
+};
+
+/// 默认语言
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// 投递方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfirmationMode {
+    /// 不发送
+    #[default]
+    Off,
+    /// 每笔成交即时发送
+    Immediate,
+    /// 日终汇总
+    Digest,
+}
+
+/// 投递渠道
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryChannel {
+    /// 邮件地址
+    Email(String),
+    /// Webhook URL
+    Webhook(String),
+}
+
+/// 账户成交确认偏好
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationPreference {
+    /// 投递方式
+    pub mode: ConfirmationMode,
+    /// 投递渠道
+    pub channel: DeliveryChannel,
+    /// 语言（如 "en"、"zh-CN"）
+    pub locale: String,
+}
+
+/// 单方成交确认
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeConfirmation {
+    pub trade_id: TradeId,
+    pub trader: TraderId,
+    pub side: Side,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub is_maker: bool,
+    pub realized_pnl: i64,
+    pub timestamp: Timestamp,
+}
+
+impl TradeConfirmation {
+    fn from_leg(record: &TradeRecord, leg: &TradeLeg, is_maker: bool) -> Self {
+        Self {
+            trade_id: record.trade_id,
+            trader: leg.trader,
+            side: leg.side,
+            price: record.price,
+            quantity: record.quantity,
+            is_maker,
+            realized_pnl: leg.realized_pnl,
+            timestamp: record.timestamp,
+        }
+    }
+}
+
+/// 通知模板
+///
+/// 占位符：`{trade_id}` `{side}` `{role}` `{price}` `{quantity}` `{pnl}` `{time}`；
+/// 汇总模板另有 `{date}` `{count}` `{lines}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationTemplates {
+    /// 即时通知标题
+    pub immediate_subject: String,
+    /// 即时通知正文
+    pub immediate_body: String,
+    /// 汇总通知标题
+    pub digest_subject: String,
+    /// 汇总通知正文
+    pub digest_body: String,
+    /// 汇总中每笔成交一行
+    pub digest_line: String,
+    /// 买 / 卖
+    pub side_labels: [String; 2],
+    /// 挂单方 / 吃单方
+    pub role_labels: [String; 2],
+}
+
+impl ConfirmationTemplates {
+    /// 英文模板
+    pub fn english() -> Self {
+        Self {
+            immediate_subject: "Trade confirmation #{trade_id}".to_string(),
+            immediate_body: "{side} {quantity} @ {price} ({role}) at {time}, realized PnL {pnl}"
+                .to_string(),
+            digest_subject: "Trade confirmations for {date}".to_string(),
+            digest_body: "{count} trades on {date}:\n{lines}".to_string(),
+            digest_line: "#{trade_id} {side} {quantity} @ {price} ({role}) {time}".to_string(),
+            side_labels: ["Buy".to_string(), "Sell".to_string()],
+            role_labels: ["maker".to_string(), "taker".to_string()],
+        }
+    }
+
+    fn render_trade(&self, template: &str, confirmation: &TradeConfirmation) -> String {
+        let side = match confirmation.side {
+            Side::Buy => &self.side_labels[0],
+            Side::Sell => &self.side_labels[1],
+        };
+        let role = &self.role_labels[usize::from(!confirmation.is_maker)];
+        template
+            .replace("{trade_id}", &confirmation.trade_id.to_string())
+            .replace("{side}", side)
+            .replace("{role}", role)
+            .replace("{price}", &confirmation.price.to_string())
+            .replace("{quantity}", &confirmation.quantity.to_string())
+            .replace("{pnl}", &confirmation.realized_pnl.to_string())
+            .replace("{time}", &format_time(confirmation.timestamp))
+    }
+}
+
+/// 本地化钩子：按语言提供模板
+pub trait ConfirmationLocalizer: Send + Sync {
+    /// 语言对应的模板（None 时回退到默认语言）
+    fn templates(&self, locale: &str) -> Option<&ConfirmationTemplates>;
+}
+
+/// 按语言注册的模板表
+#[derive(Debug, Clone)]
+pub struct TemplateCatalog {
+    templates: HashMap<String, ConfirmationTemplates>,
+}
+
+impl Default for TemplateCatalog {
+    fn default() -> Self {
+        let mut catalog = Self { templates: HashMap::new() };
+        catalog.register(DEFAULT_LOCALE, ConfirmationTemplates::english());
+        catalog
+    }
+}
+
+impl TemplateCatalog {
+    /// 注册（或替换）某语言的模板
+    pub fn register(&mut self, locale: &str, templates: ConfirmationTemplates) {
+        self.templates.insert(locale.to_string(), templates);
+    }
+}
+
+impl ConfirmationLocalizer for TemplateCatalog {
+    fn templates(&self, locale: &str) -> Option<&ConfirmationTemplates> {
+        self.templates.get(locale)
+    }
+}
+
+/// 通知类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// 单笔成交
+    Trade { trade_id: TradeId },
+    /// 日终汇总（UTC 自然日编号）
+    Digest { day: u64 },
+}
+
+/// 待投递的通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub trader: TraderId,
+    pub kind: NotificationKind,
+    pub channel: DeliveryChannel,
+    pub subject: String,
+    pub body: String,
+}
+
+/// 通知投递出站端口（邮件、Webhook 适配器实现）
+pub trait NotificationSender {
+    /// 投递一条通知，失败返回原因
+    fn send(&mut self, notification: &Notification) -> Result<(), String>;
+}
+
+/// 投递结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    /// 投递成功数
+    pub sent: usize,
+    /// 投递失败或熔断中未投递、留待重试数
+    pub failed: usize,
+}
+
+/// 成交确认服务
+pub struct TradeConfirmationService<L: ConfirmationLocalizer = TemplateCatalog> {
+    localizer: L,
+    preferences: HashMap<TraderId, ConfirmationPreference>,
+    /// 汇总待发：(交易者, 自然日) -> 成交确认
+    pending: BTreeMap<(TraderId, u64), Vec<TradeConfirmation>>,
+    /// 发件箱
+    outbox: VecDeque<Notification>,
+    /// 投递端熔断器
+    breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl Default for TradeConfirmationService {
+    fn default() -> Self {
+        Self::new(TemplateCatalog::default())
+    }
+}
+
+impl<L: ConfirmationLocalizer> TradeConfirmationService<L> {
+    pub fn new(localizer: L) -> Self {
+        Self {
+            localizer,
+            preferences: HashMap::new(),
+            pending: BTreeMap::new(),
+            outbox: VecDeque::new(),
+            breaker: None,
+        }
+    }
+
+    /// 投递端熔断器（可与同一投递端的其他调用方共享）
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// 设置账户偏好
+    ///
+    /// 改为非汇总方式时，未发送的汇总保留至日切照常发出
+    pub fn set_preference(&mut self, trader: TraderId, preference: ConfirmationPreference) {
+        self.preferences.insert(trader, preference);
+    }
+
+    /// 账户偏好
+    pub fn preference(&self, trader: TraderId) -> Option<&ConfirmationPreference> {
+        self.preferences.get(&trader)
+    }
+
+    /// 应用引擎事件（只关心成交与成交撤销）
+    pub fn apply(&mut self, envelope: &EventEnvelope) {
+        match &envelope.event {
+            EngineEvent::Trade(record) => {
+                self.confirm(TradeConfirmation::from_leg(record, &record.taker, false));
+                self.confirm(TradeConfirmation::from_leg(record, &record.maker, true));
+            }
+            // 尚未汇总发出的成交撤销后不再出现在汇总中
+            EngineEvent::TradeBusted(record) => {
+                let day = StatsPeriod::Daily.bucket(record.timestamp);
+                for trader in [record.taker.trader, record.maker.trader] {
+                    if let Some(pending) = self.pending.get_mut(&(trader, day)) {
+                        pending.retain(|c| c.trade_id != record.trade_id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 日切：为 `now` 所在自然日之前的汇总生成通知，返回生成数量
+    pub fn close_day(&mut self, now: Timestamp) -> usize {
+        let today = StatsPeriod::Daily.bucket(now);
+        let due: Vec<(TraderId, u64)> =
+            self.pending.keys().filter(|(_, day)| *day < today).copied().collect();
+        let mut generated = 0;
+        for key in due {
+            let Some(confirmations) = self.pending.remove(&key) else {
+                continue;
+            };
+            if let Some(notification) = self.render_digest(key.0, key.1, &confirmations) {
+                self.outbox.push_back(notification);
+                generated += 1;
+            }
+        }
+        generated
+    }
+
+    /// 发件箱中待投递的通知
+    pub fn outbox(&self) -> impl Iterator<Item = &Notification> {
+        self.outbox.iter()
+    }
+
+    /// 投递发件箱；失败及熔断中未投递的按原顺序保留等待下次重试
+    pub fn dispatch(&mut self, sender: &mut impl NotificationSender) -> DispatchReport {
+        let mut report = DispatchReport::default();
+        let mut retained = VecDeque::new();
+        while let Some(notification) = self.outbox.pop_front() {
+            if self.breaker.as_ref().is_some_and(|breaker| !breaker.allow()) {
+                report.failed += 1;
+                retained.push_back(notification);
+                continue;
+            }
+            let result = sender.send(&notification);
+            match (&self.breaker, &result) {
+                (Some(breaker), Ok(())) => breaker.record_success(),
+                (Some(breaker), Err(_)) => breaker.record_failure(),
+                (None, _) => {}
+            }
+            match result {
+                Ok(()) => report.sent += 1,
+                Err(_) => {
+                    report.failed += 1;
+                    retained.push_back(notification);
+                }
+            }
+        }
+        self.outbox = retained;
+        report
+    }
+
+    fn confirm(&mut self, confirmation: TradeConfirmation) {
+        let Some(preference) = self.preferences.get(&confirmation.trader) else {
+            return;
+        };
+        match preference.mode {
+            ConfirmationMode::Off => {}
+            ConfirmationMode::Immediate => {
+                let templates = self.templates(&preference.locale);
+                let notification = Notification {
+                    trader: confirmation.trader,
+                    kind: NotificationKind::Trade { trade_id: confirmation.trade_id },
+                    channel: preference.channel.clone(),
+                    subject: templates.render_trade(&templates.immediate_subject, &confirmation),
+                    body: templates.render_trade(&templates.immediate_body, &confirmation),
+                };
+                self.outbox.push_back(notification);
+            }
+            ConfirmationMode::Digest => {
+                let day = StatsPeriod::Daily.bucket(confirmation.timestamp);
+                self.pending.entry((confirmation.trader, day)).or_default().push(confirmation);
+            }
+        }
+    }
+
+    fn render_digest(
+        &self,
+        trader: TraderId,
+        day: u64,
+        confirmations: &[TradeConfirmation],
+    ) -> Option<Notification> {
+        let preference = self.preferences.get(&trader)?;
+        if confirmations.is_empty() {
+            return None;
+        }
+        let templates = self.templates(&preference.locale);
+        let lines: Vec<String> = confirmations
+            .iter()
+            .map(|c| templates.render_trade(&templates.digest_line, c))
+            .collect();
+        let date = format_date(day);
+        let count = confirmations.len().to_string();
+        let render = |template: &str| {
+            template
+                .replace("{date}", &date)
+                .replace("{count}", &count)
+                .replace("{lines}", &lines.join("\n"))
+        };
+        Some(Notification {
+            trader,
+            kind: NotificationKind::Digest { day },
+            channel: preference.channel.clone(),
+            subject: render(&templates.digest_subject),
+            body: render(&templates.digest_body),
+        })
+    }
+
+    fn templates(&self, locale: &str) -> &ConfirmationTemplates {
+        self.localizer
+            .templates(locale)
+            .or_else(|| self.localizer.templates(DEFAULT_LOCALE))
+            .expect("default locale templates must be registered")
+    }
+}
+
+/// UTC 自然日编号 → YYYY-MM-DD
+fn format_date(day: u64) -> String {
+    // Howard Hinnant 的 civil_from_days 算法
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Unix 毫秒 → YYYY-MM-DD HH:MM:SS UTC
+fn format_time(timestamp: Timestamp) -> String {
+    let seconds = timestamp / 1000 % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02} UTC",
+        format_date(StatsPeriod::Daily.bucket(timestamp)),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use resilience::{CircuitBreakerConfig, ManualClock};
+
+    use super::*;
+    use crate::domain::entity::PositionSide;
+
+    const DAY: Timestamp = 86_400_000;
+
+    fn leg(trader: TraderId, side: Side) -> TradeLeg {
+        TradeLeg {
+            trader,
+            side,
+            position_side: PositionSide::Both,
+            opened: true,
+            quantity: 10,
+            entry_price_before: 0,
+            realized_pnl: 0,
+        }
+    }
+
+    fn record(trade_id: TradeId, timestamp: Timestamp) -> TradeRecord {
+        TradeRecord {
+            trade_id,
+            price: 100,
+            quantity: 10,
+            taker: leg(1, Side::Buy),
+            maker: leg(2, Side::Sell),
+            timestamp,
+        }
+    }
+
+    fn event(event: EngineEvent) -> EventEnvelope {
+        EventEnvelope { sequence: 0, event }
+    }
+
+    fn preference(mode: ConfirmationMode, locale: &str) -> ConfirmationPreference {
+        ConfirmationPreference {
+            mode,
+            channel: DeliveryChannel::Email("a@example.com".to_string()),
+            locale: locale.to_string(),
+        }
+    }
+
+    #[derive(Default)]
+    struct FlakySender {
+        sent: Vec<Notification>,
+        fail: bool,
+    }
+
+    impl NotificationSender for FlakySender {
+        fn send(&mut self, notification: &Notification) -> Result<(), String> {
+            if self.fail {
+                return Err("smtp unavailable".to_string());
+            }
+            self.sent.push(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_time(1_709_251_199_000), "2024-02-29 23:59:59 UTC");
+    }
+
+    #[test]
+    fn test_immediate_localized_and_retry() {
+        let mut catalog = TemplateCatalog::default();
+        catalog.register(
+            "zh-CN",
+            ConfirmationTemplates {
+                immediate_subject: "成交确认 #{trade_id}".to_string(),
+                immediate_body: "{side} {quantity} @ {price}（{role}）".to_string(),
+                side_labels: ["买入".to_string(), "卖出".to_string()],
+                role_labels: ["挂单".to_string(), "吃单".to_string()],
+                ..ConfirmationTemplates::english()
+            },
+        );
+        let mut service = TradeConfirmationService::new(catalog);
+        service.set_preference(1, preference(ConfirmationMode::Immediate, "zh-CN"));
+        service.set_preference(2, preference(ConfirmationMode::Immediate, "fr"));
+        service.apply(&event(EngineEvent::Trade(record(7, 0))));
+
+        let notifications: Vec<_> = service.outbox().cloned().collect();
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].subject, "成交确认 #7");
+        assert_eq!(notifications[0].body, "买入 10 @ 100（吃单）");
+        // 未注册的语言回退到英文
+        assert_eq!(
+            notifications[1].body,
+            "Sell 10 @ 100 (maker) at 1970-01-01 00:00:00 UTC, realized PnL 0"
+        );
+
+        let mut sender = FlakySender { fail: true, ..Default::default() };
+        assert_eq!(service.dispatch(&mut sender), DispatchReport { sent: 0, failed: 2 });
+        sender.fail = false;
+        assert_eq!(service.dispatch(&mut sender), DispatchReport { sent: 2, failed: 0 });
+        assert_eq!(sender.sent, notifications);
+        assert_eq!(service.outbox().count(), 0);
+    }
+
+    #[test]
+    fn test_dispatch_circuit_breaker() {
+        let clock = Arc::new(ManualClock::new());
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(1)
+            .with_open_timeout(Duration::from_secs(60));
+        let breaker = Arc::new(CircuitBreaker::with_clock(config, clock.clone()));
+        let mut service = TradeConfirmationService::default().with_circuit_breaker(breaker);
+        service.set_preference(1, preference(ConfirmationMode::Immediate, DEFAULT_LOCALE));
+        service.apply(&event(EngineEvent::Trade(record(1, 0))));
+        service.apply(&event(EngineEvent::Trade(record(2, 0))));
+
+        // 首条失败即熔断，其余不再尝试
+        let mut sender = FlakySender { fail: true, ..Default::default() };
+        assert_eq!(service.dispatch(&mut sender), DispatchReport { sent: 0, failed: 2 });
+        sender.fail = false;
+        assert_eq!(service.dispatch(&mut sender), DispatchReport { sent: 0, failed: 2 });
+        assert!(sender.sent.is_empty());
+
+        // 冷却后探测成功，恢复投递
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(service.dispatch(&mut sender), DispatchReport { sent: 2, failed: 0 });
+        assert_eq!(service.outbox().count(), 0);
+    }
+
+    #[test]
+    fn test_digest_and_off() {
+        let mut service = TradeConfirmationService::default();
+        service.set_preference(1, preference(ConfirmationMode::Digest, DEFAULT_LOCALE));
+        service.set_preference(2, preference(ConfirmationMode::Off, DEFAULT_LOCALE));
+        service.apply(&event(EngineEvent::Trade(record(1, 1000))));
+        service.apply(&event(EngineEvent::Trade(record(2, 2000))));
+        service.apply(&event(EngineEvent::Trade(record(3, 3000))));
+        service.apply(&event(EngineEvent::TradeBusted(record(2, 2000))));
+        service.apply(&event(EngineEvent::Trade(record(4, DAY + 1))));
+        assert_eq!(service.outbox().count(), 0);
+
+        // 当日未结束不发送
+        assert_eq!(service.close_day(DAY - 1), 0);
+        assert_eq!(service.close_day(DAY), 1);
+        let digest = service.outbox().next().unwrap();
+        assert_eq!(digest.kind, NotificationKind::Digest { day: 0 });
+        assert_eq!(digest.subject, "Trade confirmations for 1970-01-01");
+        assert_eq!(
+            digest.body,
+            "2 trades on 1970-01-01:\n\
+             #1 Buy 10 @ 100 (taker) 1970-01-01 00:00:01 UTC\n\
+             #3 Buy 10 @ 100 (taker) 1970-01-01 00:00:03 UTC"
+        );
+        assert_eq!(service.close_day(2 * DAY), 1);
+    }
+}
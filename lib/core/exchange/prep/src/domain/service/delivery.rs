@@ -0,0 +1,85 @@
+//! 交割合约到期交割
+//!
+//! 管理员命令 `Command::SetExpiry` 设置到期时间（随命令日志回放），到期时刻起拒绝新委托；
+//! 交割由管理员命令 `Command::DeliverFutures` 触发：撤销全部挂单，按交割价格平掉所有持仓
+//! （结算方式 [`SettlementType::FutureDelivery`]），实现盈亏并释放全部保证金。
+//!
+//! 交割价格取到期前一段时间内指数价格的算术平均（[`DeliveryPriceSampler`]），
+//! 避免到期瞬间的价格操纵
+//!
+//! [`SettlementType::FutureDelivery`]: crate::domain::entity::SettlementType::FutureDelivery
+
+use crate::domain::entity::{OrderId, PositionSettlement, Price, Timestamp};
+
+/// 交割审计记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryRecord {
+    /// 交割价格
+    pub delivery_price: Price,
+    /// 交割前撤销的挂单
+    pub cancelled_orders: Vec<OrderId>,
+    /// 逐仓位结算
+    pub settlements: Vec<PositionSettlement>,
+    /// 操作员
+    pub operator: String,
+    pub delivered_at: Timestamp,
+}
+
+/// 交割价格采样：到期前 `window` 毫秒内指数价格的平均值
+#[derive(Debug, Clone)]
+pub struct DeliveryPriceSampler {
+    expiry: Timestamp,
+    window: u64,
+    samples: Vec<(Timestamp, Price)>,
+}
+
+impl DeliveryPriceSampler {
+    pub fn new(expiry: Timestamp, window: u64) -> Self {
+        Self { expiry, window, samples: Vec::new() }
+    }
+
+    /// 记录指数价格，窗口之外（早于窗口或不早于到期）的样本忽略
+    pub fn record(&mut self, timestamp: Timestamp, price: Price) {
+        let start = self.expiry.saturating_sub(self.window);
+        if timestamp < start || timestamp >= self.expiry || price == 0 {
+            return;
+        }
+        self.samples.push((timestamp, price));
+    }
+
+    /// 样本数
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 交割价格（没有样本时为 None，需人工指定）
+    pub fn delivery_price(&self) -> Option<Price> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let sum: u128 = self.samples.iter().map(|(_, price)| *price as u128).sum();
+        Some((sum / self.samples.len() as u128) as Price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivery_price_averages_window() {
+        let mut sampler = DeliveryPriceSampler::new(10_000, 1_000);
+        assert_eq!(sampler.delivery_price(), None);
+        sampler.record(8_999, 1);
+        sampler.record(9_000, 100);
+        sampler.record(9_500, 103);
+        sampler.record(9_999, 106);
+        sampler.record(10_000, 500);
+        assert_eq!(sampler.len(), 3);
+        assert_eq!(sampler.delivery_price(), Some(103));
+    }
+}
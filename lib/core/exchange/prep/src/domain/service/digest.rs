@@ -0,0 +1,175 @@
+//! 引擎输出摘要
+//!
+//! 每处理一条命令记录一个 [`StepDigest`]：本步成交、处理后的订单簿与仓位各一个 64 位摘要。
+//! 两次回放逐步比对摘要即可定位第一处分歧，用于排查不确定性与回归。
+//!
+//! 摘要使用 FNV-1a，按固定字段顺序写入，不依赖 `std::hash`（其算法不保证跨版本稳定），
+//! 不同构建产出的摘要可直接比较。余额由外部账户服务持有，引擎内的账户状态
+//! （保证金、已实现盈亏）随仓位摘要一并比对
+
+use std::fmt;
+
+use crate::domain::entity::{Order, Position, PositionSide, Side, Trade};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a 64 位摘要
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fnv64(u64);
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self(FNV_OFFSET)
+    }
+}
+
+impl Fnv64 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// 成交列表摘要（按成交顺序）
+pub fn trades_digest(trades: &[Trade]) -> u64 {
+    let mut hasher = Fnv64::new();
+    for trade in trades {
+        hasher.write_u64(trade.id());
+        hasher.write_u64(trade.order_id());
+        hasher.write_u64(trade.price());
+        hasher.write_u64(trade.quantity());
+        hasher.write_u64(side_code(trade.side()));
+        hasher.write_u64(trade.fee());
+        hasher.write_i64(trade.realized_pnl());
+        hasher.write_u64(trade.is_maker() as u64);
+    }
+    hasher.finish()
+}
+
+/// 订单簿摘要（调用方按买单在前、各自价格时间优先的顺序传入）
+pub fn book_digest<'a>(orders: impl IntoIterator<Item = &'a Order>) -> u64 {
+    let mut hasher = Fnv64::new();
+    for order in orders {
+        hasher.write_u64(order.id);
+        hasher.write_u64(order.trader);
+        hasher.write_u64(side_code(order.side));
+        hasher.write_u64(order.price);
+        hasher.write_u64(order.remaining_quantity);
+        hasher.write_u64(order.filled_quantity);
+    }
+    hasher.finish()
+}
+
+/// 仓位摘要（调用方按仓位ID顺序传入）
+pub fn positions_digest<'a>(positions: impl IntoIterator<Item = &'a Position>) -> u64 {
+    let mut hasher = Fnv64::new();
+    for position in positions {
+        hasher.write_u64(position.id);
+        hasher.write_u64(position.trader);
+        hasher.write_u64(match position.position_side {
+            PositionSide::Both => 0,
+            PositionSide::Long => 1,
+            PositionSide::Short => 2,
+        });
+        hasher.write_u64(position.quantity);
+        hasher.write_u64(position.entry_price);
+        hasher.write_u64(position.leverage as u64);
+        hasher.write_u64(position.margin);
+        hasher.write_i64(position.realized_pnl);
+        hasher.write_u64(position.liquidation_price);
+    }
+    hasher.finish()
+}
+
+fn side_code(side: Side) -> u64 {
+    match side {
+        Side::Buy => 0,
+        Side::Sell => 1,
+    }
+}
+
+/// 单步输出摘要
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepDigest {
+    /// 命令序列号
+    pub sequence: u64,
+    /// 本步成交
+    pub trades: u64,
+    /// 处理后的订单簿
+    pub book: u64,
+    /// 处理后的仓位（含保证金与已实现盈亏）
+    pub positions: u64,
+}
+
+impl StepDigest {
+    /// 与另一侧不一致的字段名
+    pub fn diff(&self, other: &StepDigest) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.trades != other.trades {
+            fields.push("trades");
+        }
+        if self.book != other.book {
+            fields.push("book");
+        }
+        if self.positions != other.positions {
+            fields.push("positions");
+        }
+        fields
+    }
+
+    /// 解析 [`Display`](fmt::Display) 输出的一行：`序列号 成交 订单簿 仓位`（摘要为十六进制）
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let sequence = fields.next()?.parse().ok()?;
+        let mut digest = || u64::from_str_radix(fields.next()?, 16).ok();
+        let (trades, book, positions) = (digest()?, digest()?, digest()?);
+        Some(Self { sequence, trades, book, positions })
+    }
+}
+
+impl fmt::Display for StepDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:016x} {:016x} {:016x}",
+            self.sequence, self.trades, self.book, self.positions
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_digest_line_round_trip() {
+        let step = StepDigest { sequence: 42, trades: 1, book: u64::MAX, positions: 0xabc };
+        let line = step.to_string();
+        assert_eq!(line, "42 0000000000000001 ffffffffffffffff 0000000000000abc");
+        assert_eq!(StepDigest::parse(&line), Some(step));
+        assert_eq!(StepDigest::parse("42 01 02"), None);
+
+        let other = StepDigest { book: 7, ..step };
+        assert_eq!(step.diff(&other), vec!["book"]);
+        // 空输入摘要固定为 FNV 初值
+        assert_eq!(trades_digest(&[]), FNV_OFFSET);
+    }
+}
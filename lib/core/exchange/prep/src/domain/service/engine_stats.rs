@@ -0,0 +1,338 @@
+//! 引擎运行统计
+//!
+//! `GET /api/admin/engine/stats` 的领域结果：按交易对汇报挂单数、盘口深度、
+//! 最新序列号、每秒撮合数、拒单数与订单池利用率
+//!
+//! 撮合线程只对原子计数器做 Relaxed 写入，管理端读取时不加锁、不暂停撮合；
+//! 盘口深度由 [`BookDepth`] 随挂单、成交与撤单增量维护，撮合线程每 `BOOK_STATS_INTERVAL`
+//! 条命令发布一次，发布不遍历订单簿
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::domain::entity::{ExecutionReport, Order, OrderId, Price, Quantity, Side};
+
+/// 盘口深度刷新间隔（命令数）
+pub const BOOK_STATS_INTERVAL: u64 = 256;
+
+/// 单个引擎的原子计数器（撮合线程写，管理端读）
+#[derive(Debug, Default)]
+pub struct EngineCounters {
+    sequence: AtomicU64,
+    matches: AtomicU64,
+    rejects: AtomicU64,
+    resting_orders: AtomicU64,
+    bid_levels: AtomicU64,
+    ask_levels: AtomicU64,
+    bid_quantity: AtomicU64,
+    ask_quantity: AtomicU64,
+    order_capacity: AtomicU64,
+}
+
+/// 盘口概况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BookStats {
+    /// 挂单数
+    pub resting_orders: u64,
+    /// 买盘价位数
+    pub bid_levels: u64,
+    /// 卖盘价位数
+    pub ask_levels: u64,
+    /// 买盘总挂单量
+    pub bid_quantity: u64,
+    /// 卖盘总挂单量
+    pub ask_quantity: u64,
+    /// 订单池容量
+    pub order_capacity: u64,
+}
+
+impl EngineCounters {
+    /// 记录一条命令的处理结果
+    pub fn record_command(&self, sequence: u64, matches: u64, rejected: bool) {
+        self.sequence.store(sequence, Ordering::Relaxed);
+        if matches > 0 {
+            self.matches.fetch_add(matches, Ordering::Relaxed);
+        }
+        if rejected {
+            self.rejects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 发布盘口概况
+    pub fn publish_book(&self, book: BookStats) {
+        self.resting_orders.store(book.resting_orders, Ordering::Relaxed);
+        self.bid_levels.store(book.bid_levels, Ordering::Relaxed);
+        self.ask_levels.store(book.ask_levels, Ordering::Relaxed);
+        self.bid_quantity.store(book.bid_quantity, Ordering::Relaxed);
+        self.ask_quantity.store(book.ask_quantity, Ordering::Relaxed);
+        self.order_capacity.store(book.order_capacity, Ordering::Relaxed);
+    }
+
+    fn book(&self) -> BookStats {
+        BookStats {
+            resting_orders: self.resting_orders.load(Ordering::Relaxed),
+            bid_levels: self.bid_levels.load(Ordering::Relaxed),
+            ask_levels: self.ask_levels.load(Ordering::Relaxed),
+            bid_quantity: self.bid_quantity.load(Ordering::Relaxed),
+            ask_quantity: self.ask_quantity.load(Ordering::Relaxed),
+            order_capacity: self.order_capacity.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 增量维护的盘口深度（撮合线程持有）
+///
+/// 挂单入簿时登记，之后按执行回报更新剩余数量，终态即移出
+#[derive(Debug, Clone, Default)]
+pub struct BookDepth {
+    /// 挂单 -> (方向, 价格, 剩余数量)
+    orders: HashMap<OrderId, (Side, Price, Quantity)>,
+    /// 买盘各价位的挂单数
+    bid_levels: HashMap<Price, u64>,
+    /// 卖盘各价位的挂单数
+    ask_levels: HashMap<Price, u64>,
+    bid_quantity: u64,
+    ask_quantity: u64,
+}
+
+impl BookDepth {
+    /// 由当前订单簿构建（接入统计时调用一次）
+    pub fn from_orders<'a>(orders: impl IntoIterator<Item = &'a Order>) -> Self {
+        let mut depth = Self::default();
+        for order in orders {
+            depth.rest(order);
+        }
+        depth
+    }
+
+    /// 登记入簿的挂单
+    pub fn rest(&mut self, order: &Order) {
+        let entry = (order.side, order.price, order.remaining_quantity);
+        if self.orders.insert(order.id, entry).is_some() {
+            return;
+        }
+        let (levels, quantity) = self.side_mut(order.side);
+        *levels.entry(order.price).or_insert(0) += 1;
+        *quantity += order.remaining_quantity;
+    }
+
+    /// 按执行回报更新（未登记的订单即未入簿的 Taker，忽略）
+    pub fn apply(&mut self, report: &ExecutionReport) {
+        let Some(&(side, price, remaining)) = self.orders.get(&report.order_id) else {
+            return;
+        };
+        let terminal = report.status.is_terminal();
+        let (levels, quantity) = self.side_mut(side);
+        *quantity -= remaining;
+        if terminal {
+            if let Some(count) = levels.get_mut(&price) {
+                *count -= 1;
+                if *count == 0 {
+                    levels.remove(&price);
+                }
+            }
+            self.orders.remove(&report.order_id);
+        } else {
+            *quantity += report.remaining_quantity;
+            self.orders.insert(report.order_id, (side, price, report.remaining_quantity));
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> (&mut HashMap<Price, u64>, &mut u64) {
+        match side {
+            Side::Buy => (&mut self.bid_levels, &mut self.bid_quantity),
+            Side::Sell => (&mut self.ask_levels, &mut self.ask_quantity),
+        }
+    }
+
+    /// 当前盘口概况
+    pub fn stats(&self, order_capacity: u64) -> BookStats {
+        BookStats {
+            resting_orders: self.orders.len() as u64,
+            bid_levels: self.bid_levels.len() as u64,
+            ask_levels: self.ask_levels.len() as u64,
+            bid_quantity: self.bid_quantity,
+            ask_quantity: self.ask_quantity,
+            order_capacity,
+        }
+    }
+}
+
+/// 交易对统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolStats {
+    /// 交易对
+    pub symbol: String,
+    /// 最新命令序列号
+    pub sequence: u64,
+    /// 盘口概况（最近一次刷新）
+    pub book: BookStats,
+    /// 累计撮合笔数
+    pub matches_total: u64,
+    /// 距上次采样的每秒撮合数（首次采样为 0）
+    pub matches_per_sec: f64,
+    /// 累计拒单数
+    pub rejects: u64,
+    /// 订单池利用率（容量未知为 None）
+    pub pool_utilization: Option<f64>,
+}
+
+#[derive(Debug)]
+struct Registered {
+    counters: Arc<EngineCounters>,
+    /// 上次采样 (撮合笔数, 时间)
+    last_sample: Option<(u64, Instant)>,
+}
+
+/// 引擎统计注册表
+///
+/// 锁只在注册与管理端采样时使用，撮合线程持有的是计数器的 `Arc`
+#[derive(Debug, Default)]
+pub struct EngineStatsRegistry {
+    engines: Mutex<BTreeMap<String, Registered>>,
+}
+
+impl EngineStatsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册交易对，返回交给撮合引擎的计数器（重复注册返回同一组计数器）
+    pub fn register(&self, symbol: impl Into<String>) -> Arc<EngineCounters> {
+        let mut engines = self.engines.lock().unwrap_or_else(|e| e.into_inner());
+        let registered = engines.entry(symbol.into()).or_insert_with(|| Registered {
+            counters: Arc::new(EngineCounters::default()),
+            last_sample: None,
+        });
+        Arc::clone(&registered.counters)
+    }
+
+    /// 采样全部交易对（按交易对排序）
+    pub fn collect(&self) -> Vec<SymbolStats> {
+        self.collect_at(Instant::now())
+    }
+
+    fn collect_at(&self, now: Instant) -> Vec<SymbolStats> {
+        let mut engines = self.engines.lock().unwrap_or_else(|e| e.into_inner());
+        engines
+            .iter_mut()
+            .map(|(symbol, registered)| {
+                let counters = &registered.counters;
+                let matches_total = counters.matches.load(Ordering::Relaxed);
+                let matches_per_sec = match registered.last_sample {
+                    Some((last, at)) if now > at => {
+                        matches_total.saturating_sub(last) as f64
+                            / now.duration_since(at).as_secs_f64()
+                    }
+                    _ => 0.0,
+                };
+                registered.last_sample = Some((matches_total, now));
+
+                let book = counters.book();
+                SymbolStats {
+                    symbol: symbol.clone(),
+                    sequence: counters.sequence.load(Ordering::Relaxed),
+                    book,
+                    matches_total,
+                    matches_per_sec,
+                    rejects: counters.rejects.load(Ordering::Relaxed),
+                    pool_utilization: (book.order_capacity > 0)
+                        .then(|| book.resting_orders as f64 / book.order_capacity as f64),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+    use crate::domain::service::command::{Command, PrepCommandHandler};
+    use crate::domain::service::matching::MatchingService;
+
+    fn limit(trader: u64, side: Side, price: u64, quantity: u64) -> Command {
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side: PositionSide::Both,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_engine_stats_collected_per_symbol() {
+        let registry = EngineStatsRegistry::new();
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.reserve(64, 0);
+        engine.attach_stats(registry.register("BTCUSDT"));
+        registry.register("ETHUSDT");
+
+        let start = Instant::now();
+        assert_eq!(registry.collect_at(start)[0].matches_per_sec, 0.0);
+
+        engine.handle(limit(1, Side::Sell, 101, 5));
+        engine.handle(limit(2, Side::Sell, 102, 5));
+        engine.handle(limit(3, Side::Buy, 99, 7));
+        engine.handle(limit(4, Side::Buy, 101, 2));
+        engine.handle(limit(4, Side::Buy, 0, 2));
+        engine.publish_book_stats();
+
+        let stats = registry.collect_at(start + Duration::from_secs(2));
+        assert_eq!(stats.len(), 2);
+        let btc = &stats[0];
+        assert_eq!(btc.symbol, "BTCUSDT");
+        assert_eq!(btc.sequence, 5);
+        assert_eq!((btc.matches_total, btc.rejects), (1, 1));
+        assert_eq!(btc.matches_per_sec, 0.5);
+        assert_eq!(
+            btc.book,
+            BookStats {
+                resting_orders: 3,
+                bid_levels: 1,
+                ask_levels: 2,
+                bid_quantity: 7,
+                ask_quantity: 8,
+                order_capacity: btc.book.order_capacity,
+            }
+        );
+        assert!(btc.pool_utilization.is_some_and(|u| u > 0.0 && u <= 1.0));
+        assert_eq!(stats[1].pool_utilization, None);
+    }
+
+    #[test]
+    fn test_incremental_depth_matches_full_scan() {
+        let registry = EngineStatsRegistry::new();
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.attach_stats(registry.register("BTCUSDT"));
+
+        engine.handle(limit(1, Side::Sell, 101, 5));
+        engine.handle(limit(1, Side::Sell, 101, 3));
+        engine.handle(limit(2, Side::Sell, 103, 4));
+        engine.handle(limit(3, Side::Buy, 99, 7));
+        engine.handle(limit(4, Side::Buy, 98, 1));
+        // 部分成交一档；吃完买盘后余量入簿
+        engine.handle(limit(5, Side::Buy, 101, 6));
+        engine.handle(limit(6, Side::Sell, 98, 10));
+        engine.handle(Command::CancelOrder { order_id: 3 });
+        engine.publish_book_stats();
+        let incremental = registry.collect()[0].book;
+
+        // 重新接入时按订单簿全量构建
+        engine.attach_stats(registry.register("ETHUSDT"));
+        let scanned = registry.collect()[1].book;
+        assert_eq!(incremental, scanned);
+        let levels = (incremental.bid_levels, incremental.ask_levels);
+        assert_eq!((incremental.resting_orders, levels, incremental.ask_quantity), (2, (0, 2), 4));
+    }
+}
@@ -0,0 +1,255 @@
+//! 功能开关（灰度发布）
+//!
+//! 新的撮合行为在固定的决策点查询开关，按账户灰度；未设置的功能为关闭，
+//! 引擎行为与不接入开关时一致：
+//! - 全量开启，或指定账户名单，或按交易者ID散列落入前 `percent`% 的账户
+//! - 散列与进程无关（不使用随机种子），同一账户在任何节点、任何回放中结果一致
+//!
+//! 开关变更只能通过 [`Command::SetFeatureFlag`](crate::domain::service::Command::SetFeatureFlag)
+//! 进入引擎：与订单命令一起按序列号写入命令日志，回放时在同一位置生效，
+//! 开关变化不会让回放结果偏离。启动配置（[`parse_flag_config`]）与管理接口的变更同样转为命令提交，
+//! 开关状态随快照归档
+
+use std::collections::BTreeMap;
+
+use crate::domain::entity::{Timestamp, TraderId};
+
+/// 受开关控制的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    /// 自成交防护：吃单会与本账户挂单成交时，先撤销本账户的对手挂单
+    SelfTradePrevention = 1,
+}
+
+impl Feature {
+    /// 全部功能
+    pub const ALL: [Feature; 1] = [Feature::SelfTradePrevention];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Feature::SelfTradePrevention => "self_trade_prevention",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|feature| feature.as_str() == s)
+    }
+
+    /// 编码值
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|feature| feature.code() == code)
+    }
+}
+
+/// 开关规则（三者任一命中即开启）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagRule {
+    /// 全量开启
+    pub enabled: bool,
+    /// 指定账户（有序、去重）
+    pub accounts: Vec<TraderId>,
+    /// 灰度比例（0-100）
+    pub percent: u8,
+}
+
+impl FlagRule {
+    /// 全量开启
+    pub fn on() -> Self {
+        Self { enabled: true, ..Self::default() }
+    }
+
+    /// 关闭
+    pub fn off() -> Self {
+        Self::default()
+    }
+
+    /// 只对指定账户开启
+    pub fn accounts(accounts: impl IntoIterator<Item = TraderId>) -> Self {
+        let mut accounts: Vec<TraderId> = accounts.into_iter().collect();
+        accounts.sort_unstable();
+        accounts.dedup();
+        Self { accounts, ..Self::default() }
+    }
+
+    /// 按比例灰度（超过 100 按 100）
+    pub fn with_percent(mut self, percent: u8) -> Self {
+        self.percent = percent.min(100);
+        self
+    }
+
+    /// 对该账户是否开启
+    pub fn is_enabled_for(&self, trader: TraderId) -> bool {
+        self.enabled
+            || self.accounts.binary_search(&trader).is_ok()
+            || rollout_bucket(trader) < u64::from(self.percent)
+    }
+}
+
+/// 账户的灰度桶（0-99），由交易者ID确定
+pub fn rollout_bucket(trader: TraderId) -> u64 {
+    // Fibonacci 散列，使连续的交易者ID均匀分布
+    (trader.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) % 100
+}
+
+/// 开关变更记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureFlagRecord {
+    /// 生效的命令序列号
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    pub feature: Feature,
+    pub rule: FlagRule,
+    pub operator: String,
+}
+
+/// 功能开关表（未设置的功能为关闭）
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    rules: BTreeMap<Feature, FlagRule>,
+    /// 变更日志（只追加）
+    log: Vec<FeatureFlagRecord>,
+}
+
+impl FeatureFlags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 由快照中的规则恢复（不含变更日志）
+    pub fn restore(rules: impl IntoIterator<Item = (Feature, FlagRule)>) -> Self {
+        Self { rules: rules.into_iter().collect(), log: Vec::new() }
+    }
+
+    /// 决策点查询：功能对该账户是否开启
+    pub fn is_enabled(&self, feature: Feature, trader: TraderId) -> bool {
+        self.rules.get(&feature).is_some_and(|rule| rule.is_enabled_for(trader))
+    }
+
+    /// 设置规则并记录变更
+    pub fn set(
+        &mut self,
+        feature: Feature,
+        rule: FlagRule,
+        operator: String,
+        sequence: u64,
+        timestamp: Timestamp,
+    ) {
+        self.log.push(FeatureFlagRecord {
+            sequence,
+            timestamp,
+            feature,
+            rule: rule.clone(),
+            operator,
+        });
+        if rule == FlagRule::off() {
+            self.rules.remove(&feature);
+        } else {
+            self.rules.insert(feature, rule);
+        }
+    }
+
+    pub fn rule(&self, feature: Feature) -> Option<&FlagRule> {
+        self.rules.get(&feature)
+    }
+
+    /// 生效中的规则（按功能排序，供快照）
+    pub fn rules(&self) -> Vec<(Feature, FlagRule)> {
+        self.rules.iter().map(|(feature, rule)| (*feature, rule.clone())).collect()
+    }
+
+    pub fn log(&self) -> &[FeatureFlagRecord] {
+        &self.log
+    }
+}
+
+/// 解析启动配置：`功能=规则`，以 `;` 分隔
+///
+/// 规则为 `on`、`off`，或 `accounts:1,2` 与 `percent:10` 以 `|` 组合，
+/// 如 `self_trade_prevention=accounts:7,9|percent:5`
+pub fn parse_flag_config(config: &str) -> Result<Vec<(Feature, FlagRule)>, String> {
+    let mut flags = Vec::new();
+    for entry in config.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, rule) =
+            entry.split_once('=').ok_or_else(|| format!("invalid flag entry: {}", entry))?;
+        let feature = Feature::parse(name.trim())
+            .ok_or_else(|| format!("unknown feature: {}", name.trim()))?;
+        flags.push((feature, parse_rule(rule.trim())?));
+    }
+    Ok(flags)
+}
+
+fn parse_rule(rule: &str) -> Result<FlagRule, String> {
+    match rule {
+        "on" => return Ok(FlagRule::on()),
+        "off" => return Ok(FlagRule::off()),
+        _ => {}
+    }
+    let mut parsed = FlagRule::off();
+    for part in rule.split('|') {
+        match part.split_once(':') {
+            Some(("accounts", list)) => {
+                let accounts = list
+                    .split(',')
+                    .map(|id| id.trim().parse::<TraderId>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("invalid accounts: {}", list))?;
+                parsed.accounts = FlagRule::accounts(accounts).accounts;
+            }
+            Some(("percent", percent)) => match percent.trim().parse::<u8>() {
+                Ok(percent) if percent <= 100 => parsed.percent = percent,
+                _ => return Err(format!("invalid percent: {}", percent)),
+            },
+            _ => return Err(format!("invalid flag rule: {}", part)),
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targeting() {
+        let mut flags = FeatureFlags::new();
+        assert!(!flags.is_enabled(Feature::SelfTradePrevention, 7));
+
+        let rule = FlagRule::accounts([9, 7, 7]);
+        flags.set(Feature::SelfTradePrevention, rule, "ops".into(), 3, 100);
+        assert!(flags.is_enabled(Feature::SelfTradePrevention, 7));
+        assert!(!flags.is_enabled(Feature::SelfTradePrevention, 8));
+
+        // 灰度比例单调：10% 开启的账户在 50% 时仍开启
+        let ten = FlagRule::off().with_percent(10);
+        let fifty = FlagRule::off().with_percent(50);
+        let enabled: Vec<TraderId> = (1..=1_000).filter(|t| ten.is_enabled_for(*t)).collect();
+        assert!((50..150).contains(&enabled.len()));
+        assert!(enabled.iter().all(|t| fifty.is_enabled_for(*t)));
+        assert!(FlagRule::off().with_percent(200).is_enabled_for(12_345));
+
+        flags.set(Feature::SelfTradePrevention, FlagRule::off(), "ops".into(), 4, 200);
+        assert!(flags.rules().is_empty());
+        assert_eq!(flags.log().len(), 2);
+        assert_eq!(flags.log()[1].sequence, 4);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let flags = parse_flag_config("self_trade_prevention=on;").unwrap();
+        assert_eq!(flags, [(Feature::SelfTradePrevention, FlagRule::on())]);
+        let flags = parse_flag_config(" self_trade_prevention=accounts:7,9|percent:5").unwrap();
+        assert_eq!(
+            flags[0],
+            (Feature::SelfTradePrevention, FlagRule::accounts([7, 9]).with_percent(5))
+        );
+        assert!(parse_flag_config("bogus=on").is_err());
+        assert!(parse_flag_config("self_trade_prevention=percent:101").is_err());
+        assert!(parse_flag_config("self_trade_prevention").is_err());
+        assert_eq!(Feature::from_code(1), Some(Feature::SelfTradePrevention));
+        assert_eq!(Feature::from_code(2), None);
+    }
+}
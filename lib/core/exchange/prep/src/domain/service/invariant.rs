@@ -0,0 +1,527 @@
+//! 不变量抽样检查（浸泡测试模式）
+//!
+//! 生产环境长时间运行时，状态损坏往往先以细微的不一致出现，等到撮合结果出错时已难以追溯。
+//! 抽样分两步，撮合线程只做第一步：
+//! 1. 采样（撮合线程）：每隔 `interval` 条命令随机抽取少量订单、仓位及挂单账户，
+//!    连同盘口与索引查找结果复制为 [`InvariantProbe`]，开销只与抽样数量相关
+//! 2. 检查（后台线程）：[`InvariantChecker`] 在副本上逐条核对并生成违例记录
+//!
+//! 检查项：
+//! - 订单：剩余 + 已成交 = 原始数量；可成交订单剩余大于 0 且位于所属价格档位的队列中
+//! - 盘口：最优买价低于最优卖价
+//! - 仓位：数量大于 0；按交易者与持仓方向能查回同一仓位
+//! - 余额：保证金资产的冻结余额不低于挂单最坏情况保证金
+//!
+//! 采样只读，不改变引擎状态，开启与否不影响确定性重放
+
+use std::collections::BTreeSet;
+
+use crate::domain::entity::{
+    InvariantKind, InvariantViolation, Leverage, Order, OrderId, Position, PositionId, Price,
+    Timestamp, TraderId, ViolationContext,
+};
+use crate::domain::repository::{BalanceReader, OrderRepository, PositionRepository};
+use crate::domain::service::prefunding::worst_case_margin;
+
+/// 抽样检查配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantSamplerConfig {
+    /// 每隔多少条命令采样一次
+    pub interval: u64,
+    /// 每轮抽查的订单数（抽中的可成交订单所属账户同时抽查余额）
+    pub order_probes: usize,
+    /// 每轮抽查的仓位数
+    pub position_probes: usize,
+    /// 随机数种子
+    pub seed: u64,
+    /// 保证金资产
+    pub margin_asset: String,
+}
+
+impl Default for InvariantSamplerConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1_000,
+            order_probes: 8,
+            position_probes: 4,
+            seed: 0x9E37_79B9_7F4A_7C15,
+            margin_asset: "USDT".to_string(),
+        }
+    }
+}
+
+impl InvariantSamplerConfig {
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    pub fn with_probes(mut self, order_probes: usize, position_probes: usize) -> Self {
+        self.order_probes = order_probes;
+        self.position_probes = position_probes;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// 抽中的订单
+#[derive(Debug, Clone)]
+pub struct OrderProbe {
+    pub order: Order,
+    /// 采样时订单是否位于所属价格档位的队列中
+    pub queued: bool,
+}
+
+/// 抽中的仓位
+#[derive(Debug, Clone)]
+pub struct PositionProbe {
+    pub position: Position,
+    /// 采样时按交易者与持仓方向查到的仓位
+    pub indexed: Option<PositionId>,
+}
+
+/// 抽中的挂单账户
+#[derive(Debug, Clone)]
+pub struct AccountProbe {
+    pub trader: TraderId,
+    pub orders: Vec<Order>,
+    pub positions: Vec<Position>,
+    pub leverage: Leverage,
+}
+
+/// 一轮采样的副本（撮合线程生成，后台检查）
+#[derive(Debug, Clone)]
+pub struct InvariantProbe {
+    /// 采样时的引擎序列号
+    pub sequence: u64,
+    pub captured_at: Timestamp,
+    pub best_bid: Option<Price>,
+    pub best_ask: Option<Price>,
+    pub orders: Vec<OrderProbe>,
+    pub positions: Vec<PositionProbe>,
+    pub accounts: Vec<AccountProbe>,
+}
+
+/// 不变量采样器（撮合线程持有）
+#[derive(Debug, Clone)]
+pub struct InvariantSampler {
+    config: InvariantSamplerConfig,
+    /// xorshift64 状态（非 0）
+    state: u64,
+}
+
+impl InvariantSampler {
+    pub fn new(config: InvariantSamplerConfig) -> Self {
+        let state = config.seed.max(1);
+        Self { config, state }
+    }
+
+    pub fn config(&self) -> &InvariantSamplerConfig {
+        &self.config
+    }
+
+    /// 该序列号是否应采样
+    pub fn is_due(&self, sequence: u64) -> bool {
+        sequence.is_multiple_of(self.config.interval.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// 在 [1, next_id) 中随机取一个 ID（尚未分配过 ID 时为 None）
+    fn pick(&mut self, next_id: u64) -> Option<u64> {
+        (next_id > 1).then(|| 1 + self.next_u64() % (next_id - 1))
+    }
+
+    /// 采样订单簿、仓位与挂单账户
+    pub fn capture<O, P>(
+        &mut self,
+        orders: &O,
+        positions: &P,
+        leverage: impl Fn(TraderId) -> Leverage,
+        sequence: u64,
+        now: Timestamp,
+    ) -> InvariantProbe
+    where
+        O: OrderRepository,
+        P: PositionRepository,
+    {
+        let mut probe = InvariantProbe {
+            sequence,
+            captured_at: now,
+            best_bid: orders.best_bid(),
+            best_ask: orders.best_ask(),
+            orders: Vec::new(),
+            positions: Vec::new(),
+            accounts: Vec::new(),
+        };
+
+        let mut traders = BTreeSet::new();
+        for _ in 0..self.config.order_probes {
+            let Some(id) = self.pick(orders.peek_next_order_id()) else {
+                break;
+            };
+            // 已成交或已撤销的订单不在仓储中
+            let Some(order) = orders.get_order(id) else {
+                continue;
+            };
+            let queued = order.is_active() && {
+                let level = if order.is_buy() {
+                    orders.get_bids_at_price(order.price)
+                } else {
+                    orders.get_asks_at_price(order.price)
+                };
+                level.iter().any(|o| o.id == id)
+            };
+            if order.is_active() {
+                traders.insert(order.trader);
+            }
+            probe.orders.push(OrderProbe { order: order.clone(), queued });
+        }
+
+        for _ in 0..self.config.position_probes {
+            let Some(id) = self.pick(positions.peek_next_position_id()) else {
+                break;
+            };
+            let Some(position) = positions.get_position(id) else {
+                continue;
+            };
+            let indexed = positions
+                .get_position_by_trader_side(position.trader, position.position_side)
+                .map(|p| p.id);
+            probe.positions.push(PositionProbe { position: position.clone(), indexed });
+        }
+
+        for trader in traders {
+            probe.accounts.push(AccountProbe {
+                trader,
+                orders: orders.get_orders_by_trader(trader).into_iter().cloned().collect(),
+                positions: positions.get_positions_by_trader(trader).into_iter().cloned().collect(),
+                leverage: leverage(trader),
+            });
+        }
+        probe
+    }
+}
+
+/// 抽样统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SamplerStats {
+    /// 检查轮数
+    pub runs: u64,
+    /// 实际检查的对象数
+    pub checks: u64,
+    /// 发现的违例数
+    pub violations: u64,
+}
+
+/// 不变量检查器（后台线程持有）
+#[derive(Debug, Clone)]
+pub struct InvariantChecker {
+    margin_asset: String,
+    stats: SamplerStats,
+}
+
+impl InvariantChecker {
+    pub fn new(margin_asset: impl Into<String>) -> Self {
+        Self { margin_asset: margin_asset.into(), stats: SamplerStats::default() }
+    }
+
+    pub fn stats(&self) -> SamplerStats {
+        self.stats
+    }
+
+    /// 检查一轮采样
+    ///
+    /// 余额由账户服务按下单前的预占检查异步冻结，检查晚于采样，冻结通常已追平；
+    /// 冻结余额可能还包含其他市场的保证金，因此只检查下限
+    pub fn check(
+        &mut self,
+        probe: &InvariantProbe,
+        balances: &dyn BalanceReader,
+    ) -> Vec<InvariantViolation> {
+        self.stats.runs += 1;
+        let mut violations = Vec::new();
+        let mut violation = |kind, detail: String, context| {
+            violations.push(InvariantViolation {
+                kind,
+                sequence: probe.sequence,
+                detected_at: probe.captured_at,
+                detail,
+                context,
+            });
+        };
+
+        self.stats.checks += 1;
+        if let (Some(best_bid), Some(best_ask)) = (probe.best_bid, probe.best_ask) {
+            if best_bid >= best_ask {
+                violation(
+                    InvariantKind::BookCrossed,
+                    format!("最优买价 {best_bid} 不低于最优卖价 {best_ask}"),
+                    ViolationContext::Book { best_bid, best_ask },
+                );
+            }
+        }
+
+        for sampled in &probe.orders {
+            self.stats.checks += 1;
+            for (kind, detail) in order_faults(sampled) {
+                violation(kind, detail, ViolationContext::Order(sampled.order.clone()));
+            }
+        }
+
+        for sampled in &probe.positions {
+            self.stats.checks += 1;
+            for (kind, detail) in position_faults(sampled) {
+                violation(kind, detail, ViolationContext::Position(sampled.position.clone()));
+            }
+        }
+
+        for account in &probe.accounts {
+            self.stats.checks += 1;
+            let trader = account.trader;
+            let required_margin =
+                worst_case_margin(&account.orders, &account.positions, account.leverage);
+            let balances = balances.balances_of(trader);
+            let frozen =
+                balances.iter().find(|b| b.asset == self.margin_asset).map_or(0, |b| b.frozen);
+            if frozen < required_margin {
+                violation(
+                    InvariantKind::FrozenMargin,
+                    format!(
+                        "交易者 {trader} 冻结 {frozen} {} 低于挂单保证金 {required_margin}",
+                        self.margin_asset
+                    ),
+                    ViolationContext::Balance { trader, balances, required_margin },
+                );
+            }
+        }
+
+        self.stats.violations += violations.len() as u64;
+        violations
+    }
+}
+
+fn order_faults(sampled: &OrderProbe) -> Vec<(InvariantKind, String)> {
+    let order = &sampled.order;
+    let mut faults = Vec::new();
+    let id: OrderId = order.id;
+    if order.remaining_quantity + order.filled_quantity != order.original_quantity {
+        faults.push((
+            InvariantKind::OrderQuantity,
+            format!(
+                "订单 {id} 剩余 {} + 已成交 {} != 原始 {}",
+                order.remaining_quantity, order.filled_quantity, order.original_quantity
+            ),
+        ));
+    }
+    if order.is_active() {
+        if order.remaining_quantity == 0 {
+            faults.push((
+                InvariantKind::OrderStatus,
+                format!("订单 {id} 状态 {:?} 但剩余数量为 0", order.status),
+            ));
+        }
+        if !sampled.queued {
+            faults.push((
+                InvariantKind::OrderIndexed,
+                format!("订单 {id} 不在价格档位 {} 的队列中", order.price),
+            ));
+        }
+    }
+    faults
+}
+
+fn position_faults(sampled: &PositionProbe) -> Vec<(InvariantKind, String)> {
+    let position = &sampled.position;
+    let mut faults = Vec::new();
+    let id: PositionId = position.id;
+    if position.quantity == 0 {
+        faults.push((InvariantKind::PositionQuantity, format!("仓位 {id} 数量为 0 但未移除")));
+    }
+    let indexed = sampled.indexed;
+    if indexed != Some(id) {
+        faults.push((
+            InvariantKind::PositionIndexed,
+            format!(
+                "交易者 {} {:?} 方向索引指向 {indexed:?} 而非仓位 {id}",
+                position.trader, position.position_side
+            ),
+        ));
+    }
+    faults
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::domain::entity::{AssetBalance, MarginMode, PositionSide, Side, TimeInForce};
+    use crate::domain::service::command::{Command, PrepCommandHandler};
+    use crate::domain::service::matching::MatchingService;
+
+    /// 每个账户冻结相同数量的保证金
+    struct Frozen(u64);
+
+    impl BalanceReader for Frozen {
+        fn balances_of(&self, _trader: TraderId) -> Vec<AssetBalance> {
+            vec![AssetBalance::new("USDT", 0, self.0)]
+        }
+    }
+
+    fn config() -> InvariantSamplerConfig {
+        InvariantSamplerConfig::default().with_probes(16, 16)
+    }
+
+    fn order(orders: &mut InMemoryOrderRepository, trader: u64, side: Side, price: u64) -> Order {
+        let id = orders.next_order_id();
+        let order = Order::new(
+            id,
+            trader,
+            side,
+            price,
+            10,
+            PositionSide::Both,
+            false,
+            TimeInForce::GTC,
+            1_000,
+        );
+        orders.save_order(order.clone()).unwrap();
+        order
+    }
+
+    fn position(positions: &mut InMemoryPositionRepository, trader: u64, quantity: u64) {
+        let id = positions.next_position_id();
+        let position = Position::new(
+            id,
+            trader,
+            PositionSide::Long,
+            quantity,
+            100,
+            MarginMode::Cross,
+            10,
+            100,
+            1_000,
+        );
+        positions.save_position(position).unwrap();
+    }
+
+    #[test]
+    fn test_consistent_state_has_no_violations() {
+        let mut orders = InMemoryOrderRepository::new();
+        let mut positions = InMemoryPositionRepository::new();
+        let mut sampler = InvariantSampler::new(config());
+        let mut checker = InvariantChecker::new("USDT");
+        let probe = sampler.capture(&orders, &positions, |_| 10, 0, 1_000);
+        assert!(checker.check(&probe, &Frozen(1_000)).is_empty());
+
+        order(&mut orders, 1, Side::Buy, 99);
+        order(&mut orders, 2, Side::Sell, 101);
+        position(&mut positions, 1, 5);
+
+        let probe = sampler.capture(&orders, &positions, |_| 10, 1, 1_000);
+        assert_eq!(probe.accounts.len(), 2);
+        assert!(checker.check(&probe, &Frozen(1_000)).is_empty());
+        let stats = checker.stats();
+        assert_eq!((stats.runs, stats.violations), (2, 0));
+        // 每轮都检查盘口，第二轮另有 16 + 16 次命中与 2 个挂单账户
+        assert_eq!(stats.checks, 2 + 32 + 2);
+        assert!(sampler.is_due(2_000) && !sampler.is_due(2_001));
+    }
+
+    #[test]
+    fn test_detects_corrupted_order_and_position() {
+        let mut orders = InMemoryOrderRepository::new();
+        let mut positions = InMemoryPositionRepository::new();
+        let mut broken = order(&mut orders, 1, Side::Buy, 99);
+        broken.filled_quantity = 3;
+        orders.save_order(broken.clone()).unwrap();
+        order(&mut orders, 2, Side::Sell, 98);
+        position(&mut positions, 1, 0);
+
+        let mut sampler = InvariantSampler::new(config());
+        let probe = sampler.capture(&orders, &positions, |_| 10, 7, 2_000);
+        // 检查在采样副本上进行，采样后仓储的变化不影响本轮结果
+        broken.filled_quantity = 0;
+        orders.save_order(broken).unwrap();
+
+        let mut checker = InvariantChecker::new("USDT");
+        let violations = checker.check(&probe, &Frozen(u64::MAX));
+        let kinds: BTreeSet<&str> = violations.iter().map(|v| v.kind.as_str()).collect();
+        assert_eq!(kinds, BTreeSet::from(["BOOK_CROSSED", "ORDER_QUANTITY", "POSITION_QUANTITY"]));
+        assert!(violations.iter().all(|v| v.sequence == 7 && v.detected_at == 2_000));
+        let quantity = violations.iter().find(|v| v.kind == InvariantKind::OrderQuantity).unwrap();
+        assert!(matches!(&quantity.context, ViolationContext::Order(o) if o.id == 1));
+        assert_eq!(checker.stats().violations, violations.len() as u64);
+    }
+
+    #[test]
+    fn test_detects_under_frozen_margin() {
+        struct PerTrader;
+
+        impl BalanceReader for PerTrader {
+            fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance> {
+                let frozen = if trader == 1 { 100 } else { 40 };
+                vec![AssetBalance::new("USDT", 0, frozen)]
+            }
+        }
+
+        let mut orders = InMemoryOrderRepository::new();
+        let positions = InMemoryPositionRepository::new();
+        // 10 @ 100，10 倍杠杆需冻结 100
+        order(&mut orders, 1, Side::Buy, 100);
+        order(&mut orders, 2, Side::Sell, 100);
+
+        let probe = InvariantSampler::new(config()).capture(&orders, &positions, |_| 10, 3, 5_000);
+        let violations = InvariantChecker::new("USDT").check(&probe, &PerTrader);
+        let frozen: Vec<_> =
+            violations.iter().filter(|v| v.kind == InvariantKind::FrozenMargin).collect();
+        assert_eq!(frozen.len(), 1);
+        assert!(matches!(
+            &frozen[0].context,
+            ViolationContext::Balance { trader: 2, required_margin: 100, .. }
+        ));
+    }
+
+    #[test]
+    fn test_engine_captures_every_interval() {
+        let mut engine =
+            MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        engine.enable_invariant_sampling(config().with_interval(3));
+        for (trader, side, price) in [(1, Side::Sell, 101), (2, Side::Buy, 99), (3, Side::Buy, 101)]
+        {
+            engine.handle(Command::LimitOrder {
+                trader,
+                side,
+                price,
+                quantity: 5,
+                position_side: PositionSide::Both,
+                reduce_only: false,
+                time_in_force: TimeInForce::GTC,
+            });
+        }
+        // 序列号 3 采样一次，撮合线程只生成副本
+        let probes = engine.drain_invariant_probes();
+        assert_eq!(probes.len(), 1);
+        assert_eq!(probes[0].sequence, 3);
+        assert!(engine.drain_invariant_probes().is_empty());
+
+        // 账户服务未冻结保证金：交易者 2 的挂单被发现
+        let violations = InvariantChecker::new("USDT").check(&probes[0], &Frozen(0));
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            violations[0].context,
+            ViolationContext::Balance { trader: 2, required_margin: 49, .. }
+        ));
+    }
+}
@@ -0,0 +1,407 @@
+//! 成交量与盈亏排行榜投影
+//!
+//! 按自然日 / 自然周（UTC，周一起）汇总账户成交额与已实现盈亏，
+//! 由成交、成交撤销与资金费结算增量更新，查询时按指标排序分页。
+//! 每个周期只保留最近若干个桶，早于保留范围的事件（迟到的结算、
+//! 对已淘汰成交的撤销）直接丢弃，不会重建已淘汰的桶
+//!
+//! 隐私：账户默认不上榜，须主动选择公开或匿名上榜；
+//! 匿名上榜参与排名但不返回交易者ID
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::entity::{
+    EngineEvent, EventEnvelope, SettlementType, Timestamp, TradeRecord, TraderId,
+};
+
+/// 一天的毫秒数
+const DAY_MILLIS: u64 = 86_400_000;
+
+/// 默认每个周期保留的统计桶数
+pub const DEFAULT_RETAINED_BUCKETS: usize = 8;
+
+/// 统计周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StatsPeriod {
+    /// 自然日
+    Daily,
+    /// 自然周（周一起）
+    Weekly,
+}
+
+impl StatsPeriod {
+    /// 时间所在的统计桶编号
+    pub fn bucket(self, timestamp: Timestamp) -> u64 {
+        let day = timestamp / DAY_MILLIS;
+        match self {
+            StatsPeriod::Daily => day,
+            // 1970-01-01 为周四，平移 3 天使周一为一周起点
+            StatsPeriod::Weekly => (day + 3) / 7,
+        }
+    }
+
+    const ALL: [StatsPeriod; 2] = [StatsPeriod::Daily, StatsPeriod::Weekly];
+}
+
+/// 排名指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardMetric {
+    /// 成交额
+    Volume,
+    /// 已实现盈亏（含资金费）
+    Pnl,
+}
+
+/// 上榜设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeaderboardVisibility {
+    /// 不上榜
+    #[default]
+    Hidden,
+    /// 匿名上榜
+    Anonymous,
+    /// 公开上榜
+    Public,
+}
+
+/// 账户周期统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountStats {
+    /// 成交额（价格 × 数量）
+    pub volume: u64,
+    /// 成交笔数
+    pub trade_count: u64,
+    /// 已实现盈亏（含资金费）
+    pub realized_pnl: i64,
+}
+
+/// 排行榜条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    /// 名次（从 1 开始）
+    pub rank: usize,
+    /// 交易者ID（匿名上榜为 None）
+    pub trader: Option<TraderId>,
+    /// 周期统计
+    pub stats: AccountStats,
+}
+
+/// 排行榜分页结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardPage {
+    /// 统计周期
+    pub period: StatsPeriod,
+    /// 统计桶编号
+    pub bucket: u64,
+    /// 页号（0-based）
+    pub page: usize,
+    /// 每页条数
+    pub page_size: usize,
+    /// 上榜账户总数
+    pub total: usize,
+    /// 本页条目
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// 成交量与盈亏统计投影
+#[derive(Debug)]
+pub struct StatisticsProjection {
+    /// (周期, 桶) -> 账户统计
+    buckets: BTreeMap<(StatsPeriod, u64), HashMap<TraderId, AccountStats>>,
+    /// 上榜设置
+    visibility: HashMap<TraderId, LeaderboardVisibility>,
+    /// 每个周期保留的桶数
+    retained_buckets: usize,
+}
+
+impl Default for StatisticsProjection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatisticsProjection {
+    /// 创建空投影
+    pub fn new() -> Self {
+        Self::with_retention(DEFAULT_RETAINED_BUCKETS)
+    }
+
+    /// 指定每个周期保留的桶数（至少 1）
+    pub fn with_retention(retained_buckets: usize) -> Self {
+        Self {
+            buckets: BTreeMap::new(),
+            visibility: HashMap::new(),
+            retained_buckets: retained_buckets.max(1),
+        }
+    }
+
+    /// 应用引擎事件（成交、成交撤销与系统平仓结算）
+    pub fn apply(&mut self, envelope: &EventEnvelope) {
+        match &envelope.event {
+            EngineEvent::Trade(record) => self.apply_trade(record, false),
+            EngineEvent::TradeBusted(record) => self.apply_trade(record, true),
+            // 冲正结算的盈亏已随 TradeBusted 冲回
+            EngineEvent::PositionSettled(settlement)
+                if settlement.settlement_type != SettlementType::TradeBust =>
+            {
+                self.record_settlement(
+                    settlement.trader,
+                    settlement.realized_pnl,
+                    settlement.settled_at,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    /// 计入结算盈亏（资金费等），`pnl` 为账户收入（负数为支出）
+    pub fn record_settlement(&mut self, trader: TraderId, pnl: i64, timestamp: Timestamp) {
+        for period in StatsPeriod::ALL {
+            if let Some(stats) = self.stats_mut(period, timestamp, trader) {
+                stats.realized_pnl += pnl;
+            }
+        }
+    }
+
+    /// 设置账户上榜方式
+    pub fn set_visibility(&mut self, trader: TraderId, visibility: LeaderboardVisibility) {
+        if visibility == LeaderboardVisibility::Hidden {
+            self.visibility.remove(&trader);
+        } else {
+            self.visibility.insert(trader, visibility);
+        }
+    }
+
+    /// 账户上榜方式
+    pub fn visibility(&self, trader: TraderId) -> LeaderboardVisibility {
+        self.visibility.get(&trader).copied().unwrap_or_default()
+    }
+
+    /// 账户自身在某周期的统计（不受上榜设置影响）
+    pub fn account_stats(
+        &self,
+        trader: TraderId,
+        period: StatsPeriod,
+        timestamp: Timestamp,
+    ) -> AccountStats {
+        self.buckets
+            .get(&(period, period.bucket(timestamp)))
+            .and_then(|bucket| bucket.get(&trader))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 排行榜分页
+    ///
+    /// 指标相同时按交易者ID排序，保证翻页结果稳定
+    pub fn leaderboard(
+        &self,
+        period: StatsPeriod,
+        timestamp: Timestamp,
+        metric: LeaderboardMetric,
+        page: usize,
+        page_size: usize,
+    ) -> LeaderboardPage {
+        let bucket = period.bucket(timestamp);
+        let mut ranked: Vec<(TraderId, AccountStats, LeaderboardVisibility)> = self
+            .buckets
+            .get(&(period, bucket))
+            .into_iter()
+            .flatten()
+            .filter_map(|(trader, stats)| {
+                let visibility = self.visibility.get(trader).copied()?;
+                Some((*trader, *stats, visibility))
+            })
+            .collect();
+        ranked.sort_unstable_by(|(a, a_stats, _), (b, b_stats, _)| {
+            let order = match metric {
+                LeaderboardMetric::Volume => b_stats.volume.cmp(&a_stats.volume),
+                LeaderboardMetric::Pnl => b_stats.realized_pnl.cmp(&a_stats.realized_pnl),
+            };
+            order.then(a.cmp(b))
+        });
+
+        let offset = page.saturating_mul(page_size);
+        let entries = ranked
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(page_size)
+            .map(|(index, (trader, stats, visibility))| LeaderboardEntry {
+                rank: index + 1,
+                trader: (*visibility == LeaderboardVisibility::Public).then_some(*trader),
+                stats: *stats,
+            })
+            .collect();
+
+        LeaderboardPage { period, bucket, page, page_size, total: ranked.len(), entries }
+    }
+
+    /// 成交计入（或撤销时冲回）双方所在周期
+    fn apply_trade(&mut self, record: &TradeRecord, reverse: bool) {
+        let volume = record.price * record.quantity;
+        for leg in [record.taker, record.maker] {
+            for period in StatsPeriod::ALL {
+                let Some(stats) = self.stats_mut(period, record.timestamp, leg.trader) else {
+                    continue;
+                };
+                if reverse {
+                    stats.volume = stats.volume.saturating_sub(volume);
+                    stats.trade_count = stats.trade_count.saturating_sub(1);
+                    stats.realized_pnl -= leg.realized_pnl;
+                } else {
+                    stats.volume += volume;
+                    stats.trade_count += 1;
+                    stats.realized_pnl += leg.realized_pnl;
+                }
+            }
+        }
+    }
+
+    /// 账户所在桶的统计，桶早于保留范围时返回 None
+    fn stats_mut(
+        &mut self,
+        period: StatsPeriod,
+        timestamp: Timestamp,
+        trader: TraderId,
+    ) -> Option<&mut AccountStats> {
+        let bucket = period.bucket(timestamp);
+        if bucket < self.horizon(period) {
+            return None;
+        }
+        if let Entry::Vacant(slot) = self.buckets.entry((period, bucket)) {
+            slot.insert(HashMap::new());
+            self.evict(period);
+        }
+        Some(self.buckets.entry((period, bucket)).or_default().entry(trader).or_default())
+    }
+
+    /// 保留范围内最早的桶编号（最新桶往前 `retained_buckets - 1` 个）
+    fn horizon(&self, period: StatsPeriod) -> u64 {
+        self.buckets
+            .range((period, 0)..=(period, u64::MAX))
+            .next_back()
+            .map_or(0, |((_, newest), _)| newest.saturating_sub(self.retained_buckets as u64 - 1))
+    }
+
+    /// 淘汰超出保留数量的最旧桶
+    fn evict(&mut self, period: StatsPeriod) {
+        let keys: Vec<_> =
+            self.buckets.range((period, 0)..=(period, u64::MAX)).map(|(k, _)| *k).collect();
+        let excess = keys.len().saturating_sub(self.retained_buckets);
+        for key in &keys[..excess] {
+            self.buckets.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{PositionSide, Side, TradeLeg};
+
+    fn leg(trader: TraderId, side: Side, realized_pnl: i64) -> TradeLeg {
+        TradeLeg {
+            trader,
+            side,
+            position_side: PositionSide::Both,
+            opened: realized_pnl == 0,
+            quantity: 10,
+            entry_price_before: 0,
+            realized_pnl,
+        }
+    }
+
+    fn trade(
+        trade_id: u64,
+        taker: TradeLeg,
+        maker: TradeLeg,
+        timestamp: Timestamp,
+    ) -> EventEnvelope {
+        EventEnvelope {
+            sequence: trade_id,
+            event: EngineEvent::Trade(TradeRecord {
+                trade_id,
+                price: 100,
+                quantity: 10,
+                taker,
+                maker,
+                timestamp,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_period_buckets() {
+        // 1970-01-05 为周一
+        assert_eq!(StatsPeriod::Weekly.bucket(3 * DAY_MILLIS), 0);
+        assert_eq!(StatsPeriod::Weekly.bucket(4 * DAY_MILLIS), 1);
+        assert_eq!(StatsPeriod::Daily.bucket(DAY_MILLIS - 1), 0);
+        assert_eq!(StatsPeriod::Daily.bucket(DAY_MILLIS), 1);
+    }
+
+    #[test]
+    fn test_leaderboard_privacy_and_pagination() {
+        let mut stats = StatisticsProjection::new();
+        stats.apply(&trade(1, leg(1, Side::Buy, 0), leg(2, Side::Sell, 0), 1000));
+        stats.apply(&trade(2, leg(1, Side::Sell, 50), leg(3, Side::Buy, -50), 2000));
+        stats.record_settlement(3, 80, 3000);
+
+        // 未设置上榜的账户不出现，但可查询自身统计
+        assert_eq!(
+            stats.leaderboard(StatsPeriod::Daily, 0, LeaderboardMetric::Volume, 0, 10).total,
+            0
+        );
+        assert_eq!(stats.account_stats(1, StatsPeriod::Weekly, 0).volume, 2000);
+
+        stats.set_visibility(1, LeaderboardVisibility::Public);
+        stats.set_visibility(2, LeaderboardVisibility::Anonymous);
+        stats.set_visibility(3, LeaderboardVisibility::Public);
+
+        let volume = stats.leaderboard(StatsPeriod::Daily, 0, LeaderboardMetric::Volume, 0, 2);
+        assert_eq!(volume.total, 3);
+        assert_eq!(volume.entries.len(), 2);
+        assert_eq!((volume.entries[0].rank, volume.entries[0].trader), (1, Some(1)));
+        // 同成交额按交易者ID排序，匿名账户不返回ID
+        assert_eq!((volume.entries[1].rank, volume.entries[1].trader), (2, None));
+
+        let next = stats.leaderboard(StatsPeriod::Daily, 0, LeaderboardMetric::Volume, 1, 2);
+        assert_eq!(next.entries.len(), 1);
+        assert_eq!((next.entries[0].rank, next.entries[0].trader), (3, Some(3)));
+
+        let pnl = stats.leaderboard(StatsPeriod::Weekly, 0, LeaderboardMetric::Pnl, 0, 10);
+        assert_eq!(pnl.entries[0].trader, Some(1));
+        assert_eq!(pnl.entries[0].stats.realized_pnl, 50);
+        assert_eq!(pnl.entries[1].stats.realized_pnl, 30);
+    }
+
+    #[test]
+    fn test_bust_reverses_and_old_buckets_evicted() {
+        let mut stats = StatisticsProjection::with_retention(2);
+        let first = trade(1, leg(1, Side::Buy, 0), leg(2, Side::Sell, 0), 0);
+        stats.apply(&first);
+        let EngineEvent::Trade(record) = first.event else { unreachable!() };
+        stats.apply(&EventEnvelope { sequence: 2, event: EngineEvent::TradeBusted(record) });
+        assert_eq!(stats.account_stats(1, StatsPeriod::Daily, 0), AccountStats::default());
+
+        for day in 1..=2 {
+            stats.record_settlement(1, 1, day * DAY_MILLIS);
+        }
+        // 第 0 天已淘汰，周桶仍保留
+        assert_eq!(
+            stats.buckets.range((StatsPeriod::Daily, 0)..=(StatsPeriod::Daily, u64::MAX)).count(),
+            2
+        );
+        assert!(!stats.buckets.contains_key(&(StatsPeriod::Daily, 0)));
+        assert_eq!(stats.account_stats(1, StatsPeriod::Weekly, 0).realized_pnl, 2);
+
+        // 迟到的结算与对已淘汰成交的撤销不重建旧桶，周桶照常计入
+        stats.record_settlement(1, 5, 0);
+        stats.apply(&trade(3, leg(1, Side::Buy, 0), leg(2, Side::Sell, 0), 10));
+        assert!(!stats.buckets.contains_key(&(StatsPeriod::Daily, 0)));
+        assert_eq!(stats.account_stats(1, StatsPeriod::Daily, 0), AccountStats::default());
+        assert_eq!(stats.account_stats(1, StatsPeriod::Weekly, 0).realized_pnl, 7);
+        assert_eq!(stats.account_stats(2, StatsPeriod::Weekly, 0).trade_count, 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! 批量报价与做市商保护（MMP）
+//!
+//! 做市账户用一条 `Command::MassQuote` 原子替换自己的全部报价：整组报价先整体校验
+//! （价格、数量、不与订单簿及自身交叉、风控限额），通过后撤掉旧报价再挂新报价，
+//! 任一档不合法则整条拒绝，旧报价保持不变。空报价即撤回全部报价。
+//!
+//! 做市商保护在引擎内按做市账户维护：`window` 毫秒内报价被成交达到 `fill_limit` 笔，
+//! 撮合结束后立即撤回该账户全部报价并冻结报价，直到冻结期满或 `Command::ResetMmp`。
+//! 报价集合与保护状态随做市会话存在，会话断开时由接入层发送空报价撤回全部报价
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::domain::entity::{MmpConfig, MmpState, MmpTrigger, OrderId, Timestamp, TraderId};
+
+/// 单个做市账户的报价与保护状态
+#[derive(Debug, Clone, Default)]
+struct MakerState {
+    config: Option<MmpConfig>,
+    /// 当前报价订单（可能已被完全成交）
+    quotes: Vec<OrderId>,
+    /// 窗口内的报价成交时间
+    fills: VecDeque<Timestamp>,
+    /// 冻结中：Some(冻结截止时间，None=需手动解除)
+    frozen: Option<Option<Timestamp>>,
+}
+
+/// 做市商保护
+#[derive(Debug, Clone, Default)]
+pub struct MarketMakerProtection {
+    makers: HashMap<TraderId, MakerState>,
+    /// 触发记录（只追加）
+    log: Vec<MmpTrigger>,
+}
+
+impl MarketMakerProtection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置做市商保护（None=关闭），清空窗口内的成交计数
+    pub fn set_config(&mut self, trader: TraderId, config: Option<MmpConfig>) {
+        let state = self.makers.entry(trader).or_default();
+        state.config = config;
+        state.fills.clear();
+    }
+
+    pub fn config(&self, trader: TraderId) -> Option<MmpConfig> {
+        self.makers.get(&trader).and_then(|s| s.config)
+    }
+
+    /// 账户当前的报价订单
+    pub fn quotes(&self, trader: TraderId) -> &[OrderId] {
+        self.makers.get(&trader).map_or(&[], |s| s.quotes.as_slice())
+    }
+
+    /// 替换账户的报价订单，返回旧报价
+    pub fn replace_quotes(&mut self, trader: TraderId, quotes: Vec<OrderId>) -> Vec<OrderId> {
+        std::mem::replace(&mut self.makers.entry(trader).or_default().quotes, quotes)
+    }
+
+    /// 报价是否被冻结（冻结期满自动解除）
+    pub fn is_frozen(&self, trader: TraderId, now: Timestamp) -> bool {
+        match self.makers.get(&trader).and_then(|s| s.frozen) {
+            Some(Some(until)) => now < until,
+            Some(None) => true,
+            None => false,
+        }
+    }
+
+    /// 手动解除冻结，返回是否曾处于冻结
+    pub fn reset(&mut self, trader: TraderId, now: Timestamp) -> bool {
+        let frozen = self.is_frozen(trader, now);
+        if let Some(state) = self.makers.get_mut(&trader) {
+            state.frozen = None;
+            state.fills.clear();
+        }
+        frozen
+    }
+
+    /// 记录一笔 Maker 成交；报价成交达到阈值时撤回全部报价、冻结并返回触发记录
+    ///
+    /// 撤回的订单由调用方撤销
+    pub fn on_fill(
+        &mut self,
+        trader: TraderId,
+        order_id: OrderId,
+        now: Timestamp,
+    ) -> Option<MmpTrigger> {
+        let state = self.makers.get_mut(&trader)?;
+        let config = state.config?;
+        if !state.quotes.contains(&order_id) {
+            return None;
+        }
+        state.fills.push_back(now);
+        while state.fills.front().is_some_and(|&t| now.saturating_sub(t) >= config.window) {
+            state.fills.pop_front();
+        }
+        if (state.fills.len() as u32) < config.fill_limit.max(1) {
+            return None;
+        }
+
+        let frozen_until = config.freeze.map(|freeze| now.saturating_add(freeze));
+        let trigger = MmpTrigger {
+            trader,
+            fills: state.fills.len() as u32,
+            pulled: std::mem::take(&mut state.quotes),
+            triggered_at: now,
+            frozen_until,
+        };
+        state.fills.clear();
+        state.frozen = Some(frozen_until);
+        self.log.push(trigger.clone());
+        Some(trigger)
+    }
+
+    /// 触发记录
+    pub fn log(&self) -> &[MmpTrigger] {
+        &self.log
+    }
+
+    /// 导出各做市账户的报价与保护状态（按交易者ID排序，不含触发记录）
+    pub fn export(&self) -> Vec<MmpState> {
+        let mut states: Vec<MmpState> = self
+            .makers
+            .iter()
+            .map(|(trader, state)| MmpState {
+                trader: *trader,
+                config: state.config,
+                quotes: state.quotes.clone(),
+                fills: state.fills.iter().copied().collect(),
+                frozen: state.frozen,
+            })
+            .collect();
+        states.sort_unstable_by_key(|state| state.trader);
+        states
+    }
+
+    /// 由导出的状态重建
+    pub fn import(states: impl IntoIterator<Item = MmpState>) -> Self {
+        let makers = states
+            .into_iter()
+            .map(|state| {
+                let maker = MakerState {
+                    config: state.config,
+                    quotes: state.quotes,
+                    fills: state.fills.into(),
+                    frozen: state.frozen,
+                };
+                (state.trader, maker)
+            })
+            .collect();
+        Self { makers, log: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protection(freeze: Option<Timestamp>) -> MarketMakerProtection {
+        let mut mmp = MarketMakerProtection::new();
+        mmp.set_config(1, Some(MmpConfig { window: 100, fill_limit: 3, freeze }));
+        mmp.replace_quotes(1, vec![10, 11, 12]);
+        mmp
+    }
+
+    #[test]
+    fn test_fills_within_window_trigger() {
+        let mut mmp = protection(Some(500));
+        // 非报价订单不计数
+        assert_eq!(mmp.on_fill(1, 99, 0), None);
+        assert_eq!(mmp.on_fill(1, 10, 0), None);
+        assert_eq!(mmp.on_fill(1, 11, 50), None);
+        // 第一笔滑出窗口
+        assert_eq!(mmp.on_fill(1, 12, 100), None);
+
+        let trigger = mmp.on_fill(1, 12, 120).unwrap();
+        assert_eq!((trigger.fills, trigger.frozen_until), (3, Some(620)));
+        assert_eq!(trigger.pulled, vec![10, 11, 12]);
+        assert!(mmp.quotes(1).is_empty());
+        assert!(mmp.is_frozen(1, 619));
+        assert!(!mmp.is_frozen(1, 620));
+        assert_eq!(mmp.log().len(), 1);
+    }
+
+    #[test]
+    fn test_manual_reset() {
+        let mut mmp = protection(None);
+        for (order_id, now) in [(10, 0), (11, 1)] {
+            assert_eq!(mmp.on_fill(1, order_id, now), None);
+        }
+        assert!(mmp.on_fill(1, 12, 2).is_some());
+        assert!(mmp.is_frozen(1, 1_000_000));
+        assert!(mmp.reset(1, 1_000_000));
+        assert!(!mmp.is_frozen(1, 1_000_000));
+        assert!(!mmp.reset(1, 1_000_000));
+
+        // 未配置保护的账户不计数
+        mmp.replace_quotes(2, vec![20]);
+        assert_eq!(mmp.on_fill(2, 20, 0), None);
+    }
+}
@@ -2,13 +2,168 @@
 //!
 //! 实现永续合约订单撮合逻辑
 
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use conditional_order::{
+    ConditionalBook, ConditionalEntry, ConditionalError, ConditionalEvent, ConditionalId,
+    Direction, Trail, TriggerSpec,
+};
+
 use crate::domain::ErrorCode;
 use crate::domain::entity::{
-    Leverage, MarginMode, Order, OrderId, OrderStatus, Position, PositionSide, Price, Quantity,
-    Side, TimeInForce, Timestamp, Trade, TraderId,
+    AccountSettingChange, AccountSettingRecord, AccountSettings, AccountStatus, AssetBalance,
+    CircuitBreakerRecord, EngineEvent, EventEnvelope, ExecutionReport, Leverage, MAX_LEVERAGE,
+    Margin, MarginMode, MarketAlert, MmpState, Order, OrderId, OrderStatus, Position, PositionId,
+    PositionSettlement, PositionSide, Price, PriceFeed, Quantity, QuoteEntry, RecurringExecution,
+    RecurringPlanId, RecurringSpec, RiskProfile, SETTING_HISTORY_LIMIT, SettlementType, Side,
+    TRADE_JOURNAL_LIMIT, TimeInForce, Timestamp, Trade, TradeBustRecord, TradeId, TradeLeg,
+    TradeRecord, TraderId,
+};
+use crate::domain::repository::{
+    BalanceReader, OrderRepository, PositionRepository, RepositoryError,
 };
-use crate::domain::repository::{OrderRepository, PositionRepository};
+use crate::domain::service::anomaly::{AnomalyConfig, AnomalyDetector};
 use crate::domain::service::command::{Command, CommandResult, PrepCommandHandler};
+use crate::domain::service::compression::{
+    CompressionLeg, CompressionRecord, CompressionSettlement, ParentAccountId, SubAccountGroups,
+};
+use crate::domain::service::delivery::DeliveryRecord;
+use crate::domain::service::digest::{StepDigest, book_digest, positions_digest, trades_digest};
+use crate::domain::service::engine_stats::{BOOK_STATS_INTERVAL, BookDepth, EngineCounters};
+use crate::domain::service::feature_flag::{Feature, FeatureFlags, FlagRule};
+use crate::domain::service::invariant::{InvariantProbe, InvariantSampler, InvariantSamplerConfig};
+use crate::domain::service::mass_quote::MarketMakerProtection;
+use crate::domain::service::prefunding::worst_case_margin;
+use crate::domain::service::query::{
+    AccountSnapshot, PositionRisk, PrepQueryHandler, QueuePosition, position_risks,
+};
+use crate::domain::service::recurring::{RecurringOrders, RecurringState};
+use crate::domain::service::risk::{RiskManager, SYSTEM_OPERATOR};
+
+/// 默认杠杆
+pub const DEFAULT_LEVERAGE: Leverage = 10;
+
+/// 按杠杆计算占用保证金
+pub(crate) fn initial_margin(quantity: Quantity, price: Price, leverage: Leverage) -> Margin {
+    (quantity * price) / leverage as u64
+}
+
+/// 按交易者ID排序（快照输出与哈希表遍历顺序无关）
+fn sorted_by_trader<T>(entries: impl Iterator<Item = (TraderId, T)>) -> Vec<(TraderId, T)> {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_unstable_by_key(|(trader, _)| *trader);
+    entries
+}
+
+/// 仓位保护单类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectionKind {
+    StopLoss,
+    TakeProfit,
+    TrailingStop,
+}
+
+/// 定投计划操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecurringAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// 保护单触发后的平仓委托
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloseOrder {
+    pub trader: TraderId,
+    pub position_id: PositionId,
+    pub kind: ProtectionKind,
+    /// 平仓价格（None=市价）
+    pub close_price: Option<Price>,
+}
+
+/// 仓位上生效的保护单（止损与止盈互为 OCO）
+#[derive(Debug, Default)]
+struct Protections {
+    stop_loss: Option<ConditionalId>,
+    take_profit: Option<ConditionalId>,
+    trailing_stop: Option<ConditionalId>,
+}
+
+impl Protections {
+    fn slot(&mut self, kind: ProtectionKind) -> &mut Option<ConditionalId> {
+        match kind {
+            ProtectionKind::StopLoss => &mut self.stop_loss,
+            ProtectionKind::TakeProfit => &mut self.take_profit,
+            ProtectionKind::TrailingStop => &mut self.trailing_stop,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.stop_loss.is_none() && self.take_profit.is_none() && self.trailing_stop.is_none()
+    }
+}
+
+/// 引擎快照（一致性时间点的全部可恢复状态）
+///
+/// 余额由外部账户服务持有，快照时按涉及的交易者一并读取，供灾备节点核对
+#[derive(Debug, Clone, Default)]
+pub struct EngineSnapshot {
+    /// 已处理命令序列号
+    pub sequence: u64,
+    /// 快照时间
+    pub timestamp: Timestamp,
+    /// 成交ID计数器
+    pub trade_id_counter: u64,
+    /// 下一个订单ID
+    pub next_order_id: OrderId,
+    /// 下一个仓位ID
+    pub next_position_id: PositionId,
+    /// 活跃挂单（买单在前，各自按价格时间优先）
+    pub orders: Vec<Order>,
+    /// 全部仓位
+    pub positions: Vec<Position>,
+    /// 交易者余额
+    pub balances: Vec<(TraderId, Vec<AssetBalance>)>,
+    /// 生效中的功能开关
+    pub feature_flags: Vec<(Feature, FlagRule)>,
+    /// 生效中的仓位保护单（止损/止盈/追踪止损，含追踪状态与 OCO 关联）
+    pub protections: Vec<ConditionalEntry<Price, CloseOrder>>,
+    /// 条件单监控索引最近评估的成交价
+    pub conditional_last_price: Option<Price>,
+    /// 条件单ID计数器
+    pub conditional_id_counter: ConditionalId,
+    /// 设置过杠杆或保证金模式的账户（按交易者ID排序）
+    pub account_settings: Vec<(TraderId, AccountSettings)>,
+    /// 各账户保留的设置变更记录（按交易者ID排序，同一账户由旧到新）
+    pub setting_log: Vec<AccountSettingRecord>,
+    /// 合约到期时间（永续为 None）
+    pub expiry: Option<Timestamp>,
+    /// 已同步保证金余额的账户（按交易者ID排序）
+    pub collateral: Vec<(TraderId, Margin)>,
+    /// 暂停或冻结的账户（按交易者ID排序）
+    pub account_statuses: Vec<(TraderId, AccountStatus)>,
+    /// 显式配置的风控档案（按交易者ID排序）
+    pub risk_profiles: Vec<(TraderId, RiskProfile)>,
+    /// 已熔断账户（按交易者ID排序）
+    pub killed_accounts: Vec<TraderId>,
+    /// 累计已实现盈亏（按交易者ID排序，事后监控用）
+    pub realized_pnl: Vec<(TraderId, i64)>,
+    /// 做市报价与做市商保护状态（按交易者ID排序）
+    pub mmp: Vec<MmpState>,
+    /// 熔断中的市场：触发熔断的告警（未熔断为 None）
+    pub circuit_breaker: Option<MarketAlert>,
+    /// 成交台账（按成交ID升序，成交撤销用）
+    pub trade_journal: Vec<TradeRecord>,
+    /// 定投计划与执行历史
+    pub recurring: RecurringState,
+    /// 子账户与母账户关系（按子账户ID排序）
+    pub sub_account_links: Vec<(TraderId, ParentAccountId)>,
+    /// 已同意仓位压缩的子账户（按ID排序）
+    pub compression_consent: Vec<TraderId>,
+    /// 最近一次标记价格（未收到为 None）
+    pub mark_price: Option<Price>,
+}
 
 /// 撮合服务
 pub struct MatchingService<O, P>
@@ -22,12 +177,66 @@ where
     position_repo: P,
     /// 成交ID计数器
     trade_id_counter: u64,
+    /// 已处理命令序列号
+    sequence: u64,
     /// 当前时间戳
     current_timestamp: Timestamp,
     /// 默认杠杆
     default_leverage: Leverage,
     /// 默认保证金模式
     default_margin_mode: MarginMode,
+    /// 账户杠杆与保证金模式（未设置的账户取默认值）
+    account_settings: HashMap<TraderId, AccountSettings>,
+    /// 账户设置变更日志（每个账户保留最近 SETTING_HISTORY_LIMIT 条，由旧到新）
+    setting_log: HashMap<TraderId, VecDeque<AccountSettingRecord>>,
+    /// 账户保证金余额（账户服务同步，未同步的账户不做保证金预占检查）
+    collateral: HashMap<TraderId, Margin>,
+    /// 待发布的执行回报
+    execution_reports: Vec<ExecutionReport>,
+    /// 待发布的引擎事件（读侧投影消费）
+    events: Vec<EventEnvelope>,
+    /// 成交台账（可撤销的成交，保留最近 TRADE_JOURNAL_LIMIT 条）
+    trade_journal: BTreeMap<TradeId, TradeRecord>,
+    /// 成交撤销审计日志（只追加）
+    bust_log: Vec<TradeBustRecord>,
+    /// 仓位压缩审计日志（只追加）
+    compression_log: Vec<CompressionRecord>,
+    /// 合约到期时间（永续为 None）
+    expiry: Option<Timestamp>,
+    /// 到期交割审计日志（只追加）
+    delivery_log: Vec<DeliveryRecord>,
+    /// 账户风控
+    risk: RiskManager,
+    /// 行情异常监控（未启用为 None）
+    anomaly: Option<AnomalyDetector>,
+    /// 行情异常告警日志（只追加）
+    alert_log: Vec<MarketAlert>,
+    /// 不变量抽样（未启用为 None）
+    sampler: Option<InvariantSampler>,
+    /// 待后台检查的不变量采样
+    invariant_probes: Vec<InvariantProbe>,
+    /// 市场熔断记录（只追加，最后一条未恢复即为熔断中）
+    circuit_breaker_log: Vec<CircuitBreakerRecord>,
+    /// 做市报价与做市商保护
+    mmp: MarketMakerProtection,
+    /// 运行统计计数器（未接入为 None）
+    stats: Option<Arc<EngineCounters>>,
+    /// 增量维护的盘口深度（接入运行统计后维护）
+    depth: Option<BookDepth>,
+    /// 条件单监控索引（止损/止盈/追踪止损，按成交价触发）
+    conditional: ConditionalBook<Price, CloseOrder>,
+    /// 仓位 -> 生效的保护单
+    protections: HashMap<PositionId, Protections>,
+    /// 条件单ID计数器
+    conditional_id_counter: ConditionalId,
+    /// 功能开关
+    flags: FeatureFlags,
+    /// 定投计划
+    recurring: RecurringOrders,
+    /// 子账户分组与压缩同意标记（仓位压缩复核用）
+    sub_accounts: SubAccountGroups,
+    /// 最近一次标记价格（压缩作业按此结算）
+    mark_price: Option<Price>,
 }
 
 impl<O, P> MatchingService<O, P>
@@ -41,10 +250,170 @@ where
             order_repo,
             position_repo,
             trade_id_counter: 0,
+            sequence: 0,
             current_timestamp: 0,
-            default_leverage: 10,
+            default_leverage: DEFAULT_LEVERAGE,
             default_margin_mode: MarginMode::Cross,
+            account_settings: HashMap::new(),
+            setting_log: HashMap::new(),
+            collateral: HashMap::new(),
+            execution_reports: Vec::new(),
+            events: Vec::new(),
+            trade_journal: BTreeMap::new(),
+            bust_log: Vec::new(),
+            compression_log: Vec::new(),
+            expiry: None,
+            delivery_log: Vec::new(),
+            risk: RiskManager::new(),
+            anomaly: None,
+            alert_log: Vec::new(),
+            sampler: None,
+            invariant_probes: Vec::new(),
+            circuit_breaker_log: Vec::new(),
+            mmp: MarketMakerProtection::new(),
+            stats: None,
+            depth: None,
+            conditional: ConditionalBook::new(),
+            protections: HashMap::new(),
+            conditional_id_counter: 0,
+            flags: FeatureFlags::new(),
+            recurring: RecurringOrders::new(),
+            sub_accounts: SubAccountGroups::new(),
+            mark_price: None,
+        }
+    }
+
+    /// 导出快照
+    ///
+    /// 须在两条命令之间调用，快照对应 `sequence` 之前的全部命令
+    pub fn snapshot(&self, balances: &dyn BalanceReader) -> EngineSnapshot {
+        let orders: Vec<Order> = self
+            .order_repo
+            .get_bids()
+            .into_iter()
+            .chain(self.order_repo.get_asks())
+            .cloned()
+            .collect();
+        let positions: Vec<Position> =
+            self.position_repo.get_all_positions().into_iter().cloned().collect();
+
+        let mut traders: Vec<TraderId> =
+            orders.iter().map(|o| o.trader).chain(positions.iter().map(|p| p.trader)).collect();
+        traders.sort_unstable();
+        traders.dedup();
+
+        EngineSnapshot {
+            sequence: self.sequence,
+            timestamp: self.current_timestamp,
+            trade_id_counter: self.trade_id_counter,
+            next_order_id: self.order_repo.peek_next_order_id(),
+            next_position_id: self.position_repo.peek_next_position_id(),
+            orders,
+            positions,
+            balances: traders.into_iter().map(|t| (t, balances.balances_of(t))).collect(),
+            feature_flags: self.flags.rules(),
+            protections: self.conditional.export(),
+            conditional_last_price: self.conditional.last_price(),
+            conditional_id_counter: self.conditional_id_counter,
+            account_settings: sorted_by_trader(
+                self.account_settings.iter().map(|(trader, settings)| (*trader, *settings)),
+            ),
+            setting_log: sorted_by_trader(
+                self.setting_log.iter().map(|(trader, records)| (*trader, records)),
+            )
+            .into_iter()
+            .flat_map(|(_, records)| records.iter().copied())
+            .collect(),
+            expiry: self.expiry,
+            collateral: sorted_by_trader(
+                self.collateral.iter().map(|(trader, collateral)| (*trader, *collateral)),
+            ),
+            account_statuses: self.risk.statuses(),
+            risk_profiles: self.risk.profiles(),
+            killed_accounts: self.risk.killed_accounts(),
+            realized_pnl: self.risk.realized_pnls(),
+            mmp: self.mmp.export(),
+            circuit_breaker: self
+                .circuit_breaker_log
+                .last()
+                .filter(|record| record.is_active())
+                .map(|record| record.alert),
+            trade_journal: self.trade_journal.values().copied().collect(),
+            recurring: self.recurring.export(),
+            sub_account_links: self.sub_accounts.links(),
+            compression_consent: self.sub_accounts.consented(),
+            mark_price: self.mark_price,
+        }
+    }
+
+    /// 从快照恢复（仓储须为空）
+    pub fn restore(
+        mut order_repo: O,
+        mut position_repo: P,
+        snapshot: EngineSnapshot,
+    ) -> Result<Self, RepositoryError> {
+        for order in snapshot.orders {
+            order_repo.save_order(order)?;
+        }
+        for position in snapshot.positions {
+            position_repo.save_position(position)?;
         }
+        order_repo.set_next_order_id(snapshot.next_order_id);
+        position_repo.set_next_position_id(snapshot.next_position_id);
+
+        let mut service = Self::new(order_repo, position_repo);
+        service.sequence = snapshot.sequence;
+        service.current_timestamp = snapshot.timestamp;
+        service.trade_id_counter = snapshot.trade_id_counter;
+        service.flags = FeatureFlags::restore(snapshot.feature_flags);
+        for entry in &snapshot.protections {
+            let action = entry.action;
+            *service.protections.entry(action.position_id).or_default().slot(action.kind) =
+                Some(entry.id);
+        }
+        service.conditional =
+            ConditionalBook::import(snapshot.conditional_last_price, snapshot.protections)
+                .map_err(|e| match e {
+                    ConditionalError::DuplicateId(_) => RepositoryError::Duplicate,
+                    _ => RepositoryError::NotFound,
+                })?;
+        service.conditional_id_counter = snapshot.conditional_id_counter;
+        service.account_settings = snapshot.account_settings.into_iter().collect();
+        for record in snapshot.setting_log {
+            service.push_setting_record(record);
+        }
+        service.expiry = snapshot.expiry;
+        service.collateral = snapshot.collateral.into_iter().collect();
+        service.risk.restore_statuses(snapshot.account_statuses);
+        service.risk.restore_limits(
+            snapshot.risk_profiles,
+            snapshot.killed_accounts,
+            snapshot.realized_pnl,
+        );
+        service.mmp = MarketMakerProtection::import(snapshot.mmp);
+        if let Some(alert) = snapshot.circuit_breaker {
+            service.circuit_breaker_log.push(CircuitBreakerRecord {
+                alert,
+                resumed_by: None,
+                resumed_at: None,
+            });
+        }
+        service.trade_journal =
+            snapshot.trade_journal.into_iter().map(|trade| (trade.trade_id, trade)).collect();
+        service.recurring = RecurringOrders::import(snapshot.recurring);
+        for (sub_account, parent) in snapshot.sub_account_links {
+            service.sub_accounts.link(sub_account, parent);
+        }
+        for sub_account in snapshot.compression_consent {
+            service.sub_accounts.set_consent(sub_account, true);
+        }
+        service.mark_price = snapshot.mark_price;
+        Ok(service)
+    }
+
+    /// 功能开关
+    pub fn feature_flags(&self) -> &FeatureFlags {
+        &self.flags
     }
 
     /// 设置当前时间戳
@@ -52,6 +421,200 @@ where
         self.current_timestamp = ts;
     }
 
+    /// 已处理命令序列号
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// 本步输出摘要：刚处理命令的成交，以及处理后的订单簿与仓位（回放比对用）
+    pub fn step_digest(&self, result: &CommandResult) -> StepDigest {
+        let trades = match result {
+            CommandResult::LimitOrder { trades, .. }
+            | CommandResult::MarketOrder { trades, .. }
+            | CommandResult::RunRecurringPlans { trades, .. } => trades.as_slice(),
+            _ => &[],
+        };
+        StepDigest {
+            sequence: self.sequence,
+            trades: trades_digest(trades),
+            book: book_digest(
+                self.order_repo.get_bids().into_iter().chain(self.order_repo.get_asks()),
+            ),
+            positions: positions_digest(self.position_repo.get_all_positions()),
+        }
+    }
+
+    /// 取出待发布的执行回报（按产生顺序）
+    pub fn drain_execution_reports(&mut self) -> Vec<ExecutionReport> {
+        std::mem::take(&mut self.execution_reports)
+    }
+
+    /// 取出待发布的引擎事件（按产生顺序）
+    pub fn drain_events(&mut self) -> Vec<EventEnvelope> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 成交撤销审计日志
+    pub fn bust_log(&self) -> &[TradeBustRecord] {
+        &self.bust_log
+    }
+
+    /// 仓位压缩审计日志
+    pub fn compression_log(&self) -> &[CompressionRecord] {
+        &self.compression_log
+    }
+
+    /// 合约到期时间（永续为 None）
+    pub fn expiry(&self) -> Option<Timestamp> {
+        self.expiry
+    }
+
+    /// 当前时间是否已到期
+    pub fn is_expired(&self) -> bool {
+        self.expiry.is_some_and(|expiry| self.current_timestamp >= expiry)
+    }
+
+    /// 到期交割审计日志
+    pub fn delivery_log(&self) -> &[DeliveryRecord] {
+        &self.delivery_log
+    }
+
+    /// 全部仓位（按ID排序，供压缩作业生成计划）
+    pub fn positions(&self) -> Vec<&Position> {
+        self.position_repo.get_all_positions()
+    }
+
+    /// 子账户分组与压缩同意标记
+    pub fn sub_accounts(&self) -> &SubAccountGroups {
+        &self.sub_accounts
+    }
+
+    /// 最近一次标记价格（未收到为 None）
+    pub fn mark_price(&self) -> Option<Price> {
+        self.mark_price
+    }
+
+    /// 账户风控（档案、熔断状态与审计日志）
+    pub fn risk(&self) -> &RiskManager {
+        &self.risk
+    }
+
+    /// 账户设置（未设置的账户取默认杠杆与保证金模式）
+    pub fn account_settings(&self, trader: TraderId) -> AccountSettings {
+        self.account_settings.get(&trader).copied().unwrap_or(AccountSettings {
+            leverage: self.default_leverage,
+            margin_mode: self.default_margin_mode,
+        })
+    }
+
+    /// 做市报价与做市商保护状态
+    pub fn mmp(&self) -> &MarketMakerProtection {
+        &self.mmp
+    }
+
+    /// 定投计划与执行历史
+    pub fn recurring(&self) -> &RecurringOrders {
+        &self.recurring
+    }
+
+    /// 启用行情异常监控
+    pub fn enable_anomaly_detection(&mut self, config: AnomalyConfig) {
+        self.anomaly = Some(AnomalyDetector::new(config));
+    }
+
+    /// 行情异常告警日志
+    pub fn alert_log(&self) -> &[MarketAlert] {
+        &self.alert_log
+    }
+
+    /// 启用不变量抽样：撮合线程按间隔生成采样副本，由后台检查线程核对
+    pub fn enable_invariant_sampling(&mut self, config: InvariantSamplerConfig) {
+        self.sampler = Some(InvariantSampler::new(config));
+    }
+
+    /// 不变量抽样器（未启用为 None）
+    pub fn invariant_sampler(&self) -> Option<&InvariantSampler> {
+        self.sampler.as_ref()
+    }
+
+    /// 取出待检查的不变量采样
+    pub fn drain_invariant_probes(&mut self) -> Vec<InvariantProbe> {
+        std::mem::take(&mut self.invariant_probes)
+    }
+
+    /// 市场熔断记录
+    pub fn circuit_breaker_log(&self) -> &[CircuitBreakerRecord] {
+        &self.circuit_breaker_log
+    }
+
+    /// 是否处于市场熔断
+    pub fn is_halted(&self) -> bool {
+        self.circuit_breaker_log.last().is_some_and(CircuitBreakerRecord::is_active)
+    }
+
+    /// 接入运行统计计数器：由当前订单簿构建一次盘口深度，此后增量维护，并立即发布一次
+    pub fn attach_stats(&mut self, counters: Arc<EngineCounters>) {
+        let resting = self.order_repo.get_bids().into_iter().chain(self.order_repo.get_asks());
+        self.depth = Some(BookDepth::from_orders(resting));
+        self.stats = Some(counters);
+        self.publish_book_stats();
+    }
+
+    /// 发布增量维护的盘口概况（撮合线程上调用，不遍历订单簿）
+    pub fn publish_book_stats(&self) {
+        let (Some(counters), Some(depth)) = (&self.stats, &self.depth) else {
+            return;
+        };
+        counters.publish_book(depth.stats(self.order_repo.order_capacity() as u64));
+    }
+
+    /// 按预估容量预分配存储（启动预热）
+    pub fn reserve(&mut self, orders: usize, positions: usize) {
+        self.order_repo.reserve_orders(orders);
+        self.position_repo.reserve_positions(positions);
+        self.execution_reports.reserve(orders);
+        self.events.reserve(orders);
+    }
+
+    /// 命令是否为主动成交委托（速度缓冲判定）
+    ///
+    /// 市价单总是主动；限价单按当前盘口判断是否可立即成交
+    pub fn is_aggressive(&self, command: &Command) -> bool {
+        match command {
+            Command::MarketOrder { .. } => true,
+            Command::LimitOrder { side: Side::Buy, price, .. } => {
+                self.order_repo.best_ask().is_some_and(|ask| ask <= *price)
+            }
+            Command::LimitOrder { side: Side::Sell, price, .. } => {
+                self.order_repo.best_bid().is_some_and(|bid| bid >= *price)
+            }
+            _ => false,
+        }
+    }
+
+    /// 记录引擎事件
+    fn emit(&mut self, event: EngineEvent) {
+        self.events.push(EventEnvelope { sequence: self.sequence, event });
+    }
+
+    /// 发布执行回报（同时进入事件流）
+    fn publish_report(&mut self, report: ExecutionReport) {
+        if let Some(depth) = &mut self.depth {
+            depth.apply(&report);
+        }
+        self.execution_reports.push(report);
+        self.emit(EngineEvent::Execution(report));
+    }
+
+    /// 发布交易者某方向仓位的最新状态
+    fn publish_position(&mut self, trader: TraderId, position_side: PositionSide) {
+        let event = match self.position_repo.get_position_by_trader_side(trader, position_side) {
+            Some(position) => EngineEvent::PositionChanged(position.clone()),
+            None => EngineEvent::PositionClosed { trader, position_side },
+        };
+        self.emit(event);
+    }
+
     /// 生成成交ID
     fn next_trade_id(&mut self) -> u64 {
         self.trade_id_counter += 1;
@@ -69,6 +632,18 @@ where
         reduce_only: bool,
         time_in_force: TimeInForce,
     ) -> CommandResult {
+        if self.is_expired() {
+            return CommandResult::Error {
+                code: ErrorCode::InstrumentExpired,
+                message: "合约已到期，停止交易".to_string(),
+            };
+        }
+        if self.is_halted() {
+            return CommandResult::Error {
+                code: ErrorCode::CircuitBreakerTripped,
+                message: "市场熔断中，暂停交易".to_string(),
+            };
+        }
         // 验证参数
         if quantity == 0 {
             return CommandResult::Error {
@@ -103,9 +678,37 @@ where
             }
         }
 
+        // 账户风控事前检查
+        let open_orders = self.order_repo.get_orders_by_trader(trader).len();
+        if let Err(code) = self.risk.pre_trade_check(trader, price, quantity, open_orders) {
+            let message = match code {
+                ErrorCode::AccountRestricted => "账户已暂停或冻结",
+                ErrorCode::KillSwitchActive => "账户已熔断",
+                ErrorCode::MaxOpenOrdersExceeded => "挂单数超过上限",
+                _ => "委托名义价值超过上限",
+            };
+            return CommandResult::Error { code, message: message.to_string() };
+        }
+
+        // 保证金预占检查（按挂单最坏情况保证金）
+        let candidate = Order::new(
+            self.order_repo.peek_next_order_id(),
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only,
+            time_in_force,
+            self.current_timestamp,
+        );
+        if let Err(code) = self.pre_fund(trader, &[candidate], &[]) {
+            return CommandResult::Error { code, message: "可用保证金不足".to_string() };
+        }
+
         // 创建订单
         let order_id = self.order_repo.next_order_id();
-        let order = Order::new(
+        let mut order = Order::new(
             order_id,
             trader,
             side,
@@ -118,12 +721,13 @@ where
         );
 
         // 撮合
-        let (trades, remaining) = self.match_order(order);
+        let (trades, remaining) = self.match_order(&mut order);
 
         // 处理 TimeInForce
         let status = match time_in_force {
             TimeInForce::FOK => {
                 if remaining > 0 {
+                    self.cancel_taker_remainder(&mut order);
                     return CommandResult::LimitOrder {
                         order_id,
                         trades: vec![],
@@ -134,6 +738,9 @@ where
                 OrderStatus::Filled
             }
             TimeInForce::IOC => {
+                if remaining > 0 {
+                    self.cancel_taker_remainder(&mut order);
+                }
                 if remaining > 0 && !trades.is_empty() {
                     OrderStatus::PartiallyFilled
                 } else if trades.is_empty() {
@@ -162,78 +769,124 @@ where
             }
         };
 
-        // 剩余数量挂单
+        // 成交后触发熔断的 Taker 不再挂单
+        if remaining > 0 && self.risk.is_killed(trader) && order.is_active() {
+            self.cancel_taker_remainder(&mut order);
+            return CommandResult::LimitOrder {
+                order_id,
+                trades,
+                remaining_quantity: remaining,
+                status: order.status,
+            };
+        }
+
+        // 剩余数量挂单（保留已成交数量与状态）
         if remaining > 0
             && matches!(
                 time_in_force,
                 TimeInForce::GTC | TimeInForce::GTD { .. } | TimeInForce::PostOnly
             )
         {
-            let remaining_order = Order::new(
-                order_id,
-                trader,
-                side,
-                price,
-                remaining,
-                position_side,
-                reduce_only,
-                time_in_force,
-                self.current_timestamp,
-            );
-            let _ = self.order_repo.save_order(remaining_order);
+            let accepted = order.clone();
+            if self.order_repo.save_order(order).is_ok() {
+                if let Some(depth) = &mut self.depth {
+                    depth.rest(&accepted);
+                }
+                self.emit(EngineEvent::OrderAccepted(accepted));
+            }
         }
 
         CommandResult::LimitOrder { order_id, trades, remaining_quantity: remaining, status }
     }
 
+    /// 取消 Taker 未成交部分（IOC/FOK）
+    fn cancel_taker_remainder(&mut self, order: &mut Order) {
+        if let Ok(from) = order.cancel(self.current_timestamp) {
+            self.publish_report(ExecutionReport::status_change(order, from));
+        }
+    }
+
+    /// 自成交防护：撤销本账户会与 Taker 成交的对手挂单
+    fn cancel_self_trades(&mut self, crossing: Vec<OrderId>) {
+        for order_id in crossing {
+            let Some(resting) = self.order_repo.get_order_mut(order_id) else {
+                continue;
+            };
+            if let Ok(from) = resting.cancel(self.current_timestamp) {
+                let report = ExecutionReport::status_change(resting, from);
+                self.order_repo.remove_order(order_id);
+                self.publish_report(report);
+            }
+        }
+    }
+
     /// 撮合订单
-    fn match_order(&mut self, order: Order) -> (Vec<Trade>, Quantity) {
+    fn match_order(&mut self, order: &mut Order) -> (Vec<Trade>, Quantity) {
         let mut trades = Vec::new();
         let mut remaining = order.remaining_quantity;
+        let mut legs = Vec::new();
+        let mut maker_fills = Vec::new();
 
-        // 收集可匹配的对手方订单信息
-        let matches: Vec<(OrderId, TraderId, Price, Quantity, PositionSide, bool)> = match order
-            .side
-        {
-            Side::Buy => self
-                .order_repo
-                .get_asks()
-                .iter()
-                .filter(|o| o.is_active() && order.can_match(o.price))
-                .map(|o| {
-                    (o.id, o.trader, o.price, o.remaining_quantity, o.position_side, o.reduce_only)
-                })
-                .collect(),
-            Side::Sell => self
-                .order_repo
-                .get_bids()
-                .iter()
-                .filter(|o| o.is_active() && o.can_match(order.price))
-                .map(|o| {
-                    (o.id, o.trader, o.price, o.remaining_quantity, o.position_side, o.reduce_only)
-                })
-                .collect(),
+        // 按价格-时间优先计算成交分配（撮合内核），再补齐对手方的仓位属性
+        let opposite = match order.side {
+            Side::Buy => self.order_repo.get_asks(),
+            Side::Sell => self.order_repo.get_bids(),
         };
+        // 自成交防护只看可成交价位内的本账户挂单（对手盘已按价格优先排序）
+        let self_crossing: Vec<OrderId> =
+            if self.flags.is_enabled(Feature::SelfTradePrevention, order.trader) {
+                opposite
+                    .iter()
+                    .take_while(|o| match order.side {
+                        Side::Buy => o.price <= order.price,
+                        Side::Sell => o.price >= order.price,
+                    })
+                    .filter(|o| o.is_active() && o.trader == order.trader)
+                    .map(|o| o.id)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        let (fills, _) = match_core::plan_fills(
+            order.side,
+            Some(order.price),
+            remaining,
+            opposite.iter().filter(|o| o.is_active() && !self_crossing.contains(&o.id)).map(|o| {
+                match_core::Resting {
+                    order_id: o.id,
+                    trader: o.trader,
+                    price: o.price,
+                    quantity: o.remaining_quantity,
+                }
+            }),
+        );
+        self.cancel_self_trades(self_crossing);
+        let matches: Vec<(match_core::Fill, Quantity, PositionSide, bool)> = fills
+            .into_iter()
+            .filter_map(|fill| {
+                let o = self.order_repo.get_order(fill.maker_order_id)?;
+                Some((fill, o.remaining_quantity, o.position_side, o.reduce_only))
+            })
+            .collect();
 
-        for (
-            opposite_id,
-            opposite_trader,
-            opposite_price,
-            opposite_qty,
-            opposite_pos_side,
-            opposite_reduce_only,
-        ) in matches
-        {
-            if remaining == 0 {
-                break;
-            }
-
-            let match_qty = remaining.min(opposite_qty);
-            let match_price = opposite_price;
+        for (fill, opposite_qty, opposite_pos_side, opposite_reduce_only) in matches {
+            let opposite_id = fill.maker_order_id;
+            let opposite_trader = fill.maker_trader;
+            let match_qty = fill.quantity;
+            let match_price = fill.price;
+            maker_fills.push((opposite_trader, opposite_id));
 
-            // 更新对手方订单
-            if let Some(opposite_order) = self.order_repo.get_order_mut(opposite_id) {
-                opposite_order.fill(match_qty, self.current_timestamp);
+            // 更新双方订单
+            let now = self.current_timestamp;
+            let maker_report = self.order_repo.get_order_mut(opposite_id).and_then(|opposite| {
+                let from = opposite.fill(match_qty, match_price, now).ok()?;
+                Some(ExecutionReport::fill(opposite, from, match_price, match_qty))
+            });
+            if let Some(report) = maker_report {
+                self.publish_report(report);
+            }
+            if let Ok(from) = order.fill(match_qty, match_price, self.current_timestamp) {
+                self.publish_report(ExecutionReport::fill(order, from, match_price, match_qty));
             }
 
             // 计算手续费
@@ -255,7 +908,7 @@ where
             );
 
             // 更新仓位
-            self.update_positions(
+            let (taker_leg, maker_leg) = self.update_positions(
                 order.trader,
                 opposite_trader,
                 order.side,
@@ -267,6 +920,19 @@ where
                 opposite_reduce_only,
             );
 
+            legs.push(taker_leg);
+            legs.push(maker_leg);
+            let record = TradeRecord {
+                trade_id,
+                price: match_price,
+                quantity: match_qty,
+                taker: taker_leg,
+                maker: maker_leg,
+                timestamp: self.current_timestamp,
+            };
+            self.journal_trade(record);
+            self.emit(EngineEvent::Trade(record));
+
             trades.push(trade);
             remaining -= match_qty;
 
@@ -276,10 +942,94 @@ where
             }
         }
 
+        // 事后监控：撮合结束后统一检查，避免撮合过程中撤掉对手方挂单
+        for leg in &legs {
+            self.monitor_post_trade(leg);
+        }
+        for (maker, order_id) in maker_fills {
+            if let Some(trigger) = self.mmp.on_fill(maker, order_id, self.current_timestamp) {
+                self.cancel_resting(&trigger.pulled);
+                self.emit(EngineEvent::MmpTriggered(trigger));
+            }
+        }
+
         (trades, remaining)
     }
 
-    /// 更新仓位
+    /// 记入成交台账，超出保留条数时最早的成交不再可撤销
+    fn journal_trade(&mut self, record: TradeRecord) {
+        self.trade_journal.insert(record.trade_id, record);
+        if self.trade_journal.len() > TRADE_JOURNAL_LIMIT {
+            self.trade_journal.pop_first();
+        }
+    }
+
+    /// 事后监控单边成交，突破限额时熔断并撤销该账户全部挂单
+    fn monitor_post_trade(&mut self, leg: &TradeLeg) {
+        let position_quantity: Quantity =
+            self.position_repo.get_positions_by_trader(leg.trader).iter().map(|p| p.quantity).sum();
+        let Some(reason) = self.risk.post_trade_check(leg, position_quantity) else {
+            return;
+        };
+        if self.risk.trigger_kill_switch(
+            leg.trader,
+            reason,
+            SYSTEM_OPERATOR.to_string(),
+            self.current_timestamp,
+        ) {
+            self.cancel_all_resting(leg.trader);
+        }
+    }
+
+    /// 变更账户状态；冻结时撤销账户全部挂单
+    pub fn set_account_status(
+        &mut self,
+        trader: TraderId,
+        status: AccountStatus,
+        reason: String,
+        operator: String,
+    ) -> CommandResult {
+        let timestamp = self.current_timestamp;
+        let Some(from) = self.risk.set_status(trader, status, reason, operator, timestamp) else {
+            return CommandResult::SetAccountStatus {
+                trader,
+                from: status,
+                to: status,
+                success: false,
+            };
+        };
+        if !status.can_cancel() {
+            self.cancel_all_resting(trader);
+        }
+        CommandResult::SetAccountStatus { trader, from, to: status, success: true }
+    }
+
+    /// 撤销账户全部挂单
+    fn cancel_all_resting(&mut self, trader: TraderId) {
+        let order_ids: Vec<OrderId> =
+            self.order_repo.get_orders_by_trader(trader).iter().map(|o| o.id).collect();
+        self.cancel_resting(&order_ids);
+    }
+
+    /// 撤销指定挂单（已不在订单簿的跳过），返回实际撤销的订单
+    fn cancel_resting(&mut self, order_ids: &[OrderId]) -> Vec<OrderId> {
+        let now = self.current_timestamp;
+        let mut cancelled = Vec::new();
+        for &order_id in order_ids {
+            let report = self.order_repo.get_order_mut(order_id).and_then(|order| {
+                let from = order.cancel(now).ok()?;
+                Some(ExecutionReport::status_change(order, from))
+            });
+            if let Some(report) = report {
+                self.publish_report(report);
+                cancelled.push(order_id);
+            }
+            self.order_repo.remove_order(order_id);
+        }
+        cancelled
+    }
+
+    /// 更新仓位，返回 (Taker, Maker) 双方的仓位影响
     fn update_positions(
         &mut self,
         taker_trader: TraderId,
@@ -291,9 +1041,9 @@ where
         price: Price,
         taker_reduce_only: bool,
         maker_reduce_only: bool,
-    ) {
+    ) -> (TradeLeg, TradeLeg) {
         // 更新 Taker 仓位
-        self.update_single_position(
+        let taker_leg = self.update_single_position(
             taker_trader,
             taker_side,
             taker_position_side,
@@ -307,7 +1057,7 @@ where
             Side::Buy => Side::Sell,
             Side::Sell => Side::Buy,
         };
-        self.update_single_position(
+        let maker_leg = self.update_single_position(
             maker_trader,
             maker_side,
             maker_position_side,
@@ -315,6 +1065,8 @@ where
             price,
             maker_reduce_only,
         );
+
+        (taker_leg, maker_leg)
     }
 
     /// 更新单个仓位
@@ -326,7 +1078,7 @@ where
         quantity: Quantity,
         price: Price,
         reduce_only: bool,
-    ) {
+    ) -> TradeLeg {
         let is_opening = match (side, position_side) {
             (Side::Buy, PositionSide::Long) | (Side::Buy, PositionSide::Both) => !reduce_only,
             (Side::Sell, PositionSide::Short) => !reduce_only,
@@ -338,55 +1090,1268 @@ where
         let position_id =
             self.position_repo.get_position_by_trader_side(trader, position_side).map(|p| p.id);
 
+        let mut leg = TradeLeg {
+            trader,
+            side,
+            position_side,
+            opened: is_opening,
+            quantity: 0,
+            entry_price_before: 0,
+            realized_pnl: 0,
+        };
+
         if let Some(pos_id) = position_id {
             if let Some(position) = self.position_repo.get_position_mut(pos_id) {
+                leg.entry_price_before = position.entry_price;
                 if is_opening {
                     position.add(quantity, price, self.current_timestamp);
+                    leg.quantity = quantity;
                 } else {
-                    position.reduce(quantity, price, self.current_timestamp);
+                    leg.quantity = quantity.min(position.quantity);
+                    leg.realized_pnl = position.reduce(quantity, price, self.current_timestamp);
                     if position.is_empty() {
                         self.position_repo.remove_position(pos_id);
+                        self.cancel_protections(pos_id);
                     }
                 }
             }
         } else if is_opening {
+            leg.quantity = quantity;
             // 创建新仓位
             let position_id = self.position_repo.next_position_id();
-            let margin = self.calc_margin(quantity, price, self.default_leverage);
+            let settings = self.account_settings(trader);
+            let margin = self.calc_margin(quantity, price, settings.leverage);
             let position = Position::new(
                 position_id,
                 trader,
                 position_side,
                 quantity,
                 price,
-                self.default_margin_mode,
-                self.default_leverage,
+                settings.margin_mode,
+                settings.leverage,
                 margin,
                 self.current_timestamp,
             );
             let _ = self.position_repo.save_position(position);
         }
+
+        if leg.quantity > 0 {
+            self.publish_position(trader, position_side);
+        }
+        leg
     }
 
-    /// 计算保证金
-    fn calc_margin(&self, quantity: Quantity, price: Price, leverage: Leverage) -> u64 {
-        (quantity * price) / leverage as u64
+    /// 撤销成交
+    ///
+    /// 按成交台账反向恢复双方仓位并发布冲正结算（[`SettlementType::TradeBust`]），
+    /// 成交从台账移除（同一成交只能撤销一次），并追加不可变的审计记录
+    pub fn bust_trade(
+        &mut self,
+        trade_id: TradeId,
+        reason: String,
+        operator: String,
+    ) -> CommandResult {
+        let Some(trade) = self.trade_journal.get(&trade_id).copied() else {
+            return CommandResult::Error {
+                code: ErrorCode::TradeNotFound,
+                message: "成交不存在或已撤销".to_string(),
+            };
+        };
+
+        // 开仓腿的仓位若已被后续成交平掉，无法反向恢复
+        for leg in [&trade.taker, &trade.maker] {
+            if leg.opened && leg.quantity > 0 {
+                let held = self
+                    .position_repo
+                    .get_position_by_trader_side(leg.trader, leg.position_side)
+                    .map_or(0, |p| p.quantity);
+                if held < leg.quantity {
+                    return CommandResult::Error {
+                        code: ErrorCode::PositionNotFound,
+                        message: "仓位已变动，无法撤销成交".to_string(),
+                    };
+                }
+            }
+        }
+
+        self.reverse_leg(&trade.taker, trade.price);
+        self.reverse_leg(&trade.maker, trade.price);
+        self.risk.reverse_fill(&trade.taker);
+        self.risk.reverse_fill(&trade.maker);
+
+        self.trade_journal.remove(&trade_id);
+        self.emit(EngineEvent::TradeBusted(trade));
+        self.bust_log.push(TradeBustRecord {
+            trade,
+            reason,
+            operator,
+            busted_at: self.current_timestamp,
+        });
+
+        CommandResult::BustTrade {
+            trade_id,
+            price: trade.price,
+            quantity: trade.quantity,
+            taker: trade.taker.trader,
+            maker: trade.maker.trader,
+        }
     }
 
-    /// 计算手续费
-    fn calc_fee(&self, quantity: Quantity, price: Price, is_maker: bool) -> u64 {
-        let fee_rate = if is_maker { 2 } else { 5 }; // 0.02% maker, 0.05% taker
-        quantity * price * fee_rate / 100_000
+    /// 执行仓位压缩
+    ///
+    /// 先校验全部对冲（双方同属一个母账户且均已同意压缩、仓位归属、方向、累计数量不超过持仓），
+    /// 任一不通过则整体拒绝；执行时双方按 `price` 减仓实现盈亏、按减仓比例释放保证金，
+    /// 各发布一条压缩结算事件
+    pub fn compress_positions(
+        &mut self,
+        legs: Vec<CompressionLeg>,
+        price: Price,
+        operator: String,
+    ) -> CommandResult {
+        if price == 0 {
+            return CommandResult::Error {
+                code: ErrorCode::InvalidPrice,
+                message: "价格不能为0".to_string(),
+            };
+        }
+        let mut usage: HashMap<PositionId, Quantity> = HashMap::new();
+        for leg in &legs {
+            if leg.quantity == 0 || leg.long_trader == leg.short_trader {
+                return CommandResult::Error {
+                    code: ErrorCode::InvalidQuantity,
+                    message: "无效的对冲".to_string(),
+                };
+            }
+            if !self.sub_accounts.can_compress(leg.long_trader, leg.short_trader) {
+                return CommandResult::Error {
+                    code: ErrorCode::CompressionNotEligible,
+                    message: "对冲双方未同属一个母账户或未同意压缩".to_string(),
+                };
+            }
+            let sides = [
+                (leg.long_position_id, leg.long_trader, true),
+                (leg.short_position_id, leg.short_trader, false),
+            ];
+            for (position_id, trader, long) in sides {
+                let used = usage.entry(position_id).or_default();
+                *used += leg.quantity;
+                let valid = self.position_repo.get_position(position_id).is_some_and(|p| {
+                    p.trader == trader && p.is_long() == long && p.quantity >= *used
+                });
+                if !valid {
+                    return CommandResult::Error {
+                        code: ErrorCode::PositionNotFound,
+                        message: "仓位已变动，无法压缩".to_string(),
+                    };
+                }
+            }
+        }
+
+        let settlements: Vec<CompressionSettlement> = legs
+            .into_iter()
+            .map(|leg| {
+                let (long_realized_pnl, long_released) =
+                    self.compress_side(leg.long_position_id, leg.quantity, price);
+                let (short_realized_pnl, short_released) =
+                    self.compress_side(leg.short_position_id, leg.quantity, price);
+                CompressionSettlement {
+                    leg,
+                    price,
+                    long_realized_pnl,
+                    short_realized_pnl,
+                    released_margin: long_released + short_released,
+                }
+            })
+            .collect();
+
+        self.compression_log.push(CompressionRecord {
+            settlements: settlements.clone(),
+            operator,
+            compressed_at: self.current_timestamp,
+        });
+        CommandResult::CompressPositions { settlements }
     }
-}
 
-impl<O, P> PrepCommandHandler for MatchingService<O, P>
-where
-    O: OrderRepository,
-    P: PositionRepository,
-{
+    /// 登记或解除子账户所属的母账户
+    pub fn link_sub_account(
+        &mut self,
+        sub_account: TraderId,
+        parent: Option<ParentAccountId>,
+    ) -> CommandResult {
+        match parent {
+            Some(parent) => self.sub_accounts.link(sub_account, parent),
+            None => self.sub_accounts.unlink(sub_account),
+        }
+        CommandResult::LinkSubAccount { sub_account, parent }
+    }
+
+    /// 设置子账户是否同意参与仓位压缩
+    pub fn set_compression_consent(
+        &mut self,
+        sub_account: TraderId,
+        consent: bool,
+    ) -> CommandResult {
+        self.sub_accounts.set_consent(sub_account, consent);
+        CommandResult::SetCompressionConsent { sub_account, consent }
+    }
+
+    /// 压缩单边仓位，返回 (已实现盈亏, 释放的保证金)
+    fn compress_side(
+        &mut self,
+        position_id: PositionId,
+        quantity: Quantity,
+        price: Price,
+    ) -> (i64, Margin) {
+        self.settle_position(position_id, quantity, price, SettlementType::Compression)
+            .map_or((0, 0), |s| (s.realized_pnl, s.released_margin))
+    }
+
+    /// 设置合约到期时间（交割合约），到期时刻起拒绝新委托
+    ///
+    /// 已到期（等待交割）时拒绝修改，避免已停止的交易被重新打开
+    pub fn set_expiry(&mut self, expiry: Option<Timestamp>) -> CommandResult {
+        if self.is_expired() {
+            return CommandResult::Error {
+                code: ErrorCode::InstrumentExpired,
+                message: "合约已到期，不能修改到期时间".to_string(),
+            };
+        }
+        let old_expiry = std::mem::replace(&mut self.expiry, expiry);
+        CommandResult::SetExpiry { old_expiry, new_expiry: expiry }
+    }
+
+    /// 执行到期交割
+    ///
+    /// 须已到期；撤销全部挂单后按 `delivery_price` 平掉所有仓位，保证金全部释放
+    pub fn deliver_futures(&mut self, delivery_price: Price, operator: String) -> CommandResult {
+        if !self.is_expired() {
+            return CommandResult::Error {
+                code: ErrorCode::InstrumentNotExpired,
+                message: "合约尚未到期".to_string(),
+            };
+        }
+        if delivery_price == 0 {
+            return CommandResult::Error {
+                code: ErrorCode::InvalidPrice,
+                message: "价格不能为0".to_string(),
+            };
+        }
+
+        let mut traders: Vec<TraderId> = self
+            .order_repo
+            .get_bids()
+            .into_iter()
+            .chain(self.order_repo.get_asks())
+            .map(|o| o.trader)
+            .collect();
+        traders.sort_unstable();
+        traders.dedup();
+        let mut cancelled_orders = Vec::new();
+        for trader in traders {
+            cancelled_orders
+                .extend(self.order_repo.get_orders_by_trader(trader).iter().map(|o| o.id));
+            self.cancel_all_resting(trader);
+        }
+
+        let positions: Vec<(PositionId, Quantity)> = self
+            .position_repo
+            .get_all_positions()
+            .into_iter()
+            .map(|p| (p.id, p.quantity))
+            .collect();
+        let settlements: Vec<PositionSettlement> = positions
+            .into_iter()
+            .filter_map(|(position_id, quantity)| {
+                self.settle_position(
+                    position_id,
+                    quantity,
+                    delivery_price,
+                    SettlementType::FutureDelivery,
+                )
+            })
+            .collect();
+
+        self.delivery_log.push(DeliveryRecord {
+            delivery_price,
+            cancelled_orders: cancelled_orders.clone(),
+            settlements: settlements.clone(),
+            operator,
+            delivered_at: self.current_timestamp,
+        });
+        CommandResult::DeliverFutures { cancelled_orders, settlements }
+    }
+
+    /// 发布告警；开启自动熔断且未在熔断中时以首个告警触发熔断
+    fn raise_alerts(&mut self, alerts: &[MarketAlert]) {
+        let auto_halt = self.anomaly.as_ref().is_some_and(|a| a.config().auto_halt);
+        for alert in alerts {
+            self.alert_log.push(*alert);
+            self.emit(EngineEvent::MarketAlert(*alert));
+            if auto_halt && !self.is_halted() {
+                self.circuit_breaker_log.push(CircuitBreakerRecord {
+                    alert: *alert,
+                    resumed_by: None,
+                    resumed_at: None,
+                });
+            }
+        }
+    }
+
+    /// 命令处理后监控成交价、盘口与价格流停滞
+    fn monitor_market(&mut self, trades: &[Trade]) {
+        let (best_bid, best_ask) = (self.order_repo.best_bid(), self.order_repo.best_ask());
+        let now = self.current_timestamp;
+        let Some(detector) = self.anomaly.as_mut() else {
+            return;
+        };
+        let mut alerts: Vec<MarketAlert> = trades
+            .iter()
+            .filter_map(|trade| detector.observe(PriceFeed::Trade, trade.price(), now))
+            .collect();
+        alerts.extend(detector.check_book(best_bid, best_ask, now));
+        alerts.extend(detector.check_stale(now));
+        self.raise_alerts(&alerts);
+    }
+
+    /// 处理标记价格更新
+    pub fn update_mark_price(&mut self, mark_price: Price) -> CommandResult {
+        if mark_price == 0 {
+            return CommandResult::Error {
+                code: ErrorCode::InvalidPrice,
+                message: "价格不能为0".to_string(),
+            };
+        }
+        let now = self.current_timestamp;
+        let alerts: Vec<MarketAlert> = self
+            .anomaly
+            .as_mut()
+            .and_then(|detector| detector.observe(PriceFeed::Mark, mark_price, now))
+            .into_iter()
+            .collect();
+        self.raise_alerts(&alerts);
+        self.mark_price = Some(mark_price);
+        CommandResult::UpdateMarkPrice { mark_price, alerts }
+    }
+
+    /// 解除市场熔断
+    pub fn resume_trading(&mut self, operator: String) -> CommandResult {
+        let success = self.is_halted();
+        if let Some(record) = self.circuit_breaker_log.last_mut().filter(|r| r.is_active()) {
+            record.resumed_by = Some(operator);
+            record.resumed_at = Some(self.current_timestamp);
+        }
+        CommandResult::ResumeTrading { success }
+    }
+
+    /// 批量报价：整组校验通过后撤掉旧报价、挂出新报价（PostOnly），否则整条拒绝
+    pub fn mass_quote(&mut self, trader: TraderId, quotes: Vec<QuoteEntry>) -> CommandResult {
+        if quotes.is_empty() {
+            let old = self.mmp.replace_quotes(trader, Vec::new());
+            let cancelled = self.cancel_resting(&old);
+            return CommandResult::MassQuote { trader, order_ids: Vec::new(), cancelled };
+        }
+        if let Err(error) = self.validate_quotes(trader, &quotes) {
+            return error;
+        }
+
+        let old = self.mmp.replace_quotes(trader, Vec::new());
+        let cancelled = self.cancel_resting(&old);
+        let mut order_ids = Vec::new();
+        for entry in &quotes {
+            for (side, position_side, quote) in [
+                (Side::Buy, PositionSide::Long, entry.bid()),
+                (Side::Sell, PositionSide::Short, entry.ask()),
+            ] {
+                let Some((price, quantity)) = quote else {
+                    continue;
+                };
+                if let CommandResult::LimitOrder { order_id, .. } = self.handle_limit_order(
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side,
+                    false,
+                    TimeInForce::PostOnly,
+                ) {
+                    order_ids.push(order_id);
+                }
+            }
+        }
+        self.mmp.replace_quotes(trader, order_ids.clone());
+        CommandResult::MassQuote { trader, order_ids, cancelled }
+    }
+
+    /// 批量报价的整体校验（按旧报价已撤掉计算）
+    fn validate_quotes(
+        &self,
+        trader: TraderId,
+        quotes: &[QuoteEntry],
+    ) -> Result<(), CommandResult> {
+        let error =
+            |code, message: &str| CommandResult::Error { code, message: message.to_string() };
+        if self.is_expired() {
+            return Err(error(ErrorCode::InstrumentExpired, "合约已到期，停止交易"));
+        }
+        if self.is_halted() {
+            return Err(error(ErrorCode::CircuitBreakerTripped, "市场熔断中，暂停交易"));
+        }
+        if self.mmp.is_frozen(trader, self.current_timestamp) {
+            return Err(error(ErrorCode::MmpFrozen, "做市商保护冻结中"));
+        }
+
+        let bids: Vec<(Price, Quantity)> = quotes.iter().filter_map(QuoteEntry::bid).collect();
+        let asks: Vec<(Price, Quantity)> = quotes.iter().filter_map(QuoteEntry::ask).collect();
+        if bids.is_empty() && asks.is_empty() {
+            return Err(error(ErrorCode::InvalidQuantity, "数量不能为0"));
+        }
+        if bids.iter().chain(&asks).any(|&(price, _)| price == 0) {
+            return Err(error(ErrorCode::InvalidPrice, "价格不能为0"));
+        }
+
+        // 不与自身及订单簿上他人的挂单交叉
+        let old_quotes = self.mmp.quotes(trader);
+        let resting = |o: &&Order| o.is_active() && !old_quotes.contains(&o.id);
+        let best_ask = self.order_repo.get_asks().into_iter().find(resting).map(|o| o.price);
+        let best_bid = self.order_repo.get_bids().into_iter().find(resting).map(|o| o.price);
+        let max_bid = bids.iter().map(|&(price, _)| price).max();
+        let min_ask = asks.iter().map(|&(price, _)| price).min();
+        if max_bid.zip(min_ask).is_some_and(|(bid, ask)| bid >= ask) {
+            return Err(error(ErrorCode::InvalidPrice, "报价买价不低于卖价"));
+        }
+        if max_bid.zip(best_ask).is_some_and(|(bid, ask)| bid >= ask)
+            || min_ask.zip(best_bid).is_some_and(|(ask, bid)| ask <= bid)
+        {
+            return Err(error(ErrorCode::InvalidPrice, "报价会立即成交"));
+        }
+
+        // 账户风控按替换后的挂单数检查
+        let open_orders = self
+            .order_repo
+            .get_orders_by_trader(trader)
+            .iter()
+            .filter(|o| !old_quotes.contains(&o.id))
+            .count();
+        for (i, &(price, quantity)) in bids.iter().chain(&asks).enumerate() {
+            if let Err(code) = self.risk.pre_trade_check(trader, price, quantity, open_orders + i) {
+                let message = match code {
+                    ErrorCode::AccountRestricted => "账户已暂停或冻结",
+                    ErrorCode::KillSwitchActive => "账户已熔断",
+                    ErrorCode::MaxOpenOrdersExceeded => "挂单数超过上限",
+                    _ => "委托名义价值超过上限",
+                };
+                return Err(error(code, message));
+            }
+        }
+
+        // 保证金预占按替换后的挂单计算
+        let next_order_id = self.order_repo.peek_next_order_id();
+        let candidates: Vec<Order> = quotes
+            .iter()
+            .flat_map(|entry| {
+                [
+                    entry.bid().map(|quote| (Side::Buy, PositionSide::Long, quote)),
+                    entry.ask().map(|quote| (Side::Sell, PositionSide::Short, quote)),
+                ]
+            })
+            .flatten()
+            .enumerate()
+            .map(|(i, (side, position_side, (price, quantity)))| {
+                Order::new(
+                    next_order_id + i as OrderId,
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side,
+                    false,
+                    TimeInForce::PostOnly,
+                    self.current_timestamp,
+                )
+            })
+            .collect();
+        if let Err(code) = self.pre_fund(trader, &candidates, old_quotes) {
+            return Err(error(code, "可用保证金不足"));
+        }
+        Ok(())
+    }
+
+    /// 设置杠杆
+    ///
+    /// 账户杠杆双边共用，新开仓位沿用；`position_side` 限定同步调整的已有仓位。
+    /// 已有仓位调整杠杆时保证金不变，降低杠杆所需的起始保证金超过仓位保证金时拒绝
+    pub fn set_leverage(
+        &mut self,
+        trader: TraderId,
+        leverage: Leverage,
+        position_side: Option<PositionSide>,
+    ) -> CommandResult {
+        if leverage == 0 || leverage > MAX_LEVERAGE {
+            return CommandResult::Error {
+                code: ErrorCode::InvalidLeverage,
+                message: format!("杠杆须在 1~{} 之间", MAX_LEVERAGE),
+            };
+        }
+        let positions: Vec<&Position> = self
+            .position_repo
+            .get_positions_by_trader(trader)
+            .into_iter()
+            .filter(|p| position_side.is_none_or(|side| side == p.position_side))
+            .filter(|p| p.leverage != leverage)
+            .collect();
+        if positions
+            .iter()
+            .any(|p| leverage < p.leverage && p.initial_margin_at(leverage) > p.margin)
+        {
+            return CommandResult::Error {
+                code: ErrorCode::InsufficientMargin,
+                message: "仓位保证金不足以降低杠杆".to_string(),
+            };
+        }
+        let positions: Vec<(PositionId, PositionSide)> =
+            positions.into_iter().map(|p| (p.id, p.position_side)).collect();
+
+        let timestamp = self.current_timestamp;
+        for (position_id, side) in positions {
+            if let Some(position) = self.position_repo.get_position_mut(position_id) {
+                position.set_leverage(leverage, timestamp);
+            }
+            self.publish_position(trader, side);
+        }
+
+        let mut settings = self.account_settings(trader);
+        let old_leverage = settings.leverage;
+        if old_leverage != leverage {
+            settings.leverage = leverage;
+            self.account_settings.insert(trader, settings);
+            self.record_setting_change(
+                trader,
+                AccountSettingChange::Leverage { position_side, from: old_leverage, to: leverage },
+            );
+        }
+        CommandResult::SetLeverage { trader, old_leverage, new_leverage: leverage, success: true }
+    }
+
+    /// 切换保证金模式
+    ///
+    /// 有持仓或挂单时拒绝；目标模式与当前相同时 `success=false`
+    pub fn switch_margin_mode(&mut self, trader: TraderId, mode: MarginMode) -> CommandResult {
+        let mut settings = self.account_settings(trader);
+        let old_mode = settings.margin_mode;
+        if old_mode == mode {
+            return CommandResult::SwitchMarginMode {
+                trader,
+                old_mode,
+                new_mode: mode,
+                success: false,
+            };
+        }
+        if !self.position_repo.get_positions_by_trader(trader).is_empty()
+            || !self.order_repo.get_orders_by_trader(trader).is_empty()
+        {
+            return CommandResult::Error {
+                code: ErrorCode::PositionOrOrderExists,
+                message: "有持仓或挂单时不能切换保证金模式".to_string(),
+            };
+        }
+        settings.margin_mode = mode;
+        self.account_settings.insert(trader, settings);
+        self.record_setting_change(
+            trader,
+            AccountSettingChange::MarginMode { from: old_mode, to: mode },
+        );
+        CommandResult::SwitchMarginMode { trader, old_mode, new_mode: mode, success: true }
+    }
+
+    /// 调整逐仓保证金（正=追加，负=减少）
+    ///
+    /// 减少后不能低于按当前杠杆的起始保证金；资金划转由账户服务按变更记录执行
+    pub fn adjust_margin(
+        &mut self,
+        trader: TraderId,
+        position_id: PositionId,
+        amount: i64,
+    ) -> CommandResult {
+        if let Err(error) = self.ensure_status(trader, AccountStatus::can_withdraw) {
+            return error;
+        }
+        let timestamp = self.current_timestamp;
+        let Some(position) =
+            self.position_repo.get_position_mut(position_id).filter(|p| p.trader == trader)
+        else {
+            return CommandResult::Error {
+                code: ErrorCode::PositionNotFound,
+                message: "仓位不存在".to_string(),
+            };
+        };
+        if position.margin_mode != MarginMode::Isolated {
+            return CommandResult::Error {
+                code: ErrorCode::MarginModeMismatch,
+                message: "仅逐仓仓位可调整保证金".to_string(),
+            };
+        }
+        let old_margin = position.margin;
+        let new_margin = match old_margin.checked_add_signed(amount) {
+            Some(margin) if amount >= 0 || margin >= position.initial_margin() => margin,
+            _ => {
+                return CommandResult::Error {
+                    code: ErrorCode::InsufficientMargin,
+                    message: "减少后保证金低于起始保证金".to_string(),
+                };
+            }
+        };
+        if new_margin != old_margin {
+            position.set_margin(new_margin, timestamp);
+        }
+        let (new_liquidation_price, position_side) =
+            (position.liquidation_price, position.position_side);
+        if new_margin != old_margin {
+            self.publish_position(trader, position_side);
+            self.record_setting_change(
+                trader,
+                AccountSettingChange::Margin { position_id, from: old_margin, to: new_margin },
+            );
+        }
+        CommandResult::AdjustMargin { position_id, old_margin, new_margin, new_liquidation_price }
+    }
+
+    /// 追加账户设置变更记录并发布事件
+    fn record_setting_change(&mut self, trader: TraderId, change: AccountSettingChange) {
+        let version = self
+            .setting_log
+            .get(&trader)
+            .and_then(|records| records.back())
+            .map_or(0, |record| record.version);
+        let record = AccountSettingRecord {
+            trader,
+            change,
+            sequence: self.sequence,
+            timestamp: self.current_timestamp,
+            version: version + 1,
+        };
+        self.push_setting_record(record);
+        self.emit(EngineEvent::AccountSettingChanged(record));
+    }
+
+    /// 追加到账户的设置变更日志，超出保留条数时丢弃最早的记录
+    fn push_setting_record(&mut self, record: AccountSettingRecord) {
+        let records = self.setting_log.entry(record.trader).or_default();
+        if records.len() == SETTING_HISTORY_LIMIT {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// 按结算价格平掉仓位的 `quantity`，按比例释放保证金并发布结算与仓位事件
+    fn settle_position(
+        &mut self,
+        position_id: PositionId,
+        quantity: Quantity,
+        price: Price,
+        settlement_type: SettlementType,
+    ) -> Option<PositionSettlement> {
+        let timestamp = self.current_timestamp;
+        let position = self.position_repo.get_position_mut(position_id)?;
+        let released = (position.margin as u128 * quantity as u128
+            / position.quantity.max(1) as u128) as Margin;
+        let entry_price = position.entry_price;
+        let pnl = position.reduce(quantity, price, timestamp);
+        position.margin -= released;
+        let (trader, position_side, empty) =
+            (position.trader, position.position_side, position.is_empty());
+        if empty {
+            self.position_repo.remove_position(position_id);
+            self.cancel_protections(position_id);
+        }
+        let settlement = PositionSettlement {
+            settlement_type,
+            trader,
+            position_id,
+            position_side,
+            quantity,
+            entry_price,
+            price,
+            realized_pnl: pnl,
+            released_margin: released,
+            settled_at: timestamp,
+        };
+        self.emit(EngineEvent::PositionSettled(settlement));
+        self.publish_position(trader, position_side);
+        Some(settlement)
+    }
+
+    /// 反向恢复单边仓位，发布冲正结算
+    fn reverse_leg(&mut self, leg: &TradeLeg, price: Price) {
+        if leg.quantity == 0 {
+            return;
+        }
+
+        let timestamp = self.current_timestamp;
+        let position_id = self
+            .position_repo
+            .get_position_by_trader_side(leg.trader, leg.position_side)
+            .map(|p| p.id);
+
+        let settlement = if leg.opened {
+            // 撤销开仓：按成交价退回数量，还原均价并按比例释放保证金
+            let Some(position) =
+                position_id.and_then(|pos_id| self.position_repo.get_position_mut(pos_id))
+            else {
+                return;
+            };
+            let (position_id, entry_price) = (position.id, position.entry_price);
+            let released = position.remove_fill(leg.quantity, price, timestamp);
+            if position.is_empty() {
+                self.position_repo.remove_position(position_id);
+                self.cancel_protections(position_id);
+            }
+            PositionSettlement {
+                settlement_type: SettlementType::TradeBust,
+                trader: leg.trader,
+                position_id,
+                position_side: leg.position_side,
+                quantity: leg.quantity,
+                entry_price,
+                price,
+                realized_pnl: 0,
+                released_margin: released,
+                settled_at: timestamp,
+            }
+        } else {
+            let position_id = match position_id
+                .and_then(|pos_id| self.position_repo.get_position_mut(pos_id))
+            {
+                Some(position) => {
+                    // 撤销减仓：按原均价加回仓位，冲回已实现盈亏
+                    position.add(leg.quantity, leg.entry_price_before, timestamp);
+                    position.realized_pnl -= leg.realized_pnl;
+                    position.id
+                }
+                None => {
+                    // 减仓后仓位已关闭，按原均价重建（原仓位的已实现盈亏随平仓结算，不再回填）
+                    let position_id = self.position_repo.next_position_id();
+                    let settings = self.account_settings(leg.trader);
+                    let margin =
+                        self.calc_margin(leg.quantity, leg.entry_price_before, settings.leverage);
+                    let position = Position::new(
+                        position_id,
+                        leg.trader,
+                        leg.position_side,
+                        leg.quantity,
+                        leg.entry_price_before,
+                        settings.margin_mode,
+                        settings.leverage,
+                        margin,
+                        timestamp,
+                    );
+                    let _ = self.position_repo.save_position(position);
+                    position_id
+                }
+            };
+            PositionSettlement {
+                settlement_type: SettlementType::TradeBust,
+                trader: leg.trader,
+                position_id,
+                position_side: leg.position_side,
+                quantity: leg.quantity,
+                entry_price: leg.entry_price_before,
+                price,
+                realized_pnl: -leg.realized_pnl,
+                released_margin: 0,
+                settled_at: timestamp,
+            }
+        };
+        self.emit(EngineEvent::PositionSettled(settlement));
+        self.publish_position(leg.trader, leg.position_side);
+    }
+
+    /// 设置止损（多头价格下跌触发，空头价格上涨触发）
+    pub fn set_stop_loss(
+        &mut self,
+        trader: TraderId,
+        position_id: PositionId,
+        trigger_price: Price,
+        close_price: Option<Price>,
+    ) -> CommandResult {
+        match self.set_stop_order(
+            ProtectionKind::StopLoss,
+            trader,
+            position_id,
+            trigger_price,
+            close_price,
+        ) {
+            Ok(()) => CommandResult::SetStopLoss { position_id, trigger_price, success: true },
+            Err(error) => error,
+        }
+    }
+
+    /// 设置止盈（多头价格上涨触发，空头价格下跌触发）
+    pub fn set_take_profit(
+        &mut self,
+        trader: TraderId,
+        position_id: PositionId,
+        trigger_price: Price,
+        close_price: Option<Price>,
+    ) -> CommandResult {
+        match self.set_stop_order(
+            ProtectionKind::TakeProfit,
+            trader,
+            position_id,
+            trigger_price,
+            close_price,
+        ) {
+            Ok(()) => CommandResult::SetTakeProfit { position_id, trigger_price, success: true },
+            Err(error) => error,
+        }
+    }
+
+    /// 设置追踪止损
+    ///
+    /// 按回调比例跟踪最高价（多头）或最低价（空头）；未指定激活价时从最新成交价开始跟踪，
+    /// 尚未激活时返回的触发价为 0
+    pub fn set_trailing_stop(
+        &mut self,
+        trader: TraderId,
+        position_id: PositionId,
+        callback_rate: u32,
+        activation_price: Option<Price>,
+    ) -> CommandResult {
+        if let Err(error) = self.ensure_status(trader, AccountStatus::can_trade) {
+            return error;
+        }
+        let position_side = match self.owned_position(trader, position_id) {
+            Ok(position) => position.position_side,
+            Err(error) => return error,
+        };
+        if callback_rate == 0 || callback_rate >= 10_000 {
+            return CommandResult::Error {
+                code: ErrorCode::InvalidPrice,
+                message: "回调比例无效".to_string(),
+            };
+        }
+        let spec = TriggerSpec::Trailing {
+            direction: Self::stop_direction(position_side),
+            trail: Trail::Bps(callback_rate),
+            activation: activation_price,
+        };
+        let id =
+            self.register_protection(ProtectionKind::TrailingStop, trader, position_id, spec, None);
+        CommandResult::TrailingStop {
+            position_id,
+            current_trigger_price: self.conditional.trigger_price(id).unwrap_or(0),
+            success: true,
+        }
+    }
+
+    /// 止损/止盈共用逻辑：同一仓位的止损与止盈互为 OCO，重复设置替换原触发条件
+    fn set_stop_order(
+        &mut self,
+        kind: ProtectionKind,
+        trader: TraderId,
+        position_id: PositionId,
+        trigger_price: Price,
+        close_price: Option<Price>,
+    ) -> Result<(), CommandResult> {
+        self.ensure_status(trader, AccountStatus::can_trade)?;
+        let position_side = self.owned_position(trader, position_id)?.position_side;
+        if trigger_price == 0 || close_price == Some(0) {
+            return Err(CommandResult::Error {
+                code: ErrorCode::InvalidPrice,
+                message: "价格不能为0".to_string(),
+            });
+        }
+
+        let (direction, sibling_kind) = match kind {
+            ProtectionKind::TakeProfit => {
+                (Self::stop_direction(position_side).opposite(), ProtectionKind::StopLoss)
+            }
+            _ => (Self::stop_direction(position_side), ProtectionKind::TakeProfit),
+        };
+        let spec = TriggerSpec::Stop { direction, price: trigger_price };
+        let id = self.register_protection(kind, trader, position_id, spec, close_price);
+        let sibling = self.protections.get_mut(&position_id).and_then(|p| *p.slot(sibling_kind));
+        if let Some(sibling) = sibling {
+            let _ = self.conditional.link_oco(id, sibling);
+        }
+        Ok(())
+    }
+
+    /// 登记定投计划（首期按计划时间对齐，不早于当前时间）
+    pub fn create_recurring_plan(&mut self, spec: RecurringSpec) -> CommandResult {
+        if let Err(error) = self.ensure_status(spec.trader, AccountStatus::can_trade) {
+            return error;
+        }
+        match self.recurring.create(spec, self.current_timestamp) {
+            Ok(plan_id) => {
+                self.publish_recurring_plan(plan_id);
+                CommandResult::CreateRecurringPlan {
+                    plan_id,
+                    next_run: self
+                        .recurring
+                        .get(plan_id)
+                        .and_then(|plan| plan.next_run)
+                        .unwrap_or(0),
+                }
+            }
+            Err(code) => Self::recurring_error(code),
+        }
+    }
+
+    /// 暂停、恢复或撤销定投计划（只能操作自己的计划）
+    fn update_recurring_plan(
+        &mut self,
+        trader: TraderId,
+        plan_id: RecurringPlanId,
+        action: RecurringAction,
+    ) -> CommandResult {
+        let allowed = match action {
+            RecurringAction::Resume => AccountStatus::can_trade,
+            RecurringAction::Pause | RecurringAction::Cancel => AccountStatus::can_cancel,
+        };
+        if let Err(error) = self.ensure_status(trader, allowed) {
+            return error;
+        }
+        let outcome = match action {
+            RecurringAction::Pause => self
+                .recurring
+                .pause(trader, plan_id)
+                .map(|()| CommandResult::PauseRecurringPlan { plan_id }),
+            RecurringAction::Resume => self
+                .recurring
+                .resume(trader, plan_id, self.current_timestamp)
+                .map(|next_run| CommandResult::ResumeRecurringPlan { plan_id, next_run }),
+            RecurringAction::Cancel => self
+                .recurring
+                .cancel(trader, plan_id)
+                .map(|()| CommandResult::CancelRecurringPlan { plan_id }),
+        };
+        match outcome {
+            Ok(result) => {
+                self.publish_recurring_plan(plan_id);
+                result
+            }
+            Err(code) => Self::recurring_error(code),
+        }
+    }
+
+    /// 执行到期的定投期次：每期按普通委托提交，结果写入执行历史
+    ///
+    /// 市价委托与普通市价单走同一路径（本引擎暂未实现市价单，记为拒绝）
+    pub fn run_recurring_plans(&mut self) -> CommandResult {
+        let now = self.current_timestamp;
+        let mut executions = Vec::new();
+        let mut trades = Vec::new();
+        for run in self.recurring.release_due(now) {
+            let result = match run.command {
+                Command::LimitOrder {
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side,
+                    reduce_only,
+                    time_in_force,
+                } => self.handle_limit_order(
+                    trader,
+                    side,
+                    price,
+                    quantity,
+                    position_side,
+                    reduce_only,
+                    time_in_force,
+                ),
+                _ => CommandResult::Error {
+                    code: ErrorCode::SystemError,
+                    message: "命令未实现".to_string(),
+                },
+            };
+            let (order_id, error_code) = match result {
+                CommandResult::LimitOrder { order_id, trades: filled, .. } => {
+                    trades.extend(filled);
+                    (Some(order_id), None)
+                }
+                CommandResult::Error { code, .. } => (None, Some(code as u32)),
+                _ => (None, None),
+            };
+            let execution = RecurringExecution {
+                plan_id: run.plan_id,
+                run: run.run,
+                scheduled_at: run.scheduled_at,
+                executed_at: now,
+                order_id,
+                error_code,
+            };
+            self.recurring.record(execution);
+            self.emit(EngineEvent::RecurringExecuted(execution));
+            self.publish_recurring_plan(run.plan_id);
+            executions.push(execution);
+        }
+        CommandResult::RunRecurringPlans { executions, trades }
+    }
+
+    /// 发布定投计划的最新状态
+    fn publish_recurring_plan(&mut self, plan_id: RecurringPlanId) {
+        if let Some(plan) = self.recurring.get(plan_id) {
+            let event = EngineEvent::RecurringPlanChanged(plan.clone());
+            self.emit(event);
+        }
+    }
+
+    fn recurring_error(code: ErrorCode) -> CommandResult {
+        let message = match code {
+            ErrorCode::RecurringPlanNotFound => "定投计划不存在",
+            ErrorCode::InvalidQuantity => "数量不能为0",
+            ErrorCode::InvalidPrice => "价格不能为0",
+            _ => "定投计划无效或当前状态不允许该操作",
+        };
+        CommandResult::Error { code, message: message.to_string() }
+    }
+
+    /// 账户状态检查
+    fn ensure_status(
+        &self,
+        trader: TraderId,
+        allowed: fn(AccountStatus) -> bool,
+    ) -> Result<(), CommandResult> {
+        if allowed(self.risk.status(trader)) {
+            Ok(())
+        } else {
+            Err(CommandResult::Error {
+                code: ErrorCode::AccountRestricted,
+                message: "账户已暂停或冻结".to_string(),
+            })
+        }
+    }
+
+    /// 校验仓位存在且属于该交易者
+    fn owned_position(
+        &self,
+        trader: TraderId,
+        position_id: PositionId,
+    ) -> Result<&Position, CommandResult> {
+        match self.position_repo.get_position(position_id) {
+            Some(position) if position.trader == trader && !position.is_empty() => Ok(position),
+            _ => Err(CommandResult::Error {
+                code: ErrorCode::PositionNotFound,
+                message: "仓位不存在".to_string(),
+            }),
+        }
+    }
+
+    /// 止损的触发方向：多头（含单向持仓）价格下跌触发，空头价格上涨触发
+    fn stop_direction(position_side: PositionSide) -> Direction {
+        match position_side {
+            PositionSide::Short => Direction::Rising,
+            PositionSide::Long | PositionSide::Both => Direction::Falling,
+        }
+    }
+
+    /// 登记保护单，替换该仓位同类型的原保护单
+    fn register_protection(
+        &mut self,
+        kind: ProtectionKind,
+        trader: TraderId,
+        position_id: PositionId,
+        spec: TriggerSpec<Price>,
+        close_price: Option<Price>,
+    ) -> ConditionalId {
+        let slot = self.protections.entry(position_id).or_default().slot(kind);
+        if let Some(previous) = slot.take() {
+            self.conditional.cancel(previous);
+        }
+        self.conditional_id_counter += 1;
+        let id = self.conditional_id_counter;
+        let action = CloseOrder { trader, position_id, kind, close_price };
+        // ID 单调分配，不会重复
+        let _ = self.conditional.insert(id, spec, action);
+        *slot = Some(id);
+        id
+    }
+
+    /// 仓位平仓后撤销其全部保护单
+    fn cancel_protections(&mut self, position_id: PositionId) {
+        if let Some(protections) = self.protections.remove(&position_id) {
+            for id in [protections.stop_loss, protections.take_profit, protections.trailing_stop]
+                .into_iter()
+                .flatten()
+            {
+                self.conditional.cancel(id);
+            }
+        }
+    }
+
+    /// 以最新成交价评估条件单
+    ///
+    /// 触发的平仓单产生新成交时以新成交价继续评估，直到没有条件单触发
+    fn run_conditionals(&mut self, mut price: Price) {
+        loop {
+            let mut events = Vec::new();
+            self.conditional.on_price(price, &mut events);
+            let mut next_price = None;
+            for event in events {
+                if let ConditionalEvent::Submit(fired) = event {
+                    self.release_protection(&fired.action);
+                    next_price = self.execute_close(&fired.action).or(next_price);
+                }
+            }
+            match next_price {
+                Some(last) => price = last,
+                None => break,
+            }
+        }
+    }
+
+    /// 清除已触发的保护单（OCO 另一腿已随触发从监控索引移除）
+    fn release_protection(&mut self, action: &CloseOrder) {
+        let Some(protections) = self.protections.get_mut(&action.position_id) else {
+            return;
+        };
+        *protections.slot(action.kind) = None;
+        for slot in [&mut protections.stop_loss, &mut protections.take_profit] {
+            if slot.is_some_and(|id| !self.conditional.contains(id)) {
+                *slot = None;
+            }
+        }
+        if protections.is_empty() {
+            self.protections.remove(&action.position_id);
+        }
+    }
+
+    /// 以只减仓 IOC 单平掉仓位当前全部数量，返回最后成交价
+    ///
+    /// 市价平仓以对手方最差价位吃单
+    fn execute_close(&mut self, action: &CloseOrder) -> Option<Price> {
+        let position = self
+            .position_repo
+            .get_position(action.position_id)
+            .filter(|p| p.trader == action.trader && !p.is_empty())?;
+        let (position_side, quantity) = (position.position_side, position.quantity);
+        let side = match position_side {
+            PositionSide::Short => Side::Buy,
+            PositionSide::Long | PositionSide::Both => Side::Sell,
+        };
+        let price = match (action.close_price, side) {
+            (Some(price), _) => price,
+            (None, Side::Sell) => self.order_repo.get_bids().last()?.price,
+            (None, Side::Buy) => self.order_repo.get_asks().last()?.price,
+        };
+        match self.handle_limit_order(
+            action.trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            true,
+            TimeInForce::IOC,
+        ) {
+            CommandResult::LimitOrder { trades, .. } => trades.last().map(|t| t.price()),
+            _ => None,
+        }
+    }
+
+    /// 下单前的保证金预占检查
+    ///
+    /// 返回该委托使账户挂单最坏情况保证金（见 [`worst_case_margin`]）增加的数额，
+    /// 账户服务按此冻结；超过 `available` 时返回 `InsufficientMargin`。
+    /// 平仓方向的委托先抵消现有持仓，不会与持仓重复占用保证金
+    pub fn check_collateral(
+        &self,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        position_side: PositionSide,
+        reduce_only: bool,
+        available: Margin,
+    ) -> Result<Margin, ErrorCode> {
+        // 待下委托排在全部挂单之后
+        let candidate = Order::new(
+            self.order_repo.peek_next_order_id(),
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only,
+            TimeInForce::GTC,
+            self.current_timestamp,
+        );
+        let required = self.additional_margin(trader, &[candidate], &[]);
+        if required > available {
+            return Err(ErrorCode::InsufficientMargin);
+        }
+        Ok(required)
+    }
+
+    /// 撮合路径上的保证金预占：已同步保证金余额的账户，新增的最坏情况保证金
+    /// 不得超过尚未占用的余额（`replaced` 为同时撤掉的挂单）
+    fn pre_fund(
+        &self,
+        trader: TraderId,
+        candidates: &[Order],
+        replaced: &[OrderId],
+    ) -> Result<(), ErrorCode> {
+        let Some(&collateral) = self.collateral.get(&trader) else {
+            return Ok(());
+        };
+        let positions: Vec<Position> =
+            self.position_repo.get_positions_by_trader(trader).into_iter().cloned().collect();
+        let orders = self
+            .order_repo
+            .get_orders_by_trader(trader)
+            .into_iter()
+            .filter(|o| !replaced.contains(&o.id));
+        let used = positions.iter().map(|p| p.margin).sum::<Margin>()
+            + worst_case_margin(orders, &positions, self.account_settings(trader).leverage);
+        let available = collateral.saturating_sub(used);
+        if self.additional_margin(trader, candidates, replaced) > available {
+            return Err(ErrorCode::InsufficientMargin);
+        }
+        Ok(())
+    }
+
+    /// 新委托使账户挂单最坏情况保证金增加的数额（`replaced` 为同时撤掉的挂单）
+    fn additional_margin(
+        &self,
+        trader: TraderId,
+        candidates: &[Order],
+        replaced: &[OrderId],
+    ) -> Margin {
+        let positions: Vec<Position> =
+            self.position_repo.get_positions_by_trader(trader).into_iter().cloned().collect();
+        let mut orders: Vec<&Order> = self
+            .order_repo
+            .get_orders_by_trader(trader)
+            .into_iter()
+            .filter(|o| !replaced.contains(&o.id))
+            .collect();
+        let leverage = self.account_settings(trader).leverage;
+        let before = worst_case_margin(orders.iter().copied(), &positions, leverage);
+        orders.extend(candidates);
+        let after = worst_case_margin(orders, &positions, leverage);
+        after.saturating_sub(before)
+    }
+
+    /// 计算保证金
+    fn calc_margin(&self, quantity: Quantity, price: Price, leverage: Leverage) -> u64 {
+        initial_margin(quantity, price, leverage)
+    }
+
+    /// 计算手续费
+    fn calc_fee(&self, quantity: Quantity, price: Price, is_maker: bool) -> u64 {
+        let fee_rate = if is_maker { 2 } else { 5 }; // 0.02% maker, 0.05% taker
+        quantity * price * fee_rate / 100_000
+    }
+}
+
+impl<O, P> PrepCommandHandler for MatchingService<O, P>
+where
+    O: OrderRepository,
+    P: PositionRepository,
+{
     fn handle(&mut self, command: Command) -> CommandResult {
-        match command {
+        self.sequence += 1;
+        let result = match command {
             Command::LimitOrder {
                 trader,
                 side,
@@ -406,14 +2371,29 @@ where
             ),
 
             Command::CancelOrder { order_id } => {
-                if let Some(order) = self.order_repo.get_order_mut(order_id) {
+                let restricted = self.order_repo.get_order(order_id).and_then(|order| {
+                    self.ensure_status(order.trader, AccountStatus::can_cancel).err()
+                });
+                if let Some(error) = restricted {
+                    error
+                } else if let Some(order) = self.order_repo.get_order_mut(order_id) {
                     let cancelled_qty = order.remaining_quantity;
-                    order.cancel(self.current_timestamp);
-                    self.order_repo.remove_order(order_id);
-                    CommandResult::CancelOrder {
-                        order_id,
-                        success: true,
-                        cancelled_quantity: cancelled_qty,
+                    match order.cancel(self.current_timestamp) {
+                        Ok(from) => {
+                            let report = ExecutionReport::status_change(order, from);
+                            self.order_repo.remove_order(order_id);
+                            self.publish_report(report);
+                            CommandResult::CancelOrder {
+                                order_id,
+                                success: true,
+                                cancelled_quantity: cancelled_qty,
+                            }
+                        }
+                        Err(_) => CommandResult::CancelOrder {
+                            order_id,
+                            success: false,
+                            cancelled_quantity: 0,
+                        },
                     }
                 } else {
                     CommandResult::Error {
@@ -423,14 +2403,255 @@ where
                 }
             }
 
+            Command::BustTrade { trade_id, reason, operator } => {
+                self.bust_trade(trade_id, reason, operator)
+            }
+
+            Command::CompressPositions { legs, price, operator } => {
+                self.compress_positions(legs, price, operator)
+            }
+            Command::LinkSubAccount { sub_account, parent } => {
+                self.link_sub_account(sub_account, parent)
+            }
+            Command::SetCompressionConsent { sub_account, consent } => {
+                self.set_compression_consent(sub_account, consent)
+            }
+
+            Command::SetExpiry { expiry, .. } => self.set_expiry(expiry),
+
+            Command::SetCollateral { trader, collateral } => {
+                self.collateral.insert(trader, collateral);
+                CommandResult::SetCollateral { trader, collateral }
+            }
+
+            Command::DeliverFutures { delivery_price, operator } => {
+                self.deliver_futures(delivery_price, operator)
+            }
+
+            Command::UpdateMarkPrice { mark_price } => self.update_mark_price(mark_price),
+
+            Command::ResumeTrading { operator } => self.resume_trading(operator),
+
+            Command::SetLeverage { trader, leverage, position_side } => {
+                self.set_leverage(trader, leverage, position_side)
+            }
+
+            Command::SwitchMarginMode { trader, mode } => self.switch_margin_mode(trader, mode),
+
+            Command::AdjustMargin { trader, position_id, amount } => {
+                self.adjust_margin(trader, position_id, amount)
+            }
+
+            Command::MassQuote { trader, quotes } => self.mass_quote(trader, quotes),
+
+            Command::SetMmp { trader, config } => {
+                self.mmp.set_config(trader, config);
+                CommandResult::SetMmp { trader }
+            }
+
+            Command::ResetMmp { trader } => {
+                let success = self.mmp.reset(trader, self.current_timestamp);
+                CommandResult::ResetMmp { trader, success }
+            }
+
+            Command::SetFeatureFlag { feature, rule, operator } => {
+                self.flags.set(feature, rule, operator, self.sequence, self.current_timestamp);
+                CommandResult::SetFeatureFlag { feature }
+            }
+
+            Command::CreateRecurringPlan { spec } => self.create_recurring_plan(spec),
+
+            Command::PauseRecurringPlan { trader, plan_id } => {
+                self.update_recurring_plan(trader, plan_id, RecurringAction::Pause)
+            }
+
+            Command::ResumeRecurringPlan { trader, plan_id } => {
+                self.update_recurring_plan(trader, plan_id, RecurringAction::Resume)
+            }
+
+            Command::CancelRecurringPlan { trader, plan_id } => {
+                self.update_recurring_plan(trader, plan_id, RecurringAction::Cancel)
+            }
+
+            Command::RunRecurringPlans => self.run_recurring_plans(),
+
+            // 定时委托由分片命令队列挂起，不进入撮合
+            Command::ScheduleOrder { .. } | Command::CancelScheduledOrder { .. } => {
+                CommandResult::Error {
+                    code: ErrorCode::InvalidScheduledOrder,
+                    message: "定时委托须经分片命令队列登记".to_string(),
+                }
+            }
+
+            Command::SetRiskProfile { trader, profile, operator } => {
+                self.risk.set_profile(trader, profile, operator, self.current_timestamp);
+                CommandResult::SetRiskProfile { trader }
+            }
+
+            Command::ResetKillSwitch { trader, operator } => {
+                let success = self.risk.reset_kill_switch(trader, operator, self.current_timestamp);
+                CommandResult::ResetKillSwitch { trader, success }
+            }
+
+            Command::SetAccountStatus { trader, status, reason, operator } => {
+                self.set_account_status(trader, status, reason, operator)
+            }
+
+            Command::SetStopLoss { trader, position_id, trigger_price, close_price } => {
+                self.set_stop_loss(trader, position_id, trigger_price, close_price)
+            }
+
+            Command::SetTakeProfit { trader, position_id, trigger_price, close_price } => {
+                self.set_take_profit(trader, position_id, trigger_price, close_price)
+            }
+
+            Command::TrailingStop { trader, position_id, callback_rate, activation_price } => {
+                self.set_trailing_stop(trader, position_id, callback_rate, activation_price)
+            }
+
             _ => CommandResult::Error {
                 code: ErrorCode::SystemError,
                 message: "命令未实现".to_string(),
             },
+        };
+
+        // 成交价驱动条件单触发
+        if let CommandResult::LimitOrder { trades, .. }
+        | CommandResult::MarketOrder { trades, .. }
+        | CommandResult::RunRecurringPlans { trades, .. } = &result
+        {
+            if let Some(trade) = trades.last() {
+                self.run_conditionals(trade.price());
+            }
+        }
+
+        if self.anomaly.is_some() {
+            let trades: &[Trade] = match &result {
+                CommandResult::LimitOrder { trades, .. }
+                | CommandResult::MarketOrder { trades, .. }
+                | CommandResult::RunRecurringPlans { trades, .. } => trades,
+                _ => &[],
+            };
+            self.monitor_market(trades);
+        }
+
+        if let Some(sampler) = self.sampler.as_mut() {
+            if sampler.is_due(self.sequence) {
+                let default_leverage = self.default_leverage;
+                let settings = &self.account_settings;
+                let probe = sampler.capture(
+                    &self.order_repo,
+                    &self.position_repo,
+                    |trader| settings.get(&trader).map_or(default_leverage, |s| s.leverage),
+                    self.sequence,
+                    self.current_timestamp,
+                );
+                self.invariant_probes.push(probe);
+            }
+        }
+
+        if let Some(counters) = &self.stats {
+            let matches = match &result {
+                CommandResult::LimitOrder { trades, .. }
+                | CommandResult::MarketOrder { trades, .. }
+                | CommandResult::RunRecurringPlans { trades, .. } => trades.len() as u64,
+                _ => 0,
+            };
+            counters.record_command(
+                self.sequence,
+                matches,
+                matches!(result, CommandResult::Error { .. }),
+            );
+            if self.sequence.is_multiple_of(BOOK_STATS_INTERVAL) {
+                self.publish_book_stats();
+            }
         }
+        result
     }
 
     fn handler_name(&self) -> &'static str {
         "PrepMatchingService"
     }
 }
+
+impl<O, P> PrepQueryHandler for MatchingService<O, P>
+where
+    O: OrderRepository,
+    P: PositionRepository,
+{
+    fn account_snapshot(
+        &self,
+        trader: TraderId,
+        mark_price: Option<Price>,
+        balances: &dyn BalanceReader,
+    ) -> AccountSnapshot {
+        let positions: Vec<Position> = self
+            .position_repo
+            .get_positions_by_trader(trader)
+            .into_iter()
+            .map(|p| {
+                let mut position = p.clone();
+                if let Some(mark) = mark_price {
+                    position.update_unrealized_pnl(mark);
+                }
+                position
+            })
+            .collect();
+
+        let open_order_margin = worst_case_margin(
+            self.order_repo.get_orders_by_trader(trader),
+            &positions,
+            self.account_settings(trader).leverage,
+        );
+
+        AccountSnapshot {
+            trader,
+            sequence: self.sequence,
+            timestamp: self.current_timestamp,
+            balances: balances.balances_of(trader),
+            unrealized_pnl: positions.iter().map(|p| p.unrealized_pnl).sum(),
+            positions,
+            open_order_margin,
+        }
+    }
+
+    fn queue_position(
+        &self,
+        trader: TraderId,
+        order_id: OrderId,
+    ) -> Result<QueuePosition, ErrorCode> {
+        let order = self
+            .order_repo
+            .get_order(order_id)
+            .filter(|o| o.trader == trader && o.is_active())
+            .ok_or(ErrorCode::OrderNotFound)?;
+
+        let level = match order.side {
+            Side::Buy => self.order_repo.get_bids_at_price(order.price),
+            Side::Sell => self.order_repo.get_asks_at_price(order.price),
+        };
+        let ahead: Vec<_> = level.iter().take_while(|o| o.id != order_id).collect();
+
+        Ok(QueuePosition {
+            order_id,
+            side: order.side,
+            price: order.price,
+            orders_ahead: ahead.len(),
+            quantity_ahead: ahead.iter().map(|o| o.remaining_quantity).sum(),
+            remaining_quantity: order.remaining_quantity,
+            level_quantity: level.iter().map(|o| o.remaining_quantity).sum(),
+            sequence: self.sequence,
+        })
+    }
+
+    fn position_risk(&self, trader: TraderId, mark_price: Price) -> Vec<PositionRisk> {
+        position_risks(trader, mark_price, self.position_repo.get_all_positions(), self.sequence)
+    }
+
+    fn setting_history(&self, trader: TraderId, limit: usize) -> Vec<AccountSettingRecord> {
+        self.setting_log
+            .get(&trader)
+            .map(|records| records.iter().rev().take(limit).copied().collect())
+            .unwrap_or_default()
+    }
+}
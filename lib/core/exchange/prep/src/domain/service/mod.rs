@@ -1,7 +1,51 @@
 //! 永续合约领域服务
 
+pub mod adl;
+pub mod anomaly;
 pub mod command;
+pub mod command_queue;
+pub mod compression;
+pub mod confirmation;
+pub mod delivery;
+pub mod digest;
+pub mod engine_stats;
+pub mod feature_flag;
+pub mod invariant;
+pub mod leaderboard;
+pub mod mass_quote;
 pub mod matching;
+pub mod prefunding;
+pub mod projection;
+pub mod query;
+pub mod quote_life;
+pub mod recurring;
+pub mod risk;
+pub mod scheduler;
+pub mod shard_router;
+pub mod speed_bump;
+pub mod warmup;
 
+pub use adl::*;
+pub use anomaly::*;
 pub use command::*;
+pub use command_queue::*;
+pub use compression::*;
+pub use confirmation::*;
+pub use delivery::*;
+pub use digest::*;
+pub use engine_stats::*;
+pub use feature_flag::*;
+pub use invariant::*;
+pub use leaderboard::*;
+pub use mass_quote::*;
 pub use matching::*;
+pub use prefunding::*;
+pub use projection::*;
+pub use query::*;
+pub use quote_life::*;
+pub use recurring::*;
+pub use risk::*;
+pub use scheduler::*;
+pub use shard_router::*;
+pub use speed_bump::*;
+pub use warmup::*;
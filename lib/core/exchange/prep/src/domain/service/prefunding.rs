@@ -0,0 +1,111 @@
+//! 挂单保证金预占（最坏损失模型）
+//!
+//! 挂单保证金按持仓方向分组计算。每组的挂单分为两个方向：
+//! - 开仓方向（多仓/单向持仓的买单、空仓的卖单）：全部成交时新增的仓位需要保证金
+//! - 平仓方向：先抵消现有持仓（按订单ID顺序消耗），超出持仓的部分才需要保证金
+//!
+//! 两个方向不会同时把风险推向同一边，最坏情况取两者较大值，而不是简单求和。
+//! 只减仓订单不会超过持仓，不占用保证金。平仓挂单因此不会与持仓重复锁定保证金
+
+use crate::domain::entity::{Leverage, Margin, Order, Position, PositionSide, Side};
+use crate::domain::service::matching::initial_margin;
+
+/// 持仓方向上的开仓方向
+fn opening_side(position_side: PositionSide) -> Side {
+    match position_side {
+        PositionSide::Long | PositionSide::Both => Side::Buy,
+        PositionSide::Short => Side::Sell,
+    }
+}
+
+/// 一组持仓方向的最坏情况保证金
+fn side_margin<'a>(
+    orders: impl Iterator<Item = &'a Order>,
+    position_side: PositionSide,
+    position_quantity: u64,
+    leverage: Leverage,
+) -> Margin {
+    let mut orders: Vec<&Order> = orders.filter(|o| !o.reduce_only).collect();
+    orders.sort_by_key(|o| o.id);
+
+    let opening = opening_side(position_side);
+    let mut opening_margin: Margin = 0;
+    let mut closing_margin: Margin = 0;
+    let mut offset = position_quantity;
+    for order in orders {
+        if order.side == opening {
+            opening_margin += initial_margin(order.remaining_quantity, order.price, leverage);
+        } else {
+            let covered = order.remaining_quantity.min(offset);
+            offset -= covered;
+            closing_margin +=
+                initial_margin(order.remaining_quantity - covered, order.price, leverage);
+        }
+    }
+    opening_margin.max(closing_margin)
+}
+
+/// 账户挂单的最坏情况保证金
+///
+/// `positions` 为该账户的持仓，杠杆取对应持仓的杠杆，无持仓时取 `default_leverage`
+pub fn worst_case_margin<'a>(
+    orders: impl IntoIterator<Item = &'a Order>,
+    positions: &[Position],
+    default_leverage: Leverage,
+) -> Margin {
+    let orders: Vec<&Order> = orders.into_iter().collect();
+    [PositionSide::Both, PositionSide::Long, PositionSide::Short]
+        .into_iter()
+        .map(|position_side| {
+            let position = positions.iter().find(|p| p.position_side == position_side);
+            side_margin(
+                orders.iter().copied().filter(|o| o.position_side == position_side),
+                position_side,
+                position.map_or(0, |p| p.quantity),
+                position.map_or(default_leverage, |p| p.leverage),
+            )
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{MarginMode, TimeInForce};
+
+    fn order(id: u64, side: Side, price: u64, quantity: u64, position_side: PositionSide) -> Order {
+        Order::new(id, 1, side, price, quantity, position_side, false, TimeInForce::GTC, 0)
+    }
+
+    fn long(quantity: u64) -> Position {
+        Position::new(1, 1, PositionSide::Long, quantity, 100, MarginMode::Cross, 10, 0, 0)
+    }
+
+    #[test]
+    fn test_closing_orders_offset_position() {
+        // 无持仓：买卖两侧取较大值，而不是相加
+        let orders = [
+            order(1, Side::Buy, 100, 10, PositionSide::Long),
+            order(2, Side::Sell, 100, 4, PositionSide::Long),
+        ];
+        assert_eq!(worst_case_margin(&orders, &[], 10), 100);
+
+        // 持有 10 多仓：平仓卖单被持仓完全抵消
+        let orders = [order(1, Side::Sell, 100, 10, PositionSide::Long)];
+        assert_eq!(worst_case_margin(&orders, &[long(10)], 10), 0);
+
+        // 超出持仓的部分仍需保证金，按订单ID顺序消耗持仓
+        let orders = [
+            order(1, Side::Sell, 100, 6, PositionSide::Long),
+            order(2, Side::Sell, 200, 6, PositionSide::Long),
+        ];
+        // 订单 2 剩余 2 × 200 / 10
+        assert_eq!(worst_case_margin(&orders, &[long(10)], 10), 40);
+
+        // 只减仓订单不占用保证金；空仓组的卖单为开仓方向
+        let mut reduce = order(1, Side::Sell, 100, 10, PositionSide::Long);
+        reduce.reduce_only = true;
+        let orders = [reduce, order(2, Side::Sell, 100, 10, PositionSide::Short)];
+        assert_eq!(worst_case_margin(&orders, &[], 10), 100);
+    }
+}
@@ -0,0 +1,579 @@
+//! 读侧投影
+//!
+//! 消费撮合引擎的事件流，在独立存储中维护反规范化的查询模型：
+//! 账户挂单、仓位、成交历史、余额与定投计划。全部查询由投影应答，读请求不再触达撮合路径
+//!
+//! 投影只依赖事件，可在另一线程或另一进程的只读副本上运行；
+//! 模型相对撮合引擎的延迟以 `sequence()` 表示
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use crate::domain::ErrorCode;
+use crate::domain::entity::{
+    AccountSettingRecord, AssetBalance, EngineEvent, EventEnvelope, ExecutionReport, Leverage,
+    Order, OrderId, Position, Price, Quantity, RecurringExecution, RecurringPlan, RecurringPlanId,
+    SETTING_HISTORY_LIMIT, Side, Timestamp, TradeId, TradeLeg, TradeRecord, TraderId,
+};
+use crate::domain::repository::BalanceReader;
+use crate::domain::service::matching::DEFAULT_LEVERAGE;
+use crate::domain::service::prefunding::worst_case_margin;
+use crate::domain::service::query::{
+    AccountSnapshot, PositionRisk, PrepQueryHandler, QueuePosition, position_risks,
+};
+
+/// 每个账户默认保留的成交历史条数
+pub const DEFAULT_TRADE_HISTORY: usize = 1000;
+
+/// 账户成交历史条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeHistoryEntry {
+    /// 成交ID
+    pub trade_id: TradeId,
+    /// 成交价格
+    pub price: Price,
+    /// 成交数量
+    pub quantity: Quantity,
+    /// 本账户一方
+    pub leg: TradeLeg,
+    /// 是否为 Maker
+    pub is_maker: bool,
+    /// 是否已被撤销
+    pub busted: bool,
+    /// 成交时间
+    pub timestamp: Timestamp,
+}
+
+/// 读侧投影
+#[derive(Debug)]
+pub struct ReadModelProjection {
+    /// 已应用的最大命令序列号
+    sequence: u64,
+    /// 最近事件时间
+    timestamp: Timestamp,
+    /// 最新标记价格（由标记价格更新结果写入）
+    mark_price: Option<Price>,
+    /// 默认杠杆（无仓位时估算挂单保证金）
+    default_leverage: Leverage,
+    /// 每个账户保留的成交历史条数
+    history_limit: usize,
+    /// 活跃挂单（按订单ID，即同价位的时间优先顺序）
+    open_orders: BTreeMap<OrderId, Order>,
+    /// 买方价位索引：价格 → 挂单ID（升序即时间优先）
+    bid_levels: HashMap<Price, BTreeSet<OrderId>>,
+    /// 卖方价位索引
+    ask_levels: HashMap<Price, BTreeSet<OrderId>>,
+    /// 账户挂单索引
+    trader_orders: HashMap<TraderId, BTreeSet<OrderId>>,
+    /// 账户仓位
+    positions: HashMap<TraderId, Vec<Position>>,
+    /// 账户成交历史（最新在后）
+    trades: HashMap<TraderId, VecDeque<TradeHistoryEntry>>,
+    /// 账户余额
+    balances: HashMap<TraderId, Vec<AssetBalance>>,
+    /// 账户设置变更历史（最新在后，每个账户保留最近 SETTING_HISTORY_LIMIT 条）
+    settings: HashMap<TraderId, VecDeque<AccountSettingRecord>>,
+    /// 定投计划
+    recurring_plans: BTreeMap<RecurringPlanId, RecurringPlan>,
+    /// 账户定投执行历史（最新在后，条数同成交历史）
+    recurring_history: HashMap<TraderId, VecDeque<RecurringExecution>>,
+}
+
+impl Default for ReadModelProjection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadModelProjection {
+    /// 创建空投影
+    pub fn new() -> Self {
+        Self::with_history_limit(DEFAULT_TRADE_HISTORY)
+    }
+
+    /// 指定每个账户保留的成交历史条数
+    pub fn with_history_limit(history_limit: usize) -> Self {
+        Self {
+            sequence: 0,
+            timestamp: 0,
+            mark_price: None,
+            default_leverage: DEFAULT_LEVERAGE,
+            history_limit,
+            open_orders: BTreeMap::new(),
+            bid_levels: HashMap::new(),
+            ask_levels: HashMap::new(),
+            trader_orders: HashMap::new(),
+            positions: HashMap::new(),
+            trades: HashMap::new(),
+            balances: HashMap::new(),
+            settings: HashMap::new(),
+            recurring_plans: BTreeMap::new(),
+            recurring_history: HashMap::new(),
+        }
+    }
+
+    /// 已应用的最大命令序列号
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// 最新标记价格（尚未收到标记价格更新时为 None）
+    pub fn mark_price(&self) -> Option<Price> {
+        self.mark_price
+    }
+
+    /// 记录标记价格更新
+    ///
+    /// 引擎事件流不含标记价格，由消费线程从 `CommandResult::UpdateMarkPrice` 写入
+    pub fn on_mark_price(&mut self, mark_price: Price) {
+        self.mark_price = Some(mark_price);
+    }
+
+    /// 应用一批事件
+    pub fn apply_all<'a>(&mut self, envelopes: impl IntoIterator<Item = &'a EventEnvelope>) {
+        for envelope in envelopes {
+            self.apply(envelope);
+        }
+    }
+
+    /// 应用单个事件
+    pub fn apply(&mut self, envelope: &EventEnvelope) {
+        self.sequence = self.sequence.max(envelope.sequence);
+        match &envelope.event {
+            EngineEvent::OrderAccepted(order) => {
+                self.timestamp = self.timestamp.max(order.updated_at);
+                self.insert_order(order.clone());
+            }
+            EngineEvent::Execution(report) => {
+                self.timestamp = self.timestamp.max(report.timestamp);
+                self.apply_execution(report);
+            }
+            EngineEvent::Trade(record) => {
+                self.timestamp = self.timestamp.max(record.timestamp);
+                self.record_trade(record);
+            }
+            EngineEvent::TradeBusted(record) => self.mark_busted(record),
+            EngineEvent::PositionChanged(position) => {
+                self.timestamp = self.timestamp.max(position.updated_at);
+                let positions = self.positions.entry(position.trader).or_default();
+                match positions.iter_mut().find(|p| p.position_side == position.position_side) {
+                    Some(existing) => *existing = position.clone(),
+                    None => positions.push(position.clone()),
+                }
+            }
+            EngineEvent::PositionSettled(settlement) => {
+                self.timestamp = self.timestamp.max(settlement.settled_at);
+            }
+            EngineEvent::MarketAlert(alert) => {
+                self.timestamp = self.timestamp.max(alert.raised_at);
+            }
+            EngineEvent::MmpTriggered(trigger) => {
+                self.timestamp = self.timestamp.max(trigger.triggered_at);
+            }
+            EngineEvent::AccountSettingChanged(record) => {
+                self.timestamp = self.timestamp.max(record.timestamp);
+                let records = self.settings.entry(record.trader).or_default();
+                if records.len() == SETTING_HISTORY_LIMIT {
+                    records.pop_front();
+                }
+                records.push_back(*record);
+            }
+            EngineEvent::RecurringPlanChanged(plan) => {
+                self.recurring_plans.insert(plan.id, plan.clone());
+            }
+            EngineEvent::RecurringExecuted(execution) => {
+                self.timestamp = self.timestamp.max(execution.executed_at);
+                let Some(plan) = self.recurring_plans.get(&execution.plan_id) else {
+                    return;
+                };
+                let history = self.recurring_history.entry(plan.spec.trader).or_default();
+                history.push_back(*execution);
+                while history.len() > self.history_limit {
+                    history.pop_front();
+                }
+            }
+            EngineEvent::PositionClosed { trader, position_side } => {
+                if let Some(positions) = self.positions.get_mut(trader) {
+                    positions.retain(|p| p.position_side != *position_side);
+                    if positions.is_empty() {
+                        self.positions.remove(trader);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 更新账户余额（由账户服务推送）
+    pub fn update_balance(&mut self, trader: TraderId, balance: AssetBalance) {
+        let balances = self.balances.entry(trader).or_default();
+        match balances.iter_mut().find(|b| b.asset == balance.asset) {
+            Some(existing) => *existing = balance,
+            None => balances.push(balance),
+        }
+    }
+
+    /// 账户活跃挂单（按订单ID升序）
+    pub fn open_orders(&self, trader: TraderId) -> Vec<&Order> {
+        self.trader_orders
+            .get(&trader)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.open_orders.get(id))
+            .collect()
+    }
+
+    /// 账户仓位
+    pub fn positions(&self, trader: TraderId) -> &[Position] {
+        self.positions.get(&trader).map_or(&[], Vec::as_slice)
+    }
+
+    /// 账户最近 `limit` 条成交（最新在前）
+    pub fn trade_history(&self, trader: TraderId, limit: usize) -> Vec<TradeHistoryEntry> {
+        self.trades
+            .get(&trader)
+            .map(|trades| trades.iter().rev().take(limit).copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// 账户定投计划（按计划ID升序）
+    pub fn recurring_plans(&self, trader: TraderId) -> Vec<&RecurringPlan> {
+        self.recurring_plans.values().filter(|plan| plan.spec.trader == trader).collect()
+    }
+
+    /// 账户最近 `limit` 条定投执行记录（最新在前），可按计划过滤
+    pub fn recurring_history(
+        &self,
+        trader: TraderId,
+        plan_id: Option<RecurringPlanId>,
+        limit: usize,
+    ) -> Vec<RecurringExecution> {
+        self.recurring_history
+            .get(&trader)
+            .map(|history| {
+                history
+                    .iter()
+                    .rev()
+                    .filter(|execution| plan_id.is_none_or(|id| execution.plan_id == id))
+                    .take(limit)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 使用投影自身的余额组装账户快照
+    pub fn account(&self, trader: TraderId, mark_price: Option<Price>) -> AccountSnapshot {
+        self.account_snapshot(trader, mark_price, self)
+    }
+
+    fn apply_execution(&mut self, report: &ExecutionReport) {
+        let Some(order) = self.open_orders.get_mut(&report.order_id) else {
+            // Taker 成交发生在挂单之前，剩余部分随 OrderAccepted 入簿
+            return;
+        };
+        if report.status.is_terminal() {
+            self.remove_order(report.order_id);
+            return;
+        }
+        order.status = report.status;
+        order.filled_quantity = report.cumulative_filled_quantity;
+        order.remaining_quantity = report.remaining_quantity;
+        order.cumulative_quote += report.last_fill_price * report.last_fill_quantity;
+        order.updated_at = report.timestamp;
+    }
+
+    fn insert_order(&mut self, order: Order) {
+        self.remove_order(order.id);
+        let levels = match order.side {
+            Side::Buy => &mut self.bid_levels,
+            Side::Sell => &mut self.ask_levels,
+        };
+        levels.entry(order.price).or_default().insert(order.id);
+        self.trader_orders.entry(order.trader).or_default().insert(order.id);
+        self.open_orders.insert(order.id, order);
+    }
+
+    fn remove_order(&mut self, order_id: OrderId) {
+        let Some(order) = self.open_orders.remove(&order_id) else {
+            return;
+        };
+        let levels = match order.side {
+            Side::Buy => &mut self.bid_levels,
+            Side::Sell => &mut self.ask_levels,
+        };
+        remove_indexed(levels, order.price, order_id);
+        remove_indexed(&mut self.trader_orders, order.trader, order_id);
+    }
+
+    /// 同价位挂单（时间优先）
+    fn level(&self, side: Side, price: Price) -> Vec<&Order> {
+        let levels = match side {
+            Side::Buy => &self.bid_levels,
+            Side::Sell => &self.ask_levels,
+        };
+        levels.get(&price).into_iter().flatten().filter_map(|id| self.open_orders.get(id)).collect()
+    }
+
+    fn record_trade(&mut self, record: &TradeRecord) {
+        for (leg, is_maker) in [(record.taker, false), (record.maker, true)] {
+            let history = self.trades.entry(leg.trader).or_default();
+            history.push_back(TradeHistoryEntry {
+                trade_id: record.trade_id,
+                price: record.price,
+                quantity: record.quantity,
+                leg,
+                is_maker,
+                busted: false,
+                timestamp: record.timestamp,
+            });
+            while history.len() > self.history_limit {
+                history.pop_front();
+            }
+        }
+    }
+
+    fn mark_busted(&mut self, record: &TradeRecord) {
+        for trader in [record.taker.trader, record.maker.trader] {
+            let Some(history) = self.trades.get_mut(&trader) else {
+                continue;
+            };
+            for entry in history.iter_mut().filter(|e| e.trade_id == record.trade_id) {
+                entry.busted = true;
+            }
+        }
+    }
+}
+
+impl BalanceReader for ReadModelProjection {
+    fn balances_of(&self, trader: TraderId) -> Vec<AssetBalance> {
+        self.balances.get(&trader).cloned().unwrap_or_default()
+    }
+}
+
+impl PrepQueryHandler for ReadModelProjection {
+    fn account_snapshot(
+        &self,
+        trader: TraderId,
+        mark_price: Option<Price>,
+        balances: &dyn BalanceReader,
+    ) -> AccountSnapshot {
+        let positions: Vec<Position> = self
+            .positions(trader)
+            .iter()
+            .map(|p| {
+                let mut position = p.clone();
+                if let Some(mark) = mark_price {
+                    position.update_unrealized_pnl(mark);
+                }
+                position
+            })
+            .collect();
+
+        let open_order_margin =
+            worst_case_margin(self.open_orders(trader), &positions, self.default_leverage);
+
+        AccountSnapshot {
+            trader,
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            balances: balances.balances_of(trader),
+            unrealized_pnl: positions.iter().map(|p| p.unrealized_pnl).sum(),
+            positions,
+            open_order_margin,
+        }
+    }
+
+    fn queue_position(
+        &self,
+        trader: TraderId,
+        order_id: OrderId,
+    ) -> Result<QueuePosition, ErrorCode> {
+        let order = self
+            .open_orders
+            .get(&order_id)
+            .filter(|o| o.trader == trader)
+            .ok_or(ErrorCode::OrderNotFound)?;
+
+        let level = self.level(order.side, order.price);
+        let ahead: Vec<_> = level.iter().take_while(|o| o.id != order_id).collect();
+
+        Ok(QueuePosition {
+            order_id,
+            side: order.side,
+            price: order.price,
+            orders_ahead: ahead.len(),
+            quantity_ahead: ahead.iter().map(|o| o.remaining_quantity).sum(),
+            remaining_quantity: order.remaining_quantity,
+            level_quantity: level.iter().map(|o| o.remaining_quantity).sum(),
+            sequence: self.sequence,
+        })
+    }
+
+    fn position_risk(&self, trader: TraderId, mark_price: Price) -> Vec<PositionRisk> {
+        position_risks(trader, mark_price, self.positions.values().flatten(), self.sequence)
+    }
+
+    fn setting_history(&self, trader: TraderId, limit: usize) -> Vec<AccountSettingRecord> {
+        self.settings
+            .get(&trader)
+            .map(|records| records.iter().rev().take(limit).copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// 从索引中移除一个订单，空集合一并移除
+fn remove_indexed<K: std::hash::Hash + Eq>(
+    index: &mut HashMap<K, BTreeSet<OrderId>>,
+    key: K,
+    order_id: OrderId,
+) {
+    if let Some(ids) = index.get_mut(&key) {
+        ids.remove(&order_id);
+        if ids.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor::{InMemoryOrderRepository, InMemoryPositionRepository};
+    use crate::domain::entity::{PositionSide, Side, TimeInForce};
+    use crate::domain::service::command::{Command, CommandResult, PrepCommandHandler};
+    use crate::domain::service::matching::MatchingService;
+
+    type Engine = MatchingService<InMemoryOrderRepository, InMemoryPositionRepository>;
+
+    fn limit(trader: TraderId, side: Side, price: Price, quantity: Quantity) -> Command {
+        let position_side = match side {
+            Side::Buy => PositionSide::Long,
+            Side::Sell => PositionSide::Short,
+        };
+        Command::LimitOrder {
+            trader,
+            side,
+            price,
+            quantity,
+            position_side,
+            reduce_only: false,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    fn sync(engine: &mut Engine, projection: &mut ReadModelProjection) {
+        projection.apply_all(&engine.drain_events());
+    }
+
+    #[test]
+    fn test_projection_matches_engine_queries() {
+        let mut engine =
+            Engine::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        let mut projection = ReadModelProjection::new();
+        engine.set_timestamp(1000);
+
+        engine.handle(limit(1, Side::Sell, 50000, 100));
+        let queued = match engine.handle(limit(3, Side::Sell, 50000, 40)) {
+            CommandResult::LimitOrder { order_id, .. } => order_id,
+            _ => panic!("Expected LimitOrder"),
+        };
+        engine.handle(limit(2, Side::Buy, 50000, 60));
+        let resting = match engine.handle(limit(2, Side::Buy, 49000, 10)) {
+            CommandResult::LimitOrder { order_id, .. } => order_id,
+            _ => panic!("Expected LimitOrder"),
+        };
+        sync(&mut engine, &mut projection);
+        projection.update_balance(2, AssetBalance::new("USDT", 7, 0));
+
+        for trader in [1, 2, 3] {
+            let expected = engine.account_snapshot(trader, Some(51000), &projection);
+            let actual = projection.account(trader, Some(51000));
+            assert_eq!(actual.sequence, expected.sequence);
+            assert_eq!(actual.open_order_margin, expected.open_order_margin);
+            assert_eq!(actual.unrealized_pnl, expected.unrealized_pnl);
+            assert_eq!(actual.balances, expected.balances);
+            assert_eq!(actual.positions.len(), expected.positions.len());
+            for (a, e) in actual.positions.iter().zip(&expected.positions) {
+                assert_eq!((a.quantity, a.entry_price), (e.quantity, e.entry_price));
+            }
+        }
+
+        // trader 1 剩余 40 排在 trader 3 之前
+        let queue = projection.queue_position(3, queued).unwrap();
+        assert_eq!(queue, engine.queue_position(3, queued).unwrap());
+        assert_eq!(queue.orders_ahead, 1);
+        assert_eq!(queue.quantity_ahead, 40);
+        assert_eq!(projection.queue_position(1, resting), Err(ErrorCode::OrderNotFound));
+
+        assert_eq!(projection.open_orders(2).len(), 1);
+        let history = projection.trade_history(2, 10);
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].quantity, history[0].is_maker), (60, false));
+        assert!(projection.trade_history(1, 10)[0].is_maker);
+    }
+
+    #[test]
+    fn test_cancel_and_bust_update_read_models() {
+        let mut engine =
+            Engine::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        let mut projection = ReadModelProjection::with_history_limit(1);
+
+        engine.handle(limit(1, Side::Sell, 100, 10));
+        engine.handle(limit(2, Side::Buy, 100, 10));
+        let order_id = match engine.handle(limit(1, Side::Sell, 200, 5)) {
+            CommandResult::LimitOrder { order_id, .. } => order_id,
+            _ => panic!("Expected LimitOrder"),
+        };
+        engine.handle(Command::CancelOrder { order_id });
+        engine.handle(Command::BustTrade {
+            trade_id: 1,
+            reason: "fat finger".to_string(),
+            operator: "ops".to_string(),
+        });
+        sync(&mut engine, &mut projection);
+
+        assert_eq!(projection.sequence(), engine.sequence());
+        assert!(projection.open_orders(1).is_empty());
+        // 撤单与全部成交后价位与账户索引同步清空
+        assert!(projection.bid_levels.is_empty() && projection.ask_levels.is_empty());
+        assert!(projection.trader_orders.is_empty());
+        assert!(projection.positions(1).is_empty());
+        assert!(projection.positions(2).is_empty());
+        let history = projection.trade_history(2, 10);
+        assert_eq!(history.len(), 1);
+        assert!(history[0].busted);
+    }
+
+    #[test]
+    fn test_recurring_plans_and_history() {
+        use crate::domain::entity::{RecurringOrderKind, RecurringSpec, RecurringStatus};
+
+        let mut engine =
+            Engine::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new());
+        let mut projection = ReadModelProjection::new();
+        let spec = RecurringSpec {
+            trader: 1,
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            quantity: 1,
+            kind: RecurringOrderKind::Limit { price: 100, time_in_force: TimeInForce::GTC },
+            interval: 1_000,
+            start_at: 1_000,
+            max_runs: Some(2),
+        };
+        engine.handle(Command::CreateRecurringPlan { spec });
+        for now in [1_000, 2_000] {
+            engine.set_timestamp(now);
+            engine.handle(Command::RunRecurringPlans);
+        }
+        sync(&mut engine, &mut projection);
+
+        let plans = projection.recurring_plans(1);
+        assert_eq!(plans.len(), 1);
+        assert_eq!((plans[0].status, plans[0].runs), (RecurringStatus::Completed, 2));
+        assert!(projection.recurring_plans(2).is_empty());
+        let history = projection.recurring_history(1, Some(plans[0].id), 10);
+        assert_eq!(history.iter().map(|e| e.run).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(projection.open_orders(1).len(), 2);
+        assert!(projection.recurring_history(1, Some(99), 10).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+//! 永续合约查询定义
+//!
+//! 账户快照：余额、仓位、挂单保证金、未实现盈亏在同一序列号下组装，
+//! 作为 `GET /api/account` 的领域结果，客户端无需再自行拼接
+//!
+//! 队列位置：估算挂单在其价位上的排队位置，仅对订单所有者开放，
+//! 供执行算法决定撤单或继续排队
+//!
+//! 仓位风险：`GET /api/prep/positionRisk` 的领域结果，强平价、维持保证金与保证金率
+//! 直接取自 [`Position`] 上强平判定使用的同一组计算，ADL 档位见 [`adl_ranks`]
+//!
+//! 设置变更历史：`GET /api/prep/account/settingHistory` 的领域结果，
+//! 杠杆、保证金模式与逐仓保证金每次生效的变更均附变更前后的值，用于核对用户申诉
+
+use crate::domain::ErrorCode;
+use crate::domain::entity::{
+    AccountSettingRecord, AssetBalance, Leverage, Margin, OrderId, Position, PositionId,
+    PositionSide, Price, Quantity, Side, Timestamp, TraderId,
+};
+use crate::domain::repository::BalanceReader;
+use crate::domain::service::adl::adl_ranks;
+
+/// 账户快照
+///
+/// `sequence` 为组装时撮合引擎已处理的命令序列号，
+/// 同一序列号下的快照内容一致
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    /// 交易者ID
+    pub trader: TraderId,
+    /// 命令序列号
+    pub sequence: u64,
+    /// 快照时间
+    pub timestamp: Timestamp,
+    /// 资产余额
+    pub balances: Vec<AssetBalance>,
+    /// 持仓（未实现盈亏已按标记价格刷新）
+    pub positions: Vec<Position>,
+    /// 挂单占用保证金
+    pub open_order_margin: Margin,
+    /// 未实现盈亏合计
+    pub unrealized_pnl: i64,
+}
+
+/// 挂单队列位置估算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePosition {
+    /// 订单ID
+    pub order_id: OrderId,
+    /// 方向
+    pub side: Side,
+    /// 价格
+    pub price: Price,
+    /// 排在前面的订单数
+    pub orders_ahead: usize,
+    /// 排在前面的数量
+    pub quantity_ahead: Quantity,
+    /// 本单剩余数量
+    pub remaining_quantity: Quantity,
+    /// 该价位总挂单量
+    pub level_quantity: Quantity,
+    /// 命令序列号
+    pub sequence: u64,
+}
+
+/// 仓位风险
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionRisk {
+    /// 仓位ID
+    pub position_id: PositionId,
+    /// 持仓方向
+    pub position_side: PositionSide,
+    /// 持仓数量
+    pub quantity: Quantity,
+    /// 开仓均价
+    pub entry_price: Price,
+    /// 标记价格
+    pub mark_price: Price,
+    /// 强平价格
+    pub liquidation_price: Price,
+    /// 杠杆倍数
+    pub leverage: Leverage,
+    /// 保证金
+    pub margin: Margin,
+    /// 按标记价格的未实现盈亏
+    pub unrealized_pnl: i64,
+    /// 维持保证金
+    pub maintenance_margin: Margin,
+    /// 保证金率（基点，达到 10000 即强平）
+    pub margin_ratio_bps: u64,
+    /// ADL 档位（1~5，5 最先被减仓）
+    pub adl_rank: u8,
+    /// 命令序列号
+    pub sequence: u64,
+}
+
+/// 组装账户的仓位风险
+///
+/// `all_positions` 为引擎全部仓位，ADL 档位需要与其他账户的仓位比较
+pub fn position_risks<'a>(
+    trader: TraderId,
+    mark_price: Price,
+    all_positions: impl IntoIterator<Item = &'a Position>,
+    sequence: u64,
+) -> Vec<PositionRisk> {
+    let all_positions: Vec<&Position> = all_positions.into_iter().collect();
+    let ranks = adl_ranks(all_positions.iter().copied(), mark_price);
+    let mut risks: Vec<PositionRisk> = all_positions
+        .into_iter()
+        .filter(|p| p.trader == trader && !p.is_empty())
+        .map(|p| PositionRisk {
+            position_id: p.id,
+            position_side: p.position_side,
+            quantity: p.quantity,
+            entry_price: p.entry_price,
+            mark_price,
+            liquidation_price: p.liquidation_price,
+            leverage: p.leverage,
+            margin: p.margin,
+            unrealized_pnl: p.unrealized_pnl_at(mark_price),
+            maintenance_margin: p.maintenance_margin(mark_price),
+            margin_ratio_bps: p.margin_ratio_bps(mark_price),
+            adl_rank: ranks.get(&p.id).copied().unwrap_or(0),
+            sequence,
+        })
+        .collect();
+    risks.sort_by_key(|r| r.position_id);
+    risks
+}
+
+/// 永续合约查询处理器
+///
+/// 查询只读，不推进命令序列号
+pub trait PrepQueryHandler {
+    /// 账户快照
+    ///
+    /// `mark_price` 为 None 时沿用仓位上次刷新的未实现盈亏
+    fn account_snapshot(
+        &self,
+        trader: TraderId,
+        mark_price: Option<Price>,
+        balances: &dyn BalanceReader,
+    ) -> AccountSnapshot;
+
+    /// 挂单队列位置
+    ///
+    /// 非订单所有者与不存在的订单一样返回 `OrderNotFound`，不泄露他人订单
+    fn queue_position(
+        &self,
+        trader: TraderId,
+        order_id: OrderId,
+    ) -> Result<QueuePosition, ErrorCode>;
+
+    /// 账户仓位风险（按仓位ID排序）
+    fn position_risk(&self, trader: TraderId, mark_price: Price) -> Vec<PositionRisk>;
+
+    /// 账户最近 `limit` 条设置变更（最新在前）
+    fn setting_history(&self, trader: TraderId, limit: usize) -> Vec<AccountSettingRecord>;
+}
@@ -0,0 +1,283 @@
+//! 最短挂单时间（防报价闪烁）
+//!
+//! 部分交易所要求挂单至少存续 `min_life` 毫秒，以抑制挂了就撤的闪烁报价。
+//! 每个交易对一个撮合引擎实例，按实例配置：挂单时间从命令出队、撮合结果显示
+//! 订单进入订单簿时开始计时，未满最短挂单时间到达的撤单按策略处理：
+//! - 延迟：撤单缓冲到满足最短挂单时间后再进入优先通道
+//! - 拒绝：直接拒绝，由客户端稍后重试
+//!
+//! 只约束按订单ID撤单（单笔与批量），全部撤单与风控命令不受影响。
+//! 计时只依赖调用方传入的时间与出队顺序，同一输入序列重放时结果一致
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use crate::domain::entity::{OrderId, OrderStatus, Timestamp};
+use crate::domain::service::command::{Command, CommandResult, ErrorCode};
+
+/// 未满最短挂单时间的撤单处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EarlyCancelPolicy {
+    /// 延迟到满足最短挂单时间
+    Delay,
+    /// 拒绝
+    Reject,
+}
+
+/// 最短挂单时间配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteLifeConfig {
+    /// 最短挂单时间（毫秒）
+    pub min_life: Timestamp,
+    /// 未满时的撤单处理
+    pub policy: EarlyCancelPolicy,
+}
+
+impl QuoteLifeConfig {
+    /// 未满时延迟撤单
+    pub fn delay(min_life: Timestamp) -> Self {
+        Self { min_life, policy: EarlyCancelPolicy::Delay }
+    }
+
+    /// 未满时拒绝撤单
+    pub fn reject(min_life: Timestamp) -> Self {
+        Self { min_life, policy: EarlyCancelPolicy::Reject }
+    }
+}
+
+/// 撤单检查结果
+#[derive(Debug)]
+pub enum CancelScreen {
+    /// 放行
+    Pass(Command),
+    /// 已缓冲，到期后由 [`QuoteLifeGuard::release_due`] 取出
+    Delayed,
+    /// 已拒绝
+    Rejected(ErrorCode),
+}
+
+/// 最短挂单时间检查
+#[derive(Debug)]
+pub struct QuoteLifeGuard {
+    /// 配置
+    config: QuoteLifeConfig,
+    /// 计时中的挂单：订单ID → 挂单时间
+    rested_at: HashMap<OrderId, Timestamp>,
+    /// 按挂单时间排列，用于清理已满最短挂单时间的记录
+    rest_order: VecDeque<(Timestamp, OrderId)>,
+    /// 已延迟撤单计数
+    arrival_seq: u64,
+    /// 延迟撤单，按 (释放时间, 到达序号) 排序
+    pending: BinaryHeap<Reverse<(Timestamp, u64)>>,
+    /// 延迟撤单内容，按到达序号索引
+    commands: HashMap<u64, Command>,
+}
+
+impl QuoteLifeGuard {
+    /// 创建检查
+    pub fn new(config: QuoteLifeConfig) -> Self {
+        Self {
+            config,
+            rested_at: HashMap::new(),
+            rest_order: VecDeque::new(),
+            arrival_seq: 0,
+            pending: BinaryHeap::new(),
+            commands: HashMap::new(),
+        }
+    }
+
+    /// 配置
+    pub fn config(&self) -> QuoteLifeConfig {
+        self.config
+    }
+
+    /// 记录出队命令的撮合结果（`now` 为出队时间）
+    ///
+    /// 进入订单簿的限价单开始计时，已撤销或已完结的订单停止计时
+    pub fn on_result(&mut self, result: &CommandResult, now: Timestamp) {
+        self.prune(now);
+        match result {
+            CommandResult::LimitOrder { order_id, status, .. } => match status {
+                OrderStatus::New | OrderStatus::PartiallyFilled => {
+                    if !self.rested_at.contains_key(order_id) {
+                        self.rested_at.insert(*order_id, now);
+                        self.rest_order.push_back((now, *order_id));
+                    }
+                }
+                _ => {
+                    self.rested_at.remove(order_id);
+                }
+            },
+            CommandResult::CancelOrder { order_id, .. } => {
+                self.rested_at.remove(order_id);
+            }
+            CommandResult::BatchCancelOrders { cancelled, .. } => {
+                for order_id in cancelled {
+                    self.rested_at.remove(order_id);
+                }
+            }
+            CommandResult::CancelAllOrders { order_ids, .. } => {
+                for order_id in order_ids {
+                    self.rested_at.remove(order_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 检查到达的命令（`now` 为到达时间）
+    ///
+    /// 批量撤单中任一订单未满最短挂单时间时整批处理：延迟到最晚满足的时间，或整批拒绝
+    pub fn screen(&mut self, command: Command, now: Timestamp) -> CancelScreen {
+        self.prune(now);
+        let eligible_at = match &command {
+            Command::CancelOrder { order_id } => self.eligible_at(*order_id),
+            Command::BatchCancelOrders { order_ids, .. } => {
+                order_ids.iter().filter_map(|id| self.eligible_at(*id)).max()
+            }
+            _ => None,
+        };
+        let Some(eligible_at) = eligible_at.filter(|at| *at > now) else {
+            return CancelScreen::Pass(command);
+        };
+        match self.config.policy {
+            EarlyCancelPolicy::Reject => CancelScreen::Rejected(ErrorCode::MinQuoteLifeNotElapsed),
+            EarlyCancelPolicy::Delay => {
+                let seq = self.arrival_seq;
+                self.arrival_seq += 1;
+                self.pending.push(Reverse((eligible_at, seq)));
+                self.commands.insert(seq, command);
+                CancelScreen::Delayed
+            }
+        }
+    }
+
+    /// 取出到期的延迟撤单（按释放时间，其次按到达顺序）
+    pub fn release_due(&mut self, now: Timestamp) -> Vec<Command> {
+        let mut released = Vec::new();
+        while let Some(Reverse((release_at, seq))) = self.pending.peek().copied() {
+            if release_at > now {
+                break;
+            }
+            self.pending.pop();
+            if let Some(command) = self.commands.remove(&seq) {
+                released.push(command);
+            }
+        }
+        released
+    }
+
+    /// 延迟中的撤单数
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 计时中的挂单数
+    pub fn resting_len(&self) -> usize {
+        self.rested_at.len()
+    }
+
+    /// 订单满足最短挂单时间的时刻（未计时为 None）
+    fn eligible_at(&self, order_id: OrderId) -> Option<Timestamp> {
+        self.rested_at.get(&order_id).map(|rested_at| rested_at + self.config.min_life)
+    }
+
+    /// 清理已满最短挂单时间的记录，计时记录只保留最近 `min_life` 内挂出的订单
+    fn prune(&mut self, now: Timestamp) {
+        while let Some(&(rested_at, order_id)) = self.rest_order.front() {
+            if rested_at + self.config.min_life > now {
+                break;
+            }
+            self.rest_order.pop_front();
+            if self.rested_at.get(&order_id) == Some(&rested_at) {
+                self.rested_at.remove(&order_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resting(order_id: OrderId) -> CommandResult {
+        CommandResult::LimitOrder {
+            order_id,
+            trades: Vec::new(),
+            remaining_quantity: 1,
+            status: OrderStatus::New,
+        }
+    }
+
+    #[test]
+    fn test_early_cancel_delayed_until_min_life() {
+        let mut guard = QuoteLifeGuard::new(QuoteLifeConfig::delay(50));
+        guard.on_result(&resting(1), 100);
+        guard.on_result(&resting(2), 120);
+
+        assert!(matches!(
+            guard.screen(Command::CancelOrder { order_id: 2 }, 130),
+            CancelScreen::Delayed
+        ));
+        assert!(matches!(
+            guard.screen(Command::CancelOrder { order_id: 1 }, 140),
+            CancelScreen::Delayed
+        ));
+        // 未计时的订单（如已满时间或不在簿中）直接放行
+        assert!(matches!(
+            guard.screen(Command::CancelOrder { order_id: 9 }, 140),
+            CancelScreen::Pass(_)
+        ));
+        assert_eq!(guard.pending_len(), 2);
+
+        assert!(guard.release_due(149).is_empty());
+        assert!(matches!(
+            guard.release_due(150).as_slice(),
+            [Command::CancelOrder { order_id: 1 }]
+        ));
+        assert!(matches!(
+            guard.release_due(170).as_slice(),
+            [Command::CancelOrder { order_id: 2 }]
+        ));
+        // 满足后计时记录被清理
+        assert!(matches!(
+            guard.screen(Command::CancelOrder { order_id: 2 }, 170),
+            CancelScreen::Pass(_)
+        ));
+        assert_eq!(guard.resting_len(), 0);
+    }
+
+    #[test]
+    fn test_early_cancel_rejected() {
+        let mut guard = QuoteLifeGuard::new(QuoteLifeConfig::reject(50));
+        guard.on_result(&resting(1), 100);
+        guard.on_result(&resting(2), 140);
+
+        let batch = Command::BatchCancelOrders { trader: 1, order_ids: vec![1, 2] };
+        assert!(matches!(
+            guard.screen(batch.clone(), 160),
+            CancelScreen::Rejected(ErrorCode::MinQuoteLifeNotElapsed)
+        ));
+        assert!(matches!(guard.screen(batch, 190), CancelScreen::Pass(_)));
+
+        // 已成交的订单停止计时
+        guard.on_result(&resting(3), 200);
+        guard.on_result(
+            &CommandResult::LimitOrder {
+                order_id: 3,
+                trades: Vec::new(),
+                remaining_quantity: 0,
+                status: OrderStatus::Filled,
+            },
+            210,
+        );
+        assert!(matches!(
+            guard.screen(Command::CancelOrder { order_id: 3 }, 210),
+            CancelScreen::Pass(_)
+        ));
+        assert!(matches!(
+            guard.screen(Command::CancelAllOrders { trader: 1, position_side: None }, 210),
+            CancelScreen::Pass(_)
+        ));
+    }
+}
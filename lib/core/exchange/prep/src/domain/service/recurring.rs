@@ -0,0 +1,375 @@
+//! 定投（Recurring Orders / DCA）
+//!
+//! 用户登记定投计划（交易对、方向、数量、间隔），计划随引擎状态保存：登记、暂停、
+//! 恢复、撤销都是命令（写入命令日志，随快照保存），每期由 `Command::RunRecurringPlans`
+//! 在引擎内提交市价或限价委托，回放命令日志得到相同的期次与委托。
+//!
+//! - 期次按 `start_at + k × interval` 对齐，恢复或积压时跳过已错过的期次，不补下
+//! - 分片运行时在最早一期到期后提交 `RunRecurringPlans`（见 [`RecurringOrders::next_due`]）
+//! - 计划只能由所属交易者操作，非所属交易者视为计划不存在
+//! - 交易对由网关按路由送到对应引擎，引擎不再校验
+//! - 执行历史保留最近 [`RECURRING_HISTORY_LIMIT`] 条
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::domain::entity::{
+    PositionSide, RecurringExecution, RecurringOrderKind, RecurringPlan, RecurringPlanId,
+    RecurringSpec, RecurringStatus, Timestamp, TraderId,
+};
+use crate::domain::service::command::{Command, ErrorCode};
+
+/// 执行历史保留条数
+pub const RECURRING_HISTORY_LIMIT: usize = 4096;
+
+impl RecurringSpec {
+    /// 每期提交的委托
+    pub fn command(&self) -> Command {
+        match self.kind {
+            RecurringOrderKind::Market => Command::MarketOrder {
+                trader: self.trader,
+                side: self.side,
+                quantity: self.quantity,
+                position_side: PositionSide::Both,
+                reduce_only: false,
+            },
+            RecurringOrderKind::Limit { price, time_in_force } => Command::LimitOrder {
+                trader: self.trader,
+                side: self.side,
+                price,
+                quantity: self.quantity,
+                position_side: PositionSide::Both,
+                reduce_only: false,
+                time_in_force,
+            },
+        }
+    }
+}
+
+/// 到期的一期（由引擎提交委托）
+#[derive(Debug, Clone)]
+pub struct RecurringRun {
+    /// 计划ID
+    pub plan_id: RecurringPlanId,
+    /// 期次（从 1 开始）
+    pub run: u32,
+    /// 计划时间
+    pub scheduled_at: Timestamp,
+    /// 本期委托
+    pub command: Command,
+}
+
+/// 定投状态（快照用）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecurringState {
+    /// 全部计划（按计划ID排序）
+    pub plans: Vec<RecurringPlan>,
+    /// 执行历史（按执行顺序）
+    pub history: Vec<RecurringExecution>,
+    /// 下一个计划ID
+    pub next_id: RecurringPlanId,
+}
+
+/// 定投计划管理（每个引擎一个）
+#[derive(Debug)]
+pub struct RecurringOrders {
+    plans: BTreeMap<RecurringPlanId, RecurringPlan>,
+    /// 执行中计划的下一期：(计划时间, 计划ID)
+    due: BTreeSet<(Timestamp, RecurringPlanId)>,
+    /// 执行历史（按执行顺序）
+    history: VecDeque<RecurringExecution>,
+    next_id: RecurringPlanId,
+}
+
+impl RecurringOrders {
+    /// 创建定投管理
+    pub fn new() -> Self {
+        Self { plans: BTreeMap::new(), due: BTreeSet::new(), history: VecDeque::new(), next_id: 1 }
+    }
+
+    /// 登记定投计划，返回计划ID
+    pub fn create(
+        &mut self,
+        spec: RecurringSpec,
+        now: Timestamp,
+    ) -> Result<RecurringPlanId, ErrorCode> {
+        if spec.quantity == 0 {
+            return Err(ErrorCode::InvalidQuantity);
+        }
+        if matches!(spec.kind, RecurringOrderKind::Limit { price: 0, .. }) {
+            return Err(ErrorCode::InvalidPrice);
+        }
+        if spec.interval == 0 || spec.max_runs == Some(0) {
+            return Err(ErrorCode::InvalidRecurringPlan);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut plan =
+            RecurringPlan { id, spec, status: RecurringStatus::Active, runs: 0, next_run: None };
+        let at = plan.spec.next_run_from(now);
+        Self::arm(&mut self.due, &mut plan, at);
+        self.plans.insert(id, plan);
+        Ok(id)
+    }
+
+    /// 暂停：撤回下一期
+    pub fn pause(&mut self, trader: TraderId, id: RecurringPlanId) -> Result<(), ErrorCode> {
+        let plan = Self::owned(&mut self.plans, trader, id)?;
+        if plan.status != RecurringStatus::Active {
+            return Err(ErrorCode::InvalidRecurringPlan);
+        }
+        Self::disarm(&mut self.due, plan);
+        plan.status = RecurringStatus::Paused;
+        Ok(())
+    }
+
+    /// 恢复：从不早于 `now` 的下一期继续，暂停期间的期次不补下，返回下一期时间
+    pub fn resume(
+        &mut self,
+        trader: TraderId,
+        id: RecurringPlanId,
+        now: Timestamp,
+    ) -> Result<Timestamp, ErrorCode> {
+        let plan = Self::owned(&mut self.plans, trader, id)?;
+        if plan.status != RecurringStatus::Paused {
+            return Err(ErrorCode::InvalidRecurringPlan);
+        }
+        plan.status = RecurringStatus::Active;
+        let at = plan.spec.next_run_from(now);
+        Self::arm(&mut self.due, plan, at);
+        Ok(at)
+    }
+
+    /// 撤销计划（已完成或已撤销的计划不能撤销）
+    pub fn cancel(&mut self, trader: TraderId, id: RecurringPlanId) -> Result<(), ErrorCode> {
+        let plan = Self::owned(&mut self.plans, trader, id)?;
+        if matches!(plan.status, RecurringStatus::Cancelled | RecurringStatus::Completed) {
+            return Err(ErrorCode::InvalidRecurringPlan);
+        }
+        Self::disarm(&mut self.due, plan);
+        plan.status = RecurringStatus::Cancelled;
+        Ok(())
+    }
+
+    /// 最早一期的计划时间（没有执行中的计划为 None）
+    pub fn next_due(&self) -> Option<Timestamp> {
+        self.due.first().map(|(at, _)| *at)
+    }
+
+    /// 取出到期的期次并登记下一期（按计划时间、计划ID排序）
+    ///
+    /// 执行结果由调用方通过 [`record`](Self::record) 写入历史
+    pub fn release_due(&mut self, now: Timestamp) -> Vec<RecurringRun> {
+        let mut runs = Vec::new();
+        while let Some(&(scheduled_at, plan_id)) = self.due.first() {
+            if scheduled_at > now {
+                break;
+            }
+            self.due.pop_first();
+            let Some(plan) = self.plans.get_mut(&plan_id) else {
+                continue;
+            };
+            plan.next_run = None;
+            plan.runs += 1;
+            runs.push(RecurringRun {
+                plan_id,
+                run: plan.runs,
+                scheduled_at,
+                command: plan.spec.command(),
+            });
+            if plan.spec.max_runs.is_some_and(|max| plan.runs >= max) {
+                plan.status = RecurringStatus::Completed;
+            } else {
+                // 积压时跳过已错过的期次，下一期晚于本次执行
+                let at = plan.spec.next_run_from(now + 1);
+                Self::arm(&mut self.due, plan, at);
+            }
+        }
+        runs
+    }
+
+    /// 记录一期的执行结果
+    pub fn record(&mut self, execution: RecurringExecution) {
+        self.history.push_back(execution);
+        if self.history.len() > RECURRING_HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+    }
+
+    /// 查询计划
+    pub fn get(&self, id: RecurringPlanId) -> Option<&RecurringPlan> {
+        self.plans.get(&id)
+    }
+
+    /// 交易者的全部计划（按计划ID排序）
+    pub fn plans_of(&self, trader: TraderId) -> impl Iterator<Item = &RecurringPlan> {
+        self.plans.values().filter(move |plan| plan.spec.trader == trader)
+    }
+
+    /// 计划的执行历史（按期次排序）
+    pub fn history(&self, id: RecurringPlanId) -> impl Iterator<Item = &RecurringExecution> {
+        self.history.iter().filter(move |execution| execution.plan_id == id)
+    }
+
+    /// 导出状态（快照用）
+    pub fn export(&self) -> RecurringState {
+        RecurringState {
+            plans: self.plans.values().cloned().collect(),
+            history: self.history.iter().copied().collect(),
+            next_id: self.next_id,
+        }
+    }
+
+    /// 从快照状态恢复，执行中计划的下一期重新登记
+    pub fn import(state: RecurringState) -> Self {
+        let mut due = BTreeSet::new();
+        let mut plans = BTreeMap::new();
+        for plan in state.plans {
+            if let (RecurringStatus::Active, Some(at)) = (plan.status, plan.next_run) {
+                due.insert((at, plan.id));
+            }
+            plans.insert(plan.id, plan);
+        }
+        Self { plans, due, history: state.history.into(), next_id: state.next_id.max(1) }
+    }
+
+    fn owned(
+        plans: &mut BTreeMap<RecurringPlanId, RecurringPlan>,
+        trader: TraderId,
+        id: RecurringPlanId,
+    ) -> Result<&mut RecurringPlan, ErrorCode> {
+        plans
+            .get_mut(&id)
+            .filter(|plan| plan.spec.trader == trader)
+            .ok_or(ErrorCode::RecurringPlanNotFound)
+    }
+
+    fn arm(
+        due: &mut BTreeSet<(Timestamp, RecurringPlanId)>,
+        plan: &mut RecurringPlan,
+        at: Timestamp,
+    ) {
+        due.insert((at, plan.id));
+        plan.next_run = Some(at);
+    }
+
+    fn disarm(due: &mut BTreeSet<(Timestamp, RecurringPlanId)>, plan: &mut RecurringPlan) {
+        if let Some(at) = plan.next_run.take() {
+            due.remove(&(at, plan.id));
+        }
+    }
+}
+
+impl Default for RecurringOrders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{Side, TimeInForce};
+
+    const ALICE: TraderId = 1;
+    const BOB: TraderId = 2;
+
+    fn spec(kind: RecurringOrderKind, max_runs: Option<u32>) -> RecurringSpec {
+        RecurringSpec {
+            trader: ALICE,
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            quantity: 10,
+            kind,
+            interval: 1_000,
+            start_at: 5_000,
+            max_runs,
+        }
+    }
+
+    fn executed(recurring: &mut RecurringOrders, now: Timestamp) -> Vec<Command> {
+        let runs = recurring.release_due(now);
+        for run in &runs {
+            recurring.record(RecurringExecution {
+                plan_id: run.plan_id,
+                run: run.run,
+                scheduled_at: run.scheduled_at,
+                executed_at: now,
+                order_id: Some(u64::from(run.run)),
+                error_code: None,
+            });
+        }
+        runs.into_iter().map(|run| run.command).collect()
+    }
+
+    #[test]
+    fn test_runs_each_interval_until_max_runs() {
+        let mut recurring = RecurringOrders::new();
+        let id = recurring.create(spec(RecurringOrderKind::Market, Some(3)), 1_000).unwrap();
+        assert_eq!(recurring.get(id).unwrap().next_run, Some(5_000));
+        assert_eq!(recurring.next_due(), Some(5_000));
+
+        assert!(executed(&mut recurring, 4_999).is_empty());
+        let commands = executed(&mut recurring, 5_000);
+        assert!(matches!(commands[..], [Command::MarketOrder { quantity: 10, .. }]));
+        assert_eq!(recurring.get(id).unwrap().next_run, Some(6_000));
+
+        // 积压 2.5 个间隔：只下一期，下一期对齐到 9000
+        assert_eq!(executed(&mut recurring, 8_500).len(), 1);
+        assert_eq!(recurring.get(id).unwrap().next_run, Some(9_000));
+
+        executed(&mut recurring, 9_000);
+        let plan = recurring.get(id).unwrap();
+        assert_eq!((plan.status, plan.runs, plan.next_run), (RecurringStatus::Completed, 3, None));
+        assert_eq!(recurring.next_due(), None);
+        let history: Vec<_> =
+            recurring.history(id).map(|e| (e.run, e.scheduled_at, e.executed_at)).collect();
+        assert_eq!(history, vec![(1, 5_000, 5_000), (2, 6_000, 8_500), (3, 9_000, 9_000)]);
+    }
+
+    #[test]
+    fn test_pause_resume_cancel() {
+        let mut recurring = RecurringOrders::new();
+        let limit = RecurringOrderKind::Limit { price: 100, time_in_force: TimeInForce::GTC };
+        let id = recurring.create(spec(limit, None), 5_000).unwrap();
+
+        assert_eq!(recurring.pause(BOB, id), Err(ErrorCode::RecurringPlanNotFound));
+        recurring.pause(ALICE, id).unwrap();
+        assert_eq!(recurring.next_due(), None);
+        assert!(executed(&mut recurring, 7_000).is_empty());
+        assert_eq!(recurring.pause(ALICE, id), Err(ErrorCode::InvalidRecurringPlan));
+
+        // 暂停期间的期次不补下
+        assert_eq!(recurring.resume(ALICE, id, 7_200), Ok(8_000));
+        let commands = executed(&mut recurring, 8_000);
+        assert!(matches!(commands[..], [Command::LimitOrder { price: 100, .. }]));
+
+        recurring.cancel(ALICE, id).unwrap();
+        assert_eq!(recurring.next_due(), None);
+        assert_eq!(recurring.get(id).unwrap().status, RecurringStatus::Cancelled);
+        assert_eq!(recurring.resume(ALICE, id, 9_000), Err(ErrorCode::InvalidRecurringPlan));
+        assert_eq!(recurring.plans_of(ALICE).count(), 1);
+
+        let zero = RecurringOrderKind::Limit { price: 0, time_in_force: TimeInForce::GTC };
+        assert_eq!(recurring.create(spec(zero, None), 0), Err(ErrorCode::InvalidPrice));
+    }
+
+    #[test]
+    fn test_export_import_keeps_schedule_and_history() {
+        let mut recurring = RecurringOrders::new();
+        let first = recurring.create(spec(RecurringOrderKind::Market, None), 0).unwrap();
+        let second = recurring.create(spec(RecurringOrderKind::Market, Some(5)), 0).unwrap();
+        executed(&mut recurring, 5_000);
+        recurring.pause(ALICE, second).unwrap();
+
+        let state = recurring.export();
+        let mut restored = RecurringOrders::import(state.clone());
+        assert_eq!(restored.export(), state);
+        assert_eq!(restored.next_due(), Some(6_000));
+        assert_eq!(restored.history(first).count(), 1);
+
+        let third = restored.create(spec(RecurringOrderKind::Market, None), 0).unwrap();
+        assert_eq!(third, 3);
+        let plans: Vec<_> = restored.release_due(6_000).iter().map(|run| run.plan_id).collect();
+        assert_eq!(plans, vec![third, first]);
+    }
+}
@@ -0,0 +1,357 @@
+//! 账户风控
+//!
+//! - 事前检查：同步执行，按账户风控档案校验名义价值与挂单数
+//! - 事后监控：成交后累计持仓与已实现盈亏，突破限额自动熔断
+//! - 账户状态：暂停禁止新委托，冻结禁止一切操作
+//! - 档案变更、熔断与解除、账户状态变更均写入只追加的审计日志
+
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::ErrorCode;
+use crate::domain::entity::{
+    AccountStatus, KillSwitchReason, Price, Quantity, RiskAuditAction, RiskAuditEntry, RiskProfile,
+    Timestamp, TradeLeg, TraderId,
+};
+
+/// 自动触发操作员
+pub const SYSTEM_OPERATOR: &str = "system";
+
+/// 账户风控管理器
+#[derive(Debug, Default)]
+pub struct RiskManager {
+    /// 账户风控档案（未配置的账户使用不设限额的普通档案）
+    profiles: HashMap<TraderId, RiskProfile>,
+    /// 已熔断账户
+    killed: HashSet<TraderId>,
+    /// 非正常状态的账户（未登记的账户为正常）
+    statuses: HashMap<TraderId, AccountStatus>,
+    /// 累计已实现盈亏
+    realized_pnl: HashMap<TraderId, i64>,
+    /// 审计日志
+    audit_log: Vec<RiskAuditEntry>,
+}
+
+impl RiskManager {
+    /// 创建风控管理器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 账户风控档案
+    pub fn profile(&self, trader: TraderId) -> RiskProfile {
+        self.profiles.get(&trader).copied().unwrap_or_default()
+    }
+
+    /// 设置账户风控档案
+    pub fn set_profile(
+        &mut self,
+        trader: TraderId,
+        profile: RiskProfile,
+        operator: String,
+        timestamp: Timestamp,
+    ) {
+        let from = self.profile(trader);
+        self.profiles.insert(trader, profile);
+        self.audit(
+            trader,
+            RiskAuditAction::ProfileChanged { from, to: profile },
+            operator,
+            String::new(),
+            timestamp,
+        );
+    }
+
+    /// 账户状态
+    pub fn status(&self, trader: TraderId) -> AccountStatus {
+        self.statuses.get(&trader).copied().unwrap_or_default()
+    }
+
+    /// 变更账户状态，返回原状态；状态未变返回 None
+    pub fn set_status(
+        &mut self,
+        trader: TraderId,
+        status: AccountStatus,
+        reason: String,
+        operator: String,
+        timestamp: Timestamp,
+    ) -> Option<AccountStatus> {
+        let from = self.status(trader);
+        if from == status {
+            return None;
+        }
+        if status == AccountStatus::Active {
+            self.statuses.remove(&trader);
+        } else {
+            self.statuses.insert(trader, status);
+        }
+        self.audit(
+            trader,
+            RiskAuditAction::StatusChanged { from, to: status },
+            operator,
+            reason,
+            timestamp,
+        );
+        Some(from)
+    }
+
+    /// 非正常状态的账户（按交易者ID排序，供快照）
+    pub fn statuses(&self) -> Vec<(TraderId, AccountStatus)> {
+        let mut statuses: Vec<_> =
+            self.statuses.iter().map(|(trader, status)| (*trader, *status)).collect();
+        statuses.sort_unstable_by_key(|(trader, _)| *trader);
+        statuses
+    }
+
+    /// 由快照恢复账户状态（不记审计）
+    pub fn restore_statuses(
+        &mut self,
+        statuses: impl IntoIterator<Item = (TraderId, AccountStatus)>,
+    ) {
+        self.statuses = statuses.into_iter().collect();
+    }
+
+    /// 显式配置过档案的账户（按交易者ID排序，供快照）
+    pub fn profiles(&self) -> Vec<(TraderId, RiskProfile)> {
+        let mut profiles: Vec<_> =
+            self.profiles.iter().map(|(trader, profile)| (*trader, *profile)).collect();
+        profiles.sort_unstable_by_key(|(trader, _)| *trader);
+        profiles
+    }
+
+    /// 已熔断账户（按交易者ID排序，供快照）
+    pub fn killed_accounts(&self) -> Vec<TraderId> {
+        let mut killed: Vec<_> = self.killed.iter().copied().collect();
+        killed.sort_unstable();
+        killed
+    }
+
+    /// 各账户累计已实现盈亏（按交易者ID排序，供快照）
+    pub fn realized_pnls(&self) -> Vec<(TraderId, i64)> {
+        let mut pnls: Vec<_> =
+            self.realized_pnl.iter().map(|(trader, pnl)| (*trader, *pnl)).collect();
+        pnls.sort_unstable_by_key(|(trader, _)| *trader);
+        pnls
+    }
+
+    /// 由快照恢复风控档案、熔断账户与累计已实现盈亏（不记审计）
+    pub fn restore_limits(
+        &mut self,
+        profiles: impl IntoIterator<Item = (TraderId, RiskProfile)>,
+        killed: impl IntoIterator<Item = TraderId>,
+        realized_pnl: impl IntoIterator<Item = (TraderId, i64)>,
+    ) {
+        self.profiles = profiles.into_iter().collect();
+        self.killed = killed.into_iter().collect();
+        self.realized_pnl = realized_pnl.into_iter().collect();
+    }
+
+    /// 账户是否已熔断
+    pub fn is_killed(&self, trader: TraderId) -> bool {
+        self.killed.contains(&trader)
+    }
+
+    /// 触发熔断，已熔断返回 false
+    pub fn trigger_kill_switch(
+        &mut self,
+        trader: TraderId,
+        reason: KillSwitchReason,
+        operator: String,
+        timestamp: Timestamp,
+    ) -> bool {
+        if !self.killed.insert(trader) {
+            return false;
+        }
+        let action = RiskAuditAction::KillSwitchTriggered(reason);
+        self.audit(trader, action, operator, String::new(), timestamp);
+        true
+    }
+
+    /// 解除熔断，未熔断返回 false
+    pub fn reset_kill_switch(
+        &mut self,
+        trader: TraderId,
+        operator: String,
+        timestamp: Timestamp,
+    ) -> bool {
+        if !self.killed.remove(&trader) {
+            return false;
+        }
+        self.audit(trader, RiskAuditAction::KillSwitchReset, operator, String::new(), timestamp);
+        true
+    }
+
+    /// 事前检查
+    pub fn pre_trade_check(
+        &self,
+        trader: TraderId,
+        price: Price,
+        quantity: Quantity,
+        open_orders: usize,
+    ) -> Result<(), ErrorCode> {
+        if !self.status(trader).can_trade() {
+            return Err(ErrorCode::AccountRestricted);
+        }
+        if self.is_killed(trader) {
+            return Err(ErrorCode::KillSwitchActive);
+        }
+
+        let profile = self.profile(trader);
+        if open_orders >= profile.max_open_orders {
+            return Err(ErrorCode::MaxOpenOrdersExceeded);
+        }
+        if !profile.bypass_notional_check
+            && price.saturating_mul(quantity) > profile.max_order_notional
+        {
+            return Err(ErrorCode::MaxPositionSizeExceeded);
+        }
+        Ok(())
+    }
+
+    /// 事后监控：记录一笔成交，返回需要触发的熔断原因
+    ///
+    /// `position_quantity` 为成交后该账户多空持仓合计
+    pub fn post_trade_check(
+        &mut self,
+        leg: &TradeLeg,
+        position_quantity: Quantity,
+    ) -> Option<KillSwitchReason> {
+        let realized_pnl = self.realized_pnl.entry(leg.trader).or_insert(0);
+        *realized_pnl += leg.realized_pnl;
+        let realized_pnl = *realized_pnl;
+
+        if self.is_killed(leg.trader) {
+            return None;
+        }
+        let limits = self.profile(leg.trader).post_trade?;
+
+        if position_quantity > limits.max_position_quantity {
+            return Some(KillSwitchReason::PositionLimitBreached {
+                quantity: position_quantity,
+                limit: limits.max_position_quantity,
+            });
+        }
+        if realized_pnl < 0 && realized_pnl.unsigned_abs() > limits.max_realized_loss {
+            return Some(KillSwitchReason::LossLimitBreached {
+                realized_pnl,
+                limit: limits.max_realized_loss,
+            });
+        }
+        None
+    }
+
+    /// 冲回一笔成交的已实现盈亏（成交撤销时使用）
+    pub fn reverse_fill(&mut self, leg: &TradeLeg) {
+        if let Some(realized_pnl) = self.realized_pnl.get_mut(&leg.trader) {
+            *realized_pnl -= leg.realized_pnl;
+        }
+    }
+
+    /// 审计日志
+    pub fn audit_log(&self) -> &[RiskAuditEntry] {
+        &self.audit_log
+    }
+
+    fn audit(
+        &mut self,
+        trader: TraderId,
+        action: RiskAuditAction,
+        operator: String,
+        reason: String,
+        timestamp: Timestamp,
+    ) {
+        self.audit_log.push(RiskAuditEntry { trader, action, operator, reason, timestamp });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{PositionSide, PostTradeLimits, Side};
+
+    fn leg(trader: TraderId, realized_pnl: i64) -> TradeLeg {
+        TradeLeg {
+            trader,
+            side: Side::Buy,
+            position_side: PositionSide::Long,
+            opened: realized_pnl == 0,
+            quantity: 1,
+            entry_price_before: 0,
+            realized_pnl,
+        }
+    }
+
+    #[test]
+    fn test_market_maker_bypasses_notional_check() {
+        let mut risk = RiskManager::new();
+        let huge = 10_000_000_000;
+        assert_eq!(risk.pre_trade_check(1, huge, 2, 1_000), Ok(()));
+        let limited = RiskProfile::standard().with_limits(huge, 200);
+        risk.set_profile(1, limited, "admin".to_string(), 5);
+        assert_eq!(risk.pre_trade_check(1, huge, 2, 0), Err(ErrorCode::MaxPositionSizeExceeded));
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 200), Err(ErrorCode::MaxOpenOrdersExceeded));
+
+        let limits = PostTradeLimits { max_position_quantity: 100, max_realized_loss: 1000 };
+        risk.set_profile(1, RiskProfile::market_maker(500, limits), "admin".to_string(), 10);
+        assert_eq!(risk.pre_trade_check(1, huge, 2, 0), Ok(()));
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 500), Err(ErrorCode::MaxOpenOrdersExceeded));
+
+        let entry = &risk.audit_log()[1];
+        assert_eq!(entry.operator, "admin");
+        assert!(matches!(entry.action, RiskAuditAction::ProfileChanged { .. }));
+    }
+
+    #[test]
+    fn test_post_trade_loss_limit() {
+        let mut risk = RiskManager::new();
+        let limits = PostTradeLimits { max_position_quantity: 100, max_realized_loss: 1000 };
+        risk.set_profile(1, RiskProfile::market_maker(500, limits), "admin".to_string(), 10);
+
+        assert_eq!(risk.post_trade_check(&leg(1, -600), 10), None);
+        let reason = risk.post_trade_check(&leg(1, -600), 10);
+        assert_eq!(
+            reason,
+            Some(KillSwitchReason::LossLimitBreached { realized_pnl: -1200, limit: 1000 })
+        );
+
+        assert!(risk.trigger_kill_switch(1, reason.unwrap(), SYSTEM_OPERATOR.to_string(), 20));
+        assert!(!risk.trigger_kill_switch(1, KillSwitchReason::Manual, "admin".to_string(), 21));
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 0), Err(ErrorCode::KillSwitchActive));
+
+        assert!(risk.reset_kill_switch(1, "admin".to_string(), 30));
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 0), Ok(()));
+        assert_eq!(risk.audit_log().len(), 3);
+
+        // 普通账户不做事后监控
+        assert_eq!(risk.post_trade_check(&leg(2, -1_000_000), 1_000_000), None);
+    }
+
+    #[test]
+    fn test_account_status() {
+        let mut risk = RiskManager::new();
+        assert_eq!(risk.status(1), AccountStatus::Active);
+
+        let from =
+            risk.set_status(1, AccountStatus::Suspended, "KYC review".into(), "ops".into(), 10);
+        assert_eq!(from, Some(AccountStatus::Active));
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 0), Err(ErrorCode::AccountRestricted));
+        assert_eq!(
+            risk.set_status(1, AccountStatus::Suspended, "again".into(), "ops".into(), 11),
+            None
+        );
+        assert_eq!(risk.statuses(), [(1, AccountStatus::Suspended)]);
+
+        risk.set_status(1, AccountStatus::Active, "cleared".into(), "ops".into(), 12);
+        assert_eq!(risk.pre_trade_check(1, 1, 1, 0), Ok(()));
+        assert!(risk.statuses().is_empty());
+
+        let reasons: Vec<&str> = risk.audit_log().iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["KYC review", "cleared"]);
+        assert!(matches!(
+            risk.audit_log()[1].action,
+            RiskAuditAction::StatusChanged {
+                from: AccountStatus::Suspended,
+                to: AccountStatus::Active
+            }
+        ));
+    }
+}
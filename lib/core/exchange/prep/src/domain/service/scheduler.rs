@@ -0,0 +1,234 @@
+//! 定时委托（Scheduled Orders）
+//!
+//! 带激活时间的委托先挂在时间轮上，到达激活时间后才进入命令队列（如参与开盘集合竞价、
+//! TWAP 首笔）。激活前可撤销。
+//!
+//! 时间轮按 `tick` 划分槽位，委托落在 `激活时间 / tick` 对应的槽；推进时只扫描
+//! 经过的槽，跨越整轮的委托留在槽中等待下一轮。同一时刻到期的委托按
+//! (激活时间, 定时ID) 排序释放，重放时顺序一致
+
+use std::collections::HashMap;
+
+use crate::domain::entity::Timestamp;
+use crate::domain::service::command::Command;
+
+/// 定时委托ID
+pub type ScheduleId = u64;
+
+/// 默认槽位粒度（毫秒）
+pub const DEFAULT_SCHEDULE_TICK: Timestamp = 10;
+/// 默认槽位数
+pub const DEFAULT_SCHEDULE_SLOTS: usize = 1024;
+
+/// 哈希时间轮
+#[derive(Debug)]
+pub struct TimerWheel {
+    /// 槽位粒度
+    tick: Timestamp,
+    /// 槽位：(到期时间, 定时ID)
+    slots: Vec<Vec<(Timestamp, ScheduleId)>>,
+    /// 当前 tick（之前的 tick 已全部扫描）
+    current_tick: u64,
+    /// 条目数
+    len: usize,
+}
+
+impl TimerWheel {
+    /// 创建时间轮（粒度与槽位数至少为 1）
+    pub fn new(tick: Timestamp, slots: usize) -> Self {
+        Self { tick: tick.max(1), slots: vec![Vec::new(); slots.max(1)], current_tick: 0, len: 0 }
+    }
+
+    /// 插入条目，返回所在槽位（已过期的条目落在当前槽，下一次推进时到期）
+    pub fn insert(&mut self, at: Timestamp, id: ScheduleId) -> usize {
+        let slot = self.slot_of((at / self.tick).max(self.current_tick));
+        self.slots[slot].push((at, id));
+        self.len += 1;
+        slot
+    }
+
+    /// 从槽位中移除条目
+    pub fn remove(&mut self, slot: usize, id: ScheduleId) -> bool {
+        let Some(entries) = self.slots.get_mut(slot) else {
+            return false;
+        };
+        let Some(index) = entries.iter().position(|(_, other)| *other == id) else {
+            return false;
+        };
+        entries.swap_remove(index);
+        self.len -= 1;
+        true
+    }
+
+    /// 推进到 `now`，返回到期条目（按到期时间、ID 排序）
+    ///
+    /// 跨越整轮时每个槽只扫描一次
+    pub fn advance(&mut self, now: Timestamp) -> Vec<(Timestamp, ScheduleId)> {
+        let target = now / self.tick;
+        let mut due = Vec::new();
+        if target < self.current_tick {
+            return due;
+        }
+        let ticks = (target - self.current_tick + 1).min(self.slots.len() as u64);
+        for offset in 0..ticks {
+            let slot = self.slot_of(self.current_tick + offset);
+            let entries = &mut self.slots[slot];
+            let mut index = 0;
+            while index < entries.len() {
+                if entries[index].0 <= now {
+                    due.push(entries.swap_remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+        }
+        // 目标 tick 内晚于 now 的条目留待下一次推进
+        self.current_tick = target;
+        self.len -= due.len();
+        due.sort_unstable();
+        due
+    }
+
+    /// 条目数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn slot_of(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+}
+
+/// 等待激活的定时委托
+#[derive(Debug, Clone)]
+pub struct ScheduledOrder {
+    /// 定时ID
+    pub id: ScheduleId,
+    /// 激活时间
+    pub activate_at: Timestamp,
+    /// 激活时提交的命令
+    pub command: Command,
+}
+
+/// 定时委托调度器
+#[derive(Debug)]
+pub struct OrderScheduler {
+    /// 时间轮
+    wheel: TimerWheel,
+    /// 等待激活的委托：定时ID -> (槽位, 委托)
+    pending: HashMap<ScheduleId, (usize, ScheduledOrder)>,
+    /// 下一个定时ID
+    next_id: ScheduleId,
+}
+
+impl OrderScheduler {
+    /// 创建调度器
+    pub fn new(tick: Timestamp, slots: usize) -> Self {
+        Self { wheel: TimerWheel::new(tick, slots), pending: HashMap::new(), next_id: 1 }
+    }
+
+    /// 登记定时委托，返回定时ID
+    pub fn schedule(&mut self, command: Command, activate_at: Timestamp) -> ScheduleId {
+        let id = self.next_id;
+        self.restore(ScheduledOrder { id, activate_at, command });
+        id
+    }
+
+    /// 恢复日志中的定时委托（保留原定时ID）
+    pub fn restore(&mut self, order: ScheduledOrder) {
+        self.next_id = self.next_id.max(order.id + 1);
+        if let Some((slot, previous)) = self.pending.remove(&order.id) {
+            self.wheel.remove(slot, previous.id);
+        }
+        let slot = self.wheel.insert(order.activate_at, order.id);
+        self.pending.insert(order.id, (slot, order));
+    }
+
+    /// 激活前撤销，返回原委托
+    pub fn cancel(&mut self, id: ScheduleId) -> Option<ScheduledOrder> {
+        let (slot, order) = self.pending.remove(&id)?;
+        self.wheel.remove(slot, id);
+        Some(order)
+    }
+
+    /// 取出到达激活时间的委托（按激活时间、定时ID 排序）
+    pub fn release_due(&mut self, now: Timestamp) -> Vec<ScheduledOrder> {
+        self.wheel
+            .advance(now)
+            .into_iter()
+            .filter_map(|(_, id)| self.pending.remove(&id).map(|(_, order)| order))
+            .collect()
+    }
+
+    /// 查询等待激活的委托
+    pub fn get(&self, id: ScheduleId) -> Option<&ScheduledOrder> {
+        self.pending.get(&id).map(|(_, order)| order)
+    }
+
+    /// 等待激活的委托数
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl Default for OrderScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCHEDULE_TICK, DEFAULT_SCHEDULE_SLOTS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cancel(order_id: u64) -> Command {
+        Command::CancelOrder { order_id }
+    }
+
+    fn ids(orders: &[ScheduledOrder]) -> Vec<ScheduleId> {
+        orders.iter().map(|o| o.id).collect()
+    }
+
+    #[test]
+    fn test_wheel_rounds_and_late_entries() {
+        let mut wheel = TimerWheel::new(10, 4);
+        wheel.insert(25, 1);
+        // 与 25 同槽但在下一轮
+        wheel.insert(65, 2);
+        wheel.insert(29, 3);
+
+        assert!(wheel.advance(24).is_empty());
+        assert_eq!(wheel.advance(27), vec![(25, 1)]);
+        assert_eq!(wheel.advance(40), vec![(29, 3)]);
+        // 插入已过期的时间
+        wheel.insert(5, 4);
+        assert_eq!(wheel.advance(40), vec![(5, 4)]);
+        // 跨越多轮只扫描一轮
+        assert_eq!(wheel.advance(1_000), vec![(65, 2)]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_scheduler_release_order_and_cancel() {
+        let mut scheduler = OrderScheduler::new(10, 8);
+        let first = scheduler.schedule(cancel(1), 1_000);
+        let second = scheduler.schedule(cancel(2), 995);
+        let third = scheduler.schedule(cancel(3), 1_000);
+        let cancelled = scheduler.schedule(cancel(4), 990);
+
+        assert!(scheduler.cancel(cancelled).is_some());
+        assert!(scheduler.cancel(cancelled).is_none());
+        assert!(scheduler.release_due(994).is_empty());
+        assert_eq!(ids(&scheduler.release_due(1_000)), vec![second, first, third]);
+        assert_eq!(scheduler.pending_len(), 0);
+
+        scheduler.restore(ScheduledOrder { id: 10, activate_at: 2_000, command: cancel(5) });
+        assert_eq!(scheduler.schedule(cancel(6), 3_000), 11);
+        assert_eq!(ids(&scheduler.release_due(5_000)), vec![10, 11]);
+    }
+}
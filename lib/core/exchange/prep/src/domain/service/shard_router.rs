@@ -0,0 +1,188 @@
+//! 引擎分片路由
+//!
+//! 定序器按交易对把命令转发到所在的引擎分片。滚动升级替换分片时：
+//! - `begin_drain` 暂停该分片全部交易对的转发，期间到达的命令按到达顺序暂存
+//! - 新分片确认就绪后 `cutover` 一次性把这些交易对改指向新分片，并取出暂存命令按序转发；
+//!   交接失败时 `abort_drain` 恢复原分片，暂存命令转回原分片
+//!
+//! 只有被替换分片的交易对会暂停，其他交易对的转发不受影响。暂存超过上限时拒绝新命令，
+//! 由调用方向客户端返回可重试的错误
+//!
+//! 这里只是路由表与暂存状态，不持有分片的命令通道：本仓库没有定序器，转发与
+//! 交接（见 [`handoff`](crate::adaptor::outbound::handoff)）由调用方按 [`Dispatch`] 执行
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// 分片标识
+pub type ShardId = u32;
+
+/// 默认暂存上限
+pub const DEFAULT_HOLD_LIMIT: usize = 10_000;
+
+/// 路由结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dispatch<C> {
+    /// 转发到分片
+    Forward(ShardId, C),
+    /// 分片交接中，已暂存
+    Held,
+    /// 暂存已满，拒绝
+    Rejected(C),
+    /// 交易对未分配分片
+    Unrouted(C),
+}
+
+/// 交易对到分片的路由表
+#[derive(Debug)]
+pub struct ShardRouter<C> {
+    routes: HashMap<String, ShardId>,
+    /// 排空中的分片 -> 暂存命令（交易对, 命令）
+    draining: HashMap<ShardId, VecDeque<(String, C)>>,
+    hold_limit: usize,
+    /// 路由表版本，每次切换递增
+    generation: u64,
+}
+
+impl<C> Default for ShardRouter<C> {
+    fn default() -> Self {
+        Self {
+            routes: HashMap::new(),
+            draining: HashMap::new(),
+            hold_limit: DEFAULT_HOLD_LIMIT,
+            generation: 0,
+        }
+    }
+}
+
+impl<C> ShardRouter<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 每个排空中分片的暂存上限
+    pub fn with_hold_limit(mut self, hold_limit: usize) -> Self {
+        self.hold_limit = hold_limit;
+        self
+    }
+
+    /// 分配交易对
+    pub fn assign(&mut self, symbol: impl Into<String>, shard: ShardId) {
+        self.routes.insert(symbol.into(), shard);
+        self.generation += 1;
+    }
+
+    /// 交易对所在分片
+    pub fn route(&self, symbol: &str) -> Option<ShardId> {
+        self.routes.get(symbol).copied()
+    }
+
+    /// 分片上的交易对（有序）
+    pub fn symbols_of(&self, shard: ShardId) -> BTreeSet<&str> {
+        self.routes
+            .iter()
+            .filter(|(_, assigned)| **assigned == shard)
+            .map(|(symbol, _)| symbol.as_str())
+            .collect()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn is_draining(&self, shard: ShardId) -> bool {
+        self.draining.contains_key(&shard)
+    }
+
+    /// 排空中分片的暂存命令数
+    pub fn held(&self, shard: ShardId) -> usize {
+        self.draining.get(&shard).map_or(0, VecDeque::len)
+    }
+
+    /// 路由一条命令
+    pub fn dispatch(&mut self, symbol: &str, command: C) -> Dispatch<C> {
+        let Some(shard) = self.route(symbol) else {
+            return Dispatch::Unrouted(command);
+        };
+        let Some(held) = self.draining.get_mut(&shard) else {
+            return Dispatch::Forward(shard, command);
+        };
+        if held.len() >= self.hold_limit {
+            return Dispatch::Rejected(command);
+        }
+        held.push_back((symbol.to_string(), command));
+        Dispatch::Held
+    }
+
+    /// 暂停分片的转发，已在排空中返回 false
+    pub fn begin_drain(&mut self, shard: ShardId) -> bool {
+        if self.draining.contains_key(&shard) {
+            return false;
+        }
+        self.draining.insert(shard, VecDeque::new());
+        true
+    }
+
+    /// 把排空中分片的全部交易对切到新分片，返回暂存命令（按到达顺序，转发给新分片）；
+    /// 分片不在排空中返回 None
+    pub fn cutover(&mut self, from: ShardId, to: ShardId) -> Option<Vec<(String, C)>> {
+        let held = self.draining.remove(&from)?;
+        for shard in self.routes.values_mut() {
+            if *shard == from {
+                *shard = to;
+            }
+        }
+        self.generation += 1;
+        Some(held.into())
+    }
+
+    /// 交接失败：恢复原分片的转发，返回暂存命令（按到达顺序，转发回原分片）
+    pub fn abort_drain(&mut self, shard: ShardId) -> Vec<(String, C)> {
+        self.draining.remove(&shard).map(Vec::from).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_holds_only_replaced_shard() {
+        let mut router = ShardRouter::new().with_hold_limit(2);
+        router.assign("BTCUSDT", 1);
+        router.assign("ETHUSDT", 1);
+        router.assign("SOLUSDT", 2);
+        assert_eq!(router.dispatch("BTCUSDT", "a"), Dispatch::Forward(1, "a"));
+        assert_eq!(router.dispatch("XRPUSDT", "x"), Dispatch::Unrouted("x"));
+
+        assert!(router.begin_drain(1));
+        assert!(!router.begin_drain(1));
+        assert_eq!(router.dispatch("BTCUSDT", "b"), Dispatch::Held);
+        // 其他分片照常转发
+        assert_eq!(router.dispatch("SOLUSDT", "s"), Dispatch::Forward(2, "s"));
+        assert_eq!(router.dispatch("ETHUSDT", "c"), Dispatch::Held);
+        assert_eq!(router.dispatch("ETHUSDT", "d"), Dispatch::Rejected("d"));
+        assert_eq!(router.held(1), 2);
+
+        let generation = router.generation();
+        let held = router.cutover(1, 3).unwrap();
+        assert_eq!(held, vec![("BTCUSDT".to_string(), "b"), ("ETHUSDT".to_string(), "c")]);
+        assert_eq!(router.generation(), generation + 1);
+        assert_eq!(router.symbols_of(3), BTreeSet::from(["BTCUSDT", "ETHUSDT"]));
+        assert!(router.symbols_of(1).is_empty());
+        assert_eq!(router.dispatch("BTCUSDT", "e"), Dispatch::Forward(3, "e"));
+        assert_eq!(router.cutover(1, 3), None);
+    }
+
+    #[test]
+    fn test_abort_drain_restores_shard() {
+        let mut router = ShardRouter::new();
+        router.assign("BTCUSDT", 1);
+        router.begin_drain(1);
+        assert_eq!(router.dispatch("BTCUSDT", 7), Dispatch::Held);
+
+        assert_eq!(router.abort_drain(1), vec![("BTCUSDT".to_string(), 7)]);
+        assert!(!router.is_draining(1));
+        assert_eq!(router.dispatch("BTCUSDT", 8), Dispatch::Forward(1, 8));
+        assert!(router.abort_drain(1).is_empty());
+    }
+}
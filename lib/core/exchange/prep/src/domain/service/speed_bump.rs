@@ -0,0 +1,178 @@
+//! 速度缓冲（Speed Bump）
+//!
+//! 参考 IEX / LMAX 的延迟均衡：主动成交的委托（市价单、可立即成交的限价单）
+//! 在进入撮合前统一延迟 `min_delay + [0, jitter]`，被动挂单与撤单不受影响。
+//!
+//! 每个交易对一个撮合引擎实例，速度缓冲按实例开启。随机延迟由种子与
+//! 到达序号确定，同一输入序列重放时延迟与释放顺序完全一致
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::domain::entity::Timestamp;
+use crate::domain::service::command::Command;
+
+/// 速度缓冲配置（时间单位与 `Timestamp` 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedBumpConfig {
+    /// 最小延迟
+    pub min_delay: Timestamp,
+    /// 随机抖动上限
+    pub jitter: Timestamp,
+    /// 随机种子（重放时必须一致）
+    pub seed: u64,
+}
+
+impl SpeedBumpConfig {
+    /// 固定延迟
+    pub fn fixed(delay: Timestamp) -> Self {
+        Self { min_delay: delay, jitter: 0, seed: 0 }
+    }
+
+    /// 随机延迟
+    pub fn randomized(min_delay: Timestamp, jitter: Timestamp, seed: u64) -> Self {
+        Self { min_delay, jitter, seed }
+    }
+
+    /// 第 `arrival_seq` 个被延迟委托的延迟
+    pub fn delay_for(&self, arrival_seq: u64) -> Timestamp {
+        if self.jitter == 0 {
+            return self.min_delay;
+        }
+        self.min_delay + splitmix64(self.seed ^ arrival_seq) % (self.jitter + 1)
+    }
+}
+
+/// 速度缓冲阶段
+#[derive(Debug)]
+pub struct SpeedBump {
+    /// 配置
+    config: SpeedBumpConfig,
+    /// 已延迟委托计数（决定随机延迟）
+    arrival_seq: u64,
+    /// 待释放委托，按 (释放时间, 到达序号) 排序
+    pending: BinaryHeap<Reverse<(Timestamp, u64)>>,
+    /// 待释放委托内容，按到达序号索引
+    commands: HashMap<u64, Command>,
+}
+
+impl SpeedBump {
+    /// 创建速度缓冲
+    pub fn new(config: SpeedBumpConfig) -> Self {
+        Self { config, arrival_seq: 0, pending: BinaryHeap::new(), commands: HashMap::new() }
+    }
+
+    /// 配置
+    pub fn config(&self) -> SpeedBumpConfig {
+        self.config
+    }
+
+    /// 提交命令
+    ///
+    /// 非主动委托直接返回，交由调用方立即排序；主动委托进入缓冲，返回 None
+    pub fn submit(
+        &mut self,
+        command: Command,
+        now: Timestamp,
+        aggressive: bool,
+    ) -> Option<Command> {
+        if !aggressive {
+            return Some(command);
+        }
+        let seq = self.arrival_seq;
+        self.arrival_seq += 1;
+        let release_at = now + self.config.delay_for(seq);
+        self.pending.push(Reverse((release_at, seq)));
+        self.commands.insert(seq, command);
+        None
+    }
+
+    /// 取出到期命令（按释放时间，其次按到达顺序）
+    pub fn release_due(&mut self, now: Timestamp) -> Vec<Command> {
+        let mut released = Vec::new();
+        while let Some(Reverse((release_at, seq))) = self.pending.peek().copied() {
+            if release_at > now {
+                break;
+            }
+            self.pending.pop();
+            if let Some(command) = self.commands.remove(&seq) {
+                released.push(command);
+            }
+        }
+        released
+    }
+
+    /// 下一个释放时间
+    pub fn next_release_at(&self) -> Option<Timestamp> {
+        self.pending.peek().map(|Reverse((release_at, _))| *release_at)
+    }
+
+    /// 缓冲中的命令数
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// SplitMix64，用于可重放的延迟抖动
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cancel(order_id: u64) -> Command {
+        Command::CancelOrder { order_id }
+    }
+
+    fn order_ids(commands: Vec<Command>) -> Vec<u64> {
+        commands
+            .into_iter()
+            .map(|c| match c {
+                Command::CancelOrder { order_id } => order_id,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_passive_passes_through() {
+        let mut bump = SpeedBump::new(SpeedBumpConfig::fixed(3));
+        assert!(bump.submit(cancel(1), 100, false).is_some());
+        assert_eq!(bump.pending_len(), 0);
+    }
+
+    #[test]
+    fn test_fixed_delay_release() {
+        let mut bump = SpeedBump::new(SpeedBumpConfig::fixed(3));
+        assert!(bump.submit(cancel(1), 100, true).is_none());
+        assert!(bump.submit(cancel(2), 101, true).is_none());
+        assert_eq!(bump.next_release_at(), Some(103));
+
+        assert!(bump.release_due(102).is_empty());
+        assert_eq!(order_ids(bump.release_due(103)), vec![1]);
+        assert_eq!(order_ids(bump.release_due(200)), vec![2]);
+    }
+
+    #[test]
+    fn test_randomized_delay_is_replayable() {
+        let config = SpeedBumpConfig::randomized(3_000, 1_000, 42);
+        let run = || {
+            let mut bump = SpeedBump::new(config);
+            for id in 0..16 {
+                bump.submit(cancel(id), id * 10, true);
+            }
+            order_ids(bump.release_due(u64::MAX))
+        };
+
+        assert_eq!(run(), run());
+        for seq in 0..16 {
+            let delay = config.delay_for(seq);
+            assert!((3_000..=4_000).contains(&delay));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! 启动预热
+//!
+//! 发布后第一批委托往往落在冷缓存、未分配的哈希表和未训练的分支预测器上，
+//! 延迟明显高于稳态。引擎在对外就绪前依次完成：
+//! 1. 按预估容量预分配订单与仓位存储
+//! 2. 在一次性的临时订单簿上跑合成撮合（挂单、成交、IOC、撤单），走遍热路径
+//! 3. 最后才翻转就绪标志，网关据此开始放行流量
+//!
+//! 持久化日志不在本引擎内，其预缺页由存储适配器在调用 `warm_up` 前完成
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::domain::entity::{OrderStatus, PositionSide, Price, Side, TimeInForce, TraderId};
+use crate::domain::repository::{OrderRepository, PositionRepository};
+use crate::domain::service::command::{Command, CommandResult, PrepCommandHandler};
+use crate::domain::service::matching::MatchingService;
+
+/// 合成委托使用的交易者（只出现在临时订单簿中）
+const SYNTHETIC_MAKER: TraderId = TraderId::MAX - 1;
+const SYNTHETIC_TAKER: TraderId = TraderId::MAX;
+
+/// 合成委托价格
+const SYNTHETIC_PRICE: Price = 100;
+
+/// 就绪标志（可跨线程共享）
+#[derive(Debug, Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否已就绪
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// 标记就绪
+    pub fn mark_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// 预热配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmUpConfig {
+    /// 预分配的订单容量
+    pub order_capacity: usize,
+    /// 预分配的仓位容量
+    pub position_capacity: usize,
+    /// 合成撮合轮数
+    pub synthetic_rounds: usize,
+}
+
+impl Default for WarmUpConfig {
+    fn default() -> Self {
+        Self { order_capacity: 100_000, position_capacity: 10_000, synthetic_rounds: 10_000 }
+    }
+}
+
+/// 预热结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// 执行的合成命令数
+    pub synthetic_commands: usize,
+    /// 产生的合成成交数
+    pub synthetic_trades: usize,
+}
+
+/// 预热引擎并翻转就绪标志
+///
+/// `scratch` 为一次性的临时引擎，预热结束后丢弃，不影响正式引擎的状态与序列号
+pub fn warm_up<O, P, SO, SP>(
+    engine: &mut MatchingService<O, P>,
+    mut scratch: MatchingService<SO, SP>,
+    config: WarmUpConfig,
+    readiness: &Readiness,
+) -> WarmUpReport
+where
+    O: OrderRepository,
+    P: PositionRepository,
+    SO: OrderRepository,
+    SP: PositionRepository,
+{
+    engine.reserve(config.order_capacity, config.position_capacity);
+
+    let mut report = WarmUpReport::default();
+    for round in 0..config.synthetic_rounds {
+        scratch.set_timestamp(round as u64);
+        let mut resting = None;
+        for command in synthetic_round() {
+            if let CommandResult::LimitOrder { order_id, trades, status, .. } =
+                scratch.handle(command)
+            {
+                report.synthetic_trades += trades.len();
+                if status == OrderStatus::New {
+                    resting = Some(order_id);
+                }
+            }
+            report.synthetic_commands += 1;
+        }
+        // 本轮最后一张挂单（高一档的卖单）仍在簿上，撤掉避免临时订单簿无限增长
+        if let Some(order_id) = resting {
+            scratch.handle(Command::CancelOrder { order_id });
+            report.synthetic_commands += 1;
+        }
+        scratch.drain_execution_reports();
+    }
+
+    readiness.mark_ready();
+    report
+}
+
+/// 一轮合成委托：挂卖单、吃单成交、再挂一张不成交的卖单、IOC 扫空
+fn synthetic_round() -> [Command; 4] {
+    let limit = |trader, side, price, position_side, time_in_force| Command::LimitOrder {
+        trader,
+        side,
+        price,
+        quantity: 1,
+        position_side,
+        reduce_only: false,
+        time_in_force,
+    };
+    [
+        limit(SYNTHETIC_MAKER, Side::Sell, SYNTHETIC_PRICE, PositionSide::Short, TimeInForce::GTC),
+        limit(SYNTHETIC_TAKER, Side::Buy, SYNTHETIC_PRICE, PositionSide::Long, TimeInForce::GTC),
+        limit(
+            SYNTHETIC_MAKER,
+            Side::Sell,
+            SYNTHETIC_PRICE + 1,
+            PositionSide::Short,
+            TimeInForce::GTC,
+        ),
+        limit(
+            SYNTHETIC_TAKER,
+            Side::Buy,
+            SYNTHETIC_PRICE - 1,
+            PositionSide::Long,
+            TimeInForce::IOC,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor::inbound::{InMemoryOrderRepository, InMemoryPositionRepository};
+
+    fn engine() -> MatchingService<InMemoryOrderRepository, InMemoryPositionRepository> {
+        MatchingService::new(InMemoryOrderRepository::new(), InMemoryPositionRepository::new())
+    }
+
+    #[test]
+    fn test_warm_up_leaves_engine_untouched() {
+        let mut live = engine();
+        let readiness = Readiness::new();
+        assert!(!readiness.is_ready());
+
+        let config = WarmUpConfig { order_capacity: 64, position_capacity: 8, synthetic_rounds: 3 };
+        let report = warm_up(&mut live, engine(), config, &readiness);
+
+        assert!(readiness.is_ready());
+        assert_eq!(report, WarmUpReport { synthetic_commands: 15, synthetic_trades: 3 });
+        assert_eq!(live.sequence(), 0);
+        assert!(live.drain_execution_reports().is_empty());
+    }
+}
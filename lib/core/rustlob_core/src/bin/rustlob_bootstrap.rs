@@ -0,0 +1,63 @@
+//! 按清单初始化交易所
+//!
+//! ```text
+//! rustlob_bootstrap <manifest.yaml>
+//! ```
+//!
+//! 校验清单并通过正常接口完成开户，输出初始化后的交易对规则、账户与余额，
+//! 用于检查新环境的清单（示例见 `manifests/dev.yaml`）
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use base_types::{AccountId, AssetId};
+use rustlob_core::{Manifest, bootstrap};
+
+const USAGE: &str = "usage: rustlob_bootstrap <manifest.yaml>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.as_slice() {
+        [manifest] => run(Path::new(manifest)),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(path: &Path) -> Result<(), String> {
+    let manifest = Manifest::load(path).map_err(|e| e.to_string())?;
+    let exchange = bootstrap(&manifest).map_err(|e| e.to_string())?;
+
+    let config = manifest.exchange_config().map_err(|e| e.to_string())?;
+    println!("fee account:     {}", config.fee_account.0);
+    for pair in &config.markets {
+        let rules = config.rules.get(pair).copied().unwrap_or_default();
+        println!(
+            "market {:<9} tick {} lot {} min qty {} min notional {}",
+            pair.to_symbol_string(),
+            rules.tick_size,
+            rules.lot_size,
+            rules.min_quantity,
+            rules.min_notional
+        );
+    }
+    for account in &manifest.accounts {
+        let account_id = AccountId(account.id);
+        let role = if exchange.is_admin(account_id) { "admin" } else { "user" };
+        let balances: Vec<String> = [AssetId::Usdt, AssetId::Btc, AssetId::Eth]
+            .into_iter()
+            .filter_map(|asset| {
+                let balance = exchange.balance(account_id, asset)?;
+                Some(format!("{} {}", asset, balance.available))
+            })
+            .collect();
+        println!("account {:<8} {:<5} {}", account.id, role, balances.join(", "));
+    }
+    Ok(())
+}
@@ -0,0 +1,450 @@
+//! 从声明式清单初始化交易所
+//!
+//! 清单（YAML）声明交易对及下单规则、费率与分层费率、风控限额、初始账户（管理员、
+//! 费率档位、初始余额）。[`Manifest::exchange_config`] 生成配置，
+//! [`Manifest::provision`] 再通过交易所的正常接口（`deposit`、`set_fee_profile`、
+//! `grant_admin`）逐个开户，新环境与测试由同一份清单复现：
+//!
+//! ```yaml
+//! fee_account: 99
+//! fees:
+//!   maker: 0.001
+//!   taker: 0.002
+//!   tiers:
+//!     - { name: VIP1, min_volume_30d: 1000000, maker: 0.0008, taker: 0.0015 }
+//! risk:
+//!   max_order_notional: "1000000"
+//!   max_open_orders: 200
+//! markets:
+//!   - { symbol: BTCUSDT, tick_size: "0.01", lot_size: "0.0001", min_notional: "10" }
+//! accounts:
+//!   - { id: 1, admin: true }
+//!   - { id: 2, volume_30d: 2000000, deposits: { USDT: "100000", BTC: "2" } }
+//! ```
+//!
+//! 金额可写作字符串或数字，按 8 位小数精确解析，超出精度报错而不是截断。
+//! 清单整体校验通过后才开始开户，不会留下初始化到一半的交易所
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use base_types::account::clearing::FeeProfile;
+use base_types::fee::fee_types::{ProductFeeConfig, ProductTierConfig};
+use base_types::{AccountId, AssetId, Quantity, TradingPair};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::exchange::{Exchange, ExchangeConfig, ExchangeError};
+use crate::rules::{MarketRules, RiskLimits};
+
+/// 金额小数位数
+const AMOUNT_SCALE: u32 = 8;
+
+/// 初始化错误
+#[derive(Debug, PartialEq)]
+pub enum BootstrapError {
+    /// 清单无法解析
+    Parse(String),
+    /// 不支持的交易对
+    UnknownMarket(String),
+    /// 不支持的资产
+    UnknownAsset(String),
+    /// 清单内容不合法
+    Invalid(String),
+    /// 开户失败
+    Exchange(ExchangeError),
+}
+
+impl fmt::Display for BootstrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootstrapError::Parse(msg) => write!(f, "Invalid manifest: {}", msg),
+            BootstrapError::UnknownMarket(symbol) => write!(f, "Unknown market: {}", symbol),
+            BootstrapError::UnknownAsset(asset) => write!(f, "Unknown asset: {}", asset),
+            BootstrapError::Invalid(msg) => write!(f, "Invalid manifest: {}", msg),
+            BootstrapError::Exchange(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BootstrapError {}
+
+impl From<ExchangeError> for BootstrapError {
+    fn from(e: ExchangeError) -> Self {
+        BootstrapError::Exchange(e)
+    }
+}
+
+/// 精确解析的金额
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Amount(pub Quantity);
+
+impl Amount {
+    /// 解析十进制字符串（最多 8 位小数）
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let valid = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if int.is_empty() || frac.len() > AMOUNT_SCALE as usize || !valid(int) || !valid(frac) {
+            return None;
+        }
+        let mut raw = int.parse::<i64>().ok()?.checked_mul(10_i64.pow(AMOUNT_SCALE))?;
+        if !frac.is_empty() {
+            let scale = 10_i64.pow(AMOUNT_SCALE - frac.len() as u32);
+            raw = raw.checked_add(frac.parse::<i64>().ok()? * scale)?;
+        }
+        Some(Amount(Quantity::from_raw(if negative { -raw } else { raw })))
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl Visitor<'_> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a decimal amount with at most {} fractional digits", AMOUNT_SCALE)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Amount, E> {
+                Amount::parse(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Amount, E> {
+                self.visit_str(&v.to_string())
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Amount, E> {
+                self.visit_str(&v.to_string())
+            }
+
+            // f64 的 Display 输出最短的可还原表示，0.1 按 "0.1" 解析
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Amount, E> {
+                self.visit_str(&v.to_string())
+            }
+        }
+
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+/// 交易所初始化清单
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// 手续费收入账户
+    #[serde(default)]
+    pub fee_account: u64,
+    #[serde(default)]
+    pub fees: FeeManifest,
+    #[serde(default)]
+    pub risk: RiskManifest,
+    pub markets: Vec<MarketManifest>,
+    #[serde(default)]
+    pub accounts: Vec<AccountManifest>,
+}
+
+/// 费率（小数，0.001 即 0.1%）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeManifest {
+    pub maker: f64,
+    pub taker: f64,
+    /// 按 30 天交易量分层
+    pub tiers: Vec<FeeTierManifest>,
+}
+
+impl Default for FeeManifest {
+    /// 与 [`ExchangeConfig::new`] 的默认费率一致
+    fn default() -> Self {
+        Self { maker: 0.001, taker: 0.001, tiers: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeTierManifest {
+    pub name: String,
+    pub min_volume_30d: f64,
+    pub maker: f64,
+    pub taker: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskManifest {
+    pub max_order_notional: Option<Amount>,
+    pub max_open_orders: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketManifest {
+    /// 交易对（如 BTCUSDT、BTC/USDT）
+    pub symbol: String,
+    #[serde(default)]
+    pub tick_size: Amount,
+    #[serde(default)]
+    pub lot_size: Amount,
+    #[serde(default)]
+    pub min_quantity: Amount,
+    pub max_quantity: Option<Amount>,
+    #[serde(default)]
+    pub min_notional: Amount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountManifest {
+    pub id: u64,
+    #[serde(default)]
+    pub admin: bool,
+    pub volume_30d: Option<f64>,
+    pub vip_level: Option<u32>,
+    #[serde(default)]
+    pub market_maker: bool,
+    /// 资产 -> 初始余额
+    #[serde(default)]
+    pub deposits: BTreeMap<String, Amount>,
+}
+
+impl AccountManifest {
+    fn fee_profile(&self) -> FeeProfile {
+        FeeProfile {
+            volume_30d: self.volume_30d,
+            vip_level: self.vip_level,
+            market_maker: self.market_maker,
+        }
+    }
+}
+
+impl Manifest {
+    pub fn from_yaml(yaml: &str) -> Result<Self, BootstrapError> {
+        serde_yaml::from_str(yaml).map_err(|e| BootstrapError::Parse(e.to_string()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BootstrapError> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| BootstrapError::Parse(format!("{}: {}", path.display(), e)))?;
+        Self::from_yaml(&yaml)
+    }
+
+    /// 校验并生成交易所配置
+    pub fn exchange_config(&self) -> Result<ExchangeConfig, BootstrapError> {
+        if self.markets.is_empty() {
+            return Err(BootstrapError::Invalid("no markets".to_string()));
+        }
+        let mut markets: Vec<(TradingPair, MarketRules)> = Vec::with_capacity(self.markets.len());
+        for market in &self.markets {
+            let pair = TradingPair::from_symbol_str(&market.symbol)
+                .ok_or_else(|| BootstrapError::UnknownMarket(market.symbol.clone()))?;
+            if markets.iter().any(|(listed, _)| *listed == pair) {
+                return Err(BootstrapError::Invalid(format!("duplicate market {}", pair)));
+            }
+            let amounts =
+                [market.tick_size, market.lot_size, market.min_quantity, market.min_notional];
+            if amounts.iter().chain(&market.max_quantity).any(|amount| amount.0.is_negative()) {
+                return Err(BootstrapError::Invalid(format!("negative rule for {}", pair)));
+            }
+            let rules = MarketRules {
+                tick_size: market.tick_size.0,
+                lot_size: market.lot_size.0,
+                min_quantity: market.min_quantity.0,
+                max_quantity: market.max_quantity.map(|amount| amount.0),
+                min_notional: market.min_notional.0,
+            };
+            markets.push((pair, rules));
+        }
+
+        let mut fee_config = ProductFeeConfig::spot(self.fees.maker, self.fees.taker);
+        // 分层按交易量升序排列，选取时从高到低匹配
+        let mut tiers = self.fees.tiers.clone();
+        tiers.sort_by(|a, b| a.min_volume_30d.total_cmp(&b.min_volume_30d));
+        for (index, tier) in tiers.into_iter().enumerate() {
+            fee_config.add_tier(ProductTierConfig {
+                tier_id: index as u32 + 1,
+                tier_name: tier.name,
+                min_volume_30d: tier.min_volume_30d,
+                maker_fee: tier.maker,
+                taker_fee: tier.taker,
+                is_active: true,
+            });
+        }
+
+        let mut config = ExchangeConfig::new(markets.iter().map(|(pair, _)| *pair))
+            .with_fees(fee_config, AccountId(self.fee_account))
+            .with_risk_limits(RiskLimits {
+                max_order_notional: self.risk.max_order_notional.map(|amount| amount.0),
+                max_open_orders: self.risk.max_open_orders,
+            });
+        for (pair, rules) in markets {
+            config = config.with_market_rules(pair, rules);
+        }
+        Ok(config)
+    }
+
+    /// 按清单开户：登记管理员、设置费率档位、充值初始余额
+    pub fn provision(&self, exchange: &mut Exchange) -> Result<(), BootstrapError> {
+        let mut seen = BTreeSet::new();
+        let mut deposits = Vec::new();
+        for account in &self.accounts {
+            if !seen.insert(account.id) {
+                return Err(BootstrapError::Invalid(format!("duplicate account {}", account.id)));
+            }
+            for (asset, amount) in &account.deposits {
+                let asset_id = AssetId::from_str(&asset.to_uppercase())
+                    .ok_or_else(|| BootstrapError::UnknownAsset(asset.clone()))?;
+                if !amount.0.is_positive() {
+                    return Err(BootstrapError::Invalid(format!(
+                        "deposit of {} for account {} must be positive",
+                        asset, account.id
+                    )));
+                }
+                deposits.push((AccountId(account.id), asset_id, amount.0));
+            }
+        }
+
+        for account in &self.accounts {
+            let account_id = AccountId(account.id);
+            if account.admin {
+                exchange.grant_admin(account_id);
+            }
+            exchange.set_fee_profile(account_id, account.fee_profile());
+        }
+        for (account_id, asset_id, amount) in deposits {
+            exchange.deposit(account_id, asset_id, amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// 按清单创建并初始化交易所
+pub fn bootstrap(manifest: &Manifest) -> Result<Exchange, BootstrapError> {
+    let mut exchange = Exchange::new(manifest.exchange_config()?);
+    manifest.provision(&mut exchange)?;
+    Ok(exchange)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use base_types::{ManualClock, OrderSide};
+
+    use super::*;
+    use crate::exchange::OrderRequest;
+
+    const DEV_MANIFEST: &str = include_str!("../manifests/dev.yaml");
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    fn provisioned(manifest: &Manifest) -> Exchange {
+        let config = manifest.exchange_config().unwrap();
+        let mut exchange =
+            Exchange::new(config).with_clock(Arc::new(ManualClock::from_millis(1_000)));
+        manifest.provision(&mut exchange).unwrap();
+        exchange
+    }
+
+    #[test]
+    fn test_amount_parse() {
+        assert_eq!(Amount::parse("0.01"), Some(Amount(Quantity::from_raw(1_000_000))));
+        assert_eq!(Amount::parse("-2.5"), Some(Amount(Quantity::from_raw(-250_000_000))));
+        assert_eq!(Amount::parse("0.000000001"), None);
+        assert_eq!(Amount::parse("1e3"), None);
+        assert_eq!(Amount::parse(".5"), None);
+    }
+
+    #[test]
+    fn test_dev_manifest_provisions_through_exchange() {
+        let manifest = Manifest::from_yaml(DEV_MANIFEST).unwrap();
+        let mut exchange = provisioned(&manifest);
+        assert!(exchange.is_admin(AccountId(1)));
+        assert!(!exchange.is_admin(AccountId(1001)));
+        assert_eq!(exchange.balance(AccountId(1001), AssetId::Btc).unwrap().available, q(20.0));
+        assert_eq!(
+            exchange.balance(AccountId(1002), AssetId::Usdt).unwrap().available,
+            q(100_000.0)
+        );
+
+        // 交易对规则与风控限额在下单路径生效
+        let ask = |price, quantity| {
+            OrderRequest::limit(
+                AccountId(1001),
+                TradingPair::BtcUsdt,
+                OrderSide::Sell,
+                price,
+                quantity,
+            )
+        };
+        assert_eq!(
+            exchange.submit(ask(q(40_000.005), q(0.1))),
+            Err(ExchangeError::InvalidOrder("price is not a multiple of tick size"))
+        );
+        assert_eq!(
+            exchange.submit(ask(q(40_000.0), q(0.0001))),
+            Err(ExchangeError::InvalidOrder("notional below minimum"))
+        );
+        assert_eq!(
+            exchange.submit(ask(q(40_000.0), q(30.0))),
+            Err(ExchangeError::RiskLimit("order notional above limit"))
+        );
+
+        // 1002 的 30 天交易量落在 VIP1，吃单费率 0.15%
+        exchange.submit(ask(q(40_000.0), q(1.0))).unwrap();
+        let bid = OrderRequest::limit(
+            AccountId(1002),
+            TradingPair::BtcUsdt,
+            OrderSide::Buy,
+            q(40_000.0),
+            q(1.0),
+        );
+        let trade = &exchange.submit(bid).unwrap().trades[0];
+        assert_eq!(trade.buyer_fee, q(60.0));
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let market = "markets: [{ symbol: BTCUSDT }]\n";
+        assert!(matches!(
+            Manifest::from_yaml("markets: [{ symbol: BTCUSDT, tick: 1 }]"),
+            Err(BootstrapError::Parse(_))
+        ));
+        assert!(matches!(
+            Manifest::from_yaml("markets: [{ symbol: BTCUSDT, tick_size: 0.000000001 }]"),
+            Err(BootstrapError::Parse(_))
+        ));
+        let unknown = Manifest::from_yaml("markets: [{ symbol: DOGEUSDT }]").unwrap();
+        assert_eq!(
+            unknown.exchange_config().err(),
+            Some(BootstrapError::UnknownMarket("DOGEUSDT".to_string()))
+        );
+
+        // 账户校验失败时不做任何开户
+        let yaml = format!(
+            "{}accounts:\n  - {{ id: 7, admin: true, deposits: {{ USDT: 5 }} }}\n  - {{ id: 8, deposits: {{ DOGE: 1 }} }}\n",
+            market
+        );
+        let manifest = Manifest::from_yaml(&yaml).unwrap();
+        let mut exchange = Exchange::new(manifest.exchange_config().unwrap());
+        assert_eq!(
+            manifest.provision(&mut exchange),
+            Err(BootstrapError::UnknownAsset("DOGE".to_string()))
+        );
+        assert!(!exchange.is_admin(AccountId(7)));
+        assert!(exchange.balance(AccountId(7), AssetId::Usdt).is_none());
+
+        let duplicate = format!("{}accounts: [{{ id: 7 }}, {{ id: 7 }}]\n", market);
+        let manifest = Manifest::from_yaml(&duplicate).unwrap();
+        assert!(matches!(bootstrap(&manifest), Err(BootstrapError::Invalid(_))));
+    }
+}
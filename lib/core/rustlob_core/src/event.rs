@@ -0,0 +1,72 @@
+//! 交易所事件
+//!
+//! 每次下单、撤单、充值、账户状态变更后按发生顺序回调给 [`Exchange::on_event`](crate::Exchange::on_event)
+//! 注册的监听器
+
+use base_types::account::account::AccountStatus;
+use base_types::{AccountId, AssetId, OrderSide, Price, Quantity, Timestamp, TradingPair};
+use conditional_order::ConditionalId;
+use match_core::OrderId;
+
+use crate::exchange::OrderStatus;
+
+/// 一笔成交（以 Maker 挂单价成交）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub trade_id: u64,
+    pub trading_pair: TradingPair,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub maker_order_id: OrderId,
+    pub taker_order_id: OrderId,
+    pub buyer: AccountId,
+    pub seller: AccountId,
+    /// 挂单方
+    pub maker_side: OrderSide,
+    /// 买方手续费（计价资产，负数为返佣）
+    pub buyer_fee: Quantity,
+    /// 卖方手续费（计价资产，负数为返佣）
+    pub seller_fee: Quantity,
+    pub timestamp: Timestamp,
+}
+
+/// 交易所事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExchangeEvent {
+    /// 订单通过校验并冻结资金
+    OrderAccepted {
+        order_id: OrderId,
+        account: AccountId,
+        trading_pair: TradingPair,
+        side: OrderSide,
+        price: Option<Price>,
+        quantity: Quantity,
+    },
+    /// 成交
+    Trade(Trade),
+    /// 订单结束（全部成交或剩余部分取消），剩余冻结已释放
+    OrderClosed {
+        order_id: OrderId,
+        account: AccountId,
+        trading_pair: TradingPair,
+        status: OrderStatus,
+    },
+    /// 余额变化后的快照
+    BalanceChanged { account: AccountId, asset: AssetId, available: Quantity, frozen: Quantity },
+    /// 条件单触发并按原请求下单（`order_id` 为 None 表示下单被拒，如余额不足、账户已冻结），
+    /// 下单产生的事件在此之前
+    ConditionalTriggered {
+        conditional_id: ConditionalId,
+        account: AccountId,
+        trading_pair: TradingPair,
+        order_id: Option<OrderId>,
+    },
+    /// 条件单撤销（主动撤销，或与之组成 OCO 的限价单成交、结束）
+    ConditionalCancelled {
+        conditional_id: ConditionalId,
+        account: AccountId,
+        trading_pair: TradingPair,
+    },
+    /// 账户状态变更（冻结时先撤单，撤单事件在此之前）
+    AccountStatusChanged { account: AccountId, from: AccountStatus, to: AccountStatus },
+}
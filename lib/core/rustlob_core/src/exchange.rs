@@ -0,0 +1,1501 @@
+//! 交易所门面
+//!
+//! 下单流程：校验（账户状态、交易对规则 [`MarketRules`]、风控限额 [`RiskLimits`]）→ 冻结资金 → 撮合（[`OrderBook`]）→ 逐笔清算（[`clear_spot_trade`]）
+//! 并记账 → 结束的订单释放剩余冻结 → 回调事件。资金规则：
+//! - 限价买单冻结 `价格 × 数量` 的计价资产，并按最高费率多冻结一份手续费余量；
+//!   以更优价格成交省下的部分同样留作余量，订单结束时一并解冻
+//! - 卖单冻结基础资产数量；市价买单没有可冻结的上限，直接拒绝
+//! - 手续费先从可用余额扣除，不足时动用买单的余量；仍不足（大量小额成交触发最低
+//!   手续费）的部分不再收取，手续费账户只记实收金额，各资产总量守恒
+//! - 挂单返佣（负费率）由手续费账户支付，该账户余额可为负
+//! - 费率按账户的费率档位（[`FeeProfile`]）在分层费率中选取，未设置的账户使用默认费率
+//!
+//! 启用持仓成本追踪（[`ExchangeConfig::with_cost_tracking`]）时，成交按成交价累加买方的
+//! 基础资产成本、按平均成本结转卖方成本并累计已实现盈亏，见 [`Exchange::cost_basis`]。
+//!
+//! 配置结算导出（[`SettlementExporter`]）时，每笔成交记账后按实收手续费导出结算分录。
+//!
+//! 小额资产兑换（[`DustSweeper`]）按指数价与流动性账户结算，整笔记账或整笔拒绝。
+//!
+//! 条件单（止损 / 止盈 / OCO，[`SpotConditionalOrders`]）登记时只做下单校验、不冻结资金；
+//! 每笔成交的成交价推进同一交易对的条件单，触发的订单走普通下单流程（此时冻结资金，
+//! 失败只记入 [`ExchangeEvent::ConditionalTriggered`]），其成交可继续触发。与挂单限价单
+//! 组成的 OCO 在限价单成交或结束时撤销条件单。
+//!
+//! 账户状态：暂停的账户不能下单，可以撤单与提现；冻结的账户一切操作被拒绝，
+//! 冻结时撤掉其全部挂单，避免继续成交
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+use base_types::account::account::{
+    AccountStatus, AccountStatusChange, AccountStatusCommand, AccountStatusError,
+    AccountStatusRegistry,
+};
+use base_types::account::balance::{Balance, BalanceCostBasis};
+use base_types::account::balance_change::BalanceChangeType;
+use base_types::account::clearing::{
+    ClearingContext, ClearingError, FeeLine, FeeProfile, TradeInput, clear_spot_trade,
+};
+use base_types::account::dust::{DustError, DustPreview, DustSweeper};
+use base_types::account::error::BalanceError;
+use base_types::account::settlement::Settlement;
+use base_types::account::settlement_export::{SettlementExporter, SettlementSink};
+use base_types::exchange::spot::convert::IndexPriceSource;
+use base_types::exchange::spot::spot_conditional::{SpotConditionalError, SpotConditionalOrders};
+use base_types::exchange::spot::spot_types::{
+    ConditionalType, ExecutionMethod, OrderSource, SpotOrder, TimeInForce as SpotTimeInForce,
+};
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{
+    AccountId, AssetId, OrderSide, Price, Quantity, SystemClock, Timestamp, TimestampProvider,
+    TradingPair,
+};
+use conditional_order::{ConditionalEvent, ConditionalId, Sibling};
+use match_core::{Fill, NewOrder, OrderBook, OrderId, Outcome, RejectReason, Side, TimeInForce};
+
+use crate::event::{ExchangeEvent, Trade};
+use crate::ledger::Ledger;
+use crate::rules::{MarketRules, RiskLimits};
+
+/// 交易所配置
+#[derive(Debug, Clone)]
+pub struct ExchangeConfig {
+    /// 开放交易的交易对
+    pub markets: Vec<TradingPair>,
+    pub fee_config: ProductFeeConfig,
+    /// 手续费收入账户
+    pub fee_account: AccountId,
+    /// 交易对下单规则（未配置的交易对不限制）
+    pub rules: HashMap<TradingPair, MarketRules>,
+    pub risk_limits: RiskLimits,
+    /// 追踪余额的持仓成本与已实现盈亏
+    pub cost_tracking: bool,
+}
+
+impl ExchangeConfig {
+    /// 默认费率 Maker 0.1% / Taker 0.1%，手续费记入账户 0
+    pub fn new(markets: impl IntoIterator<Item = TradingPair>) -> Self {
+        Self {
+            markets: markets.into_iter().collect(),
+            fee_config: ProductFeeConfig::spot(0.001, 0.001),
+            fee_account: AccountId(0),
+            rules: HashMap::new(),
+            risk_limits: RiskLimits::default(),
+            cost_tracking: false,
+        }
+    }
+
+    pub fn with_fees(mut self, fee_config: ProductFeeConfig, fee_account: AccountId) -> Self {
+        self.fee_config = fee_config;
+        self.fee_account = fee_account;
+        self
+    }
+
+    pub fn with_market_rules(mut self, trading_pair: TradingPair, rules: MarketRules) -> Self {
+        self.rules.insert(trading_pair, rules);
+        self
+    }
+
+    pub fn with_risk_limits(mut self, risk_limits: RiskLimits) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    pub fn with_cost_tracking(mut self) -> Self {
+        self.cost_tracking = true;
+        self
+    }
+}
+
+/// 下单请求
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderRequest {
+    pub account: AccountId,
+    pub trading_pair: TradingPair,
+    pub side: OrderSide,
+    /// 限价（None 为市价）
+    pub price: Option<Price>,
+    pub quantity: Quantity,
+    pub time_in_force: TimeInForce,
+}
+
+impl OrderRequest {
+    /// GTC 限价单
+    pub fn limit(
+        account: AccountId,
+        trading_pair: TradingPair,
+        side: OrderSide,
+        price: Price,
+        quantity: Quantity,
+    ) -> Self {
+        Self {
+            account,
+            trading_pair,
+            side,
+            price: Some(price),
+            quantity,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    /// 市价单（未成交部分取消）
+    pub fn market(
+        account: AccountId,
+        trading_pair: TradingPair,
+        side: OrderSide,
+        quantity: Quantity,
+    ) -> Self {
+        Self { account, trading_pair, side, price: None, quantity, time_in_force: TimeInForce::IOC }
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+}
+
+/// 订单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// 剩余部分在盘口挂单
+    Resting,
+    /// 全部成交
+    Filled,
+    /// 剩余部分已取消（撤单、IOC/FOK、市价单吃不完）
+    Cancelled,
+}
+
+/// 下单结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderResult {
+    pub order_id: OrderId,
+    pub status: OrderStatus,
+    pub filled: Quantity,
+    pub remaining: Quantity,
+    pub trades: Vec<Trade>,
+}
+
+/// 盘口深度，买盘从高到低、卖盘从低到高
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Depth {
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+}
+
+/// 交易所错误
+#[derive(Debug, PartialEq)]
+pub enum ExchangeError {
+    /// 交易对未开放
+    UnknownMarket(TradingPair),
+    /// 订单参数不合法
+    InvalidOrder(&'static str),
+    /// 超出风控限额
+    RiskLimit(&'static str),
+    /// 余额不足等资金错误
+    Balance(BalanceError),
+    /// 撮合拒绝（如 PostOnly 会立即成交）
+    Rejected(RejectReason),
+    /// 订单不存在或已结束
+    OrderNotFound(OrderId),
+    /// 清算失败
+    Clearing(ClearingError),
+    /// 账户状态不允许该操作
+    AccountRestricted { account: AccountId, status: AccountStatus },
+    /// 账户状态变更失败
+    AccountStatus(AccountStatusError),
+    /// 小额资产兑换失败
+    Dust(DustError),
+    /// 条件单登记失败
+    Conditional(SpotConditionalError),
+    /// 条件单不存在或已触发、撤销
+    ConditionalNotFound(ConditionalId),
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeError::UnknownMarket(pair) => write!(f, "Market {} is not listed", pair),
+            ExchangeError::InvalidOrder(reason) => write!(f, "Invalid order: {}", reason),
+            ExchangeError::RiskLimit(reason) => write!(f, "Risk limit exceeded: {}", reason),
+            ExchangeError::Balance(e) => write!(f, "{}", e),
+            ExchangeError::Rejected(reason) => write!(f, "Order rejected: {:?}", reason),
+            ExchangeError::OrderNotFound(order_id) => write!(f, "Order {} not found", order_id),
+            ExchangeError::Clearing(e) => write!(f, "{}", e),
+            ExchangeError::AccountRestricted { account, status } => {
+                write!(f, "Account {} is {}", account.0, status.as_str())
+            }
+            ExchangeError::AccountStatus(e) => write!(f, "{}", e),
+            ExchangeError::Dust(e) => write!(f, "{}", e),
+            ExchangeError::Conditional(e) => write!(f, "{}", e),
+            ExchangeError::ConditionalNotFound(id) => {
+                write!(f, "Conditional order {} not found", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+impl From<BalanceError> for ExchangeError {
+    fn from(e: BalanceError) -> Self {
+        ExchangeError::Balance(e)
+    }
+}
+
+impl From<ClearingError> for ExchangeError {
+    fn from(e: ClearingError) -> Self {
+        ExchangeError::Clearing(e)
+    }
+}
+
+impl From<DustError> for ExchangeError {
+    fn from(e: DustError) -> Self {
+        ExchangeError::Dust(e)
+    }
+}
+
+impl From<AccountStatusError> for ExchangeError {
+    fn from(e: AccountStatusError) -> Self {
+        ExchangeError::AccountStatus(e)
+    }
+}
+
+impl From<SpotConditionalError> for ExchangeError {
+    fn from(e: SpotConditionalError) -> Self {
+        ExchangeError::Conditional(e)
+    }
+}
+
+/// 未结束订单
+#[derive(Debug, Clone, Copy)]
+struct OpenOrder {
+    account: AccountId,
+    trading_pair: TradingPair,
+    side: OrderSide,
+    price: Option<Price>,
+    remaining: Quantity,
+    /// 仍冻结的资金（买单为计价资产，卖单为基础资产）
+    reserve: Quantity,
+}
+
+impl OpenOrder {
+    fn funding_asset(&self) -> AssetId {
+        match self.side {
+            OrderSide::Buy => self.trading_pair.quote_asset(),
+            OrderSide::Sell => self.trading_pair.base_asset(),
+        }
+    }
+
+    /// 覆盖剩余数量之外的冻结余量（可用于支付手续费）
+    fn surplus(&self) -> Quantity {
+        match (self.side, self.price) {
+            (OrderSide::Buy, Some(price)) => {
+                let committed = price.checked_mul(self.remaining).unwrap_or(self.reserve);
+                (self.reserve - committed).max(Quantity::default())
+            }
+            _ => Quantity::default(),
+        }
+    }
+}
+
+type Listener = Box<dyn FnMut(&ExchangeEvent) + Send>;
+
+/// 结算导出器（发送端由部署方选择）
+pub type ExchangeSettlementExporter = SettlementExporter<Box<dyn SettlementSink + Send>>;
+
+/// 单进程内存交易所
+pub struct Exchange {
+    config: ExchangeConfig,
+    books: HashMap<TradingPair, OrderBook>,
+    orders: HashMap<OrderId, OpenOrder>,
+    ledger: Ledger,
+    /// 账户状态与变更审计
+    accounts: AccountStatusRegistry,
+    /// 账户费率档位
+    fee_profiles: HashMap<AccountId, FeeProfile>,
+    /// 管理账户
+    admins: HashSet<AccountId>,
+    clock: Arc<dyn TimestampProvider>,
+    listeners: Vec<Listener>,
+    settlement_export: Option<ExchangeSettlementExporter>,
+    /// 待触发的条件单
+    conditionals: SpotConditionalOrders,
+    /// 挂单限价单 -> 与之组成 OCO 的条件单
+    oco_limits: HashMap<OrderId, ConditionalId>,
+    next_order_id: OrderId,
+    next_trade_id: u64,
+    next_convert_id: u64,
+    /// 本次操作待回调的事件
+    pending: Vec<ExchangeEvent>,
+    /// 本次操作改动过的余额
+    touched: Vec<(AccountId, AssetId)>,
+}
+
+impl Exchange {
+    pub fn new(config: ExchangeConfig) -> Self {
+        let books = config.markets.iter().map(|pair| (*pair, OrderBook::new())).collect();
+        let ledger =
+            if config.cost_tracking { Ledger::new().with_cost_tracking() } else { Ledger::new() };
+        Self {
+            config,
+            books,
+            orders: HashMap::new(),
+            ledger,
+            accounts: AccountStatusRegistry::new(),
+            fee_profiles: HashMap::new(),
+            admins: HashSet::new(),
+            clock: Arc::new(SystemClock),
+            listeners: Vec::new(),
+            settlement_export: None,
+            conditionals: SpotConditionalOrders::new(),
+            oco_limits: HashMap::new(),
+            next_order_id: 1,
+            next_trade_id: 1,
+            next_convert_id: 1,
+            pending: Vec::new(),
+            touched: Vec::new(),
+        }
+    }
+
+    /// 注入时间来源（测试与回测使用）
+    pub fn with_clock(mut self, clock: Arc<dyn TimestampProvider>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 成交记账后导出结算分录
+    pub fn with_settlement_export(mut self, exporter: ExchangeSettlementExporter) -> Self {
+        self.settlement_export = Some(exporter);
+        self
+    }
+
+    /// 结算导出器（导出与发送失败计数）
+    pub fn settlement_export(&self) -> Option<&ExchangeSettlementExporter> {
+        self.settlement_export.as_ref()
+    }
+
+    /// 注册事件回调，按注册顺序调用
+    pub fn on_event<F>(&mut self, listener: F)
+    where
+        F: FnMut(&ExchangeEvent) + Send + 'static,
+    {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// 充值到可用余额
+    pub fn deposit(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+    ) -> Result<(), ExchangeError> {
+        if !amount.is_positive() {
+            return Err(ExchangeError::InvalidOrder("deposit amount must be positive"));
+        }
+        let now = self.clock.now();
+        self.ledger.credit(account, asset, amount, now);
+        self.touch(account, asset);
+        self.flush();
+        Ok(())
+    }
+
+    /// 从可用余额提现（挂单冻结的部分不可提）
+    pub fn withdraw(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+    ) -> Result<(), ExchangeError> {
+        if !amount.is_positive() {
+            return Err(ExchangeError::InvalidOrder("withdraw amount must be positive"));
+        }
+        self.ensure(account, AccountStatus::can_withdraw)?;
+        let available = self.ledger.available(account, asset);
+        if available < amount {
+            return Err(ExchangeError::Balance(BalanceError::InsufficientAvailable {
+                required: amount.raw(),
+                available: available.raw(),
+            }));
+        }
+        let now = self.clock.now();
+        self.ledger.debit_available(account, asset, amount, now);
+        self.touch(account, asset);
+        self.flush();
+        Ok(())
+    }
+
+    pub fn balance(&self, account: AccountId, asset: AssetId) -> Option<&Balance> {
+        self.ledger.balance(account, asset)
+    }
+
+    /// 账户各资产的持仓成本与已实现盈亏（未启用成本追踪时为空）
+    pub fn cost_basis(&self, account: AccountId) -> Vec<BalanceCostBasis> {
+        self.ledger.balances_of(account).into_iter().filter_map(Balance::cost_basis_view).collect()
+    }
+
+    pub fn account_status(&self, account: AccountId) -> AccountStatus {
+        self.accounts.status(account)
+    }
+
+    /// 暂停、冻结或恢复账户，原因与操作员写入审计日志
+    pub fn apply_account_command(
+        &mut self,
+        account: AccountId,
+        command: AccountStatusCommand,
+        reason: impl Into<String>,
+        operator: impl Into<String>,
+    ) -> Result<AccountStatusChange, ExchangeError> {
+        let now = self.clock.now();
+        let change = self.accounts.apply(account, command, reason, operator, now)?;
+        if !change.to.can_cancel() {
+            let mut order_ids: Vec<OrderId> = self
+                .orders
+                .iter()
+                .filter(|(_, order)| order.account == account)
+                .map(|(order_id, _)| *order_id)
+                .collect();
+            order_ids.sort_unstable();
+            for order_id in order_ids {
+                self.cancel_open(order_id, now)?;
+            }
+        }
+        self.pending.push(ExchangeEvent::AccountStatusChanged {
+            account,
+            from: change.from,
+            to: change.to,
+        });
+        self.flush();
+        Ok(change)
+    }
+
+    /// 设置账户费率档位（30 天交易量、VIP 等级、做市商），之后的成交按此计费
+    pub fn set_fee_profile(&mut self, account: AccountId, profile: FeeProfile) {
+        self.fee_profiles.insert(account, profile);
+    }
+
+    pub fn fee_profile(&self, account: AccountId) -> FeeProfile {
+        self.fee_profiles.get(&account).copied().unwrap_or_default()
+    }
+
+    /// 登记管理账户（供嵌入方的管理接口鉴权）
+    pub fn grant_admin(&mut self, account: AccountId) {
+        self.admins.insert(account);
+    }
+
+    pub fn is_admin(&self, account: AccountId) -> bool {
+        self.admins.contains(&account)
+    }
+
+    /// 小额资产兑换预览
+    pub fn preview_dust(
+        &self,
+        account: AccountId,
+        sweeper: &DustSweeper,
+        prices: &dyn IndexPriceSource,
+    ) -> DustPreview {
+        sweeper.preview(account, self.ledger.balances_of(account), prices)
+    }
+
+    /// 兑换账户的全部小额资产，返回记账的结算
+    pub fn convert_dust(
+        &mut self,
+        account: AccountId,
+        sweeper: &DustSweeper,
+        prices: &dyn IndexPriceSource,
+    ) -> Result<Settlement, ExchangeError> {
+        self.ensure(account, AccountStatus::can_trade)?;
+        let now = self.clock.now();
+        let preview = self.preview_dust(account, sweeper, prices);
+        let settlement = sweeper.sweep(&preview, self.next_convert_id, now)?;
+        self.ledger.apply_settlement(&settlement, now)?;
+        self.next_convert_id += 1;
+        for entry in &settlement.entries {
+            self.touch(entry.account_id, entry.asset_id);
+        }
+        self.flush();
+        Ok(settlement)
+    }
+
+    /// 账户状态变更审计日志
+    pub fn account_audit_log(&self) -> &[AccountStatusChange] {
+        self.accounts.audit_log()
+    }
+
+    /// 下单：冻结资金后撮合，成交即时清算记账，成交价随后推进条件单
+    pub fn submit(&mut self, request: OrderRequest) -> Result<OrderResult, ExchangeError> {
+        let now = self.clock.now();
+        let result = self.place(request, now)?;
+        self.trigger_conditionals(&result.trades, now);
+        self.flush();
+        Ok(result)
+    }
+
+    /// 登记止损 / 止盈单：成交价触及 `stop_price` 时按 `request` 下单
+    ///
+    /// 限价为止损限价单，市价为止损市价单；登记时不冻结资金
+    pub fn submit_conditional(
+        &mut self,
+        request: OrderRequest,
+        conditional_type: ConditionalType,
+        stop_price: Price,
+    ) -> Result<ConditionalId, ExchangeError> {
+        self.check_order(&request)?;
+        Self::check_stop_price(stop_price)?;
+        let order = conditional_order(&request, conditional_type, stop_price, self.clock.now());
+        Ok(self.conditionals.submit(order)?)
+    }
+
+    /// 登记止损 + 止盈 OCO：一腿触发时另一腿撤销
+    pub fn submit_oco(
+        &mut self,
+        stop_loss: OrderRequest,
+        stop_price: Price,
+        take_profit: OrderRequest,
+        take_profit_price: Price,
+    ) -> Result<(ConditionalId, ConditionalId), ExchangeError> {
+        self.check_order(&stop_loss)?;
+        self.check_order(&take_profit)?;
+        Self::check_stop_price(stop_price)?;
+        Self::check_stop_price(take_profit_price)?;
+        let now = self.clock.now();
+        Ok(self.conditionals.submit_oco(
+            conditional_order(&stop_loss, ConditionalType::StopLoss, stop_price, now),
+            conditional_order(&take_profit, ConditionalType::TakeProfit, take_profit_price, now),
+        )?)
+    }
+
+    /// 限价单 + 止损单 OCO：限价单挂单后登记止损单，止损触发时撤销限价单，限价单成交或
+    /// 结束时撤销止损单；限价单立即全部成交或未挂单时不登记止损单
+    pub fn submit_limit_oco(
+        &mut self,
+        limit: OrderRequest,
+        stop_loss: OrderRequest,
+        stop_price: Price,
+    ) -> Result<(OrderResult, Option<ConditionalId>), ExchangeError> {
+        self.check_order(&stop_loss)?;
+        Self::check_stop_price(stop_price)?;
+        let now = self.clock.now();
+        let result = self.place(limit, now)?;
+        let mut conditional_id = None;
+        if result.status == OrderStatus::Resting {
+            let order = conditional_order(&stop_loss, ConditionalType::StopLoss, stop_price, now);
+            let id = self.conditionals.submit(order)?;
+            self.conditionals.link_limit_order(id, result.order_id)?;
+            self.oco_limits.insert(result.order_id, id);
+            conditional_id = Some(id);
+        }
+        self.trigger_conditionals(&result.trades, now);
+        self.flush();
+        Ok((result, conditional_id))
+    }
+
+    /// 撤销未触发的条件单
+    pub fn cancel_conditional(&mut self, id: ConditionalId) -> Result<(), ExchangeError> {
+        let order = self.conditionals.cancel(id).ok_or(ExchangeError::ConditionalNotFound(id))?;
+        self.oco_limits.retain(|_, linked| *linked != id);
+        self.pending.push(ExchangeEvent::ConditionalCancelled {
+            conditional_id: id,
+            account: order.trader_id.into(),
+            trading_pair: order.trading_pair,
+        });
+        self.flush();
+        Ok(())
+    }
+
+    /// 待触发的条件单数
+    pub fn open_conditionals(&self) -> usize {
+        self.conditionals.len()
+    }
+
+    /// 下单校验（账户状态、交易对、数量与价格、交易对规则、风控限额），返回需冻结的资金
+    fn check_order(&self, request: &OrderRequest) -> Result<Quantity, ExchangeError> {
+        self.ensure(request.account, AccountStatus::can_trade)?;
+        if !self.books.contains_key(&request.trading_pair) {
+            return Err(ExchangeError::UnknownMarket(request.trading_pair));
+        }
+        if !request.quantity.is_positive() {
+            return Err(ExchangeError::InvalidOrder("quantity must be positive"));
+        }
+        if request.price.is_some_and(|price| !price.is_positive()) {
+            return Err(ExchangeError::InvalidOrder("price must be positive"));
+        }
+        if let Some(rules) = self.config.rules.get(&request.trading_pair) {
+            rules.check(request.price, request.quantity).map_err(ExchangeError::InvalidOrder)?;
+        }
+        let open_orders = self.orders.values().filter(|o| o.account == request.account).count();
+        self.config
+            .risk_limits
+            .check(request.price, request.quantity, open_orders)
+            .map_err(ExchangeError::RiskLimit)?;
+        let reserve = match (request.side, request.price) {
+            (OrderSide::Buy, None) => {
+                return Err(ExchangeError::InvalidOrder("market buy orders are not supported"));
+            }
+            (OrderSide::Buy, Some(price)) => {
+                let notional = price
+                    .checked_mul(request.quantity)
+                    .ok_or(ExchangeError::InvalidOrder("notional overflows"))?;
+                notional + Quantity::from_f64(notional.to_f64() * self.fee_headroom())
+            }
+            (OrderSide::Sell, _) => request.quantity,
+        };
+        Ok(reserve)
+    }
+
+    fn check_stop_price(stop_price: Price) -> Result<(), ExchangeError> {
+        if stop_price.is_positive() {
+            Ok(())
+        } else {
+            Err(ExchangeError::InvalidOrder("stop price must be positive"))
+        }
+    }
+
+    /// 校验、冻结并撮合一笔订单（不推进条件单、不回调事件）
+    fn place(
+        &mut self,
+        request: OrderRequest,
+        now: Timestamp,
+    ) -> Result<OrderResult, ExchangeError> {
+        let reserve = self.check_order(&request)?;
+        let order_id = self.next_order_id;
+        let order = OpenOrder {
+            account: request.account,
+            trading_pair: request.trading_pair,
+            side: request.side,
+            price: request.price,
+            remaining: request.quantity,
+            reserve,
+        };
+        self.ledger.freeze(order.account, order.funding_asset(), reserve, now)?;
+
+        let book = self.books.get_mut(&request.trading_pair).expect("market checked above");
+        let execution = book.submit(NewOrder {
+            order_id,
+            trader: request.account.0,
+            side: to_side(request.side),
+            price: request.price.map(to_units),
+            quantity: to_units(request.quantity),
+            time_in_force: request.time_in_force,
+        });
+        if let Outcome::Rejected(reason) = execution.outcome {
+            self.ledger.unfreeze(order.account, order.funding_asset(), reserve, now)?;
+            return Err(ExchangeError::Rejected(reason));
+        }
+
+        self.next_order_id += 1;
+        self.orders.insert(order_id, order);
+        self.touch(order.account, order.funding_asset());
+        self.pending.push(ExchangeEvent::OrderAccepted {
+            order_id,
+            account: request.account,
+            trading_pair: request.trading_pair,
+            side: request.side,
+            price: request.price,
+            quantity: request.quantity,
+        });
+
+        let mut trades = Vec::with_capacity(execution.fills.len());
+        for fill in &execution.fills {
+            trades.push(self.settle(order_id, fill, now)?);
+        }
+        for fill in &execution.fills {
+            if self.orders.get(&fill.maker_order_id).is_some_and(|o| o.remaining.is_zero()) {
+                self.close(fill.maker_order_id, OrderStatus::Filled, now)?;
+            }
+        }
+
+        let status = match execution.outcome {
+            Outcome::Rested => OrderStatus::Resting,
+            Outcome::Filled => OrderStatus::Filled,
+            _ => OrderStatus::Cancelled,
+        };
+        if status != OrderStatus::Resting {
+            self.close(order_id, status, now)?;
+        }
+
+        let remaining = from_units(execution.remaining);
+        Ok(OrderResult {
+            order_id,
+            status,
+            filled: request.quantity - remaining,
+            remaining,
+            trades,
+        })
+    }
+
+    /// 撤单，释放剩余冻结
+    pub fn cancel(&mut self, order_id: OrderId) -> Result<(), ExchangeError> {
+        let order = self.orders.get(&order_id).ok_or(ExchangeError::OrderNotFound(order_id))?;
+        self.ensure(order.account, AccountStatus::can_cancel)?;
+        let now = self.clock.now();
+        self.cancel_open(order_id, now)?;
+        self.flush();
+        Ok(())
+    }
+
+    /// 前 `levels` 档深度
+    pub fn query_depth(
+        &self,
+        trading_pair: TradingPair,
+        levels: usize,
+    ) -> Result<Depth, ExchangeError> {
+        let book =
+            self.books.get(&trading_pair).ok_or(ExchangeError::UnknownMarket(trading_pair))?;
+        let convert = |side| {
+            book.depth(side, levels)
+                .into_iter()
+                .map(|(price, quantity)| (from_units(price), from_units(quantity)))
+                .collect()
+        };
+        Ok(Depth { bids: convert(Side::Buy), asks: convert(Side::Sell) })
+    }
+
+    /// 账户状态检查
+    fn ensure(
+        &self,
+        account: AccountId,
+        allowed: fn(AccountStatus) -> bool,
+    ) -> Result<(), ExchangeError> {
+        let status = self.accounts.status(account);
+        if allowed(status) {
+            Ok(())
+        } else {
+            Err(ExchangeError::AccountRestricted { account, status })
+        }
+    }
+
+    /// 从盘口撤下并结束订单
+    fn cancel_open(&mut self, order_id: OrderId, now: Timestamp) -> Result<(), ExchangeError> {
+        let order = self.orders.get(&order_id).ok_or(ExchangeError::OrderNotFound(order_id))?;
+        let book = self
+            .books
+            .get_mut(&order.trading_pair)
+            .ok_or(ExchangeError::UnknownMarket(order.trading_pair))?;
+        book.cancel(order_id).ok_or(ExchangeError::OrderNotFound(order_id))?;
+        self.close(order_id, OrderStatus::Cancelled, now)
+    }
+
+    /// 按最高默认费率预留的手续费比例
+    fn fee_headroom(&self) -> f64 {
+        let fees = &self.config.fee_config;
+        fees.default_maker_fee.max(fees.default_taker_fee).max(0.0)
+    }
+
+    /// 清算一笔成交并记账
+    fn settle(
+        &mut self,
+        taker_order_id: OrderId,
+        fill: &Fill,
+        now: Timestamp,
+    ) -> Result<Trade, ExchangeError> {
+        let taker = self.orders[&taker_order_id];
+        let (buy_order_id, sell_order_id) = match taker.side {
+            OrderSide::Buy => (taker_order_id, fill.maker_order_id),
+            OrderSide::Sell => (fill.maker_order_id, taker_order_id),
+        };
+        let buyer = self.orders[&buy_order_id].account;
+        let seller = self.orders[&sell_order_id].account;
+        let price = from_units(fill.price);
+        let quantity = from_units(fill.quantity);
+        let trade_id = self.next_trade_id;
+        self.next_trade_id += 1;
+
+        let record = clear_spot_trade(
+            &TradeInput {
+                trade_id,
+                trading_pair: taker.trading_pair,
+                price,
+                quantity,
+                buyer,
+                seller,
+                maker_side: taker.side.opposite(),
+                timestamp: now,
+            },
+            &ClearingContext {
+                fee_config: &self.config.fee_config,
+                fee_account: self.config.fee_account,
+                buyer_profile: self.fee_profile(buyer),
+                seller_profile: self.fee_profile(seller),
+            },
+        )?;
+
+        // 持仓成本在余额变动前结转：卖方按扣减前的平均成本计算已实现盈亏
+        if self.config.cost_tracking {
+            let base = record.base_asset;
+            self.ledger.record_acquisition(buyer, base, quantity, price, now);
+            self.ledger.record_disposal(seller, base, quantity, price, now);
+        }
+
+        // 成交分录：付出方从订单冻结中扣款，收入方记入可用；手续费分录按实收金额另行处理
+        for entry in &record.settlement.entries {
+            if entry.change_type != BalanceChangeType::Trade {
+                continue;
+            }
+            if entry.amount.is_negative() {
+                let amount = Quantity::default() - entry.amount;
+                let order_id =
+                    if entry.asset_id == record.quote_asset { buy_order_id } else { sell_order_id };
+                self.ledger.pay_frozen(entry.account_id, entry.asset_id, amount, now)?;
+                self.order_mut(order_id).reserve -= amount;
+            } else {
+                self.ledger.credit(entry.account_id, entry.asset_id, entry.amount, now);
+            }
+            self.touch(entry.account_id, entry.asset_id);
+        }
+        self.order_mut(buy_order_id).remaining -= quantity;
+        self.order_mut(sell_order_id).remaining -= quantity;
+        self.cancel_linked_conditional(buy_order_id);
+        self.cancel_linked_conditional(sell_order_id);
+
+        let buyer_fee =
+            self.charge_fee(&record.buyer_fee, record.quote_asset, buy_order_id, now)?;
+        let seller_fee =
+            self.charge_fee(&record.seller_fee, record.quote_asset, sell_order_id, now)?;
+
+        if let Some(exporter) = &mut self.settlement_export {
+            // 导出实际入账的分录：手续费按实收金额
+            let mut booked = Settlement::new(trade_id, now);
+            booked.entries = record
+                .settlement
+                .entries
+                .iter()
+                .filter(|entry| entry.change_type == BalanceChangeType::Trade)
+                .copied()
+                .collect();
+            for (payer, collected) in [(buyer, buyer_fee), (seller, seller_fee)] {
+                if !collected.is_zero() {
+                    let fee = BalanceChangeType::Fee;
+                    booked = booked
+                        .with_entry(payer, record.quote_asset, Quantity::default() - collected, fee)
+                        .with_entry(self.config.fee_account, record.quote_asset, collected, fee);
+                }
+            }
+            exporter.export(&booked).map_err(ClearingError::from)?;
+        }
+
+        let trade = Trade {
+            trade_id,
+            trading_pair: taker.trading_pair,
+            price,
+            quantity,
+            maker_order_id: fill.maker_order_id,
+            taker_order_id,
+            buyer,
+            seller,
+            maker_side: taker.side.opposite(),
+            buyer_fee,
+            seller_fee,
+            timestamp: now,
+        };
+        self.pending.push(ExchangeEvent::Trade(trade.clone()));
+        Ok(trade)
+    }
+
+    /// 收取一方手续费，返回实收金额（负数为返佣）
+    fn charge_fee(
+        &mut self,
+        fee: &FeeLine,
+        asset: AssetId,
+        order_id: OrderId,
+        now: Timestamp,
+    ) -> Result<Quantity, ExchangeError> {
+        let fee_account = self.config.fee_account;
+        let collected = if fee.amount.is_positive() {
+            let mut collected = self.ledger.debit_available(fee.account_id, asset, fee.amount, now);
+            let order = self.orders[&order_id];
+            let from_reserve = (fee.amount - collected).min(order.surplus());
+            if from_reserve.is_positive() && order.funding_asset() == asset {
+                self.ledger.pay_frozen(fee.account_id, asset, from_reserve, now)?;
+                self.order_mut(order_id).reserve -= from_reserve;
+                collected += from_reserve;
+            }
+            collected
+        } else {
+            self.ledger.credit(fee.account_id, asset, Quantity::default() - fee.amount, now);
+            fee.amount
+        };
+        if !collected.is_zero() {
+            self.ledger.credit(fee_account, asset, collected, now);
+            self.touch(fee_account, asset);
+        }
+        self.touch(fee.account_id, asset);
+        Ok(collected)
+    }
+
+    /// 结束订单：释放剩余冻结并记录事件
+    fn close(
+        &mut self,
+        order_id: OrderId,
+        status: OrderStatus,
+        now: Timestamp,
+    ) -> Result<(), ExchangeError> {
+        let order = self.orders.remove(&order_id).ok_or(ExchangeError::OrderNotFound(order_id))?;
+        self.cancel_linked_conditional(order_id);
+        if order.reserve.is_positive() {
+            self.ledger.unfreeze(order.account, order.funding_asset(), order.reserve, now)?;
+            self.touch(order.account, order.funding_asset());
+        }
+        self.pending.push(ExchangeEvent::OrderClosed {
+            order_id,
+            account: order.account,
+            trading_pair: order.trading_pair,
+            status,
+        });
+        Ok(())
+    }
+
+    /// 按成交价推进条件单，触发的订单按普通流程下单，其成交继续推进
+    fn trigger_conditionals(&mut self, trades: &[Trade], now: Timestamp) {
+        let mut prices: VecDeque<(TradingPair, Price)> =
+            trades.iter().map(|trade| (trade.trading_pair, trade.price)).collect();
+        while let Some((trading_pair, price)) = prices.pop_front() {
+            for event in self.conditionals.on_trade(trading_pair, price) {
+                match event {
+                    ConditionalEvent::Submit(fired) => {
+                        let request = triggered_request(&fired.action);
+                        let placed = self.place(request, now);
+                        self.pending.push(ExchangeEvent::ConditionalTriggered {
+                            conditional_id: fired.id,
+                            account: request.account,
+                            trading_pair,
+                            order_id: placed.as_ref().ok().map(|result| result.order_id),
+                        });
+                        if let Ok(result) = placed {
+                            prices.extend(
+                                result.trades.iter().map(|trade| (trade.trading_pair, trade.price)),
+                            );
+                        }
+                    }
+                    ConditionalEvent::CancelSibling {
+                        sibling: Sibling::External(order_id),
+                        ..
+                    } => {
+                        self.oco_limits.remove(&order_id);
+                        // 限价单可能已在同一批成交中结束
+                        if self.orders.contains_key(&order_id) {
+                            let _ = self.cancel_open(order_id, now);
+                        }
+                    }
+                    ConditionalEvent::CancelSibling { .. }
+                    | ConditionalEvent::AdjustTrail { .. } => {}
+                }
+            }
+        }
+    }
+
+    /// 限价单成交或结束时撤销与之组成 OCO 的条件单
+    fn cancel_linked_conditional(&mut self, order_id: OrderId) {
+        let Some(id) = self.oco_limits.remove(&order_id) else {
+            return;
+        };
+        if let Some(order) = self.conditionals.cancel(id) {
+            self.pending.push(ExchangeEvent::ConditionalCancelled {
+                conditional_id: id,
+                account: order.trader_id.into(),
+                trading_pair: order.trading_pair,
+            });
+        }
+    }
+
+    fn order_mut(&mut self, order_id: OrderId) -> &mut OpenOrder {
+        self.orders.get_mut(&order_id).expect("open order exists while filling")
+    }
+
+    fn touch(&mut self, account: AccountId, asset: AssetId) {
+        if !self.touched.contains(&(account, asset)) {
+            self.touched.push((account, asset));
+        }
+    }
+
+    /// 追加余额快照事件并回调
+    fn flush(&mut self) {
+        for (account, asset) in self.touched.drain(..) {
+            if let Some(balance) = self.ledger.balance(account, asset) {
+                self.pending.push(ExchangeEvent::BalanceChanged {
+                    account,
+                    asset,
+                    available: balance.available,
+                    frozen: balance.frozen,
+                });
+            }
+        }
+        for event in self.pending.drain(..) {
+            for listener in &mut self.listeners {
+                listener(&event);
+            }
+        }
+    }
+}
+
+/// 条件单登记为现货订单（订单ID在触发下单时分配）
+fn conditional_order(
+    request: &OrderRequest,
+    conditional_type: ConditionalType,
+    stop_price: Price,
+    now: Timestamp,
+) -> SpotOrder {
+    let time_in_force = match request.time_in_force {
+        TimeInForce::GTC => SpotTimeInForce::GTC,
+        TimeInForce::IOC => SpotTimeInForce::IOC,
+        TimeInForce::FOK => SpotTimeInForce::FOK,
+        TimeInForce::PostOnly => SpotTimeInForce::GTX,
+    };
+    let mut order = SpotOrder::create_order(
+        0,
+        request.account.into(),
+        request.trading_pair,
+        request.side,
+        request.price.unwrap_or_default(),
+        request.quantity,
+        time_in_force,
+        None,
+        Quantity::default(),
+        now,
+    );
+    if request.price.is_none() {
+        order.price = None;
+        order.execution_method = ExecutionMethod::Market;
+    }
+    order.source = OrderSource::ConditionalTrigger;
+    order.conditional_type = conditional_type;
+    order.stop_price = Some(stop_price);
+    order
+}
+
+/// 触发的条件单还原为下单请求
+fn triggered_request(order: &SpotOrder) -> OrderRequest {
+    let time_in_force = match order.time_in_force {
+        SpotTimeInForce::IOC => TimeInForce::IOC,
+        SpotTimeInForce::FOK => TimeInForce::FOK,
+        SpotTimeInForce::GTX => TimeInForce::PostOnly,
+        SpotTimeInForce::GTC | SpotTimeInForce::GTD => TimeInForce::GTC,
+    };
+    OrderRequest {
+        account: order.trader_id.into(),
+        trading_pair: order.trading_pair,
+        side: order.side,
+        price: order.price,
+        quantity: order.total_base_qty,
+        time_in_force,
+    }
+}
+
+fn to_side(side: OrderSide) -> Side {
+    match side {
+        OrderSide::Buy => Side::Buy,
+        OrderSide::Sell => Side::Sell,
+    }
+}
+
+/// `Price`/`Quantity` 的最小单位整数（调用方已校验为正）
+fn to_units(value: Quantity) -> u64 {
+    value.raw() as u64
+}
+
+fn from_units(units: u64) -> Quantity {
+    Quantity::from_raw(units as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use base_types::ManualClock;
+
+    use super::*;
+
+    const ALICE: AccountId = AccountId(1);
+    const BOB: AccountId = AccountId(2);
+    const FEES: AccountId = AccountId(99);
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    fn exchange() -> Exchange {
+        let config = ExchangeConfig::new([TradingPair::BtcUsdt])
+            .with_fees(ProductFeeConfig::spot(0.001, 0.002), FEES);
+        let mut exchange =
+            Exchange::new(config).with_clock(Arc::new(ManualClock::from_millis(1_000)));
+        exchange.deposit(ALICE, AssetId::Usdt, q(100_000.0)).unwrap();
+        exchange.deposit(BOB, AssetId::Btc, q(10.0)).unwrap();
+        exchange
+    }
+
+    fn total(exchange: &Exchange, asset: AssetId) -> Quantity {
+        [ALICE, BOB, FEES]
+            .iter()
+            .filter_map(|account| exchange.balance(*account, asset))
+            .map(|b| b.available + b.frozen)
+            .sum()
+    }
+
+    #[test]
+    fn test_submit_match_and_settle() {
+        let mut exchange = exchange();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        exchange.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+
+        let ask =
+            OrderRequest::limit(BOB, TradingPair::BtcUsdt, OrderSide::Sell, q(40_000.0), q(1.0));
+        let maker = exchange.submit(ask).unwrap();
+        assert_eq!(maker.status, OrderStatus::Resting);
+        assert_eq!(exchange.balance(BOB, AssetId::Btc).unwrap().frozen, q(1.0));
+
+        // 限价高于卖价，按挂单价成交
+        let bid =
+            OrderRequest::limit(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(41_000.0), q(0.5));
+        let taker = exchange.submit(bid).unwrap();
+        assert_eq!((taker.status, taker.filled), (OrderStatus::Filled, q(0.5)));
+        let trade = &taker.trades[0];
+        assert_eq!((trade.price, trade.maker_order_id), (q(40_000.0), maker.order_id));
+        assert_eq!((trade.buyer_fee, trade.seller_fee), (q(40.0), q(20.0)));
+
+        // 买方：付 20000 + 手续费 40，剩余冻结全部释放
+        let alice_usdt = exchange.balance(ALICE, AssetId::Usdt).unwrap();
+        assert_eq!((alice_usdt.available, alice_usdt.frozen), (q(79_960.0), q(0.0)));
+        assert_eq!(exchange.balance(ALICE, AssetId::Btc).unwrap().available, q(0.5));
+        let bob_usdt = exchange.balance(BOB, AssetId::Usdt).unwrap();
+        assert_eq!(bob_usdt.available, q(19_980.0));
+        assert_eq!(exchange.balance(FEES, AssetId::Usdt).unwrap().available, q(60.0));
+        assert_eq!(total(&exchange, AssetId::Usdt), q(100_000.0));
+        assert_eq!(total(&exchange, AssetId::Btc), q(10.0));
+
+        let depth = exchange.query_depth(TradingPair::BtcUsdt, 5).unwrap();
+        assert_eq!(depth.asks, [(q(40_000.0), q(0.5))]);
+        assert!(depth.bids.is_empty());
+
+        let events = events.lock().unwrap();
+        let closed: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ExchangeEvent::OrderClosed { order_id, status, .. } => Some((*order_id, *status)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(closed, [(taker.order_id, OrderStatus::Filled)]);
+        assert!(events.iter().any(|e| matches!(e, ExchangeEvent::Trade(t) if t.trade_id == 1)));
+    }
+
+    #[test]
+    fn test_settlement_exported_after_booking() {
+        use base_types::account::settlement_export::{SettlementExportConfig, encode_settlement};
+
+        struct Captured(Arc<Mutex<Vec<(String, Vec<u8>)>>>);
+
+        impl SettlementSink for Captured {
+            fn send(&mut self, _topic: &str, key: &[u8], payload: &[u8]) -> std::io::Result<()> {
+                let key = String::from_utf8_lossy(key).into_owned();
+                self.0.lock().unwrap().push((key, payload.to_vec()));
+                Ok(())
+            }
+        }
+
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let sink: Box<dyn SettlementSink + Send> = Box::new(Captured(messages.clone()));
+        let exporter = SettlementExporter::new(SettlementExportConfig::default(), sink);
+        let mut exchange = exchange().with_settlement_export(exporter);
+        exchange
+            .submit(OrderRequest::limit(
+                BOB,
+                TradingPair::BtcUsdt,
+                OrderSide::Sell,
+                q(40_000.0),
+                q(1.0),
+            ))
+            .unwrap();
+        exchange
+            .submit(OrderRequest::limit(
+                ALICE,
+                TradingPair::BtcUsdt,
+                OrderSide::Buy,
+                q(40_000.0),
+                q(0.5),
+            ))
+            .unwrap();
+
+        let expected = Settlement::new(1, Timestamp(1_000_000_000))
+            .with_entry(ALICE, AssetId::Usdt, q(-20_000.0), BalanceChangeType::Trade)
+            .with_entry(BOB, AssetId::Usdt, q(20_000.0), BalanceChangeType::Trade)
+            .with_entry(BOB, AssetId::Btc, q(-0.5), BalanceChangeType::Trade)
+            .with_entry(ALICE, AssetId::Btc, q(0.5), BalanceChangeType::Trade)
+            .with_entry(ALICE, AssetId::Usdt, q(-40.0), BalanceChangeType::Fee)
+            .with_entry(FEES, AssetId::Usdt, q(40.0), BalanceChangeType::Fee)
+            .with_entry(BOB, AssetId::Usdt, q(-20.0), BalanceChangeType::Fee)
+            .with_entry(FEES, AssetId::Usdt, q(20.0), BalanceChangeType::Fee);
+        assert_eq!(*messages.lock().unwrap(), [("1".to_string(), encode_settlement(&expected))]);
+        assert_eq!(exchange.settlement_export().unwrap().exported(), 1);
+    }
+
+    #[test]
+    fn test_cost_basis_tracks_trades_and_realized_pnl() {
+        let config = ExchangeConfig::new([TradingPair::BtcUsdt])
+            .with_fees(ProductFeeConfig::spot(0.001, 0.002), FEES)
+            .with_cost_tracking();
+        let mut tracked =
+            Exchange::new(config).with_clock(Arc::new(ManualClock::from_millis(1_000)));
+        tracked.deposit(ALICE, AssetId::Usdt, q(100_000.0)).unwrap();
+        tracked.deposit(BOB, AssetId::Btc, q(10.0)).unwrap();
+        let limit = |account, side, price, quantity| {
+            OrderRequest::limit(account, TradingPair::BtcUsdt, side, q(price), q(quantity))
+        };
+
+        tracked.submit(limit(BOB, OrderSide::Sell, 40_000.0, 1.0)).unwrap();
+        tracked.submit(limit(ALICE, OrderSide::Buy, 40_000.0, 1.0)).unwrap();
+        tracked.submit(limit(ALICE, OrderSide::Sell, 42_000.0, 0.5)).unwrap();
+        tracked.submit(limit(BOB, OrderSide::Buy, 42_000.0, 0.5)).unwrap();
+
+        let btc = |account| {
+            tracked.cost_basis(account).into_iter().find(|view| view.asset_id == AssetId::Btc)
+        };
+        let alice = btc(ALICE).unwrap();
+        assert_eq!((alice.quantity, alice.cost_basis), (q(0.5), q(20_000.0)));
+        assert_eq!((alice.avg_cost_price, alice.realized_pnl), (Some(q(40_000.0)), q(1_000.0)));
+        // 充值的成本记为 0：卖出 1 BTC 全部计为盈利
+        assert_eq!(btc(BOB).unwrap().realized_pnl, q(40_000.0));
+        // 未启用追踪时没有成本视图
+        assert!(exchange().cost_basis(BOB).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_releases_reserve() {
+        let mut exchange = exchange();
+        let bid =
+            OrderRequest::limit(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(30_000.0), q(2.0));
+        let result = exchange.submit(bid).unwrap();
+        // 60000 + 0.2% 手续费余量
+        assert_eq!(exchange.balance(ALICE, AssetId::Usdt).unwrap().frozen, q(60_120.0));
+
+        // 冻结部分不可提现
+        assert!(matches!(
+            exchange.withdraw(ALICE, AssetId::Usdt, q(40_000.0)),
+            Err(ExchangeError::Balance(_))
+        ));
+        exchange.cancel(result.order_id).unwrap();
+        exchange.withdraw(ALICE, AssetId::Usdt, q(40_000.0)).unwrap();
+        let usdt = exchange.balance(ALICE, AssetId::Usdt).unwrap();
+        assert_eq!((usdt.available, usdt.frozen), (q(60_000.0), q(0.0)));
+        assert_eq!(
+            exchange.cancel(result.order_id),
+            Err(ExchangeError::OrderNotFound(result.order_id))
+        );
+        assert!(exchange.query_depth(TradingPair::BtcUsdt, 5).unwrap().bids.is_empty());
+    }
+
+    #[test]
+    fn test_suspend_and_freeze_enforced() {
+        let mut exchange = exchange();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        exchange.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+        let bid = |price| {
+            OrderRequest::limit(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(price), q(1.0))
+        };
+        let first = exchange.submit(bid(30_000.0)).unwrap();
+        let second = exchange.submit(bid(31_000.0)).unwrap();
+
+        // 暂停：不能下单，可以撤单与提现
+        exchange
+            .apply_account_command(ALICE, AccountStatusCommand::Suspend, "KYC review", "ops")
+            .unwrap();
+        assert_eq!(
+            exchange.submit(bid(30_000.0)),
+            Err(ExchangeError::AccountRestricted {
+                account: ALICE,
+                status: AccountStatus::Suspended
+            })
+        );
+        exchange.cancel(first.order_id).unwrap();
+        exchange.withdraw(ALICE, AssetId::Usdt, q(1_000.0)).unwrap();
+
+        // 冻结：撤掉剩余挂单，提现被拒绝
+        exchange
+            .apply_account_command(ALICE, AccountStatusCommand::Freeze, "fraud", "ops")
+            .unwrap();
+        assert!(exchange.query_depth(TradingPair::BtcUsdt, 5).unwrap().bids.is_empty());
+        assert_eq!(exchange.balance(ALICE, AssetId::Usdt).unwrap().frozen, q(0.0));
+        assert!(matches!(
+            exchange.withdraw(ALICE, AssetId::Usdt, q(1.0)),
+            Err(ExchangeError::AccountRestricted { status: AccountStatus::Frozen, .. })
+        ));
+        assert_eq!(
+            exchange.apply_account_command(ALICE, AccountStatusCommand::Reinstate, "", "ops"),
+            Err(ExchangeError::AccountStatus(AccountStatusError::MissingReason))
+        );
+        exchange
+            .apply_account_command(ALICE, AccountStatusCommand::Reinstate, "cleared", "ops")
+            .unwrap();
+        assert!(exchange.submit(bid(30_000.0)).is_ok());
+
+        let log = exchange.account_audit_log();
+        assert_eq!(log.len(), 3);
+        assert_eq!((log[1].to, log[1].reason.as_str()), (AccountStatus::Frozen, "fraud"));
+        let events = events.lock().unwrap();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ExchangeEvent::OrderClosed { order_id, status: OrderStatus::Cancelled, .. }
+                if *order_id == second.order_id
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ExchangeEvent::AccountStatusChanged { to: AccountStatus::Active, .. }
+        )));
+    }
+
+    #[test]
+    fn test_convert_dust_settles_against_liquidity_account() {
+        use std::collections::HashMap;
+
+        use base_types::account::dust::DustConfig;
+        use base_types::exchange::spot::convert::ConvertEngine;
+
+        const DESK: AccountId = AccountId(500);
+        let mut exchange = exchange();
+        exchange.deposit(ALICE, AssetId::Btc, q(0.0001)).unwrap();
+        let prices = HashMap::from([((AssetId::Btc, AssetId::Usdt), q(40_000.0))]);
+        let config = DustConfig { target: AssetId::Usdt, threshold: q(10.0) };
+        let sweeper = DustSweeper::new(ConvertEngine::new(DESK).with_fee_rate(0.01), config);
+        assert_eq!(exchange.preview_dust(ALICE, &sweeper, &prices).total_net(), q(3.96));
+
+        // 流动性账户余额不足时整笔拒绝
+        assert!(matches!(
+            exchange.convert_dust(ALICE, &sweeper, &prices),
+            Err(ExchangeError::Balance(BalanceError::InsufficientAvailable { .. }))
+        ));
+        assert_eq!(exchange.balance(ALICE, AssetId::Btc).unwrap().available, q(0.0001));
+
+        exchange.deposit(DESK, AssetId::Usdt, q(1_000.0)).unwrap();
+        let settlement = exchange.convert_dust(ALICE, &sweeper, &prices).unwrap();
+        assert!(settlement.check_invariants().is_ok());
+        assert_eq!(exchange.balance(ALICE, AssetId::Btc).unwrap().available, q(0.0));
+        assert_eq!(exchange.balance(ALICE, AssetId::Usdt).unwrap().available, q(100_003.96));
+        assert_eq!(exchange.balance(DESK, AssetId::Btc).unwrap().available, q(0.0001));
+        assert_eq!(
+            exchange.convert_dust(ALICE, &sweeper, &prices),
+            Err(ExchangeError::Dust(DustError::NothingToConvert(ALICE)))
+        );
+    }
+
+    #[test]
+    fn test_conditional_orders_trigger_on_trade_price() {
+        let mut exchange = exchange();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        exchange.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+        let pair = TradingPair::BtcUsdt;
+        let sell = |price: f64, quantity: f64| {
+            OrderRequest::limit(BOB, pair, OrderSide::Sell, q(price), q(quantity))
+        };
+        let stop = OrderRequest::market(BOB, pair, OrderSide::Sell, q(2.0));
+
+        exchange
+            .submit(OrderRequest::limit(ALICE, pair, OrderSide::Buy, q(38_500.0), q(2.2)))
+            .unwrap();
+        let (limit, stop_id) =
+            exchange.submit_limit_oco(sell(45_000.0, 2.0), stop, q(39_000.0)).unwrap();
+        assert_eq!(limit.status, OrderStatus::Resting);
+        let stop_id = stop_id.unwrap();
+        assert_eq!(exchange.open_conditionals(), 1);
+
+        // 成交价 39000 触发止损：撤销限价单，市价卖出吃掉 38500 的买单
+        exchange.submit(sell(39_000.0, 0.1)).unwrap();
+        exchange
+            .submit(OrderRequest::limit(ALICE, pair, OrderSide::Buy, q(39_000.0), q(0.1)))
+            .unwrap();
+        assert_eq!(exchange.open_conditionals(), 0);
+        let depth = exchange.query_depth(pair, 5).unwrap();
+        assert!(depth.asks.is_empty());
+        assert_eq!(depth.bids, [(q(38_500.0), q(0.2))]);
+        assert_eq!(exchange.balance(BOB, AssetId::Btc).unwrap().frozen, q(0.0));
+        assert!(events.lock().unwrap().iter().any(|event| matches!(
+            event,
+            ExchangeEvent::ConditionalTriggered { conditional_id, order_id: Some(_), .. }
+                if *conditional_id == stop_id
+        )));
+
+        // 限价单撤销时一并撤销止损单
+        let (limit, stop_id) =
+            exchange.submit_limit_oco(sell(45_000.0, 1.0), stop, q(37_000.0)).unwrap();
+        exchange.cancel(limit.order_id).unwrap();
+        assert_eq!(exchange.open_conditionals(), 0);
+        assert!(events.lock().unwrap().contains(&ExchangeEvent::ConditionalCancelled {
+            conditional_id: stop_id.unwrap(),
+            account: BOB,
+            trading_pair: pair,
+        }));
+
+        let (loss, profit) =
+            exchange.submit_oco(stop, q(30_000.0), sell(50_000.0, 2.0), q(50_000.0)).unwrap();
+        exchange.cancel_conditional(loss).unwrap();
+        assert_eq!(
+            exchange.cancel_conditional(loss),
+            Err(ExchangeError::ConditionalNotFound(loss))
+        );
+        exchange.cancel_conditional(profit).unwrap();
+        assert_eq!(
+            exchange.submit_conditional(stop, ConditionalType::None, q(30_000.0)),
+            Err(ExchangeError::Conditional(SpotConditionalError::NotConditional))
+        );
+        let market_buy = OrderRequest::market(ALICE, pair, OrderSide::Buy, q(1.0));
+        assert!(matches!(
+            exchange.submit_conditional(market_buy, ConditionalType::StopLoss, q(41_000.0)),
+            Err(ExchangeError::InvalidOrder(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejections_leave_balances_untouched() {
+        let mut exchange = exchange();
+        let too_big =
+            OrderRequest::limit(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(60_000.0), q(2.0));
+        assert!(matches!(exchange.submit(too_big), Err(ExchangeError::Balance(_))));
+        let market_buy = OrderRequest::market(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(1.0));
+        assert!(matches!(exchange.submit(market_buy), Err(ExchangeError::InvalidOrder(_))));
+        let unlisted =
+            OrderRequest::limit(ALICE, TradingPair::EthUsdt, OrderSide::Buy, q(1.0), q(1.0));
+        assert_eq!(
+            exchange.submit(unlisted),
+            Err(ExchangeError::UnknownMarket(TradingPair::EthUsdt))
+        );
+
+        exchange
+            .submit(OrderRequest::limit(
+                BOB,
+                TradingPair::BtcUsdt,
+                OrderSide::Sell,
+                q(40_000.0),
+                q(1.0),
+            ))
+            .unwrap();
+        let post_only =
+            OrderRequest::limit(ALICE, TradingPair::BtcUsdt, OrderSide::Buy, q(40_000.0), q(1.0))
+                .with_time_in_force(TimeInForce::PostOnly);
+        assert_eq!(
+            exchange.submit(post_only),
+            Err(ExchangeError::Rejected(RejectReason::WouldCross))
+        );
+        let usdt = exchange.balance(ALICE, AssetId::Usdt).unwrap();
+        assert_eq!((usdt.available, usdt.frozen), (q(100_000.0), q(0.0)));
+
+        // 市价卖单吃不完的部分取消并解冻
+        exchange
+            .submit(OrderRequest::limit(
+                ALICE,
+                TradingPair::BtcUsdt,
+                OrderSide::Buy,
+                q(39_000.0),
+                q(0.1),
+            ))
+            .unwrap();
+        let sell = exchange
+            .submit(OrderRequest::market(BOB, TradingPair::BtcUsdt, OrderSide::Sell, q(0.3)))
+            .unwrap();
+        assert_eq!((sell.status, sell.filled), (OrderStatus::Cancelled, q(0.1)));
+        let btc = exchange.balance(BOB, AssetId::Btc).unwrap();
+        assert_eq!((btc.available, btc.frozen), (q(8.9), q(1.0)));
+        assert_eq!(total(&exchange, AssetId::Usdt), q(100_000.0));
+    }
+}
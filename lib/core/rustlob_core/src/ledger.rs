@@ -0,0 +1,163 @@
+//! 内存账本
+//!
+//! 按 (账户, 资产) 保存 [`Balance`]，冻结、扣款、解冻直接复用 `Balance` 的校验；
+//! 余额记录在首次使用时创建；启用持仓成本追踪时新建的余额记录同时追踪成本
+
+use std::collections::HashMap;
+
+use base_types::account::balance::Balance;
+use base_types::account::error::BalanceError;
+use base_types::account::settlement::Settlement;
+use base_types::{AccountId, AssetId, Price, Quantity, Timestamp};
+
+#[derive(Debug, Default)]
+pub struct Ledger {
+    balances: HashMap<(AccountId, AssetId), Balance>,
+    cost_tracking: bool,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新建的余额记录追踪持仓成本（充值等非成交入账的成本记为 0）
+    pub fn with_cost_tracking(mut self) -> Self {
+        self.cost_tracking = true;
+        self
+    }
+
+    pub fn balance(&self, account: AccountId, asset: AssetId) -> Option<&Balance> {
+        self.balances.get(&(account, asset))
+    }
+
+    /// 账户的全部余额（按资产ID排序）
+    pub fn balances_of(&self, account: AccountId) -> Vec<&Balance> {
+        let mut balances: Vec<&Balance> =
+            self.balances.values().filter(|b| b.account_id == account).collect();
+        balances.sort_by_key(|b| b.asset_id.as_u32());
+        balances
+    }
+
+    /// 可用余额（无记录为 0）
+    pub fn available(&self, account: AccountId, asset: AssetId) -> Quantity {
+        self.balance(account, asset).map(|b| b.available).unwrap_or_default()
+    }
+
+    /// 入账到可用余额
+    pub fn credit(&mut self, account: AccountId, asset: AssetId, amount: Quantity, now: Timestamp) {
+        self.entry(account, asset, now).add_balance(amount, now);
+    }
+
+    /// 可用 → 冻结
+    pub fn freeze(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+        now: Timestamp,
+    ) -> Result<(), BalanceError> {
+        self.entry(account, asset, now).frozen(amount, now)
+    }
+
+    /// 冻结 → 可用
+    pub fn unfreeze(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+        now: Timestamp,
+    ) -> Result<(), BalanceError> {
+        self.entry(account, asset, now).un_frozen(amount, now)
+    }
+
+    /// 从冻结余额扣款
+    pub fn pay_frozen(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+        now: Timestamp,
+    ) -> Result<(), BalanceError> {
+        self.entry(account, asset, now).frozen2pay(amount, now)
+    }
+
+    /// 从可用余额扣款，最多扣到 0，返回实际扣除数量
+    pub fn debit_available(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        amount: Quantity,
+        now: Timestamp,
+    ) -> Quantity {
+        let balance = self.entry(account, asset, now);
+        let debited = amount.min(balance.available.max(Quantity::default()));
+        if debited.is_positive() {
+            balance.add_balance(Quantity::default() - debited, now);
+        }
+        debited
+    }
+
+    /// 买入成交累加持仓成本（须在入账前调用）
+    pub fn record_acquisition(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        quantity: Quantity,
+        price: Price,
+        now: Timestamp,
+    ) {
+        self.entry(account, asset, now).record_acquisition(quantity, price);
+    }
+
+    /// 卖出成交结转持仓成本（须在扣款前调用），返回已实现盈亏
+    pub fn record_disposal(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+        quantity: Quantity,
+        price: Price,
+        now: Timestamp,
+    ) -> Option<Price> {
+        self.entry(account, asset, now).record_disposal(quantity, price)
+    }
+
+    /// 按结算分录记账（可用余额），任一账户可用不足时整笔拒绝、不做改动
+    pub fn apply_settlement(
+        &mut self,
+        settlement: &Settlement,
+        now: Timestamp,
+    ) -> Result<(), BalanceError> {
+        let mut nets: Vec<((AccountId, AssetId), Quantity)> = Vec::new();
+        for entry in &settlement.entries {
+            let key = (entry.account_id, entry.asset_id);
+            match nets.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, net)) => *net += entry.amount,
+                None => nets.push((key, entry.amount)),
+            }
+        }
+        for ((account, asset), net) in &nets {
+            let available = self.available(*account, *asset);
+            if net.is_negative() && available + *net < Quantity::default() {
+                return Err(BalanceError::InsufficientAvailable {
+                    required: (Quantity::default() - *net).raw(),
+                    available: available.raw(),
+                });
+            }
+        }
+        for ((account, asset), net) in nets {
+            if !net.is_zero() {
+                self.entry(account, asset, now).add_balance(net, now);
+            }
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, account: AccountId, asset: AssetId, now: Timestamp) -> &mut Balance {
+        let cost_tracking = self.cost_tracking;
+        self.balances.entry((account, asset)).or_insert_with(|| {
+            let balance = Balance::new(account, asset, now);
+            if cost_tracking { balance.with_cost_tracking(Quantity::default()) } else { balance }
+        })
+    }
+}
@@ -0,0 +1,42 @@
+//! 可嵌入的交易所内核
+//!
+//! 对外只暴露一个 [`Exchange`]：下单、撤单、条件单、查询深度与事件回调。内部把撮合内核
+//! （`match_core`）、账户余额（`base_types::account::balance`）与成交清算
+//! （`base_types::account::clearing`）组装在一起，全部使用内存实现，不依赖网关、
+//! 数据库与消息队列，供其他 Rust 项目（回测、仿真、集成测试）直接嵌入：
+//!
+//! ```ignore
+//! use rustlob_core::{Exchange, ExchangeConfig, OrderRequest};
+//!
+//! let mut exchange = Exchange::new(ExchangeConfig::new([TradingPair::BtcUsdt]));
+//! exchange.on_event(|event| println!("{:?}", event));
+//! exchange.deposit(AccountId(1), AssetId::Usdt, Quantity::from_f64(10_000.0))?;
+//! let result = exchange.submit(OrderRequest::limit(
+//!     AccountId(1),
+//!     TradingPair::BtcUsdt,
+//!     OrderSide::Buy,
+//!     Price::from_f64(40_000.0),
+//!     Quantity::from_f64(0.1),
+//! ))?;
+//! let depth = exchange.query_depth(TradingPair::BtcUsdt, 10)?;
+//! ```
+//!
+//! 对外接口只使用 `base_types` 的类型（账户、资产、交易对、`Price`/`Quantity`），
+//! 内部实现可以替换而不影响嵌入方
+
+pub mod bootstrap;
+pub mod event;
+pub mod exchange;
+pub mod ledger;
+pub mod rules;
+
+pub use bootstrap::{BootstrapError, Manifest, bootstrap};
+pub use conditional_order::ConditionalId;
+pub use event::{ExchangeEvent, Trade};
+pub use exchange::{
+    Depth, Exchange, ExchangeConfig, ExchangeError, ExchangeSettlementExporter, OrderRequest,
+    OrderResult, OrderStatus,
+};
+pub use ledger::Ledger;
+pub use match_core::{OrderId, RejectReason, TimeInForce};
+pub use rules::{MarketRules, RiskLimits};
@@ -0,0 +1,128 @@
+//! 下单规则与风控限额
+//!
+//! 在冻结资金之前校验，不满足的委托直接拒绝，不进入撮合：
+//! - 交易对规则：最小变动价位、数量步长、数量上下限、最小名义价值
+//! - 风控限额：单笔名义价值上限、每个账户的挂单数上限
+//!
+//! 市价单没有价格，不检查价位与名义价值
+
+use base_types::{Price, Quantity};
+
+/// 交易对下单规则（零值或 None 为不限制）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MarketRules {
+    /// 最小变动价位
+    pub tick_size: Price,
+    /// 数量步长
+    pub lot_size: Quantity,
+    /// 最小下单数量
+    pub min_quantity: Quantity,
+    /// 最大下单数量
+    pub max_quantity: Option<Quantity>,
+    /// 最小名义价值（计价资产）
+    pub min_notional: Quantity,
+}
+
+impl MarketRules {
+    /// 校验委托价格与数量，返回拒绝原因
+    pub fn check(&self, price: Option<Price>, quantity: Quantity) -> Result<(), &'static str> {
+        if !is_multiple(quantity, self.lot_size) {
+            return Err("quantity is not a multiple of lot size");
+        }
+        if quantity < self.min_quantity {
+            return Err("quantity below minimum");
+        }
+        if self.max_quantity.is_some_and(|max| quantity > max) {
+            return Err("quantity above maximum");
+        }
+        let Some(price) = price else {
+            return Ok(());
+        };
+        if !is_multiple(price, self.tick_size) {
+            return Err("price is not a multiple of tick size");
+        }
+        if price.checked_mul(quantity).is_some_and(|notional| notional < self.min_notional) {
+            return Err("notional below minimum");
+        }
+        Ok(())
+    }
+}
+
+/// 风控限额（None 为不限制）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskLimits {
+    /// 单笔委托名义价值上限（计价资产）
+    pub max_order_notional: Option<Quantity>,
+    /// 每个账户的挂单数上限
+    pub max_open_orders: Option<usize>,
+}
+
+impl RiskLimits {
+    /// 校验委托，`open_orders` 为账户当前挂单数，返回拒绝原因
+    pub fn check(
+        &self,
+        price: Option<Price>,
+        quantity: Quantity,
+        open_orders: usize,
+    ) -> Result<(), &'static str> {
+        if self.max_open_orders.is_some_and(|max| open_orders >= max) {
+            return Err("too many open orders");
+        }
+        let notional = price.map(|price| price.checked_mul(quantity));
+        match (self.max_order_notional, notional) {
+            (Some(_), Some(None)) => Err("notional overflows"),
+            (Some(max), Some(Some(notional))) if notional > max => {
+                Err("order notional above limit")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn is_multiple(value: Quantity, step: Quantity) -> bool {
+    step.raw() <= 0 || value.raw() % step.raw() == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(value: f64) -> Quantity {
+        Quantity::from_f64(value)
+    }
+
+    #[test]
+    fn test_market_rules() {
+        let rules = MarketRules {
+            tick_size: q(0.5),
+            lot_size: q(0.001),
+            min_quantity: q(0.01),
+            max_quantity: Some(q(10.0)),
+            min_notional: q(10.0),
+        };
+        assert_eq!(rules.check(Some(q(100.5)), q(0.1)), Ok(()));
+        assert_eq!(
+            rules.check(Some(q(100.2)), q(0.1)),
+            Err("price is not a multiple of tick size")
+        );
+        assert_eq!(
+            rules.check(Some(q(100.0)), q(0.1005)),
+            Err("quantity is not a multiple of lot size")
+        );
+        assert_eq!(rules.check(Some(q(100.0)), q(0.005)), Err("quantity below minimum"));
+        assert_eq!(rules.check(None, q(11.0)), Err("quantity above maximum"));
+        assert_eq!(rules.check(Some(q(100.0)), q(0.05)), Err("notional below minimum"));
+        // 市价单不检查名义价值
+        assert_eq!(rules.check(None, q(0.05)), Ok(()));
+        assert_eq!(MarketRules::default().check(Some(q(0.123)), q(0.001)), Ok(()));
+    }
+
+    #[test]
+    fn test_risk_limits() {
+        let limits = RiskLimits { max_order_notional: Some(q(1_000.0)), max_open_orders: Some(2) };
+        assert_eq!(limits.check(Some(q(100.0)), q(10.0), 1), Ok(()));
+        assert_eq!(limits.check(Some(q(100.0)), q(10.5), 1), Err("order notional above limit"));
+        assert_eq!(limits.check(None, q(10.5), 1), Ok(()));
+        assert_eq!(limits.check(Some(q(1.0)), q(1.0), 2), Err("too many open orders"));
+    }
+}
@@ -0,0 +1,499 @@
+//! 链式（Chained）HotStuff
+//!
+//! 三个阶段在相邻区块上流水线化：每个区块只收集一轮 Generic 投票，
+//! 它的 QC 同时充当父区块的 Pre-commit、祖父区块的 Commit 与曾祖父区块的 Decide。
+//!
+//! - 投票发给下一视图的 Leader，由其聚合成 QC 并放进新提案的 justify
+//! - 两链锁定：收到 b* 时锁定 b'（b* → b'' → b'）
+//! - 三链提交：b'' → b' → b 直接父子相连时提交 b 及其祖先
+//! - 视图同步与超时由 [`Pacemaker`] 负责，超时后由 TC 进入下一视图
+//!
+//! 副本不直接做网络 I/O：每个入口返回待发送的 [`Outbound`]，由调用方投递。
+
+use std::collections::HashMap;
+
+use super::consensus::ConsensusError;
+use super::entities::{
+    Block, Height, Phase, QuorumCertificate, TimeoutCertificate, TimeoutVote, ViewNumber, Vote,
+    quorum_size,
+};
+use super::pacemaker::{Pacemaker, PacemakerConfig};
+use crate::crypto::{Hash, PrivateKey, PublicKey};
+
+/// 链式 HotStuff 消息
+#[derive(Debug, Clone)]
+pub enum ChainedMessage {
+    /// 提案；若 Leader 经 TC 进入该视图，附带该 TC
+    Proposal { block: Block, tc: Option<TimeoutCertificate> },
+    /// Generic 投票，发给下一视图的 Leader
+    Vote(Vote),
+    /// 超时投票；附带发送者进入当前视图所凭借的 TC，帮助落后节点同步视图
+    Timeout { vote: TimeoutVote, tc: Option<TimeoutCertificate> },
+}
+
+/// 消息接收方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    /// 所有验证者（包括自己：Leader 的提案同样经由网络回到自身）
+    All,
+    /// 指定节点
+    Node(u64),
+}
+
+/// 待发送的消息
+#[derive(Debug, Clone)]
+pub struct Outbound {
+    pub to: Recipient,
+    pub message: ChainedMessage,
+}
+
+/// 链式 HotStuff 副本
+pub struct ChainedHotStuff {
+    /// 节点 ID（即在验证者集合中的下标）
+    id: u64,
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    /// 验证者集合（Leader = view % n）
+    validators: Vec<PublicKey>,
+    pacemaker: Pacemaker,
+    /// 区块存储
+    blocks: HashMap<Hash, Block>,
+    genesis_hash: Hash,
+    /// 最高 QC
+    high_qc: QuorumCertificate,
+    /// 锁定的 QC
+    locked_qc: QuorumCertificate,
+    /// 最近一次投票的视图（每个视图最多投一票）
+    last_voted_view: ViewNumber,
+    /// 最近一次提案的视图
+    last_proposed_view: ViewNumber,
+    /// 进入当前视图所凭借的 TC
+    last_tc: Option<TimeoutCertificate>,
+    /// 收集中的投票（作为下一视图的 Leader）
+    votes: HashMap<(Hash, ViewNumber), HashMap<PublicKey, Vote>>,
+    /// 已提交的区块（按高度排列，不含创世区块）
+    committed: Vec<Block>,
+    /// 最近提交的区块
+    last_committed: Hash,
+    /// 待打包的命令
+    pending_commands: Vec<Vec<u8>>,
+    /// 逻辑时钟（毫秒）
+    now: u64,
+}
+
+impl ChainedHotStuff {
+    /// 创建副本
+    pub fn new(
+        id: u64,
+        private_key: PrivateKey,
+        validators: Vec<PublicKey>,
+        config: PacemakerConfig,
+    ) -> Self {
+        let genesis = Block::genesis();
+        let genesis_hash = genesis.hash();
+        let genesis_qc = QuorumCertificate::genesis_with_hash(genesis_hash);
+        let mut blocks = HashMap::new();
+        blocks.insert(genesis_hash, genesis);
+
+        Self {
+            id,
+            public_key: private_key.public_key(),
+            private_key,
+            validators,
+            pacemaker: Pacemaker::new(config, 0),
+            blocks,
+            genesis_hash,
+            high_qc: genesis_qc.clone(),
+            locked_qc: genesis_qc,
+            last_voted_view: ViewNumber::new(0),
+            last_proposed_view: ViewNumber::new(0),
+            last_tc: None,
+            votes: HashMap::new(),
+            committed: Vec::new(),
+            last_committed: genesis_hash,
+            pending_commands: Vec::new(),
+            now: 0,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn current_view(&self) -> ViewNumber {
+        self.pacemaker.current_view()
+    }
+
+    pub fn pacemaker(&self) -> &Pacemaker {
+        &self.pacemaker
+    }
+
+    pub fn high_qc(&self) -> &QuorumCertificate {
+        &self.high_qc
+    }
+
+    pub fn locked_qc(&self) -> &QuorumCertificate {
+        &self.locked_qc
+    }
+
+    pub fn get_block(&self, hash: &Hash) -> Option<&Block> {
+        self.blocks.get(hash)
+    }
+
+    /// 已提交的区块（按高度排列）
+    pub fn committed_blocks(&self) -> &[Block] {
+        &self.committed
+    }
+
+    /// 已提交的最高区块高度
+    pub fn committed_height(&self) -> Height {
+        self.blocks.get(&self.last_committed).map_or(Height::new(0), Block::height)
+    }
+
+    /// 视图的 Leader（round-robin）
+    pub fn leader(&self, view: ViewNumber) -> u64 {
+        view.as_u64() % self.validators.len() as u64
+    }
+
+    /// 提交命令，作为 Leader 时打包进下一个提案
+    pub fn submit(&mut self, command: Vec<u8>) {
+        self.pending_commands.push(command);
+    }
+
+    /// 启动：视图 1 的 Leader 发出第一个提案
+    pub fn start(&mut self, now: u64) -> Vec<Outbound> {
+        self.now = now;
+        self.try_propose()
+    }
+
+    /// 推进逻辑时钟，当前视图超时则广播超时投票
+    pub fn tick(&mut self, now: u64) -> Vec<Outbound> {
+        self.now = now;
+        let Some(view) = self.pacemaker.on_tick(now) else {
+            return Vec::new();
+        };
+
+        let signature = self.private_key.sign(&(view.as_u64(), self.high_qc.view().as_u64()));
+        let vote = TimeoutVote::new(view, self.high_qc.clone(), self.public_key, signature);
+        vec![Outbound {
+            to: Recipient::All,
+            message: ChainedMessage::Timeout { vote, tc: self.last_tc.clone() },
+        }]
+    }
+
+    /// 处理收到的消息
+    pub fn handle_message(
+        &mut self,
+        message: ChainedMessage,
+        now: u64,
+    ) -> Result<Vec<Outbound>, ConsensusError> {
+        self.now = now;
+        match message {
+            ChainedMessage::Proposal { block, tc } => self.on_proposal(block, tc),
+            ChainedMessage::Vote(vote) => self.on_vote(vote),
+            ChainedMessage::Timeout { vote, tc } => self.on_timeout(vote, tc),
+        }
+    }
+
+    /// 处理提案：同步视图 → 校验 → 安全规则 → 投票 → 锁定/提交
+    fn on_proposal(
+        &mut self,
+        block: Block,
+        tc: Option<TimeoutCertificate>,
+    ) -> Result<Vec<Outbound>, ConsensusError> {
+        if let Some(tc) = &tc {
+            self.process_tc(tc)?;
+        }
+        if !self.blocks.contains_key(&block.parent_hash()) {
+            return Err(ConsensusError::MissingParent);
+        }
+        if block.justify().block_hash() != block.parent_hash() {
+            return Err(ConsensusError::InvalidQC);
+        }
+        self.process_qc(block.justify())?;
+
+        let view = block.view();
+        if view != self.current_view() {
+            return Err(ConsensusError::InvalidView);
+        }
+        if self.validators.get(self.leader(view) as usize) != Some(&block.proposer()) {
+            return Err(ConsensusError::InvalidProposer);
+        }
+
+        // safeNode：扩展自锁定区块，或 justify 比锁更新（活性）
+        let safe = self.extends(&block, self.locked_qc.block_hash())
+            || block.justify().view() > self.locked_qc.view();
+        let block_hash = block.hash();
+        self.blocks.insert(block_hash, block);
+        self.update_chain(block_hash);
+        if !safe {
+            return Err(ConsensusError::ConflictWithLockedQC);
+        }
+
+        let mut outbound = Vec::new();
+        if view > self.last_voted_view {
+            self.last_voted_view = view;
+            outbound.push(Outbound {
+                to: Recipient::Node(self.leader(view.next())),
+                message: ChainedMessage::Vote(self.sign_vote(block_hash, view)),
+            });
+        }
+        outbound.extend(self.try_propose());
+        Ok(outbound)
+    }
+
+    /// 处理投票：作为下一视图的 Leader 聚合 QC，随即提案
+    fn on_vote(&mut self, vote: Vote) -> Result<Vec<Outbound>, ConsensusError> {
+        if !self.validators.contains(&vote.voter()) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        if vote.phase() != Phase::Generic || !vote.verify() {
+            return Err(ConsensusError::InvalidSignature);
+        }
+        // 不是下一视图的 Leader，或投票已过期
+        if self.leader(vote.view().next()) != self.id || vote.view().next() < self.current_view() {
+            return Ok(Vec::new());
+        }
+
+        let key = (vote.block_hash(), vote.view());
+        let votes = self.votes.entry(key).or_default();
+        votes.insert(vote.voter(), vote);
+        if votes.len() < quorum_size(self.validators.len()) {
+            return Ok(Vec::new());
+        }
+
+        let mut qc = QuorumCertificate::new(key.0, key.1, Phase::Generic);
+        for vote in self.votes.remove(&key).unwrap_or_default().into_values() {
+            qc.add_vote(vote);
+        }
+        self.process_qc(&qc)?;
+        Ok(self.try_propose())
+    }
+
+    /// 处理超时投票：借其 high_qc / TC 同步视图，2f+1 票形成 TC
+    fn on_timeout(
+        &mut self,
+        vote: TimeoutVote,
+        tc: Option<TimeoutCertificate>,
+    ) -> Result<Vec<Outbound>, ConsensusError> {
+        if let Some(tc) = &tc {
+            self.process_tc(tc)?;
+        }
+        if !self.validators.contains(&vote.voter()) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        self.process_qc(vote.high_qc())?;
+
+        if let Some(tc) = self.pacemaker.add_timeout(vote, self.validators.len()) {
+            self.process_tc(&tc)?;
+        }
+        Ok(self.try_propose())
+    }
+
+    /// 校验 QC，更新 high_qc 并推进视图
+    fn process_qc(&mut self, qc: &QuorumCertificate) -> Result<(), ConsensusError> {
+        self.validate_qc(qc)?;
+        // 只接受本地已有区块的 QC 作为 high_qc，保证提案时父区块可用
+        if qc.view() > self.high_qc.view() && self.blocks.contains_key(&qc.block_hash()) {
+            self.high_qc = qc.clone();
+        }
+        if self.pacemaker.advance_with_qc(qc.view(), self.now) {
+            self.last_tc = None;
+            self.prune_votes();
+        }
+        Ok(())
+    }
+
+    /// 校验 TC 并推进视图
+    fn process_tc(&mut self, tc: &TimeoutCertificate) -> Result<(), ConsensusError> {
+        let voters = tc.voters();
+        let distinct = voters.windows(2).all(|pair| pair[0] < pair[1]);
+        if !distinct
+            || voters.len() < quorum_size(self.validators.len())
+            || !voters.iter().all(|voter| self.validators.contains(voter))
+        {
+            return Err(ConsensusError::InvalidTimeoutCertificate);
+        }
+
+        self.process_qc(tc.high_qc())?;
+        if self.pacemaker.advance_with_tc(tc, self.now) {
+            self.last_tc = Some(tc.clone());
+            self.prune_votes();
+        }
+        Ok(())
+    }
+
+    fn validate_qc(&self, qc: &QuorumCertificate) -> Result<(), ConsensusError> {
+        let valid = if qc.view().as_u64() == 0 {
+            qc.block_hash() == self.genesis_hash
+        } else {
+            qc.phase() == Phase::Generic
+                && qc.has_quorum(self.validators.len())
+                && qc.votes().keys().all(|voter| self.validators.contains(voter))
+        };
+        if valid { Ok(()) } else { Err(ConsensusError::InvalidQC) }
+    }
+
+    /// 两链锁定、三链提交
+    fn update_chain(&mut self, block_hash: Hash) {
+        // b* → b''
+        let Some(b2) = self.block_justified_by(block_hash) else { return };
+        // b'' → b'
+        let Some(b1) = self.block_justified_by(b2.hash()) else { return };
+        if b2.justify().view() > self.locked_qc.view() {
+            self.locked_qc = b2.justify().clone();
+        }
+
+        // b' → b，且 b'' → b' → b 直接父子相连
+        let b0_hash = b1.justify().block_hash();
+        if b2.parent_hash() == b1.hash() && b1.parent_hash() == b0_hash {
+            self.commit(b0_hash);
+        }
+    }
+
+    fn block_justified_by(&self, block_hash: Hash) -> Option<Block> {
+        let block = self.blocks.get(&block_hash)?;
+        self.blocks.get(&block.justify().block_hash()).cloned()
+    }
+
+    /// 提交区块及其尚未提交的祖先
+    fn commit(&mut self, block_hash: Hash) {
+        let committed_height = self.committed_height();
+        let mut chain = Vec::new();
+        let mut cursor = block_hash;
+        while let Some(block) = self.blocks.get(&cursor) {
+            if block.height() <= committed_height {
+                break;
+            }
+            chain.push(block.clone());
+            cursor = block.parent_hash();
+        }
+        if chain.is_empty() {
+            return;
+        }
+        debug_assert_eq!(cursor, self.last_committed, "conflicting commit");
+
+        self.last_committed = block_hash;
+        self.committed.extend(chain.into_iter().rev());
+    }
+
+    /// `block` 是否（间接）扩展自 `ancestor`
+    fn extends(&self, block: &Block, ancestor: Hash) -> bool {
+        let mut cursor = block.parent_hash();
+        while let Some(parent) = self.blocks.get(&cursor) {
+            if parent.hash() == ancestor {
+                return true;
+            }
+            cursor = parent.parent_hash();
+        }
+        false
+    }
+
+    /// 当前视图的 Leader 在拿到上一视图的 QC 或 TC 后提案
+    fn try_propose(&mut self) -> Vec<Outbound> {
+        let view = self.current_view();
+        if self.leader(view) != self.id || self.last_proposed_view >= view {
+            return Vec::new();
+        }
+        let tc = if self.high_qc.view().next() == view {
+            None
+        } else {
+            match &self.last_tc {
+                Some(tc) if tc.view().next() == view => Some(tc.clone()),
+                _ => return Vec::new(),
+            }
+        };
+        let Some(parent) = self.blocks.get(&self.high_qc.block_hash()) else {
+            return Vec::new();
+        };
+
+        let block = Block::new(
+            parent,
+            view,
+            self.public_key,
+            self.high_qc.clone(),
+            std::mem::take(&mut self.pending_commands),
+        );
+        self.last_proposed_view = view;
+        vec![Outbound { to: Recipient::All, message: ChainedMessage::Proposal { block, tc } }]
+    }
+
+    fn sign_vote(&self, block_hash: Hash, view: ViewNumber) -> Vote {
+        let mut sign_data = Vec::new();
+        sign_data.extend_from_slice(block_hash.as_bytes());
+        sign_data.extend_from_slice(&view.as_u64().to_le_bytes());
+        sign_data.push(Phase::Generic as u8);
+
+        let signature = self.private_key.sign(&sign_data);
+        Vote::new(block_hash, view, Phase::Generic, self.public_key, signature)
+    }
+
+    /// 丢弃过期视图的投票
+    fn prune_votes(&mut self) {
+        let current_view = self.current_view();
+        self.votes.retain(|(_, view), _| view.next() >= current_view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_replicas(n: u64) -> Vec<ChainedHotStuff> {
+        let validators: Vec<_> = (0..n).map(|i| PrivateKey::from_u64(i).public_key()).collect();
+        (0..n)
+            .map(|i| {
+                let config = PacemakerConfig::default();
+                ChainedHotStuff::new(i, PrivateKey::from_u64(i), validators.clone(), config)
+            })
+            .collect()
+    }
+
+    fn proposal_of(outbound: &[Outbound]) -> (Block, Option<TimeoutCertificate>) {
+        match outbound.iter().find(|out| matches!(out.message, ChainedMessage::Proposal { .. })) {
+            Some(Outbound { message: ChainedMessage::Proposal { block, tc }, .. }) => {
+                (block.clone(), tc.clone())
+            }
+            _ => panic!("Expected proposal"),
+        }
+    }
+
+    #[test]
+    fn test_vote_goes_to_next_leader() {
+        let mut replicas = create_replicas(4);
+        let outbound = replicas[1].start(0);
+        let (block, tc) = proposal_of(&outbound);
+        assert!(tc.is_none());
+
+        let replies =
+            replicas[0].handle_message(ChainedMessage::Proposal { block, tc }, 0).unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].to, Recipient::Node(2));
+
+        // 同一视图不重复投票
+        let (block, tc) = proposal_of(&outbound);
+        let replies =
+            replicas[0].handle_message(ChainedMessage::Proposal { block, tc }, 0).unwrap();
+        assert!(replies.is_empty());
+    }
+
+    #[test]
+    fn test_reject_proposal_from_non_leader() {
+        let mut replicas = create_replicas(4);
+        let genesis_hash = Block::genesis().hash();
+        let block = Block::new(
+            &Block::genesis(),
+            ViewNumber::new(1),
+            replicas[2].public_key(),
+            QuorumCertificate::genesis_with_hash(genesis_hash),
+            Vec::new(),
+        );
+
+        let result = replicas[0].handle_message(ChainedMessage::Proposal { block, tc: None }, 0);
+        assert_eq!(result.unwrap_err(), ConsensusError::InvalidProposer);
+    }
+}
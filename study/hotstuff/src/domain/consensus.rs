@@ -273,8 +273,8 @@ impl HotStuffConsensus {
                 // Commit QC 形成，可以决定提交
                 self.try_commit(&qc);
             }
-            Phase::Decide => {
-                // Decide QC（通常不会直接形成）
+            Phase::Decide | Phase::Generic => {
+                // Decide QC（通常不会直接形成）；Generic 属于链式 HotStuff
             }
         }
     }
@@ -334,6 +334,9 @@ pub enum ConsensusError {
     InvalidQC,
     ConflictWithLockedQC,
     InvalidSignature,
+    InvalidProposer,
+    UnknownValidator,
+    InvalidTimeoutCertificate,
 }
 
 impl std::fmt::Display for ConsensusError {
@@ -344,6 +347,9 @@ impl std::fmt::Display for ConsensusError {
             ConsensusError::InvalidQC => write!(f, "Invalid quorum certificate"),
             ConsensusError::ConflictWithLockedQC => write!(f, "Conflicts with locked QC"),
             ConsensusError::InvalidSignature => write!(f, "Invalid signature"),
+            ConsensusError::InvalidProposer => write!(f, "Proposer is not the leader of the view"),
+            ConsensusError::UnknownValidator => write!(f, "Unknown validator"),
+            ConsensusError::InvalidTimeoutCertificate => write!(f, "Invalid timeout certificate"),
         }
     }
 }
@@ -59,6 +59,8 @@ pub enum Phase {
     PreCommit,
     Commit,
     Decide,
+    /// 链式 HotStuff 的单一投票阶段（各阶段在相邻区块上流水线化）
+    Generic,
 }
 
 impl std::fmt::Display for Phase {
@@ -68,6 +70,7 @@ impl std::fmt::Display for Phase {
             Phase::PreCommit => write!(f, "PRECOMMIT"),
             Phase::Commit => write!(f, "COMMIT"),
             Phase::Decide => write!(f, "DECIDE"),
+            Phase::Generic => write!(f, "GENERIC"),
         }
     }
 }
@@ -129,6 +132,21 @@ impl Block {
         block
     }
 
+    /// 由各字段重建区块（如从网络解码），哈希重新计算
+    pub fn from_parts(
+        parent_hash: Hash,
+        view: ViewNumber,
+        height: Height,
+        proposer: PublicKey,
+        justify: QuorumCertificate,
+        commands: Vec<Vec<u8>>,
+    ) -> Self {
+        let mut block =
+            Self { hash: Hash::zero(), parent_hash, view, height, proposer, justify, commands };
+        block.hash = block.compute_hash();
+        block
+    }
+
     /// 计算区块哈希
     fn compute_hash(&self) -> Hash {
         let mut data = Vec::new();
@@ -281,9 +299,7 @@ impl QuorumCertificate {
 
     /// 检查是否达到仲裁（2f+1 票）
     pub fn has_quorum(&self, total_nodes: usize) -> bool {
-        let f = (total_nodes - 1) / 3; // 最多容忍 f 个拜占庭节点
-        let quorum_size = 2 * f + 1;
-        self.votes.len() >= quorum_size
+        self.votes.len() >= quorum_size(total_nodes)
     }
 
     pub fn block_hash(&self) -> Hash {
@@ -307,6 +323,104 @@ impl QuorumCertificate {
     }
 }
 
+/// 仲裁大小：n 个节点最多容忍 f = (n-1)/3 个拜占庭节点，需要 2f+1 票
+pub const fn quorum_size(total_nodes: usize) -> usize {
+    2 * ((total_nodes - 1) / 3) + 1
+}
+
+/// 超时投票（视图同步）
+///
+/// 节点在视图超时后广播，携带自己的 high_qc，供下一个 Leader 选择扩展点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutVote {
+    /// 超时的视图
+    view: ViewNumber,
+    /// 投票者的最高 QC
+    high_qc: QuorumCertificate,
+    /// 投票者公钥
+    voter: PublicKey,
+    /// 签名
+    signature: Signature,
+}
+
+impl TimeoutVote {
+    pub fn new(
+        view: ViewNumber,
+        high_qc: QuorumCertificate,
+        voter: PublicKey,
+        signature: Signature,
+    ) -> Self {
+        Self { view, high_qc, voter, signature }
+    }
+
+    pub fn view(&self) -> ViewNumber {
+        self.view
+    }
+
+    pub fn high_qc(&self) -> &QuorumCertificate {
+        &self.high_qc
+    }
+
+    pub fn voter(&self) -> PublicKey {
+        self.voter
+    }
+
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+}
+
+/// 超时证书（Timeout Certificate）：2f+1 个节点在同一视图超时的证明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutCertificate {
+    /// 超时的视图
+    view: ViewNumber,
+    /// 所有超时投票中最高的 QC
+    high_qc: QuorumCertificate,
+    /// 投票者集合
+    voters: Vec<PublicKey>,
+}
+
+impl TimeoutCertificate {
+    /// 由同一视图的超时投票聚合（调用方保证投票视图一致且非空）
+    pub fn from_votes<'a>(
+        view: ViewNumber,
+        votes: impl IntoIterator<Item = &'a TimeoutVote>,
+    ) -> Self {
+        let mut high_qc = QuorumCertificate::genesis();
+        let mut voters = Vec::new();
+        for vote in votes {
+            if vote.high_qc.view() > high_qc.view() || voters.is_empty() {
+                high_qc = vote.high_qc.clone();
+            }
+            voters.push(vote.voter);
+        }
+        voters.sort();
+        Self { view, high_qc, voters }
+    }
+
+    /// 由各字段重建（如从网络解码），不做校验
+    pub fn from_parts(
+        view: ViewNumber,
+        high_qc: QuorumCertificate,
+        voters: Vec<PublicKey>,
+    ) -> Self {
+        Self { view, high_qc, voters }
+    }
+
+    pub fn view(&self) -> ViewNumber {
+        self.view
+    }
+
+    pub fn high_qc(&self) -> &QuorumCertificate {
+        &self.high_qc
+    }
+
+    pub fn voters(&self) -> &[PublicKey] {
+        &self.voters
+    }
+}
+
 /// 提案消息
 #[derive(Debug, Clone)]
 pub struct Proposal {
@@ -362,6 +476,34 @@ mod tests {
         assert!(qc.has_quorum(4));
     }
 
+    #[test]
+    fn test_timeout_certificate_picks_highest_qc() {
+        let low = QuorumCertificate::new(Hash::compute(&"low"), ViewNumber::new(2), Phase::Generic);
+        let high =
+            QuorumCertificate::new(Hash::compute(&"high"), ViewNumber::new(5), Phase::Generic);
+        let votes = [
+            TimeoutVote::new(
+                ViewNumber::new(6),
+                low.clone(),
+                PublicKey::from_u64(2),
+                Signature::zero(),
+            ),
+            TimeoutVote::new(
+                ViewNumber::new(6),
+                high.clone(),
+                PublicKey::from_u64(0),
+                Signature::zero(),
+            ),
+            TimeoutVote::new(ViewNumber::new(6), low, PublicKey::from_u64(1), Signature::zero()),
+        ];
+
+        let tc = TimeoutCertificate::from_votes(ViewNumber::new(6), &votes);
+        assert_eq!(tc.high_qc(), &high);
+        assert_eq!(tc.voters().len(), 3);
+        assert_eq!(quorum_size(4), 3);
+        assert_eq!(quorum_size(7), 5);
+    }
+
     #[test]
     fn test_view_number_increment() {
         let view = ViewNumber::new(5);
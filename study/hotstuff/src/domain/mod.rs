@@ -2,6 +2,8 @@
 //!
 //! 包含 HotStuff 共识的核心业务逻辑
 
+pub mod chained;
 pub mod consensus;
 pub mod entities;
 pub mod node;
+pub mod pacemaker;
@@ -228,7 +228,7 @@ impl Node {
                 Phase::Commit
             }
             Phase::Commit => Phase::Decide,
-            Phase::Decide => return Vec::new(),
+            Phase::Decide | Phase::Generic => return Vec::new(),
         };
 
         // 所有节点对下一阶段投票
@@ -0,0 +1,192 @@
+//! Pacemaker：视图同步与指数退避超时
+//!
+//! - 看到视图 v 的 QC（或 TC）即进入视图 v+1，落后节点借此追上
+//! - 视图超时后广播 [`TimeoutVote`]，2f+1 个超时投票形成 [`TimeoutCertificate`]
+//! - 连续超时的视图超时时间指数增长（封顶），一旦由 QC 推进则恢复基础超时
+//!
+//! 时间使用调用方驱动的逻辑时钟（毫秒），便于在模拟网络中确定性地测试。
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::entities::{TimeoutCertificate, TimeoutVote, ViewNumber, quorum_size};
+use crate::crypto::PublicKey;
+
+/// Pacemaker 配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacemakerConfig {
+    /// 基础视图超时（毫秒）
+    pub base_timeout_ms: u64,
+    /// 超时上限（毫秒）
+    pub max_timeout_ms: u64,
+}
+
+impl Default for PacemakerConfig {
+    fn default() -> Self {
+        Self { base_timeout_ms: 1_000, max_timeout_ms: 60_000 }
+    }
+}
+
+/// Pacemaker
+#[derive(Debug)]
+pub struct Pacemaker {
+    config: PacemakerConfig,
+    /// 当前视图
+    current_view: ViewNumber,
+    /// 连续超时次数（决定退避倍数）
+    consecutive_timeouts: u32,
+    /// 当前视图的超时截止时间
+    deadline: u64,
+    /// 收集中的超时投票
+    timeouts: BTreeMap<ViewNumber, HashMap<PublicKey, TimeoutVote>>,
+}
+
+impl Pacemaker {
+    /// 创建 Pacemaker，从视图 1 开始计时
+    pub fn new(config: PacemakerConfig, now: u64) -> Self {
+        Self {
+            config,
+            current_view: ViewNumber::new(1),
+            consecutive_timeouts: 0,
+            deadline: now + config.base_timeout_ms,
+            timeouts: BTreeMap::new(),
+        }
+    }
+
+    pub fn current_view(&self) -> ViewNumber {
+        self.current_view
+    }
+
+    pub fn deadline(&self) -> u64 {
+        self.deadline
+    }
+
+    pub fn consecutive_timeouts(&self) -> u32 {
+        self.consecutive_timeouts
+    }
+
+    /// 当前超时时间：base × 2^连续超时次数，不超过上限
+    pub fn timeout_duration(&self) -> u64 {
+        let factor = 1u64.checked_shl(self.consecutive_timeouts).unwrap_or(u64::MAX);
+        self.config.base_timeout_ms.saturating_mul(factor).min(self.config.max_timeout_ms)
+    }
+
+    /// 推进逻辑时钟
+    ///
+    /// 当前视图超时则返回该视图（调用方应广播超时投票），并以加倍的超时时间重新计时，
+    /// 以便在 TC 迟迟无法形成（如网络分区）时周期性重发
+    pub fn on_tick(&mut self, now: u64) -> Option<ViewNumber> {
+        if now < self.deadline {
+            return None;
+        }
+        self.consecutive_timeouts = self.consecutive_timeouts.saturating_add(1);
+        self.deadline = now + self.timeout_duration();
+        Some(self.current_view)
+    }
+
+    /// 看到视图 `qc_view` 的 QC：进入下一视图并恢复基础超时
+    pub fn advance_with_qc(&mut self, qc_view: ViewNumber, now: u64) -> bool {
+        if qc_view.next() <= self.current_view {
+            return false;
+        }
+        self.consecutive_timeouts = 0;
+        self.enter_view(qc_view.next(), now);
+        true
+    }
+
+    /// 看到视图 `tc.view` 的 TC：进入下一视图，超时时间保持退避
+    pub fn advance_with_tc(&mut self, tc: &TimeoutCertificate, now: u64) -> bool {
+        if tc.view().next() <= self.current_view {
+            return false;
+        }
+        self.enter_view(tc.view().next(), now);
+        true
+    }
+
+    /// 收集超时投票，达到 2f+1 时形成 TC
+    ///
+    /// 过期视图的投票被忽略；同一视图的重复投票只计一次
+    pub fn add_timeout(
+        &mut self,
+        vote: TimeoutVote,
+        total_nodes: usize,
+    ) -> Option<TimeoutCertificate> {
+        let view = vote.view();
+        if view < self.current_view {
+            return None;
+        }
+
+        let votes = self.timeouts.entry(view).or_default();
+        votes.insert(vote.voter(), vote);
+        if votes.len() < quorum_size(total_nodes) {
+            return None;
+        }
+
+        let votes = self.timeouts.remove(&view)?;
+        Some(TimeoutCertificate::from_votes(view, votes.values()))
+    }
+
+    fn enter_view(&mut self, view: ViewNumber, now: u64) {
+        self.current_view = view;
+        self.deadline = now + self.timeout_duration();
+        self.timeouts = self.timeouts.split_off(&view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Signature;
+    use crate::domain::entities::QuorumCertificate;
+
+    fn config() -> PacemakerConfig {
+        PacemakerConfig { base_timeout_ms: 100, max_timeout_ms: 500 }
+    }
+
+    fn timeout_vote(view: u64, voter: u64) -> TimeoutVote {
+        TimeoutVote::new(
+            ViewNumber::new(view),
+            QuorumCertificate::genesis(),
+            PublicKey::from_u64(voter),
+            Signature::zero(),
+        )
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let mut pacemaker = Pacemaker::new(config(), 0);
+        assert_eq!(pacemaker.on_tick(99), None);
+
+        // 100 → 200 → 400 → 500（封顶）
+        let mut now = 100;
+        for expected in [200, 400, 500, 500] {
+            assert_eq!(pacemaker.on_tick(now), Some(ViewNumber::new(1)));
+            assert_eq!(pacemaker.deadline(), now + expected);
+            now += expected;
+        }
+
+        // TC 推进保持退避，QC 推进恢复基础超时
+        let tc = TimeoutCertificate::from_votes(ViewNumber::new(1), &[timeout_vote(1, 0)]);
+        assert!(pacemaker.advance_with_tc(&tc, now));
+        assert_eq!(pacemaker.deadline(), now + 500);
+        assert!(pacemaker.advance_with_qc(ViewNumber::new(2), now));
+        assert_eq!(pacemaker.current_view(), ViewNumber::new(3));
+        assert_eq!(pacemaker.deadline(), now + 100);
+        assert!(!pacemaker.advance_with_qc(ViewNumber::new(1), now));
+    }
+
+    #[test]
+    fn test_timeout_certificate_formation() {
+        let mut pacemaker = Pacemaker::new(config(), 0);
+
+        assert!(pacemaker.add_timeout(timeout_vote(1, 0), 4).is_none());
+        assert!(pacemaker.add_timeout(timeout_vote(1, 0), 4).is_none());
+        assert!(pacemaker.add_timeout(timeout_vote(1, 1), 4).is_none());
+        let tc = pacemaker.add_timeout(timeout_vote(1, 2), 4).unwrap();
+        assert_eq!(tc.view(), ViewNumber::new(1));
+        assert_eq!(tc.voters().len(), 3);
+
+        // 进入视图 2 后，视图 1 的迟到投票被忽略
+        assert!(pacemaker.advance_with_tc(&tc, 10));
+        assert!(pacemaker.add_timeout(timeout_vote(1, 3), 4).is_none());
+    }
+}
@@ -5,9 +5,14 @@
 //! - Pre-commit Phase
 //! - Commit Phase
 //!
+//! 另提供链式（流水线）HotStuff 与 Pacemaker（指数退避超时、视图同步），
+//! 见 [`domain::chained`] 与 [`domain::pacemaker`]；[`transport`] 提供基于 TCP 的
+//! 多进程运行支持。
+//!
 //! 架构遵循 Clean Architecture 原则，分层如下：
 //! - domain: 核心领域模型和业务逻辑
 //! - crypto: 密码学原语（简化实现）
+//! - transport: 网络传输层（长度前缀分帧 TCP、签名信封、断线重连）
 //!
 //! # 示例
 //!
@@ -26,10 +31,13 @@
 
 pub mod crypto;
 pub mod domain;
+pub mod transport;
 
 #[cfg(test)]
 mod tests;
 
+pub use domain::chained::{ChainedHotStuff, ChainedMessage, Outbound, Recipient};
 pub use domain::consensus::HotStuffConsensus;
 pub use domain::entities::{Block, QuorumCertificate, Vote};
 pub use domain::node::Node;
+pub use domain::pacemaker::{Pacemaker, PacemakerConfig};
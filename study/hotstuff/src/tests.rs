@@ -237,3 +237,284 @@ mod integration_tests {
         assert_eq!(network.get_node(2).role(), crate::domain::node::NodeRole::Leader);
     }
 }
+
+/// 链式 HotStuff 集成测试 - 带延迟、崩溃与分区的模拟网络
+#[cfg(test)]
+mod chained_integration_tests {
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashSet};
+
+    use crate::crypto::PrivateKey;
+    use crate::domain::chained::{ChainedHotStuff, ChainedMessage, Outbound, Recipient};
+    use crate::domain::pacemaker::PacemakerConfig;
+
+    /// 单跳网络延迟（毫秒）
+    const LATENCY_MS: u64 = 10;
+    /// 时钟步长（毫秒）
+    const STEP_MS: u64 = 5;
+
+    /// (投递时间, 消息序号, 发送方, 接收方)
+    type Delivery = (u64, usize, u64, u64);
+
+    /// 模拟网络：按投递时间排序的消息队列
+    struct SimNetwork {
+        nodes: Vec<ChainedHotStuff>,
+        in_flight: BinaryHeap<Reverse<Delivery>>,
+        messages: Vec<Option<ChainedMessage>>,
+        now: u64,
+        /// 崩溃的节点：不收不发
+        crashed: HashSet<u64>,
+        /// 分区：不同组之间的消息被丢弃
+        partition: Option<Vec<HashSet<u64>>>,
+    }
+
+    impl SimNetwork {
+        fn new(num_nodes: u64) -> Self {
+            let validators: Vec<_> =
+                (0..num_nodes).map(|i| PrivateKey::from_u64(i).public_key()).collect();
+            let config = PacemakerConfig { base_timeout_ms: 100, max_timeout_ms: 800 };
+            let nodes = (0..num_nodes)
+                .map(|i| {
+                    ChainedHotStuff::new(i, PrivateKey::from_u64(i), validators.clone(), config)
+                })
+                .collect();
+
+            Self {
+                nodes,
+                in_flight: BinaryHeap::new(),
+                messages: Vec::new(),
+                now: 0,
+                crashed: HashSet::new(),
+                partition: None,
+            }
+        }
+
+        fn start(&mut self) {
+            for id in 0..self.nodes.len() as u64 {
+                let outbound = self.nodes[id as usize].start(self.now);
+                self.send(id, outbound);
+            }
+        }
+
+        fn connected(&self, from: u64, to: u64) -> bool {
+            if self.crashed.contains(&from) || self.crashed.contains(&to) {
+                return false;
+            }
+            match &self.partition {
+                Some(groups) => {
+                    groups.iter().any(|group| group.contains(&from) == group.contains(&to))
+                }
+                None => true,
+            }
+        }
+
+        fn send(&mut self, from: u64, outbound: Vec<Outbound>) {
+            for Outbound { to, message } in outbound {
+                let recipients = match to {
+                    Recipient::All => (0..self.nodes.len() as u64).collect(),
+                    Recipient::Node(id) => vec![id],
+                };
+                for to in recipients {
+                    // 分区/崩溃在发送与投递时都会判定，被丢弃的消息不会重传
+                    if !self.connected(from, to) {
+                        continue;
+                    }
+                    let seq = self.messages.len();
+                    self.messages.push(Some(message.clone()));
+                    self.in_flight.push(Reverse((self.now + LATENCY_MS, seq, from, to)));
+                }
+            }
+        }
+
+        /// 运行到指定逻辑时间
+        fn run_until(&mut self, until: u64) {
+            while self.now < until {
+                self.now += STEP_MS;
+
+                while let Some(Reverse((at, seq, from, to))) = self.in_flight.peek().copied() {
+                    if at > self.now {
+                        break;
+                    }
+                    self.in_flight.pop();
+                    let message = self.messages[seq].take().unwrap();
+                    if !self.connected(from, to) {
+                        continue;
+                    }
+                    // 无效/过期消息被拒绝属于正常情况
+                    if let Ok(outbound) = self.nodes[to as usize].handle_message(message, self.now)
+                    {
+                        self.send(to, outbound);
+                    }
+                }
+
+                for id in 0..self.nodes.len() as u64 {
+                    if self.crashed.contains(&id) {
+                        continue;
+                    }
+                    let outbound = self.nodes[id as usize].tick(self.now);
+                    self.send(id, outbound);
+                }
+            }
+        }
+
+        fn submit(&mut self, command: &str) {
+            for node in &mut self.nodes {
+                node.submit(command.as_bytes().to_vec());
+            }
+        }
+
+        fn committed_height(&self, id: u64) -> u64 {
+            self.nodes[id as usize].committed_height().as_u64()
+        }
+
+        /// 存活节点中最低的已提交高度
+        fn min_live_height(&self) -> u64 {
+            (0..self.nodes.len() as u64)
+                .filter(|id| !self.crashed.contains(id))
+                .map(|id| self.committed_height(id))
+                .min()
+                .unwrap()
+        }
+
+        /// 安全性：任意两个节点的已提交链互为前缀
+        fn assert_consistent(&self) {
+            for a in &self.nodes {
+                for b in &self.nodes {
+                    let common = a.committed_blocks().len().min(b.committed_blocks().len());
+                    let prefix_a: Vec<_> =
+                        a.committed_blocks()[..common].iter().map(|b| b.hash()).collect();
+                    let prefix_b: Vec<_> =
+                        b.committed_blocks()[..common].iter().map(|b| b.hash()).collect();
+                    assert_eq!(prefix_a, prefix_b, "node {} and {} diverged", a.id(), b.id());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_chained_happy_path() {
+        let mut network = SimNetwork::new(4);
+        network.submit("tx1");
+        network.start();
+        network.run_until(1_000);
+
+        // 每个视图约 2 跳（提案 + 投票），不应出现超时
+        assert!(network.min_live_height() >= 20, "height {}", network.min_live_height());
+        for node in &network.nodes {
+            assert_eq!(node.pacemaker().consecutive_timeouts(), 0);
+            let heights: Vec<_> =
+                node.committed_blocks().iter().map(|b| b.height().as_u64()).collect();
+            assert_eq!(heights, (1..=heights.len() as u64).collect::<Vec<_>>());
+        }
+        // 视图 1 的 Leader 打包了命令
+        assert_eq!(network.nodes[0].committed_blocks()[0].commands(), &[b"tx1".to_vec()]);
+        network.assert_consistent();
+    }
+
+    #[test]
+    fn test_chained_leader_crash() {
+        let mut network = SimNetwork::new(4);
+        network.start();
+        network.run_until(300);
+        let before = network.min_live_height();
+        assert!(before > 0);
+
+        // Node 2 崩溃：它担任 Leader 的视图以及发给它的投票都会超时，由 TC 推进
+        network.crashed.insert(2);
+        network.run_until(5_000);
+
+        let after = network.min_live_height();
+        assert!(after >= before + 10, "progress stalled: {} -> {}", before, after);
+        network.assert_consistent();
+    }
+
+    #[test]
+    fn test_chained_partition_and_heal() {
+        let mut network = SimNetwork::new(4);
+        network.start();
+        network.run_until(300);
+
+        // 2/2 分区：任何一侧都凑不够 2f+1 = 3 票
+        network.partition = Some(vec![HashSet::from([0, 1]), HashSet::from([2, 3])]);
+        network.run_until(500);
+        let stalled: Vec<_> = (0..4).map(|id| network.committed_height(id)).collect();
+        network.run_until(3_000);
+        let during: Vec<_> = (0..4).map(|id| network.committed_height(id)).collect();
+        assert_eq!(stalled, during, "no commits without a quorum");
+        // 连续超时触发指数退避
+        assert!(network.nodes.iter().all(|node| node.pacemaker().consecutive_timeouts() >= 3));
+
+        // 分区恢复后，超时重发与 high_qc 同步视图，共识恢复
+        network.partition = None;
+        network.run_until(6_000);
+        assert!(network.min_live_height() >= during.iter().max().unwrap() + 5);
+        let views: HashSet<_> = network.nodes.iter().map(|node| node.current_view()).collect();
+        assert!(views.len() <= 2, "views not synchronized: {:?}", views);
+        network.assert_consistent();
+    }
+}
+
+/// TCP 集成测试 - 4 个副本经本机 TCP 运行链式 HotStuff
+#[cfg(test)]
+mod tcp_integration_tests {
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+    use tokio::sync::watch;
+
+    use crate::crypto::PrivateKey;
+    use crate::domain::chained::ChainedHotStuff;
+    use crate::domain::pacemaker::PacemakerConfig;
+    use crate::transport::{NetworkConfig, TcpTransport, demo_signing_key, run_replica};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tcp_cluster_commits() {
+        let mut config = NetworkConfig::local(4, 0);
+        let mut listeners = Vec::new();
+        for peer in &mut config.peers {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            peer.address = listener.local_addr().unwrap();
+            listeners.push(listener);
+        }
+
+        let validators: Vec<_> = (0..4).map(|i| PrivateKey::from_u64(i).public_key()).collect();
+        let pacemaker = PacemakerConfig { base_timeout_ms: 500, max_timeout_ms: 4_000 };
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let mut handles = Vec::new();
+        for (id, listener) in (0..4).zip(listeners) {
+            let (transport, inbound) =
+                TcpTransport::start(id, demo_signing_key(id), &config, listener).unwrap();
+            let mut replica =
+                ChainedHotStuff::new(id, PrivateKey::from_u64(id), validators.clone(), pacemaker);
+            replica.submit(format!("tx_from_{}", id).into_bytes());
+            let tick = Duration::from_millis(10);
+            handles.push(tokio::spawn(run_replica(
+                replica,
+                transport,
+                inbound,
+                tick,
+                shutdown_rx.clone(),
+            )));
+        }
+
+        tokio::time::sleep(Duration::from_millis(1_500)).await;
+        shutdown.send(true).unwrap();
+        let mut replicas = Vec::new();
+        for handle in handles {
+            replicas.push(handle.await.unwrap());
+        }
+
+        for replica in &replicas {
+            assert!(replica.committed_height().as_u64() >= 3, "node {} stalled", replica.id());
+        }
+        let shortest = replicas.iter().map(|r| r.committed_blocks().len()).min().unwrap();
+        for replica in &replicas[1..] {
+            assert_eq!(
+                replica.committed_blocks()[..shortest],
+                replicas[0].committed_blocks()[..shortest]
+            );
+        }
+        // 视图 1 的 Leader（节点 1）打包的命令已提交
+        assert_eq!(replicas[0].committed_blocks()[0].commands(), &[b"tx_from_1".to_vec()]);
+    }
+}
@@ -0,0 +1,413 @@
+//! 消息编码与分帧
+//!
+//! 帧格式：`len:u32（大端） | envelope`
+//! 信封格式：`sender:u64 | signature:64 | payload`，签名覆盖 `sender | payload`
+//!
+//! payload 为 [`ChainedMessage`] 的二进制编码（整数小端、变长字段带 u32 长度前缀）。
+
+use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::TransportError;
+use crate::crypto::{Hash, PublicKey, Signature};
+use crate::domain::chained::ChainedMessage;
+use crate::domain::entities::{
+    Block, Height, Phase, QuorumCertificate, TimeoutCertificate, TimeoutVote, ViewNumber, Vote,
+};
+
+/// 单帧上限（16 MiB），防止恶意长度耗尽内存
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+const SIGNATURE_LEN: usize = 64;
+
+/// 签名信封
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    /// 发送方节点 ID
+    pub sender: u64,
+    /// 消息编码
+    pub payload: Vec<u8>,
+    /// ed25519 签名
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl Envelope {
+    /// 用发送方的传输密钥签名
+    pub fn sign(sender: u64, payload: Vec<u8>, key: &SigningKey) -> Self {
+        let signature = key.sign(&Self::signed_bytes(sender, &payload)).to_bytes();
+        Self { sender, payload, signature }
+    }
+
+    /// 用发送方的传输公钥验证
+    pub fn verify(&self, key: &VerifyingKey) -> bool {
+        let signature = Ed25519Signature::from_bytes(&self.signature);
+        key.verify(&Self::signed_bytes(self.sender, &self.payload), &signature).is_ok()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + SIGNATURE_LEN + self.payload.len());
+        data.extend_from_slice(&self.sender.to_le_bytes());
+        data.extend_from_slice(&self.signature);
+        data.extend_from_slice(&self.payload);
+        data
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, TransportError> {
+        let mut reader = Reader { data, offset: 0 };
+        let sender = reader.u64()?;
+        let signature = reader.array::<SIGNATURE_LEN>()?;
+        let payload = data[reader.offset..].to_vec();
+        Ok(Self { sender, payload, signature })
+    }
+
+    fn signed_bytes(sender: u64, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + payload.len());
+        data.extend_from_slice(&sender.to_le_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+}
+
+/// 写一帧
+pub async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    frame: &[u8],
+) -> std::io::Result<()> {
+    writer.write_u32(frame.len() as u32).await?;
+    writer.write_all(frame).await?;
+    writer.flush().await
+}
+
+/// 读一帧
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, TransportError> {
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(TransportError::FrameTooLarge(len));
+    }
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+/// 编码共识消息
+pub fn encode_message(message: &ChainedMessage) -> Vec<u8> {
+    let mut data = Vec::new();
+    match message {
+        ChainedMessage::Proposal { block, tc } => {
+            data.push(1);
+            put_block(&mut data, block);
+            put_option(&mut data, tc.as_ref(), put_tc);
+        }
+        ChainedMessage::Vote(vote) => {
+            data.push(2);
+            put_vote(&mut data, vote);
+        }
+        ChainedMessage::Timeout { vote, tc } => {
+            data.push(3);
+            put_view(&mut data, vote.view());
+            put_qc(&mut data, vote.high_qc());
+            data.extend_from_slice(vote.voter().as_bytes());
+            data.extend_from_slice(vote.signature().as_bytes());
+            put_option(&mut data, tc.as_ref(), put_tc);
+        }
+    }
+    data
+}
+
+/// 解码共识消息
+pub fn decode_message(data: &[u8]) -> Result<ChainedMessage, TransportError> {
+    let mut reader = Reader { data, offset: 0 };
+    let message = match reader.u8()? {
+        1 => {
+            let block = reader.block()?;
+            let tc = if reader.flag()? { Some(reader.tc()?) } else { None };
+            ChainedMessage::Proposal { block, tc }
+        }
+        2 => ChainedMessage::Vote(reader.vote()?),
+        3 => {
+            let view = reader.view()?;
+            let high_qc = reader.qc()?;
+            let voter = reader.public_key()?;
+            let signature = reader.signature()?;
+            let tc = if reader.flag()? { Some(reader.tc()?) } else { None };
+            ChainedMessage::Timeout { vote: TimeoutVote::new(view, high_qc, voter, signature), tc }
+        }
+        tag => return Err(TransportError::Decode(format!("unknown message type {}", tag))),
+    };
+    if reader.offset != data.len() {
+        return Err(TransportError::Decode("trailing bytes".to_string()));
+    }
+    Ok(message)
+}
+
+fn put_view(data: &mut Vec<u8>, view: ViewNumber) {
+    data.extend_from_slice(&view.as_u64().to_le_bytes());
+}
+
+fn put_bytes(data: &mut Vec<u8>, bytes: &[u8]) {
+    data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data.extend_from_slice(bytes);
+}
+
+fn put_option<T>(data: &mut Vec<u8>, value: Option<&T>, put: fn(&mut Vec<u8>, &T)) {
+    match value {
+        Some(value) => {
+            data.push(1);
+            put(data, value);
+        }
+        None => data.push(0),
+    }
+}
+
+fn put_block(data: &mut Vec<u8>, block: &Block) {
+    data.extend_from_slice(block.parent_hash().as_bytes());
+    put_view(data, block.view());
+    data.extend_from_slice(&block.height().as_u64().to_le_bytes());
+    data.extend_from_slice(block.proposer().as_bytes());
+    put_qc(data, block.justify());
+    data.extend_from_slice(&(block.commands().len() as u32).to_le_bytes());
+    for command in block.commands() {
+        put_bytes(data, command);
+    }
+}
+
+fn put_vote(data: &mut Vec<u8>, vote: &Vote) {
+    data.extend_from_slice(vote.block_hash().as_bytes());
+    put_view(data, vote.view());
+    data.push(vote.phase() as u8);
+    data.extend_from_slice(vote.voter().as_bytes());
+    data.extend_from_slice(vote.signature().as_bytes());
+}
+
+fn put_qc(data: &mut Vec<u8>, qc: &QuorumCertificate) {
+    data.extend_from_slice(qc.block_hash().as_bytes());
+    put_view(data, qc.view());
+    data.push(qc.phase() as u8);
+    // 按投票者排序，保证编码确定
+    let mut votes: Vec<_> = qc.votes().values().collect();
+    votes.sort_by_key(|vote| vote.voter());
+    data.extend_from_slice(&(votes.len() as u32).to_le_bytes());
+    for vote in votes {
+        put_vote(data, vote);
+    }
+}
+
+fn put_tc(data: &mut Vec<u8>, tc: &TimeoutCertificate) {
+    put_view(data, tc.view());
+    put_qc(data, tc.high_qc());
+    data.extend_from_slice(&(tc.voters().len() as u32).to_le_bytes());
+    for voter in tc.voters() {
+        data.extend_from_slice(voter.as_bytes());
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], TransportError> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.data.len());
+        let end =
+            end.ok_or_else(|| TransportError::Decode("unexpected end of data".to_string()))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TransportError> {
+        self.take(N)?
+            .try_into()
+            .map_err(|_| TransportError::Decode(format!("expected {} bytes", N)))
+    }
+
+    fn u8(&mut self) -> Result<u8, TransportError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, TransportError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, TransportError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn flag(&mut self) -> Result<bool, TransportError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            flag => Err(TransportError::Decode(format!("invalid flag {}", flag))),
+        }
+    }
+
+    /// 元素个数：每个元素至少 `min_size` 字节，防止伪造的计数触发超大分配
+    fn count(&mut self, min_size: usize) -> Result<usize, TransportError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_size) > self.data.len() - self.offset {
+            return Err(TransportError::Decode(format!("invalid element count {}", count)));
+        }
+        Ok(count)
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, TransportError> {
+        let len = self.u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn hash(&mut self) -> Result<Hash, TransportError> {
+        Ok(Hash::from_bytes(self.array()?))
+    }
+
+    fn public_key(&mut self) -> Result<PublicKey, TransportError> {
+        Ok(PublicKey::from_bytes(self.array()?))
+    }
+
+    fn signature(&mut self) -> Result<Signature, TransportError> {
+        Ok(Signature::from_bytes(self.array()?))
+    }
+
+    fn view(&mut self) -> Result<ViewNumber, TransportError> {
+        Ok(ViewNumber::new(self.u64()?))
+    }
+
+    fn phase(&mut self) -> Result<Phase, TransportError> {
+        Ok(match self.u8()? {
+            0 => Phase::Prepare,
+            1 => Phase::PreCommit,
+            2 => Phase::Commit,
+            3 => Phase::Decide,
+            4 => Phase::Generic,
+            phase => return Err(TransportError::Decode(format!("unknown phase {}", phase))),
+        })
+    }
+
+    fn vote(&mut self) -> Result<Vote, TransportError> {
+        let block_hash = self.hash()?;
+        let view = self.view()?;
+        let phase = self.phase()?;
+        Ok(Vote::new(block_hash, view, phase, self.public_key()?, self.signature()?))
+    }
+
+    fn qc(&mut self) -> Result<QuorumCertificate, TransportError> {
+        let mut qc = QuorumCertificate::new(self.hash()?, self.view()?, self.phase()?);
+        for _ in 0..self.count(32 + 8 + 1 + 32 + SIGNATURE_LEN)? {
+            if !qc.add_vote(self.vote()?) {
+                return Err(TransportError::Decode("vote does not match QC".to_string()));
+            }
+        }
+        Ok(qc)
+    }
+
+    fn tc(&mut self) -> Result<TimeoutCertificate, TransportError> {
+        let view = self.view()?;
+        let high_qc = self.qc()?;
+        let voters = (0..self.count(32)?).map(|_| self.public_key()).collect::<Result<_, _>>()?;
+        Ok(TimeoutCertificate::from_parts(view, high_qc, voters))
+    }
+
+    fn block(&mut self) -> Result<Block, TransportError> {
+        let parent_hash = self.hash()?;
+        let view = self.view()?;
+        let height = Height::new(self.u64()?);
+        let proposer = self.public_key()?;
+        let justify = self.qc()?;
+        let commands = (0..self.count(4)?).map(|_| self.bytes()).collect::<Result<_, _>>()?;
+        Ok(Block::from_parts(parent_hash, view, height, proposer, justify, commands))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::PrivateKey;
+
+    fn sample_messages() -> Vec<ChainedMessage> {
+        let genesis = Block::genesis();
+        let view = ViewNumber::new(3);
+        let mut qc = QuorumCertificate::new(genesis.hash(), ViewNumber::new(2), Phase::Generic);
+        for i in 0..3 {
+            let key = PrivateKey::from_u64(i);
+            let signature = key.sign(&i);
+            qc.add_vote(Vote::new(
+                genesis.hash(),
+                ViewNumber::new(2),
+                Phase::Generic,
+                key.public_key(),
+                signature,
+            ));
+        }
+        let block = Block::new(
+            &genesis,
+            view,
+            PublicKey::from_u64(3),
+            qc.clone(),
+            vec![b"tx1".to_vec(), Vec::new()],
+        );
+        let timeout = TimeoutVote::new(view, qc.clone(), PublicKey::from_u64(1), Signature::zero());
+        let tc = TimeoutCertificate::from_votes(view, [&timeout]);
+        let vote = Vote::new(
+            block.hash(),
+            view,
+            Phase::Generic,
+            PublicKey::from_u64(2),
+            Signature::zero(),
+        );
+
+        vec![
+            ChainedMessage::Proposal { block, tc: Some(tc.clone()) },
+            ChainedMessage::Vote(vote),
+            ChainedMessage::Timeout { vote: timeout, tc: None },
+        ]
+    }
+
+    #[test]
+    fn test_message_roundtrip() {
+        let messages = sample_messages();
+        for message in &messages {
+            let encoded = encode_message(message);
+            let decoded = decode_message(&encoded).unwrap();
+            // 编码确定，再次编码应完全一致
+            assert_eq!(encode_message(&decoded), encoded);
+            assert!(decode_message(&encoded[..encoded.len() - 1]).is_err());
+        }
+
+        let ChainedMessage::Proposal { block, .. } = &messages[0] else { unreachable!() };
+        match decode_message(&encode_message(&messages[0])).unwrap() {
+            ChainedMessage::Proposal { block: decoded, tc } => {
+                assert_eq!(&decoded, block);
+                assert!(tc.is_some());
+            }
+            _ => panic!("Expected proposal"),
+        }
+    }
+
+    #[test]
+    fn test_envelope_signature() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        let envelope = Envelope::sign(1, b"payload".to_vec(), &key);
+
+        let decoded = Envelope::decode(&envelope.encode()).unwrap();
+        assert_eq!(decoded, envelope);
+        assert!(decoded.verify(&key.verifying_key()));
+        assert!(!decoded.verify(&other.verifying_key()));
+
+        // 篡改发送方或内容都会使签名失效
+        let forged = Envelope { sender: 2, ..envelope.clone() };
+        assert!(!forged.verify(&key.verifying_key()));
+        let tampered = Envelope { payload: b"payloaD".to_vec(), ..envelope };
+        assert!(!tampered.verify(&key.verifying_key()));
+    }
+
+    #[tokio::test]
+    async fn test_frame_limit() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        write_frame(&mut client, b"hello").await.unwrap();
+        assert_eq!(read_frame(&mut server).await.unwrap(), b"hello");
+
+        client.write_u32(MAX_FRAME_LEN as u32 + 1).await.unwrap();
+        assert!(matches!(read_frame(&mut server).await, Err(TransportError::FrameTooLarge(_))));
+    }
+}
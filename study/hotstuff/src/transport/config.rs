@@ -0,0 +1,164 @@
+//! 静态节点配置
+//!
+//! ```toml
+//! reconnect_base_ms = 100
+//! reconnect_max_ms = 5000
+//!
+//! [[peers]]
+//! id = 0
+//! address = "127.0.0.1:7000"
+//! public_key = "<ed25519 传输公钥 hex>"
+//! ```
+//!
+//! 节点 ID 即共识层验证者下标，须为 `0..n` 且互不重复。
+
+use std::net::SocketAddr;
+use std::path::Path;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use super::TransportError;
+
+/// 对端配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerConfig {
+    /// 节点 ID
+    pub id: u64,
+    /// 监听地址
+    pub address: SocketAddr,
+    /// ed25519 传输公钥（hex）
+    pub public_key: String,
+}
+
+impl PeerConfig {
+    /// 解析传输公钥
+    pub fn verifying_key(&self) -> Result<VerifyingKey, TransportError> {
+        let invalid = || TransportError::Config(format!("invalid public key of peer {}", self.id));
+        let bytes: [u8; 32] = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(invalid)?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())
+    }
+}
+
+/// 网络配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// 所有节点（包括自己）
+    pub peers: Vec<PeerConfig>,
+    /// 重连初始退避（毫秒）
+    #[serde(default = "default_reconnect_base_ms")]
+    pub reconnect_base_ms: u64,
+    /// 重连退避上限（毫秒）
+    #[serde(default = "default_reconnect_max_ms")]
+    pub reconnect_max_ms: u64,
+}
+
+fn default_reconnect_base_ms() -> u64 {
+    100
+}
+
+fn default_reconnect_max_ms() -> u64 {
+    5_000
+}
+
+impl NetworkConfig {
+    /// 从 TOML 文件加载
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml(&content)
+    }
+
+    /// 从 TOML 文本解析并校验
+    pub fn from_toml(content: &str) -> Result<Self, TransportError> {
+        let config: Self =
+            toml::from_str(content).map_err(|e| TransportError::Config(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> Result<String, TransportError> {
+        toml::to_string_pretty(self).map_err(|e| TransportError::Config(e.to_string()))
+    }
+
+    /// 本机多进程演示配置：n 个节点监听 `127.0.0.1:base_port+i`，使用 [`demo_signing_key`]
+    pub fn local(n: u64, base_port: u16) -> Self {
+        let peers = (0..n)
+            .map(|id| PeerConfig {
+                id,
+                address: SocketAddr::from(([127, 0, 0, 1], base_port + id as u16)),
+                public_key: hex::encode(demo_signing_key(id).verifying_key().as_bytes()),
+            })
+            .collect();
+        Self {
+            peers,
+            reconnect_base_ms: default_reconnect_base_ms(),
+            reconnect_max_ms: default_reconnect_max_ms(),
+        }
+    }
+
+    pub fn peer(&self, id: u64) -> Option<&PeerConfig> {
+        self.peers.iter().find(|peer| peer.id == id)
+    }
+
+    /// 校验：ID 为 `0..n` 的排列，公钥可解析
+    pub fn validate(&self) -> Result<(), TransportError> {
+        let mut ids: Vec<_> = self.peers.iter().map(|peer| peer.id).collect();
+        ids.sort_unstable();
+        if ids.iter().enumerate().any(|(index, &id)| index as u64 != id) {
+            return Err(TransportError::Config("peer ids must be 0..n without gaps".to_string()));
+        }
+        if self.reconnect_base_ms == 0 || self.reconnect_base_ms > self.reconnect_max_ms {
+            return Err(TransportError::Config("invalid reconnect backoff".to_string()));
+        }
+        for peer in &self.peers {
+            peer.verifying_key()?;
+        }
+        Ok(())
+    }
+}
+
+/// 演示用的确定性传输密钥（生产环境应从安全存储加载）
+pub fn demo_signing_key(id: u64) -> SigningKey {
+    let mut seed = [0x5Au8; 32];
+    seed[..8].copy_from_slice(&id.to_le_bytes());
+    SigningKey::from_bytes(&seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_roundtrip() {
+        let config = NetworkConfig::local(4, 7000);
+        let parsed = NetworkConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.peer(2).unwrap().address.port(), 7002);
+        assert_eq!(
+            parsed.peer(1).unwrap().verifying_key().unwrap(),
+            demo_signing_key(1).verifying_key()
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = NetworkConfig::local(3, 7000);
+        config.peers[2].id = 5;
+        assert!(matches!(config.validate(), Err(TransportError::Config(_))));
+
+        let mut config = NetworkConfig::local(3, 7000);
+        config.peers[0].public_key = "zz".to_string();
+        assert!(matches!(config.validate(), Err(TransportError::Config(_))));
+
+        // 未配置的退避参数取默认值
+        let public_key = hex::encode(demo_signing_key(0).verifying_key().as_bytes());
+        let defaults = format!(
+            "[[peers]]\nid = 0\naddress = \"127.0.0.1:7000\"\npublic_key = \"{}\"\n",
+            public_key
+        );
+        assert_eq!(NetworkConfig::from_toml(&defaults).unwrap().reconnect_max_ms, 5_000);
+    }
+}
@@ -0,0 +1,52 @@
+//! 网络传输层
+//!
+//! 基于 TCP 的长度前缀分帧传输，用于多进程运行链式 HotStuff：
+//! - codec: 消息二进制编码、签名信封与分帧
+//! - config: 静态节点配置（地址与传输公钥）
+//! - tcp: 连接管理（监听、按节点的发送任务、断线指数退避重连）
+//! - runner: 在传输之上驱动 [`ChainedHotStuff`](crate::ChainedHotStuff) 副本
+//!
+//! 每个信封由发送方的 ed25519 传输密钥签名，接收方按配置中的公钥验证，
+//! 验证失败的连接会被关闭。共识层签名（`crate::crypto`）仍为简化实现。
+
+pub mod codec;
+pub mod config;
+pub mod runner;
+pub mod tcp;
+
+pub use codec::{Envelope, MAX_FRAME_LEN, decode_message, encode_message};
+pub use config::{NetworkConfig, PeerConfig, demo_signing_key};
+pub use runner::run_replica;
+pub use tcp::{InboundMessage, TcpTransport};
+
+/// 传输层错误
+#[derive(Debug)]
+pub enum TransportError {
+    Io(std::io::Error),
+    FrameTooLarge(usize),
+    Decode(String),
+    UnknownPeer(u64),
+    BadSignature(u64),
+    Config(String),
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportError::Io(e) => write!(f, "I/O error: {}", e),
+            TransportError::FrameTooLarge(len) => write!(f, "Frame too large: {} bytes", len),
+            TransportError::Decode(msg) => write!(f, "Decode error: {}", msg),
+            TransportError::UnknownPeer(id) => write!(f, "Unknown peer {}", id),
+            TransportError::BadSignature(id) => write!(f, "Bad signature from peer {}", id),
+            TransportError::Config(msg) => write!(f, "Invalid config: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+impl From<std::io::Error> for TransportError {
+    fn from(e: std::io::Error) -> Self {
+        TransportError::Io(e)
+    }
+}
@@ -0,0 +1,47 @@
+//! 副本驱动循环
+//!
+//! 把 [`ChainedHotStuff`] 接到 [`TcpTransport`] 上：入站消息交给副本处理，
+//! 按固定间隔推进逻辑时钟（距启动的毫秒数），副本产生的消息经传输发出。
+
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+use super::tcp::{InboundMessage, TcpTransport};
+use crate::domain::chained::ChainedHotStuff;
+
+/// 运行副本直到 `shutdown` 变为 true（或发送端关闭），返回副本以便查看提交结果
+#[allow(clippy::disallowed_macros)] // tokio::select! 展开含不可达分支的 unreachable!，运行时不会触发
+pub async fn run_replica(
+    mut replica: ChainedHotStuff,
+    transport: TcpTransport,
+    mut inbound: mpsc::Receiver<InboundMessage>,
+    tick_interval: Duration,
+    mut shutdown: watch::Receiver<bool>,
+) -> ChainedHotStuff {
+    let started = Instant::now();
+    let now = || started.elapsed().as_millis() as u64;
+
+    transport.send_all(replica.start(now()));
+    let mut ticker = tokio::time::interval(tick_interval);
+    loop {
+        tokio::select! {
+            Some(InboundMessage { message, .. }) = inbound.recv() => {
+                // 无效或过期的消息被拒绝属于正常情况
+                if let Ok(outbound) = replica.handle_message(message, now()) {
+                    transport.send_all(outbound);
+                }
+            }
+            _ = ticker.tick() => transport.send_all(replica.tick(now())),
+            changed = shutdown.changed() => {
+                if changed.is_err() || *shutdown.borrow() {
+                    break;
+                }
+            }
+            // 所有分支都失效时退出，而不是由 select! panic
+            else => break,
+        }
+    }
+    replica
+}
@@ -0,0 +1,280 @@
+//! TCP 连接管理
+//!
+//! - 监听任务为每个入站连接启动读取任务：读帧 → 验签 → 解码 → 投入入站队列
+//! - 每个对端一个发送任务，持有一条出站连接；连接失败按指数退避重试，
+//!   退避期间的消息直接丢弃（共识层依靠超时重发保证活性，不在传输层积压过期消息）
+//! - 发给自己的消息不经网络，直接投入入站队列
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use super::TransportError;
+use super::codec::{Envelope, decode_message, encode_message, read_frame, write_frame};
+use super::config::NetworkConfig;
+use crate::domain::chained::{ChainedMessage, Outbound, Recipient};
+
+/// 每个对端的发送队列容量
+const PEER_QUEUE_CAPACITY: usize = 1024;
+/// 入站队列容量
+const INBOUND_QUEUE_CAPACITY: usize = 4096;
+
+/// 已验签的入站消息
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    /// 发送方节点 ID
+    pub from: u64,
+    pub message: ChainedMessage,
+}
+
+/// TCP 传输
+pub struct TcpTransport {
+    id: u64,
+    signing_key: SigningKey,
+    local_addr: SocketAddr,
+    /// 对端发送队列（帧）
+    peers: HashMap<u64, mpsc::Sender<Arc<Vec<u8>>>>,
+    /// 入站队列（用于投递发给自己的消息）
+    inbound: mpsc::Sender<InboundMessage>,
+}
+
+impl TcpTransport {
+    /// 监听配置中自己的地址并启动
+    pub async fn bind(
+        id: u64,
+        signing_key: SigningKey,
+        config: &NetworkConfig,
+    ) -> Result<(Self, mpsc::Receiver<InboundMessage>), TransportError> {
+        let peer = config.peer(id).ok_or(TransportError::UnknownPeer(id))?;
+        let listener = TcpListener::bind(peer.address).await?;
+        Self::start(id, signing_key, config, listener)
+    }
+
+    /// 使用已绑定的监听器启动（须在 tokio 运行时中调用）
+    pub fn start(
+        id: u64,
+        signing_key: SigningKey,
+        config: &NetworkConfig,
+        listener: TcpListener,
+    ) -> Result<(Self, mpsc::Receiver<InboundMessage>), TransportError> {
+        config.validate()?;
+        let own = config.peer(id).ok_or(TransportError::UnknownPeer(id))?;
+        if own.verifying_key()? != signing_key.verifying_key() {
+            return Err(TransportError::Config(format!("signing key does not match peer {}", id)));
+        }
+
+        let keys: HashMap<u64, VerifyingKey> = config
+            .peers
+            .iter()
+            .map(|peer| Ok((peer.id, peer.verifying_key()?)))
+            .collect::<Result<_, TransportError>>()?;
+        let (inbound, inbound_rx) = mpsc::channel(INBOUND_QUEUE_CAPACITY);
+        let local_addr = listener.local_addr()?;
+        tokio::spawn(accept_loop(listener, Arc::new(keys), inbound.clone()));
+
+        let backoff = (
+            Duration::from_millis(config.reconnect_base_ms),
+            Duration::from_millis(config.reconnect_max_ms),
+        );
+        let peers = config
+            .peers
+            .iter()
+            .filter(|peer| peer.id != id)
+            .map(|peer| {
+                let (tx, rx) = mpsc::channel(PEER_QUEUE_CAPACITY);
+                tokio::spawn(peer_writer(peer.address, rx, backoff));
+                (peer.id, tx)
+            })
+            .collect();
+
+        Ok((Self { id, signing_key, local_addr, peers, inbound }, inbound_rx))
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 发送消息（不阻塞；队列满或对端不可达时丢弃）
+    pub fn send(&self, outbound: Outbound) {
+        let Outbound { to, message } = outbound;
+        let deliver_self = matches!(to, Recipient::All) || to == Recipient::Node(self.id);
+        let targets: Vec<_> = match to {
+            Recipient::All => self.peers.values().collect(),
+            Recipient::Node(id) => self.peers.get(&id).into_iter().collect(),
+        };
+
+        if !targets.is_empty() {
+            let envelope = Envelope::sign(self.id, encode_message(&message), &self.signing_key);
+            let frame = Arc::new(envelope.encode());
+            for target in targets {
+                let _ = target.try_send(frame.clone());
+            }
+        }
+        if deliver_self {
+            let _ = self.inbound.try_send(InboundMessage { from: self.id, message });
+        }
+    }
+
+    pub fn send_all(&self, outbound: Vec<Outbound>) {
+        for message in outbound {
+            self.send(message);
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    keys: Arc<HashMap<u64, VerifyingKey>>,
+    inbound: mpsc::Sender<InboundMessage>,
+) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else { continue };
+        let _ = stream.set_nodelay(true);
+        tokio::spawn(read_loop(stream, keys.clone(), inbound.clone()));
+    }
+}
+
+/// 读取一条入站连接，出错（含验签失败）即关闭连接
+async fn read_loop(
+    mut stream: TcpStream,
+    keys: Arc<HashMap<u64, VerifyingKey>>,
+    inbound: mpsc::Sender<InboundMessage>,
+) -> Result<(), TransportError> {
+    loop {
+        let envelope = Envelope::decode(&read_frame(&mut stream).await?)?;
+        let key = keys.get(&envelope.sender).ok_or(TransportError::UnknownPeer(envelope.sender))?;
+        if !envelope.verify(key) {
+            return Err(TransportError::BadSignature(envelope.sender));
+        }
+        let message = decode_message(&envelope.payload)?;
+        if inbound.send(InboundMessage { from: envelope.sender, message }).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// 对端发送任务：按需建连，写失败后下一条消息立即重连，建连失败则指数退避
+async fn peer_writer(
+    address: SocketAddr,
+    mut frames: mpsc::Receiver<Arc<Vec<u8>>>,
+    (base, max): (Duration, Duration),
+) {
+    let mut stream: Option<TcpStream> = None;
+    let mut backoff = base;
+    let mut retry_at = Instant::now();
+
+    while let Some(frame) = frames.recv().await {
+        if stream.is_none() && Instant::now() >= retry_at {
+            match TcpStream::connect(address).await {
+                Ok(connected) => {
+                    let _ = connected.set_nodelay(true);
+                    stream = Some(connected);
+                    backoff = base;
+                }
+                Err(_) => {
+                    retry_at = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(max);
+                }
+            }
+        }
+
+        let Some(connected) = stream.as_mut() else { continue };
+        if write_frame(connected, &frame).await.is_err() {
+            stream = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Hash, PublicKey, Signature};
+    use crate::domain::entities::{Phase, ViewNumber, Vote};
+    use crate::transport::config::demo_signing_key;
+
+    fn vote_message(view: u64) -> ChainedMessage {
+        ChainedMessage::Vote(Vote::new(
+            Hash::zero(),
+            ViewNumber::new(view),
+            Phase::Generic,
+            PublicKey::from_u64(0),
+            Signature::zero(),
+        ))
+    }
+
+    /// 预留端口：绑定后立即释放，稍后再由对端监听
+    async fn reserve_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_peer_starts() {
+        let mut config = NetworkConfig::local(2, 0);
+        config.reconnect_base_ms = 20;
+        config.reconnect_max_ms = 100;
+        let listener0 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        config.peers[0].address = listener0.local_addr().unwrap();
+        config.peers[1].address = reserve_addr().await;
+
+        let (node0, _inbound0) =
+            TcpTransport::start(0, demo_signing_key(0), &config, listener0).unwrap();
+        // 对端尚未启动：消息被丢弃并进入退避
+        node0.send(Outbound { to: Recipient::Node(1), message: vote_message(1) });
+
+        let listener1 = TcpListener::bind(config.peers[1].address).await.unwrap();
+        let (_node1, mut inbound1) =
+            TcpTransport::start(1, demo_signing_key(1), &config, listener1).unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut interval = tokio::time::interval(Duration::from_millis(20));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        node0.send(Outbound { to: Recipient::Node(1), message: vote_message(2) });
+                    }
+                    Some(received) = inbound1.recv() => return received,
+                }
+            }
+        })
+        .await
+        .expect("peer never reconnected");
+        assert_eq!(received.from, 0);
+        assert!(matches!(received.message, ChainedMessage::Vote(_)));
+    }
+
+    #[tokio::test]
+    async fn test_reject_forged_sender() {
+        let config = NetworkConfig::local(2, 0);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (_node0, mut inbound) =
+            TcpTransport::start(0, demo_signing_key(0), &config, listener).unwrap();
+
+        // 用错误的密钥冒充节点 1
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let forged = Envelope::sign(1, encode_message(&vote_message(1)), &demo_signing_key(0));
+        write_frame(&mut stream, &forged.encode()).await.unwrap();
+        let genuine = Envelope::sign(1, encode_message(&vote_message(1)), &demo_signing_key(1));
+        let _ = write_frame(&mut stream, &genuine.encode()).await;
+
+        // 验签失败后连接被关闭，之后的消息也不会投递
+        let result = tokio::time::timeout(Duration::from_millis(300), inbound.recv()).await;
+        assert!(result.is_err());
+
+        // 新连接上的正确签名可以投递
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        write_frame(&mut stream, &genuine.encode()).await.unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap();
+        assert_eq!(received.unwrap().from, 1);
+    }
+}
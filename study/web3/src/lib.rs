@@ -1 +1,2 @@
 pub mod revm;
+pub mod solvency;
@@ -0,0 +1,412 @@
+//! 偿付能力证明电路（halo2）
+//!
+//! 私有输入：`2^depth` 个叶子（用户标识、余额）
+//! 公开输入（instance 列）：`[负债树根哈希, 负债合计, 资产合计]`
+//!
+//! 约束：
+//! 1. 每个余额 ∈ [0, 2^64)（逐位分解），杜绝以"负余额"压低负债合计
+//! 2. 按 [`super::tree`] 的规则在电路内重算根哈希与合计，分别等于公开的根与负债合计
+//! 3. 资产 − 负债 ∈ [0, 2^64)，即资产 ≥ 负债
+//!
+//! 列布局：MiMC 压缩占用 x / key / msg / out 四列与轮常数列，每次压缩 `ROUNDS + 1` 行；
+//! 加法与私有输入使用 a / b / c 三列；范围检查单独使用 z 列。各组列互不重叠，
+//! 单遍布局器会把它们并排放置，总行数由 MiMC 区域决定。
+
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::pasta::group::ff::PrimeField;
+use halo2_proofs::plonk::{
+    Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+};
+use halo2_proofs::poly::Rotation;
+
+use super::mimc::{self, LEAF_IV, NODE_IV, ROUNDS};
+use super::tree::Leaf;
+
+/// 余额与差额的位宽
+pub const RANGE_BITS: usize = 64;
+
+/// 公开输入中根哈希的位置
+pub const ROOT_ROW: usize = 0;
+/// 公开输入中负债合计的位置
+pub const LIABILITIES_ROW: usize = 1;
+/// 公开输入中资产合计的位置
+pub const ASSETS_ROW: usize = 2;
+
+/// 证明系统为盲化预留的行数（留足余量）
+const BLINDING_ROWS: usize = 16;
+
+type Cell = AssignedCell<Fp, Fp>;
+
+#[derive(Debug, Clone)]
+pub struct SolvencyConfig {
+    // MiMC 压缩：x_{i+1} = (x_i + key + c_i)^5，out = x_R + 2·key + msg
+    x: Column<Advice>,
+    key: Column<Advice>,
+    msg: Column<Advice>,
+    out: Column<Advice>,
+    round_constant: Column<Fixed>,
+    q_round: Selector,
+    q_out: Selector,
+    // 加法：a + b = c
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    q_add: Selector,
+    // 范围检查：z_i = 2·z_{i+1} + bit_i
+    z: Column<Advice>,
+    q_bit: Selector,
+    instance: Column<Instance>,
+}
+
+/// 偿付能力电路
+#[derive(Debug, Clone)]
+pub struct SolvencyCircuit {
+    depth: u32,
+    /// (用户标识, 余额)，长度为 `2^depth`
+    leaves: Vec<(Value<Fp>, Value<Fp>)>,
+}
+
+impl SolvencyCircuit {
+    /// 以叶子为私有输入构造电路（不足 `2^depth` 时补空叶子）
+    pub fn new(depth: u32, leaves: &[Leaf]) -> Self {
+        let mut leaves: Vec<_> = leaves
+            .iter()
+            .map(|leaf| (Value::known(leaf.user), Value::known(Fp::from(leaf.balance))))
+            .collect();
+        let empty = Leaf::empty();
+        leaves.resize(1 << depth, (Value::known(empty.user), Value::known(Fp::from(0))));
+        Self { depth, leaves }
+    }
+
+    /// 不含见证的电路（用于生成密钥）
+    pub fn empty(depth: u32) -> Self {
+        Self { depth, leaves: vec![(Value::unknown(), Value::unknown()); 1 << depth] }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// 公开输入
+    pub fn instances(root: Fp, liabilities: u64, assets: u64) -> Vec<Fp> {
+        vec![root, Fp::from(liabilities), Fp::from(assets)]
+    }
+
+    /// 给定深度所需的 `k`（电路共 `2^k` 行）
+    pub fn k(depth: u32) -> u32 {
+        let leaves = 1usize << depth;
+        let compressions = 2 * leaves + 4 * (leaves - 1);
+        let mimc_rows = compressions * (ROUNDS + 1);
+        let range_rows = (leaves + 1) * (RANGE_BITS + 1);
+        let add_rows = 2 * leaves + leaves;
+        let rows = mimc_rows.max(range_rows).max(add_rows) + BLINDING_ROWS;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl Circuit<Fp> for SolvencyCircuit {
+    type Config = SolvencyConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::empty(self.depth)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> SolvencyConfig {
+        let [x, key, msg, out, a, b, c, z] = [(); 8].map(|_| meta.advice_column());
+        let round_constant = meta.fixed_column();
+        let constants = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_constant(constants);
+        meta.enable_equality(instance);
+        for column in [x, key, msg, out, a, b, c, z] {
+            meta.enable_equality(column);
+        }
+        let [q_round, q_out, q_add, q_bit] = [(); 4].map(|_| meta.selector());
+
+        meta.create_gate("mimc round", |meta| {
+            let q = meta.query_selector(q_round);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let key_cur = meta.query_advice(key, Rotation::cur());
+            let key_next = meta.query_advice(key, Rotation::next());
+            let msg_cur = meta.query_advice(msg, Rotation::cur());
+            let msg_next = meta.query_advice(msg, Rotation::next());
+            let constant = meta.query_fixed(round_constant, Rotation::cur());
+
+            let t = x_cur + key_cur.clone() + constant;
+            let t2 = t.clone() * t.clone();
+            let t5 = t2.clone() * t2 * t;
+            vec![
+                q.clone() * (x_next - t5),
+                q.clone() * (key_next - key_cur),
+                q * (msg_next - msg_cur),
+            ]
+        });
+
+        meta.create_gate("mimc output", |meta| {
+            let q = meta.query_selector(q_out);
+            let x = meta.query_advice(x, Rotation::cur());
+            let key = meta.query_advice(key, Rotation::cur());
+            let msg = meta.query_advice(msg, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![q * (out - (x + key.clone() + key + msg))]
+        });
+
+        meta.create_gate("add", |meta| {
+            let q = meta.query_selector(q_add);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            vec![q * (a + b - c)]
+        });
+
+        meta.create_gate("range bit", |meta| {
+            let q = meta.query_selector(q_bit);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let bit = z_cur - Expression::Constant(Fp::from(2)) * z_next;
+            vec![q * bit.clone() * (Expression::Constant(Fp::from(1)) - bit)]
+        });
+
+        SolvencyConfig {
+            x,
+            key,
+            msg,
+            out,
+            round_constant,
+            q_round,
+            q_out,
+            a,
+            b,
+            c,
+            q_add,
+            z,
+            q_bit,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: SolvencyConfig,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let mut level = Vec::with_capacity(self.leaves.len());
+        for (user, balance) in &self.leaves {
+            let user = config.load(&mut layouter, *user)?;
+            let balance = config.load(&mut layouter, *balance)?;
+            config.range_check(&mut layouter, &balance)?;
+            let hash = config.hash(&mut layouter, LEAF_IV, &[user, balance.clone()])?;
+            level.push((hash, balance));
+        }
+
+        while level.len() > 1 {
+            let mut parents = Vec::with_capacity(level.len() / 2);
+            for pair in level.chunks(2) {
+                let [(left_hash, left_sum), (right_hash, right_sum)] = pair else {
+                    unreachable!("levels have an even number of nodes");
+                };
+                let inputs =
+                    [left_hash.clone(), left_sum.clone(), right_hash.clone(), right_sum.clone()];
+                let hash = config.hash(&mut layouter, NODE_IV, &inputs)?;
+                let sum = config.add(&mut layouter, left_sum, right_sum)?;
+                parents.push((hash, sum));
+            }
+            level = parents;
+        }
+
+        let (root, liabilities) = level.pop().expect("tree has a root");
+        layouter.constrain_instance(root.cell(), config.instance, ROOT_ROW)?;
+        layouter.constrain_instance(liabilities.cell(), config.instance, LIABILITIES_ROW)?;
+        let surplus = config.surplus(&mut layouter, &liabilities)?;
+        config.range_check(&mut layouter, &surplus)
+    }
+}
+
+impl SolvencyConfig {
+    /// 私有输入
+    fn load(&self, layouter: &mut impl Layouter<Fp>, value: Value<Fp>) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "private input",
+            |mut region| region.assign_advice(|| "input", self.a, 0, || value),
+        )
+    }
+
+    fn add(&self, layouter: &mut impl Layouter<Fp>, lhs: &Cell, rhs: &Cell) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "add",
+            |mut region| {
+                self.q_add.enable(&mut region, 0)?;
+                lhs.copy_advice(|| "lhs", &mut region, self.a, 0)?;
+                rhs.copy_advice(|| "rhs", &mut region, self.b, 0)?;
+                let sum = lhs.value().copied() + rhs.value().copied();
+                region.assign_advice(|| "sum", self.c, 0, || sum)
+            },
+        )
+    }
+
+    /// 差额：负债 + 差额 = 资产（资产取自公开输入）
+    fn surplus(&self, layouter: &mut impl Layouter<Fp>, liabilities: &Cell) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "surplus",
+            |mut region| {
+                self.q_add.enable(&mut region, 0)?;
+                liabilities.copy_advice(|| "liabilities", &mut region, self.a, 0)?;
+                let assets = region.assign_advice_from_instance(
+                    || "assets",
+                    self.instance,
+                    ASSETS_ROW,
+                    self.c,
+                    0,
+                )?;
+                let surplus = assets.value().copied() - liabilities.value().copied();
+                region.assign_advice(|| "surplus", self.b, 0, || surplus)
+            },
+        )
+    }
+
+    /// 约束 `value < 2^RANGE_BITS`：逐位右移，移位 RANGE_BITS 次后必须为 0
+    fn range_check(&self, layouter: &mut impl Layouter<Fp>, value: &Cell) -> Result<(), Error> {
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut z = value.copy_advice(|| "z", &mut region, self.z, 0)?;
+                for bit in 0..RANGE_BITS {
+                    self.q_bit.enable(&mut region, bit)?;
+                    let next = z.value().map(|z| shr1(*z));
+                    z = region.assign_advice(|| "z", self.z, bit + 1, || next)?;
+                }
+                region.constrain_constant(z.cell(), Fp::from(0))
+            },
+        )
+    }
+
+    /// 以 `iv` 为初值依次吸收输入，与 [`mimc::hash`] 一致
+    fn hash(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        iv: u64,
+        inputs: &[Cell],
+    ) -> Result<Cell, Error> {
+        let mut state: Option<Cell> = None;
+        for input in inputs {
+            state = Some(self.compress(layouter, state.as_ref(), iv, input)?);
+        }
+        Ok(state.expect("hash has at least one input"))
+    }
+
+    /// 单次压缩 `E_h(m) + h + m`；`h` 为空时取常量 `iv`
+    fn compress(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        h: Option<&Cell>,
+        iv: u64,
+        m: &Cell,
+    ) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "mimc compress",
+            |mut region| {
+                let key = match h {
+                    Some(h) => h.copy_advice(|| "key", &mut region, self.key, 0)?,
+                    None => {
+                        region.assign_advice_from_constant(|| "iv", self.key, 0, Fp::from(iv))?
+                    }
+                };
+                let mut x = m.copy_advice(|| "x", &mut region, self.x, 0)?;
+                let msg = m.copy_advice(|| "msg", &mut region, self.msg, 0)?;
+                let key = key.value().copied();
+                let msg = msg.value().copied();
+
+                for (round, constant) in mimc::round_constants().iter().enumerate() {
+                    self.q_round.enable(&mut region, round)?;
+                    region.assign_fixed(
+                        || "round constant",
+                        self.round_constant,
+                        round,
+                        || Value::known(*constant),
+                    )?;
+                    let next =
+                        x.value().copied().zip(key).map(|(x, k)| mimc::pow5(x + k + constant));
+                    x = region.assign_advice(|| "x", self.x, round + 1, || next)?;
+                    region.assign_advice(|| "key", self.key, round + 1, || key)?;
+                    region.assign_advice(|| "msg", self.msg, round + 1, || msg)?;
+                }
+
+                self.q_out.enable(&mut region, ROUNDS)?;
+                let out = x.value().copied().zip(key).zip(msg).map(|((x, k), m)| x + k + k + m);
+                region.assign_advice(|| "out", self.out, ROUNDS, || out)
+            },
+        )
+    }
+}
+
+/// 域元素按整数右移一位
+fn shr1(value: Fp) -> Fp {
+    let mut repr = value.to_repr();
+    let mut carry = 0u8;
+    for byte in repr.iter_mut().rev() {
+        let low = *byte & 1;
+        *byte = (*byte >> 1) | (carry << 7);
+        carry = low;
+    }
+    Fp::from_repr(repr).expect("halving an integer keeps it canonical")
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+    use crate::solvency::tree::SumTree;
+
+    fn leaves() -> Vec<Leaf> {
+        [(11, 500), (22, 1_200), (33, 0)]
+            .into_iter()
+            .map(|(user, balance)| Leaf { user: Fp::from(user), balance })
+            .collect()
+    }
+
+    fn run(circuit: &SolvencyCircuit, instances: Vec<Fp>) -> bool {
+        let prover = MockProver::run(SolvencyCircuit::k(circuit.depth), circuit, vec![instances])
+            .expect("circuit synthesizes");
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_solvent_snapshot_satisfies_circuit() {
+        let leaves = leaves();
+        let tree = SumTree::build(&leaves, 2).unwrap();
+        let circuit = SolvencyCircuit::new(2, &leaves);
+        let root = tree.root();
+        assert_eq!(root.sum, 1_700);
+
+        assert!(run(&circuit, SolvencyCircuit::instances(root.hash, 1_700, 1_700)));
+        assert!(run(&circuit, SolvencyCircuit::instances(root.hash, 1_700, 5_000)));
+        // 资产不足
+        assert!(!run(&circuit, SolvencyCircuit::instances(root.hash, 1_700, 1_699)));
+        // 声明的负债与树不符
+        assert!(!run(&circuit, SolvencyCircuit::instances(root.hash, 1_600, 5_000)));
+        // 根哈希不符
+        assert!(!run(&circuit, SolvencyCircuit::instances(Fp::from(1), 1_700, 5_000)));
+    }
+
+    #[test]
+    fn test_negative_balance_rejected() {
+        // 以域上的 -100 作为余额可把合计压低 100，范围检查应拒绝
+        let mut circuit = SolvencyCircuit::new(1, &[Leaf { user: Fp::from(1), balance: 300 }]);
+        circuit.leaves[1] = (Value::known(Fp::from(2)), Value::known(-Fp::from(100)));
+
+        let node = |user: u64, balance: Fp| mimc::hash(LEAF_IV, &[Fp::from(user), balance]);
+        let (left, right) = (node(1, Fp::from(300)), node(2, -Fp::from(100)));
+        let root = mimc::hash(NODE_IV, &[left, Fp::from(300), right, -Fp::from(100)]);
+        assert!(!run(&circuit, SolvencyCircuit::instances(root, 200, 200)));
+    }
+
+    #[test]
+    fn test_shr1() {
+        assert_eq!(shr1(Fp::from(13)), Fp::from(6));
+        assert_eq!(shr1(Fp::from(1 << 40)), Fp::from(1 << 39));
+    }
+}
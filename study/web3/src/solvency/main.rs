@@ -0,0 +1,124 @@
+//! 偿付能力证明命令行
+//!
+//! ```bash
+//! # balances.json: [{"account_id": 1, "balance": 100}, ...]
+//! cargo run --bin solvency -- prove --balances balances.json --assets 1000 \
+//!     --salt <64位十六进制> --out proof.json
+//! cargo run --bin solvency -- verify proof.json
+//! cargo run --bin solvency -- inclusion --balances balances.json --salt <...> --account 1
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use web3::solvency::snapshot::{LiabilityEntry, LiabilitySnapshot};
+use web3::solvency::{SolvencyError, encode_field, proof};
+
+#[derive(Parser)]
+#[command(name = "solvency", about = "偿付能力零知识证明")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 基于负债快照生成证明
+    Prove {
+        /// 负债列表（JSON）
+        #[arg(long)]
+        balances: PathBuf,
+        /// 资产合计（最小单位）
+        #[arg(long)]
+        assets: u64,
+        /// 用户标识的盐（32 字节十六进制）
+        #[arg(long)]
+        salt: String,
+        #[arg(long, default_value = "solvency_proof.json")]
+        out: PathBuf,
+    },
+    /// 验证证明文件
+    Verify { proof: PathBuf },
+    /// 输出某个账户的包含证明
+    Inclusion {
+        #[arg(long)]
+        balances: PathBuf,
+        #[arg(long)]
+        salt: String,
+        #[arg(long)]
+        account: u64,
+    },
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), SolvencyError> {
+    match cli.command {
+        Command::Prove { balances, assets, salt, out } => {
+            let snapshot = load_snapshot(&balances, &salt)?;
+            println!(
+                "📋 {} accounts, depth {}, liabilities {}",
+                snapshot.entries().len(),
+                snapshot.depth(),
+                snapshot.total_liabilities()
+            );
+            let proof = proof::prove(&snapshot, assets)?;
+            let json = serde_json::to_string_pretty(&proof).map_err(invalid_json)?;
+            std::fs::write(&out, json)?;
+            println!("✅ Proof written to {} (root {})", out.display(), proof.root);
+        }
+        Command::Verify { proof: path } => {
+            let json = std::fs::read_to_string(path)?;
+            let proof: proof::SolvencyProof = serde_json::from_str(&json).map_err(invalid_json)?;
+            proof::verify(&proof)?;
+            println!(
+                "✅ Valid: assets {} ≥ liabilities {} (root {})",
+                proof.assets, proof.liabilities, proof.root
+            );
+        }
+        Command::Inclusion { balances, salt, account } => {
+            let snapshot = load_snapshot(&balances, &salt)?;
+            let inclusion = snapshot.inclusion_proof(account).ok_or_else(|| {
+                SolvencyError::InvalidProof(format!("account {} is not in the snapshot", account))
+            })?;
+            let siblings: Vec<_> = inclusion
+                .siblings
+                .iter()
+                .map(
+                    |node| serde_json::json!({ "hash": encode_field(&node.hash), "sum": node.sum }),
+                )
+                .collect();
+            let output = serde_json::json!({
+                "root": encode_field(&snapshot.root().hash),
+                "index": inclusion.index,
+                "user": encode_field(&inclusion.leaf.user),
+                "balance": inclusion.leaf.balance,
+                "siblings": siblings,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).map_err(invalid_json)?);
+        }
+    }
+    Ok(())
+}
+
+fn load_snapshot(balances: &Path, salt: &str) -> Result<LiabilitySnapshot, SolvencyError> {
+    let json = std::fs::read_to_string(balances)?;
+    let entries: Vec<LiabilityEntry> = serde_json::from_str(&json).map_err(invalid_json)?;
+    LiabilitySnapshot::new(entries, parse_salt(salt)?)
+}
+
+fn parse_salt(salt: &str) -> Result<[u8; 32], SolvencyError> {
+    hex::decode(salt)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SolvencyError::InvalidProof("salt must be 32 bytes of hex".to_string()))
+}
+
+fn invalid_json(e: serde_json::Error) -> SolvencyError {
+    SolvencyError::InvalidProof(format!("invalid json: {}", e))
+}
@@ -0,0 +1,76 @@
+//! 电路友好的 MiMC 哈希
+//!
+//! 证明电路需要在约束中重算负债树，Keccak 的约束代价过高，这里使用 MiMC-5：
+//! 每轮 `x ↦ (x + k + c_i)^5`，共 [`ROUNDS`] 轮（⌈255 / log₂5⌉，覆盖 Pallas 基域），
+//! 再以 Miyaguchi–Preneel 方式压缩：`h' = E_h(m) + h + m`。
+//!
+//! 轮常数由 Keccak-256 派生，本地计算与电路（[`super::circuit`]）共享同一份常量。
+
+use std::sync::OnceLock;
+
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::pasta::group::ff::PrimeField;
+use sha3::{Digest, Keccak256};
+
+/// 轮数
+pub const ROUNDS: usize = 110;
+
+/// 叶子哈希的初始值（域分离）
+pub const LEAF_IV: u64 = 1;
+/// 内部节点哈希的初始值（域分离）
+pub const NODE_IV: u64 = 2;
+
+/// 轮常数
+pub fn round_constants() -> &'static [Fp; ROUNDS] {
+    static CONSTANTS: OnceLock<[Fp; ROUNDS]> = OnceLock::new();
+    CONSTANTS.get_or_init(|| {
+        std::array::from_fn(|round| {
+            let mut hasher = Keccak256::new();
+            hasher.update(b"rustlob/solvency/mimc");
+            hasher.update((round as u64).to_le_bytes());
+            field_from_digest(hasher.finalize().into())
+        })
+    })
+}
+
+/// 把 32 字节摘要映射为域元素（清除最高两位，保证小于模数）
+pub fn field_from_digest(mut digest: [u8; 32]) -> Fp {
+    digest[31] &= 0x3f;
+    Fp::from_repr(digest).expect("value below 2^254 is a canonical field element")
+}
+
+pub(crate) fn pow5(x: Fp) -> Fp {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// MiMC 加密：以 `key` 加密 `x`
+pub fn encrypt(x: Fp, key: Fp) -> Fp {
+    round_constants().iter().fold(x, |x, c| pow5(x + key + c)) + key
+}
+
+/// 单次压缩：`h' = E_h(m) + h + m`
+pub fn compress(h: Fp, m: Fp) -> Fp {
+    encrypt(m, h) + h + m
+}
+
+/// 以 `iv` 为初值依次吸收输入
+pub fn hash(iv: u64, inputs: &[Fp]) -> Fp {
+    inputs.iter().fold(Fp::from(iv), |h, m| compress(h, *m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_is_deterministic_and_domain_separated() {
+        let inputs = [Fp::from(7), Fp::from(100)];
+        assert_eq!(hash(LEAF_IV, &inputs), hash(LEAF_IV, &inputs));
+        assert_ne!(hash(LEAF_IV, &inputs), hash(NODE_IV, &inputs));
+        assert_ne!(hash(LEAF_IV, &inputs), hash(LEAF_IV, &[Fp::from(100), Fp::from(7)]));
+        // 轮常数互不相同
+        let constants = round_constants();
+        assert_ne!(constants[0], constants[1]);
+    }
+}
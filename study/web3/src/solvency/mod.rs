@@ -0,0 +1,87 @@
+//! 偿付能力零知识证明（原型）
+//!
+//! 基于余额快照证明"交易所资产 ≥ 用户负债"，且不泄露任何单个账户的余额：
+//!
+//! 1. [`snapshot`]：把 [`base_types::account::balance_snapshot::BalanceSnapshotBatch`]
+//!    中某一资产的余额转换为负债列表
+//! 2. [`tree`]：构建 Merkle 求和树，根节点同时承诺全部余额与负债合计，
+//!    用户可凭包含证明核对自己的余额被计入
+//! 3. [`circuit`]：halo2 电路，在约束中重算求和树并检查每个余额非负、资产 ≥ 负债
+//! 4. [`proof`]：生成与验证证明（IPA 承诺，无需可信设置）
+//!
+//! 公开的只有根哈希、负债合计与资产合计。命令行入口见 `src/solvency/main.rs`。
+
+pub mod circuit;
+pub mod mimc;
+pub mod proof;
+pub mod snapshot;
+pub mod tree;
+
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::pasta::group::ff::PrimeField;
+
+/// 偿付能力证明错误
+#[derive(Debug)]
+pub enum SolvencyError {
+    /// 账户余额为负
+    NegativeBalance(u64),
+    /// 同一账户出现多次
+    DuplicateAccount(u64),
+    /// 账户数超出树容量
+    TooManyAccounts(usize),
+    /// 负债合计溢出 u64
+    Overflow,
+    /// 资产不足以覆盖负债
+    Insolvent {
+        assets: u64,
+        liabilities: u64,
+    },
+    /// 证明系统错误
+    Halo2(String),
+    /// 证明格式错误或验证失败
+    InvalidProof(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SolvencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolvencyError::NegativeBalance(account) => {
+                write!(f, "account {} has a negative balance", account)
+            }
+            SolvencyError::DuplicateAccount(account) => {
+                write!(f, "account {} appears more than once", account)
+            }
+            SolvencyError::TooManyAccounts(count) => {
+                write!(f, "{} accounts exceed the tree capacity", count)
+            }
+            SolvencyError::Overflow => write!(f, "total liabilities overflow u64"),
+            SolvencyError::Insolvent { assets, liabilities } => {
+                write!(f, "assets {} do not cover liabilities {}", assets, liabilities)
+            }
+            SolvencyError::Halo2(e) => write!(f, "halo2 error: {}", e),
+            SolvencyError::InvalidProof(e) => write!(f, "invalid proof: {}", e),
+            SolvencyError::Io(e) => write!(f, "io error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SolvencyError {}
+
+impl From<std::io::Error> for SolvencyError {
+    fn from(e: std::io::Error) -> Self {
+        SolvencyError::Io(e)
+    }
+}
+
+/// 域元素编码为十六进制（小端表示）
+pub fn encode_field(value: &Fp) -> String {
+    hex::encode(value.to_repr())
+}
+
+pub fn decode_field(encoded: &str) -> Result<Fp, SolvencyError> {
+    let invalid = || SolvencyError::InvalidProof(format!("invalid field element {}", encoded));
+    let bytes: [u8; 32] =
+        hex::decode(encoded).ok().and_then(|bytes| bytes.try_into().ok()).ok_or_else(invalid)?;
+    Option::from(Fp::from_repr(bytes)).ok_or_else(invalid)
+}
@@ -0,0 +1,117 @@
+//! 证明生成与验证
+//!
+//! 使用 IPA 多项式承诺（Pasta 曲线），公共参数由 `k` 确定性生成，无需可信设置；
+//! 验证方按证明中的树深度重建电路并生成验证密钥，因此证明文件即可自洽验证。
+
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{SingleVerifier, create_proof, keygen_pk, keygen_vk, verify_proof};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+
+use super::circuit::SolvencyCircuit;
+use super::snapshot::LiabilitySnapshot;
+use super::tree::MAX_DEPTH;
+use super::{SolvencyError, decode_field, encode_field};
+
+/// 偿付能力证明（JSON 可序列化）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvencyProof {
+    /// 负债树深度
+    pub depth: u32,
+    /// 负债树根哈希（十六进制）
+    pub root: String,
+    /// 负债合计
+    pub liabilities: u64,
+    /// 资产合计
+    pub assets: u64,
+    /// 证明字节（十六进制）
+    pub proof: String,
+}
+
+impl SolvencyProof {
+    fn instances(&self) -> Result<Vec<Fp>, SolvencyError> {
+        let root = decode_field(&self.root)?;
+        Ok(SolvencyCircuit::instances(root, self.liabilities, self.assets))
+    }
+}
+
+/// 证明 `assets` 足以覆盖快照中的负债
+pub fn prove(snapshot: &LiabilitySnapshot, assets: u64) -> Result<SolvencyProof, SolvencyError> {
+    let liabilities = snapshot.total_liabilities();
+    if assets < liabilities {
+        return Err(SolvencyError::Insolvent { assets, liabilities });
+    }
+    let depth = snapshot.depth();
+    let root = snapshot.root().hash;
+    let params: Params<EqAffine> = Params::new(SolvencyCircuit::k(depth));
+    let vk = keygen_vk(&params, &SolvencyCircuit::empty(depth)).map_err(halo2_error)?;
+    let pk = keygen_pk(&params, vk, &SolvencyCircuit::empty(depth)).map_err(halo2_error)?;
+
+    let circuit = SolvencyCircuit::new(depth, &snapshot.leaves());
+    let instances = SolvencyCircuit::instances(root, liabilities, assets);
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&[&instances]], OsRng, &mut transcript)
+        .map_err(halo2_error)?;
+
+    Ok(SolvencyProof {
+        depth,
+        root: encode_field(&root),
+        liabilities,
+        assets,
+        proof: hex::encode(transcript.finalize()),
+    })
+}
+
+/// 验证证明
+pub fn verify(proof: &SolvencyProof) -> Result<(), SolvencyError> {
+    if proof.depth == 0 || proof.depth > MAX_DEPTH {
+        return Err(SolvencyError::InvalidProof(format!("unsupported depth {}", proof.depth)));
+    }
+    let instances = proof.instances()?;
+    let bytes = hex::decode(&proof.proof)
+        .map_err(|e| SolvencyError::InvalidProof(format!("proof is not hex: {}", e)))?;
+
+    let params: Params<EqAffine> = Params::new(SolvencyCircuit::k(proof.depth));
+    let vk = keygen_vk(&params, &SolvencyCircuit::empty(proof.depth)).map_err(halo2_error)?;
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&bytes[..]);
+    verify_proof(&params, &vk, SingleVerifier::new(&params), &[&[&instances]], &mut transcript)
+        .map_err(|e| SolvencyError::InvalidProof(format!("{:?}", e)))
+}
+
+fn halo2_error(e: halo2_proofs::plonk::Error) -> SolvencyError {
+    SolvencyError::Halo2(format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solvency::snapshot::LiabilityEntry;
+
+    #[test]
+    fn test_prove_and_verify() {
+        let entries = [
+            LiabilityEntry { account_id: 1, balance: 400 },
+            LiabilityEntry { account_id: 2, balance: 600 },
+        ];
+        let snapshot = LiabilitySnapshot::new(entries, [9; 32]).unwrap();
+        assert!(matches!(
+            prove(&snapshot, 999),
+            Err(SolvencyError::Insolvent { assets: 999, liabilities: 1_000 })
+        ));
+
+        let proof = prove(&snapshot, 1_500).unwrap();
+        assert_eq!(proof.liabilities, 1_000);
+        verify(&proof).unwrap();
+
+        // 篡改公开输入后验证失败
+        let json = serde_json::to_string(&proof).unwrap();
+        let mut forged: SolvencyProof = serde_json::from_str(&json).unwrap();
+        forged.liabilities = 900;
+        assert!(verify(&forged).is_err());
+        let mut forged = proof;
+        forged.assets = 999;
+        assert!(verify(&forged).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! 负债快照
+//!
+//! 把定时余额快照（[`BalanceSnapshotBatch`]）中某一资产的账户余额转换为求和树叶子。
+//! 账户按 ID 排序后依次放入叶子；用户标识为 `Keccak256(salt ‖ account_id)`，
+//! salt 由交易所保密保存，只随包含证明发给对应用户。
+
+use base_types::AssetId;
+use base_types::account::balance_snapshot::BalanceSnapshotBatch;
+use halo2_proofs::pasta::Fp;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use super::SolvencyError;
+use super::mimc::field_from_digest;
+use super::tree::{InclusionProof, Leaf, SumNode, SumTree};
+
+/// 单个账户的负债（最小单位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiabilityEntry {
+    pub account_id: u64,
+    pub balance: u64,
+}
+
+/// 负债快照
+#[derive(Debug, Clone)]
+pub struct LiabilitySnapshot {
+    salt: [u8; 32],
+    /// 按账户ID排序
+    entries: Vec<LiabilityEntry>,
+    tree: SumTree,
+}
+
+impl LiabilitySnapshot {
+    pub fn new(
+        entries: impl IntoIterator<Item = LiabilityEntry>,
+        salt: [u8; 32],
+    ) -> Result<Self, SolvencyError> {
+        let mut entries: Vec<LiabilityEntry> = entries.into_iter().collect();
+        entries.sort_by_key(|entry| entry.account_id);
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].account_id == pair[1].account_id)
+        {
+            return Err(SolvencyError::DuplicateAccount(pair[0].account_id));
+        }
+        let leaves: Vec<Leaf> = entries.iter().map(|entry| leaf(&salt, entry)).collect();
+        let tree = SumTree::build(&leaves, SumTree::depth_for(leaves.len()))?;
+        Ok(Self { salt, entries, tree })
+    }
+
+    /// 取余额快照批次中某一资产的总余额（可用 + 冻结）
+    ///
+    /// 余额以 8 位小数的原始整数计；负余额无法证明，直接拒绝。
+    pub fn from_batch(
+        batch: &BalanceSnapshotBatch,
+        asset_id: AssetId,
+        salt: [u8; 32],
+    ) -> Result<Self, SolvencyError> {
+        let entries = batch
+            .accounts
+            .iter()
+            .filter_map(|account| {
+                let balance = account.balance(asset_id)?.total().raw();
+                Some(
+                    u64::try_from(balance)
+                        .map(|balance| LiabilityEntry { account_id: account.account_id.0, balance })
+                        .map_err(|_| SolvencyError::NegativeBalance(account.account_id.0)),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(entries, salt)
+    }
+
+    /// 用户标识
+    pub fn user_tag(salt: &[u8; 32], account_id: u64) -> Fp {
+        let mut hasher = Keccak256::new();
+        hasher.update(salt);
+        hasher.update(account_id.to_le_bytes());
+        field_from_digest(hasher.finalize().into())
+    }
+
+    pub fn entries(&self) -> &[LiabilityEntry] {
+        &self.entries
+    }
+
+    pub fn leaves(&self) -> Vec<Leaf> {
+        self.entries.iter().map(|entry| leaf(&self.salt, entry)).collect()
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.tree.depth()
+    }
+
+    pub fn root(&self) -> SumNode {
+        self.tree.root()
+    }
+
+    pub fn total_liabilities(&self) -> u64 {
+        self.tree.root().sum
+    }
+
+    /// 账户的包含证明
+    pub fn inclusion_proof(&self, account_id: u64) -> Option<InclusionProof> {
+        let index =
+            self.entries.binary_search_by_key(&account_id, |entry| entry.account_id).ok()?;
+        self.tree.proof(index, leaf(&self.salt, &self.entries[index]))
+    }
+}
+
+fn leaf(salt: &[u8; 32], entry: &LiabilityEntry) -> Leaf {
+    Leaf { user: LiabilitySnapshot::user_tag(salt, entry.account_id), balance: entry.balance }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_types::account::balance::Balance;
+    use base_types::account::balance_snapshot::{BalanceSnapshotService, SnapshotSchedule};
+    use base_types::{AccountId, Quantity, Timestamp};
+
+    use super::*;
+
+    fn balance(account: u64, asset_id: AssetId, available: i64, frozen: i64) -> Balance {
+        let mut balance = Balance::new(AccountId(account), asset_id, Timestamp(0));
+        balance.available = Quantity::from_raw(available);
+        balance.frozen = Quantity::from_raw(frozen);
+        balance
+    }
+
+    #[test]
+    fn test_from_batch() {
+        let balances = [
+            balance(2, AssetId::Btc, 300, 50),
+            balance(1, AssetId::Btc, 100, 0),
+            balance(1, AssetId::Usdt, 9_999, 0),
+        ];
+        let mut service = BalanceSnapshotService::new(SnapshotSchedule::Hourly, Timestamp(0));
+        let batch = service.capture(7, Timestamp(0), &balances).unwrap().clone();
+
+        let snapshot = LiabilitySnapshot::from_batch(&batch, AssetId::Btc, [1; 32]).unwrap();
+        assert_eq!(
+            snapshot.entries(),
+            &[
+                LiabilityEntry { account_id: 1, balance: 100 },
+                LiabilityEntry { account_id: 2, balance: 350 }
+            ]
+        );
+        assert_eq!(snapshot.total_liabilities(), 350 + 100);
+        assert!(snapshot.inclusion_proof(2).unwrap().verify(&snapshot.root()));
+        assert!(snapshot.inclusion_proof(3).is_none());
+
+        // 不同 salt 得到不同的根
+        let other = LiabilitySnapshot::from_batch(&batch, AssetId::Btc, [2; 32]).unwrap();
+        assert_ne!(other.root(), snapshot.root());
+
+        let negative = [balance(3, AssetId::Btc, -1, 0)];
+        let batch = service.capture(8, Timestamp(1), &negative).unwrap().clone();
+        assert!(matches!(
+            LiabilitySnapshot::from_batch(&batch, AssetId::Btc, [1; 32]),
+            Err(SolvencyError::NegativeBalance(3))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_account_rejected() {
+        let entry = LiabilityEntry { account_id: 1, balance: 1 };
+        assert!(matches!(
+            LiabilitySnapshot::new([entry, entry], [0; 32]),
+            Err(SolvencyError::DuplicateAccount(1))
+        ));
+    }
+}
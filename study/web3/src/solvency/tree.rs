@@ -0,0 +1,173 @@
+//! 负债 Merkle 求和树
+//!
+//! 叶子为 (用户标识, 余额)，内部节点同时承诺左右子树的哈希与余额合计：
+//! `hash = H(NODE_IV; l.hash, l.sum, r.hash, r.sum)`，`sum = l.sum + r.sum`。
+//! 只承诺总和的求和树允许在兄弟节点之间挪移余额，分别承诺两侧合计可避免该问题。
+//! 叶子数补齐到 `2^depth`，空位为 (0, 0)。
+
+use halo2_proofs::pasta::Fp;
+
+use super::SolvencyError;
+use super::mimc::{self, LEAF_IV, NODE_IV};
+
+/// 允许的最大深度（1024 个账户）
+pub const MAX_DEPTH: u32 = 10;
+
+/// 叶子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leaf {
+    /// 用户标识（加盐哈希，不暴露账户ID）
+    pub user: Fp,
+    /// 余额（最小单位）
+    pub balance: u64,
+}
+
+impl Leaf {
+    /// 补位用的空叶子
+    pub fn empty() -> Self {
+        Leaf { user: Fp::from(0), balance: 0 }
+    }
+
+    pub fn node(&self) -> SumNode {
+        SumNode {
+            hash: mimc::hash(LEAF_IV, &[self.user, Fp::from(self.balance)]),
+            sum: self.balance,
+        }
+    }
+}
+
+/// 树节点：哈希与子树余额合计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SumNode {
+    pub hash: Fp,
+    pub sum: u64,
+}
+
+impl SumNode {
+    /// 合并左右子节点（合计溢出时返回 None）
+    pub fn parent(left: &SumNode, right: &SumNode) -> Option<SumNode> {
+        let sum = left.sum.checked_add(right.sum)?;
+        let hash =
+            mimc::hash(NODE_IV, &[left.hash, Fp::from(left.sum), right.hash, Fp::from(right.sum)]);
+        Some(SumNode { hash, sum })
+    }
+}
+
+/// 负债求和树
+#[derive(Debug, Clone)]
+pub struct SumTree {
+    /// levels[0] 为叶子层，最后一层只有根
+    levels: Vec<Vec<SumNode>>,
+}
+
+impl SumTree {
+    /// 容纳 `count` 个叶子所需的深度（至少为 1）
+    pub fn depth_for(count: usize) -> u32 {
+        count.max(2).next_power_of_two().trailing_zeros()
+    }
+
+    pub fn build(leaves: &[Leaf], depth: u32) -> Result<Self, SolvencyError> {
+        if depth == 0 || depth > MAX_DEPTH || leaves.len() > 1 << depth {
+            return Err(SolvencyError::TooManyAccounts(leaves.len()));
+        }
+        let mut level: Vec<SumNode> = leaves.iter().map(Leaf::node).collect();
+        level.resize(1 << depth, Leaf::empty().node());
+
+        let mut levels = vec![level];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| SumNode::parent(&pair[0], &pair[1]).ok_or(SolvencyError::Overflow))
+                .collect::<Result<Vec<_>, _>>()?;
+            levels.push(parents);
+        }
+        Ok(Self { levels })
+    }
+
+    pub fn depth(&self) -> u32 {
+        (self.levels.len() - 1) as u32
+    }
+
+    pub fn root(&self) -> SumNode {
+        self.levels.last().expect("levels is never empty")[0]
+    }
+
+    /// 第 `index` 个叶子的包含证明
+    pub fn proof(&self, index: usize, leaf: Leaf) -> Option<InclusionProof> {
+        if self.levels[0].get(index) != Some(&leaf.node()) {
+            return None;
+        }
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        Some(InclusionProof { index, leaf, siblings })
+    }
+}
+
+/// 包含证明：用户可据此确认自己的余额被计入已证明的负债合计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub index: usize,
+    pub leaf: Leaf,
+    /// 自底向上的兄弟节点
+    pub siblings: Vec<SumNode>,
+}
+
+impl InclusionProof {
+    /// 重算根节点
+    pub fn compute_root(&self) -> Option<SumNode> {
+        self.siblings.iter().enumerate().try_fold(self.leaf.node(), |node, (height, sibling)| {
+            if (self.index >> height) & 1 == 0 {
+                SumNode::parent(&node, sibling)
+            } else {
+                SumNode::parent(sibling, &node)
+            }
+        })
+    }
+
+    pub fn verify(&self, root: &SumNode) -> bool {
+        self.compute_root().as_ref() == Some(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves() -> Vec<Leaf> {
+        (1..=3).map(|i| Leaf { user: Fp::from(i * 11), balance: i * 100 }).collect()
+    }
+
+    #[test]
+    fn test_root_sum_and_inclusion() {
+        let leaves = leaves();
+        let tree = SumTree::build(&leaves, SumTree::depth_for(leaves.len())).unwrap();
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.root().sum, 600);
+
+        let proof = tree.proof(2, leaves[2]).unwrap();
+        assert!(proof.verify(&tree.root()));
+
+        // 篡改余额或兄弟节点合计都会使证明失效
+        let mut forged = proof.clone();
+        forged.leaf.balance += 1;
+        assert!(!forged.verify(&tree.root()));
+        let mut forged = proof;
+        forged.siblings[1].sum -= 1;
+        assert!(!forged.verify(&tree.root()));
+
+        assert!(tree.proof(1, leaves[2]).is_none());
+    }
+
+    #[test]
+    fn test_capacity_and_overflow() {
+        assert_eq!(SumTree::depth_for(1), 1);
+        assert_eq!(SumTree::depth_for(5), 3);
+        assert!(matches!(SumTree::build(&leaves(), 1), Err(SolvencyError::TooManyAccounts(3))));
+        let huge =
+            [Leaf { user: Fp::from(1), balance: u64::MAX }, Leaf { user: Fp::from(2), balance: 1 }];
+        assert!(matches!(SumTree::build(&huge, 1), Err(SolvencyError::Overflow)));
+    }
+}
@@ -0,0 +1,32 @@
+-- 端到端测试的读模型，由交易所事件投影写入；金额均为 Decimal 原始整数（8 位小数）
+CREATE TABLE IF NOT EXISTS balances (
+    account_id BIGINT UNSIGNED NOT NULL,
+    asset VARCHAR(16) NOT NULL,
+    available BIGINT NOT NULL,
+    frozen BIGINT NOT NULL,
+    PRIMARY KEY (account_id, asset)
+);
+
+CREATE TABLE IF NOT EXISTS orders (
+    order_id BIGINT UNSIGNED NOT NULL PRIMARY KEY,
+    account_id BIGINT UNSIGNED NOT NULL,
+    symbol VARCHAR(32) NOT NULL,
+    side VARCHAR(4) NOT NULL,
+    price BIGINT NULL,
+    quantity BIGINT NOT NULL,
+    filled BIGINT NOT NULL DEFAULT 0,
+    status VARCHAR(20) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS trades (
+    trade_id BIGINT UNSIGNED NOT NULL PRIMARY KEY,
+    symbol VARCHAR(32) NOT NULL,
+    price BIGINT NOT NULL,
+    quantity BIGINT NOT NULL,
+    buyer BIGINT UNSIGNED NOT NULL,
+    seller BIGINT UNSIGNED NOT NULL,
+    maker_order_id BIGINT UNSIGNED NOT NULL,
+    taker_order_id BIGINT UNSIGNED NOT NULL,
+    buyer_fee BIGINT NOT NULL,
+    seller_fee BIGINT NOT NULL
+);
@@ -0,0 +1,49 @@
+//! MySQL 测试环境
+//!
+//! 设置了 `E2E_MYSQL_URL` 时直接连接（配合 `docker-compose.yml` 或 CI 提供的数据库），
+//! 否则用 testcontainers 启动一次性的 MySQL 容器，随 [`MySqlEnv`] 析构一并删除
+
+use std::env;
+
+use testcontainers_modules::mysql::Mysql;
+use testcontainers_modules::testcontainers::Container;
+use testcontainers_modules::testcontainers::runners::SyncRunner;
+
+use crate::store::{MySqlStore, StoreError};
+
+/// 外部数据库地址的环境变量
+pub const MYSQL_URL_ENV: &str = "E2E_MYSQL_URL";
+
+/// 一个可连接的 MySQL
+pub struct MySqlEnv {
+    url: String,
+    /// 由本环境启动的容器（外部数据库为 None）
+    _container: Option<Container<Mysql>>,
+}
+
+impl MySqlEnv {
+    /// 使用外部数据库或启动容器
+    pub fn start() -> Result<Self, StoreError> {
+        if let Ok(url) = env::var(MYSQL_URL_ENV) {
+            return Ok(Self { url, _container: None });
+        }
+        let container = Mysql::default().start().map_err(|e| StoreError(e.to_string()))?;
+        let host = container.get_host().map_err(|e| StoreError(e.to_string()))?;
+        let port = container.get_host_port_ipv4(3306).map_err(|e| StoreError(e.to_string()))?;
+        Ok(Self {
+            url: format!("mysql://root@{}:{}/test", host, port),
+            _container: Some(container),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 连接、建表并清空数据，得到一个干净的存储
+    pub fn store(&self) -> Result<MySqlStore, StoreError> {
+        let mut store = MySqlStore::connect(&self.url)?;
+        store.truncate()?;
+        Ok(store)
+    }
+}
@@ -0,0 +1,359 @@
+//! 网关 + 引擎联调环境
+//!
+//! [`GatewayProcess`] 启动编译好的 `pingora_gateway`（固定监听 8080，默认后端 3001），
+//! [`EngineBackend`] 在 3001 上把网关转发来的下单 / 撤单请求交给 [`Harness`] 中的引擎。
+//! 请求经过网关的真实链路（鉴权、调用量记账、降级、转发），断言既可以看 HTTP 响应，
+//! 也可以看引擎推送与读模型
+//!
+//! 现货行为 crate 尚无源码，后端请求体用下面的最小 JSON，
+//! 现货引擎后端落地后换成其命令格式即可：
+//!
+//! ```text
+//! POST /api/spot/order/         {"accountId":1,"symbol":"BTCUSDT","side":"BUY","price":40000,"quantity":0.4}
+//! POST /api/spot/order/cancel/  {"orderId":1}
+//! ```
+
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use base_types::{AccountId, OrderSide, Price, Quantity, TradingPair};
+use rustlob_core::{OrderRequest, OrderStatus};
+use serde_json::{Value, json};
+
+use crate::{Harness, StateStore};
+
+/// 网关可执行文件路径的环境变量（未设置时取工作区 `target/debug/pingora_gateway`）
+pub const GATEWAY_BIN_ENV: &str = "E2E_GATEWAY_BIN";
+/// 网关监听地址
+pub const GATEWAY_ADDR: &str = "127.0.0.1:8080";
+/// 网关默认后端地址
+pub const BACKEND_ADDR: &str = "127.0.0.1:3001";
+
+/// 等待网关就绪的上限
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP 响应
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// 原始响应头（不含状态行）
+    pub headers: String,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// 响应头的值（名称不区分大小写）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    pub fn json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::from_str(&self.body)
+    }
+}
+
+/// 发送一个 HTTP/1.1 请求（`Connection: close`），读到连接关闭为止
+pub fn http_request(
+    addr: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> io::Result<HttpResponse> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut request =
+        format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, addr);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if method == "POST" {
+        request.push_str("Content-Type: application/json\r\n");
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes())?;
+
+    let mut raw = Vec::new();
+    match stream.read_to_end(&mut raw) {
+        Ok(_) => {}
+        // 网关在转发结束后可能直接复位连接，已读到的响应仍然有效
+        Err(e) if !raw.is_empty() && e.kind() == io::ErrorKind::ConnectionReset => {}
+        Err(e) => return Err(e),
+    }
+    parse_response(&raw)
+}
+
+fn parse_response(raw: &[u8]) -> io::Result<HttpResponse> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response");
+    let text = String::from_utf8_lossy(raw);
+    let (head, body) = text.split_once("\r\n\r\n").ok_or_else(invalid)?;
+    let (status_line, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+    let status = status_line.split_whitespace().nth(1).and_then(|s| s.parse().ok());
+    Ok(HttpResponse {
+        status: status.ok_or_else(invalid)?,
+        headers: headers.to_string(),
+        body: body.to_string(),
+    })
+}
+
+/// 运行中的网关进程，析构时结束进程
+pub struct GatewayProcess {
+    child: Child,
+}
+
+impl GatewayProcess {
+    /// 启动网关并等待其开始监听
+    ///
+    /// `envs` 为附加的网关配置（如管理令牌）
+    pub fn start(envs: &[(&str, &str)]) -> io::Result<Self> {
+        let bin = env::var(GATEWAY_BIN_ENV).map(PathBuf::from).unwrap_or_else(|_| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/debug/pingora_gateway")
+        });
+        if !bin.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "gateway binary {} not found (cargo build -p pingora_gateway or set {})",
+                    bin.display(),
+                    GATEWAY_BIN_ENV
+                ),
+            ));
+        }
+        let child = Command::new(bin)
+            .envs(envs.iter().copied())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut gateway = Self { child };
+
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while TcpStream::connect(GATEWAY_ADDR).is_err() {
+            if let Some(status) = gateway.child.try_wait()? {
+                return Err(io::Error::other(format!("gateway exited during startup: {}", status)));
+            }
+            if Instant::now() > deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "gateway did not start"));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        Ok(gateway)
+    }
+
+    /// 经网关发送请求
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> io::Result<HttpResponse> {
+        http_request(GATEWAY_ADDR, method, path, headers, body)
+    }
+}
+
+impl Drop for GatewayProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// 后端收到的请求（断言网关转发了什么）
+#[derive(Debug, Clone)]
+pub struct ForwardedRequest {
+    pub method: String,
+    pub path: String,
+    /// 原始请求头（不含请求行）
+    pub headers: String,
+    pub body: String,
+}
+
+/// 把网关转发的请求交给引擎的后端
+pub struct EngineBackend {
+    requests: Arc<Mutex<Vec<ForwardedRequest>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl EngineBackend {
+    /// 在 [`BACKEND_ADDR`] 监听
+    pub fn start<S: StateStore + Send + 'static>(
+        harness: Arc<Mutex<Harness<S>>>,
+    ) -> io::Result<Self> {
+        Self::start_on(BACKEND_ADDR, harness)
+    }
+
+    pub fn start_on<S: StateStore + Send + 'static>(
+        addr: &str,
+        harness: Arc<Mutex<Harness<S>>>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (log, stopped) = (requests.clone(), stop.clone());
+        let thread =
+            thread::Builder::new().name("e2e-engine-backend".to_string()).spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            // 单个连接出错不影响后续请求，由场景断言兜底
+                            let _ = serve(stream, &harness, &log);
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(5));
+                        }
+                        Err(_) => return,
+                    }
+                }
+            })?;
+        Ok(Self { requests, stop, thread: Some(thread) })
+    }
+
+    /// 已收到的请求
+    pub fn requests(&self) -> Vec<ForwardedRequest> {
+        self.requests.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl Drop for EngineBackend {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve<S: StateStore + 'static>(
+    stream: TcpStream,
+    harness: &Mutex<Harness<S>>,
+    log: &Mutex<Vec<ForwardedRequest>>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = String::new();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        headers.push_str(&line);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    let (status, response) = {
+        let mut harness = harness.lock().unwrap_or_else(PoisonError::into_inner);
+        dispatch(&mut harness, &method, &path, &body)
+    };
+    log.lock().unwrap_or_else(PoisonError::into_inner).push(ForwardedRequest {
+        method,
+        path,
+        headers,
+        body,
+    });
+
+    let response = response.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        if status == 200 { "OK" } else { "Bad Request" },
+        response.len(),
+        response
+    )?;
+    stream.flush()?;
+    stream.shutdown(Shutdown::Both)
+}
+
+/// 下单 / 撤单交给引擎，返回 (状态码, 响应体)
+fn dispatch<S: StateStore + 'static>(
+    harness: &mut Harness<S>,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> (u16, Value) {
+    let bad_request = |msg: String| (400, json!({ "msg": msg }));
+    if method != "POST" {
+        return bad_request(format!("Unsupported method: {}", method));
+    }
+    let Ok(body) = serde_json::from_str::<Value>(body) else {
+        return bad_request("Invalid JSON body".to_string());
+    };
+    match path {
+        "/api/spot/order/" => {
+            let Some(request) = order_request(&body) else {
+                return bad_request("Invalid order".to_string());
+            };
+            match harness.exchange.submit(request) {
+                Ok(result) => (
+                    200,
+                    json!({
+                        "orderId": result.order_id,
+                        "status": match result.status {
+                            OrderStatus::Resting => "NEW",
+                            OrderStatus::Filled => "FILLED",
+                            OrderStatus::Cancelled => "CANCELED",
+                        },
+                        "filled": result.filled.to_f64(),
+                        "remaining": result.remaining.to_f64(),
+                    }),
+                ),
+                Err(e) => bad_request(e.to_string()),
+            }
+        }
+        "/api/spot/order/cancel/" => {
+            let Some(order_id) = body["orderId"].as_u64() else {
+                return bad_request("Missing orderId".to_string());
+            };
+            match harness.exchange.cancel(order_id) {
+                Ok(()) => (200, json!({ "orderId": order_id, "status": "CANCELED" })),
+                Err(e) => bad_request(e.to_string()),
+            }
+        }
+        _ => bad_request(format!("Unknown path: {}", path)),
+    }
+}
+
+fn order_request(body: &Value) -> Option<OrderRequest> {
+    let side = match body["side"].as_str()? {
+        "BUY" => OrderSide::Buy,
+        "SELL" => OrderSide::Sell,
+        _ => return None,
+    };
+    Some(OrderRequest::limit(
+        AccountId(body["accountId"].as_u64()?),
+        TradingPair::from_symbol_str(body["symbol"].as_str()?)?,
+        side,
+        Price::from_f64(body["price"].as_f64()?),
+        Quantity::from_f64(body["quantity"].as_f64()?),
+    ))
+}
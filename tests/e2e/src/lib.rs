@@ -0,0 +1,72 @@
+//! 跨模块端到端测试
+//!
+//! 以 `rustlob-core` 的 [`Exchange`] 作为撮合 + 账户 + 清算引擎，事件流同时：
+//! - 收集为推送流（与 WebSocket 推送的事件一一对应），供场景断言推送顺序与内容
+//! - 投影进 [`StateStore`]（MySQL 或内存），供场景断言落库状态
+//!
+//! 场景见 `tests/order_lifecycle.rs`（充值 → 下单 → 部分成交 → 撤单 → 提现）与
+//! `tests/gateway.rs`（经网关下单、撤单）。本 crate 不在默认构建成员中，
+//! 外部依赖按特性开启：
+//!
+//! ```text
+//! cargo test -p e2e                                          # 内存存储
+//! cargo test -p e2e --features docker                        # testcontainers 自动启动 MySQL
+//! E2E_MYSQL_URL=mysql://... cargo test -p e2e --features docker   # 使用已有数据库
+//! cargo build -p pingora_gateway && cargo test -p e2e --features gateway
+//! ```
+//!
+//! 网关联调见 [`gateway`]：启动真实网关进程，后端由 [`Harness`] 中的引擎应答
+
+#[cfg(feature = "docker")]
+pub mod env;
+#[cfg(feature = "gateway")]
+pub mod gateway;
+pub mod store;
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use base_types::{ManualClock, Timestamp};
+use rustlob_core::{Exchange, ExchangeConfig, ExchangeEvent};
+
+pub use store::{BalanceRow, MemoryStore, OrderRow, StateStore, StoreError, TradeRow};
+
+/// 场景测试环境：引擎 + 推送流 + 读模型
+pub struct Harness<S: StateStore + 'static> {
+    pub exchange: Exchange,
+    pub clock: ManualClock,
+    store: Arc<Mutex<S>>,
+    pushes: Arc<Mutex<Vec<ExchangeEvent>>>,
+    /// 投影失败记录（回调内无法返回错误）
+    failures: Arc<Mutex<Vec<StoreError>>>,
+}
+
+impl<S: StateStore + 'static> Harness<S> {
+    pub fn new(config: ExchangeConfig, store: S) -> Self {
+        let clock = ManualClock::new(Timestamp(1_700_000_000_000_000_000));
+        let mut exchange = Exchange::new(config).with_clock(Arc::new(clock.clone()));
+        let store = Arc::new(Mutex::new(store));
+        let pushes = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(Vec::new()));
+
+        let (sink, feed, errors) = (store.clone(), pushes.clone(), failures.clone());
+        exchange.on_event(move |event| {
+            feed.lock().unwrap().push(event.clone());
+            if let Err(e) = sink.lock().unwrap().apply(event) {
+                errors.lock().unwrap().push(e);
+            }
+        });
+        Self { exchange, clock, store, pushes, failures }
+    }
+
+    /// 读模型（断言前先确认投影没有失败）
+    pub fn store(&self) -> MutexGuard<'_, S> {
+        let failures = self.failures.lock().unwrap();
+        assert!(failures.is_empty(), "projection failed: {:?}", *failures);
+        self.store.lock().unwrap()
+    }
+
+    /// 取出自上次调用以来的推送
+    pub fn take_pushes(&self) -> Vec<ExchangeEvent> {
+        std::mem::take(&mut *self.pushes.lock().unwrap())
+    }
+}
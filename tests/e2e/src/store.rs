@@ -0,0 +1,381 @@
+//! 事件投影后的读模型
+//!
+//! [`StateStore`] 把交易所事件写成余额 / 订单 / 成交三张表，场景断言只读这些表，
+//! 与生产读侧一样不直接访问引擎内部状态。[`MemoryStore`] 用于无 Docker 环境，
+//! `MySqlStore`（`docker` 特性）写入真实数据库，表结构见 `sql/schema.sql`
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use base_types::{AccountId, AssetId, OrderSide};
+use rustlob_core::{ExchangeEvent, OrderId, OrderStatus, Trade};
+
+/// 余额行（Decimal 原始整数）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BalanceRow {
+    pub available: i64,
+    pub frozen: i64,
+}
+
+/// 订单行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRow {
+    pub order_id: OrderId,
+    pub account_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub price: Option<i64>,
+    pub quantity: i64,
+    pub filled: i64,
+    /// NEW / PARTIALLY_FILLED / FILLED / CANCELED
+    pub status: String,
+}
+
+/// 成交行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeRow {
+    pub trade_id: u64,
+    pub symbol: String,
+    pub price: i64,
+    pub quantity: i64,
+    pub buyer: u64,
+    pub seller: u64,
+    pub maker_order_id: OrderId,
+    pub taker_order_id: OrderId,
+    pub buyer_fee: i64,
+    pub seller_fee: i64,
+}
+
+impl From<&Trade> for TradeRow {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            symbol: trade.trading_pair.to_symbol_string().to_string(),
+            price: trade.price.raw(),
+            quantity: trade.quantity.raw(),
+            buyer: trade.buyer.0,
+            seller: trade.seller.0,
+            maker_order_id: trade.maker_order_id,
+            taker_order_id: trade.taker_order_id,
+            buyer_fee: trade.buyer_fee.raw(),
+            seller_fee: trade.seller_fee.raw(),
+        }
+    }
+}
+
+/// 存储错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreError(pub String);
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Store error: {}", self.0)
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// 读模型存储
+pub trait StateStore: Send {
+    /// 投影一个事件
+    fn apply(&mut self, event: &ExchangeEvent) -> Result<(), StoreError>;
+
+    fn balance(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+    ) -> Result<Option<BalanceRow>, StoreError>;
+
+    fn order(&mut self, order_id: OrderId) -> Result<Option<OrderRow>, StoreError>;
+
+    /// 账户参与的成交（按成交ID排序）
+    fn trades(&mut self, account: AccountId) -> Result<Vec<TradeRow>, StoreError>;
+}
+
+pub(crate) fn side_str(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "BUY",
+        OrderSide::Sell => "SELL",
+    }
+}
+
+pub(crate) fn status_str(status: OrderStatus) -> &'static str {
+    match status {
+        OrderStatus::Resting => "NEW",
+        OrderStatus::Filled => "FILLED",
+        OrderStatus::Cancelled => "CANCELED",
+    }
+}
+
+/// 内存存储
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    balances: BTreeMap<(u64, &'static str), BalanceRow>,
+    orders: BTreeMap<OrderId, OrderRow>,
+    trades: BTreeMap<u64, TradeRow>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn fill(&mut self, order_id: OrderId, quantity: i64) {
+        if let Some(order) = self.orders.get_mut(&order_id) {
+            order.filled += quantity;
+            if order.status == "NEW" {
+                order.status = "PARTIALLY_FILLED".to_string();
+            }
+        }
+    }
+}
+
+impl StateStore for MemoryStore {
+    fn apply(&mut self, event: &ExchangeEvent) -> Result<(), StoreError> {
+        match event {
+            ExchangeEvent::OrderAccepted {
+                order_id,
+                account,
+                trading_pair,
+                side,
+                price,
+                quantity,
+            } => {
+                self.orders.insert(
+                    *order_id,
+                    OrderRow {
+                        order_id: *order_id,
+                        account_id: account.0,
+                        symbol: trading_pair.to_symbol_string().to_string(),
+                        side: side_str(*side).to_string(),
+                        price: price.map(|p| p.raw()),
+                        quantity: quantity.raw(),
+                        filled: 0,
+                        status: status_str(OrderStatus::Resting).to_string(),
+                    },
+                );
+            }
+            ExchangeEvent::Trade(trade) => {
+                self.fill(trade.maker_order_id, trade.quantity.raw());
+                self.fill(trade.taker_order_id, trade.quantity.raw());
+                self.trades.insert(trade.trade_id, TradeRow::from(trade));
+            }
+            ExchangeEvent::OrderClosed { order_id, status, .. } => {
+                let order = self.orders.get_mut(order_id).ok_or_else(|| {
+                    StoreError(format!("order {} closed before accepted", order_id))
+                })?;
+                order.status = status_str(*status).to_string();
+            }
+            ExchangeEvent::BalanceChanged { account, asset, available, frozen } => {
+                self.balances.insert(
+                    (account.0, asset.as_str()),
+                    BalanceRow { available: available.raw(), frozen: frozen.raw() },
+                );
+            }
+            // 账户状态与条件单不落库，冻结撤单与条件单触发的下单已体现为订单事件
+            ExchangeEvent::AccountStatusChanged { .. }
+            | ExchangeEvent::ConditionalTriggered { .. }
+            | ExchangeEvent::ConditionalCancelled { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn balance(
+        &mut self,
+        account: AccountId,
+        asset: AssetId,
+    ) -> Result<Option<BalanceRow>, StoreError> {
+        Ok(self.balances.get(&(account.0, asset.as_str())).copied())
+    }
+
+    fn order(&mut self, order_id: OrderId) -> Result<Option<OrderRow>, StoreError> {
+        Ok(self.orders.get(&order_id).cloned())
+    }
+
+    fn trades(&mut self, account: AccountId) -> Result<Vec<TradeRow>, StoreError> {
+        Ok(self
+            .trades
+            .values()
+            .filter(|t| t.buyer == account.0 || t.seller == account.0)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(feature = "docker")]
+pub use mysql_store::MySqlStore;
+
+#[cfg(feature = "docker")]
+mod mysql_store {
+    use mysql::prelude::Queryable;
+    use mysql::{Pool, PooledConn};
+
+    use super::*;
+
+    const SCHEMA: &str = include_str!("../sql/schema.sql");
+
+    /// MySQL 存储（每个事件一条或几条语句，直接写入，便于断言时读到最新状态）
+    pub struct MySqlStore {
+        conn: PooledConn,
+    }
+
+    fn db(e: mysql::Error) -> StoreError {
+        StoreError(e.to_string())
+    }
+
+    impl MySqlStore {
+        /// 连接并建表（表已存在时保留）
+        pub fn connect(url: &str) -> Result<Self, StoreError> {
+            let pool = Pool::new(url).map_err(db)?;
+            let mut conn = pool.get_conn().map_err(db)?;
+            for statement in SCHEMA.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                conn.query_drop(statement).map_err(db)?;
+            }
+            Ok(Self { conn })
+        }
+
+        /// 清空全部表（同一数据库上运行多个场景时使用）
+        pub fn truncate(&mut self) -> Result<(), StoreError> {
+            for table in ["balances", "orders", "trades"] {
+                self.conn.query_drop(format!("TRUNCATE TABLE {}", table)).map_err(db)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl StateStore for MySqlStore {
+        fn apply(&mut self, event: &ExchangeEvent) -> Result<(), StoreError> {
+            match event {
+                ExchangeEvent::OrderAccepted {
+                    order_id,
+                    account,
+                    trading_pair,
+                    side,
+                    price,
+                    quantity,
+                } => self.conn.exec_drop(
+                    "INSERT INTO orders (order_id, account_id, symbol, side, price, quantity, \
+                     filled, status) VALUES (?, ?, ?, ?, ?, ?, 0, 'NEW')",
+                    (
+                        order_id,
+                        account.0,
+                        trading_pair.to_symbol_string(),
+                        side_str(*side),
+                        price.map(|p| p.raw()),
+                        quantity.raw(),
+                    ),
+                ),
+                ExchangeEvent::Trade(trade) => {
+                    let row = TradeRow::from(trade);
+                    for order_id in [row.maker_order_id, row.taker_order_id] {
+                        self.conn
+                            .exec_drop(
+                                "UPDATE orders SET filled = filled + ?, status = IF(status = \
+                                 'NEW', 'PARTIALLY_FILLED', status) WHERE order_id = ?",
+                                (row.quantity, order_id),
+                            )
+                            .map_err(db)?;
+                    }
+                    self.conn.exec_drop(
+                        "INSERT INTO trades (trade_id, symbol, price, quantity, buyer, seller, \
+                         maker_order_id, taker_order_id, buyer_fee, seller_fee) \
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        (
+                            row.trade_id,
+                            row.symbol,
+                            row.price,
+                            row.quantity,
+                            row.buyer,
+                            row.seller,
+                            row.maker_order_id,
+                            row.taker_order_id,
+                            row.buyer_fee,
+                            row.seller_fee,
+                        ),
+                    )
+                }
+                ExchangeEvent::OrderClosed { order_id, status, .. } => self.conn.exec_drop(
+                    "UPDATE orders SET status = ? WHERE order_id = ?",
+                    (status_str(*status), order_id),
+                ),
+                ExchangeEvent::BalanceChanged { account, asset, available, frozen } => {
+                    self.conn.exec_drop(
+                        "INSERT INTO balances (account_id, asset, available, frozen) \
+                         VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE \
+                         available = VALUES(available), frozen = VALUES(frozen)",
+                        (account.0, asset.as_str(), available.raw(), frozen.raw()),
+                    )
+                }
+                ExchangeEvent::AccountStatusChanged { .. }
+                | ExchangeEvent::ConditionalTriggered { .. }
+                | ExchangeEvent::ConditionalCancelled { .. } => Ok(()),
+            }
+            .map_err(db)
+        }
+
+        fn balance(
+            &mut self,
+            account: AccountId,
+            asset: AssetId,
+        ) -> Result<Option<BalanceRow>, StoreError> {
+            let row: Option<(i64, i64)> = self
+                .conn
+                .exec_first(
+                    "SELECT available, frozen FROM balances WHERE account_id = ? AND asset = ?",
+                    (account.0, asset.as_str()),
+                )
+                .map_err(db)?;
+            Ok(row.map(|(available, frozen)| BalanceRow { available, frozen }))
+        }
+
+        fn order(&mut self, order_id: OrderId) -> Result<Option<OrderRow>, StoreError> {
+            type Row = (u64, String, String, Option<i64>, i64, i64, String);
+            let row: Option<Row> = self
+                .conn
+                .exec_first(
+                    "SELECT account_id, symbol, side, price, quantity, filled, status \
+                     FROM orders WHERE order_id = ?",
+                    (order_id,),
+                )
+                .map_err(db)?;
+            Ok(row.map(|(account_id, symbol, side, price, quantity, filled, status)| OrderRow {
+                order_id,
+                account_id,
+                symbol,
+                side,
+                price,
+                quantity,
+                filled,
+                status,
+            }))
+        }
+
+        fn trades(&mut self, account: AccountId) -> Result<Vec<TradeRow>, StoreError> {
+            type Row = (u64, String, i64, i64, u64, u64, u64, u64, i64, i64);
+            let rows: Vec<Row> = self
+                .conn
+                .exec(
+                    "SELECT trade_id, symbol, price, quantity, buyer, seller, maker_order_id, \
+                     taker_order_id, buyer_fee, seller_fee FROM trades \
+                     WHERE buyer = ? OR seller = ? ORDER BY trade_id",
+                    (account.0, account.0),
+                )
+                .map_err(db)?;
+            Ok(rows
+                .into_iter()
+                .map(|r| TradeRow {
+                    trade_id: r.0,
+                    symbol: r.1,
+                    price: r.2,
+                    quantity: r.3,
+                    buyer: r.4,
+                    seller: r.5,
+                    maker_order_id: r.6,
+                    taker_order_id: r.7,
+                    buyer_fee: r.8,
+                    seller_fee: r.9,
+                })
+                .collect())
+        }
+    }
+}
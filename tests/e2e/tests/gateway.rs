@@ -0,0 +1,62 @@
+//! 经网关下单与撤单：请求走真实网关进程，后端由引擎应答
+#![cfg(feature = "gateway")]
+
+use std::sync::{Arc, Mutex};
+
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{AccountId, AssetId, Quantity, TradingPair};
+use e2e::gateway::{EngineBackend, GatewayProcess};
+use e2e::{Harness, MemoryStore, StateStore};
+use rustlob_core::ExchangeConfig;
+
+const ALICE: AccountId = AccountId(1);
+const BOB: AccountId = AccountId(2);
+
+fn raw(value: f64) -> i64 {
+    Quantity::from_f64(value).raw()
+}
+
+#[test]
+fn order_through_gateway() {
+    let config = ExchangeConfig::new([TradingPair::BtcUsdt])
+        .with_fees(ProductFeeConfig::spot(0.001, 0.002), AccountId(99));
+    let harness = Arc::new(Mutex::new(Harness::new(config, MemoryStore::new())));
+    {
+        let mut harness = harness.lock().unwrap();
+        harness.exchange.deposit(ALICE, AssetId::Usdt, Quantity::from_f64(100_000.0)).unwrap();
+        harness.exchange.deposit(BOB, AssetId::Btc, Quantity::from_f64(2.0)).unwrap();
+    }
+    let backend = EngineBackend::start(harness.clone()).expect("bind engine backend");
+    let gateway = GatewayProcess::start(&[]).expect("start gateway");
+
+    // 网关直接应答的接口不到达后端
+    let time = gateway.request("GET", "/api/time", &[], "").unwrap();
+    assert_eq!(time.status, 200);
+    assert!(time.json().unwrap()["serverTime"].is_u64());
+
+    let ask = r#"{"accountId":2,"symbol":"BTCUSDT","side":"SELL","price":40000,"quantity":1}"#;
+    let ask = gateway.request("POST", "/api/spot/order/", &[], ask).unwrap();
+    assert_eq!(ask.status, 200, "{}", ask.body);
+    assert_eq!(ask.header("Content-Type"), Some("application/json"));
+    let ask_id = ask.json().unwrap()["orderId"].as_u64().unwrap();
+
+    let bid = r#"{"accountId":1,"symbol":"BTCUSDT","side":"BUY","price":40000,"quantity":0.4}"#;
+    let bid = gateway.request("POST", "/api/spot/order/", &[], bid).unwrap();
+    assert_eq!(bid.json().unwrap()["status"], "FILLED");
+
+    let cancel = format!(r#"{{"orderId":{}}}"#, ask_id);
+    let cancel = gateway.request("POST", "/api/spot/order/cancel/", &[], &cancel).unwrap();
+    assert_eq!(cancel.status, 200, "{}", cancel.body);
+
+    // 引擎读模型与 HTTP 结果一致
+    let harness = harness.lock().unwrap();
+    let mut store = harness.store();
+    let maker = store.order(ask_id).unwrap().unwrap();
+    assert_eq!((maker.status.as_str(), maker.filled), ("CANCELED", raw(0.4)));
+    assert_eq!(store.balance(ALICE, AssetId::Btc).unwrap().unwrap().available, raw(0.4));
+    assert_eq!(store.trades(BOB).unwrap().len(), 1);
+
+    let forwarded = backend.requests();
+    assert_eq!(forwarded.len(), 3);
+    assert!(forwarded.iter().all(|request| request.path.starts_with("/api/spot/order/")));
+}
@@ -0,0 +1,144 @@
+//! 订单全生命周期：充值 → 挂单 → 部分成交 → 撤单 → 提现
+//!
+//! 同一场景分别跑在内存存储与 MySQL（testcontainers）上
+
+use base_types::fee::fee_types::ProductFeeConfig;
+use base_types::{AccountId, AssetId, OrderSide, Price, Quantity, TradingPair};
+use e2e::{Harness, MemoryStore, StateStore};
+use rustlob_core::{ExchangeConfig, ExchangeError, ExchangeEvent, OrderRequest, OrderStatus};
+
+const ALICE: AccountId = AccountId(1);
+const BOB: AccountId = AccountId(2);
+const FEES: AccountId = AccountId(99);
+
+fn q(value: f64) -> Quantity {
+    Quantity::from_f64(value)
+}
+
+fn raw(value: f64) -> i64 {
+    q(value).raw()
+}
+
+fn config() -> ExchangeConfig {
+    ExchangeConfig::new([TradingPair::BtcUsdt])
+        .with_fees(ProductFeeConfig::spot(0.001, 0.002), FEES)
+}
+
+fn run_order_lifecycle<S: StateStore + 'static>(mut harness: Harness<S>) {
+    // 充值
+    harness.exchange.deposit(ALICE, AssetId::Usdt, q(100_000.0)).unwrap();
+    harness.exchange.deposit(BOB, AssetId::Btc, q(2.0)).unwrap();
+    assert_eq!(
+        harness.store().balance(ALICE, AssetId::Usdt).unwrap().unwrap().available,
+        raw(100_000.0)
+    );
+    harness.take_pushes();
+
+    // Bob 挂卖 1 BTC @ 40000
+    let ask = OrderRequest::limit(
+        BOB,
+        TradingPair::BtcUsdt,
+        OrderSide::Sell,
+        Price::from_f64(40_000.0),
+        q(1.0),
+    );
+    let ask = harness.exchange.submit(ask).unwrap();
+    assert_eq!(ask.status, OrderStatus::Resting);
+    {
+        let mut store = harness.store();
+        let order = store.order(ask.order_id).unwrap().unwrap();
+        assert_eq!((order.status.as_str(), order.side.as_str()), ("NEW", "SELL"));
+        let btc = store.balance(BOB, AssetId::Btc).unwrap().unwrap();
+        assert_eq!((btc.available, btc.frozen), (raw(1.0), raw(1.0)));
+    }
+    harness.take_pushes();
+    harness.clock.advance(1_000_000);
+
+    // Alice 吃单 0.4 BTC，Bob 的挂单部分成交
+    let bid = OrderRequest::limit(
+        ALICE,
+        TradingPair::BtcUsdt,
+        OrderSide::Buy,
+        Price::from_f64(40_000.0),
+        q(0.4),
+    );
+    let bid = harness.exchange.submit(bid).unwrap();
+    assert_eq!((bid.status, bid.filled), (OrderStatus::Filled, q(0.4)));
+
+    let pushes = harness.take_pushes();
+    let kinds: Vec<&str> = pushes
+        .iter()
+        .filter_map(|e| match e {
+            ExchangeEvent::OrderAccepted { .. } => Some("accepted"),
+            ExchangeEvent::Trade(_) => Some("trade"),
+            ExchangeEvent::OrderClosed { .. } => Some("closed"),
+            ExchangeEvent::BalanceChanged { .. }
+            | ExchangeEvent::AccountStatusChanged { .. }
+            | ExchangeEvent::ConditionalTriggered { .. }
+            | ExchangeEvent::ConditionalCancelled { .. } => None,
+        })
+        .collect();
+    assert_eq!(kinds, ["accepted", "trade", "closed"]);
+    let balance_pushes =
+        pushes.iter().filter(|e| matches!(e, ExchangeEvent::BalanceChanged { .. })).count();
+    // Alice USDT/BTC、Bob USDT/BTC、手续费账户 USDT
+    assert_eq!(balance_pushes, 5);
+
+    {
+        let mut store = harness.store();
+        let maker = store.order(ask.order_id).unwrap().unwrap();
+        assert_eq!((maker.status.as_str(), maker.filled), ("PARTIALLY_FILLED", raw(0.4)));
+        assert_eq!(store.order(bid.order_id).unwrap().unwrap().status, "FILLED");
+
+        let trades = store.trades(BOB).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].price, trades[0].quantity), (raw(40_000.0), raw(0.4)));
+        assert_eq!(
+            (trades[0].maker_order_id, trades[0].taker_order_id),
+            (ask.order_id, bid.order_id)
+        );
+        // Taker 0.2%、Maker 0.1%
+        assert_eq!((trades[0].buyer_fee, trades[0].seller_fee), (raw(32.0), raw(16.0)));
+
+        let alice_usdt = store.balance(ALICE, AssetId::Usdt).unwrap().unwrap();
+        assert_eq!((alice_usdt.available, alice_usdt.frozen), (raw(83_968.0), 0));
+        assert_eq!(store.balance(ALICE, AssetId::Btc).unwrap().unwrap().available, raw(0.4));
+        assert_eq!(store.balance(BOB, AssetId::Usdt).unwrap().unwrap().available, raw(15_984.0));
+        assert_eq!(store.balance(FEES, AssetId::Usdt).unwrap().unwrap().available, raw(48.0));
+    }
+
+    // Bob 撤销剩余 0.6 BTC
+    harness.exchange.cancel(ask.order_id).unwrap();
+    {
+        let mut store = harness.store();
+        let maker = store.order(ask.order_id).unwrap().unwrap();
+        assert_eq!((maker.status.as_str(), maker.filled), ("CANCELED", raw(0.4)));
+        let btc = store.balance(BOB, AssetId::Btc).unwrap().unwrap();
+        assert_eq!((btc.available, btc.frozen), (raw(1.6), 0));
+    }
+    let depth = harness.exchange.query_depth(TradingPair::BtcUsdt, 10).unwrap();
+    assert!(depth.bids.is_empty() && depth.asks.is_empty());
+
+    // 提现：超出可用余额拒绝，余额不变
+    assert!(matches!(
+        harness.exchange.withdraw(BOB, AssetId::Usdt, q(20_000.0)),
+        Err(ExchangeError::Balance(_))
+    ));
+    harness.exchange.withdraw(BOB, AssetId::Usdt, q(15_000.0)).unwrap();
+    let mut store = harness.store();
+    assert_eq!(store.balance(BOB, AssetId::Usdt).unwrap().unwrap().available, raw(984.0));
+    assert_eq!(store.trades(ALICE).unwrap().len(), 1);
+}
+
+#[test]
+fn order_lifecycle_in_memory() {
+    run_order_lifecycle(Harness::new(config(), MemoryStore::new()));
+}
+
+#[cfg(feature = "docker")]
+#[test]
+fn order_lifecycle_mysql() {
+    let env = e2e::env::MySqlEnv::start().expect("start MySQL");
+    let store = env.store().expect("connect MySQL");
+    run_order_lifecycle(Harness::new(config(), store));
+}